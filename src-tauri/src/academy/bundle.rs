@@ -0,0 +1,1215 @@
+//! Portable course bundles for moving courses between installs.
+//!
+//! A bundle is a zip holding `manifest.json`, `course.json` and any local
+//! media the course references under `media/`, each media file named by its
+//! SHA-256. `course.json` carries the course with its lessons and quizzes,
+//! the question banks those quizzes draw from and the course badge. The
+//! manifest lists every file with its hash and size, plus the schema
+//! version, so bundles written by older versions can be upgraded on read.
+//!
+//! On import each bundle id is matched against local content. A free id is
+//! kept, an id that already belongs to the course being imported is updated
+//! in place, and an id taken by unrelated content is re-keyed with every
+//! reference to it rewritten. Re-keyed ids are remembered, so importing a
+//! newer version of the same bundle updates the same rows and learner
+//! progress on them survives.
+
+use super::content::{ContentError, Course, Lesson, QuestionBank, Quiz};
+use super::progress::ProgressError;
+use super::quiz::{validate_draw, validate_questions};
+use super::rewards::{Badge, RewardError};
+use super::AcademyEngine;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+pub const BUNDLE_SCHEMA_VERSION: u32 = 1;
+pub const DEFAULT_MAX_MEDIA_BYTES: u64 = 100 * 1024 * 1024;
+
+const MANIFEST_FILE: &str = "manifest.json";
+const CONTENT_FILE: &str = "course.json";
+const MEDIA_PREFIX: &str = "media/";
+/// How `course.json` refers to media packed in the bundle.
+const MEDIA_SCHEME: &str = "bundle-media://";
+const MAX_MANIFEST_BYTES: u64 = 1024 * 1024;
+const MAX_CONTENT_BYTES: u64 = 32 * 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleManifest {
+    pub schema_version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub course_id: String,
+    pub course_title: String,
+    pub entries: Vec<BundleEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleEntry {
+    pub path: String,
+    pub sha256: String,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleContent {
+    pub course: Course,
+    pub lessons: Vec<Lesson>,
+    pub quizzes: Vec<Quiz>,
+    #[serde(default)]
+    pub question_banks: Vec<QuestionBank>,
+    #[serde(default)]
+    pub badges: Vec<Badge>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BundleLimits {
+    /// Total size of the media a bundle may carry.
+    pub max_media_bytes: u64,
+}
+
+impl Default for BundleLimits {
+    fn default() -> Self {
+        Self {
+            max_media_bytes: DEFAULT_MAX_MEDIA_BYTES,
+        }
+    }
+}
+
+impl BundleLimits {
+    pub fn from_megabytes(max_media_mb: Option<u64>) -> Self {
+        max_media_mb
+            .map(|mb| Self {
+                max_media_bytes: mb.saturating_mul(1024 * 1024),
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BundleEntityKind {
+    Course,
+    Lesson,
+    Quiz,
+    QuestionBank,
+    Badge,
+}
+
+impl BundleEntityKind {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            BundleEntityKind::Course => "course",
+            BundleEntityKind::Lesson => "lesson",
+            BundleEntityKind::Quiz => "quiz",
+            BundleEntityKind::QuestionBank => "question_bank",
+            BundleEntityKind::Badge => "badge",
+        }
+    }
+
+    pub(crate) fn from_db(value: &str) -> Option<Self> {
+        match value {
+            "course" => Some(BundleEntityKind::Course),
+            "lesson" => Some(BundleEntityKind::Lesson),
+            "quiz" => Some(BundleEntityKind::Quiz),
+            "question_bank" => Some(BundleEntityKind::QuestionBank),
+            "badge" => Some(BundleEntityKind::Badge),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportAction {
+    Create,
+    Update,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedEntity {
+    pub kind: BundleEntityKind,
+    pub source_id: String,
+    pub local_id: String,
+    pub title: String,
+    pub action: ImportAction,
+    /// The bundle id was taken locally, so the entity gets a new id.
+    pub rekeyed: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemovedLesson {
+    pub lesson_id: String,
+    pub title: String,
+    /// Learners whose progress on this lesson no longer counts.
+    pub learners_with_progress: i64,
+}
+
+/// What an import will do, or did once it has run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CourseImportPreview {
+    pub schema_version: u32,
+    pub source_course_id: String,
+    pub course_id: String,
+    pub course_title: String,
+    pub action: ImportAction,
+    pub entities: Vec<PlannedEntity>,
+    pub removed_lessons: Vec<RemovedLesson>,
+    pub media_files: usize,
+    pub media_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CourseExportSummary {
+    pub path: String,
+    pub course_id: String,
+    pub lessons: usize,
+    pub quizzes: usize,
+    pub question_banks: usize,
+    pub badges: usize,
+    pub media_files: usize,
+    pub media_bytes: u64,
+}
+
+/// A local id given to a re-keyed bundle entity.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportKey {
+    pub source_course_id: String,
+    pub kind: BundleEntityKind,
+    pub source_id: String,
+    pub local_id: String,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum BundleError {
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("zip error: {0}")]
+    Zip(#[from] zip::result::ZipError),
+    #[error("serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+    #[error(transparent)]
+    Content(#[from] ContentError),
+    #[error(transparent)]
+    Reward(#[from] RewardError),
+    #[error(transparent)]
+    Progress(#[from] ProgressError),
+    #[error("invalid course bundle: {0}")]
+    Invalid(String),
+    #[error(
+        "course bundle uses schema version {found}, but this app supports up to version {supported}; update the app to import it"
+    )]
+    UnsupportedVersion { found: u32, supported: u32 },
+    #[error("course bundle media is {size} bytes, over the {limit} byte limit")]
+    MediaTooLarge { size: u64, limit: u64 },
+    #[error("{path} in the course bundle does not match its manifest hash")]
+    HashMismatch { path: String },
+}
+
+/// A bundle read from disk whose hashes and references have been checked.
+#[derive(Debug, Clone)]
+pub struct LoadedBundle {
+    pub manifest: BundleManifest,
+    pub content: BundleContent,
+    /// Media file contents by file name.
+    pub media: HashMap<String, Vec<u8>>,
+}
+
+#[derive(Debug, Clone)]
+struct MediaFile {
+    name: String,
+    sha256: String,
+    bytes: Vec<u8>,
+}
+
+pub async fn export_course(
+    academy: &AcademyEngine,
+    course_id: &str,
+    path: &Path,
+    limits: &BundleLimits,
+) -> Result<CourseExportSummary, BundleError> {
+    let mut content = {
+        let service = academy.content_service();
+        let service = service.read().await;
+        let course = service.get_course(course_id).await?;
+        let lessons = service.get_course_lessons(course_id).await?;
+        let quizzes = service.get_course_quizzes(course_id).await?;
+
+        let mut question_banks = Vec::new();
+        let mut seen = HashSet::new();
+        for bank_id in quizzes.iter().filter_map(|q| q.question_bank_id.as_deref()) {
+            if seen.insert(bank_id) {
+                question_banks.push(service.get_question_bank(bank_id).await?);
+            }
+        }
+
+        BundleContent {
+            course,
+            lessons,
+            quizzes,
+            question_banks,
+            badges: Vec::new(),
+        }
+    };
+
+    if let Some(badge_id) = content.course.badge_id.clone() {
+        let rewards = academy.reward_engine();
+        let rewards = rewards.read().await;
+        match rewards.get_badge(&badge_id).await {
+            Ok(badge) => content.badges.push(badge),
+            // The course points at a badge this install doesn't have.
+            Err(RewardError::NotFound(_)) => {}
+            Err(err) => return Err(err.into()),
+        }
+    }
+
+    let media = pack_media(&mut content, limits)?;
+    write_bundle(path, &content, &media)?;
+
+    Ok(CourseExportSummary {
+        path: path.display().to_string(),
+        course_id: content.course.id.clone(),
+        lessons: content.lessons.len(),
+        quizzes: content.quizzes.len(),
+        question_banks: content.question_banks.len(),
+        badges: content.badges.len(),
+        media_files: media.len(),
+        media_bytes: media.iter().map(|file| file.bytes.len() as u64).sum(),
+    })
+}
+
+pub async fn preview_import(
+    academy: &AcademyEngine,
+    path: &Path,
+    limits: &BundleLimits,
+) -> Result<CourseImportPreview, BundleError> {
+    let bundle = read_bundle(path, limits)?;
+    Ok(plan_import(academy, &bundle).await?.preview)
+}
+
+/// Imports a bundle, copying its media into `media_dir`. Nothing is written
+/// to the database unless the whole course imports.
+pub async fn import_course(
+    academy: &AcademyEngine,
+    path: &Path,
+    media_dir: &Path,
+    limits: &BundleLimits,
+) -> Result<CourseImportPreview, BundleError> {
+    let bundle = read_bundle(path, limits)?;
+    let mut plan = plan_import(academy, &bundle).await?;
+
+    let written = store_media(&bundle.media, media_dir)?;
+    localize_media(&mut plan.content, media_dir);
+
+    let removed_lessons: Vec<String> = plan
+        .preview
+        .removed_lessons
+        .iter()
+        .map(|lesson| lesson.lesson_id.clone())
+        .collect();
+    let service = academy.content_service();
+    let result = service
+        .read()
+        .await
+        .apply_course_import(
+            &plan.content,
+            &removed_lessons,
+            &plan.removed_quizzes,
+            &plan.keys,
+        )
+        .await;
+
+    if let Err(err) = result {
+        for file in written {
+            let _ = std::fs::remove_file(file);
+        }
+        return Err(err.into());
+    }
+
+    Ok(plan.preview)
+}
+
+/// Reads and verifies a bundle: schema version, file hashes, media size and
+/// the references between the course's entities.
+pub fn read_bundle(path: &Path, limits: &BundleLimits) -> Result<LoadedBundle, BundleError> {
+    let mut archive = ZipArchive::new(File::open(path)?)?;
+
+    let manifest_bytes = read_entry(&mut archive, MANIFEST_FILE, MAX_MANIFEST_BYTES)?;
+    if manifest_bytes.len() as u64 > MAX_MANIFEST_BYTES {
+        return Err(BundleError::Invalid("manifest is too large".to_string()));
+    }
+    let manifest: serde_json::Value = serde_json::from_slice(&manifest_bytes)?;
+    let version = manifest
+        .get("schemaVersion")
+        .and_then(serde_json::Value::as_u64)
+        .ok_or_else(|| BundleError::Invalid("manifest has no schema version".to_string()))?;
+    let version = u32::try_from(version).unwrap_or(u32::MAX);
+    if version == 0 {
+        return Err(BundleError::Invalid(
+            "manifest has an invalid schema version".to_string(),
+        ));
+    }
+    if version > BUNDLE_SCHEMA_VERSION {
+        return Err(BundleError::UnsupportedVersion {
+            found: version,
+            supported: BUNDLE_SCHEMA_VERSION,
+        });
+    }
+    let manifest: BundleManifest = serde_json::from_value(manifest)?;
+
+    let media_bytes: u64 = manifest
+        .entries
+        .iter()
+        .filter(|entry| entry.path.starts_with(MEDIA_PREFIX))
+        .map(|entry| entry.size)
+        .sum();
+    if media_bytes > limits.max_media_bytes {
+        return Err(BundleError::MediaTooLarge {
+            size: media_bytes,
+            limit: limits.max_media_bytes,
+        });
+    }
+
+    let mut content_bytes = None;
+    let mut media = HashMap::new();
+    for entry in &manifest.entries {
+        let media_name = entry.path.strip_prefix(MEDIA_PREFIX);
+        let limit = match media_name {
+            Some(name) if is_media_name(name, &entry.sha256) => entry.size,
+            None if entry.path == CONTENT_FILE => MAX_CONTENT_BYTES,
+            _ => {
+                return Err(BundleError::Invalid(format!(
+                    "unexpected file {}",
+                    entry.path
+                )))
+            }
+        };
+
+        let bytes = read_entry(&mut archive, &entry.path, limit)?;
+        if bytes.len() as u64 != entry.size || sha256_hex(&bytes) != entry.sha256 {
+            return Err(BundleError::HashMismatch {
+                path: entry.path.clone(),
+            });
+        }
+        match media_name {
+            Some(name) => {
+                media.insert(name.to_string(), bytes);
+            }
+            None => content_bytes = Some(bytes),
+        }
+    }
+
+    let content_bytes = content_bytes
+        .ok_or_else(|| BundleError::Invalid(format!("bundle has no {}", CONTENT_FILE)))?;
+    let content = upgrade_content(
+        manifest.schema_version,
+        serde_json::from_slice(&content_bytes)?,
+    )?;
+    let content: BundleContent = serde_json::from_value(content)?;
+    validate_content(&manifest, &content, &media)?;
+
+    Ok(LoadedBundle {
+        manifest,
+        content,
+        media,
+    })
+}
+
+/// Brings `course.json` written under an older schema version up to the
+/// current one. Each schema change adds a step here.
+fn upgrade_content(
+    version: u32,
+    content: serde_json::Value,
+) -> Result<serde_json::Value, BundleError> {
+    match version {
+        BUNDLE_SCHEMA_VERSION => Ok(content),
+        found => Err(BundleError::UnsupportedVersion {
+            found,
+            supported: BUNDLE_SCHEMA_VERSION,
+        }),
+    }
+}
+
+fn validate_content(
+    manifest: &BundleManifest,
+    content: &BundleContent,
+    media: &HashMap<String, Vec<u8>>,
+) -> Result<(), BundleError> {
+    let invalid =
+        |message: String| -> Result<(), BundleError> { Err(BundleError::Invalid(message)) };
+    let course = &content.course;
+    if manifest.course_id != course.id {
+        return invalid("manifest and course ids differ".to_string());
+    }
+
+    let mut lesson_ids = HashSet::new();
+    for lesson in &content.lessons {
+        if lesson.course_id != course.id {
+            return invalid(format!("lesson {} belongs to another course", lesson.id));
+        }
+        if !lesson_ids.insert(lesson.id.as_str()) {
+            return invalid(format!("lesson {} appears twice", lesson.id));
+        }
+    }
+
+    let mut banks = HashMap::new();
+    for bank in &content.question_banks {
+        validate_questions(&bank.questions).map_err(BundleError::Invalid)?;
+        if banks.insert(bank.id.as_str(), bank).is_some() {
+            return invalid(format!("question bank {} appears twice", bank.id));
+        }
+    }
+
+    let mut quiz_ids = HashSet::new();
+    for quiz in &content.quizzes {
+        if !quiz_ids.insert(quiz.id.as_str()) {
+            return invalid(format!("quiz {} appears twice", quiz.id));
+        }
+        if !lesson_ids.contains(quiz.lesson_id.as_str()) {
+            return invalid(format!("quiz {} has no lesson in the bundle", quiz.id));
+        }
+        validate_questions(&quiz.questions).map_err(BundleError::Invalid)?;
+        match &quiz.question_bank_id {
+            Some(bank_id) => match banks.get(bank_id.as_str()) {
+                Some(bank) => {
+                    validate_draw(quiz, bank.questions.len()).map_err(BundleError::Invalid)?
+                }
+                None => {
+                    return invalid(format!(
+                        "quiz {} draws from question bank {}, which is not in the bundle",
+                        quiz.id, bank_id
+                    ))
+                }
+            },
+            None if quiz.questions.is_empty() => {
+                return invalid(format!("quiz {} has no questions", quiz.id));
+            }
+            None => {}
+        }
+    }
+
+    let references = std::iter::once(&course.thumbnail_url)
+        .chain(content.lessons.iter().map(|lesson| &lesson.content_url))
+        .chain(content.badges.iter().map(|badge| &badge.icon_url));
+    for reference in references.flatten() {
+        if let Some(name) = reference.strip_prefix(MEDIA_SCHEME) {
+            if !media.contains_key(name) {
+                return invalid(format!("media {} is not in the bundle", name));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+struct ImportPlan {
+    preview: CourseImportPreview,
+    content: BundleContent,
+    removed_quizzes: Vec<String>,
+    keys: Vec<ImportKey>,
+}
+
+async fn plan_import(
+    academy: &AcademyEngine,
+    bundle: &LoadedBundle,
+) -> Result<ImportPlan, BundleError> {
+    use BundleEntityKind as Kind;
+
+    let source = &bundle.content;
+    let service = academy.content_service();
+    let service = service.read().await;
+    let rewards = academy.reward_engine();
+    let rewards = rewards.read().await;
+
+    let remembered = service.import_keys(&source.course.id).await?;
+    let mut planner = ImportPlanner::new(&source.course.id, remembered);
+
+    let candidate = planner.candidate(Kind::Course, &source.course.id);
+    let state = match service.get_course(&candidate).await {
+        Ok(course) if course.created_at == source.course.created_at => LocalMatch::Same,
+        Ok(_) => LocalMatch::Foreign,
+        Err(ContentError::NotFound(_)) => LocalMatch::Absent,
+        Err(err) => return Err(err.into()),
+    };
+    let course_id = planner.resolve(Kind::Course, &source.course.id, &source.course.title, state);
+    let action = planner.entities[0].action;
+
+    for lesson in &source.lessons {
+        let candidate = planner.candidate(Kind::Lesson, &lesson.id);
+        let state = match service.lesson_course_id(&candidate).await? {
+            None => LocalMatch::Absent,
+            Some(owner) if owner == course_id => LocalMatch::Same,
+            Some(_) => LocalMatch::Foreign,
+        };
+        planner.resolve(Kind::Lesson, &lesson.id, &lesson.title, state);
+    }
+
+    for bank in &source.question_banks {
+        let candidate = planner.candidate(Kind::QuestionBank, &bank.id);
+        let state = match service.get_question_bank(&candidate).await {
+            Ok(local) if local.created_at == bank.created_at => LocalMatch::Same,
+            Ok(_) => LocalMatch::Foreign,
+            Err(ContentError::NotFound(_)) => LocalMatch::Absent,
+            Err(err) => return Err(err.into()),
+        };
+        planner.resolve(Kind::QuestionBank, &bank.id, &bank.title, state);
+    }
+
+    for quiz in &source.quizzes {
+        let candidate = planner.candidate(Kind::Quiz, &quiz.id);
+        let state = match service.quiz_course_id(&candidate).await? {
+            None => LocalMatch::Absent,
+            Some(owner) if owner == course_id => LocalMatch::Same,
+            Some(_) => LocalMatch::Foreign,
+        };
+        planner.resolve(Kind::Quiz, &quiz.id, &quiz.title, state);
+    }
+
+    for badge in &source.badges {
+        let candidate = planner.candidate(Kind::Badge, &badge.id);
+        let state = match rewards.get_badge(&candidate).await {
+            Ok(local) if local.name == badge.name => LocalMatch::Same,
+            Ok(_) => LocalMatch::Foreign,
+            Err(RewardError::NotFound(_)) => LocalMatch::Absent,
+            Err(err) => return Err(err.into()),
+        };
+        planner.resolve(Kind::Badge, &badge.id, &badge.name, state);
+    }
+
+    let content = planner.rekey(source);
+
+    let mut removed_lessons = Vec::new();
+    let mut removed_quizzes = Vec::new();
+    if action == ImportAction::Update {
+        let kept_lessons: HashSet<&str> = content.lessons.iter().map(|l| l.id.as_str()).collect();
+        let removed: Vec<Lesson> = service
+            .get_course_lessons(&course_id)
+            .await?
+            .into_iter()
+            .filter(|lesson| !kept_lessons.contains(lesson.id.as_str()))
+            .collect();
+
+        let ids: Vec<String> = removed.iter().map(|lesson| lesson.id.clone()).collect();
+        let learners = academy
+            .progress_tracker()
+            .read()
+            .await
+            .count_lesson_learners(&ids)
+            .await?;
+        removed_lessons = removed
+            .into_iter()
+            .map(|lesson| RemovedLesson {
+                learners_with_progress: learners.get(&lesson.id).copied().unwrap_or(0),
+                lesson_id: lesson.id,
+                title: lesson.title,
+            })
+            .collect();
+
+        let kept_quizzes: HashSet<&str> = content.quizzes.iter().map(|q| q.id.as_str()).collect();
+        removed_quizzes = service
+            .get_course_quizzes(&course_id)
+            .await?
+            .into_iter()
+            .map(|quiz| quiz.id)
+            .filter(|id| !kept_quizzes.contains(id.as_str()))
+            .collect();
+    }
+
+    let preview = CourseImportPreview {
+        schema_version: bundle.manifest.schema_version,
+        source_course_id: source.course.id.clone(),
+        course_id,
+        course_title: source.course.title.clone(),
+        action,
+        entities: planner.entities,
+        removed_lessons,
+        media_files: bundle.media.len(),
+        media_bytes: bundle.media.values().map(|bytes| bytes.len() as u64).sum(),
+    };
+
+    Ok(ImportPlan {
+        preview,
+        content,
+        removed_quizzes,
+        keys: planner.keys,
+    })
+}
+
+/// How a candidate id relates to what already exists locally.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LocalMatch {
+    Absent,
+    /// Exists and is the same content, so it is updated in place.
+    Same,
+    /// Taken by unrelated content.
+    Foreign,
+}
+
+struct ImportPlanner {
+    source_course_id: String,
+    remembered: HashMap<(BundleEntityKind, String), String>,
+    resolved: HashMap<(BundleEntityKind, String), String>,
+    entities: Vec<PlannedEntity>,
+    keys: Vec<ImportKey>,
+}
+
+impl ImportPlanner {
+    fn new(
+        source_course_id: &str,
+        remembered: HashMap<(BundleEntityKind, String), String>,
+    ) -> Self {
+        Self {
+            source_course_id: source_course_id.to_string(),
+            remembered,
+            resolved: HashMap::new(),
+            entities: Vec::new(),
+            keys: Vec::new(),
+        }
+    }
+
+    /// The local id to check for `source_id`: the one a previous import gave
+    /// it, or the bundle id itself.
+    fn candidate(&self, kind: BundleEntityKind, source_id: &str) -> String {
+        self.remembered
+            .get(&(kind, source_id.to_string()))
+            .cloned()
+            .unwrap_or_else(|| source_id.to_string())
+    }
+
+    fn resolve(
+        &mut self,
+        kind: BundleEntityKind,
+        source_id: &str,
+        title: &str,
+        local: LocalMatch,
+    ) -> String {
+        let remembered = self.remembered.contains_key(&(kind, source_id.to_string()));
+        let candidate = self.candidate(kind, source_id);
+        let (local_id, action) = match local {
+            LocalMatch::Absent => (candidate, ImportAction::Create),
+            LocalMatch::Same => (candidate, ImportAction::Update),
+            // A remembered id was created by an earlier import of this bundle.
+            LocalMatch::Foreign if remembered => (candidate, ImportAction::Update),
+            LocalMatch::Foreign => (Uuid::new_v4().to_string(), ImportAction::Create),
+        };
+
+        let rekeyed = local_id != source_id;
+        if rekeyed {
+            self.keys.push(ImportKey {
+                source_course_id: self.source_course_id.clone(),
+                kind,
+                source_id: source_id.to_string(),
+                local_id: local_id.clone(),
+            });
+        }
+        self.entities.push(PlannedEntity {
+            kind,
+            source_id: source_id.to_string(),
+            local_id: local_id.clone(),
+            title: title.to_string(),
+            action,
+            rekeyed,
+        });
+        self.resolved
+            .insert((kind, source_id.to_string()), local_id.clone());
+        local_id
+    }
+
+    fn local_id(&self, kind: BundleEntityKind, source_id: &str) -> String {
+        self.resolved
+            .get(&(kind, source_id.to_string()))
+            .cloned()
+            .unwrap_or_else(|| source_id.to_string())
+    }
+
+    /// Copies `source` with every resolved id and internal reference
+    /// replaced by its local id.
+    fn rekey(&self, source: &BundleContent) -> BundleContent {
+        use BundleEntityKind as Kind;
+
+        let mut content = source.clone();
+        content.course.id = self.local_id(Kind::Course, &source.course.id);
+        content.course.badge_id = source
+            .course
+            .badge_id
+            .as_deref()
+            .map(|id| self.local_id(Kind::Badge, id));
+        for lesson in &mut content.lessons {
+            lesson.id = self.local_id(Kind::Lesson, &lesson.id);
+            lesson.course_id = content.course.id.clone();
+        }
+        for quiz in &mut content.quizzes {
+            quiz.id = self.local_id(Kind::Quiz, &quiz.id);
+            quiz.lesson_id = self.local_id(Kind::Lesson, &quiz.lesson_id);
+            quiz.question_bank_id = quiz
+                .question_bank_id
+                .as_deref()
+                .map(|id| self.local_id(Kind::QuestionBank, id));
+        }
+        for bank in &mut content.question_banks {
+            bank.id = self.local_id(Kind::QuestionBank, &bank.id);
+        }
+        for badge in &mut content.badges {
+            badge.id = self.local_id(Kind::Badge, &badge.id);
+        }
+        content
+    }
+}
+
+/// Moves local media files referenced by `content` into the bundle,
+/// replacing their paths with bundle references. Remote URLs and paths that
+/// aren't files on this machine are left alone.
+fn pack_media(
+    content: &mut BundleContent,
+    limits: &BundleLimits,
+) -> Result<Vec<MediaFile>, BundleError> {
+    let mut files: Vec<MediaFile> = Vec::new();
+    let mut total = 0u64;
+
+    let mut pack = |reference: &mut Option<String>| -> Result<(), BundleError> {
+        let Some(path) = reference.as_deref().and_then(local_media_path) else {
+            return Ok(());
+        };
+        let bytes = std::fs::read(&path)?;
+        let sha256 = sha256_hex(&bytes);
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .filter(|ext| ext.chars().all(|c| c.is_ascii_alphanumeric()))
+            .map(|ext| format!(".{}", ext.to_ascii_lowercase()))
+            .unwrap_or_default();
+        let name = format!("{}{}", sha256, extension);
+
+        if !files.iter().any(|file| file.name == name) {
+            total += bytes.len() as u64;
+            if total > limits.max_media_bytes {
+                return Err(BundleError::MediaTooLarge {
+                    size: total,
+                    limit: limits.max_media_bytes,
+                });
+            }
+            files.push(MediaFile {
+                name: name.clone(),
+                sha256,
+                bytes,
+            });
+        }
+        *reference = Some(format!("{}{}", MEDIA_SCHEME, name));
+        Ok(())
+    };
+
+    pack(&mut content.course.thumbnail_url)?;
+    for lesson in &mut content.lessons {
+        pack(&mut lesson.content_url)?;
+    }
+    for badge in &mut content.badges {
+        pack(&mut badge.icon_url)?;
+    }
+
+    Ok(files)
+}
+
+fn local_media_path(reference: &str) -> Option<PathBuf> {
+    let path = match reference.strip_prefix("file://") {
+        Some(path) => PathBuf::from(path),
+        None if reference.contains("://") || reference.starts_with("data:") => return None,
+        None => PathBuf::from(reference),
+    };
+    path.is_file().then_some(path)
+}
+
+fn write_bundle(
+    path: &Path,
+    content: &BundleContent,
+    media: &[MediaFile],
+) -> Result<(), BundleError> {
+    let content_bytes = serde_json::to_vec_pretty(content)?;
+
+    let mut entries = vec![BundleEntry {
+        path: CONTENT_FILE.to_string(),
+        sha256: sha256_hex(&content_bytes),
+        size: content_bytes.len() as u64,
+    }];
+    let mut files = vec![(CONTENT_FILE.to_string(), content_bytes.as_slice())];
+    for file in media {
+        let path = format!("{}{}", MEDIA_PREFIX, file.name);
+        entries.push(BundleEntry {
+            path: path.clone(),
+            sha256: file.sha256.clone(),
+            size: file.bytes.len() as u64,
+        });
+        files.push((path, file.bytes.as_slice()));
+    }
+
+    let manifest = BundleManifest {
+        schema_version: BUNDLE_SCHEMA_VERSION,
+        exported_at: Utc::now(),
+        course_id: content.course.id.clone(),
+        course_title: content.course.title.clone(),
+        entries,
+    };
+    write_archive(path, &manifest, &files)
+}
+
+fn write_archive(
+    path: &Path,
+    manifest: &BundleManifest,
+    files: &[(String, &[u8])],
+) -> Result<(), BundleError> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut zip = ZipWriter::new(File::create(path)?);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    zip.start_file(MANIFEST_FILE, options)?;
+    zip.write_all(&serde_json::to_vec_pretty(manifest)?)?;
+    for (name, bytes) in files {
+        zip.start_file(name.as_str(), options)?;
+        zip.write_all(bytes)?;
+    }
+
+    zip.finish()?;
+    Ok(())
+}
+
+/// Reads at most `limit + 1` bytes of `name`, so callers can tell an
+/// oversized entry apart without inflating all of it.
+fn read_entry(
+    archive: &mut ZipArchive<File>,
+    name: &str,
+    limit: u64,
+) -> Result<Vec<u8>, BundleError> {
+    let file = archive
+        .by_name(name)
+        .map_err(|_| BundleError::Invalid(format!("bundle is missing {}", name)))?;
+    let mut bytes = Vec::new();
+    file.take(limit.saturating_add(1)).read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Media files are named `<sha256>[.ext]`, which also keeps them from
+/// escaping the media directory on import.
+fn is_media_name(name: &str, sha256: &str) -> bool {
+    match name.strip_prefix(sha256) {
+        Some("") => true,
+        Some(ext) => {
+            ext.len() > 1
+                && ext.starts_with('.')
+                && ext[1..].chars().all(|c| c.is_ascii_alphanumeric())
+        }
+        None => false,
+    }
+}
+
+fn store_media(
+    media: &HashMap<String, Vec<u8>>,
+    media_dir: &Path,
+) -> Result<Vec<PathBuf>, BundleError> {
+    if media.is_empty() {
+        return Ok(Vec::new());
+    }
+    std::fs::create_dir_all(media_dir)?;
+
+    let mut written = Vec::new();
+    for (name, bytes) in media {
+        let target = media_dir.join(name);
+        // Names are content hashes, so an existing file already holds these bytes.
+        if target.exists() {
+            continue;
+        }
+        if let Err(err) = std::fs::write(&target, bytes) {
+            for file in written {
+                let _ = std::fs::remove_file(file);
+            }
+            return Err(err.into());
+        }
+        written.push(target);
+    }
+    Ok(written)
+}
+
+fn localize_media(content: &mut BundleContent, media_dir: &Path) {
+    let localize = |reference: &mut Option<String>| {
+        if let Some(name) = reference
+            .as_deref()
+            .and_then(|value| value.strip_prefix(MEDIA_SCHEME))
+        {
+            *reference = Some(media_dir.join(name).display().to_string());
+        }
+    };
+
+    localize(&mut content.course.thumbnail_url);
+    for lesson in &mut content.lessons {
+        localize(&mut lesson.content_url);
+    }
+    for badge in &mut content.badges {
+        localize(&mut badge.icon_url);
+    }
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::academy::content::{ContentType, CourseLevel, QuizQuestion, ScorePolicy};
+    use crate::academy::rewards::BadgeRarity;
+
+    fn question(id: &str) -> QuizQuestion {
+        QuizQuestion {
+            id: id.to_string(),
+            question: "What is a validator?".to_string(),
+            options: vec!["A node".to_string(), "A token".to_string()],
+            correct_answer: 0,
+            explanation: String::new(),
+            points: 1,
+        }
+    }
+
+    fn sample_content(thumbnail: Option<String>) -> BundleContent {
+        let now = Utc::now();
+        BundleContent {
+            course: Course {
+                id: "course-1".to_string(),
+                title: "Staking Basics".to_string(),
+                description: String::new(),
+                level: CourseLevel::Beginner,
+                category: "defi".to_string(),
+                duration_minutes: 30,
+                xp_reward: 100,
+                badge_id: Some("badge-1".to_string()),
+                prerequisites: vec![],
+                tags: vec![],
+                thumbnail_url: thumbnail,
+                is_published: true,
+                created_at: now,
+                updated_at: now,
+            },
+            lessons: vec![Lesson {
+                id: "lesson-1".to_string(),
+                course_id: "course-1".to_string(),
+                title: "Validators".to_string(),
+                description: String::new(),
+                content_type: ContentType::Article,
+                content_url: Some("https://example.com/validators".to_string()),
+                content_data: None,
+                order_index: 0,
+                duration_minutes: 10,
+                xp_reward: 10,
+                is_mandatory: true,
+                created_at: now,
+                updated_at: now,
+            }],
+            quizzes: vec![Quiz {
+                id: "quiz-1".to_string(),
+                lesson_id: "lesson-1".to_string(),
+                title: "Validator quiz".to_string(),
+                questions: vec![],
+                passing_score: 70,
+                max_attempts: None,
+                time_limit_minutes: None,
+                question_bank_id: Some("bank-1".to_string()),
+                draw_count: Some(1),
+                retake_cooldown_minutes: None,
+                score_policy: ScorePolicy::BestScore,
+            }],
+            question_banks: vec![QuestionBank {
+                id: "bank-1".to_string(),
+                title: "Validator questions".to_string(),
+                questions: vec![question("q1"), question("q2")],
+                created_at: now,
+                updated_at: now,
+            }],
+            badges: vec![Badge {
+                id: "badge-1".to_string(),
+                name: "Staker".to_string(),
+                description: String::new(),
+                rarity: BadgeRarity::Common,
+                icon_url: None,
+                xp_reward: 0,
+                reputation_boost: 0.0,
+                requirements: "{}".to_string(),
+                is_active: true,
+                created_at: now,
+            }],
+        }
+    }
+
+    #[test]
+    fn bundle_round_trip_keeps_content_and_media() {
+        let dir = tempfile::tempdir().unwrap();
+        let image = dir.path().join("thumb.PNG");
+        std::fs::write(&image, b"not really a png").unwrap();
+
+        let mut content = sample_content(Some(image.display().to_string()));
+        let media = pack_media(&mut content, &BundleLimits::default()).unwrap();
+        assert_eq!(media.len(), 1);
+        assert!(media[0].name.ends_with(".png"));
+
+        let path = dir.path().join("course.zip");
+        write_bundle(&path, &content, &media).unwrap();
+        let bundle = read_bundle(&path, &BundleLimits::default()).unwrap();
+
+        assert_eq!(bundle.manifest.schema_version, BUNDLE_SCHEMA_VERSION);
+        assert_eq!(bundle.content.lessons.len(), 1);
+        assert_eq!(bundle.content.question_banks[0].questions.len(), 2);
+        let thumbnail = bundle.content.course.thumbnail_url.clone().unwrap();
+        let name = thumbnail.strip_prefix(MEDIA_SCHEME).unwrap();
+        assert_eq!(bundle.media[name], b"not really a png");
+
+        let mut localized = bundle.content.clone();
+        let media_dir = dir.path().join("media");
+        let written = store_media(&bundle.media, &media_dir).unwrap();
+        localize_media(&mut localized, &media_dir);
+        assert_eq!(written.len(), 1);
+        assert_eq!(
+            std::fs::read(localized.course.thumbnail_url.unwrap()).unwrap(),
+            b"not really a png"
+        );
+    }
+
+    #[test]
+    fn tampered_content_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let content = sample_content(None);
+        let bytes = serde_json::to_vec(&content).unwrap();
+        let manifest = BundleManifest {
+            schema_version: BUNDLE_SCHEMA_VERSION,
+            exported_at: Utc::now(),
+            course_id: "course-1".to_string(),
+            course_title: "Staking Basics".to_string(),
+            entries: vec![BundleEntry {
+                path: CONTENT_FILE.to_string(),
+                sha256: sha256_hex(b"something else"),
+                size: bytes.len() as u64,
+            }],
+        };
+        let path = dir.path().join("course.zip");
+        write_archive(
+            &path,
+            &manifest,
+            &[(CONTENT_FILE.to_string(), bytes.as_slice())],
+        )
+        .unwrap();
+
+        let err = read_bundle(&path, &BundleLimits::default()).unwrap_err();
+        assert!(matches!(err, BundleError::HashMismatch { .. }));
+    }
+
+    #[test]
+    fn newer_schema_versions_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let manifest = BundleManifest {
+            schema_version: BUNDLE_SCHEMA_VERSION + 1,
+            exported_at: Utc::now(),
+            course_id: "course-1".to_string(),
+            course_title: "Staking Basics".to_string(),
+            entries: vec![],
+        };
+        let path = dir.path().join("course.zip");
+        write_archive(&path, &manifest, &[]).unwrap();
+
+        let err = read_bundle(&path, &BundleLimits::default()).unwrap_err();
+        assert!(matches!(
+            err,
+            BundleError::UnsupportedVersion { found, .. } if found == BUNDLE_SCHEMA_VERSION + 1
+        ));
+    }
+
+    #[test]
+    fn oversized_media_is_rejected_on_export_and_import() {
+        let dir = tempfile::tempdir().unwrap();
+        let image = dir.path().join("thumb.png");
+        std::fs::write(&image, vec![7u8; 2048]).unwrap();
+        let small = BundleLimits {
+            max_media_bytes: 1024,
+        };
+
+        let mut content = sample_content(Some(image.display().to_string()));
+        assert!(matches!(
+            pack_media(&mut content.clone(), &small),
+            Err(BundleError::MediaTooLarge { size: 2048, .. })
+        ));
+
+        let media = pack_media(&mut content, &BundleLimits::default()).unwrap();
+        let path = dir.path().join("course.zip");
+        write_bundle(&path, &content, &media).unwrap();
+        assert!(matches!(
+            read_bundle(&path, &small),
+            Err(BundleError::MediaTooLarge { limit: 1024, .. })
+        ));
+    }
+
+    #[test]
+    fn media_names_must_be_their_hash() {
+        let sha = sha256_hex(b"x");
+        assert!(is_media_name(&sha, &sha));
+        assert!(is_media_name(&format!("{}.png", sha), &sha));
+        assert!(!is_media_name(&format!("{}./../x", sha), &sha));
+        assert!(!is_media_name("../../evil.png", &sha));
+    }
+
+    #[test]
+    fn foreign_ids_are_rekeyed_and_references_follow() {
+        use BundleEntityKind as Kind;
+
+        let source = sample_content(None);
+        let mut planner = ImportPlanner::new("course-1", HashMap::new());
+        let course_id = planner.resolve(Kind::Course, "course-1", "Staking", LocalMatch::Absent);
+        planner.resolve(Kind::Lesson, "lesson-1", "Validators", LocalMatch::Foreign);
+        planner.resolve(Kind::QuestionBank, "bank-1", "Bank", LocalMatch::Foreign);
+        planner.resolve(Kind::Quiz, "quiz-1", "Quiz", LocalMatch::Absent);
+        planner.resolve(Kind::Badge, "badge-1", "Staker", LocalMatch::Same);
+        let content = planner.rekey(&source);
+
+        assert_eq!(course_id, "course-1");
+        let lesson_id = &content.lessons[0].id;
+        assert_ne!(lesson_id, "lesson-1");
+        assert_eq!(&content.quizzes[0].lesson_id, lesson_id);
+        assert_eq!(content.quizzes[0].id, "quiz-1");
+        assert_eq!(
+            content.quizzes[0].question_bank_id.as_ref(),
+            Some(&content.question_banks[0].id)
+        );
+        assert_eq!(content.course.badge_id.as_deref(), Some("badge-1"));
+
+        let rekeyed: Vec<_> = planner.keys.iter().map(|key| key.kind).collect();
+        assert_eq!(rekeyed, vec![Kind::Lesson, Kind::QuestionBank]);
+        assert_eq!(planner.entities[4].action, ImportAction::Update);
+    }
+
+    #[test]
+    fn remembered_ids_are_updated_instead_of_rekeyed_again() {
+        use BundleEntityKind as Kind;
+
+        let mut remembered = HashMap::new();
+        remembered.insert(
+            (Kind::Lesson, "lesson-1".to_string()),
+            "local-7".to_string(),
+        );
+        let mut planner = ImportPlanner::new("course-1", remembered);
+
+        assert_eq!(planner.candidate(Kind::Lesson, "lesson-1"), "local-7");
+        let local = planner.resolve(Kind::Lesson, "lesson-1", "Validators", LocalMatch::Foreign);
+        assert_eq!(local, "local-7");
+        assert_eq!(planner.entities[0].action, ImportAction::Update);
+        assert!(planner.entities[0].rekeyed);
+        assert_eq!(planner.keys[0].local_id, "local-7");
+    }
+}
@@ -0,0 +1,457 @@
+//! Externally verifiable certificate proofs.
+//!
+//! A proof is `ECP1.<payload>.<signature>`, both parts base64url: the payload
+//! is the certificate's JSON summary and the signature is an Ed25519 signature
+//! over those exact bytes by the app's certificate key. The payload names the
+//! signing key, so anyone holding the published public keys can check a proof
+//! without the issuer's database. Rotating the key retires the old one but
+//! keeps its public half, so earlier certificates stay verifiable.
+
+use super::rewards::Certificate;
+use crate::api_config::rpc_endpoint;
+use crate::p2p::onchain::{decode_transaction, encode_transaction, missing_signers};
+use crate::security::keystore::{Keystore, KeystoreError};
+use base64::{engine::general_purpose, Engine as _};
+use chrono::{DateTime, Utc};
+use qrcodegen::{QrCode, QrCodeEcc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+    transaction::Transaction,
+};
+use std::str::FromStr;
+use std::sync::Mutex;
+use tauri::AppHandle;
+
+pub const CERTIFICATE_PROOF_PREFIX: &str = "ECP1";
+pub const MEMO_PROGRAM_ID: &str = "MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr";
+const CERTIFICATE_KEYRING_KEY: &str = "academy_certificate_keyring";
+const ANCHOR_MEMO_PREFIX: &str = "eclipse-academy-certificate";
+
+/// Serialises keyring writes so concurrent first use or rotation can't drop a key.
+static KEYRING_LOCK: Mutex<()> = Mutex::new(());
+
+/// What a proof attests to.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CertificatePayload {
+    pub version: u8,
+    pub certificate_id: String,
+    /// Wallet address of the holder.
+    pub holder: String,
+    pub course_id: Option<String>,
+    pub challenge_id: Option<String>,
+    pub title: String,
+    pub completed_at: DateTime<Utc>,
+    pub score: Option<f64>,
+    pub key_id: String,
+    pub issuer_public_key: String,
+}
+
+/// The public half of a certificate signing key.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CertificateSigningKey {
+    pub key_id: String,
+    pub public_key: String,
+    pub created_at: DateTime<Utc>,
+    pub retired_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CertificateProofBundle {
+    pub certificate_id: String,
+    pub proof: String,
+    /// The proof as a QR code, in SVG.
+    pub qr_svg: String,
+    pub proof_hash: String,
+    pub anchor_signature: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CertificateProofCheck {
+    pub payload: CertificatePayload,
+    /// SHA-256 of the payload, as anchored on-chain.
+    pub proof_hash: String,
+    /// Whether the signing key is one of this app's keys, and was still
+    /// active when the certificate was issued.
+    pub issuer_recognized: bool,
+    pub key_retired_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredSigningKey {
+    #[serde(flatten)]
+    public: CertificateSigningKey,
+    /// bs58-encoded keypair bytes.
+    secret: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CertificateKeyring {
+    active_key_id: String,
+    keys: Vec<StoredSigningKey>,
+}
+
+impl CertificateKeyring {
+    fn active(&self) -> Result<(&CertificateSigningKey, Keypair), String> {
+        let stored = self
+            .keys
+            .iter()
+            .find(|key| key.public.key_id == self.active_key_id)
+            .ok_or_else(|| "Active certificate key is missing".to_string())?;
+        let bytes = bs58::decode(&stored.secret)
+            .into_vec()
+            .map_err(|e| format!("Corrupt certificate key: {}", e))?;
+        let keypair =
+            Keypair::from_bytes(&bytes).map_err(|e| format!("Corrupt certificate key: {}", e))?;
+        Ok((&stored.public, keypair))
+    }
+
+    fn public_keys(&self) -> Vec<CertificateSigningKey> {
+        self.keys.iter().map(|key| key.public.clone()).collect()
+    }
+}
+
+fn new_signing_key() -> StoredSigningKey {
+    let keypair = Keypair::new();
+    let public_key = keypair.pubkey().to_string();
+    StoredSigningKey {
+        public: CertificateSigningKey {
+            key_id: format!("cert_{}", &public_key[..8]),
+            public_key,
+            created_at: Utc::now(),
+            retired_at: None,
+        },
+        secret: bs58::encode(keypair.to_bytes()).into_string(),
+    }
+}
+
+fn save_keyring(keystore: &Keystore, keyring: &CertificateKeyring) -> Result<(), String> {
+    let bytes = serde_json::to_vec(keyring).map_err(|e| e.to_string())?;
+    keystore
+        .store_secret(CERTIFICATE_KEYRING_KEY, &bytes)
+        .map_err(|e| e.to_string())
+}
+
+/// Loads the keyring, creating the first key on first use. Callers hold
+/// `KEYRING_LOCK`.
+fn read_keyring(keystore: &Keystore) -> Result<CertificateKeyring, String> {
+    match keystore.retrieve_secret(CERTIFICATE_KEYRING_KEY) {
+        Ok(bytes) => serde_json::from_slice(&bytes).map_err(|e| e.to_string()),
+        Err(KeystoreError::NotFound) => {
+            let key = new_signing_key();
+            let keyring = CertificateKeyring {
+                active_key_id: key.public.key_id.clone(),
+                keys: vec![key],
+            };
+            save_keyring(keystore, &keyring)?;
+            Ok(keyring)
+        }
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+fn load_keyring(keystore: &Keystore) -> Result<CertificateKeyring, String> {
+    let _guard = KEYRING_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    read_keyring(keystore)
+}
+
+pub fn certificate_signing_keys(keystore: &Keystore) -> Result<Vec<CertificateSigningKey>, String> {
+    Ok(load_keyring(keystore)?.public_keys())
+}
+
+/// Retires the active key and starts signing with a new one.
+pub fn rotate_certificate_key(keystore: &Keystore) -> Result<CertificateSigningKey, String> {
+    let _guard = KEYRING_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut keyring = read_keyring(keystore)?;
+    let now = Utc::now();
+    for key in keyring.keys.iter_mut() {
+        if key.public.key_id == keyring.active_key_id {
+            key.public.retired_at = Some(now);
+        }
+    }
+
+    let key = new_signing_key();
+    let public = key.public.clone();
+    keyring.active_key_id = public.key_id.clone();
+    keyring.keys.push(key);
+    save_keyring(keystore, &keyring)?;
+    Ok(public)
+}
+
+fn encode_proof(payload: &CertificatePayload, keypair: &Keypair) -> Result<String, String> {
+    let bytes = serde_json::to_vec(payload).map_err(|e| e.to_string())?;
+    let signature = keypair.sign_message(&bytes);
+    Ok(format!(
+        "{}.{}.{}",
+        CERTIFICATE_PROOF_PREFIX,
+        general_purpose::URL_SAFE_NO_PAD.encode(&bytes),
+        general_purpose::URL_SAFE_NO_PAD.encode(signature.as_ref())
+    ))
+}
+
+pub fn sign_certificate(keystore: &Keystore, certificate: &Certificate) -> Result<String, String> {
+    let keyring = load_keyring(keystore)?;
+    let (key, keypair) = keyring.active()?;
+    let payload = CertificatePayload {
+        version: 1,
+        certificate_id: certificate.id.clone(),
+        holder: certificate.wallet_address.clone(),
+        course_id: certificate.course_id.clone(),
+        challenge_id: certificate.challenge_id.clone(),
+        title: certificate.title.clone(),
+        completed_at: certificate.issued_at,
+        score: certificate.score,
+        key_id: key.key_id.clone(),
+        issuer_public_key: key.public_key.clone(),
+    };
+    encode_proof(&payload, &keypair)
+}
+
+fn proof_hash(payload_bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(payload_bytes))
+}
+
+/// Checks a proof's signature and reports whether it was made by one of
+/// `known_keys`. Fails if the proof is malformed or the signature is wrong.
+pub fn check_certificate_proof(
+    proof: &str,
+    known_keys: &[CertificateSigningKey],
+) -> Result<CertificateProofCheck, String> {
+    let mut parts = proof.trim().split('.');
+    let (Some(CERTIFICATE_PROOF_PREFIX), Some(payload_part), Some(signature_part), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return Err("Not a certificate proof".to_string());
+    };
+
+    let payload_bytes = general_purpose::URL_SAFE_NO_PAD
+        .decode(payload_part)
+        .map_err(|_| "Certificate proof payload is not valid base64".to_string())?;
+    let signature_bytes = general_purpose::URL_SAFE_NO_PAD
+        .decode(signature_part)
+        .map_err(|_| "Certificate proof signature is not valid base64".to_string())?;
+    let payload: CertificatePayload = serde_json::from_slice(&payload_bytes)
+        .map_err(|e| format!("Certificate proof payload is invalid: {}", e))?;
+
+    let issuer = Pubkey::from_str(&payload.issuer_public_key)
+        .map_err(|_| "Certificate proof names an invalid issuer key".to_string())?;
+    let signature = Signature::try_from(signature_bytes.as_slice())
+        .map_err(|_| "Certificate proof signature is malformed".to_string())?;
+    if !signature.verify(issuer.as_ref(), &payload_bytes) {
+        return Err("Certificate proof signature does not match its contents".to_string());
+    }
+
+    let known = known_keys
+        .iter()
+        .find(|key| key.key_id == payload.key_id && key.public_key == payload.issuer_public_key);
+    let key_retired_at = known.and_then(|key| key.retired_at);
+    let issuer_recognized =
+        known.is_some() && key_retired_at.is_none_or(|retired| payload.completed_at <= retired);
+
+    Ok(CertificateProofCheck {
+        proof_hash: proof_hash(&payload_bytes),
+        payload,
+        issuer_recognized,
+        key_retired_at,
+    })
+}
+
+pub fn proof_qr_svg(proof: &str) -> Result<String, String> {
+    let qr = QrCode::encode_text(proof, QrCodeEcc::Medium)
+        .map_err(|_| "Certificate proof is too long for a QR code".to_string())?;
+
+    let size = qr.size() as usize;
+    let border = 4;
+    let total_size = size + border * 2;
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" version=\"1.1\" viewBox=\"0 0 {} {}\" stroke=\"none\">",
+        total_size, total_size
+    );
+    svg.push_str("<rect width=\"100%\" height=\"100%\" fill=\"#ffffff\"/>");
+    svg.push_str("<path d=\"");
+    for y in 0..size {
+        for x in 0..size {
+            if qr.get_module(x as i32, y as i32) {
+                svg.push_str(&format!("M{},{}h1v1h-1z", x + border, y + border));
+            }
+        }
+    }
+    svg.push_str("\" fill=\"#000000\"/></svg>");
+    Ok(svg)
+}
+
+pub fn proof_bundle(
+    certificate: &Certificate,
+    proof: String,
+) -> Result<CertificateProofBundle, String> {
+    let check = check_certificate_proof(&proof, &[])?;
+    Ok(CertificateProofBundle {
+        certificate_id: certificate.id.clone(),
+        qr_svg: proof_qr_svg(&proof)?,
+        proof,
+        proof_hash: check.proof_hash,
+        anchor_signature: certificate.anchor_signature.clone(),
+    })
+}
+
+pub fn anchor_memo(proof_hash: &str) -> String {
+    format!("{}:{}", ANCHOR_MEMO_PREFIX, proof_hash)
+}
+
+fn memo_program() -> Pubkey {
+    Pubkey::from_str(MEMO_PROGRAM_ID).expect("valid memo program id")
+}
+
+fn anchor_rpc_client(app: &AppHandle) -> RpcClient {
+    RpcClient::new(rpc_endpoint(app))
+}
+
+fn holder_pubkey(certificate: &Certificate) -> Result<Pubkey, String> {
+    Pubkey::from_str(&certificate.wallet_address)
+        .map_err(|_| format!("{} is not a Solana address", certificate.wallet_address))
+}
+
+/// Builds an unsigned memo transaction, paid by the holder's wallet, that
+/// records the proof hash on Solana.
+pub fn prepare_anchor_transaction(
+    app: &AppHandle,
+    certificate: &Certificate,
+    proof_hash: &str,
+) -> Result<String, String> {
+    let payer = holder_pubkey(certificate)?;
+    let instruction = Instruction {
+        program_id: memo_program(),
+        accounts: vec![AccountMeta::new_readonly(payer, true)],
+        data: anchor_memo(proof_hash).into_bytes(),
+    };
+    let mut transaction = Transaction::new_with_payer(&[instruction], Some(&payer));
+    transaction.message.recent_blockhash = anchor_rpc_client(app)
+        .get_latest_blockhash()
+        .map_err(|e| format!("Failed to fetch a recent blockhash: {}", e))?;
+    encode_transaction(&transaction).map_err(|e| e.to_string())
+}
+
+/// Confirms a wallet-signed transaction only carries the expected memo.
+pub fn check_anchor_transaction(
+    transaction: &Transaction,
+    payer: &Pubkey,
+    proof_hash: &str,
+) -> Result<(), String> {
+    let message = &transaction.message;
+    if message.account_keys.first() != Some(payer) {
+        return Err("Anchor transaction must be paid by the certificate holder".to_string());
+    }
+    let memo = anchor_memo(proof_hash);
+    let [instruction] = message.instructions.as_slice() else {
+        return Err("Anchor transaction must contain only the certificate memo".to_string());
+    };
+    if message
+        .account_keys
+        .get(instruction.program_id_index as usize)
+        != Some(&memo_program())
+        || instruction.data != memo.as_bytes()
+    {
+        return Err("Anchor transaction does not carry this certificate's memo".to_string());
+    }
+    if !missing_signers(transaction).is_empty() {
+        return Err("Anchor transaction has not been signed by the holder".to_string());
+    }
+    Ok(())
+}
+
+/// Submits a signed anchor transaction and returns its signature.
+pub fn submit_anchor_transaction(
+    app: &AppHandle,
+    certificate: &Certificate,
+    proof_hash: &str,
+    signed_transaction: &str,
+) -> Result<String, String> {
+    let transaction = decode_transaction(signed_transaction).map_err(|e| e.to_string())?;
+    check_anchor_transaction(&transaction, &holder_pubkey(certificate)?, proof_hash)?;
+    let signature = anchor_rpc_client(app)
+        .send_and_confirm_transaction(&transaction)
+        .map_err(|e| format!("Failed to anchor certificate: {}", e))?;
+    Ok(signature.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signing_key(keypair: &Keypair, retired_at: Option<DateTime<Utc>>) -> CertificateSigningKey {
+        CertificateSigningKey {
+            key_id: "cert_test".to_string(),
+            public_key: keypair.pubkey().to_string(),
+            created_at: Utc::now(),
+            retired_at,
+        }
+    }
+
+    fn payload(keypair: &Keypair) -> CertificatePayload {
+        CertificatePayload {
+            version: 1,
+            certificate_id: "cert_1".to_string(),
+            holder: "holder_wallet".to_string(),
+            course_id: Some("course_1".to_string()),
+            challenge_id: None,
+            title: "Solana Basics".to_string(),
+            completed_at: Utc::now(),
+            score: Some(92.5),
+            key_id: "cert_test".to_string(),
+            issuer_public_key: keypair.pubkey().to_string(),
+        }
+    }
+
+    #[test]
+    fn test_proof_round_trip_and_tampering() {
+        let keypair = Keypair::new();
+        let issued = payload(&keypair);
+        let proof = encode_proof(&issued, &keypair).unwrap();
+
+        let check = check_certificate_proof(&proof, &[signing_key(&keypair, None)]).unwrap();
+        assert_eq!(check.payload, issued);
+        assert!(check.issuer_recognized);
+        assert!(proof_qr_svg(&proof).unwrap().starts_with("<svg"));
+
+        let unknown = check_certificate_proof(&proof, &[]).unwrap();
+        assert!(!unknown.issuer_recognized);
+
+        let parts: Vec<&str> = proof.split('.').collect();
+        let mut forged = issued;
+        forged.score = Some(100.0);
+        let forged_part =
+            general_purpose::URL_SAFE_NO_PAD.encode(serde_json::to_vec(&forged).unwrap());
+        let forged_proof = format!("{}.{}.{}", parts[0], forged_part, parts[2]);
+        assert!(check_certificate_proof(&forged_proof, &[]).is_err());
+        assert!(check_certificate_proof("not-a-proof", &[]).is_err());
+    }
+
+    #[test]
+    fn test_retired_key_only_covers_earlier_certificates() {
+        let keypair = Keypair::new();
+        let mut early = payload(&keypair);
+        early.completed_at = Utc::now() - chrono::Duration::days(30);
+        let retired = signing_key(&keypair, Some(Utc::now() - chrono::Duration::days(1)));
+
+        let proof = encode_proof(&early, &keypair).unwrap();
+        let check = check_certificate_proof(&proof, &[retired.clone()]).unwrap();
+        assert!(check.issuer_recognized);
+        assert!(check.key_retired_at.is_some());
+
+        let late = encode_proof(&payload(&keypair), &keypair).unwrap();
+        assert!(
+            !check_certificate_proof(&late, &[retired])
+                .unwrap()
+                .issuer_recognized
+        );
+    }
+}
@@ -1,5 +1,11 @@
 use super::*;
-use tauri::State;
+use crate::config::DataPaths;
+use crate::security::keystore::Keystore;
+use chrono::Utc;
+use std::collections::HashMap;
+use std::path::Path;
+use tauri::{AppHandle, State};
+use uuid::Uuid;
 
 // Course commands
 #[tauri::command]
@@ -117,6 +123,82 @@ pub async fn get_quiz(
         .map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+pub async fn create_question_bank(
+    academy: State<'_, SharedAcademyEngine>,
+    bank: content::QuestionBank,
+) -> Result<content::QuestionBank, String> {
+    academy
+        .read()
+        .await
+        .content_service()
+        .read()
+        .await
+        .create_question_bank(bank)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_question_bank(
+    academy: State<'_, SharedAcademyEngine>,
+    id: String,
+) -> Result<content::QuestionBank, String> {
+    academy
+        .read()
+        .await
+        .content_service()
+        .read()
+        .await
+        .get_question_bank(&id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn update_question_bank(
+    academy: State<'_, SharedAcademyEngine>,
+    bank: content::QuestionBank,
+) -> Result<content::QuestionBank, String> {
+    academy
+        .read()
+        .await
+        .content_service()
+        .read()
+        .await
+        .update_question_bank(bank)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_question_analytics(
+    academy: State<'_, SharedAcademyEngine>,
+    quiz_id: String,
+) -> Result<Vec<quiz::QuestionAnalytics>, String> {
+    let engine = academy.read().await;
+    let content_service = engine.content_service();
+    let content_service = content_service.read().await;
+    let quiz = content_service
+        .get_quiz(&quiz_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    let pool = content_service
+        .quiz_question_pool(&quiz)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let attempts = engine
+        .progress_tracker()
+        .read()
+        .await
+        .get_quiz_submissions(&quiz_id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(quiz::question_analytics(&pool, &attempts))
+}
+
 // Challenge commands
 #[tauri::command]
 pub async fn create_challenge(
@@ -231,6 +313,56 @@ pub async fn get_content_stats(
         .map_err(|e| e.to_string())
 }
 
+// Course bundle commands
+/// Packages a course with its lessons, quizzes, question banks, badge and
+/// local media into a zip at `path`.
+#[tauri::command]
+pub async fn academy_export_course(
+    academy: State<'_, SharedAcademyEngine>,
+    course_id: String,
+    path: String,
+    max_media_mb: Option<u64>,
+) -> Result<bundle::CourseExportSummary, String> {
+    let limits = bundle::BundleLimits::from_megabytes(max_media_mb);
+    let engine = academy.read().await;
+    bundle::export_course(&engine, &course_id, Path::new(&path), &limits)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Validates a bundle and reports what importing it would create, update
+/// and remove, without writing anything.
+#[tauri::command]
+pub async fn academy_preview_course_import(
+    academy: State<'_, SharedAcademyEngine>,
+    path: String,
+    max_media_mb: Option<u64>,
+) -> Result<bundle::CourseImportPreview, String> {
+    let limits = bundle::BundleLimits::from_megabytes(max_media_mb);
+    let engine = academy.read().await;
+    bundle::preview_import(&engine, Path::new(&path), &limits)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn academy_import_course(
+    app: AppHandle,
+    academy: State<'_, SharedAcademyEngine>,
+    path: String,
+    max_media_mb: Option<u64>,
+) -> Result<bundle::CourseImportPreview, String> {
+    let limits = bundle::BundleLimits::from_megabytes(max_media_mb);
+    let media_dir = app
+        .app_data_root()
+        .map_err(|e| format!("Unable to resolve app data directory: {e}"))?
+        .join("academy_media");
+    let engine = academy.read().await;
+    bundle::import_course(&engine, Path::new(&path), &media_dir, &limits)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 // Progress commands
 #[tauri::command]
 pub async fn start_course(
@@ -415,16 +547,119 @@ pub async fn complete_lesson(
     Ok(())
 }
 
+async fn load_quiz(
+    academy: &SharedAcademyEngine,
+    quiz_id: &str,
+) -> Result<(content::Quiz, Vec<content::QuizQuestion>), String> {
+    let content_service = academy.read().await.content_service();
+    let content_service = content_service.read().await;
+    let quiz = content_service
+        .get_quiz(quiz_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    let pool = content_service
+        .quiz_question_pool(&quiz)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok((quiz, pool))
+}
+
+/// Starts an attempt with a fresh draw of questions, or resumes the learner's
+/// unfinished attempt with the questions it was given.
+#[tauri::command]
+pub async fn start_quiz_attempt(
+    academy: State<'_, SharedAcademyEngine>,
+    wallet_address: String,
+    quiz_id: String,
+) -> Result<quiz::QuizSession, String> {
+    let (quiz, pool) = load_quiz(&academy, &quiz_id).await?;
+    let progress_tracker = academy.read().await.progress_tracker();
+    let progress_tracker = progress_tracker.read().await;
+
+    if let Some(open) = progress_tracker
+        .get_open_quiz_attempt(&wallet_address, &quiz_id)
+        .await
+        .map_err(|e| e.to_string())?
+    {
+        return Ok(quiz::quiz_session(&quiz, &open, &pool));
+    }
+
+    let previous = progress_tracker
+        .get_quiz_attempts(&wallet_address, &quiz_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    let now = Utc::now();
+    quiz::check_retake(&quiz, &previous, now)?;
+
+    let question_ids = quiz::draw_questions(&quiz, &pool, &mut rand::rng());
+    let attempt = progress_tracker
+        .start_quiz_attempt(progress::QuizAttempt {
+            id: format!("attempt_{}", Uuid::new_v4()),
+            wallet_address,
+            quiz_id,
+            score: 0,
+            total_points: 0,
+            passed: false,
+            answers: "{}".to_string(),
+            time_taken_minutes: 0,
+            attempted_at: now,
+            status: progress::QuizAttemptStatus::InProgress,
+            question_ids,
+            question_results: vec![],
+            started_at: now,
+        })
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(quiz::quiz_session(&quiz, &attempt, &pool))
+}
+
+/// Grades an in-progress attempt against the questions it drew. `answers`
+/// maps question ids to the selected option index.
 #[tauri::command]
 pub async fn submit_quiz(
     academy: State<'_, SharedAcademyEngine>,
-    attempt: progress::QuizAttempt,
+    attempt_id: String,
+    answers: HashMap<String, usize>,
 ) -> Result<progress::QuizAttempt, String> {
     let progress_tracker = academy.read().await.progress_tracker();
+    let attempt = progress_tracker
+        .read()
+        .await
+        .get_quiz_attempt(&attempt_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    if attempt.status != progress::QuizAttemptStatus::InProgress {
+        return Err(format!("Quiz attempt {} was already submitted", attempt_id));
+    }
+
+    let (quiz, pool) = load_quiz(&academy, &attempt.quiz_id).await?;
+    let previous = progress_tracker
+        .read()
+        .await
+        .get_quiz_attempts(&attempt.wallet_address, &attempt.quiz_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    quiz::check_retake(&quiz, &previous, attempt.started_at)?;
+
+    let grade = quiz::grade_attempt(&quiz, &pool, &attempt.question_ids, &answers);
+    let now = Utc::now();
+    let graded = progress::QuizAttempt {
+        score: grade.score,
+        total_points: grade.total_points,
+        passed: grade.passed,
+        answers: serde_json::to_string(&answers).map_err(|e| e.to_string())?,
+        time_taken_minutes: (now - attempt.started_at).num_minutes().max(0),
+        attempted_at: now,
+        status: progress::QuizAttemptStatus::Submitted,
+        question_results: grade.results,
+        ..attempt
+    };
+
     let result = progress_tracker
         .read()
         .await
-        .submit_quiz(attempt.clone())
+        .submit_quiz(graded)
         .await
         .map_err(|e| e.to_string())?;
 
@@ -452,6 +687,31 @@ pub async fn submit_quiz(
     Ok(result)
 }
 
+#[tauri::command]
+pub async fn get_quiz_standing(
+    academy: State<'_, SharedAcademyEngine>,
+    wallet_address: String,
+    quiz_id: String,
+) -> Result<quiz::QuizStanding, String> {
+    let (quiz, _) = load_quiz(&academy, &quiz_id).await?;
+    let attempts = academy
+        .read()
+        .await
+        .progress_tracker()
+        .read()
+        .await
+        .get_quiz_attempts(&wallet_address, &quiz_id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(quiz::quiz_standing(
+        &quiz,
+        &wallet_address,
+        &attempts,
+        Utc::now(),
+    ))
+}
+
 #[tauri::command]
 pub async fn get_quiz_attempts(
     academy: State<'_, SharedAcademyEngine>,
@@ -501,6 +761,75 @@ pub async fn get_challenge_submissions(
         .map_err(|e| e.to_string())
 }
 
+/// Starts a paper trading challenge on `account_id`, or the primary paper
+/// account when none is given.
+#[tauri::command]
+pub async fn start_paper_challenge(
+    app: AppHandle,
+    academy: State<'_, SharedAcademyEngine>,
+    wallet_address: String,
+    challenge_id: String,
+    account_id: Option<String>,
+) -> Result<progress::ChallengeRun, String> {
+    let challenge = academy
+        .read()
+        .await
+        .content_service()
+        .read()
+        .await
+        .get_challenge(&challenge_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    let progress_tracker = academy.read().await.progress_tracker();
+
+    let latest = progress_tracker
+        .read()
+        .await
+        .get_latest_challenge_run(&wallet_address, &challenge_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    if latest.is_some_and(|run| run.status == progress::ChallengeRunStatus::Active) {
+        return Err("This challenge is already in progress".to_string());
+    }
+
+    let reading = paper_challenge::read_paper_account(&app, account_id.as_deref()).await?;
+    let run = paper_challenge::start_run(&wallet_address, &challenge, &reading, Utc::now())?;
+    progress_tracker
+        .read()
+        .await
+        .create_challenge_run(run)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Live metrics for the learner's latest run at a paper trading challenge.
+#[tauri::command]
+pub async fn get_challenge_progress(
+    app: AppHandle,
+    academy: State<'_, SharedAcademyEngine>,
+    wallet_address: String,
+    challenge_id: String,
+) -> Result<paper_challenge::ChallengeProgress, String> {
+    let run = academy
+        .read()
+        .await
+        .progress_tracker()
+        .read()
+        .await
+        .get_latest_challenge_run(&wallet_address, &challenge_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "This challenge has not been started".to_string())?;
+
+    let run = if run.status == progress::ChallengeRunStatus::Active {
+        paper_challenge::advance_run(&app, &academy, run).await?
+    } else {
+        run
+    };
+
+    Ok(paper_challenge::challenge_progress(run, Utc::now()))
+}
+
 #[tauri::command]
 pub async fn record_webinar_attendance(
     academy: State<'_, SharedAcademyEngine>,
@@ -586,10 +915,37 @@ pub async fn get_user_stats(
         .map_err(|e| e.to_string())
 }
 
+/// Season leaderboard. Without a season id, the season running now is used,
+/// falling back to the all-time leaderboard when no season is active.
 #[tauri::command]
 pub async fn get_leaderboard(
     academy: State<'_, SharedAcademyEngine>,
     limit: i64,
+    season_id: Option<String>,
+) -> Result<Vec<progress::LeaderboardEntry>, String> {
+    let engine = academy.read().await;
+    let progress = engine.progress_tracker();
+    let progress = progress.read().await;
+
+    let season = match season_id {
+        Some(id) => Some(progress.get_season(&id).await.map_err(|e| e.to_string())?),
+        None => progress
+            .get_season_with_status(seasons::SeasonStatus::Active)
+            .await
+            .map_err(|e| e.to_string())?,
+    };
+
+    match season {
+        Some(season) => progress.get_season_leaderboard(&season, limit).await,
+        None => progress.get_leaderboard(limit).await,
+    }
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_all_time_leaderboard(
+    academy: State<'_, SharedAcademyEngine>,
+    limit: i64,
 ) -> Result<Vec<progress::LeaderboardEntry>, String> {
     academy
         .read()
@@ -602,6 +958,58 @@ pub async fn get_leaderboard(
         .map_err(|e| e.to_string())
 }
 
+// Season commands
+#[tauri::command]
+pub async fn create_season(
+    academy: State<'_, SharedAcademyEngine>,
+    season: seasons::Season,
+) -> Result<seasons::Season, String> {
+    let season = seasons::Season {
+        status: seasons::SeasonStatus::Scheduled,
+        finalized_at: None,
+        ..season
+    };
+    {
+        let engine = academy.read().await;
+        let progress = engine.progress_tracker();
+        let progress = progress.read().await;
+        let existing = progress.list_seasons().await.map_err(|e| e.to_string())?;
+        seasons::validate_season(&season, &existing)?;
+        progress
+            .create_season(&season)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    // Activate it straight away if its window is already open.
+    seasons::roll_seasons(&academy, Utc::now()).await?;
+
+    academy
+        .read()
+        .await
+        .progress_tracker()
+        .read()
+        .await
+        .get_season(&season.id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn list_seasons(
+    academy: State<'_, SharedAcademyEngine>,
+) -> Result<Vec<seasons::Season>, String> {
+    academy
+        .read()
+        .await
+        .progress_tracker()
+        .read()
+        .await
+        .list_seasons()
+        .await
+        .map_err(|e| e.to_string())
+}
+
 // Reward commands
 #[tauri::command]
 pub async fn create_badge(
@@ -696,8 +1104,16 @@ pub async fn get_user_badges(
 #[tauri::command]
 pub async fn issue_certificate(
     academy: State<'_, SharedAcademyEngine>,
+    keystore: State<'_, Keystore>,
     certificate: rewards::Certificate,
 ) -> Result<rewards::Certificate, String> {
+    let proof = certificate_proof::sign_certificate(&keystore, &certificate)?;
+    let certificate = rewards::Certificate {
+        proof: Some(proof),
+        anchor_signature: None,
+        ..certificate
+    };
+
     academy
         .read()
         .await
@@ -741,6 +1157,122 @@ pub async fn verify_certificate(
         .map_err(|e| e.to_string())
 }
 
+/// Loads a certificate and its proof, signing one for certificates issued
+/// before proofs existed.
+async fn load_certificate_with_proof(
+    academy: &SharedAcademyEngine,
+    keystore: &Keystore,
+    certificate_id: &str,
+) -> Result<(rewards::Certificate, String), String> {
+    let engine = academy.read().await;
+    let rewards = engine.reward_engine();
+    let rewards = rewards.read().await;
+    let certificate = rewards
+        .get_certificate(certificate_id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let proof = match &certificate.proof {
+        Some(proof) => proof.clone(),
+        None => {
+            let proof = certificate_proof::sign_certificate(keystore, &certificate)?;
+            rewards
+                .set_certificate_proof(&certificate.id, &proof)
+                .await
+                .map_err(|e| e.to_string())?;
+            proof
+        }
+    };
+    Ok((certificate, proof))
+}
+
+#[tauri::command]
+pub async fn get_certificate_proof(
+    academy: State<'_, SharedAcademyEngine>,
+    keystore: State<'_, Keystore>,
+    certificate_id: String,
+) -> Result<certificate_proof::CertificateProofBundle, String> {
+    let (certificate, proof) =
+        load_certificate_with_proof(&academy, &keystore, &certificate_id).await?;
+    certificate_proof::proof_bundle(&certificate, proof)
+}
+
+/// Checks a proof string on its own; the certificate does not need to exist
+/// in this app's database.
+#[tauri::command]
+pub async fn verify_certificate_proof(
+    keystore: State<'_, Keystore>,
+    proof: String,
+) -> Result<certificate_proof::CertificateProofCheck, String> {
+    let known_keys = certificate_proof::certificate_signing_keys(&keystore)?;
+    certificate_proof::check_certificate_proof(&proof, &known_keys)
+}
+
+#[tauri::command]
+pub async fn get_certificate_signing_keys(
+    keystore: State<'_, Keystore>,
+) -> Result<Vec<certificate_proof::CertificateSigningKey>, String> {
+    certificate_proof::certificate_signing_keys(&keystore)
+}
+
+#[tauri::command]
+pub async fn rotate_certificate_signing_key(
+    keystore: State<'_, Keystore>,
+) -> Result<certificate_proof::CertificateSigningKey, String> {
+    certificate_proof::rotate_certificate_key(&keystore)
+}
+
+/// Returns an unsigned memo transaction for the holder's wallet to sign.
+#[tauri::command]
+pub async fn prepare_certificate_anchor(
+    app: AppHandle,
+    academy: State<'_, SharedAcademyEngine>,
+    keystore: State<'_, Keystore>,
+    certificate_id: String,
+) -> Result<String, String> {
+    let (certificate, proof) =
+        load_certificate_with_proof(&academy, &keystore, &certificate_id).await?;
+    if certificate.anchor_signature.is_some() {
+        return Err("Certificate is already anchored".to_string());
+    }
+    let check = certificate_proof::check_certificate_proof(&proof, &[])?;
+    certificate_proof::prepare_anchor_transaction(&app, &certificate, &check.proof_hash)
+}
+
+#[tauri::command]
+pub async fn submit_certificate_anchor(
+    app: AppHandle,
+    academy: State<'_, SharedAcademyEngine>,
+    keystore: State<'_, Keystore>,
+    certificate_id: String,
+    signed_transaction: String,
+) -> Result<rewards::Certificate, String> {
+    let (certificate, proof) =
+        load_certificate_with_proof(&academy, &keystore, &certificate_id).await?;
+    if certificate.anchor_signature.is_some() {
+        return Err("Certificate is already anchored".to_string());
+    }
+    let check = certificate_proof::check_certificate_proof(&proof, &[])?;
+    let signature = certificate_proof::submit_anchor_transaction(
+        &app,
+        &certificate,
+        &check.proof_hash,
+        &signed_transaction,
+    )?;
+
+    let engine = academy.read().await;
+    let rewards = engine.reward_engine();
+    let rewards = rewards.read().await;
+    rewards
+        .set_certificate_anchor(&certificate.id, &signature)
+        .await
+        .map_err(|e| e.to_string())?;
+    rewards
+        .get_certificate(&certificate.id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn get_user_rewards(
     academy: State<'_, SharedAcademyEngine>,
@@ -1,8 +1,16 @@
+use crate::config::DataPaths;
+use crate::utils::ensure_column;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::{Pool, Row, Sqlite, SqlitePool};
+use std::collections::HashMap;
 use std::path::PathBuf;
-use tauri::{AppHandle, Manager};
+use tauri::AppHandle;
+
+use super::bundle::{BundleContent, BundleEntityKind, ImportKey};
+use super::paper_challenge::validate_paper_criteria;
+use super::quiz::{validate_draw, validate_questions};
+use super::rewards::RewardEngine;
 
 const ACADEMY_DB_FILE: &str = "academy.db";
 
@@ -71,9 +79,56 @@ pub struct Quiz {
     pub lesson_id: String,
     pub title: String,
     pub questions: Vec<QuizQuestion>,
+    /// Percentage of the drawn points needed to pass.
     pub passing_score: i64,
     pub max_attempts: Option<i64>,
     pub time_limit_minutes: Option<i64>,
+    /// When set, each attempt draws its questions from this bank instead of
+    /// using `questions`.
+    #[serde(default)]
+    pub question_bank_id: Option<String>,
+    /// Questions drawn per attempt; the whole bank when unset.
+    #[serde(default)]
+    pub draw_count: Option<i64>,
+    #[serde(default)]
+    pub retake_cooldown_minutes: Option<i64>,
+    #[serde(default)]
+    pub score_policy: ScorePolicy,
+}
+
+/// Which attempt counts as the learner's result for a quiz.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ScorePolicy {
+    #[default]
+    BestScore,
+    LastScore,
+}
+
+impl ScorePolicy {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ScorePolicy::BestScore => "best_score",
+            ScorePolicy::LastScore => "last_score",
+        }
+    }
+
+    fn from_db(value: &str) -> Self {
+        match value {
+            "last_score" => ScorePolicy::LastScore,
+            _ => ScorePolicy::BestScore,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuestionBank {
+    pub id: String,
+    pub title: String,
+    pub questions: Vec<QuizQuestion>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -102,6 +157,19 @@ pub struct Challenge {
     pub start_date: Option<DateTime<Utc>>,
     pub end_date: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
+    /// Set for challenges evaluated automatically from the paper account.
+    #[serde(default)]
+    pub paper_criteria: Option<PaperChallengeCriteria>,
+}
+
+/// "Return `target_return_percent` on the paper account within
+/// `duration_days`, never drawing down `max_drawdown_percent` or more."
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PaperChallengeCriteria {
+    pub target_return_percent: f64,
+    pub max_drawdown_percent: f64,
+    pub duration_days: i64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -168,7 +236,7 @@ pub struct ContentService {
 
 impl ContentService {
     pub async fn new(app_handle: &AppHandle) -> Result<Self, ContentError> {
-        let app_dir = app_handle.path().app_data_dir().map_err(|err| {
+        let app_dir = app_handle.app_data_root().map_err(|err| {
             ContentError::Io(std::io::Error::new(
                 std::io::ErrorKind::NotFound,
                 format!("Unable to resolve app data directory: {err}"),
@@ -262,6 +330,30 @@ impl ContentService {
         .execute(pool)
         .await?;
 
+        for (column, definition) in [
+            ("question_bank_id", "TEXT"),
+            ("draw_count", "INTEGER"),
+            ("retake_cooldown_minutes", "INTEGER"),
+            ("score_policy", "TEXT NOT NULL DEFAULT 'best_score'"),
+        ] {
+            ensure_column(pool, "quizzes", column, definition).await?;
+        }
+
+        // Question banks table
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS question_banks (
+                id TEXT PRIMARY KEY NOT NULL,
+                title TEXT NOT NULL,
+                questions TEXT NOT NULL, -- JSON
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            )
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
         // Challenges table
         sqlx::query(
             r#"
@@ -284,6 +376,8 @@ impl ContentService {
         .execute(pool)
         .await?;
 
+        ensure_column(pool, "challenges", "paper_criteria", "TEXT").await?;
+
         // Webinars table
         sqlx::query(
             r#"
@@ -327,6 +421,22 @@ impl ContentService {
         .execute(pool)
         .await?;
 
+        // Local ids given to re-keyed content from imported course bundles
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS academy_import_keys (
+                source_course_id TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                source_id TEXT NOT NULL,
+                local_id TEXT NOT NULL,
+                imported_at TEXT NOT NULL,
+                PRIMARY KEY (source_course_id, kind, source_id)
+            )
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
         // Create indexes
         sqlx::query("CREATE INDEX IF NOT EXISTS idx_courses_category ON courses(category)")
             .execute(pool)
@@ -474,16 +584,41 @@ impl ContentService {
         Ok(lessons)
     }
 
+    /// Course a lesson belongs to, if the lesson exists.
+    pub async fn lesson_course_id(&self, lesson_id: &str) -> Result<Option<String>, ContentError> {
+        let course_id = sqlx::query_scalar("SELECT course_id FROM lessons WHERE id = ?")
+            .bind(lesson_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(course_id)
+    }
+
     // Quiz operations
     pub async fn create_quiz(&self, quiz: Quiz) -> Result<Quiz, ContentError> {
+        validate_questions(&quiz.questions).map_err(ContentError::InvalidData)?;
+        if let Some(bank_id) = &quiz.question_bank_id {
+            let bank = self.get_question_bank(bank_id).await?;
+            validate_draw(&quiz, bank.questions.len()).map_err(ContentError::InvalidData)?;
+        } else if quiz.questions.is_empty() {
+            return Err(ContentError::InvalidData(
+                "A quiz needs questions or a question bank".to_string(),
+            ));
+        }
+        if quiz.retake_cooldown_minutes.is_some_and(|mins| mins < 0) {
+            return Err(ContentError::InvalidData(
+                "Retake cooldown cannot be negative".to_string(),
+            ));
+        }
+
         let questions_json = serde_json::to_string(&quiz.questions)?;
 
         sqlx::query(
             r#"
             INSERT INTO quizzes (
                 id, lesson_id, title, questions, passing_score,
-                max_attempts, time_limit_minutes
-            ) VALUES (?, ?, ?, ?, ?, ?, ?)
+                max_attempts, time_limit_minutes, question_bank_id,
+                draw_count, retake_cooldown_minutes, score_policy
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(&quiz.id)
@@ -493,6 +628,10 @@ impl ContentService {
         .bind(quiz.passing_score)
         .bind(quiz.max_attempts)
         .bind(quiz.time_limit_minutes)
+        .bind(&quiz.question_bank_id)
+        .bind(quiz.draw_count)
+        .bind(quiz.retake_cooldown_minutes)
+        .bind(quiz.score_policy.as_str())
         .execute(&self.pool)
         .await?;
 
@@ -509,17 +648,365 @@ impl ContentService {
         Self::quiz_from_row(&row)
     }
 
+    pub async fn get_course_quizzes(&self, course_id: &str) -> Result<Vec<Quiz>, ContentError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT q.* FROM quizzes q
+            JOIN lessons l ON l.id = q.lesson_id
+            WHERE l.course_id = ?
+            ORDER BY l.order_index ASC, q.id ASC
+            "#,
+        )
+        .bind(course_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut quizzes = Vec::new();
+        for row in rows {
+            quizzes.push(Self::quiz_from_row(&row)?);
+        }
+
+        Ok(quizzes)
+    }
+
+    /// Course a quiz belongs to through its lesson, if the quiz exists.
+    pub async fn quiz_course_id(&self, quiz_id: &str) -> Result<Option<String>, ContentError> {
+        Ok(sqlx::query_scalar(
+            r#"
+            SELECT l.course_id FROM quizzes q
+            JOIN lessons l ON l.id = q.lesson_id
+            WHERE q.id = ?
+            "#,
+        )
+        .bind(quiz_id)
+        .fetch_optional(&self.pool)
+        .await?)
+    }
+
+    /// Every question an attempt at `quiz` can be given.
+    pub async fn quiz_question_pool(&self, quiz: &Quiz) -> Result<Vec<QuizQuestion>, ContentError> {
+        match &quiz.question_bank_id {
+            Some(bank_id) => Ok(self.get_question_bank(bank_id).await?.questions),
+            None => Ok(quiz.questions.clone()),
+        }
+    }
+
+    // Question bank operations
+    pub async fn create_question_bank(
+        &self,
+        bank: QuestionBank,
+    ) -> Result<QuestionBank, ContentError> {
+        validate_questions(&bank.questions).map_err(ContentError::InvalidData)?;
+        let questions_json = serde_json::to_string(&bank.questions)?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO question_banks (id, title, questions, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&bank.id)
+        .bind(&bank.title)
+        .bind(questions_json)
+        .bind(bank.created_at.to_rfc3339())
+        .bind(bank.updated_at.to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(bank)
+    }
+
+    pub async fn get_question_bank(&self, id: &str) -> Result<QuestionBank, ContentError> {
+        let row = sqlx::query("SELECT * FROM question_banks WHERE id = ?")
+            .bind(id)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|_| ContentError::NotFound(format!("Question bank not found: {}", id)))?;
+
+        Self::question_bank_from_row(&row)
+    }
+
+    /// Replaces a bank's questions, e.g. after culling poor performers. Quizzes
+    /// drawing from the bank must still be able to draw their full count.
+    pub async fn update_question_bank(
+        &self,
+        bank: QuestionBank,
+    ) -> Result<QuestionBank, ContentError> {
+        validate_questions(&bank.questions).map_err(ContentError::InvalidData)?;
+
+        let rows = sqlx::query("SELECT * FROM quizzes WHERE question_bank_id = ?")
+            .bind(&bank.id)
+            .fetch_all(&self.pool)
+            .await?;
+        for row in rows {
+            let quiz = Self::quiz_from_row(&row)?;
+            validate_draw(&quiz, bank.questions.len()).map_err(ContentError::InvalidData)?;
+        }
+
+        let questions_json = serde_json::to_string(&bank.questions)?;
+        let updated_at = Utc::now();
+        let result = sqlx::query(
+            "UPDATE question_banks SET title = ?, questions = ?, updated_at = ? WHERE id = ?",
+        )
+        .bind(&bank.title)
+        .bind(questions_json)
+        .bind(updated_at.to_rfc3339())
+        .bind(&bank.id)
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(ContentError::NotFound(format!(
+                "Question bank not found: {}",
+                bank.id
+            )));
+        }
+
+        Ok(QuestionBank { updated_at, ..bank })
+    }
+
+    // Course bundle imports
+    /// Local ids previously given to the re-keyed entities of a bundle.
+    pub async fn import_keys(
+        &self,
+        source_course_id: &str,
+    ) -> Result<HashMap<(BundleEntityKind, String), String>, ContentError> {
+        let rows = sqlx::query(
+            "SELECT kind, source_id, local_id FROM academy_import_keys WHERE source_course_id = ?",
+        )
+        .bind(source_course_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut keys = HashMap::new();
+        for row in rows {
+            let kind: String = row.try_get("kind")?;
+            let Some(kind) = BundleEntityKind::from_db(&kind) else {
+                continue;
+            };
+            keys.insert((kind, row.try_get("source_id")?), row.try_get("local_id")?);
+        }
+
+        Ok(keys)
+    }
+
+    /// Writes an imported course in one transaction. Existing rows are
+    /// updated in place so learner progress keyed by their ids is kept;
+    /// `removed_lessons` and `removed_quizzes` are deleted.
+    pub async fn apply_course_import(
+        &self,
+        content: &BundleContent,
+        removed_lessons: &[String],
+        removed_quizzes: &[String],
+        keys: &[ImportKey],
+    ) -> Result<(), ContentError> {
+        for quiz in &content.quizzes {
+            validate_questions(&quiz.questions).map_err(ContentError::InvalidData)?;
+        }
+        for bank in &content.question_banks {
+            validate_questions(&bank.questions).map_err(ContentError::InvalidData)?;
+        }
+
+        let mut tx = self.pool.begin().await?;
+        let course = &content.course;
+
+        sqlx::query(
+            r#"
+            INSERT INTO courses (
+                id, title, description, level, category, duration_minutes,
+                xp_reward, badge_id, prerequisites, tags, thumbnail_url,
+                is_published, created_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(id) DO UPDATE SET
+                title = excluded.title,
+                description = excluded.description,
+                level = excluded.level,
+                category = excluded.category,
+                duration_minutes = excluded.duration_minutes,
+                xp_reward = excluded.xp_reward,
+                badge_id = excluded.badge_id,
+                prerequisites = excluded.prerequisites,
+                tags = excluded.tags,
+                thumbnail_url = excluded.thumbnail_url,
+                is_published = excluded.is_published,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(&course.id)
+        .bind(&course.title)
+        .bind(&course.description)
+        .bind(format!("{:?}", course.level).to_lowercase())
+        .bind(&course.category)
+        .bind(course.duration_minutes)
+        .bind(course.xp_reward)
+        .bind(&course.badge_id)
+        .bind(serde_json::to_string(&course.prerequisites)?)
+        .bind(serde_json::to_string(&course.tags)?)
+        .bind(&course.thumbnail_url)
+        .bind(course.is_published)
+        .bind(course.created_at.to_rfc3339())
+        .bind(course.updated_at.to_rfc3339())
+        .execute(&mut *tx)
+        .await?;
+
+        for quiz_id in removed_quizzes {
+            sqlx::query("DELETE FROM quizzes WHERE id = ?")
+                .bind(quiz_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        for lesson_id in removed_lessons {
+            sqlx::query("DELETE FROM quizzes WHERE lesson_id = ?")
+                .bind(lesson_id)
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query("DELETE FROM lessons WHERE id = ?")
+                .bind(lesson_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        for lesson in &content.lessons {
+            sqlx::query(
+                r#"
+                INSERT INTO lessons (
+                    id, course_id, title, description, content_type, content_url,
+                    content_data, order_index, duration_minutes, xp_reward,
+                    is_mandatory, created_at, updated_at
+                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                ON CONFLICT(id) DO UPDATE SET
+                    title = excluded.title,
+                    description = excluded.description,
+                    content_type = excluded.content_type,
+                    content_url = excluded.content_url,
+                    content_data = excluded.content_data,
+                    order_index = excluded.order_index,
+                    duration_minutes = excluded.duration_minutes,
+                    xp_reward = excluded.xp_reward,
+                    is_mandatory = excluded.is_mandatory,
+                    updated_at = excluded.updated_at
+                "#,
+            )
+            .bind(&lesson.id)
+            .bind(&lesson.course_id)
+            .bind(&lesson.title)
+            .bind(&lesson.description)
+            .bind(format!("{:?}", lesson.content_type).to_lowercase())
+            .bind(&lesson.content_url)
+            .bind(&lesson.content_data)
+            .bind(lesson.order_index)
+            .bind(lesson.duration_minutes)
+            .bind(lesson.xp_reward)
+            .bind(lesson.is_mandatory)
+            .bind(lesson.created_at.to_rfc3339())
+            .bind(lesson.updated_at.to_rfc3339())
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        for bank in &content.question_banks {
+            sqlx::query(
+                r#"
+                INSERT INTO question_banks (id, title, questions, created_at, updated_at)
+                VALUES (?, ?, ?, ?, ?)
+                ON CONFLICT(id) DO UPDATE SET
+                    title = excluded.title,
+                    questions = excluded.questions,
+                    updated_at = excluded.updated_at
+                "#,
+            )
+            .bind(&bank.id)
+            .bind(&bank.title)
+            .bind(serde_json::to_string(&bank.questions)?)
+            .bind(bank.created_at.to_rfc3339())
+            .bind(bank.updated_at.to_rfc3339())
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        for quiz in &content.quizzes {
+            sqlx::query(
+                r#"
+                INSERT INTO quizzes (
+                    id, lesson_id, title, questions, passing_score,
+                    max_attempts, time_limit_minutes, question_bank_id,
+                    draw_count, retake_cooldown_minutes, score_policy
+                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                ON CONFLICT(id) DO UPDATE SET
+                    lesson_id = excluded.lesson_id,
+                    title = excluded.title,
+                    questions = excluded.questions,
+                    passing_score = excluded.passing_score,
+                    max_attempts = excluded.max_attempts,
+                    time_limit_minutes = excluded.time_limit_minutes,
+                    question_bank_id = excluded.question_bank_id,
+                    draw_count = excluded.draw_count,
+                    retake_cooldown_minutes = excluded.retake_cooldown_minutes,
+                    score_policy = excluded.score_policy
+                "#,
+            )
+            .bind(&quiz.id)
+            .bind(&quiz.lesson_id)
+            .bind(&quiz.title)
+            .bind(serde_json::to_string(&quiz.questions)?)
+            .bind(quiz.passing_score)
+            .bind(quiz.max_attempts)
+            .bind(quiz.time_limit_minutes)
+            .bind(&quiz.question_bank_id)
+            .bind(quiz.draw_count)
+            .bind(quiz.retake_cooldown_minutes)
+            .bind(quiz.score_policy.as_str())
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        for badge in &content.badges {
+            RewardEngine::upsert_badge_with(&mut *tx, badge)
+                .await
+                .map_err(|e| ContentError::InvalidData(format!("Failed to import badge: {}", e)))?;
+        }
+
+        let imported_at = Utc::now().to_rfc3339();
+        for key in keys {
+            sqlx::query(
+                r#"
+                INSERT OR REPLACE INTO academy_import_keys (
+                    source_course_id, kind, source_id, local_id, imported_at
+                ) VALUES (?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(&key.source_course_id)
+            .bind(key.kind.as_str())
+            .bind(&key.source_id)
+            .bind(&key.local_id)
+            .bind(&imported_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
     // Challenge operations
     pub async fn create_challenge(&self, challenge: Challenge) -> Result<Challenge, ContentError> {
+        if let Some(criteria) = &challenge.paper_criteria {
+            validate_paper_criteria(criteria).map_err(ContentError::InvalidData)?;
+        }
         let difficulty_str = format!("{:?}", challenge.difficulty).to_lowercase();
+        let paper_criteria_json = challenge
+            .paper_criteria
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()?;
 
         sqlx::query(
             r#"
             INSERT INTO challenges (
                 id, title, description, category, difficulty, xp_reward,
                 badge_id, requirements, validation_criteria, start_date,
-                end_date, created_at
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                end_date, created_at, paper_criteria
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(&challenge.id)
@@ -534,12 +1021,23 @@ impl ContentService {
         .bind(challenge.start_date.map(|d| d.to_rfc3339()))
         .bind(challenge.end_date.map(|d| d.to_rfc3339()))
         .bind(challenge.created_at.to_rfc3339())
+        .bind(paper_criteria_json)
         .execute(&self.pool)
         .await?;
 
         Ok(challenge)
     }
 
+    pub async fn get_challenge(&self, id: &str) -> Result<Challenge, ContentError> {
+        let row = sqlx::query("SELECT * FROM challenges WHERE id = ?")
+            .bind(id)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|_| ContentError::NotFound(format!("Challenge not found: {}", id)))?;
+
+        Self::challenge_from_row(&row)
+    }
+
     pub async fn list_challenges(&self, active_only: bool) -> Result<Vec<Challenge>, ContentError> {
         let now = Utc::now().to_rfc3339();
 
@@ -787,6 +1285,28 @@ impl ContentService {
             passing_score: row.try_get("passing_score")?,
             max_attempts: row.try_get("max_attempts")?,
             time_limit_minutes: row.try_get("time_limit_minutes")?,
+            question_bank_id: row.try_get("question_bank_id")?,
+            draw_count: row.try_get("draw_count")?,
+            retake_cooldown_minutes: row.try_get("retake_cooldown_minutes")?,
+            score_policy: ScorePolicy::from_db(&row.try_get::<String, _>("score_policy")?),
+        })
+    }
+
+    fn question_bank_from_row(row: &sqlx::sqlite::SqliteRow) -> Result<QuestionBank, ContentError> {
+        let questions_json: String = row.try_get("questions")?;
+        let created_str: String = row.try_get("created_at")?;
+        let updated_str: String = row.try_get("updated_at")?;
+
+        Ok(QuestionBank {
+            id: row.try_get("id")?,
+            title: row.try_get("title")?,
+            questions: serde_json::from_str(&questions_json)?,
+            created_at: DateTime::parse_from_rfc3339(&created_str)
+                .map_err(|e| ContentError::InvalidData(e.to_string()))?
+                .with_timezone(&Utc),
+            updated_at: DateTime::parse_from_rfc3339(&updated_str)
+                .map_err(|e| ContentError::InvalidData(e.to_string()))?
+                .with_timezone(&Utc),
         })
     }
 
@@ -803,6 +1323,7 @@ impl ContentService {
         let created_str: String = row.try_get("created_at")?;
         let start_date_str: Option<String> = row.try_get("start_date")?;
         let end_date_str: Option<String> = row.try_get("end_date")?;
+        let paper_criteria_json: Option<String> = row.try_get("paper_criteria")?;
 
         Ok(Challenge {
             id: row.try_get("id")?,
@@ -827,6 +1348,9 @@ impl ContentService {
             created_at: DateTime::parse_from_rfc3339(&created_str)
                 .map_err(|e| ContentError::InvalidData(e.to_string()))?
                 .with_timezone(&Utc),
+            paper_criteria: paper_criteria_json
+                .map(|json| serde_json::from_str(&json))
+                .transpose()?,
         })
     }
 
@@ -1,12 +1,21 @@
+pub mod bundle;
+pub mod certificate_proof;
 pub mod commands;
 pub mod content;
+pub mod paper_challenge;
 pub mod progress;
+pub mod quiz;
 pub mod rewards;
+pub mod seasons;
 
+pub use certificate_proof::*;
 pub use commands::*;
 pub use content::*;
+pub use paper_challenge::*;
 pub use progress::*;
+pub use quiz::*;
 pub use rewards::*;
+pub use seasons::*;
 
 use std::sync::Arc;
 use tokio::sync::RwLock;
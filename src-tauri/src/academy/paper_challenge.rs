@@ -0,0 +1,589 @@
+//! Challenges evaluated automatically from a local paper trading account.
+//!
+//! Starting a run snapshots the chosen account (the primary one by default).
+//! Every check replays the trades placed since the snapshot: resetting or
+//! deleting the account, or any balance or position change the trades don't
+//! explain, invalidates the run. Runs pass or
+//! fail at their deadline, or fail early once the drawdown limit is reached.
+
+use super::content::{Challenge, PaperChallengeCriteria};
+use super::progress::{
+    ChallengeRun, ChallengeRunStatus, ChallengeSubmission, PaperAccountSnapshot,
+};
+use super::rewards::RewardError;
+use super::SharedAcademyEngine;
+use crate::trading::paper_trading::{self, PaperAccount, PaperTrade};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use tauri::{AppHandle, Emitter};
+use tokio::time::interval;
+use uuid::Uuid;
+
+const CHALLENGE_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+const BALANCE_TOLERANCE: f64 = 0.01;
+const QUANTITY_TOLERANCE: f64 = 1e-6;
+
+/// The paper account as it stands now, with every trade placed on it.
+#[derive(Debug, Clone)]
+pub struct PaperAccountReading {
+    pub account_id: String,
+    pub reset_at: Option<DateTime<Utc>>,
+    pub balance: f64,
+    pub positions: HashMap<String, f64>,
+    pub equity: f64,
+    pub trades: Vec<PaperTrade>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChallengeProgress {
+    pub run: ChallengeRun,
+    pub current_return_percent: f64,
+    pub target_return_percent: f64,
+    pub drawdown_limit_percent: f64,
+    pub remaining_secs: i64,
+    /// Whether the run would pass if it were evaluated now.
+    pub on_track: bool,
+}
+
+pub fn validate_paper_criteria(criteria: &PaperChallengeCriteria) -> Result<(), String> {
+    if !criteria.target_return_percent.is_finite() {
+        return Err("Target return must be a number".to_string());
+    }
+    if !(criteria.max_drawdown_percent > 0.0 && criteria.max_drawdown_percent <= 100.0) {
+        return Err("Maximum drawdown must be between 0 and 100%".to_string());
+    }
+    if criteria.duration_days <= 0 {
+        return Err("Challenge duration must be at least one day".to_string());
+    }
+    Ok(())
+}
+
+/// Reads `account_id`, or the primary paper account when it is `None`.
+pub async fn read_paper_account(
+    app: &AppHandle,
+    account_id: Option<&str>,
+) -> Result<PaperAccountReading, String> {
+    paper_trading::init_paper_trading(app).await?;
+    let manager = paper_trading::require_state()?;
+    let account = manager.get_account(account_id).await?;
+    reading_for(&account).await
+}
+
+/// Like `read_paper_account`, but `None` when the account has been deleted.
+async fn read_existing_account(
+    app: &AppHandle,
+    account_id: &str,
+) -> Result<Option<PaperAccountReading>, String> {
+    paper_trading::init_paper_trading(app).await?;
+    let manager = paper_trading::require_state()?;
+    match manager.find_account(account_id).await? {
+        Some(account) => reading_for(&account).await.map(Some),
+        None => Ok(None),
+    }
+}
+
+async fn reading_for(account: &PaperAccount) -> Result<PaperAccountReading, String> {
+    let manager = paper_trading::require_state()?;
+    let positions = manager.get_positions(Some(account.id.as_str())).await?;
+    let trades = manager.get_trade_history(Some(account.id.as_str())).await?;
+
+    let holdings: f64 = positions
+        .iter()
+        .map(|position| position.quantity * position.current_price)
+        .sum();
+    Ok(PaperAccountReading {
+        account_id: account.id.clone(),
+        reset_at: account.reset_at,
+        balance: account.balance,
+        positions: positions
+            .into_iter()
+            .map(|position| (position.symbol, position.quantity))
+            .collect(),
+        equity: account.balance + holdings,
+        trades,
+    })
+}
+
+pub fn start_run(
+    wallet_address: &str,
+    challenge: &Challenge,
+    reading: &PaperAccountReading,
+    now: DateTime<Utc>,
+) -> Result<ChallengeRun, String> {
+    let criteria = challenge.paper_criteria.ok_or_else(|| {
+        format!(
+            "Challenge {} is not a paper trading challenge",
+            challenge.id
+        )
+    })?;
+    if challenge.start_date.is_some_and(|start| now < start) {
+        return Err("This challenge has not opened yet".to_string());
+    }
+    if challenge.end_date.is_some_and(|end| now > end) {
+        return Err("This challenge has closed".to_string());
+    }
+    if reading.equity <= 0.0 {
+        return Err("The paper account has no equity to trade with".to_string());
+    }
+
+    Ok(ChallengeRun {
+        id: format!("run_{}", Uuid::new_v4()),
+        wallet_address: wallet_address.to_string(),
+        challenge_id: challenge.id.clone(),
+        status: ChallengeRunStatus::Active,
+        criteria,
+        snapshot: PaperAccountSnapshot {
+            account_id: reading.account_id.clone(),
+            balance: reading.balance,
+            positions: reading.positions.clone(),
+            equity: reading.equity,
+            taken_at: now,
+        },
+        latest_equity: reading.equity,
+        peak_equity: reading.equity,
+        max_drawdown_percent: 0.0,
+        started_at: now,
+        deadline: now + Duration::days(criteria.duration_days),
+        last_checked_at: now,
+        finished_at: None,
+        outcome_reason: None,
+    })
+}
+
+/// Explains why the account no longer matches the snapshot plus the trades
+/// placed since, or `None` if it does.
+pub fn detect_tampering(
+    snapshot: &PaperAccountSnapshot,
+    reading: &PaperAccountReading,
+) -> Option<String> {
+    let reset_since_snapshot = reading
+        .reset_at
+        .is_some_and(|reset_at| reset_at >= snapshot.taken_at);
+    if reading.account_id != snapshot.account_id || reset_since_snapshot {
+        return Some("The paper account was reset during the challenge".to_string());
+    }
+
+    let mut expected_balance = snapshot.balance;
+    let mut expected_positions = snapshot.positions.clone();
+    for trade in reading
+        .trades
+        .iter()
+        .filter(|trade| trade.timestamp >= snapshot.taken_at)
+    {
+        let quantity = expected_positions.entry(trade.symbol.clone()).or_default();
+        match trade.side.as_str() {
+            "buy" => {
+                expected_balance -= trade.total_cost;
+                *quantity += trade.quantity;
+            }
+            "sell" => {
+                expected_balance += trade.total_cost;
+                *quantity -= trade.quantity;
+            }
+            other => return Some(format!("Unrecognised paper trade side: {}", other)),
+        }
+    }
+
+    if (expected_balance - reading.balance).abs() > BALANCE_TOLERANCE {
+        return Some("The paper balance changed outside of trading".to_string());
+    }
+
+    let symbols: HashSet<&String> = expected_positions
+        .keys()
+        .chain(reading.positions.keys())
+        .collect();
+    for symbol in symbols {
+        let expected = expected_positions.get(symbol).copied().unwrap_or(0.0);
+        let actual = reading.positions.get(symbol).copied().unwrap_or(0.0);
+        if (expected - actual).abs() > QUANTITY_TOLERANCE {
+            return Some(format!(
+                "The {} paper position changed outside of trading",
+                symbol
+            ));
+        }
+    }
+
+    None
+}
+
+pub fn return_percent(run: &ChallengeRun) -> f64 {
+    if run.snapshot.equity > 0.0 {
+        (run.latest_equity - run.snapshot.equity) / run.snapshot.equity * 100.0
+    } else {
+        0.0
+    }
+}
+
+fn finish(run: &mut ChallengeRun, status: ChallengeRunStatus, reason: String, now: DateTime<Utc>) {
+    run.status = status;
+    run.outcome_reason = Some(reason);
+    run.finished_at = Some(now);
+}
+
+/// Applies one check to an active run. Returns `true` if the run finished.
+pub fn check_run(
+    run: &mut ChallengeRun,
+    reading: &PaperAccountReading,
+    now: DateTime<Utc>,
+) -> bool {
+    if run.status != ChallengeRunStatus::Active {
+        return false;
+    }
+    run.last_checked_at = now;
+
+    if let Some(reason) = detect_tampering(&run.snapshot, reading) {
+        finish(run, ChallengeRunStatus::Invalidated, reason, now);
+        return true;
+    }
+
+    run.latest_equity = reading.equity;
+    run.peak_equity = run.peak_equity.max(reading.equity);
+    if run.peak_equity > 0.0 {
+        let drawdown = (run.peak_equity - reading.equity) / run.peak_equity * 100.0;
+        run.max_drawdown_percent = run.max_drawdown_percent.max(drawdown);
+    }
+
+    let limit = run.criteria.max_drawdown_percent;
+    if run.max_drawdown_percent >= limit {
+        let reason = format!(
+            "Drawdown reached {:.2}%; it had to stay under {:.2}%",
+            run.max_drawdown_percent, limit
+        );
+        finish(run, ChallengeRunStatus::Failed, reason, now);
+        return true;
+    }
+
+    if now >= run.deadline {
+        let achieved = return_percent(run);
+        let target = run.criteria.target_return_percent;
+        if achieved >= target {
+            let reason = format!(
+                "Returned {:.2}% with a maximum drawdown of {:.2}%",
+                achieved, run.max_drawdown_percent
+            );
+            finish(run, ChallengeRunStatus::Passed, reason, now);
+        } else {
+            let reason = format!(
+                "Returned {:.2}%, short of the {:.2}% target",
+                achieved, target
+            );
+            finish(run, ChallengeRunStatus::Failed, reason, now);
+        }
+        return true;
+    }
+
+    false
+}
+
+/// Invalidates an active run whose paper account no longer exists. Returns
+/// `true` if the run finished.
+fn check_deleted_account(run: &mut ChallengeRun, now: DateTime<Utc>) -> bool {
+    if run.status != ChallengeRunStatus::Active {
+        return false;
+    }
+    run.last_checked_at = now;
+    let reason = "The paper account was deleted during the challenge".to_string();
+    finish(run, ChallengeRunStatus::Invalidated, reason, now);
+    true
+}
+
+pub fn challenge_progress(run: ChallengeRun, now: DateTime<Utc>) -> ChallengeProgress {
+    let current_return_percent = return_percent(&run);
+    let on_track = run.status == ChallengeRunStatus::Passed
+        || (run.status == ChallengeRunStatus::Active
+            && current_return_percent >= run.criteria.target_return_percent);
+
+    ChallengeProgress {
+        current_return_percent,
+        target_return_percent: run.criteria.target_return_percent,
+        drawdown_limit_percent: run.criteria.max_drawdown_percent,
+        remaining_secs: if run.status == ChallengeRunStatus::Active {
+            (run.deadline - now).num_seconds().max(0)
+        } else {
+            0
+        },
+        on_track,
+        run,
+    }
+}
+
+/// Records a finished run as a challenge submission and, when it passed,
+/// awards the challenge's XP and badge.
+async fn settle_run(academy: &SharedAcademyEngine, run: &ChallengeRun) -> Result<(), String> {
+    let engine = academy.read().await;
+    let challenge = engine
+        .content_service()
+        .read()
+        .await
+        .get_challenge(&run.challenge_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    let progress_tracker = engine.progress_tracker();
+    let progress_tracker = progress_tracker.read().await;
+    let passed = run.status == ChallengeRunStatus::Passed;
+    let finished_at = run.finished_at.unwrap_or_else(Utc::now);
+
+    progress_tracker
+        .submit_challenge(ChallengeSubmission {
+            id: format!("submission_{}", run.id),
+            wallet_address: run.wallet_address.clone(),
+            challenge_id: run.challenge_id.clone(),
+            submission_data: serde_json::json!({
+                "runId": run.id,
+                "returnPercent": return_percent(run),
+                "maxDrawdownPercent": run.max_drawdown_percent,
+                "status": run.status,
+            })
+            .to_string(),
+            status: if passed { "approved" } else { "rejected" }.to_string(),
+            score: Some(return_percent(run).round() as i64),
+            feedback: run.outcome_reason.clone(),
+            submitted_at: finished_at,
+            reviewed_at: Some(finished_at),
+        })
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if !passed {
+        return Ok(());
+    }
+
+    progress_tracker
+        .add_xp(&run.wallet_address, challenge.xp_reward)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if let Some(badge_id) = &challenge.badge_id {
+        progress_tracker
+            .add_badge(&run.wallet_address, badge_id)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let awarded = engine
+            .reward_engine()
+            .read()
+            .await
+            .award_badge(
+                &run.wallet_address,
+                badge_id,
+                &format!("Completed challenge: {}", challenge.title),
+            )
+            .await;
+        match awarded {
+            Ok(_) | Err(RewardError::AlreadyClaimed(_)) => {}
+            Err(e) => return Err(e.to_string()),
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks a run against its paper account and settles it if the check
+/// finished it.
+pub async fn advance_run(
+    app: &AppHandle,
+    academy: &SharedAcademyEngine,
+    mut run: ChallengeRun,
+) -> Result<ChallengeRun, String> {
+    let now = Utc::now();
+    let finished = match read_existing_account(app, &run.snapshot.account_id).await? {
+        Some(reading) => check_run(&mut run, &reading, now),
+        None => check_deleted_account(&mut run, now),
+    };
+    let progress_tracker = academy.read().await.progress_tracker();
+    let updated = progress_tracker
+        .read()
+        .await
+        .update_challenge_run(&run)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // Only the check that moved the run out of `active` settles it.
+    if finished && updated {
+        settle_run(academy, &run).await?;
+        let _ = app.emit("academy_challenge_finished", &run);
+    }
+
+    Ok(run)
+}
+
+async fn evaluate_active_runs(
+    app: &AppHandle,
+    academy: &SharedAcademyEngine,
+) -> Result<(), String> {
+    let progress_tracker = academy.read().await.progress_tracker();
+    let runs = progress_tracker
+        .read()
+        .await
+        .list_active_challenge_runs()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    for run in runs {
+        let run_id = run.id.clone();
+        if let Err(err) = advance_run(app, academy, run).await {
+            eprintln!("Failed to evaluate paper challenge run {}: {}", run_id, err);
+        }
+    }
+
+    Ok(())
+}
+
+/// Tracks every active paper challenge run and evaluates it at its deadline.
+pub async fn run_paper_challenge_evaluator(app: AppHandle, academy: SharedAcademyEngine) {
+    let mut tick = interval(CHALLENGE_CHECK_INTERVAL);
+    loop {
+        tick.tick().await;
+        if let Err(err) = evaluate_active_runs(&app, &academy).await {
+            eprintln!("Failed to evaluate paper challenges: {}", err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::academy::content::CourseLevel;
+
+    fn challenge() -> Challenge {
+        Challenge {
+            id: "challenge_1".to_string(),
+            title: "Steady returns".to_string(),
+            description: String::new(),
+            category: "trading".to_string(),
+            difficulty: CourseLevel::Intermediate,
+            xp_reward: 1000,
+            badge_id: Some("steady_hands".to_string()),
+            requirements: "{}".to_string(),
+            validation_criteria: "{}".to_string(),
+            start_date: None,
+            end_date: None,
+            created_at: Utc::now(),
+            paper_criteria: Some(PaperChallengeCriteria {
+                target_return_percent: 5.0,
+                max_drawdown_percent: 10.0,
+                duration_days: 7,
+            }),
+        }
+    }
+
+    fn reading(
+        balance: f64,
+        sol: f64,
+        sol_price: f64,
+        trades: Vec<PaperTrade>,
+    ) -> PaperAccountReading {
+        PaperAccountReading {
+            account_id: "paper_1".to_string(),
+            reset_at: None,
+            balance,
+            positions: [("SOL".to_string(), sol)].into_iter().collect(),
+            equity: balance + sol * sol_price,
+            trades,
+        }
+    }
+
+    fn trade(side: &str, quantity: f64, total_cost: f64, at: DateTime<Utc>) -> PaperTrade {
+        PaperTrade {
+            id: Uuid::new_v4().to_string(),
+            account_id: "paper_1".to_string(),
+            symbol: "SOL".to_string(),
+            side: side.to_string(),
+            order_type: "market".to_string(),
+            quantity,
+            price: total_cost / quantity,
+            trading_fee: 0.0,
+            network_fee: 0.0,
+            price_impact_fee: 0.0,
+            fee: 0.0,
+            slippage: 0.0,
+            total_cost,
+            timestamp: at,
+        }
+    }
+
+    #[test]
+    fn test_run_passes_at_deadline_from_traded_gains() {
+        let start = Utc::now();
+        let mut run = start_run(
+            "learner",
+            &challenge(),
+            &reading(10_000.0, 0.0, 100.0, vec![]),
+            start,
+        )
+        .unwrap();
+
+        let buy = trade("buy", 10.0, 1_000.0, start + Duration::hours(1));
+        let mid = reading(9_000.0, 10.0, 105.0, vec![buy.clone()]);
+        assert!(!check_run(&mut run, &mid, start + Duration::days(1)));
+        assert_eq!(run.status, ChallengeRunStatus::Active);
+
+        let sell = trade("sell", 10.0, 1_600.0, start + Duration::days(6));
+        let end = reading(10_600.0, 0.0, 160.0, vec![buy, sell]);
+        assert!(check_run(&mut run, &end, start + Duration::days(7)));
+        assert_eq!(run.status, ChallengeRunStatus::Passed);
+        assert!((return_percent(&run) - 6.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_drawdown_fails_and_tampering_invalidates() {
+        let start = Utc::now();
+        let base = reading(5_000.0, 50.0, 100.0, vec![]);
+        let mut run = start_run("learner", &challenge(), &base, start).unwrap();
+        assert!(check_run(
+            &mut run,
+            &reading(5_000.0, 50.0, 78.0, vec![]),
+            start + Duration::hours(5)
+        ));
+        assert_eq!(run.status, ChallengeRunStatus::Failed);
+
+        let mut edited = start_run("learner", &challenge(), &base, start).unwrap();
+        assert!(check_run(
+            &mut edited,
+            &reading(8_000.0, 50.0, 100.0, vec![]),
+            start + Duration::hours(1)
+        ));
+        assert_eq!(edited.status, ChallengeRunStatus::Invalidated);
+
+        let mut reset = start_run("learner", &challenge(), &base, start).unwrap();
+        let fresh = PaperAccountReading {
+            account_id: "paper_2".to_string(),
+            ..reading(10_000.0, 0.0, 100.0, vec![])
+        };
+        assert!(check_run(&mut reset, &fresh, start + Duration::hours(1)));
+        assert_eq!(reset.status, ChallengeRunStatus::Invalidated);
+    }
+
+    #[test]
+    fn test_scoped_reset_and_deletion_invalidate() {
+        let start = Utc::now();
+        let base = reading(5_000.0, 0.0, 100.0, vec![]);
+
+        let mut reset = start_run("learner", &challenge(), &base, start).unwrap();
+        let same_id = PaperAccountReading {
+            reset_at: Some(start + Duration::minutes(30)),
+            ..base.clone()
+        };
+        assert!(check_run(&mut reset, &same_id, start + Duration::hours(1)));
+        assert_eq!(reset.status, ChallengeRunStatus::Invalidated);
+
+        let earlier_reset = PaperAccountReading {
+            reset_at: Some(start - Duration::days(1)),
+            ..base.clone()
+        };
+        let mut untouched = start_run("learner", &challenge(), &base, start).unwrap();
+        assert!(!check_run(
+            &mut untouched,
+            &earlier_reset,
+            start + Duration::hours(1)
+        ));
+
+        assert!(check_deleted_account(
+            &mut untouched,
+            start + Duration::hours(2)
+        ));
+        assert_eq!(untouched.status, ChallengeRunStatus::Invalidated);
+    }
+}
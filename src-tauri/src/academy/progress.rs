@@ -1,7 +1,16 @@
+use crate::config::DataPaths;
+use crate::utils::ensure_column;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::{Pool, Row, Sqlite, SqlitePool};
-use tauri::{AppHandle, Manager};
+use std::collections::HashMap;
+use tauri::AppHandle;
+use uuid::Uuid;
+
+use super::content::PaperChallengeCriteria;
+use super::seasons::{
+    rank_standings, Season, SeasonStanding, SeasonStats, SeasonStatus, SeasonTally,
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
@@ -54,6 +63,49 @@ pub struct QuizAttempt {
     pub answers: String, // JSON
     pub time_taken_minutes: i64,
     pub attempted_at: DateTime<Utc>,
+    #[serde(default)]
+    pub status: QuizAttemptStatus,
+    /// Questions drawn for this attempt, in the order they were shown.
+    #[serde(default)]
+    pub question_ids: Vec<String>,
+    #[serde(default)]
+    pub question_results: Vec<QuestionResult>,
+    #[serde(default)]
+    pub started_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum QuizAttemptStatus {
+    InProgress,
+    #[default]
+    Submitted,
+}
+
+impl QuizAttemptStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            QuizAttemptStatus::InProgress => "in_progress",
+            QuizAttemptStatus::Submitted => "submitted",
+        }
+    }
+
+    fn from_db(value: &str) -> Self {
+        match value {
+            "in_progress" => QuizAttemptStatus::InProgress,
+            _ => QuizAttemptStatus::Submitted,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct QuestionResult {
+    pub question_id: String,
+    pub selected_answer: Option<usize>,
+    pub correct: bool,
+    pub points: i64,
+    pub points_awarded: i64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -70,6 +122,68 @@ pub struct ChallengeSubmission {
     pub reviewed_at: Option<DateTime<Utc>>,
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ChallengeRunStatus {
+    Active,
+    Passed,
+    Failed,
+    /// The paper account was reset or edited outside of trading mid-run.
+    Invalidated,
+}
+
+impl ChallengeRunStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ChallengeRunStatus::Active => "active",
+            ChallengeRunStatus::Passed => "passed",
+            ChallengeRunStatus::Failed => "failed",
+            ChallengeRunStatus::Invalidated => "invalidated",
+        }
+    }
+
+    fn from_db(value: &str) -> Self {
+        match value {
+            "passed" => ChallengeRunStatus::Passed,
+            "failed" => ChallengeRunStatus::Failed,
+            "invalidated" => ChallengeRunStatus::Invalidated,
+            _ => ChallengeRunStatus::Active,
+        }
+    }
+}
+
+/// The paper account as it stood when a challenge run started.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PaperAccountSnapshot {
+    pub account_id: String,
+    pub balance: f64,
+    /// Position quantities by symbol.
+    pub positions: HashMap<String, f64>,
+    pub equity: f64,
+    pub taken_at: DateTime<Utc>,
+}
+
+/// One learner's attempt at a paper trading challenge.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChallengeRun {
+    pub id: String,
+    pub wallet_address: String,
+    pub challenge_id: String,
+    pub status: ChallengeRunStatus,
+    pub criteria: PaperChallengeCriteria,
+    pub snapshot: PaperAccountSnapshot,
+    pub latest_equity: f64,
+    pub peak_equity: f64,
+    pub max_drawdown_percent: f64,
+    pub started_at: DateTime<Utc>,
+    pub deadline: DateTime<Utc>,
+    pub last_checked_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub outcome_reason: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct WebinarAttendance {
@@ -115,6 +229,9 @@ pub struct UserStats {
     pub current_streak_days: i64,
     pub longest_streak_days: i64,
     pub badges_earned: Vec<String>,
+    /// Standing in the season that is running now, if any.
+    #[serde(default)]
+    pub current_season: Option<SeasonStats>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -126,6 +243,15 @@ pub struct LeaderboardEntry {
     pub courses_completed: i64,
     pub badges_count: i64,
     pub streak_days: i64,
+    /// Points the entry is ranked by: season XP, or total XP for all-time.
+    #[serde(default)]
+    pub points: i64,
+    #[serde(default)]
+    pub season_id: Option<String>,
+    #[serde(default)]
+    pub reached_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub activity_count: i64,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -148,7 +274,7 @@ pub struct ProgressTracker {
 
 impl ProgressTracker {
     pub async fn new(app_handle: &AppHandle) -> Result<Self, ProgressError> {
-        let app_dir = app_handle.path().app_data_dir().map_err(|err| {
+        let app_dir = app_handle.app_data_root().map_err(|err| {
             ProgressError::Io(std::io::Error::new(
                 std::io::ErrorKind::NotFound,
                 format!("Unable to resolve app data directory: {err}"),
@@ -236,6 +362,15 @@ impl ProgressTracker {
         .execute(pool)
         .await?;
 
+        for (column, definition) in [
+            ("status", "TEXT NOT NULL DEFAULT 'submitted'"),
+            ("question_ids", "TEXT NOT NULL DEFAULT '[]'"),
+            ("question_results", "TEXT NOT NULL DEFAULT '[]'"),
+            ("started_at", "TEXT"),
+        ] {
+            ensure_column(pool, "quiz_attempts", column, definition).await?;
+        }
+
         // Challenge submissions table
         sqlx::query(
             r#"
@@ -255,6 +390,30 @@ impl ProgressTracker {
         .execute(pool)
         .await?;
 
+        // Paper challenge runs table
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS challenge_runs (
+                id TEXT PRIMARY KEY NOT NULL,
+                wallet_address TEXT NOT NULL,
+                challenge_id TEXT NOT NULL,
+                status TEXT NOT NULL DEFAULT 'active',
+                criteria TEXT NOT NULL, -- JSON
+                snapshot TEXT NOT NULL, -- JSON
+                latest_equity REAL NOT NULL,
+                peak_equity REAL NOT NULL,
+                max_drawdown_percent REAL NOT NULL DEFAULT 0.0,
+                started_at TEXT NOT NULL,
+                deadline TEXT NOT NULL,
+                last_checked_at TEXT NOT NULL,
+                finished_at TEXT,
+                outcome_reason TEXT
+            )
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
         // Webinar attendance table
         sqlx::query(
             r#"
@@ -312,6 +471,61 @@ impl ProgressTracker {
         .execute(pool)
         .await?;
 
+        for (column, definition) in [
+            ("xp_reached_at", "TEXT"),
+            ("activity_count", "INTEGER NOT NULL DEFAULT 0"),
+        ] {
+            ensure_column(pool, "user_stats", column, definition).await?;
+        }
+
+        // XP ledger, used to score seasons
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS xp_events (
+                id TEXT PRIMARY KEY NOT NULL,
+                wallet_address TEXT NOT NULL,
+                amount INTEGER NOT NULL,
+                earned_at TEXT NOT NULL
+            )
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        // Leaderboard seasons
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS academy_seasons (
+                id TEXT PRIMARY KEY NOT NULL,
+                name TEXT NOT NULL,
+                starts_at TEXT NOT NULL,
+                ends_at TEXT NOT NULL,
+                status TEXT NOT NULL,
+                reward_tiers TEXT NOT NULL, -- JSON array
+                finalized_at TEXT
+            )
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS season_standings (
+                season_id TEXT NOT NULL,
+                wallet_address TEXT NOT NULL,
+                rank INTEGER NOT NULL,
+                points INTEGER NOT NULL,
+                reached_at TEXT NOT NULL,
+                activity_count INTEGER NOT NULL,
+                reward_issued INTEGER NOT NULL DEFAULT 0,
+                PRIMARY KEY(season_id, wallet_address)
+            )
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
         // Create indexes
         sqlx::query(
             "CREATE INDEX IF NOT EXISTS idx_user_progress_wallet ON user_progress(wallet_address)",
@@ -327,6 +541,22 @@ impl ProgressTracker {
             .execute(pool)
             .await?;
 
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS idx_quiz_attempts_quiz ON quiz_attempts(quiz_id, status)",
+        )
+        .execute(pool)
+        .await?;
+
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS idx_challenge_runs_status ON challenge_runs(status)",
+        )
+        .execute(pool)
+        .await?;
+
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_xp_events_earned ON xp_events(earned_at)")
+            .execute(pool)
+            .await?;
+
         Ok(())
     }
 
@@ -542,14 +772,36 @@ impl ProgressTracker {
         Ok(())
     }
 
+    /// Learners with any progress recorded on each of `lesson_ids`.
+    pub async fn count_lesson_learners(
+        &self,
+        lesson_ids: &[String],
+    ) -> Result<HashMap<String, i64>, ProgressError> {
+        let mut counts = HashMap::new();
+        for lesson_id in lesson_ids {
+            let count: i64 = sqlx::query_scalar(
+                "SELECT COUNT(DISTINCT wallet_address) FROM lesson_progress WHERE lesson_id = ?",
+            )
+            .bind(lesson_id)
+            .fetch_one(&self.pool)
+            .await?;
+            counts.insert(lesson_id.clone(), count);
+        }
+        Ok(counts)
+    }
+
     // Quiz operations
-    pub async fn submit_quiz(&self, attempt: QuizAttempt) -> Result<QuizAttempt, ProgressError> {
+    pub async fn start_quiz_attempt(
+        &self,
+        attempt: QuizAttempt,
+    ) -> Result<QuizAttempt, ProgressError> {
         sqlx::query(
             r#"
             INSERT INTO quiz_attempts (
-                id, wallet_address, quiz_id, score, total_points, 
-                passed, answers, time_taken_minutes, attempted_at
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+                id, wallet_address, quiz_id, score, total_points,
+                passed, answers, time_taken_minutes, attempted_at,
+                status, question_ids, question_results, started_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(&attempt.id)
@@ -561,6 +813,10 @@ impl ProgressTracker {
         .bind(&attempt.answers)
         .bind(attempt.time_taken_minutes)
         .bind(attempt.attempted_at.to_rfc3339())
+        .bind(attempt.status.as_str())
+        .bind(serde_json::to_string(&attempt.question_ids)?)
+        .bind(serde_json::to_string(&attempt.question_results)?)
+        .bind(attempt.started_at.to_rfc3339())
         .execute(&self.pool)
         .await?;
 
@@ -569,6 +825,89 @@ impl ProgressTracker {
         Ok(attempt)
     }
 
+    pub async fn get_quiz_attempt(&self, id: &str) -> Result<QuizAttempt, ProgressError> {
+        let row = sqlx::query("SELECT * FROM quiz_attempts WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| ProgressError::NotFound(format!("Quiz attempt not found: {}", id)))?;
+
+        Self::quiz_attempt_from_row(&row)
+    }
+
+    /// The learner's unfinished attempt at a quiz, if any, so it can be resumed
+    /// with the same questions.
+    pub async fn get_open_quiz_attempt(
+        &self,
+        wallet_address: &str,
+        quiz_id: &str,
+    ) -> Result<Option<QuizAttempt>, ProgressError> {
+        let row = sqlx::query(
+            "SELECT * FROM quiz_attempts WHERE wallet_address = ? AND quiz_id = ? AND status = 'in_progress' ORDER BY started_at DESC LIMIT 1",
+        )
+        .bind(wallet_address)
+        .bind(quiz_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|row| Self::quiz_attempt_from_row(&row)).transpose()
+    }
+
+    /// Records the graded result of an in-progress attempt.
+    pub async fn submit_quiz(&self, attempt: QuizAttempt) -> Result<QuizAttempt, ProgressError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE quiz_attempts SET
+                score = ?, total_points = ?, passed = ?, answers = ?,
+                time_taken_minutes = ?, attempted_at = ?, status = ?,
+                question_results = ?
+            WHERE id = ? AND status = 'in_progress'
+            "#,
+        )
+        .bind(attempt.score)
+        .bind(attempt.total_points)
+        .bind(attempt.passed)
+        .bind(&attempt.answers)
+        .bind(attempt.time_taken_minutes)
+        .bind(attempt.attempted_at.to_rfc3339())
+        .bind(attempt.status.as_str())
+        .bind(serde_json::to_string(&attempt.question_results)?)
+        .bind(&attempt.id)
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(ProgressError::InvalidData(format!(
+                "Quiz attempt {} is not in progress",
+                attempt.id
+            )));
+        }
+
+        self.update_user_activity(&attempt.wallet_address).await?;
+
+        Ok(attempt)
+    }
+
+    /// Every submitted attempt at a quiz, across learners.
+    pub async fn get_quiz_submissions(
+        &self,
+        quiz_id: &str,
+    ) -> Result<Vec<QuizAttempt>, ProgressError> {
+        let rows = sqlx::query(
+            "SELECT * FROM quiz_attempts WHERE quiz_id = ? AND status = 'submitted' ORDER BY attempted_at ASC",
+        )
+        .bind(quiz_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut attempts = Vec::new();
+        for row in rows {
+            attempts.push(Self::quiz_attempt_from_row(&row)?);
+        }
+
+        Ok(attempts)
+    }
+
     pub async fn get_quiz_attempts(
         &self,
         wallet_address: &str,
@@ -637,6 +976,99 @@ impl ProgressTracker {
         Ok(submissions)
     }
 
+    // Paper challenge run operations
+    pub async fn create_challenge_run(
+        &self,
+        run: ChallengeRun,
+    ) -> Result<ChallengeRun, ProgressError> {
+        sqlx::query(
+            r#"
+            INSERT INTO challenge_runs (
+                id, wallet_address, challenge_id, status, criteria, snapshot,
+                latest_equity, peak_equity, max_drawdown_percent, started_at,
+                deadline, last_checked_at, finished_at, outcome_reason
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&run.id)
+        .bind(&run.wallet_address)
+        .bind(&run.challenge_id)
+        .bind(run.status.as_str())
+        .bind(serde_json::to_string(&run.criteria)?)
+        .bind(serde_json::to_string(&run.snapshot)?)
+        .bind(run.latest_equity)
+        .bind(run.peak_equity)
+        .bind(run.max_drawdown_percent)
+        .bind(run.started_at.to_rfc3339())
+        .bind(run.deadline.to_rfc3339())
+        .bind(run.last_checked_at.to_rfc3339())
+        .bind(run.finished_at.map(|d| d.to_rfc3339()))
+        .bind(&run.outcome_reason)
+        .execute(&self.pool)
+        .await?;
+
+        self.update_user_activity(&run.wallet_address).await?;
+
+        Ok(run)
+    }
+
+    /// The learner's most recent run at a challenge.
+    pub async fn get_latest_challenge_run(
+        &self,
+        wallet_address: &str,
+        challenge_id: &str,
+    ) -> Result<Option<ChallengeRun>, ProgressError> {
+        let row = sqlx::query(
+            "SELECT * FROM challenge_runs WHERE wallet_address = ? AND challenge_id = ? ORDER BY started_at DESC LIMIT 1",
+        )
+        .bind(wallet_address)
+        .bind(challenge_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|row| Self::challenge_run_from_row(&row))
+            .transpose()
+    }
+
+    pub async fn list_active_challenge_runs(&self) -> Result<Vec<ChallengeRun>, ProgressError> {
+        let rows = sqlx::query("SELECT * FROM challenge_runs WHERE status = 'active'")
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut runs = Vec::new();
+        for row in rows {
+            runs.push(Self::challenge_run_from_row(&row)?);
+        }
+
+        Ok(runs)
+    }
+
+    /// Saves a run's latest metrics and status. Only active runs are updated,
+    /// so a run is finished at most once; returns whether the row changed.
+    pub async fn update_challenge_run(&self, run: &ChallengeRun) -> Result<bool, ProgressError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE challenge_runs SET
+                status = ?, latest_equity = ?, peak_equity = ?,
+                max_drawdown_percent = ?, last_checked_at = ?,
+                finished_at = ?, outcome_reason = ?
+            WHERE id = ? AND status = 'active'
+            "#,
+        )
+        .bind(run.status.as_str())
+        .bind(run.latest_equity)
+        .bind(run.peak_equity)
+        .bind(run.max_drawdown_percent)
+        .bind(run.last_checked_at.to_rfc3339())
+        .bind(run.finished_at.map(|d| d.to_rfc3339()))
+        .bind(&run.outcome_reason)
+        .bind(&run.id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
     // Webinar attendance
     pub async fn record_webinar_attendance(
         &self,
@@ -798,24 +1230,47 @@ impl ProgressTracker {
             current_streak_days,
             longest_streak_days,
             badges_earned,
+            current_season: self.current_season_stats(wallet_address).await?,
         })
     }
 
+    /// Adds XP to the all-time total and records it in the XP ledger, so it
+    /// also counts towards whichever season it was earned in.
     pub async fn add_xp(&self, wallet_address: &str, xp: i64) -> Result<(), ProgressError> {
+        if xp == 0 {
+            return Ok(());
+        }
+        let now = Utc::now().to_rfc3339();
+        let mut tx = self.pool.begin().await?;
+
         sqlx::query(
             r#"
-            INSERT INTO user_stats (wallet_address, total_xp, last_activity_date)
-            VALUES (?, ?, ?)
-            ON CONFLICT(wallet_address) DO UPDATE SET 
-                total_xp = total_xp + excluded.total_xp
+            INSERT INTO user_stats (wallet_address, total_xp, last_activity_date, xp_reached_at, activity_count)
+            VALUES (?, ?, ?, ?, 1)
+            ON CONFLICT(wallet_address) DO UPDATE SET
+                total_xp = total_xp + excluded.total_xp,
+                xp_reached_at = excluded.xp_reached_at,
+                activity_count = activity_count + 1
             "#,
         )
         .bind(wallet_address)
         .bind(xp)
-        .bind(Utc::now().to_rfc3339())
-        .execute(&self.pool)
+        .bind(&now)
+        .bind(&now)
+        .execute(&mut tx)
         .await?;
 
+        sqlx::query(
+            "INSERT INTO xp_events (id, wallet_address, amount, earned_at) VALUES (?, ?, ?, ?)",
+        )
+        .bind(format!("xp_{}", Uuid::new_v4()))
+        .bind(wallet_address)
+        .bind(xp)
+        .bind(&now)
+        .execute(&mut tx)
+        .await?;
+
+        tx.commit().await?;
         Ok(())
     }
 
@@ -907,19 +1362,20 @@ impl ProgressTracker {
         Ok(())
     }
 
+    /// All-time leaderboard. Ties go to whoever reached their XP first, then
+    /// to the more active learner.
     pub async fn get_leaderboard(
         &self,
         limit: i64,
     ) -> Result<Vec<LeaderboardEntry>, ProgressError> {
         let rows = sqlx::query(
             r#"
-            SELECT 
-                wallet_address,
-                total_xp,
-                current_streak_days,
-                badges_earned
-            FROM user_stats 
-            ORDER BY total_xp DESC 
+            SELECT wallet_address, total_xp, xp_reached_at, activity_count
+            FROM user_stats
+            ORDER BY total_xp DESC,
+                COALESCE(xp_reached_at, last_activity_date) ASC,
+                activity_count DESC,
+                wallet_address ASC
             LIMIT ?
             "#,
         )
@@ -931,28 +1387,329 @@ impl ProgressTracker {
         for (rank, row) in rows.iter().enumerate() {
             let wallet_address: String = row.try_get("wallet_address")?;
             let total_xp: i64 = row.try_get("total_xp")?;
-            let streak_days: i64 = row.try_get("current_streak_days")?;
-            let badges_json: String = row.try_get("badges_earned")?;
-            let badges: Vec<String> = serde_json::from_str(&badges_json)?;
+            let reached_str: Option<String> = row.try_get("xp_reached_at")?;
+            let activity_count: i64 = row.try_get("activity_count")?;
+            let reached_at = reached_str.map(|s| Self::parse_time(&s)).transpose()?;
+
+            let mut entry = self
+                .leaderboard_entry(wallet_address, rank as i64 + 1)
+                .await?;
+            entry.total_xp = total_xp;
+            entry.points = total_xp;
+            entry.reached_at = reached_at;
+            entry.activity_count = activity_count;
+            leaderboard.push(entry);
+        }
+
+        Ok(leaderboard)
+    }
+
+    /// Leaderboard for one season: live points while it runs, the frozen
+    /// standings once it has ended.
+    pub async fn get_season_leaderboard(
+        &self,
+        season: &Season,
+        limit: i64,
+    ) -> Result<Vec<LeaderboardEntry>, ProgressError> {
+        let standings = match season.status {
+            SeasonStatus::Scheduled | SeasonStatus::Active => {
+                let tallies = self
+                    .season_tallies(season.starts_at, season.ends_at)
+                    .await?;
+                rank_standings(&season.id, tallies)
+            }
+            SeasonStatus::Finalizing | SeasonStatus::Archived => {
+                self.get_season_standings(&season.id).await?
+            }
+        };
+
+        let mut leaderboard = Vec::new();
+        for standing in standings.into_iter().take(limit.max(0) as usize) {
+            let mut entry = self
+                .leaderboard_entry(standing.wallet_address, standing.rank)
+                .await?;
+            entry.points = standing.points;
+            entry.season_id = Some(standing.season_id);
+            entry.reached_at = Some(standing.reached_at);
+            entry.activity_count = standing.activity_count;
+            leaderboard.push(entry);
+        }
+
+        Ok(leaderboard)
+    }
 
-            let courses_completed: i64 = sqlx::query_scalar(
-                "SELECT COUNT(*) FROM user_progress WHERE wallet_address = ? AND status = 'completed'"
+    async fn leaderboard_entry(
+        &self,
+        wallet_address: String,
+        rank: i64,
+    ) -> Result<LeaderboardEntry, ProgressError> {
+        let row = sqlx::query(
+            "SELECT total_xp, current_streak_days, badges_earned FROM user_stats WHERE wallet_address = ?",
+        )
+        .bind(&wallet_address)
+        .fetch_optional(&self.pool)
+        .await?;
+        let (total_xp, streak_days, badges_count) = match row {
+            Some(row) => {
+                let badges_json: String = row.try_get("badges_earned")?;
+                let badges: Vec<String> = serde_json::from_str(&badges_json)?;
+                (
+                    row.try_get("total_xp")?,
+                    row.try_get("current_streak_days")?,
+                    badges.len() as i64,
+                )
+            }
+            None => (0, 0, 0),
+        };
+
+        let courses_completed: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM user_progress WHERE wallet_address = ? AND status = 'completed'",
+        )
+        .bind(&wallet_address)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(LeaderboardEntry {
+            wallet_address,
+            rank,
+            total_xp,
+            courses_completed,
+            badges_count,
+            streak_days,
+            points: 0,
+            season_id: None,
+            reached_at: None,
+            activity_count: 0,
+        })
+    }
+
+    // Season operations
+    pub async fn create_season(&self, season: &Season) -> Result<(), ProgressError> {
+        sqlx::query(
+            r#"
+            INSERT INTO academy_seasons (id, name, starts_at, ends_at, status, reward_tiers, finalized_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&season.id)
+        .bind(&season.name)
+        .bind(season.starts_at.to_rfc3339())
+        .bind(season.ends_at.to_rfc3339())
+        .bind(season.status.as_str())
+        .bind(serde_json::to_string(&season.reward_tiers)?)
+        .bind(season.finalized_at.map(|d| d.to_rfc3339()))
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_season(&self, id: &str) -> Result<Season, ProgressError> {
+        let row = sqlx::query("SELECT * FROM academy_seasons WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| ProgressError::NotFound(format!("Season {} not found", id)))?;
+
+        Self::season_from_row(&row)
+    }
+
+    pub async fn list_seasons(&self) -> Result<Vec<Season>, ProgressError> {
+        let rows = sqlx::query("SELECT * FROM academy_seasons ORDER BY starts_at ASC")
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.iter().map(Self::season_from_row).collect()
+    }
+
+    pub async fn get_season_with_status(
+        &self,
+        status: SeasonStatus,
+    ) -> Result<Option<Season>, ProgressError> {
+        let row = sqlx::query(
+            "SELECT * FROM academy_seasons WHERE status = ? ORDER BY starts_at ASC LIMIT 1",
+        )
+        .bind(status.as_str())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.as_ref().map(Self::season_from_row).transpose()
+    }
+
+    pub async fn activate_season(&self, id: &str) -> Result<bool, ProgressError> {
+        let result = sqlx::query(
+            "UPDATE academy_seasons SET status = 'active' WHERE id = ? AND status = 'scheduled'",
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Ends an active season: stores its final standings and schedules the
+    /// next season in one transaction. Returns false if the season was
+    /// already frozen.
+    pub async fn freeze_season(
+        &self,
+        season_id: &str,
+        standings: &[SeasonStanding],
+        next: Option<&Season>,
+        now: DateTime<Utc>,
+    ) -> Result<bool, ProgressError> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query(
+            r#"
+            UPDATE academy_seasons SET status = 'finalizing', finalized_at = ?
+            WHERE id = ? AND status = 'active'
+            "#,
+        )
+        .bind(now.to_rfc3339())
+        .bind(season_id)
+        .execute(&mut tx)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Ok(false);
+        }
+
+        for standing in standings {
+            sqlx::query(
+                r#"
+                INSERT OR IGNORE INTO season_standings
+                    (season_id, wallet_address, rank, points, reached_at, activity_count, reward_issued)
+                VALUES (?, ?, ?, ?, ?, ?, 0)
+                "#,
             )
-            .bind(&wallet_address)
-            .fetch_one(&self.pool)
+            .bind(&standing.season_id)
+            .bind(&standing.wallet_address)
+            .bind(standing.rank)
+            .bind(standing.points)
+            .bind(standing.reached_at.to_rfc3339())
+            .bind(standing.activity_count)
+            .execute(&mut tx)
             .await?;
+        }
 
-            leaderboard.push(LeaderboardEntry {
-                wallet_address,
-                rank: rank as i64 + 1,
-                total_xp,
-                courses_completed,
-                badges_count: badges.len() as i64,
-                streak_days,
-            });
+        if let Some(next) = next {
+            sqlx::query(
+                r#"
+                INSERT INTO academy_seasons (id, name, starts_at, ends_at, status, reward_tiers, finalized_at)
+                VALUES (?, ?, ?, ?, ?, ?, NULL)
+                "#,
+            )
+            .bind(&next.id)
+            .bind(&next.name)
+            .bind(next.starts_at.to_rfc3339())
+            .bind(next.ends_at.to_rfc3339())
+            .bind(next.status.as_str())
+            .bind(serde_json::to_string(&next.reward_tiers)?)
+            .execute(&mut tx)
+            .await?;
         }
 
-        Ok(leaderboard)
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    pub async fn archive_season(&self, id: &str) -> Result<(), ProgressError> {
+        sqlx::query(
+            "UPDATE academy_seasons SET status = 'archived' WHERE id = ? AND status = 'finalizing'",
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_season_standings(
+        &self,
+        season_id: &str,
+    ) -> Result<Vec<SeasonStanding>, ProgressError> {
+        let rows =
+            sqlx::query("SELECT * FROM season_standings WHERE season_id = ? ORDER BY rank ASC")
+                .bind(season_id)
+                .fetch_all(&self.pool)
+                .await?;
+
+        rows.iter().map(Self::season_standing_from_row).collect()
+    }
+
+    pub async fn mark_season_reward_issued(
+        &self,
+        season_id: &str,
+        wallet_address: &str,
+    ) -> Result<(), ProgressError> {
+        sqlx::query(
+            "UPDATE season_standings SET reward_issued = 1 WHERE season_id = ? AND wallet_address = ?",
+        )
+        .bind(season_id)
+        .bind(wallet_address)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// XP earned per wallet in `[starts_at, ends_at)`.
+    pub async fn season_tallies(
+        &self,
+        starts_at: DateTime<Utc>,
+        ends_at: DateTime<Utc>,
+    ) -> Result<Vec<SeasonTally>, ProgressError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT wallet_address,
+                SUM(amount) AS points,
+                MAX(earned_at) AS reached_at,
+                COUNT(*) AS activity_count
+            FROM xp_events
+            WHERE earned_at >= ? AND earned_at < ?
+            GROUP BY wallet_address
+            HAVING SUM(amount) > 0
+            "#,
+        )
+        .bind(starts_at.to_rfc3339())
+        .bind(ends_at.to_rfc3339())
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                let reached_str: String = row.try_get("reached_at")?;
+                Ok(SeasonTally {
+                    wallet_address: row.try_get("wallet_address")?,
+                    points: row.try_get("points")?,
+                    reached_at: Self::parse_time(&reached_str)?,
+                    activity_count: row.try_get("activity_count")?,
+                })
+            })
+            .collect()
+    }
+
+    async fn current_season_stats(
+        &self,
+        wallet_address: &str,
+    ) -> Result<Option<SeasonStats>, ProgressError> {
+        let Some(season) = self.get_season_with_status(SeasonStatus::Active).await? else {
+            return Ok(None);
+        };
+        let tallies = self
+            .season_tallies(season.starts_at, season.ends_at)
+            .await?;
+        let standing = rank_standings(&season.id, tallies)
+            .into_iter()
+            .find(|s| s.wallet_address == wallet_address);
+
+        Ok(Some(SeasonStats {
+            season_id: season.id,
+            season_name: season.name,
+            ends_at: season.ends_at,
+            points: standing.as_ref().map_or(0, |s| s.points),
+            rank: standing.as_ref().map(|s| s.rank),
+            activity_count: standing.as_ref().map_or(0, |s| s.activity_count),
+        }))
     }
 
     // Helper methods
@@ -1035,6 +1792,12 @@ impl ProgressTracker {
 
     fn quiz_attempt_from_row(row: &sqlx::sqlite::SqliteRow) -> Result<QuizAttempt, ProgressError> {
         let attempted_str: String = row.try_get("attempted_at")?;
+        let started_str: Option<String> = row.try_get("started_at")?;
+        let question_ids_json: String = row.try_get("question_ids")?;
+        let question_results_json: String = row.try_get("question_results")?;
+        let attempted_at = DateTime::parse_from_rfc3339(&attempted_str)
+            .map_err(|e| ProgressError::InvalidData(e.to_string()))?
+            .with_timezone(&Utc);
 
         Ok(QuizAttempt {
             id: row.try_get("id")?,
@@ -1045,12 +1808,98 @@ impl ProgressTracker {
             passed: row.try_get::<i64, _>("passed")? != 0,
             answers: row.try_get("answers")?,
             time_taken_minutes: row.try_get("time_taken_minutes")?,
-            attempted_at: DateTime::parse_from_rfc3339(&attempted_str)
+            attempted_at,
+            status: QuizAttemptStatus::from_db(&row.try_get::<String, _>("status")?),
+            question_ids: serde_json::from_str(&question_ids_json)?,
+            question_results: serde_json::from_str(&question_results_json)?,
+            started_at: started_str
+                .and_then(|s| {
+                    DateTime::parse_from_rfc3339(&s)
+                        .ok()
+                        .map(|d| d.with_timezone(&Utc))
+                })
+                .unwrap_or(attempted_at),
+        })
+    }
+
+    fn challenge_run_from_row(
+        row: &sqlx::sqlite::SqliteRow,
+    ) -> Result<ChallengeRun, ProgressError> {
+        let criteria_json: String = row.try_get("criteria")?;
+        let snapshot_json: String = row.try_get("snapshot")?;
+        let started_str: String = row.try_get("started_at")?;
+        let deadline_str: String = row.try_get("deadline")?;
+        let checked_str: String = row.try_get("last_checked_at")?;
+        let finished_str: Option<String> = row.try_get("finished_at")?;
+
+        Ok(ChallengeRun {
+            id: row.try_get("id")?,
+            wallet_address: row.try_get("wallet_address")?,
+            challenge_id: row.try_get("challenge_id")?,
+            status: ChallengeRunStatus::from_db(&row.try_get::<String, _>("status")?),
+            criteria: serde_json::from_str(&criteria_json)?,
+            snapshot: serde_json::from_str(&snapshot_json)?,
+            latest_equity: row.try_get("latest_equity")?,
+            peak_equity: row.try_get("peak_equity")?,
+            max_drawdown_percent: row.try_get("max_drawdown_percent")?,
+            started_at: DateTime::parse_from_rfc3339(&started_str)
+                .map_err(|e| ProgressError::InvalidData(e.to_string()))?
+                .with_timezone(&Utc),
+            deadline: DateTime::parse_from_rfc3339(&deadline_str)
                 .map_err(|e| ProgressError::InvalidData(e.to_string()))?
                 .with_timezone(&Utc),
+            last_checked_at: DateTime::parse_from_rfc3339(&checked_str)
+                .map_err(|e| ProgressError::InvalidData(e.to_string()))?
+                .with_timezone(&Utc),
+            finished_at: finished_str.and_then(|s| {
+                DateTime::parse_from_rfc3339(&s)
+                    .ok()
+                    .map(|d| d.with_timezone(&Utc))
+            }),
+            outcome_reason: row.try_get("outcome_reason")?,
+        })
+    }
+
+    fn season_from_row(row: &sqlx::sqlite::SqliteRow) -> Result<Season, ProgressError> {
+        let starts_str: String = row.try_get("starts_at")?;
+        let ends_str: String = row.try_get("ends_at")?;
+        let tiers_json: String = row.try_get("reward_tiers")?;
+        let finalized_str: Option<String> = row.try_get("finalized_at")?;
+
+        Ok(Season {
+            id: row.try_get("id")?,
+            name: row.try_get("name")?,
+            starts_at: Self::parse_time(&starts_str)?,
+            ends_at: Self::parse_time(&ends_str)?,
+            status: SeasonStatus::from_db(&row.try_get::<String, _>("status")?),
+            reward_tiers: serde_json::from_str(&tiers_json)?,
+            finalized_at: finalized_str.map(|s| Self::parse_time(&s)).transpose()?,
         })
     }
 
+    fn season_standing_from_row(
+        row: &sqlx::sqlite::SqliteRow,
+    ) -> Result<SeasonStanding, ProgressError> {
+        let reached_str: String = row.try_get("reached_at")?;
+        let reward_issued: i64 = row.try_get("reward_issued")?;
+
+        Ok(SeasonStanding {
+            season_id: row.try_get("season_id")?,
+            wallet_address: row.try_get("wallet_address")?,
+            rank: row.try_get("rank")?,
+            points: row.try_get("points")?,
+            reached_at: Self::parse_time(&reached_str)?,
+            activity_count: row.try_get("activity_count")?,
+            reward_issued: reward_issued != 0,
+        })
+    }
+
+    fn parse_time(value: &str) -> Result<DateTime<Utc>, ProgressError> {
+        DateTime::parse_from_rfc3339(value)
+            .map(|d| d.with_timezone(&Utc))
+            .map_err(|e| ProgressError::InvalidData(e.to_string()))
+    }
+
     fn challenge_submission_from_row(
         row: &sqlx::sqlite::SqliteRow,
     ) -> Result<ChallengeSubmission, ProgressError> {
@@ -0,0 +1,503 @@
+//! Question drawing, server-side grading, retake rules and per-question
+//! analytics for academy quizzes.
+//!
+//! A quiz either lists its questions directly or draws them from a question
+//! bank. Drawn question ids are stored on the attempt, so a resumed attempt
+//! shows the same questions and grading never trusts the client's view.
+
+use super::content::{Quiz, QuizQuestion, ScorePolicy};
+use super::progress::{QuestionResult, QuizAttempt, QuizAttemptStatus};
+use chrono::{DateTime, Duration, Utc};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Answers a question needs before its discrimination is reported.
+const MIN_DISCRIMINATION_SAMPLES: usize = 5;
+
+/// A question as shown to the learner, without the answer key.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PresentedQuestion {
+    pub id: String,
+    pub question: String,
+    pub options: Vec<String>,
+    pub points: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuizSession {
+    pub attempt_id: String,
+    pub quiz_id: String,
+    pub title: String,
+    pub questions: Vec<PresentedQuestion>,
+    pub passing_score: i64,
+    pub time_limit_minutes: Option<i64>,
+    pub started_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuizGrade {
+    pub score: i64,
+    pub total_points: i64,
+    pub passed: bool,
+    pub results: Vec<QuestionResult>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuizStanding {
+    pub quiz_id: String,
+    pub wallet_address: String,
+    pub score_policy: ScorePolicy,
+    /// The attempt that counts as the learner's result under the policy.
+    pub counted_attempt: Option<QuizAttempt>,
+    pub attempts_used: i64,
+    pub attempts_remaining: Option<i64>,
+    pub next_attempt_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuestionAnalytics {
+    pub question_id: String,
+    pub question: String,
+    pub times_drawn: i64,
+    pub times_correct: i64,
+    /// Difficulty: the share of learners who answered correctly, 0-100.
+    pub percent_correct: Option<f64>,
+    /// Correlation between answering this question correctly and scoring
+    /// well on the rest of the attempt. Near zero or negative values mark
+    /// questions worth rewording or removing.
+    pub discrimination: Option<f64>,
+}
+
+pub fn validate_questions(questions: &[QuizQuestion]) -> Result<(), String> {
+    let mut ids = HashSet::new();
+    for question in questions {
+        if !ids.insert(question.id.as_str()) {
+            return Err(format!("Duplicate question id: {}", question.id));
+        }
+        if question.options.len() < 2 {
+            return Err(format!(
+                "Question {} needs at least two options",
+                question.id
+            ));
+        }
+        if question.correct_answer >= question.options.len() {
+            return Err(format!(
+                "Question {} has no option {}",
+                question.id, question.correct_answer
+            ));
+        }
+        if question.points <= 0 {
+            return Err(format!("Question {} must be worth points", question.id));
+        }
+    }
+    Ok(())
+}
+
+/// Checks that a bank-backed quiz can draw its questions from a bank of
+/// `bank_size` questions.
+pub fn validate_draw(quiz: &Quiz, bank_size: usize) -> Result<(), String> {
+    match quiz.draw_count {
+        Some(count) if count <= 0 => Err("Draw count must be positive".to_string()),
+        Some(count) if count as usize > bank_size => Err(format!(
+            "Quiz {} draws {} questions but the bank only has {}",
+            quiz.id, count, bank_size
+        )),
+        None if bank_size == 0 => Err("The question bank is empty".to_string()),
+        _ => Ok(()),
+    }
+}
+
+/// Picks the questions for a new attempt. Fixed quizzes keep their authored
+/// order; bank-backed quizzes get a random subset in random order.
+pub fn draw_questions<R: Rng + ?Sized>(
+    quiz: &Quiz,
+    pool: &[QuizQuestion],
+    rng: &mut R,
+) -> Vec<String> {
+    let mut ids: Vec<String> = pool.iter().map(|q| q.id.clone()).collect();
+    if quiz.question_bank_id.is_none() {
+        return ids;
+    }
+
+    ids.shuffle(rng);
+    if let Some(count) = quiz.draw_count {
+        ids.truncate(count.max(0) as usize);
+    }
+    ids
+}
+
+pub fn quiz_session(quiz: &Quiz, attempt: &QuizAttempt, pool: &[QuizQuestion]) -> QuizSession {
+    let by_id: HashMap<&str, &QuizQuestion> = pool.iter().map(|q| (q.id.as_str(), q)).collect();
+    let questions = attempt
+        .question_ids
+        .iter()
+        .filter_map(|id| by_id.get(id.as_str()))
+        .map(|q| PresentedQuestion {
+            id: q.id.clone(),
+            question: q.question.clone(),
+            options: q.options.clone(),
+            points: q.points,
+        })
+        .collect();
+
+    QuizSession {
+        attempt_id: attempt.id.clone(),
+        quiz_id: quiz.id.clone(),
+        title: quiz.title.clone(),
+        questions,
+        passing_score: quiz.passing_score,
+        time_limit_minutes: quiz.time_limit_minutes,
+        started_at: attempt.started_at,
+    }
+}
+
+/// Grades the drawn questions against the answer key. Questions removed from
+/// the bank since the attempt started are left out of the total.
+pub fn grade_attempt(
+    quiz: &Quiz,
+    pool: &[QuizQuestion],
+    question_ids: &[String],
+    answers: &HashMap<String, usize>,
+) -> QuizGrade {
+    let by_id: HashMap<&str, &QuizQuestion> = pool.iter().map(|q| (q.id.as_str(), q)).collect();
+    let mut score = 0;
+    let mut total_points = 0;
+    let mut results = Vec::new();
+
+    for id in question_ids {
+        let Some(question) = by_id.get(id.as_str()) else {
+            continue;
+        };
+        let selected_answer = answers.get(id).copied();
+        let correct = selected_answer == Some(question.correct_answer);
+        let points_awarded = if correct { question.points } else { 0 };
+
+        score += points_awarded;
+        total_points += question.points;
+        results.push(QuestionResult {
+            question_id: id.clone(),
+            selected_answer,
+            correct,
+            points: question.points,
+            points_awarded,
+        });
+    }
+
+    QuizGrade {
+        score,
+        total_points,
+        passed: total_points > 0 && score * 100 >= quiz.passing_score * total_points,
+        results,
+    }
+}
+
+fn submitted(attempts: &[QuizAttempt]) -> Vec<&QuizAttempt> {
+    attempts
+        .iter()
+        .filter(|a| a.status == QuizAttemptStatus::Submitted)
+        .collect()
+}
+
+fn next_attempt_at(quiz: &Quiz, submitted: &[&QuizAttempt]) -> Option<DateTime<Utc>> {
+    let cooldown = quiz.retake_cooldown_minutes.filter(|mins| *mins > 0)?;
+    let last = submitted.iter().map(|a| a.attempted_at).max()?;
+    Some(last + Duration::minutes(cooldown))
+}
+
+/// Enforces the quiz's retake policy for an attempt started at `started_at`.
+pub fn check_retake(
+    quiz: &Quiz,
+    previous: &[QuizAttempt],
+    started_at: DateTime<Utc>,
+) -> Result<(), String> {
+    let submitted = submitted(previous);
+
+    if let Some(max) = quiz.max_attempts {
+        if submitted.len() as i64 >= max {
+            return Err(format!("All {} attempts at this quiz have been used", max));
+        }
+    }
+    if let Some(next) = next_attempt_at(quiz, &submitted) {
+        if started_at < next {
+            return Err(format!(
+                "This quiz can be retaken from {}",
+                next.to_rfc3339()
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn score_ratio(attempt: &QuizAttempt) -> f64 {
+    if attempt.total_points > 0 {
+        attempt.score as f64 / attempt.total_points as f64
+    } else {
+        0.0
+    }
+}
+
+/// The attempt that counts as the learner's result under `policy`.
+pub fn counted_attempt(policy: ScorePolicy, attempts: &[QuizAttempt]) -> Option<&QuizAttempt> {
+    let submitted = submitted(attempts);
+    match policy {
+        ScorePolicy::LastScore => submitted.into_iter().max_by_key(|a| a.attempted_at),
+        ScorePolicy::BestScore => submitted.into_iter().reduce(|best, a| {
+            if score_ratio(a) > score_ratio(best) {
+                a
+            } else {
+                best
+            }
+        }),
+    }
+}
+
+pub fn quiz_standing(
+    quiz: &Quiz,
+    wallet_address: &str,
+    attempts: &[QuizAttempt],
+    now: DateTime<Utc>,
+) -> QuizStanding {
+    let used = submitted(attempts);
+    let attempts_used = used.len() as i64;
+    let attempts_remaining = quiz.max_attempts.map(|max| (max - attempts_used).max(0));
+    let next_attempt_at = if attempts_remaining == Some(0) {
+        None
+    } else {
+        next_attempt_at(quiz, &used).filter(|next| *next > now)
+    };
+
+    QuizStanding {
+        quiz_id: quiz.id.clone(),
+        wallet_address: wallet_address.to_string(),
+        score_policy: quiz.score_policy,
+        counted_attempt: counted_attempt(quiz.score_policy, attempts).cloned(),
+        attempts_used,
+        attempts_remaining,
+        next_attempt_at,
+    }
+}
+
+fn correlation(samples: &[(f64, f64)]) -> Option<f64> {
+    let n = samples.len() as f64;
+    let mean_x = samples.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = samples.iter().map(|(_, y)| y).sum::<f64>() / n;
+
+    let (mut cov, mut var_x, mut var_y) = (0.0, 0.0, 0.0);
+    for (x, y) in samples {
+        cov += (x - mean_x) * (y - mean_y);
+        var_x += (x - mean_x).powi(2);
+        var_y += (y - mean_y).powi(2);
+    }
+
+    if var_x <= f64::EPSILON || var_y <= f64::EPSILON {
+        return None;
+    }
+    Some(cov / (var_x * var_y).sqrt())
+}
+
+/// Difficulty and discrimination for every question in the pool, computed
+/// from submitted attempts. Discrimination uses the attempt's score on the
+/// other drawn questions so a question is not correlated with itself.
+pub fn question_analytics(
+    pool: &[QuizQuestion],
+    attempts: &[QuizAttempt],
+) -> Vec<QuestionAnalytics> {
+    let mut samples: HashMap<&str, Vec<(f64, f64)>> = HashMap::new();
+    let mut counts: HashMap<&str, (i64, i64)> = HashMap::new();
+
+    for attempt in submitted(attempts) {
+        for result in &attempt.question_results {
+            let entry = counts.entry(result.question_id.as_str()).or_default();
+            entry.0 += 1;
+            if result.correct {
+                entry.1 += 1;
+            }
+
+            let rest_total = attempt.total_points - result.points;
+            if rest_total > 0 {
+                let rest_score = (attempt.score - result.points_awarded) as f64 / rest_total as f64;
+                let correct = if result.correct { 1.0 } else { 0.0 };
+                samples
+                    .entry(result.question_id.as_str())
+                    .or_default()
+                    .push((correct, rest_score));
+            }
+        }
+    }
+
+    pool.iter()
+        .map(|question| {
+            let (times_drawn, times_correct) = counts
+                .get(question.id.as_str())
+                .copied()
+                .unwrap_or_default();
+            let discrimination = samples
+                .get(question.id.as_str())
+                .filter(|s| s.len() >= MIN_DISCRIMINATION_SAMPLES)
+                .and_then(|s| correlation(s));
+
+            QuestionAnalytics {
+                question_id: question.id.clone(),
+                question: question.question.clone(),
+                times_drawn,
+                times_correct,
+                percent_correct: (times_drawn > 0)
+                    .then(|| times_correct as f64 / times_drawn as f64 * 100.0),
+                discrimination,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn question(id: &str) -> QuizQuestion {
+        QuizQuestion {
+            id: id.to_string(),
+            question: format!("Question {}", id),
+            options: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+            correct_answer: 1,
+            explanation: String::new(),
+            points: 10,
+        }
+    }
+
+    fn bank_quiz(draw_count: i64) -> Quiz {
+        Quiz {
+            id: "quiz_1".to_string(),
+            lesson_id: "lesson_1".to_string(),
+            title: "Risk basics".to_string(),
+            questions: vec![],
+            passing_score: 70,
+            max_attempts: Some(3),
+            time_limit_minutes: None,
+            question_bank_id: Some("bank_1".to_string()),
+            draw_count: Some(draw_count),
+            retake_cooldown_minutes: Some(60),
+            score_policy: ScorePolicy::BestScore,
+        }
+    }
+
+    fn attempt(
+        quiz: &Quiz,
+        pool: &[QuizQuestion],
+        answers: &[(&str, usize)],
+        at: DateTime<Utc>,
+    ) -> QuizAttempt {
+        let question_ids: Vec<String> = answers.iter().map(|(id, _)| id.to_string()).collect();
+        let answers: HashMap<String, usize> =
+            answers.iter().map(|(id, a)| (id.to_string(), *a)).collect();
+        let grade = grade_attempt(quiz, pool, &question_ids, &answers);
+        QuizAttempt {
+            id: format!("attempt_{}", at.timestamp_millis()),
+            wallet_address: "learner".to_string(),
+            quiz_id: quiz.id.clone(),
+            score: grade.score,
+            total_points: grade.total_points,
+            passed: grade.passed,
+            answers: String::new(),
+            time_taken_minutes: 5,
+            attempted_at: at,
+            status: QuizAttemptStatus::Submitted,
+            question_ids,
+            question_results: grade.results,
+            started_at: at,
+        }
+    }
+
+    #[test]
+    fn test_draws_subset_and_grades_server_side() {
+        let pool: Vec<QuizQuestion> = (0..10).map(|i| question(&format!("q{}", i))).collect();
+        let quiz = bank_quiz(4);
+        let drawn = draw_questions(&quiz, &pool, &mut StdRng::seed_from_u64(7));
+        assert_eq!(drawn.len(), 4);
+        assert_eq!(drawn.iter().collect::<HashSet<_>>().len(), 4);
+
+        let answers: HashMap<String, usize> = drawn
+            .iter()
+            .enumerate()
+            .map(|(i, id)| (id.clone(), if i < 3 { 1 } else { 0 }))
+            .collect();
+        let grade = grade_attempt(&quiz, &pool, &drawn, &answers);
+        assert_eq!(grade.score, 30);
+        assert_eq!(grade.total_points, 40);
+        assert!(grade.passed);
+        assert_eq!(grade.results.iter().filter(|r| r.correct).count(), 3);
+        assert!(!grade.results[3].correct);
+    }
+
+    #[test]
+    fn test_retake_policy_and_score_policy() {
+        let pool = vec![question("q0"), question("q1")];
+        let mut quiz = bank_quiz(2);
+        let start = Utc::now();
+        let first = attempt(&quiz, &pool, &[("q0", 1), ("q1", 1)], start);
+        let second = attempt(
+            &quiz,
+            &pool,
+            &[("q0", 1), ("q1", 0)],
+            start + Duration::hours(2),
+        );
+
+        assert!(check_retake(&quiz, &[first.clone()], start + Duration::minutes(30)).is_err());
+        assert!(check_retake(&quiz, &[first.clone()], start + Duration::minutes(61)).is_ok());
+
+        let history = vec![first.clone(), second.clone()];
+        assert_eq!(
+            counted_attempt(ScorePolicy::BestScore, &history).map(|a| &a.id),
+            Some(&first.id)
+        );
+        assert_eq!(
+            counted_attempt(ScorePolicy::LastScore, &history).map(|a| &a.id),
+            Some(&second.id)
+        );
+
+        quiz.max_attempts = Some(2);
+        assert!(check_retake(&quiz, &history, start + Duration::days(1)).is_err());
+        let standing = quiz_standing(&quiz, "learner", &history, start + Duration::days(1));
+        assert_eq!(standing.attempts_remaining, Some(0));
+        assert_eq!(standing.next_attempt_at, None);
+    }
+
+    #[test]
+    fn test_analytics_flags_non_discriminating_questions() {
+        let pool: Vec<QuizQuestion> = (0..5).map(|i| question(&format!("q{}", i))).collect();
+        let quiz = bank_quiz(5);
+        let start = Utc::now();
+        // q0 tracks overall ability, q1 everyone gets right, q2 is answered
+        // correctly only by the weakest learners.
+        let attempts: Vec<QuizAttempt> = (0..6)
+            .map(|i| {
+                let strong = i < 3;
+                attempt(
+                    &quiz,
+                    &pool,
+                    &[
+                        ("q0", if strong { 1 } else { 0 }),
+                        ("q1", 1),
+                        ("q2", if strong { 0 } else { 1 }),
+                        ("q3", if strong { 1 } else { 0 }),
+                        ("q4", if strong { 1 } else { 0 }),
+                    ],
+                    start + Duration::minutes(i),
+                )
+            })
+            .collect();
+
+        let analytics = question_analytics(&pool, &attempts);
+        assert_eq!(analytics[1].percent_correct, Some(100.0));
+        assert_eq!(analytics[1].discrimination, None);
+        assert!(analytics[0].discrimination.unwrap() > 0.0);
+        assert!(analytics[2].discrimination.unwrap() < 0.0);
+    }
+}
@@ -1,7 +1,9 @@
+use crate::config::DataPaths;
+use crate::utils::ensure_column;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::{Pool, Row, Sqlite, SqlitePool};
-use tauri::{AppHandle, Manager};
+use sqlx::{Pool, Row, Sqlite, SqliteConnection, SqlitePool};
+use tauri::AppHandle;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
@@ -51,6 +53,14 @@ pub struct Certificate {
     pub issued_at: DateTime<Utc>,
     pub certificate_url: Option<String>,
     pub verification_code: String,
+    #[serde(default)]
+    pub score: Option<f64>,
+    /// Signed proof that can be checked without this app's database.
+    #[serde(default)]
+    pub proof: Option<String>,
+    /// Signature of the Solana memo transaction anchoring the proof hash.
+    #[serde(default)]
+    pub anchor_signature: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -110,7 +120,7 @@ pub struct RewardEngine {
 
 impl RewardEngine {
     pub async fn new(app_handle: &AppHandle) -> Result<Self, RewardError> {
-        let app_dir = app_handle.path().app_data_dir().map_err(|err| {
+        let app_dir = app_handle.app_data_root().map_err(|err| {
             RewardError::Io(std::io::Error::new(
                 std::io::ErrorKind::NotFound,
                 format!("Unable to resolve app data directory: {err}"),
@@ -176,6 +186,14 @@ impl RewardEngine {
         .execute(pool)
         .await?;
 
+        for (column, definition) in [
+            ("score", "REAL"),
+            ("proof", "TEXT"),
+            ("anchor_signature", "TEXT"),
+        ] {
+            ensure_column(pool, "certificates", column, definition).await?;
+        }
+
         // Rewards table
         sqlx::query(
             r#"
@@ -261,6 +279,47 @@ impl RewardEngine {
         Ok(badge)
     }
 
+    /// Inserts or updates a badge on `conn`, which may be a transaction
+    /// opened on another academy pool since they share one database file.
+    pub(crate) async fn upsert_badge_with(
+        conn: &mut SqliteConnection,
+        badge: &Badge,
+    ) -> Result<(), RewardError> {
+        let rarity_str = format!("{:?}", badge.rarity).to_lowercase();
+
+        sqlx::query(
+            r#"
+            INSERT INTO badges (
+                id, name, description, rarity, icon_url, xp_reward,
+                reputation_boost, requirements, is_active, created_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(id) DO UPDATE SET
+                name = excluded.name,
+                description = excluded.description,
+                rarity = excluded.rarity,
+                icon_url = excluded.icon_url,
+                xp_reward = excluded.xp_reward,
+                reputation_boost = excluded.reputation_boost,
+                requirements = excluded.requirements,
+                is_active = excluded.is_active
+            "#,
+        )
+        .bind(&badge.id)
+        .bind(&badge.name)
+        .bind(&badge.description)
+        .bind(rarity_str)
+        .bind(&badge.icon_url)
+        .bind(badge.xp_reward)
+        .bind(badge.reputation_boost)
+        .bind(&badge.requirements)
+        .bind(badge.is_active)
+        .bind(badge.created_at.to_rfc3339())
+        .execute(conn)
+        .await?;
+
+        Ok(())
+    }
+
     pub async fn get_badge(&self, id: &str) -> Result<Badge, RewardError> {
         let row = sqlx::query("SELECT * FROM badges WHERE id = ?")
             .bind(id)
@@ -405,8 +464,9 @@ impl RewardEngine {
             r#"
             INSERT INTO certificates (
                 id, wallet_address, course_id, challenge_id, title,
-                description, issued_at, certificate_url, verification_code
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+                description, issued_at, certificate_url, verification_code,
+                score, proof, anchor_signature
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(&certificate.id)
@@ -418,12 +478,49 @@ impl RewardEngine {
         .bind(certificate.issued_at.to_rfc3339())
         .bind(&certificate.certificate_url)
         .bind(&certificate.verification_code)
+        .bind(certificate.score)
+        .bind(&certificate.proof)
+        .bind(&certificate.anchor_signature)
         .execute(&self.pool)
         .await?;
 
         Ok(certificate)
     }
 
+    pub async fn get_certificate(&self, id: &str) -> Result<Certificate, RewardError> {
+        let row = sqlx::query("SELECT * FROM certificates WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| RewardError::NotFound(format!("Certificate not found: {}", id)))?;
+
+        Self::certificate_from_row(&row)
+    }
+
+    pub async fn set_certificate_proof(&self, id: &str, proof: &str) -> Result<(), RewardError> {
+        sqlx::query("UPDATE certificates SET proof = ? WHERE id = ?")
+            .bind(proof)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn set_certificate_anchor(
+        &self,
+        id: &str,
+        signature: &str,
+    ) -> Result<(), RewardError> {
+        sqlx::query("UPDATE certificates SET anchor_signature = ? WHERE id = ?")
+            .bind(signature)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
     pub async fn get_user_certificates(
         &self,
         wallet_address: &str,
@@ -731,6 +828,9 @@ impl RewardEngine {
                 .with_timezone(&Utc),
             certificate_url: row.try_get("certificate_url")?,
             verification_code: row.try_get("verification_code")?,
+            score: row.try_get("score")?,
+            proof: row.try_get("proof")?,
+            anchor_signature: row.try_get("anchor_signature")?,
         })
     }
 
@@ -0,0 +1,445 @@
+//! Leaderboard seasons.
+//!
+//! Points for a season are the XP earned inside its window. When a season
+//! ends, its standings are frozen, the next season is scheduled with the same
+//! length, and the top ranks receive season badges. Rollover is idempotent:
+//! freezing happens once in a transaction, and each standing is marked once
+//! its reward has been issued, so a run interrupted by a crash resumes where
+//! it stopped without awarding anyone twice.
+
+use super::progress::ProgressTracker;
+use super::rewards::{Badge, BadgeRarity, RewardEngine, RewardError};
+use super::SharedAcademyEngine;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use tokio::time::{interval, Duration};
+use uuid::Uuid;
+
+const SEASON_CHECK_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SeasonStatus {
+    #[default]
+    Scheduled,
+    Active,
+    /// Standings are frozen and rewards are being distributed.
+    Finalizing,
+    Archived,
+}
+
+impl SeasonStatus {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            SeasonStatus::Scheduled => "scheduled",
+            SeasonStatus::Active => "active",
+            SeasonStatus::Finalizing => "finalizing",
+            SeasonStatus::Archived => "archived",
+        }
+    }
+
+    pub(crate) fn from_db(value: &str) -> Self {
+        match value {
+            "active" => SeasonStatus::Active,
+            "finalizing" => SeasonStatus::Finalizing,
+            "archived" => SeasonStatus::Archived,
+            _ => SeasonStatus::Scheduled,
+        }
+    }
+}
+
+/// A reward band: every rank up to and including `max_rank` that isn't
+/// covered by a narrower tier.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SeasonRewardTier {
+    pub name: String,
+    pub max_rank: i64,
+    pub xp_reward: i64,
+    pub rarity: BadgeRarity,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Season {
+    pub id: String,
+    pub name: String,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    #[serde(default)]
+    pub status: SeasonStatus,
+    #[serde(default = "default_reward_tiers")]
+    pub reward_tiers: Vec<SeasonRewardTier>,
+    #[serde(default)]
+    pub finalized_at: Option<DateTime<Utc>>,
+}
+
+/// XP earned by one wallet inside a season window.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeasonTally {
+    pub wallet_address: String,
+    pub points: i64,
+    /// When the wallet reached its current points.
+    pub reached_at: DateTime<Utc>,
+    pub activity_count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SeasonStanding {
+    pub season_id: String,
+    pub wallet_address: String,
+    pub rank: i64,
+    pub points: i64,
+    pub reached_at: DateTime<Utc>,
+    pub activity_count: i64,
+    pub reward_issued: bool,
+}
+
+/// A wallet's standing in the current season.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SeasonStats {
+    pub season_id: String,
+    pub season_name: String,
+    pub ends_at: DateTime<Utc>,
+    pub points: i64,
+    pub rank: Option<i64>,
+    pub activity_count: i64,
+}
+
+pub fn default_reward_tiers() -> Vec<SeasonRewardTier> {
+    vec![
+        SeasonRewardTier {
+            name: "Champion".to_string(),
+            max_rank: 1,
+            xp_reward: 5000,
+            rarity: BadgeRarity::Legendary,
+        },
+        SeasonRewardTier {
+            name: "Podium".to_string(),
+            max_rank: 3,
+            xp_reward: 2500,
+            rarity: BadgeRarity::Epic,
+        },
+        SeasonRewardTier {
+            name: "Top 10".to_string(),
+            max_rank: 10,
+            xp_reward: 1000,
+            rarity: BadgeRarity::Rare,
+        },
+    ]
+}
+
+pub fn validate_season(season: &Season, existing: &[Season]) -> Result<(), String> {
+    if season.name.trim().is_empty() {
+        return Err("A season needs a name".to_string());
+    }
+    if season.ends_at <= season.starts_at {
+        return Err("A season must end after it starts".to_string());
+    }
+
+    let mut names = HashSet::new();
+    for tier in &season.reward_tiers {
+        if tier.max_rank <= 0 {
+            return Err(format!("Reward tier {} needs a positive rank", tier.name));
+        }
+        if tier.xp_reward < 0 {
+            return Err(format!("Reward tier {} cannot take XP away", tier.name));
+        }
+        if !names.insert(tier.name.as_str()) {
+            return Err(format!("Duplicate reward tier: {}", tier.name));
+        }
+    }
+
+    if let Some(other) = existing.iter().find(|other| {
+        other.id != season.id
+            && other.status != SeasonStatus::Archived
+            && other.starts_at < season.ends_at
+            && season.starts_at < other.ends_at
+    }) {
+        return Err(format!("Season overlaps with {}", other.name));
+    }
+    Ok(())
+}
+
+/// Ranks tallies by points; ties go to whoever reached their points first,
+/// then to the wallet with more activity.
+pub fn rank_standings(season_id: &str, mut tallies: Vec<SeasonTally>) -> Vec<SeasonStanding> {
+    tallies.sort_by(|a, b| {
+        b.points
+            .cmp(&a.points)
+            .then_with(|| a.reached_at.cmp(&b.reached_at))
+            .then_with(|| b.activity_count.cmp(&a.activity_count))
+            .then_with(|| a.wallet_address.cmp(&b.wallet_address))
+    });
+
+    tallies
+        .into_iter()
+        .enumerate()
+        .map(|(index, tally)| SeasonStanding {
+            season_id: season_id.to_string(),
+            wallet_address: tally.wallet_address,
+            rank: index as i64 + 1,
+            points: tally.points,
+            reached_at: tally.reached_at,
+            activity_count: tally.activity_count,
+            reward_issued: false,
+        })
+        .collect()
+}
+
+/// The narrowest tier that covers `rank`.
+pub fn tier_for_rank(tiers: &[SeasonRewardTier], rank: i64) -> Option<&SeasonRewardTier> {
+    tiers
+        .iter()
+        .filter(|tier| rank <= tier.max_rank)
+        .min_by_key(|tier| tier.max_rank)
+}
+
+pub fn season_badge_id(season_id: &str, tier: &SeasonRewardTier) -> String {
+    let slug: String = tier
+        .name
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    format!("season_{}_{}", season_id, slug)
+}
+
+/// The season that follows `previous`, with the same length and rewards.
+pub fn next_season(previous: &Season) -> Season {
+    let length = previous.ends_at - previous.starts_at;
+    let starts_at = previous.ends_at;
+    Season {
+        id: format!("season_{}", Uuid::new_v4()),
+        name: format!("Season of {}", starts_at.format("%B %-d, %Y")),
+        starts_at,
+        ends_at: starts_at + length,
+        status: SeasonStatus::Scheduled,
+        reward_tiers: previous.reward_tiers.clone(),
+        finalized_at: None,
+    }
+}
+
+async fn ensure_season_badge(
+    rewards: &RewardEngine,
+    season: &Season,
+    tier: &SeasonRewardTier,
+) -> Result<String, RewardError> {
+    let badge_id = season_badge_id(&season.id, tier);
+    match rewards.get_badge(&badge_id).await {
+        Ok(_) => return Ok(badge_id),
+        Err(RewardError::NotFound(_)) => {}
+        Err(e) => return Err(e),
+    }
+
+    rewards
+        .create_badge(Badge {
+            id: badge_id.clone(),
+            name: format!("{} · {}", season.name, tier.name),
+            description: format!(
+                "Finished {} in the top {} of the leaderboard",
+                season.name, tier.max_rank
+            ),
+            rarity: tier.rarity.clone(),
+            icon_url: None,
+            xp_reward: tier.xp_reward,
+            reputation_boost: 0.0,
+            requirements: serde_json::json!({
+                "seasonId": season.id,
+                "maxRank": tier.max_rank,
+            })
+            .to_string(),
+            is_active: true,
+            created_at: Utc::now(),
+        })
+        .await?;
+    Ok(badge_id)
+}
+
+/// Awards season badges (and, through them, tier XP) to every frozen standing
+/// that hasn't been rewarded yet, then archives the season.
+async fn distribute_rewards(
+    progress: &ProgressTracker,
+    rewards: &RewardEngine,
+    season: &Season,
+) -> Result<(), String> {
+    let standings = progress
+        .get_season_standings(&season.id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    for standing in standings.iter().filter(|s| !s.reward_issued) {
+        if let Some(tier) = tier_for_rank(&season.reward_tiers, standing.rank) {
+            let badge_id = ensure_season_badge(rewards, season, tier)
+                .await
+                .map_err(|e| e.to_string())?;
+            let source = format!(
+                "Ranked #{} in {} with {} points",
+                standing.rank, season.name, standing.points
+            );
+            match rewards
+                .award_badge(&standing.wallet_address, &badge_id, &source)
+                .await
+            {
+                Ok(_) | Err(RewardError::AlreadyClaimed(_)) => {}
+                Err(e) => return Err(e.to_string()),
+            }
+            progress
+                .add_badge(&standing.wallet_address, &badge_id)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+
+        progress
+            .mark_season_reward_issued(&season.id, &standing.wallet_address)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    progress
+        .archive_season(&season.id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Closes any season past its end, finishes interrupted distributions and
+/// activates the season whose window has opened.
+pub async fn roll_seasons(academy: &SharedAcademyEngine, now: DateTime<Utc>) -> Result<(), String> {
+    let engine = academy.read().await;
+    let progress = engine.progress_tracker();
+    let progress = progress.read().await;
+    let rewards = engine.reward_engine();
+    let rewards = rewards.read().await;
+
+    if let Some(active) = progress
+        .get_season_with_status(SeasonStatus::Active)
+        .await
+        .map_err(|e| e.to_string())?
+    {
+        if now >= active.ends_at {
+            let tallies = progress
+                .season_tallies(active.starts_at, active.ends_at)
+                .await
+                .map_err(|e| e.to_string())?;
+            let standings = rank_standings(&active.id, tallies);
+
+            // Keep a season the admin scheduled; otherwise continue with the same format.
+            let upcoming = progress
+                .list_seasons()
+                .await
+                .map_err(|e| e.to_string())?
+                .into_iter()
+                .any(|s| s.status == SeasonStatus::Scheduled && s.starts_at >= active.starts_at);
+            let next = (!upcoming).then(|| next_season(&active));
+
+            progress
+                .freeze_season(&active.id, &standings, next.as_ref(), now)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+    }
+
+    for season in progress
+        .list_seasons()
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|s| s.status == SeasonStatus::Finalizing)
+    {
+        distribute_rewards(&progress, &rewards, &season).await?;
+    }
+
+    let seasons = progress.list_seasons().await.map_err(|e| e.to_string())?;
+    if !seasons.iter().any(|s| s.status == SeasonStatus::Active) {
+        if let Some(due) = seasons
+            .iter()
+            .filter(|s| s.status == SeasonStatus::Scheduled && s.starts_at <= now)
+            .min_by_key(|s| s.starts_at)
+        {
+            progress
+                .activate_season(&due.id)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn run_season_rollover(academy: SharedAcademyEngine) {
+    let mut tick = interval(SEASON_CHECK_INTERVAL);
+    loop {
+        tick.tick().await;
+        if let Err(err) = roll_seasons(&academy, Utc::now()).await {
+            eprintln!("Failed to roll over academy seasons: {}", err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration as ChronoDuration;
+
+    fn tally(wallet: &str, points: i64, reached_mins: i64, activity: i64) -> SeasonTally {
+        SeasonTally {
+            wallet_address: wallet.to_string(),
+            points,
+            reached_at: DateTime::<Utc>::UNIX_EPOCH + ChronoDuration::minutes(reached_mins),
+            activity_count: activity,
+        }
+    }
+
+    #[test]
+    fn test_ties_break_on_time_then_activity() {
+        let standings = rank_standings(
+            "s1",
+            vec![
+                tally("late", 500, 30, 9),
+                tally("early", 500, 10, 2),
+                tally("busy", 500, 10, 5),
+                tally("leader", 900, 50, 1),
+            ],
+        );
+        let order: Vec<&str> = standings
+            .iter()
+            .map(|s| s.wallet_address.as_str())
+            .collect();
+        assert_eq!(order, vec!["leader", "busy", "early", "late"]);
+        assert_eq!(standings[3].rank, 4);
+    }
+
+    #[test]
+    fn test_tiers_and_next_season() {
+        let tiers = default_reward_tiers();
+        assert_eq!(tier_for_rank(&tiers, 1).map(|t| t.max_rank), Some(1));
+        assert_eq!(tier_for_rank(&tiers, 3).map(|t| t.max_rank), Some(3));
+        assert_eq!(tier_for_rank(&tiers, 7).map(|t| t.max_rank), Some(10));
+        assert!(tier_for_rank(&tiers, 11).is_none());
+
+        let start = Utc::now();
+        let season = Season {
+            id: "s1".to_string(),
+            name: "Launch season".to_string(),
+            starts_at: start,
+            ends_at: start + ChronoDuration::days(30),
+            status: SeasonStatus::Active,
+            reward_tiers: tiers,
+            finalized_at: None,
+        };
+        let next = next_season(&season);
+        assert_eq!(next.starts_at, season.ends_at);
+        assert_eq!(next.ends_at - next.starts_at, ChronoDuration::days(30));
+        assert!(validate_season(&next, &[season.clone()]).is_ok());
+
+        let overlapping = Season {
+            id: "s2".to_string(),
+            starts_at: start + ChronoDuration::days(10),
+            ..next
+        };
+        assert!(validate_season(&overlapping, &[season]).is_err());
+    }
+}
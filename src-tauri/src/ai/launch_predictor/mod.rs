@@ -8,12 +8,13 @@ pub use inference::*;
 pub use model::*;
 pub use training::*;
 
+use crate::config::DataPaths;
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use sqlx::{Pool, Row, Sqlite, SqlitePool};
 use std::collections::HashMap;
 use std::sync::Arc;
-use tauri::{AppHandle, Manager};
+use tauri::AppHandle;
 use tokio::sync::RwLock;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -65,7 +66,7 @@ pub type SharedLaunchPredictor = Arc<RwLock<LaunchPredictor>>;
 
 impl LaunchPredictor {
     pub async fn new(app: &AppHandle) -> Result<Self, sqlx::Error> {
-        let mut db_path = app.path().app_data_dir().map_err(|_| {
+        let mut db_path = app.app_data_root().map_err(|_| {
             sqlx::Error::Io(std::io::Error::new(
                 std::io::ErrorKind::NotFound,
                 "App data dir not found",
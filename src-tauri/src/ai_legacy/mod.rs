@@ -1,8 +1,13 @@
 pub mod launch_predictor;
 pub use launch_predictor::*;
 
+use crate::api_analytics::{admit_metered, RequestPriority};
+use crate::config::privacy_mode::{Masked, PrivacyMasked, SharedPrivacyMode};
+use crate::config::DataPaths;
 use crate::security::keystore::Keystore;
+use crate::wallet::balances::SharedTokenBalanceService;
 use chrono::{DateTime, Utc};
+use futures_util::stream::{self, StreamExt};
 use serde::{Deserialize, Serialize};
 use sqlx::{Pool, Row, Sqlite, SqlitePool};
 use std::collections::HashMap;
@@ -37,6 +42,15 @@ pub struct RiskFeatures {
     pub token_age_days: f64,
     pub volume_24h: f64,
     pub price_volatility: f64,
+
+    // Supply features
+    /// Largest scheduled unlock in the next 14 days, as % of circulating supply.
+    #[serde(default)]
+    pub upcoming_unlock_percent: f64,
+    /// Share (0-1) of pool liquidity unlocked or releasing soon; 0.5 when the
+    /// lock status is unknown.
+    #[serde(default)]
+    pub liquidity_unlock_exposure: f64,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -73,6 +87,187 @@ pub struct RiskHistoryPoint {
     pub risk_level: String,
 }
 
+/// Scores computed by `batch_risk_scores` at once; the rest wait for a slot.
+const BATCH_RISK_CONCURRENCY: usize = 8;
+/// Cached scores younger than this are served without recomputing.
+const DEFAULT_RISK_SCORE_MAX_AGE_MINUTES: i64 = 30;
+/// Provider whose budget feature gathering (holders, metadata) is charged to.
+const RISK_FEATURE_SERVICE: &str = "solana_rpc";
+const RISK_LEVELS: [&str; 4] = ["Low", "Medium", "High", "Critical"];
+
+/// Outcome for one mint of a batch; exactly one of `score` and `error` is set.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchRiskResult {
+    pub mint: String,
+    pub score: Option<RiskScore>,
+    pub error: Option<String>,
+    pub from_cache: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchRiskScores {
+    pub results: Vec<BatchRiskResult>,
+    pub cached: usize,
+    pub computed: usize,
+    pub failed: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RiskBucket {
+    pub risk_level: String,
+    pub token_count: usize,
+    pub value: f64,
+    pub value_share: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RiskContributor {
+    pub mint: String,
+    pub symbol: String,
+    pub score: f64,
+    pub risk_level: String,
+    pub value: f64,
+    /// Share of the value-weighted composite score this holding accounts for.
+    pub contribution: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PortfolioRiskSummary {
+    pub wallet_address: String,
+    /// Value-weighted average of the holdings' risk scores (0-100).
+    pub composite_score: f64,
+    pub risk_level: String,
+    pub total_value: f64,
+    pub buckets: Vec<RiskBucket>,
+    pub top_contributors: Vec<RiskContributor>,
+    /// Holdings left out of the composite because they could not be scored.
+    pub unscored: Vec<BatchRiskResult>,
+    pub cached: usize,
+    pub computed: usize,
+}
+
+impl PrivacyMasked for PortfolioRiskSummary {
+    const MASKED_FIELDS: &'static [&'static str] = &["value", "totalValue"];
+    const WALLET_KEY: Option<&'static str> = Some("walletAddress");
+}
+
+fn risk_level_for(score: f64) -> &'static str {
+    if score < 30.0 {
+        "Low"
+    } else if score < 60.0 {
+        "Medium"
+    } else if score < 80.0 {
+        "High"
+    } else {
+        "Critical"
+    }
+}
+
+/// One held token for the portfolio summary.
+#[derive(Clone, Debug)]
+pub struct RiskHolding {
+    pub mint: String,
+    pub symbol: String,
+    pub value: f64,
+}
+
+/// Aggregates scored holdings into a value-weighted summary. Holdings with
+/// no value are ignored; failed scores are reported as unscored.
+pub fn summarize_portfolio_risk(
+    wallet_address: &str,
+    holdings: &[RiskHolding],
+    batch: BatchRiskScores,
+) -> PortfolioRiskSummary {
+    let scores: HashMap<&str, &BatchRiskResult> = batch
+        .results
+        .iter()
+        .map(|result| (result.mint.as_str(), result))
+        .collect();
+
+    let mut scored = Vec::new();
+    let mut unscored = Vec::new();
+    for holding in holdings.iter().filter(|holding| holding.value > 0.0) {
+        match scores.get(holding.mint.as_str()) {
+            Some(BatchRiskResult {
+                score: Some(score), ..
+            }) => scored.push((holding, score)),
+            Some(result) => unscored.push((*result).clone()),
+            None => {}
+        }
+    }
+
+    let total_value: f64 = scored.iter().map(|(holding, _)| holding.value).sum();
+    let weighted: f64 = scored
+        .iter()
+        .map(|(holding, score)| holding.value * score.score)
+        .sum();
+    let composite_score = if total_value > 0.0 {
+        weighted / total_value
+    } else {
+        0.0
+    };
+
+    let buckets = RISK_LEVELS
+        .iter()
+        .map(|level| {
+            let in_bucket: Vec<_> = scored
+                .iter()
+                .filter(|(_, score)| score.risk_level == *level)
+                .collect();
+            let value: f64 = in_bucket.iter().map(|(holding, _)| holding.value).sum();
+            RiskBucket {
+                risk_level: level.to_string(),
+                token_count: in_bucket.len(),
+                value,
+                value_share: if total_value > 0.0 {
+                    value / total_value
+                } else {
+                    0.0
+                },
+            }
+        })
+        .collect();
+
+    let mut top_contributors: Vec<RiskContributor> = scored
+        .iter()
+        .map(|(holding, score)| RiskContributor {
+            mint: holding.mint.clone(),
+            symbol: holding.symbol.clone(),
+            score: score.score,
+            risk_level: score.risk_level.clone(),
+            value: holding.value,
+            contribution: if weighted > 0.0 {
+                holding.value * score.score / weighted
+            } else {
+                0.0
+            },
+        })
+        .collect();
+    top_contributors.sort_by(|a, b| {
+        b.contribution
+            .partial_cmp(&a.contribution)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    top_contributors.truncate(3);
+
+    PortfolioRiskSummary {
+        wallet_address: wallet_address.to_string(),
+        composite_score,
+        risk_level: risk_level_for(composite_score).to_string(),
+        total_value,
+        buckets,
+        top_contributors,
+        unscored,
+        cached: batch.cached,
+        computed: batch.computed,
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct RiskModel {
     // Logistic regression weights for each feature
@@ -116,6 +311,12 @@ impl RiskModel {
         // High volatility = higher risk
         weights.insert("volatility".to_string(), 12.0);
 
+        // Large supply unlocks ahead = higher risk
+        weights.insert("large_unlock".to_string(), 15.0);
+
+        // Unlocked or soon-unlocking pool liquidity = higher risk
+        weights.insert("liquidity_unlock".to_string(), 15.0);
+
         Self {
             weights,
             intercept: 50.0, // Base risk score
@@ -193,6 +394,15 @@ impl RiskModel {
         let volatility_score = (features.price_volatility / 100.0).clamp(0.0, 1.0);
         feature_map.insert("volatility", volatility_score);
 
+        // Upcoming unlocks saturate at 10% of circulating supply
+        let unlock_score = (features.upcoming_unlock_percent / 10.0).clamp(0.0, 1.0);
+        feature_map.insert("large_unlock", unlock_score);
+
+        feature_map.insert(
+            "liquidity_unlock",
+            features.liquidity_unlock_exposure.clamp(0.0, 1.0),
+        );
+
         // Calculate weighted score
         let mut score = self.intercept;
         let mut factor_contributions = Vec::new();
@@ -233,6 +443,8 @@ impl RiskModel {
                     "sentiment" => ("Market sentiment", false),
                     "age_score" => ("Token age", false),
                     "volatility" => ("Price volatility", true),
+                    "large_unlock" => ("Large token unlock within 14 days", true),
+                    "liquidity_unlock" => ("Pool liquidity unlocked or unlocking soon", true),
                     _ => ("Unknown factor", true),
                 };
 
@@ -277,7 +489,7 @@ pub type SharedRiskAnalyzer = Arc<RwLock<RiskAnalyzer>>;
 
 impl RiskAnalyzer {
     pub async fn new(app: &AppHandle) -> Result<Self, sqlx::Error> {
-        let mut db_path = app.path().app_data_dir().map_err(|_| {
+        let mut db_path = app.app_data_root().map_err(|_| {
             sqlx::Error::Io(std::io::Error::new(
                 std::io::ErrorKind::NotFound,
                 "App data dir not found",
@@ -351,18 +563,8 @@ impl RiskAnalyzer {
         token_address: &str,
         features: RiskFeatures,
     ) -> Result<RiskScore, sqlx::Error> {
-        let model = self.model.read().await;
-        let (score, factors) = model.score_token(&features);
-
-        let risk_level = if score < 30.0 {
-            "Low"
-        } else if score < 60.0 {
-            "Medium"
-        } else if score < 80.0 {
-            "High"
-        } else {
-            "Critical"
-        };
+        let (score, factors) = self.model.read().await.score_token(&features);
+        let risk_level = risk_level_for(score);
 
         let risk_score = RiskScore {
             token_address: token_address.to_string(),
@@ -460,6 +662,85 @@ impl RiskAnalyzer {
         }
     }
 
+    /// Scores several mints at once. Scores younger than `max_age` are served
+    /// from the database; the rest are computed at most
+    /// `BATCH_RISK_CONCURRENCY` at a time with features from `gather`, each
+    /// admitted by the API budget first. A failing mint is reported in its
+    /// result instead of failing the batch.
+    pub async fn batch_risk_scores<F, Fut>(
+        &self,
+        mints: Vec<String>,
+        max_age: chrono::Duration,
+        gather: F,
+    ) -> BatchRiskScores
+    where
+        F: Fn(String) -> Fut,
+        Fut: std::future::Future<Output = Result<RiskFeatures, String>>,
+    {
+        let mut seen = std::collections::HashSet::new();
+        let mints: Vec<String> = mints
+            .into_iter()
+            .map(|mint| mint.trim().to_string())
+            .filter(|mint| !mint.is_empty() && seen.insert(mint.clone()))
+            .collect();
+        let cutoff = Utc::now() - max_age;
+        let gather = &gather;
+
+        let results: Vec<BatchRiskResult> = stream::iter(mints)
+            .map(|mint| async move {
+                match self.get_latest_risk_score(&mint).await {
+                    Ok(Some(cached)) if is_fresh(&cached.timestamp, cutoff) => {
+                        return BatchRiskResult {
+                            mint,
+                            score: Some(cached),
+                            error: None,
+                            from_cache: true,
+                        };
+                    }
+                    _ => {}
+                }
+
+                let scored = async {
+                    admit_metered(RISK_FEATURE_SERVICE, RequestPriority::Standard).await?;
+                    let features = gather(mint.clone()).await?;
+                    self.score_token(&mint, features)
+                        .await
+                        .map_err(|e| format!("Failed to score token: {}", e))
+                }
+                .await;
+
+                match scored {
+                    Ok(score) => BatchRiskResult {
+                        mint,
+                        score: Some(score),
+                        error: None,
+                        from_cache: false,
+                    },
+                    Err(error) => BatchRiskResult {
+                        mint,
+                        score: None,
+                        error: Some(error),
+                        from_cache: false,
+                    },
+                }
+            })
+            .buffered(BATCH_RISK_CONCURRENCY)
+            .collect()
+            .await;
+
+        let cached = results.iter().filter(|result| result.from_cache).count();
+        let failed = results
+            .iter()
+            .filter(|result| result.error.is_some())
+            .count();
+        BatchRiskScores {
+            cached,
+            computed: results.len() - cached - failed,
+            failed,
+            results,
+        }
+    }
+
     pub async fn save_model(&self, metrics: Option<String>) -> Result<(), sqlx::Error> {
         let model = self.model.read().await;
         let model_json = model.to_json().map_err(|e| {
@@ -542,18 +823,24 @@ pub async fn assess_risk(features: Vec<f32>) -> Result<f32, String> {
     Ok(score.max(0.0).min(1.0))
 }
 
-// New ML-based commands
-#[tauri::command]
-pub async fn get_token_risk_score(
-    token_address: String,
-    risk_analyzer: State<'_, SharedRiskAnalyzer>,
-    holder_analyzer: State<'_, crate::market::SharedHolderAnalyzer>,
-) -> Result<RiskScore, String> {
-    // Gather features from various sources
+fn is_fresh(timestamp: &str, cutoff: DateTime<Utc>) -> bool {
+    DateTime::parse_from_rfc3339(timestamp)
+        .map(|at| at.with_timezone(&Utc) >= cutoff)
+        .unwrap_or(false)
+}
+
+/// Collects the model inputs for one token from the holder analyzer, the
+/// unlock calendar and the liquidity lock monitor.
+async fn gather_risk_features(
+    holder_analyzer: &crate::market::SharedHolderAnalyzer,
+    unlocks: &crate::market::SharedTokenUnlockCalendar,
+    liquidity_locks: &crate::market::SharedLiquidityLockMonitor,
+    token_address: &str,
+) -> Result<RiskFeatures, String> {
     let holder_data = {
         let analyzer = holder_analyzer.read().await;
         analyzer
-            .get_holder_distribution(&token_address)
+            .get_holder_distribution(token_address)
             .await
             .map_err(|e| format!("Failed to get holder data: {}", e))?
     };
@@ -561,7 +848,7 @@ pub async fn get_token_risk_score(
     let metadata = {
         let analyzer = holder_analyzer.read().await;
         analyzer
-            .get_token_metadata(&token_address)
+            .get_token_metadata(token_address)
             .await
             .map_err(|e| format!("Failed to get metadata: {}", e))?
     };
@@ -569,7 +856,7 @@ pub async fn get_token_risk_score(
     let verification = {
         let analyzer = holder_analyzer.read().await;
         analyzer
-            .get_verification_status(&token_address)
+            .get_verification_status(token_address)
             .await
             .map_err(|e| format!("Failed to get verification: {}", e))?
     };
@@ -583,8 +870,7 @@ pub async fn get_token_risk_score(
         (now - creation_date).num_days() as f64
     };
 
-    // Build features
-    let features = RiskFeatures {
+    Ok(RiskFeatures {
         gini_coefficient: holder_data.gini_coefficient,
         top_10_percentage: holder_data.top_10_percentage,
         total_holders: holder_data.total_holders,
@@ -599,7 +885,22 @@ pub async fn get_token_risk_score(
         token_age_days,
         volume_24h: 50000.0,    // Mock
         price_volatility: 15.0, // Mock
-    };
+        upcoming_unlock_percent: unlocks.upcoming_unlock_percent(token_address, Utc::now()),
+        liquidity_unlock_exposure: liquidity_locks.status(token_address, false).await.exposure,
+    })
+}
+
+// New ML-based commands
+#[tauri::command]
+pub async fn get_token_risk_score(
+    token_address: String,
+    risk_analyzer: State<'_, SharedRiskAnalyzer>,
+    holder_analyzer: State<'_, crate::market::SharedHolderAnalyzer>,
+    unlocks: State<'_, crate::market::SharedTokenUnlockCalendar>,
+    liquidity_locks: State<'_, crate::market::SharedLiquidityLockMonitor>,
+) -> Result<RiskScore, String> {
+    let features =
+        gather_risk_features(&holder_analyzer, &unlocks, &liquidity_locks, &token_address).await?;
 
     let analyzer = risk_analyzer.read().await;
     let risk_score = analyzer
@@ -610,6 +911,86 @@ pub async fn get_token_risk_score(
     Ok(risk_score)
 }
 
+#[tauri::command]
+pub async fn batch_risk_scores(
+    mints: Vec<String>,
+    max_age_minutes: Option<i64>,
+    risk_analyzer: State<'_, SharedRiskAnalyzer>,
+    holder_analyzer: State<'_, crate::market::SharedHolderAnalyzer>,
+    unlocks: State<'_, crate::market::SharedTokenUnlockCalendar>,
+    liquidity_locks: State<'_, crate::market::SharedLiquidityLockMonitor>,
+) -> Result<BatchRiskScores, String> {
+    let max_age =
+        chrono::Duration::minutes(max_age_minutes.unwrap_or(DEFAULT_RISK_SCORE_MAX_AGE_MINUTES));
+    let analyzer = risk_analyzer.read().await.clone();
+    let holder_analyzer = holder_analyzer.inner().clone();
+    let unlocks = unlocks.inner().clone();
+    let liquidity_locks = liquidity_locks.inner().clone();
+
+    Ok(analyzer
+        .batch_risk_scores(mints, max_age, |mint| {
+            let holder_analyzer = holder_analyzer.clone();
+            let unlocks = unlocks.clone();
+            let liquidity_locks = liquidity_locks.clone();
+            async move {
+                gather_risk_features(&holder_analyzer, &unlocks, &liquidity_locks, &mint).await
+            }
+        })
+        .await)
+}
+
+#[tauri::command]
+pub async fn get_portfolio_risk_summary(
+    wallet: String,
+    max_age_minutes: Option<i64>,
+    risk_analyzer: State<'_, SharedRiskAnalyzer>,
+    holder_analyzer: State<'_, crate::market::SharedHolderAnalyzer>,
+    unlocks: State<'_, crate::market::SharedTokenUnlockCalendar>,
+    liquidity_locks: State<'_, crate::market::SharedLiquidityLockMonitor>,
+    balances: State<'_, SharedTokenBalanceService>,
+    privacy: State<'_, SharedPrivacyMode>,
+) -> Result<Masked<PortfolioRiskSummary>, String> {
+    let view = balances
+        .balances(&wallet, false)
+        .await
+        .map_err(|e| e.to_string())?;
+    let holdings: Vec<RiskHolding> = view
+        .balances
+        .iter()
+        .map(|balance| RiskHolding {
+            mint: balance.mint.clone(),
+            symbol: balance.symbol.clone(),
+            value: balance.usd_value,
+        })
+        .collect();
+
+    let max_age =
+        chrono::Duration::minutes(max_age_minutes.unwrap_or(DEFAULT_RISK_SCORE_MAX_AGE_MINUTES));
+    let analyzer = risk_analyzer.read().await.clone();
+    let holder_analyzer = holder_analyzer.inner().clone();
+    let unlocks = unlocks.inner().clone();
+    let liquidity_locks = liquidity_locks.inner().clone();
+    let mints = holdings
+        .iter()
+        .filter(|holding| holding.value > 0.0)
+        .map(|holding| holding.mint.clone())
+        .collect();
+    let batch = analyzer
+        .batch_risk_scores(mints, max_age, |mint| {
+            let holder_analyzer = holder_analyzer.clone();
+            let unlocks = unlocks.clone();
+            let liquidity_locks = liquidity_locks.clone();
+            async move {
+                gather_risk_features(&holder_analyzer, &unlocks, &liquidity_locks, &mint).await
+            }
+        })
+        .await;
+
+    let summary = summarize_portfolio_risk(&wallet, &holdings, batch);
+    let privacy = privacy.read().map_err(|e| e.to_string())?;
+    Ok(privacy.apply_for_wallet(summary, &wallet))
+}
+
 #[tauri::command]
 pub async fn get_risk_history(
     token_address: String,
@@ -1082,7 +1463,7 @@ pub struct ConversationManager {
 
 impl ConversationManager {
     pub async fn new(app: &AppHandle) -> Result<Self, sqlx::Error> {
-        let mut db_path = app.path().app_data_dir().map_err(|_| {
+        let mut db_path = app.app_data_root().map_err(|_| {
             sqlx::Error::Io(std::io::Error::new(
                 std::io::ErrorKind::NotFound,
                 "App data dir not found",
@@ -1327,7 +1708,7 @@ impl UsageThrottle {
         max_requests_per_hour: u32,
         max_tokens_per_day: u64,
     ) -> Result<Self, sqlx::Error> {
-        let mut db_path = app.path().app_data_dir().map_err(|_| {
+        let mut db_path = app.app_data_root().map_err(|_| {
             sqlx::Error::Io(std::io::Error::new(
                 std::io::ErrorKind::NotFound,
                 "App data dir not found",
@@ -1894,6 +2275,131 @@ pub async fn ai_is_configured(ai_assistant: State<'_, SharedAIAssistant>) -> Res
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::test_pool;
+
+    async fn analyzer() -> RiskAnalyzer {
+        let pool = test_pool().await;
+        RiskAnalyzer::with_pool(pool).await.unwrap()
+    }
+
+    fn features(risky: bool) -> RiskFeatures {
+        RiskFeatures {
+            gini_coefficient: if risky { 0.95 } else { 0.3 },
+            top_10_percentage: if risky { 85.0 } else { 25.0 },
+            total_holders: if risky { 50 } else { 10000 },
+            liquidity_usd: if risky { 5000.0 } else { 1000000.0 },
+            liquidity_to_mcap_ratio: 0.1,
+            has_mint_authority: risky,
+            has_freeze_authority: risky,
+            verified: !risky,
+            audited: !risky,
+            community_trust_score: if risky { 0.2 } else { 0.9 },
+            sentiment_score: 0.0,
+            token_age_days: if risky { 2.0 } else { 365.0 },
+            volume_24h: 50000.0,
+            price_volatility: if risky { 50.0 } else { 5.0 },
+            upcoming_unlock_percent: 0.0,
+            liquidity_unlock_exposure: 0.0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_batch_risk_scores_cache_and_errors() {
+        let analyzer = analyzer().await;
+        analyzer
+            .score_token("cached", features(false))
+            .await
+            .unwrap();
+
+        let batch = analyzer
+            .batch_risk_scores(
+                vec![
+                    "cached".to_string(),
+                    "risky".to_string(),
+                    "broken".to_string(),
+                    "risky".to_string(),
+                ],
+                chrono::Duration::minutes(30),
+                |mint| async move {
+                    match mint.as_str() {
+                        "broken" => Err("Failed to get holder data: timeout".to_string()),
+                        "cached" => panic!("fresh scores must not be recomputed"),
+                        _ => Ok(features(true)),
+                    }
+                },
+            )
+            .await;
+
+        let mints: Vec<&str> = batch.results.iter().map(|r| r.mint.as_str()).collect();
+        assert_eq!(mints, vec!["cached", "risky", "broken"]);
+        assert_eq!((batch.cached, batch.computed, batch.failed), (1, 1, 1));
+        assert!(batch.results[0].from_cache);
+        assert!(batch.results[2].score.is_none());
+        assert!(batch.results[2].error.is_some());
+    }
+
+    #[test]
+    fn test_portfolio_risk_summary() {
+        let score = |mint: &str, value: f64| BatchRiskResult {
+            mint: mint.to_string(),
+            score: Some(RiskScore {
+                token_address: mint.to_string(),
+                score: value,
+                risk_level: risk_level_for(value).to_string(),
+                contributing_factors: Vec::new(),
+                timestamp: Utc::now().to_rfc3339(),
+            }),
+            error: None,
+            from_cache: false,
+        };
+        let holding = |mint: &str, value: f64| RiskHolding {
+            mint: mint.to_string(),
+            symbol: mint.to_uppercase(),
+            value,
+        };
+        let batch = BatchRiskScores {
+            results: vec![
+                score("a", 10.0),
+                score("b", 90.0),
+                score("c", 50.0),
+                score("d", 70.0),
+                BatchRiskResult {
+                    mint: "e".to_string(),
+                    score: None,
+                    error: Some("throttled".to_string()),
+                    from_cache: false,
+                },
+            ],
+            cached: 0,
+            computed: 4,
+            failed: 1,
+        };
+        let holdings = vec![
+            holding("a", 600.0),
+            holding("b", 200.0),
+            holding("c", 100.0),
+            holding("d", 100.0),
+            holding("e", 50.0),
+        ];
+
+        let summary = summarize_portfolio_risk("wallet", &holdings, batch);
+        // (600*10 + 200*90 + 100*50 + 100*70) / 1000
+        assert!((summary.composite_score - 36.0).abs() < 1e-9);
+        assert_eq!(summary.risk_level, "Medium");
+        assert_eq!(summary.unscored.len(), 1);
+
+        let critical = &summary.buckets[3];
+        assert_eq!(critical.risk_level, "Critical");
+        assert_eq!(critical.token_count, 1);
+        assert!((critical.value_share - 0.2).abs() < 1e-9);
+
+        let top: Vec<&str> = summary
+            .top_contributors
+            .iter()
+            .map(|c| c.mint.as_str())
+            .collect();
+        assert_eq!(top, vec!["b", "d", "a"]);
+    }
 
     #[test]
     fn test_risk_model_scoring() {
@@ -1915,6 +2421,8 @@ mod tests {
             token_age_days: 2.0,
             volume_24h: 1000.0,
             price_volatility: 50.0,
+            upcoming_unlock_percent: 0.0,
+            liquidity_unlock_exposure: 0.0,
         };
 
         let (score, factors) = model.score_token(&high_risk);
@@ -1937,6 +2445,8 @@ mod tests {
             token_age_days: 180.0,
             volume_24h: 500000.0,
             price_volatility: 5.0,
+            upcoming_unlock_percent: 0.0,
+            liquidity_unlock_exposure: 0.0,
         };
 
         let (score, _) = model.score_token(&low_risk);
@@ -1970,6 +2480,8 @@ mod tests {
             token_age_days: 30.0,
             volume_24h: 50000.0,
             price_volatility: 10.0,
+            upcoming_unlock_percent: 0.0,
+            liquidity_unlock_exposure: 0.0,
         };
 
         let model = RiskModel::new();
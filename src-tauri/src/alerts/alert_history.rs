@@ -1,13 +1,39 @@
-use chrono::Utc;
+use crate::config::DataPaths;
+use crate::utils::ensure_column;
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::{Pool, Row, Sqlite, SqlitePool};
+use std::collections::HashMap;
 use std::path::PathBuf;
-use tauri::{AppHandle, State};
+use std::sync::Arc;
+use tauri::{AppHandle, Manager, State};
+use tokio::sync::RwLock;
 
-use super::price_alerts::{AlertError, CompoundCondition, NotificationChannel};
+use super::price_alerts::{
+    AlertConditionType, AlertError, CompoundCondition, NotificationChannel, PriceAlert,
+};
 
 const ALERT_HISTORY_DB_FILE: &str = "alert_history.db";
 
+const HISTORY_COLUMNS: &str = r#"
+    id, alert_id, alert_name, symbol, mint, compound_condition,
+    triggered_price, triggered_volume, conditions_met, notification_channels,
+    triggered_at, bookmarked, outcome_notes, price_after_1h,
+    price_after_4h, price_after_24h, outcome_type, user_action, actioned_at,
+    suppressed, created_at
+"#;
+
+/// Moves smaller than this, in percent, count as noise.
+const NOISE_THRESHOLD_PCT: f64 = 2.0;
+/// A sample taken later than this after its due time no longer says much
+/// about the move, so it is dropped instead.
+const MAX_SAMPLE_LATENESS_MINUTES: i64 = 120;
+/// Resolved triggers needed before an alert can be called noisy.
+const MIN_RESOLVED_FOR_VERDICT: usize = 3;
+/// Share of resolved triggers that must be noise for an alert to be noisy.
+const NOISY_ALERT_RATE: f64 = 0.6;
+const DEFAULT_REPORT_WINDOW_DAYS: u32 = 30;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AlertHistoryEntry {
@@ -18,6 +44,7 @@ pub struct AlertHistoryEntry {
     pub mint: String,
     pub compound_condition: CompoundCondition,
     pub triggered_price: f64,
+    pub triggered_volume: Option<f64>,
     pub conditions_met: String,
     pub notification_channels: Vec<NotificationChannel>,
     pub triggered_at: String,
@@ -26,10 +53,83 @@ pub struct AlertHistoryEntry {
     pub price_after_1h: Option<f64>,
     pub price_after_4h: Option<f64>,
     pub price_after_24h: Option<f64>,
-    pub outcome_type: Option<String>, // "profit", "loss", "neutral", "pending"
+    /// Percent move from the trigger price, derived from the samples.
+    pub move_1h_pct: Option<f64>,
+    pub move_24h_pct: Option<f64>,
+    pub outcome_type: Option<String>, // "profit", "loss", "neutral", "pending", "expired"
+    pub user_action: Option<TriggerAction>,
+    pub actioned_at: Option<String>,
+    /// Fired while the alert was snoozed or its token muted, so no
+    /// notification went out.
+    pub suppressed: bool,
     pub created_at: String,
 }
 
+/// What the user did about a trigger.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TriggerAction {
+    ActedOn,
+    Ignored,
+}
+
+impl TriggerAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TriggerAction::ActedOn => "acted_on",
+            TriggerAction::Ignored => "ignored",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "acted_on" => Some(TriggerAction::ActedOn),
+            "ignored" => Some(TriggerAction::Ignored),
+            _ => None,
+        }
+    }
+}
+
+/// Post-trigger price samples, kept in the database so they survive restarts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SampleHorizon {
+    OneHour,
+    OneDay,
+}
+
+impl SampleHorizon {
+    const ALL: [SampleHorizon; 2] = [SampleHorizon::OneHour, SampleHorizon::OneDay];
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            SampleHorizon::OneHour => "1h",
+            SampleHorizon::OneDay => "24h",
+        }
+    }
+
+    fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "1h" => Some(SampleHorizon::OneHour),
+            "24h" => Some(SampleHorizon::OneDay),
+            _ => None,
+        }
+    }
+
+    fn delay(&self) -> Duration {
+        match self {
+            SampleHorizon::OneHour => Duration::hours(1),
+            SampleHorizon::OneDay => Duration::hours(24),
+        }
+    }
+
+    fn column(&self) -> &'static str {
+        match self {
+            SampleHorizon::OneHour => "price_after_1h",
+            SampleHorizon::OneDay => "price_after_24h",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AlertHistoryFilter {
@@ -67,16 +167,159 @@ pub struct UpdateAlertHistoryRequest {
     pub outcome_type: Option<String>,
 }
 
+/// How one alert's triggers played out over the report window.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AlertEffectiveness {
+    pub alert_id: String,
+    pub alert_name: String,
+    pub symbol: String,
+    pub triggers: usize,
+    /// Triggers whose outcome is known.
+    pub resolved: usize,
+    /// Followed by a move of at least the noise threshold in the alert's
+    /// direction, or in any direction for alerts without one.
+    pub favorable: usize,
+    pub adverse: usize,
+    pub noise: usize,
+    pub acted_on: usize,
+    pub ignored: usize,
+    /// Triggers silenced by a snooze or mute; counted in the totals above.
+    pub suppressed: usize,
+    pub hit_rate: Option<f64>,
+    pub noise_rate: Option<f64>,
+    pub avg_move_24h_pct: Option<f64>,
+    /// Mostly noise over enough triggers to judge; a candidate for pruning.
+    pub noisy: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AlertEffectivenessReport {
+    pub window_days: u32,
+    pub since: String,
+    pub total_triggers: usize,
+    pub noisy_alerts: usize,
+    /// Noisiest first.
+    pub alerts: Vec<AlertEffectiveness>,
+    pub generated_at: String,
+}
+
+fn move_pct(from: f64, to: Option<f64>) -> Option<f64> {
+    to.filter(|_| from > 0.0)
+        .map(|to| (to - from) / from * 100.0)
+}
+
+/// `1.0` for alerts that fire on a rise, `-1.0` on a drop, `None` when the
+/// conditions don't agree on a direction.
+fn expected_direction(condition: &CompoundCondition) -> Option<f64> {
+    let mut directions = condition.conditions.iter().map(|c| match c.condition_type {
+        AlertConditionType::Above => Some(1.0),
+        AlertConditionType::Below => Some(-1.0),
+        AlertConditionType::PercentChange | AlertConditionType::VolumeSpike => None,
+    });
+    let first = directions.next()??;
+    directions.all(|d| d == Some(first)).then_some(first)
+}
+
+/// Outcome of a trigger given the price move that followed it.
+pub fn classify_outcome(condition: &CompoundCondition, move_pct: f64) -> &'static str {
+    if move_pct.abs() < NOISE_THRESHOLD_PCT {
+        return "neutral";
+    }
+    match expected_direction(condition) {
+        Some(direction) if move_pct * direction < 0.0 => "loss",
+        _ => "profit",
+    }
+}
+
+/// Groups history entries per alert.
+pub fn effectiveness(entries: &[AlertHistoryEntry]) -> Vec<AlertEffectiveness> {
+    let mut by_alert: HashMap<&str, (AlertEffectiveness, Vec<f64>)> = HashMap::new();
+    for entry in entries {
+        let (stats, moves) = by_alert.entry(&entry.alert_id).or_insert_with(|| {
+            (
+                AlertEffectiveness {
+                    alert_id: entry.alert_id.clone(),
+                    alert_name: entry.alert_name.clone(),
+                    symbol: entry.symbol.clone(),
+                    triggers: 0,
+                    resolved: 0,
+                    favorable: 0,
+                    adverse: 0,
+                    noise: 0,
+                    acted_on: 0,
+                    ignored: 0,
+                    suppressed: 0,
+                    hit_rate: None,
+                    noise_rate: None,
+                    avg_move_24h_pct: None,
+                    noisy: false,
+                },
+                Vec::new(),
+            )
+        });
+        stats.triggers += 1;
+        if entry.suppressed {
+            stats.suppressed += 1;
+        }
+        match entry.outcome_type.as_deref() {
+            Some("profit") => stats.favorable += 1,
+            Some("loss") => stats.adverse += 1,
+            Some("neutral") => stats.noise += 1,
+            _ => {}
+        }
+        match entry.user_action {
+            Some(TriggerAction::ActedOn) => stats.acted_on += 1,
+            Some(TriggerAction::Ignored) => stats.ignored += 1,
+            None => {}
+        }
+        moves.extend(entry.move_24h_pct);
+    }
+
+    let mut report: Vec<AlertEffectiveness> = by_alert
+        .into_values()
+        .map(|(mut stats, moves)| {
+            stats.resolved = stats.favorable + stats.adverse + stats.noise;
+            if stats.resolved > 0 {
+                let resolved = stats.resolved as f64;
+                stats.hit_rate = Some(stats.favorable as f64 / resolved);
+                stats.noise_rate = Some(stats.noise as f64 / resolved);
+            }
+            if !moves.is_empty() {
+                stats.avg_move_24h_pct = Some(moves.iter().sum::<f64>() / moves.len() as f64);
+            }
+            stats.noisy = stats.resolved >= MIN_RESOLVED_FOR_VERDICT
+                && stats
+                    .noise_rate
+                    .is_some_and(|rate| rate >= NOISY_ALERT_RATE);
+            stats
+        })
+        .collect();
+    report.sort_by(|a, b| {
+        b.noise_rate
+            .unwrap_or(0.0)
+            .total_cmp(&a.noise_rate.unwrap_or(0.0))
+            .then(b.triggers.cmp(&a.triggers))
+    });
+    report
+}
+
 pub struct AlertHistoryManager {
     pool: Pool<Sqlite>,
 }
 
+pub type SharedAlertHistoryManager = Arc<RwLock<AlertHistoryManager>>;
+
 impl AlertHistoryManager {
     pub async fn new(app: &AppHandle) -> Result<Self, AlertError> {
         let db_path = alert_history_db_path(app)?;
         let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
         let pool = SqlitePool::connect(&db_url).await?;
+        Self::with_pool(pool).await
+    }
 
+    async fn with_pool(pool: Pool<Sqlite>) -> Result<Self, AlertError> {
         let manager = Self { pool };
         manager.initialize().await?;
         Ok(manager)
@@ -93,6 +336,7 @@ impl AlertHistoryManager {
                 mint TEXT NOT NULL,
                 compound_condition TEXT NOT NULL,
                 triggered_price REAL NOT NULL,
+                triggered_volume REAL,
                 conditions_met TEXT NOT NULL,
                 notification_channels TEXT NOT NULL,
                 triggered_at TEXT NOT NULL,
@@ -102,12 +346,35 @@ impl AlertHistoryManager {
                 price_after_4h REAL,
                 price_after_24h REAL,
                 outcome_type TEXT,
+                user_action TEXT,
+                actioned_at TEXT,
+                suppressed INTEGER NOT NULL DEFAULT 0,
                 created_at TEXT NOT NULL
             )
             "#,
         )
         .execute(&self.pool)
         .await?;
+        ensure_column(
+            &self.pool,
+            "alert_history",
+            "suppressed",
+            "INTEGER NOT NULL DEFAULT 0",
+        )
+        .await?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS alert_outcome_samples (
+                history_id TEXT NOT NULL,
+                horizon TEXT NOT NULL,
+                due_at TEXT NOT NULL,
+                PRIMARY KEY (history_id, horizon)
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
 
         sqlx::query(
             r#"
@@ -116,6 +383,7 @@ impl AlertHistoryManager {
             CREATE INDEX IF NOT EXISTS idx_history_triggered_at ON alert_history(triggered_at);
             CREATE INDEX IF NOT EXISTS idx_history_bookmarked ON alert_history(bookmarked);
             CREATE INDEX IF NOT EXISTS idx_history_outcome_type ON alert_history(outcome_type);
+            CREATE INDEX IF NOT EXISTS idx_samples_due_at ON alert_outcome_samples(due_at);
             "#,
         )
         .execute(&self.pool)
@@ -124,72 +392,253 @@ impl AlertHistoryManager {
         Ok(())
     }
 
+    /// Records a trigger and schedules its post-trigger price samples.
+    /// Suppressed triggers are sampled too, so their outcomes still count.
     pub async fn record_triggered_alert(
         &self,
-        alert_id: &str,
-        alert_name: &str,
-        symbol: &str,
-        mint: &str,
-        compound_condition: &CompoundCondition,
+        alert: &PriceAlert,
         triggered_price: f64,
+        triggered_volume: Option<f64>,
         conditions_met: &str,
-        notification_channels: &[NotificationChannel],
+        suppressed: bool,
     ) -> Result<AlertHistoryEntry, AlertError> {
         let id = uuid::Uuid::new_v4().to_string();
-        let now = Utc::now().to_rfc3339();
+        let triggered_at = Utc::now();
+        let now = triggered_at.to_rfc3339();
 
-        let compound_condition_json = serde_json::to_string(compound_condition)?;
-        let channels_json = serde_json::to_string(notification_channels)?;
+        let compound_condition_json = serde_json::to_string(&alert.compound_condition)?;
+        let channels_json = serde_json::to_string(&alert.notification_channels)?;
 
+        let mut tx = self.pool.begin().await?;
         sqlx::query(
             r#"
             INSERT INTO alert_history (
                 id, alert_id, alert_name, symbol, mint, compound_condition,
-                triggered_price, conditions_met, notification_channels,
-                triggered_at, bookmarked, outcome_notes, price_after_1h,
-                price_after_4h, price_after_24h, outcome_type, created_at
+                triggered_price, triggered_volume, conditions_met,
+                notification_channels, triggered_at, bookmarked, outcome_type,
+                suppressed, created_at
             )
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, 0, 'pending', ?12, ?11)
             "#,
         )
         .bind(&id)
-        .bind(alert_id)
-        .bind(alert_name)
-        .bind(symbol)
-        .bind(mint)
+        .bind(&alert.id)
+        .bind(&alert.name)
+        .bind(&alert.symbol)
+        .bind(&alert.mint)
         .bind(&compound_condition_json)
         .bind(triggered_price)
+        .bind(triggered_volume)
         .bind(conditions_met)
         .bind(&channels_json)
         .bind(&now)
-        .bind(0) // bookmarked = false
-        .bind::<Option<String>>(None) // outcome_notes
-        .bind::<Option<f64>>(None) // price_after_1h
-        .bind::<Option<f64>>(None) // price_after_4h
-        .bind::<Option<f64>>(None) // price_after_24h
-        .bind::<Option<String>>(Some("pending".to_string())) // outcome_type
-        .bind(&now)
+        .bind(suppressed)
+        .execute(&mut *tx)
+        .await?;
+
+        for horizon in SampleHorizon::ALL {
+            sqlx::query(
+                "INSERT INTO alert_outcome_samples (history_id, horizon, due_at) VALUES (?1, ?2, ?3)",
+            )
+            .bind(&id)
+            .bind(horizon.as_str())
+            .bind((triggered_at + horizon.delay()).to_rfc3339())
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        self.get_history_entry(&id).await
+    }
+
+    /// Takes the price samples that are due. `price` looks a token up by mint
+    /// and symbol; a sample without a price stays queued until it is too late
+    /// to mean anything. Returns how many samples were stored.
+    pub async fn process_due_samples(
+        &self,
+        now: DateTime<Utc>,
+        price: impl Fn(&str, &str) -> Option<f64>,
+    ) -> Result<usize, AlertError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT s.history_id, s.horizon, s.due_at, h.mint, h.symbol
+            FROM alert_outcome_samples s
+            JOIN alert_history h ON h.id = s.history_id
+            WHERE s.due_at <= ?1
+            ORDER BY s.due_at ASC
+            "#,
+        )
+        .bind(now.to_rfc3339())
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut stored = 0;
+        for row in rows {
+            let history_id: String = row.try_get("history_id")?;
+            let horizon_str: String = row.try_get("horizon")?;
+            let due_at: String = row.try_get("due_at")?;
+            let Some(horizon) = SampleHorizon::from_str(&horizon_str) else {
+                self.delete_sample(&history_id, &horizon_str).await?;
+                continue;
+            };
+            let late = DateTime::parse_from_rfc3339(&due_at)
+                .map(|due| {
+                    now - due.with_timezone(&Utc) > Duration::minutes(MAX_SAMPLE_LATENESS_MINUTES)
+                })
+                .unwrap_or(true);
+            let mint: String = row.try_get("mint")?;
+            let symbol: String = row.try_get("symbol")?;
+
+            match price(&mint, &symbol) {
+                Some(price) if !late => {
+                    sqlx::query(&format!(
+                        "UPDATE alert_history SET {} = ?1 WHERE id = ?2",
+                        horizon.column()
+                    ))
+                    .bind(price)
+                    .bind(&history_id)
+                    .execute(&self.pool)
+                    .await?;
+                    self.delete_sample(&history_id, horizon.as_str()).await?;
+                    stored += 1;
+                }
+                _ if late => self.delete_sample(&history_id, horizon.as_str()).await?,
+                _ => continue,
+            }
+            self.resolve_outcome(&history_id).await?;
+        }
+
+        Ok(stored)
+    }
+
+    async fn delete_sample(&self, history_id: &str, horizon: &str) -> Result<(), AlertError> {
+        sqlx::query("DELETE FROM alert_outcome_samples WHERE history_id = ?1 AND horizon = ?2")
+            .bind(history_id)
+            .bind(horizon)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Settles the outcome once no samples are left, from the 24h move or,
+    /// when that sample was missed, the 1h move.
+    async fn resolve_outcome(&self, history_id: &str) -> Result<(), AlertError> {
+        let remaining: i64 = sqlx::query(
+            "SELECT COUNT(*) AS count FROM alert_outcome_samples WHERE history_id = ?1",
+        )
+        .bind(history_id)
+        .fetch_one(&self.pool)
+        .await?
+        .try_get("count")?;
+        if remaining > 0 {
+            return Ok(());
+        }
+
+        let entry = self.get_history_entry(history_id).await?;
+        if entry.outcome_type.as_deref() != Some("pending") {
+            return Ok(());
+        }
+        let outcome = entry
+            .move_24h_pct
+            .or(entry.move_1h_pct)
+            .map_or("expired", |moved| {
+                classify_outcome(&entry.compound_condition, moved)
+            });
+        sqlx::query("UPDATE alert_history SET outcome_type = ?1 WHERE id = ?2")
+            .bind(outcome)
+            .bind(history_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn mark_trigger(
+        &self,
+        id: &str,
+        action: Option<TriggerAction>,
+    ) -> Result<AlertHistoryEntry, AlertError> {
+        let result = sqlx::query(
+            "UPDATE alert_history SET user_action = ?1, actioned_at = ?2 WHERE id = ?3",
+        )
+        .bind(action.map(|a| a.as_str()))
+        .bind(action.map(|_| Utc::now().to_rfc3339()))
+        .bind(id)
         .execute(&self.pool)
         .await?;
 
-        Ok(AlertHistoryEntry {
-            id,
-            alert_id: alert_id.to_string(),
-            alert_name: alert_name.to_string(),
-            symbol: symbol.to_string(),
-            mint: mint.to_string(),
-            compound_condition: compound_condition.clone(),
-            triggered_price,
-            conditions_met: conditions_met.to_string(),
-            notification_channels: notification_channels.to_vec(),
-            triggered_at: now.clone(),
-            bookmarked: false,
-            outcome_notes: None,
-            price_after_1h: None,
-            price_after_4h: None,
-            price_after_24h: None,
-            outcome_type: Some("pending".to_string()),
-            created_at: now,
+        if result.rows_affected() == 0 {
+            return Err(AlertError::NotFound(id.to_string()));
+        }
+        self.get_history_entry(id).await
+    }
+
+    pub async fn get_trigger_history(
+        &self,
+        alert_id: &str,
+    ) -> Result<Vec<AlertHistoryEntry>, AlertError> {
+        let rows = sqlx::query(&format!(
+            "SELECT {HISTORY_COLUMNS} FROM alert_history WHERE alert_id = ?1 ORDER BY triggered_at DESC"
+        ))
+        .bind(alert_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| self.row_to_history_entry(row))
+            .collect()
+    }
+
+    /// Triggers for `mint` between two RFC 3339 times, oldest first.
+    pub async fn triggers_for_mint(
+        &self,
+        mint: &str,
+        from: &str,
+        to: &str,
+        limit: i64,
+    ) -> Result<Vec<AlertHistoryEntry>, AlertError> {
+        let rows = sqlx::query(&format!(
+            "SELECT {HISTORY_COLUMNS} FROM alert_history
+             WHERE mint = ?1 AND triggered_at >= ?2 AND triggered_at <= ?3
+             ORDER BY triggered_at ASC LIMIT ?4"
+        ))
+        .bind(mint)
+        .bind(from)
+        .bind(to)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| self.row_to_history_entry(row))
+            .collect()
+    }
+
+    pub async fn effectiveness_report(
+        &self,
+        window_days: u32,
+    ) -> Result<AlertEffectivenessReport, AlertError> {
+        let since = (Utc::now() - Duration::days(i64::from(window_days))).to_rfc3339();
+        let entries = self
+            .list_history(AlertHistoryFilter {
+                symbol: None,
+                alert_name: None,
+                bookmarked_only: None,
+                outcome_type: None,
+                from_date: Some(since.clone()),
+                to_date: None,
+                limit: None,
+                offset: None,
+            })
+            .await?;
+
+        let alerts = effectiveness(&entries);
+        Ok(AlertEffectivenessReport {
+            window_days,
+            since,
+            total_triggers: entries.len(),
+            noisy_alerts: alerts.iter().filter(|alert| alert.noisy).count(),
+            alerts,
+            generated_at: Utc::now().to_rfc3339(),
         })
     }
 
@@ -197,16 +646,7 @@ impl AlertHistoryManager {
         &self,
         filter: AlertHistoryFilter,
     ) -> Result<Vec<AlertHistoryEntry>, AlertError> {
-        let mut query = String::from(
-            r#"
-            SELECT id, alert_id, alert_name, symbol, mint, compound_condition,
-                   triggered_price, conditions_met, notification_channels,
-                   triggered_at, bookmarked, outcome_notes, price_after_1h,
-                   price_after_4h, price_after_24h, outcome_type, created_at
-            FROM alert_history
-            WHERE 1=1
-            "#,
-        );
+        let mut query = format!("SELECT {HISTORY_COLUMNS} FROM alert_history WHERE 1=1");
 
         let mut params: Vec<String> = Vec::new();
 
@@ -265,16 +705,9 @@ impl AlertHistoryManager {
     }
 
     pub async fn get_history_entry(&self, id: &str) -> Result<AlertHistoryEntry, AlertError> {
-        let row = sqlx::query(
-            r#"
-            SELECT id, alert_id, alert_name, symbol, mint, compound_condition,
-                   triggered_price, conditions_met, notification_channels,
-                   triggered_at, bookmarked, outcome_notes, price_after_1h,
-                   price_after_4h, price_after_24h, outcome_type, created_at
-            FROM alert_history
-            WHERE id = ?1
-            "#,
-        )
+        let row = sqlx::query(&format!(
+            "SELECT {HISTORY_COLUMNS} FROM alert_history WHERE id = ?1"
+        ))
         .bind(id)
         .fetch_optional(&self.pool)
         .await?
@@ -327,7 +760,7 @@ impl AlertHistoryManager {
         .execute(&self.pool)
         .await?;
 
-        Ok(entry)
+        self.get_history_entry(id).await
     }
 
     pub async fn delete_history_entry(&self, id: &str) -> Result<(), AlertError> {
@@ -340,19 +773,24 @@ impl AlertHistoryManager {
             return Err(AlertError::NotFound(id.to_string()));
         }
 
+        sqlx::query("DELETE FROM alert_outcome_samples WHERE history_id = ?1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
         Ok(())
     }
 
     pub async fn get_statistics(&self) -> Result<AlertHistoryStats, AlertError> {
         let row = sqlx::query(
             r#"
-            SELECT 
+            SELECT
                 COUNT(*) as total_alerts,
-                SUM(CASE WHEN bookmarked = 1 THEN 1 ELSE 0 END) as bookmarked_count,
-                SUM(CASE WHEN outcome_type = 'profit' THEN 1 ELSE 0 END) as profit_count,
-                SUM(CASE WHEN outcome_type = 'loss' THEN 1 ELSE 0 END) as loss_count,
-                SUM(CASE WHEN outcome_type = 'neutral' THEN 1 ELSE 0 END) as neutral_count,
-                SUM(CASE WHEN outcome_type = 'pending' THEN 1 ELSE 0 END) as pending_count,
+                COALESCE(SUM(CASE WHEN bookmarked = 1 THEN 1 ELSE 0 END), 0) as bookmarked_count,
+                COALESCE(SUM(CASE WHEN outcome_type = 'profit' THEN 1 ELSE 0 END), 0) as profit_count,
+                COALESCE(SUM(CASE WHEN outcome_type = 'loss' THEN 1 ELSE 0 END), 0) as loss_count,
+                COALESCE(SUM(CASE WHEN outcome_type = 'neutral' THEN 1 ELSE 0 END), 0) as neutral_count,
+                COALESCE(SUM(CASE WHEN outcome_type = 'pending' THEN 1 ELSE 0 END), 0) as pending_count,
                 AVG(CASE WHEN price_after_1h IS NOT NULL THEN (price_after_1h - triggered_price) / triggered_price * 100 ELSE NULL END) as avg_change_1h,
                 AVG(CASE WHEN price_after_24h IS NOT NULL THEN (price_after_24h - triggered_price) / triggered_price * 100 ELSE NULL END) as avg_change_24h
             FROM alert_history
@@ -376,11 +814,11 @@ impl AlertHistoryManager {
     pub async fn export_to_csv(&self, filter: AlertHistoryFilter) -> Result<String, AlertError> {
         let entries = self.list_history(filter).await?;
 
-        let mut csv = String::from("ID,Alert Name,Symbol,Triggered Price,Triggered At,Bookmarked,Outcome Type,Outcome Notes,Price After 1h,Price After 4h,Price After 24h\n");
+        let mut csv = String::from("ID,Alert Name,Symbol,Triggered Price,Triggered At,Bookmarked,Outcome Type,Outcome Notes,Price After 1h,Price After 4h,Price After 24h,User Action,Suppressed\n");
 
         for entry in entries {
             csv.push_str(&format!(
-                "{},{},{},{},{},{},{},{},{},{},{}\n",
+                "{},{},{},{},{},{},{},{},{},{},{},{},{}\n",
                 entry.id,
                 entry.alert_name,
                 entry.symbol,
@@ -389,9 +827,20 @@ impl AlertHistoryManager {
                 entry.bookmarked,
                 entry.outcome_type.unwrap_or_default(),
                 entry.outcome_notes.unwrap_or_default().replace(',', ";"),
-                entry.price_after_1h.map(|p| p.to_string()).unwrap_or_default(),
-                entry.price_after_4h.map(|p| p.to_string()).unwrap_or_default(),
-                entry.price_after_24h.map(|p| p.to_string()).unwrap_or_default(),
+                entry
+                    .price_after_1h
+                    .map(|p| p.to_string())
+                    .unwrap_or_default(),
+                entry
+                    .price_after_4h
+                    .map(|p| p.to_string())
+                    .unwrap_or_default(),
+                entry
+                    .price_after_24h
+                    .map(|p| p.to_string())
+                    .unwrap_or_default(),
+                entry.user_action.map(|a| a.as_str()).unwrap_or_default(),
+                entry.suppressed,
             ));
         }
 
@@ -403,14 +852,17 @@ impl AlertHistoryManager {
         row: sqlx::sqlite::SqliteRow,
     ) -> Result<AlertHistoryEntry, AlertError> {
         let compound_condition_json: String = row.try_get("compound_condition")?;
-        let compound_condition: CompoundCondition =
-            serde_json::from_str(&compound_condition_json)?;
+        let compound_condition: CompoundCondition = serde_json::from_str(&compound_condition_json)?;
 
         let channels_json: String = row.try_get("notification_channels")?;
-        let notification_channels: Vec<NotificationChannel> =
-            serde_json::from_str(&channels_json)?;
+        let notification_channels: Vec<NotificationChannel> = serde_json::from_str(&channels_json)?;
 
         let bookmarked_int: i32 = row.try_get("bookmarked")?;
+        let triggered_price: f64 = row.try_get("triggered_price")?;
+        let price_after_1h: Option<f64> = row.try_get("price_after_1h")?;
+        let price_after_24h: Option<f64> = row.try_get("price_after_24h")?;
+        let user_action: Option<String> = row.try_get("user_action")?;
+        let suppressed_int: i32 = row.try_get("suppressed")?;
 
         Ok(AlertHistoryEntry {
             id: row.try_get("id")?,
@@ -419,35 +871,79 @@ impl AlertHistoryManager {
             symbol: row.try_get("symbol")?,
             mint: row.try_get("mint")?,
             compound_condition,
-            triggered_price: row.try_get("triggered_price")?,
+            triggered_price,
+            triggered_volume: row.try_get("triggered_volume")?,
             conditions_met: row.try_get("conditions_met")?,
             notification_channels,
             triggered_at: row.try_get("triggered_at")?,
             bookmarked: bookmarked_int == 1,
             outcome_notes: row.try_get("outcome_notes")?,
-            price_after_1h: row.try_get("price_after_1h")?,
+            price_after_1h,
             price_after_4h: row.try_get("price_after_4h")?,
-            price_after_24h: row.try_get("price_after_24h")?,
+            price_after_24h,
+            move_1h_pct: move_pct(triggered_price, price_after_1h),
+            move_24h_pct: move_pct(triggered_price, price_after_24h),
             outcome_type: row.try_get("outcome_type")?,
+            user_action: user_action.as_deref().and_then(TriggerAction::from_str),
+            actioned_at: row.try_get("actioned_at")?,
+            suppressed: suppressed_int == 1,
             created_at: row.try_get("created_at")?,
         })
     }
 }
 
 fn alert_history_db_path(app: &AppHandle) -> Result<PathBuf, AlertError> {
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .ok_or_else(|| AlertError::Internal("Unable to resolve app data directory".to_string()))?;
+    let app_data_dir = app.app_data_root().map_err(|e| {
+        AlertError::Internal(format!("Unable to resolve app data directory: {}", e))
+    })?;
 
     std::fs::create_dir_all(&app_data_dir)?;
     Ok(app_data_dir.join(ALERT_HISTORY_DB_FILE))
 }
 
+/// Records a trigger in the history. Failures are logged so history
+/// bookkeeping never stops an alert from firing.
+pub async fn record_alert_trigger(
+    app: &AppHandle,
+    alert: &PriceAlert,
+    price: f64,
+    volume: Option<f64>,
+    conditions_met: &str,
+    suppressed: bool,
+) {
+    let Some(history) = app.try_state::<SharedAlertHistoryManager>() else {
+        return;
+    };
+    let history = history.read().await;
+    if let Err(e) = history
+        .record_triggered_alert(alert, price, volume, conditions_met, suppressed)
+        .await
+    {
+        eprintln!("Failed to record trigger of alert {}: {}", alert.id, e);
+    }
+}
+
+/// Takes due post-trigger price samples once a minute. Pending samples live in
+/// the database, so ones that came due while the app was closed are taken on
+/// the next start, or dropped when they are too late.
+pub async fn run_alert_outcome_sampler(history: SharedAlertHistoryManager) {
+    let mut tick = tokio::time::interval(std::time::Duration::from_secs(60));
+    loop {
+        tick.tick().await;
+        let engine = crate::core::price_engine::get_price_engine();
+        let price =
+            |mint: &str, symbol: &str| engine.get_price(mint).or_else(|| engine.get_price(symbol));
+        let history = history.read().await;
+        if let Err(e) = history.process_due_samples(Utc::now(), price).await {
+            eprintln!("Failed to sample alert outcomes: {}", e);
+        }
+    }
+}
+
 // Tauri commands
 #[tauri::command]
 pub async fn alert_history_list(
-    manager: State<'_, crate::alerts::SharedAlertHistoryManager>,
+    manager: State<'_, SharedAlertHistoryManager>,
     filter: AlertHistoryFilter,
 ) -> Result<Vec<AlertHistoryEntry>, String> {
     let mgr = manager.read().await;
@@ -456,7 +952,7 @@ pub async fn alert_history_list(
 
 #[tauri::command]
 pub async fn alert_history_get(
-    manager: State<'_, crate::alerts::SharedAlertHistoryManager>,
+    manager: State<'_, SharedAlertHistoryManager>,
     id: String,
 ) -> Result<AlertHistoryEntry, String> {
     let mgr = manager.read().await;
@@ -465,7 +961,7 @@ pub async fn alert_history_get(
 
 #[tauri::command]
 pub async fn alert_history_update(
-    manager: State<'_, crate::alerts::SharedAlertHistoryManager>,
+    manager: State<'_, SharedAlertHistoryManager>,
     id: String,
     req: UpdateAlertHistoryRequest,
 ) -> Result<AlertHistoryEntry, String> {
@@ -477,7 +973,7 @@ pub async fn alert_history_update(
 
 #[tauri::command]
 pub async fn alert_history_delete(
-    manager: State<'_, crate::alerts::SharedAlertHistoryManager>,
+    manager: State<'_, SharedAlertHistoryManager>,
     id: String,
 ) -> Result<(), String> {
     let mgr = manager.read().await;
@@ -488,7 +984,7 @@ pub async fn alert_history_delete(
 
 #[tauri::command]
 pub async fn alert_history_stats(
-    manager: State<'_, crate::alerts::SharedAlertHistoryManager>,
+    manager: State<'_, SharedAlertHistoryManager>,
 ) -> Result<AlertHistoryStats, String> {
     let mgr = manager.read().await;
     mgr.get_statistics().await.map_err(|e| e.to_string())
@@ -496,9 +992,231 @@ pub async fn alert_history_stats(
 
 #[tauri::command]
 pub async fn alert_history_export_csv(
-    manager: State<'_, crate::alerts::SharedAlertHistoryManager>,
+    manager: State<'_, SharedAlertHistoryManager>,
     filter: AlertHistoryFilter,
 ) -> Result<String, String> {
     let mgr = manager.read().await;
     mgr.export_to_csv(filter).await.map_err(|e| e.to_string())
 }
+
+#[tauri::command]
+pub async fn alert_get_trigger_history(
+    manager: State<'_, SharedAlertHistoryManager>,
+    alert_id: String,
+) -> Result<Vec<AlertHistoryEntry>, String> {
+    let mgr = manager.read().await;
+    mgr.get_trigger_history(&alert_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Marks a trigger as acted on or ignored; `None` clears the mark.
+#[tauri::command]
+pub async fn alert_mark_trigger(
+    manager: State<'_, SharedAlertHistoryManager>,
+    id: String,
+    action: Option<TriggerAction>,
+) -> Result<AlertHistoryEntry, String> {
+    let mgr = manager.read().await;
+    mgr.mark_trigger(&id, action)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn alerts_effectiveness_report(
+    manager: State<'_, SharedAlertHistoryManager>,
+    window_days: Option<u32>,
+) -> Result<AlertEffectivenessReport, String> {
+    let mgr = manager.read().await;
+    mgr.effectiveness_report(window_days.unwrap_or(DEFAULT_REPORT_WINDOW_DAYS))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alerts::price_alerts::{AlertCondition, AlertState, LogicalOperator};
+    use crate::utils::test_pool;
+
+    async fn manager() -> AlertHistoryManager {
+        let pool = test_pool().await;
+        AlertHistoryManager::with_pool(pool).await.unwrap()
+    }
+
+    fn condition(condition_type: AlertConditionType) -> CompoundCondition {
+        CompoundCondition {
+            conditions: vec![AlertCondition {
+                condition_type,
+                value: 100.0,
+                timeframe_minutes: None,
+            }],
+            operator: LogicalOperator::And,
+        }
+    }
+
+    fn alert(id: &str, condition_type: AlertConditionType) -> PriceAlert {
+        PriceAlert {
+            id: id.to_string(),
+            name: format!("{id} alert"),
+            symbol: "SOL".to_string(),
+            mint: "So11111111111111111111111111111111111111112".to_string(),
+            watchlist_id: None,
+            compound_condition: condition(condition_type),
+            notification_channels: vec![NotificationChannel::InApp],
+            cooldown_minutes: 15,
+            state: AlertState::Active,
+            last_triggered_at: None,
+            cooldown_until: None,
+            snoozed_until: None,
+            muted_until: None,
+            created_at: Utc::now().to_rfc3339(),
+            updated_at: Utc::now().to_rfc3339(),
+        }
+    }
+
+    #[tokio::test]
+    async fn samples_prices_after_trigger_and_resolves_the_outcome() {
+        let history = manager().await;
+        let entry = history
+            .record_triggered_alert(
+                &alert("breakout", AlertConditionType::Above),
+                100.0,
+                Some(5e6),
+                "Price above threshold $100.00",
+                false,
+            )
+            .await
+            .unwrap();
+        assert_eq!(entry.outcome_type.as_deref(), Some("pending"));
+        assert_eq!(entry.triggered_volume, Some(5e6));
+
+        let start = Utc::now();
+        let none = |_: &str, _: &str| None;
+        let price = |mint: &str, _: &str| mint.starts_with("So1").then_some(95.0);
+
+        // Nothing is due yet, and an unknown price keeps the sample queued.
+        assert_eq!(history.process_due_samples(start, price).await.unwrap(), 0);
+        let after_1h = start + Duration::minutes(61);
+        assert_eq!(
+            history.process_due_samples(after_1h, none).await.unwrap(),
+            0
+        );
+        assert_eq!(
+            history.process_due_samples(after_1h, price).await.unwrap(),
+            1
+        );
+
+        let entry = history.get_history_entry(&entry.id).await.unwrap();
+        assert_eq!(entry.price_after_1h, Some(95.0));
+        assert!((entry.move_1h_pct.unwrap() + 5.0).abs() < 1e-9);
+        assert_eq!(entry.outcome_type.as_deref(), Some("pending"));
+
+        // The 24h sample comes due while the app is closed and is missed.
+        let much_later = start + Duration::hours(30);
+        assert_eq!(
+            history
+                .process_due_samples(much_later, price)
+                .await
+                .unwrap(),
+            0
+        );
+        let entry = history.get_history_entry(&entry.id).await.unwrap();
+        assert_eq!(entry.price_after_24h, None);
+        // A breakout alert followed by a 5% drop.
+        assert_eq!(entry.outcome_type.as_deref(), Some("loss"));
+
+        let marked = history
+            .mark_trigger(&entry.id, Some(TriggerAction::Ignored))
+            .await
+            .unwrap();
+        assert_eq!(marked.user_action, Some(TriggerAction::Ignored));
+        assert_eq!(
+            history.get_trigger_history("breakout").await.unwrap().len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn suppressed_triggers_are_kept_and_counted() {
+        let history = manager().await;
+        let muted = alert("muted", AlertConditionType::Below);
+        for suppressed in [true, false] {
+            history
+                .record_triggered_alert(&muted, 100.0, None, "Price below", suppressed)
+                .await
+                .unwrap();
+        }
+
+        let entries = history.get_trigger_history("muted").await.unwrap();
+        assert_eq!(entries.iter().filter(|entry| entry.suppressed).count(), 1);
+        // Suppressed triggers still get their outcome sampled.
+        let after_1h = Utc::now() + Duration::minutes(61);
+        let price = |_: &str, _: &str| Some(90.0);
+        assert_eq!(
+            history.process_due_samples(after_1h, price).await.unwrap(),
+            2
+        );
+
+        let report = history.effectiveness_report(30).await.unwrap();
+        assert_eq!(report.total_triggers, 2);
+        assert_eq!(report.alerts[0].triggers, 2);
+        assert_eq!(report.alerts[0].suppressed, 1);
+    }
+
+    #[test]
+    fn effectiveness_flags_alerts_that_are_mostly_noise() {
+        let entry = |alert_id: &str, outcome: &str, moved: f64| AlertHistoryEntry {
+            id: uuid::Uuid::new_v4().to_string(),
+            alert_id: alert_id.to_string(),
+            alert_name: alert_id.to_string(),
+            symbol: "SOL".to_string(),
+            mint: "mint".to_string(),
+            compound_condition: condition(AlertConditionType::VolumeSpike),
+            triggered_price: 100.0,
+            triggered_volume: None,
+            conditions_met: String::new(),
+            notification_channels: vec![],
+            triggered_at: Utc::now().to_rfc3339(),
+            bookmarked: false,
+            outcome_notes: None,
+            price_after_1h: None,
+            price_after_4h: None,
+            price_after_24h: None,
+            move_1h_pct: None,
+            move_24h_pct: Some(moved),
+            outcome_type: Some(outcome.to_string()),
+            user_action: None,
+            actioned_at: None,
+            suppressed: false,
+            created_at: Utc::now().to_rfc3339(),
+        };
+        let entries = vec![
+            entry("chatty", "neutral", 0.5),
+            entry("chatty", "neutral", -1.0),
+            entry("chatty", "neutral", 0.2),
+            entry("chatty", "profit", 6.0),
+            entry("sharp", "profit", 8.0),
+            entry("sharp", "loss", -4.0),
+            entry("sharp", "profit", 5.0),
+        ];
+
+        let report = effectiveness(&entries);
+        assert_eq!(report[0].alert_id, "chatty");
+        assert!(report[0].noisy);
+        assert_eq!(report[0].noise_rate, Some(0.75));
+        assert!(!report[1].noisy);
+        assert_eq!(report[1].favorable, 2);
+        assert!((report[1].avg_move_24h_pct.unwrap() - 3.0).abs() < 1e-9);
+
+        // Direction-less alerts count any large move as favorable; directed
+        // ones only a move their way.
+        let volume = condition(AlertConditionType::VolumeSpike);
+        let below = condition(AlertConditionType::Below);
+        assert_eq!(classify_outcome(&volume, -5.0), "profit");
+        assert_eq!(classify_outcome(&below, -5.0), "profit");
+        assert_eq!(classify_outcome(&below, 5.0), "loss");
+        assert_eq!(classify_outcome(&below, 1.0), "neutral");
+    }
+}
@@ -1,3 +1,4 @@
+use crate::config::DataPaths;
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use sqlx::{Pool, Row, Sqlite, SqlitePool};
@@ -475,8 +476,7 @@ impl AlertTemplateManager {
 
 fn alert_templates_db_path(app: &AppHandle) -> Result<PathBuf, AlertError> {
     let app_data_dir = app
-        .path()
-        .app_data_dir()
+        .app_data_root()
         .ok_or_else(|| AlertError::Internal("Unable to resolve app data directory".to_string()))?;
 
     std::fs::create_dir_all(&app_data_dir)?;
@@ -16,6 +16,7 @@ pub enum ConditionType {
     PriceRange,
     Volatility,
     TrendChange,
+    OrderbookImbalance,
 }
 
 impl ConditionType {
@@ -33,6 +34,7 @@ impl ConditionType {
             ConditionType::PriceRange => "price_range",
             ConditionType::Volatility => "volatility",
             ConditionType::TrendChange => "trend_change",
+            ConditionType::OrderbookImbalance => "orderbook_imbalance",
         }
     }
 
@@ -50,6 +52,7 @@ impl ConditionType {
             "price_range" => Some(ConditionType::PriceRange),
             "volatility" => Some(ConditionType::Volatility),
             "trend_change" | "momentum_shift" => Some(ConditionType::TrendChange),
+            "orderbook_imbalance" | "book_imbalance" => Some(ConditionType::OrderbookImbalance),
             _ => None,
         }
     }
@@ -122,6 +125,10 @@ impl Default for ComparisonOperator {
 #[serde(rename_all = "camelCase")]
 pub struct MarketData {
     pub symbol: String,
+
+    /// Lets the order book imbalance be filled in when the caller leaves it out.
+    #[serde(default)]
+    pub mint: Option<String>,
     pub current_price: f64,
 
     #[serde(default)]
@@ -142,6 +149,11 @@ pub struct MarketData {
     #[serde(default)]
     pub price_change_percentage: Option<f64>,
 
+    /// Bid/ask imbalance within 2% of the mid price, from -1.0 (all asks) to
+    /// 1.0 (all bids).
+    #[serde(default)]
+    pub orderbook_imbalance: Option<f64>,
+
     #[serde(default)]
     pub timestamp: Option<String>,
 }
@@ -194,6 +206,7 @@ impl Condition {
             ConditionType::PriceRange => self.evaluate_price_range(market_data),
             ConditionType::Volatility => self.evaluate_volatility(market_data),
             ConditionType::TrendChange => self.evaluate_trend_change(market_data),
+            ConditionType::OrderbookImbalance => self.evaluate_orderbook_imbalance(market_data),
         }
     }
 
@@ -590,4 +603,51 @@ impl Condition {
             }
         }
     }
+
+    /// Compares the imbalance with `comparison_operator` (default greater),
+    /// so a negative threshold with `less` catches ask-heavy books.
+    fn evaluate_orderbook_imbalance(&self, market_data: &MarketData) -> ConditionEvaluationResult {
+        if let Some(imbalance) = market_data.orderbook_imbalance {
+            let threshold = self.parameters.threshold.unwrap_or(0.0);
+            let met = match self
+                .parameters
+                .comparison_operator
+                .as_ref()
+                .unwrap_or(&ComparisonOperator::Greater)
+            {
+                ComparisonOperator::Greater => imbalance > threshold,
+                ComparisonOperator::GreaterOrEqual => imbalance >= threshold,
+                ComparisonOperator::Less => imbalance < threshold,
+                ComparisonOperator::LessOrEqual => imbalance <= threshold,
+                ComparisonOperator::Equal => (imbalance - threshold).abs() < f64::EPSILON,
+                ComparisonOperator::Between => {
+                    imbalance >= self.parameters.min_value.unwrap_or(-1.0)
+                        && imbalance <= self.parameters.max_value.unwrap_or(1.0)
+                }
+            };
+
+            ConditionEvaluationResult {
+                condition_id: self.condition_id(),
+                met,
+                message: format!(
+                    "Order book imbalance {:.2} {} condition",
+                    imbalance,
+                    if met { "meets" } else { "does not meet" }
+                ),
+                confidence: 1.0,
+                data: Some(serde_json::json!({
+                    "orderbookImbalance": imbalance,
+                    "threshold": threshold,
+                })),
+            }
+        } else {
+            ConditionEvaluationResult {
+                condition_id: self.condition_id(),
+                met: false,
+                message: "Order book data unavailable".to_string(),
+                confidence: 0.0,
+                data: None,
+            }
+        }
+    }
 }
@@ -176,6 +176,7 @@ impl DryRunSimulator {
             (
                 MarketData {
                     symbol: symbol.to_string(),
+                    mint: None,
                     current_price,
                     price_24h_ago: Some(current_price * 0.9),
                     volume_24h: Some(1_000_000.0),
@@ -183,6 +184,7 @@ impl DryRunSimulator {
                     liquidity: Some(2_000_000.0),
                     volatility: Some(5.0),
                     price_change_percentage: Some(10.0),
+                    orderbook_imbalance: None,
                     timestamp: Some(Utc::now().to_rfc3339()),
                 },
                 None,
@@ -190,6 +192,7 @@ impl DryRunSimulator {
             (
                 MarketData {
                     symbol: symbol.to_string(),
+                    mint: None,
                     current_price: current_price * 1.2,
                     price_24h_ago: Some(current_price),
                     volume_24h: Some(5_000_000.0),
@@ -197,6 +200,7 @@ impl DryRunSimulator {
                     liquidity: Some(2_000_000.0),
                     volatility: Some(15.0),
                     price_change_percentage: Some(20.0),
+                    orderbook_imbalance: None,
                     timestamp: Some(Utc::now().to_rfc3339()),
                 },
                 None,
@@ -204,6 +208,7 @@ impl DryRunSimulator {
             (
                 MarketData {
                     symbol: symbol.to_string(),
+                    mint: None,
                     current_price: current_price * 0.8,
                     price_24h_ago: Some(current_price),
                     volume_24h: Some(800_000.0),
@@ -211,6 +216,7 @@ impl DryRunSimulator {
                     liquidity: Some(2_000_000.0),
                     volatility: Some(12.0),
                     price_change_percentage: Some(-20.0),
+                    orderbook_imbalance: None,
                     timestamp: Some(Utc::now().to_rfc3339()),
                 },
                 None,
@@ -327,6 +333,7 @@ mod tests {
             owner_id: Some("user1".to_string()),
             shared_with: vec![],
             tags: vec![],
+            pack: None,
             created_at: Utc::now().to_rfc3339(),
             updated_at: Utc::now().to_rfc3339(),
         };
@@ -391,6 +398,7 @@ mod tests {
             owner_id: Some("user1".to_string()),
             shared_with: vec![],
             tags: vec![],
+            pack: None,
             created_at: Utc::now().to_rfc3339(),
             updated_at: Utc::now().to_rfc3339(),
         };
@@ -1,14 +1,17 @@
 use super::actions::Action;
-use super::conditions::{MarketData, WhaleActivity};
+use super::conditions::{ConditionType, MarketData, WhaleActivity};
 use super::dry_run::{execute_rule_with_dry_run, DryRunResult, DryRunSimulator};
 use super::rule_engine::{AlertRule, Permission, RuleExecutionResult, RuleNode, SharedAccess};
 use crate::alerts::logic::serialization::{deserialize_rule_from_json, serialize_rule_to_json};
+use crate::config::DataPaths;
+use crate::market::orderbook::{OrderBookResult, OrderBookService, SharedOrderBookService};
+use crate::utils::ensure_column;
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
-use sqlx::{Pool, Row, Sqlite, SqlitePool};
+use sqlx::{Pool, Row, Sqlite, SqliteConnection, SqlitePool};
 use std::path::PathBuf;
 use std::sync::Arc;
-use tauri::{AppHandle, Manager, State};
+use tauri::{AppHandle, State};
 use tokio::sync::RwLock;
 
 const SMART_ALERTS_DB_FILE: &str = "smart_alerts.db";
@@ -27,6 +30,9 @@ pub enum SmartAlertError {
     #[error("rule serialization error: {0}")]
     RuleSerialization(#[from] crate::alerts::logic::serialization::SerializationError),
 
+    #[error("rule pack error: {0}")]
+    Pack(#[from] crate::alerts::logic::packs::RulePackError),
+
     #[error("rule not found: {0}")]
     NotFound(String),
 
@@ -145,11 +151,14 @@ impl SmartAlertManager {
         .execute(&self.pool)
         .await?;
 
+        ensure_column(&self.pool, "smart_alerts", "pack_id", "TEXT").await?;
+
         sqlx::query(
             r#"
             CREATE INDEX IF NOT EXISTS idx_smart_alerts_owner ON smart_alerts(owner_id);
             CREATE INDEX IF NOT EXISTS idx_smart_alerts_team ON smart_alerts(team_id);
             CREATE INDEX IF NOT EXISTS idx_smart_alerts_enabled ON smart_alerts(enabled);
+            CREATE INDEX IF NOT EXISTS idx_smart_alerts_pack ON smart_alerts(pack_id);
             "#,
         )
         .execute(&self.pool)
@@ -183,6 +192,7 @@ impl SmartAlertManager {
             team_id: req.team_id.take(),
             shared_with: req.shared_with,
             tags: req.tags,
+            pack: None,
             created_at: now.clone(),
             updated_at: now.clone(),
         };
@@ -318,13 +328,54 @@ impl SmartAlertManager {
         self.row_to_rule(row)
     }
 
+    /// Swaps out every rule installed from `pack_id` for `rules` in one
+    /// transaction, so an updated pack never leaves a mix of versions behind.
+    /// Returns how many earlier rules were replaced.
+    pub async fn replace_pack_rules(
+        &self,
+        pack_id: &str,
+        rules: &[AlertRule],
+    ) -> Result<u64, SmartAlertError> {
+        let mut tx = self.pool.begin().await?;
+
+        let replaced = sqlx::query("DELETE FROM smart_alerts WHERE pack_id = ?1")
+            .bind(pack_id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+
+        for rule in rules {
+            write_rule(&mut *tx, rule).await?;
+        }
+
+        tx.commit().await?;
+        Ok(replaced)
+    }
+
+    /// Deletes every rule installed from `pack_id`, returning how many went.
+    pub async fn remove_pack(&self, pack_id: &str) -> Result<u64, SmartAlertError> {
+        let removed = sqlx::query("DELETE FROM smart_alerts WHERE pack_id = ?1")
+            .bind(pack_id)
+            .execute(&self.pool)
+            .await?
+            .rows_affected();
+
+        if removed == 0 {
+            return Err(SmartAlertError::NotFound(pack_id.to_string()));
+        }
+
+        Ok(removed)
+    }
+
     pub async fn dry_run(
         &self,
         id: &str,
-        market_data: MarketData,
+        mut market_data: MarketData,
         whale_activity: Option<WhaleActivity>,
+        orderbook: Option<&OrderBookService>,
     ) -> Result<DryRunResult, SmartAlertError> {
         let rule = self.get_rule(id).await?;
+        fill_orderbook_imbalance(&rule, &mut market_data, orderbook).await;
         Ok(DryRunSimulator::simulate_rule(
             &rule,
             &market_data,
@@ -335,11 +386,13 @@ impl SmartAlertManager {
     pub async fn execute(
         &self,
         id: &str,
-        market_data: MarketData,
+        mut market_data: MarketData,
         whale_activity: Option<WhaleActivity>,
         dry_run: bool,
+        orderbook: Option<&OrderBookService>,
     ) -> Result<RuleExecutionResult, SmartAlertError> {
         let rule = self.get_rule(id).await?;
+        fill_orderbook_imbalance(&rule, &mut market_data, orderbook).await;
         Ok(execute_rule_with_dry_run(
             &rule,
             &market_data,
@@ -349,46 +402,8 @@ impl SmartAlertManager {
     }
 
     async fn persist_rule(&self, rule: &AlertRule) -> Result<(), SmartAlertError> {
-        let rule_json = serialize_rule_to_json(rule)?;
-        let shared_with_json = serde_json::to_string(&rule.shared_with)?;
-        let tags_json = serde_json::to_string(&rule.tags)?;
-
-        sqlx::query(
-            r#"
-            INSERT INTO smart_alerts (
-                id, name, description, rule_json, enabled, symbol,
-                owner_id, team_id, shared_with, tags, created_at, updated_at
-            )
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
-            ON CONFLICT(id) DO UPDATE SET
-                name = excluded.name,
-                description = excluded.description,
-                rule_json = excluded.rule_json,
-                enabled = excluded.enabled,
-                symbol = excluded.symbol,
-                owner_id = excluded.owner_id,
-                team_id = excluded.team_id,
-                shared_with = excluded.shared_with,
-                tags = excluded.tags,
-                updated_at = excluded.updated_at
-            "#,
-        )
-        .bind(&rule.id)
-        .bind(&rule.name)
-        .bind(&rule.description)
-        .bind(&rule_json)
-        .bind(rule.enabled)
-        .bind(&rule.symbol)
-        .bind(&rule.owner_id)
-        .bind(&rule.team_id)
-        .bind(&shared_with_json)
-        .bind(&tags_json)
-        .bind(&rule.created_at)
-        .bind(&rule.updated_at)
-        .execute(&self.pool)
-        .await?;
-
-        Ok(())
+        let mut conn = self.pool.acquire().await?;
+        write_rule(&mut conn, rule).await
     }
 
     fn row_to_rule(&self, row: sqlx::sqlite::SqliteRow) -> Result<AlertRule, SmartAlertError> {
@@ -416,9 +431,80 @@ impl SmartAlertManager {
     }
 }
 
+async fn write_rule(conn: &mut SqliteConnection, rule: &AlertRule) -> Result<(), SmartAlertError> {
+    let rule_json = serialize_rule_to_json(rule)?;
+    let shared_with_json = serde_json::to_string(&rule.shared_with)?;
+    let tags_json = serde_json::to_string(&rule.tags)?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO smart_alerts (
+            id, name, description, rule_json, enabled, symbol,
+            owner_id, team_id, shared_with, tags, created_at, updated_at, pack_id
+        )
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
+        ON CONFLICT(id) DO UPDATE SET
+            name = excluded.name,
+            description = excluded.description,
+            rule_json = excluded.rule_json,
+            enabled = excluded.enabled,
+            symbol = excluded.symbol,
+            owner_id = excluded.owner_id,
+            team_id = excluded.team_id,
+            shared_with = excluded.shared_with,
+            tags = excluded.tags,
+            updated_at = excluded.updated_at,
+            pack_id = excluded.pack_id
+        "#,
+    )
+    .bind(&rule.id)
+    .bind(&rule.name)
+    .bind(&rule.description)
+    .bind(&rule_json)
+    .bind(rule.enabled)
+    .bind(&rule.symbol)
+    .bind(&rule.owner_id)
+    .bind(&rule.team_id)
+    .bind(&shared_with_json)
+    .bind(&tags_json)
+    .bind(&rule.created_at)
+    .bind(&rule.updated_at)
+    .bind(rule.pack.as_ref().map(|pack| pack.pack_id.as_str()))
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Reads the order book imbalance for rules that use it when the caller
+/// didn't supply one. Without a mint or a book the condition stays unmet.
+async fn fill_orderbook_imbalance(
+    rule: &AlertRule,
+    market_data: &mut MarketData,
+    orderbook: Option<&OrderBookService>,
+) {
+    if market_data.orderbook_imbalance.is_some()
+        || !rule
+            .rule_tree
+            .uses_condition(&ConditionType::OrderbookImbalance)
+    {
+        return;
+    }
+    let (Some(orderbook), Some(mint)) = (orderbook, market_data.mint.as_deref()) else {
+        return;
+    };
+    match orderbook.snapshot(mint).await {
+        Ok(OrderBookResult::Supported { snapshot }) => {
+            market_data.orderbook_imbalance = snapshot.imbalance;
+        }
+        Ok(OrderBookResult::Unsupported { .. }) => {}
+        Err(e) => eprintln!("Failed to read order book for {}: {}", mint, e),
+    }
+}
+
 fn smart_alerts_db_path(app: &AppHandle) -> Result<PathBuf, SmartAlertError> {
     let app_handle = app.clone();
-    let mut app_data_dir = app_handle.path().app_data_dir().map_err(|err| {
+    let mut app_data_dir = app_handle.app_data_root().map_err(|err| {
         SmartAlertError::Internal(format!("Unable to resolve app data directory: {err}"))
     })?;
 
@@ -481,11 +567,17 @@ pub async fn smart_alert_dry_run(
     id: String,
     market_data: MarketData,
     whale_activity: Option<WhaleActivity>,
+    orderbook: State<'_, SharedOrderBookService>,
 ) -> Result<DryRunResult, String> {
     let mgr = manager.read().await;
-    mgr.dry_run(&id, market_data, whale_activity)
-        .await
-        .map_err(|e| e.to_string())
+    mgr.dry_run(
+        &id,
+        market_data,
+        whale_activity,
+        Some(orderbook.inner().as_ref()),
+    )
+    .await
+    .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -495,9 +587,16 @@ pub async fn smart_alert_execute(
     market_data: MarketData,
     whale_activity: Option<WhaleActivity>,
     dry_run: bool,
+    orderbook: State<'_, SharedOrderBookService>,
 ) -> Result<RuleExecutionResult, String> {
     let mgr = manager.read().await;
-    mgr.execute(&id, market_data, whale_activity, dry_run)
-        .await
-        .map_err(|e| e.to_string())
+    mgr.execute(
+        &id,
+        market_data,
+        whale_activity,
+        dry_run,
+        Some(orderbook.inner().as_ref()),
+    )
+    .await
+    .map_err(|e| e.to_string())
 }
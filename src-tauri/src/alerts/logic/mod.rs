@@ -2,6 +2,7 @@ pub mod actions;
 pub mod conditions;
 pub mod dry_run;
 pub mod manager;
+pub mod packs;
 pub mod rule_engine;
 pub mod serialization;
 
@@ -9,5 +10,6 @@ pub use actions::*;
 pub use conditions::*;
 pub use dry_run::*;
 pub use manager::*;
+pub use packs::*;
 pub use rule_engine::*;
 pub use serialization::*;
@@ -0,0 +1,1122 @@
+//! Rule packs: shareable sets of smart alert rules with declared parameters.
+//!
+//! A pack stores each rule as a JSON template. Values the importer should
+//! choose are replaced by `"{{key}}"` placeholders, and each key is declared
+//! in the pack's parameter list. Importing fills the placeholders and creates
+//! the rules disabled, tagged with the pack they came from.
+
+use super::actions::{Action, ActionType};
+use super::manager::{SharedSmartAlertManager, SmartAlertError, SmartRuleFilter};
+use super::rule_engine::{AlertRule, RuleNode};
+use crate::notifications::email::EmailManager;
+use crate::notifications::router::SharedNotificationRouter;
+use crate::security::keystore::Keystore;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use tauri::{AppHandle, Manager, State};
+
+pub const RULE_PACK_FORMAT_VERSION: &str = "1.0";
+pub const MAX_PACK_BYTES: usize = 256 * 1024;
+const MAX_PACK_RULES: usize = 50;
+const MAX_PACK_ID_LEN: usize = 64;
+const MAX_TEXT_PARAMETER_LEN: usize = 512;
+const MAX_TOKEN_PARAMETER_LEN: usize = 64;
+
+/// Action parameters that point at the exporter's own channels. They must be
+/// bound to a parameter or removed before a pack can be shared.
+const PERSONAL_TARGETS: &[&str] = &["webhookUrl", "chatId", "channelId", "emailTo"];
+
+#[derive(Debug, thiserror::Error)]
+pub enum RulePackError {
+    #[error("invalid rule pack: {0}")]
+    InvalidFormat(String),
+
+    #[error("unsupported rule pack format {0}")]
+    UnsupportedVersion(String),
+
+    #[error("rule pack is too large")]
+    TooLarge,
+
+    #[error("placeholder '{0}' is not declared as a parameter")]
+    UndeclaredPlaceholder(String),
+
+    #[error("missing value for parameter '{0}'")]
+    MissingParameter(String),
+
+    #[error("invalid value for parameter '{key}': {reason}")]
+    InvalidParameter { key: String, reason: String },
+
+    #[error("rule {index} ('{name}') is invalid: {reason}")]
+    InvalidRule {
+        index: usize,
+        name: String,
+        reason: String,
+    },
+
+    #[error("json error: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RulePack {
+    pub format_version: String,
+    /// Stable across versions so a re-import replaces the earlier rules.
+    pub pack_id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub version: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    pub created_at: String,
+    #[serde(default)]
+    pub parameters: Vec<PackParameter>,
+    /// Rule templates; see [`PackRuleTemplate`] for the shape.
+    pub rules: Vec<Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PackParameter {
+    pub key: String,
+    pub label: String,
+    #[serde(default)]
+    pub description: String,
+    pub kind: PackParameterKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PackParameterKind {
+    Number {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        min: Option<f64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max: Option<f64>,
+    },
+    /// A token symbol or mint address.
+    Token,
+    Text,
+}
+
+/// The parts of a rule a pack carries. Ownership, sharing and timestamps
+/// belong to whoever imports it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackRuleTemplate {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub rule_tree: RuleNode,
+    #[serde(default)]
+    pub actions: Vec<Action>,
+    #[serde(default)]
+    pub symbol: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// Where an imported rule came from.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RulePackProvenance {
+    pub pack_id: String,
+    pub pack_name: String,
+    pub pack_version: u32,
+    pub rule_index: usize,
+    pub imported_at: String,
+    /// Data sources the rule needs that weren't configured at import.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub missing_sources: Vec<PackDataSource>,
+}
+
+/// External services a rule depends on to do anything useful.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum PackDataSource {
+    Telegram,
+    Slack,
+    Discord,
+    Email,
+}
+
+impl PackDataSource {
+    pub fn for_action(action_type: &ActionType) -> Option<Self> {
+        match action_type {
+            ActionType::SendTelegram => Some(PackDataSource::Telegram),
+            ActionType::SendSlack => Some(PackDataSource::Slack),
+            ActionType::SendDiscord => Some(PackDataSource::Discord),
+            ActionType::SendEmail => Some(PackDataSource::Email),
+            _ => None,
+        }
+    }
+}
+
+/// Which data sources the user has set up.
+#[derive(Debug, Clone, Default)]
+pub struct ConfiguredSources {
+    pub telegram: bool,
+    pub slack: bool,
+    pub discord: bool,
+    pub email: bool,
+}
+
+impl ConfiguredSources {
+    pub fn has(&self, source: PackDataSource) -> bool {
+        match source {
+            PackDataSource::Telegram => self.telegram,
+            PackDataSource::Slack => self.slack,
+            PackDataSource::Discord => self.discord,
+            PackDataSource::Email => self.email,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MissingDataSource {
+    pub rule_index: usize,
+    pub rule_name: String,
+    pub source: PackDataSource,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackRuleSummary {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// What the import dialog needs: the parameter schema to prompt for and the
+/// sources that still have to be configured.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RulePackPreview {
+    pub pack_id: String,
+    pub name: String,
+    pub description: String,
+    pub version: u32,
+    #[serde(default)]
+    pub author: Option<String>,
+    pub parameters: Vec<PackParameter>,
+    pub rules: Vec<PackRuleSummary>,
+    pub missing_sources: Vec<MissingDataSource>,
+    /// Version already installed, when this import would be an update.
+    #[serde(default)]
+    pub installed_version: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RulePackImportResult {
+    pub pack_id: String,
+    pub version: u32,
+    pub rules: Vec<AlertRule>,
+    /// Rules from an earlier version of the pack that were replaced.
+    pub replaced: u64,
+    pub missing_sources: Vec<MissingDataSource>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstalledRulePack {
+    pub pack_id: String,
+    pub name: String,
+    pub version: u32,
+    pub rule_count: usize,
+    pub enabled_count: usize,
+    pub imported_at: String,
+}
+
+/// Puts a parameter placeholder at `path` (a JSON pointer into the rule's
+/// template) of the `rule_index`th exported rule.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackBinding {
+    pub key: String,
+    pub rule_index: usize,
+    pub path: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportRulePackRequest {
+    /// Reuse an earlier pack's id to publish a new version of it.
+    #[serde(default)]
+    pub pack_id: Option<String>,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default = "default_pack_version")]
+    pub version: u32,
+    #[serde(default)]
+    pub author: Option<String>,
+    pub rule_ids: Vec<String>,
+    #[serde(default)]
+    pub parameters: Vec<PackParameter>,
+    #[serde(default)]
+    pub bindings: Vec<PackBinding>,
+}
+
+fn default_pack_version() -> u32 {
+    1
+}
+
+fn placeholder(key: &str) -> String {
+    format!("{{{{{}}}}}", key)
+}
+
+/// The key of a string that is exactly one placeholder.
+fn placeholder_key(value: &str) -> Option<&str> {
+    value
+        .strip_prefix("{{")
+        .and_then(|rest| rest.strip_suffix("}}"))
+        .filter(|key| is_valid_key(key))
+}
+
+fn is_valid_key(key: &str) -> bool {
+    !key.is_empty()
+        && key.len() <= 48
+        && key
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+fn collect_placeholders(value: &Value, keys: &mut BTreeSet<String>) {
+    match value {
+        Value::String(s) => {
+            if let Some(key) = placeholder_key(s) {
+                keys.insert(key.to_string());
+            }
+        }
+        Value::Array(items) => items
+            .iter()
+            .for_each(|item| collect_placeholders(item, keys)),
+        Value::Object(map) => map
+            .values()
+            .for_each(|item| collect_placeholders(item, keys)),
+        _ => {}
+    }
+}
+
+fn substitute(value: &mut Value, values: &HashMap<String, Value>) {
+    match value {
+        Value::String(s) => {
+            if let Some(replacement) = placeholder_key(s).and_then(|key| values.get(key)) {
+                *value = replacement.clone();
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| substitute(item, values)),
+        Value::Object(map) => map.values_mut().for_each(|item| substitute(item, values)),
+        _ => {}
+    }
+}
+
+impl PackParameter {
+    /// Checks a supplied value against the parameter's kind, normalising
+    /// token symbols and text by trimming them.
+    fn check(&self, value: &Value) -> Result<Value, RulePackError> {
+        let invalid = |reason: String| RulePackError::InvalidParameter {
+            key: self.key.clone(),
+            reason,
+        };
+        match &self.kind {
+            PackParameterKind::Number { min, max } => {
+                let number = value
+                    .as_f64()
+                    .filter(|n| n.is_finite())
+                    .ok_or_else(|| invalid("expected a number".to_string()))?;
+                if let Some(min) = min.filter(|min| number < *min) {
+                    return Err(invalid(format!("must be at least {}", min)));
+                }
+                if let Some(max) = max.filter(|max| number > *max) {
+                    return Err(invalid(format!("must be at most {}", max)));
+                }
+                Ok(value.clone())
+            }
+            PackParameterKind::Token => {
+                let token = value
+                    .as_str()
+                    .map(str::trim)
+                    .filter(|token| !token.is_empty())
+                    .ok_or_else(|| invalid("expected a token symbol or mint".to_string()))?;
+                if token.len() > MAX_TOKEN_PARAMETER_LEN || token.contains(char::is_whitespace) {
+                    return Err(invalid("not a token symbol or mint".to_string()));
+                }
+                Ok(Value::String(token.to_string()))
+            }
+            PackParameterKind::Text => {
+                let text = value
+                    .as_str()
+                    .map(str::trim)
+                    .ok_or_else(|| invalid("expected text".to_string()))?;
+                if text.len() > MAX_TEXT_PARAMETER_LEN {
+                    return Err(invalid(format!(
+                        "longer than {} characters",
+                        MAX_TEXT_PARAMETER_LEN
+                    )));
+                }
+                Ok(Value::String(text.to_string()))
+            }
+        }
+    }
+
+    /// A stand-in value used to check that templates deserialize before the
+    /// importer has chosen anything.
+    fn sample(&self) -> Value {
+        if let Some(default) = &self.default {
+            return default.clone();
+        }
+        match &self.kind {
+            PackParameterKind::Number { min, max } => {
+                serde_json::json!(min.or(*max).unwrap_or(0.0))
+            }
+            PackParameterKind::Token => Value::String("SOL".to_string()),
+            PackParameterKind::Text => Value::String(String::new()),
+        }
+    }
+}
+
+/// Parses and validates a pack: its header, its parameter declarations, and
+/// that every rule template deserializes once parameters are filled in.
+pub fn parse_rule_pack(json: &str) -> Result<RulePack, RulePackError> {
+    if json.len() > MAX_PACK_BYTES {
+        return Err(RulePackError::TooLarge);
+    }
+    let pack: RulePack = serde_json::from_str(json)?;
+    validate_pack(&pack)?;
+    Ok(pack)
+}
+
+fn validate_pack(pack: &RulePack) -> Result<(), RulePackError> {
+    if pack.format_version != RULE_PACK_FORMAT_VERSION {
+        return Err(RulePackError::UnsupportedVersion(
+            pack.format_version.clone(),
+        ));
+    }
+    if pack.pack_id.is_empty()
+        || pack.pack_id.len() > MAX_PACK_ID_LEN
+        || !pack
+            .pack_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    {
+        return Err(RulePackError::InvalidFormat(
+            "pack id must be 1-64 letters, digits, '-', '_' or '.'".to_string(),
+        ));
+    }
+    if pack.name.trim().is_empty() {
+        return Err(RulePackError::InvalidFormat(
+            "pack name is empty".to_string(),
+        ));
+    }
+    if pack.rules.is_empty() || pack.rules.len() > MAX_PACK_RULES {
+        return Err(RulePackError::InvalidFormat(format!(
+            "a pack holds between 1 and {} rules",
+            MAX_PACK_RULES
+        )));
+    }
+
+    let mut declared = BTreeSet::new();
+    for parameter in &pack.parameters {
+        if !is_valid_key(&parameter.key) {
+            return Err(RulePackError::InvalidFormat(format!(
+                "parameter key '{}' must be lowercase letters, digits or '_'",
+                parameter.key
+            )));
+        }
+        if !declared.insert(parameter.key.clone()) {
+            return Err(RulePackError::InvalidFormat(format!(
+                "parameter '{}' is declared twice",
+                parameter.key
+            )));
+        }
+        if let Some(default) = &parameter.default {
+            parameter.check(default)?;
+        }
+    }
+
+    let mut used = BTreeSet::new();
+    for rule in &pack.rules {
+        collect_placeholders(rule, &mut used);
+    }
+    if let Some(key) = used.difference(&declared).next() {
+        return Err(RulePackError::UndeclaredPlaceholder(key.clone()));
+    }
+    if let Some(key) = declared.difference(&used).next() {
+        return Err(RulePackError::InvalidFormat(format!(
+            "parameter '{}' is not used by any rule",
+            key
+        )));
+    }
+
+    let samples = pack
+        .parameters
+        .iter()
+        .map(|parameter| (parameter.key.clone(), parameter.sample()))
+        .collect();
+    fill_templates(pack, &samples)?;
+    Ok(())
+}
+
+/// Resolves the importer's values against the declared parameters, falling
+/// back to defaults. Values for keys the pack doesn't declare are rejected.
+pub fn resolve_parameters(
+    pack: &RulePack,
+    values: &HashMap<String, Value>,
+) -> Result<HashMap<String, Value>, RulePackError> {
+    if let Some(key) = values
+        .keys()
+        .find(|key| !pack.parameters.iter().any(|p| &p.key == *key))
+    {
+        return Err(RulePackError::InvalidParameter {
+            key: key.clone(),
+            reason: "not declared by this pack".to_string(),
+        });
+    }
+
+    pack.parameters
+        .iter()
+        .map(|parameter| {
+            let value = values
+                .get(&parameter.key)
+                .filter(|value| !value.is_null())
+                .or(parameter.default.as_ref())
+                .ok_or_else(|| RulePackError::MissingParameter(parameter.key.clone()))?;
+            Ok((parameter.key.clone(), parameter.check(value)?))
+        })
+        .collect()
+}
+
+fn fill_templates(
+    pack: &RulePack,
+    values: &HashMap<String, Value>,
+) -> Result<Vec<PackRuleTemplate>, RulePackError> {
+    pack.rules
+        .iter()
+        .enumerate()
+        .map(|(index, template)| {
+            let mut filled = template.clone();
+            substitute(&mut filled, values);
+            let name = filled
+                .get("name")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string();
+            let invalid = |reason: String| RulePackError::InvalidRule {
+                index,
+                name: name.clone(),
+                reason,
+            };
+
+            let rule: PackRuleTemplate =
+                serde_json::from_value(filled).map_err(|e| invalid(e.to_string()))?;
+            if rule.name.trim().is_empty() {
+                return Err(invalid("rule name is empty".to_string()));
+            }
+            for action in &rule.actions {
+                action.validate().map_err(&invalid)?;
+            }
+            Ok(rule)
+        })
+        .collect()
+}
+
+/// Rules whose actions need a source the user hasn't configured.
+pub fn missing_sources(
+    rules: &[PackRuleTemplate],
+    configured: &ConfiguredSources,
+) -> Vec<MissingDataSource> {
+    let mut missing = Vec::new();
+    for (index, rule) in rules.iter().enumerate() {
+        let needed: BTreeSet<PackDataSource> = rule
+            .actions
+            .iter()
+            .filter_map(|action| PackDataSource::for_action(&action.action_type))
+            .collect();
+        missing.extend(
+            needed
+                .into_iter()
+                .filter(|source| !configured.has(*source))
+                .map(|source| MissingDataSource {
+                    rule_index: index,
+                    rule_name: rule.name.clone(),
+                    source,
+                }),
+        );
+    }
+    missing
+}
+
+pub fn preview_pack(
+    pack: &RulePack,
+    configured: &ConfiguredSources,
+    installed_version: Option<u32>,
+) -> Result<RulePackPreview, RulePackError> {
+    let samples = pack
+        .parameters
+        .iter()
+        .map(|parameter| (parameter.key.clone(), parameter.sample()))
+        .collect();
+    let templates = fill_templates(pack, &samples)?;
+
+    Ok(RulePackPreview {
+        pack_id: pack.pack_id.clone(),
+        name: pack.name.clone(),
+        description: pack.description.clone(),
+        version: pack.version,
+        author: pack.author.clone(),
+        parameters: pack.parameters.clone(),
+        rules: templates
+            .iter()
+            .map(|rule| PackRuleSummary {
+                name: rule.name.clone(),
+                description: rule.description.clone(),
+            })
+            .collect(),
+        missing_sources: missing_sources(&templates, configured),
+        installed_version,
+    })
+}
+
+/// Builds the pack's rules with the importer's values filled in. Every rule
+/// starts disabled so nothing fires before the user has reviewed it.
+pub fn instantiate_pack(
+    pack: &RulePack,
+    values: &HashMap<String, Value>,
+    configured: &ConfiguredSources,
+    owner_id: Option<String>,
+) -> Result<(Vec<AlertRule>, Vec<MissingDataSource>), RulePackError> {
+    let resolved = resolve_parameters(pack, values)?;
+    let templates = fill_templates(pack, &resolved)?;
+    let missing = missing_sources(&templates, configured);
+    let now = Utc::now().to_rfc3339();
+
+    let rules = templates
+        .into_iter()
+        .enumerate()
+        .map(|(index, template)| AlertRule {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: template.name,
+            description: template.description,
+            rule_tree: template.rule_tree,
+            actions: template.actions,
+            enabled: false,
+            symbol: template.symbol,
+            owner_id: owner_id.clone(),
+            team_id: None,
+            shared_with: Vec::new(),
+            tags: template.tags,
+            pack: Some(RulePackProvenance {
+                pack_id: pack.pack_id.clone(),
+                pack_name: pack.name.clone(),
+                pack_version: pack.version,
+                rule_index: index,
+                imported_at: now.clone(),
+                missing_sources: missing
+                    .iter()
+                    .filter(|m| m.rule_index == index)
+                    .map(|m| m.source)
+                    .collect(),
+            }),
+            created_at: now.clone(),
+            updated_at: now.clone(),
+        })
+        .collect();
+
+    Ok((rules, missing))
+}
+
+/// Turns rules into a pack, putting placeholders where `bindings` point.
+/// Unbound personal targets (webhook URLs, chat ids, email recipients) are
+/// rejected so exporting never leaks the exporter's own channels.
+pub fn build_pack(
+    req: &ExportRulePackRequest,
+    rules: &[AlertRule],
+) -> Result<RulePack, RulePackError> {
+    let mut templates = rules
+        .iter()
+        .map(|rule| {
+            serde_json::to_value(PackRuleTemplate {
+                name: rule.name.clone(),
+                description: rule.description.clone(),
+                rule_tree: rule.rule_tree.clone(),
+                actions: rule.actions.clone(),
+                symbol: rule.symbol.clone(),
+                tags: rule.tags.clone(),
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    for binding in &req.bindings {
+        if !req.parameters.iter().any(|p| p.key == binding.key) {
+            return Err(RulePackError::UndeclaredPlaceholder(binding.key.clone()));
+        }
+        let target = templates
+            .get_mut(binding.rule_index)
+            .and_then(|template| template.pointer_mut(&binding.path))
+            .ok_or_else(|| {
+                RulePackError::InvalidFormat(format!(
+                    "binding for '{}' points at nothing: rule {} {}",
+                    binding.key, binding.rule_index, binding.path
+                ))
+            })?;
+        *target = Value::String(placeholder(&binding.key));
+    }
+
+    for (index, template) in templates.iter().enumerate() {
+        check_personal_targets(index, template)?;
+    }
+
+    let pack = RulePack {
+        format_version: RULE_PACK_FORMAT_VERSION.to_string(),
+        pack_id: req
+            .pack_id
+            .clone()
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+        name: req.name.trim().to_string(),
+        description: req.description.clone(),
+        version: req.version,
+        author: req.author.clone(),
+        created_at: Utc::now().to_rfc3339(),
+        parameters: req.parameters.clone(),
+        rules: templates,
+    };
+    validate_pack(&pack)?;
+    Ok(pack)
+}
+
+fn check_personal_targets(index: usize, template: &Value) -> Result<(), RulePackError> {
+    let actions = template
+        .get("actions")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    for action in actions {
+        let Some(parameters) = action.get("parameters") else {
+            continue;
+        };
+        for field in PERSONAL_TARGETS {
+            let bound = match parameters.get(*field) {
+                None | Some(Value::Null) => true,
+                Some(Value::String(s)) => placeholder_key(s).is_some(),
+                Some(Value::Array(items)) => items
+                    .iter()
+                    .all(|item| item.as_str().and_then(placeholder_key).is_some()),
+                Some(_) => false,
+            };
+            if !bound {
+                return Err(RulePackError::InvalidRule {
+                    index,
+                    name: template
+                        .get("name")
+                        .and_then(Value::as_str)
+                        .unwrap_or_default()
+                        .to_string(),
+                    reason: format!("bind '{}' to a parameter before sharing", field),
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Groups rules by the pack they were imported from.
+pub fn installed_packs(rules: &[AlertRule]) -> Vec<InstalledRulePack> {
+    let mut packs: BTreeMap<String, InstalledRulePack> = BTreeMap::new();
+    for rule in rules {
+        let Some(provenance) = &rule.pack else {
+            continue;
+        };
+        let entry = packs
+            .entry(provenance.pack_id.clone())
+            .or_insert_with(|| InstalledRulePack {
+                pack_id: provenance.pack_id.clone(),
+                name: provenance.pack_name.clone(),
+                version: provenance.pack_version,
+                rule_count: 0,
+                enabled_count: 0,
+                imported_at: provenance.imported_at.clone(),
+            });
+        entry.rule_count += 1;
+        if rule.enabled {
+            entry.enabled_count += 1;
+        }
+    }
+    packs.into_values().collect()
+}
+
+/// Looks up which chat services and email the user has set up.
+pub async fn configured_sources(app: &AppHandle) -> ConfiguredSources {
+    let mut sources = ConfiguredSources::default();
+
+    if let Some(router) = app.try_state::<SharedNotificationRouter>() {
+        match router.read().await.get_settings().await {
+            Ok(settings) => {
+                sources.telegram = settings.telegram.iter().any(|c| c.enabled);
+                sources.slack = settings.slack.iter().any(|c| c.enabled);
+                sources.discord = settings.discord.iter().any(|c| c.enabled);
+            }
+            Err(e) => eprintln!("Failed to read chat integrations: {}", e),
+        }
+    }
+
+    if let Some(keystore) = app.try_state::<Keystore>() {
+        if let Ok(manager) = EmailManager::new(app).await {
+            sources.email = manager.get_config(&keystore).await.is_ok();
+        }
+    }
+
+    sources
+}
+
+async fn all_rules(manager: &SharedSmartAlertManager) -> Result<Vec<AlertRule>, SmartAlertError> {
+    manager
+        .read()
+        .await
+        .list_rules(Some(SmartRuleFilter {
+            include_disabled: true,
+            ..Default::default()
+        }))
+        .await
+}
+
+// Tauri Commands
+
+#[tauri::command]
+pub async fn smart_alert_export_pack(
+    manager: State<'_, SharedSmartAlertManager>,
+    req: ExportRulePackRequest,
+) -> Result<String, String> {
+    let mgr = manager.read().await;
+    let mut rules = Vec::with_capacity(req.rule_ids.len());
+    for id in &req.rule_ids {
+        rules.push(mgr.get_rule(id).await.map_err(|e| e.to_string())?);
+    }
+    let pack = build_pack(&req, &rules).map_err(|e| e.to_string())?;
+    serde_json::to_string_pretty(&pack).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn smart_alert_preview_pack(
+    app: AppHandle,
+    manager: State<'_, SharedSmartAlertManager>,
+    pack_json: String,
+) -> Result<RulePackPreview, String> {
+    let pack = parse_rule_pack(&pack_json).map_err(|e| e.to_string())?;
+    let installed_version = all_rules(&manager)
+        .await
+        .map_err(|e| e.to_string())?
+        .iter()
+        .filter_map(|rule| rule.pack.as_ref())
+        .find(|provenance| provenance.pack_id == pack.pack_id)
+        .map(|provenance| provenance.pack_version);
+    let configured = configured_sources(&app).await;
+    preview_pack(&pack, &configured, installed_version).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn smart_alert_import_pack(
+    app: AppHandle,
+    manager: State<'_, SharedSmartAlertManager>,
+    pack_json: String,
+    values: HashMap<String, Value>,
+    owner_id: Option<String>,
+) -> Result<RulePackImportResult, String> {
+    let pack = parse_rule_pack(&pack_json).map_err(|e| e.to_string())?;
+    let configured = configured_sources(&app).await;
+    let (rules, missing_sources) =
+        instantiate_pack(&pack, &values, &configured, owner_id).map_err(|e| e.to_string())?;
+
+    let mgr = manager.write().await;
+    let replaced = mgr
+        .replace_pack_rules(&pack.pack_id, &rules)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(RulePackImportResult {
+        pack_id: pack.pack_id,
+        version: pack.version,
+        rules,
+        replaced,
+        missing_sources,
+    })
+}
+
+#[tauri::command]
+pub async fn smart_alert_list_packs(
+    manager: State<'_, SharedSmartAlertManager>,
+) -> Result<Vec<InstalledRulePack>, String> {
+    let rules = all_rules(&manager).await.map_err(|e| e.to_string())?;
+    Ok(installed_packs(&rules))
+}
+
+#[tauri::command]
+pub async fn smart_alert_remove_pack(
+    manager: State<'_, SharedSmartAlertManager>,
+    pack_id: String,
+) -> Result<u64, String> {
+    let mgr = manager.write().await;
+    mgr.remove_pack(&pack_id).await.map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alerts::logic::actions::ActionParameters;
+    use crate::alerts::logic::conditions::{Condition, ConditionParameters, ConditionType};
+
+    fn rule(name: &str, threshold: f64, actions: Vec<Action>) -> AlertRule {
+        AlertRule {
+            id: format!("{}-id", name),
+            name: name.to_string(),
+            description: Some("Fires on liquidity pulls".to_string()),
+            rule_tree: RuleNode {
+                id: Some("root".to_string()),
+                label: None,
+                condition: Some(Condition {
+                    id: Some("c1".to_string()),
+                    condition_type: ConditionType::Liquidity,
+                    parameters: ConditionParameters {
+                        threshold: Some(threshold),
+                        ..Default::default()
+                    },
+                    description: None,
+                }),
+                group: None,
+                metadata: None,
+            },
+            actions,
+            enabled: true,
+            symbol: Some("BONK".to_string()),
+            owner_id: Some("author".to_string()),
+            team_id: None,
+            shared_with: vec![],
+            tags: vec!["rug".to_string()],
+            pack: None,
+            created_at: Utc::now().to_rfc3339(),
+            updated_at: Utc::now().to_rfc3339(),
+        }
+    }
+
+    fn action(action_type: ActionType, parameters: ActionParameters) -> Action {
+        Action {
+            id: None,
+            action_type,
+            parameters,
+            description: None,
+            enabled: true,
+        }
+    }
+
+    fn export_request(bindings: Vec<PackBinding>) -> ExportRulePackRequest {
+        ExportRulePackRequest {
+            pack_id: Some("rug-early-warning".to_string()),
+            name: "Rug early-warning".to_string(),
+            description: String::new(),
+            version: 2,
+            author: None,
+            rule_ids: vec![],
+            parameters: vec![
+                PackParameter {
+                    key: "min_liquidity".to_string(),
+                    label: "Minimum liquidity".to_string(),
+                    description: String::new(),
+                    kind: PackParameterKind::Number {
+                        min: Some(0.0),
+                        max: None,
+                    },
+                    default: Some(serde_json::json!(50_000.0)),
+                },
+                PackParameter {
+                    key: "token".to_string(),
+                    label: "Token".to_string(),
+                    description: String::new(),
+                    kind: PackParameterKind::Token,
+                    default: None,
+                },
+            ],
+            bindings,
+        }
+    }
+
+    fn bindings() -> Vec<PackBinding> {
+        vec![
+            PackBinding {
+                key: "min_liquidity".to_string(),
+                rule_index: 0,
+                path: "/ruleTree/condition/parameters/threshold".to_string(),
+            },
+            PackBinding {
+                key: "token".to_string(),
+                rule_index: 0,
+                path: "/symbol".to_string(),
+            },
+        ]
+    }
+
+    #[test]
+    fn export_then_import_fills_parameters_and_disables_rules() {
+        let notify = action(ActionType::Notify, ActionParameters::default());
+        let pack = build_pack(
+            &export_request(bindings()),
+            &[rule("Liquidity pull", 10_000.0, vec![notify])],
+        )
+        .unwrap();
+        let json = serde_json::to_string(&pack).unwrap();
+        assert!(json.contains("{{min_liquidity}}"));
+        assert!(!json.contains("BONK"));
+
+        let pack = parse_rule_pack(&json).unwrap();
+        let values = HashMap::from([("token".to_string(), serde_json::json!(" WIF "))]);
+        let (rules, missing) =
+            instantiate_pack(&pack, &values, &ConfiguredSources::default(), None).unwrap();
+
+        assert!(missing.is_empty());
+        assert_eq!(rules.len(), 1);
+        assert!(!rules[0].enabled);
+        assert_eq!(rules[0].symbol.as_deref(), Some("WIF"));
+        let threshold = rules[0]
+            .rule_tree
+            .condition
+            .as_ref()
+            .unwrap()
+            .parameters
+            .threshold;
+        assert_eq!(threshold, Some(50_000.0));
+        let provenance = rules[0].pack.as_ref().unwrap();
+        assert_eq!(provenance.pack_id, "rug-early-warning");
+        assert_eq!(provenance.pack_version, 2);
+    }
+
+    #[test]
+    fn import_rejects_missing_and_out_of_range_values() {
+        let pack = build_pack(
+            &export_request(bindings()),
+            &[rule("Liquidity pull", 10_000.0, vec![])],
+        )
+        .unwrap();
+        let configured = ConfiguredSources::default();
+
+        let err = instantiate_pack(&pack, &HashMap::new(), &configured, None).unwrap_err();
+        assert!(matches!(err, RulePackError::MissingParameter(key) if key == "token"));
+
+        let values = HashMap::from([
+            ("token".to_string(), serde_json::json!("WIF")),
+            ("min_liquidity".to_string(), serde_json::json!(-1.0)),
+        ]);
+        let err = instantiate_pack(&pack, &values, &configured, None).unwrap_err();
+        assert!(matches!(err, RulePackError::InvalidParameter { .. }));
+
+        let values = HashMap::from([
+            ("token".to_string(), serde_json::json!("WIF")),
+            ("unknown".to_string(), serde_json::json!(1)),
+        ]);
+        assert!(instantiate_pack(&pack, &values, &configured, None).is_err());
+    }
+
+    #[test]
+    fn undeclared_placeholders_fail_validation() {
+        let mut pack = build_pack(
+            &export_request(bindings()),
+            &[rule("Liquidity pull", 10_000.0, vec![])],
+        )
+        .unwrap();
+        pack.rules[0]["description"] = Value::String("{{mystery}}".to_string());
+        let json = serde_json::to_string(&pack).unwrap();
+
+        let err = parse_rule_pack(&json).unwrap_err();
+        assert!(matches!(err, RulePackError::UndeclaredPlaceholder(key) if key == "mystery"));
+    }
+
+    #[test]
+    fn unbound_personal_targets_block_export() {
+        let telegram = action(
+            ActionType::SendTelegram,
+            ActionParameters {
+                chat_id: Some("12345".to_string()),
+                ..Default::default()
+            },
+        );
+        let rules = [rule("Liquidity pull", 10_000.0, vec![telegram])];
+        assert!(build_pack(&export_request(bindings()), &rules).is_err());
+
+        let mut req = export_request(bindings());
+        req.parameters.push(PackParameter {
+            key: "telegram_chat".to_string(),
+            label: "Telegram chat id".to_string(),
+            description: String::new(),
+            kind: PackParameterKind::Text,
+            default: None,
+        });
+        req.bindings.push(PackBinding {
+            key: "telegram_chat".to_string(),
+            rule_index: 0,
+            path: "/actions/0/parameters/chatId".to_string(),
+        });
+        let pack = build_pack(&req, &rules).unwrap();
+        assert!(!serde_json::to_string(&pack).unwrap().contains("12345"));
+    }
+
+    #[test]
+    fn unconfigured_sources_are_flagged_on_the_rule() {
+        let telegram = action(
+            ActionType::SendTelegram,
+            ActionParameters {
+                chat_id: Some("{{telegram_chat}}".to_string()),
+                ..Default::default()
+            },
+        );
+        let mut req = export_request(bindings());
+        req.parameters.push(PackParameter {
+            key: "telegram_chat".to_string(),
+            label: "Telegram chat id".to_string(),
+            description: String::new(),
+            kind: PackParameterKind::Text,
+            default: Some(serde_json::json!("0")),
+        });
+        let pack = build_pack(&req, &[rule("Liquidity pull", 10_000.0, vec![telegram])]).unwrap();
+        let values = HashMap::from([("token".to_string(), serde_json::json!("WIF"))]);
+
+        let (rules, missing) =
+            instantiate_pack(&pack, &values, &ConfiguredSources::default(), None).unwrap();
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].source, PackDataSource::Telegram);
+        assert_eq!(
+            rules[0].pack.as_ref().unwrap().missing_sources,
+            vec![PackDataSource::Telegram]
+        );
+
+        let configured = ConfiguredSources {
+            telegram: true,
+            ..Default::default()
+        };
+        let (_, missing) = instantiate_pack(&pack, &values, &configured, None).unwrap();
+        assert!(missing.is_empty());
+    }
+
+    #[test]
+    fn installed_packs_group_rules_by_provenance() {
+        let pack = build_pack(
+            &export_request(bindings()),
+            &[rule("Liquidity pull", 10_000.0, vec![])],
+        )
+        .unwrap();
+        let values = HashMap::from([("token".to_string(), serde_json::json!("WIF"))]);
+        let (mut rules, _) =
+            instantiate_pack(&pack, &values, &ConfiguredSources::default(), None).unwrap();
+        rules[0].enabled = true;
+        rules.push(rule("Hand made", 1.0, vec![]));
+
+        let installed = installed_packs(&rules);
+        assert_eq!(installed.len(), 1);
+        assert_eq!(installed[0].rule_count, 1);
+        assert_eq!(installed[0].enabled_count, 1);
+    }
+}
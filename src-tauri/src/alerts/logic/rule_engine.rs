@@ -1,5 +1,8 @@
 use super::actions::{Action, ActionExecutionContext, ActionExecutionResult};
-use super::conditions::{Condition, ConditionEvaluationResult, MarketData, WhaleActivity};
+use super::conditions::{
+    Condition, ConditionEvaluationResult, ConditionType, MarketData, WhaleActivity,
+};
+use super::packs::RulePackProvenance;
 use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -47,6 +50,21 @@ pub struct RuleNode {
     pub metadata: Option<serde_json::Value>,
 }
 
+impl RuleNode {
+    /// Whether any condition in this subtree is of `condition_type`.
+    pub fn uses_condition(&self, condition_type: &ConditionType) -> bool {
+        self.condition
+            .as_ref()
+            .is_some_and(|condition| &condition.condition_type == condition_type)
+            || self.group.as_ref().is_some_and(|group| {
+                group
+                    .nodes
+                    .iter()
+                    .any(|node| node.uses_condition(condition_type))
+            })
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RuleGroup {
@@ -89,6 +107,10 @@ pub struct AlertRule {
     #[serde(default)]
     pub tags: Vec<String>,
 
+    /// Set on rules instantiated from a rule pack.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pack: Option<RulePackProvenance>,
+
     pub created_at: String,
     pub updated_at: String,
 }
@@ -377,7 +399,7 @@ impl Default for RuleEngine {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::alerts::logic::conditions::{ConditionParameters, ConditionType};
+    use crate::alerts::logic::conditions::{ComparisonOperator, ConditionParameters};
 
     #[test]
     fn test_simple_and_rule() {
@@ -436,6 +458,7 @@ mod tests {
             team_id: None,
             shared_with: vec![],
             tags: vec![],
+            pack: None,
             created_at: Utc::now().to_rfc3339(),
             updated_at: Utc::now().to_rfc3339(),
         };
@@ -508,6 +531,7 @@ mod tests {
             team_id: None,
             shared_with: vec![],
             tags: vec![],
+            pack: None,
             created_at: Utc::now().to_rfc3339(),
             updated_at: Utc::now().to_rfc3339(),
         };
@@ -562,6 +586,7 @@ mod tests {
             team_id: None,
             shared_with: vec![],
             tags: vec![],
+            pack: None,
             created_at: Utc::now().to_rfc3339(),
             updated_at: Utc::now().to_rfc3339(),
         };
@@ -609,6 +634,7 @@ mod tests {
                 },
             ],
             tags: vec![],
+            pack: None,
             created_at: Utc::now().to_rfc3339(),
             updated_at: Utc::now().to_rfc3339(),
         };
@@ -620,4 +646,87 @@ mod tests {
         assert!(rule.has_access("editor", Permission::View));
         assert!(!rule.has_access("stranger", Permission::View));
     }
+
+    #[test]
+    fn test_orderbook_imbalance_condition() {
+        let condition = |condition_type, parameters| RuleNode {
+            id: None,
+            label: None,
+            condition: Some(Condition {
+                id: None,
+                condition_type,
+                parameters,
+                description: None,
+            }),
+            group: None,
+            metadata: None,
+        };
+        let rule = AlertRule {
+            id: "test-imbalance".to_string(),
+            name: "Ask wall above".to_string(),
+            description: None,
+            rule_tree: RuleNode {
+                id: None,
+                label: None,
+                condition: None,
+                group: Some(RuleGroup {
+                    operator: LogicalOperator::And,
+                    nodes: vec![
+                        condition(
+                            ConditionType::Above,
+                            ConditionParameters {
+                                threshold: Some(100.0),
+                                ..Default::default()
+                            },
+                        ),
+                        condition(
+                            ConditionType::OrderbookImbalance,
+                            ConditionParameters {
+                                threshold: Some(-0.3),
+                                comparison_operator: Some(ComparisonOperator::Less),
+                                ..Default::default()
+                            },
+                        ),
+                    ],
+                    window_minutes: None,
+                    label: None,
+                    description: None,
+                }),
+                metadata: None,
+            },
+            actions: vec![],
+            enabled: true,
+            symbol: Some("SOL".to_string()),
+            owner_id: None,
+            team_id: None,
+            shared_with: vec![],
+            tags: vec![],
+            pack: None,
+            created_at: Utc::now().to_rfc3339(),
+            updated_at: Utc::now().to_rfc3339(),
+        };
+        assert!(rule
+            .rule_tree
+            .uses_condition(&ConditionType::OrderbookImbalance));
+        assert!(!rule.rule_tree.uses_condition(&ConditionType::Liquidity));
+
+        let mut market_data = MarketData {
+            symbol: "SOL".to_string(),
+            current_price: 150.0,
+            orderbook_imbalance: Some(-0.5),
+            ..Default::default()
+        };
+        assert!(rule.evaluate(&market_data, &None).triggered);
+
+        market_data.orderbook_imbalance = Some(0.1);
+        assert!(!rule.evaluate(&market_data, &None).triggered);
+
+        market_data.orderbook_imbalance = None;
+        let result = rule.evaluate(&market_data, &None);
+        assert!(!result.triggered);
+        assert!(result
+            .condition_results
+            .iter()
+            .any(|r| r.message == "Order book data unavailable"));
+    }
 }
@@ -159,6 +159,7 @@ mod tests {
             owner_id: Some("user1".to_string()),
             shared_with: vec![],
             tags: vec!["test".to_string()],
+            pack: None,
             created_at: Utc::now().to_rfc3339(),
             updated_at: Utc::now().to_rfc3339(),
         };
@@ -189,6 +190,7 @@ mod tests {
                 owner_id: None,
                 shared_with: vec![],
                 tags: vec![],
+                pack: None,
                 created_at: Utc::now().to_rfc3339(),
                 updated_at: Utc::now().to_rfc3339(),
             },
@@ -207,6 +209,7 @@ mod tests {
                 owner_id: None,
                 shared_with: vec![],
                 tags: vec![],
+                pack: None,
                 created_at: Utc::now().to_rfc3339(),
                 updated_at: Utc::now().to_rfc3339(),
             },
@@ -237,6 +240,7 @@ mod tests {
             owner_id: None,
             shared_with: vec![],
             tags: vec![],
+            pack: None,
             created_at: Utc::now().to_rfc3339(),
             updated_at: Utc::now().to_rfc3339(),
         };
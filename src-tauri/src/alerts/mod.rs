@@ -1,12 +1,23 @@
+pub mod alert_history;
 pub mod logic;
 pub mod price_alerts;
+pub mod price_feed;
 
+pub use alert_history::{
+    alert_get_trigger_history, alert_history_delete, alert_history_export_csv, alert_history_get,
+    alert_history_list, alert_history_stats, alert_history_update, alert_mark_trigger,
+    alerts_effectiveness_report, run_alert_outcome_sampler, AlertEffectiveness,
+    AlertEffectivenessReport, AlertHistoryEntry, AlertHistoryFilter, AlertHistoryManager,
+    AlertHistoryStats, SharedAlertHistoryManager, TriggerAction, UpdateAlertHistoryRequest,
+};
 pub use logic::*;
+pub use price_feed::run_alert_price_feed;
 // Re-export price_alerts items except LogicalOperator (already exported from logic::rule_engine to avoid ambiguity)
 pub use price_alerts::{
     AlertCondition, AlertConditionType, AlertError, AlertManager, AlertState, AlertTestResult,
     AlertTriggerEvent, CompoundCondition, CreateAlertRequest, NotificationChannel, PriceAlert,
     SharedAlertManager, UpdateAlertRequest,
     alert_create, alert_list, alert_get, alert_update, alert_delete, alert_test,
-    alert_check_triggers, alert_reset_cooldowns,
+    alert_check_triggers, alert_reset_cooldowns, alert_snooze, alerts_mute_token,
+    alerts_mute_all,
 };
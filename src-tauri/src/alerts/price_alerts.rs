@@ -1,3 +1,5 @@
+use crate::config::DataPaths;
+use crate::errors::AppError;
 use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::{Pool, Row, Sqlite, SqlitePool};
@@ -8,7 +10,22 @@ use sysinfo::CpuExt;
 use tauri::{AppHandle, Emitter, Manager, State};
 use tokio::sync::RwLock;
 
+use super::alert_history::record_alert_trigger;
+use crate::notifications::integration::send_alert_notifications;
+use crate::notifications::router::SharedNotificationRouter;
+use crate::portfolio::{token_annotation_summary, TokenAnnotationSummary};
+use crate::utils::ensure_column;
+use crate::webhooks::events::{emit_webhook_event, AlertTriggeredPayload, WebhookEventType};
+
 const ALERTS_DB_FILE: &str = "price_alerts.db";
+/// The `alert_mutes` key for the mute covering every alert.
+const ALL_TOKENS: &str = "*";
+
+const ALERT_COLUMNS: &str = r#"
+    id, name, symbol, mint, watchlist_id, compound_condition,
+    notification_channels, cooldown_minutes, state,
+    last_triggered_at, cooldown_until, snoozed_until, created_at, updated_at
+"#;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
@@ -162,10 +179,21 @@ pub struct PriceAlert {
     pub state: AlertState,
     pub last_triggered_at: Option<String>,
     pub cooldown_until: Option<String>,
+    /// Set while this alert is snoozed; cleared once the time passes.
+    pub snoozed_until: Option<String>,
+    /// Set while the alert's token, or every alert, is muted.
+    pub muted_until: Option<String>,
     pub created_at: String,
     pub updated_at: String,
 }
 
+impl PriceAlert {
+    /// Whether a trigger right now would be suppressed.
+    pub fn is_silenced(&self) -> bool {
+        self.snoozed_until.is_some() || self.muted_until.is_some()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CreateAlertRequest {
@@ -207,6 +235,9 @@ pub struct AlertTriggerEvent {
     pub current_price: f64,
     pub conditions_met: String,
     pub triggered_at: String,
+    /// Latest note and tags for the alert's token.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub annotation: Option<TokenAnnotationSummary>,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -221,10 +252,34 @@ pub enum AlertError {
     NotFound(String),
     #[error("alert in cooldown until: {0}")]
     InCooldown(String),
+    #[error("invalid snooze: {0}")]
+    InvalidSnooze(String),
     #[error("internal error: {0}")]
     Internal(String),
 }
 
+impl From<AlertError> for AppError {
+    fn from(err: AlertError) -> Self {
+        match err {
+            AlertError::NotFound(id) => AppError::not_found("Alert not found")
+                .with_code("alert_not_found")
+                .with_detail("alertId", id),
+            AlertError::InCooldown(until) => {
+                AppError::validation(format!("Alert is in cooldown until {until}"))
+                    .with_code("alert_in_cooldown")
+                    .with_detail("cooldownUntil", until)
+            }
+            AlertError::InvalidSnooze(message) => {
+                AppError::validation(message).with_code("invalid_alert_snooze")
+            }
+            AlertError::Database(err) => err.into(),
+            AlertError::Io(err) => err.into(),
+            AlertError::Serialization(err) => err.into(),
+            AlertError::Internal(message) => AppError::internal(message),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct AlertManager {
     pool: Pool<Sqlite>,
@@ -262,6 +317,7 @@ impl AlertManager {
                 state TEXT NOT NULL,
                 last_triggered_at TEXT,
                 cooldown_until TEXT,
+                snoozed_until TEXT,
                 created_at TEXT NOT NULL,
                 updated_at TEXT NOT NULL
             )
@@ -269,6 +325,19 @@ impl AlertManager {
         )
         .execute(&self.pool)
         .await?;
+        ensure_column(&self.pool, "price_alerts", "snoozed_until", "TEXT").await?;
+
+        // Keyed by mint, or ALL_TOKENS for the global mute
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS alert_mutes (
+                target TEXT PRIMARY KEY,
+                until TEXT NOT NULL
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
 
         sqlx::query(
             r#"
@@ -328,48 +397,150 @@ impl AlertManager {
             state: AlertState::Active,
             last_triggered_at: None,
             cooldown_until: None,
+            snoozed_until: None,
+            muted_until: None,
             created_at: now.clone(),
             updated_at: now,
         })
     }
 
     pub async fn list_alerts(&self) -> Result<Vec<PriceAlert>, AlertError> {
-        let rows = sqlx::query(
-            r#"
-            SELECT id, name, symbol, mint, watchlist_id, compound_condition,
-                   notification_channels, cooldown_minutes, state,
-                   last_triggered_at, cooldown_until, created_at, updated_at
-            FROM price_alerts
-            ORDER BY created_at DESC
-            "#,
-        )
+        let rows = sqlx::query(&format!(
+            "SELECT {ALERT_COLUMNS} FROM price_alerts ORDER BY created_at DESC"
+        ))
         .fetch_all(&self.pool)
         .await?;
 
+        let mutes = self.active_mutes().await?;
+        let now = Utc::now();
         let mut alerts = Vec::new();
         for row in rows {
-            alerts.push(self.row_to_alert(row)?);
+            let mut alert = self.row_to_alert(row)?;
+            apply_silences(&mut alert, &mutes, now);
+            alerts.push(alert);
         }
 
         Ok(alerts)
     }
 
     pub async fn get_alert(&self, id: &str) -> Result<PriceAlert, AlertError> {
-        let row = sqlx::query(
-            r#"
-            SELECT id, name, symbol, mint, watchlist_id, compound_condition,
-                   notification_channels, cooldown_minutes, state,
-                   last_triggered_at, cooldown_until, created_at, updated_at
-            FROM price_alerts
-            WHERE id = ?1
-            "#,
-        )
+        let row = sqlx::query(&format!(
+            "SELECT {ALERT_COLUMNS} FROM price_alerts WHERE id = ?1"
+        ))
         .bind(id)
         .fetch_optional(&self.pool)
         .await?
         .ok_or_else(|| AlertError::NotFound(id.to_string()))?;
 
-        self.row_to_alert(row)
+        let mut alert = self.row_to_alert(row)?;
+        apply_silences(&mut alert, &self.active_mutes().await?, Utc::now());
+        Ok(alert)
+    }
+
+    /// Snoozes one alert until `until`, or lifts its snooze when `None`.
+    pub async fn snooze_alert(
+        &self,
+        id: &str,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<PriceAlert, AlertError> {
+        let until = until.map(future_time).transpose()?;
+        let result = sqlx::query(
+            "UPDATE price_alerts SET snoozed_until = ?1, updated_at = ?2 WHERE id = ?3",
+        )
+        .bind(&until)
+        .bind(Utc::now().to_rfc3339())
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AlertError::NotFound(id.to_string()));
+        }
+        self.get_alert(id).await
+    }
+
+    /// Mutes every alert on `mint` until `until`, or unmutes it when `None`.
+    /// Returns the alerts on the token.
+    pub async fn mute_token(
+        &self,
+        mint: &str,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<Vec<PriceAlert>, AlertError> {
+        let mint = mint.trim();
+        if mint.is_empty() || mint == ALL_TOKENS {
+            return Err(AlertError::InvalidSnooze(
+                "A token mint is required".to_string(),
+            ));
+        }
+        self.set_mute(mint, until).await?;
+
+        let mut alerts = self.list_alerts().await?;
+        alerts.retain(|alert| alert.mint == mint);
+        Ok(alerts)
+    }
+
+    /// Pauses every alert until `until`, or ends the pause when `None`.
+    pub async fn mute_all(
+        &self,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<Vec<PriceAlert>, AlertError> {
+        self.set_mute(ALL_TOKENS, until).await?;
+        self.list_alerts().await
+    }
+
+    async fn set_mute(&self, target: &str, until: Option<DateTime<Utc>>) -> Result<(), AlertError> {
+        match until.map(future_time).transpose()? {
+            Some(until) => {
+                sqlx::query(
+                    r#"
+                    INSERT INTO alert_mutes (target, until) VALUES (?1, ?2)
+                    ON CONFLICT(target) DO UPDATE SET until = excluded.until
+                    "#,
+                )
+                .bind(target)
+                .bind(until)
+                .execute(&self.pool)
+                .await?;
+            }
+            None => {
+                sqlx::query("DELETE FROM alert_mutes WHERE target = ?1")
+                    .bind(target)
+                    .execute(&self.pool)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Mutes that have not ended, by mint or ALL_TOKENS.
+    async fn active_mutes(&self) -> Result<HashMap<String, String>, AlertError> {
+        let rows = sqlx::query("SELECT target, until FROM alert_mutes WHERE until > ?1")
+            .bind(Utc::now().to_rfc3339())
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut mutes = HashMap::new();
+        for row in rows {
+            mutes.insert(row.try_get("target")?, row.try_get("until")?);
+        }
+        Ok(mutes)
+    }
+
+    /// Drops snoozes and mutes whose time has passed.
+    pub async fn clear_expired_silences(&self) -> Result<usize, AlertError> {
+        let now = Utc::now().to_rfc3339();
+
+        let snoozes =
+            sqlx::query("UPDATE price_alerts SET snoozed_until = NULL WHERE snoozed_until <= ?1")
+                .bind(&now)
+                .execute(&self.pool)
+                .await?;
+        let mutes = sqlx::query("DELETE FROM alert_mutes WHERE until <= ?1")
+            .bind(&now)
+            .execute(&self.pool)
+            .await?;
+
+        Ok((snoozes.rows_affected() + mutes.rows_affected()) as usize)
     }
 
     pub async fn update_alert(
@@ -468,24 +639,20 @@ impl AlertManager {
         volume_24h: Option<f64>,
     ) -> Result<Vec<String>, AlertError> {
         let now = Utc::now();
-        let rows = sqlx::query(
-            r#"
-            SELECT id, name, symbol, mint, watchlist_id, compound_condition,
-                   notification_channels, cooldown_minutes, state,
-                   last_triggered_at, cooldown_until, created_at, updated_at
-            FROM price_alerts
-            WHERE symbol = ?1 AND state = ?2
-            "#,
-        )
+        let rows = sqlx::query(&format!(
+            "SELECT {ALERT_COLUMNS} FROM price_alerts WHERE symbol = ?1 AND state = ?2"
+        ))
         .bind(symbol)
         .bind(AlertState::Active.as_str())
         .fetch_all(&self.pool)
         .await?;
 
+        let mutes = self.active_mutes().await?;
         let mut triggered_alerts = Vec::new();
 
         for row in rows {
-            let alert = self.row_to_alert(row)?;
+            let mut alert = self.row_to_alert(row)?;
+            apply_silences(&mut alert, &mutes, now);
 
             if let Some(cooldown_until_str) = &alert.cooldown_until {
                 if let Ok(cooldown_until) = DateTime::parse_from_rfc3339(cooldown_until_str) {
@@ -503,8 +670,11 @@ impl AlertManager {
             );
 
             if would_trigger {
-                self.trigger_alert(&alert, current_price, &message).await?;
-                triggered_alerts.push(alert.id.clone());
+                self.trigger_alert(&alert, current_price, volume_24h, &message)
+                    .await?;
+                if !alert.is_silenced() {
+                    triggered_alerts.push(alert.id.clone());
+                }
             }
         }
 
@@ -515,6 +685,7 @@ impl AlertManager {
         &self,
         alert: &PriceAlert,
         current_price: f64,
+        volume_24h: Option<f64>,
         message: &str,
     ) -> Result<(), AlertError> {
         let now = Utc::now();
@@ -535,6 +706,23 @@ impl AlertManager {
         .execute(&self.pool)
         .await?;
 
+        // A silenced alert still goes through its cooldown and into the
+        // history, so the effectiveness report sees every trigger, but
+        // nothing is emitted and the notification router never hears of it.
+        let suppressed = alert.is_silenced();
+        record_alert_trigger(
+            &self.app_handle,
+            alert,
+            current_price,
+            volume_24h,
+            message,
+            suppressed,
+        )
+        .await;
+        if suppressed {
+            return Ok(());
+        }
+
         let event = AlertTriggerEvent {
             alert_id: alert.id.clone(),
             alert_name: alert.name.clone(),
@@ -542,12 +730,33 @@ impl AlertManager {
             current_price,
             conditions_met: message.to_string(),
             triggered_at: now.to_rfc3339(),
+            annotation: token_annotation_summary(&self.app_handle, &alert.mint),
         };
 
         self.app_handle
-            .emit("alert_triggered", event)
+            .emit("alert_triggered", event.clone())
             .map_err(|e| AlertError::Internal(format!("Failed to emit event: {}", e)))?;
 
+        emit_webhook_event(
+            &self.app_handle,
+            WebhookEventType::AlertTriggered,
+            AlertTriggeredPayload {
+                alert_id: event.alert_id.clone(),
+                alert_name: event.alert_name.clone(),
+                symbol: event.symbol.clone(),
+                current_price,
+                conditions_met: event.conditions_met.clone(),
+            },
+        );
+
+        if let Some(router) = self.app_handle.try_state::<SharedNotificationRouter>() {
+            tauri::async_runtime::spawn(send_alert_notifications(
+                router.inner().clone(),
+                event,
+                alert.notification_channels.clone(),
+            ));
+        }
+
         Ok(())
     }
 
@@ -669,17 +878,51 @@ impl AlertManager {
             state,
             last_triggered_at: row.try_get("last_triggered_at")?,
             cooldown_until: row.try_get("cooldown_until")?,
+            snoozed_until: row.try_get("snoozed_until")?,
+            muted_until: None,
             created_at: row.try_get("created_at")?,
             updated_at: row.try_get("updated_at")?,
         })
     }
 }
 
+/// Fills in `muted_until` from `mutes` and hides a snooze that has ended.
+fn apply_silences(alert: &mut PriceAlert, mutes: &HashMap<String, String>, now: DateTime<Utc>) {
+    alert.snoozed_until = alert
+        .snoozed_until
+        .take()
+        .filter(|until| is_after(until, now));
+    alert.muted_until = [mutes.get(&alert.mint), mutes.get(ALL_TOKENS)]
+        .into_iter()
+        .flatten()
+        .filter(|until| is_after(until, now))
+        .max_by_key(|until| parse_time(until))
+        .cloned();
+}
+
+fn parse_time(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|time| time.with_timezone(&Utc))
+}
+
+fn is_after(value: &str, now: DateTime<Utc>) -> bool {
+    parse_time(value).is_some_and(|time| time > now)
+}
+
+fn future_time(until: DateTime<Utc>) -> Result<String, AlertError> {
+    if until <= Utc::now() {
+        return Err(AlertError::InvalidSnooze(
+            "The snooze must end in the future".to_string(),
+        ));
+    }
+    Ok(until.to_rfc3339())
+}
+
 fn alerts_db_path(app: &AppHandle) -> Result<PathBuf, AlertError> {
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| AlertError::Internal(format!("Unable to resolve app data directory: {}", e)))?;
+    let app_data_dir = app.app_data_root().map_err(|e| {
+        AlertError::Internal(format!("Unable to resolve app data directory: {}", e))
+    })?;
 
     std::fs::create_dir_all(&app_data_dir)?;
     Ok(app_data_dir.join(ALERTS_DB_FILE))
@@ -690,24 +933,26 @@ fn alerts_db_path(app: &AppHandle) -> Result<PathBuf, AlertError> {
 pub async fn alert_create(
     manager: State<'_, SharedAlertManager>,
     req: CreateAlertRequest,
-) -> Result<PriceAlert, String> {
+) -> Result<PriceAlert, AppError> {
     let mgr = manager.read().await;
-    mgr.create_alert(req).await.map_err(|e| e.to_string())
+    mgr.create_alert(req).await.map_err(AppError::from)
 }
 
 #[tauri::command]
-pub async fn alert_list(manager: State<'_, SharedAlertManager>) -> Result<Vec<PriceAlert>, String> {
+pub async fn alert_list(
+    manager: State<'_, SharedAlertManager>,
+) -> Result<Vec<PriceAlert>, AppError> {
     let mgr = manager.read().await;
-    mgr.list_alerts().await.map_err(|e| e.to_string())
+    mgr.list_alerts().await.map_err(AppError::from)
 }
 
 #[tauri::command]
 pub async fn alert_get(
     manager: State<'_, SharedAlertManager>,
     id: String,
-) -> Result<PriceAlert, String> {
+) -> Result<PriceAlert, AppError> {
     let mgr = manager.read().await;
-    mgr.get_alert(&id).await.map_err(|e| e.to_string())
+    mgr.get_alert(&id).await.map_err(AppError::from)
 }
 
 #[tauri::command]
@@ -715,18 +960,18 @@ pub async fn alert_update(
     manager: State<'_, SharedAlertManager>,
     id: String,
     req: UpdateAlertRequest,
-) -> Result<PriceAlert, String> {
+) -> Result<PriceAlert, AppError> {
     let mgr = manager.read().await;
-    mgr.update_alert(&id, req).await.map_err(|e| e.to_string())
+    mgr.update_alert(&id, req).await.map_err(AppError::from)
 }
 
 #[tauri::command]
 pub async fn alert_delete(
     manager: State<'_, SharedAlertManager>,
     id: String,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     let mgr = manager.read().await;
-    mgr.delete_alert(&id).await.map_err(|e| e.to_string())
+    mgr.delete_alert(&id).await.map_err(AppError::from)
 }
 
 #[tauri::command]
@@ -736,11 +981,11 @@ pub async fn alert_test(
     current_price: f64,
     price_24h_ago: Option<f64>,
     volume_24h: Option<f64>,
-) -> Result<AlertTestResult, String> {
+) -> Result<AlertTestResult, AppError> {
     let mgr = manager.read().await;
     mgr.test_alert(&id, current_price, price_24h_ago, volume_24h)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(AppError::from)
 }
 
 #[tauri::command]
@@ -750,17 +995,120 @@ pub async fn alert_check_triggers(
     current_price: f64,
     price_24h_ago: Option<f64>,
     volume_24h: Option<f64>,
-) -> Result<Vec<String>, String> {
+) -> Result<Vec<String>, AppError> {
     let mgr = manager.read().await;
     mgr.check_and_trigger_alerts(&symbol, current_price, price_24h_ago, volume_24h)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(AppError::from)
 }
 
 #[tauri::command]
 pub async fn alert_reset_cooldowns(
     manager: State<'_, SharedAlertManager>,
-) -> Result<usize, String> {
+) -> Result<usize, AppError> {
+    let mgr = manager.read().await;
+    mgr.reset_cooldowns().await.map_err(AppError::from)
+}
+
+/// Snoozes one alert until `until`; `None` lifts the snooze.
+#[tauri::command]
+pub async fn alert_snooze(
+    manager: State<'_, SharedAlertManager>,
+    alert_id: String,
+    until: Option<DateTime<Utc>>,
+) -> Result<PriceAlert, AppError> {
+    let mgr = manager.read().await;
+    mgr.snooze_alert(&alert_id, until)
+        .await
+        .map_err(AppError::from)
+}
+
+/// Mutes every alert on `mint` until `until`; `None` unmutes the token.
+#[tauri::command]
+pub async fn alerts_mute_token(
+    manager: State<'_, SharedAlertManager>,
+    mint: String,
+    until: Option<DateTime<Utc>>,
+) -> Result<Vec<PriceAlert>, AppError> {
     let mgr = manager.read().await;
-    mgr.reset_cooldowns().await.map_err(|e| e.to_string())
+    mgr.mute_token(&mint, until).await.map_err(AppError::from)
+}
+
+/// Pauses all alerts until `until`; `None` ends the pause.
+#[tauri::command]
+pub async fn alerts_mute_all(
+    manager: State<'_, SharedAlertManager>,
+    until: Option<DateTime<Utc>>,
+) -> Result<Vec<PriceAlert>, AppError> {
+    let mgr = manager.read().await;
+    mgr.mute_all(until).await.map_err(AppError::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alert(mint: &str, snoozed_until: Option<DateTime<Utc>>) -> PriceAlert {
+        PriceAlert {
+            id: "alert".to_string(),
+            name: "alert".to_string(),
+            symbol: "BONK".to_string(),
+            mint: mint.to_string(),
+            watchlist_id: None,
+            compound_condition: CompoundCondition {
+                conditions: vec![],
+                operator: LogicalOperator::And,
+            },
+            notification_channels: vec![NotificationChannel::InApp],
+            cooldown_minutes: 15,
+            state: AlertState::Active,
+            last_triggered_at: None,
+            cooldown_until: None,
+            snoozed_until: snoozed_until.map(|until| until.to_rfc3339()),
+            muted_until: None,
+            created_at: Utc::now().to_rfc3339(),
+            updated_at: Utc::now().to_rfc3339(),
+        }
+    }
+
+    #[test]
+    fn the_latest_mute_covering_an_alert_applies() {
+        let now = Utc::now();
+        let mutes: HashMap<String, String> = [
+            ("bonk".to_string(), (now + Duration::hours(2)).to_rfc3339()),
+            (
+                ALL_TOKENS.to_string(),
+                (now + Duration::hours(1)).to_rfc3339(),
+            ),
+        ]
+        .into_iter()
+        .collect();
+
+        let mut bonk = alert("bonk", None);
+        apply_silences(&mut bonk, &mutes, now);
+        assert_eq!(bonk.muted_until, mutes.get("bonk").cloned());
+        assert!(bonk.is_silenced());
+
+        let mut other = alert("wif", None);
+        apply_silences(&mut other, &mutes, now);
+        assert_eq!(other.muted_until, mutes.get(ALL_TOKENS).cloned());
+
+        // Two and a half hours later both have ended
+        let mut later = alert("wif", None);
+        apply_silences(&mut later, &mutes, now + Duration::minutes(150));
+        assert!(!later.is_silenced());
+    }
+
+    #[test]
+    fn ended_snoozes_are_cleared() {
+        let now = Utc::now();
+        let mut snoozed = alert("bonk", Some(now + Duration::minutes(30)));
+        apply_silences(&mut snoozed, &HashMap::new(), now);
+        assert!(snoozed.snoozed_until.is_some());
+
+        apply_silences(&mut snoozed, &HashMap::new(), now + Duration::hours(1));
+        assert!(snoozed.snoozed_until.is_none());
+        assert!(!snoozed.is_silenced());
+        assert!(future_time(now - Duration::minutes(1)).is_err());
+    }
 }
@@ -0,0 +1,125 @@
+use super::price_alerts::{AlertState, SharedAlertManager};
+use crate::core::{WebSocketManager, ALERTS_CONSUMER};
+use crate::journal::{ideas, SharedJournalDatabase};
+use chrono::Utc;
+use std::collections::HashSet;
+use tokio::time::{interval, Duration};
+
+const ALERT_PRICE_THROTTLE: Duration = Duration::from_secs(1);
+const WATCHLIST_RESYNC_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Evaluates alerts and grades open trade ideas against the live price
+/// stream. Subscribes through the multiplexer as the `alerts` consumer, so
+/// symbols already streamed for the UI or charts don't open extra upstream
+/// subscriptions.
+pub async fn run_alert_price_feed(
+    alerts: SharedAlertManager,
+    ws_manager: WebSocketManager,
+    journal: SharedJournalDatabase,
+) {
+    let mut feed = ws_manager.price_feed(ALERTS_CONSUMER, ALERT_PRICE_THROTTLE);
+    let mut watched: HashSet<String> = ws_manager
+        .price_consumer_tokens(ALERTS_CONSUMER)
+        .into_iter()
+        .collect();
+    let mut idea_symbols: HashSet<String> = HashSet::new();
+    let mut resync = interval(WATCHLIST_RESYNC_INTERVAL);
+
+    loop {
+        tokio::select! {
+            _ = resync.tick() => {
+                idea_symbols = refresh_trade_ideas(&journal).await;
+                sync_watched_symbols(&alerts, &ws_manager, &idea_symbols, &mut watched).await;
+            }
+            delta = feed.recv() => {
+                let Some(delta) = delta else {
+                    break;
+                };
+                let Some(price) = delta.price else {
+                    continue;
+                };
+                let mgr = alerts.read().await;
+                if let Err(err) = mgr
+                    .check_and_trigger_alerts(&delta.symbol, price, None, delta.volume)
+                    .await
+                {
+                    eprintln!("Failed to check alerts for {}: {}", delta.symbol, err);
+                }
+                drop(mgr);
+                if idea_symbols.contains(&delta.symbol) {
+                    let db = journal.read().await;
+                    let now = Utc::now().timestamp();
+                    if let Err(err) =
+                        ideas::grade_trade_ideas(&db, &delta.symbol, price, now).await
+                    {
+                        eprintln!("Failed to grade trade ideas for {}: {}", delta.symbol, err);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Expires trade ideas past their horizon and returns the symbols of those
+/// still open.
+async fn refresh_trade_ideas(journal: &SharedJournalDatabase) -> HashSet<String> {
+    let db = journal.read().await;
+    if let Err(err) = ideas::expire_trade_ideas(&db, Utc::now().timestamp()).await {
+        eprintln!("Failed to expire trade ideas: {}", err);
+    }
+    ideas::open_trade_idea_symbols(&db)
+        .await
+        .unwrap_or_else(|err| {
+            eprintln!("Failed to list trade ideas for price feed: {}", err);
+            HashSet::new()
+        })
+}
+
+/// Alerts cycle between active and cooldown, so both keep their symbol
+/// watched, as do open trade ideas.
+async fn sync_watched_symbols(
+    alerts: &SharedAlertManager,
+    ws_manager: &WebSocketManager,
+    idea_symbols: &HashSet<String>,
+    watched: &mut HashSet<String>,
+) {
+    let wanted: HashSet<String> = {
+        let mgr = alerts.read().await;
+        match mgr.list_alerts().await {
+            Ok(list) => list
+                .into_iter()
+                .filter(|alert| matches!(alert.state, AlertState::Active | AlertState::Cooldown))
+                .map(|alert| alert.symbol)
+                .chain(idea_symbols.iter().cloned())
+                .collect(),
+            Err(err) => {
+                eprintln!("Failed to list alerts for price feed: {}", err);
+                return;
+            }
+        }
+    };
+
+    let added: Vec<String> = wanted.difference(watched).cloned().collect();
+    let removed: Vec<String> = watched.difference(&wanted).cloned().collect();
+
+    if !added.is_empty() {
+        match ws_manager
+            .subscribe_prices_as(ALERTS_CONSUMER, added.clone())
+            .await
+        {
+            Ok(()) => watched.extend(added),
+            Err(err) => eprintln!("Failed to subscribe alert symbols: {}", err),
+        }
+    }
+    if !removed.is_empty() {
+        if let Err(err) = ws_manager
+            .unsubscribe_prices_as(ALERTS_CONSUMER, removed.clone())
+            .await
+        {
+            eprintln!("Failed to unsubscribe alert symbols: {}", err);
+        }
+        for symbol in removed {
+            watched.remove(&symbol);
+        }
+    }
+}
@@ -1,9 +1,31 @@
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
+use tauri::{AppHandle, Manager};
 use tokio::sync::RwLock;
 
+use crate::core::{JobSchedule, JobScheduler};
+use crate::market::{scope_mints, UnlockScope};
+use crate::portfolio::SharedWatchlistManager;
+use crate::social::analysis::{SocialSpike, SocialSpikeEvidence, SpikePattern};
+use crate::social::SharedSocialAnalysisService;
+use crate::wallet::multi_wallet::MultiWalletManager;
+
+pub const DEFAULT_PROFILE: &str = "default";
+const CONSERVATIVE_PROFILE: &str = "conservative";
+const AGGRESSIVE_PROFILE: &str = "aggressive";
+const BASELINE_FORMAT_VERSION: u32 = 1;
+const MAX_HISTORY: usize = 1000;
+const MAX_ANOMALIES: usize = 200;
+pub const SOCIAL_SPIKE_ANOMALY: &str = "social_spike";
+const SOCIAL_SPIKE_CHECK_INTERVAL_SECS: u64 = 15 * 60;
+const SOCIAL_SPIKE_CHECK_TIMEOUT_SECS: u64 = 5 * 60;
+
+fn default_profile_name() -> String {
+    DEFAULT_PROFILE.to_string()
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct PriceData {
     pub timestamp: i64,
@@ -32,6 +54,21 @@ pub struct Anomaly {
     pub explanation: String,
     pub details: HashMap<String, String>,
     pub is_active: bool,
+    /// Sensitivity profile the token was evaluated under.
+    #[serde(default = "default_profile_name")]
+    pub profile: String,
+    /// Set when the user dismissed the anomaly, as opposed to it expiring.
+    #[serde(default)]
+    pub dismissed: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub evidence: Option<AnomalyEvidence>,
+}
+
+/// Supporting data for anomaly types that carry more than `details`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AnomalyEvidence {
+    SocialSpike(SocialSpikeEvidence),
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -44,6 +81,137 @@ pub struct AnomalyDetectionConfig {
     pub notification_channels: Vec<String>,
 }
 
+/// Detection thresholds under a name. `default` follows the global
+/// [`AnomalyDetectionConfig`]; `conservative` and `aggressive` are fixed
+/// presets; any other name is a user-defined custom profile.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SensitivityProfile {
+    pub name: String,
+    pub zscore_threshold: f64,
+    pub iqr_multiplier: f64,
+    /// Volume over the mean volume that counts as a spike.
+    pub volume_spike_ratio: f64,
+    pub wash_trading_threshold: f64,
+    pub min_data_points: usize,
+}
+
+impl SensitivityProfile {
+    fn conservative() -> Self {
+        Self {
+            name: CONSERVATIVE_PROFILE.to_string(),
+            zscore_threshold: 4.0,
+            iqr_multiplier: 3.0,
+            volume_spike_ratio: 10.0,
+            wash_trading_threshold: 0.9,
+            min_data_points: 50,
+        }
+    }
+
+    fn aggressive() -> Self {
+        Self {
+            name: AGGRESSIVE_PROFILE.to_string(),
+            zscore_threshold: 2.5,
+            iqr_multiplier: 1.0,
+            volume_spike_ratio: 3.0,
+            wash_trading_threshold: 0.6,
+            min_data_points: 10,
+        }
+    }
+
+    fn validate(&self) -> Result<(), AnomalyProfileError> {
+        if self.name.trim().is_empty() {
+            return Err(AnomalyProfileError::Invalid("profile name is empty".into()));
+        }
+        let positive = [
+            self.zscore_threshold,
+            self.iqr_multiplier,
+            self.volume_spike_ratio,
+            self.wash_trading_threshold,
+        ];
+        if positive.iter().any(|v| !v.is_finite() || *v <= 0.0) {
+            return Err(AnomalyProfileError::Invalid(
+                "thresholds must be positive numbers".into(),
+            ));
+        }
+        if self.wash_trading_threshold > 1.0 {
+            return Err(AnomalyProfileError::Invalid(
+                "wash trading threshold is a ratio between 0 and 1".into(),
+            ));
+        }
+        if self.min_data_points < 2 {
+            return Err(AnomalyProfileError::Invalid(
+                "at least 2 data points are required".into(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn is_builtin_profile(name: &str) -> bool {
+    matches!(
+        name,
+        DEFAULT_PROFILE | CONSERVATIVE_PROFILE | AGGRESSIVE_PROFILE
+    )
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum AnomalyProfileError {
+    #[error("unknown sensitivity profile: {0}")]
+    UnknownProfile(String),
+    #[error("'{0}' is a built-in profile and cannot be changed")]
+    BuiltinProfile(String),
+    #[error("invalid profile: {0}")]
+    Invalid(String),
+    #[error("no baseline recorded for {0}")]
+    NoBaseline(String),
+    #[error("unsupported baseline format version {0}")]
+    UnsupportedVersion(u32),
+}
+
+/// What a profile applies to. Token assignments win over watchlist ones.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "kind", content = "id", rename_all = "snake_case")]
+pub enum ProfileTarget {
+    Token(String),
+    Watchlist(String),
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct AnomalyProfileAssignments {
+    pub tokens: HashMap<String, String>,
+    pub watchlists: HashMap<String, String>,
+}
+
+/// Running totals for anomalies no longer held in memory, so dismissal rates
+/// survive trimming and profile resets.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default)]
+struct ProfileOutcomes {
+    raised: usize,
+    dismissed: usize,
+}
+
+/// How noisy a profile has been: the share of its anomalies that users
+/// dismissed serves as a false-positive proxy.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ProfileNoise {
+    pub profile: String,
+    pub raised: usize,
+    pub dismissed: usize,
+    pub dismissal_rate: f64,
+}
+
+/// A token's detector history and the profile it was tuned under, in a form
+/// that can be moved between installs.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AnomalyBaseline {
+    pub version: u32,
+    pub token_address: String,
+    pub profile: SensitivityProfile,
+    pub exported_at: i64,
+    pub price_history: Vec<PriceData>,
+    pub transaction_history: Vec<TransactionData>,
+}
+
 pub type SharedAnomalyDetector = Arc<RwLock<AnomalyDetector>>;
 
 pub struct AnomalyDetector {
@@ -51,6 +219,15 @@ pub struct AnomalyDetector {
     transaction_history: HashMap<String, Vec<TransactionData>>,
     anomalies: Vec<Anomaly>,
     config: AnomalyDetectionConfig,
+    custom_profiles: HashMap<String, SensitivityProfile>,
+    token_profiles: HashMap<String, String>,
+    /// Watchlist assignments in the order they were made; the latest one
+    /// wins for tokens on several watchlists.
+    watchlist_profiles: Vec<(String, String)>,
+    watchlist_members: HashMap<String, HashSet<String>>,
+    /// Profile each token's history was collected under.
+    evaluated_profiles: HashMap<String, String>,
+    retired_outcomes: HashMap<String, ProfileOutcomes>,
 }
 
 impl AnomalyDetector {
@@ -67,53 +244,73 @@ impl AnomalyDetector {
                 min_data_points: 20,
                 notification_channels: vec!["in-app".to_string()],
             },
+            custom_profiles: HashMap::new(),
+            token_profiles: HashMap::new(),
+            watchlist_profiles: Vec::new(),
+            watchlist_members: HashMap::new(),
+            evaluated_profiles: HashMap::new(),
+            retired_outcomes: HashMap::new(),
         }
     }
 
     pub fn add_price_data(&mut self, token_address: String, data: PriceData) {
+        let profile = self.prepare_token(&token_address);
         let history = self
             .price_history
             .entry(token_address.clone())
             .or_insert_with(Vec::new);
         history.push(data);
 
-        if history.len() > 1000 {
-            history.drain(0..history.len() - 1000);
+        if history.len() > MAX_HISTORY {
+            history.drain(0..history.len() - MAX_HISTORY);
         }
 
-        if self.config.enabled && history.len() >= self.config.min_data_points {
-            self.detect_price_anomalies(&token_address);
+        if self.config.enabled && history.len() >= profile.min_data_points {
+            self.detect_price_anomalies(&token_address, &profile);
         }
     }
 
     pub fn add_transaction_data(&mut self, token_address: String, data: TransactionData) {
+        let profile = self.prepare_token(&token_address);
         let history = self
             .transaction_history
             .entry(token_address.clone())
             .or_insert_with(Vec::new);
         history.push(data);
 
-        if history.len() > 1000 {
-            history.drain(0..history.len() - 1000);
+        if history.len() > MAX_HISTORY {
+            history.drain(0..history.len() - MAX_HISTORY);
+        }
+
+        if self.config.enabled && history.len() >= profile.min_data_points {
+            self.detect_wash_trading(&token_address, &profile);
         }
+    }
 
-        if self.config.enabled && history.len() >= self.config.min_data_points {
-            self.detect_wash_trading(&token_address);
+    /// Resolves the profile for a token about to be evaluated, resetting its
+    /// state first if the profile changed since its history was collected.
+    fn prepare_token(&mut self, token_address: &str) -> SensitivityProfile {
+        let name = self.resolve_profile_name(token_address);
+        if self.evaluated_profiles.get(token_address) != Some(&name) {
+            self.reset_token(token_address);
+            self.evaluated_profiles
+                .insert(token_address.to_string(), name.clone());
         }
+        self.profile(&name)
     }
 
-    fn detect_price_anomalies(&mut self, token_address: &str) {
+    fn detect_price_anomalies(&mut self, token_address: &str, profile: &SensitivityProfile) {
         if self.price_history.contains_key(token_address) {
             let history = self.price_history[token_address].clone();
-            if history.len() < self.config.min_data_points {
+            if history.len() < profile.min_data_points {
                 return;
             }
 
             let latest = &history[history.len() - 1];
 
-            self.detect_zscore_anomaly(token_address, &history, latest);
-            self.detect_iqr_anomaly(token_address, &history, latest);
-            self.detect_volume_anomaly(token_address, &history, latest);
+            self.detect_zscore_anomaly(token_address, &history, latest, profile);
+            self.detect_iqr_anomaly(token_address, &history, latest, profile);
+            self.detect_volume_anomaly(token_address, &history, latest, profile);
         }
     }
 
@@ -122,6 +319,7 @@ impl AnomalyDetector {
         token_address: &str,
         history: &[PriceData],
         latest: &PriceData,
+        profile: &SensitivityProfile,
     ) {
         let prices: Vec<f64> = history.iter().map(|d| d.price).collect();
         let mean = prices.iter().sum::<f64>() / prices.len() as f64;
@@ -132,7 +330,7 @@ impl AnomalyDetector {
         if std_dev > 0.0 {
             let zscore = (latest.price - mean) / std_dev;
 
-            if zscore.abs() >= self.config.zscore_threshold {
+            if zscore.abs() >= profile.zscore_threshold {
                 let severity = if zscore.abs() >= 5.0 {
                     "critical"
                 } else if zscore.abs() >= 4.0 {
@@ -162,10 +360,13 @@ impl AnomalyDetector {
                     severity: severity.to_string(),
                     timestamp: latest.timestamp,
                     value: latest.price,
-                    threshold: self.config.zscore_threshold,
+                    threshold: profile.zscore_threshold,
                     explanation,
                     details,
                     is_active: true,
+                    profile: profile.name.clone(),
+                    dismissed: false,
+                    evidence: None,
                 };
 
                 self.anomalies.push(anomaly);
@@ -178,6 +379,7 @@ impl AnomalyDetector {
         token_address: &str,
         history: &[PriceData],
         latest: &PriceData,
+        profile: &SensitivityProfile,
     ) {
         let mut prices: Vec<f64> = history.iter().map(|d| d.price).collect();
         prices.sort_by(|a, b| a.partial_cmp(b).unwrap());
@@ -191,8 +393,8 @@ impl AnomalyDetector {
             let q3 = prices[q3_idx];
             let iqr = q3 - q1;
 
-            let lower_bound = q1 - self.config.iqr_multiplier * iqr;
-            let upper_bound = q3 + self.config.iqr_multiplier * iqr;
+            let lower_bound = q1 - profile.iqr_multiplier * iqr;
+            let upper_bound = q3 + profile.iqr_multiplier * iqr;
 
             if latest.price < lower_bound || latest.price > upper_bound {
                 let severity = if latest.price < q1 - 3.0 * iqr || latest.price > q3 + 3.0 * iqr {
@@ -235,10 +437,13 @@ impl AnomalyDetector {
                     severity: severity.to_string(),
                     timestamp: latest.timestamp,
                     value: latest.price,
-                    threshold: self.config.iqr_multiplier,
+                    threshold: profile.iqr_multiplier,
                     explanation,
                     details,
                     is_active: true,
+                    profile: profile.name.clone(),
+                    dismissed: false,
+                    evidence: None,
                 };
 
                 self.anomalies.push(anomaly);
@@ -251,6 +456,7 @@ impl AnomalyDetector {
         token_address: &str,
         history: &[PriceData],
         latest: &PriceData,
+        profile: &SensitivityProfile,
     ) {
         let volumes: Vec<f64> = history.iter().map(|d| d.volume).collect();
         let mean_volume = volumes.iter().sum::<f64>() / volumes.len() as f64;
@@ -258,8 +464,8 @@ impl AnomalyDetector {
         if mean_volume > 0.0 {
             let volume_ratio = latest.volume / mean_volume;
 
-            if volume_ratio >= 5.0 {
-                let severity = if volume_ratio >= 10.0 {
+            if volume_ratio >= profile.volume_spike_ratio {
+                let severity = if volume_ratio >= profile.volume_spike_ratio * 2.0 {
                     "high"
                 } else {
                     "medium"
@@ -288,24 +494,107 @@ impl AnomalyDetector {
                     severity: severity.to_string(),
                     timestamp: latest.timestamp,
                     value: latest.volume,
-                    threshold: 5.0,
+                    threshold: profile.volume_spike_ratio,
                     explanation,
                     details,
                     is_active: true,
+                    profile: profile.name.clone(),
+                    dismissed: false,
+                    evidence: None,
                 };
 
                 self.anomalies.push(anomaly);
             }
         }
 
-        if self.anomalies.len() > 200 {
-            self.anomalies.drain(0..self.anomalies.len() - 200);
+        self.trim_anomalies();
+    }
+
+    fn trim_anomalies(&mut self) {
+        if self.anomalies.len() > MAX_ANOMALIES {
+            let trimmed: Vec<Anomaly> = self
+                .anomalies
+                .drain(0..self.anomalies.len() - MAX_ANOMALIES)
+                .collect();
+            self.retire(trimmed);
         }
     }
 
-    fn detect_wash_trading(&mut self, token_address: &str) {
+    /// Raises a `social_spike` anomaly for a mention spike found by the
+    /// social analysis service.
+    pub fn record_social_spike(&mut self, spike: &SocialSpike) {
+        if !self.config.enabled {
+            return;
+        }
+        let evidence = &spike.evidence;
+        let pattern = evidence
+            .pattern
+            .map(|pattern| pattern.as_str())
+            .unwrap_or("none");
+        let mut explanation = format!(
+            "Social mentions spiked to {} in the last hour, {:.1}x this token's baseline of {:.2} per hour. \
+            Sentiment moved from {:.2} to {:.2}.",
+            evidence.mentions_last_hour,
+            evidence.velocity_ratio,
+            evidence.baseline_per_hour,
+            evidence.sentiment_baseline,
+            evidence.sentiment_now
+        );
+        match evidence.pattern {
+            Some(SpikePattern::PrePump) => explanation
+                .push_str(" Rising sentiment on a spike like this has tended to precede pumps."),
+            Some(SpikePattern::PreDump) => explanation
+                .push_str(" Falling sentiment on a spike like this has tended to precede dumps."),
+            None => {}
+        }
+        if evidence.coordinated_shilling {
+            explanation.push_str(&format!(
+                " {:.0}% of the authors are new, low-follower accounts, which points to coordinated shilling.",
+                evidence.new_low_follower_share * 100.0
+            ));
+        }
+
+        let mut details = HashMap::new();
+        details.insert("method".to_string(), SOCIAL_SPIKE_ANOMALY.to_string());
+        details.insert("pattern".to_string(), pattern.to_string());
+        details.insert(
+            "mentions_last_hour".to_string(),
+            evidence.mentions_last_hour.to_string(),
+        );
+        details.insert(
+            "baseline_per_hour".to_string(),
+            format!("{:.2}", evidence.baseline_per_hour),
+        );
+        details.insert(
+            "sentiment_shift".to_string(),
+            format!("{:.2}", evidence.sentiment_shift),
+        );
+        details.insert(
+            "coordinated_shilling".to_string(),
+            evidence.coordinated_shilling.to_string(),
+        );
+
+        self.anomalies.push(Anomaly {
+            id: spike.id.clone(),
+            token_address: spike.token.clone(),
+            anomaly_type: SOCIAL_SPIKE_ANOMALY.to_string(),
+            severity: spike.severity.clone(),
+            timestamp: spike.detected_at,
+            value: evidence.velocity_ratio,
+            threshold: evidence.velocity_threshold,
+            explanation,
+            details,
+            is_active: true,
+            profile: self.resolve_profile_name(&spike.token),
+            dismissed: false,
+            evidence: Some(AnomalyEvidence::SocialSpike(evidence.clone())),
+        });
+        self.trim_anomalies();
+    }
+
+    fn detect_wash_trading(&mut self, token_address: &str, profile: &SensitivityProfile) {
         if let Some(history) = self.transaction_history.get(token_address) {
-            if history.len() < self.config.min_data_points {
+            if history.len() < profile.min_data_points {
                 return;
             }
 
@@ -328,7 +617,7 @@ impl AnomalyDetector {
                 if txs.len() >= 3 {
                     let back_and_forth = self.analyze_back_and_forth_pattern(txs);
 
-                    if back_and_forth >= self.config.wash_trading_threshold {
+                    if back_and_forth >= profile.wash_trading_threshold {
                         let total_volume: f64 = txs.iter().map(|tx| tx.amount * tx.price).sum();
                         let avg_price: f64 =
                             txs.iter().map(|tx| tx.price).sum::<f64>() / txs.len() as f64;
@@ -361,10 +650,13 @@ impl AnomalyDetector {
                             severity: "high".to_string(),
                             timestamp: Utc::now().timestamp(),
                             value: back_and_forth,
-                            threshold: self.config.wash_trading_threshold,
+                            threshold: profile.wash_trading_threshold,
                             explanation,
                             details,
                             is_active: true,
+                            profile: profile.name.clone(),
+                            dismissed: false,
+                            evidence: None,
                         };
 
                         self.anomalies.push(anomaly);
@@ -421,11 +713,18 @@ impl AnomalyDetector {
     pub fn dismiss_anomaly(&mut self, anomaly_id: &str) {
         if let Some(anomaly) = self.anomalies.iter_mut().find(|a| a.id == anomaly_id) {
             anomaly.is_active = false;
+            anomaly.dismissed = true;
         }
     }
 
+    /// Replaces the global config. Tokens on the `default` profile start
+    /// over when its thresholds change.
     pub fn update_config(&mut self, config: AnomalyDetectionConfig) {
+        let previous = self.default_profile();
         self.config = config;
+        if self.default_profile() != previous {
+            self.reset_tokens_on(DEFAULT_PROFILE);
+        }
     }
 
     pub fn get_config(&self) -> AnomalyDetectionConfig {
@@ -462,8 +761,296 @@ impl AnomalyDetector {
             active_anomalies: token_anomalies.iter().filter(|a| a.is_active).count(),
             by_type,
             by_severity,
+            profile: self.resolve_profile_name(token_address),
+            profile_noise: self.profile_noise(),
         })
     }
+
+    fn default_profile(&self) -> SensitivityProfile {
+        SensitivityProfile {
+            name: DEFAULT_PROFILE.to_string(),
+            zscore_threshold: self.config.zscore_threshold,
+            iqr_multiplier: self.config.iqr_multiplier,
+            volume_spike_ratio: 5.0,
+            wash_trading_threshold: self.config.wash_trading_threshold,
+            min_data_points: self.config.min_data_points,
+        }
+    }
+
+    /// The named profile, falling back to `default` for names that no
+    /// longer exist.
+    fn profile(&self, name: &str) -> SensitivityProfile {
+        match name {
+            CONSERVATIVE_PROFILE => SensitivityProfile::conservative(),
+            AGGRESSIVE_PROFILE => SensitivityProfile::aggressive(),
+            _ => self
+                .custom_profiles
+                .get(name)
+                .cloned()
+                .unwrap_or_else(|| self.default_profile()),
+        }
+    }
+
+    fn has_profile(&self, name: &str) -> bool {
+        is_builtin_profile(name) || self.custom_profiles.contains_key(name)
+    }
+
+    pub fn list_profiles(&self) -> Vec<SensitivityProfile> {
+        let mut custom: Vec<SensitivityProfile> = self.custom_profiles.values().cloned().collect();
+        custom.sort_by(|a, b| a.name.cmp(&b.name));
+        let mut profiles = vec![
+            SensitivityProfile::conservative(),
+            self.default_profile(),
+            SensitivityProfile::aggressive(),
+        ];
+        profiles.extend(custom);
+        profiles
+    }
+
+    /// Profile a token is evaluated under: its own assignment, else the most
+    /// recently assigned watchlist containing it, else `default`.
+    pub fn resolve_profile_name(&self, token_address: &str) -> String {
+        if let Some(name) = self.token_profiles.get(token_address) {
+            return name.clone();
+        }
+        self.watchlist_profiles
+            .iter()
+            .rev()
+            .find(|(watchlist_id, _)| {
+                self.watchlist_members
+                    .get(watchlist_id)
+                    .is_some_and(|members| members.contains(token_address))
+            })
+            .map(|(_, name)| name.clone())
+            .unwrap_or_else(default_profile_name)
+    }
+
+    /// Creates or replaces a custom profile. Tokens already on it start over
+    /// so their statistics are not mixed across threshold sets.
+    pub fn save_profile(&mut self, profile: SensitivityProfile) -> Result<(), AnomalyProfileError> {
+        profile.validate()?;
+        if is_builtin_profile(&profile.name) {
+            return Err(AnomalyProfileError::BuiltinProfile(profile.name));
+        }
+        let name = profile.name.clone();
+        let changed = self.custom_profiles.get(&name) != Some(&profile);
+        self.custom_profiles.insert(name.clone(), profile);
+        if changed {
+            self.reset_tokens_on(&name);
+        }
+        Ok(())
+    }
+
+    /// Removes a custom profile along with every assignment to it.
+    pub fn delete_profile(&mut self, name: &str) -> Result<(), AnomalyProfileError> {
+        if is_builtin_profile(name) {
+            return Err(AnomalyProfileError::BuiltinProfile(name.to_string()));
+        }
+        if self.custom_profiles.remove(name).is_none() {
+            return Err(AnomalyProfileError::UnknownProfile(name.to_string()));
+        }
+        self.token_profiles.retain(|_, profile| profile != name);
+        self.watchlist_profiles
+            .retain(|(_, profile)| profile != name);
+        self.refresh_evaluated_profiles();
+        Ok(())
+    }
+
+    /// Assigns `profile` to a token or watchlist, or clears the assignment
+    /// when `profile` is `None`.
+    pub fn assign_profile(
+        &mut self,
+        target: ProfileTarget,
+        profile: Option<String>,
+    ) -> Result<(), AnomalyProfileError> {
+        if let Some(name) = &profile {
+            if !self.has_profile(name) {
+                return Err(AnomalyProfileError::UnknownProfile(name.clone()));
+            }
+        }
+        match target {
+            ProfileTarget::Token(mint) => match profile {
+                Some(name) => {
+                    self.token_profiles.insert(mint, name);
+                }
+                None => {
+                    self.token_profiles.remove(&mint);
+                }
+            },
+            ProfileTarget::Watchlist(id) => {
+                self.watchlist_profiles
+                    .retain(|(watchlist_id, _)| *watchlist_id != id);
+                if let Some(name) = profile {
+                    self.watchlist_profiles.push((id, name));
+                }
+            }
+        }
+        self.refresh_evaluated_profiles();
+        Ok(())
+    }
+
+    pub fn profile_assignments(&self) -> AnomalyProfileAssignments {
+        AnomalyProfileAssignments {
+            tokens: self.token_profiles.clone(),
+            watchlists: self.watchlist_profiles.iter().cloned().collect(),
+        }
+    }
+
+    pub fn has_watchlist_profiles(&self) -> bool {
+        !self.watchlist_profiles.is_empty()
+    }
+
+    /// Updates which tokens each watchlist holds. Tokens whose resolved
+    /// profile changes as a result are reset.
+    pub fn set_watchlist_members(&mut self, members: HashMap<String, HashSet<String>>) {
+        if members != self.watchlist_members {
+            self.watchlist_members = members;
+            self.refresh_evaluated_profiles();
+        }
+    }
+
+    /// Resets every token whose history was collected under a profile other
+    /// than the one it now resolves to.
+    fn refresh_evaluated_profiles(&mut self) {
+        let changed: Vec<String> = self
+            .evaluated_profiles
+            .iter()
+            .filter(|(token, name)| self.resolve_profile_name(token) != **name)
+            .map(|(token, _)| token.clone())
+            .collect();
+        for token in changed {
+            self.reset_token(&token);
+            let name = self.resolve_profile_name(&token);
+            self.evaluated_profiles.insert(token, name);
+        }
+    }
+
+    fn reset_tokens_on(&mut self, profile: &str) {
+        let tokens: Vec<String> = self
+            .evaluated_profiles
+            .iter()
+            .filter(|(_, name)| name.as_str() == profile)
+            .map(|(token, _)| token.clone())
+            .collect();
+        for token in tokens {
+            self.reset_token(&token);
+        }
+    }
+
+    /// Drops a token's history and moves its anomalies into the per-profile
+    /// totals, so the next evaluation starts from a clean baseline.
+    fn reset_token(&mut self, token_address: &str) {
+        self.price_history.remove(token_address);
+        self.transaction_history.remove(token_address);
+        let (retired, kept): (Vec<Anomaly>, Vec<Anomaly>) = self
+            .anomalies
+            .drain(..)
+            .partition(|a| a.token_address == token_address);
+        self.anomalies = kept;
+        self.retire(retired);
+    }
+
+    fn retire(&mut self, anomalies: Vec<Anomaly>) {
+        for anomaly in anomalies {
+            let outcomes = self.retired_outcomes.entry(anomaly.profile).or_default();
+            outcomes.raised += 1;
+            if anomaly.dismissed {
+                outcomes.dismissed += 1;
+            }
+        }
+    }
+
+    /// Dismissal rate per profile over every anomaly raised under it.
+    pub fn profile_noise(&self) -> Vec<ProfileNoise> {
+        let mut totals = self.retired_outcomes.clone();
+        for anomaly in &self.anomalies {
+            let outcomes = totals.entry(anomaly.profile.clone()).or_default();
+            outcomes.raised += 1;
+            if anomaly.dismissed {
+                outcomes.dismissed += 1;
+            }
+        }
+        let mut noise: Vec<ProfileNoise> = totals
+            .into_iter()
+            .map(|(profile, outcomes)| ProfileNoise {
+                profile,
+                raised: outcomes.raised,
+                dismissed: outcomes.dismissed,
+                dismissal_rate: if outcomes.raised == 0 {
+                    0.0
+                } else {
+                    outcomes.dismissed as f64 / outcomes.raised as f64
+                },
+            })
+            .collect();
+        noise.sort_by(|a, b| a.profile.cmp(&b.profile));
+        noise
+    }
+
+    pub fn export_baseline(
+        &self,
+        token_address: &str,
+    ) -> Result<AnomalyBaseline, AnomalyProfileError> {
+        let price_history = self
+            .price_history
+            .get(token_address)
+            .cloned()
+            .unwrap_or_default();
+        let transaction_history = self
+            .transaction_history
+            .get(token_address)
+            .cloned()
+            .unwrap_or_default();
+        if price_history.is_empty() && transaction_history.is_empty() {
+            return Err(AnomalyProfileError::NoBaseline(token_address.to_string()));
+        }
+
+        Ok(AnomalyBaseline {
+            version: BASELINE_FORMAT_VERSION,
+            token_address: token_address.to_string(),
+            profile: self.profile(&self.resolve_profile_name(token_address)),
+            exported_at: Utc::now().timestamp(),
+            price_history,
+            transaction_history,
+        })
+    }
+
+    /// Installs exported baselines. Each token is assigned the baseline's
+    /// profile and its history replaced. A custom profile replaces a local
+    /// one of the same name; built-in profiles keep their local thresholds.
+    pub fn import_baselines(
+        &mut self,
+        baselines: Vec<AnomalyBaseline>,
+    ) -> Result<usize, AnomalyProfileError> {
+        for baseline in &baselines {
+            if baseline.version != BASELINE_FORMAT_VERSION {
+                return Err(AnomalyProfileError::UnsupportedVersion(baseline.version));
+            }
+            if !is_builtin_profile(&baseline.profile.name) {
+                baseline.profile.validate()?;
+            }
+        }
+
+        let count = baselines.len();
+        for baseline in baselines {
+            let token = baseline.token_address;
+            let name = baseline.profile.name.clone();
+            if !is_builtin_profile(&name) {
+                self.save_profile(baseline.profile)?;
+            }
+            self.token_profiles.insert(token.clone(), name.clone());
+            self.reset_token(&token);
+            self.evaluated_profiles.insert(token.clone(), name);
+
+            let mut prices = baseline.price_history;
+            prices.drain(..prices.len().saturating_sub(MAX_HISTORY));
+            let mut transactions = baseline.transaction_history;
+            transactions.drain(..transactions.len().saturating_sub(MAX_HISTORY));
+            self.price_history.insert(token.clone(), prices);
+            self.transaction_history.insert(token, transactions);
+        }
+        Ok(count)
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -473,6 +1060,117 @@ pub struct AnomalyStatistics {
     pub active_anomalies: usize,
     pub by_type: HashMap<String, usize>,
     pub by_severity: HashMap<String, usize>,
+    /// Profile the token is currently evaluated under.
+    #[serde(default = "default_profile_name")]
+    pub profile: String,
+    #[serde(default)]
+    pub profile_noise: Vec<ProfileNoise>,
+}
+
+/// Current watchlist membership, read only when some watchlist has a
+/// profile assigned.
+async fn watchlist_members(
+    detector: &SharedAnomalyDetector,
+    watchlists: &SharedWatchlistManager,
+) -> Result<Option<HashMap<String, HashSet<String>>>, String> {
+    if !detector.read().await.has_watchlist_profiles() {
+        return Ok(None);
+    }
+    let lists = watchlists
+        .read()
+        .await
+        .list_watchlists()
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(Some(
+        lists
+            .into_iter()
+            .map(|list| (list.id, list.items.into_iter().map(|i| i.mint).collect()))
+            .collect(),
+    ))
+}
+
+/// Mints held by the user's own wallets or on any watchlist. Watch-only
+/// wallets are left out: their holdings are not the user's.
+async fn social_spike_tokens(app: &AppHandle) -> Vec<String> {
+    let mut mints = HashSet::new();
+    if let Some(manager) = app.try_state::<MultiWalletManager>() {
+        match manager.list_wallets() {
+            Ok(wallets) => {
+                for wallet in wallets.into_iter().filter(|wallet| !wallet.watch_only) {
+                    let scope = UnlockScope::Wallet {
+                        address: wallet.public_key,
+                    };
+                    match scope_mints(app, &scope).await {
+                        Ok(held) => mints.extend(held),
+                        Err(e) => eprintln!("Social spike check: failed to read holdings: {}", e),
+                    }
+                }
+            }
+            Err(e) => eprintln!("Social spike check: failed to list wallets: {}", e),
+        }
+    }
+    if let Some(watchlists) = app.try_state::<SharedWatchlistManager>() {
+        match watchlists.read().await.list_watchlists().await {
+            Ok(lists) => mints.extend(
+                lists
+                    .into_iter()
+                    .flat_map(|list| list.items.into_iter().map(|item| item.mint)),
+            ),
+            Err(e) => eprintln!("Social spike check: failed to list watchlists: {}", e),
+        }
+    }
+    let mut mints: Vec<String> = mints.into_iter().collect();
+    mints.sort();
+    mints
+}
+
+/// Looks for mention spikes on held and watched tokens and raises each one
+/// as a `social_spike` anomaly.
+pub async fn check_social_spikes(
+    app: &AppHandle,
+    analysis: &SharedSocialAnalysisService,
+    detector: &SharedAnomalyDetector,
+) -> Result<(), String> {
+    if !detector.read().await.get_config().enabled {
+        return Ok(());
+    }
+    let tokens = social_spike_tokens(app).await;
+    if tokens.is_empty() {
+        return Ok(());
+    }
+    let spikes = analysis
+        .write()
+        .await
+        .detect_social_spikes(&tokens)
+        .await
+        .map_err(|e| e.to_string())?;
+    let mut det = detector.write().await;
+    for spike in &spikes {
+        det.record_social_spike(spike);
+    }
+    Ok(())
+}
+
+pub fn schedule_social_spike_checks(
+    scheduler: &JobScheduler,
+    app: AppHandle,
+    analysis: SharedSocialAnalysisService,
+    detector: SharedAnomalyDetector,
+) -> Result<(), String> {
+    scheduler.register(
+        "social_spike_checks",
+        JobSchedule::every_from_start(std::time::Duration::from_secs(
+            SOCIAL_SPIKE_CHECK_INTERVAL_SECS,
+        )),
+        std::time::Duration::from_secs(SOCIAL_SPIKE_CHECK_TIMEOUT_SECS),
+        move || {
+            let app = app.clone();
+            let analysis = analysis.clone();
+            let detector = detector.clone();
+            async move { check_social_spikes(&app, &analysis, &detector).await }
+        },
+    )
 }
 
 #[tauri::command]
@@ -480,8 +1178,13 @@ pub async fn add_price_data(
     token_address: String,
     data: PriceData,
     detector: tauri::State<'_, SharedAnomalyDetector>,
+    watchlists: tauri::State<'_, SharedWatchlistManager>,
 ) -> Result<(), String> {
+    let members = watchlist_members(&detector, &watchlists).await?;
     let mut det = detector.write().await;
+    if let Some(members) = members {
+        det.set_watchlist_members(members);
+    }
     det.add_price_data(token_address, data);
     Ok(())
 }
@@ -491,8 +1194,13 @@ pub async fn add_transaction_data(
     token_address: String,
     data: TransactionData,
     detector: tauri::State<'_, SharedAnomalyDetector>,
+    watchlists: tauri::State<'_, SharedWatchlistManager>,
 ) -> Result<(), String> {
+    let members = watchlist_members(&detector, &watchlists).await?;
     let mut det = detector.write().await;
+    if let Some(members) = members {
+        det.set_watchlist_members(members);
+    }
     det.add_transaction_data(token_address, data);
     Ok(())
 }
@@ -552,6 +1260,91 @@ pub async fn get_anomaly_statistics(
     Ok(det.get_statistics(&token_address))
 }
 
+#[tauri::command]
+pub async fn list_anomaly_profiles(
+    detector: tauri::State<'_, SharedAnomalyDetector>,
+) -> Result<Vec<SensitivityProfile>, String> {
+    let det = detector.read().await;
+    Ok(det.list_profiles())
+}
+
+#[tauri::command]
+pub async fn save_anomaly_profile(
+    profile: SensitivityProfile,
+    detector: tauri::State<'_, SharedAnomalyDetector>,
+) -> Result<(), String> {
+    let mut det = detector.write().await;
+    det.save_profile(profile).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn delete_anomaly_profile(
+    name: String,
+    detector: tauri::State<'_, SharedAnomalyDetector>,
+) -> Result<(), String> {
+    let mut det = detector.write().await;
+    det.delete_profile(&name).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn assign_anomaly_profile(
+    target: ProfileTarget,
+    profile: Option<String>,
+    detector: tauri::State<'_, SharedAnomalyDetector>,
+    watchlists: tauri::State<'_, SharedWatchlistManager>,
+) -> Result<(), String> {
+    let mut det = detector.write().await;
+    det.assign_profile(target, profile)
+        .map_err(|e| e.to_string())?;
+    drop(det);
+
+    let members = watchlist_members(&detector, &watchlists).await?;
+    if let Some(members) = members {
+        detector.write().await.set_watchlist_members(members);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_anomaly_profile_assignments(
+    detector: tauri::State<'_, SharedAnomalyDetector>,
+) -> Result<AnomalyProfileAssignments, String> {
+    let det = detector.read().await;
+    Ok(det.profile_assignments())
+}
+
+#[tauri::command]
+pub async fn export_anomaly_baselines(
+    mint: String,
+    detector: tauri::State<'_, SharedAnomalyDetector>,
+) -> Result<AnomalyBaseline, String> {
+    let det = detector.read().await;
+    det.export_baseline(&mint).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn import_anomaly_baselines(
+    baselines: Vec<AnomalyBaseline>,
+    detector: tauri::State<'_, SharedAnomalyDetector>,
+) -> Result<usize, String> {
+    let mut det = detector.write().await;
+    det.import_baselines(baselines).map_err(|e| e.to_string())
+}
+
+/// Every recorded social spike for a mint, newest first, with its evidence.
+#[tauri::command]
+pub async fn get_social_spike_history(
+    mint: String,
+    analysis: tauri::State<'_, SharedSocialAnalysisService>,
+) -> Result<Vec<SocialSpike>, String> {
+    analysis
+        .read()
+        .await
+        .get_social_spike_history(mint.trim())
+        .await
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn generate_mock_anomaly_data(
     token_address: String,
@@ -708,4 +1501,169 @@ mod tests {
         let stats = stats.unwrap();
         assert!(stats.total_anomalies > 0);
     }
+
+    fn feed_spike(detector: &mut AnomalyDetector, token_address: &str, points: i64) {
+        let now = Utc::now().timestamp();
+        for i in 0..points {
+            let data = PriceData {
+                timestamp: now + i * 60,
+                price: 100.0,
+                volume: 1000.0,
+            };
+            detector.add_price_data(token_address.to_string(), data);
+        }
+        let spike = PriceData {
+            timestamp: now + points * 60,
+            price: 300.0,
+            volume: 50000.0,
+        };
+        detector.add_price_data(token_address.to_string(), spike);
+    }
+
+    #[test]
+    fn test_profile_resolution_prefers_token_over_watchlist() {
+        let mut detector = AnomalyDetector::new();
+        detector
+            .assign_profile(
+                ProfileTarget::Watchlist("memes".into()),
+                Some("aggressive".into()),
+            )
+            .unwrap();
+        detector.set_watchlist_members(HashMap::from([(
+            "memes".to_string(),
+            HashSet::from(["bonk".to_string(), "wif".to_string()]),
+        )]));
+        detector
+            .assign_profile(
+                ProfileTarget::Token("wif".into()),
+                Some("conservative".into()),
+            )
+            .unwrap();
+
+        assert_eq!(detector.resolve_profile_name("bonk"), "aggressive");
+        assert_eq!(detector.resolve_profile_name("wif"), "conservative");
+        assert_eq!(detector.resolve_profile_name("sol"), DEFAULT_PROFILE);
+        assert!(detector
+            .assign_profile(ProfileTarget::Token("sol".into()), Some("missing".into()))
+            .is_err());
+    }
+
+    #[test]
+    fn test_profile_change_resets_token_state() {
+        let mut detector = AnomalyDetector::new();
+        detector.config.min_data_points = 5;
+        feed_spike(&mut detector, "bonk", 10);
+        let first = detector.get_anomalies(Some("bonk"), None);
+        assert!(!first.is_empty());
+        detector.dismiss_anomaly(&first[0].id);
+
+        detector
+            .assign_profile(
+                ProfileTarget::Token("bonk".into()),
+                Some("aggressive".into()),
+            )
+            .unwrap();
+        assert!(detector.get_anomalies(Some("bonk"), None).is_empty());
+        assert!(detector.export_baseline("bonk").is_err());
+
+        feed_spike(&mut detector, "bonk", 10);
+        let stats = detector.get_statistics("bonk").unwrap();
+        assert_eq!(stats.profile, "aggressive");
+        assert!(detector
+            .get_anomalies(Some("bonk"), None)
+            .iter()
+            .all(|a| a.profile == "aggressive"));
+
+        let default_noise = stats
+            .profile_noise
+            .iter()
+            .find(|n| n.profile == DEFAULT_PROFILE)
+            .unwrap();
+        assert_eq!(default_noise.raised, first.len());
+        assert_eq!(default_noise.dismissed, 1);
+    }
+
+    #[test]
+    fn test_builtin_profiles_are_read_only() {
+        let mut detector = AnomalyDetector::new();
+        let mut profile = SensitivityProfile::aggressive();
+        assert!(detector.save_profile(profile.clone()).is_err());
+
+        profile.name = "memecoins".into();
+        profile.zscore_threshold = 2.0;
+        detector.save_profile(profile).unwrap();
+        assert_eq!(detector.list_profiles().len(), 4);
+        assert!(detector.delete_profile(DEFAULT_PROFILE).is_err());
+    }
+
+    #[test]
+    fn test_baseline_round_trip() {
+        let mut source = AnomalyDetector::new();
+        let mut profile = SensitivityProfile::aggressive();
+        profile.name = "memecoins".into();
+        source.save_profile(profile.clone()).unwrap();
+        source
+            .assign_profile(
+                ProfileTarget::Token("bonk".into()),
+                Some("memecoins".into()),
+            )
+            .unwrap();
+        feed_spike(&mut source, "bonk", 12);
+        let baseline = source.export_baseline("bonk").unwrap();
+        assert_eq!(baseline.profile, profile);
+
+        let json = serde_json::to_string(&baseline).unwrap();
+        let mut target = AnomalyDetector::new();
+        let imported = target
+            .import_baselines(vec![serde_json::from_str(&json).unwrap()])
+            .unwrap();
+        assert_eq!(imported, 1);
+        assert_eq!(target.resolve_profile_name("bonk"), "memecoins");
+        assert_eq!(
+            target.export_baseline("bonk").unwrap().price_history.len(),
+            13
+        );
+        assert!(target.get_anomalies(Some("bonk"), None).is_empty());
+    }
+
+    #[test]
+    fn test_social_spike_recorded_with_evidence() {
+        let mut detector = AnomalyDetector::new();
+        let spike = SocialSpike {
+            id: "spike-1".into(),
+            token: "bonk".into(),
+            detected_at: Utc::now().timestamp(),
+            severity: "high".into(),
+            evidence: SocialSpikeEvidence {
+                pattern: Some(SpikePattern::PrePump),
+                mentions_last_hour: 40,
+                baseline_per_hour: 2.0,
+                velocity_ratio: 20.0,
+                velocity_threshold: 3.0,
+                sentiment_baseline: 0.1,
+                sentiment_now: 0.6,
+                sentiment_shift: 0.5,
+                distinct_authors: 30,
+                coordinated_shilling: true,
+                new_low_follower_share: 0.8,
+                account_ages: Default::default(),
+                top_posts: Vec::new(),
+            },
+        };
+        detector.record_social_spike(&spike);
+
+        let anomalies = detector.get_anomalies(Some("bonk"), Some(SOCIAL_SPIKE_ANOMALY));
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].details["pattern"], "pre_pump");
+        assert!(anomalies[0].explanation.contains("coordinated shilling"));
+
+        let json = serde_json::to_value(&anomalies[0]).unwrap();
+        assert_eq!(json["evidence"]["kind"], "social_spike");
+        assert_eq!(json["evidence"]["pattern"], "pre_pump");
+        let restored: Anomaly = serde_json::from_value(json).unwrap();
+        assert!(matches!(
+            restored.evidence,
+            Some(AnomalyEvidence::SocialSpike(ref e)) if e.coordinated_shilling
+        ));
+    }
 }
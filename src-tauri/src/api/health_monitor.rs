@@ -1,9 +1,10 @@
+use crate::config::DataPaths;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::{Pool, Row, Sqlite, SqlitePool};
 use std::collections::HashMap;
 use std::sync::Arc;
-use tauri::{AppHandle, Manager};
+use tauri::AppHandle;
 use tokio::sync::RwLock;
 
 const HEALTH_DB_FILE: &str = "api_health.db";
@@ -111,7 +112,7 @@ impl ApiHealthMonitor {
     }
 
     fn health_db_path(app: &AppHandle) -> Result<std::path::PathBuf, HealthMonitorError> {
-        let mut path = app.path().app_data_dir().map_err(|err| {
+        let mut path = app.app_data_root().map_err(|err| {
             HealthMonitorError::Internal(format!("Unable to resolve app data directory: {err}"))
         })?;
 
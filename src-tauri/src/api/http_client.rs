@@ -0,0 +1,667 @@
+//! Shared HTTP layer for third-party providers.
+//!
+//! Every provider call goes through one pooled [`reqwest::Client`]. A
+//! [`ProviderClient`] adds what each provider needs on top: its API key from
+//! [`ApiConfigManager`], its timeout, retries for idempotent requests, and a
+//! single usage record per logical request once the retries are done.
+
+use crate::api_analytics::{
+    admit_with, budget_guard, record_with, ApiBudgetError, ApiUsageRecord, ApiUsageTracker,
+    RequestPriority,
+};
+use crate::api_config::ApiConfigManager;
+use crate::config::provider_url;
+use crate::security::keystore::Keystore;
+use chrono::Utc;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, RETRY_AFTER, USER_AGENT};
+use reqwest::{Client, Method, Request, RequestBuilder, Response, StatusCode};
+use serde::Serialize;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+
+static SHARED_HTTP: OnceLock<Client> = OnceLock::new();
+static KEY_SOURCE: OnceLock<AppHandle> = OnceLock::new();
+
+fn shared_http() -> Client {
+    SHARED_HTTP
+        .get_or_init(|| {
+            Client::builder()
+                .pool_idle_timeout(Duration::from_secs(90))
+                .build()
+                .unwrap_or_else(|_| Client::new())
+        })
+        .clone()
+}
+
+/// Lets provider clients resolve keys from the managed [`ApiConfigManager`]
+/// and report how each key performed. Called once at startup.
+pub fn install_http_key_source(app: &AppHandle) {
+    let _ = KEY_SOURCE.set(app.clone());
+}
+
+fn configured_key(service: &str) -> Option<String> {
+    let app = KEY_SOURCE.get()?;
+    let manager = app.try_state::<ApiConfigManager>()?;
+    let keystore = app.try_state::<Keystore>()?;
+    manager.api_key(service, &keystore)
+}
+
+fn report_key_outcome(service: &str, api_key: &str, success: bool) {
+    let Some(app) = KEY_SOURCE.get() else {
+        return;
+    };
+    if let (Some(manager), Some(keystore)) = (
+        app.try_state::<ApiConfigManager>(),
+        app.try_state::<Keystore>(),
+    ) {
+        manager.report_key_outcome(service, api_key, success, &keystore);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProviderAuth {
+    None,
+    /// The key goes in the named header as-is.
+    Header(&'static str),
+    Bearer,
+}
+
+/// What the shared layer needs to know about a provider.
+#[derive(Debug, Clone, Copy)]
+pub struct ProviderProfile {
+    /// Name used for keys, budgets and usage records.
+    pub service: &'static str,
+    pub base_url: &'static str,
+    pub auth: ProviderAuth,
+    pub timeout: Duration,
+    pub priority: RequestPriority,
+    pub user_agent: Option<&'static str>,
+}
+
+impl ProviderProfile {
+    pub const BIRDEYE: ProviderProfile = ProviderProfile {
+        service: "birdeye",
+        base_url: "https://public-api.birdeye.so",
+        auth: ProviderAuth::Header("X-API-KEY"),
+        timeout: Duration::from_secs(10),
+        priority: RequestPriority::Standard,
+        user_agent: None,
+    };
+
+    pub const JUPITER: ProviderProfile = ProviderProfile {
+        service: "jupiter",
+        base_url: "https://quote-api.jup.ag/v6",
+        auth: ProviderAuth::Bearer,
+        timeout: Duration::from_secs(20),
+        priority: RequestPriority::Critical,
+        user_agent: None,
+    };
+
+    /// JSON-RPC calls pass the resolved endpoint as an absolute URL; see
+    /// [`crate::api_config::solana_rpc`].
+    pub const SOLANA_RPC: ProviderProfile = ProviderProfile {
+        service: "solana_rpc",
+        base_url: "https://api.mainnet-beta.solana.com",
+        auth: ProviderAuth::None,
+        timeout: Duration::from_secs(20),
+        priority: RequestPriority::Standard,
+        user_agent: None,
+    };
+
+    pub const REDDIT: ProviderProfile = ProviderProfile {
+        service: "reddit",
+        base_url: "https://www.reddit.com",
+        auth: ProviderAuth::None,
+        timeout: Duration::from_secs(10),
+        priority: RequestPriority::Standard,
+        user_agent: Some("eclipse-market-pro:v0.1.0"),
+    };
+
+    /// Twitter's bearer token is per-user and passed with each request.
+    pub const TWITTER: ProviderProfile = ProviderProfile {
+        service: "twitter",
+        base_url: "https://api.twitter.com/2",
+        auth: ProviderAuth::None,
+        timeout: Duration::from_secs(10),
+        priority: RequestPriority::Standard,
+        user_agent: Some("eclipse-market-pro/0.1.0"),
+    };
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HttpRetryPolicy {
+    pub max_retries: u32,
+    pub base_delay: Duration,
+    /// Longest wait between attempts. A `Retry-After` beyond this is handed
+    /// back to the caller instead of being waited out.
+    pub max_delay: Duration,
+}
+
+impl Default for HttpRetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 2,
+            base_delay: Duration::from_millis(250),
+            max_delay: Duration::from_secs(10),
+        }
+    }
+}
+
+impl HttpRetryPolicy {
+    pub const NONE: HttpRetryPolicy = HttpRetryPolicy {
+        max_retries: 0,
+        base_delay: Duration::ZERO,
+        max_delay: Duration::ZERO,
+    };
+
+    /// Exponential backoff with equal jitter: half the step is fixed, the
+    /// other half random, so parallel callers spread out.
+    fn backoff(&self, attempt: u32) -> Duration {
+        let step = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_delay);
+        let half = step / 2;
+        half + half.mul_f64(rand::random_range(0.0..=1.0))
+    }
+
+    fn delay_for(
+        &self,
+        result: &Result<Response, reqwest::Error>,
+        attempt: u32,
+    ) -> Option<Duration> {
+        if attempt >= self.max_retries {
+            return None;
+        }
+        match result {
+            Ok(response) => {
+                let status = response.status();
+                if status != StatusCode::TOO_MANY_REQUESTS && !status.is_server_error() {
+                    return None;
+                }
+                match retry_after(response.headers()) {
+                    Some(hint) if hint > self.max_delay => None,
+                    Some(hint) => Some(hint),
+                    None => Some(self.backoff(attempt)),
+                }
+            }
+            Err(err) if err.is_timeout() || err.is_connect() => Some(self.backoff(attempt)),
+            Err(_) => None,
+        }
+    }
+}
+
+/// Parses `Retry-After` as delay seconds or an HTTP date.
+pub fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    Some(
+        (at.with_timezone(&Utc) - Utc::now())
+            .to_std()
+            .unwrap_or(Duration::ZERO),
+    )
+}
+
+#[derive(Clone)]
+pub struct ProviderClient {
+    profile: ProviderProfile,
+    http: Client,
+    base_url: String,
+    api_key: Option<String>,
+    timeout: Duration,
+    retry: HttpRetryPolicy,
+    tracker: Option<Arc<Mutex<ApiUsageTracker>>>,
+}
+
+impl std::fmt::Debug for ProviderClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProviderClient")
+            .field("service", &self.profile.service)
+            .field("base_url", &self.base_url)
+            .field("has_api_key", &self.api_key.is_some())
+            .field("retry", &self.retry)
+            .finish()
+    }
+}
+
+pub struct ProviderClientBuilder {
+    profile: ProviderProfile,
+    base_url: Option<String>,
+    api_key: Option<String>,
+    timeout: Option<Duration>,
+    retry: HttpRetryPolicy,
+    tracker: Option<Arc<Mutex<ApiUsageTracker>>>,
+}
+
+impl ProviderClientBuilder {
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = Some(base_url.into());
+        self
+    }
+
+    /// Overrides the configured key. `None` or an empty key keeps the
+    /// configured one.
+    pub fn api_key(mut self, api_key: Option<String>) -> Self {
+        self.api_key = api_key.filter(|key| !key.trim().is_empty());
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn retry_policy(mut self, retry: HttpRetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Meters against `tracker` instead of the installed budget guard.
+    pub fn usage_tracker(mut self, tracker: Arc<Mutex<ApiUsageTracker>>) -> Self {
+        self.tracker = Some(tracker);
+        self
+    }
+
+    pub fn build(self) -> ProviderClient {
+        ProviderClient {
+            http: shared_http(),
+            base_url: self
+                .base_url
+                .unwrap_or_else(|| provider_url(self.profile.base_url))
+                .trim_end_matches('/')
+                .to_string(),
+            api_key: self.api_key,
+            timeout: self.timeout.unwrap_or(self.profile.timeout),
+            retry: self.retry,
+            tracker: self.tracker,
+            profile: self.profile,
+        }
+    }
+}
+
+impl ProviderClient {
+    pub fn builder(profile: ProviderProfile) -> ProviderClientBuilder {
+        ProviderClientBuilder {
+            profile,
+            base_url: None,
+            api_key: None,
+            timeout: None,
+            retry: HttpRetryPolicy::default(),
+            tracker: None,
+        }
+    }
+
+    pub fn new(profile: ProviderProfile) -> Self {
+        Self::builder(profile).build()
+    }
+
+    pub fn service(&self) -> &'static str {
+        self.profile.service
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// True when an explicit or configured key is available.
+    pub fn has_api_key(&self) -> bool {
+        self.resolve_key().is_some()
+    }
+
+    pub fn get(&self, path: &str) -> ProviderRequest<'_> {
+        self.request(Method::GET, path)
+    }
+
+    pub fn post(&self, path: &str) -> ProviderRequest<'_> {
+        self.request(Method::POST, path)
+    }
+
+    /// `path` is appended to the base URL unless it is already absolute.
+    pub fn request(&self, method: Method, path: &str) -> ProviderRequest<'_> {
+        let url = if path.starts_with("http://") || path.starts_with("https://") {
+            path.to_string()
+        } else {
+            format!("{}{}", self.base_url, path)
+        };
+        let idempotent = matches!(
+            method,
+            Method::GET | Method::HEAD | Method::OPTIONS | Method::PUT | Method::DELETE
+        );
+        ProviderRequest {
+            client: self,
+            builder: self.http.request(method, url),
+            priority: self.profile.priority,
+            idempotent,
+            bearer_override: None,
+        }
+    }
+
+    fn resolve_key(&self) -> Option<String> {
+        if self.profile.auth == ProviderAuth::None {
+            return None;
+        }
+        self.api_key
+            .clone()
+            .or_else(|| configured_key(self.profile.service))
+    }
+
+    fn tracker(&self) -> Option<Arc<Mutex<ApiUsageTracker>>> {
+        self.tracker.clone().or_else(budget_guard)
+    }
+}
+
+pub struct ProviderRequest<'a> {
+    client: &'a ProviderClient,
+    builder: RequestBuilder,
+    priority: RequestPriority,
+    idempotent: bool,
+    bearer_override: Option<String>,
+}
+
+impl ProviderRequest<'_> {
+    pub fn query<T: Serialize + ?Sized>(mut self, query: &T) -> Self {
+        self.builder = self.builder.query(query);
+        self
+    }
+
+    pub fn json<T: Serialize + ?Sized>(mut self, body: &T) -> Self {
+        self.builder = self.builder.json(body);
+        self
+    }
+
+    pub fn header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.builder = self.builder.header(name, value);
+        self
+    }
+
+    /// Per-request bearer token, used instead of the provider key.
+    pub fn bearer_auth(mut self, token: impl Into<String>) -> Self {
+        self.bearer_override = Some(token.into());
+        self
+    }
+
+    pub fn priority(mut self, priority: RequestPriority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Marks a non-GET request as safe to repeat, e.g. a POST that only
+    /// builds something server-side.
+    pub fn idempotent(mut self) -> Self {
+        self.idempotent = true;
+        self
+    }
+
+    /// Sends the request through the budget guard, retrying idempotent
+    /// requests on 429, 5xx, timeouts and connection failures. Usage is
+    /// recorded once with the final status and total latency.
+    pub async fn send(self) -> Result<Response, ApiBudgetError> {
+        let client = self.client;
+        let service = client.profile.service;
+        let tracker = client.tracker();
+        if let Some(tracker) = &tracker {
+            admit_with(tracker, service, self.priority).await?;
+        }
+
+        let api_key = client.resolve_key();
+        let mut builder = self.builder.timeout(client.timeout);
+        if let Some(user_agent) = client.profile.user_agent {
+            builder = builder.header(USER_AGENT, user_agent);
+        }
+        builder = match (&self.bearer_override, &api_key, client.profile.auth) {
+            (Some(token), _, _) => builder.bearer_auth(token),
+            (None, Some(key), ProviderAuth::Header(name)) => builder.header(name, key.as_str()),
+            (None, Some(key), ProviderAuth::Bearer) => builder.bearer_auth(key),
+            _ => builder,
+        };
+        let request = builder.build().map_err(request_error)?;
+        let endpoint = request.url().path().to_string();
+
+        let started = Instant::now();
+        let result = execute_with_retries(client, request, self.idempotent).await;
+
+        if let Some(tracker) = &tracker {
+            let record = ApiUsageRecord {
+                service: service.to_string(),
+                endpoint,
+                timestamp: Utc::now(),
+                status_code: result
+                    .as_ref()
+                    .map(|response| response.status().as_u16())
+                    .unwrap_or(0),
+                latency_ms: started.elapsed().as_millis() as u64,
+            };
+            record_with(tracker, record);
+        }
+        if let (None, Some(key)) = (&self.bearer_override, &api_key) {
+            let success = result
+                .as_ref()
+                .is_ok_and(|response| response.status().is_success());
+            report_key_outcome(service, key, success);
+        }
+
+        result.map_err(request_error)
+    }
+}
+
+async fn execute_with_retries(
+    client: &ProviderClient,
+    request: Request,
+    idempotent: bool,
+) -> Result<Response, reqwest::Error> {
+    let policy = if idempotent {
+        client.retry
+    } else {
+        HttpRetryPolicy::NONE
+    };
+    let mut attempt = 0;
+    loop {
+        // Streaming bodies cannot be replayed, so they get a single attempt.
+        let Some(next) = request.try_clone() else {
+            return client.http.execute(request).await;
+        };
+        let result = client.http.execute(next).await;
+        match policy.delay_for(&result, attempt) {
+            Some(delay) => {
+                attempt += 1;
+                tracing::debug!(
+                    "Retrying {} request in {}ms (attempt {})",
+                    client.profile.service,
+                    delay.as_millis(),
+                    attempt
+                );
+                tokio::time::sleep(delay).await;
+            }
+            None => return result,
+        }
+    }
+}
+
+fn request_error(error: reqwest::Error) -> ApiBudgetError {
+    ApiBudgetError::Request {
+        message: error.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use httpmock::prelude::*;
+
+    const FAST_RETRIES: HttpRetryPolicy = HttpRetryPolicy {
+        max_retries: 2,
+        base_delay: Duration::from_millis(1),
+        max_delay: Duration::from_secs(2),
+    };
+
+    fn tracker(dir: &tempfile::TempDir) -> Arc<Mutex<ApiUsageTracker>> {
+        let tracker = ApiUsageTracker::new(dir.path().join("api_usage.json"), Vec::new()).unwrap();
+        Arc::new(Mutex::new(tracker))
+    }
+
+    fn recorded_calls(tracker: &Arc<Mutex<ApiUsageTracker>>, service: &str) -> u64 {
+        tracker
+            .lock()
+            .unwrap()
+            .get_analytics(1)
+            .unwrap()
+            .services
+            .get(service)
+            .map(|stats| stats.total_calls)
+            .unwrap_or(0)
+    }
+
+    fn client(
+        profile: ProviderProfile,
+        server: &MockServer,
+        tracker: &Arc<Mutex<ApiUsageTracker>>,
+    ) -> ProviderClientBuilder {
+        ProviderClient::builder(profile)
+            .base_url(server.base_url())
+            .retry_policy(FAST_RETRIES)
+            .usage_tracker(tracker.clone())
+    }
+
+    #[tokio::test]
+    async fn retries_server_errors_and_records_one_call() {
+        let dir = tempfile::tempdir().unwrap();
+        let usage = tracker(&dir);
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(GET).path("/defi/price");
+            then.status(503);
+        });
+
+        let client = client(ProviderProfile::BIRDEYE, &server, &usage).build();
+        let response = client.get("/defi/price").send().await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        mock.assert_hits(3);
+        assert_eq!(recorded_calls(&usage, "birdeye"), 1);
+    }
+
+    #[tokio::test]
+    async fn honors_retry_after_within_the_policy() {
+        let dir = tempfile::tempdir().unwrap();
+        let usage = tracker(&dir);
+        let server = MockServer::start();
+        let short = server.mock(|when, then| {
+            when.method(GET).path("/short");
+            then.status(429).header("Retry-After", "1");
+        });
+        let long = server.mock(|when, then| {
+            when.method(GET).path("/long");
+            then.status(429).header("Retry-After", "3600");
+        });
+
+        let client = client(ProviderProfile::BIRDEYE, &server, &usage)
+            .retry_policy(HttpRetryPolicy {
+                max_retries: 1,
+                ..FAST_RETRIES
+            })
+            .build();
+
+        let started = Instant::now();
+        client.get("/short").send().await.unwrap();
+        assert!(started.elapsed() >= Duration::from_secs(1));
+        short.assert_hits(2);
+
+        // Waiting an hour is the caller's decision, not the client's.
+        let response = client.get("/long").send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(
+            retry_after(response.headers()),
+            Some(Duration::from_secs(3600))
+        );
+        long.assert_hits(1);
+        assert_eq!(recorded_calls(&usage, "birdeye"), 2);
+    }
+
+    #[tokio::test]
+    async fn only_retries_idempotent_requests() {
+        let dir = tempfile::tempdir().unwrap();
+        let usage = tracker(&dir);
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(POST).path("/swap");
+            then.status(502);
+        });
+
+        let client = client(ProviderProfile::JUPITER, &server, &usage).build();
+        client.post("/swap").send().await.unwrap();
+        mock.assert_hits(1);
+
+        client.post("/swap").idempotent().send().await.unwrap();
+        mock.assert_hits(4);
+        assert_eq!(recorded_calls(&usage, "jupiter"), 2);
+    }
+
+    #[tokio::test]
+    async fn injects_provider_credentials() {
+        let dir = tempfile::tempdir().unwrap();
+        let usage = tracker(&dir);
+        let server = MockServer::start();
+        let birdeye = server.mock(|when, then| {
+            when.method(GET)
+                .path("/defi/price")
+                .header("X-API-KEY", "birdeye-key");
+            then.status(200);
+        });
+        let jupiter = server.mock(|when, then| {
+            when.method(GET)
+                .path("/quote")
+                .header("Authorization", "Bearer jupiter-key");
+            then.status(200);
+        });
+        let twitter = server.mock(|when, then| {
+            when.method(GET)
+                .path("/tweets/search/recent")
+                .header("Authorization", "Bearer user-token")
+                .header("User-Agent", "eclipse-market-pro/0.1.0");
+            then.status(200);
+        });
+
+        let birdeye_client = client(ProviderProfile::BIRDEYE, &server, &usage)
+            .api_key(Some("birdeye-key".to_string()))
+            .build();
+        let jupiter_client = client(ProviderProfile::JUPITER, &server, &usage)
+            .api_key(Some("jupiter-key".to_string()))
+            .build();
+        let twitter_client = client(ProviderProfile::TWITTER, &server, &usage).build();
+
+        let response = birdeye_client.get("/defi/price").send().await.unwrap();
+        assert!(response.status().is_success());
+        let response = jupiter_client.get("/quote").send().await.unwrap();
+        assert!(response.status().is_success());
+        let response = twitter_client
+            .get("/tweets/search/recent")
+            .bearer_auth("user-token")
+            .send()
+            .await
+            .unwrap();
+        assert!(response.status().is_success());
+
+        birdeye.assert();
+        jupiter.assert();
+        twitter.assert();
+    }
+
+    #[test]
+    fn parses_retry_after_dates() {
+        let mut headers = HeaderMap::new();
+        let at = (Utc::now() + chrono::Duration::seconds(30)).to_rfc2822();
+        headers.insert(RETRY_AFTER, HeaderValue::from_str(&at).unwrap());
+        let delay = retry_after(&headers).unwrap();
+        assert!(delay > Duration::from_secs(25) && delay <= Duration::from_secs(30));
+
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_static("Sun, 06 Nov 1994 08:49:37 GMT"),
+        );
+        assert_eq!(retry_after(&headers), Some(Duration::ZERO));
+    }
+}
@@ -1,15 +1,17 @@
 use base64::{engine::general_purpose, Engine as _};
-use reqwest::{
-    header::{HeaderMap, HeaderValue, AUTHORIZATION},
-    Client, StatusCode,
-};
+use reqwest::StatusCode;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::time::Duration;
 use thiserror::Error;
 use tracing::{debug, instrument, warn};
 
-const JUPITER_BASE_URL: &str = "https://quote-api.jup.ag/v6";
+use super::http_client::{ProviderClient, ProviderProfile};
+use crate::api_analytics::{record_quote_drift, ApiBudgetError, QuoteDriftRecord};
+use crate::trading::{record_execution_stage, ExecutionStage, SharedSafetyEngine, TradeOrigin};
+use crate::wallet::multi_wallet::MultiWalletManager;
+
+/// Trigger orders live on their own API rather than under the quote API.
+const TRIGGER_CREATE_ORDER_URL: &str = "https://lite-api.jup.ag/trigger/v1/createOrder";
 
 #[derive(Debug, Error)]
 pub enum JupiterError {
@@ -23,6 +25,25 @@ pub enum JupiterError {
     InvalidResponse(String),
     #[error("missing quote when executing swap")]
     MissingQuote,
+    #[error(
+        "quote drift: expected {expected}, now {current} ({drift_bps} bps); confirm the new quote"
+    )]
+    QuoteDrift {
+        expected: u64,
+        current: u64,
+        drift_bps: i64,
+    },
+    #[error(transparent)]
+    Budget(ApiBudgetError),
+}
+
+impl From<ApiBudgetError> for JupiterError {
+    fn from(value: ApiBudgetError) -> Self {
+        match value {
+            ApiBudgetError::Request { message } => JupiterError::Network(message),
+            other => JupiterError::Budget(other),
+        }
+    }
 }
 
 impl From<JupiterError> for String {
@@ -33,9 +54,7 @@ impl From<JupiterError> for String {
 
 #[derive(Debug, Clone)]
 pub struct JupiterClient {
-    http: Client,
-    base_url: String,
-    api_key: Option<String>,
+    http: ProviderClient,
 }
 
 impl Default for JupiterClient {
@@ -45,37 +64,22 @@ impl Default for JupiterClient {
 }
 
 impl JupiterClient {
+    /// Without an explicit key the configured Jupiter key is used, if any.
     pub fn new(api_key: Option<String>) -> Self {
-        let http = Client::builder()
-            .timeout(Duration::from_secs(20))
-            .build()
-            .expect("failed to construct http client");
-
         Self {
-            http,
-            base_url: JUPITER_BASE_URL.to_string(),
-            api_key,
+            http: ProviderClient::builder(ProviderProfile::JUPITER)
+                .api_key(api_key)
+                .build(),
         }
     }
 
     #[cfg(test)]
     pub fn with_base_url(base_url: impl Into<String>) -> Self {
-        let http = Client::new();
         Self {
-            http,
-            base_url: base_url.into(),
-            api_key: None,
-        }
-    }
-
-    fn headers(&self) -> Result<HeaderMap, JupiterError> {
-        let mut map = HeaderMap::new();
-        if let Some(api_key) = &self.api_key {
-            let value = HeaderValue::from_str(&format!("Bearer {api_key}"))
-                .map_err(|e| JupiterError::Serialization(e.to_string()))?;
-            map.insert(AUTHORIZATION, value);
+            http: ProviderClient::builder(ProviderProfile::JUPITER)
+                .base_url(base_url)
+                .build(),
         }
-        Ok(map)
     }
 }
 
@@ -251,6 +255,91 @@ pub struct SwapResponse {
     pub error: Option<Value>,
 }
 
+/// A resting limit order for Jupiter's trigger program: sell `making_amount`
+/// of `input_mint` for at least `taking_amount` of `output_mint`, both in
+/// base units.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TriggerOrderInput {
+    pub input_mint: String,
+    pub output_mint: String,
+    pub maker: String,
+    pub making_amount: u64,
+    pub taking_amount: u64,
+}
+
+/// The unsigned transaction that opens a trigger order, which the maker's
+/// wallet still has to sign and send.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TriggerOrderResponse {
+    pub order: String,
+    pub transaction: String,
+    #[serde(default)]
+    pub request_id: Option<String>,
+}
+
+/// How far a fresh quote moved from the one a trade was approved on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuoteDrift {
+    pub expected: u64,
+    pub current: u64,
+    /// Adverse move in basis points of `expected`; negative when the fresh
+    /// quote is better.
+    pub drift_bps: i64,
+}
+
+impl QuoteDrift {
+    /// Exact-in swaps compare the output received, exact-out swaps the input
+    /// spent.
+    pub fn between(approved: &QuoteResponse, fresh: &QuoteResponse) -> Result<Self, JupiterError> {
+        let (expected, current) = match approved.swap_mode {
+            SwapMode::ExactIn => (&approved.output_amount, &fresh.output_amount),
+            SwapMode::ExactOut => (&approved.input_amount, &fresh.input_amount),
+        };
+        let expected = parse_base_units(expected)?;
+        let current = parse_base_units(current)?;
+        let moved = match approved.swap_mode {
+            SwapMode::ExactIn => expected as i128 - current as i128,
+            SwapMode::ExactOut => current as i128 - expected as i128,
+        };
+        let drift_bps = if expected == 0 {
+            0
+        } else {
+            (moved * 10_000 / expected as i128) as i64
+        };
+        Ok(Self {
+            expected,
+            current,
+            drift_bps,
+        })
+    }
+
+    fn token(approved: &QuoteResponse) -> &str {
+        match approved.swap_mode {
+            SwapMode::ExactIn => &approved.output_mint,
+            SwapMode::ExactOut => &approved.input_mint,
+        }
+    }
+}
+
+impl From<QuoteDrift> for JupiterError {
+    fn from(drift: QuoteDrift) -> Self {
+        JupiterError::QuoteDrift {
+            expected: drift.expected,
+            current: drift.current,
+            drift_bps: drift.drift_bps,
+        }
+    }
+}
+
+fn parse_base_units(amount: &str) -> Result<u64, JupiterError> {
+    amount
+        .parse()
+        .map_err(|_| JupiterError::InvalidResponse(format!("invalid amount {amount}")))
+}
+
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 struct QuoteQueryParams<'a> {
@@ -295,9 +384,13 @@ struct SwapRequestBody<'a> {
 
 #[tauri::command]
 #[instrument(skip(input), fields(input_mint = %input.input_mint, output_mint = %input.output_mint, amount = input.amount))]
-pub async fn jupiter_quote(input: QuoteCommandInput) -> Result<QuoteResult, String> {
+pub async fn jupiter_quote(
+    input: QuoteCommandInput,
+    correlation_id: Option<String>,
+) -> Result<QuoteResult, String> {
     let client = JupiterClient::default();
     let response = client.quote(&input).await.map_err(String::from)?;
+    record_execution_stage(correlation_id.as_deref(), quote_stage(&response, false)).await;
     let route = parse_route_plan(&response);
     Ok(QuoteResult {
         context_slot: response.context_slot,
@@ -309,15 +402,62 @@ pub async fn jupiter_quote(input: QuoteCommandInput) -> Result<QuoteResult, Stri
 
 #[tauri::command]
 #[instrument(skip(input), fields(user = %input.user_public_key))]
-pub async fn jupiter_swap(input: SwapCommandInput) -> Result<SwapResult, String> {
+pub async fn jupiter_swap(
+    input: SwapCommandInput,
+    correlation_id: Option<String>,
+    wallets: tauri::State<'_, MultiWalletManager>,
+    safety: tauri::State<'_, SharedSafetyEngine>,
+) -> Result<SwapResult, String> {
+    let correlation_id = correlation_id.as_deref();
+    wallets
+        .ensure_can_sign(&input.user_public_key)
+        .map_err(|e| e.to_string())?;
     if input.quote.route_plan.is_empty() {
         return Err(JupiterError::MissingQuote.into());
     }
 
+    let max_drift_bps = safety
+        .read()
+        .await
+        .get_policy()
+        .max_quote_drift_bps(TradeOrigin::Manual);
     let client = JupiterClient::default();
-    let response = client
+    let quote = match client
+        .requote(&input.quote, max_drift_bps, TradeOrigin::Manual)
+        .await
+    {
+        Ok(quote) => quote,
+        Err(e) => {
+            record_execution_stage(correlation_id, swap_error_stage(&e)).await;
+            return Err(e.into());
+        }
+    };
+    record_execution_stage(correlation_id, quote_stage(&quote, true)).await;
+    let input = SwapCommandInput { quote, ..input };
+    let response = match client
         .execute_swap(&input, input.simulate.unwrap_or(false))
-        .await?;
+        .await
+    {
+        Ok(response) => response,
+        Err(e) => {
+            record_execution_stage(correlation_id, swap_error_stage(&e)).await;
+            return Err(e.into());
+        }
+    };
+    record_execution_stage(
+        correlation_id,
+        ExecutionStage::PriorityFee {
+            micro_lamports: input
+                .priority_fee_config
+                .as_ref()
+                .and_then(|config| config.compute_unit_price_micro_lamports),
+            prioritization_fee_lamports: response
+                .prioritization_fee_lamports
+                .as_deref()
+                .and_then(|fee| fee.parse().ok()),
+        },
+    )
+    .await;
     let swap_transaction = response
         .swap_transaction
         .ok_or_else(|| JupiterError::InvalidResponse("missing transaction".into()))?;
@@ -338,6 +478,26 @@ pub async fn jupiter_swap(input: SwapCommandInput) -> Result<SwapResult, String>
     })
 }
 
+fn quote_stage(quote: &QuoteResponse, requote: bool) -> ExecutionStage {
+    ExecutionStage::Quote {
+        input_mint: quote.input_mint.clone(),
+        output_mint: quote.output_mint.clone(),
+        input_amount: quote.input_amount.clone(),
+        output_amount: quote.output_amount.clone(),
+        price_impact_pct: quote.price_impact_pct,
+        context_slot: quote.context_slot,
+        requote,
+    }
+}
+
+fn swap_error_stage(error: &JupiterError) -> ExecutionStage {
+    ExecutionStage::Error {
+        source: "jupiter".to_string(),
+        message: error.to_string(),
+        retrying: false,
+    }
+}
+
 fn parse_route_plan(quote: &QuoteResponse) -> ParsedRoutePlan {
     let hops: Vec<ParsedRouteHop> = quote
         .route_plan
@@ -420,15 +580,98 @@ impl JupiterClient {
 
         let query = serde_urlencoded::to_string(params)
             .map_err(|e| JupiterError::Serialization(e.to_string()))?;
-        let url = format!("{}/quote?{}", self.base_url, query);
+        let response = self.http.get(&format!("/quote?{query}")).send().await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "<unavailable>".into());
+            return Err(JupiterError::Http { status, body });
+        }
+
+        response
+            .json::<QuoteResponse>()
+            .await
+            .map_err(|e| JupiterError::Serialization(e.to_string()))
+    }
+
+    /// Fetches a fresh quote for `approved` right before the swap is built and
+    /// returns it if it drifted no more than `max_drift_bps`. Manual trades
+    /// fail with [`JupiterError::QuoteDrift`] so the user can re-confirm;
+    /// automated ones re-quote once more before giving up.
+    pub async fn requote(
+        &self,
+        approved: &QuoteResponse,
+        max_drift_bps: u32,
+        origin: TradeOrigin,
+    ) -> Result<QuoteResponse, JupiterError> {
+        let input = QuoteCommandInput {
+            input_mint: approved.input_mint.clone(),
+            output_mint: approved.output_mint.clone(),
+            amount: match approved.swap_mode {
+                SwapMode::ExactIn => parse_base_units(&approved.input_amount)?,
+                SwapMode::ExactOut => parse_base_units(&approved.output_amount)?,
+            },
+            slippage_bps: approved.slippage_bps,
+            swap_mode: Some(approved.swap_mode),
+            platform_fee_bps: None,
+            only_direct_routes: None,
+            referral_account: None,
+            as_legacy_transaction: None,
+            priority_fee_config: None,
+        };
+        let mut retries = if origin.is_automated() { 1 } else { 0 };
+
+        loop {
+            let fresh = self.quote(&input).await?;
+            let drift = QuoteDrift::between(approved, &fresh)?;
+            let accepted = drift.drift_bps <= i64::from(max_drift_bps);
+            record_quote_drift(QuoteDriftRecord {
+                token: QuoteDrift::token(approved).to_string(),
+                drift_bps: drift.drift_bps,
+                automated: origin.is_automated(),
+                accepted,
+                timestamp: chrono::Utc::now(),
+            });
+            if accepted {
+                return Ok(fresh);
+            }
+            warn!(
+                drift_bps = drift.drift_bps,
+                max_drift_bps, "fresh quote drifted past the approved one"
+            );
+            if retries == 0 {
+                return Err(drift.into());
+            }
+            retries -= 1;
+        }
+    }
 
+    /// Builds the transaction for a resting limit order on Jupiter's trigger
+    /// program.
+    pub async fn create_trigger_order(
+        &self,
+        input: &TriggerOrderInput,
+    ) -> Result<TriggerOrderResponse, JupiterError> {
+        let body = serde_json::json!({
+            "inputMint": input.input_mint,
+            "outputMint": input.output_mint,
+            "maker": input.maker,
+            "payer": input.maker,
+            "params": {
+                "makingAmount": input.making_amount.to_string(),
+                "takingAmount": input.taking_amount.to_string(),
+            },
+            "computeUnitPrice": "auto",
+        });
         let response = self
             .http
-            .get(&url)
-            .headers(self.headers()?)
+            .post(TRIGGER_CREATE_ORDER_URL)
+            .json(&body)
             .send()
-            .await
-            .map_err(|e| JupiterError::Network(e.to_string()))?;
+            .await?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -440,7 +683,7 @@ impl JupiterClient {
         }
 
         response
-            .json::<QuoteResponse>()
+            .json::<TriggerOrderResponse>()
             .await
             .map_err(|e| JupiterError::Serialization(e.to_string()))
     }
@@ -468,14 +711,16 @@ impl JupiterClient {
             simulate: Some(simulate),
         };
 
+        // Building the swap transaction has no server-side effect, so a
+        // failed attempt is safe to repeat.
         let response = self
             .http
-            .post(format!("{}/swap", self.base_url))
-            .headers(self.headers().map_err(String::from)?)
+            .post("/swap")
             .json(&body)
+            .idempotent()
             .send()
             .await
-            .map_err(|e| JupiterError::Network(e.to_string()).to_string())?;
+            .map_err(|e| JupiterError::from(e).to_string())?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -606,6 +851,109 @@ mod tests {
         assert_eq!(result.compute_units_consumed, Some(50000));
     }
 
+    fn drifted_quote(output_amount: &str) -> QuoteResponse {
+        QuoteResponse {
+            output_amount: output_amount.into(),
+            ..mock_quote_response()
+        }
+    }
+
+    #[test]
+    fn quote_drift_measures_the_adverse_side() {
+        let approved = mock_quote_response();
+
+        let worse = QuoteDrift::between(&approved, &drifted_quote("985050")).unwrap();
+        assert_eq!(worse.expected, 995000);
+        assert_eq!(worse.current, 985050);
+        assert_eq!(worse.drift_bps, 100);
+
+        let better = QuoteDrift::between(&approved, &drifted_quote("999975")).unwrap();
+        assert_eq!(better.drift_bps, -50);
+
+        let exact_out = QuoteResponse {
+            swap_mode: SwapMode::ExactOut,
+            ..mock_quote_response()
+        };
+        let fresh = QuoteResponse {
+            input_amount: "1020000".into(),
+            ..exact_out.clone()
+        };
+        assert_eq!(
+            QuoteDrift::between(&exact_out, &fresh).unwrap().drift_bps,
+            200
+        );
+    }
+
+    #[tokio::test]
+    async fn requote_returns_fresh_quote_within_threshold() {
+        let server = MockServer::start();
+        let approved = mock_quote_response();
+        let mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/quote")
+                .query_param("amount", approved.input_amount.clone());
+            then.status(200)
+                .json_body(serde_json::to_value(drifted_quote("990025")).unwrap());
+        });
+
+        let client = JupiterClient::with_base_url(server.base_url());
+        let fresh = client
+            .requote(&approved, 100, TradeOrigin::Manual)
+            .await
+            .expect("drift within threshold");
+
+        assert_eq!(fresh.output_amount, "990025");
+        mock.assert_hits(1);
+    }
+
+    #[tokio::test]
+    async fn requote_aborts_manual_trades_past_threshold() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(GET).path("/quote");
+            then.status(200)
+                .json_body(serde_json::to_value(drifted_quote("985050")).unwrap());
+        });
+
+        let client = JupiterClient::with_base_url(server.base_url());
+        let err = client
+            .requote(&mock_quote_response(), 50, TradeOrigin::Manual)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            JupiterError::QuoteDrift {
+                expected: 995000,
+                current: 985050,
+                drift_bps: 100
+            }
+        ));
+        mock.assert_hits(1);
+    }
+
+    #[tokio::test]
+    async fn requote_retries_once_for_automated_trades() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(GET).path("/quote");
+            then.status(200)
+                .json_body(serde_json::to_value(drifted_quote("985050")).unwrap());
+        });
+
+        let client = JupiterClient::with_base_url(server.base_url());
+        let err = client
+            .requote(&mock_quote_response(), 50, TradeOrigin::Dca)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            JupiterError::QuoteDrift { drift_bps: 100, .. }
+        ));
+        mock.assert_hits(2);
+    }
+
     fn dummy_versioned_tx() -> solana_sdk::transaction::VersionedTransaction {
         use solana_sdk::{
             instruction::CompiledInstruction,
@@ -1,9 +1,11 @@
 pub mod health_commands;
 pub mod health_monitor;
+pub mod http_client;
 pub mod jupiter;
 pub mod trading_execution;
 
 pub use health_commands::*;
 pub use health_monitor::*;
+pub use http_client::*;
 pub use jupiter::*;
 pub use trading_execution::*;
@@ -1,3 +1,4 @@
+use crate::trading::{record_execution_stage, ExecutionStage};
 use serde::{Deserialize, Serialize};
 use std::time::{SystemTime, UNIX_EPOCH};
 use tracing::{debug, instrument};
@@ -92,7 +93,9 @@ pub async fn get_network_congestion() -> Result<CongestionData, String> {
 /// Get priority fee estimates for different presets
 #[tauri::command]
 #[instrument]
-pub async fn get_priority_fee_estimates() -> Result<Vec<PriorityFeeEstimate>, String> {
+pub async fn get_priority_fee_estimates(
+    correlation_id: Option<String>,
+) -> Result<Vec<PriorityFeeEstimate>, String> {
     let congestion = get_network_congestion().await?;
 
     let multiplier = match congestion.level.as_str() {
@@ -120,6 +123,17 @@ pub async fn get_priority_fee_estimates() -> Result<Vec<PriorityFeeEstimate>, St
     ];
 
     debug!("Priority fee estimates: {:?}", estimates);
+    record_execution_stage(
+        correlation_id.as_deref(),
+        ExecutionStage::PriorityFeeEstimates {
+            congestion: congestion.level,
+            micro_lamports: estimates
+                .iter()
+                .map(|estimate| (estimate.preset.clone(), estimate.micro_lamports))
+                .collect(),
+        },
+    )
+    .await;
     Ok(estimates)
 }
 
@@ -129,6 +143,7 @@ pub async fn get_priority_fee_estimates() -> Result<Vec<PriorityFeeEstimate>, St
 pub async fn submit_with_mev_protection(
     transaction_base64: String,
     config: MEVProtectionConfig,
+    correlation_id: Option<String>,
 ) -> Result<MEVProtectionResult, String> {
     debug!(
         "Submitting transaction with MEV protection: jito={}, private_rpc={}",
@@ -136,12 +151,14 @@ pub async fn submit_with_mev_protection(
     );
 
     if !config.enabled {
-        return Ok(MEVProtectionResult {
+        let result = MEVProtectionResult {
             protected: false,
             method: None,
             bundle_id: None,
             estimated_savings: 0.0,
-        });
+        };
+        record_mev_submission(correlation_id.as_deref(), &result).await;
+        return Ok(result);
     }
 
     // In a real implementation, this would:
@@ -179,12 +196,28 @@ pub async fn submit_with_mev_protection(
         method, bundle_id, estimated_savings
     );
 
-    Ok(MEVProtectionResult {
+    let result = MEVProtectionResult {
         protected: true,
         method,
         bundle_id,
         estimated_savings,
-    })
+    };
+    record_mev_submission(correlation_id.as_deref(), &result).await;
+    Ok(result)
+}
+
+async fn record_mev_submission(correlation_id: Option<&str>, result: &MEVProtectionResult) {
+    record_execution_stage(
+        correlation_id,
+        ExecutionStage::MevSubmission {
+            protected: result.protected,
+            method: result.method.clone(),
+            // Bundles are only submitted so far; landing is not tracked.
+            bundle_status: result.bundle_id.as_ref().map(|_| "submitted".to_string()),
+            bundle_id: result.bundle_id.clone(),
+        },
+    )
+    .await;
 }
 
 /// Validate if a trade should be blocked based on slippage/impact thresholds
@@ -226,7 +259,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_get_priority_fee_estimates() {
-        let result = get_priority_fee_estimates().await;
+        let result = get_priority_fee_estimates(None).await;
         assert!(result.is_ok());
 
         let estimates = result.unwrap();
@@ -262,7 +295,7 @@ mod tests {
             use_private_rpc: false,
         };
 
-        let result = submit_with_mev_protection("test_tx".to_string(), config).await;
+        let result = submit_with_mev_protection("test_tx".to_string(), config, None).await;
         assert!(result.is_ok());
 
         let protection = result.unwrap();
@@ -278,7 +311,7 @@ mod tests {
             use_private_rpc: false,
         };
 
-        let result = submit_with_mev_protection("test_tx".to_string(), config).await;
+        let result = submit_with_mev_protection("test_tx".to_string(), config, None).await;
         assert!(result.is_ok());
 
         let protection = result.unwrap();
@@ -0,0 +1,594 @@
+use super::{AlertType, ApiUsageRecord, ApiUsageTracker, FairUseLimit, UsageAlert};
+use crate::api_config::ProviderBudget;
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tauri::Emitter;
+use thiserror::Error;
+
+/// Longest a throttled request waits for capacity before it is rejected, so
+/// callers can fall back to cached data instead of stalling.
+const MAX_THROTTLE_WAIT: Duration = Duration::from_secs(10);
+/// Non-critical requests allowed back to back once throttling starts.
+const THROTTLE_BURST: f64 = 3.0;
+
+static BUDGET_GUARD: OnceLock<Arc<Mutex<ApiUsageTracker>>> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RequestPriority {
+    /// Refreshes and lookups that can wait or fall back to cached data.
+    Standard,
+    /// Trade-critical calls such as quotes. Never throttled, only hard-stopped.
+    Critical,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetPeriod {
+    Daily,
+    Monthly,
+}
+
+impl BudgetPeriod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BudgetPeriod::Daily => "daily",
+            BudgetPeriod::Monthly => "monthly",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            BudgetPeriod::Daily => "Daily",
+            BudgetPeriod::Monthly => "Monthly",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Error)]
+#[serde(
+    tag = "kind",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum ApiBudgetError {
+    #[error("{service} {} quota exceeded ({used}/{limit}), resets at {resets_at}", .period.as_str())]
+    QuotaExceeded {
+        service: String,
+        period: BudgetPeriod,
+        used: u64,
+        limit: u64,
+        resets_at: DateTime<Utc>,
+    },
+    #[error("{service} is throttled near its daily budget, retry in {retry_after_ms}ms")]
+    Throttled {
+        service: String,
+        retry_after_ms: u64,
+    },
+    #[error("request failed: {message}")]
+    Request { message: String },
+}
+
+impl From<ApiBudgetError> for String {
+    fn from(value: ApiBudgetError) -> Self {
+        value.to_string()
+    }
+}
+
+/// Month-to-date burn rate for a provider and when it runs out at that rate.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetProjection {
+    pub service: String,
+    pub daily_used: u64,
+    pub daily_limit: u64,
+    pub monthly_used: u64,
+    pub monthly_limit: u64,
+    pub average_daily_calls: f64,
+    pub projected_monthly_calls: u64,
+    /// Set when the budget runs out before the monthly reset.
+    pub exhausts_at: Option<DateTime<Utc>>,
+    pub summary: String,
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct TokenBucket {
+    tokens: f64,
+    capacity: f64,
+    refill_per_sec: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(capacity: f64, refill_per_sec: f64, now: Instant) -> Self {
+        Self {
+            tokens: capacity,
+            capacity,
+            refill_per_sec,
+            last_refill: now,
+        }
+    }
+
+    /// Takes a token and returns how long the caller has to wait for it.
+    fn take(&mut self, now: Instant) -> Duration {
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        self.last_refill = now;
+        self.tokens -= 1.0;
+
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else if self.refill_per_sec > 0.0 {
+            Duration::from_secs_f64(-self.tokens / self.refill_per_sec)
+        } else {
+            Duration::MAX
+        }
+    }
+
+    fn refund(&mut self) {
+        self.tokens = (self.tokens + 1.0).min(self.capacity);
+    }
+}
+
+impl ApiUsageTracker {
+    /// Checks `service`'s budgets before a request goes out. Returns how long
+    /// a throttled request should wait, or an error once a budget is spent.
+    pub fn admit(
+        &self,
+        service: &str,
+        priority: RequestPriority,
+        now: DateTime<Utc>,
+    ) -> Result<Duration, ApiBudgetError> {
+        let lock_error = |what: &str| ApiBudgetError::Request {
+            message: format!("Failed to lock {what}"),
+        };
+        let mut limits = self
+            .fair_use_limits
+            .lock()
+            .map_err(|_| lock_error("fair use limits"))?;
+        let Some(limit) = limits.get_mut(service) else {
+            return Ok(Duration::ZERO);
+        };
+        roll_budget_periods(limit, now);
+
+        for period in [BudgetPeriod::Monthly, BudgetPeriod::Daily] {
+            let (used, cap) = period_usage(limit, period);
+            if used >= cap {
+                return Err(ApiBudgetError::QuotaExceeded {
+                    service: service.to_string(),
+                    period,
+                    used,
+                    limit: cap,
+                    resets_at: period_reset(limit, period),
+                });
+            }
+        }
+
+        let throttle_at = self
+            .budgets
+            .lock()
+            .map_err(|_| lock_error("provider budgets"))?
+            .get(service)
+            .map(|budget| budget.throttle_at_percent)
+            .unwrap_or(100.0);
+        limit.throttled = budget_usage_percent(limit, BudgetPeriod::Daily) >= throttle_at;
+
+        let mut buckets = self.buckets.lock().map_err(|_| lock_error("throttle"))?;
+        if !limit.throttled {
+            buckets.remove(service);
+            return Ok(Duration::ZERO);
+        }
+        if priority == RequestPriority::Critical {
+            return Ok(Duration::ZERO);
+        }
+
+        // Spread what is left of today's budget evenly until the reset.
+        let remaining = limit.daily_limit.saturating_sub(limit.current_daily_usage) as f64;
+        let seconds_left = (limit.reset_at - now).num_seconds().max(1) as f64;
+        let refill_per_sec = remaining / seconds_left;
+        let instant = Instant::now();
+        let bucket = buckets
+            .entry(service.to_string())
+            .or_insert_with(|| TokenBucket::new(THROTTLE_BURST, refill_per_sec, instant));
+        bucket.refill_per_sec = refill_per_sec;
+
+        let wait = bucket.take(instant);
+        if wait > MAX_THROTTLE_WAIT {
+            bucket.refund();
+            return Err(ApiBudgetError::Throttled {
+                service: service.to_string(),
+                retry_after_ms: wait.as_millis().min(u64::MAX as u128) as u64,
+            });
+        }
+        Ok(wait)
+    }
+
+    /// Emits an `api_budget_alert` event the first time usage crosses each
+    /// configured threshold within a period.
+    pub(super) fn notify_budget_thresholds(&self, limit: &FairUseLimit) -> Result<(), String> {
+        let Some(budget) = self
+            .budgets
+            .lock()
+            .map_err(|_| "Failed to lock provider budgets".to_string())?
+            .get(&limit.service)
+            .cloned()
+        else {
+            return Ok(());
+        };
+        let mut notified = self
+            .notified
+            .lock()
+            .map_err(|_| "Failed to lock budget notifications".to_string())?;
+
+        for period in [BudgetPeriod::Daily, BudgetPeriod::Monthly] {
+            let usage_percent = budget_usage_percent(limit, period);
+            let Some(threshold) = crossed_threshold(&budget, usage_percent) else {
+                continue;
+            };
+            let resets_at = period_reset(limit, period);
+            let key = (limit.service.clone(), period);
+            if notified
+                .get(&key)
+                .is_some_and(|(at, highest)| *at == resets_at && *highest >= threshold)
+            {
+                continue;
+            }
+            notified.insert(key, (resets_at, threshold));
+
+            let (used, cap) = period_usage(limit, period);
+            let alert = UsageAlert {
+                service: limit.service.clone(),
+                alert_type: if usage_percent >= 100.0 {
+                    AlertType::LimitExceeded
+                } else {
+                    AlertType::ApproachingLimit
+                },
+                message: format!(
+                    "{} has used {:.0}% of its {} budget ({}/{})",
+                    provider_name(&limit.service),
+                    usage_percent,
+                    period.as_str(),
+                    used,
+                    cap
+                ),
+                timestamp: Utc::now(),
+            };
+            if let Some(app) = &self.app_handle {
+                let _ = app.emit("api_budget_alert", &alert);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn budget_projections(&self, now: DateTime<Utc>) -> Result<Vec<BudgetProjection>, String> {
+        let mut limits = self
+            .fair_use_limits
+            .lock()
+            .map_err(|_| "Failed to lock fair use limits".to_string())?;
+
+        let mut projections: Vec<BudgetProjection> = limits
+            .values_mut()
+            .map(|limit| {
+                roll_budget_periods(limit, now);
+                project_budget(limit, now)
+            })
+            .collect();
+        projections.sort_by(|a, b| a.service.cmp(&b.service));
+        Ok(projections)
+    }
+}
+
+/// Routes all budget-checked requests through `tracker`. Called once at startup.
+pub fn install_budget_guard(tracker: Arc<Mutex<ApiUsageTracker>>) {
+    let _ = BUDGET_GUARD.set(tracker);
+}
+
+/// The tracker installed by [`install_budget_guard`], if any.
+pub fn budget_guard() -> Option<Arc<Mutex<ApiUsageTracker>>> {
+    BUDGET_GUARD.get().cloned()
+}
+
+/// Waits until `service`'s budget admits another call. For work that spends
+/// a provider call through a path other than [`send_metered`].
+pub async fn admit_metered(service: &str, priority: RequestPriority) -> Result<(), ApiBudgetError> {
+    match BUDGET_GUARD.get() {
+        Some(tracker) => admit_with(tracker, service, priority).await,
+        None => Ok(()),
+    }
+}
+
+/// [`admit_metered`] against a specific tracker.
+pub async fn admit_with(
+    tracker: &Mutex<ApiUsageTracker>,
+    service: &str,
+    priority: RequestPriority,
+) -> Result<(), ApiBudgetError> {
+    let wait = tracker
+        .lock()
+        .map_err(|_| ApiBudgetError::Request {
+            message: "Failed to lock usage tracker".to_string(),
+        })?
+        .admit(service, priority, Utc::now())?;
+    if !wait.is_zero() {
+        tokio::time::sleep(wait).await;
+    }
+    Ok(())
+}
+
+/// Records one provider call. Failures are logged rather than surfaced, since
+/// the call itself has already happened.
+pub fn record_with(tracker: &Mutex<ApiUsageTracker>, record: ApiUsageRecord) {
+    let service = record.service.clone();
+    if let Ok(tracker) = tracker.lock() {
+        if let Err(err) = tracker.record_usage(record) {
+            tracing::warn!("Failed to record {service} usage: {err}");
+        }
+    }
+}
+
+/// Sends a provider request through the budget guard and records its usage.
+/// Before the guard is installed the request goes out unchecked.
+pub async fn send_metered(
+    service: &str,
+    priority: RequestPriority,
+    request: reqwest::RequestBuilder,
+) -> Result<reqwest::Response, ApiBudgetError> {
+    admit_metered(service, priority).await?;
+    let guard = BUDGET_GUARD.get().cloned();
+
+    let (client, request) = request.build_split();
+    let request = request.map_err(|e| ApiBudgetError::Request {
+        message: e.to_string(),
+    })?;
+    let endpoint = request.url().path().to_string();
+    let started = Instant::now();
+    let result = client.execute(request).await;
+
+    if let Some(tracker) = guard {
+        let record = ApiUsageRecord {
+            service: service.to_string(),
+            endpoint,
+            timestamp: Utc::now(),
+            status_code: result
+                .as_ref()
+                .map(|response| response.status().as_u16())
+                .unwrap_or(0),
+            latency_ms: started.elapsed().as_millis() as u64,
+        };
+        record_with(&tracker, record);
+    }
+
+    result.map_err(|e| ApiBudgetError::Request {
+        message: e.to_string(),
+    })
+}
+
+pub(super) fn period_start(period: BudgetPeriod, now: DateTime<Utc>) -> DateTime<Utc> {
+    let date = now.date_naive();
+    let start = match period {
+        BudgetPeriod::Daily => date,
+        BudgetPeriod::Monthly => date.with_day(1).unwrap_or(date),
+    };
+    start
+        .and_hms_opt(0, 0, 0)
+        .map(|start| start.and_utc())
+        .unwrap_or(now)
+}
+
+pub(super) fn next_period_reset(period: BudgetPeriod, now: DateTime<Utc>) -> DateTime<Utc> {
+    let start = period_start(period, now);
+    match period {
+        BudgetPeriod::Daily => start + ChronoDuration::days(1),
+        BudgetPeriod::Monthly => {
+            let (year, month) = if start.month() == 12 {
+                (start.year() + 1, 1)
+            } else {
+                (start.year(), start.month() + 1)
+            };
+            NaiveDate::from_ymd_opt(year, month, 1)
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+                .map(|reset| reset.and_utc())
+                .unwrap_or(start + ChronoDuration::days(31))
+        }
+    }
+}
+
+pub(super) fn roll_budget_periods(limit: &mut FairUseLimit, now: DateTime<Utc>) {
+    if now >= limit.reset_at {
+        limit.current_daily_usage = 0;
+        limit.reset_at = next_period_reset(BudgetPeriod::Daily, now);
+    }
+    if now >= limit.monthly_reset_at {
+        limit.current_monthly_usage = 0;
+        limit.monthly_reset_at = next_period_reset(BudgetPeriod::Monthly, now);
+    }
+}
+
+fn period_reset(limit: &FairUseLimit, period: BudgetPeriod) -> DateTime<Utc> {
+    match period {
+        BudgetPeriod::Daily => limit.reset_at,
+        BudgetPeriod::Monthly => limit.monthly_reset_at,
+    }
+}
+
+pub(super) fn period_usage(limit: &FairUseLimit, period: BudgetPeriod) -> (u64, u64) {
+    match period {
+        BudgetPeriod::Daily => (limit.current_daily_usage, limit.daily_limit),
+        BudgetPeriod::Monthly => (limit.current_monthly_usage, limit.monthly_limit),
+    }
+}
+
+pub(super) fn budget_usage_percent(limit: &FairUseLimit, period: BudgetPeriod) -> f64 {
+    let (used, cap) = period_usage(limit, period);
+    if cap == 0 {
+        return 100.0;
+    }
+    used as f64 / cap as f64 * 100.0
+}
+
+/// Highest alert threshold at or below `usage_percent`. Reaching 100% always
+/// counts, whatever thresholds are configured.
+pub(super) fn crossed_threshold(budget: &ProviderBudget, usage_percent: f64) -> Option<f64> {
+    budget
+        .alert_thresholds
+        .iter()
+        .copied()
+        .chain(std::iter::once(100.0))
+        .filter(|threshold| usage_percent >= *threshold)
+        .max_by(f64::total_cmp)
+}
+
+fn project_budget(limit: &FairUseLimit, now: DateTime<Utc>) -> BudgetProjection {
+    let month_start = period_start(BudgetPeriod::Monthly, now);
+    let elapsed_days = ((now - month_start).num_seconds() as f64 / 86_400.0).max(1.0 / 24.0);
+    let month_days = (limit.monthly_reset_at - month_start).num_seconds() as f64 / 86_400.0;
+    let average_daily_calls = limit.current_monthly_usage as f64 / elapsed_days;
+    let remaining = limit
+        .monthly_limit
+        .saturating_sub(limit.current_monthly_usage);
+
+    let exhausts_at = if remaining == 0 {
+        Some(now)
+    } else if average_daily_calls > 0.0 {
+        let days_left = remaining as f64 / average_daily_calls;
+        let at = now + ChronoDuration::seconds((days_left * 86_400.0) as i64);
+        (at < limit.monthly_reset_at).then_some(at)
+    } else {
+        None
+    };
+
+    let name = provider_name(&limit.service);
+    let projected_monthly_calls = (average_daily_calls * month_days).round() as u64;
+    let summary = match exhausts_at {
+        _ if remaining == 0 => format!("{name} has used its entire monthly budget"),
+        Some(at) => format!(
+            "At this rate you exhaust {name} on the {}",
+            ordinal(at.day())
+        ),
+        None => format!(
+            "{name} is on track for about {projected_monthly_calls} of {} requests this month",
+            limit.monthly_limit
+        ),
+    };
+
+    BudgetProjection {
+        service: limit.service.clone(),
+        daily_used: limit.current_daily_usage,
+        daily_limit: limit.daily_limit,
+        monthly_used: limit.current_monthly_usage,
+        monthly_limit: limit.monthly_limit,
+        average_daily_calls,
+        projected_monthly_calls,
+        exhausts_at,
+        summary,
+    }
+}
+
+fn provider_name(service: &str) -> String {
+    match service {
+        "helius" => "Helius".to_string(),
+        "birdeye" => "Birdeye".to_string(),
+        "jupiter" => "Jupiter".to_string(),
+        "raydium" => "Raydium".to_string(),
+        "solana_rpc" => "Solana RPC".to_string(),
+        other => other.to_string(),
+    }
+}
+
+fn ordinal(day: u32) -> String {
+    let suffix = match (day % 10, day % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{day}{suffix}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api_config::ApiConfigManager;
+    use chrono::TimeZone;
+
+    fn tracker_with_daily_usage(used: u64, now: DateTime<Utc>) -> ApiUsageTracker {
+        let dir = tempfile::tempdir().unwrap();
+        let budgets = ApiConfigManager::new().budgets();
+        let tracker = ApiUsageTracker::new(dir.path().join("api_usage.json"), budgets).unwrap();
+        {
+            let mut limits = tracker.fair_use_limits.lock().unwrap();
+            let limit = limits.get_mut("birdeye").unwrap();
+            limit.daily_limit = 100;
+            limit.current_daily_usage = used;
+            limit.reset_at = now + ChronoDuration::hours(10);
+            limit.monthly_reset_at = now + ChronoDuration::days(10);
+        }
+        tracker
+    }
+
+    #[test]
+    fn throttles_standard_requests_but_not_critical_ones() {
+        let now = Utc::now();
+        let tracker = tracker_with_daily_usage(85, now);
+
+        for _ in 0..THROTTLE_BURST as usize {
+            let wait = tracker.admit("birdeye", RequestPriority::Standard, now);
+            assert_eq!(wait.unwrap(), Duration::ZERO);
+        }
+        assert!(matches!(
+            tracker.admit("birdeye", RequestPriority::Standard, now),
+            Err(ApiBudgetError::Throttled { .. })
+        ));
+        assert_eq!(
+            tracker
+                .admit("birdeye", RequestPriority::Critical, now)
+                .unwrap(),
+            Duration::ZERO
+        );
+
+        let tracker = tracker_with_daily_usage(100, now);
+        assert!(matches!(
+            tracker.admit("birdeye", RequestPriority::Critical, now),
+            Err(ApiBudgetError::QuotaExceeded {
+                period: BudgetPeriod::Daily,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn projects_exhaustion_day_from_month_to_date_rate() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 10, 12, 0, 0).unwrap();
+        let limit = FairUseLimit {
+            service: "birdeye".to_string(),
+            daily_limit: 5_000,
+            monthly_limit: 1_000,
+            current_daily_usage: 60,
+            current_monthly_usage: 570,
+            reset_at: next_period_reset(BudgetPeriod::Daily, now),
+            monthly_reset_at: next_period_reset(BudgetPeriod::Monthly, now),
+            throttled: false,
+        };
+
+        let projection = project_budget(&limit, now);
+        assert_eq!(projection.average_daily_calls, 60.0);
+        assert_eq!(
+            projection.summary,
+            "At this rate you exhaust Birdeye on the 17th"
+        );
+
+        let quiet = FairUseLimit {
+            current_monthly_usage: 95,
+            ..limit
+        };
+        assert!(project_budget(&quiet, now).exhausts_at.is_none());
+        assert_eq!(ordinal(22), "22nd");
+        assert_eq!(ordinal(12), "12th");
+    }
+}
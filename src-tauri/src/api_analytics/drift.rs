@@ -0,0 +1,164 @@
+use super::{budget_guard, ApiUsageTracker};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+/// One pre-sign re-quote compared with the quote the trade was approved on.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuoteDriftRecord {
+    /// Mint whose amount was compared: the output of an exact-in swap, the
+    /// input of an exact-out one.
+    pub token: String,
+    /// Adverse move in basis points; negative when the fresh quote was better.
+    pub drift_bps: i64,
+    pub automated: bool,
+    /// Whether the drift was within the policy threshold.
+    pub accepted: bool,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenDriftStats {
+    pub token: String,
+    pub samples: u64,
+    pub average_drift_bps: f64,
+    pub max_drift_bps: i64,
+    pub rejected: u64,
+}
+
+impl ApiUsageTracker {
+    fn drift_path(&self) -> PathBuf {
+        self.data_path.with_file_name("quote_drift.json")
+    }
+
+    pub(super) fn load_drift_data(&self) -> Result<(), String> {
+        let path = self.drift_path();
+        if path.exists() {
+            let data = fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read quote drift data: {}", e))?;
+            let records: Vec<QuoteDriftRecord> = serde_json::from_str(&data)
+                .map_err(|e| format!("Failed to parse quote drift data: {}", e))?;
+
+            if let Ok(mut log) = self.drift_log.lock() {
+                *log = records;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn record_quote_drift(&self, record: QuoteDriftRecord) -> Result<(), String> {
+        let mut log = self
+            .drift_log
+            .lock()
+            .map_err(|_| "Failed to lock quote drift log".to_string())?;
+        log.push(record);
+        let cutoff = Utc::now() - chrono::Duration::days(31);
+        log.retain(|r| r.timestamp > cutoff);
+
+        let data = serde_json::to_string_pretty(&*log)
+            .map_err(|e| format!("Failed to serialize quote drift data: {}", e))?;
+        fs::write(self.drift_path(), data)
+            .map_err(|e| format!("Failed to write quote drift data: {}", e))
+    }
+
+    /// Drift per token since `cutoff`, most drifting first.
+    pub fn quote_drift_stats(&self, cutoff: DateTime<Utc>) -> Result<Vec<TokenDriftStats>, String> {
+        let log = self
+            .drift_log
+            .lock()
+            .map_err(|_| "Failed to lock quote drift log".to_string())?;
+
+        let mut by_token: HashMap<&str, TokenDriftStats> = HashMap::new();
+        for record in log.iter().filter(|r| r.timestamp > cutoff) {
+            let stats = by_token
+                .entry(record.token.as_str())
+                .or_insert_with(|| TokenDriftStats {
+                    token: record.token.clone(),
+                    samples: 0,
+                    average_drift_bps: 0.0,
+                    max_drift_bps: record.drift_bps,
+                    rejected: 0,
+                });
+            stats.samples += 1;
+            stats.average_drift_bps += record.drift_bps as f64;
+            stats.max_drift_bps = stats.max_drift_bps.max(record.drift_bps);
+            if !record.accepted {
+                stats.rejected += 1;
+            }
+        }
+
+        let mut stats: Vec<TokenDriftStats> = by_token
+            .into_values()
+            .map(|mut stats| {
+                stats.average_drift_bps /= stats.samples as f64;
+                stats
+            })
+            .collect();
+        stats.sort_by(|a, b| b.average_drift_bps.total_cmp(&a.average_drift_bps));
+        Ok(stats)
+    }
+}
+
+/// Records a re-quote against the installed tracker. Failures are logged, as
+/// the trade decision has already been made.
+pub fn record_quote_drift(record: QuoteDriftRecord) {
+    let Some(tracker) = budget_guard() else {
+        return;
+    };
+    if let Ok(tracker) = tracker.lock() {
+        if let Err(err) = tracker.record_quote_drift(record) {
+            tracing::warn!("Failed to record quote drift: {err}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api_config::ApiConfigManager;
+
+    fn record(token: &str, drift_bps: i64, accepted: bool) -> QuoteDriftRecord {
+        QuoteDriftRecord {
+            token: token.into(),
+            drift_bps,
+            automated: false,
+            accepted,
+            timestamp: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn averages_drift_per_token_and_persists() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("api_usage.json");
+        let budgets = ApiConfigManager::new().budgets();
+        let tracker = ApiUsageTracker::new(path.clone(), budgets.clone()).unwrap();
+
+        tracker
+            .record_quote_drift(record("BONK", 20, true))
+            .unwrap();
+        tracker
+            .record_quote_drift(record("BONK", 140, false))
+            .unwrap();
+        tracker
+            .record_quote_drift(record("USDC", -4, true))
+            .unwrap();
+
+        let reloaded = ApiUsageTracker::new(path, budgets).unwrap();
+        let stats = reloaded
+            .quote_drift_stats(Utc::now() - chrono::Duration::days(1))
+            .unwrap();
+
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].token, "BONK");
+        assert_eq!(stats[0].samples, 2);
+        assert_eq!(stats[0].average_drift_bps, 80.0);
+        assert_eq!(stats[0].max_drift_bps, 140);
+        assert_eq!(stats[0].rejected, 1);
+        assert_eq!(stats[1].average_drift_bps, -4.0);
+    }
+}
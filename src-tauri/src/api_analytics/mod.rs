@@ -1,3 +1,4 @@
+use crate::config::DataPaths;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -6,6 +7,15 @@ use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 use tauri::{AppHandle, Manager, State};
 
+use crate::api_config::{ApiConfigManager, ProviderBudget};
+use crate::security::keystore::Keystore;
+
+mod budget;
+mod drift;
+
+pub use budget::*;
+pub use drift::*;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ApiUsageRecord {
@@ -45,6 +55,9 @@ pub struct ApiUsageAnalytics {
     pub endpoint_breakdown: HashMap<String, Vec<EndpointUsage>>,
     pub daily_calls: HashMap<String, u64>,
     pub alerts: Vec<UsageAlert>,
+    pub budget_projections: Vec<BudgetProjection>,
+    /// Pre-sign re-quote drift per token over the same window.
+    pub quote_drift: Vec<TokenDriftStats>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -74,24 +87,40 @@ pub struct FairUseLimit {
     pub current_daily_usage: u64,
     pub current_monthly_usage: u64,
     pub reset_at: DateTime<Utc>,
+    pub monthly_reset_at: DateTime<Utc>,
+    /// Non-critical requests are currently being slowed down.
+    pub throttled: bool,
 }
 
 pub struct ApiUsageTracker {
     usage_log: Arc<Mutex<Vec<ApiUsageRecord>>>,
     fair_use_limits: Arc<Mutex<HashMap<String, FairUseLimit>>>,
+    budgets: Arc<Mutex<HashMap<String, ProviderBudget>>>,
+    buckets: Arc<Mutex<HashMap<String, TokenBucket>>>,
+    /// Highest threshold already notified per service and period, keyed by
+    /// the period's reset time so each period notifies afresh.
+    notified: Arc<Mutex<HashMap<(String, BudgetPeriod), (DateTime<Utc>, f64)>>>,
+    drift_log: Arc<Mutex<Vec<QuoteDriftRecord>>>,
+    app_handle: Option<AppHandle>,
     data_path: PathBuf,
 }
 
 impl ApiUsageTracker {
-    pub fn new(data_path: PathBuf) -> Result<Self, String> {
+    pub fn new(data_path: PathBuf, budgets: Vec<ProviderBudget>) -> Result<Self, String> {
         let tracker = Self {
             usage_log: Arc::new(Mutex::new(Vec::new())),
             fair_use_limits: Arc::new(Mutex::new(HashMap::new())),
+            budgets: Arc::new(Mutex::new(HashMap::new())),
+            buckets: Arc::new(Mutex::new(HashMap::new())),
+            notified: Arc::new(Mutex::new(HashMap::new())),
+            drift_log: Arc::new(Mutex::new(Vec::new())),
+            app_handle: None,
             data_path,
         };
 
         tracker.load_usage_data()?;
-        tracker.initialize_fair_use_limits()?;
+        tracker.load_drift_data()?;
+        tracker.apply_budgets(budgets)?;
 
         Ok(tracker)
     }
@@ -127,32 +156,48 @@ impl ApiUsageTracker {
         Ok(())
     }
 
-    fn initialize_fair_use_limits(&self) -> Result<(), String> {
+    /// Replaces the budgets the tracker enforces. Current usage is recounted
+    /// from the log so limits loaded at startup start from the real totals.
+    pub fn apply_budgets(&self, budgets: Vec<ProviderBudget>) -> Result<(), String> {
+        let now = Utc::now();
+        let log = self
+            .usage_log
+            .lock()
+            .map_err(|_| "Failed to lock usage log".to_string())?;
         let mut limits = self
             .fair_use_limits
             .lock()
             .map_err(|_| "Failed to lock fair use limits".to_string())?;
+        let mut enforced = self
+            .budgets
+            .lock()
+            .map_err(|_| "Failed to lock provider budgets".to_string())?;
+
+        let day_start = period_start(BudgetPeriod::Daily, now);
+        let month_start = period_start(BudgetPeriod::Monthly, now);
+        for budget in budgets {
+            let service_records = log.iter().filter(|r| r.service == budget.service);
+            let current_monthly_usage = service_records
+                .clone()
+                .filter(|r| r.timestamp >= month_start)
+                .count() as u64;
+            let current_daily_usage =
+                service_records.filter(|r| r.timestamp >= day_start).count() as u64;
 
-        // Default fair-use limits for developer keys
-        let services = vec![
-            ("helius", 10000, 300000),
-            ("birdeye", 5000, 150000),
-            ("jupiter", 20000, 600000),
-            ("solana_rpc", 50000, 1500000),
-        ];
-
-        for (service, daily, monthly) in services {
             limits.insert(
-                service.to_string(),
+                budget.service.clone(),
                 FairUseLimit {
-                    service: service.to_string(),
-                    daily_limit: daily,
-                    monthly_limit: monthly,
-                    current_daily_usage: 0,
-                    current_monthly_usage: 0,
+                    service: budget.service.clone(),
+                    daily_limit: budget.daily_limit,
+                    monthly_limit: budget.monthly_limit,
+                    current_daily_usage,
+                    current_monthly_usage,
                     reset_at: calculate_next_reset(),
+                    monthly_reset_at: calculate_next_monthly_reset(),
+                    throttled: false,
                 },
             );
+            enforced.insert(budget.service.clone(), budget);
         }
 
         Ok(())
@@ -163,23 +208,25 @@ impl ApiUsageTracker {
         if let Ok(mut log) = self.usage_log.lock() {
             log.push(record.clone());
 
-            // Keep only last 30 days
-            let cutoff = Utc::now() - chrono::Duration::days(30);
+            // Keep enough history to cover the current calendar month
+            let cutoff = Utc::now() - chrono::Duration::days(31);
             log.retain(|r| r.timestamp > cutoff);
         }
 
         // Update fair use limits
-        if let Ok(mut limits) = self.fair_use_limits.lock() {
-            if let Some(limit) = limits.get_mut(&record.service) {
+        let usage = if let Ok(mut limits) = self.fair_use_limits.lock() {
+            limits.get_mut(&record.service).map(|limit| {
+                roll_budget_periods(limit, Utc::now());
                 limit.current_daily_usage += 1;
                 limit.current_monthly_usage += 1;
+                limit.clone()
+            })
+        } else {
+            None
+        };
 
-                // Reset if needed
-                if Utc::now() > limit.reset_at {
-                    limit.current_daily_usage = 0;
-                    limit.reset_at = calculate_next_reset();
-                }
-            }
+        if let Some(limit) = usage {
+            self.notify_budget_thresholds(&limit)?;
         }
 
         self.save_usage_data()?;
@@ -275,14 +322,20 @@ impl ApiUsageTracker {
             endpoint_breakdown.insert(service.clone(), endpoints);
         }
 
+        drop(log);
+
         // Generate alerts
         let alerts = self.generate_alerts(&services)?;
+        let budget_projections = self.budget_projections(Utc::now())?;
+        let quote_drift = self.quote_drift_stats(cutoff)?;
 
         Ok(ApiUsageAnalytics {
             services,
             endpoint_breakdown,
             daily_calls,
             alerts,
+            budget_projections,
+            quote_drift,
         })
     }
 
@@ -296,21 +349,36 @@ impl ApiUsageTracker {
             .lock()
             .map_err(|_| "Failed to lock fair use limits".to_string())?;
 
+        let budgets = self
+            .budgets
+            .lock()
+            .map_err(|_| "Failed to lock provider budgets".to_string())?;
+
         for (service, limit) in limits.iter() {
-            // Check daily limit
-            let daily_usage_percent =
-                (limit.current_daily_usage as f64 / limit.daily_limit as f64) * 100.0;
-            if daily_usage_percent >= 90.0 {
+            // Check daily and monthly budgets
+            for period in [BudgetPeriod::Daily, BudgetPeriod::Monthly] {
+                let usage_percent = budget_usage_percent(limit, period);
+                let Some(threshold) = budgets
+                    .get(service)
+                    .and_then(|budget| crossed_threshold(budget, usage_percent))
+                else {
+                    continue;
+                };
+                let (used, cap) = period_usage(limit, period);
                 alerts.push(UsageAlert {
                     service: service.clone(),
-                    alert_type: if daily_usage_percent >= 100.0 {
+                    alert_type: if usage_percent >= 100.0 {
                         AlertType::LimitExceeded
                     } else {
                         AlertType::ApproachingLimit
                     },
                     message: format!(
-                        "Daily usage at {:.1}% ({}/{})",
-                        daily_usage_percent, limit.current_daily_usage, limit.daily_limit
+                        "{} usage at {:.1}% ({}/{}), past the {:.0}% threshold",
+                        period.label(),
+                        usage_percent,
+                        used,
+                        cap,
+                        threshold
                     ),
                     timestamp: Utc::now(),
                 });
@@ -355,8 +423,11 @@ impl ApiUsageTracker {
 }
 
 fn calculate_next_reset() -> DateTime<Utc> {
-    let now = Utc::now();
-    now + chrono::Duration::days(1)
+    next_period_reset(BudgetPeriod::Daily, Utc::now())
+}
+
+fn calculate_next_monthly_reset() -> DateTime<Utc> {
+    next_period_reset(BudgetPeriod::Monthly, Utc::now())
 }
 
 #[tauri::command]
@@ -405,14 +476,45 @@ pub async fn get_fair_use_status(
     tracker.get_fair_use_limits()
 }
 
+#[tauri::command]
+pub async fn get_api_budgets(
+    config_manager: State<'_, ApiConfigManager>,
+) -> Result<Vec<ProviderBudget>, String> {
+    Ok(config_manager.budgets())
+}
+
+#[tauri::command]
+pub async fn set_api_budget(
+    budget: ProviderBudget,
+    config_manager: State<'_, ApiConfigManager>,
+    keystore: State<'_, Keystore>,
+    tracker: State<'_, Arc<Mutex<ApiUsageTracker>>>,
+) -> Result<ProviderBudget, String> {
+    let saved = config_manager.set_budget(budget, &keystore)?;
+
+    let tracker = tracker
+        .lock()
+        .map_err(|_| "Failed to lock usage tracker".to_string())?;
+    tracker.apply_budgets(vec![saved.clone()])?;
+
+    Ok(saved)
+}
+
 pub fn initialize_usage_tracker(app: &AppHandle) -> Result<Arc<Mutex<ApiUsageTracker>>, String> {
     let mut data_path = app
-        .path()
-        .app_data_dir()
+        .app_data_root()
         .map_err(|err| format!("Unable to resolve app data directory: {err}"))?;
 
     data_path.push("api_usage.json");
 
-    let tracker = ApiUsageTracker::new(data_path)?;
-    Ok(Arc::new(Mutex::new(tracker)))
+    let budgets = app
+        .try_state::<ApiConfigManager>()
+        .map(|config| config.budgets())
+        .unwrap_or_else(|| ApiConfigManager::new().budgets());
+    let mut tracker = ApiUsageTracker::new(data_path, budgets)?;
+    tracker.app_handle = Some(app.clone());
+
+    let tracker = Arc::new(Mutex::new(tracker));
+    install_budget_guard(tracker.clone());
+    Ok(tracker)
 }
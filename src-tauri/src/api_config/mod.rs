@@ -1,16 +1,25 @@
 use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
-use tauri::{Manager, State};
+use tauri::{AppHandle, Manager, Runtime, State};
 
+use crate::api::http_client::{ProviderClient, ProviderProfile};
+use crate::config::{active_environment, default_rpc_url, provider_url};
 use crate::security::keystore::{Keystore, KeystoreError};
 
+mod rotation;
+
+pub use rotation::*;
+
 const KEY_HELIUS_API: &str = "api_key_helius";
 const KEY_BIRDEYE_API: &str = "api_key_birdeye";
 const KEY_JUPITER_API: &str = "api_key_jupiter";
 const KEY_SOLANA_RPC: &str = "api_rpc_endpoint";
+const KEY_RPC_SWITCH: &str = "api_rpc_switched_endpoint";
 const KEY_API_METADATA: &str = "api_key_metadata";
+const KEY_API_BUDGETS: &str = "api_provider_budgets";
 
 // Stock API keys
 const KEY_ALPHA_VANTAGE_API: &str = "api_key_alpha_vantage";
@@ -126,8 +135,67 @@ pub struct ConnectionTestResult {
     pub rate_limit_info: Option<RateLimitInfo>,
 }
 
+/// Reachability target for a configured provider, used by network diagnostics.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderEndpoint {
+    pub service: String,
+    pub url: String,
+    pub using_default: bool,
+}
+
+/// Request budget for a provider. Usage past `throttle_at_percent` of the
+/// daily budget slows non-critical requests; 100% of either budget stops them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderBudget {
+    pub service: String,
+    pub daily_limit: u64,
+    pub monthly_limit: u64,
+    #[serde(default = "default_throttle_at_percent")]
+    pub throttle_at_percent: f64,
+    /// Usage percentages that trigger a budget notification.
+    #[serde(default = "default_alert_thresholds")]
+    pub alert_thresholds: Vec<f64>,
+}
+
+fn default_throttle_at_percent() -> f64 {
+    80.0
+}
+
+fn default_alert_thresholds() -> Vec<f64> {
+    vec![50.0, 80.0, 95.0]
+}
+
+/// Default fair-use budgets for developer keys.
+fn default_budgets() -> HashMap<String, ProviderBudget> {
+    [
+        ("helius", 10_000, 300_000),
+        ("birdeye", 5_000, 150_000),
+        ("jupiter", 20_000, 600_000),
+        ("solana_rpc", 50_000, 1_500_000),
+    ]
+    .into_iter()
+    .map(|(service, daily_limit, monthly_limit)| {
+        (
+            service.to_string(),
+            ProviderBudget {
+                service: service.to_string(),
+                daily_limit,
+                monthly_limit,
+                throttle_at_percent: default_throttle_at_percent(),
+                alert_thresholds: default_alert_thresholds(),
+            },
+        )
+    })
+    .collect()
+}
+
 pub struct ApiConfigManager {
     metadata: Arc<Mutex<HashMap<String, ApiKeyMetadata>>>,
+    budgets: Arc<Mutex<HashMap<String, ProviderBudget>>>,
+    rotations: Arc<Mutex<HashMap<String, KeyRotation>>>,
+    rpc_switch: Arc<Mutex<Option<String>>>,
 }
 
 fn default_metadata(service: &str, use_default: bool) -> ApiKeyMetadata {
@@ -154,6 +222,9 @@ impl ApiConfigManager {
     pub fn new() -> Self {
         Self {
             metadata: Arc::new(Mutex::new(HashMap::new())),
+            budgets: Arc::new(Mutex::new(default_budgets())),
+            rotations: Arc::new(Mutex::new(HashMap::new())),
+            rpc_switch: Arc::new(Mutex::new(None)),
         }
     }
 
@@ -168,6 +239,17 @@ impl ApiConfigManager {
                 }
             }
         }
+        if let Ok(data) = keystore.retrieve_secret(KEY_API_BUDGETS) {
+            if let Ok(saved) = serde_json::from_slice::<HashMap<String, ProviderBudget>>(&data) {
+                if let Ok(mut budgets) = self.budgets.lock() {
+                    budgets.extend(saved);
+                }
+            }
+        }
+        self.load_rotations(keystore);
+        if let Ok(secret) = keystore.retrieve_secret(KEY_RPC_SWITCH) {
+            self.set_rpc_switch(String::from_utf8(secret.to_vec()).ok());
+        }
         Ok(())
     }
 
@@ -197,10 +279,159 @@ impl ApiConfigManager {
         Ok(())
     }
 
+    pub fn budgets(&self) -> Vec<ProviderBudget> {
+        let mut budgets: Vec<ProviderBudget> = self
+            .budgets
+            .lock()
+            .map(|budgets| budgets.values().cloned().collect())
+            .unwrap_or_default();
+        budgets.sort_by(|a, b| a.service.cmp(&b.service));
+        budgets
+    }
+
+    pub fn set_budget(
+        &self,
+        mut budget: ProviderBudget,
+        keystore: &Keystore,
+    ) -> Result<ProviderBudget, String> {
+        if budget.daily_limit == 0 || budget.monthly_limit == 0 {
+            return Err("Budgets must allow at least one request".to_string());
+        }
+        if !(0.0..=100.0).contains(&budget.throttle_at_percent) {
+            return Err("Throttle threshold must be between 0 and 100".to_string());
+        }
+        budget
+            .alert_thresholds
+            .retain(|threshold| *threshold > 0.0 && *threshold <= 100.0);
+        budget.alert_thresholds.sort_by(f64::total_cmp);
+        budget.alert_thresholds.dedup();
+
+        let serialized = {
+            let mut budgets = self
+                .budgets
+                .lock()
+                .map_err(|_| "Failed to lock provider budgets".to_string())?;
+            budgets.insert(budget.service.clone(), budget.clone());
+            serde_json::to_vec(&*budgets).map_err(|e| e.to_string())?
+        };
+        keystore
+            .store_secret(KEY_API_BUDGETS, &serialized)
+            .map_err(|e| format!("Failed to save provider budgets: {}", e))?;
+        Ok(budget)
+    }
+
     pub fn get_or_create_metadata(&self, service: &str, use_default: bool) -> ApiKeyMetadata {
         self.get_metadata(service)
             .unwrap_or_else(|| default_metadata(service, use_default))
     }
+
+    /// Base URLs for every configured provider. Keys are never included, so the
+    /// result is safe to surface in diagnostics reports.
+    pub fn provider_endpoints(&self) -> Vec<ProviderEndpoint> {
+        ["helius", "birdeye", "jupiter"]
+            .iter()
+            .map(|service| {
+                let using_default = self
+                    .get_metadata(service)
+                    .map(|m| m.use_default)
+                    .unwrap_or(true);
+                let url = match *service {
+                    "helius" => "https://api.helius.xyz",
+                    "birdeye" => "https://public-api.birdeye.so",
+                    _ => "https://quote-api.jup.ag/v6",
+                };
+                ProviderEndpoint {
+                    service: service.to_string(),
+                    url: provider_url(url),
+                    using_default,
+                }
+            })
+            .collect()
+    }
+
+    /// The RPC endpoint saved for `solana_rpc`, or the public default, unless
+    /// diagnostics auto repair switched away from it. Off mainnet the
+    /// environment's RPC is used, since saved endpoints are mainnet ones.
+    pub fn rpc_endpoint(&self, keystore: &Keystore) -> String {
+        let environment = active_environment();
+        if !environment.is_mainnet() {
+            return environment.rpc_url();
+        }
+        if let Some(switched) = self.rpc_switch() {
+            return switched;
+        }
+        let use_default = self
+            .get_metadata("solana_rpc")
+            .map(|m| m.use_default)
+            .unwrap_or(true);
+        if use_default {
+            return DEFAULT_RPC_ENDPOINT.to_string();
+        }
+        if let Some(candidate) = self.soak_candidate("solana_rpc", keystore) {
+            return candidate;
+        }
+        keystore
+            .retrieve_secret(KEY_SOLANA_RPC)
+            .ok()
+            .and_then(|secret| String::from_utf8(secret.to_vec()).ok())
+            .filter(|endpoint| !endpoint.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_RPC_ENDPOINT.to_string())
+    }
+
+    /// The endpoint diagnostics auto repair switched RPC traffic to, if any.
+    pub fn rpc_switch(&self) -> Option<String> {
+        self.rpc_switch.lock().ok()?.clone()
+    }
+
+    /// Sends every RPC caller to `endpoint` after auto repair found the
+    /// configured one failing. Kept until a `solana_rpc` endpoint is saved,
+    /// removed or reset to the default.
+    pub fn switch_rpc_endpoint(
+        &self,
+        endpoint: &str,
+        keystore: &Keystore,
+    ) -> Result<(), KeystoreError> {
+        self.set_rpc_switch(Some(endpoint.to_string()));
+        keystore.store_secret(KEY_RPC_SWITCH, endpoint.as_bytes())
+    }
+
+    /// Drops an auto repair switch so the configured endpoint applies again.
+    pub fn clear_rpc_switch(&self, keystore: &Keystore) -> Result<(), KeystoreError> {
+        self.set_rpc_switch(None);
+        keystore.remove_secret(KEY_RPC_SWITCH)
+    }
+
+    fn set_rpc_switch(&self, endpoint: Option<String>) {
+        if let Ok(mut switch) = self.rpc_switch.lock() {
+            *switch = endpoint;
+        }
+    }
+
+    /// The key in effect for a REST provider: the saved custom key, or the default.
+    pub fn api_key(&self, service: &str, keystore: &Keystore) -> Option<String> {
+        let key_id = match service {
+            "helius" => KEY_HELIUS_API,
+            "birdeye" => KEY_BIRDEYE_API,
+            "jupiter" => KEY_JUPITER_API,
+            _ => return None,
+        };
+        let use_default = self
+            .get_metadata(service)
+            .map(|m| m.use_default)
+            .unwrap_or(true);
+        let key = if use_default {
+            get_default_key(service)
+        } else if let Some(candidate) = self.soak_candidate(service, keystore) {
+            candidate
+        } else {
+            keystore
+                .retrieve_secret(key_id)
+                .ok()
+                .and_then(|secret| String::from_utf8(secret.to_vec()).ok())
+                .unwrap_or_else(|| get_default_key(service))
+        };
+        Some(key).filter(|key| !key.trim().is_empty())
+    }
 }
 
 impl Default for ApiConfigManager {
@@ -209,6 +440,50 @@ impl Default for ApiConfigManager {
     }
 }
 
+/// The RPC endpoint in effect for the app: the saved one when the API config
+/// and keystore are managed, otherwise the active environment's default.
+pub fn rpc_endpoint<R: Runtime>(app: &AppHandle<R>) -> String {
+    match (
+        app.try_state::<ApiConfigManager>(),
+        app.try_state::<Keystore>(),
+    ) {
+        (Some(config), Some(keystore)) => config.rpc_endpoint(&keystore),
+        _ => default_rpc_url(),
+    }
+}
+
+/// Sends one Solana JSON-RPC call to `url` and returns its `result`. It goes
+/// through the shared provider client, so it is metered as `solana_rpc` and
+/// retried on 429 and 5xx. Errors name the method; callers wrap them in
+/// their own error type.
+pub async fn solana_rpc(url: &str, method: &str, params: Value) -> Result<Value, String> {
+    let payload = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": method,
+        "params": params,
+    });
+    // Reads are safe to repeat, and a resent signed transaction lands once.
+    let response = ProviderClient::new(ProviderProfile::SOLANA_RPC)
+        .post(url)
+        .json(&payload)
+        .idempotent()
+        .send()
+        .await
+        .map_err(|e| format!("{method}: {e}"))?;
+    if !response.status().is_success() {
+        return Err(format!("{method}: HTTP {}", response.status()));
+    }
+    let body: Value = response
+        .json()
+        .await
+        .map_err(|e| format!("{method}: {e}"))?;
+    if let Some(error) = body.get("error") {
+        return Err(format!("{method} failed: {error}"));
+    }
+    Ok(body["result"].clone())
+}
+
 #[tauri::command]
 pub async fn save_api_key(
     service: String,
@@ -262,6 +537,11 @@ pub async fn save_api_key(
     config_manager
         .update_metadata(&service, metadata, &keystore)
         .map_err(|e| format!("Failed to update metadata: {}", e))?;
+    if service == "solana_rpc" {
+        config_manager
+            .clear_rpc_switch(&keystore)
+            .map_err(|e| format!("Failed to clear RPC switch: {}", e))?;
+    }
 
     Ok(format!("API key for {} saved successfully", service))
 }
@@ -297,6 +577,11 @@ pub async fn remove_api_key(
     config_manager
         .update_metadata(&service, metadata, &keystore)
         .map_err(|e| format!("Failed to update metadata: {}", e))?;
+    if service == "solana_rpc" {
+        config_manager
+            .clear_rpc_switch(&keystore)
+            .map_err(|e| format!("Failed to clear RPC switch: {}", e))?;
+    }
 
     Ok(format!("API key for {} removed", service))
 }
@@ -314,6 +599,11 @@ pub async fn set_use_default_key(
     config_manager
         .update_metadata(&service, metadata, &keystore)
         .map_err(|e| format!("Failed to update metadata: {}", e))?;
+    if service == "solana_rpc" {
+        config_manager
+            .clear_rpc_switch(&keystore)
+            .map_err(|e| format!("Failed to clear RPC switch: {}", e))?;
+    }
 
     Ok(format!(
         "Service {} now using {} keys",
@@ -353,13 +643,7 @@ pub async fn test_api_connection(
     };
 
     // Test the connection based on service
-    let result = match service.as_str() {
-        "helius" => test_helius_connection(&api_key).await,
-        "birdeye" => test_birdeye_connection(&api_key).await,
-        "jupiter" => test_jupiter_connection(&api_key).await,
-        "solana_rpc" => test_rpc_connection(&api_key).await,
-        _ => return Err("Unknown service".to_string()),
-    };
+    let result = probe_connection(&service, &api_key).await?;
 
     let latency = start.elapsed().as_millis() as u64;
 
@@ -495,6 +779,21 @@ fn get_service_status(
     })
 }
 
+/// Checks `api_key` against the provider. The outer error is for unknown
+/// services; the inner result is the connection outcome.
+async fn probe_connection(
+    service: &str,
+    api_key: &str,
+) -> Result<Result<(u16, Option<RateLimitInfo>), String>, String> {
+    Ok(match service {
+        "helius" => test_helius_connection(api_key).await,
+        "birdeye" => test_birdeye_connection(api_key).await,
+        "jupiter" => test_jupiter_connection(api_key).await,
+        "solana_rpc" => test_rpc_connection(api_key).await,
+        _ => return Err("Unknown service".to_string()),
+    })
+}
+
 fn get_default_key(service: &str) -> String {
     match service {
         "helius" => DEFAULT_HELIUS_KEY.to_string(),
@@ -651,6 +950,16 @@ pub async fn rotate_api_key(
         return Err("Cannot rotate default keys. Please add a custom key first.".to_string());
     }
 
+    if config_manager
+        .key_rotation(&service)
+        .is_some_and(|rotation| rotation.stage.is_active())
+    {
+        return Err(format!(
+            "A staged key rotation is already in progress for {}",
+            service
+        ));
+    }
+
     let mut meta = metadata.unwrap();
     let now = Utc::now();
 
@@ -689,6 +998,18 @@ pub async fn check_rotation_reminders(
     let now = Utc::now();
 
     for service in services {
+        if let Some(rotation) = config_manager.key_rotation(service) {
+            // A rotation underway replaces the reminder; its outcome is
+            // reported once when it finishes.
+            if rotation.stage.is_active() {
+                continue;
+            }
+            if !rotation.reported {
+                reminders.push(rotation.summary());
+                config_manager.mark_rotation_reported(service, &keystore);
+            }
+        }
+
         if let Some(mut metadata) = config_manager.get_metadata(service) {
             if let Some(rotation_due) = metadata.rotation_due_at {
                 let days_until_rotation = (rotation_due - now).num_days();
@@ -781,3 +1102,54 @@ pub fn register_api_config_manager(app: &tauri::App) -> Result<(), Box<dyn std::
     app.manage(config_manager);
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use httpmock::prelude::*;
+    use tauri::test::mock_app;
+
+    #[tokio::test]
+    async fn solana_rpc_returns_the_result_or_the_rpc_error() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(POST).body_contains("\"getBalance\"");
+            then.status(200)
+                .json_body(json!({ "jsonrpc": "2.0", "id": 1, "result": { "value": 5 } }));
+        });
+        server.mock(|when, then| {
+            when.method(POST).body_contains("\"getSlot\"");
+            then.status(200).json_body(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "error": { "code": -32601, "message": "Method not found" }
+            }));
+        });
+
+        let result = solana_rpc(&server.base_url(), "getBalance", json!(["wallet"]))
+            .await
+            .unwrap();
+        assert_eq!(result["value"], 5);
+        let err = solana_rpc(&server.base_url(), "getSlot", json!([]))
+            .await
+            .unwrap_err();
+        assert!(err.starts_with("getSlot failed:"), "{err}");
+    }
+
+    #[test]
+    fn rpc_endpoint_follows_an_auto_repair_switch() {
+        let dir = tempfile::tempdir().unwrap();
+        let app = mock_app();
+        app.manage(ApiConfigManager::new());
+        app.manage(Keystore::at(dir.path().join("keystore.json")));
+        assert_eq!(rpc_endpoint(app.handle()), DEFAULT_RPC_ENDPOINT);
+
+        let fallback = "https://fallback.example.com";
+        app.state::<ApiConfigManager>()
+            .set_rpc_switch(Some(fallback.to_string()));
+        assert_eq!(rpc_endpoint(app.handle()), fallback);
+
+        app.state::<ApiConfigManager>().set_rpc_switch(None);
+        assert_eq!(rpc_endpoint(app.handle()), DEFAULT_RPC_ENDPOINT);
+    }
+}
@@ -0,0 +1,295 @@
+use super::{command_program, LinuxAutoStartBackend, LoginItem, AUTO_START_ARG};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const SESSION_TARGET: &str = "graphical-session.target";
+
+fn config_dir() -> Option<PathBuf> {
+    std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+}
+
+/// `Eclipse Market Pro` -> `eclipse-market-pro`.
+fn slug(app_name: &str) -> String {
+    app_name
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(str::to_ascii_lowercase)
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Quotes a path for both `Exec=` and `ExecStart=`, which share the rule
+/// that `"`, `\`, `` ` `` and `$` are backslash-escaped inside quotes.
+fn quoted(exe: &Path) -> String {
+    let mut quoted = String::from("\"");
+    for c in exe.to_string_lossy().chars() {
+        if matches!(c, '"' | '\\' | '`' | '$') {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn read_optional(path: &Path) -> Result<Option<String>, String> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(Some(contents)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(format!("Failed to read {}: {e}", path.display())),
+    }
+}
+
+fn remove_optional(path: &Path) -> Result<(), String> {
+    match fs::remove_file(path) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(format!("Failed to remove {}: {e}", path.display())),
+    }
+}
+
+fn write_file(path: &Path, contents: &str) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {e}", parent.display()))?;
+    }
+    fs::write(path, contents).map_err(|e| format!("Failed to write {}: {e}", path.display()))
+}
+
+fn key_value<'a>(contents: &'a str, key: &str) -> Option<&'a str> {
+    contents
+        .lines()
+        .find_map(|line| line.trim().strip_prefix(key)?.strip_prefix('='))
+}
+
+struct XdgAutostart {
+    app_name: String,
+    path: PathBuf,
+}
+
+impl LoginItem for XdgAutostart {
+    fn registered_target(&self) -> Result<Option<PathBuf>, String> {
+        let Some(contents) = read_optional(&self.path)? else {
+            return Ok(None);
+        };
+        let disabled = key_value(&contents, "Hidden") == Some("true")
+            || key_value(&contents, "X-GNOME-Autostart-enabled") == Some("false");
+        if disabled {
+            return Ok(None);
+        }
+        Ok(key_value(&contents, "Exec").and_then(command_program))
+    }
+
+    fn register(&self, exe: &Path) -> Result<(), String> {
+        let entry = format!(
+            "[Desktop Entry]\n\
+             Type=Application\n\
+             Name={}\n\
+             Exec={} {}\n\
+             Terminal=false\n\
+             X-GNOME-Autostart-enabled=true\n",
+            self.app_name,
+            quoted(exe),
+            AUTO_START_ARG
+        );
+        write_file(&self.path, &entry)
+    }
+
+    fn unregister(&self) -> Result<(), String> {
+        remove_optional(&self.path)
+    }
+}
+
+/// Enabled means the unit is linked into `graphical-session.target.wants`,
+/// which is exactly what `systemctl --user enable` would create.
+struct SystemdUserUnit {
+    app_name: String,
+    unit_path: PathBuf,
+    wants_link: PathBuf,
+    reload_daemon: bool,
+}
+
+impl SystemdUserUnit {
+    fn daemon_reload(&self) {
+        if !self.reload_daemon {
+            return;
+        }
+        // Best effort: without a running user manager the link still takes
+        // effect at the next login.
+        let _ = Command::new("systemctl")
+            .args(["--user", "daemon-reload"])
+            .status();
+    }
+}
+
+impl LoginItem for SystemdUserUnit {
+    fn registered_target(&self) -> Result<Option<PathBuf>, String> {
+        if fs::symlink_metadata(&self.wants_link).is_err() {
+            return Ok(None);
+        }
+        let Some(unit) = read_optional(&self.unit_path)? else {
+            return Ok(None);
+        };
+        Ok(key_value(&unit, "ExecStart").and_then(command_program))
+    }
+
+    fn register(&self, exe: &Path) -> Result<(), String> {
+        let unit = format!(
+            "[Unit]\n\
+             Description={name}\n\
+             PartOf={target}\n\
+             After={target}\n\
+             \n\
+             [Service]\n\
+             Type=simple\n\
+             ExecStart={exe} {arg}\n\
+             Restart=no\n\
+             \n\
+             [Install]\n\
+             WantedBy={target}\n",
+            name = self.app_name,
+            target = SESSION_TARGET,
+            exe = quoted(exe),
+            arg = AUTO_START_ARG
+        );
+        write_file(&self.unit_path, &unit)?;
+
+        if let Some(wants_dir) = self.wants_link.parent() {
+            fs::create_dir_all(wants_dir)
+                .map_err(|e| format!("Failed to create {}: {e}", wants_dir.display()))?;
+        }
+        remove_optional(&self.wants_link)?;
+        std::os::unix::fs::symlink(&self.unit_path, &self.wants_link)
+            .map_err(|e| format!("Failed to enable {}: {e}", self.unit_path.display()))?;
+
+        self.daemon_reload();
+        Ok(())
+    }
+
+    fn unregister(&self) -> Result<(), String> {
+        remove_optional(&self.wants_link)?;
+        remove_optional(&self.unit_path)?;
+        self.daemon_reload();
+        Ok(())
+    }
+}
+
+fn backend_item(
+    config: &Path,
+    app_name: &str,
+    backend: LinuxAutoStartBackend,
+) -> Box<dyn LoginItem> {
+    let name = slug(app_name);
+    match backend {
+        LinuxAutoStartBackend::Xdg => Box::new(XdgAutostart {
+            app_name: app_name.to_string(),
+            path: config.join("autostart").join(format!("{name}.desktop")),
+        }),
+        LinuxAutoStartBackend::Systemd => {
+            let unit = format!("{name}.service");
+            let user_units = config.join("systemd").join("user");
+            Box::new(SystemdUserUnit {
+                app_name: app_name.to_string(),
+                wants_link: user_units
+                    .join(format!("{SESSION_TARGET}.wants"))
+                    .join(&unit),
+                unit_path: user_units.join(unit),
+                reload_daemon: true,
+            })
+        }
+    }
+}
+
+pub(super) fn login_item(
+    app_name: &str,
+    backend: LinuxAutoStartBackend,
+) -> Option<Box<dyn LoginItem>> {
+    Some(backend_item(&config_dir()?, app_name, backend))
+}
+
+/// Switching backends must not leave the app starting twice.
+pub(super) fn remove_other_backends(
+    app_name: &str,
+    selected: LinuxAutoStartBackend,
+) -> Result<(), String> {
+    let Some(config) = config_dir() else {
+        return Ok(());
+    };
+    for backend in [LinuxAutoStartBackend::Xdg, LinuxAutoStartBackend::Systemd] {
+        if backend == selected {
+            continue;
+        }
+        let item = backend_item(&config, app_name, backend);
+        if item.registered_target()?.is_some() {
+            item.unregister()?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{AutoStartManager, AutoStartStatus};
+    use super::*;
+
+    #[test]
+    fn xdg_entry_round_trips_and_detects_moved_executable() {
+        let config = tempfile::tempdir().unwrap();
+        let item = backend_item(
+            config.path(),
+            "Eclipse Market Pro",
+            LinuxAutoStartBackend::Xdg,
+        );
+        let exe = PathBuf::from("/opt/Eclipse Market/eclipse-market-pro");
+
+        assert_eq!(
+            AutoStartManager::status_of(item.as_ref(), &exe).unwrap(),
+            AutoStartStatus::Disabled
+        );
+        item.register(&exe).unwrap();
+        assert!(config
+            .path()
+            .join("autostart/eclipse-market-pro.desktop")
+            .exists());
+        assert_eq!(item.registered_target().unwrap(), Some(exe.clone()));
+        assert_eq!(
+            AutoStartManager::status_of(item.as_ref(), &exe).unwrap(),
+            AutoStartStatus::Enabled
+        );
+        assert_eq!(
+            AutoStartManager::status_of(item.as_ref(), Path::new("/usr/bin/eclipse")).unwrap(),
+            AutoStartStatus::Stale
+        );
+
+        item.unregister().unwrap();
+        assert_eq!(item.registered_target().unwrap(), None);
+    }
+
+    #[test]
+    fn systemd_unit_is_enabled_through_the_wants_link() {
+        let config = tempfile::tempdir().unwrap();
+        let user_units = config.path().join("systemd/user");
+        let item = SystemdUserUnit {
+            app_name: "Eclipse Market Pro".to_string(),
+            unit_path: user_units.join("eclipse-market-pro.service"),
+            wants_link: user_units
+                .join("graphical-session.target.wants/eclipse-market-pro.service"),
+            reload_daemon: false,
+        };
+        let exe = PathBuf::from("/usr/bin/eclipse-market-pro");
+
+        item.register(&exe).unwrap();
+        assert_eq!(item.registered_target().unwrap(), Some(exe));
+
+        fs::remove_file(&item.wants_link).unwrap();
+        assert_eq!(item.registered_target().unwrap(), None);
+
+        item.unregister().unwrap();
+        assert!(!item.unit_path.exists());
+    }
+}
@@ -0,0 +1,228 @@
+use super::{LoginItem, AUTO_START_ARG};
+use objc2::msg_send;
+use objc2::runtime::{AnyClass, AnyObject};
+use std::ffi::{c_char, CStr};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::ptr;
+
+#[link(name = "ServiceManagement", kind = "framework")]
+extern "C" {}
+
+/// `SMAppServiceStatusEnabled`; the other states are not registered,
+/// requires approval and not found.
+const SM_APP_SERVICE_ENABLED: isize = 1;
+
+/// `SMAppService.mainAppService` (macOS 13+). It always launches the bundle
+/// it was registered from, so it can't go stale when the app moves.
+struct AppService {
+    service: *mut AnyObject,
+}
+
+impl AppService {
+    fn main_app() -> Option<Self> {
+        let class = AnyClass::get(c"SMAppService")?;
+        let service: *mut AnyObject = unsafe { msg_send![class, mainAppService] };
+        (!service.is_null()).then_some(Self { service })
+    }
+
+    fn is_enabled(&self) -> bool {
+        let status: isize = unsafe { msg_send![self.service, status] };
+        status == SM_APP_SERVICE_ENABLED
+    }
+
+    fn register(&self) -> Result<(), String> {
+        let mut error: *mut AnyObject = ptr::null_mut();
+        let registered: bool =
+            unsafe { msg_send![self.service, registerAndReturnError: &mut error] };
+        if registered {
+            Ok(())
+        } else {
+            Err(format!(
+                "SMAppService registration failed: {}",
+                describe(error)
+            ))
+        }
+    }
+
+    fn unregister(&self) -> Result<(), String> {
+        let mut error: *mut AnyObject = ptr::null_mut();
+        let unregistered: bool =
+            unsafe { msg_send![self.service, unregisterAndReturnError: &mut error] };
+        if unregistered {
+            Ok(())
+        } else {
+            Err(format!(
+                "SMAppService unregistration failed: {}",
+                describe(error)
+            ))
+        }
+    }
+}
+
+fn describe(error: *mut AnyObject) -> String {
+    if error.is_null() {
+        return "unknown error".to_string();
+    }
+    unsafe {
+        let description: *mut AnyObject = msg_send![error, localizedDescription];
+        if description.is_null() {
+            return "unknown error".to_string();
+        }
+        let utf8: *const c_char = msg_send![description, UTF8String];
+        if utf8.is_null() {
+            return "unknown error".to_string();
+        }
+        CStr::from_ptr(utf8).to_string_lossy().into_owned()
+    }
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn xml_unescape(value: &str) -> String {
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+/// First element of the `ProgramArguments` array.
+fn program_argument(plist: &str) -> Option<PathBuf> {
+    let after_key = &plist[plist.find("<key>ProgramArguments</key>")?..];
+    let start = after_key.find("<string>")? + "<string>".len();
+    let end = start + after_key[start..].find("</string>")?;
+    Some(PathBuf::from(xml_unescape(after_key[start..end].trim())))
+}
+
+/// `~/Library/LaunchAgents/<app name>.plist`, the same file earlier
+/// versions wrote, so those entries are recognised and replaced.
+struct LaunchAgent {
+    label: String,
+    path: PathBuf,
+}
+
+impl LoginItem for LaunchAgent {
+    fn registered_target(&self) -> Result<Option<PathBuf>, String> {
+        match fs::read_to_string(&self.path) {
+            Ok(plist) => Ok(program_argument(&plist)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(format!("Failed to read {}: {e}", self.path.display())),
+        }
+    }
+
+    fn register(&self, exe: &Path) -> Result<(), String> {
+        let plist = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{label}</string>
+    <key>ProgramArguments</key>
+    <array>
+        <string>{exe}</string>
+        <string>{arg}</string>
+    </array>
+    <key>RunAtLoad</key>
+    <true/>
+</dict>
+</plist>
+"#,
+            label = xml_escape(&self.label),
+            exe = xml_escape(&exe.to_string_lossy()),
+            arg = AUTO_START_ARG
+        );
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {e}", parent.display()))?;
+        }
+        fs::write(&self.path, plist)
+            .map_err(|e| format!("Failed to write {}: {e}", self.path.display()))
+    }
+
+    fn unregister(&self) -> Result<(), String> {
+        match fs::remove_file(&self.path) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(format!("Failed to remove {}: {e}", self.path.display())),
+        }
+    }
+}
+
+/// Prefers `SMAppService`, falling back to a launch agent on older macOS or
+/// when the service refuses, e.g. for an unsigned build outside a bundle.
+struct MacLoginItem {
+    service: Option<AppService>,
+    agent: LaunchAgent,
+}
+
+impl LoginItem for MacLoginItem {
+    fn registered_target(&self) -> Result<Option<PathBuf>, String> {
+        if self.service.as_ref().is_some_and(AppService::is_enabled) {
+            return std::env::current_exe()
+                .map(Some)
+                .map_err(|e| format!("Unable to locate current executable: {e}"));
+        }
+        self.agent.registered_target()
+    }
+
+    fn register(&self, exe: &Path) -> Result<(), String> {
+        if let Some(service) = &self.service {
+            match service.register() {
+                // A leftover launch agent would start the app a second time
+                Ok(()) => return self.agent.unregister(),
+                Err(e) => eprintln!("{e}; falling back to a launch agent"),
+            }
+        }
+        self.agent.register(exe)
+    }
+
+    fn unregister(&self) -> Result<(), String> {
+        if let Some(service) = self.service.as_ref().filter(|s| s.is_enabled()) {
+            service.unregister()?;
+        }
+        self.agent.unregister()
+    }
+}
+
+pub(super) fn login_item(app_name: &str) -> Box<dyn LoginItem> {
+    let home = std::env::var_os("HOME")
+        .map(PathBuf::from)
+        .unwrap_or_default();
+    Box::new(MacLoginItem {
+        service: AppService::main_app(),
+        agent: LaunchAgent {
+            label: app_name.to_string(),
+            path: home
+                .join("Library/LaunchAgents")
+                .join(format!("{app_name}.plist")),
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn launch_agent_round_trips_program_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let agent = LaunchAgent {
+            label: "Eclipse Market Pro".to_string(),
+            path: dir.path().join("LaunchAgents/Eclipse Market Pro.plist"),
+        };
+        let exe = PathBuf::from("/Applications/R&D/Eclipse Market Pro.app/Contents/MacOS/app");
+
+        assert_eq!(agent.registered_target().unwrap(), None);
+        agent.register(&exe).unwrap();
+        assert_eq!(agent.registered_target().unwrap(), Some(exe));
+        agent.unregister().unwrap();
+        assert!(!agent.path.exists());
+    }
+}
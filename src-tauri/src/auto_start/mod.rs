@@ -1,16 +1,38 @@
-use auto_launch::AutoLaunch;
+#[cfg(target_os = "linux")]
+mod linux;
+#[cfg(target_os = "macos")]
+mod macos;
+#[cfg(target_os = "windows")]
+mod win32;
+
+use crate::config::DataPaths;
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use tauri::{AppHandle, Manager};
+use tauri::AppHandle;
+
+const AUTO_START_ARG: &str = "--auto-start";
+
+/// How the login item is registered on Linux. Ignored on other platforms.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum LinuxAutoStartBackend {
+    /// `~/.config/autostart/*.desktop`, started by the desktop session.
+    #[default]
+    Xdg,
+    /// A `systemd --user` unit wanted by `graphical-session.target`.
+    Systemd,
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AutoStartSettings {
     pub enabled: bool,
     pub start_minimized: bool,
     pub delay_seconds: u32,
+    #[serde(default)]
+    pub linux_backend: LinuxAutoStartBackend,
 }
 
 impl Default for AutoStartSettings {
@@ -19,40 +41,50 @@ impl Default for AutoStartSettings {
             enabled: false,
             start_minimized: false,
             delay_seconds: 0,
+            linux_backend: LinuxAutoStartBackend::Xdg,
         }
     }
 }
 
+/// What the OS currently has registered, compared with the running binary.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AutoStartStatus {
+    Enabled,
+    Disabled,
+    /// Registered, but for an executable other than this one, typically
+    /// because the app was moved or updated.
+    Stale,
+    Unsupported,
+}
+
+/// A platform's login-item mechanism.
+trait LoginItem {
+    /// The executable the registered entry launches, or `None` when nothing
+    /// is registered.
+    fn registered_target(&self) -> Result<Option<PathBuf>, String>;
+    /// Registers `exe`, replacing any existing entry.
+    fn register(&self, exe: &Path) -> Result<(), String>;
+    fn unregister(&self) -> Result<(), String>;
+}
+
 pub struct AutoStartManager {
+    app_name: String,
     settings: RwLock<AutoStartSettings>,
     settings_path: RwLock<Option<PathBuf>>,
-    auto_launch: Option<AutoLaunch>,
 }
 
 impl AutoStartManager {
-    pub fn new(app_name: &str, app_path: &str) -> Result<Self, String> {
-        use auto_launch::AutoLaunchBuilder;
-
-        let mut builder = AutoLaunchBuilder::new();
-        builder.set_app_name(app_name);
-        builder.set_app_path(app_path);
-        builder.set_args(&["--auto-start"]);
-        #[cfg(target_os = "macos")]
-        builder.set_use_launch_agent(true);
-
-        let auto_launch = builder
-            .build()
-            .map_err(|e| format!("Failed to build AutoLaunch: {:?}", e))?;
-
-        Ok(Self {
+    pub fn new(app_name: &str) -> Self {
+        Self {
+            app_name: app_name.to_string(),
             settings: RwLock::new(AutoStartSettings::default()),
             settings_path: RwLock::new(None),
-            auto_launch: Some(auto_launch),
-        })
+        }
     }
 
     pub fn initialize(&self, app_handle: &AppHandle) {
-        if let Ok(mut data_dir) = app_handle.path().app_data_dir() {
+        if let Ok(mut data_dir) = app_handle.app_data_root() {
             if let Err(err) = fs::create_dir_all(&data_dir) {
                 eprintln!("Failed to ensure auto-start settings directory: {err}");
             } else {
@@ -68,7 +100,7 @@ impl AutoStartManager {
             }
         }
 
-        // Sync current state with OS
+        // Verify the OS entry against this executable, repairing it if stale
         if let Err(err) = self.sync_state() {
             eprintln!("Failed to sync auto-start state: {err}");
         }
@@ -91,24 +123,71 @@ impl AutoStartManager {
         Ok(())
     }
 
+    /// Resolved on every use rather than at startup so a moved or updated
+    /// app is compared against where it runs from now.
+    fn current_executable() -> Result<PathBuf, String> {
+        // An AppImage runs from a temporary mount; the image itself is what
+        // should be launched at login.
+        #[cfg(target_os = "linux")]
+        if let Some(appimage) = std::env::var_os("APPIMAGE") {
+            return Ok(PathBuf::from(appimage));
+        }
+        std::env::current_exe().map_err(|e| format!("Unable to locate current executable: {e}"))
+    }
+
+    #[allow(unused_variables)]
+    fn login_item(&self, settings: &AutoStartSettings) -> Option<Box<dyn LoginItem>> {
+        #[cfg(target_os = "linux")]
+        {
+            linux::login_item(&self.app_name, settings.linux_backend)
+        }
+        #[cfg(target_os = "macos")]
+        {
+            Some(macos::login_item(&self.app_name))
+        }
+        #[cfg(target_os = "windows")]
+        {
+            Some(win32::login_item(&self.app_name))
+        }
+        #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+        {
+            None
+        }
+    }
+
+    fn status_of(item: &dyn LoginItem, exe: &Path) -> Result<AutoStartStatus, String> {
+        Ok(match item.registered_target()? {
+            None => AutoStartStatus::Disabled,
+            Some(target) if same_executable(&target, exe) => AutoStartStatus::Enabled,
+            Some(_) => AutoStartStatus::Stale,
+        })
+    }
+
     fn sync_state(&self) -> Result<(), String> {
-        if let Some(auto_launch) = &self.auto_launch {
-            let enabled = self.settings.read().enabled;
-            let is_enabled = auto_launch
-                .is_enabled()
-                .map_err(|e| format!("Failed to check auto-start status: {e}"))?;
-
-            if enabled && !is_enabled {
-                auto_launch
-                    .enable()
-                    .map_err(|e| format!("Failed to enable auto-start: {e}"))?;
-            } else if !enabled && is_enabled {
-                auto_launch
-                    .disable()
-                    .map_err(|e| format!("Failed to disable auto-start: {e}"))?;
+        let settings = self.settings.read().clone();
+        let Some(item) = self.login_item(&settings) else {
+            return Ok(());
+        };
+        let exe = Self::current_executable()?;
+
+        #[cfg(target_os = "linux")]
+        linux::remove_other_backends(&self.app_name, settings.linux_backend)?;
+
+        match (settings.enabled, Self::status_of(item.as_ref(), &exe)?) {
+            (true, AutoStartStatus::Stale) => {
+                if let Ok(Some(previous)) = item.registered_target() {
+                    eprintln!(
+                        "Repairing stale auto-start entry: {} -> {}",
+                        previous.display(),
+                        exe.display()
+                    );
+                }
+                item.register(&exe)
             }
+            (true, AutoStartStatus::Disabled) => item.register(&exe),
+            (false, AutoStartStatus::Enabled | AutoStartStatus::Stale) => item.unregister(),
+            _ => Ok(()),
         }
-        Ok(())
     }
 
     pub fn get_settings(&self) -> AutoStartSettings {
@@ -126,17 +205,32 @@ impl AutoStartManager {
         Ok(())
     }
 
-    pub fn is_enabled(&self) -> Result<bool, String> {
-        if let Some(auto_launch) = &self.auto_launch {
-            auto_launch
-                .is_enabled()
-                .map_err(|e| format!("Failed to check auto-start status: {e}"))
-        } else {
-            Ok(false)
+    pub fn status(&self) -> Result<AutoStartStatus, String> {
+        let settings = self.settings.read().clone();
+        match self.login_item(&settings) {
+            Some(item) => Self::status_of(item.as_ref(), &Self::current_executable()?),
+            None => Ok(AutoStartStatus::Unsupported),
         }
     }
 }
 
+fn same_executable(a: &Path, b: &Path) -> bool {
+    let canonical = |path: &Path| fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    canonical(a) == canonical(b)
+}
+
+/// Splits an `Exec=`/`ExecStart=`-style command line and returns the program,
+/// honouring double quotes around paths with spaces.
+#[cfg_attr(target_os = "macos", allow(dead_code))]
+fn command_program(command: &str) -> Option<PathBuf> {
+    let command = command.trim();
+    let program = match command.strip_prefix('"') {
+        Some(rest) => &rest[..rest.find('"')?],
+        None => command.split_whitespace().next()?,
+    };
+    (!program.is_empty()).then(|| PathBuf::from(program))
+}
+
 pub type SharedAutoStartManager = Arc<AutoStartManager>;
 
 // Tauri commands
@@ -159,8 +253,8 @@ pub fn update_auto_start_settings(
 #[tauri::command]
 pub fn check_auto_start_enabled(
     auto_start_manager: tauri::State<'_, SharedAutoStartManager>,
-) -> Result<bool, String> {
-    auto_start_manager.is_enabled()
+) -> Result<AutoStartStatus, String> {
+    auto_start_manager.status()
 }
 
 #[tauri::command]
@@ -180,3 +274,21 @@ pub fn disable_auto_start(
     settings.enabled = false;
     auto_start_manager.update_settings(settings)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_program_from_quoted_and_bare_commands() {
+        assert_eq!(
+            command_program("\"/opt/Eclipse Market/app\" --auto-start"),
+            Some(PathBuf::from("/opt/Eclipse Market/app"))
+        );
+        assert_eq!(
+            command_program("/usr/bin/eclipse --auto-start"),
+            Some(PathBuf::from("/usr/bin/eclipse"))
+        );
+        assert_eq!(command_program("   "), None);
+    }
+}
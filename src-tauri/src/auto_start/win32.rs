@@ -0,0 +1,102 @@
+use super::{command_program, LoginItem, AUTO_START_ARG};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const RUN_KEY: &str = r"HKCU\Software\Microsoft\Windows\CurrentVersion\Run";
+
+/// A value under the per-user `Run` key, read and written with `reg.exe` so
+/// no registry bindings are needed.
+struct RunKeyEntry {
+    value_name: String,
+}
+
+/// Pulls the command out of `reg query` output, whose data line reads
+/// `    <name>    REG_SZ    "C:\path\app.exe" --auto-start`.
+fn registered_command(output: &str) -> Option<&str> {
+    output.lines().find_map(|line| {
+        let (_, data) = line.split_once("REG_SZ")?;
+        Some(data.trim())
+    })
+}
+
+fn run_reg(args: &[&str]) -> Result<std::process::Output, String> {
+    Command::new("reg")
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run reg.exe: {e}"))
+}
+
+impl LoginItem for RunKeyEntry {
+    fn registered_target(&self) -> Result<Option<PathBuf>, String> {
+        let output = run_reg(&["query", RUN_KEY, "/v", &self.value_name])?;
+        // reg exits non-zero when the value doesn't exist
+        if !output.status.success() {
+            return Ok(None);
+        }
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        Ok(registered_command(&stdout).and_then(command_program))
+    }
+
+    fn register(&self, exe: &Path) -> Result<(), String> {
+        let command = format!("\"{}\" {}", exe.display(), AUTO_START_ARG);
+        let output = run_reg(&[
+            "add",
+            RUN_KEY,
+            "/v",
+            &self.value_name,
+            "/t",
+            "REG_SZ",
+            "/d",
+            &command,
+            "/f",
+        ])?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(format!(
+                "Failed to register auto-start: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ))
+        }
+    }
+
+    fn unregister(&self) -> Result<(), String> {
+        if self.registered_target()?.is_none() {
+            return Ok(());
+        }
+        let output = run_reg(&["delete", RUN_KEY, "/v", &self.value_name, "/f"])?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(format!(
+                "Failed to remove auto-start entry: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ))
+        }
+    }
+}
+
+pub(super) fn login_item(app_name: &str) -> Box<dyn LoginItem> {
+    Box::new(RunKeyEntry {
+        value_name: app_name.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_reg_query_output() {
+        let output = "\r\nHKEY_CURRENT_USER\\Software\\Microsoft\\Windows\\CurrentVersion\\Run\r\n    \
+                      Eclipse Market Pro    REG_SZ    \"C:\\Program Files\\Eclipse Market Pro\\app.exe\" --auto-start\r\n\r\n";
+        let command = registered_command(output).unwrap();
+        assert_eq!(
+            command_program(command),
+            Some(PathBuf::from(
+                r"C:\Program Files\Eclipse Market Pro\app.exe"
+            ))
+        );
+        assert_eq!(registered_command("ERROR: value not found"), None);
+    }
+}
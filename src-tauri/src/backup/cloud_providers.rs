@@ -1,8 +1,9 @@
+use crate::config::DataPaths;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
-use tauri::{AppHandle, Manager};
+use tauri::AppHandle;
 
 const STORAGE_DIR: &str = "cloud_backups";
 const MAX_VERSIONS: usize = 20;
@@ -85,11 +86,9 @@ impl CloudProviderManager {
     }
 
     fn storage_base(&self) -> Result<PathBuf, CloudProviderError> {
-        let mut path = self
-            .app_handle
-            .path()
-            .app_data_dir()
-            .map_err(|e| std::io::Error::new(std::io::ErrorKind::NotFound, format!("app data dir: {}", e)))?;
+        let mut path = self.app_handle.app_data_root().map_err(|e| {
+            std::io::Error::new(std::io::ErrorKind::NotFound, format!("app data dir: {}", e))
+        })?;
         path.push(STORAGE_DIR);
         if !path.exists() {
             fs::create_dir_all(&path)?;
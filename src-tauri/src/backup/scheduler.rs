@@ -1,9 +1,10 @@
+use crate::config::DataPaths;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
 use std::sync::Arc;
-use tauri::{AppHandle, Manager};
+use tauri::AppHandle;
 use tokio::sync::RwLock;
 
 const SCHEDULER_CONFIG_FILE: &str = "backup_schedule.json";
@@ -107,7 +108,7 @@ impl BackupScheduler {
     }
 
     fn config_path(&self) -> Result<PathBuf, SchedulerError> {
-        let mut path = self.app_handle.path().app_data_dir().map_err(|e| {
+        let mut path = self.app_handle.app_data_root().map_err(|e| {
             std::io::Error::new(std::io::ErrorKind::NotFound, format!("App data directory not found: {}", e))
         })?;
 
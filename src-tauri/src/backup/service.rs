@@ -1,3 +1,4 @@
+use crate::config::DataPaths;
 use aes_gcm::aead::generic_array::GenericArray;
 use aes_gcm::{
     aead::{Aead, KeyInit, OsRng},
@@ -15,7 +16,11 @@ use tauri::{AppHandle, Manager, State};
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
+use crate::config::privacy_mode::warn_unmasked_export;
+use crate::journal::{JournalAttachmentError, SharedJournalAttachmentStore};
+use crate::portfolio::{SharedTokenAnnotationStore, TokenAnnotationError};
 use crate::security::keystore::{Keystore, KeystoreError};
+use crate::webhooks::events::{emit_webhook_event, BackupCompletedPayload, WebhookEventType};
 
 use super::cloud_providers::{
     BackupMetadata, CloudProvider, CloudProviderConfig, CloudProviderError, CloudProviderManager,
@@ -27,6 +32,8 @@ use super::settings_manager::{AppSettings, SettingsError, SettingsManager};
 
 const BACKUP_KEY_ID: &str = "backup.encryption_key";
 const BACKUP_CONFIG_FILE: &str = "backup_config.enc";
+const TOKEN_ANNOTATIONS_SECTION: &str = "tokenAnnotations";
+const JOURNAL_ATTACHMENTS_SECTION: &str = "journalAttachments";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -60,6 +67,10 @@ pub enum BackupError {
     Serialization(#[from] serde_json::Error),
     #[error("integrity check failed")]
     IntegrityCheckFailed,
+    #[error("token annotations error: {0}")]
+    TokenAnnotations(#[from] TokenAnnotationError),
+    #[error("journal attachments error: {0}")]
+    JournalAttachments(#[from] JournalAttachmentError),
 }
 
 pub type SharedBackupService = Arc<RwLock<BackupService>>;
@@ -163,8 +174,29 @@ impl BackupService {
             .try_state::<Keystore>()
             .ok_or(BackupError::KeystoreUnavailable)?;
 
+        // Backups must restore real values, so privacy mode never applies to them.
+        warn_unmasked_export(&self.app_handle, "backup");
+
+        let includes = |section: &str| match &sections {
+            Some(sections) => sections.iter().any(|s| s == section),
+            None => true,
+        };
+        let include_annotations = includes(TOKEN_ANNOTATIONS_SECTION);
+        let include_attachments = includes(JOURNAL_ATTACHMENTS_SECTION);
+
         // Export settings
-        let settings = self.settings_manager.export_settings(sections)?;
+        let mut settings = self.settings_manager.export_settings(sections)?;
+        if include_annotations {
+            settings.token_annotations = self
+                .app_handle
+                .try_state::<SharedTokenAnnotationStore>()
+                .map(|store| store.read().all());
+        }
+        if include_attachments {
+            if let Some(store) = self.app_handle.try_state::<SharedJournalAttachmentStore>() {
+                settings.journal_attachments = Some(store.read().export()?);
+            }
+        }
 
         // Serialize to JSON
         let json = serde_json::to_vec(&settings)?;
@@ -189,11 +221,22 @@ impl BackupService {
         self.cloud_manager
             .upload_backup(provider, &backup_data, metadata.clone())?;
 
+        emit_webhook_event(
+            &self.app_handle,
+            WebhookEventType::BackupCompleted,
+            BackupCompletedPayload {
+                filename: metadata.filename.clone(),
+                size_bytes: metadata.size_bytes,
+                version: metadata.version,
+                checksum: metadata.checksum.clone(),
+            },
+        );
+
         Ok(metadata)
     }
 
     fn config_path(&self) -> Result<PathBuf, BackupError> {
-        let mut path = self.app_handle.path().app_data_dir().map_err(|e| {
+        let mut path = self.app_handle.app_data_root().map_err(|e| {
             std::io::Error::new(std::io::ErrorKind::NotFound, format!("App data directory not found: {}", e))
         })?;
         if !path.exists() {
@@ -407,9 +450,7 @@ impl BackupService {
         let settings: AppSettings = serde_json::from_slice(&plaintext)?;
 
         // Import settings
-        self.settings_manager.import_settings(settings, merge)?;
-
-        Ok(())
+        self.import_settings(settings, merge)
     }
 
     pub fn list_backups(
@@ -456,8 +497,25 @@ impl BackupService {
         Ok(self.settings_manager.export_settings(sections)?)
     }
 
-    pub fn import_settings(&self, settings: AppSettings, merge: bool) -> Result<(), BackupError> {
+    pub fn import_settings(
+        &self,
+        mut settings: AppSettings,
+        merge: bool,
+    ) -> Result<(), BackupError> {
+        let annotations = settings.token_annotations.take();
+        let attachments = settings.journal_attachments.take();
         self.settings_manager.import_settings(settings, merge)?;
+
+        if let Some(annotations) = annotations {
+            if let Some(store) = self.app_handle.try_state::<SharedTokenAnnotationStore>() {
+                store.write().import(annotations, merge)?;
+            }
+        }
+        if let Some(attachments) = attachments {
+            if let Some(store) = self.app_handle.try_state::<SharedJournalAttachmentStore>() {
+                store.write().import(attachments, merge)?;
+            }
+        }
         Ok(())
     }
 
@@ -1,9 +1,13 @@
+use crate::config::DataPaths;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
-use tauri::{AppHandle, Manager};
+use tauri::AppHandle;
+
+use crate::journal::JournalAttachmentBackup;
+use crate::portfolio::TokenAnnotation;
 
 const SETTINGS_VERSION: u32 = 1;
 
@@ -18,6 +22,14 @@ pub struct AppSettings {
     pub api: Option<ApiSettings>,
     pub notifications: Option<NotificationSettings>,
     pub custom: Option<HashMap<String, serde_json::Value>>,
+    /// Token notes and tags. Only backups carry these; they are restored into
+    /// the annotation store rather than the settings file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_annotations: Option<Vec<TokenAnnotation>>,
+    /// Journal attachment files and their entry links, restored into the
+    /// attachment store. Only backups carry these.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub journal_attachments: Option<JournalAttachmentBackup>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -105,16 +117,12 @@ impl SettingsManager {
     }
 
     fn settings_path(&self) -> Result<PathBuf, SettingsError> {
-        let mut path = self
-            .app_handle
-            .path()
-            .app_data_dir()
-            .map_err(|e| {
-                SettingsError::Io(std::io::Error::new(
-                    std::io::ErrorKind::NotFound,
-                    format!("App data directory not found: {}", e),
-                ))
-            })?;
+        let mut path = self.app_handle.app_data_root().map_err(|e| {
+            SettingsError::Io(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("App data directory not found: {}", e),
+            ))
+        })?;
 
         if !path.exists() {
             fs::create_dir_all(&path)?;
@@ -166,6 +174,8 @@ impl SettingsManager {
                 api: None,
                 notifications: Some(NotificationSettings::default()),
                 custom: None,
+                token_annotations: None,
+                journal_attachments: None,
             }
         };
 
@@ -234,6 +244,8 @@ impl SettingsManager {
             api: None,
             notifications: Some(NotificationSettings::default()),
             custom: None,
+            token_annotations: None,
+            journal_attachments: None,
         };
 
         let path = self.settings_path()?;
@@ -314,6 +326,8 @@ impl SettingsManager {
                     webhook_enabled: false,
                 }),
                 custom: None,
+                token_annotations: None,
+                journal_attachments: None,
             },
             "aggressive" => AppSettings {
                 version: SETTINGS_VERSION,
@@ -346,6 +360,8 @@ impl SettingsManager {
                     webhook_enabled: true,
                 }),
                 custom: None,
+                token_annotations: None,
+                journal_attachments: None,
             },
             _ => {
                 return Err(SettingsError::Validation(
@@ -1,13 +1,26 @@
 use crate::api::jupiter::{
-    jupiter_quote, PriorityFeeConfig, QuoteCommandInput, QuoteResult, SwapMode,
+    jupiter_quote, JupiterClient, JupiterError, PriorityFeeConfig, QuoteCommandInput, QuoteResult,
+    SwapMode,
 };
+use crate::config::DataPaths;
+use crate::core::{OutboxDispatch, SharedOutbox};
+use crate::errors::ShutdownListener;
+use crate::trading::events::{
+    publish_order_event, OrderEvent, OrderEventKind, OrderEventSource, OrderRoute,
+};
+use crate::trading::idempotency::{IdempotencyGuard, IdempotencyKeys};
+use crate::trading::safety::{SafetyPolicy, SharedSafetyEngine, TradeOrigin};
+use crate::trading::types::OrderSide;
 use crate::utils::{OptionalRfc3339DateTime, Rfc3339DateTime};
+use crate::wallet::performance::{record_bot_trade, RecordTradeRequest, StrategySource};
+use crate::webhooks::events::{emit_webhook_event, DcaExecutedPayload, WebhookEventType};
 use chrono::{DateTime, NaiveDateTime, Utc};
 use cron::Schedule;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use sqlx::{FromRow, Pool, Row, Sqlite, SqlitePool};
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::Arc;
 use tauri::{AppHandle, Emitter, Manager};
@@ -15,6 +28,11 @@ use tokio::sync::{OnceCell, RwLock};
 use tokio::time::{interval, Duration};
 use uuid::Uuid;
 
+const DCA_OUTBOX_KIND: &str = "dca_execution";
+/// Executions queued while offline are skipped once they are this old, since
+/// the price they were scheduled at no longer holds.
+const DCA_OUTBOX_MAX_AGE: Duration = Duration::from_secs(15 * 60);
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DcaConfig {
     pub id: String,
@@ -130,6 +148,10 @@ pub struct CreateDcaRequest {
     pub priority_fee_micro_lamports: i32,
     pub max_price_impact_pct: f64,
     pub daily_spend_cap: Option<f64>,
+    /// UUID chosen by the client; retrying with the same id returns the DCA
+    /// created by the first attempt.
+    #[serde(default)]
+    pub client_request_id: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -243,9 +265,15 @@ impl DcaDatabase {
         .execute(&self.pool)
         .await?;
 
+        IdempotencyKeys::initialize(&self.pool).await?;
+
         Ok(())
     }
 
+    pub fn idempotency_keys(&self) -> IdempotencyKeys {
+        IdempotencyKeys::new(self.pool.clone())
+    }
+
     pub async fn create_config(&self, config: &DcaConfig) -> Result<(), sqlx::Error> {
         sqlx::query(
             r#"
@@ -420,6 +448,14 @@ impl DcaDatabase {
         .await
     }
 
+    pub async fn successful_executions(&self) -> Result<Vec<DcaExecution>, sqlx::Error> {
+        sqlx::query_as::<_, DcaExecution>(
+            "SELECT * FROM dca_executions WHERE status = 'success' ORDER BY executed_at ASC",
+        )
+        .fetch_all(&self.pool)
+        .await
+    }
+
     pub async fn execution_summary(
         &self,
         dca_id: &str,
@@ -476,6 +512,7 @@ pub struct DcaManager {
     db: SharedDcaDatabase,
     app_handle: AppHandle,
     schedules: Arc<RwLock<HashMap<String, Schedule>>>,
+    create_guard: IdempotencyGuard<String>,
 }
 
 impl DcaManager {
@@ -484,10 +521,25 @@ impl DcaManager {
             db,
             app_handle,
             schedules: Arc::new(RwLock::new(HashMap::new())),
+            create_guard: IdempotencyGuard::new("dca_create"),
         }
     }
 
+    /// Creates a DCA, or returns the one already created for the request's
+    /// `client_request_id`.
     pub async fn create_dca(&self, request: CreateDcaRequest) -> Result<DcaConfig, String> {
+        let request_id = request.client_request_id.clone();
+        let keys = self.db.read().await.idempotency_keys();
+        let id = self
+            .create_guard
+            .run(&keys, request_id.as_deref(), || async {
+                self.insert_dca(request).await.map(|config| config.id)
+            })
+            .await?;
+        self.get_dca(&id).await
+    }
+
+    async fn insert_dca(&self, request: CreateDcaRequest) -> Result<DcaConfig, String> {
         if request.amount_per_execution <= 0.0 {
             return Err("Amount per execution must be greater than zero".into());
         }
@@ -702,7 +754,30 @@ impl DcaManager {
             .await
             .map_err(|e| format!("Failed to fetch due DCA configs: {e}"))?;
 
+        let outbox = self
+            .app_handle
+            .try_state::<SharedOutbox>()
+            .map(|outbox| outbox.inner().clone());
         for config in due_configs {
+            if let Some(outbox) = &outbox {
+                let key = outbox_key(&config.id);
+                // Queued executions are submitted by the outbox.
+                if outbox.contains(&key) {
+                    continue;
+                }
+                if !outbox.is_online() {
+                    if let Err(err) = outbox.enqueue(
+                        DCA_OUTBOX_KIND,
+                        &key,
+                        format!("DCA execution for {}", config.name),
+                        &config.id,
+                        Utc::now(),
+                    ) {
+                        eprintln!("Failed to queue DCA {}: {}", config.id, err);
+                    }
+                    continue;
+                }
+            }
             if let Err(err) = self.execute_config(&config).await {
                 eprintln!("Failed to run DCA {}: {}", config.id, err);
             }
@@ -778,7 +853,7 @@ impl DcaManager {
             }),
         };
 
-        let quote_result: QuoteResult = jupiter_quote(quote_input)
+        let quote_result: QuoteResult = jupiter_quote(quote_input, None)
             .await
             .map_err(|e| format!("Failed to fetch quote: {e}"))?;
 
@@ -801,7 +876,37 @@ impl DcaManager {
             return Ok(());
         }
 
-        let output_amount = parse_amount(&quote_result.quote.output_amount, config.output_decimals);
+        let max_drift_bps = match self.app_handle.try_state::<SharedSafetyEngine>() {
+            Some(safety) => safety
+                .read()
+                .await
+                .get_policy()
+                .max_quote_drift_bps(TradeOrigin::Dca),
+            None => SafetyPolicy::default().max_quote_drift_bps(TradeOrigin::Dca),
+        };
+        let quote = match JupiterClient::default()
+            .requote(&quote_result.quote, max_drift_bps, TradeOrigin::Dca)
+            .await
+        {
+            Ok(quote) => quote,
+            Err(err @ JupiterError::QuoteDrift { .. }) => {
+                self.log_execution(
+                    config,
+                    0.0,
+                    0.0,
+                    0.0,
+                    "skipped",
+                    Some(err.to_string()),
+                    None,
+                )
+                .await?;
+                self.schedule_next(config, Some(&quote_result)).await?;
+                return Ok(());
+            }
+            Err(err) => return Err(format!("Failed to re-quote: {err}")),
+        };
+
+        let output_amount = parse_amount(&quote.output_amount, config.output_decimals);
         let input_amount = config.amount_per_execution;
         let price = if output_amount > 0.0 {
             input_amount / output_amount
@@ -810,17 +915,19 @@ impl DcaManager {
         };
 
         let execution_time = Utc::now();
+        let tx_signature = format!("simulated_{}", Uuid::new_v4());
 
-        self.log_execution(
-            config,
-            input_amount,
-            output_amount,
-            price,
-            "success",
-            None,
-            Some(format!("simulated_{}", Uuid::new_v4())),
-        )
-        .await?;
+        let execution_id = self
+            .log_execution(
+                config,
+                input_amount,
+                output_amount,
+                price,
+                "success",
+                None,
+                Some(tx_signature.clone()),
+            )
+            .await?;
 
         let new_spent = config.spent_amount + input_amount;
         self.db
@@ -839,6 +946,37 @@ impl DcaManager {
             "success",
             execution_time,
         );
+        record_bot_trade(
+            &self.app_handle,
+            performance_trade(
+                config,
+                &execution_id,
+                output_amount,
+                price,
+                &tx_signature,
+                execution_time,
+            ),
+        )
+        .await;
+        publish_order_event(OrderEvent::new(
+            OrderEventSource::Dca,
+            execution_id,
+            &config.output_symbol,
+            OrderSide::Buy,
+            output_amount,
+            OrderEventKind::Filled {
+                filled_amount: output_amount,
+                fill_price: Some(price),
+                tx_signature: Some(tx_signature),
+            },
+        )
+        .with_route(OrderRoute {
+            wallet_address: config.wallet_address.clone(),
+            input_mint: config.input_mint.clone(),
+            output_mint: config.output_mint.clone(),
+            input_symbol: config.input_symbol.clone(),
+            output_symbol: config.output_symbol.clone(),
+        }));
 
         Ok(())
     }
@@ -852,7 +990,7 @@ impl DcaManager {
         status: &str,
         error_message: Option<String>,
         tx_signature: Option<String>,
-    ) -> Result<(), String> {
+    ) -> Result<String, String> {
         let execution = DcaExecution {
             id: Uuid::new_v4().to_string(),
             dca_config_id: config.id.clone(),
@@ -871,7 +1009,9 @@ impl DcaManager {
             .await
             .record_execution(&execution)
             .await
-            .map_err(|e| format!("Failed to persist execution log: {e}"))
+            .map_err(|e| format!("Failed to persist execution log: {e}"))?;
+
+        Ok(execution.id)
     }
 
     async fn schedule_next(
@@ -920,13 +1060,31 @@ impl DcaManager {
             tx_signature: None,
         };
 
+        emit_webhook_event(
+            &self.app_handle,
+            WebhookEventType::DcaExecuted,
+            DcaExecutedPayload {
+                dca_id: event.dca_id.clone(),
+                name: event.name.clone(),
+                input_symbol: event.input_symbol.clone(),
+                output_symbol: event.output_symbol.clone(),
+                input_amount,
+                output_amount,
+                price,
+                status: event.status.clone(),
+                tx_signature: event.tx_signature.clone(),
+            },
+        );
         let _ = self.app_handle.emit("dca_execution", event);
     }
 
-    pub async fn start_monitoring(manager: Arc<Self>) {
+    pub async fn start_monitoring(manager: Arc<Self>, mut shutdown: ShutdownListener) {
         let mut ticker = interval(Duration::from_secs(30));
         loop {
-            ticker.tick().await;
+            tokio::select! {
+                _ = ticker.tick() => {}
+                _ = shutdown.cancelled() => break,
+            }
             if let Err(err) = manager.check_and_execute().await {
                 eprintln!("Error running DCA scheduler: {err}");
             }
@@ -934,6 +1092,72 @@ impl DcaManager {
     }
 }
 
+fn outbox_key(dca_id: &str) -> String {
+    format!("dca:{}", dca_id)
+}
+
+/// Runs an execution queued while offline, or records it as skipped when the
+/// outbox abandons it. Paused or deleted DCAs are dropped.
+async fn dispatch_queued_execution(dispatch: OutboxDispatch, payload: Value) -> Result<(), String> {
+    let dca_id: String = serde_json::from_value(payload)
+        .map_err(|e| format!("Invalid queued DCA execution: {e}"))?;
+    let state = require_state()?;
+    let config = state
+        .db
+        .read()
+        .await
+        .get_config(&dca_id)
+        .await
+        .map_err(|e| format!("Failed to load DCA config: {e}"))?;
+    let Some(config) = config.filter(|config| config.is_active) else {
+        return Ok(());
+    };
+
+    match dispatch {
+        OutboxDispatch::Submit => state.manager.execute_config(&config).await,
+        OutboxDispatch::Abandon(reason) => {
+            state
+                .manager
+                .log_execution(
+                    &config,
+                    0.0,
+                    0.0,
+                    0.0,
+                    "skipped",
+                    Some(format!("Queued while offline and {}", reason.describe())),
+                    None,
+                )
+                .await?;
+            state.manager.schedule_next(&config, None).await
+        }
+    }
+}
+
+/// Maps a successful execution onto a buy in the wallet performance database.
+fn performance_trade(
+    config: &DcaConfig,
+    execution_id: &str,
+    output_amount: f64,
+    price: f64,
+    tx_signature: &str,
+    executed_at: DateTime<Utc>,
+) -> RecordTradeRequest {
+    RecordTradeRequest {
+        wallet_address: config.wallet_address.clone(),
+        token_mint: config.output_mint.clone(),
+        token_symbol: config.output_symbol.clone(),
+        side: "buy".to_string(),
+        amount: output_amount,
+        price,
+        fee: 0.0,
+        tx_signature: tx_signature.to_string(),
+        strategy_source: Some(StrategySource::Dca(config.id.clone()).tag()),
+        external_id: Some(format!("dca:{}", execution_id)),
+        external_source: None,
+        executed_at: Some(executed_at),
+    }
+}
+
 fn to_base_units(amount: f64, decimals: i32) -> Result<u64, String> {
     if amount < 0.0 {
         return Err("Amount cannot be negative".into());
@@ -982,8 +1206,7 @@ pub async fn init_dca(app_handle: &AppHandle) -> Result<(), String> {
     }
 
     let app_dir = app_handle
-        .path()
-        .app_data_dir()
+        .app_data_root()
         .map_err(|e| format!("Unable to resolve app data directory: {}", e))?;
 
     std::fs::create_dir_all(&app_dir)
@@ -1002,8 +1225,9 @@ pub async fn init_dca(app_handle: &AppHandle) -> Result<(), String> {
 
     let handle_for_task = app_handle.clone();
     let manager_for_task = manager.clone();
+    let shutdown = crate::errors::shutdown_listener(app_handle, "dca_scheduler");
     tauri::async_runtime::spawn(async move {
-        DcaManager::start_monitoring(manager_for_task).await;
+        DcaManager::start_monitoring(manager_for_task, shutdown).await;
         let _ = handle_for_task.emit("dca_scheduler_stopped", "Scheduler stopped");
     });
 
@@ -1014,9 +1238,78 @@ pub async fn init_dca(app_handle: &AppHandle) -> Result<(), String> {
         })
         .map_err(|_| "DCA state already initialized".to_string())?;
 
+    if let Some(outbox) = app_handle.try_state::<SharedOutbox>() {
+        outbox.register(
+            DCA_OUTBOX_KIND,
+            Some(DCA_OUTBOX_MAX_AGE),
+            dispatch_queued_execution,
+        );
+    }
+
     Ok(())
 }
 
+/// Successful executions as performance trades, used to back-fill history
+/// recorded before bots reported to the performance database.
+pub async fn dca_performance_history() -> Result<Vec<RecordTradeRequest>, String> {
+    let Some(state) = DCA_STATE.get() else {
+        return Ok(Vec::new());
+    };
+
+    let db = state.db.read().await;
+    let executions = db
+        .successful_executions()
+        .await
+        .map_err(|e| format!("Failed to load DCA executions: {e}"))?;
+
+    let mut configs: HashMap<String, Option<DcaConfig>> = HashMap::new();
+    let mut requests = Vec::with_capacity(executions.len());
+    for execution in executions {
+        if !configs.contains_key(&execution.dca_config_id) {
+            let config = db
+                .get_config(&execution.dca_config_id)
+                .await
+                .map_err(|e| format!("Failed to load DCA config: {e}"))?;
+            configs.insert(execution.dca_config_id.clone(), config);
+        }
+        if let Some(Some(config)) = configs.get(&execution.dca_config_id) {
+            requests.push(performance_trade(
+                config,
+                &execution.id,
+                execution.output_amount,
+                execution.price,
+                execution.tx_signature.as_deref().unwrap_or_default(),
+                execution.executed_at,
+            ));
+        }
+    }
+
+    Ok(requests)
+}
+
+/// Mints the wallet's active DCA configs trade. Until the DCA module is
+/// initialized they are read from the automation database in `data_dir`.
+pub async fn active_dca_mints(
+    data_dir: &Path,
+    wallet_address: &str,
+) -> Result<HashSet<String>, String> {
+    let configs = match DCA_STATE.get() {
+        Some(state) => state.db.read().await.list_configs(wallet_address).await,
+        None => {
+            let db = DcaDatabase::new(data_dir.join("automation.db"))
+                .await
+                .map_err(|e| format!("Failed to open DCA database: {e}"))?;
+            db.list_configs(wallet_address).await
+        }
+    }
+    .map_err(|e| format!("Failed to load DCA configs: {e}"))?;
+    Ok(configs
+        .into_iter()
+        .filter(|config| config.is_active)
+        .flat_map(|config| [config.input_mint, config.output_mint])
+        .collect())
+}
+
 fn require_state<'a>() -> Result<&'a DcaState, String> {
     DCA_STATE
         .get()
@@ -1089,6 +1382,52 @@ mod tests {
         assert_eq!(next.hour(), 12);
     }
 
+    #[tokio::test]
+    async fn active_dca_mints_reads_the_database_before_init() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = DcaDatabase::new(dir.path().join("automation.db"))
+            .await
+            .unwrap();
+        let now = Utc::now();
+        let config = |id: &str, output_mint: &str, is_active: bool| DcaConfig {
+            id: id.to_string(),
+            name: id.to_string(),
+            wallet_address: "wallet".to_string(),
+            input_mint: "So11111111111111111111111111111111111111112".to_string(),
+            output_mint: output_mint.to_string(),
+            input_symbol: "SOL".to_string(),
+            output_symbol: "TOKEN".to_string(),
+            input_decimals: 9,
+            output_decimals: 6,
+            amount_per_execution: 0.1,
+            total_budget: 1.0,
+            spent_amount: 0.0,
+            schedule_cron: "0 0 12 * * *".to_string(),
+            slippage_bps: 50,
+            priority_fee_micro_lamports: 5000,
+            max_price_impact_pct: 1.0,
+            daily_spend_cap: None,
+            is_active,
+            created_at: now,
+            updated_at: now,
+            last_execution: None,
+            next_execution: None,
+        };
+        let usdc = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+        let bonk = "DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263";
+        db.create_config(&config("active", usdc, true))
+            .await
+            .unwrap();
+        db.create_config(&config("paused", bonk, false))
+            .await
+            .unwrap();
+
+        assert!(DCA_STATE.get().is_none());
+        let mints = active_dca_mints(dir.path(), "wallet").await.unwrap();
+        assert!(mints.contains(usdc));
+        assert!(!mints.contains(bonk));
+    }
+
     #[test]
     fn test_preview_next_execution_invalid() {
         let cron = "invalid cron";
@@ -44,6 +44,8 @@ pub async fn warm_cache(
                 CacheType::TopCoins
             } else if key.starts_with("trending_") {
                 CacheType::TrendingCoins
+            } else if key.starts_with("history_") {
+                CacheType::PriceHistory
             } else {
                 CacheType::UserData
             };
@@ -72,6 +74,7 @@ pub async fn get_cache_item(
         "TopCoins" => CacheType::TopCoins,
         "TrendingCoins" => CacheType::TrendingCoins,
         "UserData" => CacheType::UserData,
+        "PriceHistory" => CacheType::PriceHistory,
         _ => return Err("Invalid cache type".to_string()),
     };
 
@@ -93,6 +96,7 @@ pub async fn set_cache_item(
         "TopCoins" => CacheType::TopCoins,
         "TrendingCoins" => CacheType::TrendingCoins,
         "UserData" => CacheType::UserData,
+        "PriceHistory" => CacheType::PriceHistory,
         _ => return Err("Invalid cache type".to_string()),
     };
 
@@ -14,6 +14,7 @@ pub use polygon::*;
 pub use solana::*;
 pub use types::*;
 
+use crate::config::EnvironmentConfig;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -138,6 +139,14 @@ impl ChainManager {
         }
     }
 
+    /// Points Solana at the environment's RPC. The EVM chains stay on
+    /// their mainnets.
+    pub fn apply_environment(&mut self, environment: &EnvironmentConfig) {
+        if let Some(config) = self.configs.get_mut(&ChainId::Solana) {
+            config.rpc_url = environment.rpc_url();
+        }
+    }
+
     pub fn get_active_chain(&self) -> ChainId {
         self.active_chain.clone()
     }
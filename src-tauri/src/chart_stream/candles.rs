@@ -0,0 +1,315 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Closed candles kept per aggregator.
+pub const MAX_CANDLE_HISTORY: usize = 1000;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum CandleResolution {
+    #[serde(rename = "1s")]
+    OneSecond,
+    #[serde(rename = "5s")]
+    FiveSeconds,
+    #[serde(rename = "1m")]
+    OneMinute,
+    #[serde(rename = "5m")]
+    FiveMinutes,
+    #[serde(rename = "1h")]
+    OneHour,
+}
+
+impl CandleResolution {
+    pub const ALL: [CandleResolution; 5] = [
+        CandleResolution::OneSecond,
+        CandleResolution::FiveSeconds,
+        CandleResolution::OneMinute,
+        CandleResolution::FiveMinutes,
+        CandleResolution::OneHour,
+    ];
+
+    pub fn seconds(&self) -> i64 {
+        match self {
+            CandleResolution::OneSecond => 1,
+            CandleResolution::FiveSeconds => 5,
+            CandleResolution::OneMinute => 60,
+            CandleResolution::FiveMinutes => 300,
+            CandleResolution::OneHour => 3600,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CandleResolution::OneSecond => "1s",
+            CandleResolution::FiveSeconds => "5s",
+            CandleResolution::OneMinute => "1m",
+            CandleResolution::FiveMinutes => "5m",
+            CandleResolution::OneHour => "1h",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|res| res.as_str() == value)
+    }
+
+    /// Birdeye OHLCV `type` parameter; sub-minute candles only exist locally.
+    pub fn birdeye_type(&self) -> Option<&'static str> {
+        match self {
+            CandleResolution::OneSecond | CandleResolution::FiveSeconds => None,
+            CandleResolution::OneMinute => Some("1m"),
+            CandleResolution::FiveMinutes => Some("5m"),
+            CandleResolution::OneHour => Some("1H"),
+        }
+    }
+
+    /// True when candles of this resolution can be rolled up into `target`.
+    pub fn divides(&self, target: CandleResolution) -> bool {
+        self.seconds() <= target.seconds() && target.seconds() % self.seconds() == 0
+    }
+
+    pub fn bucket_start(&self, ts: i64) -> i64 {
+        ts - ts.rem_euclid(self.seconds())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Candle {
+    /// Bucket start, unix seconds.
+    pub time: i64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: f64,
+}
+
+impl Candle {
+    fn new(time: i64, price: f64, volume: f64) -> Self {
+        Self {
+            time,
+            open: price,
+            high: price,
+            low: price,
+            close: price,
+            volume,
+        }
+    }
+
+    fn apply(&mut self, price: f64, volume: f64) {
+        self.high = self.high.max(price);
+        self.low = self.low.min(price);
+        self.close = price;
+        self.volume += volume;
+    }
+
+    fn merge(&mut self, later: &Candle) {
+        self.high = self.high.max(later.high);
+        self.low = self.low.min(later.low);
+        self.close = later.close;
+        self.volume += later.volume;
+    }
+}
+
+/// Builds candles of one resolution from price ticks.
+#[derive(Debug)]
+pub struct CandleAggregator {
+    resolution: CandleResolution,
+    history: VecDeque<Candle>,
+    current: Option<Candle>,
+    last_volume_24h: Option<f64>,
+}
+
+impl CandleAggregator {
+    pub fn new(resolution: CandleResolution) -> Self {
+        Self {
+            resolution,
+            history: VecDeque::new(),
+            current: None,
+            last_volume_24h: None,
+        }
+    }
+
+    pub fn resolution(&self) -> CandleResolution {
+        self.resolution
+    }
+
+    /// Replaces history with `candles` (oldest first), keeping anything already
+    /// aggregated live that is newer than the seed.
+    pub fn seed(&mut self, candles: Vec<Candle>) {
+        let newest_seeded = candles.last().map(|c| c.time).unwrap_or(i64::MIN);
+        let live: Vec<Candle> = self
+            .history
+            .drain(..)
+            .filter(|c| c.time > newest_seeded)
+            .collect();
+        self.history = candles.into_iter().chain(live).collect();
+        if let Some(current) = &self.current {
+            self.history.retain(|c| c.time < current.time);
+        }
+        self.trim();
+    }
+
+    /// Folds a tick in. `volume_24h` is the rolling 24h volume from the stream;
+    /// candle volume is its increase between ticks. Returns the candle this tick
+    /// closed, if it opened a new bucket.
+    pub fn push_tick(&mut self, ts: i64, price: f64, volume_24h: Option<f64>) -> Option<Candle> {
+        let volume = match (self.last_volume_24h, volume_24h) {
+            (Some(previous), Some(latest)) => (latest - previous).max(0.0),
+            _ => 0.0,
+        };
+        if volume_24h.is_some() {
+            self.last_volume_24h = volume_24h;
+        }
+
+        let bucket = self.resolution.bucket_start(ts);
+        match self.current.as_mut() {
+            Some(current) if current.time == bucket => {
+                current.apply(price, volume);
+                None
+            }
+            // Late tick for a bucket that already closed.
+            Some(current) if bucket < current.time => None,
+            _ => {
+                let closed = self.close_current();
+                self.current = Some(Candle::new(bucket, price, volume));
+                closed
+            }
+        }
+    }
+
+    /// Closes the in-progress candle once its bucket has ended, even if no tick
+    /// arrived since.
+    pub fn close_if_elapsed(&mut self, now: i64) -> Option<Candle> {
+        let ended = self
+            .current
+            .as_ref()
+            .is_some_and(|c| now >= c.time + self.resolution.seconds());
+        if ended {
+            self.close_current()
+        } else {
+            None
+        }
+    }
+
+    fn close_current(&mut self) -> Option<Candle> {
+        let closed = self.current.take()?;
+        self.history.push_back(closed.clone());
+        self.trim();
+        Some(closed)
+    }
+
+    fn trim(&mut self) {
+        while self.history.len() > MAX_CANDLE_HISTORY {
+            self.history.pop_front();
+        }
+    }
+
+    pub fn current(&self) -> Option<&Candle> {
+        self.current.as_ref()
+    }
+
+    /// The most recent `limit` closed candles, oldest first.
+    pub fn recent(&self, limit: usize) -> Vec<Candle> {
+        let skip = self.history.len().saturating_sub(limit);
+        self.history.iter().skip(skip).cloned().collect()
+    }
+
+    /// Closed candles plus the in-progress one.
+    pub fn snapshot(&self) -> Vec<Candle> {
+        self.history
+            .iter()
+            .chain(self.current.iter())
+            .cloned()
+            .collect()
+    }
+}
+
+/// Combines oldest-first candles into candles of `target`. Callers must only pass
+/// candles whose resolution divides `target`.
+pub fn rollup(candles: &[Candle], target: CandleResolution) -> Vec<Candle> {
+    let mut out: Vec<Candle> = Vec::new();
+    for candle in candles {
+        let bucket = target.bucket_start(candle.time);
+        match out.last_mut() {
+            Some(last) if last.time == bucket => last.merge(candle),
+            _ => out.push(Candle {
+                time: bucket,
+                ..candle.clone()
+            }),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ticks_roll_into_candles() {
+        let mut agg = CandleAggregator::new(CandleResolution::FiveSeconds);
+
+        assert!(agg.push_tick(100, 10.0, Some(1_000.0)).is_none());
+        assert!(agg.push_tick(102, 12.0, Some(1_005.0)).is_none());
+        assert!(agg.push_tick(104, 9.0, Some(1_004.0)).is_none());
+
+        let closed = agg.push_tick(105, 11.0, Some(1_010.0)).unwrap();
+        assert_eq!(closed.time, 100);
+        assert_eq!(
+            (closed.open, closed.high, closed.low, closed.close),
+            (10.0, 12.0, 9.0, 9.0)
+        );
+        assert_eq!(closed.volume, 5.0);
+        assert_eq!(agg.current().unwrap().volume, 6.0);
+
+        // Late ticks for closed buckets are ignored.
+        assert!(agg.push_tick(103, 50.0, None).is_none());
+        assert_eq!(agg.recent(10).len(), 1);
+
+        assert!(agg.close_if_elapsed(109).is_none());
+        assert_eq!(agg.close_if_elapsed(110).unwrap().time, 105);
+        assert!(agg.current().is_none());
+    }
+
+    #[test]
+    fn rollup_merges_finer_candles() {
+        let minutes: Vec<Candle> = (0..10)
+            .map(|i| Candle {
+                time: 60 * i,
+                open: i as f64,
+                high: i as f64 + 1.0,
+                low: i as f64 - 1.0,
+                close: i as f64 + 0.5,
+                volume: 1.0,
+            })
+            .collect();
+
+        let five = rollup(&minutes, CandleResolution::FiveMinutes);
+        assert_eq!(five.len(), 2);
+        assert_eq!(five[0].time, 0);
+        assert_eq!(five[0].open, 0.0);
+        assert_eq!(five[0].high, 5.0);
+        assert_eq!(five[0].low, -1.0);
+        assert_eq!(five[0].close, 4.5);
+        assert_eq!(five[0].volume, 5.0);
+        assert_eq!(five[1].time, 300);
+
+        assert!(CandleResolution::OneMinute.divides(CandleResolution::OneHour));
+        assert!(!CandleResolution::FiveMinutes.divides(CandleResolution::OneMinute));
+    }
+
+    #[test]
+    fn seed_keeps_newer_live_candles() {
+        let mut agg = CandleAggregator::new(CandleResolution::OneMinute);
+        agg.push_tick(600, 1.0, None);
+        agg.push_tick(660, 2.0, None);
+
+        let seeded = (0..10).map(|i| Candle::new(60 * i, 0.5, 0.0)).collect();
+        agg.seed(seeded);
+
+        let recent = agg.recent(100);
+        assert_eq!(recent.len(), 11);
+        assert_eq!(recent.last().unwrap().time, 600);
+        assert_eq!(recent.last().unwrap().close, 1.0);
+        assert_eq!(agg.current().unwrap().time, 660);
+    }
+}
@@ -0,0 +1,117 @@
+use super::candles::{Candle, CandleResolution};
+use crate::api::http_client::{ProviderClient, ProviderProfile};
+use serde_json::Value;
+
+/// Fetches the most recent `limit` closed candles from Birdeye. Sub-minute
+/// resolutions have no REST source and return an empty list.
+pub async fn fetch_recent_candles(
+    symbol: &str,
+    resolution: CandleResolution,
+    limit: usize,
+) -> anyhow::Result<Vec<Candle>> {
+    let Some(interval) = resolution.birdeye_type() else {
+        return Ok(Vec::new());
+    };
+    let client = ProviderClient::new(ProviderProfile::BIRDEYE);
+    if !client.has_api_key() {
+        anyhow::bail!("Birdeye API key not configured");
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    let until = resolution.bucket_start(now);
+    let since = until - resolution.seconds() * limit as i64;
+
+    let response = client
+        .get("/defi/ohlcv")
+        .query(&[
+            ("address", symbol.to_string()),
+            ("type", interval.to_string()),
+            ("time_from", since.to_string()),
+            ("time_to", until.to_string()),
+        ])
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        anyhow::bail!("Birdeye returned {}", response.status());
+    }
+
+    let body: Value = response.json().await?;
+    // The bucket at `until` is still open and comes from the live aggregator.
+    Ok(parse_ohlcv(&body)
+        .into_iter()
+        .filter(|candle| candle.time < until)
+        .collect())
+}
+
+/// Parses a Birdeye OHLCV response into oldest-first candles.
+pub fn parse_ohlcv(body: &Value) -> Vec<Candle> {
+    let Some(items) = body.pointer("/data/items").and_then(Value::as_array) else {
+        return Vec::new();
+    };
+
+    let mut candles: Vec<Candle> = items
+        .iter()
+        .filter_map(|item| {
+            let field = |key: &str| item.get(key).and_then(Value::as_f64);
+            Some(Candle {
+                time: item.get("unixTime")?.as_i64()?,
+                open: field("o")?,
+                high: field("h")?,
+                low: field("l")?,
+                close: field("c")?,
+                volume: field("v").unwrap_or_default(),
+            })
+        })
+        .collect();
+    candles.sort_by_key(|candle| candle.time);
+    candles.dedup_by_key(|candle| candle.time);
+    candles
+}
+
+/// Puts `older` (e.g. from REST) in front of `newer` (aggregated locally),
+/// preferring local candles where both cover a bucket.
+pub fn merge_history(older: Vec<Candle>, newer: Vec<Candle>) -> Vec<Candle> {
+    let Some(first_local) = newer.first().map(|c| c.time) else {
+        return older;
+    };
+    older
+        .into_iter()
+        .filter(|candle| candle.time < first_local)
+        .chain(newer)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parses_and_merges_history() {
+        let body = json!({
+            "data": { "items": [
+                { "unixTime": 120, "o": 2.0, "h": 2.5, "l": 1.5, "c": 2.2, "v": 3.0 },
+                { "unixTime": 60, "o": 1.0, "h": 1.5, "l": 0.5, "c": 1.2 },
+                { "unixTime": 180, "o": 3.0 }
+            ]}
+        });
+
+        let rest = parse_ohlcv(&body);
+        assert_eq!(rest.len(), 2);
+        assert_eq!(rest[0].time, 60);
+        assert_eq!(rest[0].volume, 0.0);
+
+        let local = vec![Candle {
+            time: 120,
+            open: 2.1,
+            high: 2.4,
+            low: 2.0,
+            close: 2.3,
+            volume: 1.0,
+        }];
+        let merged = merge_history(rest, local);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[1].close, 2.3);
+    }
+}
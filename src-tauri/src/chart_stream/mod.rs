@@ -1,153 +1,396 @@
-use crate::core::price_engine::{get_price_engine, PriceUpdate};
-use crate::core::WebSocketManager;
+pub mod candles;
+mod history;
+
+pub use candles::*;
+pub use history::fetch_recent_candles;
+
+use crate::core::{WebSocketManager, CHART_CONSUMER};
+use crate::websocket::types::PriceDelta;
+use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
-use tauri::{AppHandle, Emitter, Manager, State};
-use tokio::sync::RwLock;
+use std::time::Instant;
+use tauri::{AppHandle, Emitter, State};
+use tokio::sync::{broadcast, mpsc};
 use tokio::time::Duration;
 
+pub const CHART_CANDLE_EVENT: &str = "chart_candle_update";
+
+const DEFAULT_HISTORY_LIMIT: usize = 300;
+const DEFAULT_UPDATE_INTERVAL_MS: u64 = 1000;
+const FLUSH_INTERVAL: Duration = Duration::from_millis(100);
+/// How long candles of an unsubscribed stream stay around for resolution switches.
+const RETIRED_TTL: Duration = Duration::from_secs(600);
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ChartPriceUpdate {
+pub struct ChartCandleUpdate {
     pub symbol: String,
-    pub price: f64,
-    pub volume: f64,
-    pub timestamp: u64,
-    pub change_24h: f64,
+    pub resolution: CandleResolution,
+    pub candle: Candle,
+    /// False for throttled updates of the in-progress candle.
+    pub closed: bool,
 }
 
-#[derive(Default)]
-struct ChartSubscriptions {
-    symbols: HashMap<String, ChartSubscriptionInfo>,
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChartSubscription {
+    pub symbol: String,
+    pub resolution: CandleResolution,
+    pub candles: Vec<Candle>,
+    pub current: Option<Candle>,
+    pub subscribers: u32,
 }
 
-struct ChartSubscriptionInfo {
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChartStreamInfo {
+    pub symbol: String,
+    pub resolution: CandleResolution,
+    pub subscribers: u32,
+    pub interval_ms: u64,
+    pub candles: usize,
+}
+
+type ChartKey = (String, CandleResolution);
+
+struct ChartStream {
+    aggregator: CandleAggregator,
+    subscribers: u32,
     interval_ms: u64,
-    ref_count: u32,
+    last_emit: Option<Instant>,
+    dirty: bool,
 }
 
-lazy_static::lazy_static! {
-    static ref CHART_SUBS: Arc<RwLock<ChartSubscriptions>> = Arc::new(RwLock::new(ChartSubscriptions::default()));
+struct RetiredCandles {
+    candles: Vec<Candle>,
+    retired_at: Instant,
 }
 
-/// Subscribe to high-frequency chart price updates
-#[tauri::command]
-pub async fn subscribe_chart_prices(
-    app_handle: AppHandle,
-    ws_manager: State<'_, WebSocketManager>,
-    symbol: String,
-    interval_ms: Option<u64>,
-) -> Result<(), String> {
-    let interval_ms = interval_ms.unwrap_or(1000); // Default to 1 second
-
-    // Subscribe to the WebSocket manager for real-time updates
-    ws_manager
-        .subscribe_prices(vec![symbol.clone()])
-        .await
-        .map_err(|e| e.to_string())?;
-
-    // Track subscription with ref counting
-    let should_start_task = {
-        let mut subs = CHART_SUBS.write().await;
-        let entry = subs
-            .symbols
-            .entry(symbol.clone())
-            .or_insert(ChartSubscriptionInfo {
+/// One aggregator per (token, resolution), shared by every chart window showing it.
+#[derive(Default)]
+struct ChartStreams {
+    streams: HashMap<ChartKey, ChartStream>,
+    retired: HashMap<ChartKey, RetiredCandles>,
+    feed_running: bool,
+}
+
+impl ChartStreams {
+    /// Adds a subscriber; returns true when this created the aggregator.
+    fn subscribe(&mut self, key: &ChartKey, interval_ms: u64, now: i64) -> bool {
+        if let Some(stream) = self.streams.get_mut(key) {
+            stream.subscribers += 1;
+            stream.interval_ms = stream.interval_ms.min(interval_ms);
+            return false;
+        }
+
+        let mut aggregator = CandleAggregator::new(key.1);
+        let local = self.local_history(key, now);
+        if !local.is_empty() {
+            aggregator.seed(local);
+        }
+        self.streams.insert(
+            key.clone(),
+            ChartStream {
+                aggregator,
+                subscribers: 1,
                 interval_ms,
-                ref_count: 0,
-            });
-        entry.ref_count += 1;
-        entry.ref_count == 1
-    };
+                last_emit: None,
+                dirty: false,
+            },
+        );
+        true
+    }
+
+    /// Drops a subscriber; returns true when this removed the aggregator.
+    fn unsubscribe(&mut self, key: &ChartKey) -> bool {
+        let Some(stream) = self.streams.get_mut(key) else {
+            return false;
+        };
+        stream.subscribers = stream.subscribers.saturating_sub(1);
+        if stream.subscribers > 0 {
+            return false;
+        }
 
-    if !should_start_task {
-        // Already running
-        return Ok(());
+        if let Some(stream) = self.streams.remove(key) {
+            self.retired.insert(
+                key.clone(),
+                RetiredCandles {
+                    candles: stream.aggregator.snapshot(),
+                    retired_at: Instant::now(),
+                },
+            );
+        }
+        true
     }
 
-    // Start emission task for this symbol if not already running
-    let app_handle_clone = app_handle.clone();
-    let symbol_clone = symbol.clone();
+    /// Candles for `key` built from what's already aggregated: a retired stream of
+    /// the same resolution, or the finer stream covering the longest span, rolled up.
+    /// A newest candle whose bucket is still open at `now` is left to the live feed.
+    fn local_history(&self, key: &ChartKey, now: i64) -> Vec<Candle> {
+        let (symbol, resolution) = key;
+        let live = self
+            .streams
+            .iter()
+            .filter(|((s, _), _)| s == symbol)
+            .map(|((_, res), stream)| (*res, stream.aggregator.snapshot()));
+        let retired = self
+            .retired
+            .iter()
+            .filter(|((s, _), retired)| s == symbol && retired.retired_at.elapsed() < RETIRED_TTL)
+            .map(|((_, res), retired)| (*res, retired.candles.clone()));
 
-    tokio::spawn(async move {
-        loop {
-            let interval_ms = {
-                let subs = CHART_SUBS.read().await;
-                subs.symbols.get(&symbol_clone).map(|info| info.interval_ms)
-            };
+        live.chain(retired)
+            .filter(|(res, candles)| res.divides(*resolution) && !candles.is_empty())
+            .min_by_key(|(_, candles)| candles[0].time)
+            .map(|(res, candles)| {
+                let mut rolled = if res == *resolution {
+                    candles
+                } else {
+                    rollup(&candles, *resolution)
+                };
+                let open = rolled
+                    .last()
+                    .is_some_and(|last| last.time + resolution.seconds() > now);
+                if open {
+                    rolled.pop();
+                }
+                rolled
+            })
+            .unwrap_or_default()
+    }
 
-            let interval_ms = match interval_ms {
-                Some(value) => value,
-                None => break,
-            };
+    fn seed(&mut self, key: &ChartKey, candles: Vec<Candle>) {
+        if let Some(stream) = self.streams.get_mut(key) {
+            stream.aggregator.seed(candles);
+        }
+    }
 
-            tokio::time::sleep(Duration::from_millis(interval_ms)).await;
+    fn subscription(&self, key: &ChartKey, limit: usize) -> Option<ChartSubscription> {
+        let stream = self.streams.get(key)?;
+        Some(ChartSubscription {
+            symbol: key.0.clone(),
+            resolution: key.1,
+            candles: stream.aggregator.recent(limit),
+            current: stream.aggregator.current().cloned(),
+            subscribers: stream.subscribers,
+        })
+    }
 
-            // After sleep, verify still subscribed
-            let still_subscribed = {
-                let subs = CHART_SUBS.read().await;
-                subs.symbols.contains_key(&symbol_clone)
-            };
+    fn on_tick(&mut self, delta: &PriceDelta) -> Vec<ChartCandleUpdate> {
+        let Some(price) = delta.price else {
+            return Vec::new();
+        };
 
-            if !still_subscribed {
-                break;
+        let mut updates = Vec::new();
+        for ((symbol, resolution), stream) in self.streams.iter_mut() {
+            if *symbol != delta.symbol {
+                continue;
+            }
+            if let Some(closed) = stream.aggregator.push_tick(delta.ts, price, delta.volume) {
+                updates.push(ChartCandleUpdate {
+                    symbol: symbol.clone(),
+                    resolution: *resolution,
+                    candle: closed,
+                    closed: true,
+                });
             }
+            stream.dirty = true;
+        }
+        updates
+    }
 
-            // Get latest price from engine
-            let engine = get_price_engine();
-            if let Some(cached_price) = engine.get_cached_price(&symbol_clone) {
-                let update = ChartPriceUpdate {
-                    symbol: symbol_clone.clone(),
-                    price: cached_price.price,
-                    volume: cached_price.volume,
-                    timestamp: cached_price.timestamp,
-                    change_24h: cached_price.change_24h,
-                };
+    /// Closes candles whose bucket ended and emits in-progress candles that are due.
+    fn flush(&mut self, now_secs: i64, now: Instant) -> Vec<ChartCandleUpdate> {
+        self.retired
+            .retain(|_, retired| now.duration_since(retired.retired_at) < RETIRED_TTL);
+
+        let mut updates = Vec::new();
+        for ((symbol, resolution), stream) in self.streams.iter_mut() {
+            if let Some(closed) = stream.aggregator.close_if_elapsed(now_secs) {
+                updates.push(ChartCandleUpdate {
+                    symbol: symbol.clone(),
+                    resolution: *resolution,
+                    candle: closed,
+                    closed: true,
+                });
+                stream.dirty = false;
+                continue;
+            }
 
-                // Emit event to frontend
-                let _ = app_handle_clone.emit("chart_price_update", &update);
+            let due = match stream.last_emit {
+                Some(last) => now.duration_since(last) >= Duration::from_millis(stream.interval_ms),
+                None => true,
+            };
+            if !stream.dirty || !due {
+                continue;
+            }
+            if let Some(current) = stream.aggregator.current() {
+                updates.push(ChartCandleUpdate {
+                    symbol: symbol.clone(),
+                    resolution: *resolution,
+                    candle: current.clone(),
+                    closed: false,
+                });
             }
+            stream.dirty = false;
+            stream.last_emit = Some(now);
         }
-    });
+        updates
+    }
+}
 
-    Ok(())
+lazy_static::lazy_static! {
+    static ref CHART_STREAMS: Arc<Mutex<ChartStreams>> = Arc::new(Mutex::new(ChartStreams::default()));
+    /// Closed candles for backend consumers such as indicator alerts.
+    static ref CLOSED_CANDLES: broadcast::Sender<ChartCandleUpdate> = broadcast::channel(256).0;
+}
+
+/// Every candle closed by any chart stream, whether or not a window shows it.
+pub fn closed_candles() -> broadcast::Receiver<ChartCandleUpdate> {
+    CLOSED_CANDLES.subscribe()
+}
+
+/// Up to `limit` closed candles of a stream, oldest first.
+pub fn recent_candles(symbol: &str, resolution: CandleResolution, limit: usize) -> Vec<Candle> {
+    CHART_STREAMS
+        .lock()
+        .streams
+        .get(&(symbol.to_string(), resolution))
+        .map(|stream| stream.aggregator.recent(limit))
+        .unwrap_or_default()
+}
+
+fn parse_resolution(resolution: Option<String>) -> Result<CandleResolution, String> {
+    match resolution {
+        None => Ok(CandleResolution::OneMinute),
+        Some(value) => CandleResolution::parse(&value)
+            .ok_or_else(|| format!("Unsupported chart resolution: {}", value)),
+    }
+}
+
+/// Subscribe to live candles for `symbol` at `resolution` (1s/5s/1m/5m/1h).
+/// Returns the most recent candles so the chart can render before live data.
+#[tauri::command]
+pub async fn subscribe_chart_prices(
+    app_handle: AppHandle,
+    ws_manager: State<'_, WebSocketManager>,
+    symbol: String,
+    resolution: Option<String>,
+    interval_ms: Option<u64>,
+    history_limit: Option<usize>,
+) -> Result<ChartSubscription, String> {
+    let resolution = parse_resolution(resolution)?;
+    subscribe_stream(
+        &app_handle,
+        &ws_manager,
+        &symbol,
+        resolution,
+        interval_ms.unwrap_or(DEFAULT_UPDATE_INTERVAL_MS),
+        history_limit.unwrap_or(DEFAULT_HISTORY_LIMIT),
+    )
+    .await
+}
+
+/// Adds one subscriber to the stream for `symbol` at `resolution`, creating
+/// it if needed. Backend consumers hold a subscriber like any chart window,
+/// so the stream keeps running after every window has closed.
+pub async fn subscribe_stream(
+    app_handle: &AppHandle,
+    ws_manager: &WebSocketManager,
+    symbol: &str,
+    resolution: CandleResolution,
+    interval_ms: u64,
+    history_limit: usize,
+) -> Result<ChartSubscription, String> {
+    let limit = history_limit.min(MAX_CANDLE_HISTORY);
+    let key = (symbol.to_string(), resolution);
+
+    // Registering the feed before attaching keeps a stopping feed task from
+    // releasing the new refs.
+    let (created, feed) = {
+        let mut streams = CHART_STREAMS.lock();
+        let feed = if streams.feed_running {
+            None
+        } else {
+            streams.feed_running = true;
+            Some(ws_manager.price_feed(CHART_CONSUMER, Duration::ZERO))
+        };
+        let created = streams.subscribe(&key, interval_ms, chrono::Utc::now().timestamp());
+        (created, feed)
+    };
+
+    if let Some(feed) = feed {
+        let app = app_handle.clone();
+        tauri::async_runtime::spawn(async move {
+            run_chart_feed(app, feed).await;
+        });
+    }
+
+    if created {
+        if let Err(e) = ws_manager
+            .subscribe_prices_as(CHART_CONSUMER, vec![symbol.to_string()])
+            .await
+        {
+            CHART_STREAMS.lock().unsubscribe(&key);
+            return Err(e.to_string());
+        }
+    }
+    backfill_history(symbol, resolution, limit).await;
+
+    let streams = CHART_STREAMS.lock();
+    streams
+        .subscription(&key, limit)
+        .ok_or_else(|| format!("Chart stream for {} was closed", symbol))
 }
 
-impl Clone for ChartSubscriptionInfo {
-    fn clone(&self) -> Self {
-        Self {
-            interval_ms: self.interval_ms,
-            ref_count: self.ref_count,
+/// Loads closed candles from REST until a running stream holds `limit` of
+/// them, e.g. enough bars to warm an indicator.
+pub async fn backfill_history(symbol: &str, resolution: CandleResolution, limit: usize) {
+    let limit = limit.min(MAX_CANDLE_HISTORY);
+    if recent_candles(symbol, resolution, limit).len() >= limit {
+        return;
+    }
+
+    let key = (symbol.to_string(), resolution);
+    match history::fetch_recent_candles(symbol, resolution, limit).await {
+        Ok(remote) => {
+            let mut streams = CHART_STREAMS.lock();
+            let local = streams
+                .streams
+                .get(&key)
+                .map(|stream| stream.aggregator.recent(MAX_CANDLE_HISTORY))
+                .unwrap_or_default();
+            streams.seed(&key, history::merge_history(remote, local));
         }
+        Err(e) => eprintln!("Failed to load chart history for {}: {}", symbol, e),
     }
 }
 
-/// Unsubscribe from chart price updates
+/// Unsubscribe one chart window from `symbol` at `resolution`
 #[tauri::command]
 pub async fn unsubscribe_chart_prices(
     ws_manager: State<'_, WebSocketManager>,
     symbol: String,
+    resolution: Option<String>,
 ) -> Result<(), String> {
-    // Decrement ref count and potentially remove
-    let should_unsubscribe = {
-        let mut subs = CHART_SUBS.write().await;
-        if let Some(info) = subs.symbols.get_mut(&symbol) {
-            info.ref_count = info.ref_count.saturating_sub(1);
-            if info.ref_count == 0 {
-                subs.symbols.remove(&symbol);
-                true
-            } else {
-                false
-            }
-        } else {
-            false
-        }
+    let resolution = parse_resolution(resolution)?;
+    unsubscribe_stream(&ws_manager, &symbol, resolution).await
+}
+
+/// Drops one subscriber added by [`subscribe_stream`].
+pub async fn unsubscribe_stream(
+    ws_manager: &WebSocketManager,
+    symbol: &str,
+    resolution: CandleResolution,
+) -> Result<(), String> {
+    let removed = {
+        let mut streams = CHART_STREAMS.lock();
+        streams.unsubscribe(&(symbol.to_string(), resolution))
     };
 
-    // Only unsubscribe from WebSocket if no more refs
-    if should_unsubscribe {
+    // Each aggregator holds one multiplexer ref, released with its last subscriber
+    if removed {
         ws_manager
-            .unsubscribe_prices(vec![symbol])
+            .unsubscribe_prices_as(CHART_CONSUMER, vec![symbol.to_string()])
             .await
             .map_err(|e| e.to_string())?;
     }
@@ -155,9 +398,161 @@ pub async fn unsubscribe_chart_prices(
     Ok(())
 }
 
-/// Get current chart subscription status
+/// Get current chart streams and their subscriber counts
 #[tauri::command]
-pub async fn get_chart_subscriptions() -> Result<Vec<String>, String> {
-    let subs = CHART_SUBS.read().await;
-    Ok(subs.symbols.keys().cloned().collect())
+pub async fn get_chart_subscriptions() -> Result<Vec<ChartStreamInfo>, String> {
+    let streams = CHART_STREAMS.lock();
+    Ok(streams
+        .streams
+        .iter()
+        .map(|((symbol, resolution), stream)| ChartStreamInfo {
+            symbol: symbol.clone(),
+            resolution: *resolution,
+            subscribers: stream.subscribers,
+            interval_ms: stream.interval_ms,
+            candles: stream.aggregator.recent(MAX_CANDLE_HISTORY).len(),
+        })
+        .collect())
+}
+
+async fn run_chart_feed(app_handle: AppHandle, mut feed: mpsc::Receiver<PriceDelta>) {
+    let mut ticker = tokio::time::interval(FLUSH_INTERVAL);
+    loop {
+        let updates = tokio::select! {
+            delta = feed.recv() => {
+                let mut streams = CHART_STREAMS.lock();
+                match delta {
+                    Some(delta) => streams.on_tick(&delta),
+                    None => {
+                        streams.feed_running = false;
+                        break;
+                    }
+                }
+            }
+            _ = ticker.tick() => {
+                let mut streams = CHART_STREAMS.lock();
+                if streams.streams.is_empty() {
+                    streams.feed_running = false;
+                    break;
+                }
+                streams.flush(chrono::Utc::now().timestamp(), Instant::now())
+            }
+        };
+
+        for update in updates {
+            if update.closed {
+                let _ = CLOSED_CANDLES.send(update.clone());
+            }
+            let _ = app_handle.emit(CHART_CANDLE_EVENT, &update);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tick(symbol: &str, ts: i64, price: f64) -> PriceDelta {
+        PriceDelta {
+            symbol: symbol.to_string(),
+            price: Some(price),
+            change: None,
+            volume: None,
+            ts,
+            snapshot: false,
+        }
+    }
+
+    #[test]
+    fn windows_share_one_aggregator() {
+        let mut streams = ChartStreams::default();
+        let key = ("SOL".to_string(), CandleResolution::FiveSeconds);
+
+        assert!(streams.subscribe(&key, 1000, 0));
+        assert!(!streams.subscribe(&key, 250, 0));
+        assert_eq!(streams.streams.len(), 1);
+        assert_eq!(streams.streams[&key].interval_ms, 250);
+
+        assert!(!streams.unsubscribe(&key));
+        assert!(streams.unsubscribe(&key));
+        assert!(streams.streams.is_empty());
+        assert!(!streams.unsubscribe(&key));
+    }
+
+    #[test]
+    fn ticks_emit_closed_and_throttled_candles() {
+        let mut streams = ChartStreams::default();
+        let key = ("SOL".to_string(), CandleResolution::OneSecond);
+        streams.subscribe(&key, 1000, 0);
+
+        assert!(streams.on_tick(&tick("SOL", 10, 1.0)).is_empty());
+        assert!(streams.on_tick(&tick("BONK", 10, 9.0)).is_empty());
+
+        let start = Instant::now();
+        let updates = streams.flush(10, start);
+        assert_eq!(updates.len(), 1);
+        assert!(!updates[0].closed);
+
+        // New tick, but the throttle has not elapsed.
+        streams.on_tick(&tick("SOL", 10, 1.5));
+        assert!(streams
+            .flush(10, start + Duration::from_millis(500))
+            .is_empty());
+
+        let closed = streams.on_tick(&tick("SOL", 11, 2.0));
+        assert_eq!(closed.len(), 1);
+        assert!(closed[0].closed);
+        assert_eq!(closed[0].candle.close, 1.5);
+
+        let updates = streams.flush(12, start + Duration::from_millis(600));
+        assert_eq!(updates.len(), 1);
+        assert!(updates[0].closed);
+        assert_eq!(updates[0].candle.time, 11);
+    }
+
+    #[test]
+    fn resolution_switch_reuses_finer_candles() {
+        let mut streams = ChartStreams::default();
+        let fine = ("SOL".to_string(), CandleResolution::OneMinute);
+        streams.subscribe(&fine, 1000, 0);
+        for minute in 0..12 {
+            streams.on_tick(&tick("SOL", minute * 60, minute as f64));
+        }
+        streams.unsubscribe(&fine);
+
+        let coarse = ("SOL".to_string(), CandleResolution::FiveMinutes);
+        assert!(streams.subscribe(&coarse, 1000, 11 * 60 + 30));
+        let sub = streams.subscription(&coarse, 100).unwrap();
+        // Minutes 0-9 roll up into two 5m candles; minutes 10-11 are still filling in.
+        assert_eq!(sub.candles.len(), 2);
+        assert_eq!(sub.candles[0].open, 0.0);
+        assert_eq!(sub.candles[1].close, 9.0);
+
+        // Coarser candles cannot be split back into finer ones.
+        let finer = ("SOL".to_string(), CandleResolution::OneSecond);
+        streams.subscribe(&finer, 1000, 11 * 60 + 30);
+        assert!(streams
+            .subscription(&finer, 100)
+            .unwrap()
+            .candles
+            .is_empty());
+    }
+
+    #[test]
+    fn closed_newest_candle_is_kept() {
+        let mut streams = ChartStreams::default();
+        let fine = ("SOL".to_string(), CandleResolution::OneMinute);
+        streams.subscribe(&fine, 1000, 0);
+        for minute in 0..15 {
+            streams.on_tick(&tick("SOL", minute * 60, minute as f64));
+        }
+        streams.unsubscribe(&fine);
+
+        // By 15:00 the 10-14 bucket has closed, so all three 5m candles seed.
+        let coarse = ("SOL".to_string(), CandleResolution::FiveMinutes);
+        assert!(streams.subscribe(&coarse, 1000, 15 * 60));
+        let sub = streams.subscription(&coarse, 100).unwrap();
+        assert_eq!(sub.candles.len(), 3);
+        assert_eq!(sub.candles[2].close, 14.0);
+    }
 }
@@ -1,13 +1,20 @@
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
 use chrono::Utc;
 use tauri::State;
 use uuid::Uuid;
 
+use crate::collab::competition::{
+    enroll, is_auto_scored, measure, prepare_competition, record_reading,
+};
 use crate::collab::crypto::RoomEncryption;
+use crate::collab::history::{MessagePage, MessageRetention, DEFAULT_HISTORY_PAGE_SIZE};
 use crate::collab::moderation::ModerationManager;
-use crate::collab::permissions::{can_modify_permissions, default_permissions_for_role};
+use crate::collab::orders::{apply_live_order, finalize, refresh_linked_orders, visible_to};
+use crate::collab::permissions::can_modify_message;
 use crate::collab::state::CollabState;
 use crate::collab::types::*;
+use crate::trading::limit_orders::require_state;
+use crate::wallet::performance::SharedPerformanceDatabase;
 
 #[tauri::command]
 pub async fn collab_create_room(
@@ -61,6 +68,12 @@ pub async fn collab_delete_room(
         .delete_room(&uuid, &user_id)
         .map_err(|e| e.to_string())?;
 
+    state
+        .history
+        .purge_room(&uuid)
+        .await
+        .map_err(|e| e.to_string())?;
+
     state
         .websocket
         .broadcast(uuid, CollabMessage::RoomDeleted { room_id: uuid })
@@ -68,6 +81,7 @@ pub async fn collab_delete_room(
 
     state.websocket.clean_room(&uuid);
     state.rtc.clear_room(&uuid);
+    state.moderation.clear_room(&uuid);
 
     Ok(())
 }
@@ -77,12 +91,23 @@ pub async fn collab_join_room(
     request: JoinRoomRequest,
     user_id: String,
     state: State<'_, CollabState>,
-) -> Result<Participant, String> {
+) -> Result<JoinedRoom, String> {
     let participant = state
         .rooms
         .join_room(request.clone(), user_id.clone())
         .map_err(|e| e.to_string())?;
 
+    let history = state
+        .history
+        .page(
+            &request.room_id,
+            None,
+            None,
+            Some(request.history_limit.unwrap_or(DEFAULT_HISTORY_PAGE_SIZE)),
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+
     state.websocket.subscribe_to_room(request.room_id, user_id);
 
     state
@@ -95,7 +120,10 @@ pub async fn collab_join_room(
         )
         .map_err(|e| e.to_string())?;
 
-    Ok(participant)
+    Ok(JoinedRoom {
+        participant,
+        history,
+    })
 }
 
 #[tauri::command]
@@ -147,36 +175,129 @@ pub async fn collab_update_permissions(
     moderator_id: String,
     state: State<'_, CollabState>,
 ) -> Result<(), String> {
-    let moderator = state
+    let (participant, entry) = state
         .rooms
-        .get_participant(&request.room_id, &moderator_id)
+        .update_permissions(
+            &request.room_id,
+            &moderator_id,
+            &request.user_id,
+            &request.permissions,
+        )
         .map_err(|e| e.to_string())?;
+    state.moderation.record_role_change(entry);
 
-    if !can_modify_permissions(moderator.role) {
-        return Err("Insufficient permissions".to_string());
-    }
-
-    let mut participant = state
-        .rooms
-        .get_participant(&request.room_id, &request.user_id)
+    state
+        .websocket
+        .broadcast(
+            request.room_id,
+            CollabMessage::ParticipantUpdated { participant },
+        )
         .map_err(|e| e.to_string())?;
 
-    participant.permissions = request.permissions;
+    Ok(())
+}
 
-    state
+/// Moderators can move traders and viewers between those roles; the owner
+/// can also appoint moderators. Per-participant overrides are kept.
+#[tauri::command]
+pub async fn collab_set_role(
+    room_id: String,
+    target_user_id: String,
+    role: ParticipantRole,
+    user_id: String,
+    state: State<'_, CollabState>,
+) -> Result<Participant, String> {
+    let uuid = Uuid::parse_str(&room_id).map_err(|e| e.to_string())?;
+    let (participant, change) = state
         .rooms
-        .update_participant(participant.clone())
+        .set_role(&uuid, &user_id, &target_user_id, role)
         .map_err(|e| e.to_string())?;
+    state.moderation.record_role_change(change.clone());
 
     state
         .websocket
         .broadcast(
-            request.room_id,
-            CollabMessage::ParticipantUpdated { participant },
+            uuid,
+            CollabMessage::RoleChanged {
+                change,
+                participant: participant.clone(),
+            },
         )
         .map_err(|e| e.to_string())?;
 
-    Ok(())
+    Ok(participant)
+}
+
+/// The only way ownership changes hands. The previous owner becomes a
+/// moderator and may then leave.
+#[tauri::command]
+pub async fn collab_transfer_ownership(
+    room_id: String,
+    new_owner_id: String,
+    user_id: String,
+    state: State<'_, CollabState>,
+) -> Result<Room, String> {
+    let uuid = Uuid::parse_str(&room_id).map_err(|e| e.to_string())?;
+    let (room, changes) = state
+        .rooms
+        .transfer_ownership(&uuid, &user_id, &new_owner_id)
+        .map_err(|e| e.to_string())?;
+
+    for (participant, change) in changes {
+        state.moderation.record_role_change(change.clone());
+        state
+            .websocket
+            .broadcast(
+                uuid,
+                CollabMessage::RoleChanged {
+                    change,
+                    participant,
+                },
+            )
+            .map_err(|e| e.to_string())?;
+    }
+    state
+        .websocket
+        .broadcast(uuid, CollabMessage::RoomUpdated { room: room.clone() })
+        .map_err(|e| e.to_string())?;
+
+    Ok(room)
+}
+
+#[tauri::command]
+pub async fn collab_get_my_permissions(
+    room_id: String,
+    user_id: String,
+    state: State<'_, CollabState>,
+) -> Result<MyPermissions, String> {
+    let uuid = Uuid::parse_str(&room_id).map_err(|e| e.to_string())?;
+    let room = state.rooms.get_room(&uuid).map_err(|e| e.to_string())?;
+    let participant = state
+        .rooms
+        .get_participant(&uuid, &user_id)
+        .map_err(|e| e.to_string())?;
+
+    Ok(MyPermissions {
+        room_id: uuid,
+        role: participant.role,
+        permissions: participant.permissions,
+        is_owner: room.owner_id == user_id,
+    })
+}
+
+/// Who changed whose role or permissions; visible to moderators.
+#[tauri::command]
+pub async fn collab_get_role_audit(
+    room_id: String,
+    user_id: String,
+    state: State<'_, CollabState>,
+) -> Result<Vec<RoleAuditEntry>, String> {
+    let uuid = Uuid::parse_str(&room_id).map_err(|e| e.to_string())?;
+    state
+        .rooms
+        .require_capability(&uuid, &user_id, Capability::Moderate)
+        .map_err(|e| e.to_string())?;
+    Ok(state.moderation.role_history(&uuid))
 }
 
 #[tauri::command]
@@ -191,6 +312,12 @@ pub async fn collab_send_message(
         .send_message(request.clone(), user_id, username)
         .map_err(|e| e.to_string())?;
 
+    state
+        .history
+        .append(&message)
+        .await
+        .map_err(|e| e.to_string())?;
+
     state
         .websocket
         .broadcast(
@@ -204,14 +331,168 @@ pub async fn collab_send_message(
     Ok(message)
 }
 
+/// Pages through a room's history: the latest messages by default, or the page
+/// before/after a message id.
 #[tauri::command]
 pub async fn collab_get_messages(
     room_id: String,
+    before: Option<String>,
+    after: Option<String>,
+    limit: Option<usize>,
+    state: State<'_, CollabState>,
+) -> Result<MessagePage, String> {
+    let uuid = Uuid::parse_str(&room_id).map_err(|e| e.to_string())?;
+    let before = before
+        .map(|id| Uuid::parse_str(&id))
+        .transpose()
+        .map_err(|e| e.to_string())?;
+    let after = after
+        .map(|id| Uuid::parse_str(&id))
+        .transpose()
+        .map_err(|e| e.to_string())?;
+
+    state
+        .history
+        .page(&uuid, before, after, limit)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn collab_search_messages(
+    room_id: String,
+    query: String,
     limit: Option<usize>,
     state: State<'_, CollabState>,
 ) -> Result<Vec<ChatMessage>, String> {
     let uuid = Uuid::parse_str(&room_id).map_err(|e| e.to_string())?;
-    Ok(state.rooms.get_messages(&uuid, limit))
+    state
+        .history
+        .search(&uuid, &query, limit)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn collab_edit_message(
+    request: EditMessageRequest,
+    user_id: String,
+    state: State<'_, CollabState>,
+) -> Result<ChatMessage, String> {
+    let participant = state
+        .rooms
+        .get_participant(&request.room_id, &user_id)
+        .map_err(|e| e.to_string())?;
+    let original = state
+        .history
+        .get_message(&request.room_id, &request.message_id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if !can_modify_message(&participant, &original.user_id) {
+        return Err("Only the author or a moderator can edit this message".to_string());
+    }
+
+    let message = state
+        .history
+        .edit(
+            &request.room_id,
+            &request.message_id,
+            request.content,
+            Utc::now(),
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+
+    state
+        .websocket
+        .broadcast(
+            request.room_id,
+            CollabMessage::ChatMessageEdited {
+                message: message.clone(),
+            },
+        )
+        .map_err(|e| e.to_string())?;
+
+    Ok(message)
+}
+
+#[tauri::command]
+pub async fn collab_delete_message(
+    room_id: String,
+    message_id: String,
+    user_id: String,
+    state: State<'_, CollabState>,
+) -> Result<ChatMessage, String> {
+    let room_uuid = Uuid::parse_str(&room_id).map_err(|e| e.to_string())?;
+    let message_uuid = Uuid::parse_str(&message_id).map_err(|e| e.to_string())?;
+
+    let participant = state
+        .rooms
+        .get_participant(&room_uuid, &user_id)
+        .map_err(|e| e.to_string())?;
+    let original = state
+        .history
+        .get_message(&room_uuid, &message_uuid)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if !can_modify_message(&participant, &original.user_id) {
+        return Err("Only the author or a moderator can delete this message".to_string());
+    }
+
+    let message = state
+        .history
+        .tombstone(&room_uuid, &message_uuid, &user_id, Utc::now())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    state
+        .websocket
+        .broadcast(
+            room_uuid,
+            CollabMessage::ChatMessageDeleted {
+                message: message.clone(),
+            },
+        )
+        .map_err(|e| e.to_string())?;
+
+    Ok(message)
+}
+
+#[tauri::command]
+pub async fn collab_get_message_retention(
+    room_id: String,
+    state: State<'_, CollabState>,
+) -> Result<MessageRetention, String> {
+    let uuid = Uuid::parse_str(&room_id).map_err(|e| e.to_string())?;
+    state
+        .history
+        .retention(&uuid)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Only the room owner may change retention; `None` keeps history indefinitely.
+#[tauri::command]
+pub async fn collab_set_message_retention(
+    room_id: String,
+    retention_days: Option<u32>,
+    user_id: String,
+    state: State<'_, CollabState>,
+) -> Result<MessageRetention, String> {
+    let uuid = Uuid::parse_str(&room_id).map_err(|e| e.to_string())?;
+    let room = state.rooms.get_room(&uuid).map_err(|e| e.to_string())?;
+
+    if room.owner_id != user_id {
+        return Err("Only room owner can change message retention".to_string());
+    }
+
+    state
+        .history
+        .set_retention(&uuid, retention_days, &user_id, Utc::now())
+        .await
+        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -223,6 +504,10 @@ pub async fn collab_share_watchlist(
     state: State<'_, CollabState>,
 ) -> Result<SharedWatchlist, String> {
     let uuid = Uuid::parse_str(&room_id).map_err(|e| e.to_string())?;
+    state
+        .rooms
+        .require_capability(&uuid, &user_id, Capability::ShareWatchlists)
+        .map_err(|e| e.to_string())?;
 
     let watchlist = SharedWatchlist {
         id: Uuid::new_v4(),
@@ -268,7 +553,12 @@ pub async fn collab_share_order(
     username: String,
     state: State<'_, CollabState>,
 ) -> Result<SharedOrder, String> {
-    let order = SharedOrder {
+    state
+        .rooms
+        .require_capability(&request.room_id, &user_id, Capability::ShareOrders)
+        .map_err(|e| e.to_string())?;
+
+    let mut order = SharedOrder {
         id: Uuid::new_v4(),
         room_id: request.room_id,
         user_id,
@@ -276,13 +566,23 @@ pub async fn collab_share_order(
         symbol: request.symbol,
         side: request.side,
         order_type: request.order_type,
-        quantity: request.quantity,
+        quantity: Some(request.quantity),
         price: request.price,
         status: OrderStatus::Pending,
         timestamp: Utc::now(),
         notes: request.notes,
+        linked_order_id: request.order_id.clone(),
+        visibility: request.visibility,
+        filled_quantity: None,
+        updated_at: None,
+        finalized: None,
     };
 
+    if let Some(order_id) = &request.order_id {
+        let live_order = require_state()?.manager.get_order(order_id).await?;
+        apply_live_order(&mut order, &live_order, Utc::now());
+    }
+
     state
         .rooms
         .add_order(order.clone())
@@ -293,7 +593,7 @@ pub async fn collab_share_order(
         .broadcast(
             request.room_id,
             CollabMessage::OrderShared {
-                order: order.clone(),
+                order: visible_to(&order, None),
             },
         )
         .map_err(|e| e.to_string())?;
@@ -301,13 +601,22 @@ pub async fn collab_share_order(
     Ok(order)
 }
 
+/// Returns current status and fills for linked orders, redacted per each share's
+/// visibility unless `user_id` is the sharer.
 #[tauri::command]
 pub async fn collab_get_orders(
     room_id: String,
+    user_id: Option<String>,
     state: State<'_, CollabState>,
 ) -> Result<Vec<SharedOrder>, String> {
     let uuid = Uuid::parse_str(&room_id).map_err(|e| e.to_string())?;
-    Ok(state.rooms.get_orders(&uuid))
+    refresh_linked_orders(&state, &uuid).await;
+    Ok(state
+        .rooms
+        .get_orders(&uuid)
+        .iter()
+        .map(|order| visible_to(order, user_id.as_deref()))
+        .collect())
 }
 
 #[tauri::command]
@@ -315,6 +624,7 @@ pub async fn collab_update_order(
     order_id: String,
     room_id: String,
     status: OrderStatus,
+    user_id: String,
     state: State<'_, CollabState>,
 ) -> Result<(), String> {
     let order_uuid = Uuid::parse_str(&order_id).map_err(|e| e.to_string())?;
@@ -322,6 +632,15 @@ pub async fn collab_update_order(
 
     let mut orders = state.rooms.get_orders(&room_uuid);
     if let Some(order) = orders.iter_mut().find(|o| o.id == order_uuid) {
+        if order.user_id != user_id {
+            state
+                .rooms
+                .require_capability(&room_uuid, &user_id, Capability::Moderate)
+                .map_err(|_| "Only the sharer or a moderator can update this order".to_string())?;
+        }
+        if order.linked_order_id.is_some() && order.finalized.is_none() {
+            return Err("Status of a linked order follows the live order".to_string());
+        }
         order.status = status;
         state
             .rooms
@@ -333,7 +652,7 @@ pub async fn collab_update_order(
             .broadcast(
                 room_uuid,
                 CollabMessage::OrderUpdated {
-                    order: order.clone(),
+                    order: visible_to(order, None),
                 },
             )
             .map_err(|e| e.to_string())?;
@@ -344,6 +663,53 @@ pub async fn collab_update_order(
     }
 }
 
+/// Stops tracking the live order; the room keeps the record in its last state.
+#[tauri::command]
+pub async fn collab_unshare_order(
+    order_id: String,
+    room_id: String,
+    user_id: String,
+    state: State<'_, CollabState>,
+) -> Result<SharedOrder, String> {
+    let order_uuid = Uuid::parse_str(&order_id).map_err(|e| e.to_string())?;
+    let room_uuid = Uuid::parse_str(&room_id).map_err(|e| e.to_string())?;
+
+    let mut order = state
+        .rooms
+        .get_orders(&room_uuid)
+        .into_iter()
+        .find(|o| o.id == order_uuid)
+        .ok_or_else(|| "Order not found".to_string())?;
+
+    if order.user_id != user_id {
+        let participant = state
+            .rooms
+            .get_participant(&room_uuid, &user_id)
+            .map_err(|e| e.to_string())?;
+        if !participant.permissions.can_moderate {
+            return Err("Only the sharer or a moderator can unshare this order".to_string());
+        }
+    }
+
+    finalize(&mut order, SharedOrderFinalReason::Unshared, Utc::now());
+    state
+        .rooms
+        .update_order(order.clone())
+        .map_err(|e| e.to_string())?;
+
+    state
+        .websocket
+        .broadcast(
+            room_uuid,
+            CollabMessage::OrderUpdated {
+                order: visible_to(&order, None),
+            },
+        )
+        .map_err(|e| e.to_string())?;
+
+    Ok(order)
+}
+
 #[tauri::command]
 pub async fn collab_share_strategy(
     request: ShareStrategyRequest,
@@ -351,6 +717,11 @@ pub async fn collab_share_strategy(
     username: String,
     state: State<'_, CollabState>,
 ) -> Result<Strategy, String> {
+    state
+        .rooms
+        .require_capability(&request.room_id, &user_id, Capability::ShareStrategies)
+        .map_err(|e| e.to_string())?;
+
     let strategy = Strategy {
         id: Uuid::new_v4(),
         room_id: request.room_id,
@@ -385,6 +756,10 @@ pub async fn collab_send_webrtc_signal(
     state: State<'_, CollabState>,
 ) -> Result<(), String> {
     let uuid = Uuid::parse_str(&room_id).map_err(|e| e.to_string())?;
+    state
+        .rooms
+        .get_participant(&uuid, &signal.from_user_id)
+        .map_err(|e| e.to_string())?;
 
     state.rtc.enqueue_signal(uuid, signal.clone());
 
@@ -472,8 +847,19 @@ pub async fn collab_get_room_state(
 #[tauri::command]
 pub async fn collab_set_competition(
     competition: Competition,
+    user_id: String,
     state: State<'_, CollabState>,
 ) -> Result<(), String> {
+    state
+        .rooms
+        .require_capability(
+            &competition.room_id,
+            &user_id,
+            Capability::StartCompetitions,
+        )
+        .map_err(|e| e.to_string())?;
+    let existing = state.rooms.get_competition(&competition.room_id);
+    let competition = prepare_competition(competition, existing.as_ref())?;
     state
         .rooms
         .set_competition(competition.clone())
@@ -499,13 +885,98 @@ pub async fn collab_get_competition(
     Ok(state.rooms.get_competition(&uuid))
 }
 
+#[tauri::command]
+pub async fn collab_join_competition(
+    request: JoinCompetitionRequest,
+    state: State<'_, CollabState>,
+    performance_db: State<'_, SharedPerformanceDatabase>,
+) -> Result<Competition, String> {
+    let uuid = Uuid::parse_str(&request.room_id).map_err(|e| e.to_string())?;
+    let participant = state
+        .rooms
+        .get_participant(&uuid, &request.user_id)
+        .map_err(|e| e.to_string())?;
+    let now = Utc::now();
+
+    let late = state
+        .rooms
+        .update_competition(&uuid, |competition| {
+            enroll(competition, &participant, request.source.clone(), now).map_err(|e| anyhow!(e))
+        })
+        .map_err(|e| e.to_string())?;
+
+    // Late joiners are scored from now on, so their baseline is this reading.
+    if late {
+        let competition = state
+            .rooms
+            .get_competition(&uuid)
+            .ok_or_else(|| "Competition not found".to_string())?;
+        match measure(
+            &request.source,
+            &competition.rules,
+            now,
+            now,
+            Some(performance_db.inner()),
+        )
+        .await
+        {
+            Ok(reading) => {
+                let _ = state.rooms.update_competition(&uuid, |competition| {
+                    if let Some(entrant) = competition
+                        .entrants
+                        .iter_mut()
+                        .find(|entrant| entrant.user_id == request.user_id)
+                    {
+                        if entrant.baseline.is_none() {
+                            record_reading(entrant, reading);
+                        }
+                    }
+                    Ok(())
+                });
+            }
+            Err(err) => eprintln!(
+                "Failed to measure baseline for {} in room {}: {}",
+                request.user_id, uuid, err
+            ),
+        }
+    }
+
+    let competition = state
+        .rooms
+        .get_competition(&uuid)
+        .ok_or_else(|| "Competition not found".to_string())?;
+    state
+        .websocket
+        .broadcast(
+            uuid,
+            CollabMessage::CompetitionUpdated {
+                competition: competition.clone(),
+            },
+        )
+        .map_err(|e| e.to_string())?;
+
+    Ok(competition)
+}
+
 #[tauri::command]
 pub async fn collab_update_leaderboard(
     room_id: String,
     leaderboard: Vec<LeaderboardEntry>,
+    user_id: String,
     state: State<'_, CollabState>,
 ) -> Result<(), String> {
     let uuid = Uuid::parse_str(&room_id).map_err(|e| e.to_string())?;
+    state
+        .rooms
+        .require_capability(&uuid, &user_id, Capability::StartCompetitions)
+        .map_err(|e| e.to_string())?;
+    if state
+        .rooms
+        .get_competition(&uuid)
+        .is_some_and(|competition| is_auto_scored(&competition))
+    {
+        return Err("Leaderboard is scored automatically for this competition".to_string());
+    }
 
     state
         .websocket
@@ -0,0 +1,522 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use tauri::{AppHandle, Manager};
+use tokio::time::{interval, Duration};
+
+use crate::collab::state::CollabState;
+use crate::collab::types::{
+    CollabMessage, Competition, CompetitionDataScope, CompetitionDataSource, CompetitionEntrant,
+    CompetitionRules, CompetitionSnapshot, CompetitionStatus, LeaderboardEntry, Participant,
+    ScoringMethod,
+};
+use crate::trading::paper_trading;
+use crate::wallet::performance::SharedPerformanceDatabase;
+
+const SCORING_INTERVAL: Duration = Duration::from_secs(60);
+/// A day of one-minute samples; older samples are dropped first.
+const MAX_SAMPLES: usize = 1440;
+
+/// One measurement of an entrant's account over their scoring window.
+#[derive(Debug, Clone, Copy)]
+pub struct PerformanceReading {
+    pub value: f64,
+    pub trades: usize,
+    pub win_rate: f64,
+}
+
+pub fn is_auto_scored(competition: &Competition) -> bool {
+    competition.rules.data_scope.is_some()
+}
+
+fn is_closed(competition: &Competition) -> bool {
+    matches!(
+        competition.status,
+        CompetitionStatus::Completed | CompetitionStatus::Cancelled
+    ) || competition.final_snapshot.is_some()
+}
+
+/// Validates an incoming competition definition and merges it with the one
+/// already running in the room. Entrants, standings and the final snapshot are
+/// owned by the scorer, so client-supplied values for them are ignored.
+pub fn prepare_competition(
+    mut incoming: Competition,
+    existing: Option<&Competition>,
+) -> Result<Competition, String> {
+    if incoming.end_time <= incoming.start_time {
+        return Err("Competition must end after it starts".to_string());
+    }
+    if !is_auto_scored(&incoming) {
+        return Ok(incoming);
+    }
+    if !matches!(
+        incoming.rules.scoring_method,
+        ScoringMethod::TotalReturn | ScoringMethod::RiskAdjustedReturn | ScoringMethod::TotalPnl
+    ) {
+        return Err(
+            "Automated scoring supports TotalReturn, RiskAdjustedReturn and TotalPnl".to_string(),
+        );
+    }
+    if incoming.rules.starting_capital <= 0.0 {
+        return Err("Starting capital must be positive".to_string());
+    }
+
+    match existing.filter(|current| current.id == incoming.id) {
+        Some(current) => {
+            if is_closed(current) {
+                return Err("Competition results are frozen".to_string());
+            }
+            if current.rules.data_scope != incoming.rules.data_scope && !current.entrants.is_empty()
+            {
+                return Err("Data scope cannot change once entrants have joined".to_string());
+            }
+            incoming.entrants = current.entrants.clone();
+            incoming.leaderboard = current.leaderboard.clone();
+            // Only cancellation is taken from the client; the scorer drives
+            // every other status change.
+            if !matches!(incoming.status, CompetitionStatus::Cancelled) {
+                incoming.status = current.status;
+            }
+        }
+        None => {
+            incoming.entrants.clear();
+            incoming.leaderboard.clear();
+            incoming.status = CompetitionStatus::Pending;
+        }
+    }
+    incoming.final_snapshot = None;
+    Ok(incoming)
+}
+
+/// Adds a participant who has consented to `source` being read. Returns `true`
+/// when the competition is already running, in which case the caller measures
+/// the entrant's baseline now and only activity from this point is scored.
+pub fn enroll(
+    competition: &mut Competition,
+    participant: &Participant,
+    source: CompetitionDataSource,
+    now: DateTime<Utc>,
+) -> Result<bool, String> {
+    let Some(scope) = competition.rules.data_scope else {
+        return Err("Competition is not automatically scored".to_string());
+    };
+    if is_closed(competition) || now >= competition.end_time {
+        return Err("Competition has ended".to_string());
+    }
+    if source.scope() != scope {
+        return Err(match scope {
+            CompetitionDataScope::PaperAccount => {
+                "This competition is scored from paper trading accounts".to_string()
+            }
+            CompetitionDataScope::Wallet => {
+                "This competition is scored from a designated wallet".to_string()
+            }
+        });
+    }
+    if let CompetitionDataSource::Wallet { address } = &source {
+        if address.trim().is_empty() {
+            return Err("A wallet address is required".to_string());
+        }
+    }
+    if competition
+        .entrants
+        .iter()
+        .any(|entrant| entrant.user_id == participant.user_id)
+    {
+        return Err("Already entered in this competition".to_string());
+    }
+
+    let late = now > competition.start_time;
+    // Wallet scores are built from realized P&L, so every entrant starts from
+    // the nominal capital; paper accounts are measured on their first reading.
+    let baseline = match source {
+        CompetitionDataSource::Wallet { .. } => Some(competition.rules.starting_capital),
+        CompetitionDataSource::PaperAccount => None,
+    };
+    competition.entrants.push(CompetitionEntrant {
+        user_id: participant.user_id.clone(),
+        username: participant.username.clone(),
+        source,
+        joined_at: now,
+        measured_from: if late { now } else { competition.start_time },
+        baseline,
+        latest_value: baseline,
+        trades: 0,
+        win_rate: 0.0,
+        samples: baseline.into_iter().collect(),
+    });
+    Ok(late)
+}
+
+pub fn record_reading(entrant: &mut CompetitionEntrant, reading: PerformanceReading) {
+    if entrant.baseline.is_none() {
+        entrant.baseline = Some(reading.value);
+    }
+    entrant.latest_value = Some(reading.value);
+    entrant.trades = reading.trades;
+    entrant.win_rate = reading.win_rate;
+    entrant.samples.push(reading.value);
+    if entrant.samples.len() > MAX_SAMPLES {
+        let excess = entrant.samples.len() - MAX_SAMPLES;
+        entrant.samples.drain(..excess);
+    }
+}
+
+/// Sharpe ratio of the per-pass returns, without a risk-free rate.
+fn sharpe_ratio(samples: &[f64]) -> f64 {
+    let returns: Vec<f64> = samples
+        .windows(2)
+        .filter(|pair| pair[0] > 0.0)
+        .map(|pair| (pair[1] - pair[0]) / pair[0])
+        .collect();
+    if returns.len() < 2 {
+        return 0.0;
+    }
+    let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+    let variance =
+        returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (returns.len() - 1) as f64;
+    let std_dev = variance.sqrt();
+    if std_dev == 0.0 {
+        0.0
+    } else {
+        mean / std_dev
+    }
+}
+
+pub fn compute_standings(
+    method: ScoringMethod,
+    entrants: &[CompetitionEntrant],
+) -> Vec<LeaderboardEntry> {
+    let mut entries: Vec<LeaderboardEntry> = entrants
+        .iter()
+        .filter_map(|entrant| {
+            let baseline = entrant.baseline?;
+            let latest = entrant.latest_value?;
+            let total_return = if baseline > 0.0 {
+                (latest - baseline) / baseline * 100.0
+            } else {
+                0.0
+            };
+            let score = match method {
+                ScoringMethod::TotalPnl => latest - baseline,
+                ScoringMethod::RiskAdjustedReturn => sharpe_ratio(&entrant.samples),
+                _ => total_return,
+            };
+            Some(LeaderboardEntry {
+                rank: 0,
+                user_id: entrant.user_id.clone(),
+                username: entrant.username.clone(),
+                score,
+                trades: entrant.trades,
+                win_rate: entrant.win_rate,
+                total_return,
+            })
+        })
+        .collect();
+
+    entries.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.user_id.cmp(&b.user_id))
+    });
+    for (index, entry) in entries.iter_mut().enumerate() {
+        entry.rank = index + 1;
+    }
+    entries
+}
+
+/// Applies one scoring pass and freezes the standings once the competition
+/// has reached its end time. Returns `true` when this pass closed it.
+pub fn apply_scoring_pass(
+    competition: &mut Competition,
+    readings: &HashMap<String, PerformanceReading>,
+    now: DateTime<Utc>,
+) -> bool {
+    for entrant in competition.entrants.iter_mut() {
+        if let Some(reading) = readings.get(&entrant.user_id) {
+            record_reading(entrant, *reading);
+        }
+    }
+    competition.leaderboard =
+        compute_standings(competition.rules.scoring_method, &competition.entrants);
+
+    if now >= competition.end_time {
+        competition.status = CompetitionStatus::Completed;
+        competition.final_snapshot = Some(CompetitionSnapshot {
+            leaderboard: competition.leaderboard.clone(),
+            frozen_at: competition.end_time,
+        });
+        true
+    } else {
+        competition.status = CompetitionStatus::Active;
+        false
+    }
+}
+
+/// Reads an entrant's account over `[from, to]`. Paper entrants are measured
+/// from this app's primary paper trading account at its current marks.
+pub async fn measure(
+    source: &CompetitionDataSource,
+    rules: &CompetitionRules,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    performance_db: Option<&SharedPerformanceDatabase>,
+) -> Result<PerformanceReading, String> {
+    match source {
+        CompetitionDataSource::PaperAccount => {
+            let manager = paper_trading::require_state()?;
+            let account = manager.get_account(None).await?;
+            let positions = manager.get_positions(None).await?;
+            let trades = manager
+                .get_trade_history(None)
+                .await?
+                .into_iter()
+                .filter(|trade| trade.timestamp >= from && trade.timestamp <= to)
+                .count();
+            let performance = manager.get_performance(None).await?;
+            let holdings: f64 = positions
+                .iter()
+                .map(|position| position.quantity * position.current_price)
+                .sum();
+            Ok(PerformanceReading {
+                value: account.balance + holdings,
+                trades,
+                win_rate: performance.win_rate,
+            })
+        }
+        CompetitionDataSource::Wallet { address } => {
+            let db =
+                performance_db.ok_or_else(|| "Performance database unavailable".to_string())?;
+            let trades = db
+                .read()
+                .await
+                .get_trades_between(address, from, to)
+                .await
+                .map_err(|e| e.to_string())?;
+            let closed: Vec<f64> = trades.iter().filter_map(|trade| trade.pnl).collect();
+            let wins = closed.iter().filter(|pnl| **pnl > 0.0).count();
+            Ok(PerformanceReading {
+                value: rules.starting_capital + closed.iter().sum::<f64>(),
+                trades: trades.len(),
+                win_rate: if closed.is_empty() {
+                    0.0
+                } else {
+                    wins as f64 / closed.len() as f64 * 100.0
+                },
+            })
+        }
+    }
+}
+
+async fn score_competitions(app: &AppHandle, state: &CollabState) {
+    let performance_db = app
+        .try_state::<SharedPerformanceDatabase>()
+        .map(|db| db.inner().clone());
+    let now = Utc::now();
+
+    for competition in state.rooms.list_competitions() {
+        if !is_auto_scored(&competition) || is_closed(&competition) {
+            continue;
+        }
+        if now < competition.start_time {
+            continue;
+        }
+
+        let until = now.min(competition.end_time);
+        let mut readings = HashMap::new();
+        for entrant in &competition.entrants {
+            match measure(
+                &entrant.source,
+                &competition.rules,
+                entrant.measured_from,
+                until,
+                performance_db.as_ref(),
+            )
+            .await
+            {
+                Ok(reading) => {
+                    readings.insert(entrant.user_id.clone(), reading);
+                }
+                Err(err) => eprintln!(
+                    "Failed to score {} in competition {}: {}",
+                    entrant.user_id, competition.id, err
+                ),
+            }
+        }
+
+        let updated = state
+            .rooms
+            .update_competition(&competition.room_id, |current| {
+                if current.id != competition.id || is_closed(current) {
+                    return Ok(None);
+                }
+                let closed = apply_scoring_pass(current, &readings, now);
+                Ok(Some((current.clone(), closed)))
+            });
+        let Ok(Some((updated, closed))) = updated else {
+            continue;
+        };
+
+        let _ = state.websocket.broadcast(
+            updated.room_id,
+            CollabMessage::LeaderboardUpdated {
+                room_id: updated.room_id,
+                leaderboard: updated.leaderboard.clone(),
+            },
+        );
+        if closed {
+            let _ = state.websocket.broadcast(
+                updated.room_id,
+                CollabMessage::CompetitionUpdated {
+                    competition: updated,
+                },
+            );
+        }
+    }
+}
+
+pub async fn run_competition_scoring(app: AppHandle, state: CollabState) {
+    let mut tick = interval(SCORING_INTERVAL);
+    loop {
+        tick.tick().await;
+        score_competitions(&app, &state).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collab::permissions::default_permissions_for_role;
+    use crate::collab::types::{ParticipantRole, ParticipantStatus};
+    use chrono::Duration as ChronoDuration;
+    use uuid::Uuid;
+
+    fn competition(scope: CompetitionDataScope, method: ScoringMethod) -> Competition {
+        let now = Utc::now();
+        Competition {
+            id: Uuid::new_v4(),
+            room_id: Uuid::new_v4(),
+            name: "Weekly".to_string(),
+            description: String::new(),
+            start_time: now - ChronoDuration::hours(1),
+            end_time: now + ChronoDuration::hours(1),
+            rules: CompetitionRules {
+                starting_capital: 1000.0,
+                allowed_assets: None,
+                max_position_size: None,
+                scoring_method: method,
+                data_scope: Some(scope),
+            },
+            leaderboard: Vec::new(),
+            status: CompetitionStatus::Pending,
+            entrants: Vec::new(),
+            final_snapshot: None,
+        }
+    }
+
+    fn participant(user_id: &str, room_id: Uuid) -> Participant {
+        Participant {
+            id: Uuid::new_v4(),
+            user_id: user_id.to_string(),
+            username: user_id.to_string(),
+            room_id,
+            joined_at: Utc::now(),
+            last_active: Utc::now(),
+            role: ParticipantRole::Trader,
+            permissions: default_permissions_for_role(ParticipantRole::Trader),
+            permission_overrides: Default::default(),
+            status: ParticipantStatus::Active,
+            is_muted: false,
+            is_video_off: true,
+            is_screen_sharing: false,
+        }
+    }
+
+    fn reading(value: f64) -> PerformanceReading {
+        PerformanceReading {
+            value,
+            trades: 1,
+            win_rate: 100.0,
+        }
+    }
+
+    #[test]
+    fn test_enroll_requires_matching_consent_and_tracks_late_joiners() {
+        let mut comp = competition(CompetitionDataScope::Wallet, ScoringMethod::TotalPnl);
+        let alice = participant("alice", comp.room_id);
+
+        assert!(enroll(
+            &mut comp,
+            &alice,
+            CompetitionDataSource::PaperAccount,
+            Utc::now()
+        )
+        .is_err());
+
+        let now = Utc::now();
+        let late = enroll(
+            &mut comp,
+            &alice,
+            CompetitionDataSource::Wallet {
+                address: "wallet-a".to_string(),
+            },
+            now,
+        )
+        .unwrap();
+        assert!(late);
+        assert_eq!(comp.entrants[0].measured_from, now);
+        assert_eq!(comp.entrants[0].baseline, Some(1000.0));
+
+        let again = enroll(
+            &mut comp,
+            &alice,
+            CompetitionDataSource::Wallet {
+                address: "wallet-a".to_string(),
+            },
+            now,
+        );
+        assert!(again.is_err());
+    }
+
+    #[test]
+    fn test_scoring_ranks_by_return_and_freezes_at_end() {
+        let mut comp = competition(
+            CompetitionDataScope::PaperAccount,
+            ScoringMethod::TotalReturn,
+        );
+        let now = Utc::now();
+        for user in ["alice", "bob"] {
+            let p = participant(user, comp.room_id);
+            enroll(&mut comp, &p, CompetitionDataSource::PaperAccount, now).unwrap();
+        }
+
+        // Bob joined with a smaller account but grew it by a larger share.
+        let first: HashMap<_, _> = [
+            ("alice".to_string(), reading(10_000.0)),
+            ("bob".to_string(), reading(500.0)),
+        ]
+        .into_iter()
+        .collect();
+        apply_scoring_pass(&mut comp, &first, now);
+        let second: HashMap<_, _> = [
+            ("alice".to_string(), reading(10_500.0)),
+            ("bob".to_string(), reading(600.0)),
+        ]
+        .into_iter()
+        .collect();
+        assert!(!apply_scoring_pass(&mut comp, &second, now));
+        assert_eq!(comp.leaderboard[0].user_id, "bob");
+        assert!((comp.leaderboard[0].total_return - 20.0).abs() < 1e-9);
+        assert!(comp.final_snapshot.is_none());
+
+        let end = comp.end_time;
+        assert!(apply_scoring_pass(&mut comp, &HashMap::new(), end));
+        assert!(matches!(comp.status, CompetitionStatus::Completed));
+        let snapshot = comp.final_snapshot.clone().unwrap();
+        assert_eq!(snapshot.frozen_at, end);
+        assert_eq!(snapshot.leaderboard.len(), 2);
+
+        assert!(prepare_competition(comp.clone(), Some(&comp)).is_err());
+    }
+}
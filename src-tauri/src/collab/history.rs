@@ -0,0 +1,731 @@
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::{SqlitePool, SqliteRow};
+use sqlx::Row;
+use uuid::Uuid;
+
+use crate::collab::types::ChatMessage;
+use crate::utils::ensure_column;
+
+pub const DEFAULT_HISTORY_PAGE_SIZE: usize = 50;
+const MAX_HISTORY_PAGE_SIZE: usize = 200;
+
+/// One page of a room's history, oldest first. The `has_more_*` flags tell the
+/// client whether another page exists on either side of this one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessagePage {
+    pub messages: Vec<ChatMessage>,
+    pub has_more_before: bool,
+    pub has_more_after: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageRetention {
+    pub room_id: Uuid,
+    pub retention_days: Option<u32>,
+    pub updated_by: Option<String>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// Persistent chat history for collaborative rooms. Edits and deletions update
+/// rows in place, and a deleted message stays behind as a tombstone, so every
+/// participant pages through the same sequence.
+pub struct MessageHistoryStore {
+    pool: SqlitePool,
+}
+
+impl MessageHistoryStore {
+    pub async fn new(db_path: PathBuf) -> Result<Self> {
+        let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+        let pool = SqlitePool::connect(&db_url)
+            .await
+            .with_context(|| format!("Failed to open collab history at {:?}", db_path))?;
+        Self::with_pool(pool).await
+    }
+
+    pub async fn with_pool(pool: SqlitePool) -> Result<Self> {
+        let store = Self { pool };
+        store.initialize().await?;
+        Ok(store)
+    }
+
+    async fn initialize(&self) -> Result<()> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS collab_messages (
+                seq INTEGER PRIMARY KEY AUTOINCREMENT,
+                id TEXT NOT NULL UNIQUE,
+                room_id TEXT NOT NULL,
+                user_id TEXT NOT NULL,
+                username TEXT NOT NULL,
+                content TEXT NOT NULL,
+                timestamp INTEGER NOT NULL,
+                encrypted INTEGER NOT NULL,
+                mentions TEXT NOT NULL,
+                replied_to TEXT,
+                edited_at INTEGER,
+                deleted_at INTEGER,
+                deleted_by TEXT
+            );
+            CREATE INDEX IF NOT EXISTS idx_collab_messages_room_seq ON collab_messages(room_id, seq);
+            CREATE INDEX IF NOT EXISTS idx_collab_messages_room_ts ON collab_messages(room_id, timestamp);
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+            CREATE VIRTUAL TABLE IF NOT EXISTS collab_messages_fts USING fts5(
+                content,
+                message_id UNINDEXED,
+                room_id UNINDEXED
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS collab_room_retention (
+                room_id TEXT PRIMARY KEY,
+                retention_days INTEGER NOT NULL,
+                updated_by TEXT NOT NULL,
+                updated_at INTEGER NOT NULL
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        ensure_column(&self.pool, "collab_messages", "attachment", "TEXT").await?;
+
+        Ok(())
+    }
+
+    pub async fn append(&self, message: &ChatMessage) -> Result<()> {
+        let room_id = message.room_id.to_string();
+        let message_id = message.id.to_string();
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO collab_messages (
+                id, room_id, user_id, username, content, timestamp, encrypted,
+                mentions, replied_to, edited_at, deleted_at, deleted_by, attachment
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
+            "#,
+        )
+        .bind(&message_id)
+        .bind(&room_id)
+        .bind(&message.user_id)
+        .bind(&message.username)
+        .bind(&message.content)
+        .bind(message.timestamp.timestamp_millis())
+        .bind(message.encrypted)
+        .bind(serde_json::to_string(&message.mentions)?)
+        .bind(message.replied_to.map(|id| id.to_string()))
+        .bind(message.edited_at.map(|at| at.timestamp_millis()))
+        .bind(message.deleted_at.map(|at| at.timestamp_millis()))
+        .bind(&message.deleted_by)
+        .bind(
+            message
+                .attachment
+                .as_ref()
+                .map(serde_json::to_string)
+                .transpose()?,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        if message.deleted_at.is_none() {
+            sqlx::query(
+                "INSERT INTO collab_messages_fts (content, message_id, room_id) VALUES (?1, ?2, ?3)",
+            )
+            .bind(&message.content)
+            .bind(&message_id)
+            .bind(&room_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        self.prune_room(&message.room_id, Utc::now()).await?;
+        Ok(())
+    }
+
+    pub async fn get_message(&self, room_id: &Uuid, message_id: &Uuid) -> Result<ChatMessage> {
+        let row = sqlx::query("SELECT * FROM collab_messages WHERE room_id = ?1 AND id = ?2")
+            .bind(room_id.to_string())
+            .bind(message_id.to_string())
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| anyhow!("Message not found"))?;
+        message_from_row(&row)
+    }
+
+    /// Pages backwards from `before`, forwards from `after`, or returns the latest
+    /// messages when neither cursor is given.
+    pub async fn page(
+        &self,
+        room_id: &Uuid,
+        before: Option<Uuid>,
+        after: Option<Uuid>,
+        limit: Option<usize>,
+    ) -> Result<MessagePage> {
+        let limit = limit
+            .unwrap_or(DEFAULT_HISTORY_PAGE_SIZE)
+            .clamp(1, MAX_HISTORY_PAGE_SIZE);
+        let room = room_id.to_string();
+
+        let (rows, forward) = match (before, after) {
+            (Some(_), Some(_)) => return Err(anyhow!("Specify either before or after, not both")),
+            (None, Some(after)) => {
+                let seq = self.cursor_seq(&room, &after).await?;
+                let rows = sqlx::query(
+                    r#"
+                    SELECT * FROM collab_messages
+                    WHERE room_id = ?1 AND seq > ?2
+                    ORDER BY seq ASC
+                    LIMIT ?3
+                    "#,
+                )
+                .bind(&room)
+                .bind(seq)
+                .bind((limit + 1) as i64)
+                .fetch_all(&self.pool)
+                .await?;
+                (rows, true)
+            }
+            (before, None) => {
+                let seq = match before {
+                    Some(before) => self.cursor_seq(&room, &before).await?,
+                    None => i64::MAX,
+                };
+                let rows = sqlx::query(
+                    r#"
+                    SELECT * FROM collab_messages
+                    WHERE room_id = ?1 AND seq < ?2
+                    ORDER BY seq DESC
+                    LIMIT ?3
+                    "#,
+                )
+                .bind(&room)
+                .bind(seq)
+                .bind((limit + 1) as i64)
+                .fetch_all(&self.pool)
+                .await?;
+                (rows, false)
+            }
+        };
+
+        let has_more = rows.len() > limit;
+        let mut entries = rows
+            .iter()
+            .take(limit)
+            .map(|row| Ok((row.try_get::<i64, _>("seq")?, message_from_row(row)?)))
+            .collect::<Result<Vec<_>>>()?;
+        if !forward {
+            entries.reverse();
+        }
+
+        let (has_more_before, has_more_after) = match (entries.first(), entries.last()) {
+            (Some((first, _)), Some(_)) if forward => {
+                (self.has_rows_before(&room, *first).await?, has_more)
+            }
+            (Some(_), Some((last, _))) => (has_more, self.has_rows_after(&room, *last).await?),
+            _ => (false, false),
+        };
+
+        Ok(MessagePage {
+            messages: entries.into_iter().map(|(_, message)| message).collect(),
+            has_more_before,
+            has_more_after,
+        })
+    }
+
+    async fn cursor_seq(&self, room: &str, message_id: &Uuid) -> Result<i64> {
+        let row = sqlx::query("SELECT seq FROM collab_messages WHERE room_id = ?1 AND id = ?2")
+            .bind(room)
+            .bind(message_id.to_string())
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| anyhow!("Cursor message not found in this room"))?;
+        Ok(row.try_get("seq")?)
+    }
+
+    async fn has_rows_before(&self, room: &str, seq: i64) -> Result<bool> {
+        let row = sqlx::query(
+            "SELECT EXISTS(SELECT 1 FROM collab_messages WHERE room_id = ?1 AND seq < ?2) AS found",
+        )
+        .bind(room)
+        .bind(seq)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(row.try_get::<bool, _>("found")?)
+    }
+
+    async fn has_rows_after(&self, room: &str, seq: i64) -> Result<bool> {
+        let row = sqlx::query(
+            "SELECT EXISTS(SELECT 1 FROM collab_messages WHERE room_id = ?1 AND seq > ?2) AS found",
+        )
+        .bind(room)
+        .bind(seq)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(row.try_get::<bool, _>("found")?)
+    }
+
+    /// Full-text search within one room, newest matches first. Deleted messages
+    /// are dropped from the index and never match.
+    pub async fn search(
+        &self,
+        room_id: &Uuid,
+        query: &str,
+        limit: Option<usize>,
+    ) -> Result<Vec<ChatMessage>> {
+        let Some(match_expr) = fts_query(query) else {
+            return Ok(Vec::new());
+        };
+        let limit = limit
+            .unwrap_or(DEFAULT_HISTORY_PAGE_SIZE)
+            .clamp(1, MAX_HISTORY_PAGE_SIZE);
+
+        let rows = sqlx::query(
+            r#"
+            SELECT m.* FROM collab_messages_fts f
+            JOIN collab_messages m ON m.id = f.message_id
+            WHERE f.room_id = ?1 AND f.collab_messages_fts MATCH ?2
+            ORDER BY m.seq DESC
+            LIMIT ?3
+            "#,
+        )
+        .bind(room_id.to_string())
+        .bind(match_expr)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(message_from_row).collect()
+    }
+
+    pub async fn edit(
+        &self,
+        room_id: &Uuid,
+        message_id: &Uuid,
+        content: String,
+        edited_at: DateTime<Utc>,
+    ) -> Result<ChatMessage> {
+        let mut message = self.get_message(room_id, message_id).await?;
+        if message.deleted_at.is_some() {
+            return Err(anyhow!("Cannot edit a deleted message"));
+        }
+        message.content = content;
+        message.edited_at = Some(edited_at);
+
+        let id = message_id.to_string();
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("UPDATE collab_messages SET content = ?1, edited_at = ?2 WHERE id = ?3")
+            .bind(&message.content)
+            .bind(edited_at.timestamp_millis())
+            .bind(&id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("UPDATE collab_messages_fts SET content = ?1 WHERE message_id = ?2")
+            .bind(&message.content)
+            .bind(&id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        Ok(message)
+    }
+
+    /// Replaces the message with a tombstone: content is cleared but the row keeps
+    /// its place in the history.
+    pub async fn tombstone(
+        &self,
+        room_id: &Uuid,
+        message_id: &Uuid,
+        deleted_by: &str,
+        deleted_at: DateTime<Utc>,
+    ) -> Result<ChatMessage> {
+        let mut message = self.get_message(room_id, message_id).await?;
+        if message.deleted_at.is_some() {
+            return Ok(message);
+        }
+        message.content = String::new();
+        message.mentions.clear();
+        message.attachment = None;
+        message.deleted_at = Some(deleted_at);
+        message.deleted_by = Some(deleted_by.to_string());
+
+        let id = message_id.to_string();
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            r#"
+            UPDATE collab_messages
+            SET content = '', mentions = '[]', attachment = NULL, deleted_at = ?1, deleted_by = ?2
+            WHERE id = ?3
+            "#,
+        )
+        .bind(deleted_at.timestamp_millis())
+        .bind(deleted_by)
+        .bind(&id)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query("DELETE FROM collab_messages_fts WHERE message_id = ?1")
+            .bind(&id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        Ok(message)
+    }
+
+    pub async fn retention(&self, room_id: &Uuid) -> Result<MessageRetention> {
+        let row = sqlx::query("SELECT * FROM collab_room_retention WHERE room_id = ?1")
+            .bind(room_id.to_string())
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(match row {
+            Some(row) => MessageRetention {
+                room_id: *room_id,
+                retention_days: Some(row.try_get::<i64, _>("retention_days")? as u32),
+                updated_by: Some(row.try_get("updated_by")?),
+                updated_at: Some(millis_to_datetime(row.try_get("updated_at")?)),
+            },
+            None => MessageRetention {
+                room_id: *room_id,
+                retention_days: None,
+                updated_by: None,
+                updated_at: None,
+            },
+        })
+    }
+
+    /// `None` keeps history forever. Messages already past the new window are
+    /// pruned immediately.
+    pub async fn set_retention(
+        &self,
+        room_id: &Uuid,
+        retention_days: Option<u32>,
+        updated_by: &str,
+        now: DateTime<Utc>,
+    ) -> Result<MessageRetention> {
+        match retention_days {
+            Some(days) => {
+                if days == 0 {
+                    return Err(anyhow!("Retention must be at least one day"));
+                }
+                sqlx::query(
+                    r#"
+                    INSERT INTO collab_room_retention (room_id, retention_days, updated_by, updated_at)
+                    VALUES (?1, ?2, ?3, ?4)
+                    ON CONFLICT(room_id) DO UPDATE SET
+                        retention_days = excluded.retention_days,
+                        updated_by = excluded.updated_by,
+                        updated_at = excluded.updated_at
+                    "#,
+                )
+                .bind(room_id.to_string())
+                .bind(days as i64)
+                .bind(updated_by)
+                .bind(now.timestamp_millis())
+                .execute(&self.pool)
+                .await?;
+            }
+            None => {
+                sqlx::query("DELETE FROM collab_room_retention WHERE room_id = ?1")
+                    .bind(room_id.to_string())
+                    .execute(&self.pool)
+                    .await?;
+            }
+        }
+
+        self.prune_room(room_id, now).await?;
+        self.retention(room_id).await
+    }
+
+    async fn prune_room(&self, room_id: &Uuid, now: DateTime<Utc>) -> Result<u64> {
+        let Some(days) = self.retention(room_id).await?.retention_days else {
+            return Ok(0);
+        };
+        let cutoff = (now - Duration::days(days as i64)).timestamp_millis();
+        let room = room_id.to_string();
+
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            r#"
+            DELETE FROM collab_messages_fts WHERE message_id IN (
+                SELECT id FROM collab_messages WHERE room_id = ?1 AND timestamp < ?2
+            )
+            "#,
+        )
+        .bind(&room)
+        .bind(cutoff)
+        .execute(&mut *tx)
+        .await?;
+        let removed =
+            sqlx::query("DELETE FROM collab_messages WHERE room_id = ?1 AND timestamp < ?2")
+                .bind(&room)
+                .bind(cutoff)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+        tx.commit().await?;
+
+        Ok(removed)
+    }
+
+    pub async fn purge_room(&self, room_id: &Uuid) -> Result<()> {
+        let room = room_id.to_string();
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM collab_messages_fts WHERE room_id = ?1")
+            .bind(&room)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM collab_messages WHERE room_id = ?1")
+            .bind(&room)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM collab_room_retention WHERE room_id = ?1")
+            .bind(&room)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+/// Turns free text into an FTS5 expression matching all terms, quoting each one
+/// so user input can't inject query syntax.
+fn fts_query(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split_whitespace()
+        .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+        .collect();
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" "))
+    }
+}
+
+fn millis_to_datetime(millis: i64) -> DateTime<Utc> {
+    Utc.timestamp_millis_opt(millis)
+        .single()
+        .unwrap_or_else(Utc::now)
+}
+
+fn parse_uuid(value: &str) -> Result<Uuid> {
+    Uuid::parse_str(value).with_context(|| format!("Invalid id in collab history: {}", value))
+}
+
+fn message_from_row(row: &SqliteRow) -> Result<ChatMessage> {
+    let mentions: String = row.try_get("mentions")?;
+    let replied_to: Option<String> = row.try_get("replied_to")?;
+    let edited_at: Option<i64> = row.try_get("edited_at")?;
+    let deleted_at: Option<i64> = row.try_get("deleted_at")?;
+    let attachment: Option<String> = row.try_get("attachment")?;
+
+    Ok(ChatMessage {
+        id: parse_uuid(&row.try_get::<String, _>("id")?)?,
+        room_id: parse_uuid(&row.try_get::<String, _>("room_id")?)?,
+        user_id: row.try_get("user_id")?,
+        username: row.try_get("username")?,
+        content: row.try_get("content")?,
+        timestamp: millis_to_datetime(row.try_get("timestamp")?),
+        encrypted: row.try_get("encrypted")?,
+        mentions: serde_json::from_str(&mentions).unwrap_or_default(),
+        replied_to: replied_to.as_deref().map(parse_uuid).transpose()?,
+        edited_at: edited_at.map(millis_to_datetime),
+        deleted_at: deleted_at.map(millis_to_datetime),
+        deleted_by: row.try_get("deleted_by")?,
+        attachment: attachment
+            .as_deref()
+            .map(serde_json::from_str)
+            .transpose()
+            .unwrap_or_default(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collab::types::MessageAttachment;
+    use crate::utils::test_pool;
+
+    async fn store() -> MessageHistoryStore {
+        let pool = test_pool().await;
+        MessageHistoryStore::with_pool(pool).await.unwrap()
+    }
+
+    fn message(room_id: Uuid, content: &str, minutes_ago: i64) -> ChatMessage {
+        ChatMessage {
+            id: Uuid::new_v4(),
+            room_id,
+            user_id: "alice".to_string(),
+            username: "Alice".to_string(),
+            content: content.to_string(),
+            timestamp: Utc::now() - Duration::minutes(minutes_ago),
+            encrypted: false,
+            mentions: Vec::new(),
+            replied_to: None,
+            edited_at: None,
+            deleted_at: None,
+            deleted_by: None,
+            attachment: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cursor_pagination() {
+        let store = store().await;
+        let room = Uuid::new_v4();
+        let mut ids = Vec::new();
+        for i in 0..5 {
+            let msg = message(room, &format!("message {}", i), 10 - i);
+            ids.push(msg.id);
+            store.append(&msg).await.unwrap();
+        }
+        store
+            .append(&message(Uuid::new_v4(), "other room", 1))
+            .await
+            .unwrap();
+
+        let latest = store.page(&room, None, None, Some(2)).await.unwrap();
+        let latest_ids: Vec<Uuid> = latest.messages.iter().map(|m| m.id).collect();
+        assert_eq!(latest_ids, ids[3..5]);
+        assert!(latest.has_more_before);
+        assert!(!latest.has_more_after);
+
+        let older = store
+            .page(&room, Some(ids[3]), None, Some(10))
+            .await
+            .unwrap();
+        assert_eq!(older.messages.len(), 3);
+        assert!(!older.has_more_before);
+        assert!(older.has_more_after);
+
+        let newer = store
+            .page(&room, None, Some(ids[1]), Some(2))
+            .await
+            .unwrap();
+        let newer_ids: Vec<Uuid> = newer.messages.iter().map(|m| m.id).collect();
+        assert_eq!(newer_ids, ids[2..4]);
+        assert!(newer.has_more_before);
+        assert!(newer.has_more_after);
+    }
+
+    #[tokio::test]
+    async fn test_attachments_round_trip() {
+        let store = store().await;
+        let room = Uuid::new_v4();
+        let mut shared = message(room, "my trend setup", 1);
+        let attachment = MessageAttachment::IndicatorPreset {
+            code: "emp1.000000000000.AA".to_string(),
+            name: "Trend".to_string(),
+            indicator_count: 2,
+            custom_indicator_count: 1,
+            drawing_template_count: 0,
+        };
+        shared.attachment = Some(attachment.clone());
+        store.append(&shared).await.unwrap();
+        store.append(&message(room, "nice", 0)).await.unwrap();
+
+        let page = store.page(&room, None, None, None).await.unwrap();
+        assert_eq!(page.messages[0].attachment, Some(attachment));
+        assert!(page.messages[1].attachment.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_tombstones_and_search() {
+        let store = store().await;
+        let room = Uuid::new_v4();
+        let keep = message(room, "SOL breakout above resistance", 3);
+        let mut removed = message(room, "SOL looks weak", 2);
+        removed.attachment = Some(MessageAttachment::IndicatorPreset {
+            code: "emp1.000000000000.AA".to_string(),
+            name: "Trend".to_string(),
+            indicator_count: 1,
+            custom_indicator_count: 0,
+            drawing_template_count: 0,
+        });
+        store.append(&keep).await.unwrap();
+        store.append(&removed).await.unwrap();
+
+        let edited = store
+            .edit(
+                &room,
+                &keep.id,
+                "SOL breakout confirmed".to_string(),
+                Utc::now(),
+            )
+            .await
+            .unwrap();
+        assert!(edited.edited_at.is_some());
+        store
+            .tombstone(&room, &removed.id, "moderator", Utc::now())
+            .await
+            .unwrap();
+
+        let results = store.search(&room, "sol", None).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].content, "SOL breakout confirmed");
+        assert!(store
+            .search(&room, "resistance", None)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(store
+            .search(&room, "\"unbalanced", None)
+            .await
+            .unwrap()
+            .is_empty());
+
+        let page = store.page(&room, None, None, None).await.unwrap();
+        assert_eq!(page.messages.len(), 2);
+        let tombstone = &page.messages[1];
+        assert!(tombstone.content.is_empty());
+        assert!(tombstone.attachment.is_none());
+        assert_eq!(tombstone.deleted_by.as_deref(), Some("moderator"));
+        assert!(store
+            .edit(&room, &removed.id, "revived".to_string(), Utc::now())
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_retention_prunes_old_messages() {
+        let store = store().await;
+        let room = Uuid::new_v4();
+        let old = message(room, "ancient", 3 * 24 * 60);
+        store.append(&old).await.unwrap();
+        store.append(&message(room, "fresh", 1)).await.unwrap();
+
+        let retention = store
+            .set_retention(&room, Some(1), "owner", Utc::now())
+            .await
+            .unwrap();
+        assert_eq!(retention.retention_days, Some(1));
+
+        let page = store.page(&room, None, None, None).await.unwrap();
+        assert_eq!(page.messages.len(), 1);
+        assert_eq!(page.messages[0].content, "fresh");
+        assert!(store
+            .search(&room, "ancient", None)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(store
+            .set_retention(&room, Some(0), "owner", Utc::now())
+            .await
+            .is_err());
+    }
+}
@@ -1,6 +1,9 @@
 pub mod commands;
+pub mod competition;
 pub mod crypto;
+pub mod history;
 pub mod moderation;
+pub mod orders;
 pub mod permissions;
 pub mod room;
 pub mod rtc;
@@ -6,14 +6,16 @@ use chrono::{DateTime, Utc};
 use parking_lot::RwLock;
 use uuid::Uuid;
 
+use crate::collab::permissions::outranks;
 use crate::collab::types::{
-    ModerationAction, ModerationActionType, ParticipantPermissions, ParticipantRole,
+    ModerationAction, ModerationActionType, ParticipantPermissions, ParticipantRole, RoleAuditEntry,
 };
 
 #[derive(Default)]
 pub struct ModerationManager {
     actions: RwLock<HashMap<Uuid, Vec<ModerationAction>>>,
     banned_users: RwLock<HashSet<String>>,
+    role_changes: RwLock<HashMap<Uuid, Vec<RoleAuditEntry>>>,
 }
 
 impl ModerationManager {
@@ -32,6 +34,28 @@ impl ModerationManager {
         map.entry(action.room_id).or_default().push(action);
     }
 
+    pub fn record_role_change(&self, entry: RoleAuditEntry) {
+        self.role_changes
+            .write()
+            .entry(entry.room_id)
+            .or_default()
+            .push(entry);
+    }
+
+    /// Role and permission changes in the room, oldest first.
+    pub fn role_history(&self, room_id: &Uuid) -> Vec<RoleAuditEntry> {
+        self.role_changes
+            .read()
+            .get(room_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub fn clear_room(&self, room_id: &Uuid) {
+        self.actions.write().remove(room_id);
+        self.role_changes.write().remove(room_id);
+    }
+
     pub fn is_banned(&self, room_id: &Uuid, user_id: &str) -> bool {
         if self.banned_users.read().contains(user_id) {
             return true;
@@ -112,8 +136,11 @@ pub fn ensure_moderation_permission(
             if !permissions.can_kick {
                 return Err(anyhow!("Moderator cannot remove participants"));
             }
-            if moderator_role == target_role {
-                return Err(anyhow!("Cannot moderate user with equal role"));
+            if action == ModerationActionType::Ban && !permissions.can_ban {
+                return Err(anyhow!("Moderator cannot ban participants"));
+            }
+            if !outranks(moderator_role, target_role) {
+                return Err(anyhow!("Cannot moderate user with equal or higher role"));
             }
         }
         ModerationActionType::Warning => {
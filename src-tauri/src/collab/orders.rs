@@ -0,0 +1,286 @@
+use chrono::{DateTime, Utc};
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::collab::state::CollabState;
+use crate::collab::types::{
+    CollabMessage, OrderSide, OrderStatus, OrderType, SharedOrder, SharedOrderFinalReason,
+    SharedOrderFinalization, SharedOrderVisibility,
+};
+use crate::trading::events::order_events;
+use crate::trading::limit_orders::require_state;
+use crate::trading::types as live;
+
+pub fn status_from_live(status: live::OrderStatus) -> OrderStatus {
+    match status {
+        live::OrderStatus::Pending => OrderStatus::Pending,
+        live::OrderStatus::PartiallyFilled => OrderStatus::PartiallyFilled,
+        live::OrderStatus::Filled => OrderStatus::Filled,
+        live::OrderStatus::Cancelled => OrderStatus::Cancelled,
+        live::OrderStatus::Expired => OrderStatus::Expired,
+        live::OrderStatus::Failed => OrderStatus::Rejected,
+    }
+}
+
+fn type_from_live(order_type: live::OrderType) -> OrderType {
+    match order_type {
+        live::OrderType::Market => OrderType::Market,
+        live::OrderType::Limit => OrderType::Limit,
+        live::OrderType::StopLoss | live::OrderType::TakeProfit | live::OrderType::TrailingStop => {
+            OrderType::Stop
+        }
+    }
+}
+
+fn side_from_live(side: live::OrderSide) -> OrderSide {
+    match side {
+        live::OrderSide::Buy => OrderSide::Buy,
+        live::OrderSide::Sell => OrderSide::Sell,
+    }
+}
+
+/// The token being traded: what a buy acquires or a sell disposes of.
+pub fn live_order_symbol(order: &live::Order) -> String {
+    match order.side {
+        live::OrderSide::Buy => order.output_symbol.clone(),
+        live::OrderSide::Sell => order.input_symbol.clone(),
+    }
+}
+
+fn is_terminal(status: OrderStatus) -> bool {
+    matches!(
+        status,
+        OrderStatus::Filled | OrderStatus::Cancelled | OrderStatus::Expired | OrderStatus::Rejected
+    )
+}
+
+/// Copies the live order's current state onto the room record and finalizes it
+/// once the order can no longer change.
+pub fn apply_live_order(shared: &mut SharedOrder, order: &live::Order, now: DateTime<Utc>) {
+    if shared.finalized.is_some() {
+        return;
+    }
+    shared.symbol = live_order_symbol(order);
+    shared.side = side_from_live(order.side);
+    shared.order_type = type_from_live(order.order_type);
+    shared.quantity = Some(order.amount);
+    shared.price = order.limit_price.or(order.stop_price);
+    shared.status = status_from_live(order.status);
+    shared.filled_quantity = Some(order.filled_amount);
+    shared.updated_at = Some(now);
+    if is_terminal(shared.status) {
+        finalize(shared, SharedOrderFinalReason::Completed, now);
+    }
+}
+
+pub fn finalize(shared: &mut SharedOrder, reason: SharedOrderFinalReason, now: DateTime<Utc>) {
+    if shared.finalized.is_none() {
+        shared.finalized = Some(SharedOrderFinalization { reason, at: now });
+        shared.updated_at = Some(now);
+    }
+}
+
+/// What a participant sees. The sharer always gets the full record; everyone
+/// else gets it with the fields their visibility setting hides removed.
+pub fn visible_to(shared: &SharedOrder, viewer_id: Option<&str>) -> SharedOrder {
+    let mut view = shared.clone();
+    if viewer_id == Some(shared.user_id.as_str()) {
+        return view;
+    }
+    match shared.visibility {
+        SharedOrderVisibility::Full => {}
+        SharedOrderVisibility::HideSize => {
+            view.quantity = None;
+            view.filled_quantity = None;
+        }
+        SharedOrderVisibility::DirectionAndStatus => {
+            view.quantity = None;
+            view.filled_quantity = None;
+            view.price = None;
+        }
+    }
+    view
+}
+
+/// Applies a live order change to every room that shares it and broadcasts the
+/// participant view of each updated record.
+pub fn sync_live_order(state: &CollabState, order: &live::Order) {
+    let now = Utc::now();
+    for mut shared in state.rooms.find_linked_orders(&order.id) {
+        if shared.finalized.is_some() {
+            continue;
+        }
+        apply_live_order(&mut shared, order, now);
+        if let Err(err) = state.rooms.update_order(shared.clone()) {
+            eprintln!("Failed to update shared order {}: {}", shared.id, err);
+            continue;
+        }
+        let _ = state.websocket.broadcast(
+            shared.room_id,
+            CollabMessage::OrderUpdated {
+                order: visible_to(&shared, None),
+            },
+        );
+    }
+}
+
+/// Re-reads linked orders that are still open, covering status changes made
+/// without an order event or whose events were dropped. An order that has
+/// aged out of the order history keeps its last known state and is finalized.
+pub async fn refresh_linked_orders(state: &CollabState, room_id: &uuid::Uuid) {
+    let Ok(trading) = require_state() else {
+        return;
+    };
+    let now = Utc::now();
+
+    for mut shared in state.rooms.get_orders(room_id) {
+        let Some(order_id) = shared.linked_order_id.clone() else {
+            continue;
+        };
+        if shared.finalized.is_some() {
+            continue;
+        }
+
+        let live_order = match trading.db.read().await.get_order(&order_id).await {
+            Ok(order) => order,
+            Err(err) => {
+                eprintln!("Failed to refresh shared order {}: {}", order_id, err);
+                continue;
+            }
+        };
+        match live_order {
+            Some(order) => apply_live_order(&mut shared, &order, now),
+            None => finalize(&mut shared, SharedOrderFinalReason::HistoryUnavailable, now),
+        }
+        let _ = state.rooms.update_order(shared);
+    }
+}
+
+pub fn attach_order_listener(state: CollabState) {
+    let mut events = order_events();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            match events.recv().await {
+                Ok(event) => {
+                    if let Some(order) = event.order {
+                        sync_live_order(&state, &order);
+                    }
+                }
+                // Shared orders catch up on the next refresh.
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn live_order(status: live::OrderStatus, filled: f64) -> live::Order {
+        live::Order {
+            id: "order-1".to_string(),
+            order_type: live::OrderType::Limit,
+            side: live::OrderSide::Buy,
+            status,
+            input_mint: "usdc".to_string(),
+            output_mint: "sol".to_string(),
+            input_symbol: "USDC".to_string(),
+            output_symbol: "SOL".to_string(),
+            amount: 100.0,
+            filled_amount: filled,
+            fill_price: None,
+            limit_price: Some(140.0),
+            stop_price: None,
+            trailing_percent: None,
+            highest_price: None,
+            lowest_price: None,
+            linked_order_id: None,
+            min_book_depth_usd: None,
+            slippage_bps: 50,
+            priority_fee_micro_lamports: 0,
+            wallet_address: "wallet".to_string(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            triggered_at: None,
+            tx_signature: None,
+            error_message: None,
+            correlation_id: None,
+            origin: None,
+        }
+    }
+
+    fn shared(visibility: SharedOrderVisibility) -> SharedOrder {
+        SharedOrder {
+            id: Uuid::new_v4(),
+            room_id: Uuid::new_v4(),
+            user_id: "sharer".to_string(),
+            username: "Sharer".to_string(),
+            symbol: "SOL".to_string(),
+            side: OrderSide::Buy,
+            order_type: OrderType::Limit,
+            quantity: Some(100.0),
+            price: Some(140.0),
+            status: OrderStatus::Pending,
+            timestamp: Utc::now(),
+            notes: None,
+            linked_order_id: Some("order-1".to_string()),
+            visibility,
+            filled_quantity: Some(0.0),
+            updated_at: None,
+            finalized: None,
+        }
+    }
+
+    #[test]
+    fn test_live_updates_finalize_on_completion() {
+        let mut record = shared(SharedOrderVisibility::Full);
+        let now = Utc::now();
+
+        apply_live_order(
+            &mut record,
+            &live_order(live::OrderStatus::PartiallyFilled, 40.0),
+            now,
+        );
+        assert_eq!(record.filled_quantity, Some(40.0));
+        assert!(record.finalized.is_none());
+
+        apply_live_order(
+            &mut record,
+            &live_order(live::OrderStatus::Filled, 100.0),
+            now,
+        );
+        assert!(matches!(record.status, OrderStatus::Filled));
+        assert_eq!(
+            record.finalized.as_ref().map(|f| f.reason),
+            Some(SharedOrderFinalReason::Completed)
+        );
+
+        // Finalized records no longer follow the live order.
+        apply_live_order(
+            &mut record,
+            &live_order(live::OrderStatus::Cancelled, 0.0),
+            now,
+        );
+        assert!(matches!(record.status, OrderStatus::Filled));
+    }
+
+    #[test]
+    fn test_visibility_redacts_for_other_participants() {
+        let record = shared(SharedOrderVisibility::DirectionAndStatus);
+
+        let view = visible_to(&record, Some("someone-else"));
+        assert!(view.quantity.is_none());
+        assert!(view.filled_quantity.is_none());
+        assert!(view.price.is_none());
+        assert!(matches!(view.side, OrderSide::Buy));
+
+        let own = visible_to(&record, Some("sharer"));
+        assert_eq!(own.quantity, Some(100.0));
+
+        let hidden_size = visible_to(&shared(SharedOrderVisibility::HideSize), None);
+        assert!(hidden_size.quantity.is_none());
+        assert_eq!(hidden_size.price, Some(140.0));
+    }
+}
@@ -1,4 +1,86 @@
-use crate::collab::types::{ParticipantPermissions, ParticipantRole};
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, Result};
+
+use crate::collab::types::{Capability, Participant, ParticipantPermissions, ParticipantRole};
+
+impl Capability {
+    pub const ALL: [Capability; 12] = [
+        Capability::Speak,
+        Capability::ShareVideo,
+        Capability::ShareScreen,
+        Capability::Chat,
+        Capability::ShareOrders,
+        Capability::ShareStrategies,
+        Capability::ShareWatchlists,
+        Capability::StartCompetitions,
+        Capability::Moderate,
+        Capability::Kick,
+        Capability::Ban,
+        Capability::Invite,
+    ];
+
+    fn describe(&self) -> &'static str {
+        match self {
+            Capability::Speak => "speaking",
+            Capability::ShareVideo => "sharing video",
+            Capability::ShareScreen => "sharing the screen",
+            Capability::Chat => "sending messages",
+            Capability::ShareOrders => "sharing orders",
+            Capability::ShareStrategies => "sharing strategies",
+            Capability::ShareWatchlists => "sharing watchlists",
+            Capability::StartCompetitions => "running competitions",
+            Capability::Moderate => "moderating users",
+            Capability::Kick => "removing participants",
+            Capability::Ban => "banning participants",
+            Capability::Invite => "inviting others",
+        }
+    }
+}
+
+impl ParticipantPermissions {
+    pub fn allows(&self, capability: Capability) -> bool {
+        *self.flag(capability)
+    }
+
+    fn set(&mut self, capability: Capability, allowed: bool) {
+        *self.flag_mut(capability) = allowed;
+    }
+
+    fn flag(&self, capability: Capability) -> &bool {
+        match capability {
+            Capability::Speak => &self.can_speak,
+            Capability::ShareVideo => &self.can_share_video,
+            Capability::ShareScreen => &self.can_share_screen,
+            Capability::Chat => &self.can_chat,
+            Capability::ShareOrders => &self.can_share_orders,
+            Capability::ShareStrategies => &self.can_share_strategies,
+            Capability::ShareWatchlists => &self.can_share_watchlists,
+            Capability::StartCompetitions => &self.can_start_competitions,
+            Capability::Moderate => &self.can_moderate,
+            Capability::Kick => &self.can_kick,
+            Capability::Ban => &self.can_ban,
+            Capability::Invite => &self.can_invite,
+        }
+    }
+
+    fn flag_mut(&mut self, capability: Capability) -> &mut bool {
+        match capability {
+            Capability::Speak => &mut self.can_speak,
+            Capability::ShareVideo => &mut self.can_share_video,
+            Capability::ShareScreen => &mut self.can_share_screen,
+            Capability::Chat => &mut self.can_chat,
+            Capability::ShareOrders => &mut self.can_share_orders,
+            Capability::ShareStrategies => &mut self.can_share_strategies,
+            Capability::ShareWatchlists => &mut self.can_share_watchlists,
+            Capability::StartCompetitions => &mut self.can_start_competitions,
+            Capability::Moderate => &mut self.can_moderate,
+            Capability::Kick => &mut self.can_kick,
+            Capability::Ban => &mut self.can_ban,
+            Capability::Invite => &mut self.can_invite,
+        }
+    }
+}
 
 pub fn default_permissions_for_role(role: ParticipantRole) -> ParticipantPermissions {
     match role {
@@ -9,9 +91,12 @@ pub fn default_permissions_for_role(role: ParticipantRole) -> ParticipantPermiss
             can_chat: true,
             can_share_orders: true,
             can_share_strategies: true,
+            can_share_watchlists: true,
+            can_start_competitions: true,
             can_moderate: true,
             can_kick: true,
             can_ban: true,
+            can_invite: true,
         },
         ParticipantRole::Moderator => ParticipantPermissions {
             can_speak: true,
@@ -20,35 +105,213 @@ pub fn default_permissions_for_role(role: ParticipantRole) -> ParticipantPermiss
             can_chat: true,
             can_share_orders: true,
             can_share_strategies: true,
+            can_share_watchlists: true,
+            can_start_competitions: true,
             can_moderate: true,
             can_kick: true,
             can_ban: false,
+            can_invite: true,
         },
-        ParticipantRole::Member => ParticipantPermissions {
+        ParticipantRole::Trader => ParticipantPermissions {
             can_speak: true,
             can_share_video: true,
             can_share_screen: true,
             can_chat: true,
             can_share_orders: true,
             can_share_strategies: true,
+            can_share_watchlists: true,
+            can_start_competitions: false,
             can_moderate: false,
             can_kick: false,
             can_ban: false,
+            can_invite: true,
         },
-        ParticipantRole::Guest => ParticipantPermissions {
+        ParticipantRole::Viewer => ParticipantPermissions {
             can_speak: false,
             can_share_video: false,
             can_share_screen: false,
             can_chat: false,
             can_share_orders: false,
             can_share_strategies: false,
+            can_share_watchlists: false,
+            can_start_competitions: false,
             can_moderate: false,
             can_kick: false,
             can_ban: false,
+            can_invite: false,
         },
     }
 }
 
-pub fn can_modify_permissions(role: ParticipantRole) -> bool {
-    matches!(role, ParticipantRole::Owner | ParticipantRole::Moderator)
+/// The role's defaults with `overrides` applied.
+pub fn effective_permissions(
+    role: ParticipantRole,
+    overrides: &BTreeMap<Capability, bool>,
+) -> ParticipantPermissions {
+    let mut permissions = default_permissions_for_role(role);
+    for (capability, allowed) in overrides {
+        permissions.set(*capability, *allowed);
+    }
+    permissions
+}
+
+/// The overrides that turn `role`'s defaults into `permissions`.
+pub fn overrides_for(
+    role: ParticipantRole,
+    permissions: &ParticipantPermissions,
+) -> BTreeMap<Capability, bool> {
+    let defaults = default_permissions_for_role(role);
+    Capability::ALL
+        .into_iter()
+        .filter(|capability| defaults.allows(*capability) != permissions.allows(*capability))
+        .map(|capability| (capability, permissions.allows(capability)))
+        .collect()
+}
+
+pub fn require_capability(participant: &Participant, capability: Capability) -> Result<()> {
+    if participant.permissions.allows(capability) {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "Your permissions in this room do not allow {}",
+            capability.describe()
+        ))
+    }
+}
+
+fn rank(role: ParticipantRole) -> u8 {
+    match role {
+        ParticipantRole::Owner => 3,
+        ParticipantRole::Moderator => 2,
+        ParticipantRole::Trader => 1,
+        ParticipantRole::Viewer => 0,
+    }
+}
+
+/// Moderators manage traders and viewers; the owner manages everyone else.
+pub fn outranks(actor: ParticipantRole, target: ParticipantRole) -> bool {
+    rank(actor) > rank(target)
+}
+
+pub fn ensure_can_set_role(
+    actor: &Participant,
+    target: &Participant,
+    role: ParticipantRole,
+) -> Result<()> {
+    require_capability(actor, Capability::Moderate)?;
+    if role == ParticipantRole::Owner {
+        return Err(anyhow!(
+            "Ownership can only be handed over with collab_transfer_ownership"
+        ));
+    }
+    if target.role == ParticipantRole::Owner {
+        return Err(anyhow!(
+            "The owner's role changes only by transferring ownership"
+        ));
+    }
+    if !outranks(actor.role, target.role) || !outranks(actor.role, role) {
+        return Err(anyhow!("Cannot assign roles at or above your own"));
+    }
+    Ok(())
+}
+
+/// Overrides may only be set on lower roles and may not grant anything the
+/// actor lacks.
+pub fn ensure_can_update_permissions(
+    actor: &Participant,
+    target: &Participant,
+    requested: &ParticipantPermissions,
+) -> Result<()> {
+    require_capability(actor, Capability::Moderate)?;
+    if !outranks(actor.role, target.role) {
+        return Err(anyhow!(
+            "Cannot change permissions of a participant at or above your role"
+        ));
+    }
+    if let Some(capability) = Capability::ALL.into_iter().find(|capability| {
+        requested.allows(*capability)
+            && !target.permissions.allows(*capability)
+            && !actor.permissions.allows(*capability)
+    }) {
+        return Err(anyhow!(
+            "Cannot grant {} without having it yourself",
+            capability.describe()
+        ));
+    }
+    Ok(())
+}
+
+/// Messages can be edited or deleted by their author or by anyone who can moderate.
+pub fn can_modify_message(participant: &Participant, author_id: &str) -> bool {
+    participant.user_id == author_id || participant.permissions.can_moderate
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collab::types::ParticipantStatus;
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    fn participant(user_id: &str, role: ParticipantRole) -> Participant {
+        Participant {
+            id: Uuid::new_v4(),
+            user_id: user_id.to_string(),
+            username: user_id.to_string(),
+            room_id: Uuid::nil(),
+            joined_at: Utc::now(),
+            last_active: Utc::now(),
+            role,
+            permissions: default_permissions_for_role(role),
+            permission_overrides: BTreeMap::new(),
+            status: ParticipantStatus::Active,
+            is_muted: false,
+            is_video_off: false,
+            is_screen_sharing: false,
+        }
+    }
+
+    #[test]
+    fn overrides_survive_role_changes() {
+        let mut requested = default_permissions_for_role(ParticipantRole::Trader);
+        requested.can_share_orders = false;
+        requested.can_start_competitions = true;
+
+        let overrides = overrides_for(ParticipantRole::Trader, &requested);
+        assert_eq!(overrides.len(), 2);
+        assert_eq!(overrides.get(&Capability::ShareOrders), Some(&false));
+
+        let viewer = effective_permissions(ParticipantRole::Viewer, &overrides);
+        assert!(viewer.allows(Capability::StartCompetitions));
+        assert!(!viewer.allows(Capability::ShareOrders));
+        assert!(!viewer.allows(Capability::Chat));
+    }
+
+    #[test]
+    fn role_changes_respect_rank_and_ownership() {
+        let owner = participant("owner", ParticipantRole::Owner);
+        let moderator = participant("mod", ParticipantRole::Moderator);
+        let trader = participant("trader", ParticipantRole::Trader);
+
+        assert!(ensure_can_set_role(&owner, &trader, ParticipantRole::Moderator).is_ok());
+        assert!(ensure_can_set_role(&moderator, &trader, ParticipantRole::Viewer).is_ok());
+        assert!(ensure_can_set_role(&moderator, &trader, ParticipantRole::Moderator).is_err());
+        assert!(ensure_can_set_role(&moderator, &owner, ParticipantRole::Viewer).is_err());
+        assert!(ensure_can_set_role(&owner, &trader, ParticipantRole::Owner).is_err());
+        assert!(ensure_can_set_role(&trader, &trader, ParticipantRole::Viewer).is_err());
+    }
+
+    #[test]
+    fn moderators_cannot_grant_what_they_lack() {
+        let moderator = participant("mod", ParticipantRole::Moderator);
+        let trader = participant("trader", ParticipantRole::Trader);
+
+        let mut requested = trader.permissions.clone();
+        requested.can_chat = false;
+        assert!(ensure_can_update_permissions(&moderator, &trader, &requested).is_ok());
+
+        requested.can_ban = true;
+        assert!(ensure_can_update_permissions(&moderator, &trader, &requested).is_err());
+        assert!(ensure_can_update_permissions(&trader, &moderator, &trader.permissions).is_err());
+    }
 }
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 
 use anyhow::{anyhow, Context, Result};
@@ -6,18 +6,23 @@ use chrono::Utc;
 use parking_lot::RwLock;
 use uuid::Uuid;
 
+use crate::alerts::logic::parse_rule_pack;
 use crate::collab::crypto::{hash_password, verify_password};
-use crate::collab::permissions::default_permissions_for_role;
+use crate::collab::permissions::{
+    default_permissions_for_role, effective_permissions, ensure_can_set_role,
+    ensure_can_update_permissions, overrides_for, require_capability,
+};
 use crate::collab::types::{
-    ChatMessage, Competition, CreateRoomRequest, JoinRoomRequest, Participant, ParticipantRole,
-    ParticipantStatus, Room, RoomState, SendMessageRequest, SharedOrder, SharedWatchlist,
+    Capability, ChatMessage, Competition, CreateRoomRequest, JoinRoomRequest, MessageAttachment,
+    Participant, ParticipantPermissions, ParticipantRole, ParticipantStatus, RoleAuditEntry,
+    RoleChangeKind, Room, RoomState, SendMessageRequest, SharedOrder, SharedWatchlist,
 };
+use crate::indicators::decode_preset_code;
 
 #[derive(Clone)]
 pub struct RoomManager {
     rooms: Arc<RwLock<HashMap<Uuid, Room>>>,
     participants: Arc<RwLock<HashMap<Uuid, Vec<Participant>>>>,
-    chat_messages: Arc<RwLock<HashMap<Uuid, Vec<ChatMessage>>>>,
     watchlists: Arc<RwLock<HashMap<Uuid, Vec<SharedWatchlist>>>>,
     orders: Arc<RwLock<HashMap<Uuid, Vec<SharedOrder>>>>,
     competitions: Arc<RwLock<HashMap<Uuid, Competition>>>,
@@ -28,7 +33,6 @@ impl RoomManager {
         Self {
             rooms: Arc::new(RwLock::new(HashMap::new())),
             participants: Arc::new(RwLock::new(HashMap::new())),
-            chat_messages: Arc::new(RwLock::new(HashMap::new())),
             watchlists: Arc::new(RwLock::new(HashMap::new())),
             orders: Arc::new(RwLock::new(HashMap::new())),
             competitions: Arc::new(RwLock::new(HashMap::new())),
@@ -62,7 +66,6 @@ impl RoomManager {
 
         self.rooms.write().insert(room_id, room.clone());
         self.participants.write().insert(room_id, Vec::new());
-        self.chat_messages.write().insert(room_id, Vec::new());
         self.watchlists.write().insert(room_id, Vec::new());
         self.orders.write().insert(room_id, Vec::new());
 
@@ -95,7 +98,6 @@ impl RoomManager {
 
         self.rooms.write().remove(room_id);
         self.participants.write().remove(room_id);
-        self.chat_messages.write().remove(room_id);
         self.watchlists.write().remove(room_id);
         self.orders.write().remove(room_id);
         self.competitions.write().remove(room_id);
@@ -130,9 +132,9 @@ impl RoomManager {
         let role = if user_id == room.owner_id {
             ParticipantRole::Owner
         } else if room.settings.allow_guest_join {
-            ParticipantRole::Guest
+            ParticipantRole::Viewer
         } else {
-            ParticipantRole::Member
+            ParticipantRole::Trader
         };
 
         let participant = Participant {
@@ -144,6 +146,7 @@ impl RoomManager {
             last_active: Utc::now(),
             role,
             permissions: default_permissions_for_role(role),
+            permission_overrides: BTreeMap::new(),
             status: ParticipantStatus::Active,
             is_muted: false,
             is_video_off: false,
@@ -159,7 +162,14 @@ impl RoomManager {
         Ok(participant)
     }
 
+    /// The owner cannot leave; they transfer ownership or delete the room.
     pub fn leave_room(&self, room_id: &Uuid, user_id: &str) -> Result<()> {
+        if self.get_room(room_id)?.owner_id == user_id {
+            return Err(anyhow!(
+                "Transfer ownership or delete the room before leaving"
+            ));
+        }
+
         let mut participants = self.participants.write();
         if let Some(room_participants) = participants.get_mut(room_id) {
             room_participants.retain(|p| p.user_id != user_id);
@@ -185,6 +195,116 @@ impl RoomManager {
             .ok_or_else(|| anyhow!("Participant not found"))
     }
 
+    /// The participant, if their permissions include `capability`.
+    pub fn require_capability(
+        &self,
+        room_id: &Uuid,
+        user_id: &str,
+        capability: Capability,
+    ) -> Result<Participant> {
+        let participant = self.get_participant(room_id, user_id)?;
+        require_capability(&participant, capability)?;
+        Ok(participant)
+    }
+
+    /// Gives `target_user_id` a new role, keeping their overrides.
+    pub fn set_role(
+        &self,
+        room_id: &Uuid,
+        actor_id: &str,
+        target_user_id: &str,
+        role: ParticipantRole,
+    ) -> Result<(Participant, RoleAuditEntry)> {
+        let mut participants = self.participants.write();
+        let room_participants = participants
+            .get_mut(room_id)
+            .ok_or_else(|| anyhow!("Room not found"))?;
+        let actor = find_participant(room_participants, actor_id)?.clone();
+        let target = find_participant_mut(room_participants, target_user_id)?;
+        ensure_can_set_role(&actor, target, role)?;
+
+        let previous_role = target.role;
+        target.role = role;
+        target.permissions = effective_permissions(role, &target.permission_overrides);
+        let entry = audit_entry(actor_id, target, RoleChangeKind::Role, previous_role);
+        Ok((target.clone(), entry))
+    }
+
+    /// Replaces the participant's permissions. They are stored as overrides
+    /// of the role's defaults so a later role change keeps them.
+    pub fn update_permissions(
+        &self,
+        room_id: &Uuid,
+        actor_id: &str,
+        target_user_id: &str,
+        requested: &ParticipantPermissions,
+    ) -> Result<(Participant, RoleAuditEntry)> {
+        let mut participants = self.participants.write();
+        let room_participants = participants
+            .get_mut(room_id)
+            .ok_or_else(|| anyhow!("Room not found"))?;
+        let actor = find_participant(room_participants, actor_id)?.clone();
+        let target = find_participant_mut(room_participants, target_user_id)?;
+        ensure_can_update_permissions(&actor, target, requested)?;
+
+        target.permission_overrides = overrides_for(target.role, requested);
+        target.permissions = effective_permissions(target.role, &target.permission_overrides);
+        let entry = audit_entry(actor_id, target, RoleChangeKind::Permissions, target.role);
+        Ok((target.clone(), entry))
+    }
+
+    /// Hands the room to another participant. The previous owner stays on as
+    /// a moderator.
+    pub fn transfer_ownership(
+        &self,
+        room_id: &Uuid,
+        actor_id: &str,
+        new_owner_id: &str,
+    ) -> Result<(Room, Vec<(Participant, RoleAuditEntry)>)> {
+        let mut rooms = self.rooms.write();
+        let room = rooms
+            .get_mut(room_id)
+            .ok_or_else(|| anyhow!("Room not found"))?;
+        if room.owner_id != actor_id {
+            return Err(anyhow!("Only the room owner can transfer ownership"));
+        }
+        if new_owner_id == actor_id {
+            return Err(anyhow!("You already own this room"));
+        }
+
+        let mut participants = self.participants.write();
+        let room_participants = participants
+            .get_mut(room_id)
+            .ok_or_else(|| anyhow!("Room not found"))?;
+        find_participant(room_participants, new_owner_id)?;
+
+        let mut changes = Vec::new();
+        for (user_id, role) in [
+            (new_owner_id, ParticipantRole::Owner),
+            (actor_id, ParticipantRole::Moderator),
+        ] {
+            // The previous owner may never have joined.
+            let Ok(participant) = find_participant_mut(room_participants, user_id) else {
+                continue;
+            };
+            let previous_role = participant.role;
+            participant.role = role;
+            participant.permissions =
+                effective_permissions(role, &participant.permission_overrides);
+            let entry = audit_entry(
+                actor_id,
+                participant,
+                RoleChangeKind::OwnershipTransfer,
+                previous_role,
+            );
+            changes.push((participant.clone(), entry));
+        }
+
+        room.owner_id = new_owner_id.to_string();
+        room.updated_at = Utc::now();
+        Ok((room.clone(), changes))
+    }
+
     pub fn update_participant(&self, participant: Participant) -> Result<()> {
         let mut participants = self.participants.write();
         if let Some(room_participants) = participants.get_mut(&participant.room_id) {
@@ -202,6 +322,8 @@ impl RoomManager {
         }
     }
 
+    /// Checks the sender may chat and builds the message; persisting it is left to
+    /// the history store.
     pub fn send_message(
         &self,
         request: SendMessageRequest,
@@ -214,6 +336,7 @@ impl RoomManager {
         if !participant.permissions.can_chat {
             return Err(anyhow!("User does not have chat permissions"));
         }
+        let attachment = request.attachment.map(validate_attachment).transpose()?;
 
         let message = ChatMessage {
             id: Uuid::new_v4(),
@@ -225,28 +348,15 @@ impl RoomManager {
             encrypted: room.encryption_enabled,
             mentions: Vec::new(),
             replied_to: request.replied_to,
+            edited_at: None,
+            deleted_at: None,
+            deleted_by: None,
+            attachment,
         };
 
-        self.chat_messages
-            .write()
-            .entry(request.room_id)
-            .or_default()
-            .push(message.clone());
-
         Ok(message)
     }
 
-    pub fn get_messages(&self, room_id: &Uuid, limit: Option<usize>) -> Vec<ChatMessage> {
-        let messages = self.chat_messages.read();
-        let room_messages = messages.get(room_id).cloned().unwrap_or_default();
-
-        if let Some(limit) = limit {
-            room_messages.into_iter().rev().take(limit).rev().collect()
-        } else {
-            room_messages
-        }
-    }
-
     pub fn add_watchlist(&self, watchlist: SharedWatchlist) -> Result<()> {
         self.watchlists
             .write()
@@ -277,6 +387,16 @@ impl RoomManager {
         self.orders.read().get(room_id).cloned().unwrap_or_default()
     }
 
+    pub fn find_linked_orders(&self, linked_order_id: &str) -> Vec<SharedOrder> {
+        self.orders
+            .read()
+            .values()
+            .flatten()
+            .filter(|o| o.linked_order_id.as_deref() == Some(linked_order_id))
+            .cloned()
+            .collect()
+    }
+
     pub fn update_order(&self, order: SharedOrder) -> Result<()> {
         let mut orders = self.orders.write();
         if let Some(room_orders) = orders.get_mut(&order.room_id) {
@@ -302,6 +422,24 @@ impl RoomManager {
         self.competitions.read().get(room_id).cloned()
     }
 
+    pub fn list_competitions(&self) -> Vec<Competition> {
+        self.competitions.read().values().cloned().collect()
+    }
+
+    /// Applies `update` to the room's competition under the lock, so scoring
+    /// passes never overwrite entrants that joined in the meantime.
+    pub fn update_competition<T>(
+        &self,
+        room_id: &Uuid,
+        update: impl FnOnce(&mut Competition) -> Result<T>,
+    ) -> Result<T> {
+        let mut competitions = self.competitions.write();
+        let competition = competitions
+            .get_mut(room_id)
+            .ok_or_else(|| anyhow!("Competition not found"))?;
+        update(competition)
+    }
+
     pub fn get_room_state(&self, room_id: &Uuid) -> Result<RoomState> {
         let room = self.get_room(room_id)?;
         let participants = self.get_participants(room_id);
@@ -324,3 +462,156 @@ impl Default for RoomManager {
         Self::new()
     }
 }
+
+fn find_participant<'a>(participants: &'a [Participant], user_id: &str) -> Result<&'a Participant> {
+    participants
+        .iter()
+        .find(|p| p.user_id == user_id)
+        .ok_or_else(|| anyhow!("Participant not found"))
+}
+
+fn find_participant_mut<'a>(
+    participants: &'a mut [Participant],
+    user_id: &str,
+) -> Result<&'a mut Participant> {
+    participants
+        .iter_mut()
+        .find(|p| p.user_id == user_id)
+        .ok_or_else(|| anyhow!("Participant not found"))
+}
+
+fn audit_entry(
+    actor_id: &str,
+    target: &Participant,
+    kind: RoleChangeKind,
+    previous_role: ParticipantRole,
+) -> RoleAuditEntry {
+    RoleAuditEntry {
+        id: Uuid::new_v4(),
+        room_id: target.room_id,
+        actor_id: actor_id.to_string(),
+        target_user_id: target.user_id.clone(),
+        kind,
+        previous_role,
+        new_role: target.role,
+        overrides: target.permission_overrides.clone(),
+        timestamp: Utc::now(),
+    }
+}
+
+/// Rejects attachments recipients could not use and fills in their summary
+/// from the content itself rather than trusting the sender's.
+fn validate_attachment(attachment: MessageAttachment) -> Result<MessageAttachment> {
+    match attachment {
+        MessageAttachment::IndicatorPreset { code, .. } => {
+            let code = code.trim().to_string();
+            let payload = decode_preset_code(&code)
+                .map_err(|e| anyhow!("Invalid preset attachment: {}", e))?;
+            Ok(MessageAttachment::IndicatorPreset {
+                code,
+                name: payload.name,
+                indicator_count: payload.indicators.len(),
+                custom_indicator_count: payload.custom_indicators.len(),
+                drawing_template_count: payload.drawing_templates.len(),
+            })
+        }
+        MessageAttachment::AlertRulePack { pack, .. } => {
+            let parsed = parse_rule_pack(&pack)
+                .map_err(|e| anyhow!("Invalid rule pack attachment: {}", e))?;
+            Ok(MessageAttachment::AlertRulePack {
+                pack_id: parsed.pack_id,
+                name: parsed.name,
+                version: parsed.version,
+                rule_count: parsed.rules.len(),
+                parameter_count: parsed.parameters.len(),
+                pack,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collab::types::RoomSettings;
+
+    fn room_with(manager: &RoomManager, members: &[&str]) -> Uuid {
+        let room = manager
+            .create_room(
+                CreateRoomRequest {
+                    name: "desk".into(),
+                    description: None,
+                    max_participants: 10,
+                    is_public: true,
+                    password: None,
+                    settings: RoomSettings::default(),
+                },
+                "alice".into(),
+            )
+            .unwrap();
+        for user in ["alice"].iter().chain(members) {
+            manager
+                .join_room(
+                    JoinRoomRequest {
+                        room_id: room.id,
+                        password: None,
+                        username: user.to_string(),
+                        history_limit: None,
+                    },
+                    user.to_string(),
+                )
+                .unwrap();
+        }
+        room.id
+    }
+
+    #[test]
+    fn owner_leaves_only_after_transferring() {
+        let manager = RoomManager::new();
+        let room_id = room_with(&manager, &["bob"]);
+
+        assert!(manager.leave_room(&room_id, "alice").is_err());
+        assert!(manager
+            .set_role(&room_id, "alice", "bob", ParticipantRole::Owner)
+            .is_err());
+
+        let (room, changes) = manager
+            .transfer_ownership(&room_id, "alice", "bob")
+            .unwrap();
+        assert_eq!(room.owner_id, "bob");
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[1].1.previous_role, ParticipantRole::Owner);
+        assert_eq!(
+            manager.get_participant(&room_id, "alice").unwrap().role,
+            ParticipantRole::Moderator
+        );
+
+        manager.leave_room(&room_id, "alice").unwrap();
+        assert!(manager.leave_room(&room_id, "bob").is_err());
+    }
+
+    #[test]
+    fn role_change_keeps_overrides_and_audits_actor() {
+        let manager = RoomManager::new();
+        let room_id = room_with(&manager, &["bob"]);
+
+        let mut muted = manager
+            .get_participant(&room_id, "bob")
+            .unwrap()
+            .permissions;
+        muted.can_chat = false;
+        manager
+            .update_permissions(&room_id, "alice", "bob", &muted)
+            .unwrap();
+
+        let (bob, entry) = manager
+            .set_role(&room_id, "alice", "bob", ParticipantRole::Moderator)
+            .unwrap();
+        assert!(!bob.permissions.can_chat);
+        assert!(bob.permissions.can_moderate);
+        assert_eq!(entry.actor_id, "alice");
+        assert_eq!(entry.target_user_id, "bob");
+        assert_eq!(entry.previous_role, ParticipantRole::Trader);
+        assert_eq!(entry.new_role, ParticipantRole::Moderator);
+    }
+}
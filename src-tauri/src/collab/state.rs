@@ -7,6 +7,7 @@ use tauri::State;
 use uuid::Uuid;
 
 use crate::collab::crypto::RoomEncryption;
+use crate::collab::history::MessageHistoryStore;
 use crate::collab::moderation::ModerationManager;
 use crate::collab::room::RoomManager;
 use crate::collab::rtc::RtcSessionManager;
@@ -19,16 +20,18 @@ pub struct CollabState {
     pub rtc: Arc<RtcSessionManager>,
     pub websocket: Arc<CollabWebSocketManager>,
     pub moderation: Arc<ModerationManager>,
+    pub history: Arc<MessageHistoryStore>,
     encryption_keys: Arc<RwLock<HashMap<Uuid, [u8; 32]>>>,
 }
 
 impl CollabState {
-    pub fn new(websocket: CollabWebSocketManager) -> Self {
+    pub fn new(websocket: CollabWebSocketManager, history: MessageHistoryStore) -> Self {
         Self {
             rooms: Arc::new(RoomManager::new()),
             rtc: Arc::new(RtcSessionManager::new()),
             websocket: Arc::new(websocket),
             moderation: Arc::new(ModerationManager::new()),
+            history: Arc::new(history),
             encryption_keys: Arc::new(RwLock::new(HashMap::new())),
         }
     }
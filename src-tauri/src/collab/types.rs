@@ -1,7 +1,11 @@
+use std::collections::BTreeMap;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::collab::history::MessagePage;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Room {
     pub id: Uuid,
@@ -58,22 +62,32 @@ pub struct Participant {
     pub joined_at: DateTime<Utc>,
     pub last_active: DateTime<Utc>,
     pub role: ParticipantRole,
+    /// Effective permissions: the role's defaults with `permission_overrides`
+    /// applied.
     pub permissions: ParticipantPermissions,
+    /// Capabilities granted or revoked for this participant regardless of
+    /// role. They survive role changes.
+    #[serde(default)]
+    pub permission_overrides: BTreeMap<Capability, bool>,
     pub status: ParticipantStatus,
     pub is_muted: bool,
     pub is_video_off: bool,
     pub is_screen_sharing: bool,
 }
 
+/// A room has exactly one owner, `Room::owner_id`; ownership only changes
+/// hands through an explicit transfer.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 pub enum ParticipantRole {
     Owner,
     Moderator,
-    Member,
-    Guest,
+    #[serde(alias = "Member")]
+    Trader,
+    #[serde(alias = "Guest")]
+    Viewer,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ParticipantPermissions {
     pub can_speak: bool,
     pub can_share_video: bool,
@@ -81,9 +95,32 @@ pub struct ParticipantPermissions {
     pub can_chat: bool,
     pub can_share_orders: bool,
     pub can_share_strategies: bool,
+    #[serde(default)]
+    pub can_share_watchlists: bool,
+    #[serde(default)]
+    pub can_start_competitions: bool,
     pub can_moderate: bool,
     pub can_kick: bool,
     pub can_ban: bool,
+    #[serde(default)]
+    pub can_invite: bool,
+}
+
+/// One flag of `ParticipantPermissions`, for per-participant overrides.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Capability {
+    Speak,
+    ShareVideo,
+    ShareScreen,
+    Chat,
+    ShareOrders,
+    ShareStrategies,
+    ShareWatchlists,
+    StartCompetitions,
+    Moderate,
+    Kick,
+    Ban,
+    Invite,
 }
 
 impl Default for ParticipantPermissions {
@@ -95,9 +132,12 @@ impl Default for ParticipantPermissions {
             can_chat: true,
             can_share_orders: true,
             can_share_strategies: true,
+            can_share_watchlists: true,
+            can_start_competitions: false,
             can_moderate: false,
             can_kick: false,
             can_ban: false,
+            can_invite: true,
         }
     }
 }
@@ -121,6 +161,51 @@ pub struct ChatMessage {
     pub encrypted: bool,
     pub mentions: Vec<String>,
     pub replied_to: Option<Uuid>,
+    #[serde(default)]
+    pub edited_at: Option<DateTime<Utc>>,
+    /// Deleted messages are kept as tombstones with their content cleared.
+    #[serde(default)]
+    pub deleted_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub deleted_by: Option<String>,
+    #[serde(default)]
+    pub attachment: Option<MessageAttachment>,
+}
+
+/// Structured content sent along with a chat message.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum MessageAttachment {
+    /// An indicator preset code recipients can pass to
+    /// `indicator_import_preset`. The summary fields are filled in from the
+    /// decoded code when the message is sent.
+    IndicatorPreset {
+        code: String,
+        #[serde(default)]
+        name: String,
+        #[serde(default)]
+        indicator_count: usize,
+        #[serde(default)]
+        custom_indicator_count: usize,
+        #[serde(default)]
+        drawing_template_count: usize,
+    },
+    /// A smart alert rule pack recipients can pass to
+    /// `smart_alert_preview_pack` and `smart_alert_import_pack`. The summary
+    /// fields are filled in from the pack when the message is sent.
+    AlertRulePack {
+        pack: String,
+        #[serde(default)]
+        pack_id: String,
+        #[serde(default)]
+        name: String,
+        #[serde(default)]
+        version: u32,
+        #[serde(default)]
+        rule_count: usize,
+        #[serde(default)]
+        parameter_count: usize,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -143,11 +228,47 @@ pub struct SharedOrder {
     pub symbol: String,
     pub side: OrderSide,
     pub order_type: OrderType,
-    pub quantity: f64,
+    /// `None` when hidden from other participants by the sharer.
+    pub quantity: Option<f64>,
     pub price: Option<f64>,
     pub status: OrderStatus,
     pub timestamp: DateTime<Utc>,
     pub notes: Option<String>,
+    /// Id of the live trading order this share tracks, if any.
+    #[serde(default)]
+    pub linked_order_id: Option<String>,
+    #[serde(default)]
+    pub visibility: SharedOrderVisibility,
+    #[serde(default)]
+    pub filled_quantity: Option<f64>,
+    #[serde(default)]
+    pub updated_at: Option<DateTime<Utc>>,
+    /// Set once the record stops following the live order.
+    #[serde(default)]
+    pub finalized: Option<SharedOrderFinalization>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SharedOrderVisibility {
+    #[default]
+    Full,
+    HideSize,
+    /// Only side, symbol and status; size and price are hidden.
+    DirectionAndStatus,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SharedOrderFinalReason {
+    Completed,
+    Unshared,
+    /// The live order aged out of order history while the room was still open.
+    HistoryUnavailable,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SharedOrderFinalization {
+    pub reason: SharedOrderFinalReason,
+    pub at: DateTime<Utc>,
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
@@ -170,6 +291,7 @@ pub enum OrderStatus {
     Filled,
     PartiallyFilled,
     Cancelled,
+    Expired,
     Rejected,
 }
 
@@ -215,6 +337,11 @@ pub struct Competition {
     pub rules: CompetitionRules,
     pub leaderboard: Vec<LeaderboardEntry>,
     pub status: CompetitionStatus,
+    #[serde(default)]
+    pub entrants: Vec<CompetitionEntrant>,
+    /// Standings frozen at `end_time` for automatically scored competitions.
+    #[serde(default)]
+    pub final_snapshot: Option<CompetitionSnapshot>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -223,6 +350,10 @@ pub struct CompetitionRules {
     pub allowed_assets: Option<Vec<String>>,
     pub max_position_size: Option<f64>,
     pub scoring_method: ScoringMethod,
+    /// Where entrant performance is read from. `None` keeps the leaderboard
+    /// manual, updated through `collab_update_leaderboard`.
+    #[serde(default)]
+    pub data_scope: Option<CompetitionDataScope>,
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
@@ -231,6 +362,62 @@ pub enum ScoringMethod {
     RiskAdjustedReturn,
     WinRate,
     ProfitFactor,
+    TotalPnl,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CompetitionDataScope {
+    PaperAccount,
+    Wallet,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum CompetitionDataSource {
+    PaperAccount,
+    Wallet { address: String },
+}
+
+impl CompetitionDataSource {
+    pub fn scope(&self) -> CompetitionDataScope {
+        match self {
+            CompetitionDataSource::PaperAccount => CompetitionDataScope::PaperAccount,
+            CompetitionDataSource::Wallet { .. } => CompetitionDataScope::Wallet,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompetitionEntrant {
+    pub user_id: String,
+    pub username: String,
+    pub source: CompetitionDataSource,
+    pub joined_at: DateTime<Utc>,
+    /// Start of the entrant's scoring window: the competition start, or the
+    /// join time for late joiners.
+    pub measured_from: DateTime<Utc>,
+    /// Account value at `measured_from`; set on the first reading.
+    pub baseline: Option<f64>,
+    pub latest_value: Option<f64>,
+    pub trades: usize,
+    pub win_rate: f64,
+    /// Account values recorded at each scoring pass, for risk-adjusted scores.
+    #[serde(default)]
+    pub samples: Vec<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompetitionSnapshot {
+    pub leaderboard: Vec<LeaderboardEntry>,
+    pub frozen_at: DateTime<Utc>,
+}
+
+/// Explicit consent to have the named data source read for scoring.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JoinCompetitionRequest {
+    pub room_id: String,
+    pub user_id: String,
+    pub source: CompetitionDataSource,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -244,7 +431,7 @@ pub struct LeaderboardEntry {
     pub total_return: f64,
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum CompetitionStatus {
     Pending,
     Active,
@@ -272,6 +459,37 @@ pub enum ModerationActionType {
     Warning,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RoleChangeKind {
+    Role,
+    Permissions,
+    OwnershipTransfer,
+}
+
+/// Who changed whose role or permissions, kept per room.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoleAuditEntry {
+    pub id: Uuid,
+    pub room_id: Uuid,
+    pub actor_id: String,
+    pub target_user_id: String,
+    pub kind: RoleChangeKind,
+    pub previous_role: ParticipantRole,
+    pub new_role: ParticipantRole,
+    /// The target's overrides after the change.
+    pub overrides: BTreeMap<Capability, bool>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// What the caller may do in a room, so the UI can hide the rest.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MyPermissions {
+    pub room_id: Uuid,
+    pub role: ParticipantRole,
+    pub permissions: ParticipantPermissions,
+    pub is_owner: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WebRTCSignal {
     pub from_user_id: String,
@@ -309,9 +527,19 @@ pub enum CollabMessage {
     ParticipantUpdated {
         participant: Participant,
     },
+    RoleChanged {
+        change: RoleAuditEntry,
+        participant: Participant,
+    },
     ChatMessage {
         message: ChatMessage,
     },
+    ChatMessageEdited {
+        message: ChatMessage,
+    },
+    ChatMessageDeleted {
+        message: ChatMessage,
+    },
     WatchlistUpdated {
         watchlist: SharedWatchlist,
     },
@@ -366,6 +594,16 @@ pub struct JoinRoomRequest {
     pub room_id: Uuid,
     pub password: Option<String>,
     pub username: String,
+    /// How many recent messages to return with the join; older pages load on demand.
+    #[serde(default)]
+    pub history_limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JoinedRoom {
+    #[serde(flatten)]
+    pub participant: Participant,
+    pub history: MessagePage,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -380,6 +618,15 @@ pub struct SendMessageRequest {
     pub room_id: Uuid,
     pub content: String,
     pub replied_to: Option<Uuid>,
+    #[serde(default)]
+    pub attachment: Option<MessageAttachment>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EditMessageRequest {
+    pub room_id: Uuid,
+    pub message_id: Uuid,
+    pub content: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -391,6 +638,12 @@ pub struct ShareOrderRequest {
     pub quantity: f64,
     pub price: Option<f64>,
     pub notes: Option<String>,
+    /// Links the share to a live trading order, whose details replace the fields
+    /// above and whose later status changes are pushed to the room.
+    #[serde(default)]
+    pub order_id: Option<String>,
+    #[serde(default)]
+    pub visibility: SharedOrderVisibility,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
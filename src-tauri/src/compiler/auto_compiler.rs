@@ -1,7 +1,15 @@
+use super::diagnostics::parse_cargo_message;
+use super::watch::WatchSession;
 use chrono::{DateTime, Utc};
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use tauri::{AppHandle, Emitter};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::Command;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
@@ -21,6 +29,39 @@ pub struct CompilationError {
     pub message: String,
     pub severity: String,
     pub code: Option<String>,
+    #[serde(default)]
+    pub end_line: Option<u32>,
+    #[serde(default)]
+    pub end_column: Option<u32>,
+    #[serde(default)]
+    pub rendered: Option<String>,
+    #[serde(default)]
+    pub suggestions: Vec<SuggestedFix>,
+}
+
+/// A replacement rustc proposed for part of a file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SuggestedFix {
+    pub message: String,
+    pub file: String,
+    pub byte_start: usize,
+    pub byte_end: usize,
+    pub line: u32,
+    pub replacement: String,
+    pub applicability: String,
+    /// rustc is confident the replacement is correct as-is.
+    pub machine_applicable: bool,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum BuildTrigger {
+    /// `compile_now`: a full `cargo build`.
+    #[default]
+    Manual,
+    /// A source change seen by watch mode: an incremental `cargo check`.
+    Watch,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -31,12 +72,20 @@ pub struct CompilationResult {
     pub errors: Vec<CompilationError>,
     pub warnings: Vec<CompilationError>,
     pub duration_ms: f64,
+    #[serde(default)]
+    pub trigger: BuildTrigger,
 }
 
 #[derive(Clone)]
 pub struct AutoCompiler {
     status: Arc<RwLock<BuildStatus>>,
     last_result: Arc<RwLock<Option<CompilationResult>>>,
+    pub(super) project_root: PathBuf,
+    /// Builds run one at a time, in the order they were requested.
+    build_queue: Arc<tokio::sync::Mutex<()>>,
+    /// A watch build is already waiting, so further changes fold into it.
+    pub(super) watch_build_queued: Arc<AtomicBool>,
+    pub(super) watch: Arc<RwLock<Option<WatchSession>>>,
 }
 
 impl Default for AutoCompiler {
@@ -50,6 +99,10 @@ impl AutoCompiler {
         Self {
             status: Arc::new(RwLock::new(BuildStatus::Idle)),
             last_result: Arc::new(RwLock::new(None)),
+            project_root: PathBuf::from(env!("CARGO_MANIFEST_DIR")),
+            build_queue: Arc::new(tokio::sync::Mutex::new(())),
+            watch_build_queued: Arc::new(AtomicBool::new(false)),
+            watch: Arc::new(RwLock::new(None)),
         }
     }
 
@@ -77,22 +130,107 @@ impl AutoCompiler {
             .unwrap_or_default()
     }
 
-    pub fn compile_now(&self) -> Result<CompilationResult, String> {
+    pub async fn compile_now(&self, app: Option<&AppHandle>) -> Result<CompilationResult, String> {
+        self.run_build(BuildTrigger::Manual, app).await
+    }
+
+    /// Runs cargo with JSON diagnostics, streaming each one to the frontend as
+    /// `compiler_diagnostic` while the build is still going.
+    pub async fn run_build(
+        &self,
+        trigger: BuildTrigger,
+        app: Option<&AppHandle>,
+    ) -> Result<CompilationResult, String> {
+        let _queued = self.build_queue.lock().await;
+        if trigger == BuildTrigger::Watch {
+            self.watch_build_queued.store(false, Ordering::SeqCst);
+        }
+
         self.set_status(BuildStatus::Building);
+        if let Some(app) = app {
+            let _ = app.emit("compiler_build_started", trigger);
+        }
 
         let start = std::time::Instant::now();
+        let outcome = self.run_cargo(trigger, app).await;
+        let (success, errors, warnings) = match outcome {
+            Ok(outcome) => outcome,
+            Err(err) => {
+                self.set_status(BuildStatus::Failed);
+                return Err(err);
+            }
+        };
 
         let result = CompilationResult {
-            status: BuildStatus::Success,
+            status: if success && errors.is_empty() {
+                BuildStatus::Success
+            } else {
+                BuildStatus::Failed
+            },
             timestamp: Utc::now(),
-            errors: vec![],
-            warnings: vec![],
+            errors,
+            warnings,
             duration_ms: start.elapsed().as_millis() as f64,
+            trigger,
         };
 
         self.set_status(result.status.clone());
         self.set_result(result.clone());
+        if let Some(app) = app {
+            let _ = app.emit("compiler_build_finished", &result);
+        }
 
         Ok(result)
     }
+
+    async fn run_cargo(
+        &self,
+        trigger: BuildTrigger,
+        app: Option<&AppHandle>,
+    ) -> Result<(bool, Vec<CompilationError>, Vec<CompilationError>), String> {
+        let subcommand = match trigger {
+            BuildTrigger::Manual => "build",
+            BuildTrigger::Watch => "check",
+        };
+        let mut child = Command::new("cargo")
+            .arg(subcommand)
+            .arg("--message-format=json")
+            .current_dir(&self.project_root)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| format!("Failed to start cargo {}: {}", subcommand, e))?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| "Failed to capture cargo output".to_string())?;
+
+        let mut errors = Vec::new();
+        let mut warnings = Vec::new();
+        let mut lines = BufReader::new(stdout).lines();
+        while let Some(line) = lines
+            .next_line()
+            .await
+            .map_err(|e| format!("Failed to read cargo output: {}", e))?
+        {
+            let Some(diagnostic) = parse_cargo_message(&line) else {
+                continue;
+            };
+            if let Some(app) = app {
+                let _ = app.emit("compiler_diagnostic", &diagnostic);
+            }
+            if diagnostic.severity == "error" {
+                errors.push(diagnostic);
+            } else {
+                warnings.push(diagnostic);
+            }
+        }
+
+        let status = child
+            .wait()
+            .await
+            .map_err(|e| format!("Failed to wait for cargo: {}", e))?;
+        Ok((status.success(), errors, warnings))
+    }
 }
@@ -0,0 +1,137 @@
+use super::{CompilationError, SuggestedFix};
+use serde_json::Value;
+
+/// Parses one line of `cargo --message-format=json` output. Returns `None`
+/// for build-script output, artifacts and diagnostics without a source span.
+pub fn parse_cargo_message(line: &str) -> Option<CompilationError> {
+    let value: Value = serde_json::from_str(line).ok()?;
+    if value.get("reason")?.as_str()? != "compiler-message" {
+        return None;
+    }
+    let message = value.get("message")?;
+    let severity = message.get("level")?.as_str()?.to_string();
+    if severity != "error" && severity != "warning" {
+        return None;
+    }
+
+    let spans = message.get("spans")?.as_array()?;
+    let primary = spans
+        .iter()
+        .find(|span| span.get("is_primary").and_then(Value::as_bool) == Some(true))
+        .or_else(|| spans.first())?;
+
+    let text = message.get("message")?.as_str()?.to_string();
+    let mut suggestions: Vec<SuggestedFix> = spans
+        .iter()
+        .filter_map(|span| suggestion_from_span(span, &text))
+        .collect();
+    for child in message
+        .get("children")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        let child_text = child
+            .get("message")
+            .and_then(Value::as_str)
+            .unwrap_or(&text);
+        suggestions.extend(
+            child
+                .get("spans")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(|span| suggestion_from_span(span, child_text)),
+        );
+    }
+
+    let field = |key: &str| primary.get(key).and_then(Value::as_u64).map(|v| v as u32);
+    Some(CompilationError {
+        file: primary.get("file_name")?.as_str()?.to_string(),
+        line: field("line_start")?,
+        column: field("column_start"),
+        message: text,
+        severity,
+        code: message
+            .pointer("/code/code")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        end_line: field("line_end"),
+        end_column: field("column_end"),
+        rendered: message
+            .get("rendered")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        suggestions,
+    })
+}
+
+fn suggestion_from_span(span: &Value, message: &str) -> Option<SuggestedFix> {
+    let replacement = span.get("suggested_replacement")?.as_str()?.to_string();
+    let applicability = span
+        .get("suggestion_applicability")
+        .and_then(Value::as_str)
+        .unwrap_or("Unspecified")
+        .to_string();
+    Some(SuggestedFix {
+        message: message.to_string(),
+        file: span.get("file_name")?.as_str()?.to_string(),
+        byte_start: span.get("byte_start")?.as_u64()? as usize,
+        byte_end: span.get("byte_end")?.as_u64()? as usize,
+        line: span.get("line_start")?.as_u64()? as u32,
+        machine_applicable: applicability == "MachineApplicable",
+        applicability,
+        replacement,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parses_diagnostic_with_machine_applicable_suggestion() {
+        let line = json!({
+            "reason": "compiler-message",
+            "message": {
+                "message": "unused import: `std::fmt`",
+                "level": "warning",
+                "code": { "code": "unused_imports" },
+                "rendered": "warning: unused import",
+                "spans": [{
+                    "file_name": "src/lib.rs", "is_primary": true,
+                    "byte_start": 4, "byte_end": 12,
+                    "line_start": 1, "line_end": 1, "column_start": 5, "column_end": 13,
+                    "suggested_replacement": null
+                }],
+                "children": [{
+                    "message": "remove the unused import",
+                    "level": "help",
+                    "spans": [{
+                        "file_name": "src/lib.rs", "is_primary": true,
+                        "byte_start": 0, "byte_end": 14,
+                        "line_start": 1, "line_end": 1, "column_start": 1, "column_end": 15,
+                        "suggested_replacement": "",
+                        "suggestion_applicability": "MachineApplicable"
+                    }]
+                }]
+            }
+        })
+        .to_string();
+
+        let diagnostic = parse_cargo_message(&line).unwrap();
+        assert_eq!(diagnostic.file, "src/lib.rs");
+        assert_eq!((diagnostic.line, diagnostic.column), (1, Some(5)));
+        assert_eq!(diagnostic.code.as_deref(), Some("unused_imports"));
+        assert_eq!(diagnostic.suggestions.len(), 1);
+        assert!(diagnostic.suggestions[0].machine_applicable);
+        assert_eq!(
+            diagnostic.suggestions[0].message,
+            "remove the unused import"
+        );
+
+        let artifact = json!({ "reason": "compiler-artifact" }).to_string();
+        assert!(parse_cargo_message(&artifact).is_none());
+    }
+}
@@ -1,3 +1,7 @@
 pub mod auto_compiler;
+pub mod diagnostics;
+pub mod watch;
 
 pub use auto_compiler::*;
+pub use diagnostics::*;
+pub use watch::*;
@@ -0,0 +1,220 @@
+use super::{AutoCompiler, BuildTrigger, CompilationResult};
+use crate::fixer::AutoFixer;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+use tauri::{AppHandle, Emitter, Manager};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+fn default_debounce_ms() -> u64 {
+    750
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchConfig {
+    /// Apply rustc's machine-applicable suggestions after each check.
+    #[serde(default)]
+    pub auto_fix: bool,
+    /// Quiet period after the last change before a check starts.
+    #[serde(default = "default_debounce_ms")]
+    pub debounce_ms: u64,
+}
+
+impl Default for WatchConfig {
+    fn default() -> Self {
+        Self {
+            auto_fix: false,
+            debounce_ms: default_debounce_ms(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchStatus {
+    pub active: bool,
+    pub config: Option<WatchConfig>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub last_change_at: Option<DateTime<Utc>>,
+    pub builds_triggered: u64,
+}
+
+pub struct WatchSession {
+    config: WatchConfig,
+    started_at: DateTime<Utc>,
+    last_change_at: Option<DateTime<Utc>>,
+    builds_triggered: u64,
+    stop: Arc<AtomicBool>,
+}
+
+type SourceSnapshot = BTreeMap<PathBuf, (SystemTime, u64)>;
+
+impl AutoCompiler {
+    /// Starts watching the source tree, replacing any running watch.
+    pub fn start_watch(self: &Arc<Self>, app: AppHandle, config: WatchConfig) -> WatchStatus {
+        self.stop_watch();
+
+        let stop = Arc::new(AtomicBool::new(false));
+        *self.watch.write() = Some(WatchSession {
+            config: config.clone(),
+            started_at: Utc::now(),
+            last_change_at: None,
+            builds_triggered: 0,
+            stop: stop.clone(),
+        });
+        tauri::async_runtime::spawn(watch_loop(Arc::clone(self), app, config, stop));
+
+        self.watch_status()
+    }
+
+    pub fn stop_watch(&self) -> WatchStatus {
+        if let Some(session) = self.watch.write().take() {
+            session.stop.store(true, Ordering::SeqCst);
+        }
+        self.watch_status()
+    }
+
+    pub fn watch_status(&self) -> WatchStatus {
+        match self.watch.read().as_ref() {
+            Some(session) => WatchStatus {
+                active: true,
+                config: Some(session.config.clone()),
+                started_at: Some(session.started_at),
+                last_change_at: session.last_change_at,
+                builds_triggered: session.builds_triggered,
+            },
+            None => WatchStatus {
+                active: false,
+                config: None,
+                started_at: None,
+                last_change_at: None,
+                builds_triggered: 0,
+            },
+        }
+    }
+
+    /// Queues an incremental check behind any running build. Changes that
+    /// arrive while one is already waiting are covered by it.
+    fn queue_watch_build(self: &Arc<Self>, app: &AppHandle, auto_fix: bool) {
+        if self.watch_build_queued.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        if let Some(session) = self.watch.write().as_mut() {
+            session.builds_triggered += 1;
+        }
+
+        let compiler = Arc::clone(self);
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            match compiler.run_build(BuildTrigger::Watch, Some(&app)).await {
+                Ok(result) if auto_fix => compiler.apply_machine_fixes(&app, &result),
+                Ok(_) => {}
+                Err(err) => eprintln!("Watch build failed: {err}"),
+            }
+        });
+    }
+
+    fn apply_machine_fixes(&self, app: &AppHandle, result: &CompilationResult) {
+        let Some(fixer) = app.try_state::<Arc<AutoFixer>>() else {
+            return;
+        };
+        let diagnostics: Vec<_> = result
+            .errors
+            .iter()
+            .chain(&result.warnings)
+            .cloned()
+            .collect();
+        let build_started =
+            result.timestamp - chrono::Duration::milliseconds(result.duration_ms as i64);
+
+        let applied =
+            fixer.apply_suggestions(&self.project_root, &diagnostics, build_started.into());
+        if !applied.is_empty() {
+            let _ = app.emit("compiler_fixes_applied", &applied);
+        }
+    }
+}
+
+async fn watch_loop(
+    compiler: Arc<AutoCompiler>,
+    app: AppHandle,
+    config: WatchConfig,
+    stop: Arc<AtomicBool>,
+) {
+    let root = compiler.project_root.clone();
+    let debounce = Duration::from_millis(config.debounce_ms);
+    let mut snapshot = scan_sources(root.clone()).await;
+    let mut changed_at: Option<Instant> = None;
+
+    while !stop.load(Ordering::SeqCst) {
+        tokio::time::sleep(POLL_INTERVAL).await;
+
+        let current = scan_sources(root.clone()).await;
+        if current != snapshot {
+            snapshot = current;
+            changed_at = Some(Instant::now());
+            if let Some(session) = compiler.watch.write().as_mut() {
+                session.last_change_at = Some(Utc::now());
+            }
+        }
+
+        if changed_at.is_some_and(|at| at.elapsed() >= debounce) {
+            changed_at = None;
+            compiler.queue_watch_build(&app, config.auto_fix);
+        }
+    }
+}
+
+async fn scan_sources(root: PathBuf) -> SourceSnapshot {
+    tokio::task::spawn_blocking(move || snapshot_sources(&root))
+        .await
+        .unwrap_or_default()
+}
+
+/// Modification times of the manifest, build script and every `.rs` file
+/// under `src/`. `target/` is outside `src/`, so build output never counts.
+fn snapshot_sources(root: &Path) -> SourceSnapshot {
+    let mut files = SourceSnapshot::new();
+    let mut record = |path: PathBuf, metadata: fs::Metadata| {
+        if let Ok(modified) = metadata.modified() {
+            files.insert(path, (modified, metadata.len()));
+        }
+    };
+
+    for name in ["Cargo.toml", "build.rs"] {
+        let path = root.join(name);
+        if let Ok(metadata) = fs::metadata(&path) {
+            record(path, metadata);
+        }
+    }
+
+    let mut dirs = vec![root.join("src")];
+    while let Some(dir) = dirs.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            let hidden = entry.file_name().to_string_lossy().starts_with('.');
+            if metadata.is_dir() {
+                if !hidden {
+                    dirs.push(path);
+                }
+            } else if path.extension().is_some_and(|ext| ext == "rs") {
+                record(path, metadata);
+            }
+        }
+    }
+
+    files
+}
@@ -1,9 +1,23 @@
+use super::privacy_mode::{sync_privacy_mode, SharedPrivacyMode};
 use super::settings_manager::{
     SettingsChange, SettingsExport, SettingsManager, SettingsProfile, SharedSettingsManager,
 };
 use super::settings_schema::{SettingMetadata, SettingType, UniversalSettings};
 use serde_json::json;
 use std::collections::HashMap;
+use tauri::{AppHandle, Manager};
+
+/// Keeps the live privacy mode in step with settings written through the
+/// generic settings commands.
+fn sync_privacy_settings(app: &AppHandle, manager: &SettingsManager) {
+    if let Some(privacy) = app.try_state::<SharedPrivacyMode>() {
+        sync_privacy_mode(
+            app,
+            &privacy,
+            &manager.get_all_settings().data_privacy.privacy_mode,
+        );
+    }
+}
 
 #[tauri::command]
 pub async fn get_all_settings(
@@ -15,6 +29,7 @@ pub async fn get_all_settings(
 
 #[tauri::command]
 pub async fn update_setting(
+    app: AppHandle,
     settings: tauri::State<'_, SharedSettingsManager>,
     category: String,
     key: String,
@@ -23,27 +38,37 @@ pub async fn update_setting(
     let mut manager = settings.write().await;
     manager
         .update_setting(category, key, value)
-        .map_err(|e| e.to_string())
+        .map_err(|e| e.to_string())?;
+    sync_privacy_settings(&app, &manager);
+    Ok(())
 }
 
 #[tauri::command]
 pub async fn bulk_update_settings(
+    app: AppHandle,
     settings: tauri::State<'_, SharedSettingsManager>,
     changes: HashMap<String, HashMap<String, serde_json::Value>>,
 ) -> Result<(), String> {
     let mut manager = settings.write().await;
     manager
         .bulk_update_settings(changes)
-        .map_err(|e| e.to_string())
+        .map_err(|e| e.to_string())?;
+    sync_privacy_settings(&app, &manager);
+    Ok(())
 }
 
 #[tauri::command]
 pub async fn reset_config_settings(
+    app: AppHandle,
     settings: tauri::State<'_, SharedSettingsManager>,
     category: Option<String>,
 ) -> Result<(), String> {
     let mut manager = settings.write().await;
-    manager.reset_settings(category).map_err(|e| e.to_string())
+    manager
+        .reset_settings(category)
+        .map_err(|e| e.to_string())?;
+    sync_privacy_settings(&app, &manager);
+    Ok(())
 }
 
 #[tauri::command]
@@ -59,11 +84,14 @@ pub async fn export_config_settings(
 
 #[tauri::command]
 pub async fn import_config_settings(
+    app: AppHandle,
     settings: tauri::State<'_, SharedSettingsManager>,
     export: SettingsExport,
 ) -> Result<(), String> {
     let mut manager = settings.write().await;
-    manager.import_settings(export).map_err(|e| e.to_string())
+    manager.import_settings(export).map_err(|e| e.to_string())?;
+    sync_privacy_settings(&app, &manager);
+    Ok(())
 }
 
 #[tauri::command]
@@ -85,11 +113,14 @@ pub async fn create_settings_profile(
 
 #[tauri::command]
 pub async fn load_settings_profile(
+    app: AppHandle,
     settings: tauri::State<'_, SharedSettingsManager>,
     name: String,
 ) -> Result<(), String> {
     let mut manager = settings.write().await;
-    manager.load_profile(name).map_err(|e| e.to_string())
+    manager.load_profile(name).map_err(|e| e.to_string())?;
+    sync_privacy_settings(&app, &manager);
+    Ok(())
 }
 
 #[tauri::command]
@@ -497,6 +528,15 @@ fn generate_settings_schema() -> Vec<SettingMetadata> {
             default_value: json!(true),
             constraints: None,
         },
+        SettingMetadata {
+            key: "privacyMode".to_string(),
+            category: "dataPrivacy".to_string(),
+            label: "Privacy Mode".to_string(),
+            description: "Mask balances and values while screen-sharing".to_string(),
+            setting_type: SettingType::Object,
+            default_value: json!({ "enabled": false, "mode": "hide", "walletOverrides": {} }),
+            constraints: None,
+        },
         SettingMetadata {
             key: "dataRetentionDays".to_string(),
             category: "dataPrivacy".to_string(),
@@ -0,0 +1,807 @@
+use crate::config::environment::{active_environment, tag_data_dir};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+
+/// Kept in the config directory rather than the data root, so it survives
+/// the data root moving.
+const DATA_ROOT_FILE: &str = "data_root.json";
+const PROBE_FILE: &str = ".eclipse_write_probe";
+/// Gives the frontend time to show the result before the app restarts.
+const RESTART_DELAY: Duration = Duration::from_millis(1500);
+
+#[derive(Debug, thiserror::Error)]
+pub enum DataRootError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+    #[error("Path resolution error: {0}")]
+    Path(#[from] tauri::Error),
+    #[error("Invalid target directory: {0}")]
+    InvalidTarget(String),
+    #[error("Checksum mismatch for {0}; the file changed or was damaged during the move")]
+    ChecksumMismatch(String),
+    #[error("A data directory migration is already running")]
+    MigrationInProgress,
+    #[error("Data directory unavailable: {0}")]
+    Unavailable(String),
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DataRootConfig {
+    #[serde(default)]
+    custom_root: Option<PathBuf>,
+    /// Files left behind by the last migration, deleted on the next start
+    /// once the new root is live.
+    #[serde(default)]
+    pending_cleanup: Option<PendingCleanup>,
+    /// A move scheduled by `migrate_data_directory`, carried out on the next
+    /// start before any database is opened.
+    #[serde(default)]
+    pending_migration: Option<PendingMigration>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PendingMigration {
+    from: PathBuf,
+    to: PathBuf,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PendingCleanup {
+    dir: PathBuf,
+    files: Vec<PathBuf>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataRootStatus {
+    pub active_dir: PathBuf,
+    pub default_dir: PathBuf,
+    pub configured_dir: Option<PathBuf>,
+    /// Set when the configured directory could not be opened at startup. The
+    /// session then runs on the default directory until it is recovered.
+    pub unavailable_reason: Option<String>,
+    pub migrating: bool,
+    /// Set when the move scheduled for this start failed. The session runs
+    /// on the old directory, which was left untouched.
+    pub migration_error: Option<String>,
+    /// The last update of each phase of the move run at this start. It ran
+    /// before the window opened, so no events were sent for it.
+    pub migration_progress: Vec<DataMigrationProgress>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MigrationPhase {
+    Copying,
+    Verifying,
+    RollingBack,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataMigrationProgress {
+    pub phase: MigrationPhase,
+    pub files_done: usize,
+    pub files_total: usize,
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+    pub current_file: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataMigrationReport {
+    pub from: PathBuf,
+    pub to: PathBuf,
+    pub files: usize,
+    pub bytes: u64,
+    pub restart_scheduled: bool,
+}
+
+/// A file under the data root, relative to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct DataFile {
+    path: PathBuf,
+    size: u64,
+}
+
+/// The directory every database, cache, historical dataset, log and backup
+/// lives under. Resolved once in setup; a migration takes effect on restart.
+pub struct DataRoot {
+    active_dir: PathBuf,
+    default_dir: PathBuf,
+    configured_dir: Option<PathBuf>,
+    unavailable_reason: Option<String>,
+    migrating: AtomicBool,
+    migration_error: Option<String>,
+    migration_progress: Vec<DataMigrationProgress>,
+}
+
+/// Shared path provider. Modules resolve their files through
+/// `app.app_data_root()` instead of `app.path().app_data_dir()`, so they
+/// follow the configured data root.
+pub trait DataPaths<R: Runtime> {
+    fn app_data_root(&self) -> tauri::Result<PathBuf>;
+}
+
+impl<R: Runtime, M: Manager<R>> DataPaths<R> for M {
+    fn app_data_root(&self) -> tauri::Result<PathBuf> {
+        if let Some(root) = self.try_state::<DataRoot>() {
+            return Ok(root.active_dir.clone());
+        }
+
+        // Before setup has managed the root, resolve it from the config file.
+        let default_dir = self.path().app_data_dir()?;
+        let config = load_config(&config_file(self)?);
+        let root = match config.custom_root {
+            Some(dir) => check_available(&dir).map(|_| dir).map_err(|reason| {
+                tauri::Error::Io(io::Error::new(io::ErrorKind::NotFound, reason))
+            })?,
+            None => default_dir,
+        };
+        Ok(active_environment().data_dir(&root))
+    }
+}
+
+impl DataRoot {
+    /// Resolves the data root for this session. An unavailable custom root is
+    /// not fatal: the session falls back to the default directory and reports
+    /// the problem through `get_data_root_status` for the recovery dialog.
+    /// Outside mainnet the session runs on that environment's directory
+    /// under the root instead.
+    ///
+    /// A scheduled move runs here, before any module opens a database, so
+    /// the files are copied and verified while nothing writes to them.
+    pub fn initialize<R: Runtime>(app: &AppHandle<R>) -> Result<Self, DataRootError> {
+        let default_dir = app.path().app_data_dir()?;
+        let config_path = config_file(app)?;
+        let mut config = load_config(&config_path);
+
+        let (mut root_dir, unavailable_reason) = resolve_root(&default_dir, &config);
+        if unavailable_reason.is_none() {
+            if let Some(pending) = config.pending_cleanup.take() {
+                if pending.dir != root_dir {
+                    remove_files(&pending.dir, &pending.files);
+                }
+                if let Err(e) = save_config(&config_path, &config) {
+                    eprintln!("Failed to clear pending data cleanup: {}", e);
+                }
+            }
+        }
+
+        let mut migration_error = None;
+        let mut migration_progress = Vec::new();
+        if let Some(pending) = config.pending_migration.take() {
+            let result = if pending.from == root_dir && unavailable_reason.is_none() {
+                apply_migration(
+                    &pending,
+                    &default_dir,
+                    &config_path,
+                    &config,
+                    &mut migration_progress,
+                )
+            } else {
+                Err(DataRootError::Unavailable(format!(
+                    "{} is no longer the active data directory",
+                    pending.from.display()
+                )))
+            };
+            match result {
+                Ok(next) => {
+                    config = next;
+                    root_dir = pending.to;
+                }
+                Err(e) => {
+                    eprintln!("Data directory migration failed: {}", e);
+                    migration_error = Some(e.to_string());
+                    if let Err(e) = save_config(&config_path, &config) {
+                        eprintln!("Failed to clear the failed data migration: {}", e);
+                    }
+                }
+            }
+        }
+
+        let environment = active_environment();
+        let active_dir = environment.data_dir(&root_dir);
+        fs::create_dir_all(&active_dir)?;
+        tag_data_dir(&active_dir, &environment)?;
+
+        Ok(Self {
+            active_dir,
+            default_dir,
+            configured_dir: config.custom_root,
+            unavailable_reason,
+            migrating: AtomicBool::new(false),
+            migration_error,
+            migration_progress,
+        })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.active_dir
+    }
+
+    pub fn status(&self) -> DataRootStatus {
+        DataRootStatus {
+            active_dir: self.active_dir.clone(),
+            default_dir: self.default_dir.clone(),
+            configured_dir: self.configured_dir.clone(),
+            unavailable_reason: self.unavailable_reason.clone(),
+            migrating: self.migrating.load(Ordering::SeqCst),
+            migration_error: self.migration_error.clone(),
+            migration_progress: self.migration_progress.clone(),
+        }
+    }
+}
+
+fn config_file<R: Runtime, M: Manager<R>>(app: &M) -> tauri::Result<PathBuf> {
+    Ok(app.path().app_config_dir()?.join(DATA_ROOT_FILE))
+}
+
+fn load_config(path: &Path) -> DataRootConfig {
+    match fs::read_to_string(path) {
+        Ok(raw) => serde_json::from_str(&raw).unwrap_or_else(|e| {
+            eprintln!("Ignoring unreadable data root config: {}", e);
+            DataRootConfig::default()
+        }),
+        Err(_) => DataRootConfig::default(),
+    }
+}
+
+/// Writes through a temporary file and a rename, so the root switches in a
+/// single step.
+fn save_config(path: &Path, config: &DataRootConfig) -> Result<(), DataRootError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, serde_json::to_vec_pretty(config)?)?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+fn resolve_root(default_dir: &Path, config: &DataRootConfig) -> (PathBuf, Option<String>) {
+    match &config.custom_root {
+        Some(dir) => match check_available(dir) {
+            Ok(()) => (dir.clone(), None),
+            Err(reason) => (default_dir.to_path_buf(), Some(reason)),
+        },
+        None => (default_dir.to_path_buf(), None),
+    }
+}
+
+/// A custom root must already exist. It is never created here: on an
+/// unmounted drive that would silently put the data on the mount point's
+/// parent filesystem.
+fn check_available(dir: &Path) -> Result<(), String> {
+    if !dir.is_dir() {
+        return Err(format!(
+            "{} does not exist or is not mounted",
+            dir.display()
+        ));
+    }
+    let probe = dir.join(PROBE_FILE);
+    fs::write(&probe, b"ok").map_err(|e| format!("{} is not writable: {}", dir.display(), e))?;
+    let _ = fs::remove_file(probe);
+    Ok(())
+}
+
+fn list_files(root: &Path) -> io::Result<Vec<DataFile>> {
+    fn walk(root: &Path, dir: &Path, files: &mut Vec<DataFile>) -> io::Result<()> {
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            let metadata = fs::symlink_metadata(&path)?;
+            if metadata.is_dir() {
+                walk(root, &path, files)?;
+            } else if metadata.is_file() {
+                let relative = path.strip_prefix(root).unwrap_or(&path).to_path_buf();
+                files.push(DataFile {
+                    path: relative,
+                    size: metadata.len(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    let mut files = Vec::new();
+    walk(root, root, &mut files)?;
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(files)
+}
+
+fn validate_target(from: &Path, to: &Path, files: &[DataFile]) -> Result<(), DataRootError> {
+    if !to.is_absolute() {
+        return Err(DataRootError::InvalidTarget(
+            "the path must be absolute".to_string(),
+        ));
+    }
+    let canonical_to = if to.exists() {
+        to.canonicalize()?
+    } else {
+        let parent = to
+            .parent()
+            .filter(|parent| parent.is_dir())
+            .ok_or_else(|| {
+                DataRootError::InvalidTarget(format!(
+                    "the parent of {} does not exist",
+                    to.display()
+                ))
+            })?;
+        let name = to.file_name().ok_or_else(|| {
+            DataRootError::InvalidTarget(format!("{} has no directory name", to.display()))
+        })?;
+        parent.canonicalize()?.join(name)
+    };
+    let canonical_from = from.canonicalize()?;
+    if canonical_to.starts_with(&canonical_from) || canonical_from.starts_with(&canonical_to) {
+        return Err(DataRootError::InvalidTarget(
+            "the new directory cannot contain or sit inside the current one".to_string(),
+        ));
+    }
+    if to.exists() && !to.is_dir() {
+        return Err(DataRootError::InvalidTarget(format!(
+            "{} is not a directory",
+            to.display()
+        )));
+    }
+    if let Some(existing) = files.iter().find(|file| to.join(&file.path).exists()) {
+        return Err(DataRootError::InvalidTarget(format!(
+            "{} already exists in the new directory",
+            existing.path.display()
+        )));
+    }
+    Ok(())
+}
+
+fn file_sha256(path: &Path) -> io::Result<[u8; 32]> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut fs::File::open(path)?, &mut hasher)?;
+    Ok(hasher.finalize().into())
+}
+
+/// Copies every file to `to` and compares checksums of both copies. On any
+/// failure the copies are removed again and the original is left untouched.
+fn copy_and_verify(
+    from: &Path,
+    to: &Path,
+    files: &[DataFile],
+    on_progress: &mut dyn FnMut(&DataMigrationProgress),
+) -> Result<(), DataRootError> {
+    let created_target = !to.exists();
+    let mut progress = DataMigrationProgress {
+        phase: MigrationPhase::Copying,
+        files_done: 0,
+        files_total: files.len(),
+        bytes_done: 0,
+        bytes_total: files.iter().map(|file| file.size).sum(),
+        current_file: None,
+    };
+
+    let result = copy_files(from, to, files, &mut progress, on_progress)
+        .and_then(|_| verify_files(from, to, files, &mut progress, on_progress));
+
+    if result.is_ok() {
+        progress.current_file = None;
+        on_progress(&progress);
+    } else {
+        progress.phase = MigrationPhase::RollingBack;
+        progress.current_file = None;
+        on_progress(&progress);
+        remove_files(to, &relative_paths(files));
+        if created_target {
+            let _ = fs::remove_dir(to);
+        }
+    }
+    result
+}
+
+fn copy_files(
+    from: &Path,
+    to: &Path,
+    files: &[DataFile],
+    progress: &mut DataMigrationProgress,
+    on_progress: &mut dyn FnMut(&DataMigrationProgress),
+) -> Result<(), DataRootError> {
+    fs::create_dir_all(to)?;
+    for file in files {
+        progress.current_file = Some(file.path.display().to_string());
+        on_progress(progress);
+        let destination = to.join(&file.path);
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(from.join(&file.path), &destination)?;
+        progress.files_done += 1;
+        progress.bytes_done += file.size;
+    }
+    Ok(())
+}
+
+/// Hashes the source again rather than trusting the listing, so a file that
+/// changed mid-copy fails verification instead of moving stale.
+fn verify_files(
+    from: &Path,
+    to: &Path,
+    files: &[DataFile],
+    progress: &mut DataMigrationProgress,
+    on_progress: &mut dyn FnMut(&DataMigrationProgress),
+) -> Result<(), DataRootError> {
+    progress.phase = MigrationPhase::Verifying;
+    progress.files_done = 0;
+    progress.bytes_done = 0;
+    for file in files {
+        progress.current_file = Some(file.path.display().to_string());
+        on_progress(progress);
+        if file_sha256(&from.join(&file.path))? != file_sha256(&to.join(&file.path))? {
+            return Err(DataRootError::ChecksumMismatch(
+                file.path.display().to_string(),
+            ));
+        }
+        progress.files_done += 1;
+        progress.bytes_done += file.size;
+    }
+    Ok(())
+}
+
+fn relative_paths(files: &[DataFile]) -> Vec<PathBuf> {
+    files.iter().map(|file| file.path.clone()).collect()
+}
+
+/// Removes the given files under `root` and any directories left empty.
+fn remove_files(root: &Path, files: &[PathBuf]) {
+    let mut dirs = Vec::new();
+    for file in files {
+        let path = root.join(file);
+        if let Err(e) = fs::remove_file(&path) {
+            if e.kind() != io::ErrorKind::NotFound {
+                eprintln!("Failed to remove {}: {}", path.display(), e);
+            }
+        }
+        let mut parent = file.parent();
+        while let Some(dir) = parent.filter(|dir| !dir.as_os_str().is_empty()) {
+            dirs.push(root.join(dir));
+            parent = dir.parent();
+        }
+    }
+    // Deepest first, so parents are empty by the time they are reached.
+    dirs.sort_by(|a, b| {
+        b.components()
+            .count()
+            .cmp(&a.components().count())
+            .then_with(|| a.cmp(b))
+    });
+    dirs.dedup();
+    for dir in dirs {
+        let _ = fs::remove_dir(dir);
+    }
+}
+
+/// Copies and verifies a scheduled move, then switches the root. Returns the
+/// saved config; on failure nothing is saved and the copies are removed.
+/// The last update of each phase is kept in `log`.
+fn apply_migration(
+    pending: &PendingMigration,
+    default_dir: &Path,
+    config_path: &Path,
+    config: &DataRootConfig,
+    log: &mut Vec<DataMigrationProgress>,
+) -> Result<DataRootConfig, DataRootError> {
+    let files = list_files(&pending.from)?;
+    validate_target(&pending.from, &pending.to, &files)?;
+
+    copy_and_verify(
+        &pending.from,
+        &pending.to,
+        &files,
+        &mut |progress| match log.last_mut() {
+            Some(last) if last.phase == progress.phase => *last = progress.clone(),
+            _ => {
+                log.push(progress.clone());
+                eprintln!(
+                    "Data directory migration to {}: {:?} {} files",
+                    pending.to.display(),
+                    progress.phase,
+                    progress.files_total
+                );
+            }
+        },
+    )?;
+
+    let mut next = config.clone();
+    next.custom_root = (pending.to != default_dir).then(|| pending.to.clone());
+    next.pending_cleanup = Some(PendingCleanup {
+        dir: pending.from.clone(),
+        files: relative_paths(&files),
+    });
+    if let Err(e) = save_config(config_path, &next) {
+        remove_files(&pending.to, &relative_paths(&files));
+        return Err(e);
+    }
+    Ok(next)
+}
+
+/// Checks the target now, so an obvious mistake is reported before the
+/// restart, and records the move for the next start.
+fn schedule_migration<R: Runtime>(
+    app: &AppHandle<R>,
+    from: &Path,
+    to: &Path,
+) -> Result<DataMigrationReport, DataRootError> {
+    let files = list_files(from)?;
+    validate_target(from, to, &files)?;
+
+    let config_path = config_file(app)?;
+    let mut config = load_config(&config_path);
+    config.pending_migration = Some(PendingMigration {
+        from: from.to_path_buf(),
+        to: to.to_path_buf(),
+    });
+    save_config(&config_path, &config)?;
+
+    Ok(DataMigrationReport {
+        from: from.to_path_buf(),
+        to: to.to_path_buf(),
+        files: files.len(),
+        bytes: files.iter().map(|file| file.size).sum(),
+        restart_scheduled: true,
+    })
+}
+
+pub(crate) fn schedule_restart(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(RESTART_DELAY).await;
+        crate::errors::request_restart(&app);
+    });
+}
+
+#[tauri::command]
+pub fn get_data_root_status(root: State<'_, DataRoot>) -> Result<DataRootStatus, String> {
+    Ok(root.status())
+}
+
+/// Moves the data root to `new_path`. The move is scheduled and the app
+/// restarts; the next start copies and verifies the files before any
+/// database opens, then switches the root. The old files are deleted on the
+/// start after that, once the new root has opened. A failed move is reported
+/// through `get_data_root_status`.
+#[tauri::command]
+pub async fn migrate_data_directory(
+    app: AppHandle,
+    root: State<'_, DataRoot>,
+    new_path: String,
+) -> Result<DataMigrationReport, String> {
+    if let Some(reason) = &root.unavailable_reason {
+        return Err(DataRootError::Unavailable(reason.clone()).to_string());
+    }
+    // The root holds every environment's data, but only mainnet runs on it
+    // directly.
+    if !active_environment().is_mainnet() {
+        return Err(DataRootError::InvalidTarget(
+            "switch back to mainnet before moving the data directory".to_string(),
+        )
+        .to_string());
+    }
+    if root.migrating.swap(true, Ordering::SeqCst) {
+        return Err(DataRootError::MigrationInProgress.to_string());
+    }
+
+    let from = root.active_dir.clone();
+    let to = PathBuf::from(new_path.trim());
+    let handle = app.clone();
+    let result =
+        tauri::async_runtime::spawn_blocking(move || schedule_migration(&handle, &from, &to))
+            .await
+            .map_err(|e| e.to_string())
+            .and_then(|result| result.map_err(|e| e.to_string()));
+
+    match result {
+        Ok(report) => {
+            // Left set, so no second migration starts before the restart.
+            schedule_restart(app);
+            Ok(report)
+        }
+        Err(e) => {
+            root.migrating.store(false, Ordering::SeqCst);
+            let _ = app.emit("data_migration_failed", &e);
+            Err(e)
+        }
+    }
+}
+
+/// Recovery: restarts on the configured directory once it is reachable again.
+#[tauri::command]
+pub fn retry_data_directory(app: AppHandle, root: State<'_, DataRoot>) -> Result<(), String> {
+    let dir = root
+        .configured_dir
+        .as_ref()
+        .ok_or_else(|| "No custom data directory is configured".to_string())?;
+    check_available(dir)?;
+    schedule_restart(app);
+    Ok(())
+}
+
+/// Recovery: forgets the configured directory and restarts on the default
+/// one. Nothing in the configured directory is touched.
+#[tauri::command]
+pub fn reset_data_directory(app: AppHandle, root: State<'_, DataRoot>) -> Result<(), String> {
+    if root.migrating.load(Ordering::SeqCst) {
+        return Err(DataRootError::MigrationInProgress.to_string());
+    }
+    let config_path = config_file(&app).map_err(|e| e.to_string())?;
+    let mut config = load_config(&config_path);
+    config.custom_root = None;
+    save_config(&config_path, &config).map_err(|e| e.to_string())?;
+    schedule_restart(app);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(root: &Path, relative: &str, contents: &str) {
+        let path = root.join(relative);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn modules_resolve_data_through_data_root() {
+        let src = Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+        let offenders: Vec<_> = list_files(&src)
+            .unwrap()
+            .into_iter()
+            .filter(|file| file.path.extension().is_some_and(|ext| ext == "rs"))
+            .filter(|file| file.path != Path::new("config/data_root.rs"))
+            .filter(|file| {
+                fs::read_to_string(src.join(&file.path))
+                    .unwrap()
+                    .contains(".app_data_dir()")
+            })
+            .map(|file| file.path.display().to_string())
+            .collect();
+        assert!(
+            offenders.is_empty(),
+            "use DataPaths::app_data_root instead of app_data_dir in {:?}",
+            offenders
+        );
+    }
+
+    #[test]
+    fn unavailable_custom_root_falls_back_to_default() {
+        let default_dir = tempfile::tempdir().unwrap();
+        let custom = tempfile::tempdir().unwrap();
+        let missing = custom.path().join("unmounted");
+
+        let config = DataRootConfig {
+            custom_root: Some(missing.clone()),
+            pending_cleanup: None,
+            pending_migration: None,
+        };
+        let (active, reason) = resolve_root(default_dir.path(), &config);
+        assert_eq!(active, default_dir.path());
+        assert!(reason.unwrap().contains("does not exist"));
+        assert!(!missing.exists());
+
+        let config = DataRootConfig {
+            custom_root: Some(custom.path().to_path_buf()),
+            pending_cleanup: None,
+            pending_migration: None,
+        };
+        let (active, reason) = resolve_root(default_dir.path(), &config);
+        assert_eq!(active, custom.path());
+        assert!(reason.is_none());
+    }
+
+    #[test]
+    fn copies_verifies_and_cleans_up() {
+        let from = tempfile::tempdir().unwrap();
+        let parent = tempfile::tempdir().unwrap();
+        let to = parent.path().join("data");
+        write(from.path(), "trading.db", "orders");
+        write(from.path(), "historical/sol/1m.bin", "candles");
+        write(from.path(), "logs/app.log", "started");
+
+        let files = list_files(from.path()).unwrap();
+        assert_eq!(files.len(), 3);
+        validate_target(from.path(), &to, &files).unwrap();
+
+        let mut phases = Vec::new();
+        copy_and_verify(from.path(), &to, &files, &mut |progress| {
+            phases.push(progress.phase)
+        })
+        .unwrap();
+        assert!(phases.contains(&MigrationPhase::Verifying));
+        assert_eq!(
+            fs::read_to_string(to.join("historical/sol/1m.bin")).unwrap(),
+            "candles"
+        );
+
+        remove_files(from.path(), &relative_paths(&files));
+        assert!(from.path().exists());
+        assert_eq!(fs::read_dir(from.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn scheduled_migration_switches_root_and_records_cleanup() {
+        let from = tempfile::tempdir().unwrap();
+        let parent = tempfile::tempdir().unwrap();
+        let config_dir = tempfile::tempdir().unwrap();
+        let config_path = config_dir.path().join(DATA_ROOT_FILE);
+        write(from.path(), "trading.db", "orders");
+        write(from.path(), "trading.db-wal", "frames");
+
+        let pending = PendingMigration {
+            from: from.path().to_path_buf(),
+            to: parent.path().join("data"),
+        };
+        let mut log = Vec::new();
+        let config = apply_migration(
+            &pending,
+            from.path(),
+            &config_path,
+            &DataRootConfig::default(),
+            &mut log,
+        )
+        .unwrap();
+        assert_eq!(config.custom_root.as_ref(), Some(&pending.to));
+        let phases: Vec<_> = log.iter().map(|progress| progress.phase).collect();
+        assert_eq!(phases, [MigrationPhase::Copying, MigrationPhase::Verifying]);
+        assert_eq!(log[1].files_done, 2);
+        assert_eq!(log[1].bytes_done, log[1].bytes_total);
+        assert_eq!(config.pending_cleanup.unwrap().files.len(), 2);
+        assert!(load_config(&config_path).pending_migration.is_none());
+        assert_eq!(
+            fs::read_to_string(pending.to.join("trading.db-wal")).unwrap(),
+            "frames"
+        );
+
+        // The target now holds the files, so moving there again is refused
+        // and the config is left alone.
+        let saved = fs::read_to_string(&config_path).unwrap();
+        assert!(apply_migration(
+            &pending,
+            from.path(),
+            &config_path,
+            &DataRootConfig::default(),
+            &mut Vec::new(),
+        )
+        .is_err());
+        assert_eq!(fs::read_to_string(&config_path).unwrap(), saved);
+    }
+
+    #[test]
+    fn rejects_conflicting_or_nested_targets() {
+        let from = tempfile::tempdir().unwrap();
+        let to = tempfile::tempdir().unwrap();
+        write(from.path(), "trading.db", "orders");
+        write(to.path(), "trading.db", "other");
+        let files = list_files(from.path()).unwrap();
+
+        assert!(matches!(
+            validate_target(from.path(), to.path(), &files),
+            Err(DataRootError::InvalidTarget(_))
+        ));
+        assert!(matches!(
+            validate_target(from.path(), &from.path().join("nested"), &files),
+            Err(DataRootError::InvalidTarget(_))
+        ));
+        assert!(matches!(
+            validate_target(from.path(), Path::new("relative/dir"), &files),
+            Err(DataRootError::InvalidTarget(_))
+        ));
+    }
+}
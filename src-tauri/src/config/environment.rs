@@ -0,0 +1,418 @@
+//! Network environment: Solana mainnet, devnet or a custom cluster.
+//!
+//! Like the data root, the environment is kept in the config directory and
+//! resolved once at startup. Switching saves the choice and restarts, so RPC
+//! clients, provider endpoints and stores all come up on the same network.
+//! Data created outside mainnet lives in its own directory under the data
+//! root, so it never reaches mainnet analytics or tax reports.
+
+use crate::config::data_root::{schedule_restart, DataRoot};
+use crate::errors::AppError;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tauri::{AppHandle, Manager, Runtime, State};
+
+const ENVIRONMENT_FILE: &str = "environment.json";
+/// Written into the data directory of every non-mainnet environment.
+const ENVIRONMENT_MARKER: &str = ".eclipse_environment";
+/// Non-mainnet data directories live under this directory of the data root.
+const ENVIRONMENTS_DIR: &str = "environments";
+pub const MAINNET_RPC_URL: &str = "https://api.mainnet-beta.solana.com";
+pub const DEVNET_RPC_URL: &str = "https://api.devnet.solana.com";
+/// Mainnet provider URL prefixes and their devnet sandboxes.
+const DEVNET_SANDBOXES: &[(&str, &str)] = &[
+    ("https://api.helius.xyz", "https://api-devnet.helius.xyz"),
+    (
+        "https://mainnet.helius-rpc.com",
+        "https://devnet.helius-rpc.com",
+    ),
+    (
+        "wss://mainnet.helius-rpc.com",
+        "wss://devnet.helius-rpc.com",
+    ),
+];
+/// Providers without a sandbox, which keep serving mainnet data.
+const MAINNET_ONLY_PROVIDERS: &[&str] = &["birdeye", "jupiter"];
+
+static ACTIVE_ENVIRONMENT: OnceLock<EnvironmentConfig> = OnceLock::new();
+
+#[derive(Debug, thiserror::Error)]
+pub enum EnvironmentError {
+    #[error("invalid environment: {0}")]
+    Invalid(String),
+    #[error("switching environments restarts the app and must be confirmed")]
+    ConfirmationRequired,
+    #[error("io error: {0}")]
+    Io(#[from] io::Error),
+    #[error("serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+    #[error("path resolution error: {0}")]
+    Path(#[from] tauri::Error),
+}
+
+impl From<EnvironmentError> for AppError {
+    fn from(err: EnvironmentError) -> Self {
+        match err {
+            EnvironmentError::Invalid(message) => {
+                AppError::validation(message).with_code("invalid_environment")
+            }
+            EnvironmentError::ConfirmationRequired => {
+                AppError::validation(err.to_string()).with_code("environment_confirmation_required")
+            }
+            EnvironmentError::Io(err) => err.into(),
+            EnvironmentError::Serialization(err) => err.into(),
+            EnvironmentError::Path(err) => AppError::internal(err.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NetworkEnvironment {
+    #[default]
+    Mainnet,
+    Devnet,
+    /// Any other cluster, such as testnet or a local validator, reached
+    /// through `custom_rpc_url`.
+    Custom,
+}
+
+impl NetworkEnvironment {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NetworkEnvironment::Mainnet => "mainnet",
+            NetworkEnvironment::Devnet => "devnet",
+            NetworkEnvironment::Custom => "custom",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvironmentConfig {
+    pub environment: NetworkEnvironment,
+    #[serde(default)]
+    pub custom_rpc_url: Option<String>,
+}
+
+impl EnvironmentConfig {
+    fn new(
+        environment: NetworkEnvironment,
+        custom_rpc_url: Option<String>,
+    ) -> Result<Self, EnvironmentError> {
+        if environment != NetworkEnvironment::Custom {
+            return Ok(Self {
+                environment,
+                custom_rpc_url: None,
+            });
+        }
+        let url = custom_rpc_url
+            .map(|url| url.trim().to_string())
+            .filter(|url| url.starts_with("http://") || url.starts_with("https://"))
+            .ok_or_else(|| {
+                EnvironmentError::Invalid(
+                    "A custom environment needs an http(s) RPC URL".to_string(),
+                )
+            })?;
+        Ok(Self {
+            environment,
+            custom_rpc_url: Some(url),
+        })
+    }
+
+    pub fn is_mainnet(&self) -> bool {
+        self.environment == NetworkEnvironment::Mainnet
+    }
+
+    /// The Solana RPC of the environment. On mainnet a custom RPC saved in
+    /// the API config takes precedence over this.
+    pub fn rpc_url(&self) -> String {
+        match self.environment {
+            NetworkEnvironment::Mainnet => MAINNET_RPC_URL.to_string(),
+            NetworkEnvironment::Devnet => DEVNET_RPC_URL.to_string(),
+            NetworkEnvironment::Custom => self
+                .custom_rpc_url
+                .clone()
+                .unwrap_or_else(|| MAINNET_RPC_URL.to_string()),
+        }
+    }
+
+    /// `url` pointed at the provider's sandbox, where it has one.
+    pub fn provider_url(&self, url: &str) -> String {
+        if self.environment != NetworkEnvironment::Devnet {
+            return url.to_string();
+        }
+        DEVNET_SANDBOXES
+            .iter()
+            .find_map(|(mainnet, sandbox)| {
+                url.strip_prefix(mainnet)
+                    .map(|rest| format!("{}{}", sandbox, rest))
+            })
+            .unwrap_or_else(|| url.to_string())
+    }
+
+    /// Where the environment keeps its data under `root`.
+    pub fn data_dir(&self, root: &Path) -> PathBuf {
+        if self.is_mainnet() {
+            return root.to_path_buf();
+        }
+        root.join(ENVIRONMENTS_DIR).join(self.environment.as_str())
+    }
+
+    fn sandboxed_providers(&self) -> Vec<String> {
+        let sandboxed: &[&str] = match self.environment {
+            NetworkEnvironment::Mainnet => &[],
+            NetworkEnvironment::Devnet => &["solana_rpc", "helius"],
+            NetworkEnvironment::Custom => &["solana_rpc"],
+        };
+        sandboxed.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn mainnet_only_providers(&self) -> Vec<String> {
+        let mut providers: Vec<String> = match self.environment {
+            NetworkEnvironment::Mainnet => return Vec::new(),
+            NetworkEnvironment::Devnet => Vec::new(),
+            NetworkEnvironment::Custom => vec!["helius".to_string()],
+        };
+        providers.extend(MAINNET_ONLY_PROVIDERS.iter().map(|s| s.to_string()));
+        providers
+    }
+
+    fn indicator(&self) -> Option<String> {
+        match self.environment {
+            NetworkEnvironment::Mainnet => None,
+            NetworkEnvironment::Devnet => Some("DEVNET - test funds only".to_string()),
+            NetworkEnvironment::Custom => {
+                Some(format!("CUSTOM NETWORK ({}) - not mainnet", self.rpc_url()))
+            }
+        }
+    }
+}
+
+/// The environment this session runs in; mainnet until one is resolved.
+pub fn active_environment() -> EnvironmentConfig {
+    ACTIVE_ENVIRONMENT.get().cloned().unwrap_or_default()
+}
+
+/// The Solana RPC of the active environment.
+pub fn default_rpc_url() -> String {
+    active_environment().rpc_url()
+}
+
+/// `url` pointed at the provider's sandbox in the active environment.
+pub fn provider_url(url: &str) -> String {
+    active_environment().provider_url(url)
+}
+
+/// Resolves the session's environment from the config directory. Runs
+/// before the data root, which is namespaced by it.
+pub fn initialize_environment<R: Runtime>(
+    app: &AppHandle<R>,
+) -> Result<EnvironmentConfig, EnvironmentError> {
+    let config = load_config(&config_file(app)?);
+    let _ = ACTIVE_ENVIRONMENT.set(config.clone());
+    Ok(active_environment())
+}
+
+/// Marks a non-mainnet data directory with the environment it belongs to.
+pub(crate) fn tag_data_dir(dir: &Path, config: &EnvironmentConfig) -> io::Result<()> {
+    if config.is_mainnet() {
+        return Ok(());
+    }
+    fs::write(
+        dir.join(ENVIRONMENT_MARKER),
+        serde_json::to_vec_pretty(config)?,
+    )
+}
+
+fn config_file<R: Runtime, M: Manager<R>>(app: &M) -> tauri::Result<PathBuf> {
+    Ok(app.path().app_config_dir()?.join(ENVIRONMENT_FILE))
+}
+
+/// An unreadable config falls back to mainnet, the environment every
+/// install starts in.
+fn load_config(path: &Path) -> EnvironmentConfig {
+    match fs::read_to_string(path) {
+        Ok(raw) => serde_json::from_str(&raw).unwrap_or_else(|e| {
+            eprintln!("Ignoring unreadable environment config: {}", e);
+            EnvironmentConfig::default()
+        }),
+        Err(_) => EnvironmentConfig::default(),
+    }
+}
+
+fn save_config(path: &Path, config: &EnvironmentConfig) -> Result<(), EnvironmentError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, serde_json::to_vec_pretty(config)?)?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvironmentStatus {
+    pub active: EnvironmentConfig,
+    /// The environment saved for the next start.
+    pub selected: EnvironmentConfig,
+    pub restart_pending: bool,
+    pub is_mainnet: bool,
+    pub rpc_url: String,
+    pub data_dir: PathBuf,
+    pub sandboxed_providers: Vec<String>,
+    pub mainnet_only_providers: Vec<String>,
+    /// Banner text the frontend shows for as long as the app is off mainnet.
+    pub indicator: Option<String>,
+}
+
+fn status(
+    active: EnvironmentConfig,
+    selected: EnvironmentConfig,
+    data_dir: PathBuf,
+) -> EnvironmentStatus {
+    EnvironmentStatus {
+        restart_pending: selected != active,
+        is_mainnet: active.is_mainnet(),
+        rpc_url: active.rpc_url(),
+        data_dir,
+        sandboxed_providers: active.sandboxed_providers(),
+        mainnet_only_providers: active.mainnet_only_providers(),
+        indicator: active.indicator(),
+        active,
+        selected,
+    }
+}
+
+#[tauri::command]
+pub fn get_environment(
+    app: AppHandle,
+    root: State<'_, DataRoot>,
+) -> Result<EnvironmentStatus, AppError> {
+    let selected = load_config(&config_file(&app).map_err(EnvironmentError::from)?);
+    Ok(status(
+        active_environment(),
+        selected,
+        root.dir().to_path_buf(),
+    ))
+}
+
+/// Saves the environment for the next start and restarts into it. Nothing
+/// is switched in place, so no client keeps talking to the old network.
+#[tauri::command]
+pub fn set_environment(
+    app: AppHandle,
+    root: State<'_, DataRoot>,
+    environment: NetworkEnvironment,
+    custom_rpc_url: Option<String>,
+    confirm: bool,
+) -> Result<EnvironmentStatus, AppError> {
+    if !confirm {
+        return Err(EnvironmentError::ConfirmationRequired.into());
+    }
+    let selected = EnvironmentConfig::new(environment, custom_rpc_url)?;
+    save_config(
+        &config_file(&app).map_err(EnvironmentError::from)?,
+        &selected,
+    )?;
+
+    let status = status(active_environment(), selected, root.dir().to_path_buf());
+    if status.restart_pending {
+        schedule_restart(app);
+    }
+    Ok(status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn devnet() -> EnvironmentConfig {
+        EnvironmentConfig::new(NetworkEnvironment::Devnet, None).unwrap()
+    }
+
+    #[test]
+    fn custom_environment_needs_an_rpc_url() {
+        assert!(EnvironmentConfig::new(NetworkEnvironment::Custom, None).is_err());
+        assert!(
+            EnvironmentConfig::new(NetworkEnvironment::Custom, Some("localhost".into())).is_err()
+        );
+
+        let custom = EnvironmentConfig::new(
+            NetworkEnvironment::Custom,
+            Some(" http://127.0.0.1:8899 ".into()),
+        )
+        .unwrap();
+        assert_eq!(custom.rpc_url(), "http://127.0.0.1:8899");
+
+        let devnet = EnvironmentConfig::new(
+            NetworkEnvironment::Devnet,
+            Some("http://127.0.0.1:8899".into()),
+        )
+        .unwrap();
+        assert_eq!(devnet.custom_rpc_url, None);
+        assert_eq!(devnet.rpc_url(), DEVNET_RPC_URL);
+    }
+
+    #[test]
+    fn non_mainnet_data_is_kept_apart() {
+        let root = Path::new("/data");
+        assert_eq!(EnvironmentConfig::default().data_dir(root), root);
+        assert_eq!(
+            devnet().data_dir(root),
+            Path::new("/data/environments/devnet")
+        );
+    }
+
+    #[test]
+    fn devnet_uses_provider_sandboxes() {
+        assert_eq!(
+            devnet().provider_url("https://api.helius.xyz/v0"),
+            "https://api-devnet.helius.xyz/v0"
+        );
+        assert_eq!(
+            devnet().provider_url("wss://mainnet.helius-rpc.com/?api-key=KEY"),
+            "wss://devnet.helius-rpc.com/?api-key=KEY"
+        );
+        assert_eq!(
+            devnet().provider_url("https://public-api.birdeye.so/defi/ohlcv"),
+            "https://public-api.birdeye.so/defi/ohlcv"
+        );
+        assert_eq!(
+            EnvironmentConfig::default().provider_url("https://api.helius.xyz/v0"),
+            "https://api.helius.xyz/v0"
+        );
+    }
+
+    #[test]
+    fn unreadable_config_falls_back_to_mainnet() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(ENVIRONMENT_FILE);
+        fs::write(&path, "not json").unwrap();
+        assert!(load_config(&path).is_mainnet());
+
+        save_config(&path, &devnet()).unwrap();
+        assert_eq!(load_config(&path), devnet());
+    }
+
+    #[test]
+    fn status_flags_a_pending_switch() {
+        let pending = status(
+            EnvironmentConfig::default(),
+            devnet(),
+            PathBuf::from("/data"),
+        );
+        assert!(pending.restart_pending);
+        assert!(pending.is_mainnet);
+        assert!(pending.indicator.is_none());
+
+        let current = status(devnet(), devnet(), PathBuf::from("/data"));
+        assert!(!current.restart_pending);
+        assert!(current.indicator.unwrap().starts_with("DEVNET"));
+        assert_eq!(current.mainnet_only_providers, vec!["birdeye", "jupiter"]);
+    }
+}
@@ -1,7 +1,13 @@
 pub mod commands;
+pub mod data_root;
+pub mod environment;
+pub mod privacy_mode;
 pub mod settings_manager;
 pub mod settings_schema;
 
 pub use commands::*;
+pub use data_root::*;
+pub use environment::*;
+pub use privacy_mode::*;
 pub use settings_manager::*;
 pub use settings_schema::*;
@@ -0,0 +1,531 @@
+use super::settings_manager::SharedSettingsManager;
+use super::settings_schema::{PrivacyMaskMode, PrivacyModeSettings};
+use crate::tray::SharedTrayManager;
+use serde::{Deserialize, Serialize, Serializer};
+use serde_json::{json, Value};
+use std::sync::{Arc, RwLock};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+pub const PRIVACY_MODE_EVENT: &str = "privacy://mode-changed";
+pub const UNMASKED_EXPORT_EVENT: &str = "privacy://unmasked-export";
+
+/// Exports are never masked: a masked tax report or backup would silently
+/// corrupt the user's records.
+pub const UNMASKED_EXPORTS: &[&str] = &[
+    "tax_report",
+    "tax_center_report",
+    "backup",
+    "portfolio_state",
+];
+
+/// A response type whose balance- and value-bearing fields are masked while
+/// privacy mode is on. Commands opt in by returning [`Masked`].
+pub trait PrivacyMasked: Serialize {
+    /// Serialized names of the fields to mask, matched at any depth.
+    const MASKED_FIELDS: &'static [&'static str];
+
+    /// Key naming the wallet a nested object belongs to, so per-wallet
+    /// overrides apply to each entry of a multi-wallet response.
+    const WALLET_KEY: Option<&'static str> = None;
+
+    /// Wallets the whole response belongs to.
+    fn wallets(&self) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+impl<T: PrivacyMasked> PrivacyMasked for Vec<T> {
+    const MASKED_FIELDS: &'static [&'static str] = T::MASKED_FIELDS;
+    const WALLET_KEY: Option<&'static str> = T::WALLET_KEY;
+
+    fn wallets(&self) -> Vec<String> {
+        self.iter().flat_map(PrivacyMasked::wallets).collect()
+    }
+}
+
+impl<T: PrivacyMasked> PrivacyMasked for Option<T> {
+    const MASKED_FIELDS: &'static [&'static str] = T::MASKED_FIELDS;
+    const WALLET_KEY: Option<&'static str> = T::WALLET_KEY;
+
+    fn wallets(&self) -> Vec<String> {
+        self.as_ref()
+            .map(PrivacyMasked::wallets)
+            .unwrap_or_default()
+    }
+}
+
+/// A command response that is masked on serialization when privacy mode was
+/// active at the time it was built. Masked values are `null` (or scaled) and
+/// each object holding one gains `"masked": true`.
+#[derive(Debug)]
+pub struct Masked<T> {
+    value: T,
+    wallets: Vec<String>,
+    privacy: Option<PrivacyMode>,
+}
+
+impl<T> Masked<T> {
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T: PrivacyMasked> Serialize for Masked<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let Some(privacy) = &self.privacy else {
+            return self.value.serialize(serializer);
+        };
+
+        let mut json = serde_json::to_value(&self.value).map_err(serde::ser::Error::custom)?;
+        let mut wallets = self.wallets.clone();
+        wallets.extend(self.value.wallets());
+        privacy.mask_value(&mut json, T::MASKED_FIELDS, T::WALLET_KEY, wallets);
+        json.serialize(serializer)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PrivacyMode {
+    settings: PrivacyModeSettings,
+    scale_factor: f64,
+}
+
+pub type SharedPrivacyMode = Arc<RwLock<PrivacyMode>>;
+
+/// Log-uniform between 1/4 and 4, and at least 25% away from 1 so scaled
+/// values never pass for real ones.
+fn session_scale_factor() -> f64 {
+    loop {
+        let exponent: f64 = rand::random_range(-2.0..2.0);
+        if exponent.abs() >= 0.33 {
+            return 2f64.powf(exponent);
+        }
+    }
+}
+
+impl PrivacyMode {
+    pub fn new(settings: PrivacyModeSettings) -> Self {
+        Self::with_scale_factor(settings, session_scale_factor())
+    }
+
+    pub fn with_scale_factor(settings: PrivacyModeSettings, scale_factor: f64) -> Self {
+        Self {
+            settings,
+            scale_factor,
+        }
+    }
+
+    pub fn settings(&self) -> &PrivacyModeSettings {
+        &self.settings
+    }
+
+    pub fn update(&mut self, settings: PrivacyModeSettings) {
+        self.settings = settings;
+    }
+
+    /// Whether values of `wallet` are masked; values not tied to a wallet
+    /// follow the global toggle.
+    pub fn masks_wallet(&self, wallet: Option<&str>) -> bool {
+        wallet
+            .and_then(|wallet| self.settings.wallet_overrides.get(wallet).copied())
+            .unwrap_or(self.settings.enabled)
+    }
+
+    /// True when anything at all is masked.
+    pub fn is_active(&self) -> bool {
+        self.settings.enabled
+            || self
+                .settings
+                .wallet_overrides
+                .values()
+                .any(|masked| *masked)
+    }
+
+    /// Masks a single amount; `None` when it is hidden.
+    pub fn mask_amount(&self, amount: f64, wallet: Option<&str>) -> Option<f64> {
+        if !self.masks_wallet(wallet) {
+            return Some(amount);
+        }
+        match self.settings.mode {
+            PrivacyMaskMode::Hide => None,
+            PrivacyMaskMode::Scale => Some(amount * self.scale_factor),
+        }
+    }
+
+    pub fn apply<T: PrivacyMasked>(&self, value: T) -> Masked<T> {
+        Masked {
+            value,
+            wallets: Vec::new(),
+            privacy: self.is_active().then(|| self.clone()),
+        }
+    }
+
+    /// For responses that don't carry their wallet, e.g. a balance list
+    /// fetched by address.
+    pub fn apply_for_wallet<T: PrivacyMasked>(&self, value: T, wallet: &str) -> Masked<T> {
+        Masked {
+            wallets: vec![wallet.to_string()],
+            ..self.apply(value)
+        }
+    }
+
+    /// Totals are masked when any wallet they include is masked; a response
+    /// tied to no wallet follows the global toggle.
+    fn mask_value(
+        &self,
+        value: &mut Value,
+        fields: &[&str],
+        wallet_key: Option<&str>,
+        mut wallets: Vec<String>,
+    ) {
+        if let Some(key) = wallet_key {
+            collect_wallets(value, key, &mut wallets);
+        }
+        let masked = if wallets.is_empty() {
+            self.settings.enabled
+        } else {
+            wallets.iter().any(|wallet| self.masks_wallet(Some(wallet)))
+        };
+        self.mask_node(value, fields, wallet_key, masked);
+    }
+
+    fn mask_node(
+        &self,
+        value: &mut Value,
+        fields: &[&str],
+        wallet_key: Option<&str>,
+        masked: bool,
+    ) {
+        match value {
+            Value::Array(items) => {
+                for item in items {
+                    self.mask_node(item, fields, wallet_key, masked);
+                }
+            }
+            Value::Object(map) => {
+                let masked = wallet_key
+                    .and_then(|key| map.get(key))
+                    .and_then(Value::as_str)
+                    .map_or(masked, |wallet| self.masks_wallet(Some(wallet)));
+
+                let mut changed = false;
+                for (key, field) in map.iter_mut() {
+                    if masked && fields.contains(&key.as_str()) {
+                        if let Some(amount) = field.as_f64() {
+                            *field = match self.settings.mode {
+                                PrivacyMaskMode::Hide => Value::Null,
+                                PrivacyMaskMode::Scale => json!(amount * self.scale_factor),
+                            };
+                            changed = true;
+                            continue;
+                        }
+                    }
+                    self.mask_node(field, fields, wallet_key, masked);
+                }
+                if changed {
+                    map.insert("masked".to_string(), Value::Bool(true));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn collect_wallets(value: &Value, key: &str, wallets: &mut Vec<String>) {
+    match value {
+        Value::Array(items) => items
+            .iter()
+            .for_each(|item| collect_wallets(item, key, wallets)),
+        Value::Object(map) => {
+            if let Some(wallet) = map.get(key).and_then(Value::as_str) {
+                wallets.push(wallet.to_string());
+            }
+            map.values()
+                .for_each(|field| collect_wallets(field, key, wallets));
+        }
+        _ => {}
+    }
+}
+
+/// Brings the in-memory privacy mode in line with the stored settings after
+/// they were changed wholesale (import, profile load, reset).
+pub fn sync_privacy_mode(
+    app: &AppHandle,
+    privacy: &SharedPrivacyMode,
+    settings: &PrivacyModeSettings,
+) {
+    let changed = match privacy.write() {
+        Ok(mut privacy) if privacy.settings() != settings => {
+            privacy.update(settings.clone());
+            true
+        }
+        _ => false,
+    };
+    if changed {
+        privacy_mode_changed(app, privacy);
+    }
+}
+
+/// Exports are exempt from masking. While privacy mode is on this warns that
+/// the export holds real values rather than masking it silently.
+pub fn warn_unmasked_export(app: &AppHandle, export: &str) {
+    let Some(privacy) = app.try_state::<SharedPrivacyMode>() else {
+        return;
+    };
+    let active = privacy
+        .read()
+        .map(|privacy| privacy.is_active())
+        .unwrap_or(false);
+    if !active {
+        return;
+    }
+
+    let message = format!(
+        "Privacy mode is on, but the {} export contains unmasked balances and values",
+        export.replace('_', " ")
+    );
+    eprintln!("{}", message);
+    if let Err(e) = app.emit(
+        UNMASKED_EXPORT_EVENT,
+        json!({ "export": export, "message": message }),
+    ) {
+        eprintln!("Failed to emit unmasked export warning: {}", e);
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrivacyModeStatus {
+    pub enabled: bool,
+    pub mode: PrivacyMaskMode,
+    pub wallet_overrides: std::collections::HashMap<String, bool>,
+    /// Whether any response is currently masked.
+    pub active: bool,
+    pub unmasked_exports: Vec<String>,
+}
+
+impl PrivacyModeStatus {
+    fn from_mode(privacy: &PrivacyMode) -> Self {
+        let settings = privacy.settings();
+        Self {
+            enabled: settings.enabled,
+            mode: settings.mode,
+            wallet_overrides: settings.wallet_overrides.clone(),
+            active: privacy.is_active(),
+            unmasked_exports: UNMASKED_EXPORTS.iter().map(|e| e.to_string()).collect(),
+        }
+    }
+}
+
+fn privacy_mode_changed(app: &AppHandle, privacy: &SharedPrivacyMode) {
+    if let Ok(privacy) = privacy.read() {
+        if let Err(e) = app.emit(PRIVACY_MODE_EVENT, PrivacyModeStatus::from_mode(&privacy)) {
+            eprintln!("Failed to emit privacy mode change: {}", e);
+        }
+    }
+    // The tray menu shows the portfolio value
+    if let Some(tray) = app.try_state::<SharedTrayManager>() {
+        if let Err(e) = tray.refresh_tray_menu(app) {
+            eprintln!("Failed to refresh tray after privacy mode change: {}", e);
+        }
+    }
+}
+
+async fn store_privacy_mode(
+    app: &AppHandle,
+    settings: &SharedSettingsManager,
+    privacy: &SharedPrivacyMode,
+    updated: PrivacyModeSettings,
+) -> Result<PrivacyModeStatus, String> {
+    let value = serde_json::to_value(&updated).map_err(|e| e.to_string())?;
+    settings
+        .write()
+        .await
+        .update_setting("dataPrivacy".to_string(), "privacyMode".to_string(), value)
+        .map_err(|e| e.to_string())?;
+
+    let status = {
+        let mut privacy = privacy.write().map_err(|e| e.to_string())?;
+        privacy.update(updated);
+        PrivacyModeStatus::from_mode(&privacy)
+    };
+    privacy_mode_changed(app, privacy);
+    Ok(status)
+}
+
+#[tauri::command]
+pub async fn privacy_mode_set(
+    app: AppHandle,
+    enabled: bool,
+    mode: Option<PrivacyMaskMode>,
+    settings: State<'_, SharedSettingsManager>,
+    privacy: State<'_, SharedPrivacyMode>,
+) -> Result<PrivacyModeStatus, String> {
+    let mut updated = privacy
+        .read()
+        .map_err(|e| e.to_string())?
+        .settings()
+        .clone();
+    updated.enabled = enabled;
+    if let Some(mode) = mode {
+        updated.mode = mode;
+    }
+    store_privacy_mode(&app, &settings, &privacy, updated).await
+}
+
+/// Overrides the global toggle for one wallet; `masked: None` removes the
+/// override.
+#[tauri::command]
+pub async fn privacy_mode_set_wallet(
+    app: AppHandle,
+    wallet_address: String,
+    masked: Option<bool>,
+    settings: State<'_, SharedSettingsManager>,
+    privacy: State<'_, SharedPrivacyMode>,
+) -> Result<PrivacyModeStatus, String> {
+    let mut updated = privacy
+        .read()
+        .map_err(|e| e.to_string())?
+        .settings()
+        .clone();
+    match masked {
+        Some(masked) => updated.wallet_overrides.insert(wallet_address, masked),
+        None => updated.wallet_overrides.remove(&wallet_address),
+    };
+    store_privacy_mode(&app, &settings, &privacy, updated).await
+}
+
+#[tauri::command]
+pub fn privacy_mode_status(
+    privacy: State<'_, SharedPrivacyMode>,
+) -> Result<PrivacyModeStatus, String> {
+    let privacy = privacy.read().map_err(|e| e.to_string())?;
+    Ok(PrivacyModeStatus::from_mode(&privacy))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[derive(Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Holding {
+        wallet: String,
+        balance: f64,
+        change_percent: f64,
+    }
+
+    #[derive(Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Summary {
+        total_balance: f64,
+        total_change_percent: f64,
+        holdings: Vec<Holding>,
+    }
+
+    impl PrivacyMasked for Summary {
+        const MASKED_FIELDS: &'static [&'static str] = &["totalBalance", "balance"];
+        const WALLET_KEY: Option<&'static str> = Some("wallet");
+    }
+
+    fn summary() -> Summary {
+        Summary {
+            total_balance: 300.0,
+            total_change_percent: 5.0,
+            holdings: vec![
+                Holding {
+                    wallet: "wallet_a".to_string(),
+                    balance: 100.0,
+                    change_percent: 2.0,
+                },
+                Holding {
+                    wallet: "wallet_b".to_string(),
+                    balance: 200.0,
+                    change_percent: 3.0,
+                },
+            ],
+        }
+    }
+
+    fn privacy_mode(
+        enabled: bool,
+        mode: PrivacyMaskMode,
+        overrides: &[(&str, bool)],
+    ) -> PrivacyMode {
+        PrivacyMode::with_scale_factor(
+            PrivacyModeSettings {
+                enabled,
+                mode,
+                wallet_overrides: overrides
+                    .iter()
+                    .map(|(wallet, masked)| (wallet.to_string(), *masked))
+                    .collect::<HashMap<_, _>>(),
+            },
+            2.0,
+        )
+    }
+
+    #[test]
+    fn test_inactive_mode_leaves_response_untouched() {
+        let masked = privacy_mode(false, PrivacyMaskMode::Hide, &[]).apply(summary());
+        let json = serde_json::to_value(&masked).unwrap();
+        assert_eq!(json["totalBalance"], 300.0);
+        assert!(json.get("masked").is_none());
+        assert_eq!(json["holdings"][0]["balance"], 100.0);
+    }
+
+    #[test]
+    fn test_hide_mode_nulls_values_and_flags_objects() {
+        let masked = privacy_mode(true, PrivacyMaskMode::Hide, &[]).apply(summary());
+        let json = serde_json::to_value(&masked).unwrap();
+        assert!(json["totalBalance"].is_null());
+        assert_eq!(json["masked"], true);
+        assert_eq!(json["totalChangePercent"], 5.0);
+        assert!(json["holdings"][1]["balance"].is_null());
+        assert_eq!(json["holdings"][1]["masked"], true);
+        assert_eq!(json["holdings"][1]["changePercent"], 3.0);
+    }
+
+    #[test]
+    fn test_scale_mode_keeps_proportions() {
+        let masked = privacy_mode(true, PrivacyMaskMode::Scale, &[]).apply(summary());
+        let json = serde_json::to_value(&masked).unwrap();
+        assert_eq!(json["totalBalance"], 600.0);
+        assert_eq!(json["holdings"][0]["balance"], 200.0);
+        assert_eq!(json["holdings"][1]["balance"], 400.0);
+        assert_eq!(json["masked"], true);
+    }
+
+    #[test]
+    fn test_wallet_overrides() {
+        // Only wallet_b is masked; the total includes it so it is masked too
+        let privacy = privacy_mode(false, PrivacyMaskMode::Hide, &[("wallet_b", true)]);
+        assert!(privacy.is_active());
+        let json = serde_json::to_value(privacy.apply(summary())).unwrap();
+        assert!(json["totalBalance"].is_null());
+        assert_eq!(json["holdings"][0]["balance"], 100.0);
+        assert!(json["holdings"][0].get("masked").is_none());
+        assert!(json["holdings"][1]["balance"].is_null());
+
+        // Globally on with both wallets exempt leaves everything visible
+        let privacy = privacy_mode(
+            true,
+            PrivacyMaskMode::Hide,
+            &[("wallet_a", false), ("wallet_b", false)],
+        );
+        let json = serde_json::to_value(privacy.apply(summary())).unwrap();
+        assert_eq!(json["totalBalance"], 300.0);
+        assert_eq!(privacy.mask_amount(50.0, Some("wallet_a")), Some(50.0));
+        assert_eq!(privacy.mask_amount(50.0, None), None);
+    }
+
+    #[test]
+    fn test_session_scale_factor_is_far_from_one() {
+        for _ in 0..1000 {
+            let factor = session_scale_factor();
+            assert!((0.25..=4.0).contains(&factor));
+            assert!(!(0.8..1.25).contains(&factor));
+        }
+    }
+}
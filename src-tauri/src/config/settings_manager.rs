@@ -1,3 +1,4 @@
+use super::data_root::DataPaths;
 use super::settings_schema::*;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -5,7 +6,7 @@ use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 use std::sync::Arc;
-use tauri::{AppHandle, Manager};
+use tauri::AppHandle;
 use tokio::sync::RwLock;
 
 pub type SharedSettingsManager = Arc<RwLock<SettingsManager>>;
@@ -90,7 +91,7 @@ impl SettingsManager {
 
     fn settings_path(&self) -> Result<PathBuf, SettingsError> {
         let app_handle = self.app_handle.clone();
-        let mut path = app_handle.path().app_data_dir().map_err(|e| {
+        let mut path = app_handle.app_data_root().map_err(|e| {
             SettingsError::Io(std::io::Error::new(
                 std::io::ErrorKind::NotFound,
                 format!("App data directory not found: {}", e),
@@ -107,7 +108,7 @@ impl SettingsManager {
 
     fn profiles_path(&self) -> Result<PathBuf, SettingsError> {
         let app_handle = self.app_handle.clone();
-        let mut path = app_handle.path().app_data_dir().map_err(|e| {
+        let mut path = app_handle.app_data_root().map_err(|e| {
             SettingsError::Io(std::io::Error::new(
                 std::io::ErrorKind::NotFound,
                 format!("App data directory not found: {}", e),
@@ -299,6 +300,10 @@ impl SettingsManager {
             "privateRpcEnabled" => {
                 self.current_settings.trading.private_rpc_enabled = serde_json::from_value(value)?
             }
+            "performanceScoreWeights" => {
+                self.current_settings.trading.performance_score_weights =
+                    serde_json::from_value(value)?
+            }
             _ => {
                 return Err(SettingsError::SettingNotFound {
                     category: "trading".to_string(),
@@ -604,6 +609,9 @@ impl SettingsManager {
             "crashReporting" => {
                 self.current_settings.data_privacy.crash_reporting = serde_json::from_value(value)?
             }
+            "privacyMode" => {
+                self.current_settings.data_privacy.privacy_mode = serde_json::from_value(value)?
+            }
             _ => {
                 return Err(SettingsError::SettingNotFound {
                     category: "dataPrivacy".to_string(),
@@ -886,6 +894,11 @@ impl SettingsManager {
             ));
         }
 
+        s.trading
+            .performance_score_weights
+            .validate()
+            .map_err(SettingsError::Validation)?;
+
         // Validate AI settings
         if s.ai_assistant.temperature < 0.0 || s.ai_assistant.temperature > 2.0 {
             return Err(SettingsError::Validation(
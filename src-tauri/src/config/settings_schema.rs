@@ -37,6 +37,35 @@ pub struct TradingSettings {
     pub mev_protection: bool,
     pub jito_enabled: bool,
     pub private_rpc_enabled: bool,
+    #[serde(default)]
+    pub performance_score_weights: PerformanceScoreWeights,
+}
+
+/// Relative weights of the wallet performance score components; only their
+/// ratios matter. The defaults give the score its original formula, before
+/// drawdown and fee efficiency were tracked.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PerformanceScoreWeights {
+    pub win_rate: f64,
+    pub profit_factor: f64,
+    pub sharpe_ratio: f64,
+    pub drawdown: f64,
+    pub consistency: f64,
+    pub fee_efficiency: f64,
+}
+
+impl Default for PerformanceScoreWeights {
+    fn default() -> Self {
+        Self {
+            win_rate: 30.0,
+            profit_factor: 30.0,
+            sharpe_ratio: 20.0,
+            drawdown: 0.0,
+            consistency: 20.0,
+            fee_efficiency: 0.0,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -262,6 +291,30 @@ pub struct DataPrivacySettings {
     pub export_format: ExportFormat,
     pub telemetry_enabled: bool,
     pub crash_reporting: bool,
+    #[serde(default)]
+    pub privacy_mode: PrivacyModeSettings,
+}
+
+/// Masks balances and values in responses, e.g. while screen-sharing.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrivacyModeSettings {
+    pub enabled: bool,
+    pub mode: PrivacyMaskMode,
+    /// Per-wallet masking that takes precedence over `enabled`.
+    #[serde(default)]
+    pub wallet_overrides: HashMap<String, bool>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PrivacyMaskMode {
+    /// Values are replaced with `null`.
+    #[default]
+    Hide,
+    /// Values are multiplied by a random per-session factor so charts keep
+    /// their shape.
+    Scale,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -394,6 +447,7 @@ impl Default for TradingSettings {
             mev_protection: true,
             jito_enabled: false,
             private_rpc_enabled: false,
+            performance_score_weights: PerformanceScoreWeights::default(),
         }
     }
 }
@@ -501,6 +555,7 @@ impl Default for DataPrivacySettings {
             export_format: ExportFormat::Json,
             telemetry_enabled: true,
             crash_reporting: true,
+            privacy_mode: PrivacyModeSettings::default(),
         }
     }
 }
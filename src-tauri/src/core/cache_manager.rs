@@ -49,6 +49,7 @@ pub enum CacheType {
     TopCoins,
     TrendingCoins,
     UserData,
+    PriceHistory,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -516,7 +517,7 @@ impl CacheManager {
             | CacheType::MarketData
             | CacheType::TopCoins
             | CacheType::TrendingCoins => ttl_config.metadata,
-            CacheType::UserData => ttl_config.history,
+            CacheType::UserData | CacheType::PriceHistory => ttl_config.history,
         }
     }
 
@@ -853,6 +854,25 @@ impl CacheManager {
         }
     }
 
+    /// Writes every live in-memory entry back to disk. Memory hits only update
+    /// access counts in memory, so this keeps them across restarts.
+    pub async fn persist_all(&self) -> Result<usize, String> {
+        let current_time = self.now_ms();
+        let entries: Vec<CacheEntry> = self
+            .cache
+            .read()
+            .await
+            .values()
+            .filter(|entry| current_time.saturating_sub(entry.created_at_ms) <= entry.ttl_ms)
+            .cloned()
+            .collect();
+
+        for entry in &entries {
+            self.disk_cache.persist(entry)?;
+        }
+        Ok(entries.len())
+    }
+
     pub async fn get_top_accessed_keys(&self, limit: usize) -> Vec<String> {
         let cache = self.cache.read().await;
         let mut entries: Vec<_> = cache.values().collect();
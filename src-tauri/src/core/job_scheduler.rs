@@ -0,0 +1,579 @@
+//! Periodic background jobs.
+//!
+//! A job is a name, a schedule, a timeout and an async handler. The scheduler
+//! never runs a job twice at once, keeps the outcome of its recent runs, and
+//! files a crash report when a job starts failing so broken maintenance work
+//! shows up in the crash log instead of only on stderr. Jobs can be paused,
+//! resumed and run on demand from settings.
+
+use crate::errors::supervisor::panic_message;
+use crate::errors::{spawn_supervised, SharedCrashReporter};
+use crate::logger::SharedLogger;
+use chrono::{DateTime, Utc};
+use cron::Schedule;
+use futures_util::future::BoxFuture;
+use futures_util::FutureExt;
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State};
+use tokio::sync::Notify;
+
+/// Runs kept per job, newest first.
+const HISTORY_LIMIT: usize = 50;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum JobSchedule {
+    /// Every `every_secs` after the previous run finishes. The first run is
+    /// immediate when `run_at_start` is set, otherwise one period in.
+    #[serde(rename_all = "camelCase")]
+    Interval { every_secs: u64, run_at_start: bool },
+    /// A cron expression with a leading seconds field, evaluated in UTC.
+    Cron { expression: String },
+}
+
+impl JobSchedule {
+    pub fn every(period: Duration) -> Self {
+        JobSchedule::Interval {
+            every_secs: period.as_secs(),
+            run_at_start: false,
+        }
+    }
+
+    pub fn every_from_start(period: Duration) -> Self {
+        JobSchedule::Interval {
+            every_secs: period.as_secs(),
+            run_at_start: true,
+        }
+    }
+
+    pub fn cron(expression: &str) -> Result<Self, String> {
+        Schedule::from_str(expression).map_err(|e| format!("Invalid cron expression: {e}"))?;
+        Ok(JobSchedule::Cron {
+            expression: expression.to_string(),
+        })
+    }
+
+    /// The next time the job is due. `previous` is when the last run
+    /// finished, or when a paused slot was skipped; `None` before the first.
+    fn next_run(
+        &self,
+        previous: Option<DateTime<Utc>>,
+        now: DateTime<Utc>,
+    ) -> Option<DateTime<Utc>> {
+        match self {
+            JobSchedule::Interval {
+                every_secs,
+                run_at_start,
+            } => {
+                let period = chrono::Duration::seconds(*every_secs as i64);
+                match previous {
+                    Some(previous) => Some(previous + period),
+                    None if *run_at_start => Some(now),
+                    None => Some(now + period),
+                }
+            }
+            JobSchedule::Cron { expression } => {
+                // Never before the previous slot, so a run that finishes
+                // within its own second is not repeated.
+                let after = previous.map_or(now, |previous| previous.max(now));
+                Schedule::from_str(expression).ok()?.after(&after).next()
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobTrigger {
+    Scheduled,
+    Manual,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobRunStatus {
+    Succeeded,
+    Failed,
+    TimedOut,
+    /// The previous run was still in progress.
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobRun {
+    pub trigger: JobTrigger,
+    pub status: JobRunStatus,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    pub duration_ms: u64,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobInfo {
+    pub name: String,
+    pub schedule: JobSchedule,
+    pub timeout_secs: u64,
+    pub paused: bool,
+    pub running: bool,
+    pub next_run_at: Option<DateTime<Utc>>,
+    pub last_run: Option<JobRun>,
+    pub consecutive_failures: u32,
+}
+
+type JobHandler = Arc<dyn Fn() -> BoxFuture<'static, Result<(), String>> + Send + Sync>;
+
+struct Job {
+    name: &'static str,
+    schedule: JobSchedule,
+    timeout: Duration,
+    handler: JobHandler,
+    state: Mutex<JobState>,
+    /// Wakes the run loop to recompute its next slot after a resume.
+    wake: Notify,
+}
+
+#[derive(Default)]
+struct JobState {
+    paused: bool,
+    running: bool,
+    next_run_at: Option<DateTime<Utc>>,
+    consecutive_failures: u32,
+    history: VecDeque<JobRun>,
+}
+
+impl JobState {
+    fn record(&mut self, run: JobRun) {
+        self.history.push_front(run);
+        self.history.truncate(HISTORY_LIMIT);
+    }
+}
+
+impl Job {
+    fn info(&self) -> JobInfo {
+        let state = self.state.lock();
+        JobInfo {
+            name: self.name.to_string(),
+            schedule: self.schedule.clone(),
+            timeout_secs: self.timeout.as_secs(),
+            paused: state.paused,
+            running: state.running,
+            next_run_at: state.next_run_at,
+            last_run: state.history.front().cloned(),
+            consecutive_failures: state.consecutive_failures,
+        }
+    }
+}
+
+pub type SharedJobScheduler = Arc<JobScheduler>;
+
+pub struct JobScheduler {
+    app: Option<AppHandle>,
+    jobs: RwLock<BTreeMap<&'static str, Arc<Job>>>,
+}
+
+impl JobScheduler {
+    pub fn new(app: &AppHandle) -> Self {
+        Self {
+            app: Some(app.clone()),
+            jobs: RwLock::new(BTreeMap::new()),
+        }
+    }
+
+    /// A scheduler without run loops; jobs only run through `run_now`.
+    pub fn detached() -> Self {
+        Self {
+            app: None,
+            jobs: RwLock::new(BTreeMap::new()),
+        }
+    }
+
+    /// Adds a job and starts its run loop under the task supervisor, which
+    /// stops it on shutdown.
+    pub fn register<F, Fut>(
+        &self,
+        name: &'static str,
+        schedule: JobSchedule,
+        timeout: Duration,
+        handler: F,
+    ) -> Result<(), String>
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), String>> + Send + 'static,
+    {
+        match &schedule {
+            JobSchedule::Interval { every_secs: 0, .. } => {
+                return Err(format!("Job '{}' needs a non-zero interval", name));
+            }
+            JobSchedule::Cron { expression } => {
+                JobSchedule::cron(expression)?;
+            }
+            JobSchedule::Interval { .. } => {}
+        }
+
+        let job = Arc::new(Job {
+            name,
+            schedule,
+            timeout,
+            handler: Arc::new(move || handler().boxed()),
+            state: Mutex::new(JobState::default()),
+            wake: Notify::new(),
+        });
+        {
+            let mut jobs = self.jobs.write();
+            if jobs.contains_key(name) {
+                return Err(format!("Job '{}' is already registered", name));
+            }
+            jobs.insert(name, job.clone());
+        }
+
+        if let Some(app) = &self.app {
+            let loop_app = app.clone();
+            spawn_supervised(app, name, move || {
+                run_job_loop(loop_app.clone(), job.clone())
+            });
+        }
+        Ok(())
+    }
+
+    pub fn list(&self) -> Vec<JobInfo> {
+        self.jobs.read().values().map(|job| job.info()).collect()
+    }
+
+    /// Runs the job now, whether or not it is paused, and returns the run.
+    pub async fn run_now(&self, name: &str) -> Result<JobRun, String> {
+        let job = self.job(name)?;
+        Ok(execute(self.app.as_ref(), &job, JobTrigger::Manual).await)
+    }
+
+    pub fn pause(&self, name: &str) -> Result<JobInfo, String> {
+        let job = self.job(name)?;
+        job.state.lock().paused = true;
+        Ok(job.info())
+    }
+
+    pub fn resume(&self, name: &str) -> Result<JobInfo, String> {
+        let job = self.job(name)?;
+        job.state.lock().paused = false;
+        job.wake.notify_one();
+        Ok(job.info())
+    }
+
+    pub fn history(&self, name: &str) -> Result<Vec<JobRun>, String> {
+        let job = self.job(name)?;
+        let history = job.state.lock().history.iter().cloned().collect();
+        Ok(history)
+    }
+
+    fn job(&self, name: &str) -> Result<Arc<Job>, String> {
+        self.jobs
+            .read()
+            .get(name)
+            .cloned()
+            .ok_or_else(|| format!("Unknown job '{}'", name))
+    }
+}
+
+async fn run_job_loop(app: AppHandle, job: Arc<Job>) {
+    let mut previous = None;
+    loop {
+        let now = Utc::now();
+        let Some(due) = job.schedule.next_run(previous, now) else {
+            eprintln!("[scheduler] job '{}' has no upcoming run", job.name);
+            return;
+        };
+        job.state.lock().next_run_at = Some(due);
+
+        let wait = (due - now).to_std().unwrap_or_default();
+        tokio::select! {
+            _ = tokio::time::sleep(wait) => {}
+            _ = job.wake.notified() => continue,
+        }
+
+        if job.state.lock().paused {
+            previous = Some(due);
+            continue;
+        }
+        let run = execute(Some(&app), &job, JobTrigger::Scheduled).await;
+        previous = Some(run.finished_at.max(due));
+    }
+}
+
+async fn execute(app: Option<&AppHandle>, job: &Job, trigger: JobTrigger) -> JobRun {
+    let started_at = Utc::now();
+    {
+        let mut state = job.state.lock();
+        if state.running {
+            let run = JobRun {
+                trigger,
+                status: JobRunStatus::Skipped,
+                started_at,
+                finished_at: started_at,
+                duration_ms: 0,
+                error: Some("Previous run still in progress".to_string()),
+            };
+            state.record(run.clone());
+            drop(state);
+            if let Some(app) = app {
+                report(app, job.name, &run, false);
+            }
+            return run;
+        }
+        state.running = true;
+    }
+
+    let clock = Instant::now();
+    let handler = (job.handler)();
+    let outcome = tokio::time::timeout(job.timeout, AssertUnwindSafe(handler).catch_unwind()).await;
+    let (status, error) = match outcome {
+        Ok(Ok(Ok(()))) => (JobRunStatus::Succeeded, None),
+        Ok(Ok(Err(error))) => (JobRunStatus::Failed, Some(error)),
+        Ok(Err(payload)) => (
+            JobRunStatus::Failed,
+            Some(format!("panicked: {}", panic_message(payload.as_ref()))),
+        ),
+        Err(_) => (
+            JobRunStatus::TimedOut,
+            Some(format!("Timed out after {}s", job.timeout.as_secs())),
+        ),
+    };
+    let run = JobRun {
+        trigger,
+        status,
+        started_at,
+        finished_at: Utc::now(),
+        duration_ms: clock.elapsed().as_millis() as u64,
+        error,
+    };
+
+    let first_failure = {
+        let mut state = job.state.lock();
+        state.running = false;
+        if status == JobRunStatus::Succeeded {
+            state.consecutive_failures = 0;
+        } else {
+            state.consecutive_failures += 1;
+        }
+        state.record(run.clone());
+        state.consecutive_failures == 1
+    };
+    if let Some(app) = app {
+        report(app, job.name, &run, first_failure);
+    }
+    run
+}
+
+/// Leaves a breadcrumb for every run that did not succeed, and a crash
+/// report for the first failure in a streak.
+fn report(app: &AppHandle, name: &str, run: &JobRun, first_failure: bool) {
+    let Some(error) = &run.error else {
+        return;
+    };
+    eprintln!("[scheduler] job '{}' {:?}: {}", name, run.status, error);
+    if let Some(logger) = app.try_state::<SharedLogger>() {
+        logger.add_breadcrumb(
+            &format!("job:{}", name),
+            "run did not succeed",
+            Some(serde_json::json!({
+                "status": run.status,
+                "trigger": run.trigger,
+                "durationMs": run.duration_ms,
+                "error": error,
+            })),
+        );
+    }
+    if run.status == JobRunStatus::Skipped || !first_failure {
+        return;
+    }
+    if let Some(reporter) = app.try_state::<SharedCrashReporter>() {
+        if let Err(err) = reporter.capture_job_failure(name, error, run.duration_ms) {
+            eprintln!("[scheduler] failed to record crash for '{}': {}", name, err);
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn jobs_list(scheduler: State<'_, SharedJobScheduler>) -> Result<Vec<JobInfo>, String> {
+    Ok(scheduler.list())
+}
+
+#[tauri::command]
+pub async fn jobs_run_now(
+    name: String,
+    scheduler: State<'_, SharedJobScheduler>,
+) -> Result<JobRun, String> {
+    scheduler.run_now(&name).await
+}
+
+#[tauri::command]
+pub async fn jobs_pause(
+    name: String,
+    scheduler: State<'_, SharedJobScheduler>,
+) -> Result<JobInfo, String> {
+    scheduler.pause(&name)
+}
+
+#[tauri::command]
+pub async fn jobs_resume(
+    name: String,
+    scheduler: State<'_, SharedJobScheduler>,
+) -> Result<JobInfo, String> {
+    scheduler.resume(&name)
+}
+
+#[tauri::command]
+pub async fn jobs_get_history(
+    name: String,
+    scheduler: State<'_, SharedJobScheduler>,
+) -> Result<Vec<JobRun>, String> {
+    scheduler.history(&name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn at(hour: u32, minute: u32, second: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 10, hour, minute, second)
+            .unwrap()
+    }
+
+    #[test]
+    fn schedules_compute_the_next_slot() {
+        let hourly = JobSchedule::every(Duration::from_secs(3600));
+        assert_eq!(hourly.next_run(None, at(1, 0, 0)), Some(at(2, 0, 0)));
+        assert_eq!(
+            hourly.next_run(Some(at(1, 30, 0)), at(1, 31, 0)),
+            Some(at(2, 30, 0))
+        );
+        let eager = JobSchedule::every_from_start(Duration::from_secs(3600));
+        assert_eq!(eager.next_run(None, at(1, 0, 0)), Some(at(1, 0, 0)));
+
+        let nightly = JobSchedule::cron("0 0 3 * * *").unwrap();
+        assert_eq!(nightly.next_run(None, at(1, 0, 0)), Some(at(3, 0, 0)));
+        // A run that finished a moment early by the wall clock is not repeated.
+        assert_eq!(
+            nightly.next_run(Some(at(3, 0, 0)), at(2, 59, 59)),
+            Some(at(3, 0, 0) + chrono::Duration::days(1))
+        );
+        assert!(JobSchedule::cron("every night").is_err());
+    }
+
+    #[tokio::test]
+    async fn runs_record_status_and_failure_streaks() {
+        let scheduler = JobScheduler::detached();
+        let calls = Arc::new(AtomicU32::new(0));
+        let counter = calls.clone();
+        scheduler
+            .register(
+                "flaky",
+                JobSchedule::every(Duration::from_secs(60)),
+                Duration::from_secs(5),
+                move || {
+                    let attempt = counter.fetch_add(1, Ordering::SeqCst);
+                    async move {
+                        match attempt {
+                            0 | 1 => Err(format!("attempt {attempt} failed")),
+                            2 => panic!("handler blew up"),
+                            _ => Ok(()),
+                        }
+                    }
+                },
+            )
+            .unwrap();
+
+        let first = scheduler.run_now("flaky").await.unwrap();
+        assert_eq!(first.status, JobRunStatus::Failed);
+        assert_eq!(first.trigger, JobTrigger::Manual);
+        scheduler.run_now("flaky").await.unwrap();
+        let panicked = scheduler.run_now("flaky").await.unwrap();
+        assert_eq!(panicked.status, JobRunStatus::Failed);
+        assert!(panicked.error.unwrap().contains("handler blew up"));
+        assert_eq!(scheduler.list()[0].consecutive_failures, 3);
+
+        let recovered = scheduler.run_now("flaky").await.unwrap();
+        assert_eq!(recovered.status, JobRunStatus::Succeeded);
+        let info = &scheduler.list()[0];
+        assert_eq!(info.consecutive_failures, 0);
+        assert!(!info.running);
+
+        let history = scheduler.history("flaky").unwrap();
+        assert_eq!(history.len(), 4);
+        assert_eq!(history[0].status, JobRunStatus::Succeeded);
+        assert_eq!(history[3].error.as_deref(), Some("attempt 0 failed"));
+    }
+
+    #[tokio::test]
+    async fn slow_runs_time_out_and_block_overlapping_runs() {
+        let scheduler = Arc::new(JobScheduler::detached());
+        let release = Arc::new(Notify::new());
+        let gate = release.clone();
+        scheduler
+            .register(
+                "slow",
+                JobSchedule::every(Duration::from_secs(60)),
+                Duration::from_millis(200),
+                move || {
+                    let gate = gate.clone();
+                    async move {
+                        gate.notified().await;
+                        Ok(())
+                    }
+                },
+            )
+            .unwrap();
+
+        let background = scheduler.clone();
+        let first = tokio::spawn(async move { background.run_now("slow").await });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(scheduler.list()[0].running);
+
+        let overlapping = scheduler.run_now("slow").await.unwrap();
+        assert_eq!(overlapping.status, JobRunStatus::Skipped);
+
+        let timed_out = first.await.unwrap().unwrap();
+        assert_eq!(timed_out.status, JobRunStatus::TimedOut);
+        assert!(!scheduler.list()[0].running);
+
+        release.notify_one();
+        let finished = scheduler.run_now("slow").await.unwrap();
+        assert_eq!(finished.status, JobRunStatus::Succeeded);
+    }
+
+    #[test]
+    fn registration_and_controls_validate_names() {
+        let scheduler = JobScheduler::detached();
+        let job = || async { Ok::<(), String>(()) };
+        let every_minute = JobSchedule::every(Duration::from_secs(60));
+        scheduler
+            .register("job", every_minute.clone(), Duration::from_secs(1), job)
+            .unwrap();
+        assert!(scheduler
+            .register("job", every_minute, Duration::from_secs(1), job)
+            .is_err());
+        assert!(scheduler
+            .register(
+                "zero",
+                JobSchedule::every(Duration::ZERO),
+                Duration::from_secs(1),
+                job
+            )
+            .is_err());
+
+        assert!(scheduler.pause("job").unwrap().paused);
+        assert!(!scheduler.resume("job").unwrap().paused);
+        assert!(scheduler.pause("missing").is_err());
+        assert!(scheduler.history("missing").is_err());
+    }
+}
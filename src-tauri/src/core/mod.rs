@@ -1,7 +1,13 @@
 pub mod cache_manager;
+pub mod job_scheduler;
+pub mod outbox;
 pub mod price_engine;
+pub mod stream_multiplexer;
 pub mod websocket_manager;
 
 pub use cache_manager::*;
+pub use job_scheduler::*;
+pub use outbox::*;
 pub use price_engine::*;
+pub use stream_multiplexer::*;
 pub use websocket_manager::*;
@@ -0,0 +1,553 @@
+//! Offline outbox.
+//!
+//! Operations that can wait for the network are queued here while the app is
+//! offline instead of failing, and submitted in the order they were queued
+//! once connectivity returns. Connectivity comes from the same internet probe
+//! the network diagnostics panel uses, run on a timer. Each kind of operation
+//! registers a handler and a maximum age; items older than that are abandoned
+//! rather than submitted, so a trade queued at one price never executes at a
+//! very different one. Time-critical operations are never queued: they call
+//! [`require_online`] and fail fast.
+
+use crate::config::DataPaths;
+use crate::diagnostics::network_repair::internet_reachable;
+use crate::errors::AppError;
+use chrono::{DateTime, Utc};
+use futures_util::future::BoxFuture;
+use futures_util::FutureExt;
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+use uuid::Uuid;
+
+const OUTBOX_FILE: &str = "outbox.json";
+const PROBE_INTERVAL: Duration = Duration::from_secs(10);
+/// Failed submissions before an item is abandoned.
+const MAX_ATTEMPTS: u32 = 5;
+/// Emitted with an [`OutboxStatus`] when connectivity or the queue changes.
+pub const OUTBOX_EVENT: &str = "outbox_updated";
+
+#[derive(Debug, thiserror::Error)]
+pub enum OutboxError {
+    #[error("no outbox handler for '{0}'")]
+    UnknownKind(String),
+    #[error("outbox item not found: {0}")]
+    NotFound(String),
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+}
+
+impl From<OutboxError> for AppError {
+    fn from(err: OutboxError) -> Self {
+        match err {
+            OutboxError::UnknownKind(kind) => {
+                AppError::internal(format!("No outbox handler for '{}'", kind))
+            }
+            OutboxError::NotFound(id) => {
+                AppError::not_found(format!("Outbox item {} not found", id))
+                    .with_code("outbox_item_not_found")
+            }
+            OutboxError::Io(err) => err.into(),
+            OutboxError::Serialization(err) => err.into(),
+        }
+    }
+}
+
+/// Why a queued item was dropped without being submitted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AbandonReason {
+    /// It outlived its kind's maximum age while offline.
+    Expired,
+    /// The user discarded it.
+    Discarded,
+    /// Every submission attempt failed.
+    Failed,
+}
+
+impl AbandonReason {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            AbandonReason::Expired => "expired while offline",
+            AbandonReason::Discarded => "discarded while offline",
+            AbandonReason::Failed => "failed after reconnecting",
+        }
+    }
+}
+
+/// What a handler is asked to do with an item. Abandoned items are handed
+/// over once so their owner can record the skip.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutboxDispatch {
+    Submit,
+    Abandon(AbandonReason),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutboxItem {
+    pub id: String,
+    pub kind: String,
+    /// Identifies the operation, so queueing it twice keeps one item.
+    pub key: String,
+    pub description: String,
+    pub payload: Value,
+    pub queued_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub attempts: u32,
+    pub last_error: Option<String>,
+}
+
+impl OutboxItem {
+    fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|expires_at| now >= expires_at)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutboxStatus {
+    pub online: bool,
+    pub pending: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DrainSummary {
+    pub submitted: usize,
+    pub abandoned: usize,
+    /// Whether the drain stopped at an item that failed and will be retried.
+    pub stalled: bool,
+}
+
+type OutboxHandler =
+    Arc<dyn Fn(OutboxDispatch, Value) -> BoxFuture<'static, Result<(), String>> + Send + Sync>;
+
+struct OutboxKind {
+    max_age: Option<Duration>,
+    handler: OutboxHandler,
+}
+
+pub type SharedOutbox = Arc<Outbox>;
+
+pub struct Outbox {
+    path: Option<PathBuf>,
+    online: AtomicBool,
+    items: Mutex<Vec<OutboxItem>>,
+    kinds: RwLock<HashMap<&'static str, OutboxKind>>,
+    /// Serializes drains so items are submitted once and in order.
+    draining: tokio::sync::Mutex<()>,
+}
+
+impl Outbox {
+    pub fn new(app: &AppHandle) -> Result<Self, OutboxError> {
+        let dir = app.app_data_root().map_err(|e| {
+            OutboxError::Io(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("App data directory not found: {}", e),
+            ))
+        })?;
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(OUTBOX_FILE);
+        let items = if path.exists() {
+            serde_json::from_str(&fs::read_to_string(&path)?)?
+        } else {
+            Vec::new()
+        };
+        Ok(Self {
+            path: Some(path),
+            ..Self::with_items(items)
+        })
+    }
+
+    /// An outbox that is never persisted.
+    pub fn in_memory() -> Self {
+        Self::with_items(Vec::new())
+    }
+
+    fn with_items(items: Vec<OutboxItem>) -> Self {
+        Self {
+            path: None,
+            // Assumed until the first probe says otherwise.
+            online: AtomicBool::new(true),
+            items: Mutex::new(items),
+            kinds: RwLock::new(HashMap::new()),
+            draining: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// Sets the handler for a kind of operation, replacing any earlier one.
+    /// Items older than `max_age` are abandoned instead of submitted.
+    pub fn register<F, Fut>(&self, kind: &'static str, max_age: Option<Duration>, handler: F)
+    where
+        F: Fn(OutboxDispatch, Value) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), String>> + Send + 'static,
+    {
+        let handler: OutboxHandler =
+            Arc::new(move |dispatch, payload| handler(dispatch, payload).boxed());
+        self.kinds
+            .write()
+            .insert(kind, OutboxKind { max_age, handler });
+    }
+
+    pub fn is_online(&self) -> bool {
+        self.online.load(Ordering::SeqCst)
+    }
+
+    /// Records the latest probe, returning whether connectivity changed.
+    pub fn set_online(&self, online: bool) -> bool {
+        self.online.swap(online, Ordering::SeqCst) != online
+    }
+
+    pub fn status(&self) -> OutboxStatus {
+        OutboxStatus {
+            online: self.is_online(),
+            pending: self.items.lock().len(),
+        }
+    }
+
+    pub fn list(&self) -> Vec<OutboxItem> {
+        self.items.lock().clone()
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.items.lock().iter().any(|item| item.key == key)
+    }
+
+    /// Queues an operation, or returns the item already queued under `key`.
+    pub fn enqueue(
+        &self,
+        kind: &'static str,
+        key: &str,
+        description: impl Into<String>,
+        payload: impl Serialize,
+        now: DateTime<Utc>,
+    ) -> Result<OutboxItem, OutboxError> {
+        let max_age = self
+            .kinds
+            .read()
+            .get(kind)
+            .map(|registered| registered.max_age)
+            .ok_or_else(|| OutboxError::UnknownKind(kind.to_string()))?;
+        let payload = serde_json::to_value(payload)?;
+
+        let mut items = self.items.lock();
+        if let Some(existing) = items.iter().find(|item| item.key == key) {
+            return Ok(existing.clone());
+        }
+        let item = OutboxItem {
+            id: Uuid::new_v4().to_string(),
+            kind: kind.to_string(),
+            key: key.to_string(),
+            description: description.into(),
+            payload,
+            queued_at: now,
+            expires_at: max_age
+                .and_then(|age| chrono::Duration::from_std(age).ok().map(|age| now + age)),
+            attempts: 0,
+            last_error: None,
+        };
+        items.push(item.clone());
+        self.persist(&items)?;
+        Ok(item)
+    }
+
+    /// Removes an item without submitting it.
+    pub async fn discard(&self, id: &str) -> Result<OutboxItem, OutboxError> {
+        let item = self
+            .remove(id)?
+            .ok_or_else(|| OutboxError::NotFound(id.to_string()))?;
+        self.abandon(&item, AbandonReason::Discarded).await;
+        Ok(item)
+    }
+
+    /// Submits queued items oldest first. Expired items are abandoned; a
+    /// failed submission stays queued and ends the drain, so later items never
+    /// overtake it. Items whose kind has no handler yet are left for later.
+    pub async fn drain(&self, now: DateTime<Utc>) -> Result<DrainSummary, OutboxError> {
+        let _guard = self.draining.lock().await;
+        let mut summary = DrainSummary::default();
+        for item in self.list() {
+            let Some(handler) = self.handler(&item.kind) else {
+                continue;
+            };
+            if item.is_expired(now) {
+                self.remove(&item.id)?;
+                self.abandon(&item, AbandonReason::Expired).await;
+                summary.abandoned += 1;
+                continue;
+            }
+
+            match handler(OutboxDispatch::Submit, item.payload.clone()).await {
+                Ok(()) => {
+                    self.remove(&item.id)?;
+                    summary.submitted += 1;
+                }
+                Err(error) => {
+                    let attempts = self.record_failure(&item.id, error)?;
+                    if attempts < MAX_ATTEMPTS {
+                        summary.stalled = true;
+                        break;
+                    }
+                    self.remove(&item.id)?;
+                    self.abandon(&item, AbandonReason::Failed).await;
+                    summary.abandoned += 1;
+                }
+            }
+        }
+        Ok(summary)
+    }
+
+    fn handler(&self, kind: &str) -> Option<OutboxHandler> {
+        self.kinds
+            .read()
+            .get(kind)
+            .map(|registered| registered.handler.clone())
+    }
+
+    async fn abandon(&self, item: &OutboxItem, reason: AbandonReason) {
+        let Some(handler) = self.handler(&item.kind) else {
+            return;
+        };
+        if let Err(err) = handler(OutboxDispatch::Abandon(reason), item.payload.clone()).await {
+            eprintln!(
+                "[outbox] failed to abandon {} ({}): {}",
+                item.id, item.description, err
+            );
+        }
+    }
+
+    fn remove(&self, id: &str) -> Result<Option<OutboxItem>, OutboxError> {
+        let mut items = self.items.lock();
+        let Some(index) = items.iter().position(|item| item.id == id) else {
+            return Ok(None);
+        };
+        let item = items.remove(index);
+        self.persist(&items)?;
+        Ok(Some(item))
+    }
+
+    fn record_failure(&self, id: &str, error: String) -> Result<u32, OutboxError> {
+        let mut items = self.items.lock();
+        let Some(item) = items.iter_mut().find(|item| item.id == id) else {
+            return Ok(MAX_ATTEMPTS);
+        };
+        item.attempts += 1;
+        item.last_error = Some(error);
+        let attempts = item.attempts;
+        self.persist(&items)?;
+        Ok(attempts)
+    }
+
+    fn persist(&self, items: &[OutboxItem]) -> Result<(), OutboxError> {
+        if let Some(path) = &self.path {
+            let tmp = path.with_extension("json.tmp");
+            fs::write(&tmp, serde_json::to_vec_pretty(items)?)?;
+            fs::rename(&tmp, path)?;
+        }
+        Ok(())
+    }
+}
+
+/// Fails fast with an `offline` error when the last probe found no
+/// connectivity. Time-critical operations call this instead of queueing.
+pub fn require_online<R: Runtime>(app: &AppHandle<R>) -> Result<(), AppError> {
+    match app.try_state::<SharedOutbox>() {
+        Some(outbox) if !outbox.is_online() => Err(AppError::upstream(
+            "network",
+            "You are offline. This action needs a connection and was not queued.",
+            true,
+        )
+        .with_code("offline")),
+        _ => Ok(()),
+    }
+}
+
+/// Probes connectivity on a timer and drains the outbox whenever the app is
+/// online with items pending.
+pub async fn run_outbox(app: AppHandle) {
+    let Some(outbox) = app
+        .try_state::<SharedOutbox>()
+        .map(|outbox| outbox.inner().clone())
+    else {
+        return;
+    };
+    let client = reqwest::Client::builder()
+        .timeout(PROBE_INTERVAL)
+        .build()
+        .unwrap_or_default();
+    let mut ticker = tokio::time::interval(PROBE_INTERVAL);
+    loop {
+        ticker.tick().await;
+        let online = internet_reachable(&client).await;
+        let mut changed = outbox.set_online(online);
+        if online && outbox.status().pending > 0 {
+            match outbox.drain(Utc::now()).await {
+                Ok(summary) => changed |= summary.submitted + summary.abandoned > 0,
+                Err(err) => eprintln!("[outbox] drain failed: {}", err),
+            }
+        }
+        if changed {
+            let _ = app.emit(OUTBOX_EVENT, outbox.status());
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn outbox_list(outbox: State<'_, SharedOutbox>) -> Result<Vec<OutboxItem>, AppError> {
+    Ok(outbox.list())
+}
+
+#[tauri::command]
+pub async fn outbox_discard(
+    app: AppHandle,
+    id: String,
+    outbox: State<'_, SharedOutbox>,
+) -> Result<OutboxItem, AppError> {
+    let item = outbox.discard(&id).await?;
+    let _ = app.emit(OUTBOX_EVENT, outbox.status());
+    Ok(item)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 10, 15, 12, minute, 0).unwrap()
+    }
+
+    /// Registers `kind`, recording every dispatch and failing submissions of
+    /// payloads listed in `failing`.
+    fn recording(
+        outbox: &Outbox,
+        kind: &'static str,
+        max_age: Option<Duration>,
+        failing: &[&str],
+    ) -> Arc<Mutex<Vec<(OutboxDispatch, String)>>> {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let seen = log.clone();
+        let failing: Vec<String> = failing.iter().map(|s| s.to_string()).collect();
+        outbox.register(kind, max_age, move |dispatch, payload| {
+            let payload = payload.as_str().unwrap_or_default().to_string();
+            seen.lock().push((dispatch, payload.clone()));
+            let fail = dispatch == OutboxDispatch::Submit && failing.contains(&payload);
+            async move {
+                if fail {
+                    Err(format!("{payload} unreachable"))
+                } else {
+                    Ok(())
+                }
+            }
+        });
+        log
+    }
+
+    #[tokio::test]
+    async fn drains_in_order_and_dedupes_keys() {
+        let outbox = Outbox::in_memory();
+        let log = recording(&outbox, "ack", None, &[]);
+        outbox.enqueue("ack", "a", "first", "a", at(0)).unwrap();
+        outbox.enqueue("ack", "b", "second", "b", at(1)).unwrap();
+        let again = outbox.enqueue("ack", "a", "first", "a", at(2)).unwrap();
+        assert_eq!(again.queued_at, at(0));
+        assert_eq!(outbox.list().len(), 2);
+        assert!(outbox.enqueue("unknown", "c", "", "c", at(0)).is_err());
+
+        let summary = outbox.drain(at(3)).await.unwrap();
+        assert_eq!(summary.submitted, 2);
+        assert!(outbox.list().is_empty());
+        let submitted: Vec<String> = log.lock().iter().map(|(_, p)| p.clone()).collect();
+        assert_eq!(submitted, vec!["a", "b"]);
+    }
+
+    #[tokio::test]
+    async fn stale_items_expire_instead_of_submitting() {
+        let outbox = Outbox::in_memory();
+        let log = recording(&outbox, "trade", Some(Duration::from_secs(300)), &[]);
+        outbox.enqueue("trade", "old", "old", "old", at(0)).unwrap();
+        outbox.enqueue("trade", "new", "new", "new", at(8)).unwrap();
+
+        let summary = outbox.drain(at(10)).await.unwrap();
+        assert_eq!(summary.abandoned, 1);
+        assert_eq!(summary.submitted, 1);
+        assert_eq!(
+            *log.lock(),
+            vec![
+                (
+                    OutboxDispatch::Abandon(AbandonReason::Expired),
+                    "old".to_string()
+                ),
+                (OutboxDispatch::Submit, "new".to_string()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn failures_hold_the_queue_until_abandoned() {
+        let outbox = Outbox::in_memory();
+        let log = recording(&outbox, "send", None, &["a"]);
+        outbox.enqueue("send", "a", "a", "a", at(0)).unwrap();
+        outbox.enqueue("send", "b", "b", "b", at(1)).unwrap();
+
+        for attempt in 1..MAX_ATTEMPTS {
+            let summary = outbox.drain(at(2)).await.unwrap();
+            assert!(summary.stalled);
+            assert_eq!(summary.submitted, 0);
+            let head = &outbox.list()[0];
+            assert_eq!(head.attempts, attempt);
+            assert_eq!(head.last_error.as_deref(), Some("a unreachable"));
+        }
+
+        let summary = outbox.drain(at(2)).await.unwrap();
+        assert_eq!(summary.abandoned, 1);
+        assert_eq!(summary.submitted, 1);
+        assert!(outbox.list().is_empty());
+        assert!(log.lock().contains(&(
+            OutboxDispatch::Abandon(AbandonReason::Failed),
+            "a".to_string()
+        )));
+    }
+
+    #[tokio::test]
+    async fn discard_hands_the_item_back_to_its_owner() {
+        let outbox = Outbox::in_memory();
+        let log = recording(&outbox, "ack", None, &[]);
+        let item = outbox.enqueue("ack", "a", "a", "a", at(0)).unwrap();
+
+        assert_eq!(outbox.discard(&item.id).await.unwrap().key, "a");
+        assert!(outbox.list().is_empty());
+        assert_eq!(
+            *log.lock(),
+            vec![(
+                OutboxDispatch::Abandon(AbandonReason::Discarded),
+                "a".to_string()
+            )]
+        );
+        assert!(matches!(
+            outbox.discard(&item.id).await,
+            Err(OutboxError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn connectivity_changes_are_reported_once() {
+        let outbox = Outbox::in_memory();
+        assert!(outbox.is_online());
+        assert!(outbox.set_online(false));
+        assert!(!outbox.set_online(false));
+        assert!(!outbox.status().online);
+        assert!(outbox.set_online(true));
+    }
+}
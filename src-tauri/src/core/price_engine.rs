@@ -12,6 +12,8 @@ use sysinfo::{CpuExt, CpuRefreshKind, RefreshKind, System, SystemExt};
 use tauri::{AppHandle, Emitter, Manager};
 use tracing::{info, trace, warn};
 
+use crate::monitor::{command_metrics, CommandStats, TracingConfig};
+
 const LATENCY_WINDOW: usize = 10_000;
 const MEMORY_POOL_CAPACITY: usize = 512;
 const MEMORY_POOL_BUFFER_SIZE: usize = 1024;
@@ -77,6 +79,9 @@ pub struct PerformanceMetrics {
     pub errors: u64,
     pub uptime_ms: u64,
     pub cpu_usage: f32,
+    /// Per-command latency, grouped by Tauri command name.
+    #[serde(default)]
+    pub commands: Vec<CommandStats>,
 }
 
 struct LatencyTracker {
@@ -294,6 +299,7 @@ impl PriceEngine {
             errors,
             uptime_ms,
             cpu_usage,
+            commands: Vec::new(),
         }
     }
 
@@ -349,7 +355,15 @@ pub fn get_price_engine() -> Arc<PriceEngine> {
 
 #[tauri::command]
 pub fn get_performance_metrics() -> Result<PerformanceMetrics, String> {
-    Ok(get_price_engine().get_metrics())
+    let mut metrics = get_price_engine().get_metrics();
+    metrics.commands = command_metrics().snapshot();
+    Ok(metrics)
+}
+
+#[tauri::command]
+pub fn set_performance_tracing(enabled: bool, threshold_ms: u64) -> Result<TracingConfig, String> {
+    command_metrics().set_tracing(enabled, threshold_ms);
+    Ok(command_metrics().tracing_config())
 }
 
 #[tauri::command]
@@ -364,6 +378,7 @@ pub async fn run_performance_test(
 #[tauri::command]
 pub fn reset_performance_stats() -> Result<(), String> {
     get_price_engine().reset_stats();
+    command_metrics().reset();
     Ok(())
 }
 
@@ -0,0 +1,514 @@
+use crate::websocket::types::PriceDelta;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::{self, error::TrySendError};
+
+const CONSUMER_CAPACITY: usize = 256;
+const RATE_WINDOW: Duration = Duration::from_secs(5);
+
+/// Frontend subscriptions made through the stream commands; delivered via `price_update`.
+pub const UI_CONSUMER: &str = "ui";
+pub const CHART_CONSUMER: &str = "chart";
+pub const ALERTS_CONSUMER: &str = "alerts";
+pub const TRAY_CONSUMER: &str = "tray";
+pub const WIDGET_CONSUMER: &str = "widgets";
+pub const P2P_CONSUMER: &str = "p2p";
+pub const TRADING_CONSUMER: &str = "trading";
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct MultiplexerStats {
+    pub upstream_connections: usize,
+    pub upstream_subscriptions: usize,
+    pub messages_per_sec: f64,
+    pub tokens: Vec<TokenFanout>,
+    pub consumers: Vec<ConsumerStats>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenFanout {
+    pub token: String,
+    pub consumers: usize,
+    pub refs: u32,
+    pub messages_per_sec: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsumerStats {
+    pub name: String,
+    pub throttle_ms: u64,
+    pub tokens: usize,
+    pub delivered: u64,
+    pub coalesced: u64,
+    pub dropped: u64,
+}
+
+#[derive(Debug, Default)]
+struct RateMeter {
+    window_start: Option<Instant>,
+    count: u64,
+    rate: f64,
+}
+
+impl RateMeter {
+    fn tick(&mut self, now: Instant) {
+        let start = *self.window_start.get_or_insert(now);
+        self.count += 1;
+        let elapsed = now.duration_since(start);
+        if elapsed >= RATE_WINDOW {
+            self.rate = self.count as f64 / elapsed.as_secs_f64();
+            self.count = 0;
+            self.window_start = Some(now);
+        }
+    }
+
+    /// Last full-window rate; decays to zero once the token goes quiet.
+    fn rate(&self, now: Instant) -> f64 {
+        match self.window_start {
+            Some(start) if now.duration_since(start) < RATE_WINDOW * 2 => self.rate,
+            _ => 0.0,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Consumer {
+    throttle: Duration,
+    sink: Option<mpsc::Sender<PriceDelta>>,
+    refs: HashMap<String, u32>,
+    last_sent: HashMap<String, Instant>,
+    pending: HashMap<String, PriceDelta>,
+    delivered: u64,
+    coalesced: u64,
+    dropped: u64,
+    closed: bool,
+}
+
+impl Consumer {
+    fn new(throttle: Duration, sink: Option<mpsc::Sender<PriceDelta>>) -> Self {
+        Self {
+            throttle,
+            sink,
+            refs: HashMap::new(),
+            last_sent: HashMap::new(),
+            pending: HashMap::new(),
+            delivered: 0,
+            coalesced: 0,
+            dropped: 0,
+            closed: false,
+        }
+    }
+
+    fn offer(&mut self, delta: &PriceDelta, now: Instant) {
+        if self.sink.is_none() {
+            return;
+        }
+        let throttled = matches!(
+            self.last_sent.get(&delta.symbol),
+            Some(last) if now.duration_since(*last) < self.throttle
+        );
+        if !throttled {
+            let merged = match self.pending.remove(&delta.symbol) {
+                Some(older) => coalesce(older, delta),
+                None => delta.clone(),
+            };
+            self.send(merged, now);
+            return;
+        }
+
+        // Latest value wins, but fields missing from a partial delta are kept.
+        let merged = match self.pending.remove(&delta.symbol) {
+            Some(older) => {
+                self.coalesced += 1;
+                coalesce(older, delta)
+            }
+            None => delta.clone(),
+        };
+        self.pending.insert(delta.symbol.clone(), merged);
+    }
+
+    fn flush_due(&mut self, now: Instant) {
+        let due: Vec<String> = self
+            .pending
+            .keys()
+            .filter(|symbol| {
+                !matches!(
+                    self.last_sent.get(*symbol),
+                    Some(last) if now.duration_since(*last) < self.throttle
+                )
+            })
+            .cloned()
+            .collect();
+        for symbol in due {
+            if let Some(delta) = self.pending.remove(&symbol) {
+                self.send(delta, now);
+            }
+        }
+    }
+
+    fn send(&mut self, delta: PriceDelta, now: Instant) {
+        let Some(sink) = &self.sink else {
+            return;
+        };
+        let symbol = delta.symbol.clone();
+        match sink.try_send(delta) {
+            Ok(()) => {
+                self.delivered += 1;
+                self.last_sent.insert(symbol, now);
+            }
+            Err(TrySendError::Full(_)) => self.dropped += 1,
+            Err(TrySendError::Closed(_)) => {
+                self.sink = None;
+                self.closed = true;
+            }
+        }
+    }
+}
+
+fn coalesce(older: PriceDelta, newer: &PriceDelta) -> PriceDelta {
+    PriceDelta {
+        symbol: newer.symbol.clone(),
+        price: newer.price.or(older.price),
+        change: newer.change.or(older.change),
+        volume: newer.volume.or(older.volume),
+        ts: newer.ts.max(older.ts),
+        snapshot: newer.snapshot || older.snapshot,
+    }
+}
+
+/// Fans one upstream price subscription per token out to any number of local
+/// consumers. A token stays subscribed upstream while any consumer holds a ref.
+///
+/// The multiplexer only does bookkeeping; callers act on the token lists returned
+/// by `attach`/`detach`/`release` to (un)subscribe upstream.
+#[derive(Debug, Default)]
+pub struct PriceMultiplexer {
+    consumers: HashMap<String, Consumer>,
+    token_refs: HashMap<String, u32>,
+    token_rates: HashMap<String, RateMeter>,
+    total_rate: RateMeter,
+}
+
+impl PriceMultiplexer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a consumer that wants price deliveries, at most one per token per
+    /// `throttle`. Registering again replaces the channel but keeps its refs.
+    pub fn register(&mut self, consumer: &str, throttle: Duration) -> mpsc::Receiver<PriceDelta> {
+        let (tx, rx) = mpsc::channel(CONSUMER_CAPACITY);
+        let entry = self
+            .consumers
+            .entry(consumer.to_string())
+            .or_insert_with(|| Consumer::new(throttle, None));
+        entry.throttle = throttle;
+        entry.sink = Some(tx);
+        entry.closed = false;
+        entry.pending.clear();
+        rx
+    }
+
+    /// Adds one ref per token for `consumer`. Returns the tokens that just gained
+    /// their first ref and therefore need an upstream subscription.
+    pub fn attach(&mut self, consumer: &str, tokens: &[String]) -> Vec<String> {
+        let entry = self
+            .consumers
+            .entry(consumer.to_string())
+            .or_insert_with(|| Consumer::new(Duration::ZERO, None));
+
+        let mut first_refs = Vec::new();
+        for token in tokens {
+            *entry.refs.entry(token.clone()).or_insert(0) += 1;
+            let total = self.token_refs.entry(token.clone()).or_insert(0);
+            *total += 1;
+            if *total == 1 {
+                first_refs.push(token.clone());
+            }
+        }
+        first_refs
+    }
+
+    /// Drops one ref per token for `consumer`. Returns the tokens whose last ref
+    /// went away. Tokens the consumer never attached are ignored.
+    pub fn detach(&mut self, consumer: &str, tokens: &[String]) -> Vec<String> {
+        let Some(entry) = self.consumers.get_mut(consumer) else {
+            return Vec::new();
+        };
+
+        let mut released = Vec::new();
+        for token in tokens {
+            let Some(refs) = entry.refs.get_mut(token) else {
+                continue;
+            };
+            *refs -= 1;
+            if *refs == 0 {
+                entry.refs.remove(token);
+                entry.pending.remove(token);
+                entry.last_sent.remove(token);
+            }
+            if Self::release_ref(&mut self.token_refs, token, 1) {
+                self.token_rates.remove(token);
+                released.push(token.clone());
+            }
+        }
+        released
+    }
+
+    /// Removes a consumer with all of its refs.
+    pub fn release(&mut self, consumer: &str) -> Vec<String> {
+        let Some(entry) = self.consumers.remove(consumer) else {
+            return Vec::new();
+        };
+
+        let mut released = Vec::new();
+        for (token, refs) in entry.refs {
+            if Self::release_ref(&mut self.token_refs, &token, refs) {
+                self.token_rates.remove(&token);
+                released.push(token);
+            }
+        }
+        released
+    }
+
+    /// Releases consumers whose receiver was dropped.
+    pub fn prune_closed(&mut self) -> Vec<String> {
+        let closed: Vec<String> = self
+            .consumers
+            .iter()
+            .filter(|(_, consumer)| consumer.closed)
+            .map(|(name, _)| name.clone())
+            .collect();
+        closed.iter().flat_map(|name| self.release(name)).collect()
+    }
+
+    fn release_ref(token_refs: &mut HashMap<String, u32>, token: &str, count: u32) -> bool {
+        match token_refs.get_mut(token) {
+            Some(total) if *total > count => {
+                *total -= count;
+                false
+            }
+            Some(_) => {
+                token_refs.remove(token);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn dispatch(&mut self, delta: &PriceDelta) {
+        if !self.token_refs.contains_key(&delta.symbol) {
+            return;
+        }
+        let now = Instant::now();
+        self.total_rate.tick(now);
+        self.token_rates
+            .entry(delta.symbol.clone())
+            .or_default()
+            .tick(now);
+
+        for consumer in self.consumers.values_mut() {
+            if consumer.refs.contains_key(&delta.symbol) {
+                consumer.offer(delta, now);
+            }
+        }
+    }
+
+    /// Delivers coalesced updates whose throttle window has elapsed.
+    pub fn flush_due(&mut self) {
+        let now = Instant::now();
+        for consumer in self.consumers.values_mut() {
+            consumer.flush_due(now);
+        }
+    }
+
+    pub fn ref_count(&self, token: &str) -> u32 {
+        self.token_refs.get(token).copied().unwrap_or(0)
+    }
+
+    pub fn upstream_tokens(&self) -> Vec<String> {
+        self.token_refs.keys().cloned().collect()
+    }
+
+    pub fn consumer_tokens(&self, consumer: &str) -> Vec<String> {
+        self.consumers
+            .get(consumer)
+            .map(|entry| entry.refs.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn stats(&self) -> MultiplexerStats {
+        let now = Instant::now();
+        let mut tokens: Vec<TokenFanout> = self
+            .token_refs
+            .iter()
+            .map(|(token, refs)| TokenFanout {
+                token: token.clone(),
+                consumers: self
+                    .consumers
+                    .values()
+                    .filter(|consumer| consumer.refs.contains_key(token))
+                    .count(),
+                refs: *refs,
+                messages_per_sec: self
+                    .token_rates
+                    .get(token)
+                    .map(|meter| meter.rate(now))
+                    .unwrap_or(0.0),
+            })
+            .collect();
+        tokens.sort_by(|a, b| a.token.cmp(&b.token));
+
+        let mut consumers: Vec<ConsumerStats> = self
+            .consumers
+            .iter()
+            .map(|(name, consumer)| ConsumerStats {
+                name: name.clone(),
+                throttle_ms: consumer.throttle.as_millis() as u64,
+                tokens: consumer.refs.len(),
+                delivered: consumer.delivered,
+                coalesced: consumer.coalesced,
+                dropped: consumer.dropped,
+            })
+            .collect();
+        consumers.sort_by(|a, b| a.name.cmp(&b.name));
+
+        MultiplexerStats {
+            upstream_connections: 0,
+            upstream_subscriptions: self.token_refs.len(),
+            messages_per_sec: self.total_rate.rate(now),
+            tokens,
+            consumers,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn delta(symbol: &str, price: f64) -> PriceDelta {
+        PriceDelta {
+            symbol: symbol.to_string(),
+            price: Some(price),
+            change: None,
+            volume: None,
+            ts: 0,
+            snapshot: false,
+        }
+    }
+
+    #[test]
+    fn upstream_follows_first_and_last_ref() {
+        let mut mux = PriceMultiplexer::new();
+        let sol = vec!["SOL".to_string()];
+
+        assert_eq!(mux.attach(CHART_CONSUMER, &sol), sol);
+        assert!(mux.attach(ALERTS_CONSUMER, &sol).is_empty());
+        assert_eq!(mux.ref_count("SOL"), 2);
+
+        assert!(mux.detach(CHART_CONSUMER, &sol).is_empty());
+        assert_eq!(mux.detach(ALERTS_CONSUMER, &sol), sol);
+        assert_eq!(mux.ref_count("SOL"), 0);
+
+        // Detaching something never attached must not underflow other refs.
+        mux.attach(CHART_CONSUMER, &sol);
+        assert!(mux.detach(TRAY_CONSUMER, &sol).is_empty());
+        assert_eq!(mux.ref_count("SOL"), 1);
+    }
+
+    #[test]
+    fn throttled_consumer_gets_coalesced_latest() {
+        let mut mux = PriceMultiplexer::new();
+        let mut chart = mux.register(CHART_CONSUMER, Duration::ZERO);
+        let mut tray = mux.register(TRAY_CONSUMER, Duration::from_secs(10));
+        let sol = vec!["SOL".to_string()];
+        mux.attach(CHART_CONSUMER, &sol);
+        mux.attach(TRAY_CONSUMER, &sol);
+
+        for price in [1.0, 2.0, 3.0] {
+            mux.dispatch(&delta("SOL", price));
+        }
+        mux.flush_due();
+
+        let mut chart_prices = Vec::new();
+        while let Ok(update) = chart.try_recv() {
+            chart_prices.push(update.price.unwrap());
+        }
+        assert_eq!(chart_prices, vec![1.0, 2.0, 3.0]);
+
+        assert_eq!(tray.try_recv().unwrap().price, Some(1.0));
+        assert!(tray.try_recv().is_err());
+        let tray_stats = mux
+            .stats()
+            .consumers
+            .into_iter()
+            .find(|c| c.name == TRAY_CONSUMER)
+            .unwrap();
+        assert_eq!(tray_stats.coalesced, 1);
+    }
+
+    #[test]
+    fn dropped_receiver_releases_refs() {
+        let mut mux = PriceMultiplexer::new();
+        let rx = mux.register(ALERTS_CONSUMER, Duration::ZERO);
+        let sol = vec!["SOL".to_string()];
+        mux.attach(ALERTS_CONSUMER, &sol);
+        drop(rx);
+
+        mux.dispatch(&delta("SOL", 1.0));
+        assert_eq!(mux.prune_closed(), sol);
+        assert!(mux.upstream_tokens().is_empty());
+    }
+
+    #[test]
+    fn churn_across_200_tokens_leaves_no_refs() {
+        let mut mux = PriceMultiplexer::new();
+        let tokens: Vec<String> = (0..200).map(|i| format!("TOKEN{}", i)).collect();
+        let consumers = [UI_CONSUMER, CHART_CONSUMER, ALERTS_CONSUMER, TRAY_CONSUMER];
+        let _receivers: Vec<_> = consumers
+            .iter()
+            .map(|name| mux.register(name, Duration::from_millis(10)))
+            .collect();
+
+        // Mirror of what each consumer holds, to check the multiplexer against.
+        let mut expected: HashMap<(usize, usize), u32> = HashMap::new();
+        let mut upstream_subscribed = 0i64;
+        let mut seed = 0x2545_f491_u64;
+        for _ in 0..50_000 {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            let consumer = (seed >> 33) as usize % consumers.len();
+            let token = (seed >> 13) as usize % tokens.len();
+            let batch = std::slice::from_ref(&tokens[token]);
+            let held = expected.entry((consumer, token)).or_insert(0);
+
+            if seed & 1 == 0 || *held == 0 {
+                *held += 1;
+                upstream_subscribed += mux.attach(consumers[consumer], batch).len() as i64;
+            } else {
+                *held -= 1;
+                upstream_subscribed -= mux.detach(consumers[consumer], batch).len() as i64;
+            }
+            if seed % 7 == 0 {
+                mux.dispatch(&delta(&tokens[token], 1.0));
+            }
+        }
+
+        for (index, token) in tokens.iter().enumerate() {
+            let refs: u32 = (0..consumers.len())
+                .map(|c| expected.get(&(c, index)).copied().unwrap_or(0))
+                .sum();
+            assert_eq!(mux.ref_count(token), refs, "refcount drift for {}", token);
+        }
+        assert_eq!(upstream_subscribed, mux.upstream_tokens().len() as i64);
+
+        for name in consumers {
+            upstream_subscribed -= mux.release(name).len() as i64;
+        }
+        assert_eq!(upstream_subscribed, 0);
+        assert!(mux.upstream_tokens().is_empty());
+        assert_eq!(mux.stats().upstream_subscriptions, 0);
+    }
+}
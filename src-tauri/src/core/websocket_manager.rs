@@ -1,10 +1,12 @@
+use crate::core::stream_multiplexer::{PriceMultiplexer, UI_CONSUMER};
 use crate::market::get_coin_price;
 use crate::websocket::birdeye::BirdeyeStream;
 use crate::websocket::helius::HeliusStream;
-use crate::websocket::reconnect::ExponentialBackoff;
+use crate::websocket::reconnect::{CircuitBreaker, ExponentialBackoff};
 use crate::websocket::types::*;
 use rand::Rng;
 use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tauri::{AppHandle, Emitter, Manager};
@@ -18,6 +20,7 @@ const QUEUE_CAPACITY: usize = 1000;
 const POLL_INTERVAL: Duration = Duration::from_secs(5);
 const MAX_SYMBOL_BATCH: usize = 100;
 const UI_BATCH_WINDOW_MS: u64 = 16;
+const MULTIPLEXER_FLUSH_INTERVAL: Duration = Duration::from_millis(250);
 
 #[derive(Clone)]
 pub struct StreamConnection {
@@ -32,11 +35,82 @@ pub struct StreamConnection {
     pub statistics: Arc<RwLock<StreamStatisticsInternal>>,
     pub event_tx: broadcast::Sender<StreamEvent>,
     pub command_tx: Arc<Mutex<Option<mpsc::UnboundedSender<StreamCommand>>>>,
+    /// Shared with every connection; owned by the manager.
+    pub registry: Arc<RwLock<SubscriptionRegistry>>,
+    pub multiplexer: Arc<parking_lot::Mutex<PriceMultiplexer>>,
+    pub circuit: Arc<Mutex<CircuitBreaker>>,
+    /// Unix ms when the current outage began; `None` while connected.
+    pub gap_started: Arc<RwLock<Option<i64>>>,
+    pub last_gap: Arc<RwLock<Option<GapFilledEvent>>>,
+    /// Bumped for every connect attempt so a superseded socket's exit is ignored.
+    pub epoch: Arc<AtomicU64>,
+}
+
+impl StreamConnection {
+    /// Marks the socket as up. Returns when the outage began if this connect ends
+    /// one, so the caller can backfill the gap once subscriptions are restored.
+    pub async fn mark_connected(&self) -> Option<i64> {
+        *self.state.write().await = ConnectionStateInternal::Connected;
+        {
+            let mut fallback = self.fallback.write().await;
+            fallback.active = false;
+            fallback.reason = None;
+        }
+        self.backoff.lock().await.reset();
+        self.circuit.lock().await.record_success();
+
+        let gap_started = self.gap_started.write().await.take();
+        let mut stats = self.statistics.write().await;
+        stats.connected_at = Some(Instant::now());
+        if gap_started.is_some() {
+            stats.reconnect_count += 1;
+        }
+        gap_started
+    }
+
+    /// Starts the outage clock; later failures in the same outage keep the first time.
+    pub async fn mark_disconnected(&self) {
+        let mut gap_started = self.gap_started.write().await;
+        if gap_started.is_none() {
+            *gap_started = Some(chrono::Utc::now().timestamp_millis());
+        }
+    }
+
+    /// Queues an event, advances the subscription cursors, and forwards it to the
+    /// UI and to in-process listeners.
+    pub async fn publish_event(&self, app_handle: &AppHandle, event: StreamEvent) {
+        {
+            let mut queue = self.queue.lock().await;
+            queue.push(event.clone());
+            let dropped = queue.dropped_count();
+            let mut stats = self.statistics.write().await;
+            stats.dropped_messages = dropped;
+        }
+        self.registry.write().await.observe(&event);
+
+        match &event {
+            StreamEvent::PriceUpdate(delta) => {
+                self.multiplexer.lock().dispatch(delta);
+                let _ = app_handle.emit("price_update", delta);
+            }
+            StreamEvent::TransactionUpdate(tx) => {
+                let _ = app_handle.emit("transaction_update", tx);
+            }
+            StreamEvent::StatusChange(status) => {
+                let _ = app_handle.emit("stream_status_change", status);
+            }
+            StreamEvent::Error { .. } => {}
+        }
+
+        let _ = self.event_tx.send(event);
+    }
 }
 
 #[derive(Clone)]
 pub struct WebSocketManager {
     connections: Arc<RwLock<HashMap<StreamProvider, StreamConnection>>>,
+    registry: Arc<RwLock<SubscriptionRegistry>>,
+    multiplexer: Arc<parking_lot::Mutex<PriceMultiplexer>>,
     app_handle: AppHandle,
 }
 
@@ -104,15 +178,41 @@ impl WebSocketManager {
     pub fn new(app_handle: AppHandle) -> Self {
         let manager = Self {
             connections: Arc::new(RwLock::new(HashMap::new())),
+            registry: Arc::new(RwLock::new(SubscriptionRegistry::default())),
+            multiplexer: Arc::new(parking_lot::Mutex::new(PriceMultiplexer::new())),
             app_handle,
         };
 
         manager.initialize_connection(StreamProvider::Birdeye);
         manager.initialize_connection(StreamProvider::Helius);
 
+        let flusher = manager.clone();
+        tauri::async_runtime::spawn(async move {
+            flusher.run_multiplexer_flush().await;
+        });
+
         manager
     }
 
+    /// Delivers throttled price updates that came due and drops consumers whose
+    /// receiver went away, unsubscribing upstream tokens nobody watches anymore.
+    async fn run_multiplexer_flush(&self) {
+        let mut ticker = tokio::time::interval(MULTIPLEXER_FLUSH_INTERVAL);
+        loop {
+            ticker.tick().await;
+            let orphaned = {
+                let mut multiplexer = self.multiplexer.lock();
+                multiplexer.flush_due();
+                multiplexer.prune_closed()
+            };
+            if !orphaned.is_empty() {
+                if let Err(err) = self.unsubscribe_upstream(orphaned).await {
+                    eprintln!("Failed to release orphaned price subscriptions: {}", err);
+                }
+            }
+        }
+    }
+
     fn initialize_connection(&self, provider: StreamProvider) {
         let (tx, _rx) = broadcast::channel(1024);
 
@@ -135,6 +235,12 @@ impl WebSocketManager {
             statistics: Arc::new(RwLock::new(StreamStatisticsInternal::default())),
             event_tx: tx,
             command_tx: Arc::new(Mutex::new(None)),
+            registry: self.registry.clone(),
+            multiplexer: self.multiplexer.clone(),
+            circuit: Arc::new(Mutex::new(CircuitBreaker::default())),
+            gap_started: Arc::new(RwLock::new(None)),
+            last_gap: Arc::new(RwLock::new(None)),
+            epoch: Arc::new(AtomicU64::new(0)),
         };
 
         self.connections
@@ -159,60 +265,105 @@ impl WebSocketManager {
 
     async fn start_connection(&self, provider: StreamProvider) {
         if let Some(connection) = self.get_connection(&provider).await {
+            let epoch = connection.epoch.fetch_add(1, Ordering::SeqCst) + 1;
             self.transition_state(&connection, ConnectionStateInternal::Connecting)
                 .await;
             self.emit_status(&connection).await;
 
-            match provider {
+            let result = match provider {
                 StreamProvider::Birdeye => {
-                    let stream = BirdeyeStream::new(connection.clone(), self.app_handle.clone());
-                    if let Err(err) = stream.start().await {
-                        self.handle_connection_error(&connection, err).await;
-                    }
+                    BirdeyeStream::new(connection.clone(), self.app_handle.clone())
+                        .start()
+                        .await
                 }
                 StreamProvider::Helius => {
-                    let stream = HeliusStream::new(connection.clone(), self.app_handle.clone());
-                    if let Err(err) = stream.start().await {
-                        self.handle_connection_error(&connection, err).await;
-                    }
+                    HeliusStream::new(connection.clone(), self.app_handle.clone())
+                        .start()
+                        .await
                 }
+            };
+
+            // A newer attempt (manual or heartbeat reconnect) owns the connection now.
+            if connection.epoch.load(Ordering::SeqCst) != epoch {
+                return;
             }
+
+            // A clean close is still an outage from the subscriber's point of view.
+            let error = result
+                .err()
+                .unwrap_or_else(|| anyhow::anyhow!("stream closed by server"));
+            self.handle_connection_error(&connection, error).await;
         }
     }
 
     async fn handle_connection_error(&self, connection: &StreamConnection, error: anyhow::Error) {
+        connection.mark_disconnected().await;
         {
             let mut state = connection.state.write().await;
             *state = ConnectionStateInternal::Failed;
         }
-        {
+        let was_polling = {
             let mut fallback = connection.fallback.write().await;
+            let was_active = fallback.active;
             fallback.active = true;
             fallback.reason = Some(error.to_string());
-        }
+            was_active
+        };
+        let tripped = connection
+            .circuit
+            .lock()
+            .await
+            .record_failure(&error.to_string());
         self.emit_status(connection).await;
+        if tripped {
+            self.emit_error(
+                connection,
+                format!("Reconnects paused after repeated failures: {}", error),
+            )
+            .await;
+        }
 
-        let manager = self.clone();
-        let provider = connection.provider.clone();
-        tauri::async_runtime::spawn(async move {
-            manager.start_polling_fallback(provider.clone()).await;
-        });
-
-        if let Some(delay) = connection.backoff.lock().await.next_delay() {
+        if !was_polling {
             let manager = self.clone();
             let provider = connection.provider.clone();
-            self.schedule_reconnect(provider, delay, manager);
+            tauri::async_runtime::spawn(async move {
+                manager.start_polling_fallback(provider.clone()).await;
+            });
+        }
+
+        if let Some(delay) = self.next_reconnect_delay(connection).await {
+            self.schedule_reconnect(connection, delay);
         }
     }
 
-    fn schedule_reconnect(
-        &self,
-        provider: StreamProvider,
-        delay: Duration,
-        manager: WebSocketManager,
-    ) {
+    /// Backoff delay while the circuit is closed; the remaining cooldown (then a
+    /// single probe) while it is open.
+    async fn next_reconnect_delay(&self, connection: &StreamConnection) -> Option<Duration> {
+        let cooldown = {
+            let circuit = connection.circuit.lock().await;
+            (circuit.state() != CircuitState::Closed).then(|| circuit.remaining_cooldown())
+        };
+        let mut backoff = connection.backoff.lock().await;
+        match cooldown {
+            Some(cooldown) => {
+                backoff.reset();
+                Some(cooldown)
+            }
+            None => backoff.next_delay(),
+        }
+    }
+
+    fn schedule_reconnect(&self, connection: &StreamConnection, delay: Duration) {
+        let manager = self.clone();
+        let provider = connection.provider.clone();
+        let epoch = connection.epoch.clone();
+        let expected = epoch.load(Ordering::SeqCst);
         tauri::async_runtime::spawn(async move {
             tokio::time::sleep(delay).await;
+            // Another reconnect already started in the meantime.
+            if epoch.load(Ordering::SeqCst) != expected {
+                return;
+            }
             manager.start_connection(provider).await;
         });
     }
@@ -240,12 +391,10 @@ impl WebSocketManager {
             loop {
                 ticker.tick().await;
 
+                // Reconnects in flight are driven by backoff; only watch live sockets.
                 let state = connection.state.read().await.clone();
-                if matches!(
-                    state,
-                    ConnectionStateInternal::Disconnected | ConnectionStateInternal::Failed
-                ) {
-                    break;
+                if !matches!(state, ConnectionStateInternal::Connected) {
+                    continue;
                 }
 
                 let last_message = connection.last_message.read().await.clone();
@@ -262,27 +411,36 @@ impl WebSocketManager {
     }
 
     pub async fn force_reconnect(&self, connection: &StreamConnection, reason: &str) {
+        // Supersede the running socket so its exit is not counted as another failure.
+        connection.epoch.fetch_add(1, Ordering::SeqCst);
+        if let Some(tx) = connection.command_tx.lock().await.take() {
+            let _ = tx.send(StreamCommand::Close);
+        }
+        connection.mark_disconnected().await;
+
         {
             let mut state = connection.state.write().await;
             *state = ConnectionStateInternal::Disconnecting;
         }
 
-        {
+        let was_polling = {
             let mut fallback = connection.fallback.write().await;
+            let was_active = fallback.active;
             fallback.active = true;
             fallback.reason = Some(reason.to_string());
-        }
+            was_active
+        };
         self.emit_status(connection).await;
-        self.start_polling_fallback(connection.provider.clone())
-            .await;
+        if !was_polling {
+            self.start_polling_fallback(connection.provider.clone())
+                .await;
+        }
 
-        let delay = connection
-            .backoff
-            .lock()
+        let delay = self
+            .next_reconnect_delay(connection)
             .await
-            .next_delay()
             .unwrap_or(Duration::from_secs(60));
-        self.schedule_reconnect(connection.provider.clone(), delay, self.clone());
+        self.schedule_reconnect(connection, delay);
     }
 
     async fn emit_status(&self, connection: &StreamConnection) {
@@ -303,7 +461,66 @@ impl WebSocketManager {
         let _ = self.app_handle.emit("stream_error", &event);
     }
 
+    /// Subscribes on behalf of the frontend stream commands.
     pub async fn subscribe_prices(&self, symbols: Vec<String>) -> anyhow::Result<()> {
+        self.subscribe_prices_as(UI_CONSUMER, symbols).await
+    }
+
+    pub async fn unsubscribe_prices(&self, symbols: Vec<String>) -> anyhow::Result<()> {
+        self.unsubscribe_prices_as(UI_CONSUMER, symbols).await
+    }
+
+    /// Takes one ref per symbol for `consumer`. Only symbols nobody else watches
+    /// yet are subscribed upstream.
+    pub async fn subscribe_prices_as(
+        &self,
+        consumer: &str,
+        symbols: Vec<String>,
+    ) -> anyhow::Result<()> {
+        let first_refs = self.multiplexer.lock().attach(consumer, &symbols);
+        if first_refs.is_empty() {
+            return Ok(());
+        }
+        if let Err(err) = self.subscribe_upstream(first_refs).await {
+            self.multiplexer.lock().detach(consumer, &symbols);
+            return Err(err);
+        }
+        Ok(())
+    }
+
+    /// Drops one ref per symbol for `consumer`; the upstream subscription goes away
+    /// with the last ref.
+    pub async fn unsubscribe_prices_as(
+        &self,
+        consumer: &str,
+        symbols: Vec<String>,
+    ) -> anyhow::Result<()> {
+        let released = self.multiplexer.lock().detach(consumer, &symbols);
+        if released.is_empty() {
+            return Ok(());
+        }
+        self.unsubscribe_upstream(released).await
+    }
+
+    /// Local feed of price updates for `consumer`, at most one per symbol per
+    /// `throttle`. Dropping the receiver releases the consumer's refs.
+    pub fn price_feed(&self, consumer: &str, throttle: Duration) -> mpsc::Receiver<PriceDelta> {
+        self.multiplexer.lock().register(consumer, throttle)
+    }
+
+    pub fn price_consumer_tokens(&self, consumer: &str) -> Vec<String> {
+        self.multiplexer.lock().consumer_tokens(consumer)
+    }
+
+    pub async fn release_price_consumer(&self, consumer: &str) -> anyhow::Result<()> {
+        let released = self.multiplexer.lock().release(consumer);
+        if released.is_empty() {
+            return Ok(());
+        }
+        self.unsubscribe_upstream(released).await
+    }
+
+    async fn subscribe_upstream(&self, symbols: Vec<String>) -> anyhow::Result<()> {
         let connection = self
             .get_connection(&StreamProvider::Birdeye)
             .await
@@ -324,6 +541,7 @@ impl WebSocketManager {
         if new_symbols.is_empty() {
             return Ok(());
         }
+        self.registry.write().await.register_prices(&new_symbols);
 
         let mut batches: Vec<Vec<String>> = Vec::new();
         let mut current_batch: Vec<String> = Vec::new();
@@ -354,7 +572,7 @@ impl WebSocketManager {
         Ok(())
     }
 
-    pub async fn unsubscribe_prices(&self, symbols: Vec<String>) -> anyhow::Result<()> {
+    async fn unsubscribe_upstream(&self, symbols: Vec<String>) -> anyhow::Result<()> {
         let connection = self
             .get_connection(&StreamProvider::Birdeye)
             .await
@@ -371,6 +589,7 @@ impl WebSocketManager {
         if !to_remove.is_empty() {
             subs.prices.retain(|s| !to_remove.contains(s));
             drop(subs);
+            self.registry.write().await.unregister_prices(&to_remove);
 
             let command_tx = connection.command_tx.lock().await;
             if let Some(ref tx) = *command_tx {
@@ -398,6 +617,7 @@ impl WebSocketManager {
 
         if !new_addresses.is_empty() {
             drop(subs);
+            self.registry.write().await.register_wallets(&new_addresses);
             let command_tx = connection.command_tx.lock().await;
             if let Some(ref tx) = *command_tx {
                 let _ = tx.send(StreamCommand::SubscribeWallets(new_addresses));
@@ -424,6 +644,7 @@ impl WebSocketManager {
         if !to_remove.is_empty() {
             subs.wallets.retain(|a| !to_remove.contains(a));
             drop(subs);
+            self.registry.write().await.unregister_wallets(&to_remove);
 
             let command_tx = connection.command_tx.lock().await;
             if let Some(ref tx) = *command_tx {
@@ -435,9 +656,10 @@ impl WebSocketManager {
     }
 
     pub async fn get_status(&self) -> Vec<StreamStatus> {
+        let connections: Vec<StreamConnection> =
+            self.connections.read().await.values().cloned().collect();
         let mut statuses = Vec::new();
-        let connections = self.connections.read().await;
-        for (_, conn) in connections.iter() {
+        for conn in &connections {
             if let Ok(status) = self.current_status(conn).await {
                 statuses.push(status);
             }
@@ -445,12 +667,26 @@ impl WebSocketManager {
         statuses
     }
 
+    async fn upstream_connection_count(&self) -> usize {
+        let connections: Vec<StreamConnection> =
+            self.connections.read().await.values().cloned().collect();
+        let mut connected = 0;
+        for conn in &connections {
+            if matches!(*conn.state.read().await, ConnectionStateInternal::Connected) {
+                connected += 1;
+            }
+        }
+        connected
+    }
+
     pub async fn reconnect(&self, provider: StreamProvider) -> anyhow::Result<()> {
         let connection = self
             .get_connection(&provider)
             .await
             .ok_or_else(|| anyhow::anyhow!("Connection not found"))?;
 
+        // A manual reconnect is an explicit retry, so it bypasses an open circuit.
+        connection.circuit.lock().await.reset();
         self.force_reconnect(&connection, "manual reconnect").await;
         Ok(())
     }
@@ -466,6 +702,16 @@ impl WebSocketManager {
         let subscriptions = connection.subscriptions.read().await.clone();
         let fallback = connection.fallback.read().await.clone();
         let stats = connection.statistics.read().await.clone();
+        let circuit_breaker = connection.circuit.lock().await.status();
+        let last_gap = connection.last_gap.read().await.clone();
+        // Prices are multiplexed over the Birdeye socket only.
+        let multiplexer = if connection.provider == StreamProvider::Birdeye {
+            let mut stats = self.multiplexer.lock().stats();
+            stats.upstream_connections = self.upstream_connection_count().await;
+            Some(stats)
+        } else {
+            None
+        };
 
         Ok(StreamStatus {
             provider: connection.provider.clone(),
@@ -503,6 +749,11 @@ impl WebSocketManager {
                 interval_ms: fallback.interval.as_millis() as u64,
                 reason: fallback.reason.clone(),
             }),
+            circuit_breaker: Some(circuit_breaker),
+            last_gap,
+            multiplexer,
+            // Filled in by `get_stream_status`, which has the trading state.
+            order_protection: Vec::new(),
         })
     }
 
@@ -570,28 +821,7 @@ impl WebSocketManager {
     }
 
     pub async fn enqueue_event(&self, connection: &StreamConnection, event: StreamEvent) {
-        {
-            let mut queue = connection.queue.lock().await;
-            queue.push(event.clone());
-            let dropped = queue.dropped_count();
-            let mut stats = connection.statistics.write().await;
-            stats.dropped_messages = dropped;
-        }
-
-        match &event {
-            StreamEvent::PriceUpdate(delta) => {
-                let _ = self.app_handle.emit("price_update", delta);
-            }
-            StreamEvent::TransactionUpdate(tx) => {
-                let _ = self.app_handle.emit("transaction_update", tx);
-            }
-            StreamEvent::StatusChange(status) => {
-                let _ = self.app_handle.emit("stream_status_change", status);
-            }
-            StreamEvent::Error { .. } => {}
-        }
-
-        let _ = connection.event_tx.send(event);
+        connection.publish_event(&self.app_handle, event).await;
     }
 
     pub async fn drain_queue(&self, provider: StreamProvider) -> Vec<StreamEvent> {
@@ -1,5 +1,8 @@
+use crate::config::DataPaths;
 use crate::data::database::{CompressionConfig, CompressionStats, SharedCompressionManager};
-use tauri::{Manager, State};
+use crate::data::event_store::SharedEventStore;
+use crate::data::tiers::{ArchiveInfo, ArchiveManifest};
+use tauri::State;
 
 #[tauri::command]
 pub async fn get_compression_stats(
@@ -28,19 +31,51 @@ pub async fn compress_old_data(
         .await
         .map_err(|e| e.to_string())?;
 
+    manager
+        .archive_cold_events()
+        .await
+        .map_err(|e| e.to_string())?;
+
     Ok(events_compressed + trades_compressed)
 }
 
 #[tauri::command]
 pub async fn update_compression_config(
     compression_manager: State<'_, SharedCompressionManager>,
+    event_store: State<'_, SharedEventStore>,
     config: CompressionConfig,
 ) -> Result<(), String> {
+    config.validate()?;
+    let warm_query_row_limit = config.warm_query_row_limit;
+
     let manager = compression_manager.read().await;
     manager
         .update_config(config)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(|e| e.to_string())?;
+
+    event_store
+        .read()
+        .await
+        .set_warm_query_row_limit(warm_query_row_limit);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn list_event_archives(
+    compression_manager: State<'_, SharedCompressionManager>,
+) -> Result<Vec<ArchiveInfo>, String> {
+    let manager = compression_manager.read().await;
+    manager.list_archives().await
+}
+
+#[tauri::command]
+pub async fn restore_event_archive(
+    compression_manager: State<'_, SharedCompressionManager>,
+    archive_id: String,
+) -> Result<ArchiveManifest, String> {
+    let manager = compression_manager.read().await;
+    manager.restore_archive(&archive_id).await
 }
 
 #[tauri::command]
@@ -68,8 +103,7 @@ pub async fn get_database_size(app_handle: tauri::AppHandle) -> Result<DatabaseS
     use std::fs;
 
     let mut data_dir = app_handle
-        .path()
-        .app_data_dir()
+        .app_data_root()
         .map_err(|e| format!("Unable to resolve app data directory: {}", e))?;
 
     let mut total_size = 0u64;
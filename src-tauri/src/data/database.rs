@@ -1,4 +1,8 @@
+use crate::data::tiers::{
+    self, ArchiveInfo, ArchiveManifest, TierStats, DEFAULT_BLOCK_ROWS, DEFAULT_WARM_QUERY_ROW_LIMIT,
+};
 use crate::trading::types::Order;
+use crate::utils::ensure_column;
 use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, Pool, Row, Sqlite, SqlitePool};
@@ -8,12 +12,35 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 use zstd;
 
+/// Events stay hot (uncompressed) for `age_threshold_days`, then move to
+/// compressed warm blocks; warm blocks older than `cold_after_days` are
+/// exported to archive files and dropped from queries.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CompressionConfig {
     pub enabled: bool,
     pub age_threshold_days: i64,
     pub compression_level: i32,
     pub auto_compress: bool,
+    /// `None` keeps warm blocks in the database indefinitely.
+    #[serde(default = "default_cold_after_days")]
+    pub cold_after_days: Option<i64>,
+    /// Maximum warm rows a single event query returns.
+    #[serde(default = "default_warm_query_row_limit")]
+    pub warm_query_row_limit: i64,
+    #[serde(default = "default_block_rows")]
+    pub block_rows: i64,
+}
+
+fn default_cold_after_days() -> Option<i64> {
+    Some(180)
+}
+
+fn default_warm_query_row_limit() -> i64 {
+    DEFAULT_WARM_QUERY_ROW_LIMIT
+}
+
+fn default_block_rows() -> i64 {
+    DEFAULT_BLOCK_ROWS
 }
 
 impl Default for CompressionConfig {
@@ -23,7 +50,33 @@ impl Default for CompressionConfig {
             age_threshold_days: 7,
             compression_level: 3,
             auto_compress: true,
+            cold_after_days: default_cold_after_days(),
+            warm_query_row_limit: default_warm_query_row_limit(),
+            block_rows: default_block_rows(),
+        }
+    }
+}
+
+impl CompressionConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.age_threshold_days < 0 {
+            return Err("Hot retention must not be negative".to_string());
+        }
+        if let Some(cold_after_days) = self.cold_after_days {
+            if cold_after_days <= self.age_threshold_days {
+                return Err(format!(
+                    "Cold tier boundary ({} days) must be later than the hot boundary ({} days)",
+                    cold_after_days, self.age_threshold_days
+                ));
+            }
+        }
+        if self.warm_query_row_limit <= 0 {
+            return Err("Warm query row limit must be positive".to_string());
+        }
+        if self.block_rows <= 0 {
+            return Err("Block size must be at least one row".to_string());
         }
+        Ok(())
     }
 }
 
@@ -35,6 +88,8 @@ pub struct CompressionStats {
     pub num_compressed_records: i64,
     pub space_saved_mb: f64,
     pub last_compression_run: Option<String>,
+    #[serde(default)]
+    pub tiers: TierStats,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -56,6 +111,7 @@ struct DecompressedCacheEntry {
 
 pub struct CompressionManager {
     pool: Pool<Sqlite>,
+    archive_dir: PathBuf,
     config: Arc<RwLock<CompressionConfig>>,
     decompression_cache: Arc<RwLock<HashMap<String, DecompressedCacheEntry>>>,
     stats_cache: Arc<RwLock<Option<(CompressionStats, DateTime<Utc>)>>>,
@@ -65,9 +121,14 @@ impl CompressionManager {
     pub async fn new(db_path: PathBuf) -> Result<Self, sqlx::Error> {
         let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
         let pool = SqlitePool::connect(&db_url).await?;
+        let archive_dir = db_path
+            .parent()
+            .map(|dir| dir.join("archives"))
+            .unwrap_or_else(|| PathBuf::from("archives"));
 
         let manager = Self {
             pool,
+            archive_dir,
             config: Arc::new(RwLock::new(CompressionConfig::default())),
             decompression_cache: Arc::new(RwLock::new(HashMap::new())),
             stats_cache: Arc::new(RwLock::new(None)),
@@ -137,6 +198,30 @@ impl CompressionManager {
         .execute(&self.pool)
         .await?;
 
+        ensure_column(
+            &self.pool,
+            "compression_config",
+            "cold_after_days",
+            "INTEGER DEFAULT 180",
+        )
+        .await?;
+        ensure_column(
+            &self.pool,
+            "compression_config",
+            "warm_query_row_limit",
+            "INTEGER NOT NULL DEFAULT 10000",
+        )
+        .await?;
+        ensure_column(
+            &self.pool,
+            "compression_config",
+            "block_rows",
+            "INTEGER NOT NULL DEFAULT 1000",
+        )
+        .await?;
+
+        tiers::ensure_schema(&self.pool).await?;
+
         // Initialize config if not exists
         sqlx::query(
             r#"
@@ -157,7 +242,8 @@ impl CompressionManager {
     async fn load_config(&self) -> Result<(), sqlx::Error> {
         let row = sqlx::query(
             r#"
-            SELECT enabled, age_threshold_days, compression_level, auto_compress
+            SELECT enabled, age_threshold_days, compression_level, auto_compress,
+                cold_after_days, warm_query_row_limit, block_rows
             FROM compression_config
             WHERE id = 1
             "#,
@@ -170,6 +256,9 @@ impl CompressionManager {
         config.age_threshold_days = row.get::<i64, _>("age_threshold_days");
         config.compression_level = row.get::<i32, _>("compression_level");
         config.auto_compress = row.get::<i32, _>("auto_compress") != 0;
+        config.cold_after_days = row.get::<Option<i64>, _>("cold_after_days");
+        config.warm_query_row_limit = row.get::<i64, _>("warm_query_row_limit");
+        config.block_rows = row.get::<i64, _>("block_rows");
 
         Ok(())
     }
@@ -182,7 +271,8 @@ impl CompressionManager {
         sqlx::query(
             r#"
             UPDATE compression_config
-            SET enabled = ?1, age_threshold_days = ?2, compression_level = ?3, auto_compress = ?4, updated_at = ?5
+            SET enabled = ?1, age_threshold_days = ?2, compression_level = ?3, auto_compress = ?4, updated_at = ?5,
+                cold_after_days = ?6, warm_query_row_limit = ?7, block_rows = ?8
             WHERE id = 1
             "#,
         )
@@ -191,6 +281,9 @@ impl CompressionManager {
         .bind(new_config.compression_level)
         .bind(if new_config.auto_compress { 1 } else { 0 })
         .bind(Utc::now().to_rfc3339())
+        .bind(new_config.cold_after_days)
+        .bind(new_config.warm_query_row_limit)
+        .bind(new_config.block_rows)
         .execute(&self.pool)
         .await?;
 
@@ -289,6 +382,8 @@ impl CompressionManager {
         Ok(decompressed)
     }
 
+    /// Moves events past the hot window into compressed warm blocks and
+    /// returns the number of events moved.
     pub async fn compress_old_events(&self) -> Result<i64, Box<dyn std::error::Error>> {
         let config = self.config.read().await.clone();
 
@@ -299,51 +394,18 @@ impl CompressionManager {
         let start_time = std::time::Instant::now();
         let threshold_date = Utc::now() - Duration::days(config.age_threshold_days);
 
-        // Get old events that aren't compressed yet
-        let old_events = sqlx::query(
-            r#"
-            SELECT id, event_data, timestamp
-            FROM events
-            WHERE timestamp < ?1
-            AND id NOT IN (SELECT id FROM compressed_data WHERE record_type = 'event')
-            LIMIT 1000
-            "#,
+        let summary = tiers::compact_hot_events(
+            &self.pool,
+            threshold_date,
+            config.block_rows,
+            config.compression_level,
         )
-        .bind(threshold_date.to_rfc3339())
-        .fetch_all(&self.pool)
         .await?;
 
-        let mut compressed_count = 0;
-        let mut space_saved = 0i64;
-
-        for event in old_events {
-            let event_id: String = event.get("id");
-            let event_data: String = event.get("event_data");
-            let timestamp_str: String = event.get("timestamp");
-
-            let data = event_data.as_bytes();
-            let original_size = data.len() as i64;
-
-            let timestamp = DateTime::parse_from_rfc3339(&timestamp_str)?.with_timezone(&Utc);
-
-            self.compress_data(data, "event", &event_id, timestamp)
-                .await?;
-
-            // Get compressed size
-            let compressed =
-                sqlx::query("SELECT compressed_size FROM compressed_data WHERE id = ?1")
-                    .bind(&event_id)
-                    .fetch_one(&self.pool)
-                    .await?;
-
-            space_saved += original_size - compressed.get::<i64, _>("compressed_size");
-            compressed_count += 1;
-        }
-
         let compression_time = start_time.elapsed().as_millis() as i64;
 
         // Log compression run
-        if compressed_count > 0 {
+        if summary.rows > 0 {
             sqlx::query(
                 r#"
                 INSERT INTO compression_log (id, records_compressed, space_saved_bytes, compression_time_ms, timestamp)
@@ -351,15 +413,50 @@ impl CompressionManager {
                 "#,
             )
             .bind(uuid::Uuid::new_v4().to_string())
-            .bind(compressed_count)
-            .bind(space_saved)
+            .bind(summary.rows)
+            .bind(summary.original_bytes - summary.compressed_bytes)
             .bind(compression_time)
             .bind(Utc::now().to_rfc3339())
             .execute(&self.pool)
             .await?;
+
+            *self.stats_cache.write().await = None;
         }
 
-        Ok(compressed_count)
+        Ok(summary.rows)
+    }
+
+    /// Exports warm blocks past the cold boundary to an archive file under
+    /// the archive directory.
+    pub async fn archive_cold_events(
+        &self,
+    ) -> Result<Option<ArchiveManifest>, Box<dyn std::error::Error>> {
+        let config = self.config.read().await.clone();
+
+        let Some(cold_after_days) = config.cold_after_days.filter(|_| config.enabled) else {
+            return Ok(None);
+        };
+
+        let threshold_date = Utc::now() - Duration::days(cold_after_days);
+        let manifest =
+            tiers::archive_warm_blocks(&self.pool, threshold_date, &self.archive_dir).await?;
+        if manifest.is_some() {
+            *self.stats_cache.write().await = None;
+        }
+
+        Ok(manifest)
+    }
+
+    /// Loads a cold archive back into the warm tier so its events are
+    /// queryable again.
+    pub async fn restore_archive(&self, archive_id: &str) -> Result<ArchiveManifest, String> {
+        let manifest = tiers::restore_archive(&self.pool, archive_id).await?;
+        *self.stats_cache.write().await = None;
+        Ok(manifest)
+    }
+
+    pub async fn list_archives(&self) -> Result<Vec<ArchiveInfo>, String> {
+        tiers::list_archives(&self.pool).await
     }
 
     pub async fn compress_old_trades(&self) -> Result<i64, Box<dyn std::error::Error>> {
@@ -462,9 +559,12 @@ impl CompressionManager {
         .fetch_optional(&self.pool)
         .await?;
 
-        let total_uncompressed = totals.get::<i64, _>("total_original");
-        let total_compressed = totals.get::<i64, _>("total_compressed");
-        let num_records = totals.get::<i64, _>("num_records");
+        let tiers = tiers::tier_stats(&self.pool).await?;
+
+        // Warm blocks are compressed in place alongside per-record entries
+        let total_uncompressed = totals.get::<i64, _>("total_original") + tiers.warm.original_bytes;
+        let total_compressed = totals.get::<i64, _>("total_compressed") + tiers.warm.stored_bytes;
+        let num_records = totals.get::<i64, _>("num_records") + tiers.warm.rows;
 
         let compression_ratio = if total_uncompressed > 0 {
             ((total_uncompressed - total_compressed) as f64 / total_uncompressed as f64) * 100.0
@@ -479,6 +579,7 @@ impl CompressionManager {
             num_compressed_records: num_records,
             space_saved_mb: (total_uncompressed - total_compressed) as f64 / 1024.0 / 1024.0,
             last_compression_run: last_run.map(|r| r.get::<String, _>("timestamp")),
+            tiers,
         };
 
         // Update cache
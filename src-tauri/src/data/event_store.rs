@@ -1,8 +1,10 @@
+use crate::data::tiers::{self, DEFAULT_WARM_QUERY_ROW_LIMIT};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, Pool, Sqlite, SqlitePool};
 use std::collections::HashMap;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicI64, Ordering};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use uuid::Uuid;
@@ -105,10 +107,21 @@ pub struct EventFilter {
     pub offset: Option<i64>,
 }
 
+/// Events matching a query. Warm (compressed) rows come first in timestamp
+/// order, followed by hot rows in sequence order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventQueryResult {
+    pub events: Vec<EventRecord>,
+    pub warm_rows: usize,
+    /// Set when the warm row limit cut the result short.
+    pub warm_truncated: bool,
+}
+
 pub struct EventStore {
     pool: Pool<Sqlite>,
     sequence_counters: Arc<RwLock<HashMap<String, i64>>>,
     point_in_time_cache: Arc<RwLock<HashMap<String, (DateTime<Utc>, String)>>>,
+    warm_query_row_limit: AtomicI64,
 }
 
 impl EventStore {
@@ -129,6 +142,7 @@ impl EventStore {
             pool,
             sequence_counters: Arc::new(RwLock::new(HashMap::new())),
             point_in_time_cache: Arc::new(RwLock::new(HashMap::new())),
+            warm_query_row_limit: AtomicI64::new(DEFAULT_WARM_QUERY_ROW_LIMIT),
         };
 
         store.initialize().await?;
@@ -137,6 +151,15 @@ impl EventStore {
         Ok(store)
     }
 
+    /// Folds the write-ahead log back into the event database, so a quit
+    /// right after a burst of events leaves nothing in the WAL.
+    pub async fn checkpoint(&self) -> Result<(), sqlx::Error> {
+        sqlx::query("PRAGMA wal_checkpoint(TRUNCATE)")
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
     async fn initialize(&self) -> Result<(), sqlx::Error> {
         // Create events table
         sqlx::query(
@@ -190,6 +213,8 @@ impl EventStore {
         .execute(&self.pool)
         .await?;
 
+        tiers::ensure_schema(&self.pool).await?;
+
         Ok(())
     }
 
@@ -204,9 +229,17 @@ impl EventStore {
         .fetch_all(&self.pool)
         .await?;
 
+        // Aggregates whose rows were all moved to the warm tier
+        let floors = sqlx::query_as::<_, (String, i64)>(
+            "SELECT aggregate_id, sequence FROM event_sequence_floor",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
         let mut counters = self.sequence_counters.write().await;
-        for (aggregate_id, max_sequence) in records {
-            counters.insert(aggregate_id, max_sequence);
+        for (aggregate_id, max_sequence) in records.into_iter().chain(floors) {
+            let counter = counters.entry(aggregate_id).or_insert(0);
+            *counter = (*counter).max(max_sequence);
         }
 
         Ok(())
@@ -271,7 +304,56 @@ impl EventStore {
         .to_string()
     }
 
+    pub fn set_warm_query_row_limit(&self, limit: i64) {
+        self.warm_query_row_limit
+            .store(limit.max(1), Ordering::Relaxed);
+    }
+
     pub async fn get_events(&self, filter: EventFilter) -> Result<Vec<EventRecord>, sqlx::Error> {
+        Ok(self.query_events(filter).await?.events)
+    }
+
+    /// Queries the warm and hot tiers. Warm blocks are decompressed one at a
+    /// time and contribute at most the warm row limit to a single query.
+    pub async fn query_events(&self, filter: EventFilter) -> Result<EventQueryResult, sqlx::Error> {
+        let offset = filter.offset.unwrap_or(0).max(0);
+        let limit = filter.limit.map(|limit| limit.max(0) as usize);
+        let warm_limit = self.warm_query_row_limit.load(Ordering::Relaxed).max(1) as usize;
+        let warm_take = limit.map_or(warm_limit, |limit| limit.min(warm_limit));
+
+        let mut events = Vec::new();
+        let scan =
+            tiers::scan_warm_events(&self.pool, &filter, offset, warm_take, &mut events).await?;
+
+        if !scan.exhausted {
+            // Either the caller's limit is filled or the warm limit was hit
+            let warm_truncated = limit.map_or(true, |limit| scan.returned < limit);
+            return Ok(EventQueryResult {
+                events,
+                warm_rows: scan.returned,
+                warm_truncated,
+            });
+        }
+
+        let hot_offset = (offset - scan.matched).max(0);
+        let hot_limit = limit.map(|limit| limit.saturating_sub(scan.returned) as i64);
+        if hot_limit != Some(0) {
+            events.extend(self.get_hot_events(&filter, hot_offset, hot_limit).await?);
+        }
+
+        Ok(EventQueryResult {
+            events,
+            warm_rows: scan.returned,
+            warm_truncated: false,
+        })
+    }
+
+    async fn get_hot_events(
+        &self,
+        filter: &EventFilter,
+        offset: i64,
+        limit: Option<i64>,
+    ) -> Result<Vec<EventRecord>, sqlx::Error> {
         let mut query = String::from("SELECT * FROM events WHERE 1=1");
         let mut conditions = Vec::new();
 
@@ -294,10 +376,9 @@ impl EventStore {
 
         query.push_str(" ORDER BY sequence ASC");
 
-        if let Some(limit) = filter.limit {
-            query.push_str(&format!(" LIMIT {}", limit));
-        }
-        if let Some(offset) = filter.offset {
+        // SQLite only accepts OFFSET after a LIMIT
+        query.push_str(&format!(" LIMIT {}", limit.unwrap_or(-1)));
+        if offset > 0 {
             query.push_str(&format!(" OFFSET {}", offset));
         }
 
@@ -381,7 +462,22 @@ impl EventStore {
         // Load events after snapshot
         let from_sequence = snapshot_opt.as_ref().map(|s| s.sequence).unwrap_or(0);
 
-        let records = sqlx::query_as::<_, EventRecord>(
+        // Older events may already have moved to the warm tier
+        let warm_filter = EventFilter {
+            aggregate_id: Some(aggregate_id.to_string()),
+            event_type: None,
+            from_time: None,
+            to_time: Some(timestamp),
+            limit: None,
+            offset: None,
+        };
+        let mut records = Vec::new();
+        let warm_limit = self.warm_query_row_limit.load(Ordering::Relaxed).max(1) as usize;
+        tiers::scan_warm_events(&self.pool, &warm_filter, 0, warm_limit, &mut records).await?;
+        records.retain(|record| record.sequence > from_sequence);
+        records.sort_by_key(|record| record.sequence);
+
+        let hot_records = sqlx::query_as::<_, EventRecord>(
             r#"
             SELECT * FROM events
             WHERE aggregate_id = ?1 AND sequence > ?2 AND timestamp <= ?3
@@ -393,6 +489,7 @@ impl EventStore {
         .bind(timestamp.to_rfc3339())
         .fetch_all(&self.pool)
         .await?;
+        records.extend(hot_records);
 
         for record in records {
             let event: Event = serde_json::from_str(&record.event_data)?;
@@ -619,9 +716,11 @@ impl EventStore {
                     .await?;
             Ok(count)
         } else {
-            let (count,) = sqlx::query_as::<_, (i64,)>("SELECT COUNT(*) FROM events")
-                .fetch_one(&self.pool)
-                .await?;
+            let (count,) = sqlx::query_as::<_, (i64,)>(
+                "SELECT (SELECT COUNT(*) FROM events) + (SELECT COALESCE(SUM(row_count), 0) FROM event_blocks)",
+            )
+            .fetch_one(&self.pool)
+            .await?;
             Ok(count)
         }
     }
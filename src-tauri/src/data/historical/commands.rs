@@ -1,9 +1,10 @@
 use super::counterfactual::{CounterfactualRequest, CounterfactualResult};
+use super::dataset::{DatasetSelector, HistoricalCacheStats};
 use super::fetcher::FetchRequest;
 use super::manager::{SharedHistoricalReplayManager, SimulationPayload};
+use super::quota::HistoricalStoreSettings;
 use super::storage::{HistoricalDataPoint, HistoricalDataSet, OrderBookSnapshot};
 use serde::Serialize;
-use std::collections::HashMap;
 use tauri::{AppHandle, State};
 
 #[tauri::command]
@@ -49,19 +50,37 @@ pub async fn historical_compute_counterfactual(
 #[tauri::command]
 pub async fn historical_get_cache_stats(
     manager: State<'_, SharedHistoricalReplayManager>,
-    symbol: String,
-) -> Result<HashMap<String, u64>, String> {
+    symbol: Option<String>,
+) -> Result<HistoricalCacheStats, String> {
     let mgr = manager.read().await;
-    mgr.get_cache_stats(&symbol).await
+    mgr.get_cache_stats(symbol.as_deref()).await
 }
 
 #[tauri::command]
 pub async fn historical_clear_old_data(
     manager: State<'_, SharedHistoricalReplayManager>,
-    days: i64,
+    selector: DatasetSelector,
 ) -> Result<u64, String> {
     let mgr = manager.read().await;
-    mgr.clear_old_data(days).await
+    mgr.clear_data(selector).await
+}
+
+#[tauri::command]
+pub async fn historical_get_store_settings(
+    manager: State<'_, SharedHistoricalReplayManager>,
+) -> Result<HistoricalStoreSettings, String> {
+    let mgr = manager.read().await;
+    mgr.get_store_settings().await
+}
+
+/// Returns the ids of datasets evicted to fit the new quota.
+#[tauri::command]
+pub async fn historical_update_store_settings(
+    manager: State<'_, SharedHistoricalReplayManager>,
+    settings: HistoricalStoreSettings,
+) -> Result<Vec<String>, String> {
+    let mgr = manager.read().await;
+    mgr.update_store_settings(settings).await
 }
 
 #[tauri::command]
@@ -0,0 +1,259 @@
+use super::storage::{HistoricalDataPoint, OrderBookSnapshot};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Datasets are downloaded and verified one UTC day at a time.
+pub const CHUNK_SECONDS: i64 = 86_400;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DatasetKind {
+    Candles,
+    OrderBooks,
+}
+
+impl DatasetKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DatasetKind::Candles => "candles",
+            DatasetKind::OrderBooks => "orderbooks",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "candles" => Some(DatasetKind::Candles),
+            "orderbooks" => Some(DatasetKind::OrderBooks),
+            _ => None,
+        }
+    }
+}
+
+/// Candles of one symbol and interval, or the order books of one symbol.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DatasetRef {
+    pub symbol: String,
+    pub kind: DatasetKind,
+    pub interval: Option<String>,
+}
+
+impl DatasetRef {
+    pub fn candles(symbol: &str, interval: &str) -> Self {
+        Self {
+            symbol: symbol.to_string(),
+            kind: DatasetKind::Candles,
+            interval: Some(interval.to_string()),
+        }
+    }
+
+    pub fn orderbooks(symbol: &str) -> Self {
+        Self {
+            symbol: symbol.to_string(),
+            kind: DatasetKind::OrderBooks,
+            interval: None,
+        }
+    }
+
+    /// `SOL:1h` or `SOL:orderbook`.
+    pub fn id(&self) -> String {
+        match &self.interval {
+            Some(interval) => format!("{}:{}", self.symbol, interval),
+            None => format!("{}:orderbook", self.symbol),
+        }
+    }
+}
+
+/// What `historical_clear_old_data` removes. Empty filters match every
+/// dataset; `older_than_days` limits removal to data older than the cutoff.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DatasetSelector {
+    pub dataset_ids: Vec<String>,
+    pub symbol: Option<String>,
+    pub kind: Option<DatasetKind>,
+    pub older_than_days: Option<i64>,
+}
+
+impl DatasetSelector {
+    pub fn matches_all_datasets(&self) -> bool {
+        self.dataset_ids.is_empty() && self.symbol.is_none() && self.kind.is_none()
+    }
+}
+
+/// A chunk as recorded in the manifest.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChunkRecord {
+    pub day_start: i64,
+    pub point_count: u64,
+    pub size_bytes: u64,
+    pub checksum: String,
+    /// False for the current day, which is re-downloaded until it is over.
+    pub complete: bool,
+}
+
+/// One day of either kind of data.
+#[derive(Debug, Clone)]
+pub enum ChunkData {
+    Candles(Vec<HistoricalDataPoint>),
+    OrderBooks(Vec<OrderBookSnapshot>),
+}
+
+impl ChunkData {
+    /// Keeps only the chunk's day, sorted and with one entry per timestamp,
+    /// which is exactly what reading the chunk back from storage returns.
+    pub fn normalize(self, day_start: i64) -> Self {
+        let in_day = |timestamp: i64| (day_start..day_start + CHUNK_SECONDS).contains(&timestamp);
+        match self {
+            ChunkData::Candles(mut points) => {
+                points.retain(|p| in_day(p.timestamp));
+                points.sort_by_key(|p| p.timestamp);
+                points.dedup_by_key(|p| p.timestamp);
+                ChunkData::Candles(points)
+            }
+            ChunkData::OrderBooks(mut snapshots) => {
+                snapshots.retain(|s| in_day(s.timestamp));
+                snapshots.sort_by_key(|s| s.timestamp);
+                snapshots.dedup_by_key(|s| s.timestamp);
+                ChunkData::OrderBooks(snapshots)
+            }
+        }
+    }
+
+    pub fn point_count(&self) -> usize {
+        match self {
+            ChunkData::Candles(points) => points.len(),
+            ChunkData::OrderBooks(snapshots) => snapshots.len(),
+        }
+    }
+
+    pub fn digest(&self) -> (String, u64) {
+        match self {
+            ChunkData::Candles(points) => candle_digest(points),
+            ChunkData::OrderBooks(snapshots) => orderbook_digest(snapshots),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatasetStats {
+    pub id: String,
+    pub symbol: String,
+    pub kind: DatasetKind,
+    pub interval: Option<String>,
+    pub size_bytes: u64,
+    pub points: u64,
+    /// Days in the widest range ever requested for this dataset.
+    pub chunks_expected: u64,
+    pub chunks_complete: u64,
+    pub last_accessed_at: i64,
+    pub pinned: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoricalCacheStats {
+    pub total_bytes: u64,
+    pub quota_bytes: u64,
+    pub datasets: Vec<DatasetStats>,
+}
+
+/// Start of every UTC day overlapping `[start_time, end_time]`.
+pub fn day_chunks(start_time: i64, end_time: i64) -> Vec<i64> {
+    if end_time < start_time {
+        return Vec::new();
+    }
+    let first = start_time.div_euclid(CHUNK_SECONDS) * CHUNK_SECONDS;
+    (first..=end_time).step_by(CHUNK_SECONDS as usize).collect()
+}
+
+/// SHA-256 over the raw values, plus the size of that encoding, which is
+/// what the quota accounts for.
+pub fn candle_digest(points: &[HistoricalDataPoint]) -> (String, u64) {
+    let mut hasher = Sha256::new();
+    for point in points {
+        hasher.update(point.timestamp.to_le_bytes());
+        for value in [point.open, point.high, point.low, point.close, point.volume] {
+            hasher.update(value.to_bits().to_le_bytes());
+        }
+    }
+    (hex::encode(hasher.finalize()), points.len() as u64 * 48)
+}
+
+pub fn orderbook_digest(snapshots: &[OrderBookSnapshot]) -> (String, u64) {
+    let mut hasher = Sha256::new();
+    let mut size = 0u64;
+    for snapshot in snapshots {
+        hasher.update(snapshot.timestamp.to_le_bytes());
+        for side in [&snapshot.bids, &snapshot.asks] {
+            hasher.update((side.len() as u64).to_le_bytes());
+            for (price, quantity) in side {
+                hasher.update(price.to_bits().to_le_bytes());
+                hasher.update(quantity.to_bits().to_le_bytes());
+            }
+            size += side.len() as u64 * 16;
+        }
+        size += 8;
+    }
+    (hex::encode(hasher.finalize()), size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn day_chunks_cover_partial_days_at_both_ends() {
+        let start = 3 * CHUNK_SECONDS + 3_600;
+        let end = 5 * CHUNK_SECONDS + 60;
+        assert_eq!(
+            day_chunks(start, end),
+            vec![3 * CHUNK_SECONDS, 4 * CHUNK_SECONDS, 5 * CHUNK_SECONDS]
+        );
+        assert_eq!(day_chunks(start, start), vec![3 * CHUNK_SECONDS]);
+        assert!(day_chunks(end, start).is_empty());
+    }
+
+    #[test]
+    fn normalize_matches_what_storage_returns() {
+        let point = |timestamp| HistoricalDataPoint {
+            timestamp,
+            open: 1.0,
+            high: 1.0,
+            low: 1.0,
+            close: 1.0,
+            volume: 1.0,
+        };
+        let day = 2 * CHUNK_SECONDS;
+        let chunk = ChunkData::Candles(vec![
+            point(day + 120),
+            point(day - 60),
+            point(day + 60),
+            point(day + 120),
+            point(day + CHUNK_SECONDS),
+        ])
+        .normalize(day);
+
+        let ChunkData::Candles(points) = chunk else {
+            panic!("expected candles");
+        };
+        let timestamps: Vec<i64> = points.iter().map(|p| p.timestamp).collect();
+        assert_eq!(timestamps, vec![day + 60, day + 120]);
+    }
+
+    #[test]
+    fn candle_digest_changes_with_any_value() {
+        let point = HistoricalDataPoint {
+            timestamp: 1_700_000_000,
+            open: 1.0,
+            high: 2.0,
+            low: 0.5,
+            close: 1.5,
+            volume: 10.0,
+        };
+        let (checksum, size) = candle_digest(&[point.clone()]);
+        assert_eq!(size, 48);
+
+        let mut altered = point;
+        altered.volume = 10.000001;
+        assert_ne!(candle_digest(&[altered]).0, checksum);
+    }
+}
@@ -1,3 +1,5 @@
+use super::dataset::{day_chunks, ChunkData, ChunkRecord, DatasetRef, CHUNK_SECONDS};
+use super::quota::{enforce_quota, DatasetPins};
 use super::storage::{
     HistoricalDataPoint, HistoricalDataSet, HistoricalStorage, OrderBookSnapshot,
 };
@@ -5,6 +7,10 @@ use chrono::{DateTime, Utc};
 use reqwest;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use std::time::Duration;
+
+const CHUNK_ATTEMPTS: u32 = 3;
+const RETRY_BASE_DELAY_MS: u64 = 500;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FetchRequest {
@@ -14,32 +20,63 @@ pub struct FetchRequest {
     pub end_time: i64,
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ChunkStatus {
+    /// Already stored and its checksum still matched.
+    Cached,
+    Downloaded,
+    Failed,
+}
+
+/// Emitted once per chunk, including chunks that were already cached.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FetchProgress {
+    pub dataset_id: String,
     pub symbol: String,
-    pub total_points: u64,
-    pub fetched_points: u64,
+    pub chunk_start: i64,
+    pub chunk_index: u64,
+    pub total_chunks: u64,
+    pub completed_chunks: u64,
+    pub status: ChunkStatus,
     pub percent_complete: f64,
+    pub error: Option<String>,
 }
 
 pub struct HistoricalDataFetcher {
     storage: Arc<HistoricalStorage>,
     api_key: Option<String>,
+    pins: DatasetPins,
 }
 
 impl HistoricalDataFetcher {
-    pub fn new(storage: Arc<HistoricalStorage>, api_key: Option<String>) -> Self {
-        Self { storage, api_key }
+    pub fn new(
+        storage: Arc<HistoricalStorage>,
+        api_key: Option<String>,
+        pins: DatasetPins,
+    ) -> Self {
+        Self {
+            storage,
+            api_key,
+            pins,
+        }
     }
 
-    pub async fn fetch_data(
+    pub async fn fetch_data<F>(
         &self,
         request: FetchRequest,
-    ) -> Result<HistoricalDataSet, Box<dyn std::error::Error>> {
-        // Check if data already exists in cache
-        let has_data = self
+        progress: F,
+    ) -> Result<HistoricalDataSet, Box<dyn std::error::Error>>
+    where
+        F: Fn(FetchProgress) + Send + Sync,
+    {
+        let dataset = DatasetRef::candles(&request.symbol, &request.interval);
+        self.sync_chunks(&dataset, request.start_time, request.end_time, &progress)
+            .await?;
+
+        let data = self
             .storage
-            .check_data_coverage(
+            .get_price_data(
                 &request.symbol,
                 &request.interval,
                 request.start_time,
@@ -47,50 +84,199 @@ impl HistoricalDataFetcher {
             )
             .await?;
 
-        if has_data {
-            // Return cached data
-            let data = self
-                .storage
-                .get_price_data(
-                    &request.symbol,
-                    &request.interval,
-                    request.start_time,
-                    request.end_time,
-                )
-                .await?;
-
-            return Ok(HistoricalDataSet {
-                symbol: request.symbol,
-                interval: request.interval,
-                data,
-                fetched_at: Utc::now(),
+        Ok(HistoricalDataSet {
+            symbol: request.symbol,
+            interval: request.interval,
+            data,
+            fetched_at: Utc::now(),
+        })
+    }
+
+    pub async fn fetch_orderbook_history<F>(
+        &self,
+        symbol: &str,
+        start_time: i64,
+        end_time: i64,
+        progress: F,
+    ) -> Result<Vec<OrderBookSnapshot>, Box<dyn std::error::Error>>
+    where
+        F: Fn(FetchProgress) + Send + Sync,
+    {
+        let dataset = DatasetRef::orderbooks(symbol);
+        self.sync_chunks(&dataset, start_time, end_time, &progress)
+            .await?;
+
+        Ok(self
+            .storage
+            .get_orderbook_snapshots(symbol, start_time, end_time)
+            .await?)
+    }
+
+    /// Makes sure every day of the range is stored and verified. Completed
+    /// chunks survive a failure, so retrying the same request resumes where
+    /// it stopped.
+    async fn sync_chunks<F>(
+        &self,
+        dataset: &DatasetRef,
+        start_time: i64,
+        end_time: i64,
+        progress: &F,
+    ) -> Result<(), String>
+    where
+        F: Fn(FetchProgress) + Send + Sync,
+    {
+        let dataset_id = dataset.id();
+        // Keeps eviction away from the dataset while it is being filled
+        let _lease = self.pins.pin(vec![dataset_id.clone()]);
+        self.storage
+            .register_dataset(dataset, start_time, end_time)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let days = day_chunks(start_time, end_time);
+        let total_chunks = days.len() as u64;
+        let report = |index: usize, day: i64, status: ChunkStatus, error: Option<String>| {
+            let completed_chunks = match status {
+                ChunkStatus::Failed => index as u64,
+                _ => index as u64 + 1,
+            };
+            progress(FetchProgress {
+                dataset_id: dataset_id.clone(),
+                symbol: dataset.symbol.clone(),
+                chunk_start: day,
+                chunk_index: index as u64,
+                total_chunks,
+                completed_chunks,
+                status,
+                percent_complete: completed_chunks as f64 / total_chunks as f64 * 100.0,
+                error,
             });
-        }
+        };
 
-        // Fetch from API or generate mock data
-        let data = if let Some(ref api_key) = self.api_key {
-            match self.fetch_from_birdeye(&request, api_key).await {
-                Ok(data) => data,
+        for (index, day) in days.into_iter().enumerate() {
+            match self.sync_chunk(dataset, day).await {
+                Ok(status) => {
+                    if status == ChunkStatus::Downloaded {
+                        if let Err(e) = enforce_quota(&self.storage, &self.pins).await {
+                            eprintln!("Failed to enforce historical data quota: {}", e);
+                        }
+                    }
+                    report(index, day, status, None);
+                }
                 Err(e) => {
-                    eprintln!("Birdeye API error: {}, falling back to mock data", e);
-                    self.generate_mock_data(&request)
+                    report(index, day, ChunkStatus::Failed, Some(e.clone()));
+                    return Err(format!(
+                        "Failed to fetch {} for {}: {}",
+                        dataset_id,
+                        DateTime::<Utc>::from_timestamp(day, 0)
+                            .map(|d| d.format("%Y-%m-%d").to_string())
+                            .unwrap_or_else(|| day.to_string()),
+                        e
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn sync_chunk(&self, dataset: &DatasetRef, day: i64) -> Result<ChunkStatus, String> {
+        let dataset_id = dataset.id();
+
+        if let Some(chunk) = self
+            .storage
+            .get_chunk(&dataset_id, day)
+            .await
+            .map_err(|e| e.to_string())?
+        {
+            if chunk.complete {
+                let stored = self
+                    .storage
+                    .load_chunk(dataset, day)
+                    .await
+                    .map_err(|e| e.to_string())?;
+                if stored.digest().0 == chunk.checksum {
+                    return Ok(ChunkStatus::Cached);
                 }
+                eprintln!(
+                    "Chunk {} of {} failed verification, downloading it again",
+                    day, dataset_id
+                );
+            }
+        }
+
+        let mut attempt = 0;
+        let data = loop {
+            match self.download_chunk(dataset, day).await {
+                Ok(data) => break data.normalize(day),
+                Err(e) if attempt + 1 < CHUNK_ATTEMPTS => {
+                    attempt += 1;
+                    eprintln!(
+                        "Chunk {} of {} failed (attempt {}): {}",
+                        day, dataset_id, attempt, e
+                    );
+                    tokio::time::sleep(Duration::from_millis(RETRY_BASE_DELAY_MS << attempt)).await;
+                }
+                Err(e) => return Err(e),
             }
-        } else {
-            self.generate_mock_data(&request)
         };
 
-        // Store in cache
+        let (checksum, size_bytes) = data.digest();
+        let chunk = ChunkRecord {
+            day_start: day,
+            point_count: data.point_count() as u64,
+            size_bytes,
+            checksum,
+            // Today keeps changing, so it is fetched again next time
+            complete: day + CHUNK_SECONDS <= Utc::now().timestamp(),
+        };
         self.storage
-            .store_price_data(&request.symbol, &request.interval, &data)
-            .await?;
+            .store_chunk(dataset, &data, &chunk)
+            .await
+            .map_err(|e| e.to_string())?;
 
-        Ok(HistoricalDataSet {
-            symbol: request.symbol,
-            interval: request.interval,
-            data,
-            fetched_at: Utc::now(),
-        })
+        // Verify what actually landed on disk, not just what was downloaded
+        let stored = self
+            .storage
+            .load_chunk(dataset, day)
+            .await
+            .map_err(|e| e.to_string())?;
+        if stored.digest().0 != chunk.checksum {
+            self.storage
+                .delete_chunk(&dataset_id, day)
+                .await
+                .map_err(|e| e.to_string())?;
+            return Err(format!("chunk {} failed verification after writing", day));
+        }
+
+        Ok(ChunkStatus::Downloaded)
+    }
+
+    async fn download_chunk(&self, dataset: &DatasetRef, day: i64) -> Result<ChunkData, String> {
+        let day_end = day + CHUNK_SECONDS - 1;
+        match &dataset.interval {
+            Some(interval) => {
+                let request = FetchRequest {
+                    symbol: dataset.symbol.clone(),
+                    interval: interval.clone(),
+                    start_time: day,
+                    end_time: day_end,
+                };
+                let data = match &self.api_key {
+                    Some(api_key) => self
+                        .fetch_from_birdeye(&request, api_key)
+                        .await
+                        .map_err(|e| format!("Birdeye API error: {}", e))?,
+                    None => self.generate_mock_data(&request),
+                };
+                Ok(ChunkData::Candles(data))
+            }
+            None => Ok(ChunkData::OrderBooks(self.generate_mock_orderbooks(
+                &dataset.symbol,
+                day,
+                day_end,
+            ))),
+        }
     }
 
     async fn fetch_from_birdeye(
@@ -200,33 +386,6 @@ impl HistoricalDataFetcher {
         data
     }
 
-    pub async fn fetch_orderbook_history(
-        &self,
-        symbol: &str,
-        start_time: i64,
-        end_time: i64,
-    ) -> Result<Vec<OrderBookSnapshot>, Box<dyn std::error::Error>> {
-        // Check cache first
-        let snapshots = self
-            .storage
-            .get_orderbook_snapshots(symbol, start_time, end_time)
-            .await?;
-
-        if !snapshots.is_empty() {
-            return Ok(snapshots);
-        }
-
-        // Generate mock orderbook snapshots
-        let snapshots = self.generate_mock_orderbooks(symbol, start_time, end_time);
-
-        // Store in cache
-        for snapshot in &snapshots {
-            self.storage.store_orderbook_snapshot(snapshot).await?;
-        }
-
-        Ok(snapshots)
-    }
-
     fn generate_mock_orderbooks(
         &self,
         symbol: &str,
@@ -272,48 +431,4 @@ impl HistoricalDataFetcher {
 
         snapshots
     }
-
-    pub async fn fetch_in_chunks(
-        &self,
-        request: FetchRequest,
-        chunk_size_hours: i64,
-        progress_callback: impl Fn(FetchProgress),
-    ) -> Result<HistoricalDataSet, Box<dyn std::error::Error>> {
-        let total_duration = request.end_time - request.start_time;
-        let chunk_duration = chunk_size_hours * 3600;
-        let num_chunks = (total_duration as f64 / chunk_duration as f64).ceil() as u64;
-
-        let mut all_data = Vec::new();
-        let mut current_start = request.start_time;
-
-        for chunk_idx in 0..num_chunks {
-            let current_end = (current_start + chunk_duration).min(request.end_time);
-
-            let chunk_request = FetchRequest {
-                symbol: request.symbol.clone(),
-                interval: request.interval.clone(),
-                start_time: current_start,
-                end_time: current_end,
-            };
-
-            let chunk_data = self.fetch_data(chunk_request).await?;
-            all_data.extend(chunk_data.data);
-
-            progress_callback(FetchProgress {
-                symbol: request.symbol.clone(),
-                total_points: num_chunks,
-                fetched_points: chunk_idx + 1,
-                percent_complete: ((chunk_idx + 1) as f64 / num_chunks as f64) * 100.0,
-            });
-
-            current_start = current_end;
-        }
-
-        Ok(HistoricalDataSet {
-            symbol: request.symbol,
-            interval: request.interval,
-            data: all_data,
-            fetched_at: Utc::now(),
-        })
-    }
 }
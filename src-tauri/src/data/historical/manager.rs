@@ -1,16 +1,19 @@
 use super::counterfactual::{
     compute_hold_counterfactual, CounterfactualRequest, CounterfactualResult,
 };
+use super::dataset::{DatasetRef, DatasetSelector, HistoricalCacheStats};
 use super::fetcher::{FetchProgress, FetchRequest, HistoricalDataFetcher};
+use super::quota::{enforce_quota, DatasetLease, DatasetPins, HistoricalStoreSettings};
 use super::simulator::{run_simulation, PortfolioHolding, SimulationConfig, SimulationResult};
 use super::storage::{
     HistoricalDataPoint, HistoricalDataSet, HistoricalStorage, OrderBookSnapshot,
 };
+use crate::config::DataPaths;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
-use tauri::Manager;
+use tauri::{AppHandle, Emitter};
 use tokio::sync::RwLock;
 
 pub type SharedHistoricalReplayManager = Arc<RwLock<HistoricalReplayManager>>;
@@ -24,6 +27,8 @@ pub struct SimulationPayload {
 pub struct HistoricalReplayManager {
     storage: Arc<HistoricalStorage>,
     api_key: Option<String>,
+    pins: DatasetPins,
+    app_handle: AppHandle,
 }
 
 impl HistoricalReplayManager {
@@ -32,8 +37,7 @@ impl HistoricalReplayManager {
         api_key: Option<String>,
     ) -> Result<Self, String> {
         let mut db_path = app_handle
-            .path()
-            .app_data_dir()
+            .app_data_root()
             .map_err(|e| format!("Unable to resolve app data directory: {}", e))?;
 
         std::fs::create_dir_all(&db_path)
@@ -48,11 +52,40 @@ impl HistoricalReplayManager {
         Ok(Self {
             storage: Arc::new(storage),
             api_key,
+            pins: DatasetPins::default(),
+            app_handle: app_handle.clone(),
         })
     }
 
     fn fetcher(&self) -> HistoricalDataFetcher {
-        HistoricalDataFetcher::new(self.storage.clone(), self.api_key.clone())
+        HistoricalDataFetcher::new(
+            self.storage.clone(),
+            self.api_key.clone(),
+            self.pins.clone(),
+        )
+    }
+
+    fn emit_progress(&self, progress: FetchProgress) {
+        if let Err(e) = self.app_handle.emit("historical_fetch_progress", progress) {
+            eprintln!("Failed to emit historical fetch progress: {}", e);
+        }
+    }
+
+    /// Pins every stored dataset of `symbols` for as long as the lease lives.
+    async fn pin_symbols<'a>(
+        &self,
+        symbols: impl IntoIterator<Item = &'a String>,
+    ) -> Result<DatasetLease, String> {
+        let mut ids = Vec::new();
+        for symbol in symbols {
+            ids.extend(
+                self.storage
+                    .dataset_ids_for_symbol(symbol)
+                    .await
+                    .map_err(|e| e.to_string())?,
+            );
+        }
+        Ok(self.pins.pin(ids))
     }
 
     pub fn set_api_key(&mut self, api_key: Option<String>) {
@@ -63,20 +96,8 @@ impl HistoricalReplayManager {
         &self,
         request: FetchRequest,
     ) -> Result<HistoricalDataSet, Box<dyn std::error::Error>> {
-        self.fetcher().fetch_data(request).await
-    }
-
-    pub async fn fetch_dataset_chunked<F>(
-        &self,
-        request: FetchRequest,
-        chunk_size_hours: i64,
-        progress_callback: F,
-    ) -> Result<HistoricalDataSet, Box<dyn std::error::Error>>
-    where
-        F: Fn(FetchProgress) + Send,
-    {
         self.fetcher()
-            .fetch_in_chunks(request, chunk_size_hours, progress_callback)
+            .fetch_data(request, |progress| self.emit_progress(progress))
             .await
     }
 
@@ -87,7 +108,9 @@ impl HistoricalReplayManager {
         end_time: i64,
     ) -> Result<Vec<OrderBookSnapshot>, Box<dyn std::error::Error>> {
         self.fetcher()
-            .fetch_orderbook_history(symbol, start_time, end_time)
+            .fetch_orderbook_history(symbol, start_time, end_time, |progress| {
+                self.emit_progress(progress)
+            })
             .await
     }
 
@@ -95,6 +118,8 @@ impl HistoricalReplayManager {
         &self,
         payload: SimulationPayload,
     ) -> Result<SimulationResult, String> {
+        let _lease = self.pin_symbols(payload.datasets.keys()).await?;
+
         let mut datasets_map = HashMap::new();
         for (symbol, data_points) in payload.datasets.iter() {
             datasets_map.insert(symbol.clone(), data_points.clone());
@@ -107,6 +132,13 @@ impl HistoricalReplayManager {
         &self,
         request: CounterfactualRequest,
     ) -> Result<Option<CounterfactualResult>, String> {
+        let dataset_id = DatasetRef::candles(&request.symbol, "1h").id();
+        let _lease = self.pins.pin(vec![dataset_id.clone()]);
+        self.storage
+            .touch_dataset(&dataset_id)
+            .await
+            .map_err(|e| e.to_string())?;
+
         let data = self
             .storage
             .get_price_data(&request.symbol, "1h", request.start_time, request.end_time)
@@ -116,16 +148,49 @@ impl HistoricalReplayManager {
         Ok(compute_hold_counterfactual(request, &data))
     }
 
-    pub async fn get_cache_stats(&self, symbol: &str) -> Result<HashMap<String, u64>, String> {
+    pub async fn get_cache_stats(
+        &self,
+        symbol: Option<&str>,
+    ) -> Result<HistoricalCacheStats, String> {
+        let datasets = self
+            .storage
+            .dataset_stats(symbol, &self.pins.pinned())
+            .await
+            .map_err(|e| e.to_string())?;
+        let settings = self
+            .storage
+            .get_settings()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(HistoricalCacheStats {
+            total_bytes: datasets.iter().map(|d| d.size_bytes).sum(),
+            quota_bytes: settings.quota_bytes,
+            datasets,
+        })
+    }
+
+    pub async fn clear_data(&self, selector: DatasetSelector) -> Result<u64, String> {
         self.storage
-            .get_cache_stats(symbol)
+            .clear_datasets(&selector)
             .await
             .map_err(|e| e.to_string())
     }
 
-    pub async fn clear_old_data(&self, days: i64) -> Result<u64, String> {
+    pub async fn get_store_settings(&self) -> Result<HistoricalStoreSettings, String> {
+        self.storage.get_settings().await.map_err(|e| e.to_string())
+    }
+
+    /// Saves the settings and immediately evicts down to a lowered quota.
+    pub async fn update_store_settings(
+        &self,
+        settings: HistoricalStoreSettings,
+    ) -> Result<Vec<String>, String> {
         self.storage
-            .clear_old_data(days)
+            .save_settings(&settings)
+            .await
+            .map_err(|e| e.to_string())?;
+        enforce_quota(&self.storage, &self.pins)
             .await
             .map_err(|e| e.to_string())
     }
@@ -1,13 +1,17 @@
 pub mod commands;
 pub mod counterfactual;
+pub mod dataset;
 pub mod fetcher;
 pub mod manager;
+pub mod quota;
 pub mod simulator;
 pub mod storage;
 
 pub use commands::*;
 pub use counterfactual::*;
+pub use dataset::*;
 pub use fetcher::*;
 pub use manager::*;
+pub use quota::*;
 pub use simulator::*;
 pub use storage::*;
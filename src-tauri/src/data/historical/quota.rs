@@ -0,0 +1,147 @@
+use super::storage::HistoricalStorage;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoricalStoreSettings {
+    pub quota_bytes: u64,
+}
+
+impl Default for HistoricalStoreSettings {
+    fn default() -> Self {
+        Self {
+            quota_bytes: 2 * 1024 * 1024 * 1024,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DatasetUsage {
+    pub id: String,
+    pub size_bytes: u64,
+    pub last_accessed_at: i64,
+}
+
+/// Datasets referenced by a running backtest or download, which eviction
+/// must leave alone. Pins are counted, so overlapping users are fine.
+#[derive(Clone, Default)]
+pub struct DatasetPins {
+    counts: Arc<Mutex<HashMap<String, usize>>>,
+}
+
+impl DatasetPins {
+    pub fn pin(&self, ids: Vec<String>) -> DatasetLease {
+        let mut counts = self.counts.lock();
+        for id in &ids {
+            *counts.entry(id.clone()).or_insert(0) += 1;
+        }
+        DatasetLease {
+            pins: self.clone(),
+            ids,
+        }
+    }
+
+    pub fn pinned(&self) -> HashSet<String> {
+        self.counts.lock().keys().cloned().collect()
+    }
+}
+
+/// Unpins its datasets when dropped.
+pub struct DatasetLease {
+    pins: DatasetPins,
+    ids: Vec<String>,
+}
+
+impl Drop for DatasetLease {
+    fn drop(&mut self) {
+        let mut counts = self.pins.counts.lock();
+        for id in &self.ids {
+            if let Some(count) = counts.get_mut(id) {
+                *count -= 1;
+                if *count == 0 {
+                    counts.remove(id);
+                }
+            }
+        }
+    }
+}
+
+/// Least recently used unpinned datasets to evict to get under the quota.
+/// May leave the store over quota when only pinned datasets remain.
+pub fn plan_eviction(
+    datasets: &[DatasetUsage],
+    quota_bytes: u64,
+    pinned: &HashSet<String>,
+) -> Vec<String> {
+    let mut total: u64 = datasets.iter().map(|d| d.size_bytes).sum();
+    let mut candidates: Vec<&DatasetUsage> = datasets
+        .iter()
+        .filter(|d| !pinned.contains(&d.id))
+        .collect();
+    candidates.sort_by_key(|d| d.last_accessed_at);
+
+    let mut evict = Vec::new();
+    for dataset in candidates {
+        if total <= quota_bytes {
+            break;
+        }
+        total -= dataset.size_bytes;
+        evict.push(dataset.id.clone());
+    }
+    evict
+}
+
+/// Evicts datasets until the store fits its quota. Returns the evicted ids.
+pub async fn enforce_quota(
+    storage: &HistoricalStorage,
+    pins: &DatasetPins,
+) -> Result<Vec<String>, sqlx::Error> {
+    let settings = storage.get_settings().await?;
+    let usage = storage.dataset_usage().await?;
+    let evict = plan_eviction(&usage, settings.quota_bytes, &pins.pinned());
+
+    for id in &evict {
+        storage.evict_dataset(id).await?;
+        eprintln!("Evicted historical dataset {} to stay within quota", id);
+    }
+    Ok(evict)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(id: &str, size_bytes: u64, last_accessed_at: i64) -> DatasetUsage {
+        DatasetUsage {
+            id: id.to_string(),
+            size_bytes,
+            last_accessed_at,
+        }
+    }
+
+    #[test]
+    fn evicts_least_recently_used_but_never_pinned() {
+        let datasets = vec![
+            usage("SOL:1h", 400, 10),
+            usage("BONK:1h", 300, 20),
+            usage("JUP:1h", 300, 30),
+        ];
+        let pins = DatasetPins::default();
+
+        assert_eq!(
+            plan_eviction(&datasets, 700, &pins.pinned()),
+            vec!["SOL:1h".to_string()]
+        );
+
+        let lease = pins.pin(vec!["SOL:1h".to_string()]);
+        assert_eq!(
+            plan_eviction(&datasets, 500, &pins.pinned()),
+            vec!["BONK:1h".to_string(), "JUP:1h".to_string()]
+        );
+
+        drop(lease);
+        assert!(pins.pinned().is_empty());
+    }
+}
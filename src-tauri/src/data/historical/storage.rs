@@ -1,9 +1,16 @@
+use super::dataset::{
+    day_chunks, ChunkData, ChunkRecord, DatasetKind, DatasetRef, DatasetSelector, DatasetStats,
+    CHUNK_SECONDS,
+};
+use super::quota::{DatasetUsage, HistoricalStoreSettings};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::{Pool, Sqlite, SqlitePool};
-use std::collections::HashMap;
+use sqlx::{Pool, Row, Sqlite, SqlitePool};
+use std::collections::HashSet;
 use std::path::PathBuf;
 
+const SETTINGS_KEY: &str = "store";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HistoricalDataPoint {
     pub timestamp: i64,
@@ -103,17 +110,45 @@ impl HistoricalStorage {
         .execute(&self.pool)
         .await?;
 
-        // Create data_cache_metadata table for tracking fetched ranges
+        // Datasets and the manifest of their per-day chunks
         sqlx::query(
             r#"
-            CREATE TABLE IF NOT EXISTS data_cache_metadata (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
+            CREATE TABLE IF NOT EXISTS historical_datasets (
+                id TEXT PRIMARY KEY,
                 symbol TEXT NOT NULL,
-                interval TEXT NOT NULL,
-                start_timestamp INTEGER NOT NULL,
-                end_timestamp INTEGER NOT NULL,
+                kind TEXT NOT NULL,
+                interval TEXT,
+                range_start INTEGER NOT NULL,
+                range_end INTEGER NOT NULL,
+                last_accessed_at INTEGER NOT NULL
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS historical_chunks (
+                dataset_id TEXT NOT NULL,
+                day_start INTEGER NOT NULL,
+                point_count INTEGER NOT NULL,
+                size_bytes INTEGER NOT NULL,
+                checksum TEXT NOT NULL,
+                complete INTEGER NOT NULL,
                 fetched_at TEXT NOT NULL,
-                UNIQUE(symbol, interval, start_timestamp, end_timestamp)
+                PRIMARY KEY (dataset_id, day_start)
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS historical_settings (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
             )
             "#,
         )
@@ -123,17 +158,31 @@ impl HistoricalStorage {
         Ok(())
     }
 
-    pub async fn store_price_data(
+    /// Replaces one day of candles and records the chunk in the same
+    /// transaction, so an interrupted download never leaves a chunk that
+    /// looks complete.
+    pub async fn store_candle_chunk(
         &self,
-        symbol: &str,
-        interval: &str,
+        dataset: &DatasetRef,
         data: &[HistoricalDataPoint],
+        chunk: &ChunkRecord,
     ) -> Result<(), sqlx::Error> {
-        if data.is_empty() {
-            return Ok(());
-        }
-
+        let interval = dataset.interval.as_deref().unwrap_or_default();
         let fetched_at = Utc::now().to_rfc3339();
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            r#"
+            DELETE FROM historical_prices
+            WHERE symbol = ?1 AND interval = ?2 AND timestamp >= ?3 AND timestamp < ?4
+            "#,
+        )
+        .bind(&dataset.symbol)
+        .bind(interval)
+        .bind(chunk.day_start)
+        .bind(chunk.day_start + CHUNK_SECONDS)
+        .execute(&mut *tx)
+        .await?;
 
         for point in data {
             sqlx::query(
@@ -143,7 +192,7 @@ impl HistoricalStorage {
                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
                 "#,
             )
-            .bind(symbol)
+            .bind(&dataset.symbol)
             .bind(interval)
             .bind(point.timestamp)
             .bind(point.open)
@@ -152,29 +201,12 @@ impl HistoricalStorage {
             .bind(point.close)
             .bind(point.volume)
             .bind(&fetched_at)
-            .execute(&self.pool)
-            .await?;
-        }
-
-        // Update metadata
-        if let (Some(first), Some(last)) = (data.first(), data.last()) {
-            sqlx::query(
-                r#"
-                INSERT OR REPLACE INTO data_cache_metadata
-                (symbol, interval, start_timestamp, end_timestamp, fetched_at)
-                VALUES (?1, ?2, ?3, ?4, ?5)
-                "#,
-            )
-            .bind(symbol)
-            .bind(interval)
-            .bind(first.timestamp)
-            .bind(last.timestamp)
-            .bind(&fetched_at)
-            .execute(&self.pool)
+            .execute(&mut *tx)
             .await?;
         }
 
-        Ok(())
+        Self::record_chunk(&mut tx, &dataset.id(), chunk, &fetched_at).await?;
+        tx.commit().await
     }
 
     pub async fn get_price_data(
@@ -217,53 +249,188 @@ impl HistoricalStorage {
         Ok(data)
     }
 
-    pub async fn check_data_coverage(
+    pub async fn store_orderbook_chunk(
         &self,
-        symbol: &str,
-        interval: &str,
-        start_time: i64,
-        end_time: i64,
-    ) -> Result<bool, sqlx::Error> {
-        let count: (i64,) = sqlx::query_as(
+        dataset: &DatasetRef,
+        snapshots: &[OrderBookSnapshot],
+        chunk: &ChunkRecord,
+    ) -> Result<(), sqlx::Error> {
+        let fetched_at = Utc::now().to_rfc3339();
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
             r#"
-            SELECT COUNT(*) FROM data_cache_metadata
-            WHERE symbol = ?1 AND interval = ?2
-              AND start_timestamp <= ?3 AND end_timestamp >= ?4
+            DELETE FROM historical_orderbooks
+            WHERE symbol = ?1 AND timestamp >= ?2 AND timestamp < ?3
             "#,
         )
-        .bind(symbol)
-        .bind(interval)
-        .bind(start_time)
-        .bind(end_time)
-        .fetch_one(&self.pool)
+        .bind(&dataset.symbol)
+        .bind(chunk.day_start)
+        .bind(chunk.day_start + CHUNK_SECONDS)
+        .execute(&mut *tx)
         .await?;
 
-        Ok(count.0 > 0)
+        for snapshot in snapshots {
+            let bids_json = serde_json::to_string(&snapshot.bids).unwrap_or_default();
+            let asks_json = serde_json::to_string(&snapshot.asks).unwrap_or_default();
+
+            sqlx::query(
+                r#"
+                INSERT OR REPLACE INTO historical_orderbooks
+                (symbol, timestamp, bids, asks, fetched_at)
+                VALUES (?1, ?2, ?3, ?4, ?5)
+                "#,
+            )
+            .bind(&snapshot.symbol)
+            .bind(snapshot.timestamp)
+            .bind(&bids_json)
+            .bind(&asks_json)
+            .bind(&fetched_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        Self::record_chunk(&mut tx, &dataset.id(), chunk, &fetched_at).await?;
+        tx.commit().await
     }
 
-    pub async fn store_orderbook_snapshot(
+    pub async fn store_chunk(
         &self,
-        snapshot: &OrderBookSnapshot,
+        dataset: &DatasetRef,
+        data: &ChunkData,
+        chunk: &ChunkRecord,
     ) -> Result<(), sqlx::Error> {
-        let bids_json = serde_json::to_string(&snapshot.bids).unwrap_or_default();
-        let asks_json = serde_json::to_string(&snapshot.asks).unwrap_or_default();
-        let fetched_at = Utc::now().to_rfc3339();
+        match data {
+            ChunkData::Candles(points) => self.store_candle_chunk(dataset, points, chunk).await,
+            ChunkData::OrderBooks(snapshots) => {
+                self.store_orderbook_chunk(dataset, snapshots, chunk).await
+            }
+        }
+    }
 
+    pub async fn load_chunk(
+        &self,
+        dataset: &DatasetRef,
+        day_start: i64,
+    ) -> Result<ChunkData, sqlx::Error> {
+        let day_end = day_start + CHUNK_SECONDS - 1;
+        Ok(match dataset.kind {
+            DatasetKind::Candles => ChunkData::Candles(
+                self.get_price_data(
+                    &dataset.symbol,
+                    dataset.interval.as_deref().unwrap_or_default(),
+                    day_start,
+                    day_end,
+                )
+                .await?,
+            ),
+            DatasetKind::OrderBooks => ChunkData::OrderBooks(
+                self.get_orderbook_snapshots(&dataset.symbol, day_start, day_end)
+                    .await?,
+            ),
+        })
+    }
+
+    async fn record_chunk(
+        tx: &mut sqlx::Transaction<'_, Sqlite>,
+        dataset_id: &str,
+        chunk: &ChunkRecord,
+        fetched_at: &str,
+    ) -> Result<(), sqlx::Error> {
         sqlx::query(
             r#"
-            INSERT OR REPLACE INTO historical_orderbooks
-            (symbol, timestamp, bids, asks, fetched_at)
-            VALUES (?1, ?2, ?3, ?4, ?5)
+            INSERT OR REPLACE INTO historical_chunks
+            (dataset_id, day_start, point_count, size_bytes, checksum, complete, fetched_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
             "#,
         )
-        .bind(&snapshot.symbol)
-        .bind(snapshot.timestamp)
-        .bind(&bids_json)
-        .bind(&asks_json)
-        .bind(&fetched_at)
+        .bind(dataset_id)
+        .bind(chunk.day_start)
+        .bind(chunk.point_count as i64)
+        .bind(chunk.size_bytes as i64)
+        .bind(&chunk.checksum)
+        .bind(chunk.complete)
+        .bind(fetched_at)
+        .execute(&mut **tx)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_chunk(
+        &self,
+        dataset_id: &str,
+        day_start: i64,
+    ) -> Result<Option<ChunkRecord>, sqlx::Error> {
+        let row = sqlx::query(
+            r#"
+            SELECT day_start, point_count, size_bytes, checksum, complete
+            FROM historical_chunks
+            WHERE dataset_id = ?1 AND day_start = ?2
+            "#,
+        )
+        .bind(dataset_id)
+        .bind(day_start)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|row| {
+            Ok(ChunkRecord {
+                day_start: row.try_get("day_start")?,
+                point_count: row.try_get::<i64, _>("point_count")? as u64,
+                size_bytes: row.try_get::<i64, _>("size_bytes")? as u64,
+                checksum: row.try_get("checksum")?,
+                complete: row.try_get("complete")?,
+            })
+        })
+        .transpose()
+    }
+
+    pub async fn delete_chunk(&self, dataset_id: &str, day_start: i64) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM historical_chunks WHERE dataset_id = ?1 AND day_start = ?2")
+            .bind(dataset_id)
+            .bind(day_start)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Records a request for `dataset`, widening its tracked range, and marks
+    /// it as just used.
+    pub async fn register_dataset(
+        &self,
+        dataset: &DatasetRef,
+        start_time: i64,
+        end_time: i64,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO historical_datasets
+            (id, symbol, kind, interval, range_start, range_end, last_accessed_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            ON CONFLICT(id) DO UPDATE SET
+                range_start = MIN(range_start, excluded.range_start),
+                range_end = MAX(range_end, excluded.range_end),
+                last_accessed_at = excluded.last_accessed_at
+            "#,
+        )
+        .bind(dataset.id())
+        .bind(&dataset.symbol)
+        .bind(dataset.kind.as_str())
+        .bind(dataset.interval.as_deref())
+        .bind(start_time)
+        .bind(end_time)
+        .bind(Utc::now().timestamp_millis())
         .execute(&self.pool)
         .await?;
+        Ok(())
+    }
 
+    pub async fn touch_dataset(&self, dataset_id: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE historical_datasets SET last_accessed_at = ?1 WHERE id = ?2")
+            .bind(Utc::now().timestamp_millis())
+            .bind(dataset_id)
+            .execute(&self.pool)
+            .await?;
         Ok(())
     }
 
@@ -303,39 +470,260 @@ impl HistoricalStorage {
         Ok(snapshots)
     }
 
-    pub async fn get_cache_stats(&self, symbol: &str) -> Result<HashMap<String, u64>, sqlx::Error> {
-        let mut stats = HashMap::new();
-
-        let count: (i64,) =
-            sqlx::query_as("SELECT COUNT(*) FROM historical_prices WHERE symbol = ?1")
+    pub async fn dataset_ids_for_symbol(&self, symbol: &str) -> Result<Vec<String>, sqlx::Error> {
+        let rows: Vec<(String,)> =
+            sqlx::query_as("SELECT id FROM historical_datasets WHERE symbol = ?1")
                 .bind(symbol)
-                .fetch_one(&self.pool)
+                .fetch_all(&self.pool)
                 .await?;
-        stats.insert("price_points".to_string(), count.0 as u64);
+        Ok(rows.into_iter().map(|(id,)| id).collect())
+    }
 
-        let count: (i64,) =
-            sqlx::query_as("SELECT COUNT(*) FROM historical_orderbooks WHERE symbol = ?1")
-                .bind(symbol)
-                .fetch_one(&self.pool)
-                .await?;
-        stats.insert("orderbook_snapshots".to_string(), count.0 as u64);
+    pub async fn dataset_usage(&self) -> Result<Vec<DatasetUsage>, sqlx::Error> {
+        let rows: Vec<(String, i64, i64)> = sqlx::query_as(
+            r#"
+            SELECT d.id, COALESCE(SUM(c.size_bytes), 0), d.last_accessed_at
+            FROM historical_datasets d
+            LEFT JOIN historical_chunks c ON c.dataset_id = d.id
+            GROUP BY d.id
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(id, size_bytes, last_accessed_at)| DatasetUsage {
+                id,
+                size_bytes: size_bytes as u64,
+                last_accessed_at,
+            })
+            .collect())
+    }
+
+    pub async fn dataset_stats(
+        &self,
+        symbol: Option<&str>,
+        pinned: &HashSet<String>,
+    ) -> Result<Vec<DatasetStats>, sqlx::Error> {
+        let rows = sqlx::query(
+            r#"
+            SELECT d.id, d.symbol, d.kind, d.interval, d.range_start, d.range_end,
+                   d.last_accessed_at,
+                   COALESCE(SUM(c.size_bytes), 0) AS size_bytes,
+                   COALESCE(SUM(c.point_count), 0) AS points,
+                   COALESCE(SUM(CASE
+                       WHEN c.complete = 1 AND c.day_start + ?2 > d.range_start
+                            AND c.day_start <= d.range_end THEN 1
+                       ELSE 0
+                   END), 0) AS chunks_complete
+            FROM historical_datasets d
+            LEFT JOIN historical_chunks c ON c.dataset_id = d.id
+            WHERE ?1 IS NULL OR d.symbol = ?1
+            GROUP BY d.id
+            ORDER BY d.last_accessed_at DESC
+            "#,
+        )
+        .bind(symbol)
+        .bind(CHUNK_SECONDS)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut stats = Vec::new();
+        for row in rows {
+            let id: String = row.try_get("id")?;
+            let kind: String = row.try_get("kind")?;
+            let range_start: i64 = row.try_get("range_start")?;
+            let range_end: i64 = row.try_get("range_end")?;
+
+            stats.push(DatasetStats {
+                pinned: pinned.contains(&id),
+                id,
+                symbol: row.try_get("symbol")?,
+                kind: DatasetKind::from_str(&kind).unwrap_or(DatasetKind::Candles),
+                interval: row.try_get("interval")?,
+                size_bytes: row.try_get::<i64, _>("size_bytes")? as u64,
+                points: row.try_get::<i64, _>("points")? as u64,
+                chunks_expected: day_chunks(range_start, range_end).len() as u64,
+                chunks_complete: row.try_get::<i64, _>("chunks_complete")? as u64,
+                last_accessed_at: row.try_get("last_accessed_at")?,
+            });
+        }
 
         Ok(stats)
     }
 
-    pub async fn clear_old_data(&self, days: i64) -> Result<u64, sqlx::Error> {
-        let cutoff_time = (Utc::now() - chrono::Duration::days(days)).timestamp();
+    async fn datasets(&self) -> Result<Vec<(String, DatasetRef)>, sqlx::Error> {
+        let rows: Vec<(String, String, String, Option<String>)> =
+            sqlx::query_as("SELECT id, symbol, kind, interval FROM historical_datasets")
+                .fetch_all(&self.pool)
+                .await?;
 
-        let result = sqlx::query("DELETE FROM historical_prices WHERE timestamp < ?1")
-            .bind(cutoff_time)
+        Ok(rows
+            .into_iter()
+            .filter_map(|(id, symbol, kind, interval)| {
+                let kind = DatasetKind::from_str(&kind)?;
+                Some((
+                    id,
+                    DatasetRef {
+                        symbol,
+                        kind,
+                        interval,
+                    },
+                ))
+            })
+            .collect())
+    }
+
+    /// Deletes a dataset's data before `cutoff`, or all of it, along with
+    /// the matching manifest entries. Returns the number of rows removed.
+    async fn delete_dataset_data(
+        &self,
+        dataset: &DatasetRef,
+        cutoff: Option<i64>,
+    ) -> Result<u64, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = match dataset.kind {
+            DatasetKind::Candles => {
+                sqlx::query(
+                    r#"
+                    DELETE FROM historical_prices
+                    WHERE symbol = ?1 AND interval = ?2 AND (?3 IS NULL OR timestamp < ?3)
+                    "#,
+                )
+                .bind(&dataset.symbol)
+                .bind(dataset.interval.as_deref())
+                .bind(cutoff)
+                .execute(&mut *tx)
+                .await?
+            }
+            DatasetKind::OrderBooks => {
+                sqlx::query(
+                    r#"
+                    DELETE FROM historical_orderbooks
+                    WHERE symbol = ?1 AND (?2 IS NULL OR timestamp < ?2)
+                    "#,
+                )
+                .bind(&dataset.symbol)
+                .bind(cutoff)
+                .execute(&mut *tx)
+                .await?
+            }
+        };
+
+        // A chunk cut in half by the cutoff is dropped so it is re-downloaded
+        sqlx::query(
+            "DELETE FROM historical_chunks WHERE dataset_id = ?1 AND (?2 IS NULL OR day_start < ?2)",
+        )
+        .bind(dataset.id())
+        .bind(cutoff)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(result.rows_affected())
+    }
+
+    pub async fn evict_dataset(&self, dataset_id: &str) -> Result<u64, sqlx::Error> {
+        let Some((_, dataset)) = self
+            .datasets()
+            .await?
+            .into_iter()
+            .find(|(id, _)| id == dataset_id)
+        else {
+            return Ok(0);
+        };
+
+        let removed = self.delete_dataset_data(&dataset, None).await?;
+        sqlx::query("DELETE FROM historical_datasets WHERE id = ?1")
+            .bind(dataset_id)
             .execute(&self.pool)
             .await?;
+        Ok(removed)
+    }
 
-        sqlx::query("DELETE FROM historical_orderbooks WHERE timestamp < ?1")
-            .bind(cutoff_time)
+    pub async fn clear_datasets(&self, selector: &DatasetSelector) -> Result<u64, sqlx::Error> {
+        let cutoff = selector
+            .older_than_days
+            .map(|days| (Utc::now() - chrono::Duration::days(days)).timestamp());
+        let mut removed = 0;
+
+        if selector.matches_all_datasets() {
+            // Also covers rows stored before datasets were tracked
+            let result =
+                sqlx::query("DELETE FROM historical_prices WHERE ?1 IS NULL OR timestamp < ?1")
+                    .bind(cutoff)
+                    .execute(&self.pool)
+                    .await?;
+            removed += result.rows_affected();
+
+            let result =
+                sqlx::query("DELETE FROM historical_orderbooks WHERE ?1 IS NULL OR timestamp < ?1")
+                    .bind(cutoff)
+                    .execute(&self.pool)
+                    .await?;
+            removed += result.rows_affected();
+
+            sqlx::query("DELETE FROM historical_chunks WHERE ?1 IS NULL OR day_start < ?1")
+                .bind(cutoff)
+                .execute(&self.pool)
+                .await?;
+        } else {
+            for (id, dataset) in self.datasets().await? {
+                let selected = (selector.dataset_ids.is_empty()
+                    || selector.dataset_ids.contains(&id))
+                    && selector
+                        .symbol
+                        .as_ref()
+                        .map_or(true, |symbol| *symbol == dataset.symbol)
+                    && selector.kind.map_or(true, |kind| kind == dataset.kind);
+                if selected {
+                    removed += self.delete_dataset_data(&dataset, cutoff).await?;
+                }
+            }
+        }
+
+        if cutoff.is_none() {
+            sqlx::query(
+                r#"
+                DELETE FROM historical_datasets
+                WHERE id NOT IN (SELECT DISTINCT dataset_id FROM historical_chunks)
+                "#,
+            )
             .execute(&self.pool)
             .await?;
+        }
 
-        Ok(result.rows_affected())
+        Ok(removed)
+    }
+
+    pub async fn get_settings(&self) -> Result<HistoricalStoreSettings, sqlx::Error> {
+        let row: Option<(String,)> =
+            sqlx::query_as("SELECT value FROM historical_settings WHERE key = ?1")
+                .bind(SETTINGS_KEY)
+                .fetch_optional(&self.pool)
+                .await?;
+
+        Ok(row
+            .and_then(|(value,)| serde_json::from_str(&value).ok())
+            .unwrap_or_default())
+    }
+
+    pub async fn save_settings(
+        &self,
+        settings: &HistoricalStoreSettings,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO historical_settings (key, value) VALUES (?1, ?2)
+            ON CONFLICT(key) DO UPDATE SET value = excluded.value
+            "#,
+        )
+        .bind(SETTINGS_KEY)
+        .bind(serde_json::to_string(settings).unwrap_or_default())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
     }
 }
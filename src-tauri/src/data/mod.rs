@@ -2,8 +2,10 @@ pub mod compression_commands;
 pub mod database;
 pub mod event_store;
 pub mod historical;
+pub mod tiers;
 
 pub use compression_commands::*;
 pub use database::*;
 pub use event_store::*;
 pub use historical::*;
+pub use tiers::{ArchiveInfo, ArchiveManifest, ArchivedBlock, TierStats, TierUsage};
@@ -0,0 +1,936 @@
+//! Tiered retention for the event log.
+//!
+//! Hot events live uncompressed in `events`. Once they age past the hot
+//! window they are packed, oldest first, into zstd blocks of newline-delimited
+//! JSON in `event_blocks` (the warm tier); queries decode those blocks one at a
+//! time so memory stays bounded by the block size rather than the tier size.
+//! Warm blocks past the cold boundary are moved out to zip archives with a
+//! manifest (the cold tier), are no longer queried, and can be restored.
+
+use crate::data::event_store::{EventFilter, EventRecord};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::{Pool, Row, Sqlite};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+pub const DEFAULT_BLOCK_ROWS: i64 = 1_000;
+pub const DEFAULT_WARM_QUERY_ROW_LIMIT: i64 = 10_000;
+pub const ARCHIVE_MANIFEST_NAME: &str = "manifest.json";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TierUsage {
+    pub rows: i64,
+    /// Size of the rows as uncompressed JSON.
+    pub original_bytes: i64,
+    /// Bytes actually stored for the tier (database or archive files).
+    pub stored_bytes: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TierStats {
+    pub hot: TierUsage,
+    pub warm: TierUsage,
+    pub cold: TierUsage,
+    pub warm_blocks: i64,
+    pub cold_archives: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CompactionSummary {
+    pub blocks: i64,
+    pub rows: i64,
+    pub original_bytes: i64,
+    pub compressed_bytes: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedBlock {
+    pub id: String,
+    pub first_timestamp: String,
+    pub last_timestamp: String,
+    pub row_count: i64,
+    pub original_size: i64,
+    pub compressed_size: i64,
+    pub sha256: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveManifest {
+    pub id: String,
+    pub created_at: DateTime<Utc>,
+    pub first_timestamp: String,
+    pub last_timestamp: String,
+    pub row_count: i64,
+    pub original_size: i64,
+    pub compressed_size: i64,
+    pub blocks: Vec<ArchivedBlock>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveInfo {
+    pub manifest: ArchiveManifest,
+    pub path: String,
+    pub restored_at: Option<String>,
+}
+
+/// Result of scanning the warm tier for one query.
+#[derive(Debug, Clone, Default)]
+pub struct WarmScan {
+    /// Matching rows seen, including the ones skipped by the offset.
+    pub matched: i64,
+    pub returned: usize,
+    /// False when the scan stopped early because `take` rows were returned.
+    pub exhausted: bool,
+}
+
+pub async fn ensure_schema(pool: &Pool<Sqlite>) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS event_blocks (
+            id TEXT PRIMARY KEY,
+            first_timestamp TEXT NOT NULL,
+            last_timestamp TEXT NOT NULL,
+            row_count INTEGER NOT NULL,
+            original_size INTEGER NOT NULL,
+            compressed_size INTEGER NOT NULL,
+            data BLOB NOT NULL,
+            compressed_at TEXT NOT NULL,
+            restored_from TEXT
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE INDEX IF NOT EXISTS idx_event_blocks_first ON event_blocks(first_timestamp);
+        CREATE INDEX IF NOT EXISTS idx_event_blocks_last ON event_blocks(last_timestamp);
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS event_archives (
+            id TEXT PRIMARY KEY,
+            path TEXT NOT NULL,
+            manifest TEXT NOT NULL,
+            row_count INTEGER NOT NULL,
+            original_size INTEGER NOT NULL,
+            compressed_size INTEGER NOT NULL,
+            created_at TEXT NOT NULL,
+            restored_at TEXT
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    // Highest sequence moved out of `events` per aggregate, so sequence
+    // counters keep increasing once an aggregate's hot rows are gone.
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS event_sequence_floor (
+            aggregate_id TEXT PRIMARY KEY,
+            sequence INTEGER NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+async fn table_exists(pool: &Pool<Sqlite>, table: &str) -> Result<bool, sqlx::Error> {
+    let row = sqlx::query("SELECT name FROM sqlite_master WHERE type = 'table' AND name = ?1")
+        .bind(table)
+        .fetch_optional(pool)
+        .await?;
+    Ok(row.is_some())
+}
+
+/// Compresses records into a block of newline-delimited JSON. Returns the
+/// block and the uncompressed size.
+pub fn encode_block(records: &[EventRecord], level: i32) -> Result<(Vec<u8>, i64), String> {
+    let mut encoder = zstd::stream::write::Encoder::new(Vec::new(), level)
+        .map_err(|e| format!("Failed to start block compression: {}", e))?;
+    let mut original_size = 0i64;
+
+    for record in records {
+        let line = serde_json::to_vec(record)
+            .map_err(|e| format!("Failed to serialize event {}: {}", record.id, e))?;
+        original_size += line.len() as i64 + 1;
+        encoder
+            .write_all(&line)
+            .and_then(|_| encoder.write_all(b"\n"))
+            .map_err(|e| format!("Failed to compress event {}: {}", record.id, e))?;
+    }
+
+    let block = encoder
+        .finish()
+        .map_err(|e| format!("Failed to finish block compression: {}", e))?;
+    Ok((block, original_size))
+}
+
+/// Streams the records of a block to `visit` without inflating the whole
+/// block; `visit` returns false to stop early.
+pub fn for_each_in_block<F>(block: &[u8], mut visit: F) -> Result<(), sqlx::Error>
+where
+    F: FnMut(EventRecord) -> bool,
+{
+    let decoder = zstd::stream::read::Decoder::new(block)?;
+    let mut reader = BufReader::new(decoder);
+    let mut line = String::new();
+
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(());
+        }
+        let trimmed = line.trim_end();
+        if trimmed.is_empty() {
+            continue;
+        }
+        let record: EventRecord =
+            serde_json::from_str(trimmed).map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+        if !visit(record) {
+            return Ok(());
+        }
+    }
+}
+
+fn matches_filter(
+    record: &EventRecord,
+    filter: &EventFilter,
+    from: Option<&str>,
+    to: Option<&str>,
+) -> bool {
+    if let Some(aggregate_id) = &filter.aggregate_id {
+        if &record.aggregate_id != aggregate_id {
+            return false;
+        }
+    }
+    if let Some(event_type) = &filter.event_type {
+        if &record.event_type != event_type {
+            return false;
+        }
+    }
+    if let Some(from) = from {
+        if record.timestamp.as_str() < from {
+            return false;
+        }
+    }
+    if let Some(to) = to {
+        if record.timestamp.as_str() > to {
+            return false;
+        }
+    }
+    true
+}
+
+/// Appends up to `take` warm rows matching `filter` to `out`, after skipping
+/// the first `skip` matches. Blocks outside the filter's time range are never
+/// loaded and only one block is held in memory at a time.
+pub async fn scan_warm_events(
+    pool: &Pool<Sqlite>,
+    filter: &EventFilter,
+    skip: i64,
+    take: usize,
+    out: &mut Vec<EventRecord>,
+) -> Result<WarmScan, sqlx::Error> {
+    let from = filter.from_time.map(|t| t.to_rfc3339());
+    let to = filter.to_time.map(|t| t.to_rfc3339());
+    let mut scan = WarmScan {
+        exhausted: true,
+        ..Default::default()
+    };
+    if take == 0 {
+        scan.exhausted = false;
+        return Ok(scan);
+    }
+
+    let block_ids: Vec<String> = sqlx::query_scalar(
+        r#"
+        SELECT id FROM event_blocks
+        WHERE (?1 IS NULL OR last_timestamp >= ?1)
+        AND (?2 IS NULL OR first_timestamp <= ?2)
+        ORDER BY first_timestamp ASC, id ASC
+        "#,
+    )
+    .bind(&from)
+    .bind(&to)
+    .fetch_all(pool)
+    .await?;
+
+    for block_id in block_ids {
+        let block: Option<Vec<u8>> =
+            sqlx::query_scalar("SELECT data FROM event_blocks WHERE id = ?1")
+                .bind(&block_id)
+                .fetch_optional(pool)
+                .await?;
+        // Archived between listing and loading
+        let Some(block) = block else {
+            continue;
+        };
+
+        for_each_in_block(&block, |record| {
+            if !matches_filter(&record, filter, from.as_deref(), to.as_deref()) {
+                return true;
+            }
+            scan.matched += 1;
+            if scan.matched <= skip {
+                return true;
+            }
+            out.push(record);
+            scan.returned += 1;
+            scan.returned < take
+        })?;
+
+        if scan.returned >= take {
+            scan.exhausted = false;
+            break;
+        }
+    }
+
+    Ok(scan)
+}
+
+/// Moves events older than `before` out of `events` into warm blocks of
+/// `block_rows` rows, oldest first.
+pub async fn compact_hot_events(
+    pool: &Pool<Sqlite>,
+    before: DateTime<Utc>,
+    block_rows: i64,
+    compression_level: i32,
+) -> Result<CompactionSummary, String> {
+    let mut summary = CompactionSummary::default();
+    if !table_exists(pool, "events")
+        .await
+        .map_err(|e| e.to_string())?
+    {
+        return Ok(summary);
+    }
+
+    let before = before.to_rfc3339();
+    let block_rows = block_rows.max(1);
+    let has_legacy_copies = table_exists(pool, "compressed_data")
+        .await
+        .map_err(|e| e.to_string())?;
+
+    loop {
+        let records = sqlx::query_as::<_, EventRecord>(
+            r#"
+            SELECT * FROM events
+            WHERE timestamp < ?1
+            ORDER BY timestamp ASC, id ASC
+            LIMIT ?2
+            "#,
+        )
+        .bind(&before)
+        .bind(block_rows)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Failed to load hot events: {}", e))?;
+
+        let (Some(first), Some(last)) = (records.first(), records.last()) else {
+            break;
+        };
+
+        let (block, original_size) = encode_block(&records, compression_level)?;
+        let mut floors: HashMap<&str, i64> = HashMap::new();
+        for record in &records {
+            let floor = floors.entry(record.aggregate_id.as_str()).or_insert(0);
+            *floor = (*floor).max(record.sequence);
+        }
+
+        let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+        sqlx::query(
+            r#"
+            INSERT INTO event_blocks
+            (id, first_timestamp, last_timestamp, row_count, original_size, compressed_size, data, compressed_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+            "#,
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(&first.timestamp)
+        .bind(&last.timestamp)
+        .bind(records.len() as i64)
+        .bind(original_size)
+        .bind(block.len() as i64)
+        .bind(&block)
+        .bind(Utc::now().to_rfc3339())
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to store warm block: {}", e))?;
+
+        for (aggregate_id, sequence) in floors {
+            sqlx::query(
+                r#"
+                INSERT INTO event_sequence_floor (aggregate_id, sequence)
+                VALUES (?1, ?2)
+                ON CONFLICT(aggregate_id) DO UPDATE SET sequence = MAX(sequence, excluded.sequence)
+                "#,
+            )
+            .bind(aggregate_id)
+            .bind(sequence)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+        }
+
+        // The block holds exactly the oldest rows up to (last.timestamp, last.id)
+        sqlx::query(
+            r#"
+            DELETE FROM events
+            WHERE timestamp < ?1
+            AND (timestamp < ?2 OR (timestamp = ?2 AND id <= ?3))
+            "#,
+        )
+        .bind(&before)
+        .bind(&last.timestamp)
+        .bind(&last.id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to remove compacted events: {}", e))?;
+
+        // Per-record copies written by earlier versions are superseded by the block
+        if has_legacy_copies {
+            for record in &records {
+                sqlx::query("DELETE FROM compressed_data WHERE id = ?1 AND record_type = 'event'")
+                    .bind(&record.id)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(|e| e.to_string())?;
+            }
+        }
+
+        tx.commit().await.map_err(|e| e.to_string())?;
+
+        summary.blocks += 1;
+        summary.rows += records.len() as i64;
+        summary.original_bytes += original_size;
+        summary.compressed_bytes += block.len() as i64;
+
+        if (records.len() as i64) < block_rows {
+            break;
+        }
+    }
+
+    Ok(summary)
+}
+
+fn archive_path(archive_dir: &Path, archive_id: &str) -> PathBuf {
+    archive_dir.join(format!("events-{}.zip", archive_id))
+}
+
+/// Moves warm blocks whose newest row is older than `before` into a new zip
+/// archive under `archive_dir`. Blocks restored from an archive stay warm.
+pub async fn archive_warm_blocks(
+    pool: &Pool<Sqlite>,
+    before: DateTime<Utc>,
+    archive_dir: &Path,
+) -> Result<Option<ArchiveManifest>, String> {
+    let rows = sqlx::query(
+        r#"
+        SELECT id, first_timestamp, last_timestamp, row_count, original_size, compressed_size
+        FROM event_blocks
+        WHERE last_timestamp < ?1 AND restored_from IS NULL
+        ORDER BY first_timestamp ASC, id ASC
+        "#,
+    )
+    .bind(before.to_rfc3339())
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to list warm blocks: {}", e))?;
+
+    if rows.is_empty() {
+        return Ok(None);
+    }
+
+    let archive_id = Uuid::new_v4().to_string();
+    fs::create_dir_all(archive_dir)
+        .map_err(|e| format!("Failed to create archive directory: {}", e))?;
+    let path = archive_path(archive_dir, &archive_id);
+
+    let manifest = match write_archive(pool, &archive_id, &rows, &path).await {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            let _ = fs::remove_file(&path);
+            return Err(e);
+        }
+    };
+    let manifest_json = serde_json::to_string(&manifest)
+        .map_err(|e| format!("Failed to serialize archive manifest: {}", e))?;
+
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    sqlx::query(
+        r#"
+        INSERT INTO event_archives
+        (id, path, manifest, row_count, original_size, compressed_size, created_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+        "#,
+    )
+    .bind(&manifest.id)
+    .bind(path.display().to_string())
+    .bind(&manifest_json)
+    .bind(manifest.row_count)
+    .bind(manifest.original_size)
+    .bind(manifest.compressed_size)
+    .bind(manifest.created_at.to_rfc3339())
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to record archive: {}", e))?;
+
+    for block in &manifest.blocks {
+        sqlx::query("DELETE FROM event_blocks WHERE id = ?1")
+            .bind(&block.id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    if let Err(e) = tx.commit().await {
+        let _ = fs::remove_file(&path);
+        return Err(format!("Failed to commit archive: {}", e));
+    }
+
+    Ok(Some(manifest))
+}
+
+async fn write_archive(
+    pool: &Pool<Sqlite>,
+    archive_id: &str,
+    rows: &[sqlx::sqlite::SqliteRow],
+    path: &Path,
+) -> Result<ArchiveManifest, String> {
+    let file = File::create(path).map_err(|e| format!("Failed to create archive: {}", e))?;
+    let mut zip = ZipWriter::new(file);
+    // Blocks are already zstd compressed
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+
+    let mut blocks = Vec::with_capacity(rows.len());
+    for row in rows {
+        let id: String = row.get("id");
+        let data: Vec<u8> = sqlx::query_scalar("SELECT data FROM event_blocks WHERE id = ?1")
+            .bind(&id)
+            .fetch_one(pool)
+            .await
+            .map_err(|e| format!("Failed to load warm block {}: {}", id, e))?;
+
+        zip.start_file(format!("blocks/{}.zst", id), options)
+            .and_then(|_| zip.write_all(&data).map_err(Into::into))
+            .map_err(|e| format!("Failed to write block {} to archive: {}", id, e))?;
+
+        blocks.push(ArchivedBlock {
+            sha256: hex::encode(Sha256::digest(&data)),
+            first_timestamp: row.get("first_timestamp"),
+            last_timestamp: row.get("last_timestamp"),
+            row_count: row.get("row_count"),
+            original_size: row.get("original_size"),
+            compressed_size: data.len() as i64,
+            id,
+        });
+    }
+
+    let manifest = ArchiveManifest {
+        id: archive_id.to_string(),
+        created_at: Utc::now(),
+        first_timestamp: blocks
+            .first()
+            .map(|b| b.first_timestamp.clone())
+            .unwrap_or_default(),
+        last_timestamp: blocks
+            .iter()
+            .map(|b| b.last_timestamp.clone())
+            .max()
+            .unwrap_or_default(),
+        row_count: blocks.iter().map(|b| b.row_count).sum(),
+        original_size: blocks.iter().map(|b| b.original_size).sum(),
+        compressed_size: blocks.iter().map(|b| b.compressed_size).sum(),
+        blocks,
+    };
+
+    let manifest_json = serde_json::to_vec_pretty(&manifest)
+        .map_err(|e| format!("Failed to serialize archive manifest: {}", e))?;
+    zip.start_file(ARCHIVE_MANIFEST_NAME, options)
+        .and_then(|_| zip.write_all(&manifest_json).map_err(Into::into))
+        .map_err(|e| format!("Failed to write archive manifest: {}", e))?;
+    zip.finish()
+        .map_err(|e| format!("Failed to finish archive: {}", e))?;
+
+    Ok(manifest)
+}
+
+/// Reads an archive's manifest from the archive file itself.
+pub fn read_archive_manifest(path: &Path) -> Result<ArchiveManifest, String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open archive: {}", e))?;
+    let mut archive =
+        ZipArchive::new(file).map_err(|e| format!("Failed to read archive: {}", e))?;
+    let mut contents = String::new();
+    archive
+        .by_name(ARCHIVE_MANIFEST_NAME)
+        .map_err(|e| format!("Archive manifest missing: {}", e))?
+        .read_to_string(&mut contents)
+        .map_err(|e| format!("Failed to read archive manifest: {}", e))?;
+    serde_json::from_str(&contents).map_err(|e| format!("Invalid archive manifest: {}", e))
+}
+
+/// Loads the blocks of a cold archive back into the warm tier after checking
+/// them against the manifest.
+pub async fn restore_archive(
+    pool: &Pool<Sqlite>,
+    archive_id: &str,
+) -> Result<ArchiveManifest, String> {
+    let row = sqlx::query("SELECT path, restored_at FROM event_archives WHERE id = ?1")
+        .bind(archive_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Archive {} not found", archive_id))?;
+    if let Some(restored_at) = row.get::<Option<String>, _>("restored_at") {
+        return Err(format!(
+            "Archive {} was already restored at {}",
+            archive_id, restored_at
+        ));
+    }
+
+    let path = PathBuf::from(row.get::<String, _>("path"));
+    let manifest = read_archive_manifest(&path)?;
+    if manifest.id != archive_id {
+        return Err(format!(
+            "Archive file {} holds archive {}, expected {}",
+            path.display(),
+            manifest.id,
+            archive_id
+        ));
+    }
+
+    let file = File::open(&path).map_err(|e| format!("Failed to open archive: {}", e))?;
+    let mut archive =
+        ZipArchive::new(file).map_err(|e| format!("Failed to read archive: {}", e))?;
+    let restored_at = Utc::now().to_rfc3339();
+
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    for block in &manifest.blocks {
+        let mut data = Vec::with_capacity(block.compressed_size.max(0) as usize);
+        archive
+            .by_name(&format!("blocks/{}.zst", block.id))
+            .map_err(|e| format!("Block {} missing from archive: {}", block.id, e))?
+            .read_to_end(&mut data)
+            .map_err(|e| format!("Failed to read block {}: {}", block.id, e))?;
+        if hex::encode(Sha256::digest(&data)) != block.sha256 {
+            return Err(format!("Block {} failed its checksum", block.id));
+        }
+
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO event_blocks
+            (id, first_timestamp, last_timestamp, row_count, original_size, compressed_size, data, compressed_at, restored_from)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+            "#,
+        )
+        .bind(&block.id)
+        .bind(&block.first_timestamp)
+        .bind(&block.last_timestamp)
+        .bind(block.row_count)
+        .bind(block.original_size)
+        .bind(block.compressed_size)
+        .bind(&data)
+        .bind(&restored_at)
+        .bind(archive_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to restore block {}: {}", block.id, e))?;
+    }
+
+    sqlx::query("UPDATE event_archives SET restored_at = ?1 WHERE id = ?2")
+        .bind(&restored_at)
+        .bind(archive_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    Ok(manifest)
+}
+
+pub async fn list_archives(pool: &Pool<Sqlite>) -> Result<Vec<ArchiveInfo>, String> {
+    let rows = sqlx::query(
+        "SELECT manifest, path, restored_at FROM event_archives ORDER BY created_at DESC",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to list archives: {}", e))?;
+
+    rows.into_iter()
+        .map(|row| {
+            let manifest = serde_json::from_str(&row.get::<String, _>("manifest"))
+                .map_err(|e| format!("Invalid archive manifest: {}", e))?;
+            Ok(ArchiveInfo {
+                manifest,
+                path: row.get("path"),
+                restored_at: row.get("restored_at"),
+            })
+        })
+        .collect()
+}
+
+pub async fn tier_stats(pool: &Pool<Sqlite>) -> Result<TierStats, sqlx::Error> {
+    let mut stats = TierStats::default();
+
+    if table_exists(pool, "events").await? {
+        let hot = sqlx::query(
+            "SELECT COUNT(*) AS row_total, COALESCE(SUM(LENGTH(event_data)), 0) AS byte_total FROM events",
+        )
+        .fetch_one(pool)
+        .await?;
+        stats.hot = TierUsage {
+            rows: hot.get("row_total"),
+            original_bytes: hot.get("byte_total"),
+            stored_bytes: hot.get("byte_total"),
+        };
+    }
+
+    let warm = sqlx::query(
+        r#"
+        SELECT COUNT(*) AS blocks,
+            COALESCE(SUM(row_count), 0) AS row_total,
+            COALESCE(SUM(original_size), 0) AS original,
+            COALESCE(SUM(compressed_size), 0) AS stored
+        FROM event_blocks
+        "#,
+    )
+    .fetch_one(pool)
+    .await?;
+    stats.warm_blocks = warm.get("blocks");
+    stats.warm = TierUsage {
+        rows: warm.get("row_total"),
+        original_bytes: warm.get("original"),
+        stored_bytes: warm.get("stored"),
+    };
+
+    let cold = sqlx::query(
+        r#"
+        SELECT COUNT(*) AS archives,
+            COALESCE(SUM(row_count), 0) AS row_total,
+            COALESCE(SUM(original_size), 0) AS original,
+            COALESCE(SUM(compressed_size), 0) AS stored
+        FROM event_archives
+        WHERE restored_at IS NULL
+        "#,
+    )
+    .fetch_one(pool)
+    .await?;
+    stats.cold_archives = cold.get("archives");
+    stats.cold = TierUsage {
+        rows: cold.get("row_total"),
+        original_bytes: cold.get("original"),
+        stored_bytes: cold.get("stored"),
+    };
+
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use sqlx::SqlitePool;
+
+    fn record(id: usize, aggregate_id: &str, timestamp: DateTime<Utc>) -> EventRecord {
+        EventRecord {
+            id: format!("evt-{:06}", id),
+            event_type: "setting_changed".to_string(),
+            event_data: format!(r#"{{"type":"setting_changed","key":"k{}"}}"#, id),
+            aggregate_id: aggregate_id.to_string(),
+            sequence: id as i64 + 1,
+            timestamp: timestamp.to_rfc3339(),
+        }
+    }
+
+    async fn setup(records: &[EventRecord]) -> Pool<Sqlite> {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        sqlx::query(
+            r#"
+            CREATE TABLE events (
+                id TEXT PRIMARY KEY,
+                event_type TEXT NOT NULL,
+                event_data TEXT NOT NULL,
+                aggregate_id TEXT NOT NULL,
+                sequence INTEGER NOT NULL,
+                timestamp TEXT NOT NULL
+            )
+            "#,
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        ensure_schema(&pool).await.unwrap();
+
+        for r in records {
+            sqlx::query("INSERT INTO events VALUES (?1, ?2, ?3, ?4, ?5, ?6)")
+                .bind(&r.id)
+                .bind(&r.event_type)
+                .bind(&r.event_data)
+                .bind(&r.aggregate_id)
+                .bind(r.sequence)
+                .bind(&r.timestamp)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+        pool
+    }
+
+    fn empty_filter() -> EventFilter {
+        EventFilter {
+            aggregate_id: None,
+            event_type: None,
+            from_time: None,
+            to_time: None,
+            limit: None,
+            offset: None,
+        }
+    }
+
+    #[test]
+    fn test_block_roundtrip() {
+        let now = Utc::now();
+        let records: Vec<_> = (0..50).map(|i| record(i, "settings", now)).collect();
+        let (block, original_size) = encode_block(&records, 3).unwrap();
+        assert!(original_size > block.len() as i64);
+
+        let mut decoded = Vec::new();
+        for_each_in_block(&block, |r| {
+            decoded.push(r);
+            true
+        })
+        .unwrap();
+        assert_eq!(decoded.len(), 50);
+        assert_eq!(decoded[49].id, records[49].id);
+    }
+
+    #[tokio::test]
+    async fn test_compaction_moves_only_aged_rows() {
+        let old = Utc::now() - Duration::days(30);
+        let mut records: Vec<_> = (0..25)
+            .map(|i| record(i, "settings", old + Duration::seconds(i as i64)))
+            .collect();
+        records.push(record(25, "settings", Utc::now()));
+        let pool = setup(&records).await;
+
+        let summary = compact_hot_events(&pool, Utc::now() - Duration::days(7), 10, 3)
+            .await
+            .unwrap();
+        assert_eq!(summary.rows, 25);
+        assert_eq!(summary.blocks, 3);
+
+        let stats = tier_stats(&pool).await.unwrap();
+        assert_eq!(stats.hot.rows, 1);
+        assert_eq!(stats.warm.rows, 25);
+        assert_eq!(stats.warm_blocks, 3);
+
+        let floor: i64 = sqlx::query_scalar(
+            "SELECT sequence FROM event_sequence_floor WHERE aggregate_id = 'settings'",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(floor, 25);
+    }
+
+    #[tokio::test]
+    async fn test_warm_scan_honours_filter_offset_and_take() {
+        let old = Utc::now() - Duration::days(30);
+        let records: Vec<_> = (0..40)
+            .map(|i| {
+                let aggregate = if i % 2 == 0 { "even" } else { "odd" };
+                record(i, aggregate, old + Duration::seconds(i as i64))
+            })
+            .collect();
+        let pool = setup(&records).await;
+        compact_hot_events(&pool, Utc::now(), 8, 3).await.unwrap();
+
+        let filter = EventFilter {
+            aggregate_id: Some("even".to_string()),
+            ..empty_filter()
+        };
+        let mut out = Vec::new();
+        let scan = scan_warm_events(&pool, &filter, 5, 4, &mut out)
+            .await
+            .unwrap();
+        assert_eq!(scan.returned, 4);
+        assert!(!scan.exhausted);
+        assert_eq!(out[0].id, "evt-000010");
+        assert!(out.iter().all(|r| r.aggregate_id == "even"));
+
+        let filter = EventFilter {
+            from_time: Some(old + Duration::seconds(35)),
+            ..empty_filter()
+        };
+        let mut out = Vec::new();
+        let scan = scan_warm_events(&pool, &filter, 0, 100, &mut out)
+            .await
+            .unwrap();
+        assert!(scan.exhausted);
+        assert_eq!(scan.matched, 5);
+    }
+
+    #[tokio::test]
+    async fn test_archive_and_restore() {
+        let old = Utc::now() - Duration::days(400);
+        let records: Vec<_> = (0..30)
+            .map(|i| record(i, "settings", old + Duration::seconds(i as i64)))
+            .collect();
+        let pool = setup(&records).await;
+        compact_hot_events(&pool, Utc::now(), 10, 3).await.unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let manifest = archive_warm_blocks(&pool, Utc::now() - Duration::days(180), dir.path())
+            .await
+            .unwrap()
+            .expect("blocks archived");
+        assert_eq!(manifest.row_count, 30);
+        assert_eq!(manifest.blocks.len(), 3);
+
+        let stats = tier_stats(&pool).await.unwrap();
+        assert_eq!(stats.warm.rows, 0);
+        assert_eq!(stats.cold.rows, 30);
+
+        let mut out = Vec::new();
+        scan_warm_events(&pool, &empty_filter(), 0, 100, &mut out)
+            .await
+            .unwrap();
+        assert!(out.is_empty());
+
+        let on_disk = read_archive_manifest(&archive_path(dir.path(), &manifest.id)).unwrap();
+        assert_eq!(on_disk.row_count, 30);
+
+        restore_archive(&pool, &manifest.id).await.unwrap();
+        scan_warm_events(&pool, &empty_filter(), 0, 100, &mut out)
+            .await
+            .unwrap();
+        assert_eq!(out.len(), 30);
+        assert!(restore_archive(&pool, &manifest.id).await.is_err());
+
+        // Restored blocks are not archived again
+        let again = archive_warm_blocks(&pool, Utc::now() - Duration::days(180), dir.path())
+            .await
+            .unwrap();
+        assert!(again.is_none());
+
+        let stats = tier_stats(&pool).await.unwrap();
+        assert_eq!(stats.warm.rows, 30);
+        assert_eq!(stats.cold.rows, 0);
+    }
+}
@@ -0,0 +1,306 @@
+// Health factors of lending accounts. Live risk metrics and stress tests
+// both go through `LendingAccount::health_factor`, so a stress test with no
+// price change always reports the live value.
+
+use crate::defi::types::*;
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_LIQUIDATION_THRESHOLD: f64 = 0.80;
+pub const DEFAULT_LIQUIDATION_PENALTY: f64 = 0.05;
+/// Largest price rise searched for when a debt asset's rise would trigger
+/// liquidation, as a multiple of the current price.
+pub const MAX_PRICE_MULTIPLE: f64 = 11.0;
+
+const BISECTION_STEPS: usize = 60;
+
+/// Liquidation threshold and penalty of `asset` used as collateral,
+/// matching the reserve parameters the lending adapters report.
+pub fn liquidation_params(asset: &str) -> (f64, f64) {
+    match asset {
+        "USDC" | "USDT" | "DAI" => (0.85, 0.05),
+        _ => (DEFAULT_LIQUIDATION_THRESHOLD, DEFAULT_LIQUIDATION_PENALTY),
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountAsset {
+    pub position_id: String,
+    pub asset: String,
+    pub amount: f64,
+    pub value_usd: f64,
+    pub liquidation_threshold: f64,
+    pub liquidation_penalty: f64,
+}
+
+impl AccountAsset {
+    fn from_position(position: &DeFiPosition) -> Self {
+        let (liquidation_threshold, liquidation_penalty) = liquidation_params(&position.asset);
+        Self {
+            position_id: position.id.clone(),
+            asset: position.asset.clone(),
+            amount: position.amount,
+            value_usd: position.value_usd,
+            liquidation_threshold,
+            liquidation_penalty,
+        }
+    }
+
+    /// USD price per unit, when the amount is known.
+    pub fn price(&self) -> Option<f64> {
+        (self.amount > 0.0).then(|| self.value_usd / self.amount)
+    }
+}
+
+/// One wallet's deposits and borrows on a single lending protocol.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LendingAccount {
+    pub protocol: Protocol,
+    pub collateral: Vec<AccountAsset>,
+    pub debt: Vec<AccountAsset>,
+}
+
+/// The price move of one asset at which an account's health factor
+/// reaches 1.0.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LiquidationTrigger {
+    pub protocol: Protocol,
+    pub asset: String,
+    /// Positions of the account holding or owing `asset`.
+    pub position_ids: Vec<String>,
+    /// Negative for a fall, positive for a rise; 0 when already liquidatable.
+    pub move_percent: f64,
+    pub current_price: Option<f64>,
+    pub trigger_price: Option<f64>,
+}
+
+impl LendingAccount {
+    /// Groups lending and borrowing positions by protocol.
+    pub fn from_positions(positions: &[DeFiPosition]) -> Vec<LendingAccount> {
+        let mut accounts: Vec<LendingAccount> = Vec::new();
+        for position in positions {
+            let is_debt = match position.position_type {
+                PositionType::Lending => false,
+                PositionType::Borrowing => true,
+                _ => continue,
+            };
+            let index = match accounts
+                .iter()
+                .position(|account| account.protocol == position.protocol)
+            {
+                Some(index) => index,
+                None => {
+                    accounts.push(LendingAccount {
+                        protocol: position.protocol.clone(),
+                        collateral: Vec::new(),
+                        debt: Vec::new(),
+                    });
+                    accounts.len() - 1
+                }
+            };
+            let asset = AccountAsset::from_position(position);
+            if is_debt {
+                accounts[index].debt.push(asset);
+            } else {
+                accounts[index].collateral.push(asset);
+            }
+        }
+        accounts
+    }
+
+    pub fn contains(&self, position_id: &str) -> bool {
+        self.collateral
+            .iter()
+            .chain(&self.debt)
+            .any(|asset| asset.position_id == position_id)
+    }
+
+    /// Threshold-weighted collateral over debt, with each asset's value
+    /// scaled by `price_multiplier(asset)`. `None` without debt.
+    pub fn health_factor(&self, price_multiplier: &dyn Fn(&str) -> f64) -> Option<f64> {
+        let debt: f64 = self
+            .debt
+            .iter()
+            .map(|asset| asset.value_usd * price_multiplier(&asset.asset))
+            .sum();
+        if debt <= 0.0 {
+            return None;
+        }
+        let weighted_collateral: f64 = self
+            .collateral
+            .iter()
+            .map(|asset| {
+                asset.value_usd * price_multiplier(&asset.asset) * asset.liquidation_threshold
+            })
+            .sum();
+        Some(weighted_collateral / debt)
+    }
+
+    pub fn current_health_factor(&self) -> Option<f64> {
+        self.health_factor(&|_| 1.0)
+    }
+
+    /// Solves, by bisection, for the move in `asset` alone that brings the
+    /// health factor to 1.0. `None` when no fall to zero or rise up to
+    /// [`MAX_PRICE_MULTIPLE`] would do it.
+    pub fn liquidation_trigger(&self, asset: &str) -> Option<LiquidationTrigger> {
+        let current = self.current_health_factor()?;
+        // Without debt left, e.g. when the only borrowed asset goes to zero,
+        // the account cannot be liquidated.
+        let health_at = |multiple: f64| {
+            self.health_factor(&|candidate| if candidate == asset { multiple } else { 1.0 })
+                .unwrap_or(f64::INFINITY)
+        };
+
+        let multiple = if current < 1.0 {
+            1.0
+        } else {
+            // The health factor is monotonic in one asset's price, so only
+            // one direction can reach 1.0.
+            let (mut safe, mut unsafe_) = if health_at(0.0) < 1.0 {
+                (1.0, 0.0)
+            } else if health_at(MAX_PRICE_MULTIPLE) < 1.0 {
+                (1.0, MAX_PRICE_MULTIPLE)
+            } else {
+                return None;
+            };
+            for _ in 0..BISECTION_STEPS {
+                let mid = (safe + unsafe_) / 2.0;
+                if health_at(mid) < 1.0 {
+                    unsafe_ = mid;
+                } else {
+                    safe = mid;
+                }
+            }
+            unsafe_
+        };
+
+        let holdings: Vec<&AccountAsset> = self
+            .collateral
+            .iter()
+            .chain(&self.debt)
+            .filter(|holding| holding.asset == asset)
+            .collect();
+        let current_price = holdings.iter().find_map(|holding| holding.price());
+
+        Some(LiquidationTrigger {
+            protocol: self.protocol.clone(),
+            asset: asset.to_string(),
+            position_ids: holdings
+                .iter()
+                .map(|holding| holding.position_id.clone())
+                .collect(),
+            move_percent: (multiple - 1.0) * 100.0,
+            current_price,
+            trigger_price: current_price.map(|price| price * multiple),
+        })
+    }
+
+    /// Triggers for every asset in the account, nearest first.
+    pub fn liquidation_triggers(&self) -> Vec<LiquidationTrigger> {
+        let mut assets: Vec<&str> = self
+            .collateral
+            .iter()
+            .chain(&self.debt)
+            .map(|holding| holding.asset.as_str())
+            .collect();
+        assets.sort_unstable();
+        assets.dedup();
+
+        let mut triggers: Vec<LiquidationTrigger> = assets
+            .into_iter()
+            .filter_map(|asset| self.liquidation_trigger(asset))
+            .collect();
+        triggers.sort_by(|a, b| a.move_percent.abs().total_cmp(&b.move_percent.abs()));
+        triggers
+    }
+}
+
+/// Replaces each lending and borrowing position's health factor with the
+/// one computed for its account.
+pub fn apply_health_factors(positions: &mut [DeFiPosition]) {
+    for account in LendingAccount::from_positions(positions) {
+        let health_factor = account.current_health_factor();
+        for position in positions.iter_mut() {
+            if account.contains(&position.id) {
+                position.health_factor = health_factor;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(
+        id: &str,
+        position_type: PositionType,
+        asset: &str,
+        amount: f64,
+        value_usd: f64,
+    ) -> DeFiPosition {
+        DeFiPosition {
+            id: id.to_string(),
+            protocol: Protocol::Solend,
+            position_type,
+            asset: asset.to_string(),
+            amount,
+            value_usd,
+            apy: 0.0,
+            rewards: vec![],
+            health_factor: None,
+            created_at: 0,
+            last_updated: 0,
+        }
+    }
+
+    #[test]
+    fn health_factor_weights_collateral_by_threshold() {
+        let positions = vec![
+            position("sol", PositionType::Lending, "SOL", 100.0, 10_000.0),
+            position("usdc", PositionType::Borrowing, "USDC", 4_000.0, 4_000.0),
+        ];
+        let accounts = LendingAccount::from_positions(&positions);
+
+        assert_eq!(accounts.len(), 1);
+        let health = accounts[0].current_health_factor().unwrap();
+        assert!((health - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn bisection_finds_the_collateral_drop() {
+        let positions = vec![
+            position("sol", PositionType::Lending, "SOL", 100.0, 10_000.0),
+            position("usdc", PositionType::Borrowing, "USDC", 4_000.0, 4_000.0),
+        ];
+        let account = &LendingAccount::from_positions(&positions)[0];
+
+        // 10_000 * m * 0.8 = 4_000 at m = 0.5.
+        let trigger = account.liquidation_trigger("SOL").unwrap();
+        assert!((trigger.move_percent + 50.0).abs() < 1e-6);
+        assert!((trigger.trigger_price.unwrap() - 50.0).abs() < 1e-6);
+
+        // Borrowed USDC would have to double.
+        let trigger = account.liquidation_trigger("USDC").unwrap();
+        assert!((trigger.move_percent - 100.0).abs() < 1e-6);
+
+        assert_eq!(account.liquidation_triggers()[0].asset, "SOL");
+    }
+
+    #[test]
+    fn apply_health_factors_covers_the_whole_account() {
+        let mut positions = vec![
+            position("sol", PositionType::Lending, "SOL", 100.0, 10_000.0),
+            position("usdc", PositionType::Borrowing, "USDC", 4_000.0, 4_000.0),
+            position("stake", PositionType::Staking, "mSOL", 1.0, 100.0),
+        ];
+        apply_health_factors(&mut positions);
+
+        assert_eq!(positions[0].health_factor, positions[1].health_factor);
+        assert!(positions[0].health_factor.is_some());
+        assert!(positions[2].health_factor.is_none());
+    }
+}
@@ -15,6 +15,8 @@ pub mod yield_farming;
 pub mod position_manager;
 pub mod governance;
 pub mod auto_compound;
+pub mod health;
+pub mod stress_test;
 
 pub use types::*;
 pub use yield_tracker::YieldTracker;
@@ -1,8 +1,10 @@
 use crate::defi::auto_compound::AutoCompoundEngine;
+use crate::defi::health::{apply_health_factors, LendingAccount};
 use crate::defi::kamino::KaminoAdapter;
 use crate::defi::marginfi::MarginfiAdapter;
 use crate::defi::solend::SolendAdapter;
 use crate::defi::staking::StakingAdapter;
+use crate::defi::stress_test::{self, StressScenario, StressTestReport};
 use crate::defi::types::*;
 use crate::defi::yield_farming::YieldFarmingAdapter;
 use serde::{Deserialize, Serialize};
@@ -41,7 +43,9 @@ impl PositionManager {
         }
     }
 
-    pub async fn build_portfolio_summary(&self, wallet: &str) -> Result<PortfolioSummary, String> {
+    /// All positions across protocols, with lending health factors
+    /// computed per account.
+    pub async fn fetch_positions(&self, wallet: &str) -> Result<Vec<DeFiPosition>, String> {
         let solend_positions = self.solend.get_user_positions(wallet).await?;
         let marginfi_positions = self.marginfi.get_positions(wallet).await?;
         let kamino_positions = self.kamino.get_user_positions(wallet).await?;
@@ -55,6 +59,13 @@ impl PositionManager {
         positions.extend(staking_positions);
         positions.extend(farming_positions);
 
+        apply_health_factors(&mut positions);
+        Ok(positions)
+    }
+
+    pub async fn build_portfolio_summary(&self, wallet: &str) -> Result<PortfolioSummary, String> {
+        let positions = self.fetch_positions(wallet).await?;
+
         let total_value_usd: f64 = positions.iter().map(|p| p.value_usd).sum();
         let lending_value = positions
             .iter()
@@ -105,6 +116,7 @@ impl PositionManager {
 
     pub async fn calculate_risk_metrics(&self, wallet: &str) -> Result<Vec<RiskMetrics>, String> {
         let summary = self.build_portfolio_summary(wallet).await?;
+        let accounts = LendingAccount::from_positions(&summary.positions);
         let mut metrics = Vec::new();
 
         for position in summary.positions.iter() {
@@ -143,10 +155,16 @@ impl PositionManager {
                 warnings.push("Yield may be unsustainable".to_string());
             }
 
+            let liquidation_price = accounts
+                .iter()
+                .find(|account| account.contains(&position.id))
+                .and_then(|account| account.liquidation_trigger(&position.asset))
+                .and_then(|trigger| trigger.trigger_price);
+
             metrics.push(RiskMetrics {
                 position_id: position.id.clone(),
                 risk_level,
-                liquidation_price: None,
+                liquidation_price,
                 health_factor: position.health_factor,
                 collateral_ratio: position.health_factor.map(|hf| hf * 0.5),
                 warnings,
@@ -175,6 +193,15 @@ impl PositionManager {
         let recommendations = auto_compound.analyze_positions(&summary.positions).await;
         Ok(recommendations)
     }
+
+    pub async fn stress_test(
+        &self,
+        wallet: &str,
+        scenarios: &[StressScenario],
+    ) -> Result<StressTestReport, String> {
+        let positions = self.fetch_positions(wallet).await?;
+        Ok(stress_test::stress_test(wallet, &positions, scenarios))
+    }
 }
 
 #[tauri::command]
@@ -202,3 +229,22 @@ pub async fn get_auto_compound_recommendations(
         .recommend_auto_compound(&wallet)
         .await
 }
+
+#[tauri::command]
+pub async fn stress_test_positions(
+    wallet: String,
+    scenarios: Option<Vec<StressScenario>>,
+) -> Result<StressTestReport, String> {
+    let scenarios = scenarios.unwrap_or_else(stress_test::preset_scenarios);
+    if scenarios.is_empty() {
+        return Err("At least one stress scenario is required".to_string());
+    }
+    PositionManager::new()
+        .stress_test(&wallet, &scenarios)
+        .await
+}
+
+#[tauri::command]
+pub async fn get_stress_test_presets() -> Result<Vec<StressScenario>, String> {
+    Ok(stress_test::preset_scenarios())
+}
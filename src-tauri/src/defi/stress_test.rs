@@ -0,0 +1,449 @@
+// Price-shock stress tests for DeFi positions. Health factors come from
+// `health::LendingAccount`, the same code that feeds live risk metrics.
+
+use crate::defi::health::{LendingAccount, LiquidationTrigger};
+use crate::defi::types::*;
+use crate::portfolio::analytics::classify_sector;
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+/// Scenario results kept before the cache is cleared.
+const MAX_CACHED_RESULTS: usize = 256;
+
+lazy_static! {
+    static ref RESULT_CACHE: Mutex<HashMap<String, ScenarioResult>> = Mutex::new(HashMap::new());
+}
+
+/// What a price shock applies to. The most specific matching target wins:
+/// a named asset, then its class, then `all`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShockTarget {
+    Asset(String),
+    Stablecoins,
+    /// Everything except stablecoins and SOL, ETH and BTC.
+    Alts,
+    All,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceShock {
+    pub target: ShockTarget,
+    /// e.g. -30.0 for a 30% fall.
+    pub change_percent: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StressScenario {
+    pub id: String,
+    pub name: String,
+    pub shocks: Vec<PriceShock>,
+}
+
+impl StressScenario {
+    fn shock_for(&self, asset: &str) -> f64 {
+        let class = match classify_sector(asset).as_str() {
+            "Stablecoin" => Some(ShockTarget::Stablecoins),
+            "Layer 1" => None,
+            _ => Some(ShockTarget::Alts),
+        };
+        let find = |target: &ShockTarget| {
+            self.shocks
+                .iter()
+                .find(|shock| &shock.target == target)
+                .map(|shock| shock.change_percent)
+        };
+
+        find(&ShockTarget::Asset(asset.to_string()))
+            .or_else(|| class.as_ref().and_then(find))
+            .or_else(|| find(&ShockTarget::All))
+            .unwrap_or(0.0)
+    }
+
+    /// Price multiplier for `asset`. Pool tokens named `A-B` move by the
+    /// average of their two legs.
+    pub fn multiplier(&self, asset: &str) -> f64 {
+        let legs: Vec<&str> = asset.split('-').filter(|leg| !leg.is_empty()).collect();
+        let change = if legs.len() > 1 {
+            legs.iter().map(|leg| self.shock_for(leg)).sum::<f64>() / legs.len() as f64
+        } else {
+            self.shock_for(asset)
+        };
+        (1.0 + change / 100.0).max(0.0)
+    }
+}
+
+fn scenario(id: &str, name: &str, shocks: Vec<(ShockTarget, f64)>) -> StressScenario {
+    StressScenario {
+        id: id.to_string(),
+        name: name.to_string(),
+        shocks: shocks
+            .into_iter()
+            .map(|(target, change_percent)| PriceShock {
+                target,
+                change_percent,
+            })
+            .collect(),
+    }
+}
+
+pub fn preset_scenarios() -> Vec<StressScenario> {
+    vec![
+        scenario(
+            "sol_crash",
+            "SOL -30%",
+            vec![(ShockTarget::Asset("SOL".to_string()), -30.0)],
+        ),
+        scenario(
+            "alt_collapse",
+            "Alts -50%",
+            vec![(ShockTarget::Alts, -50.0)],
+        ),
+        scenario(
+            "stable_depeg",
+            "Stablecoins depeg 5%",
+            vec![(ShockTarget::Stablecoins, -5.0)],
+        ),
+        scenario(
+            "market_crash",
+            "Market crash: SOL -40%, alts -60%",
+            vec![
+                (ShockTarget::Asset("SOL".to_string()), -40.0),
+                (ShockTarget::Alts, -60.0),
+            ],
+        ),
+    ]
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountStress {
+    pub protocol: Protocol,
+    pub health_before: Option<f64>,
+    pub health_after: Option<f64>,
+    pub liquidated: bool,
+    pub collateral_after_usd: f64,
+    pub debt_after_usd: f64,
+    /// Collateral lost to liquidators if the whole debt is repaid by
+    /// liquidation at the collateral's penalty rates.
+    pub estimated_penalty_usd: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScenarioResult {
+    pub scenario: StressScenario,
+    pub accounts: Vec<AccountStress>,
+    pub liquidated_position_ids: Vec<String>,
+    pub total_penalty_usd: f64,
+    /// Assets minus debt across all positions.
+    pub net_value_before_usd: f64,
+    /// Net value under the shock, less liquidation penalties.
+    pub net_value_after_usd: f64,
+    pub value_change_usd: f64,
+    pub value_change_percent: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StressTestReport {
+    pub wallet: String,
+    /// Identifies the positions the results were computed from.
+    pub snapshot_id: String,
+    pub results: Vec<ScenarioResult>,
+    /// Per account and asset, the single price move that liquidates.
+    pub liquidation_triggers: Vec<LiquidationTrigger>,
+    /// The smallest of `liquidation_triggers`.
+    pub first_liquidation: Option<LiquidationTrigger>,
+}
+
+fn net_value(positions: &[DeFiPosition], multiplier: &dyn Fn(&str) -> f64) -> f64 {
+    positions
+        .iter()
+        .map(|position| {
+            let value = position.value_usd * multiplier(&position.asset);
+            if position.position_type == PositionType::Borrowing {
+                -value
+            } else {
+                value
+            }
+        })
+        .sum()
+}
+
+/// Hash of the fields stress results depend on, so fetches of unchanged
+/// positions share cached results.
+pub fn snapshot_id(positions: &[DeFiPosition]) -> String {
+    let mut hasher = Sha256::new();
+    for position in positions {
+        hasher.update(
+            format!(
+                "{}|{:?}|{:?}|{}|{}|{}\n",
+                position.id,
+                position.protocol,
+                position.position_type,
+                position.asset,
+                position.amount,
+                position.value_usd
+            )
+            .as_bytes(),
+        );
+    }
+    hex::encode(hasher.finalize())
+}
+
+pub fn run_scenario(positions: &[DeFiPosition], scenario: &StressScenario) -> ScenarioResult {
+    let multiplier = |asset: &str| scenario.multiplier(asset);
+    let mut accounts = Vec::new();
+    let mut liquidated_position_ids = Vec::new();
+
+    for account in LendingAccount::from_positions(positions) {
+        let health_before = account.current_health_factor();
+        let health_after = account.health_factor(&multiplier);
+        let liquidated = health_after.is_some_and(|health| health < 1.0);
+
+        let collateral_after_usd: f64 = account
+            .collateral
+            .iter()
+            .map(|asset| asset.value_usd * multiplier(&asset.asset))
+            .sum();
+        let debt_after_usd: f64 = account
+            .debt
+            .iter()
+            .map(|asset| asset.value_usd * multiplier(&asset.asset))
+            .sum();
+
+        let estimated_penalty_usd = if liquidated && collateral_after_usd > 0.0 {
+            let penalty_rate = account
+                .collateral
+                .iter()
+                .map(|asset| asset.value_usd * multiplier(&asset.asset) * asset.liquidation_penalty)
+                .sum::<f64>()
+                / collateral_after_usd;
+            (debt_after_usd * penalty_rate).min(collateral_after_usd)
+        } else {
+            0.0
+        };
+
+        if liquidated {
+            liquidated_position_ids.extend(
+                account
+                    .collateral
+                    .iter()
+                    .chain(&account.debt)
+                    .map(|asset| asset.position_id.clone()),
+            );
+        }
+
+        accounts.push(AccountStress {
+            protocol: account.protocol.clone(),
+            health_before,
+            health_after,
+            liquidated,
+            collateral_after_usd,
+            debt_after_usd,
+            estimated_penalty_usd,
+        });
+    }
+
+    let total_penalty_usd: f64 = accounts.iter().map(|a| a.estimated_penalty_usd).sum();
+    let net_value_before_usd = net_value(positions, &|_| 1.0);
+    let net_value_after_usd = net_value(positions, &multiplier) - total_penalty_usd;
+    let value_change_usd = net_value_after_usd - net_value_before_usd;
+
+    ScenarioResult {
+        scenario: scenario.clone(),
+        accounts,
+        liquidated_position_ids,
+        total_penalty_usd,
+        net_value_before_usd,
+        net_value_after_usd,
+        value_change_usd,
+        value_change_percent: if net_value_before_usd.abs() > f64::EPSILON {
+            value_change_usd / net_value_before_usd.abs() * 100.0
+        } else {
+            0.0
+        },
+    }
+}
+
+/// Runs `scenarios` against `positions`, reusing results cached for the
+/// same snapshot and scenario.
+pub fn stress_test(
+    wallet: &str,
+    positions: &[DeFiPosition],
+    scenarios: &[StressScenario],
+) -> StressTestReport {
+    let snapshot_id = snapshot_id(positions);
+
+    let results = scenarios
+        .iter()
+        .map(|scenario| {
+            let key = format!(
+                "{}:{}",
+                snapshot_id,
+                serde_json::to_string(scenario).unwrap_or_default()
+            );
+            if let Some(cached) = RESULT_CACHE.lock().get(&key) {
+                return cached.clone();
+            }
+            let result = run_scenario(positions, scenario);
+            let mut cache = RESULT_CACHE.lock();
+            if cache.len() >= MAX_CACHED_RESULTS {
+                cache.clear();
+            }
+            cache.insert(key, result.clone());
+            result
+        })
+        .collect();
+
+    let mut liquidation_triggers: Vec<LiquidationTrigger> =
+        LendingAccount::from_positions(positions)
+            .iter()
+            .flat_map(|account| account.liquidation_triggers())
+            .collect();
+    liquidation_triggers.sort_by(|a, b| a.move_percent.abs().total_cmp(&b.move_percent.abs()));
+
+    StressTestReport {
+        wallet: wallet.to_string(),
+        snapshot_id,
+        results,
+        first_liquidation: liquidation_triggers.first().cloned(),
+        liquidation_triggers,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(
+        id: &str,
+        protocol: Protocol,
+        position_type: PositionType,
+        asset: &str,
+        value_usd: f64,
+    ) -> DeFiPosition {
+        DeFiPosition {
+            id: id.to_string(),
+            protocol,
+            position_type,
+            asset: asset.to_string(),
+            amount: value_usd / 100.0,
+            value_usd,
+            apy: 0.0,
+            rewards: vec![],
+            health_factor: None,
+            created_at: 0,
+            last_updated: 0,
+        }
+    }
+
+    fn positions() -> Vec<DeFiPosition> {
+        vec![
+            // HF = 10_000 * 0.8 / 6_000 = 1.33
+            position(
+                "s-sol",
+                Protocol::Solend,
+                PositionType::Lending,
+                "SOL",
+                10_000.0,
+            ),
+            position(
+                "s-usdc",
+                Protocol::Solend,
+                PositionType::Borrowing,
+                "USDC",
+                6_000.0,
+            ),
+            // HF = 10_000 * 0.85 / 2_000 = 4.25
+            position(
+                "m-usdc",
+                Protocol::MarginFi,
+                PositionType::Lending,
+                "USDC",
+                10_000.0,
+            ),
+            position(
+                "m-bonk",
+                Protocol::MarginFi,
+                PositionType::Borrowing,
+                "BONK",
+                2_000.0,
+            ),
+            position(
+                "lp",
+                Protocol::Kamino,
+                PositionType::LiquidityPool,
+                "SOL-USDC",
+                1_000.0,
+            ),
+        ]
+    }
+
+    #[test]
+    fn most_specific_shock_applies() {
+        let scenario = scenario(
+            "mixed",
+            "Mixed",
+            vec![
+                (ShockTarget::All, -10.0),
+                (ShockTarget::Alts, -50.0),
+                (ShockTarget::Asset("SOL".to_string()), -30.0),
+            ],
+        );
+
+        assert!((scenario.multiplier("SOL") - 0.7).abs() < 1e-9);
+        assert!((scenario.multiplier("BONK") - 0.5).abs() < 1e-9);
+        assert!((scenario.multiplier("USDC") - 0.9).abs() < 1e-9);
+        assert!((scenario.multiplier("SOL-USDC") - 0.8).abs() < 1e-9);
+    }
+
+    #[test]
+    fn sol_crash_liquidates_only_the_sol_backed_account() {
+        let sol_crash = &preset_scenarios()[0];
+        let result = run_scenario(&positions(), sol_crash);
+
+        let solend = &result.accounts[0];
+        assert!(solend.liquidated);
+        // 7_000 * 0.8 / 6_000
+        assert!((solend.health_after.unwrap() - 0.9333333).abs() < 1e-6);
+        assert!((solend.estimated_penalty_usd - 300.0).abs() < 1e-6);
+        assert!(!result.accounts[1].liquidated);
+        assert_eq!(result.liquidated_position_ids, vec!["s-sol", "s-usdc"]);
+
+        // Net 13_000 before; SOL -3_000, LP -150, penalty -300.
+        assert!((result.net_value_before_usd - 13_000.0).abs() < 1e-6);
+        assert!((result.value_change_usd + 3_450.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn unshocked_health_matches_live_health() {
+        let mut live = positions();
+        crate::defi::health::apply_health_factors(&mut live);
+        let result = run_scenario(&positions(), &scenario("none", "None", vec![]));
+
+        assert_eq!(result.accounts[0].health_after, live[0].health_factor);
+        assert_eq!(result.accounts[1].health_after, live[2].health_factor);
+        assert_eq!(result.value_change_usd, 0.0);
+    }
+
+    #[test]
+    fn first_liquidation_is_the_smallest_move() {
+        let report = stress_test("wallet", &positions(), &preset_scenarios());
+
+        let first = report.first_liquidation.unwrap();
+        assert_eq!(first.asset, "SOL");
+        // 10_000 * m * 0.8 = 6_000 at m = 0.75.
+        assert!((first.move_percent + 25.0).abs() < 1e-6);
+        assert_eq!(report.results.len(), 4);
+
+        let again = stress_test("wallet", &positions(), &preset_scenarios());
+        assert_eq!(again.snapshot_id, report.snapshot_id);
+    }
+}
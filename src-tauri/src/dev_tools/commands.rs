@@ -1,18 +1,45 @@
-use crate::compiler::{AutoCompiler, BuildStatus, CompilationResult};
+use crate::compiler::{AutoCompiler, BuildStatus, CompilationResult, WatchConfig, WatchStatus};
 use crate::errors::{CrashReport, SharedCrashReporter, SharedRuntimeHandler};
 use crate::fixer::{AutoFixer, FixAttempt, FixStats};
-use crate::logger::{ComprehensiveLogger, LogEntry, LogLevel, LoggerConfig, SharedLogger};
+use crate::logger::{
+    Breadcrumb, ComprehensiveLogger, LogEntry, LogLevel, LogStreamFilter, LogStreamInfo,
+    LoggerConfig, SharedLogger,
+};
 use crate::monitor::{PerformanceMetrics, SharedPerformanceMonitor};
 use crate::recovery::{ErrorRecoveryManager, RecoveryPlan};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
-use tauri::State;
+use tauri::{AppHandle, State};
 
 #[tauri::command]
 pub async fn compile_now(
+    app_handle: AppHandle,
     compiler: State<'_, Arc<AutoCompiler>>,
 ) -> Result<CompilationResult, String> {
-    compiler.compile_now()
+    compiler.compile_now(Some(&app_handle)).await
+}
+
+#[tauri::command]
+pub async fn start_compiler_watch(
+    app_handle: AppHandle,
+    compiler: State<'_, Arc<AutoCompiler>>,
+    config: Option<WatchConfig>,
+) -> Result<WatchStatus, String> {
+    Ok(compiler.start_watch(app_handle, config.unwrap_or_default()))
+}
+
+#[tauri::command]
+pub async fn stop_compiler_watch(
+    compiler: State<'_, Arc<AutoCompiler>>,
+) -> Result<WatchStatus, String> {
+    Ok(compiler.stop_watch())
+}
+
+#[tauri::command]
+pub async fn get_compiler_watch_status(
+    compiler: State<'_, Arc<AutoCompiler>>,
+) -> Result<WatchStatus, String> {
+    Ok(compiler.watch_status())
 }
 
 #[tauri::command]
@@ -62,6 +89,14 @@ pub async fn clear_fix_history(fixer: State<'_, Arc<AutoFixer>>) -> Result<(), S
     Ok(())
 }
 
+#[tauri::command]
+pub async fn revert_auto_fix(
+    fixer: State<'_, Arc<AutoFixer>>,
+    attempt_id: String,
+) -> Result<FixAttempt, String> {
+    fixer.revert_fix(&attempt_id)
+}
+
 #[tauri::command]
 pub async fn get_logs(
     logger: State<'_, SharedLogger>,
@@ -156,6 +191,40 @@ pub async fn list_crash_reports(
     Ok(crash_reporter.list_reports())
 }
 
+#[tauri::command]
+pub async fn get_breadcrumbs(
+    logger: State<'_, SharedLogger>,
+    subsystem: Option<String>,
+) -> Result<Vec<Breadcrumb>, String> {
+    Ok(logger.get_breadcrumbs(subsystem.as_deref()))
+}
+
+#[tauri::command]
+pub async fn start_log_stream(
+    app_handle: AppHandle,
+    logger: State<'_, SharedLogger>,
+    filter: Option<LogStreamFilter>,
+) -> Result<String, String> {
+    Ok(logger.start_log_stream(app_handle, filter.unwrap_or_default()))
+}
+
+#[tauri::command]
+pub async fn stop_log_stream(
+    logger: State<'_, SharedLogger>,
+    stream_id: String,
+) -> Result<LogStreamInfo, String> {
+    logger
+        .stop_log_stream(&stream_id)
+        .ok_or_else(|| format!("Unknown log stream: {}", stream_id))
+}
+
+#[tauri::command]
+pub async fn list_log_streams(
+    logger: State<'_, SharedLogger>,
+) -> Result<Vec<LogStreamInfo>, String> {
+    Ok(logger.list_log_streams())
+}
+
 #[tauri::command]
 pub async fn force_gc() -> Result<(), String> {
     Ok(())
@@ -109,6 +109,7 @@ impl DiagnosticsEngine {
             summary,
             panels,
             issues: all_issues,
+            dependencies: self.network_diagnostics.last_dependencies().to_vec(),
             repair_history: self.repair_history.clone(),
             notes: all_notes,
         };
@@ -201,6 +202,22 @@ impl DiagnosticsEngine {
         results
     }
 
+    /// Points the network suite at the currently configured providers and RPC endpoints.
+    pub fn configure_network_targets(
+        &mut self,
+        api_targets: Vec<NetworkTarget>,
+        active_rpc: String,
+        rpc_fallbacks: Vec<String>,
+    ) {
+        self.network_diagnostics.set_api_targets(api_targets);
+        self.network_diagnostics
+            .set_rpc_endpoints(active_rpc, rpc_fallbacks);
+    }
+
+    pub fn active_rpc_endpoint(&self) -> String {
+        self.network_diagnostics.active_rpc_endpoint().to_string()
+    }
+
     pub fn verify_integrity(&self) -> Result<bool, String> {
         // Verify app data directory structure
         if !self.app_data_dir.exists() {
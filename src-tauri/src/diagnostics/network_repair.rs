@@ -1,33 +1,125 @@
 use super::types::*;
+use crate::config::provider_url;
 use chrono::Utc;
+use futures_util::future::join_all;
 use reqwest::Client;
-use serde_json::json;
 use std::collections::HashMap;
-use std::time::Duration;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::net::{lookup_host, TcpStream, UdpSocket};
 use tokio::time::timeout;
+use tokio_tungstenite::connect_async;
 use uuid::Uuid;
 
+// Every probe is bounded so a full scan never blocks for more than a few seconds.
+const DNS_TIMEOUT: Duration = Duration::from_secs(2);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(4);
+const NTP_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Fetched to decide whether the machine is online at all.
+const CONNECTIVITY_PROBE_URL: &str = "https://1.1.1.1";
+
+const NTP_SERVER: &str = "pool.ntp.org:123";
+/// Seconds between the NTP epoch (1900-01-01) and the Unix epoch.
+const NTP_UNIX_OFFSET_SECS: u64 = 2_208_988_800;
+
+const LATENCY_WARNING_MS: u64 = 1_500;
+const CLOCK_SKEW_WARNING_MS: i64 = 2_000;
+// TOTP codes use a 30 second step, so anything past half of that breaks 2FA.
+const CLOCK_SKEW_CRITICAL_MS: i64 = 15_000;
+
+pub const DEFAULT_RPC_ENDPOINT: &str = "https://api.mainnet-beta.solana.com";
+const DEFAULT_RPC_FALLBACKS: &[&str] = &["https://solana-api.projectserum.com"];
+
+const BIRDEYE_WS_URL: &str = "wss://public-api.birdeye.so/socket";
+const HELIUS_WS_URL: &str = "wss://mainnet.helius-rpc.com";
+
 pub struct NetworkDiagnostics {
     client: Client,
+    api_targets: Vec<NetworkTarget>,
+    stream_targets: Vec<NetworkTarget>,
+    active_rpc: String,
+    rpc_fallbacks: Vec<String>,
+    last_dependencies: Vec<DependencyStatus>,
 }
 
 impl NetworkDiagnostics {
     pub fn new() -> Self {
         let client = Client::builder()
-            .timeout(Duration::from_secs(5))
+            .timeout(REQUEST_TIMEOUT)
+            .connect_timeout(CONNECT_TIMEOUT)
             .build()
             .unwrap_or_else(|_| Client::new());
 
-        Self { client }
+        Self {
+            client,
+            api_targets: vec![
+                NetworkTarget {
+                    name: "Coingecko".to_string(),
+                    kind: DependencyKind::ApiProvider,
+                    url: "https://api.coingecko.com/api/v3/ping".to_string(),
+                },
+                NetworkTarget {
+                    name: "Defillama".to_string(),
+                    kind: DependencyKind::ApiProvider,
+                    url: "https://api.llama.fi/protocols".to_string(),
+                },
+            ],
+            stream_targets: vec![
+                NetworkTarget {
+                    name: "Birdeye stream".to_string(),
+                    kind: DependencyKind::WebSocketStream,
+                    url: BIRDEYE_WS_URL.to_string(),
+                },
+                NetworkTarget {
+                    name: "Helius stream".to_string(),
+                    kind: DependencyKind::WebSocketStream,
+                    url: provider_url(HELIUS_WS_URL),
+                },
+            ],
+            active_rpc: DEFAULT_RPC_ENDPOINT.to_string(),
+            rpc_fallbacks: DEFAULT_RPC_FALLBACKS
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+            last_dependencies: Vec::new(),
+        }
+    }
+
+    /// Replaces the probed API providers, usually with the services configured in
+    /// `ApiConfigManager`.
+    pub fn set_api_targets(&mut self, targets: Vec<NetworkTarget>) {
+        if !targets.is_empty() {
+            self.api_targets = targets;
+        }
+    }
+
+    pub fn set_rpc_endpoints(&mut self, active: String, fallbacks: Vec<String>) {
+        if !active.trim().is_empty() {
+            self.active_rpc = active;
+        }
+        self.rpc_fallbacks = fallbacks
+            .into_iter()
+            .filter(|url| !url.trim().is_empty() && *url != self.active_rpc)
+            .collect();
     }
 
-    pub async fn diagnose(&self) -> ModuleDiagnostics {
+    pub fn active_rpc_endpoint(&self) -> &str {
+        &self.active_rpc
+    }
+
+    pub fn last_dependencies(&self) -> &[DependencyStatus] {
+        &self.last_dependencies
+    }
+
+    pub async fn diagnose(&mut self) -> ModuleDiagnostics {
         let mut issues = Vec::new();
         let mut metrics = Vec::new();
         let mut notes = Vec::new();
 
         // Test internet connectivity
-        let internet_ok = self.test_connectivity("https://1.1.1.1").await;
+        let internet_ok = internet_reachable(&self.client).await;
         metrics.push(PanelMetric {
             label: "Internet".to_string(),
             value: if internet_ok {
@@ -49,55 +141,95 @@ impl NetworkDiagnostics {
                 "Unable to reach public DNS resolver",
                 IssueSeverity::Critical,
                 "Check network connection or firewall settings",
+                None,
             ));
         } else {
             notes.push("Internet connectivity verified".to_string());
         }
 
-        // Test API endpoints
-        let endpoints = vec![
-            ("Coingecko", "https://api.coingecko.com/api/v3/ping"),
-            ("Defillama", "https://api.llama.fi/protocols"),
-        ];
-
-        let mut api_failures = Vec::new();
-
-        for (name, url) in &endpoints {
-            if !self.test_connectivity(url).await {
-                api_failures.push((*name, *url));
-            }
-        }
+        // Probe every dependency concurrently; each probe carries its own timeouts.
+        let rpc_target = NetworkTarget {
+            name: "Solana RPC".to_string(),
+            kind: DependencyKind::RpcEndpoint,
+            url: self.active_rpc.clone(),
+        };
+        let api_checks = join_all(self.api_targets.iter().map(|t| self.check_http(t)));
+        let stream_checks = join_all(self.stream_targets.iter().map(|t| self.check_websocket(t)));
+        let (api_results, rpc_result, stream_results, clock_result) = tokio::join!(
+            api_checks,
+            self.check_rpc(&rpc_target),
+            stream_checks,
+            self.check_clock_skew()
+        );
 
+        let api_failures = api_results.iter().filter(|d| is_failing(&d.level)).count();
         metrics.push(PanelMetric {
             label: "API Endpoints".to_string(),
             value: format!(
                 "{}/{} reachable",
-                endpoints.len() - api_failures.len(),
-                endpoints.len()
+                api_results.len() - api_failures,
+                api_results.len()
             ),
-            level: Some(if api_failures.is_empty() {
+            level: Some(if api_failures == 0 {
                 HealthLevel::Excellent
             } else {
                 HealthLevel::Degraded
             }),
         });
 
-        for (name, url) in api_failures {
-            issues.push(self.create_issue(
-                &format!("{} API unreachable", name),
-                &format!("Failed to reach endpoint: {}", url),
-                IssueSeverity::Warning,
-                "Switch to backup endpoint or retry later",
-            ));
-        }
-
-        // Test latency to RPC endpoints (mocked)
         metrics.push(PanelMetric {
             label: "RPC Latency".to_string(),
-            value: "Stable".to_string(),
-            level: Some(HealthLevel::Good),
+            value: rpc_result
+                .latency_ms
+                .map(|ms| format!("{} ms", ms))
+                .unwrap_or_else(|| "Unavailable".to_string()),
+            level: Some(rpc_result.level.clone()),
+        });
+
+        let stream_failures = stream_results
+            .iter()
+            .filter(|d| is_failing(&d.level))
+            .count();
+        metrics.push(PanelMetric {
+            label: "Streams".to_string(),
+            value: format!(
+                "{}/{} connected",
+                stream_results.len() - stream_failures,
+                stream_results.len()
+            ),
+            level: Some(if stream_failures == 0 {
+                HealthLevel::Excellent
+            } else {
+                HealthLevel::Warning
+            }),
+        });
+
+        metrics.push(PanelMetric {
+            label: "Clock Skew".to_string(),
+            value: clock_result
+                .clock_skew_ms
+                .map(|ms| format!("{} ms", ms))
+                .unwrap_or_else(|| "Unknown".to_string()),
+            level: Some(clock_result.level.clone()),
         });
-        notes.push("RPC latency check placeholder".to_string());
+
+        let mut dependencies = api_results;
+        dependencies.push(rpc_result);
+        dependencies.extend(stream_results);
+        dependencies.push(clock_result);
+
+        for dependency in &dependencies {
+            if let Some(issue) = self.issue_for_dependency(dependency) {
+                issues.push(issue);
+            }
+        }
+
+        notes.push(format!(
+            "Checked {} upstream dependencies (RPC: {})",
+            dependencies.len(),
+            self.active_rpc
+        ));
+        self.last_dependencies = dependencies;
 
         let health = if internet_ok {
             if issues
@@ -124,7 +256,10 @@ impl NetworkDiagnostics {
                     format!("{} network issue(s) detected", issues.len())
                 },
                 metrics,
-                actions: vec!["Test alternative endpoints".to_string()],
+                actions: vec![
+                    "Test alternative endpoints".to_string(),
+                    "Switch to fallback RPC".to_string(),
+                ],
             },
             issues,
             auto_fixed: 0,
@@ -132,12 +267,287 @@ impl NetworkDiagnostics {
         }
     }
 
-    async fn test_connectivity(&self, url: &str) -> bool {
-        let request = self.client.get(url);
-        match timeout(Duration::from_secs(5), request.send()).await {
-            Ok(Ok(response)) => response.status().is_success(),
-            _ => false,
+    /// DNS and TCP connect timings for an `http(s)` or `ws(s)` URL. TLS is not
+    /// probed here; it is part of the timed request that follows.
+    async fn probe_transport(&self, target: &NetworkTarget, status: &mut DependencyStatus) -> bool {
+        let parsed = match url::Url::parse(&target.url) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                status.error = Some(format!("Invalid URL: {}", e));
+                status.remediation = Some("Correct the endpoint URL in settings".to_string());
+                return false;
+            }
+        };
+        let host = match parsed.host_str() {
+            Some(host) => host.to_string(),
+            None => {
+                status.error = Some("URL has no host".to_string());
+                status.remediation = Some("Correct the endpoint URL in settings".to_string());
+                return false;
+            }
+        };
+        let port = parsed.port_or_known_default().unwrap_or(443);
+
+        let dns_start = Instant::now();
+        let addr: SocketAddr = match timeout(DNS_TIMEOUT, lookup_host((host.as_str(), port))).await
+        {
+            Ok(Ok(mut addrs)) => match addrs.next() {
+                Some(addr) => addr,
+                None => {
+                    status.error = Some(format!("DNS returned no addresses for {}", host));
+                    status.remediation = Some(dns_remediation());
+                    return false;
+                }
+            },
+            Ok(Err(e)) => {
+                status.error = Some(format!("DNS resolution failed: {}", e));
+                status.remediation = Some(dns_remediation());
+                return false;
+            }
+            Err(_) => {
+                status.error = Some("DNS resolution timed out".to_string());
+                status.remediation = Some(dns_remediation());
+                return false;
+            }
+        };
+        status.dns_ms = Some(elapsed_ms(dns_start));
+
+        let connect_start = Instant::now();
+        match timeout(CONNECT_TIMEOUT, TcpStream::connect(addr)).await {
+            Ok(Ok(_)) => status.connect_ms = Some(elapsed_ms(connect_start)),
+            Ok(Err(e)) => {
+                status.error = Some(format!("TCP connect to {} failed: {}", addr, e));
+                status.remediation =
+                    Some("Check firewall or proxy rules for outbound connections".to_string());
+                return false;
+            }
+            Err(_) => {
+                status.error = Some(format!("TCP connect to {} timed out", addr));
+                status.remediation =
+                    Some("Check firewall or proxy rules for outbound connections".to_string());
+                return false;
+            }
+        }
+
+        true
+    }
+
+    async fn check_http(&self, target: &NetworkTarget) -> DependencyStatus {
+        let mut status = new_status(target);
+        if !self.probe_transport(target, &mut status).await {
+            status.level = HealthLevel::Critical;
+            return status;
+        }
+
+        // Any HTTP response proves DNS, TCP and TLS all work; an auth error from a
+        // keyless probe still means the provider is reachable.
+        let start = Instant::now();
+        match timeout(REQUEST_TIMEOUT, self.client.head(&target.url).send()).await {
+            Ok(Ok(response)) => {
+                let latency = elapsed_ms(start);
+                status.request_ms = Some(latency);
+                status.latency_ms = Some(latency);
+                if response.status().is_server_error() {
+                    status.level = HealthLevel::Degraded;
+                    status.error = Some(format!("HTTP {}", response.status().as_u16()));
+                    status.remediation =
+                        Some(format!("{} is returning errors; retry later", target.name));
+                } else {
+                    status.level = latency_level(latency);
+                    if latency > LATENCY_WARNING_MS {
+                        status.remediation =
+                            Some("High latency; consider a closer endpoint".to_string());
+                    }
+                }
+            }
+            Ok(Err(e)) => {
+                status.level = HealthLevel::Critical;
+                status.error = Some(format!("HTTP request failed: {}", e));
+                status.remediation = Some(if e.is_connect() {
+                    "Connection failed after TCP; check certificates or an intercepting proxy"
+                        .to_string()
+                } else {
+                    format!("{} is unreachable; retry later", target.name)
+                });
+            }
+            Err(_) => {
+                status.level = HealthLevel::Critical;
+                status.error = Some("Request timed out".to_string());
+                status.remediation =
+                    Some(format!("{} is not responding; retry later", target.name));
+            }
+        }
+
+        status
+    }
+
+    async fn check_rpc(&self, target: &NetworkTarget) -> DependencyStatus {
+        let mut status = new_status(target);
+        if !self.probe_transport(target, &mut status).await {
+            status.level = HealthLevel::Critical;
+            status.remediation = Some(rpc_remediation());
+            return status;
+        }
+
+        let body = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "getHealth"
+        });
+        let start = Instant::now();
+        match timeout(
+            REQUEST_TIMEOUT,
+            self.client.post(&target.url).json(&body).send(),
+        )
+        .await
+        {
+            Ok(Ok(response)) if response.status().is_success() => {
+                let latency = elapsed_ms(start);
+                status.request_ms = Some(latency);
+                status.latency_ms = Some(latency);
+                status.level = latency_level(latency);
+                if latency > LATENCY_WARNING_MS {
+                    status.remediation = Some(rpc_remediation());
+                }
+            }
+            Ok(Ok(response)) => {
+                status.latency_ms = Some(elapsed_ms(start));
+                status.level = HealthLevel::Critical;
+                status.error = Some(format!("HTTP {}", response.status().as_u16()));
+                status.remediation = Some(rpc_remediation());
+            }
+            Ok(Err(e)) => {
+                status.level = HealthLevel::Critical;
+                status.error = Some(format!("RPC request failed: {}", e));
+                status.remediation = Some(rpc_remediation());
+            }
+            Err(_) => {
+                status.level = HealthLevel::Critical;
+                status.error = Some("RPC request timed out".to_string());
+                status.remediation = Some(rpc_remediation());
+            }
+        }
+
+        status
+    }
+
+    async fn check_websocket(&self, target: &NetworkTarget) -> DependencyStatus {
+        let mut status = new_status(target);
+        if !self.probe_transport(target, &mut status).await {
+            status.level = HealthLevel::Degraded;
+            return status;
         }
+
+        let start = Instant::now();
+        match timeout(REQUEST_TIMEOUT, connect_async(target.url.as_str())).await {
+            Ok(Ok((mut stream, _))) => {
+                status.latency_ms = Some(elapsed_ms(start));
+                status.level = HealthLevel::Excellent;
+                let _ = stream.close(None).await;
+            }
+            Ok(Err(e)) => {
+                status.level = HealthLevel::Degraded;
+                status.error = Some(format!("WebSocket handshake failed: {}", e));
+                status.remediation = Some(
+                    "Live prices will fall back to polling; check the stream API key".to_string(),
+                );
+            }
+            Err(_) => {
+                status.level = HealthLevel::Degraded;
+                status.error = Some("WebSocket handshake timed out".to_string());
+                status.remediation = Some(
+                    "Live prices will fall back to polling until the stream recovers".to_string(),
+                );
+            }
+        }
+
+        status
+    }
+
+    /// Single SNTP round trip; the offset follows RFC 4330.
+    async fn check_clock_skew(&self) -> DependencyStatus {
+        let target = NetworkTarget {
+            name: "NTP".to_string(),
+            kind: DependencyKind::TimeSource,
+            url: NTP_SERVER.to_string(),
+        };
+        let mut status = new_status(&target);
+
+        match timeout(NTP_TIMEOUT, query_ntp_offset_ms(NTP_SERVER)).await {
+            Ok(Ok(offset_ms)) => {
+                status.clock_skew_ms = Some(offset_ms);
+                status.level = if offset_ms.abs() >= CLOCK_SKEW_CRITICAL_MS {
+                    HealthLevel::Critical
+                } else if offset_ms.abs() >= CLOCK_SKEW_WARNING_MS {
+                    HealthLevel::Warning
+                } else {
+                    HealthLevel::Excellent
+                };
+                if offset_ms.abs() >= CLOCK_SKEW_WARNING_MS {
+                    status.remediation = Some(
+                        "Enable automatic time sync; 2FA codes and signed requests depend on an accurate clock"
+                            .to_string(),
+                    );
+                }
+            }
+            Ok(Err(e)) => {
+                status.level = HealthLevel::Unknown;
+                status.error = Some(format!("NTP query failed: {}", e));
+                status.remediation = Some("Allow outbound UDP port 123".to_string());
+            }
+            Err(_) => {
+                status.level = HealthLevel::Unknown;
+                status.error = Some("NTP query timed out".to_string());
+                status.remediation = Some("Allow outbound UDP port 123".to_string());
+            }
+        }
+
+        status
+    }
+
+    fn issue_for_dependency(&self, dependency: &DependencyStatus) -> Option<DiagnosticIssue> {
+        let severity = match dependency.level {
+            HealthLevel::Critical if dependency.kind == DependencyKind::RpcEndpoint => {
+                IssueSeverity::Critical
+            }
+            HealthLevel::Critical if dependency.kind == DependencyKind::TimeSource => {
+                IssueSeverity::Critical
+            }
+            HealthLevel::Critical | HealthLevel::Degraded => IssueSeverity::Warning,
+            HealthLevel::Warning => IssueSeverity::Info,
+            _ => return None,
+        };
+
+        let title = match dependency.kind {
+            DependencyKind::TimeSource => "System clock skew detected".to_string(),
+            DependencyKind::RpcEndpoint => format!("{} failing health checks", dependency.name),
+            _ => format!("{} unreachable", dependency.name),
+        };
+        let description = dependency
+            .error
+            .clone()
+            .or_else(|| {
+                dependency
+                    .clock_skew_ms
+                    .map(|ms| format!("Local clock differs from NTP by {} ms", ms))
+            })
+            .unwrap_or_else(|| format!("Degraded response from {}", dependency.target));
+        let action = dependency
+            .remediation
+            .clone()
+            .unwrap_or_else(|| "Switch to backup endpoint or retry later".to_string());
+
+        let mut issue =
+            self.create_issue(&title, &description, severity, &action, Some(dependency));
+        // Only RPC failures can be fixed automatically, by switching endpoints.
+        issue.auto_repair_available =
+            dependency.kind == DependencyKind::RpcEndpoint && !self.rpc_fallbacks.is_empty();
+        issue.repair_level = if issue.auto_repair_available {
+            RepairLevel::Automatic
+        } else {
+            RepairLevel::Manual
+        };
+        Some(issue)
     }
 
     fn create_issue(
@@ -146,7 +556,16 @@ impl NetworkDiagnostics {
         description: &str,
         severity: IssueSeverity,
         recommended_action: &str,
+        dependency: Option<&DependencyStatus>,
     ) -> DiagnosticIssue {
+        let mut metadata = HashMap::new();
+        if let Some(dependency) = dependency {
+            metadata.insert(
+                "dependency".to_string(),
+                serde_json::to_value(dependency).unwrap_or_default(),
+            );
+        }
+
         DiagnosticIssue {
             id: Uuid::new_v4().to_string(),
             category: IssueCategory::Network,
@@ -162,32 +581,205 @@ impl NetworkDiagnostics {
             },
             auto_repair_available: !matches!(severity, IssueSeverity::Critical),
             status: RepairStatus::Pending,
-            metadata: HashMap::new(),
+            metadata,
         }
     }
 
+    /// Probes the fallback RPC endpoints and promotes the first healthy one.
     pub async fn auto_switch_endpoint(
-        &self,
+        &mut self,
         issue: &DiagnosticIssue,
     ) -> Result<AutoRepairResult, String> {
-        let backup_endpoints = vec![
-            json!({"name": "Backup RPC", "url": "https://solana-api.projectserum.com"}),
-            json!({"name": "Secondary RPC", "url": "https://api.mainnet-beta.solana.com"}),
-        ];
+        let is_rpc_issue = issue
+            .metadata
+            .get("dependency")
+            .and_then(|d| d.get("kind"))
+            .and_then(|kind| kind.as_str())
+            == Some("rpc_endpoint");
+        if !is_rpc_issue {
+            return Err("Only RPC endpoint failures can be repaired automatically".to_string());
+        }
+        if self.rpc_fallbacks.is_empty() {
+            return Err("No fallback RPC endpoints configured".to_string());
+        }
 
-        Ok(AutoRepairResult {
-            issue_id: Some(issue.id.clone()),
-            status: RepairStatus::Completed,
-            message: "Switched to backup endpoint".to_string(),
-            actions: vec![RepairAction {
+        let mut actions = Vec::new();
+        for candidate in self.rpc_fallbacks.clone() {
+            let target = NetworkTarget {
+                name: "Fallback RPC".to_string(),
+                kind: DependencyKind::RpcEndpoint,
+                url: candidate.clone(),
+            };
+            let status = self.check_rpc(&target).await;
+            actions.push(RepairAction {
+                action: "test_endpoint".to_string(),
+                description: format!(
+                    "{}: {}",
+                    candidate,
+                    status.error.as_deref().unwrap_or("healthy")
+                ),
+                estimated_duration: Some("< 5 seconds".to_string()),
+                level: RepairLevel::Automatic,
+                requires_confirmation: false,
+            });
+
+            if is_failing(&status.level) {
+                continue;
+            }
+
+            let previous = std::mem::replace(&mut self.active_rpc, candidate.clone());
+            self.rpc_fallbacks.retain(|url| *url != candidate);
+            self.rpc_fallbacks.push(previous.clone());
+
+            actions.push(RepairAction {
                 action: "switch_endpoint".to_string(),
-                description: "Selected best available RPC endpoint".to_string(),
+                description: format!("Switched RPC from {} to {}", previous, candidate),
                 estimated_duration: Some("< 5 seconds".to_string()),
                 level: RepairLevel::Automatic,
                 requires_confirmation: false,
-            }],
+            });
+
+            return Ok(AutoRepairResult {
+                issue_id: Some(issue.id.clone()),
+                status: RepairStatus::Completed,
+                message: format!("Switched to backup endpoint {}", candidate),
+                actions,
+                backup_location: None,
+                rollback_token: Some(previous),
+            });
+        }
+
+        Ok(AutoRepairResult {
+            issue_id: Some(issue.id.clone()),
+            status: RepairStatus::Failed,
+            message: "No healthy fallback RPC endpoint available".to_string(),
+            actions,
             backup_location: None,
-            rollback_token: Some(Uuid::new_v4().to_string()),
+            rollback_token: None,
         })
     }
 }
+
+fn new_status(target: &NetworkTarget) -> DependencyStatus {
+    DependencyStatus {
+        name: target.name.clone(),
+        kind: target.kind,
+        target: target.url.clone(),
+        level: HealthLevel::Unknown,
+        checked_at: Utc::now(),
+        dns_ms: None,
+        connect_ms: None,
+        request_ms: None,
+        latency_ms: None,
+        clock_skew_ms: None,
+        error: None,
+        remediation: None,
+    }
+}
+
+fn is_failing(level: &HealthLevel) -> bool {
+    matches!(level, HealthLevel::Critical | HealthLevel::Degraded)
+}
+
+fn latency_level(latency_ms: u64) -> HealthLevel {
+    if latency_ms > LATENCY_WARNING_MS {
+        HealthLevel::Warning
+    } else if latency_ms > LATENCY_WARNING_MS / 3 {
+        HealthLevel::Good
+    } else {
+        HealthLevel::Excellent
+    }
+}
+
+fn elapsed_ms(start: Instant) -> u64 {
+    start.elapsed().as_millis() as u64
+}
+
+/// The network panel's internet check, shared with the offline outbox.
+pub async fn internet_reachable(client: &Client) -> bool {
+    let request = client.get(CONNECTIVITY_PROBE_URL);
+    match timeout(REQUEST_TIMEOUT, request.send()).await {
+        Ok(Ok(response)) => response.status().is_success(),
+        _ => false,
+    }
+}
+
+fn dns_remediation() -> String {
+    "DNS lookup failed; check your DNS server or try 1.1.1.1 / 8.8.8.8".to_string()
+}
+
+fn rpc_remediation() -> String {
+    "Run auto repair to switch to a fallback RPC endpoint".to_string()
+}
+
+async fn query_ntp_offset_ms(server: &str) -> std::io::Result<i64> {
+    let socket = UdpSocket::bind("0.0.0.0:0").await?;
+    socket.connect(server).await?;
+
+    // LI = 0, VN = 4, Mode = 3 (client)
+    let mut request = [0u8; 48];
+    request[0] = 0x23;
+
+    let t0 = unix_now_ms();
+    socket.send(&request).await?;
+    let mut response = [0u8; 48];
+    let read = socket.recv(&mut response).await?;
+    let t3 = unix_now_ms();
+
+    if read < 48 {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "short NTP response",
+        ));
+    }
+
+    let t1 = ntp_timestamp_ms(&response[32..40]);
+    let t2 = ntp_timestamp_ms(&response[40..48]);
+    Ok(((t1 - t0) + (t2 - t3)) / 2)
+}
+
+fn ntp_timestamp_ms(bytes: &[u8]) -> i64 {
+    let seconds = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as u64;
+    let fraction = u32::from_be_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]) as u64;
+    let unix_seconds = seconds.saturating_sub(NTP_UNIX_OFFSET_SECS);
+    (unix_seconds * 1000 + ((fraction * 1000) >> 32)) as i64
+}
+
+fn unix_now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ntp_timestamp_converts_to_unix_millis() {
+        // 2024-01-01T00:00:00Z plus half a second.
+        let seconds = (1_704_067_200u64 + NTP_UNIX_OFFSET_SECS) as u32;
+        let mut bytes = [0u8; 8];
+        bytes[..4].copy_from_slice(&seconds.to_be_bytes());
+        bytes[4..].copy_from_slice(&(u32::MAX / 2 + 1).to_be_bytes());
+        assert_eq!(ntp_timestamp_ms(&bytes), 1_704_067_200_500);
+    }
+
+    #[test]
+    fn rpc_fallbacks_exclude_active_endpoint() {
+        let mut diagnostics = NetworkDiagnostics::new();
+        diagnostics.set_rpc_endpoints(
+            "https://rpc.example.com".to_string(),
+            vec![
+                "https://rpc.example.com".to_string(),
+                "https://backup.example.com".to_string(),
+            ],
+        );
+        assert_eq!(diagnostics.active_rpc_endpoint(), "https://rpc.example.com");
+        assert_eq!(
+            diagnostics.rpc_fallbacks,
+            vec!["https://backup.example.com"]
+        );
+    }
+}
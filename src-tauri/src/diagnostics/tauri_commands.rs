@@ -1,47 +1,132 @@
 use super::engine::DiagnosticsEngine;
+use super::network_repair::DEFAULT_RPC_ENDPOINT;
 use super::types::*;
+use crate::api_config::ApiConfigManager;
+use crate::config::settings_manager::SharedSettingsManager;
+use crate::config::DataPaths;
+use crate::security::keystore::Keystore;
 use std::sync::Arc;
 use tauri::{Emitter, Manager};
 use tokio::sync::RwLock;
 
 pub type SharedDiagnosticsEngine = Arc<RwLock<DiagnosticsEngine>>;
 
+/// Syncs the network suite with the providers in `ApiConfigManager` and the RPC
+/// endpoints from network settings before a scan.
+pub async fn refresh_network_targets(
+    app_handle: &tauri::AppHandle,
+    engine: &SharedDiagnosticsEngine,
+) {
+    let mut api_targets = Vec::new();
+    let mut active_rpc = None;
+
+    if let (Some(config), Some(keystore)) = (
+        app_handle.try_state::<ApiConfigManager>(),
+        app_handle.try_state::<Keystore>(),
+    ) {
+        api_targets = config
+            .provider_endpoints()
+            .into_iter()
+            .map(|endpoint| NetworkTarget {
+                name: endpoint.service,
+                kind: DependencyKind::ApiProvider,
+                url: endpoint.url,
+            })
+            .collect();
+        active_rpc = Some(config.rpc_endpoint(&keystore));
+    }
+
+    let mut rpc_fallbacks = Vec::new();
+    if let Some(settings) = app_handle.try_state::<SharedSettingsManager>() {
+        let network = settings.read().await.get_all_settings().network;
+        // A custom key in ApiConfigManager wins over the settings endpoint.
+        if active_rpc
+            .as_deref()
+            .map_or(true, |rpc| rpc == DEFAULT_RPC_ENDPOINT)
+        {
+            active_rpc = Some(network.solana_rpc_endpoint);
+        }
+        rpc_fallbacks = network.rpc_fallback_endpoints;
+    }
+
+    let mut engine = engine.write().await;
+    let active_rpc = active_rpc.unwrap_or_else(|| engine.active_rpc_endpoint());
+    engine.configure_network_targets(api_targets, active_rpc, rpc_fallbacks);
+}
+
+/// Switches `api_config::rpc_endpoint` to the RPC endpoint chosen by auto
+/// repair, and mirrors it into network settings.
+async fn persist_rpc_switch(app_handle: &tauri::AppHandle, previous: &str, current: &str) {
+    if previous == current {
+        return;
+    }
+    if let (Some(config), Some(keystore)) = (
+        app_handle.try_state::<ApiConfigManager>(),
+        app_handle.try_state::<Keystore>(),
+    ) {
+        if let Err(e) = config.switch_rpc_endpoint(current, &keystore) {
+            eprintln!("Failed to switch RPC endpoint: {}", e);
+        }
+    }
+    if let Some(settings) = app_handle.try_state::<SharedSettingsManager>() {
+        let mut settings = settings.write().await;
+        if let Err(e) = settings.update_setting(
+            "network".to_string(),
+            "solanaRpcEndpoint".to_string(),
+            serde_json::json!(current),
+        ) {
+            eprintln!("Failed to persist fallback RPC endpoint: {}", e);
+        }
+    }
+}
+
 #[tauri::command]
 pub async fn run_diagnostics(
+    app_handle: tauri::AppHandle,
     engine: tauri::State<'_, SharedDiagnosticsEngine>,
 ) -> Result<DiagnosticsReport, String> {
+    refresh_network_targets(&app_handle, &engine).await;
     let mut engine = engine.write().await;
     Ok(engine.run_full_diagnostics().await)
 }
 
 #[tauri::command]
 pub async fn get_health_report(
+    app_handle: tauri::AppHandle,
     engine: tauri::State<'_, SharedDiagnosticsEngine>,
 ) -> Result<DiagnosticsReport, String> {
-    let mut engine = engine.write().await;
-    if let Some(report) = engine.get_last_report() {
-        Ok(report)
-    } else {
-        Ok(engine.run_full_diagnostics().await)
+    if let Some(report) = engine.read().await.get_last_report() {
+        return Ok(report);
     }
+    refresh_network_targets(&app_handle, &engine).await;
+    let mut engine = engine.write().await;
+    Ok(engine.run_full_diagnostics().await)
 }
 
 #[tauri::command]
 pub async fn auto_repair_issue(
     issue: DiagnosticIssue,
+    app_handle: tauri::AppHandle,
     engine: tauri::State<'_, SharedDiagnosticsEngine>,
 ) -> Result<AutoRepairResult, String> {
     let mut engine = engine.write().await;
-    engine.auto_repair(&issue).await
+    let previous_rpc = engine.active_rpc_endpoint();
+    let result = engine.auto_repair(&issue).await;
+    persist_rpc_switch(&app_handle, &previous_rpc, &engine.active_rpc_endpoint()).await;
+    result
 }
 
 #[tauri::command]
 pub async fn auto_repair(
     issues: Vec<DiagnosticIssue>,
+    app_handle: tauri::AppHandle,
     engine: tauri::State<'_, SharedDiagnosticsEngine>,
 ) -> Result<Vec<AutoRepairResult>, String> {
     let mut engine = engine.write().await;
-    Ok(engine.auto_repair_all(issues).await)
+    let previous_rpc = engine.active_rpc_endpoint();
+    let results = engine.auto_repair_all(issues).await;
+    persist_rpc_switch(&app_handle, &previous_rpc, &engine.active_rpc_endpoint()).await;
+    Ok(results)
 }
 
 #[tauri::command]
@@ -95,8 +180,7 @@ pub async fn get_diagnostics_settings(
     app_handle: tauri::AppHandle,
 ) -> Result<DiagnosticsSettings, String> {
     let app_data_dir = app_handle
-        .path()
-        .app_data_dir()
+        .app_data_root()
         .map_err(|e| format!("Failed to resolve app data directory: {}", e))?;
 
     let settings_file = app_data_dir.join("settings").join("diagnostics.json");
@@ -116,8 +200,7 @@ pub async fn save_diagnostics_settings(
     app_handle: tauri::AppHandle,
 ) -> Result<(), String> {
     let app_data_dir = app_handle
-        .path()
-        .app_data_dir()
+        .app_data_root()
         .map_err(|e| format!("Failed to resolve app data directory: {}", e))?;
 
     let settings_dir = app_data_dir.join("settings");
@@ -137,8 +220,7 @@ pub async fn save_diagnostics_settings(
 #[tauri::command]
 pub async fn backup_before_repair(app_handle: tauri::AppHandle) -> Result<String, String> {
     let app_data_dir = app_handle
-        .path()
-        .app_data_dir()
+        .app_data_root()
         .map_err(|e| format!("Failed to resolve app data directory: {}", e))?;
 
     let backup_dir = app_data_dir.join("backups");
@@ -172,8 +254,7 @@ pub async fn export_diagnostics_report(
     app_handle: tauri::AppHandle,
 ) -> Result<String, String> {
     let app_data_dir = app_handle
-        .path()
-        .app_data_dir()
+        .app_data_root()
         .map_err(|e| format!("Failed to resolve app data directory: {}", e))?;
 
     let exports_dir = app_data_dir.join("exports");
@@ -196,8 +277,7 @@ pub fn initialize_diagnostics_engine(
     app_handle: &tauri::AppHandle,
 ) -> Result<SharedDiagnosticsEngine, String> {
     let app_data_dir = app_handle
-        .path()
-        .app_data_dir()
+        .app_data_root()
         .map_err(|e| format!("Failed to resolve app data directory: {}", e))?;
 
     let project_root =
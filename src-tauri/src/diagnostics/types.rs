@@ -155,11 +155,55 @@ pub struct DiagnosticsReport {
     pub panels: BTreeMap<String, PanelStatus>,
     pub issues: Vec<DiagnosticIssue>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dependencies: Vec<DependencyStatus>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub repair_history: Vec<RepairRecord>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub notes: Vec<String>,
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DependencyKind {
+    ApiProvider,
+    RpcEndpoint,
+    WebSocketStream,
+    TimeSource,
+}
+
+/// An upstream service probed by the network diagnostics suite.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkTarget {
+    pub name: String,
+    pub kind: DependencyKind,
+    pub url: String,
+}
+
+/// Result of probing a single upstream dependency.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DependencyStatus {
+    pub name: String,
+    pub kind: DependencyKind,
+    pub target: String,
+    pub level: HealthLevel,
+    pub checked_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dns_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub connect_ms: Option<u64>,
+    /// The whole HTTP request, TLS handshake included; TLS is not timed alone.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub clock_skew_ms: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remediation: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModuleDiagnostics {
     pub panel: PanelStatus,
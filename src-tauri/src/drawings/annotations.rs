@@ -0,0 +1,394 @@
+//! Read-only chart markers built from alert triggers, order fills and
+//! journal entries. They are assembled on request and never stored with the
+//! user's drawings.
+
+use crate::alerts::SharedAlertHistoryManager;
+use crate::journal::SharedJournalDatabase;
+use crate::trading::limit_orders::filled_orders_for_mint;
+use crate::trading::types::{Order, OrderSide};
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Trade markers returned before fills are merged into buckets.
+pub const MAX_TRADE_MARKERS: usize = 300;
+/// Cap per source for alert triggers and journal entries.
+const MAX_SOURCE_MARKERS: i64 = 500;
+const JOURNAL_TOOLTIP_CHARS: usize = 140;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnnotationSource {
+    Alert,
+    Trade,
+    Journal,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnnotationKind {
+    AlertTrigger,
+    Buy,
+    Sell,
+    JournalEntry,
+}
+
+/// Time range of the visible chart, in milliseconds.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ChartRange {
+    pub from: i64,
+    pub to: i64,
+}
+
+fn enabled() -> bool {
+    true
+}
+
+/// Which sources to include. Every source is on unless turned off.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct AnnotationSources {
+    #[serde(default = "enabled")]
+    pub alerts: bool,
+    #[serde(default = "enabled")]
+    pub trades: bool,
+    #[serde(default = "enabled")]
+    pub journal: bool,
+}
+
+impl Default for AnnotationSources {
+    fn default() -> Self {
+        Self {
+            alerts: true,
+            trades: true,
+            journal: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChartAnnotation {
+    /// Unique within a response: the source and its entity id.
+    pub id: String,
+    pub source: AnnotationSource,
+    /// Id of the alert history entry, order or journal entry to open on
+    /// click. For merged fills, the largest fill in the bucket.
+    pub source_id: String,
+    pub kind: AnnotationKind,
+    /// Milliseconds.
+    pub timestamp: i64,
+    /// `None` pins the marker to the time axis only.
+    pub price: Option<f64>,
+    pub label: String,
+    pub tooltip: Option<String>,
+    /// Filled amount of a trade marker, in the order's input token.
+    pub size: Option<f64>,
+    /// Fills merged into this marker; 1 for everything else.
+    pub merged_count: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChartAnnotations {
+    pub mint: String,
+    pub range: ChartRange,
+    /// Sorted by timestamp.
+    pub annotations: Vec<ChartAnnotation>,
+    /// Set when fills were merged; the width of each bucket in ms.
+    pub trade_bucket_ms: Option<i64>,
+}
+
+fn to_rfc3339(ms: i64) -> Result<String, String> {
+    Utc.timestamp_millis_opt(ms)
+        .single()
+        .map(|time| time.to_rfc3339())
+        .ok_or_else(|| format!("Invalid timestamp: {}", ms))
+}
+
+fn rfc3339_millis(value: &str) -> Option<i64> {
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|time| time.timestamp_millis())
+}
+
+fn trade_annotation(order: &Order) -> Option<ChartAnnotation> {
+    let timestamp = order.triggered_at?.timestamp_millis();
+    // Fills recorded before the fill price column fall back to the price
+    // the order was waiting for.
+    let price = order.fill_price.or(order.limit_price).or(order.stop_price);
+    let (kind, label) = match order.side {
+        OrderSide::Buy => (AnnotationKind::Buy, "Buy"),
+        OrderSide::Sell => (AnnotationKind::Sell, "Sell"),
+    };
+    let mut tooltip = format!(
+        "{} {} {} → {}",
+        label, order.filled_amount, order.input_symbol, order.output_symbol
+    );
+    if let Some(price) = price {
+        tooltip.push_str(&format!(" @ {}", price));
+    }
+    Some(ChartAnnotation {
+        id: format!("trade:{}", order.id),
+        source: AnnotationSource::Trade,
+        source_id: order.id.clone(),
+        kind,
+        timestamp,
+        price,
+        label: label.to_string(),
+        tooltip: Some(tooltip),
+        size: Some(order.filled_amount),
+        merged_count: 1,
+    })
+}
+
+/// Merges trade markers into per-side time buckets when there are more than
+/// `max_markers`, so a wide range over a busy history stays bounded. Merged
+/// markers sit at the size-weighted average price and time of their fills.
+/// Returns the bucket width when merging happened.
+pub fn decimate_trades(
+    trades: Vec<ChartAnnotation>,
+    range: ChartRange,
+    max_markers: usize,
+) -> (Vec<ChartAnnotation>, Option<i64>) {
+    if trades.len() <= max_markers || max_markers < 2 {
+        return (trades, None);
+    }
+    // Each bucket can hold one buy and one sell marker.
+    let buckets = (max_markers / 2) as i64;
+    let bucket_ms = ((range.to - range.from).max(1) + buckets - 1) / buckets;
+
+    let mut grouped: BTreeMap<(i64, bool), Vec<ChartAnnotation>> = BTreeMap::new();
+    for trade in trades {
+        let bucket = (trade.timestamp - range.from).max(0) / bucket_ms;
+        let is_buy = trade.kind == AnnotationKind::Buy;
+        grouped.entry((bucket, is_buy)).or_default().push(trade);
+    }
+
+    let merged = grouped
+        .into_values()
+        .map(|mut group| {
+            if group.len() == 1 {
+                return group.remove(0);
+            }
+            let weight = |trade: &ChartAnnotation| trade.size.unwrap_or(0.0).max(0.0);
+            let total_size: f64 = group.iter().map(weight).sum();
+            let priced: Vec<&ChartAnnotation> =
+                group.iter().filter(|trade| trade.price.is_some()).collect();
+            let priced_size: f64 = priced.iter().map(|trade| weight(trade)).sum();
+            let price = if priced.is_empty() {
+                None
+            } else if priced_size > 0.0 {
+                Some(
+                    priced
+                        .iter()
+                        .map(|trade| trade.price.unwrap_or(0.0) * weight(trade))
+                        .sum::<f64>()
+                        / priced_size,
+                )
+            } else {
+                Some(
+                    priced
+                        .iter()
+                        .map(|trade| trade.price.unwrap_or(0.0))
+                        .sum::<f64>()
+                        / priced.len() as f64,
+                )
+            };
+            let timestamp = if total_size > 0.0 {
+                (group
+                    .iter()
+                    .map(|trade| trade.timestamp as f64 * weight(trade))
+                    .sum::<f64>()
+                    / total_size) as i64
+            } else {
+                group[group.len() / 2].timestamp
+            };
+
+            let largest = group
+                .iter()
+                .max_by(|a, b| weight(a).total_cmp(&weight(b)))
+                .expect("group is not empty");
+            let count = group.len() as u32;
+            ChartAnnotation {
+                id: format!("{}+{}", largest.id, count - 1),
+                source: AnnotationSource::Trade,
+                source_id: largest.source_id.clone(),
+                kind: largest.kind,
+                timestamp,
+                price,
+                label: format!("{} ×{}", largest.label, count),
+                tooltip: Some(format!(
+                    "{} fills, {} total{}",
+                    count,
+                    total_size,
+                    price
+                        .map(|p| format!(" @ avg {:.6}", p))
+                        .unwrap_or_default()
+                )),
+                size: Some(total_size),
+                merged_count: count,
+            }
+        })
+        .collect();
+    (merged, Some(bucket_ms))
+}
+
+#[tauri::command]
+pub async fn get_chart_annotations(
+    mint: String,
+    range: ChartRange,
+    sources: Option<AnnotationSources>,
+    alert_history: tauri::State<'_, SharedAlertHistoryManager>,
+    journal: tauri::State<'_, SharedJournalDatabase>,
+) -> Result<ChartAnnotations, String> {
+    if range.to < range.from {
+        return Err("Range end must not be before its start".to_string());
+    }
+    let sources = sources.unwrap_or_default();
+    let (from, to) = (to_rfc3339(range.from)?, to_rfc3339(range.to)?);
+    let mut annotations = Vec::new();
+
+    if sources.alerts {
+        let triggers = alert_history
+            .read()
+            .await
+            .triggers_for_mint(&mint, &from, &to, MAX_SOURCE_MARKERS)
+            .await
+            .map_err(|e| e.to_string())?;
+        annotations.extend(triggers.into_iter().filter_map(|entry| {
+            Some(ChartAnnotation {
+                id: format!("alert:{}", entry.id),
+                source: AnnotationSource::Alert,
+                source_id: entry.id,
+                kind: AnnotationKind::AlertTrigger,
+                timestamp: rfc3339_millis(&entry.triggered_at)?,
+                price: Some(entry.triggered_price),
+                label: entry.alert_name,
+                tooltip: Some(entry.conditions_met),
+                size: None,
+                merged_count: 1,
+            })
+        }));
+    }
+
+    let mut trade_bucket_ms = None;
+    if sources.trades {
+        let fills = filled_orders_for_mint(&mint, &from, &to).await?;
+        let trades = fills.iter().filter_map(trade_annotation).collect();
+        let (trades, bucket_ms) = decimate_trades(trades, range, MAX_TRADE_MARKERS);
+        trade_bucket_ms = bucket_ms;
+        annotations.extend(trades);
+    }
+
+    if sources.journal {
+        let entries = journal
+            .read()
+            .await
+            .get_entries_for_mint(
+                &mint,
+                range.from.div_euclid(1000),
+                range.to.div_euclid(1000),
+                MAX_SOURCE_MARKERS,
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+        annotations.extend(entries.into_iter().map(|entry| {
+            let label = serde_json::to_value(&entry.entry_type)
+                .ok()
+                .and_then(|value| value.as_str().map(str::to_string))
+                .unwrap_or_else(|| "journal".to_string());
+            let tooltip = (!entry.notes.is_empty())
+                .then(|| entry.notes.chars().take(JOURNAL_TOOLTIP_CHARS).collect());
+            ChartAnnotation {
+                id: format!("journal:{}", entry.id),
+                source: AnnotationSource::Journal,
+                source_id: entry.id,
+                kind: AnnotationKind::JournalEntry,
+                timestamp: entry.timestamp * 1000,
+                price: entry.entry_price.map(f64::from),
+                label,
+                tooltip,
+                size: None,
+                merged_count: 1,
+            }
+        }));
+    }
+
+    annotations.sort_by_key(|annotation| annotation.timestamp);
+    Ok(ChartAnnotations {
+        mint,
+        range,
+        annotations,
+        trade_bucket_ms,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fill(
+        id: &str,
+        kind: AnnotationKind,
+        timestamp: i64,
+        price: f64,
+        size: f64,
+    ) -> ChartAnnotation {
+        ChartAnnotation {
+            id: format!("trade:{}", id),
+            source: AnnotationSource::Trade,
+            source_id: id.to_string(),
+            kind,
+            timestamp,
+            price: Some(price),
+            label: "Buy".to_string(),
+            tooltip: None,
+            size: Some(size),
+            merged_count: 1,
+        }
+    }
+
+    #[test]
+    fn narrow_ranges_keep_every_fill() {
+        let range = ChartRange { from: 0, to: 1_000 };
+        let trades = vec![
+            fill("a", AnnotationKind::Buy, 10, 1.0, 1.0),
+            fill("b", AnnotationKind::Sell, 20, 1.0, 1.0),
+        ];
+        let (kept, bucket_ms) = decimate_trades(trades.clone(), range, 4);
+        assert_eq!(kept, trades);
+        assert!(bucket_ms.is_none());
+    }
+
+    #[test]
+    fn wide_ranges_merge_fills_per_side() {
+        let range = ChartRange { from: 0, to: 1_000 };
+        let mut trades: Vec<ChartAnnotation> = (0..100)
+            .map(|i| fill(&format!("buy{i}"), AnnotationKind::Buy, i * 10, 10.0, 1.0))
+            .collect();
+        trades[3].size = Some(5.0);
+        trades[3].price = Some(16.0);
+        trades.push(fill("sell", AnnotationKind::Sell, 5, 12.0, 2.0));
+
+        let (merged, bucket_ms) = decimate_trades(trades, range, 4);
+        assert_eq!(bucket_ms, Some(500));
+        // Two buy buckets and one lone sell.
+        assert_eq!(merged.len(), 3);
+        assert!(merged.len() <= 4);
+
+        let first_buys = merged
+            .iter()
+            .find(|m| m.kind == AnnotationKind::Buy && m.timestamp < 500)
+            .unwrap();
+        assert_eq!(first_buys.merged_count, 50);
+        assert_eq!(first_buys.source_id, "buy3");
+        assert_eq!(first_buys.size, Some(54.0));
+        // (49 * 10 + 5 * 16) / 54
+        assert!((first_buys.price.unwrap() - 570.0 / 54.0).abs() < 1e-9);
+
+        let sell = merged
+            .iter()
+            .find(|m| m.kind == AnnotationKind::Sell)
+            .unwrap();
+        assert_eq!(sell.merged_count, 1);
+        assert_eq!(sell.source_id, "sell");
+    }
+}
@@ -1,12 +1,26 @@
+mod annotations;
+mod sync;
+
+pub use annotations::*;
+pub use sync::*;
+
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::fs;
 use std::path::PathBuf;
 use std::sync::Arc;
+use tauri::Emitter;
 use tokio::sync::RwLock;
 
 pub type SharedDrawingManager = Arc<RwLock<DrawingManager>>;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+pub const DRAWINGS_CHANGED_EVENT: &str = "drawings-changed";
+
+/// Scope used for templates, which are not tied to a symbol.
+const TEMPLATE_SCOPE: &str = "";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct DrawingPoint {
     pub x: f64,
     pub y: f64,
@@ -14,7 +28,7 @@ pub struct DrawingPoint {
     pub price: Option<f64>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct DrawingStyle {
     pub stroke_color: String,
     pub stroke_width: f64,
@@ -28,7 +42,7 @@ pub struct DrawingStyle {
     pub background: Option<bool>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct DrawingObject {
     pub id: String,
     pub user_id: String,
@@ -43,9 +57,12 @@ pub struct DrawingObject {
     pub updated_at: String,
     pub shared_with: Option<Vec<String>>,
     pub metadata: Option<serde_json::Value>,
+    /// Revision this copy was read at, sent back with edits.
+    #[serde(default)]
+    pub revision: u64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct DrawingTemplate {
     pub id: String,
     pub name: String,
@@ -54,11 +71,112 @@ pub struct DrawingTemplate {
     pub style: DrawingStyle,
     pub default_points: Vec<DrawingPoint>,
     pub metadata: Option<serde_json::Value>,
+    #[serde(default)]
+    pub revision: u64,
+}
+
+impl SyncedObject for DrawingObject {
+    fn object_id(&self) -> &str {
+        &self.id
+    }
+    fn revision(&self) -> u64 {
+        self.revision
+    }
+    fn set_revision(&mut self, revision: u64) {
+        self.revision = revision;
+    }
+    fn edited_at(&self) -> Option<i64> {
+        DateTime::parse_from_rfc3339(&self.updated_at)
+            .ok()
+            .map(|t| t.timestamp_millis())
+    }
+}
+
+impl SyncedObject for DrawingTemplate {
+    fn object_id(&self) -> &str {
+        &self.id
+    }
+    fn revision(&self) -> u64 {
+        self.revision
+    }
+    fn set_revision(&mut self, revision: u64) {
+        self.revision = revision;
+    }
+}
+
+/// Result of saving a batch of drawings or templates.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DrawingSaveResult {
+    /// Cursor to pass to the next save or `drawing_changes` call.
+    pub cursor: u64,
+    pub changed_ids: Vec<String>,
+    pub conflicts: Vec<DrawingConflict>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DrawingChanges {
+    pub cursor: u64,
+    pub template_cursor: u64,
+    pub drawings: Vec<DrawingRecord<DrawingObject>>,
+    pub templates: Vec<DrawingRecord<DrawingTemplate>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DrawingKind {
+    Drawing,
+    Template,
+}
+
+/// Payload of `drawings-changed`, emitted after every write so other windows
+/// can fetch just the changed objects.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DrawingsChangedEvent {
+    pub kind: DrawingKind,
+    /// Symbol of the changed drawings, `None` for templates.
+    pub symbol: Option<String>,
+    pub object_ids: Vec<String>,
+    pub origin: String,
+    pub cursor: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "versions", rename_all = "snake_case")]
+pub enum DrawingHistory {
+    Drawing(Vec<DrawingVersion<DrawingObject>>),
+    Template(Vec<DrawingVersion<DrawingTemplate>>),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "record", rename_all = "snake_case")]
+pub enum RestoredDrawing {
+    Drawing(DrawingRecord<DrawingObject>),
+    Template(DrawingRecord<DrawingTemplate>),
+}
+
+impl RestoredDrawing {
+    fn changed_event(&self, origin: &str) -> DrawingsChangedEvent {
+        let (kind, symbol, id, cursor) = match self {
+            RestoredDrawing::Drawing(r) => {
+                (DrawingKind::Drawing, Some(r.scope.clone()), &r.id, r.seq)
+            }
+            RestoredDrawing::Template(r) => (DrawingKind::Template, None, &r.id, r.seq),
+        };
+        DrawingsChangedEvent {
+            kind,
+            symbol,
+            object_ids: vec![id.clone()],
+            origin: origin.to_string(),
+            cursor,
+        }
+    }
 }
 
 pub struct DrawingManager {
     drawings_path: PathBuf,
     templates_path: PathBuf,
+    drawings: DrawingStore<DrawingObject>,
+    templates: DrawingStore<DrawingTemplate>,
 }
 
 impl DrawingManager {
@@ -66,54 +184,233 @@ impl DrawingManager {
         let drawings_dir = app_data_dir.join("drawings");
         let _ = fs::create_dir_all(&drawings_dir);
 
+        let drawings_path = drawings_dir.join("drawing_records.json");
+        let templates_path = drawings_dir.join("template_records.json");
+        let now = Utc::now().timestamp_millis();
+
+        let drawings = Self::read_json(&drawings_path).unwrap_or_else(|| {
+            let legacy: Vec<DrawingObject> =
+                Self::read_json(&drawings_dir.join("drawings.json")).unwrap_or_default();
+            let mut store = DrawingStore::default();
+            for drawing in legacy {
+                let scope = drawing.symbol.clone();
+                store.apply("local", &scope, change_for(drawing, 0, now), now);
+            }
+            store
+        });
+        let templates = Self::read_json(&templates_path).unwrap_or_else(|| {
+            let legacy: Vec<DrawingTemplate> =
+                Self::read_json(&drawings_dir.join("templates.json")).unwrap_or_default();
+            let mut store = DrawingStore::default();
+            for template in legacy {
+                store.apply("local", TEMPLATE_SCOPE, change_for(template, 0, now), now);
+            }
+            store
+        });
+
         Self {
-            drawings_path: drawings_dir.join("drawings.json"),
-            templates_path: drawings_dir.join("templates.json"),
+            drawings_path,
+            templates_path,
+            drawings,
+            templates,
         }
     }
 
-    fn read_json<T: for<'de> Deserialize<'de>>(&self, path: &PathBuf) -> Result<T, String> {
-        if !path.exists() {
-            return serde_json::from_str("{}").map_err(|e| e.to_string());
-        }
-        let data = fs::read_to_string(path).map_err(|e| e.to_string())?;
-        serde_json::from_str(&data).map_err(|e| e.to_string())
+    fn read_json<T: for<'de> Deserialize<'de>>(path: &PathBuf) -> Option<T> {
+        let data = fs::read_to_string(path).ok()?;
+        serde_json::from_str(&data).ok()
     }
 
-    fn write_json<T: Serialize>(&self, path: &PathBuf, data: &T) -> Result<(), String> {
+    fn write_json<T: Serialize>(path: &PathBuf, data: &T) -> Result<(), String> {
         let serialized = serde_json::to_string_pretty(data).map_err(|e| e.to_string())?;
         fs::write(path, serialized).map_err(|e| e.to_string())
     }
 
     pub fn list_drawings(&self, symbol: &str) -> Result<Vec<DrawingObject>, String> {
-        let all_drawings: Vec<DrawingObject> =
-            self.read_json(&self.drawings_path).unwrap_or_default();
-        Ok(all_drawings
-            .into_iter()
-            .filter(|d| d.symbol == symbol)
-            .collect::<Vec<_>>())
+        Ok(self.drawings.live(Some(symbol)))
     }
 
-    pub fn save_drawings(&self, symbol: &str, drawings: &[DrawingObject]) -> Result<(), String> {
-        // Load all drawings, replace those matching symbol
-        let mut all_drawings: Vec<DrawingObject> =
-            self.read_json(&self.drawings_path).unwrap_or_default();
-        all_drawings.retain(|d| d.symbol != symbol);
-        all_drawings.extend_from_slice(drawings);
-        self.write_json(&self.drawings_path, &all_drawings)
+    /// Merges the client's view of a symbol's drawings into the store. Each
+    /// drawing is applied against the revision it carries; drawings the
+    /// client no longer has are deleted, except ones created after `cursor`,
+    /// which the client has not seen yet.
+    pub fn save_drawings(
+        &mut self,
+        symbol: &str,
+        drawings: &[DrawingObject],
+        origin: &str,
+        cursor: Option<u64>,
+    ) -> Result<DrawingSaveResult, String> {
+        let result = merge_snapshot(&mut self.drawings, symbol, drawings, origin, cursor);
+        Self::write_json(&self.drawings_path, &self.drawings)?;
+        Ok(result)
     }
 
     pub fn sync_drawings(&self, symbol: &str) -> Result<Vec<DrawingObject>, String> {
-        // For now, sync is equivalent to list, but may include remote sync in future
         self.list_drawings(symbol)
     }
 
+    /// Drawings for `symbol` (all symbols when `None`) changed after
+    /// `cursor` and templates changed after `template_cursor`, tombstones
+    /// included. Drawings and templates are sequenced separately.
+    pub fn changes_since(
+        &self,
+        cursor: u64,
+        template_cursor: u64,
+        symbol: Option<&str>,
+    ) -> DrawingChanges {
+        DrawingChanges {
+            cursor: self.drawings.cursor(),
+            template_cursor: self.templates.cursor(),
+            drawings: self.drawings.changed_since(cursor, symbol),
+            templates: self.templates.changed_since(template_cursor, None),
+        }
+    }
+
     pub fn list_templates(&self) -> Result<Vec<DrawingTemplate>, String> {
-        self.read_json(&self.templates_path)
+        Ok(self.templates.live(None))
+    }
+
+    pub fn save_templates(
+        &mut self,
+        templates: &[DrawingTemplate],
+        origin: &str,
+        cursor: Option<u64>,
+    ) -> Result<DrawingSaveResult, String> {
+        let result = merge_snapshot(
+            &mut self.templates,
+            TEMPLATE_SCOPE,
+            templates,
+            origin,
+            cursor,
+        );
+        Self::write_json(&self.templates_path, &self.templates)?;
+        Ok(result)
+    }
+
+    /// Adds templates alongside the existing ones, e.g. from an imported
+    /// indicator preset.
+    pub fn add_templates(
+        &mut self,
+        templates: &[DrawingTemplate],
+        origin: &str,
+    ) -> Result<DrawingSaveResult, String> {
+        let mut all = self.templates.live(None);
+        all.extend_from_slice(templates);
+        self.save_templates(&all, origin, None)
+    }
+
+    pub fn history(&self, object_id: &str) -> Result<DrawingHistory, String> {
+        if self.drawings.get(object_id).is_some() {
+            Ok(DrawingHistory::Drawing(self.drawings.history(object_id)))
+        } else if self.templates.get(object_id).is_some() {
+            Ok(DrawingHistory::Template(self.templates.history(object_id)))
+        } else {
+            Err(format!("Object {} not found", object_id))
+        }
+    }
+
+    pub fn restore_version(
+        &mut self,
+        object_id: &str,
+        version_id: &str,
+        origin: &str,
+    ) -> Result<RestoredDrawing, String> {
+        let now = Utc::now().timestamp_millis();
+        if self.drawings.get(object_id).is_some() {
+            let record = self
+                .drawings
+                .restore_version(object_id, version_id, origin, now)?;
+            Self::write_json(&self.drawings_path, &self.drawings)?;
+            Ok(RestoredDrawing::Drawing(record))
+        } else if self.templates.get(object_id).is_some() {
+            let record = self
+                .templates
+                .restore_version(object_id, version_id, origin, now)?;
+            Self::write_json(&self.templates_path, &self.templates)?;
+            Ok(RestoredDrawing::Template(record))
+        } else {
+            Err(format!("Object {} not found", object_id))
+        }
+    }
+}
+
+fn change_for<T: SyncedObject>(value: T, base_revision: u64, now: i64) -> DrawingChange<T> {
+    DrawingChange {
+        id: value.object_id().to_string(),
+        base_revision,
+        updated_at: value.edited_at().unwrap_or(now),
+        deleted: false,
+        value: Some(value),
+    }
+}
+
+fn merge_snapshot<T: SyncedObject>(
+    store: &mut DrawingStore<T>,
+    scope: &str,
+    values: &[T],
+    origin: &str,
+    cursor: Option<u64>,
+) -> DrawingSaveResult {
+    let now = Utc::now().timestamp_millis();
+    let mut changed_ids = Vec::new();
+    let mut conflicts = Vec::new();
+    let mut record = |id: String, outcome: DrawingOutcome| match outcome {
+        DrawingOutcome::Applied { .. } => changed_ids.push(id),
+        DrawingOutcome::Conflicted { conflict, .. } => {
+            if conflict.kept_origin == origin {
+                changed_ids.push(id);
+            }
+            conflicts.push(conflict);
+        }
+        DrawingOutcome::Unchanged => {}
+    };
+
+    let kept: HashSet<&str> = values.iter().map(|v| v.object_id()).collect();
+    let removed: Vec<(String, u64)> = store
+        .changed_since(0, Some(scope))
+        .into_iter()
+        .filter(|r| !r.deleted && !kept.contains(r.id.as_str()))
+        .filter(|r| cursor.map_or(true, |c| r.created_seq <= c))
+        .map(|r| {
+            // Edited since the client's cursor: the deletion is concurrent.
+            let base = match cursor {
+                Some(c) if r.seq > c => r.revision - 1,
+                _ => r.revision,
+            };
+            (r.id, base)
+        })
+        .collect();
+
+    for value in values {
+        let id = value.object_id().to_string();
+        let change = change_for(value.clone(), value.revision(), now);
+        record(id, store.apply(origin, scope, change, now));
+    }
+    for (id, base_revision) in removed {
+        let change = DrawingChange {
+            id: id.clone(),
+            base_revision,
+            updated_at: now,
+            deleted: true,
+            value: None,
+        };
+        record(id, store.apply(origin, scope, change, now));
     }
 
-    pub fn save_templates(&self, templates: &[DrawingTemplate]) -> Result<(), String> {
-        self.write_json(&self.templates_path, &templates)
+    DrawingSaveResult {
+        cursor: store.cursor(),
+        changed_ids,
+        conflicts,
+    }
+}
+
+pub(crate) fn emit_changed(window: &tauri::Window, event: &DrawingsChangedEvent) {
+    if event.object_ids.is_empty() {
+        return;
+    }
+    if let Err(e) = window.emit(DRAWINGS_CHANGED_EVENT, event) {
+        eprintln!("Failed to emit {}: {}", DRAWINGS_CHANGED_EVENT, e);
     }
 }
 
@@ -126,14 +423,31 @@ pub async fn drawing_list(
     mgr.list_drawings(&symbol)
 }
 
+/// `origin` identifies the writer (a device id for mobile sync) and defaults
+/// to the calling window's label.
 #[tauri::command]
 pub async fn drawing_save(
     symbol: String,
     drawings: Vec<DrawingObject>,
+    origin: Option<String>,
+    cursor: Option<u64>,
+    window: tauri::Window,
     manager: tauri::State<'_, SharedDrawingManager>,
-) -> Result<(), String> {
-    let mgr = manager.read().await;
-    mgr.save_drawings(&symbol, &drawings)
+) -> Result<DrawingSaveResult, String> {
+    let origin = origin.unwrap_or_else(|| window.label().to_string());
+    let mut mgr = manager.write().await;
+    let result = mgr.save_drawings(&symbol, &drawings, &origin, cursor)?;
+    emit_changed(
+        &window,
+        &DrawingsChangedEvent {
+            kind: DrawingKind::Drawing,
+            symbol: Some(symbol),
+            object_ids: result.changed_ids.clone(),
+            origin,
+            cursor: result.cursor,
+        },
+    );
+    Ok(result)
 }
 
 #[tauri::command]
@@ -145,6 +459,17 @@ pub async fn drawing_sync(
     mgr.sync_drawings(&symbol)
 }
 
+#[tauri::command]
+pub async fn drawing_changes(
+    cursor: u64,
+    template_cursor: u64,
+    symbol: Option<String>,
+    manager: tauri::State<'_, SharedDrawingManager>,
+) -> Result<DrawingChanges, String> {
+    let mgr = manager.read().await;
+    Ok(mgr.changes_since(cursor, template_cursor, symbol.as_deref()))
+}
+
 #[tauri::command]
 pub async fn drawing_list_templates(
     manager: tauri::State<'_, SharedDrawingManager>,
@@ -156,8 +481,47 @@ pub async fn drawing_list_templates(
 #[tauri::command]
 pub async fn drawing_save_templates(
     templates: Vec<DrawingTemplate>,
+    origin: Option<String>,
+    cursor: Option<u64>,
+    window: tauri::Window,
+    manager: tauri::State<'_, SharedDrawingManager>,
+) -> Result<DrawingSaveResult, String> {
+    let origin = origin.unwrap_or_else(|| window.label().to_string());
+    let mut mgr = manager.write().await;
+    let result = mgr.save_templates(&templates, &origin, cursor)?;
+    emit_changed(
+        &window,
+        &DrawingsChangedEvent {
+            kind: DrawingKind::Template,
+            symbol: None,
+            object_ids: result.changed_ids.clone(),
+            origin,
+            cursor: result.cursor,
+        },
+    );
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn drawing_get_history(
+    object_id: String,
     manager: tauri::State<'_, SharedDrawingManager>,
-) -> Result<(), String> {
+) -> Result<DrawingHistory, String> {
     let mgr = manager.read().await;
-    mgr.save_templates(&templates)
+    mgr.history(&object_id)
+}
+
+#[tauri::command]
+pub async fn drawing_restore_version(
+    object_id: String,
+    version_id: String,
+    origin: Option<String>,
+    window: tauri::Window,
+    manager: tauri::State<'_, SharedDrawingManager>,
+) -> Result<RestoredDrawing, String> {
+    let origin = origin.unwrap_or_else(|| window.label().to_string());
+    let mut mgr = manager.write().await;
+    let restored = mgr.restore_version(&object_id, &version_id, &origin)?;
+    emit_changed(&window, &restored.changed_event(&origin));
+    Ok(restored)
 }
@@ -0,0 +1,465 @@
+//! Per-object revisioned storage for drawings and templates.
+//!
+//! Every object is a record with a stable id, a revision counter and a store
+//! sequence number. Deletions leave a tombstone so other windows and devices
+//! learn about them through `changed_since`. Edits made against an older
+//! revision are concurrent: the later edit wins (ties broken by origin) and
+//! the losing version is kept in the object's history, from which any version
+//! can be restored as a new revision.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Superseded and losing versions kept per object, oldest dropped first.
+pub const MAX_VERSIONS_PER_OBJECT: usize = 50;
+
+/// An object stored in a `DrawingStore`. The revision lives on the object so
+/// clients can send back the revision their edit was based on.
+pub trait SyncedObject: Clone + PartialEq {
+    fn object_id(&self) -> &str;
+    fn revision(&self) -> u64;
+    fn set_revision(&mut self, revision: u64);
+    /// When the client made the edit, in ms. Used to order concurrent edits.
+    fn edited_at(&self) -> Option<i64> {
+        None
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DrawingRecord<T> {
+    pub id: String,
+    /// Symbol for drawings, empty for templates.
+    pub scope: String,
+    pub revision: u64,
+    pub seq: u64,
+    pub created_seq: u64,
+    pub origin: String,
+    pub updated_at: i64,
+    pub deleted: bool,
+    /// `None` once deleted; the last value is in the history.
+    pub value: Option<T>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VersionReason {
+    /// Replaced by a later edit based on it.
+    Superseded,
+    /// Lost a last-writer-wins race against a concurrent edit.
+    LostConflict,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DrawingVersion<T> {
+    pub version_id: String,
+    pub object_id: String,
+    pub revision: u64,
+    pub origin: String,
+    pub updated_at: i64,
+    pub deleted: bool,
+    pub value: Option<T>,
+    pub reason: VersionReason,
+    pub recorded_at: i64,
+}
+
+/// An edit from one window or device.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DrawingChange<T> {
+    pub id: String,
+    /// Revision the edit was made against; 0 for a new object.
+    pub base_revision: u64,
+    pub updated_at: i64,
+    #[serde(default)]
+    pub deleted: bool,
+    #[serde(default)]
+    pub value: Option<T>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DrawingConflict {
+    pub object_id: String,
+    /// History entry holding the version that lost.
+    pub lost_version_id: String,
+    pub lost_origin: String,
+    pub kept_origin: String,
+    pub detected_at: i64,
+}
+
+#[derive(Debug, Clone)]
+pub enum DrawingOutcome {
+    Applied {
+        revision: u64,
+    },
+    Unchanged,
+    Conflicted {
+        revision: u64,
+        conflict: DrawingConflict,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DrawingStore<T> {
+    seq: u64,
+    records: HashMap<String, DrawingRecord<T>>,
+    #[serde(default)]
+    history: HashMap<String, Vec<DrawingVersion<T>>>,
+}
+
+impl<T> Default for DrawingStore<T> {
+    fn default() -> Self {
+        Self {
+            seq: 0,
+            records: HashMap::new(),
+            history: HashMap::new(),
+        }
+    }
+}
+
+impl<T: SyncedObject> DrawingStore<T> {
+    /// Sequence number of the latest change, used as a sync cursor.
+    pub fn cursor(&self) -> u64 {
+        self.seq
+    }
+
+    pub fn get(&self, id: &str) -> Option<&DrawingRecord<T>> {
+        self.records.get(id)
+    }
+
+    /// Live objects in a scope (all scopes when `None`), in creation order,
+    /// with their current revision filled in.
+    pub fn live(&self, scope: Option<&str>) -> Vec<T> {
+        let mut records: Vec<&DrawingRecord<T>> = self
+            .records
+            .values()
+            .filter(|r| !r.deleted && scope.map_or(true, |s| r.scope == s))
+            .collect();
+        records.sort_by_key(|r| r.created_seq);
+        records
+            .into_iter()
+            .filter_map(Self::current_value)
+            .collect()
+    }
+
+    /// Records, tombstones included, changed after `cursor`, oldest first.
+    pub fn changed_since(&self, cursor: u64, scope: Option<&str>) -> Vec<DrawingRecord<T>> {
+        let mut records: Vec<DrawingRecord<T>> = self
+            .records
+            .values()
+            .filter(|r| r.seq > cursor && scope.map_or(true, |s| r.scope == s))
+            .map(|r| DrawingRecord {
+                value: Self::current_value(r),
+                ..r.clone()
+            })
+            .collect();
+        records.sort_by_key(|r| r.seq);
+        records
+    }
+
+    pub fn history(&self, id: &str) -> Vec<DrawingVersion<T>> {
+        self.history.get(id).cloned().unwrap_or_default()
+    }
+
+    pub fn apply(
+        &mut self,
+        origin: &str,
+        scope: &str,
+        change: DrawingChange<T>,
+        now: i64,
+    ) -> DrawingOutcome {
+        let value = change.value.map(normalized);
+        let deleted = change.deleted || value.is_none();
+
+        let Some(stored) = self.records.get(&change.id) else {
+            if deleted {
+                return DrawingOutcome::Unchanged;
+            }
+            self.seq += 1;
+            self.records.insert(
+                change.id.clone(),
+                DrawingRecord {
+                    id: change.id,
+                    scope: scope.to_string(),
+                    revision: 1,
+                    seq: self.seq,
+                    created_seq: self.seq,
+                    origin: origin.to_string(),
+                    updated_at: change.updated_at,
+                    deleted: false,
+                    value,
+                },
+            );
+            return DrawingOutcome::Applied { revision: 1 };
+        };
+
+        if stored.deleted == deleted && stored.value == value {
+            return DrawingOutcome::Unchanged;
+        }
+
+        let concurrent = change.base_revision < stored.revision;
+        let incoming_wins = !concurrent
+            || (change.updated_at, origin) > (stored.updated_at, stored.origin.as_str());
+
+        if !incoming_wins {
+            let version_id = self.record_version(DrawingVersion {
+                version_id: String::new(),
+                object_id: change.id.clone(),
+                revision: change.base_revision,
+                origin: origin.to_string(),
+                updated_at: change.updated_at,
+                deleted,
+                value,
+                reason: VersionReason::LostConflict,
+                recorded_at: now,
+            });
+            let stored = &self.records[&change.id];
+            return DrawingOutcome::Conflicted {
+                revision: stored.revision,
+                conflict: DrawingConflict {
+                    object_id: change.id,
+                    lost_version_id: version_id,
+                    lost_origin: origin.to_string(),
+                    kept_origin: stored.origin.clone(),
+                    detected_at: now,
+                },
+            };
+        }
+
+        let previous = stored.clone();
+        let version_id = self.record_version(DrawingVersion {
+            version_id: String::new(),
+            object_id: previous.id.clone(),
+            revision: previous.revision,
+            origin: previous.origin.clone(),
+            updated_at: previous.updated_at,
+            deleted: previous.deleted,
+            value: previous.value,
+            reason: if concurrent {
+                VersionReason::LostConflict
+            } else {
+                VersionReason::Superseded
+            },
+            recorded_at: now,
+        });
+
+        self.seq += 1;
+        let record = self.records.get_mut(&change.id).expect("record exists");
+        record.revision += 1;
+        record.seq = self.seq;
+        record.origin = origin.to_string();
+        record.updated_at = change.updated_at;
+        record.deleted = deleted;
+        record.value = value;
+        if !deleted {
+            record.scope = scope.to_string();
+        }
+        let revision = record.revision;
+
+        if concurrent {
+            DrawingOutcome::Conflicted {
+                revision,
+                conflict: DrawingConflict {
+                    object_id: change.id,
+                    lost_version_id: version_id,
+                    lost_origin: previous.origin,
+                    kept_origin: origin.to_string(),
+                    detected_at: now,
+                },
+            }
+        } else {
+            DrawingOutcome::Applied { revision }
+        }
+    }
+
+    /// Makes a version from the history current again as a new revision. The
+    /// version being replaced goes into the history, so a restore can itself
+    /// be undone.
+    pub fn restore_version(
+        &mut self,
+        object_id: &str,
+        version_id: &str,
+        origin: &str,
+        now: i64,
+    ) -> Result<DrawingRecord<T>, String> {
+        let version = self
+            .history
+            .get(object_id)
+            .and_then(|versions| versions.iter().find(|v| v.version_id == version_id))
+            .cloned()
+            .ok_or_else(|| format!("Version {} of {} not found", version_id, object_id))?;
+        let Some(value) = version.value else {
+            return Err(format!(
+                "Version {} records a deletion and cannot be restored",
+                version_id
+            ));
+        };
+        let stored = self
+            .records
+            .get(object_id)
+            .ok_or_else(|| format!("Object {} not found", object_id))?;
+        let (scope, base_revision) = (stored.scope.clone(), stored.revision);
+
+        self.apply(
+            origin,
+            &scope,
+            DrawingChange {
+                id: object_id.to_string(),
+                base_revision,
+                updated_at: now,
+                deleted: false,
+                value: Some(value),
+            },
+            now,
+        );
+        let record = &self.records[object_id];
+        Ok(DrawingRecord {
+            value: Self::current_value(record),
+            ..record.clone()
+        })
+    }
+
+    fn record_version(&mut self, mut version: DrawingVersion<T>) -> String {
+        version.version_id = Uuid::new_v4().to_string();
+        let id = version.version_id.clone();
+        let versions = self.history.entry(version.object_id.clone()).or_default();
+        versions.push(version);
+        if versions.len() > MAX_VERSIONS_PER_OBJECT {
+            let excess = versions.len() - MAX_VERSIONS_PER_OBJECT;
+            versions.drain(..excess);
+        }
+        id
+    }
+
+    fn current_value(record: &DrawingRecord<T>) -> Option<T> {
+        record.value.clone().map(|mut value| {
+            value.set_revision(record.revision);
+            value
+        })
+    }
+}
+
+/// Values are stored without their revision so equality ignores it.
+fn normalized<T: SyncedObject>(mut value: T) -> T {
+    value.set_revision(0);
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Line {
+        id: String,
+        revision: u64,
+        y: f64,
+    }
+
+    impl SyncedObject for Line {
+        fn object_id(&self) -> &str {
+            &self.id
+        }
+        fn revision(&self) -> u64 {
+            self.revision
+        }
+        fn set_revision(&mut self, revision: u64) {
+            self.revision = revision;
+        }
+    }
+
+    fn edit(id: &str, base: u64, at: i64, y: f64) -> DrawingChange<Line> {
+        DrawingChange {
+            id: id.to_string(),
+            base_revision: base,
+            updated_at: at,
+            deleted: false,
+            value: Some(Line {
+                id: id.to_string(),
+                revision: base,
+                y,
+            }),
+        }
+    }
+
+    #[test]
+    fn concurrent_edits_to_different_objects_merge() {
+        let mut store = DrawingStore::default();
+        store.apply("main", "SOL", edit("a", 0, 1, 1.0), 1);
+        store.apply("main", "SOL", edit("b", 0, 2, 2.0), 2);
+        let cursor = store.cursor();
+
+        // Two windows each edit a different line against revision 1.
+        let first = store.apply("main", "SOL", edit("a", 1, 3, 10.0), 3);
+        let second = store.apply("chart-2", "SOL", edit("b", 1, 4, 20.0), 4);
+        assert!(matches!(first, DrawingOutcome::Applied { revision: 2 }));
+        assert!(matches!(second, DrawingOutcome::Applied { revision: 2 }));
+
+        let ys: Vec<f64> = store.live(Some("SOL")).iter().map(|l| l.y).collect();
+        assert_eq!(ys, vec![10.0, 20.0]);
+        assert_eq!(store.changed_since(cursor, None).len(), 2);
+        assert!(matches!(
+            store.apply("main", "SOL", edit("a", 2, 5, 10.0), 5),
+            DrawingOutcome::Unchanged
+        ));
+    }
+
+    #[test]
+    fn same_object_is_last_writer_wins_with_loser_restorable() {
+        let mut store = DrawingStore::default();
+        store.apply("main", "SOL", edit("a", 0, 1, 1.0), 1);
+        store.apply("phone", "SOL", edit("a", 1, 30, 3.0), 30);
+
+        // An older concurrent edit loses but is kept.
+        let DrawingOutcome::Conflicted { revision, conflict } =
+            store.apply("main", "SOL", edit("a", 1, 20, 2.0), 31)
+        else {
+            panic!("expected a conflict");
+        };
+        assert_eq!(revision, 2);
+        assert_eq!(conflict.kept_origin, "phone");
+        assert_eq!(store.live(None)[0].y, 3.0);
+
+        let restored = store
+            .restore_version("a", &conflict.lost_version_id, "main", 40)
+            .unwrap();
+        assert_eq!(restored.revision, 3);
+        assert_eq!(restored.value.unwrap().y, 2.0);
+        let reasons: Vec<VersionReason> = store.history("a").iter().map(|v| v.reason).collect();
+        assert_eq!(
+            reasons,
+            vec![
+                VersionReason::Superseded,
+                VersionReason::LostConflict,
+                VersionReason::Superseded
+            ]
+        );
+    }
+
+    #[test]
+    fn deletions_leave_tombstones() {
+        let mut store = DrawingStore::default();
+        store.apply("main", "SOL", edit("a", 0, 1, 1.0), 1);
+        let cursor = store.cursor();
+        store.apply(
+            "main",
+            "SOL",
+            DrawingChange {
+                id: "a".into(),
+                base_revision: 1,
+                updated_at: 2,
+                deleted: true,
+                value: None,
+            },
+            2,
+        );
+
+        assert!(store.live(Some("SOL")).is_empty());
+        let changed = store.changed_since(cursor, Some("SOL"));
+        assert!(changed[0].deleted && changed[0].value.is_none());
+        let deleted_version = store.history("a")[0].version_id.clone();
+        assert!(store
+            .restore_version("a", &deleted_version, "main", 3)
+            .is_ok());
+        assert_eq!(store.live(Some("SOL")).len(), 1);
+    }
+}
@@ -1,33 +1,236 @@
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::Duration;
 use thiserror::Error;
 
-#[derive(Debug, Error)]
-pub enum AppError {
-    #[error("Generic error: {0}")]
-    Generic(String),
+/// Backoff suggested for a retryable upstream failure that gave no hint.
+pub const DEFAULT_RETRY_AFTER_MS: u64 = 1_000;
+/// Backoff suggested when a provider rate limits without a `Retry-After`.
+pub const DEFAULT_RATE_LIMIT_RETRY_AFTER_MS: u64 = 5_000;
+
+/// The part of an error every category shares. `code` is stable and safe to
+/// branch on; `message` is for people and may change wording.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErrorInfo {
+    pub code: String,
+    pub message: String,
+    pub details: BTreeMap<String, Value>,
+}
+
+impl ErrorInfo {
+    fn new(code: &str, message: impl Into<String>) -> Self {
+        Self {
+            code: code.to_string(),
+            message: message.into(),
+            details: BTreeMap::new(),
+        }
+    }
+}
 
+impl fmt::Display for ErrorInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// Error returned by Tauri commands. Serializes to
+/// `{ kind, code, message, retryable, retryAfterMs?, provider?, details? }`
+/// so the frontend can branch on `kind`/`code` instead of parsing messages.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum AppError {
     #[error("Validation error: {0}")]
-    Validation(String),
+    Validation(ErrorInfo),
 
     #[error("Not found: {0}")]
-    NotFound(String),
+    NotFound(ErrorInfo),
+
+    #[error("Upstream error from {provider}: {info}")]
+    Upstream {
+        provider: String,
+        retryable: bool,
+        /// Only set when `retryable`.
+        retry_after_ms: Option<u64>,
+        info: ErrorInfo,
+    },
 
     #[error("Unauthorized: {0}")]
-    Unauthorized(String),
+    Unauthorized(ErrorInfo),
+
+    #[error("Rate limited: {info}")]
+    RateLimited {
+        provider: Option<String>,
+        retry_after_ms: u64,
+        info: ErrorInfo,
+    },
+
+    #[error("Internal error: {0}")]
+    Internal(ErrorInfo),
+
+    /// The command is not available while the app is locked in kiosk mode.
+    #[error("Kiosk mode: {0}")]
+    KioskMode(ErrorInfo),
+}
+
+impl AppError {
+    pub fn validation(message: impl Into<String>) -> Self {
+        Self::Validation(ErrorInfo::new("validation_failed", message))
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::NotFound(ErrorInfo::new("not_found", message))
+    }
+
+    pub fn unauthorized(message: impl Into<String>) -> Self {
+        Self::Unauthorized(ErrorInfo::new("unauthorized", message))
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::Internal(ErrorInfo::new("internal", message))
+    }
+
+    pub fn kiosk_mode(message: impl Into<String>) -> Self {
+        Self::KioskMode(ErrorInfo::new("kiosk_mode_active", message))
+    }
+
+    pub fn upstream(
+        provider: impl Into<String>,
+        message: impl Into<String>,
+        retryable: bool,
+    ) -> Self {
+        Self::Upstream {
+            provider: provider.into(),
+            retryable,
+            retry_after_ms: retryable.then_some(DEFAULT_RETRY_AFTER_MS),
+            info: ErrorInfo::new("upstream_error", message),
+        }
+    }
+
+    pub fn rate_limited(provider: Option<String>, message: impl Into<String>) -> Self {
+        Self::RateLimited {
+            provider,
+            retry_after_ms: DEFAULT_RATE_LIMIT_RETRY_AFTER_MS,
+            info: ErrorInfo::new("rate_limited", message),
+        }
+    }
+
+    pub fn with_code(mut self, code: impl Into<String>) -> Self {
+        self.info_mut().code = code.into();
+        self
+    }
+
+    /// Adds a structured detail. Values that fail to serialize are dropped.
+    pub fn with_detail(mut self, key: impl Into<String>, value: impl Serialize) -> Self {
+        if let Ok(value) = serde_json::to_value(value) {
+            self.info_mut().details.insert(key.into(), value);
+        }
+        self
+    }
 
-    #[error("Database error: {0}")]
-    Database(#[from] sqlx::Error),
+    /// Overrides the suggested backoff. Ignored for errors that are not retryable.
+    pub fn with_retry_after(mut self, delay: Duration) -> Self {
+        let millis = delay.as_millis().min(u64::MAX as u128) as u64;
+        match &mut self {
+            Self::Upstream {
+                retryable: true,
+                retry_after_ms,
+                ..
+            } => *retry_after_ms = Some(millis),
+            Self::RateLimited { retry_after_ms, .. } => *retry_after_ms = millis,
+            _ => {}
+        }
+        self
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Validation(_) => "validation",
+            Self::NotFound(_) => "not_found",
+            Self::Upstream { .. } => "upstream",
+            Self::Unauthorized(_) => "unauthorized",
+            Self::RateLimited { .. } => "rate_limited",
+            Self::Internal(_) => "internal",
+            Self::KioskMode(_) => "kiosk_mode",
+        }
+    }
 
-    #[error("IO error: {0}")]
-    Io(#[from] std::io::Error),
+    pub fn info(&self) -> &ErrorInfo {
+        match self {
+            Self::Validation(info)
+            | Self::NotFound(info)
+            | Self::Unauthorized(info)
+            | Self::Internal(info)
+            | Self::KioskMode(info)
+            | Self::Upstream { info, .. }
+            | Self::RateLimited { info, .. } => info,
+        }
+    }
+
+    fn info_mut(&mut self) -> &mut ErrorInfo {
+        match self {
+            Self::Validation(info)
+            | Self::NotFound(info)
+            | Self::Unauthorized(info)
+            | Self::Internal(info)
+            | Self::KioskMode(info)
+            | Self::Upstream { info, .. }
+            | Self::RateLimited { info, .. } => info,
+        }
+    }
 
-    #[error("Serialization error: {0}")]
-    Serialization(#[from] serde_json::Error),
+    pub fn code(&self) -> &str {
+        &self.info().code
+    }
+
+    pub fn retryable(&self) -> bool {
+        match self {
+            Self::Upstream { retryable, .. } => *retryable,
+            Self::RateLimited { .. } => true,
+            _ => false,
+        }
+    }
+
+    pub fn retry_after_ms(&self) -> Option<u64> {
+        match self {
+            Self::Upstream { retry_after_ms, .. } => *retry_after_ms,
+            Self::RateLimited { retry_after_ms, .. } => Some(*retry_after_ms),
+            _ => None,
+        }
+    }
 
-    #[error("Network error: {0}")]
-    Network(#[from] reqwest::Error),
+    fn provider(&self) -> Option<&str> {
+        match self {
+            Self::Upstream { provider, .. } => Some(provider),
+            Self::RateLimited { provider, .. } => provider.as_deref(),
+            _ => None,
+        }
+    }
+}
 
-    #[error("Solana client error: {0}")]
-    SolanaClient(String),
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let info = self.info();
+        let mut state = serializer.serialize_struct("AppError", 7)?;
+        state.serialize_field("kind", self.kind())?;
+        state.serialize_field("code", &info.code)?;
+        state.serialize_field("message", &info.message)?;
+        state.serialize_field("retryable", &self.retryable())?;
+        match self.retry_after_ms() {
+            Some(retry_after_ms) => state.serialize_field("retryAfterMs", &retry_after_ms)?,
+            None => state.skip_field("retryAfterMs")?,
+        }
+        match self.provider() {
+            Some(provider) => state.serialize_field("provider", provider)?,
+            None => state.skip_field("provider")?,
+        }
+        if info.details.is_empty() {
+            state.skip_field("details")?;
+        } else {
+            state.serialize_field("details", &info.details)?;
+        }
+        state.end()
+    }
 }
 
 impl From<AppError> for String {
@@ -35,3 +238,207 @@ impl From<AppError> for String {
         err.to_string()
     }
 }
+
+/// Lets commands migrate one at a time: helpers still returning `String`
+/// errors can be used with `?`, and surface as internal errors.
+impl From<String> for AppError {
+    fn from(message: String) -> Self {
+        Self::internal(message)
+    }
+}
+
+impl From<sqlx::Error> for AppError {
+    fn from(err: sqlx::Error) -> Self {
+        match err {
+            sqlx::Error::RowNotFound => {
+                Self::not_found("Record not found").with_code("record_not_found")
+            }
+            err => Self::internal(format!("Database error: {err}")).with_code("database_error"),
+        }
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(err: std::io::Error) -> Self {
+        Self::internal(format!("IO error: {err}")).with_code("io_error")
+    }
+}
+
+impl From<serde_json::Error> for AppError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::internal(format!("Serialization error: {err}")).with_code("serialization_error")
+    }
+}
+
+impl From<reqwest::Error> for AppError {
+    fn from(err: reqwest::Error) -> Self {
+        let provider = err
+            .url()
+            .and_then(|url| url.host_str())
+            .unwrap_or("http")
+            .to_string();
+        let message = err.to_string();
+
+        if let Some(status) = err.status() {
+            let error = match status.as_u16() {
+                429 => Self::rate_limited(Some(provider), message),
+                401 | 403 => {
+                    Self::upstream(provider, message, false).with_code("upstream_unauthorized")
+                }
+                500..=599 => {
+                    Self::upstream(provider, message, true).with_code("upstream_unavailable")
+                }
+                _ => Self::upstream(provider, message, false).with_code("upstream_rejected"),
+            };
+            return error.with_detail("status", status.as_u16());
+        }
+
+        if err.is_timeout() {
+            Self::upstream(provider, message, true).with_code("upstream_timeout")
+        } else if err.is_connect() {
+            Self::upstream(provider, message, true).with_code("upstream_unreachable")
+        } else if err.is_decode() {
+            Self::upstream(provider, message, false).with_code("upstream_invalid_response")
+        } else {
+            Self::upstream(provider, message, false)
+        }
+    }
+}
+
+impl From<crate::security::keystore::KeystoreError> for AppError {
+    fn from(err: crate::security::keystore::KeystoreError) -> Self {
+        use crate::security::keystore::KeystoreError;
+
+        match err {
+            KeystoreError::NotFound => {
+                Self::not_found("Secret not found").with_code("secret_not_found")
+            }
+            KeystoreError::Decryption => {
+                Self::unauthorized("Unable to decrypt secret").with_code("decryption_failed")
+            }
+            KeystoreError::Keyring(err) => {
+                Self::internal(format!("Keyring error: {err}")).with_code("keyring_unavailable")
+            }
+            err => Self::internal(format!("Keystore error: {err}")).with_code("keystore_error"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn validation_errors_serialize_without_retry_fields() {
+        let err = AppError::validation("Amount must be positive")
+            .with_code("invalid_amount")
+            .with_detail("field", "amount");
+
+        assert_eq!(
+            serde_json::to_value(&err).unwrap(),
+            json!({
+                "kind": "validation",
+                "code": "invalid_amount",
+                "message": "Amount must be positive",
+                "retryable": false,
+                "details": { "field": "amount" },
+            })
+        );
+    }
+
+    #[test]
+    fn retryable_upstream_errors_carry_provider_and_backoff() {
+        let err = AppError::upstream("solana_rpc", "RPC request timed out", true)
+            .with_code("upstream_timeout");
+
+        assert_eq!(
+            serde_json::to_value(&err).unwrap(),
+            json!({
+                "kind": "upstream",
+                "code": "upstream_timeout",
+                "message": "RPC request timed out",
+                "retryable": true,
+                "retryAfterMs": DEFAULT_RETRY_AFTER_MS,
+                "provider": "solana_rpc",
+            })
+        );
+
+        let permanent = AppError::upstream("jupiter", "Route not found", false)
+            .with_retry_after(Duration::from_secs(30));
+        let value = serde_json::to_value(&permanent).unwrap();
+        assert_eq!(value["retryable"], json!(false));
+        assert!(value.get("retryAfterMs").is_none());
+    }
+
+    #[test]
+    fn rate_limits_use_the_hinted_backoff() {
+        let err = AppError::rate_limited(Some("birdeye".to_string()), "Too many requests")
+            .with_retry_after(Duration::from_secs(12));
+
+        assert_eq!(
+            serde_json::to_value(&err).unwrap(),
+            json!({
+                "kind": "rate_limited",
+                "code": "rate_limited",
+                "message": "Too many requests",
+                "retryable": true,
+                "retryAfterMs": 12_000,
+                "provider": "birdeye",
+            })
+        );
+        assert_eq!(
+            AppError::rate_limited(None, "slow down").retry_after_ms(),
+            Some(DEFAULT_RATE_LIMIT_RETRY_AFTER_MS)
+        );
+    }
+
+    #[test]
+    fn every_kind_has_a_stable_name() {
+        let kinds: Vec<_> = [
+            AppError::validation("x"),
+            AppError::not_found("x"),
+            AppError::upstream("p", "x", false),
+            AppError::unauthorized("x"),
+            AppError::rate_limited(None, "x"),
+            AppError::internal("x"),
+            AppError::kiosk_mode("x"),
+        ]
+        .iter()
+        .map(|err| serde_json::to_value(err).unwrap()["kind"].clone())
+        .collect();
+
+        assert_eq!(
+            kinds,
+            vec![
+                json!("validation"),
+                json!("not_found"),
+                json!("upstream"),
+                json!("unauthorized"),
+                json!("rate_limited"),
+                json!("internal"),
+                json!("kiosk_mode"),
+            ]
+        );
+    }
+
+    #[test]
+    fn common_sources_map_to_categories() {
+        let missing: AppError = sqlx::Error::RowNotFound.into();
+        assert_eq!(missing.kind(), "not_found");
+        assert_eq!(missing.code(), "record_not_found");
+
+        let pool: AppError = sqlx::Error::PoolTimedOut.into();
+        assert_eq!(pool.code(), "database_error");
+
+        let locked: AppError = crate::security::keystore::KeystoreError::Decryption.into();
+        assert_eq!(locked.kind(), "unauthorized");
+
+        let legacy: AppError = "Trading module not initialized".to_string().into();
+        assert_eq!(legacy.kind(), "internal");
+        assert_eq!(
+            String::from(legacy),
+            "Internal error: Trading module not initialized"
+        );
+    }
+}
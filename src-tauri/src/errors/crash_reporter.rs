@@ -1,4 +1,5 @@
-use crate::logger::{ComprehensiveLogger, LogLevel, SharedLogger};
+use crate::config::DataPaths;
+use crate::logger::{Breadcrumb, ComprehensiveLogger, LogLevel, SharedLogger};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::fs::File;
@@ -18,6 +19,10 @@ pub struct CrashReport {
     pub system_state: serde_json::Value,
     pub user_actions: Option<Vec<String>>,
     pub logs: Vec<crate::logger::LogEntry>,
+    #[serde(default)]
+    pub breadcrumbs: Vec<Breadcrumb>,
+    #[serde(default)]
+    pub task_name: Option<String>,
     pub app_version: String,
     pub environment: String,
 }
@@ -33,7 +38,7 @@ pub struct CrashReporter {
 
 impl CrashReporter {
     pub fn new(app: &AppHandle, logger: SharedLogger) -> Result<Self, std::io::Error> {
-        let mut report_dir = app.path().app_data_dir().map_err(|err| {
+        let mut report_dir = app.app_data_root().map_err(|err| {
             std::io::Error::new(std::io::ErrorKind::NotFound, "App data dir not found")
         })?;
 
@@ -52,6 +57,56 @@ impl CrashReporter {
         message: &str,
         stack_trace: Option<String>,
         system_state: serde_json::Value,
+    ) -> Result<CrashReport, String> {
+        self.build_report(message, stack_trace, system_state, None)
+    }
+
+    /// Records a panic caught by the task supervisor.
+    pub fn capture_task_panic(
+        &self,
+        task_name: &str,
+        message: &str,
+        restarts: u32,
+    ) -> Result<CrashReport, String> {
+        let system_state = serde_json::json!({
+            "timestamp": Utc::now(),
+            "source": "background_task",
+            "restarts": restarts,
+        });
+        self.build_report(
+            &format!("Background task '{}' panicked: {}", task_name, message),
+            None,
+            system_state,
+            Some(task_name.to_string()),
+        )
+    }
+
+    /// Records the first failure in a streak of failed runs of a scheduled job.
+    pub fn capture_job_failure(
+        &self,
+        job_name: &str,
+        message: &str,
+        duration_ms: u64,
+    ) -> Result<CrashReport, String> {
+        let system_state = serde_json::json!({
+            "timestamp": Utc::now(),
+            "source": "scheduled_job",
+            "duration_ms": duration_ms,
+        });
+        self.build_report(
+            &format!("Scheduled job '{}' failed: {}", job_name, message),
+            None,
+            system_state,
+            Some(job_name.to_string()),
+        )
+    }
+
+    fn build_report(
+        &self,
+        message: &str,
+        stack_trace: Option<String>,
+        system_state: serde_json::Value,
+        task_name: Option<String>,
     ) -> Result<CrashReport, String> {
         let crash_id = Uuid::new_v4().to_string();
         let timestamp = Utc::now();
@@ -60,6 +115,7 @@ impl CrashReporter {
             std::env::var("ENVIRONMENT").unwrap_or_else(|_| "development".to_string());
 
         let logs = self.logger.get_recent_logs(1000, None);
+        let breadcrumbs = self.logger.get_breadcrumbs(None);
 
         let report = CrashReport {
             crash_id: crash_id.clone(),
@@ -69,6 +125,8 @@ impl CrashReporter {
             system_state,
             user_actions: None,
             logs,
+            breadcrumbs,
+            task_name: task_name.clone(),
             app_version,
             environment,
         };
@@ -81,6 +139,7 @@ impl CrashReporter {
             Some(serde_json::json!({
                 "crash_id": crash_id,
                 "message": message,
+                "task": task_name,
             })),
         );
 
@@ -1,7 +1,11 @@
 pub mod app_error;
 pub mod crash_reporter;
 pub mod runtime_handler;
+pub mod shutdown;
+pub mod supervisor;
 
 pub use app_error::*;
 pub use crash_reporter::*;
 pub use runtime_handler::*;
+pub use shutdown::*;
+pub use supervisor::*;
@@ -0,0 +1,393 @@
+use futures_util::future::BoxFuture;
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, RunEvent, Runtime};
+use tokio::sync::{watch, Notify, OnceCell};
+
+/// How long background tasks get to stop once shutdown is signalled.
+pub const TASK_DRAIN_TIMEOUT: Duration = Duration::from_secs(5);
+/// Per flush; a hung flush must not keep the process alive.
+pub const FLUSH_TIMEOUT: Duration = Duration::from_secs(3);
+
+pub type SharedShutdownCoordinator = Arc<ShutdownCoordinator>;
+
+type FlushFn = Box<dyn Fn() -> BoxFuture<'static, Result<(), String>> + Send + Sync>;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FlushFailure {
+    pub name: String,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShutdownReport {
+    /// Tasks still running when the drain timeout expired.
+    pub unacknowledged_tasks: Vec<String>,
+    /// Flushes that completed, in the order they ran.
+    pub flushed: Vec<String>,
+    pub failed_flushes: Vec<FlushFailure>,
+    pub elapsed_ms: u64,
+}
+
+#[derive(Default)]
+struct TaskRegistry {
+    live: Mutex<HashMap<u64, &'static str>>,
+    next_id: AtomicU64,
+    changed: Notify,
+}
+
+impl TaskRegistry {
+    fn register(self: &Arc<Self>, name: &'static str) -> TaskRegistration {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.live.lock().insert(id, name);
+        TaskRegistration {
+            registry: self.clone(),
+            id,
+        }
+    }
+
+    async fn wait_idle(&self) {
+        loop {
+            if self.live.lock().is_empty() {
+                return;
+            }
+            self.changed.notified().await;
+        }
+    }
+
+    fn live_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .live
+            .lock()
+            .values()
+            .map(|name| name.to_string())
+            .collect();
+        names.sort();
+        names
+    }
+}
+
+/// Dropping the registration is how a task acknowledges shutdown.
+struct TaskRegistration {
+    registry: Arc<TaskRegistry>,
+    id: u64,
+}
+
+impl Drop for TaskRegistration {
+    fn drop(&mut self) {
+        self.registry.live.lock().remove(&self.id);
+        self.registry.changed.notify_one();
+    }
+}
+
+/// Held by a background task for as long as it runs. `cancelled()` resolves
+/// once shutdown starts; the task acknowledges by dropping the listener.
+pub struct ShutdownListener {
+    signal: Option<watch::Receiver<bool>>,
+    _registration: Option<TaskRegistration>,
+}
+
+impl ShutdownListener {
+    /// A listener that never fires, for tasks started without a coordinator.
+    pub fn detached() -> Self {
+        Self {
+            signal: None,
+            _registration: None,
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.signal.as_ref().is_some_and(|signal| *signal.borrow())
+    }
+
+    pub async fn cancelled(&mut self) {
+        let Some(signal) = self.signal.as_mut() else {
+            return std::future::pending().await;
+        };
+        loop {
+            if *signal.borrow_and_update() {
+                return;
+            }
+            if signal.changed().await.is_err() {
+                return std::future::pending().await;
+            }
+        }
+    }
+}
+
+/// Two-phase shutdown: signal every registered task and wait (bounded) for
+/// them to stop, then run the registered flushes in registration order.
+pub struct ShutdownCoordinator {
+    signal: watch::Sender<bool>,
+    registry: Arc<TaskRegistry>,
+    flushes: Mutex<Vec<(&'static str, FlushFn)>>,
+    report: OnceCell<ShutdownReport>,
+}
+
+impl Default for ShutdownCoordinator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ShutdownCoordinator {
+    pub fn new() -> Self {
+        let (signal, _) = watch::channel(false);
+        Self {
+            signal,
+            registry: Arc::new(TaskRegistry::default()),
+            flushes: Mutex::new(Vec::new()),
+            report: OnceCell::new(),
+        }
+    }
+
+    pub fn listener(&self, task: &'static str) -> ShutdownListener {
+        ShutdownListener {
+            signal: Some(self.signal.subscribe()),
+            _registration: Some(self.registry.register(task)),
+        }
+    }
+
+    pub fn register_flush<F, Fut>(&self, name: &'static str, flush: F)
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), String>> + Send + 'static,
+    {
+        self.flushes
+            .lock()
+            .push((name, Box::new(move || Box::pin(flush()))));
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.report.initialized()
+    }
+
+    /// Runs the shutdown once; later and concurrent callers get the same report.
+    pub async fn shutdown(&self) -> ShutdownReport {
+        self.shutdown_with(TASK_DRAIN_TIMEOUT, FLUSH_TIMEOUT).await
+    }
+
+    pub async fn shutdown_with(
+        &self,
+        drain_timeout: Duration,
+        flush_timeout: Duration,
+    ) -> ShutdownReport {
+        self.report
+            .get_or_init(|| self.run(drain_timeout, flush_timeout))
+            .await
+            .clone()
+    }
+
+    async fn run(&self, drain_timeout: Duration, flush_timeout: Duration) -> ShutdownReport {
+        let started = Instant::now();
+        let mut report = ShutdownReport::default();
+
+        self.signal.send_replace(true);
+        if tokio::time::timeout(drain_timeout, self.registry.wait_idle())
+            .await
+            .is_err()
+        {
+            report.unacknowledged_tasks = self.registry.live_names();
+        }
+
+        let flushes = std::mem::take(&mut *self.flushes.lock());
+        for (name, flush) in flushes {
+            match tokio::time::timeout(flush_timeout, flush()).await {
+                Ok(Ok(())) => report.flushed.push(name.to_string()),
+                Ok(Err(error)) => report.failed_flushes.push(FlushFailure {
+                    name: name.to_string(),
+                    error,
+                }),
+                Err(_) => report.failed_flushes.push(FlushFailure {
+                    name: name.to_string(),
+                    error: format!("timed out after {}ms", flush_timeout.as_millis()),
+                }),
+            }
+        }
+
+        report.elapsed_ms = started.elapsed().as_millis() as u64;
+        report
+    }
+}
+
+/// A listener for `task`, or a detached one when no coordinator is managed.
+pub fn shutdown_listener<R: Runtime>(app: &AppHandle<R>, task: &'static str) -> ShutdownListener {
+    match app.try_state::<SharedShutdownCoordinator>() {
+        Some(coordinator) => coordinator.listener(task),
+        None => ShutdownListener::detached(),
+    }
+}
+
+pub fn register_flush<R, F, Fut>(app: &AppHandle<R>, name: &'static str, flush: F)
+where
+    R: Runtime,
+    F: Fn() -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<(), String>> + Send + 'static,
+{
+    if let Some(coordinator) = app.try_state::<SharedShutdownCoordinator>() {
+        coordinator.register_flush(name, flush);
+    }
+}
+
+async fn shutdown_app<R: Runtime>(app: &AppHandle<R>) {
+    let Some(coordinator) = app
+        .try_state::<SharedShutdownCoordinator>()
+        .map(|state| state.inner().clone())
+    else {
+        return;
+    };
+    let report = coordinator.shutdown().await;
+    eprintln!(
+        "[shutdown] finished in {}ms; flushed {:?}",
+        report.elapsed_ms, report.flushed
+    );
+    if !report.unacknowledged_tasks.is_empty() {
+        eprintln!(
+            "[shutdown] tasks still running at timeout: {:?}",
+            report.unacknowledged_tasks
+        );
+    }
+    for failure in &report.failed_flushes {
+        eprintln!(
+            "[shutdown] flush '{}' failed: {}",
+            failure.name, failure.error
+        );
+    }
+}
+
+/// Exits the app once the coordinated shutdown has finished.
+pub fn request_exit<R: Runtime>(app: &AppHandle<R>, code: i32) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        shutdown_app(&app).await;
+        app.exit(code);
+    });
+}
+
+/// Restarts the app once the coordinated shutdown has finished.
+pub fn request_restart<R: Runtime>(app: &AppHandle<R>) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        shutdown_app(&app).await;
+        app.restart();
+    });
+}
+
+/// Holds back any exit that did not come through the coordinator, runs the
+/// shutdown, then exits with the requested code.
+pub fn handle_run_event<R: Runtime>(app: &AppHandle<R>, event: &RunEvent) {
+    if let RunEvent::ExitRequested { code, api, .. } = event {
+        let complete = match app.try_state::<SharedShutdownCoordinator>() {
+            Some(coordinator) => coordinator.is_complete(),
+            None => true,
+        };
+        if !complete {
+            api.prevent_exit();
+            request_exit(app, code.unwrap_or(0));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicBool;
+
+    const SHORT: Duration = Duration::from_millis(200);
+
+    #[tokio::test]
+    async fn flushes_run_after_tasks_stop() {
+        let coordinator = Arc::new(ShutdownCoordinator::new());
+        let stopped = Arc::new(AtomicBool::new(false));
+        let order = Arc::new(Mutex::new(Vec::new()));
+
+        let mut listener = coordinator.listener("writer");
+        let task_stopped = stopped.clone();
+        let task = tokio::spawn(async move {
+            listener.cancelled().await;
+            // Finishes its in-flight write before acknowledging.
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            task_stopped.store(true, Ordering::SeqCst);
+        });
+
+        for name in ["cache", "wal_checkpoint"] {
+            let stopped = stopped.clone();
+            let order = order.clone();
+            coordinator.register_flush(name, move || {
+                let stopped = stopped.clone();
+                let order = order.clone();
+                async move {
+                    order.lock().push((name, stopped.load(Ordering::SeqCst)));
+                    Ok(())
+                }
+            });
+        }
+
+        let report = coordinator.shutdown_with(SHORT, SHORT).await;
+        task.await.unwrap();
+
+        assert!(report.unacknowledged_tasks.is_empty());
+        assert_eq!(report.flushed, vec!["cache", "wal_checkpoint"]);
+        assert_eq!(
+            *order.lock(),
+            vec![("cache", true), ("wal_checkpoint", true)]
+        );
+        assert!(coordinator.is_complete());
+    }
+
+    #[tokio::test]
+    async fn stuck_tasks_and_flushes_time_out() {
+        let coordinator = Arc::new(ShutdownCoordinator::new());
+        // Never checks the signal, so never acknowledges.
+        let stuck = coordinator.listener("stuck_loop");
+        let mut cooperative = coordinator.listener("cooperative_loop");
+        tokio::spawn(async move {
+            cooperative.cancelled().await;
+        });
+
+        coordinator.register_flush("hangs", || async {
+            std::future::pending::<()>().await;
+            Ok(())
+        });
+        coordinator.register_flush("fails", || async { Err("disk full".to_string()) });
+        coordinator.register_flush("succeeds", || async { Ok(()) });
+
+        let started = Instant::now();
+        let report = coordinator.shutdown_with(SHORT, SHORT).await;
+        assert!(started.elapsed() < SHORT * 4);
+
+        assert_eq!(report.unacknowledged_tasks, vec!["stuck_loop"]);
+        assert_eq!(report.flushed, vec!["succeeds"]);
+        let failed: Vec<_> = report
+            .failed_flushes
+            .iter()
+            .map(|failure| failure.name.as_str())
+            .collect();
+        assert_eq!(failed, vec!["hangs", "fails"]);
+
+        // A second request reuses the first run rather than waiting again.
+        let again = coordinator.shutdown_with(SHORT, SHORT).await;
+        assert_eq!(again.flushed, report.flushed);
+        drop(stuck);
+    }
+
+    #[tokio::test]
+    async fn late_listeners_see_shutdown_immediately() {
+        let coordinator = ShutdownCoordinator::new();
+        coordinator.shutdown_with(SHORT, SHORT).await;
+
+        let mut listener = coordinator.listener("late");
+        assert!(listener.is_cancelled());
+        tokio::time::timeout(SHORT, listener.cancelled())
+            .await
+            .expect("already cancelled");
+        assert!(!ShutdownListener::detached().is_cancelled());
+    }
+}
@@ -0,0 +1,326 @@
+use crate::errors::{shutdown_listener, SharedCrashReporter, ShutdownListener};
+use crate::logger::SharedLogger;
+use futures_util::FutureExt;
+use std::any::Any;
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+const MAX_RESTARTS: u32 = 5;
+
+const DEFAULT_POLICY: RestartPolicy = RestartPolicy {
+    initial_backoff: INITIAL_BACKOFF,
+    max_backoff: MAX_BACKOFF,
+    max_restarts: MAX_RESTARTS,
+    // A run that outlasts the longest backoff is treated as healthy, so the next
+    // panic starts a fresh restart budget instead of counting towards the old one.
+    healthy_run: MAX_BACKOFF,
+};
+
+#[derive(Debug, Clone, Copy)]
+struct RestartPolicy {
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    max_restarts: u32,
+    healthy_run: Duration,
+}
+
+/// Restart bookkeeping for one supervised task.
+#[derive(Debug)]
+struct Backoff {
+    policy: RestartPolicy,
+    restarts: u32,
+    delay: Duration,
+}
+
+impl Backoff {
+    fn new(policy: RestartPolicy) -> Self {
+        Self {
+            policy,
+            restarts: 0,
+            delay: policy.initial_backoff,
+        }
+    }
+
+    /// Records how long the attempt that just panicked ran for, resetting the
+    /// restart count and delay when it ran long enough to count as healthy.
+    fn observe_run(&mut self, ran_for: Duration) {
+        if ran_for >= self.policy.healthy_run {
+            self.restarts = 0;
+            self.delay = self.policy.initial_backoff;
+        }
+    }
+
+    /// The delay before the next attempt, or `None` once the restart limit is reached.
+    fn next_delay(&mut self) -> Option<Duration> {
+        if self.restarts >= self.policy.max_restarts {
+            return None;
+        }
+        let delay = self.delay;
+        self.restarts += 1;
+        self.delay = (delay * 2).min(self.policy.max_backoff);
+        Some(delay)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TaskEvent {
+    Stopped,
+    Exited,
+    Panicked { message: String, restarts: u32 },
+    GaveUp,
+}
+
+/// Runs a long-lived background task, restarting it with exponential backoff when it
+/// panics. Each panic is recorded as a crash report tagged with the task name.
+///
+/// `factory` is called once per attempt so the task can rebuild its state (cloned
+/// handles, timers) after a restart. A task that returns normally is not restarted.
+/// The restart budget and backoff reset once an attempt runs for longer than the
+/// maximum backoff, so occasional panics in a long-lived task never exhaust it.
+///
+/// On shutdown the task is dropped at its next await point, which counts as its
+/// acknowledgement to the shutdown coordinator.
+pub fn spawn_supervised<F, Fut>(app: &AppHandle, name: &'static str, factory: F)
+where
+    F: Fn() -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let app = app.clone();
+    let shutdown = shutdown_listener(&app, name);
+    tauri::async_runtime::spawn(async move {
+        supervise(name, factory, shutdown, DEFAULT_POLICY, |event| {
+            report_event(&app, name, event)
+        })
+        .await;
+    });
+}
+
+async fn supervise<F, Fut>(
+    name: &'static str,
+    factory: F,
+    mut shutdown: ShutdownListener,
+    policy: RestartPolicy,
+    mut on_event: impl FnMut(TaskEvent),
+) where
+    F: Fn() -> Fut,
+    Fut: Future<Output = ()>,
+{
+    let mut backoff = Backoff::new(policy);
+
+    loop {
+        let started = Instant::now();
+        let outcome = tokio::select! {
+            outcome = AssertUnwindSafe(factory()).catch_unwind() => outcome,
+            _ = shutdown.cancelled() => {
+                on_event(TaskEvent::Stopped);
+                return;
+            }
+        };
+        let payload = match outcome {
+            Ok(()) => {
+                on_event(TaskEvent::Exited);
+                return;
+            }
+            Err(payload) => payload,
+        };
+
+        backoff.observe_run(started.elapsed());
+        let message = panic_message(payload.as_ref());
+        eprintln!(
+            "[supervisor] task '{}' panicked (restart {}/{}): {}",
+            name, backoff.restarts, policy.max_restarts, message
+        );
+        on_event(TaskEvent::Panicked {
+            message,
+            restarts: backoff.restarts,
+        });
+
+        let Some(delay) = backoff.next_delay() else {
+            eprintln!(
+                "[supervisor] task '{}' exceeded {} restarts; giving up",
+                name, policy.max_restarts
+            );
+            on_event(TaskEvent::GaveUp);
+            return;
+        };
+
+        tokio::select! {
+            _ = tokio::time::sleep(delay) => {}
+            _ = shutdown.cancelled() => return,
+        }
+    }
+}
+
+fn report_event(app: &AppHandle, name: &str, event: TaskEvent) {
+    match event {
+        TaskEvent::Stopped => breadcrumb(app, name, "task stopped for shutdown", None),
+        TaskEvent::Exited => breadcrumb(app, name, "task exited", None),
+        TaskEvent::Panicked { message, restarts } => {
+            breadcrumb(
+                app,
+                name,
+                "task panicked",
+                Some(serde_json::json!({ "message": message, "restarts": restarts })),
+            );
+            if let Some(reporter) = app.try_state::<SharedCrashReporter>() {
+                if let Err(err) = reporter.capture_task_panic(name, &message, restarts) {
+                    eprintln!(
+                        "[supervisor] failed to record crash for '{}': {}",
+                        name, err
+                    );
+                }
+            }
+        }
+        TaskEvent::GaveUp => {}
+    }
+}
+
+fn breadcrumb(app: &AppHandle, task: &str, message: &str, data: Option<serde_json::Value>) {
+    if let Some(logger) = app.try_state::<SharedLogger>() {
+        logger.add_breadcrumb(&format!("task:{}", task), message, data);
+    }
+}
+
+pub(crate) fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic payload".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::ShutdownCoordinator;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    const TEST_POLICY: RestartPolicy = RestartPolicy {
+        initial_backoff: Duration::from_millis(5),
+        max_backoff: Duration::from_millis(20),
+        max_restarts: 3,
+        healthy_run: Duration::from_millis(100),
+    };
+
+    async fn run<Fut: Future<Output = ()>>(factory: impl Fn() -> Fut) -> Vec<TaskEvent> {
+        let mut events = Vec::new();
+        supervise(
+            "test",
+            factory,
+            ShutdownListener::detached(),
+            TEST_POLICY,
+            |event| events.push(event),
+        )
+        .await;
+        events
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        let mut backoff = Backoff::new(TEST_POLICY);
+        let delays: Vec<_> = std::iter::from_fn(|| backoff.next_delay()).collect();
+        assert_eq!(delays, [5, 10, 20].map(Duration::from_millis).to_vec());
+        assert_eq!(backoff.restarts, 3);
+        assert_eq!(backoff.delay, TEST_POLICY.max_backoff);
+    }
+
+    #[test]
+    fn healthy_run_resets_restarts_and_backoff() {
+        let mut backoff = Backoff::new(TEST_POLICY);
+        backoff.next_delay();
+        backoff.next_delay();
+
+        backoff.observe_run(Duration::from_millis(10));
+        assert_eq!(backoff.restarts, 2);
+        assert_eq!(backoff.next_delay(), Some(Duration::from_millis(20)));
+
+        backoff.observe_run(TEST_POLICY.healthy_run);
+        assert_eq!(backoff.restarts, 0);
+        assert_eq!(backoff.next_delay(), Some(TEST_POLICY.initial_backoff));
+    }
+
+    #[tokio::test]
+    async fn restarts_after_panic_until_the_task_exits() {
+        let attempts = Arc::new(AtomicU32::new(0));
+        let counter = attempts.clone();
+        let events = run(move || {
+            let counter = counter.clone();
+            async move {
+                if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+                    panic!("first attempt fails");
+                }
+            }
+        })
+        .await;
+
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+        assert_eq!(
+            events,
+            vec![
+                TaskEvent::Panicked {
+                    message: "first attempt fails".to_string(),
+                    restarts: 0,
+                },
+                TaskEvent::Exited,
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_the_restart_limit() {
+        let attempts = Arc::new(AtomicU32::new(0));
+        let counter = attempts.clone();
+        let events = run(move || {
+            let counter = counter.clone();
+            async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+                panic!("always fails");
+            }
+        })
+        .await;
+
+        assert_eq!(
+            attempts.load(Ordering::SeqCst),
+            TEST_POLICY.max_restarts + 1
+        );
+        let restarts: Vec<_> = events
+            .iter()
+            .filter_map(|event| match event {
+                TaskEvent::Panicked { restarts, .. } => Some(*restarts),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(restarts, vec![0, 1, 2, 3]);
+        assert_eq!(events.last(), Some(&TaskEvent::GaveUp));
+    }
+
+    #[tokio::test]
+    async fn shutdown_stops_a_running_task() {
+        let coordinator = ShutdownCoordinator::new();
+        let shutdown = coordinator.listener("test");
+        let mut events = Vec::new();
+        let task = supervise(
+            "test",
+            std::future::pending::<()>,
+            shutdown,
+            TEST_POLICY,
+            |event| events.push(event),
+        );
+        let stop = async {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            coordinator
+                .shutdown_with(Duration::from_millis(200), Duration::from_millis(200))
+                .await
+        };
+        tokio::join!(task, stop);
+
+        assert_eq!(events, vec![TaskEvent::Stopped]);
+    }
+}
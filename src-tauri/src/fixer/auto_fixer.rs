@@ -1,8 +1,14 @@
+use crate::compiler::{CompilationError, SuggestedFix};
 use chrono::{DateTime, Utc};
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::SystemTime;
+
+const DIFF_CONTEXT_LINES: usize = 3;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -15,6 +21,12 @@ pub struct FixAttempt {
     pub before: Option<String>,
     pub after: Option<String>,
     pub description: String,
+    #[serde(default)]
+    pub file: Option<String>,
+    #[serde(default)]
+    pub diff: Option<String>,
+    #[serde(default)]
+    pub reverted: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,9 +39,18 @@ pub struct FixStats {
     pub fixes_by_type: HashMap<String, usize>,
 }
 
+/// Whole-file contents around an applied fix, kept so it can be reverted.
+#[derive(Debug, Clone)]
+struct FileSnapshot {
+    path: PathBuf,
+    before: String,
+    after: String,
+}
+
 #[derive(Clone)]
 pub struct AutoFixer {
     attempts: Arc<RwLock<Vec<FixAttempt>>>,
+    snapshots: Arc<RwLock<HashMap<String, FileSnapshot>>>,
     max_attempts: usize,
 }
 
@@ -43,21 +64,15 @@ impl AutoFixer {
     pub fn new(max_attempts: usize) -> Self {
         Self {
             attempts: Arc::new(RwLock::new(Vec::new())),
+            snapshots: Arc::new(RwLock::new(HashMap::new())),
             max_attempts,
         }
     }
 
     pub fn attempt_fix(&self, error_message: &str) -> Result<FixAttempt, String> {
-        let attempts = self.attempts.read();
-        let error_attempts = attempts
-            .iter()
-            .filter(|a| a.error_message == error_message)
-            .count();
-
-        if error_attempts >= self.max_attempts {
+        if self.attempts_for(error_message) >= self.max_attempts {
             return Err("Max fix attempts reached for this error".to_string());
         }
-        drop(attempts);
 
         let fix_attempt = self.apply_fix_pattern(error_message)?;
 
@@ -90,11 +105,144 @@ impl AutoFixer {
             before: None,
             after: None,
             description: format!("Applied fix pattern: {}", fix_type),
+            file: None,
+            diff: None,
+            reverted: false,
         };
 
         Ok(attempt)
     }
 
+    fn attempts_for(&self, error_message: &str) -> usize {
+        self.attempts
+            .read()
+            .iter()
+            .filter(|a| a.error_message == error_message)
+            .count()
+    }
+
+    /// Applies rustc's machine-applicable suggestions, recording one attempt
+    /// per edit. Files modified after `built_at` are skipped because their
+    /// byte offsets may no longer line up with the diagnostics.
+    pub fn apply_suggestions(
+        &self,
+        project_root: &Path,
+        diagnostics: &[CompilationError],
+        built_at: SystemTime,
+    ) -> Vec<FixAttempt> {
+        let mut by_file: HashMap<&str, Vec<(&CompilationError, &SuggestedFix)>> = HashMap::new();
+        for diagnostic in diagnostics {
+            for suggestion in diagnostic
+                .suggestions
+                .iter()
+                .filter(|s| s.machine_applicable)
+            {
+                by_file
+                    .entry(suggestion.file.as_str())
+                    .or_default()
+                    .push((diagnostic, suggestion));
+            }
+        }
+
+        let mut applied = Vec::new();
+        for (file, mut edits) in by_file {
+            let path = project_root.join(file);
+            let modified = fs::metadata(&path).and_then(|m| m.modified());
+            if !modified.is_ok_and(|modified| modified <= built_at) {
+                continue;
+            }
+            let Ok(mut content) = fs::read_to_string(&path) else {
+                continue;
+            };
+
+            // Back to front, so each edit leaves earlier offsets untouched.
+            edits.sort_by(|a, b| {
+                (b.1.byte_start, b.1.byte_end).cmp(&(a.1.byte_start, a.1.byte_end))
+            });
+            edits.dedup_by(|a, b| {
+                (a.1.byte_start, a.1.byte_end, &a.1.replacement)
+                    == (b.1.byte_start, b.1.byte_end, &b.1.replacement)
+            });
+
+            let mut applied_from = usize::MAX;
+            for (diagnostic, suggestion) in edits {
+                let (start, end) = (suggestion.byte_start, suggestion.byte_end);
+                let in_bounds = start <= end
+                    && end <= content.len()
+                    && content.is_char_boundary(start)
+                    && content.is_char_boundary(end);
+                if !in_bounds
+                    || end > applied_from
+                    || self.attempts_for(&diagnostic.message) >= self.max_attempts
+                {
+                    continue;
+                }
+
+                let before = content.clone();
+                content.replace_range(start..end, &suggestion.replacement);
+                if fs::write(&path, &content).is_err() {
+                    content = before;
+                    continue;
+                }
+                applied_from = start;
+
+                let attempt = FixAttempt {
+                    id: uuid::Uuid::new_v4().to_string(),
+                    error_message: diagnostic.message.clone(),
+                    fix_type: "rustc_suggestion".to_string(),
+                    success: true,
+                    timestamp: Utc::now(),
+                    before: Some(before[start..end].to_string()),
+                    after: Some(suggestion.replacement.clone()),
+                    description: format!("{} ({}:{})", suggestion.message, file, suggestion.line),
+                    file: Some(file.to_string()),
+                    diff: Some(unified_diff(file, &before, &content)),
+                    reverted: false,
+                };
+                self.snapshots.write().insert(
+                    attempt.id.clone(),
+                    FileSnapshot {
+                        path: path.clone(),
+                        before,
+                        after: content.clone(),
+                    },
+                );
+                self.attempts.write().push(attempt.clone());
+                applied.push(attempt);
+            }
+        }
+
+        applied
+    }
+
+    /// Restores the file a fix touched, provided nothing has edited it since.
+    pub fn revert_fix(&self, attempt_id: &str) -> Result<FixAttempt, String> {
+        let snapshot = self
+            .snapshots
+            .read()
+            .get(attempt_id)
+            .cloned()
+            .ok_or_else(|| "No revertible change recorded for this fix".to_string())?;
+
+        let current = fs::read_to_string(&snapshot.path).map_err(|e| e.to_string())?;
+        if current != snapshot.after {
+            return Err(format!(
+                "{} has changed since this fix was applied; revert later fixes first",
+                snapshot.path.display()
+            ));
+        }
+        fs::write(&snapshot.path, &snapshot.before).map_err(|e| e.to_string())?;
+        self.snapshots.write().remove(attempt_id);
+
+        let mut attempts = self.attempts.write();
+        let attempt = attempts
+            .iter_mut()
+            .find(|a| a.id == attempt_id)
+            .ok_or_else(|| "Fix attempt not found".to_string())?;
+        attempt.reverted = true;
+        Ok(attempt.clone())
+    }
+
     pub fn get_attempts(&self) -> Vec<FixAttempt> {
         self.attempts.read().clone()
     }
@@ -126,5 +274,119 @@ impl AutoFixer {
 
     pub fn clear_history(&self) {
         self.attempts.write().clear();
+        self.snapshots.write().clear();
+    }
+}
+
+/// Single-hunk unified diff covering the changed region of `before`.
+fn unified_diff(file: &str, before: &str, after: &str) -> String {
+    let old: Vec<&str> = before.lines().collect();
+    let new: Vec<&str> = after.lines().collect();
+
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let start = prefix.saturating_sub(DIFF_CONTEXT_LINES);
+    let old_changed_end = old.len() - suffix;
+    let new_changed_end = new.len() - suffix;
+    let old_end = (old_changed_end + DIFF_CONTEXT_LINES).min(old.len());
+    let new_end = (new_changed_end + DIFF_CONTEXT_LINES).min(new.len());
+
+    let mut diff = format!(
+        "--- a/{file}\n+++ b/{file}\n@@ -{},{} +{},{} @@\n",
+        start + 1,
+        old_end - start,
+        start + 1,
+        new_end - start
+    );
+    let mut push = |marker: char, lines: &[&str]| {
+        for line in lines {
+            diff.push(marker);
+            diff.push_str(line);
+            diff.push('\n');
+        }
+    };
+    push(' ', &old[start..prefix]);
+    push('-', &old[prefix..old_changed_end]);
+    push('+', &new[prefix..new_changed_end]);
+    push(' ', &old[old_changed_end..old_end]);
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn unused_import(file: &str) -> CompilationError {
+        CompilationError {
+            file: file.to_string(),
+            line: 1,
+            column: Some(5),
+            message: "unused import: `std::fmt`".to_string(),
+            severity: "warning".to_string(),
+            code: Some("unused_imports".to_string()),
+            end_line: Some(1),
+            end_column: Some(13),
+            rendered: None,
+            suggestions: vec![SuggestedFix {
+                message: "remove the unused import".to_string(),
+                file: file.to_string(),
+                byte_start: 0,
+                byte_end: 14,
+                line: 1,
+                replacement: String::new(),
+                applicability: "MachineApplicable".to_string(),
+                machine_applicable: true,
+            }],
+        }
+    }
+
+    #[test]
+    fn applies_and_reverts_machine_applicable_suggestion() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lib.rs");
+        let original = "use std::fmt;\nfn main() {}\n";
+        fs::write(&path, original).unwrap();
+
+        let fixer = AutoFixer::default();
+        let built_at = SystemTime::now() + Duration::from_secs(60);
+        let applied = fixer.apply_suggestions(dir.path(), &[unused_import("lib.rs")], built_at);
+
+        assert_eq!(applied.len(), 1);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "fn main() {}\n");
+        assert!(applied[0]
+            .diff
+            .as_deref()
+            .unwrap()
+            .contains("-use std::fmt;"));
+
+        let reverted = fixer.revert_fix(&applied[0].id).unwrap();
+        assert!(reverted.reverted);
+        assert_eq!(fs::read_to_string(&path).unwrap(), original);
+        assert!(fixer.revert_fix(&applied[0].id).is_err());
+    }
+
+    #[test]
+    fn refuses_to_revert_over_later_edits() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lib.rs");
+        fs::write(&path, "use std::fmt;\nfn main() {}\n").unwrap();
+
+        let fixer = AutoFixer::default();
+        let built_at = SystemTime::now() + Duration::from_secs(60);
+        let applied = fixer.apply_suggestions(dir.path(), &[unused_import("lib.rs")], built_at);
+        fs::write(&path, "fn main() { println!(); }\n").unwrap();
+
+        assert!(fixer.revert_fix(&applied[0].id).is_err());
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "fn main() { println!(); }\n"
+        );
     }
 }
@@ -73,7 +73,13 @@ pub async fn submit_signed_vote(
 ) -> Result<VoteRecord, String> {
     let voting_power = {
         let guard = manager.read().await;
-        guard.get_voting_power(&wallet_address, &proposal_id).await
+        let dao_id = guard
+            .find_dao_id_by_proposal(&proposal_id)
+            .ok_or_else(|| "Proposal not found".to_string())?;
+        guard
+            .effective_voting_power(&wallet_address, &dao_id)
+            .await
+            .total
     };
 
     let mut guard = manager.write().await;
@@ -171,6 +177,20 @@ pub async fn get_governance_deadlines(
     Ok(guard.get_upcoming_deadlines(&wallet_address).await)
 }
 
+#[tauri::command]
+pub async fn simulate_governance_vote(
+    proposal_id: String,
+    wallet_address: String,
+    vote_choice: VoteChoice,
+    manager: State<'_, SharedGovernanceManager>,
+) -> Result<VoteSimulation, String> {
+    let guard = manager.read().await;
+    guard
+        .simulate_vote(&proposal_id, &wallet_address, vote_choice)
+        .await
+        .map_err(|err| err.to_string())
+}
+
 #[tauri::command]
 pub async fn prepare_vote_signature(
     proposal_id: String,
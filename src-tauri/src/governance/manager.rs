@@ -1,3 +1,4 @@
+use super::simulation;
 use super::types::*;
 use crate::errors::AppError;
 use std::collections::HashMap;
@@ -9,6 +10,7 @@ pub type SharedGovernanceManager = Arc<RwLock<GovernanceManager>>;
 pub struct GovernanceManager {
     memberships: HashMap<String, Vec<DAOMembership>>,
     proposals: HashMap<String, Vec<GovernanceProposal>>,
+    proposals_synced_at: HashMap<String, i64>,
     votes: HashMap<String, VoteRecord>,
     delegations: HashMap<String, Vec<DelegationRecord>>,
     reminders: HashMap<String, Vec<ProposalReminder>>,
@@ -19,6 +21,7 @@ impl GovernanceManager {
         Self {
             memberships: HashMap::new(),
             proposals: HashMap::new(),
+            proposals_synced_at: HashMap::new(),
             votes: HashMap::new(),
             delegations: HashMap::new(),
             reminders: HashMap::new(),
@@ -48,6 +51,8 @@ impl GovernanceManager {
     ) -> Result<Vec<GovernanceProposal>, AppError> {
         let proposals = self.fetch_dao_proposals(dao_id).await?;
         self.proposals.insert(dao_id.to_string(), proposals.clone());
+        self.proposals_synced_at
+            .insert(dao_id.to_string(), chrono::Utc::now().timestamp());
         Ok(proposals)
     }
 
@@ -82,6 +87,135 @@ impl GovernanceManager {
             .unwrap_or(0.0)
     }
 
+    /// Own power net of active outgoing delegations, plus power delegated
+    /// to this wallet that has not expired.
+    pub async fn effective_voting_power(
+        &self,
+        wallet_address: &str,
+        dao_id: &str,
+    ) -> VotingPowerBreakdown {
+        let now = chrono::Utc::now().timestamp();
+        let is_live = |d: &&DelegationRecord| {
+            d.is_active && d.dao_id == dao_id && d.expires_at.map_or(true, |at| at > now)
+        };
+
+        let membership_power = self.get_voting_power(wallet_address, dao_id).await;
+        let delegated_away: f64 = self
+            .delegations
+            .get(wallet_address)
+            .map(|delegations| {
+                delegations
+                    .iter()
+                    .filter(is_live)
+                    .map(|d| d.voting_power)
+                    .sum()
+            })
+            .unwrap_or(0.0);
+        let delegated_in: f64 = self
+            .delegations
+            .values()
+            .flatten()
+            .filter(is_live)
+            .filter(|d| d.delegate == wallet_address)
+            .map(|d| d.voting_power)
+            .sum();
+
+        let own_power = (membership_power - delegated_away).max(0.0);
+        VotingPowerBreakdown {
+            own_power,
+            delegated_away: delegated_away.min(membership_power),
+            delegated_in,
+            total: own_power + delegated_in,
+        }
+    }
+
+    /// How much of the wallet's power has been cast on open proposals
+    /// across all of its DAOs.
+    pub async fn power_utilization(&self, wallet_address: &str) -> PowerUtilization {
+        let memberships = self.get_memberships(wallet_address).await;
+        let mut utilization = PowerUtilization {
+            total_voting_power: 0.0,
+            open_proposals: 0,
+            voted_proposals: 0,
+            exercised_power: 0.0,
+            idle_power: 0.0,
+            utilization_percent: 0.0,
+        };
+
+        for membership in memberships {
+            let power = self
+                .effective_voting_power(wallet_address, &membership.dao_id)
+                .await
+                .total;
+            utilization.total_voting_power += power;
+
+            let Some(proposals) = self.proposals.get(&membership.dao_id) else {
+                continue;
+            };
+            for proposal in proposals
+                .iter()
+                .filter(|p| p.status == ProposalStatus::Active)
+            {
+                utilization.open_proposals += 1;
+                let cast = self
+                    .votes
+                    .values()
+                    .find(|v| v.proposal_id == proposal.proposal_id && v.voter == wallet_address);
+                match cast {
+                    Some(vote) => {
+                        utilization.voted_proposals += 1;
+                        utilization.exercised_power += vote.voting_power;
+                    }
+                    None => utilization.idle_power += power,
+                }
+            }
+        }
+
+        let available = utilization.exercised_power + utilization.idle_power;
+        if available > 0.0 {
+            utilization.utilization_percent = utilization.exercised_power / available * 100.0;
+        }
+        utilization
+    }
+
+    pub async fn simulate_vote(
+        &self,
+        proposal_id: &str,
+        wallet_address: &str,
+        vote_choice: VoteChoice,
+    ) -> Result<VoteSimulation, AppError> {
+        let proposal = self
+            .proposals
+            .values()
+            .flatten()
+            .find(|p| p.proposal_id == proposal_id)
+            .ok_or_else(|| {
+                AppError::not_found("Proposal not found".to_string())
+                    .with_code("proposal_not_found")
+            })?;
+
+        let power = self
+            .effective_voting_power(wallet_address, &proposal.dao_id)
+            .await;
+        let previous_vote = self
+            .votes
+            .values()
+            .filter(|v| v.proposal_id == proposal_id && v.voter == wallet_address)
+            .max_by_key(|v| v.timestamp);
+        let utilization = self.power_utilization(wallet_address).await;
+
+        Ok(simulation::simulate_vote(
+            proposal,
+            wallet_address,
+            vote_choice,
+            power,
+            previous_vote,
+            utilization,
+            self.proposals_synced_at.get(&proposal.dao_id).copied(),
+            chrono::Utc::now().timestamp(),
+        ))
+    }
+
     pub async fn submit_vote(
         &mut self,
         proposal_id: String,
@@ -181,7 +315,7 @@ impl GovernanceManager {
         let current_proposal = proposals_flat
             .iter()
             .find(|p| p.proposal_id == proposal_id)
-            .ok_or_else(|| AppError::NotFound("Proposal not found".to_string()))?;
+            .ok_or_else(|| AppError::not_found("Proposal not found".to_string()))?;
 
         let similar_proposals = self.find_similar_proposals(current_proposal, &proposals_flat);
 
@@ -586,4 +720,41 @@ mod tests {
         let delegations = manager.get_delegations(wallet).await;
         assert!(!delegations[0].is_active);
     }
+
+    #[tokio::test]
+    async fn test_simulate_vote_uses_delegated_power() {
+        let mut manager = GovernanceManager::new();
+        let wallet = "sim-wallet";
+        manager.sync_memberships(wallet).await.unwrap();
+        for membership in manager.get_memberships(wallet).await {
+            manager.sync_proposals(&membership.dao_id).await.unwrap();
+        }
+
+        let dao = "realms-marinade-dao".to_string();
+        manager
+            .delegate_votes(dao.clone(), wallet.into(), "other".into(), 5000.0, None)
+            .await
+            .unwrap();
+        manager
+            .delegate_votes(dao.clone(), "friend".into(), wallet.into(), 2000.0, None)
+            .await
+            .unwrap();
+
+        let simulation = manager
+            .simulate_vote("marinade-prop-12", wallet, VoteChoice::Yes)
+            .await
+            .unwrap();
+        assert_eq!(simulation.voting_power.total, 12000.0);
+        assert_eq!(simulation.projected.yes_votes, 57000.0);
+        assert!(!simulation.reaches_quorum);
+        assert!(!simulation.is_estimate);
+        assert_eq!(simulation.power_utilization.open_proposals, 2);
+        assert_eq!(simulation.power_utilization.voted_proposals, 0);
+
+        let missing = manager
+            .simulate_vote("unknown", wallet, VoteChoice::No)
+            .await
+            .unwrap_err();
+        assert_eq!(missing.code(), "proposal_not_found");
+    }
 }
@@ -1,6 +1,7 @@
 pub mod commands;
 pub mod manager;
 pub mod signature;
+pub mod simulation;
 pub mod types;
 
 pub use manager::{GovernanceManager, SharedGovernanceManager};
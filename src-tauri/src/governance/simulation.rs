@@ -0,0 +1,287 @@
+use super::types::*;
+
+/// Tallies older than this are reported as estimates.
+pub const TALLY_STALE_AFTER_SECS: i64 = 15 * 60;
+
+/// Applies the tallies of `proposal`, minus `previous` (a vote being
+/// replaced) and plus `added` of `choice`.
+pub fn project_tally(
+    proposal: &GovernanceProposal,
+    previous: Option<(&VoteChoice, f64)>,
+    added: Option<(&VoteChoice, f64)>,
+) -> TallyProjection {
+    let mut yes = proposal.yes_votes;
+    let mut no = proposal.no_votes;
+    let mut abstain = proposal.abstain_votes;
+
+    let mut apply = |choice: &VoteChoice, power: f64| match choice {
+        VoteChoice::Yes => yes = (yes + power).max(0.0),
+        VoteChoice::No => no = (no + power).max(0.0),
+        VoteChoice::Abstain => abstain = (abstain + power).max(0.0),
+    };
+    if let Some((choice, power)) = previous {
+        apply(choice, -power);
+    }
+    if let Some((choice, power)) = added {
+        apply(choice, power);
+    }
+
+    let total = yes + no + abstain;
+    let decisive = yes + no;
+    let yes_percent = if decisive > 0.0 {
+        yes / decisive * 100.0
+    } else {
+        0.0
+    };
+    let quorum_reached = total >= proposal.quorum_required;
+    let threshold = proposal.threshold_percent / 100.0;
+
+    TallyProjection {
+        yes_votes: yes,
+        no_votes: no,
+        abstain_votes: abstain,
+        total_votes: total,
+        quorum_reached,
+        quorum_shortfall: (proposal.quorum_required - total).max(0.0),
+        yes_percent,
+        passing: quorum_reached && decisive > 0.0 && yes_percent >= proposal.threshold_percent,
+        leading_choice: if yes > no {
+            Some(VoteChoice::Yes)
+        } else if no > yes {
+            Some(VoteChoice::No)
+        } else {
+            None
+        },
+        margin_votes: yes - threshold * decisive,
+    }
+}
+
+/// Projects the effect of `wallet` voting `choice` on `proposal`.
+#[allow(clippy::too_many_arguments)]
+pub fn simulate_vote(
+    proposal: &GovernanceProposal,
+    wallet_address: &str,
+    choice: VoteChoice,
+    power: VotingPowerBreakdown,
+    previous_vote: Option<&VoteRecord>,
+    power_utilization: PowerUtilization,
+    tallies_synced_at: Option<i64>,
+    now: i64,
+) -> VoteSimulation {
+    let previous = previous_vote.map(|vote| (&vote.vote_choice, vote.voting_power));
+    let current = project_tally(proposal, None, None);
+    let projected = project_tally(proposal, previous, Some((&choice, power.total)));
+
+    let voting_open = proposal.status == ProposalStatus::Active
+        && now >= proposal.voting_starts_at
+        && now < proposal.voting_ends_at;
+    let time_remaining_secs = (proposal.voting_ends_at - now).max(0);
+
+    let mut notes = Vec::new();
+    let is_estimate = match tallies_synced_at {
+        Some(synced_at) if now - synced_at <= TALLY_STALE_AFTER_SECS => false,
+        Some(synced_at) => {
+            notes.push(format!(
+                "Estimate: tallies were last synced {} minutes ago",
+                (now - synced_at) / 60
+            ));
+            true
+        }
+        None => {
+            notes.push("Estimate: tallies have not been synced this session".to_string());
+            true
+        }
+    };
+    if !voting_open {
+        notes.push("Voting is not open for this proposal".to_string());
+    }
+    if power.total <= 0.0 {
+        notes.push("This wallet has no voting power in the DAO".to_string());
+    }
+    if power.delegated_away > 0.0 {
+        notes.push(format!(
+            "{:.2} of this wallet's power is delegated to others and not counted",
+            power.delegated_away
+        ));
+    }
+    if let Some(vote) = previous_vote {
+        notes.push(format!(
+            "Replaces the existing {:?} vote of {:.2}",
+            vote.vote_choice, vote.voting_power
+        ));
+    }
+
+    VoteSimulation {
+        proposal_id: proposal.proposal_id.clone(),
+        dao_id: proposal.dao_id.clone(),
+        wallet_address: wallet_address.to_string(),
+        choice,
+        voting_power: power,
+        previous_vote: previous_vote.map(|vote| vote.vote_choice.clone()),
+        reaches_quorum: !current.quorum_reached && projected.quorum_reached,
+        flips_outcome: current.passing != projected.passing,
+        flips_leader: current.leading_choice != projected.leading_choice,
+        current,
+        projected,
+        voting_open,
+        time_remaining_secs,
+        power_utilization,
+        tallies_synced_at,
+        is_estimate,
+        notes,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn proposal(yes: f64, no: f64, abstain: f64) -> GovernanceProposal {
+        GovernanceProposal {
+            proposal_id: "prop-1".into(),
+            dao_id: "dao".into(),
+            dao_name: "DAO".into(),
+            platform: DAOPlatform::Realms,
+            title: "Test".into(),
+            description: String::new(),
+            proposer: "proposer".into(),
+            status: ProposalStatus::Active,
+            created_at: NOW - 7200,
+            voting_starts_at: NOW - 3600,
+            voting_ends_at: NOW + 3600,
+            execution_eta: None,
+            yes_votes: yes,
+            no_votes: no,
+            abstain_votes: abstain,
+            quorum_required: 100.0,
+            threshold_percent: 60.0,
+            instructions: vec![],
+            discussion_url: None,
+            tags: vec![],
+        }
+    }
+
+    fn power(total: f64) -> VotingPowerBreakdown {
+        VotingPowerBreakdown {
+            own_power: total,
+            total,
+            ..Default::default()
+        }
+    }
+
+    fn utilization() -> PowerUtilization {
+        PowerUtilization {
+            total_voting_power: 0.0,
+            open_proposals: 0,
+            voted_proposals: 0,
+            exercised_power: 0.0,
+            idle_power: 0.0,
+            utilization_percent: 0.0,
+        }
+    }
+
+    #[test]
+    fn vote_that_reaches_quorum_flips_outcome() {
+        let proposal = proposal(55.0, 30.0, 0.0);
+        let simulation = simulate_vote(
+            &proposal,
+            "wallet",
+            VoteChoice::Yes,
+            power(20.0),
+            None,
+            utilization(),
+            Some(NOW - 60),
+            NOW,
+        );
+
+        assert!(!simulation.current.quorum_reached);
+        assert!(simulation.reaches_quorum);
+        assert!(simulation.flips_outcome);
+        assert!(simulation.projected.passing);
+        assert!((simulation.projected.margin_votes - 24.0).abs() < 1e-9);
+        assert_eq!(simulation.time_remaining_secs, 3600);
+        assert!(!simulation.is_estimate);
+    }
+
+    #[test]
+    fn no_vote_can_flip_the_leader() {
+        let proposal = proposal(70.0, 60.0, 0.0);
+        let simulation = simulate_vote(
+            &proposal,
+            "wallet",
+            VoteChoice::No,
+            power(15.0),
+            None,
+            utilization(),
+            Some(NOW),
+            NOW,
+        );
+
+        assert_eq!(simulation.current.leading_choice, Some(VoteChoice::Yes));
+        assert_eq!(simulation.projected.leading_choice, Some(VoteChoice::No));
+        assert!(simulation.flips_leader);
+        // 70/130 was already below the 60% threshold.
+        assert!(!simulation.flips_outcome);
+    }
+
+    #[test]
+    fn replaces_an_existing_vote() {
+        let proposal = proposal(80.0, 40.0, 0.0);
+        let previous = VoteRecord {
+            vote_id: "v".into(),
+            proposal_id: "prop-1".into(),
+            voter: "wallet".into(),
+            vote_choice: VoteChoice::Yes,
+            voting_power: 30.0,
+            timestamp: NOW - 100,
+            transaction_signature: None,
+        };
+        let simulation = simulate_vote(
+            &proposal,
+            "wallet",
+            VoteChoice::Abstain,
+            power(30.0),
+            Some(&previous),
+            utilization(),
+            Some(NOW),
+            NOW,
+        );
+
+        assert_eq!(simulation.projected.yes_votes, 50.0);
+        assert_eq!(simulation.projected.abstain_votes, 30.0);
+        assert_eq!(simulation.projected.total_votes, 120.0);
+        assert_eq!(simulation.previous_vote, Some(VoteChoice::Yes));
+    }
+
+    #[test]
+    fn stale_or_missing_tallies_are_estimates() {
+        let proposal = proposal(10.0, 0.0, 0.0);
+        let stale = simulate_vote(
+            &proposal,
+            "wallet",
+            VoteChoice::Yes,
+            power(1.0),
+            None,
+            utilization(),
+            Some(NOW - 2 * TALLY_STALE_AFTER_SECS),
+            NOW,
+        );
+        assert!(stale.is_estimate);
+        assert!(stale.notes[0].contains("30 minutes ago"));
+
+        let never = simulate_vote(
+            &proposal,
+            "wallet",
+            VoteChoice::Yes,
+            power(1.0),
+            None,
+            utilization(),
+            None,
+            NOW,
+        );
+        assert!(never.is_estimate);
+        assert_eq!(never.tallies_synced_at, None);
+    }
+}
@@ -179,3 +179,72 @@ pub struct UpcomingDeadline {
     pub time_remaining_hours: i64,
     pub has_voted: bool,
 }
+
+/// A wallet's voting power in one DAO, split by where it comes from.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VotingPowerBreakdown {
+    pub own_power: f64,
+    /// Own power currently delegated to someone else, already subtracted
+    /// from `own_power`.
+    pub delegated_away: f64,
+    pub delegated_in: f64,
+    pub total: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TallyProjection {
+    pub yes_votes: f64,
+    pub no_votes: f64,
+    pub abstain_votes: f64,
+    pub total_votes: f64,
+    pub quorum_reached: bool,
+    /// Votes still missing for quorum; zero once it is reached.
+    pub quorum_shortfall: f64,
+    /// Yes share of the yes and no votes, in percent.
+    pub yes_percent: f64,
+    pub passing: bool,
+    /// `None` on a tie.
+    pub leading_choice: Option<VoteChoice>,
+    /// Yes votes above (positive) or below (negative) the approval threshold.
+    pub margin_votes: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PowerUtilization {
+    pub total_voting_power: f64,
+    pub open_proposals: usize,
+    pub voted_proposals: usize,
+    /// Power cast on open proposals, summed per proposal.
+    pub exercised_power: f64,
+    /// Power that could still be cast on open proposals.
+    pub idle_power: f64,
+    pub utilization_percent: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VoteSimulation {
+    pub proposal_id: String,
+    pub dao_id: String,
+    pub wallet_address: String,
+    pub choice: VoteChoice,
+    pub voting_power: VotingPowerBreakdown,
+    /// Set when the wallet already voted; the simulation replaces that vote.
+    pub previous_vote: Option<VoteChoice>,
+    pub current: TallyProjection,
+    pub projected: TallyProjection,
+    pub reaches_quorum: bool,
+    pub flips_outcome: bool,
+    pub flips_leader: bool,
+    pub voting_open: bool,
+    pub time_remaining_secs: i64,
+    pub power_utilization: PowerUtilization,
+    /// When the proposal tallies were last synced, if ever.
+    pub tallies_synced_at: Option<i64>,
+    /// True when the tallies are stale, so the projection is an estimate.
+    pub is_estimate: bool,
+    pub notes: Vec<String>,
+}
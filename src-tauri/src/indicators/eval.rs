@@ -0,0 +1,533 @@
+use super::formula::{BinaryOp, Expr, Formula, Function, PriceSeries, UnaryOp};
+use crate::chart_stream::Candle;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Longest candle series a formula is evaluated over.
+pub const MAX_SERIES_LEN: usize = 20_000;
+/// Upper bound on `cost_per_candle * candles` for one evaluation.
+const MAX_EVAL_COST: u64 = 50_000_000;
+const EVAL_TIME_LIMIT: Duration = Duration::from_millis(250);
+
+/// Evaluates formulas over one candle series. Missing values (warm-up,
+/// division by zero) are NaN internally and `None` in the output.
+pub struct FormulaEvaluator<'a> {
+    candles: &'a [Candle],
+    indicators: HashMap<String, Vec<f64>>,
+    deadline: Instant,
+}
+
+impl<'a> FormulaEvaluator<'a> {
+    pub fn new(candles: &'a [Candle]) -> Result<Self, String> {
+        if candles.len() > MAX_SERIES_LEN {
+            return Err(format!(
+                "Formulas can be evaluated over at most {} candles, got {}",
+                MAX_SERIES_LEN,
+                candles.len()
+            ));
+        }
+        Ok(Self {
+            candles,
+            indicators: HashMap::new(),
+            deadline: Instant::now() + EVAL_TIME_LIMIT,
+        })
+    }
+
+    /// Evaluates `formula` and makes its output available to later
+    /// formulas under `name`.
+    pub fn define(&mut self, name: &str, formula: &Formula) -> Result<Vec<Option<f64>>, String> {
+        let values = self.evaluate_raw(formula)?;
+        let output = to_output(&values);
+        self.indicators.insert(name.to_string(), values);
+        Ok(output)
+    }
+
+    pub fn evaluate(&self, formula: &Formula) -> Result<Vec<Option<f64>>, String> {
+        Ok(to_output(&self.evaluate_raw(formula)?))
+    }
+
+    fn evaluate_raw(&self, formula: &Formula) -> Result<Vec<f64>, String> {
+        let cost = formula
+            .cost_per_candle
+            .saturating_mul(self.candles.len() as u64);
+        if cost > MAX_EVAL_COST {
+            return Err(format!(
+                "Formula is too expensive to evaluate over {} candles; use shorter periods or fewer candles",
+                self.candles.len()
+            ));
+        }
+        self.eval(&formula.expr)
+    }
+
+    fn eval(&self, expr: &Expr) -> Result<Vec<f64>, String> {
+        if Instant::now() > self.deadline {
+            return Err(format!(
+                "Formula evaluation exceeded {} ms",
+                EVAL_TIME_LIMIT.as_millis()
+            ));
+        }
+        let len = self.candles.len();
+        Ok(match expr {
+            Expr::Number(value) => vec![*value; len],
+            Expr::Price(series) => self.candles.iter().map(|c| price(c, *series)).collect(),
+            Expr::Indicator(name) => self
+                .indicators
+                .get(name)
+                .cloned()
+                .ok_or_else(|| format!("Custom indicator `{}` was not evaluated", name))?,
+            Expr::Unary(op, inner) => {
+                let values = self.eval(inner)?;
+                match op {
+                    UnaryOp::Neg => values.into_iter().map(|v| -v).collect(),
+                    UnaryOp::Not => values.into_iter().map(|v| map_bool(v, |b| !b)).collect(),
+                }
+            }
+            Expr::Binary(op, lhs, rhs) => {
+                let lhs = self.eval(lhs)?;
+                let rhs = self.eval(rhs)?;
+                lhs.into_iter()
+                    .zip(rhs)
+                    .map(|(a, b)| binary(*op, a, b))
+                    .collect()
+            }
+            Expr::Call {
+                function,
+                args,
+                period,
+            } => {
+                let args = args
+                    .iter()
+                    .map(|arg| self.eval(arg))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let n = period.unwrap_or(1);
+                match function {
+                    Function::Sma => sma(&args[0], n),
+                    Function::Ema => ema(&args[0], n),
+                    Function::Wma => wma(&args[0], n),
+                    Function::Rsi => rsi(&args[0], n),
+                    Function::Stdev => stdev(&args[0], n),
+                    Function::Highest => window(&args[0], n, |w| {
+                        w.iter().copied().fold(f64::NEG_INFINITY, f64::max)
+                    }),
+                    Function::Lowest => window(&args[0], n, |w| {
+                        w.iter().copied().fold(f64::INFINITY, f64::min)
+                    }),
+                    Function::Lag => lag(&args[0], n),
+                    Function::Change => {
+                        let lagged = lag(&args[0], n);
+                        args[0].iter().zip(lagged).map(|(v, l)| v - l).collect()
+                    }
+                    Function::Atr => atr(self.candles, n),
+                    Function::Abs => args[0].iter().map(|v| v.abs()).collect(),
+                    Function::Min => pairwise(&args[0], &args[1], f64::min),
+                    Function::Max => pairwise(&args[0], &args[1], f64::max),
+                    Function::CrossesAbove => crosses(&args[0], &args[1], true),
+                    Function::CrossesBelow => crosses(&args[0], &args[1], false),
+                }
+            }
+        })
+    }
+}
+
+fn to_output(values: &[f64]) -> Vec<Option<f64>> {
+    values.iter().map(|v| v.is_finite().then_some(*v)).collect()
+}
+
+fn price(candle: &Candle, series: PriceSeries) -> f64 {
+    match series {
+        PriceSeries::Open => candle.open,
+        PriceSeries::High => candle.high,
+        PriceSeries::Low => candle.low,
+        PriceSeries::Close => candle.close,
+        PriceSeries::Volume => candle.volume,
+        PriceSeries::Hl2 => (candle.high + candle.low) / 2.0,
+        PriceSeries::Hlc3 => (candle.high + candle.low + candle.close) / 3.0,
+        PriceSeries::Ohlc4 => (candle.open + candle.high + candle.low + candle.close) / 4.0,
+    }
+}
+
+fn from_bool(value: bool) -> f64 {
+    if value {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+fn map_bool(value: f64, f: impl Fn(bool) -> bool) -> f64 {
+    if value.is_nan() {
+        f64::NAN
+    } else {
+        from_bool(f(value != 0.0))
+    }
+}
+
+fn binary(op: BinaryOp, a: f64, b: f64) -> f64 {
+    if a.is_nan() || b.is_nan() {
+        return f64::NAN;
+    }
+    match op {
+        BinaryOp::Add => a + b,
+        BinaryOp::Sub => a - b,
+        BinaryOp::Mul => a * b,
+        BinaryOp::Div if b == 0.0 => f64::NAN,
+        BinaryOp::Div => a / b,
+        BinaryOp::Gt => from_bool(a > b),
+        BinaryOp::Lt => from_bool(a < b),
+        BinaryOp::Ge => from_bool(a >= b),
+        BinaryOp::Le => from_bool(a <= b),
+        BinaryOp::Eq => from_bool(a == b),
+        BinaryOp::Ne => from_bool(a != b),
+        BinaryOp::And => from_bool(a != 0.0 && b != 0.0),
+        BinaryOp::Or => from_bool(a != 0.0 || b != 0.0),
+    }
+}
+
+fn pairwise(a: &[f64], b: &[f64], f: fn(f64, f64) -> f64) -> Vec<f64> {
+    a.iter()
+        .zip(b)
+        .map(|(x, y)| {
+            if x.is_nan() || y.is_nan() {
+                f64::NAN
+            } else {
+                f(*x, *y)
+            }
+        })
+        .collect()
+}
+
+/// Applies `f` to every full window of `n` values; windows with a gap are NaN.
+fn window(values: &[f64], n: usize, f: impl Fn(&[f64]) -> f64) -> Vec<f64> {
+    let mut out = vec![f64::NAN; values.len()];
+    for end in n..=values.len() {
+        let w = &values[end - n..end];
+        if w.iter().all(|v| v.is_finite()) {
+            out[end - 1] = f(w);
+        }
+    }
+    out
+}
+
+fn sma(values: &[f64], n: usize) -> Vec<f64> {
+    let mut out = vec![f64::NAN; values.len()];
+    let mut sum = 0.0;
+    let mut run = 0;
+    for (i, value) in values.iter().enumerate() {
+        if !value.is_finite() {
+            sum = 0.0;
+            run = 0;
+            continue;
+        }
+        sum += value;
+        run += 1;
+        if run > n {
+            sum -= values[i - n];
+        }
+        if run >= n {
+            out[i] = sum / n as f64;
+        }
+    }
+    out
+}
+
+fn wma(values: &[f64], n: usize) -> Vec<f64> {
+    let denominator = (n * (n + 1) / 2) as f64;
+    window(values, n, |w| {
+        w.iter()
+            .enumerate()
+            .map(|(i, v)| v * (i + 1) as f64)
+            .sum::<f64>()
+            / denominator
+    })
+}
+
+fn stdev(values: &[f64], n: usize) -> Vec<f64> {
+    window(values, n, |w| {
+        let mean = w.iter().sum::<f64>() / n as f64;
+        (w.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n as f64).sqrt()
+    })
+}
+
+/// Exponential smoothing seeded with the simple average of the first `n`
+/// values; `alpha` is `2 / (n + 1)` for EMA and `1 / n` for Wilder's RMA.
+fn smooth(values: &[f64], n: usize, alpha: f64) -> Vec<f64> {
+    let mut out = vec![f64::NAN; values.len()];
+    let Some(start) = values.iter().position(|v| v.is_finite()) else {
+        return out;
+    };
+    if start + n > values.len() {
+        return out;
+    }
+    let seed = &values[start..start + n];
+    if !seed.iter().all(|v| v.is_finite()) {
+        return out;
+    }
+    let mut current = seed.iter().sum::<f64>() / n as f64;
+    out[start + n - 1] = current;
+    for (value, slot) in values.iter().zip(out.iter_mut()).skip(start + n) {
+        if value.is_finite() {
+            current += alpha * (value - current);
+            *slot = current;
+        }
+    }
+    out
+}
+
+fn ema(values: &[f64], n: usize) -> Vec<f64> {
+    smooth(values, n, 2.0 / (n as f64 + 1.0))
+}
+
+fn rma(values: &[f64], n: usize) -> Vec<f64> {
+    smooth(values, n, 1.0 / n as f64)
+}
+
+fn rsi(values: &[f64], n: usize) -> Vec<f64> {
+    let mut gains = vec![f64::NAN; values.len()];
+    let mut losses = vec![f64::NAN; values.len()];
+    for i in 1..values.len() {
+        let delta = values[i] - values[i - 1];
+        gains[i] = delta.max(0.0);
+        losses[i] = (-delta).max(0.0);
+        if delta.is_nan() {
+            gains[i] = f64::NAN;
+            losses[i] = f64::NAN;
+        }
+    }
+    rma(&gains, n)
+        .into_iter()
+        .zip(rma(&losses, n))
+        .map(|(gain, loss)| {
+            if gain.is_nan() || loss.is_nan() {
+                f64::NAN
+            } else if loss == 0.0 {
+                if gain == 0.0 {
+                    50.0
+                } else {
+                    100.0
+                }
+            } else {
+                100.0 - 100.0 / (1.0 + gain / loss)
+            }
+        })
+        .collect()
+}
+
+fn atr(candles: &[Candle], n: usize) -> Vec<f64> {
+    let true_range: Vec<f64> = candles
+        .iter()
+        .enumerate()
+        .map(|(i, candle)| {
+            let range = candle.high - candle.low;
+            match i.checked_sub(1).map(|p| candles[p].close) {
+                Some(prev_close) => range
+                    .max((candle.high - prev_close).abs())
+                    .max((candle.low - prev_close).abs()),
+                None => range,
+            }
+        })
+        .collect();
+    rma(&true_range, n)
+}
+
+fn lag(values: &[f64], n: usize) -> Vec<f64> {
+    (0..values.len())
+        .map(|i| i.checked_sub(n).map_or(f64::NAN, |p| values[p]))
+        .collect()
+}
+
+fn crosses(a: &[f64], b: &[f64], above: bool) -> Vec<f64> {
+    (0..a.len())
+        .map(|i| {
+            if i == 0 {
+                return f64::NAN;
+            }
+            let (prev, now) = (a[i - 1] - b[i - 1], a[i] - b[i]);
+            if prev.is_nan() || now.is_nan() {
+                f64::NAN
+            } else if above {
+                from_bool(prev <= 0.0 && now > 0.0)
+            } else {
+                from_bool(prev >= 0.0 && now < 0.0)
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::formula::compile_with;
+    use super::*;
+
+    /// Ten candles with known indicator values worked out by hand.
+    fn fixture() -> Vec<Candle> {
+        let closes = [10.0, 11.0, 12.0, 11.0, 13.0, 14.0, 13.0, 15.0, 16.0, 15.0];
+        closes
+            .iter()
+            .enumerate()
+            .map(|(i, &close)| Candle {
+                time: i as i64 * 60,
+                open: close - 0.5,
+                high: close + 1.0,
+                low: close - 1.0,
+                close,
+                volume: 100.0 + i as f64,
+            })
+            .collect()
+    }
+
+    fn eval(source: &str) -> Vec<Option<f64>> {
+        let candles = fixture();
+        let formula = Formula::compile(source).unwrap();
+        FormulaEvaluator::new(&candles)
+            .unwrap()
+            .evaluate(&formula)
+            .unwrap()
+    }
+
+    fn assert_series(source: &str, expected: &[Option<f64>]) {
+        let actual = eval(source);
+        assert_eq!(actual.len(), expected.len(), "{}", source);
+        for (i, (a, e)) in actual.iter().zip(expected).enumerate() {
+            match (a, e) {
+                (Some(a), Some(e)) => assert!(
+                    (a - e).abs() < 1e-9,
+                    "{} at {}: expected {}, got {}",
+                    source,
+                    i,
+                    e,
+                    a
+                ),
+                _ => assert_eq!(a, e, "{} at {}", source, i),
+            }
+        }
+    }
+
+    fn last(source: &str) -> f64 {
+        eval(source).last().copied().flatten().unwrap()
+    }
+
+    #[test]
+    fn evaluates_expected_values_over_fixture_candles() {
+        let n = None;
+        assert_series(
+            "sma(close, 3)",
+            &[
+                n,
+                n,
+                Some(11.0),
+                Some(34.0 / 3.0),
+                Some(12.0),
+                Some(38.0 / 3.0),
+                Some(40.0 / 3.0),
+                Some(14.0),
+                Some(44.0 / 3.0),
+                Some(46.0 / 3.0),
+            ],
+        );
+        // EMA(3) seeds with SMA 11 then applies alpha 0.5.
+        assert_series(
+            "ema(close, 3)",
+            &[
+                n,
+                n,
+                Some(11.0),
+                Some(11.0),
+                Some(12.0),
+                Some(13.0),
+                Some(13.0),
+                Some(14.0),
+                Some(15.0),
+                Some(15.0),
+            ],
+        );
+        assert_series("(high - low) / 2", &[Some(1.0); 10]);
+        assert_series(
+            "lag(close, 2)",
+            &[
+                n,
+                n,
+                Some(10.0),
+                Some(11.0),
+                Some(12.0),
+                Some(11.0),
+                Some(13.0),
+                Some(14.0),
+                Some(13.0),
+                Some(15.0),
+            ],
+        );
+
+        assert!((last("wma(close, 3)") - (16.0 * 2.0 + 15.0 * 3.0 + 15.0) / 6.0).abs() < 1e-9);
+        assert_eq!(last("highest(high, 4)"), 17.0);
+        assert_eq!(last("lowest(low, 4)"), 12.0);
+        assert_eq!(last("change(close, 5)"), 2.0);
+        assert!((last("stdev(close, 2)") - 0.5).abs() < 1e-9);
+        assert_eq!(last("hlc3"), 15.0);
+        assert_eq!(last("-abs(close - 20)"), -5.0);
+        assert_eq!(last("max(close, 20) + min(close, 2)"), 22.0);
+    }
+
+    #[test]
+    fn evaluates_wilder_indicators() {
+        // Every true range is 2 except where a gap beyond the prior close
+        // widens it: bar 4 (13 vs prev close 11 -> high 14, range 3) and bar
+        // 7 (15 vs 13 -> high 16, range 3).
+        let atr = eval("atr(3)");
+        assert_eq!(atr[1], None);
+        assert!((atr[2].unwrap() - 2.0).abs() < 1e-9);
+        assert!((atr[4].unwrap() - 7.0 / 3.0).abs() < 1e-9);
+        assert!((last("(high - low) / atr(3)") - 2.0 / atr[9].unwrap()).abs() < 1e-9);
+
+        // Gains 1,1,0,2 and losses 0,0,1,0 over the first four changes.
+        let rsi = eval("rsi(close, 4)");
+        assert_eq!(rsi[3], None);
+        assert!((rsi[4].unwrap() - 80.0).abs() < 1e-9);
+        assert!(rsi.iter().flatten().all(|v| (0.0..=100.0).contains(v)));
+    }
+
+    #[test]
+    fn evaluates_conditions_and_missing_values() {
+        assert_series(
+            "crosses_above(close, 12.5)",
+            &[
+                None,
+                Some(0.0),
+                Some(0.0),
+                Some(0.0),
+                Some(1.0),
+                Some(0.0),
+                Some(0.0),
+                Some(0.0),
+                Some(0.0),
+                Some(0.0),
+            ],
+        );
+        assert_eq!(last("close > ema(close, 3) or not (volume > 0)"), 0.0);
+        assert_eq!(last("close >= 15 and volume == 109"), 1.0);
+        assert_eq!(eval("close / (high - high)")[0], None);
+        assert_eq!(eval("sma(close, 3) > 0")[0], None);
+    }
+
+    #[test]
+    fn custom_indicators_read_each_other_and_bound_work() {
+        let candles = fixture();
+        let mut evaluator = FormulaEvaluator::new(&candles).unwrap();
+        let spread = compile_with("ema(close, 2) - ema(close, 4)", &[]).unwrap();
+        let signal = compile_with("sma(spread, 2)", &["spread"]).unwrap();
+        let spread_values = evaluator.define("spread", &spread).unwrap();
+        let signal_values = evaluator.define("signal", &signal).unwrap();
+        let expected = (spread_values[8].unwrap() + spread_values[9].unwrap()) / 2.0;
+        assert!((signal_values[9].unwrap() - expected).abs() < 1e-9);
+
+        let too_long = vec![fixture()[0].clone(); MAX_SERIES_LEN + 1];
+        assert!(FormulaEvaluator::new(&too_long).is_err());
+
+        let expensive = Formula::compile(&["highest(close, 1000)"; 4].join(" + ")).unwrap();
+        let many = vec![fixture()[0].clone(); MAX_SERIES_LEN];
+        let err = FormulaEvaluator::new(&many)
+            .unwrap()
+            .evaluate(&expensive)
+            .unwrap_err();
+        assert!(err.contains("too expensive"));
+    }
+}
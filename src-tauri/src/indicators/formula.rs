@@ -0,0 +1,927 @@
+use super::CustomIndicator;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Bumped whenever a formula that compiled before could change meaning.
+pub const FORMULA_LANGUAGE_VERSION: u32 = 1;
+
+pub const MAX_FORMULA_LEN: usize = 1024;
+pub const MAX_PERIOD: usize = 1000;
+const MAX_NODES: usize = 256;
+const MAX_DEPTH: usize = 32;
+
+const PRICE_SERIES: [&str; 8] = [
+    "open", "high", "low", "close", "volume", "hl2", "hlc3", "ohlc4",
+];
+const KEYWORDS: [&str; 3] = ["and", "or", "not"];
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FormulaError {
+    pub message: String,
+    /// Zero-based character offset the error points at.
+    pub position: usize,
+}
+
+impl FormulaError {
+    fn new(message: impl Into<String>, position: usize) -> Self {
+        Self {
+            message: message.into(),
+            position,
+        }
+    }
+}
+
+impl fmt::Display for FormulaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (column {})", self.message, self.position + 1)
+    }
+}
+
+impl std::error::Error for FormulaError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PriceSeries {
+    Open,
+    High,
+    Low,
+    Close,
+    Volume,
+    Hl2,
+    Hlc3,
+    Ohlc4,
+}
+
+impl PriceSeries {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "open" => PriceSeries::Open,
+            "high" => PriceSeries::High,
+            "low" => PriceSeries::Low,
+            "close" => PriceSeries::Close,
+            "volume" => PriceSeries::Volume,
+            "hl2" => PriceSeries::Hl2,
+            "hlc3" => PriceSeries::Hlc3,
+            "ohlc4" => PriceSeries::Ohlc4,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Function {
+    Sma,
+    Ema,
+    Wma,
+    Rsi,
+    Stdev,
+    Highest,
+    Lowest,
+    Lag,
+    Change,
+    Atr,
+    Abs,
+    Min,
+    Max,
+    CrossesAbove,
+    CrossesBelow,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Param {
+    Series,
+    Period,
+}
+
+struct FunctionSpec {
+    name: &'static str,
+    function: Function,
+    params: &'static [Param],
+    signature: &'static str,
+}
+
+const SERIES_PERIOD: &[Param] = &[Param::Series, Param::Period];
+const TWO_SERIES: &[Param] = &[Param::Series, Param::Series];
+
+const FUNCTIONS: &[FunctionSpec] = &[
+    FunctionSpec {
+        name: "sma",
+        function: Function::Sma,
+        params: SERIES_PERIOD,
+        signature: "sma(series, period)",
+    },
+    FunctionSpec {
+        name: "ema",
+        function: Function::Ema,
+        params: SERIES_PERIOD,
+        signature: "ema(series, period)",
+    },
+    FunctionSpec {
+        name: "wma",
+        function: Function::Wma,
+        params: SERIES_PERIOD,
+        signature: "wma(series, period)",
+    },
+    FunctionSpec {
+        name: "rsi",
+        function: Function::Rsi,
+        params: SERIES_PERIOD,
+        signature: "rsi(series, period)",
+    },
+    FunctionSpec {
+        name: "stdev",
+        function: Function::Stdev,
+        params: SERIES_PERIOD,
+        signature: "stdev(series, period)",
+    },
+    FunctionSpec {
+        name: "highest",
+        function: Function::Highest,
+        params: SERIES_PERIOD,
+        signature: "highest(series, period)",
+    },
+    FunctionSpec {
+        name: "lowest",
+        function: Function::Lowest,
+        params: SERIES_PERIOD,
+        signature: "lowest(series, period)",
+    },
+    FunctionSpec {
+        name: "lag",
+        function: Function::Lag,
+        params: SERIES_PERIOD,
+        signature: "lag(series, bars)",
+    },
+    FunctionSpec {
+        name: "change",
+        function: Function::Change,
+        params: SERIES_PERIOD,
+        signature: "change(series, bars)",
+    },
+    FunctionSpec {
+        name: "atr",
+        function: Function::Atr,
+        params: &[Param::Period],
+        signature: "atr(period)",
+    },
+    FunctionSpec {
+        name: "abs",
+        function: Function::Abs,
+        params: &[Param::Series],
+        signature: "abs(value)",
+    },
+    FunctionSpec {
+        name: "min",
+        function: Function::Min,
+        params: TWO_SERIES,
+        signature: "min(a, b)",
+    },
+    FunctionSpec {
+        name: "max",
+        function: Function::Max,
+        params: TWO_SERIES,
+        signature: "max(a, b)",
+    },
+    FunctionSpec {
+        name: "crosses_above",
+        function: Function::CrossesAbove,
+        params: TWO_SERIES,
+        signature: "crosses_above(a, b)",
+    },
+    FunctionSpec {
+        name: "crosses_below",
+        function: Function::CrossesBelow,
+        params: TWO_SERIES,
+        signature: "crosses_below(a, b)",
+    },
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Gt,
+    Lt,
+    Ge,
+    Le,
+    Eq,
+    Ne,
+    And,
+    Or,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum UnaryOp {
+    Neg,
+    Not,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum Expr {
+    Number(f64),
+    Price(PriceSeries),
+    /// Output of another custom indicator in the same preset.
+    Indicator(String),
+    Unary(UnaryOp, Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Call {
+        function: Function,
+        args: Vec<Expr>,
+        period: Option<usize>,
+    },
+}
+
+/// A formula that parsed and passed validation.
+#[derive(Debug, Clone)]
+pub struct Formula {
+    pub(crate) expr: Expr,
+    /// Work per candle; windowed functions cost their period.
+    pub(crate) cost_per_candle: u64,
+    lookback: usize,
+    references: Vec<String>,
+}
+
+impl Formula {
+    /// Compiles a formula that may only use price series and functions.
+    pub fn compile(source: &str) -> Result<Self, FormulaError> {
+        compile_with(source, &[])
+    }
+
+    /// Candles needed before the formula produces its first value, e.g.
+    /// 200 for `ema(close, 200)`.
+    pub fn warmup_bars(&self) -> usize {
+        self.lookback + 1
+    }
+
+    /// Custom indicators this formula reads.
+    pub fn references(&self) -> &[String] {
+        &self.references
+    }
+}
+
+/// Compiles `source`, resolving bare names in `indicators` to other
+/// custom indicators.
+pub(crate) fn compile_with(source: &str, indicators: &[&str]) -> Result<Formula, FormulaError> {
+    if source.chars().count() > MAX_FORMULA_LEN {
+        return Err(FormulaError::new(
+            format!("formula is longer than {} characters", MAX_FORMULA_LEN),
+            MAX_FORMULA_LEN,
+        ));
+    }
+    let tokens = tokenize(source)?;
+    let mut parser = Parser {
+        tokens,
+        pos: 0,
+        source_len: source.chars().count(),
+        nodes: 0,
+        depth: 0,
+        indicators,
+        references: Vec::new(),
+    };
+    let expr = parser.parse_or()?;
+    if let Some(token) = parser.tokens.get(parser.pos) {
+        return Err(FormulaError::new(
+            format!("unexpected {}", token.kind.describe()),
+            token.pos,
+        ));
+    }
+    Ok(Formula {
+        cost_per_candle: cost(&expr),
+        lookback: lookback(&expr),
+        expr,
+        references: parser.references,
+    })
+}
+
+fn cost(expr: &Expr) -> u64 {
+    match expr {
+        Expr::Number(_) | Expr::Price(_) | Expr::Indicator(_) => 1,
+        Expr::Unary(_, inner) => 1 + cost(inner),
+        Expr::Binary(_, lhs, rhs) => 1 + cost(lhs) + cost(rhs),
+        Expr::Call {
+            function,
+            args,
+            period,
+        } => {
+            let own = match function {
+                Function::Wma | Function::Stdev | Function::Highest | Function::Lowest => {
+                    period.unwrap_or(1) as u64
+                }
+                _ => 2,
+            };
+            own + args.iter().map(cost).sum::<u64>()
+        }
+    }
+}
+
+/// Earlier candles the latest value depends on, not counting what a
+/// referenced custom indicator needs itself.
+fn lookback(expr: &Expr) -> usize {
+    match expr {
+        Expr::Number(_) | Expr::Price(_) | Expr::Indicator(_) => 0,
+        Expr::Unary(_, inner) => lookback(inner),
+        Expr::Binary(_, lhs, rhs) => lookback(lhs).max(lookback(rhs)),
+        Expr::Call {
+            function,
+            args,
+            period,
+        } => {
+            let inner = args.iter().map(lookback).max().unwrap_or(0);
+            let n = period.unwrap_or(1);
+            let own = match function {
+                Function::Sma
+                | Function::Ema
+                | Function::Wma
+                | Function::Stdev
+                | Function::Highest
+                | Function::Lowest
+                | Function::Atr => n - 1,
+                Function::Rsi | Function::Lag | Function::Change => n,
+                Function::CrossesAbove | Function::CrossesBelow => 1,
+                Function::Abs | Function::Min | Function::Max => 0,
+            };
+            inner + own
+        }
+    }
+}
+
+/// Compiles every custom indicator of a preset, dependencies first, and
+/// rejects reference cycles.
+pub fn compile_custom_indicators(
+    indicators: &[CustomIndicator],
+) -> Result<Vec<(String, Formula)>, String> {
+    let mut names: HashMap<&str, usize> = HashMap::new();
+    for (index, indicator) in indicators.iter().enumerate() {
+        validate_indicator_name(&indicator.name)?;
+        if indicator.language_version > FORMULA_LANGUAGE_VERSION {
+            return Err(format!(
+                "Custom indicator `{}` uses formula language v{}, this version supports up to v{}",
+                indicator.name, indicator.language_version, FORMULA_LANGUAGE_VERSION
+            ));
+        }
+        if names.insert(indicator.name.as_str(), index).is_some() {
+            return Err(format!(
+                "Custom indicator name `{}` is used twice",
+                indicator.name
+            ));
+        }
+    }
+
+    let all_names: Vec<&str> = indicators.iter().map(|i| i.name.as_str()).collect();
+    let mut compiled = Vec::with_capacity(indicators.len());
+    for indicator in indicators {
+        let formula = compile_with(&indicator.expression, &all_names)
+            .map_err(|e| format!("Custom indicator `{}`: {}", indicator.name, e))?;
+        compiled.push(Some(formula));
+    }
+
+    // Depth-first topological sort; `visiting` catches cycles.
+    fn visit(
+        index: usize,
+        indicators: &[CustomIndicator],
+        names: &HashMap<&str, usize>,
+        compiled: &mut [Option<Formula>],
+        visiting: &mut Vec<usize>,
+        ordered: &mut Vec<(String, Formula)>,
+    ) -> Result<(), String> {
+        if visiting.contains(&index) {
+            let cycle: Vec<&str> = visiting
+                .iter()
+                .map(|&i| indicators[i].name.as_str())
+                .chain(std::iter::once(indicators[index].name.as_str()))
+                .collect();
+            return Err(format!(
+                "Custom indicators reference each other in a cycle: {}",
+                cycle.join(" -> ")
+            ));
+        }
+        let Some(formula) = compiled[index].take() else {
+            return Ok(());
+        };
+        visiting.push(index);
+        for reference in formula.references() {
+            visit(
+                names[reference.as_str()],
+                indicators,
+                names,
+                compiled,
+                visiting,
+                ordered,
+            )?;
+        }
+        visiting.pop();
+        ordered.push((indicators[index].name.clone(), formula));
+        Ok(())
+    }
+
+    let mut ordered = Vec::with_capacity(indicators.len());
+    let mut visiting = Vec::new();
+    for index in 0..indicators.len() {
+        visit(
+            index,
+            indicators,
+            &names,
+            &mut compiled,
+            &mut visiting,
+            &mut ordered,
+        )?;
+    }
+    Ok(ordered)
+}
+
+fn validate_indicator_name(name: &str) -> Result<(), String> {
+    let mut chars = name.chars();
+    let valid = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid {
+        return Err(format!(
+            "Custom indicator name `{}` must start with a letter and contain only letters, digits and underscores",
+            name
+        ));
+    }
+    let lower = name.to_ascii_lowercase();
+    if PRICE_SERIES.contains(&lower.as_str())
+        || KEYWORDS.contains(&lower.as_str())
+        || FUNCTIONS.iter().any(|f| f.name == lower)
+    {
+        return Err(format!(
+            "Custom indicator name `{}` is reserved by the formula language",
+            name
+        ));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Number(f64),
+    Ident(String),
+    LParen,
+    RParen,
+    Comma,
+    Op(&'static str),
+}
+
+impl TokenKind {
+    fn describe(&self) -> String {
+        match self {
+            TokenKind::Number(n) => format!("number `{}`", n),
+            TokenKind::Ident(name) => format!("`{}`", name),
+            TokenKind::LParen => "`(`".to_string(),
+            TokenKind::RParen => "`)`".to_string(),
+            TokenKind::Comma => "`,`".to_string(),
+            TokenKind::Op(op) => format!("`{}`", op),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    pos: usize,
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, FormulaError> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+        let kind = if c.is_ascii_digit()
+            || (c == '.' && chars.get(i + 1).is_some_and(char::is_ascii_digit))
+        {
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            let value = text
+                .parse::<f64>()
+                .map_err(|_| FormulaError::new(format!("invalid number `{}`", text), start))?;
+            TokenKind::Number(value)
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            TokenKind::Ident(chars[start..i].iter().collect())
+        } else {
+            let next = chars.get(i + 1).copied();
+            let (op, width) = match (c, next) {
+                ('(', _) => ("(", 1),
+                (')', _) => (")", 1),
+                (',', _) => (",", 1),
+                ('>', Some('=')) => (">=", 2),
+                ('<', Some('=')) => ("<=", 2),
+                ('=', Some('=')) => ("==", 2),
+                ('!', Some('=')) => ("!=", 2),
+                ('>', _) => (">", 1),
+                ('<', _) => ("<", 1),
+                ('+', _) => ("+", 1),
+                ('-', _) => ("-", 1),
+                ('*', _) => ("*", 1),
+                ('/', _) => ("/", 1),
+                ('=', _) => return Err(FormulaError::new("use `==` to compare values", start)),
+                _ => {
+                    return Err(FormulaError::new(
+                        format!("unexpected character `{}`", c),
+                        start,
+                    ))
+                }
+            };
+            i += width;
+            match op {
+                "(" => TokenKind::LParen,
+                ")" => TokenKind::RParen,
+                "," => TokenKind::Comma,
+                _ => TokenKind::Op(op),
+            }
+        };
+        tokens.push(Token { kind, pos: start });
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    pos: usize,
+    source_len: usize,
+    nodes: usize,
+    depth: usize,
+    indicators: &'a [&'a str],
+    references: Vec<String>,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&TokenKind> {
+        self.tokens.get(self.pos).map(|t| &t.kind)
+    }
+
+    fn position(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .map(|t| t.pos)
+            .unwrap_or(self.source_len)
+    }
+
+    fn eat_op(&mut self, ops: &[&'static str]) -> Option<&'static str> {
+        match self.peek() {
+            Some(TokenKind::Op(op)) if ops.contains(op) => {
+                let op = *op;
+                self.pos += 1;
+                Some(op)
+            }
+            Some(TokenKind::Ident(word)) if ops.contains(&word.as_str()) => {
+                let op = ops.iter().find(|o| **o == word.as_str()).copied();
+                self.pos += 1;
+                op
+            }
+            _ => None,
+        }
+    }
+
+    fn node(&mut self, expr: Expr) -> Result<Expr, FormulaError> {
+        self.nodes += 1;
+        if self.nodes > MAX_NODES {
+            return Err(FormulaError::new(
+                format!("formula has more than {} terms", MAX_NODES),
+                self.position(),
+            ));
+        }
+        Ok(expr)
+    }
+
+    fn descend(&mut self) -> Result<(), FormulaError> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(FormulaError::new(
+                format!("formula is nested more than {} levels deep", MAX_DEPTH),
+                self.position(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn binary(
+        &mut self,
+        ops: &[&'static str],
+        next: fn(&mut Self) -> Result<Expr, FormulaError>,
+    ) -> Result<Expr, FormulaError> {
+        let mut lhs = next(self)?;
+        while let Some(op) = self.eat_op(ops) {
+            let rhs = next(self)?;
+            let op = match op {
+                "or" => BinaryOp::Or,
+                "and" => BinaryOp::And,
+                ">" => BinaryOp::Gt,
+                "<" => BinaryOp::Lt,
+                ">=" => BinaryOp::Ge,
+                "<=" => BinaryOp::Le,
+                "==" => BinaryOp::Eq,
+                "!=" => BinaryOp::Ne,
+                "+" => BinaryOp::Add,
+                "-" => BinaryOp::Sub,
+                "*" => BinaryOp::Mul,
+                _ => BinaryOp::Div,
+            };
+            lhs = self.node(Expr::Binary(op, Box::new(lhs), Box::new(rhs)))?;
+        }
+        Ok(lhs)
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, FormulaError> {
+        self.binary(&["or"], Self::parse_and)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, FormulaError> {
+        self.binary(&["and"], Self::parse_not)
+    }
+
+    fn parse_not(&mut self) -> Result<Expr, FormulaError> {
+        if self.eat_op(&["not"]).is_some() {
+            self.descend()?;
+            let inner = self.parse_not()?;
+            self.depth -= 1;
+            return self.node(Expr::Unary(UnaryOp::Not, Box::new(inner)));
+        }
+        self.parse_comparison()
+    }
+
+    fn parse_comparison(&mut self) -> Result<Expr, FormulaError> {
+        self.binary(&[">=", "<=", "==", "!=", ">", "<"], Self::parse_additive)
+    }
+
+    fn parse_additive(&mut self) -> Result<Expr, FormulaError> {
+        self.binary(&["+", "-"], Self::parse_multiplicative)
+    }
+
+    fn parse_multiplicative(&mut self) -> Result<Expr, FormulaError> {
+        self.binary(&["*", "/"], Self::parse_unary)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, FormulaError> {
+        if self.eat_op(&["-"]).is_some() {
+            self.descend()?;
+            let inner = self.parse_unary()?;
+            self.depth -= 1;
+            return self.node(Expr::Unary(UnaryOp::Neg, Box::new(inner)));
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, FormulaError> {
+        let pos = self.position();
+        let Some(token) = self.tokens.get(self.pos).cloned() else {
+            return Err(FormulaError::new("formula ends unexpectedly", pos));
+        };
+        self.pos += 1;
+        match token.kind {
+            TokenKind::Number(value) => self.node(Expr::Number(value)),
+            TokenKind::LParen => {
+                self.descend()?;
+                let inner = self.parse_or()?;
+                self.depth -= 1;
+                self.expect_rparen(pos)?;
+                Ok(inner)
+            }
+            TokenKind::Ident(name) if self.peek() == Some(&TokenKind::LParen) => {
+                self.parse_call(&name, pos)
+            }
+            TokenKind::Ident(name) => {
+                if let Some(series) = PriceSeries::from_name(&name) {
+                    return self.node(Expr::Price(series));
+                }
+                if self.indicators.contains(&name.as_str()) {
+                    if !self.references.contains(&name) {
+                        self.references.push(name.clone());
+                    }
+                    return self.node(Expr::Indicator(name));
+                }
+                if FUNCTIONS.iter().any(|f| f.name == name) {
+                    return Err(FormulaError::new(
+                        format!("`{}` is a function and needs arguments", name),
+                        pos,
+                    ));
+                }
+                let mut candidates: Vec<&str> = PRICE_SERIES.to_vec();
+                candidates.extend(self.indicators.iter().copied());
+                Err(FormulaError::new(
+                    format!(
+                        "unknown series `{}`{}",
+                        name,
+                        suggestion(&name, candidates.into_iter())
+                    ),
+                    pos,
+                ))
+            }
+            other => Err(FormulaError::new(
+                format!("expected a value, found {}", other.describe()),
+                pos,
+            )),
+        }
+    }
+
+    fn expect_rparen(&mut self, open_pos: usize) -> Result<(), FormulaError> {
+        match self.peek() {
+            Some(TokenKind::RParen) => {
+                self.pos += 1;
+                Ok(())
+            }
+            Some(other) => Err(FormulaError::new(
+                format!("expected `)`, found {}", other.describe()),
+                self.position(),
+            )),
+            None => Err(FormulaError::new("unclosed `(`", open_pos)),
+        }
+    }
+
+    fn parse_call(&mut self, name: &str, pos: usize) -> Result<Expr, FormulaError> {
+        let Some(spec) = FUNCTIONS.iter().find(|f| f.name == name) else {
+            return Err(FormulaError::new(
+                format!(
+                    "unknown function `{}`{}",
+                    name,
+                    suggestion(name, FUNCTIONS.iter().map(|f| f.name))
+                ),
+                pos,
+            ));
+        };
+
+        // Consume `(`.
+        self.pos += 1;
+        self.descend()?;
+        let mut raw_args = Vec::new();
+        if self.peek() != Some(&TokenKind::RParen) {
+            loop {
+                let arg_pos = self.position();
+                raw_args.push((self.parse_or()?, arg_pos));
+                if self.peek() == Some(&TokenKind::Comma) {
+                    self.pos += 1;
+                } else {
+                    break;
+                }
+            }
+        }
+        self.expect_rparen(pos)?;
+        self.depth -= 1;
+
+        if raw_args.len() != spec.params.len() {
+            return Err(FormulaError::new(
+                format!(
+                    "`{}` takes {} argument{}, got {}; usage: {}",
+                    spec.name,
+                    spec.params.len(),
+                    if spec.params.len() == 1 { "" } else { "s" },
+                    raw_args.len(),
+                    spec.signature
+                ),
+                pos,
+            ));
+        }
+
+        let mut args = Vec::new();
+        let mut period = None;
+        for ((arg, arg_pos), param) in raw_args.into_iter().zip(spec.params) {
+            match param {
+                Param::Series => args.push(arg),
+                Param::Period => period = Some(period_value(spec, &arg, arg_pos)?),
+            }
+        }
+        self.node(Expr::Call {
+            function: spec.function,
+            args,
+            period,
+        })
+    }
+}
+
+fn period_value(spec: &FunctionSpec, arg: &Expr, pos: usize) -> Result<usize, FormulaError> {
+    match arg {
+        Expr::Number(n) if n.fract() == 0.0 && *n >= 1.0 && *n <= MAX_PERIOD as f64 => {
+            Ok(*n as usize)
+        }
+        _ => Err(FormulaError::new(
+            format!(
+                "the period of `{}` must be a whole number from 1 to {}; usage: {}",
+                spec.name, MAX_PERIOD, spec.signature
+            ),
+            pos,
+        )),
+    }
+}
+
+/// `; did you mean ...?` when a candidate is at most two edits away.
+fn suggestion<'a>(name: &str, candidates: impl Iterator<Item = &'a str>) -> String {
+    candidates
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= 2)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| format!("; did you mean `{}`?", candidate))
+        .unwrap_or_default()
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let current = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                previous
+            } else {
+                1 + previous.min(row[j]).min(current)
+            };
+            previous = current;
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(source: &str) -> FormulaError {
+        Formula::compile(source).unwrap_err()
+    }
+
+    #[test]
+    fn reports_unknown_names_arity_and_periods() {
+        assert_eq!(
+            error("emaa(close, 21)").message,
+            "unknown function `emaa`; did you mean `ema`?"
+        );
+        let arity = error("(high - low) / atr()");
+        assert_eq!(
+            arity.message,
+            "`atr` takes 1 argument, got 0; usage: atr(period)"
+        );
+        assert_eq!(arity.position, 15);
+        assert!(error("ema(close, 2.5)").message.contains("whole number"));
+        assert!(error("ema(close, close)").message.contains("whole number"));
+        assert!(error("clsoe > 1")
+            .message
+            .contains("unknown series `clsoe`; did you mean `close`?"));
+        assert_eq!(error("ema(close, 21").message, "unclosed `(`");
+        assert!(error("close = open").message.contains("=="));
+    }
+
+    #[test]
+    fn warmup_covers_nested_lookbacks() {
+        let warmup = |source: &str| Formula::compile(source).unwrap().warmup_bars();
+        assert_eq!(warmup("close"), 1);
+        assert_eq!(warmup("ema(close, 200)"), 200);
+        assert_eq!(warmup("rsi(close, 14) > 70"), 15);
+        assert_eq!(warmup("crosses_above(sma(close, 5), lag(close, 10))"), 12);
+    }
+
+    #[test]
+    fn bounds_formula_size_and_nesting() {
+        let deep = format!("{}close{}", "(".repeat(40), ")".repeat(40));
+        assert!(error(&deep).message.contains("nested"));
+        let wide = ["1"; 300].join("+");
+        assert!(error(&wide).message.contains("more than 256 terms"));
+        let long = "1".repeat(MAX_FORMULA_LEN + 1);
+        assert!(error(&long).message.contains("longer than"));
+    }
+
+    #[test]
+    fn orders_custom_indicators_and_rejects_cycles() {
+        let indicator = |name: &str, expression: &str| CustomIndicator {
+            id: name.to_string(),
+            name: name.to_string(),
+            expression: expression.to_string(),
+            language_version: FORMULA_LANGUAGE_VERSION,
+            panel: "main".to_string(),
+            color: None,
+        };
+
+        let ordered = compile_custom_indicators(&[
+            indicator("signal", "ema(macd, 9)"),
+            indicator("macd", "ema(close, 12) - ema(close, 26)"),
+        ])
+        .unwrap();
+        let names: Vec<&str> = ordered.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, vec!["macd", "signal"]);
+
+        let err = compile_custom_indicators(&[indicator("a", "b + 1"), indicator("b", "a * 2")])
+            .unwrap_err();
+        assert_eq!(
+            err,
+            "Custom indicators reference each other in a cycle: a -> b -> a"
+        );
+
+        assert!(compile_custom_indicators(&[indicator("ema", "close")])
+            .unwrap_err()
+            .contains("reserved"));
+    }
+}
@@ -1,9 +1,21 @@
+mod eval;
+mod formula;
+mod monitor;
+mod share;
+
+pub use eval::*;
+pub use formula::*;
+pub use monitor::*;
+pub use share::*;
+
+use crate::chart_stream::{Candle, CandleResolution, MAX_CANDLE_HISTORY};
+use crate::drawings::{DrawingKind, DrawingTemplate, DrawingsChangedEvent, SharedDrawingManager};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::PathBuf;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{Notify, RwLock};
 
 pub type SharedIndicatorManager = Arc<RwLock<IndicatorManager>>;
 
@@ -21,12 +33,50 @@ pub struct IndicatorConfig {
     pub visible: Option<bool>,
 }
 
+impl IndicatorConfig {
+    /// The formula equivalent of a built-in indicator, for the types the
+    /// formula language covers.
+    pub fn formula(&self) -> Option<String> {
+        let period = self.params.get("period").and_then(|v| v.as_u64())?;
+        let function = match self.indicator_type.to_ascii_uppercase().as_str() {
+            "SMA" => "sma",
+            "EMA" => "ema",
+            "WMA" => "wma",
+            "RSI" => "rsi",
+            "ATR" => return Some(format!("atr({})", period)),
+            _ => return None,
+        };
+        Some(format!("{}(close, {})", function, period))
+    }
+}
+
+/// A user-defined indicator computed from a formula such as
+/// `ema(close, 21) - ema(close, 55)`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomIndicator {
+    pub id: String,
+    /// Name other formulas in the same preset use to read this indicator.
+    pub name: String,
+    pub expression: String,
+    /// Formula language version the expression was written for.
+    #[serde(default = "default_language_version")]
+    pub language_version: u32,
+    pub panel: String,
+    pub color: Option<String>,
+}
+
+fn default_language_version() -> u32 {
+    FORMULA_LANGUAGE_VERSION
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IndicatorPreset {
     pub id: String,
     pub name: String,
     pub description: Option<String>,
     pub indicators: Vec<IndicatorConfig>,
+    #[serde(default)]
+    pub custom_indicators: Vec<CustomIndicator>,
     pub created_at: String,
     pub updated_at: String,
 }
@@ -39,12 +89,80 @@ pub struct IndicatorAlert {
     pub threshold: f64,
     pub enabled: bool,
     pub notification_channels: Vec<String>,
+    /// When set, `condition` and `threshold` apply to this formula's value
+    /// instead of the indicator's, e.g. `close - ema(close, 21)`.
+    #[serde(default)]
+    pub formula: Option<String>,
+    /// Market the backend evaluates this alert on whenever a candle of
+    /// `resolution` (default 1m) closes. Alerts without a symbol are only
+    /// evaluated on demand.
+    #[serde(default)]
+    pub symbol: Option<String>,
+    #[serde(default)]
+    pub resolution: Option<CandleResolution>,
+}
+
+impl IndicatorAlert {
+    /// The formula this alert watches: its own, or that of the built-in
+    /// indicator it refers to.
+    fn source_formula(&self, indicators: &[IndicatorConfig]) -> Option<String> {
+        self.formula.clone().or_else(|| {
+            indicators
+                .iter()
+                .find(|i| i.id == self.indicator_id)
+                .and_then(IndicatorConfig::formula)
+        })
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let Some(formula) = &self.formula else {
+            return Ok(());
+        };
+        if !ALERT_CONDITIONS.contains(&self.condition.as_str()) {
+            return Err(format!(
+                "Unknown alert condition `{}`; expected one of {}",
+                self.condition,
+                ALERT_CONDITIONS.join(", ")
+            ));
+        }
+        let compiled =
+            Formula::compile(formula).map_err(|e| format!("Invalid alert formula: {}", e))?;
+        // Live evaluation only keeps this many candles per stream.
+        if self.symbol.is_some() && compiled.warmup_bars() + 2 > MAX_CANDLE_HISTORY {
+            return Err(format!(
+                "Alert formula needs {} candles to warm up; live alerts support up to {}",
+                compiled.warmup_bars(),
+                MAX_CANDLE_HISTORY - 2
+            ));
+        }
+        Ok(())
+    }
+}
+
+const ALERT_CONDITIONS: [&str; 4] = ["above", "below", "crosses_above", "crosses_below"];
+
+/// A formula alert whose condition holds on the latest candle.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndicatorAlertTrigger {
+    pub alert_id: String,
+    pub value: f64,
+}
+
+fn condition_met(condition: &str, threshold: f64, previous: Option<f64>, current: f64) -> bool {
+    match condition {
+        "above" => current > threshold,
+        "below" => current < threshold,
+        "crosses_above" => previous.is_some_and(|p| p <= threshold) && current > threshold,
+        "crosses_below" => previous.is_some_and(|p| p >= threshold) && current < threshold,
+        _ => false,
+    }
 }
 
 pub struct IndicatorManager {
     indicators_path: PathBuf,
     presets_path: PathBuf,
     alerts_path: PathBuf,
+    alerts_changed: Arc<Notify>,
 }
 
 impl IndicatorManager {
@@ -56,14 +174,21 @@ impl IndicatorManager {
             indicators_path: indicators_dir.join("indicators.json"),
             presets_path: indicators_dir.join("presets.json"),
             alerts_path: indicators_dir.join("alerts.json"),
+            alerts_changed: Arc::new(Notify::new()),
         }
     }
 
+    /// Notified whenever alerts or the indicators they refer to change.
+    pub fn alerts_changed(&self) -> Arc<Notify> {
+        self.alerts_changed.clone()
+    }
+
     pub fn save_indicators(&self, indicators: &[IndicatorConfig]) -> Result<(), String> {
         let json = serde_json::to_string_pretty(indicators)
             .map_err(|e| format!("Failed to serialize indicators: {}", e))?;
         fs::write(&self.indicators_path, json)
             .map_err(|e| format!("Failed to write indicators: {}", e))?;
+        self.alerts_changed.notify_one();
         Ok(())
     }
 
@@ -88,6 +213,7 @@ impl IndicatorManager {
     }
 
     pub fn save_preset(&self, preset: &IndicatorPreset) -> Result<(), String> {
+        compile_custom_indicators(&preset.custom_indicators)?;
         let mut presets = self.list_presets().unwrap_or_default();
 
         // Remove existing preset with the same ID if any
@@ -116,6 +242,83 @@ impl IndicatorManager {
         self.save_preset(preset)
     }
 
+    /// A shareable code for the preset, bundling `drawing_templates` if any.
+    pub fn export_preset(
+        &self,
+        preset_id: &str,
+        drawing_templates: Vec<DrawingTemplate>,
+    ) -> Result<String, String> {
+        let preset = self
+            .list_presets()?
+            .into_iter()
+            .find(|p| p.id == preset_id)
+            .ok_or_else(|| format!("Preset {} not found", preset_id))?;
+        encode_preset_code(&preset, drawing_templates)
+    }
+
+    /// Saves a decoded preset code as a new preset, suffixing its name if a
+    /// preset with that name already exists. Drawing templates in the payload
+    /// are left to the caller.
+    pub fn import_preset(
+        &self,
+        payload: &SharedPresetPayload,
+    ) -> Result<PresetImportReport, String> {
+        let taken: HashSet<String> = self.list_presets()?.into_iter().map(|p| p.name).collect();
+        let name = unique_name(&payload.name, &taken);
+        let now = chrono::Utc::now().to_rfc3339();
+        let preset = IndicatorPreset {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: name.clone(),
+            description: payload.description.clone(),
+            indicators: payload.indicators.clone(),
+            custom_indicators: payload.custom_indicators.clone(),
+            created_at: now.clone(),
+            updated_at: now,
+        };
+        self.save_preset(&preset)?;
+
+        Ok(PresetImportReport {
+            code_version: PRESET_CODE_VERSION,
+            preset_renamed_from: (name != payload.name.trim()).then(|| payload.name.clone()),
+            indicator_types: preset
+                .indicators
+                .iter()
+                .map(|i| i.indicator_type.clone())
+                .collect(),
+            custom_indicators: preset
+                .custom_indicators
+                .iter()
+                .map(|i| i.name.clone())
+                .collect(),
+            drawing_templates: Vec::new(),
+            preset,
+        })
+    }
+
+    /// Values of every custom indicator in a preset, keyed by indicator id.
+    pub fn evaluate_preset(
+        &self,
+        preset_id: &str,
+        candles: &[Candle],
+    ) -> Result<HashMap<String, Vec<Option<f64>>>, String> {
+        let preset = self
+            .list_presets()?
+            .into_iter()
+            .find(|p| p.id == preset_id)
+            .ok_or_else(|| format!("Preset {} not found", preset_id))?;
+        let compiled = compile_custom_indicators(&preset.custom_indicators)?;
+
+        let mut evaluator = FormulaEvaluator::new(candles)?;
+        let mut values = HashMap::new();
+        for (name, formula) in &compiled {
+            let series = evaluator.define(name, formula)?;
+            if let Some(indicator) = preset.custom_indicators.iter().find(|i| &i.name == name) {
+                values.insert(indicator.id.clone(), series);
+            }
+        }
+        Ok(values)
+    }
+
     pub fn list_alerts(&self) -> Result<Vec<IndicatorAlert>, String> {
         if !self.alerts_path.exists() {
             return Ok(Vec::new());
@@ -127,12 +330,14 @@ impl IndicatorManager {
     }
 
     pub fn create_alert(&self, alert: &IndicatorAlert) -> Result<(), String> {
+        alert.validate()?;
         let mut alerts = self.list_alerts().unwrap_or_default();
         alerts.push(alert.clone());
 
         let json = serde_json::to_string_pretty(&alerts)
             .map_err(|e| format!("Failed to serialize alerts: {}", e))?;
         fs::write(&self.alerts_path, json).map_err(|e| format!("Failed to write alerts: {}", e))?;
+        self.alerts_changed.notify_one();
         Ok(())
     }
 
@@ -143,10 +348,12 @@ impl IndicatorManager {
         let json = serde_json::to_string_pretty(&alerts)
             .map_err(|e| format!("Failed to serialize alerts: {}", e))?;
         fs::write(&self.alerts_path, json).map_err(|e| format!("Failed to write alerts: {}", e))?;
+        self.alerts_changed.notify_one();
         Ok(())
     }
 
     pub fn update_alert(&self, alert_id: &str, updated: &IndicatorAlert) -> Result<(), String> {
+        updated.validate()?;
         let mut alerts = self.list_alerts().unwrap_or_default();
         if let Some(alert) = alerts.iter_mut().find(|a| a.id == alert_id) {
             *alert = updated.clone();
@@ -155,8 +362,38 @@ impl IndicatorManager {
         let json = serde_json::to_string_pretty(&alerts)
             .map_err(|e| format!("Failed to serialize alerts: {}", e))?;
         fs::write(&self.alerts_path, json).map_err(|e| format!("Failed to write alerts: {}", e))?;
+        self.alerts_changed.notify_one();
         Ok(())
     }
+
+    /// Checks enabled formula alerts against the latest candle.
+    pub fn evaluate_alerts(
+        &self,
+        candles: &[Candle],
+    ) -> Result<Vec<IndicatorAlertTrigger>, String> {
+        let evaluator = FormulaEvaluator::new(candles)?;
+        let mut triggers = Vec::new();
+        for alert in self.list_alerts()?.into_iter().filter(|a| a.enabled) {
+            let Some(source) = &alert.formula else {
+                continue;
+            };
+            let formula = Formula::compile(source)
+                .map_err(|e| format!("Alert {} has an invalid formula: {}", alert.id, e))?;
+            let values = evaluator.evaluate(&formula)?;
+            let mut recent = values.iter().rev();
+            let Some(current) = recent.next().copied().flatten() else {
+                continue;
+            };
+            let previous = recent.next().copied().flatten();
+            if condition_met(&alert.condition, alert.threshold, previous, current) {
+                triggers.push(IndicatorAlertTrigger {
+                    alert_id: alert.id,
+                    value: current,
+                });
+            }
+        }
+        Ok(triggers)
+    }
 }
 
 // Tauri commands
@@ -247,3 +484,104 @@ pub async fn indicator_update_alert(
     let mgr = manager.read().await;
     mgr.update_alert(&alert_id, &updates)
 }
+
+/// `template_ids` names drawing templates to bundle with the preset.
+#[tauri::command]
+pub async fn indicator_export_preset(
+    preset_id: String,
+    template_ids: Option<Vec<String>>,
+    manager: tauri::State<'_, SharedIndicatorManager>,
+    drawings: tauri::State<'_, SharedDrawingManager>,
+) -> Result<String, String> {
+    let templates = match template_ids.filter(|ids| !ids.is_empty()) {
+        Some(ids) => {
+            let templates: Vec<DrawingTemplate> = drawings
+                .read()
+                .await
+                .list_templates()?
+                .into_iter()
+                .filter(|t| ids.contains(&t.id))
+                .collect();
+            if let Some(missing) = ids
+                .iter()
+                .find(|id| !templates.iter().any(|t| &t.id == *id))
+            {
+                return Err(format!("Drawing template {} not found", missing));
+            }
+            templates
+        }
+        None => Vec::new(),
+    };
+    let mgr = manager.read().await;
+    mgr.export_preset(&preset_id, templates)
+}
+
+#[tauri::command]
+pub async fn indicator_import_preset(
+    code: String,
+    window: tauri::Window,
+    manager: tauri::State<'_, SharedIndicatorManager>,
+    drawings: tauri::State<'_, SharedDrawingManager>,
+) -> Result<PresetImportReport, String> {
+    let payload = decode_preset_code(&code).map_err(|e| e.to_string())?;
+    let mut report = {
+        let mgr = manager.read().await;
+        mgr.import_preset(&payload)?
+    };
+
+    if !payload.drawing_templates.is_empty() {
+        let origin = window.label().to_string();
+        let mut drawings = drawings.write().await;
+        let (templates, names) =
+            prepare_templates(payload.drawing_templates, &drawings.list_templates()?);
+        let result = drawings.add_templates(&templates, &origin)?;
+        crate::drawings::emit_changed(
+            &window,
+            &DrawingsChangedEvent {
+                kind: DrawingKind::Template,
+                symbol: None,
+                object_ids: result.changed_ids.clone(),
+                origin,
+                cursor: result.cursor,
+            },
+        );
+        report.drawing_templates = names;
+    }
+
+    Ok(report)
+}
+
+#[tauri::command]
+pub async fn indicator_validate_formula(expression: String) -> Result<(), String> {
+    Formula::compile(&expression)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn indicator_evaluate_formula(
+    expression: String,
+    candles: Vec<Candle>,
+) -> Result<Vec<Option<f64>>, String> {
+    let formula = Formula::compile(&expression).map_err(|e| e.to_string())?;
+    FormulaEvaluator::new(&candles)?.evaluate(&formula)
+}
+
+#[tauri::command]
+pub async fn indicator_evaluate_preset(
+    preset_id: String,
+    candles: Vec<Candle>,
+    manager: tauri::State<'_, SharedIndicatorManager>,
+) -> Result<HashMap<String, Vec<Option<f64>>>, String> {
+    let mgr = manager.read().await;
+    mgr.evaluate_preset(&preset_id, &candles)
+}
+
+#[tauri::command]
+pub async fn indicator_evaluate_alerts(
+    candles: Vec<Candle>,
+    manager: tauri::State<'_, SharedIndicatorManager>,
+) -> Result<Vec<IndicatorAlertTrigger>, String> {
+    let mgr = manager.read().await;
+    mgr.evaluate_alerts(&candles)
+}
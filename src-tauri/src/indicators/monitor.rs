@@ -0,0 +1,393 @@
+use super::{
+    condition_met, Formula, FormulaEvaluator, IndicatorAlert, IndicatorConfig,
+    SharedIndicatorManager,
+};
+use crate::alerts::price_alerts::{AlertTriggerEvent, NotificationChannel};
+use crate::chart_stream::{self, Candle, CandleResolution, ChartCandleUpdate};
+use crate::core::WebSocketManager;
+use crate::notifications::integration::send_alert_notifications;
+use crate::notifications::router::SharedNotificationRouter;
+use crate::portfolio::token_annotation_summary;
+use chrono::{TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::{interval, Duration};
+
+pub const INDICATOR_ALERT_EVENT: &str = "indicator_alert_triggered";
+
+/// Also retries stream subscriptions that failed, e.g. before the price
+/// stream connected.
+const RECONCILE_INTERVAL: Duration = Duration::from_secs(60);
+/// The monitor only reads closed candles, so it never asks for frequent
+/// in-progress updates.
+const STREAM_UPDATE_INTERVAL_MS: u64 = 60_000;
+
+type StreamKey = (String, CandleResolution);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndicatorAlertEvent {
+    pub alert_id: String,
+    pub indicator_id: String,
+    pub symbol: String,
+    pub resolution: CandleResolution,
+    pub condition: String,
+    pub threshold: f64,
+    pub value: f64,
+    /// The closed candle the condition was met on.
+    pub candle: Candle,
+    pub triggered_at: String,
+}
+
+/// An enabled alert the backend evaluates on candle close.
+struct WatchedAlert {
+    alert: IndicatorAlert,
+    formula: Formula,
+    /// Candles to evaluate over: the warm-up plus two more, so level
+    /// conditions can tell whether they already held on the previous close.
+    bars: usize,
+}
+
+/// Enabled alerts with a symbol, grouped by the stream they need. Alerts
+/// whose indicator has no formula equivalent are left to the chart.
+fn plan_watches(
+    alerts: Vec<IndicatorAlert>,
+    indicators: &[IndicatorConfig],
+) -> HashMap<StreamKey, Vec<WatchedAlert>> {
+    let mut watches: HashMap<StreamKey, Vec<WatchedAlert>> = HashMap::new();
+    for alert in alerts.into_iter().filter(|a| a.enabled) {
+        let Some(symbol) = alert.symbol.clone() else {
+            continue;
+        };
+        let Some(source) = alert.source_formula(indicators) else {
+            continue;
+        };
+        let formula = match Formula::compile(&source) {
+            Ok(formula) => formula,
+            Err(e) => {
+                eprintln!("Skipping indicator alert {}: {}", alert.id, e);
+                continue;
+            }
+        };
+        let resolution = alert.resolution.unwrap_or(CandleResolution::OneMinute);
+        let bars = formula.warmup_bars() + 2;
+        watches
+            .entry((symbol, resolution))
+            .or_default()
+            .push(WatchedAlert {
+                alert,
+                formula,
+                bars,
+            });
+    }
+    watches
+}
+
+/// The latest value when the alert fires on the newest candle. Level
+/// conditions fire when they start to hold rather than on every close, which
+/// also keeps a restart from repeating an alert that already fired.
+fn fires_on_close(condition: &str, threshold: f64, values: &[Option<f64>]) -> Option<f64> {
+    let mut recent = values.iter().rev().copied();
+    let current = recent.next().flatten()?;
+    let previous = recent.next().flatten();
+    let before = recent.next().flatten();
+    if !condition_met(condition, threshold, previous, current) {
+        return None;
+    }
+    let held_before = matches!(condition, "above" | "below")
+        && previous.is_some_and(|p| condition_met(condition, threshold, before, p));
+    (!held_before).then_some(current)
+}
+
+#[derive(Default)]
+struct AlertMonitor {
+    watches: HashMap<StreamKey, Vec<WatchedAlert>>,
+    /// Streams this monitor holds a subscriber on, with the history each was
+    /// backfilled to.
+    streams: HashMap<StreamKey, usize>,
+}
+
+impl AlertMonitor {
+    async fn reconcile(
+        &mut self,
+        app: &AppHandle,
+        indicators: &SharedIndicatorManager,
+        ws_manager: &WebSocketManager,
+    ) {
+        let loaded = {
+            let mgr = indicators.read().await;
+            mgr.list_alerts()
+                .and_then(|alerts| mgr.load_indicators().map(|configs| (alerts, configs)))
+        };
+        let (alerts, configs) = match loaded {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                eprintln!("Failed to load indicator alerts: {}", e);
+                return;
+            }
+        };
+        self.watches = plan_watches(alerts, &configs);
+
+        let stale: Vec<StreamKey> = self
+            .streams
+            .keys()
+            .filter(|key| !self.watches.contains_key(*key))
+            .cloned()
+            .collect();
+        for key in stale {
+            self.streams.remove(&key);
+            if let Err(e) = chart_stream::unsubscribe_stream(ws_manager, &key.0, key.1).await {
+                eprintln!(
+                    "Failed to release {} {} candles: {}",
+                    key.0,
+                    key.1.as_str(),
+                    e
+                );
+            }
+        }
+
+        for (key, watched) in &self.watches {
+            let bars = watched.iter().map(|w| w.bars).max().unwrap_or(0);
+            match self.streams.get(key).copied() {
+                None => {
+                    match chart_stream::subscribe_stream(
+                        app,
+                        ws_manager,
+                        &key.0,
+                        key.1,
+                        STREAM_UPDATE_INTERVAL_MS,
+                        bars,
+                    )
+                    .await
+                    {
+                        Ok(_) => {
+                            self.streams.insert(key.clone(), bars);
+                        }
+                        Err(e) => eprintln!(
+                            "Failed to stream {} {} candles for indicator alerts: {}",
+                            key.0,
+                            key.1.as_str(),
+                            e
+                        ),
+                    }
+                }
+                // A new alert needs a longer warm-up than the stream has.
+                Some(backfilled) if backfilled < bars => {
+                    chart_stream::backfill_history(&key.0, key.1, bars).await;
+                    self.streams.insert(key.clone(), bars);
+                }
+                Some(_) => {}
+            }
+        }
+    }
+
+    fn on_candle_close(&self, app: &AppHandle, update: &ChartCandleUpdate) {
+        let key = (update.symbol.clone(), update.resolution);
+        let Some(watched) = self.watches.get(&key) else {
+            return;
+        };
+        let bars = watched.iter().map(|w| w.bars).max().unwrap_or(0);
+        let candles = chart_stream::recent_candles(&update.symbol, update.resolution, bars);
+        if candles.last().map(|c| c.time) != Some(update.candle.time) {
+            return;
+        }
+
+        for watch in watched {
+            let window = &candles[candles.len().saturating_sub(watch.bars)..];
+            let values =
+                match FormulaEvaluator::new(window).and_then(|e| e.evaluate(&watch.formula)) {
+                    Ok(values) => values,
+                    Err(e) => {
+                        eprintln!(
+                            "Failed to evaluate indicator alert {}: {}",
+                            watch.alert.id, e
+                        );
+                        continue;
+                    }
+                };
+            if let Some(value) =
+                fires_on_close(&watch.alert.condition, watch.alert.threshold, &values)
+            {
+                fire(app, &watch.alert, update, value);
+            }
+        }
+    }
+}
+
+fn fire(app: &AppHandle, alert: &IndicatorAlert, update: &ChartCandleUpdate, value: f64) {
+    let candle = &update.candle;
+    let triggered_at = Utc::now().to_rfc3339();
+    let event = IndicatorAlertEvent {
+        alert_id: alert.id.clone(),
+        indicator_id: alert.indicator_id.clone(),
+        symbol: update.symbol.clone(),
+        resolution: update.resolution,
+        condition: alert.condition.clone(),
+        threshold: alert.threshold,
+        value,
+        candle: candle.clone(),
+        triggered_at: triggered_at.clone(),
+    };
+    if let Err(e) = app.emit(INDICATOR_ALERT_EVENT, &event) {
+        eprintln!("Failed to emit indicator alert {}: {}", alert.id, e);
+    }
+
+    let Some(router) = app.try_state::<SharedNotificationRouter>() else {
+        return;
+    };
+    let opened_at = Utc
+        .timestamp_opt(candle.time, 0)
+        .single()
+        .map(|t| t.format("%Y-%m-%d %H:%M UTC").to_string())
+        .unwrap_or_default();
+    let trigger =
+        AlertTriggerEvent {
+            alert_id: alert.id.clone(),
+            alert_name: format!("{} {} alert", update.symbol, alert.indicator_id),
+            symbol: update.symbol.clone(),
+            current_price: candle.close,
+            conditions_met: format!(
+            "{} {} {} ({:.4}) on the {} candle opened {}: O {:.4} H {:.4} L {:.4} C {:.4} V {:.2}",
+            alert.formula.as_deref().unwrap_or(alert.indicator_id.as_str()),
+            alert.condition.replace('_', " "),
+            alert.threshold,
+            value,
+            update.resolution.as_str(),
+            opened_at,
+            candle.open,
+            candle.high,
+            candle.low,
+            candle.close,
+            candle.volume
+        ),
+            triggered_at,
+            annotation: token_annotation_summary(app, &update.symbol),
+        };
+    let channels = alert
+        .notification_channels
+        .iter()
+        .filter_map(|c| NotificationChannel::from_str(c))
+        .collect();
+    tauri::async_runtime::spawn(send_alert_notifications(
+        router.inner().clone(),
+        trigger,
+        channels,
+    ));
+}
+
+/// Evaluates indicator alerts on every candle close, whether or not a chart
+/// shows the market. Holds its own chart stream subscribers, so closing chart
+/// windows never stops evaluation.
+pub async fn run_indicator_alert_monitor(
+    app: AppHandle,
+    indicators: SharedIndicatorManager,
+    ws_manager: WebSocketManager,
+) {
+    let mut closed = chart_stream::closed_candles();
+    let changed = indicators.read().await.alerts_changed();
+    let mut monitor = AlertMonitor::default();
+    let mut reconcile = interval(RECONCILE_INTERVAL);
+
+    loop {
+        tokio::select! {
+            _ = reconcile.tick() => {
+                monitor.reconcile(&app, &indicators, &ws_manager).await;
+            }
+            _ = changed.notified() => {
+                monitor.reconcile(&app, &indicators, &ws_manager).await;
+            }
+            update = closed.recv() => match update {
+                Ok(update) => monitor.on_candle_close(&app, &update),
+                Err(RecvError::Lagged(skipped)) => {
+                    eprintln!("Indicator alert monitor skipped {} candle closes", skipped);
+                }
+                Err(RecvError::Closed) => break,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alert(id: &str, indicator_id: &str, formula: Option<&str>) -> IndicatorAlert {
+        IndicatorAlert {
+            id: id.to_string(),
+            indicator_id: indicator_id.to_string(),
+            condition: "crosses_above".to_string(),
+            threshold: 0.0,
+            enabled: true,
+            notification_channels: vec!["in_app".to_string()],
+            formula: formula.map(str::to_string),
+            symbol: Some("SOL".to_string()),
+            resolution: Some(CandleResolution::FiveMinutes),
+        }
+    }
+
+    #[test]
+    fn plans_warm_up_per_stream_from_formulas_and_builtins() {
+        let ema = IndicatorConfig {
+            id: "ema-200".to_string(),
+            indicator_type: "EMA".to_string(),
+            enabled: true,
+            panel: "main".to_string(),
+            params: HashMap::from([("period".to_string(), serde_json::json!(200))]),
+            color: None,
+            line_width: None,
+            style: None,
+            visible: None,
+        };
+        let mut chart_only = alert("c", "macd", None);
+        chart_only.indicator_id = "unknown".to_string();
+        let mut no_symbol = alert("d", "x", Some("close"));
+        no_symbol.symbol = None;
+
+        let watches = plan_watches(
+            vec![
+                alert("a", "ema-200", None),
+                alert("b", "custom", Some("close - ema(close, 21)")),
+                chart_only,
+                no_symbol,
+            ],
+            &[ema],
+        );
+
+        let key = ("SOL".to_string(), CandleResolution::FiveMinutes);
+        assert_eq!(watches.len(), 1);
+        let bars: Vec<(&str, usize)> = watches[&key]
+            .iter()
+            .map(|w| (w.alert.id.as_str(), w.bars))
+            .collect();
+        assert_eq!(bars, vec![("a", 202), ("b", 23)]);
+    }
+
+    #[test]
+    fn level_conditions_fire_once_when_they_start_to_hold() {
+        let values = |v: &[f64]| v.iter().map(|x| Some(*x)).collect::<Vec<_>>();
+
+        assert_eq!(
+            fires_on_close("above", 1.0, &values(&[0.5, 0.8, 1.2])),
+            Some(1.2)
+        );
+        assert_eq!(
+            fires_on_close("above", 1.0, &values(&[0.5, 1.1, 1.2])),
+            None
+        );
+        assert_eq!(
+            fires_on_close("below", 1.0, &values(&[1.5, 1.2, 0.9])),
+            Some(0.9)
+        );
+        assert_eq!(
+            fires_on_close("crosses_above", 1.0, &values(&[0.5, 0.9, 1.1])),
+            Some(1.1)
+        );
+        assert_eq!(
+            fires_on_close("crosses_above", 1.0, &values(&[1.1, 1.2])),
+            None
+        );
+        // Not enough history for a value yet.
+        assert_eq!(fires_on_close("above", 1.0, &[None, None]), None);
+    }
+}
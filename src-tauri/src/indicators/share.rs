@@ -0,0 +1,401 @@
+use base64::{engine::general_purpose, Engine};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use uuid::Uuid;
+
+use super::{compile_custom_indicators, CustomIndicator, IndicatorConfig, IndicatorPreset};
+use crate::drawings::DrawingTemplate;
+
+/// Prefix of a preset code; the format version follows it, e.g. `emp1`.
+const PRESET_CODE_PREFIX: &str = "emp";
+pub const PRESET_CODE_VERSION: u32 = 1;
+/// Hex characters of the SHA-256 kept as the code's checksum.
+const CHECKSUM_CHARS: usize = 12;
+/// Upper bound on the decompressed payload, so a hostile code cannot
+/// inflate into something huge.
+const MAX_PAYLOAD_BYTES: usize = 512 * 1024;
+const COMPRESSION_LEVEL: i32 = 19;
+
+/// Built-in indicator types this version of the app can render. Imported
+/// presets referring to anything else are rejected.
+pub const SUPPORTED_INDICATOR_TYPES: &[&str] = &[
+    "SMA",
+    "EMA",
+    "WMA",
+    "VWAP",
+    "RSI",
+    "MACD",
+    "STOCH",
+    "STOCH_RSI",
+    "BB",
+    "KC",
+    "ATR",
+    "ADX",
+    "CCI",
+    "MFI",
+    "OBV",
+    "WILLIAMS_R",
+    "PSAR",
+    "SUPERTREND",
+    "ICHIMOKU",
+    "VOLUME",
+];
+
+/// What a preset code carries. Ids are not shared; the importer assigns its own.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SharedPresetPayload {
+    pub name: String,
+    pub description: Option<String>,
+    pub indicators: Vec<IndicatorConfig>,
+    #[serde(default)]
+    pub custom_indicators: Vec<CustomIndicator>,
+    #[serde(default)]
+    pub drawing_templates: Vec<DrawingTemplate>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum PresetCodeError {
+    #[error("Preset code is empty")]
+    Empty,
+    #[error("Not an indicator preset code")]
+    NotAPresetCode,
+    #[error("Preset code is truncated")]
+    Truncated,
+    #[error(
+        "Preset code version {0} is newer than this app supports; update the app to import it"
+    )]
+    UnsupportedVersion(u32),
+    #[error("Preset code checksum does not match; the code was altered or copied incompletely")]
+    ChecksumMismatch,
+    #[error("Preset code contains characters outside its alphabet")]
+    InvalidEncoding,
+    #[error("Preset code payload could not be decompressed")]
+    InvalidCompression,
+    #[error("Preset code payload is too large")]
+    PayloadTooLarge,
+    #[error("Preset code payload is invalid: {0}")]
+    InvalidPayload(String),
+    #[error("Preset uses indicator types this app version does not support: {}", .0.join(", "))]
+    UnknownIndicatorTypes(Vec<String>),
+    #[error("Preset has an invalid custom indicator: {0}")]
+    InvalidFormula(String),
+}
+
+pub fn is_supported_indicator_type(indicator_type: &str) -> bool {
+    SUPPORTED_INDICATOR_TYPES
+        .iter()
+        .any(|t| t.eq_ignore_ascii_case(indicator_type))
+}
+
+fn checksum(version: u32, body: &str) -> String {
+    let digest = Sha256::digest(format!("{}{}.{}", PRESET_CODE_PREFIX, version, body));
+    hex::encode(digest)[..CHECKSUM_CHARS].to_string()
+}
+
+/// Builds a shareable code for `preset`, optionally bundling drawing templates.
+pub fn encode_preset_code(
+    preset: &IndicatorPreset,
+    drawing_templates: Vec<DrawingTemplate>,
+) -> Result<String, String> {
+    let payload = SharedPresetPayload {
+        name: preset.name.clone(),
+        description: preset.description.clone(),
+        indicators: preset.indicators.clone(),
+        custom_indicators: preset.custom_indicators.clone(),
+        drawing_templates: drawing_templates
+            .into_iter()
+            .map(|template| DrawingTemplate {
+                revision: 0,
+                ..template
+            })
+            .collect(),
+    };
+    let json =
+        serde_json::to_vec(&payload).map_err(|e| format!("Failed to serialize preset: {}", e))?;
+    let compressed = zstd::encode_all(json.as_slice(), COMPRESSION_LEVEL)
+        .map_err(|e| format!("Failed to compress preset: {}", e))?;
+    let body = general_purpose::URL_SAFE_NO_PAD.encode(compressed);
+    Ok(format!(
+        "{}{}.{}.{}",
+        PRESET_CODE_PREFIX,
+        PRESET_CODE_VERSION,
+        checksum(PRESET_CODE_VERSION, &body),
+        body
+    ))
+}
+
+/// Parses and validates a preset code without saving anything.
+pub fn decode_preset_code(code: &str) -> Result<SharedPresetPayload, PresetCodeError> {
+    let code: String = code.chars().filter(|c| !c.is_whitespace()).collect();
+    if code.is_empty() {
+        return Err(PresetCodeError::Empty);
+    }
+
+    let mut parts = code.splitn(3, '.');
+    let header = parts.next().unwrap_or_default();
+    let version = header
+        .strip_prefix(PRESET_CODE_PREFIX)
+        .and_then(|v| v.parse::<u32>().ok())
+        .ok_or(PresetCodeError::NotAPresetCode)?;
+    if version == 0 {
+        return Err(PresetCodeError::NotAPresetCode);
+    }
+    if version > PRESET_CODE_VERSION {
+        return Err(PresetCodeError::UnsupportedVersion(version));
+    }
+    let (Some(expected), Some(body)) = (parts.next(), parts.next()) else {
+        return Err(PresetCodeError::Truncated);
+    };
+    if expected.len() != CHECKSUM_CHARS || body.is_empty() {
+        return Err(PresetCodeError::Truncated);
+    }
+    if checksum(version, body) != expected.to_ascii_lowercase() {
+        return Err(PresetCodeError::ChecksumMismatch);
+    }
+
+    let compressed = general_purpose::URL_SAFE_NO_PAD
+        .decode(body)
+        .map_err(|_| PresetCodeError::InvalidEncoding)?;
+    let json = decompress(&compressed)?;
+    let payload: SharedPresetPayload = serde_json::from_slice(&json)
+        .map_err(|e| PresetCodeError::InvalidPayload(e.to_string()))?;
+    validate_payload(&payload)?;
+    Ok(payload)
+}
+
+fn decompress(compressed: &[u8]) -> Result<Vec<u8>, PresetCodeError> {
+    use std::io::Read;
+
+    let decoder = zstd::stream::read::Decoder::new(compressed)
+        .map_err(|_| PresetCodeError::InvalidCompression)?;
+    let mut json = Vec::new();
+    decoder
+        .take(MAX_PAYLOAD_BYTES as u64 + 1)
+        .read_to_end(&mut json)
+        .map_err(|_| PresetCodeError::InvalidCompression)?;
+    if json.len() > MAX_PAYLOAD_BYTES {
+        return Err(PresetCodeError::PayloadTooLarge);
+    }
+    Ok(json)
+}
+
+fn validate_payload(payload: &SharedPresetPayload) -> Result<(), PresetCodeError> {
+    if payload.name.trim().is_empty() {
+        return Err(PresetCodeError::InvalidPayload(
+            "preset has no name".to_string(),
+        ));
+    }
+
+    let mut seen = HashSet::new();
+    let unknown: Vec<String> = payload
+        .indicators
+        .iter()
+        .filter(|i| !is_supported_indicator_type(&i.indicator_type))
+        .filter(|i| seen.insert(i.indicator_type.to_ascii_uppercase()))
+        .map(|i| i.indicator_type.clone())
+        .collect();
+    if !unknown.is_empty() {
+        return Err(PresetCodeError::UnknownIndicatorTypes(unknown));
+    }
+
+    compile_custom_indicators(&payload.custom_indicators)
+        .map_err(PresetCodeError::InvalidFormula)?;
+    Ok(())
+}
+
+/// Returns `name`, or `name (2)`, `name (3)`, ... if it is already taken.
+pub fn unique_name(name: &str, taken: &HashSet<String>) -> String {
+    let name = name.trim();
+    if !taken.contains(name) {
+        return name.to_string();
+    }
+    (2..)
+        .map(|n| format!("{} ({})", name, n))
+        .find(|candidate| !taken.contains(candidate))
+        .unwrap_or_else(|| name.to_string())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportedName {
+    pub id: String,
+    pub name: String,
+    /// The name in the code, when it collided and was suffixed.
+    pub renamed_from: Option<String>,
+}
+
+/// Everything an `indicator_import_preset` call added.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PresetImportReport {
+    pub code_version: u32,
+    pub preset: IndicatorPreset,
+    pub preset_renamed_from: Option<String>,
+    pub indicator_types: Vec<String>,
+    pub custom_indicators: Vec<String>,
+    pub drawing_templates: Vec<ImportedName>,
+}
+
+/// Gives imported templates fresh ids and suffixes names that collide with
+/// `existing`.
+pub fn prepare_templates(
+    templates: Vec<DrawingTemplate>,
+    existing: &[DrawingTemplate],
+) -> (Vec<DrawingTemplate>, Vec<ImportedName>) {
+    let mut taken: HashSet<String> = existing.iter().map(|t| t.name.clone()).collect();
+    let mut prepared = Vec::with_capacity(templates.len());
+    let mut names = Vec::with_capacity(templates.len());
+    for template in templates {
+        let name = unique_name(&template.name, &taken);
+        taken.insert(name.clone());
+        let id = Uuid::new_v4().to_string();
+        names.push(ImportedName {
+            id: id.clone(),
+            name: name.clone(),
+            renamed_from: (name != template.name.trim()).then(|| template.name.clone()),
+        });
+        prepared.push(DrawingTemplate {
+            id,
+            name,
+            revision: 0,
+            ..template
+        });
+    }
+    (prepared, names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::drawings::{DrawingPoint, DrawingStyle};
+    use crate::indicators::FORMULA_LANGUAGE_VERSION;
+    use std::collections::HashMap;
+
+    fn preset() -> IndicatorPreset {
+        IndicatorPreset {
+            id: "p1".to_string(),
+            name: "Trend".to_string(),
+            description: Some("EMA ribbon".to_string()),
+            indicators: vec![IndicatorConfig {
+                id: "ema21".to_string(),
+                indicator_type: "EMA".to_string(),
+                enabled: true,
+                panel: "main".to_string(),
+                params: HashMap::from([("period".to_string(), serde_json::json!(21))]),
+                color: Some("#ff9900".to_string()),
+                line_width: Some(2),
+                style: None,
+                visible: Some(true),
+            }],
+            custom_indicators: vec![CustomIndicator {
+                id: "spread".to_string(),
+                name: "spread".to_string(),
+                expression: "ema(close, 21) - ema(close, 55)".to_string(),
+                language_version: FORMULA_LANGUAGE_VERSION,
+                panel: "lower".to_string(),
+                color: None,
+            }],
+            created_at: "2026-01-01T00:00:00Z".to_string(),
+            updated_at: "2026-01-01T00:00:00Z".to_string(),
+        }
+    }
+
+    fn template() -> DrawingTemplate {
+        DrawingTemplate {
+            id: "t1".to_string(),
+            name: "Support".to_string(),
+            description: None,
+            tool: "horizontal_line".to_string(),
+            style: DrawingStyle {
+                stroke_color: "#00ff00".to_string(),
+                stroke_width: 1.0,
+                fill_color: None,
+                opacity: 1.0,
+                line_style: Some("dashed".to_string()),
+                font_size: None,
+                font_family: None,
+                bold: None,
+                italic: None,
+                background: None,
+            },
+            default_points: vec![DrawingPoint {
+                x: 0.0,
+                y: 0.0,
+                timestamp: None,
+                price: None,
+            }],
+            metadata: None,
+            revision: 7,
+        }
+    }
+
+    #[test]
+    fn test_code_round_trips_with_templates() {
+        let code = encode_preset_code(&preset(), vec![template()]).unwrap();
+        assert!(code.starts_with("emp1."));
+
+        let payload = decode_preset_code(&format!("  {}\n", code)).unwrap();
+        assert_eq!(payload.name, "Trend");
+        assert_eq!(payload.indicators[0].indicator_type, "EMA");
+        assert_eq!(payload.custom_indicators[0].name, "spread");
+        assert_eq!(payload.drawing_templates[0].name, "Support");
+        assert_eq!(payload.drawing_templates[0].revision, 0);
+    }
+
+    #[test]
+    fn test_malformed_codes_report_specific_errors() {
+        let code = encode_preset_code(&preset(), Vec::new()).unwrap();
+
+        assert_eq!(decode_preset_code("   "), Err(PresetCodeError::Empty));
+        assert_eq!(
+            decode_preset_code("hello world"),
+            Err(PresetCodeError::NotAPresetCode)
+        );
+        assert_eq!(
+            decode_preset_code(&code.replacen("emp1", "emp9", 1)),
+            Err(PresetCodeError::UnsupportedVersion(9))
+        );
+        assert_eq!(decode_preset_code("emp1."), Err(PresetCodeError::Truncated));
+        assert_eq!(
+            decode_preset_code(&code[..code.len() - 5]),
+            Err(PresetCodeError::ChecksumMismatch)
+        );
+
+        let garbled = format!("emp1.{}.{}", checksum(1, "***"), "***");
+        assert_eq!(
+            decode_preset_code(&garbled),
+            Err(PresetCodeError::InvalidEncoding)
+        );
+        let not_zstd = general_purpose::URL_SAFE_NO_PAD.encode(b"plain text");
+        assert_eq!(
+            decode_preset_code(&format!("emp1.{}.{}", checksum(1, &not_zstd), not_zstd)),
+            Err(PresetCodeError::InvalidCompression)
+        );
+    }
+
+    #[test]
+    fn test_unknown_indicator_types_are_rejected() {
+        let mut preset = preset();
+        preset.indicators[0].indicator_type = "HYPERWAVE".to_string();
+        let code = encode_preset_code(&preset, Vec::new()).unwrap();
+        assert_eq!(
+            decode_preset_code(&code),
+            Err(PresetCodeError::UnknownIndicatorTypes(vec![
+                "HYPERWAVE".to_string()
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_name_collisions_are_suffixed() {
+        let taken: HashSet<String> = ["Trend".to_string(), "Trend (2)".to_string()].into();
+        assert_eq!(unique_name("Trend", &taken), "Trend (3)");
+        assert_eq!(unique_name("Scalp", &taken), "Scalp");
+
+        let (prepared, names) = prepare_templates(vec![template(), template()], &[template()]);
+        assert_eq!(names[0].name, "Support (2)");
+        assert_eq!(names[0].renamed_from.as_deref(), Some("Support"));
+        assert_eq!(names[1].name, "Support (3)");
+        assert_ne!(prepared[0].id, "t1");
+        assert_eq!(prepared[0].revision, 0);
+    }
+}
@@ -1,4 +1,5 @@
 use super::types::*;
+use crate::webhooks::events::{emit_webhook_event, WebhookEventType, WhaleDetectedPayload};
 use chrono::{DateTime, Duration, Utc};
 use serde_json::json;
 use sqlx::{Row, SqlitePool};
@@ -138,6 +139,20 @@ impl AlertManager {
             let _ = self.app_handle.emit("whale_alert", alert);
         }
 
+        emit_webhook_event(
+            &self.app_handle,
+            WebhookEventType::WhaleDetected,
+            WhaleDetectedPayload {
+                wallet_address: alert.wallet_address.clone(),
+                wallet_label: alert.wallet_label.clone(),
+                action_type: alert.action_type.clone(),
+                token_symbol: alert.token_symbol.clone(),
+                amount_usd: alert.amount_usd,
+                threshold: alert.threshold,
+                tx_signature: alert.tx_signature.clone(),
+            },
+        );
+
         if config.telegram_enabled {
             if let Some(telegram_config_id) = &config.telegram_config_id {
                 let _ = self
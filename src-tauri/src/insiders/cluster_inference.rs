@@ -0,0 +1,795 @@
+use super::types::*;
+use super::WalletMonitor;
+use crate::api_config::{rpc_endpoint, solana_rpc};
+use crate::token_flow::builtin_known_entities;
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value};
+use sqlx::{Pool, Row, Sqlite};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tauri::AppHandle;
+use tokio::sync::Mutex;
+use tokio::time::{sleep, Duration, Instant};
+use uuid::Uuid;
+
+/// Spacing between RPC requests; keeps the job under public endpoint limits.
+const RPC_MIN_INTERVAL: Duration = Duration::from_millis(250);
+const SIGNATURE_PAGE_SIZE: usize = 1000;
+/// Pages of history walked back when looking for a wallet's first funding.
+const MAX_SIGNATURE_PAGES: usize = 3;
+/// Oldest transactions of a wallet checked for the funding transfer.
+const FUNDING_TX_SCAN: usize = 5;
+/// Recent transactions of a funder checked for other wallets it funded.
+const FUNDER_SCAN_LIMIT: usize = 50;
+const FUNDER_SCAN_TTL_SECS: i64 = 6 * 3600;
+/// Wallets whose funder could not be found are looked up again after this.
+const UNFUNDED_RETRY_SECS: i64 = 24 * 3600;
+pub const MAX_FUNDING_HOPS: u32 = 4;
+const DEFAULT_FUNDING_HOPS: u32 = 2;
+const DEFAULT_MIN_CONFIDENCE: f64 = 0.4;
+const HOP_DECAY: f64 = 0.6;
+/// Funders that funded more wallets than this look like services and weigh less.
+const FANOUT_FREE: usize = 5;
+const TIMING_SCALE_SECS: f64 = 7.0 * 86_400.0;
+
+/// SOL transfers in a `jsonParsed` transaction, including inner instructions.
+pub fn system_transfers(tx: &Value) -> Vec<FundingEdge> {
+    let signature = tx["transaction"]["signatures"][0]
+        .as_str()
+        .unwrap_or_default()
+        .to_string();
+    let timestamp = tx["blockTime"].as_i64().unwrap_or(0);
+
+    let outer = tx["transaction"]["message"]["instructions"]
+        .as_array()
+        .into_iter()
+        .flatten();
+    let inner = tx["meta"]["innerInstructions"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .flat_map(|group| group["instructions"].as_array().into_iter().flatten());
+
+    outer
+        .chain(inner)
+        .filter(|ix| ix["program"].as_str() == Some("system"))
+        .filter_map(|ix| {
+            let info = &ix["parsed"]["info"];
+            let funded = match ix["parsed"]["type"].as_str()? {
+                "transfer" | "transferWithSeed" => info["destination"].as_str()?,
+                "createAccount" | "createAccountWithSeed" => info["newAccount"].as_str()?,
+                _ => return None,
+            };
+            Some(FundingEdge {
+                funder: info["source"].as_str()?.to_string(),
+                funded: funded.to_string(),
+                lamports: info["lamports"].as_u64().unwrap_or(0),
+                signature: signature.clone(),
+                timestamp,
+            })
+        })
+        .filter(|edge| edge.funder != edge.funded)
+        .collect()
+}
+
+/// How likely `sibling` belongs to the same operator as the wallet funded by
+/// `path_edge`, given both were funded by the same wallet `hops` levels up.
+/// Siblings funded close in time and with similar amounts score higher;
+/// funders that fan out to many wallets score lower.
+pub fn sibling_confidence(
+    hops: u32,
+    path_edge: &FundingEdge,
+    sibling: &FundingEdge,
+    funder_fanout: usize,
+) -> f64 {
+    let hop_weight = HOP_DECAY.powi(hops.saturating_sub(1) as i32);
+    let timing =
+        (-((path_edge.timestamp - sibling.timestamp).abs() as f64) / TIMING_SCALE_SECS).exp();
+    let (low, high) = if path_edge.lamports <= sibling.lamports {
+        (path_edge.lamports, sibling.lamports)
+    } else {
+        (sibling.lamports, path_edge.lamports)
+    };
+    let amount = if high == 0 {
+        0.0
+    } else {
+        low as f64 / high as f64
+    };
+    let fanout_penalty = if funder_fanout <= FANOUT_FREE {
+        1.0
+    } else {
+        (FANOUT_FREE as f64 / funder_fanout as f64).sqrt()
+    };
+
+    (hop_weight * (0.4 + 0.35 * timing + 0.25 * amount) * fanout_penalty).clamp(0.0, 1.0)
+}
+
+#[derive(Default)]
+struct RunStats {
+    cache_hits: usize,
+}
+
+/// Finds wallets that share funding sources with monitored wallets. RPC
+/// calls are spaced out and funding lookups are cached, so re-running the
+/// job mostly reads from the cache.
+pub struct ClusterInference {
+    pool: Pool<Sqlite>,
+    app_handle: AppHandle,
+    next_rpc_slot: Mutex<Instant>,
+    rpc_calls: AtomicUsize,
+    run_lock: Mutex<()>,
+}
+
+impl ClusterInference {
+    pub async fn new(pool: Pool<Sqlite>, app_handle: AppHandle) -> Result<Self, sqlx::Error> {
+        let inference = Self {
+            pool,
+            app_handle,
+            next_rpc_slot: Mutex::new(Instant::now()),
+            rpc_calls: AtomicUsize::new(0),
+            run_lock: Mutex::new(()),
+        };
+        inference.initialize().await?;
+        Ok(inference)
+    }
+
+    async fn initialize(&self) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS funding_sources (
+                wallet_address TEXT PRIMARY KEY,
+                funder TEXT,
+                lamports INTEGER,
+                signature TEXT,
+                funded_at INTEGER,
+                fetched_at INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS funding_edges (
+                signature TEXT NOT NULL,
+                funder TEXT NOT NULL,
+                funded TEXT NOT NULL,
+                lamports INTEGER NOT NULL,
+                funded_at INTEGER NOT NULL,
+                PRIMARY KEY (signature, funded)
+            );
+            CREATE INDEX IF NOT EXISTS idx_funding_edges_funder ON funding_edges(funder);
+            CREATE TABLE IF NOT EXISTS funder_scans (
+                funder TEXT PRIMARY KEY,
+                fetched_at INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS cluster_candidates (
+                id TEXT PRIMARY KEY,
+                seed_wallet TEXT NOT NULL,
+                candidate_wallet TEXT NOT NULL,
+                shared_funder TEXT NOT NULL,
+                hops INTEGER NOT NULL,
+                confidence REAL NOT NULL,
+                status TEXT NOT NULL,
+                funding_signature TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                reviewed_at TEXT,
+                UNIQUE(seed_wallet, candidate_wallet)
+            );
+            CREATE INDEX IF NOT EXISTS idx_cluster_candidates_status ON cluster_candidates(status);
+            CREATE TABLE IF NOT EXISTS insider_clusters (
+                id TEXT PRIMARY KEY,
+                label TEXT,
+                created_at TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS insider_cluster_members (
+                wallet_address TEXT PRIMARY KEY,
+                cluster_id TEXT NOT NULL,
+                added_at TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_insider_cluster_members_cluster ON insider_cluster_members(cluster_id);
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Paced so the job stays under public endpoint limits; 429s are
+    /// retried by the shared RPC helper.
+    async fn rpc(&self, method: &str, params: Value) -> Result<Value, String> {
+        {
+            let mut next = self.next_rpc_slot.lock().await;
+            sleep(next.saturating_duration_since(Instant::now())).await;
+            *next = Instant::now() + RPC_MIN_INTERVAL;
+        }
+        self.rpc_calls.fetch_add(1, Ordering::Relaxed);
+        solana_rpc(&rpc_endpoint(&self.app_handle), method, params).await
+    }
+
+    async fn signatures(
+        &self,
+        address: &str,
+        limit: usize,
+        max_pages: usize,
+    ) -> Result<Vec<String>, String> {
+        let mut signatures = Vec::new();
+        let mut before: Option<String> = None;
+        for _ in 0..max_pages {
+            let mut options = json!({ "limit": limit });
+            if let Some(before) = &before {
+                options["before"] = json!(before);
+            }
+            let page = self
+                .rpc("getSignaturesForAddress", json!([address, options]))
+                .await?;
+            let page: Vec<String> = page
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|entry| entry["signature"].as_str().map(str::to_string))
+                .collect();
+            let full = page.len() == limit;
+            before = page.last().cloned();
+            signatures.extend(page);
+            if !full {
+                break;
+            }
+        }
+        Ok(signatures)
+    }
+
+    async fn transaction_transfers(&self, signature: &str) -> Result<Vec<FundingEdge>, String> {
+        let tx = self
+            .rpc(
+                "getTransaction",
+                json!([
+                    signature,
+                    { "encoding": "jsonParsed", "maxSupportedTransactionVersion": 0 }
+                ]),
+            )
+            .await?;
+        Ok(system_transfers(&tx))
+    }
+
+    /// The first SOL transfer into `wallet`, cached once found.
+    async fn funding_source(
+        &self,
+        wallet: &str,
+        stats: &mut RunStats,
+    ) -> Result<Option<FundingEdge>, String> {
+        let now = Utc::now().timestamp();
+        let cached = sqlx::query(
+            "SELECT funder, lamports, signature, funded_at, fetched_at FROM funding_sources WHERE wallet_address = ?1",
+        )
+        .bind(wallet)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| format!("Failed to read funding cache: {e}"))?;
+
+        if let Some(row) = cached {
+            let funder: Option<String> = row.try_get("funder").unwrap_or(None);
+            let fetched_at: i64 = row.try_get("fetched_at").unwrap_or(0);
+            match funder {
+                Some(funder) => {
+                    stats.cache_hits += 1;
+                    return Ok(Some(FundingEdge {
+                        funder,
+                        funded: wallet.to_string(),
+                        lamports: row.try_get::<i64, _>("lamports").unwrap_or(0) as u64,
+                        signature: row.try_get("signature").unwrap_or_default(),
+                        timestamp: row.try_get("funded_at").unwrap_or(0),
+                    }));
+                }
+                None if now - fetched_at < UNFUNDED_RETRY_SECS => {
+                    stats.cache_hits += 1;
+                    return Ok(None);
+                }
+                None => {}
+            }
+        }
+
+        let signatures = self
+            .signatures(wallet, SIGNATURE_PAGE_SIZE, MAX_SIGNATURE_PAGES)
+            .await?;
+        let mut funding = None;
+        for signature in signatures.iter().rev().take(FUNDING_TX_SCAN) {
+            let transfers = self.transaction_transfers(signature).await?;
+            if let Some(edge) = transfers.into_iter().find(|edge| edge.funded == wallet) {
+                funding = Some(edge);
+                break;
+            }
+        }
+
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO funding_sources
+                (wallet_address, funder, lamports, signature, funded_at, fetched_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            "#,
+        )
+        .bind(wallet)
+        .bind(funding.as_ref().map(|edge| edge.funder.clone()))
+        .bind(funding.as_ref().map(|edge| edge.lamports as i64))
+        .bind(funding.as_ref().map(|edge| edge.signature.clone()))
+        .bind(funding.as_ref().map(|edge| edge.timestamp))
+        .bind(now)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| format!("Failed to cache funding source: {e}"))?;
+
+        if let Some(edge) = &funding {
+            self.store_edges(std::slice::from_ref(edge)).await?;
+        }
+        Ok(funding)
+    }
+
+    async fn store_edges(&self, edges: &[FundingEdge]) -> Result<(), String> {
+        for edge in edges {
+            sqlx::query(
+                r#"
+                INSERT OR IGNORE INTO funding_edges (signature, funder, funded, lamports, funded_at)
+                VALUES (?1, ?2, ?3, ?4, ?5)
+                "#,
+            )
+            .bind(&edge.signature)
+            .bind(&edge.funder)
+            .bind(&edge.funded)
+            .bind(edge.lamports as i64)
+            .bind(edge.timestamp)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| format!("Failed to cache funding edge: {e}"))?;
+        }
+        Ok(())
+    }
+
+    /// Wallets `funder` sent SOL to, from a cached scan of its recent history.
+    async fn funded_wallets(
+        &self,
+        funder: &str,
+        stats: &mut RunStats,
+    ) -> Result<Vec<FundingEdge>, String> {
+        let now = Utc::now().timestamp();
+        let scanned_at: Option<i64> =
+            sqlx::query("SELECT fetched_at FROM funder_scans WHERE funder = ?1")
+                .bind(funder)
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(|e| format!("Failed to read funder cache: {e}"))?
+                .and_then(|row| row.try_get("fetched_at").ok());
+
+        if scanned_at.is_some_and(|at| now - at < FUNDER_SCAN_TTL_SECS) {
+            stats.cache_hits += 1;
+        } else {
+            for signature in self.signatures(funder, FUNDER_SCAN_LIMIT, 1).await? {
+                let transfers: Vec<FundingEdge> = self
+                    .transaction_transfers(&signature)
+                    .await?
+                    .into_iter()
+                    .filter(|edge| edge.funder == funder)
+                    .collect();
+                self.store_edges(&transfers).await?;
+            }
+            sqlx::query("INSERT OR REPLACE INTO funder_scans (funder, fetched_at) VALUES (?1, ?2)")
+                .bind(funder)
+                .bind(now)
+                .execute(&self.pool)
+                .await
+                .map_err(|e| format!("Failed to cache funder scan: {e}"))?;
+        }
+
+        let rows = sqlx::query(
+            "SELECT signature, funded, lamports, funded_at FROM funding_edges WHERE funder = ?1",
+        )
+        .bind(funder)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| format!("Failed to read funding edges: {e}"))?;
+
+        Ok(rows
+            .iter()
+            .map(|row| FundingEdge {
+                funder: funder.to_string(),
+                funded: row.try_get("funded").unwrap_or_default(),
+                lamports: row.try_get::<i64, _>("lamports").unwrap_or(0) as u64,
+                signature: row.try_get("signature").unwrap_or_default(),
+                timestamp: row.try_get("funded_at").unwrap_or(0),
+            })
+            .collect())
+    }
+
+    /// Walks up to `max_hops` funders above each seed wallet and proposes the
+    /// other wallets those funders created as cluster candidates.
+    pub async fn infer(
+        &self,
+        seeds: &[String],
+        monitored: &HashSet<String>,
+        max_hops: Option<u32>,
+        min_confidence: Option<f64>,
+    ) -> Result<ClusterInferenceReport, String> {
+        let _running = self.run_lock.lock().await;
+        let calls_before = self.rpc_calls.load(Ordering::Relaxed);
+        let max_hops = max_hops
+            .unwrap_or(DEFAULT_FUNDING_HOPS)
+            .clamp(1, MAX_FUNDING_HOPS);
+        let min_confidence = min_confidence.unwrap_or(DEFAULT_MIN_CONFIDENCE);
+        let services: HashSet<String> = builtin_known_entities()
+            .into_iter()
+            .map(|entity| entity.address)
+            .collect();
+
+        let mut stats = RunStats::default();
+        let mut found: HashMap<(String, String), (FundingEdge, u32, f64)> = HashMap::new();
+
+        for seed in seeds {
+            let mut path: HashSet<String> = HashSet::from([seed.clone()]);
+            let mut current = seed.clone();
+            for hop in 1..=max_hops {
+                let Some(path_edge) = self.funding_source(&current, &mut stats).await? else {
+                    break;
+                };
+                let funder = path_edge.funder.clone();
+                if services.contains(&funder) || path.contains(&funder) {
+                    break;
+                }
+
+                let children = self.funded_wallets(&funder, &mut stats).await?;
+                let fanout = children
+                    .iter()
+                    .map(|edge| edge.funded.as_str())
+                    .collect::<HashSet<_>>()
+                    .len();
+                for child in &children {
+                    if path.contains(&child.funded)
+                        || monitored.contains(&child.funded)
+                        || services.contains(&child.funded)
+                    {
+                        continue;
+                    }
+                    let confidence = sibling_confidence(hop, &path_edge, child, fanout);
+                    if confidence < min_confidence {
+                        continue;
+                    }
+                    let key = (seed.clone(), child.funded.clone());
+                    if found
+                        .get(&key)
+                        .is_none_or(|(_, _, best)| confidence > *best)
+                    {
+                        found.insert(key, (child.clone(), hop, confidence));
+                    }
+                }
+
+                path.insert(funder.clone());
+                current = funder;
+            }
+        }
+
+        let mut candidates = Vec::new();
+        for ((seed, _), (edge, hops, confidence)) in found {
+            if let Some(candidate) = self.save_candidate(&seed, &edge, hops, confidence).await? {
+                candidates.push(candidate);
+            }
+        }
+        candidates.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+
+        Ok(ClusterInferenceReport {
+            wallets_analyzed: seeds.len(),
+            rpc_calls: self.rpc_calls.load(Ordering::Relaxed) - calls_before,
+            cache_hits: stats.cache_hits,
+            candidates,
+        })
+    }
+
+    /// Stores a candidate, refreshing its score while it is still pending.
+    /// Returns `None` for candidates the user already reviewed.
+    async fn save_candidate(
+        &self,
+        seed: &str,
+        edge: &FundingEdge,
+        hops: u32,
+        confidence: f64,
+    ) -> Result<Option<ClusterCandidate>, String> {
+        sqlx::query(
+            r#"
+            INSERT INTO cluster_candidates (
+                id, seed_wallet, candidate_wallet, shared_funder, hops, confidence,
+                status, funding_signature, created_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, 'pending', ?7, ?8)
+            ON CONFLICT(seed_wallet, candidate_wallet) DO UPDATE SET
+                shared_funder = excluded.shared_funder,
+                hops = excluded.hops,
+                confidence = excluded.confidence,
+                funding_signature = excluded.funding_signature
+            WHERE cluster_candidates.status = 'pending'
+            "#,
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(seed)
+        .bind(&edge.funded)
+        .bind(&edge.funder)
+        .bind(hops as i64)
+        .bind(confidence)
+        .bind(&edge.signature)
+        .bind(Utc::now().to_rfc3339())
+        .execute(&self.pool)
+        .await
+        .map_err(|e| format!("Failed to save cluster candidate: {e}"))?;
+
+        let candidate = self
+            .candidates_where(
+                "seed_wallet = ?1 AND candidate_wallet = ?2",
+                &[seed, edge.funded.as_str()],
+            )
+            .await?
+            .into_iter()
+            .next();
+        Ok(candidate.filter(|c| c.status == ClusterCandidateStatus::Pending))
+    }
+
+    async fn candidates_where(
+        &self,
+        condition: &str,
+        binds: &[&str],
+    ) -> Result<Vec<ClusterCandidate>, String> {
+        let query = format!(
+            "SELECT * FROM cluster_candidates WHERE {} ORDER BY confidence DESC",
+            condition
+        );
+        let mut q = sqlx::query(&query);
+        for value in binds {
+            q = q.bind(*value);
+        }
+        let rows = q
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| format!("Failed to load cluster candidates: {e}"))?;
+
+        Ok(rows.iter().map(candidate_from_row).collect())
+    }
+
+    pub async fn list_candidates(
+        &self,
+        status: Option<ClusterCandidateStatus>,
+    ) -> Result<Vec<ClusterCandidate>, String> {
+        match status {
+            Some(status) => {
+                self.candidates_where("status = ?1", &[status.as_str()])
+                    .await
+            }
+            None => self.candidates_where("1 = 1", &[]).await,
+        }
+    }
+
+    /// Applies accept/reject decisions. Accepted wallets join the seed's
+    /// cluster and are monitored with the seed's label and settings.
+    pub async fn review_candidates(
+        &self,
+        monitor: &WalletMonitor,
+        decisions: Vec<ClusterCandidateDecision>,
+    ) -> Result<Vec<ClusterCandidate>, String> {
+        let mut wallets = monitor.list_wallets().await?;
+        let mut reviewed = Vec::new();
+
+        for decision in decisions {
+            let Some(candidate) = self
+                .candidates_where("id = ?1", &[decision.candidate_id.as_str()])
+                .await?
+                .into_iter()
+                .next()
+            else {
+                return Err(format!(
+                    "Cluster candidate {} not found",
+                    decision.candidate_id
+                ));
+            };
+            if candidate.status != ClusterCandidateStatus::Pending {
+                reviewed.push(candidate);
+                continue;
+            }
+
+            let status = if decision.accept {
+                let seed = wallets
+                    .iter()
+                    .find(|w| w.wallet_address == candidate.seed_wallet);
+                let label = seed.and_then(|w| w.label.clone());
+                let cluster_id = match self.cluster_of(&candidate.seed_wallet).await? {
+                    Some(cluster) => cluster.id,
+                    None => {
+                        self.create_cluster(&candidate.seed_wallet, label.clone())
+                            .await?
+                    }
+                };
+                self.add_member(&cluster_id, &candidate.candidate_wallet)
+                    .await?;
+
+                if !wallets
+                    .iter()
+                    .any(|w| w.wallet_address == candidate.candidate_wallet)
+                {
+                    let added = monitor
+                        .add_wallet(AddMonitoredWalletRequest {
+                            wallet_address: candidate.candidate_wallet.clone(),
+                            label,
+                            min_transaction_size: seed.and_then(|w| w.min_transaction_size),
+                            is_whale: seed.is_some_and(|w| w.is_whale),
+                        })
+                        .await?;
+                    wallets.push(added);
+                }
+                ClusterCandidateStatus::Accepted
+            } else {
+                ClusterCandidateStatus::Rejected
+            };
+
+            let reviewed_at = Utc::now();
+            sqlx::query(
+                "UPDATE cluster_candidates SET status = ?1, reviewed_at = ?2 WHERE id = ?3 AND status = 'pending'",
+            )
+            .bind(status.as_str())
+            .bind(reviewed_at.to_rfc3339())
+            .bind(&candidate.id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| format!("Failed to update cluster candidate: {e}"))?;
+
+            reviewed.push(ClusterCandidate {
+                status,
+                reviewed_at: Some(reviewed_at),
+                ..candidate
+            });
+        }
+
+        Ok(reviewed)
+    }
+
+    async fn create_cluster(&self, seed: &str, label: Option<String>) -> Result<String, String> {
+        let id = Uuid::new_v4().to_string();
+        sqlx::query("INSERT INTO insider_clusters (id, label, created_at) VALUES (?1, ?2, ?3)")
+            .bind(&id)
+            .bind(label)
+            .bind(Utc::now().to_rfc3339())
+            .execute(&self.pool)
+            .await
+            .map_err(|e| format!("Failed to create cluster: {e}"))?;
+        self.add_member(&id, seed).await?;
+        Ok(id)
+    }
+
+    async fn add_member(&self, cluster_id: &str, wallet: &str) -> Result<(), String> {
+        sqlx::query(
+            "INSERT OR IGNORE INTO insider_cluster_members (wallet_address, cluster_id, added_at) VALUES (?1, ?2, ?3)",
+        )
+        .bind(wallet)
+        .bind(cluster_id)
+        .bind(Utc::now().to_rfc3339())
+        .execute(&self.pool)
+        .await
+        .map_err(|e| format!("Failed to add cluster member: {e}"))?;
+        Ok(())
+    }
+
+    pub async fn list_clusters(&self) -> Result<Vec<InsiderCluster>, String> {
+        let rows = sqlx::query(
+            r#"
+            SELECT c.id, c.label, c.created_at, m.wallet_address
+            FROM insider_clusters c
+            LEFT JOIN insider_cluster_members m ON m.cluster_id = c.id
+            ORDER BY c.created_at, c.id, m.added_at
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| format!("Failed to list clusters: {e}"))?;
+
+        let mut clusters: Vec<InsiderCluster> = Vec::new();
+        for row in rows {
+            let id: String = row.try_get("id").unwrap_or_default();
+            let wallet: Option<String> = row.try_get("wallet_address").unwrap_or(None);
+            if clusters.last().is_none_or(|c| c.id != id) {
+                clusters.push(InsiderCluster {
+                    id,
+                    label: row.try_get("label").unwrap_or(None),
+                    wallets: Vec::new(),
+                    created_at: parse_time(row.try_get("created_at").ok()).unwrap_or_else(Utc::now),
+                });
+            }
+            if let (Some(cluster), Some(wallet)) = (clusters.last_mut(), wallet) {
+                cluster.wallets.push(wallet);
+            }
+        }
+        Ok(clusters)
+    }
+
+    pub async fn cluster_of(&self, wallet: &str) -> Result<Option<InsiderCluster>, String> {
+        let cluster_id: Option<String> =
+            sqlx::query("SELECT cluster_id FROM insider_cluster_members WHERE wallet_address = ?1")
+                .bind(wallet)
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(|e| format!("Failed to look up cluster: {e}"))?
+                .and_then(|row| row.try_get("cluster_id").ok());
+
+        let Some(cluster_id) = cluster_id else {
+            return Ok(None);
+        };
+        Ok(self
+            .list_clusters()
+            .await?
+            .into_iter()
+            .find(|cluster| cluster.id == cluster_id))
+    }
+}
+
+fn parse_time(value: Option<String>) -> Option<DateTime<Utc>> {
+    value
+        .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+fn candidate_from_row(row: &sqlx::sqlite::SqliteRow) -> ClusterCandidate {
+    ClusterCandidate {
+        id: row.try_get("id").unwrap_or_default(),
+        seed_wallet: row.try_get("seed_wallet").unwrap_or_default(),
+        candidate_wallet: row.try_get("candidate_wallet").unwrap_or_default(),
+        shared_funder: row.try_get("shared_funder").unwrap_or_default(),
+        hops: row.try_get::<i64, _>("hops").unwrap_or(1) as u32,
+        confidence: row.try_get("confidence").unwrap_or(0.0),
+        status: ClusterCandidateStatus::from_db(
+            &row.try_get::<String, _>("status").unwrap_or_default(),
+        ),
+        funding_signature: row.try_get("funding_signature").unwrap_or_default(),
+        created_at: parse_time(row.try_get("created_at").ok()).unwrap_or_else(Utc::now),
+        reviewed_at: parse_time(row.try_get("reviewed_at").unwrap_or(None)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edge(funded: &str, lamports: u64, timestamp: i64) -> FundingEdge {
+        FundingEdge {
+            funder: "FUNDER".to_string(),
+            funded: funded.to_string(),
+            lamports,
+            signature: format!("sig-{funded}"),
+            timestamp,
+        }
+    }
+
+    #[test]
+    fn parses_outer_and_inner_system_transfers() {
+        let tx = json!({
+            "blockTime": 1_700_000_000,
+            "transaction": {
+                "signatures": ["sig1"],
+                "message": { "instructions": [
+                    { "program": "system", "parsed": { "type": "transfer", "info": {
+                        "source": "F", "destination": "W1", "lamports": 5_000_000_000u64 } } },
+                    { "program": "spl-token", "parsed": { "type": "transfer", "info": {
+                        "source": "F", "destination": "X", "amount": "1" } } }
+                ] }
+            },
+            "meta": { "innerInstructions": [ { "index": 0, "instructions": [
+                { "program": "system", "parsed": { "type": "createAccount", "info": {
+                    "source": "F", "newAccount": "W2", "lamports": 2_039_280u64 } } }
+            ] } ] }
+        });
+
+        let transfers = system_transfers(&tx);
+        assert_eq!(transfers.len(), 2);
+        assert_eq!(transfers[0].funded, "W1");
+        assert_eq!(transfers[0].lamports, 5_000_000_000);
+        assert_eq!(transfers[1].funded, "W2");
+        assert!(transfers.iter().all(|t| t.signature == "sig1"));
+    }
+
+    #[test]
+    fn confidence_prefers_close_siblings_from_small_funders() {
+        let seed = edge("SEED", 1_000_000_000, 1_000);
+        let twin = edge("TWIN", 1_000_000_000, 1_600);
+        let stranger = edge("OTHER", 10_000_000, 1_000 + 60 * 86_400);
+
+        let twin_score = sibling_confidence(1, &seed, &twin, 3);
+        assert!(twin_score > 0.9);
+        assert!(sibling_confidence(1, &seed, &stranger, 3) < twin_score);
+        assert!(sibling_confidence(2, &seed, &twin, 3) < twin_score);
+        assert!(sibling_confidence(1, &seed, &twin, 500) < 0.2);
+    }
+}
@@ -1,6 +1,7 @@
 use super::types::*;
 use crate::insiders::wallet_monitor::require_state;
 use sqlx::Row;
+use std::collections::HashSet;
 
 #[tauri::command]
 pub async fn classify_smart_money_wallet(
@@ -27,6 +28,15 @@ pub async fn get_smart_money_consensus(
     detector.get_consensus(time_window_hours).await
 }
 
+#[tauri::command]
+pub async fn get_smart_money_wallet_accuracy(
+    wallet_address: String,
+) -> Result<SmartMoneyAccuracy, String> {
+    let state = require_state()?;
+    let detector = state.smart_money_detector.clone();
+    detector.get_wallet_accuracy(&wallet_address).await
+}
+
 #[tauri::command]
 pub async fn get_sentiment_comparison(token_mint: String) -> Result<SentimentComparison, String> {
     let state = require_state()?;
@@ -90,3 +100,52 @@ pub async fn scan_wallets_for_smart_money() -> Result<Vec<SmartMoneyClassificati
 
     Ok(classifications)
 }
+
+/// Looks for unmonitored wallets funded from the same source as monitored
+/// ones and stores them as pending cluster candidates.
+#[tauri::command]
+pub async fn wallet_monitor_infer_clusters(
+    request: ClusterInferenceRequest,
+) -> Result<ClusterInferenceReport, String> {
+    let state = require_state()?;
+    let wallets = state.monitor.list_wallets().await?;
+    let monitored: HashSet<String> = wallets.iter().map(|w| w.wallet_address.clone()).collect();
+    let seeds: Vec<String> = match request.wallet_addresses {
+        Some(addresses) => addresses,
+        None => wallets
+            .iter()
+            .filter(|w| w.is_active)
+            .map(|w| w.wallet_address.clone())
+            .collect(),
+    };
+
+    state
+        .cluster_inference
+        .infer(&seeds, &monitored, request.max_hops, request.min_confidence)
+        .await
+}
+
+#[tauri::command]
+pub async fn wallet_monitor_list_cluster_candidates(
+    status: Option<ClusterCandidateStatus>,
+) -> Result<Vec<ClusterCandidate>, String> {
+    let state = require_state()?;
+    state.cluster_inference.list_candidates(status).await
+}
+
+#[tauri::command]
+pub async fn wallet_monitor_review_cluster_candidates(
+    decisions: Vec<ClusterCandidateDecision>,
+) -> Result<Vec<ClusterCandidate>, String> {
+    let state = require_state()?;
+    state
+        .cluster_inference
+        .review_candidates(&state.monitor, decisions)
+        .await
+}
+
+#[tauri::command]
+pub async fn wallet_monitor_list_clusters() -> Result<Vec<InsiderCluster>, String> {
+    let state = require_state()?;
+    state.cluster_inference.list_clusters().await
+}
@@ -1,10 +1,12 @@
 pub mod alert_manager;
+pub mod cluster_inference;
 pub mod commands;
 pub mod smart_money;
 pub mod types;
 pub mod wallet_monitor;
 
 pub use alert_manager::*;
+pub use cluster_inference::*;
 pub use commands::*;
 pub use smart_money::*;
 pub use types::*;
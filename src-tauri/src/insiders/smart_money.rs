@@ -4,6 +4,44 @@ use sqlx::{Row, SqlitePool};
 use std::collections::HashMap;
 use uuid::Uuid;
 
+/// Pseudo-observations of a 50% hit rate mixed into every wallet's accuracy,
+/// so a handful of lucky or unlucky entries can't produce extreme weights.
+const ACCURACY_PRIOR_STRENGTH: f64 = 10.0;
+const ACCURACY_PRIOR_MEAN: f64 = 0.5;
+/// Outcomes fade out of the rolling accuracy with this half-life.
+const ACCURACY_HALF_LIFE_SECS: f64 = 30.0 * 86_400.0;
+/// Consensus signals lose half their weight every this many hours.
+const SIGNAL_HALF_LIFE_HOURS: f64 = 48.0;
+
+struct OutcomeHorizon {
+    seconds: i64,
+    /// How long past the horizon a price observation still counts.
+    tolerance_seconds: i64,
+    resolved_column: &'static str,
+    return_column: &'static str,
+    evaluated_column: &'static str,
+    hits_column: &'static str,
+}
+
+const OUTCOME_HORIZONS: [OutcomeHorizon; 2] = [
+    OutcomeHorizon {
+        seconds: 86_400,
+        tolerance_seconds: 6 * 3600,
+        resolved_column: "resolved_1d",
+        return_column: "return_1d",
+        evaluated_column: "evaluated_1d",
+        hits_column: "hits_1d",
+    },
+    OutcomeHorizon {
+        seconds: 7 * 86_400,
+        tolerance_seconds: 24 * 3600,
+        resolved_column: "resolved_7d",
+        return_column: "return_7d",
+        evaluated_column: "evaluated_7d",
+        hits_column: "hits_7d",
+    },
+];
+
 #[derive(Clone)]
 pub struct SmartMoneyDetector {
     pool: SqlitePool,
@@ -346,33 +384,344 @@ impl SmartMoneyDetector {
         .map_err(|e| format!("Failed to fetch smart money wallets: {e}"))
     }
 
+    /// Registers new buys and scores entries whose horizon has passed. Only
+    /// activity added since the last run and still-pending entries are read.
+    pub async fn refresh_accuracy(&self) -> Result<(), String> {
+        let now = Utc::now();
+        let cursor: i64 =
+            sqlx::query("SELECT last_rowid FROM smart_money_accuracy_cursor WHERE id = 1")
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(|e| format!("Failed to read accuracy cursor: {e}"))?
+                .and_then(|row| row.try_get("last_rowid").ok())
+                .unwrap_or(0);
+        let latest: i64 =
+            sqlx::query("SELECT COALESCE(MAX(rowid), 0) AS latest FROM wallet_activities")
+                .fetch_one(&self.pool)
+                .await
+                .map_err(|e| format!("Failed to read activity cursor: {e}"))?
+                .try_get("latest")
+                .unwrap_or(cursor);
+
+        if latest > cursor {
+            let mut tx = self
+                .pool
+                .begin()
+                .await
+                .map_err(|e| format!("Failed to start accuracy update: {e}"))?;
+            sqlx::query(
+                r#"
+                INSERT OR IGNORE INTO smart_money_entries (
+                    activity_id, wallet_address, token_mint, quote_mint, entry_price, entry_at
+                )
+                SELECT id, wallet_address, output_mint, input_mint, price, timestamp
+                FROM wallet_activities
+                WHERE rowid > ?1 AND rowid <= ?2
+                AND action_type = 'buy'
+                AND price > 0
+                AND output_mint IS NOT NULL
+                AND input_mint IS NOT NULL
+                "#,
+            )
+            .bind(cursor)
+            .bind(latest)
+            .execute(&mut tx)
+            .await
+            .map_err(|e| format!("Failed to register smart money entries: {e}"))?;
+            sqlx::query(
+                r#"
+                INSERT INTO smart_money_accuracy_cursor (id, last_rowid) VALUES (1, ?1)
+                ON CONFLICT(id) DO UPDATE SET last_rowid = excluded.last_rowid
+                "#,
+            )
+            .bind(latest)
+            .execute(&mut tx)
+            .await
+            .map_err(|e| format!("Failed to advance accuracy cursor: {e}"))?;
+            tx.commit()
+                .await
+                .map_err(|e| format!("Failed to commit accuracy update: {e}"))?;
+        }
+
+        let first_due = now - Duration::seconds(OUTCOME_HORIZONS[0].seconds);
+        let pending = sqlx::query(
+            r#"
+            SELECT activity_id, wallet_address, token_mint, quote_mint, entry_price, entry_at,
+                   resolved_1d, resolved_7d
+            FROM smart_money_entries
+            WHERE (resolved_1d = 0 OR resolved_7d = 0) AND entry_at <= ?1
+            "#,
+        )
+        .bind(first_due.to_rfc3339())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| format!("Failed to load pending entries: {e}"))?;
+
+        for row in pending {
+            let activity_id: String = row.try_get("activity_id").unwrap_or_default();
+            let wallet_address: String = row.try_get("wallet_address").unwrap_or_default();
+            let token_mint: String = row.try_get("token_mint").unwrap_or_default();
+            let quote_mint: String = row.try_get("quote_mint").unwrap_or_default();
+            let entry_price: f64 = row.try_get("entry_price").unwrap_or(0.0);
+            let Some(entry_at) = parse_timestamp(row.try_get("entry_at").ok()) else {
+                continue;
+            };
+
+            for horizon in &OUTCOME_HORIZONS {
+                if row.try_get::<i64, _>(horizon.resolved_column).unwrap_or(0) == 1 {
+                    continue;
+                }
+                let target = entry_at + Duration::seconds(horizon.seconds);
+                let give_up = target + Duration::seconds(horizon.tolerance_seconds);
+                if target > now {
+                    continue;
+                }
+
+                let later_price = self
+                    .price_between(&token_mint, &quote_mint, target, give_up)
+                    .await?;
+                let outcome = match later_price {
+                    Some(price) if entry_price > 0.0 => Some(price / entry_price - 1.0),
+                    _ if now < give_up => continue,
+                    _ => None,
+                };
+                self.record_outcome(
+                    &activity_id,
+                    &wallet_address,
+                    horizon,
+                    outcome,
+                    target.timestamp(),
+                )
+                .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// First price observed for `token_mint` against the same quote token
+    /// within `[from, until]`.
+    async fn price_between(
+        &self,
+        token_mint: &str,
+        quote_mint: &str,
+        from: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> Result<Option<f64>, String> {
+        let row = sqlx::query(
+            r#"
+            SELECT price FROM wallet_activities
+            WHERE timestamp >= ?1 AND timestamp <= ?2
+            AND price > 0
+            AND (
+                (action_type = 'buy' AND output_mint = ?3 AND input_mint = ?4)
+                OR (action_type = 'sell' AND input_mint = ?3 AND output_mint = ?4)
+            )
+            ORDER BY timestamp ASC
+            LIMIT 1
+            "#,
+        )
+        .bind(from.to_rfc3339())
+        .bind(until.to_rfc3339())
+        .bind(token_mint)
+        .bind(quote_mint)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| format!("Failed to look up later price: {e}"))?;
+
+        Ok(row.and_then(|row| row.try_get("price").ok()))
+    }
+
+    /// Marks one horizon of an entry as resolved and, when a return could be
+    /// measured, folds the hit or miss into the wallet's rolling accuracy.
+    async fn record_outcome(
+        &self,
+        activity_id: &str,
+        wallet_address: &str,
+        horizon: &OutcomeHorizon,
+        outcome: Option<f64>,
+        observed_at: i64,
+    ) -> Result<(), String> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| format!("Failed to start outcome update: {e}"))?;
+
+        let resolved = sqlx::query(&format!(
+            "UPDATE smart_money_entries SET {} = 1, {} = ?1 WHERE activity_id = ?2 AND {} = 0",
+            horizon.resolved_column, horizon.return_column, horizon.resolved_column
+        ))
+        .bind(outcome)
+        .bind(activity_id)
+        .execute(&mut tx)
+        .await
+        .map_err(|e| format!("Failed to resolve entry: {e}"))?
+        .rows_affected();
+
+        if let (1, Some(outcome)) = (resolved, outcome) {
+            let current = sqlx::query(
+                "SELECT weighted_hits, weighted_total, updated_at FROM smart_money_accuracy WHERE wallet_address = ?1",
+            )
+            .bind(wallet_address)
+            .fetch_optional(&mut tx)
+            .await
+            .map_err(|e| format!("Failed to load wallet accuracy: {e}"))?;
+            let (hits, total, updated_at) = current
+                .map(|row| {
+                    (
+                        row.try_get("weighted_hits").unwrap_or(0.0),
+                        row.try_get("weighted_total").unwrap_or(0.0),
+                        row.try_get("updated_at").unwrap_or(observed_at),
+                    )
+                })
+                .unwrap_or((0.0, 0.0, observed_at));
+            let hit = outcome > 0.0;
+            let (hits, total, updated_at) = fold_outcome(hits, total, updated_at, observed_at, hit);
+
+            sqlx::query(&format!(
+                r#"
+                INSERT INTO smart_money_accuracy (
+                    wallet_address, weighted_hits, weighted_total, {evaluated}, {hits}, updated_at
+                ) VALUES (?1, ?2, ?3, 1, ?4, ?5)
+                ON CONFLICT(wallet_address) DO UPDATE SET
+                    weighted_hits = excluded.weighted_hits,
+                    weighted_total = excluded.weighted_total,
+                    {evaluated} = {evaluated} + 1,
+                    {hits} = {hits} + excluded.{hits},
+                    updated_at = excluded.updated_at
+                "#,
+                evaluated = horizon.evaluated_column,
+                hits = horizon.hits_column,
+            ))
+            .bind(wallet_address)
+            .bind(hits)
+            .bind(total)
+            .bind(if hit { 1 } else { 0 })
+            .bind(updated_at)
+            .execute(&mut tx)
+            .await
+            .map_err(|e| format!("Failed to update wallet accuracy: {e}"))?;
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| format!("Failed to commit outcome update: {e}"))
+    }
+
+    pub async fn get_wallet_accuracy(
+        &self,
+        wallet_address: &str,
+    ) -> Result<SmartMoneyAccuracy, String> {
+        self.refresh_accuracy().await?;
+
+        let row = sqlx::query("SELECT * FROM smart_money_accuracy WHERE wallet_address = ?1")
+            .bind(wallet_address)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| format!("Failed to load wallet accuracy: {e}"))?;
+        let entries_tracked: i64 = sqlx::query(
+            "SELECT COUNT(*) AS tracked FROM smart_money_entries WHERE wallet_address = ?1",
+        )
+        .bind(wallet_address)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| format!("Failed to count entries: {e}"))?
+        .try_get("tracked")
+        .unwrap_or(0);
+
+        let recent_entries = sqlx::query(
+            r#"
+            SELECT activity_id, token_mint, entry_price, entry_at, return_1d, return_7d
+            FROM smart_money_entries
+            WHERE wallet_address = ?1
+            ORDER BY entry_at DESC
+            LIMIT 20
+            "#,
+        )
+        .bind(wallet_address)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| format!("Failed to load entries: {e}"))?
+        .into_iter()
+        .map(|row| SmartMoneyEntryOutcome {
+            activity_id: row.try_get("activity_id").unwrap_or_default(),
+            token_mint: row.try_get("token_mint").unwrap_or_default(),
+            entry_price: row.try_get("entry_price").unwrap_or(0.0),
+            entry_at: parse_timestamp(row.try_get("entry_at").ok()).unwrap_or_else(Utc::now),
+            return_1d: row.try_get("return_1d").unwrap_or(None),
+            return_7d: row.try_get("return_7d").unwrap_or(None),
+        })
+        .collect();
+
+        let get = |column: &str| -> i64 {
+            row.as_ref()
+                .and_then(|row| row.try_get(column).ok())
+                .unwrap_or(0)
+        };
+        let weighted_hits: f64 = row
+            .as_ref()
+            .and_then(|row| row.try_get("weighted_hits").ok())
+            .unwrap_or(0.0);
+        let weighted_total: f64 = row
+            .as_ref()
+            .and_then(|row| row.try_get("weighted_total").ok())
+            .unwrap_or(0.0);
+        let accuracy = shrunk_accuracy(weighted_hits, weighted_total);
+
+        Ok(SmartMoneyAccuracy {
+            wallet_address: wallet_address.to_string(),
+            entries_tracked,
+            evaluated_1d: get("evaluated_1d"),
+            hits_1d: get("hits_1d"),
+            evaluated_7d: get("evaluated_7d"),
+            hits_7d: get("hits_7d"),
+            raw_accuracy: (weighted_total > 0.0).then(|| weighted_hits / weighted_total),
+            accuracy,
+            accuracy_weight: accuracy / ACCURACY_PRIOR_MEAN,
+            prior_strength: ACCURACY_PRIOR_STRENGTH,
+            updated_at: row
+                .as_ref()
+                .and_then(|row| row.try_get::<i64, _>("updated_at").ok())
+                .and_then(|ts| DateTime::from_timestamp(ts, 0)),
+            recent_entries,
+        })
+    }
+
+    /// Tokens several smart money wallets traded in the window. Each wallet
+    /// counts by its historical accuracy and by how recent its activity is.
     pub async fn get_consensus(
         &self,
         time_window_hours: i64,
     ) -> Result<Vec<SmartMoneyConsensus>, String> {
-        let cutoff = Utc::now() - Duration::hours(time_window_hours);
+        if let Err(err) = self.refresh_accuracy().await {
+            eprintln!("Smart money consensus is using stale accuracy: {err}");
+        }
+
+        let now = Utc::now();
+        let cutoff = now - Duration::hours(time_window_hours);
 
         let rows = sqlx::query(
             r#"
-            SELECT 
+            SELECT
+                wa.wallet_address,
+                smw.label,
                 wa.output_mint as token_mint,
                 wa.output_symbol as token_symbol,
                 wa.action_type as action,
-                COUNT(DISTINCT wa.wallet_address) as smart_wallets_count,
-                SUM(wa.amount_usd) as total_volume_usd,
-                AVG(wa.price) as avg_price,
-                MIN(wa.timestamp) as first_seen,
-                MAX(wa.timestamp) as last_updated
+                wa.amount_usd,
+                wa.price,
+                wa.timestamp,
+                sma.weighted_hits,
+                sma.weighted_total
             FROM wallet_activities wa
             INNER JOIN smart_money_wallets smw ON wa.wallet_address = smw.wallet_address
+            LEFT JOIN smart_money_accuracy sma ON sma.wallet_address = wa.wallet_address
             WHERE smw.is_smart_money = 1
             AND wa.timestamp >= ?1
             AND (wa.action_type = 'buy' OR wa.action_type = 'sell')
             AND wa.output_mint IS NOT NULL
-            GROUP BY wa.output_mint, wa.action_type
-            HAVING COUNT(DISTINCT wa.wallet_address) >= 3
-            ORDER BY smart_wallets_count DESC, total_volume_usd DESC
-            LIMIT 20
+            ORDER BY wa.timestamp ASC
             "#,
         )
         .bind(cutoff.to_rfc3339())
@@ -380,36 +729,113 @@ impl SmartMoneyDetector {
         .await
         .map_err(|e| format!("Failed to fetch consensus: {e}"))?;
 
-        let mut consensus_list = Vec::new();
+        let mut groups: HashMap<(String, String), ConsensusGroup> = HashMap::new();
         for row in rows {
-            let smart_wallets_count: i64 = row.try_get("smart_wallets_count").unwrap_or(0);
-            let total_volume: f64 = row.try_get("total_volume_usd").unwrap_or(0.0);
-            let consensus_strength = (smart_wallets_count as f64 / 10.0).min(1.0) * 50.0
-                + (total_volume / 100000.0).min(1.0) * 50.0;
-
-            consensus_list.push(SmartMoneyConsensus {
-                token_mint: row.try_get("token_mint").unwrap_or_default(),
-                token_symbol: row.try_get("token_symbol").ok(),
-                action: row.try_get("action").unwrap_or_default(),
-                smart_wallets_count,
-                total_volume_usd: total_volume,
-                avg_price: row.try_get("avg_price").unwrap_or(0.0),
-                consensus_strength,
-                first_seen: row
-                    .try_get::<String, _>("first_seen")
-                    .ok()
-                    .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
-                    .map(|dt| dt.with_timezone(&Utc))
-                    .unwrap_or_else(|| Utc::now()),
-                last_updated: row
-                    .try_get::<String, _>("last_updated")
-                    .ok()
-                    .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
-                    .map(|dt| dt.with_timezone(&Utc))
-                    .unwrap_or_else(|| Utc::now()),
-            });
+            let Some(timestamp) = parse_timestamp(row.try_get("timestamp").ok()) else {
+                continue;
+            };
+            let token_mint: String = row.try_get("token_mint").unwrap_or_default();
+            let action: String = row.try_get("action").unwrap_or_default();
+            let amount_usd: f64 = row
+                .try_get::<Option<f64>, _>("amount_usd")
+                .unwrap_or(None)
+                .unwrap_or(0.0);
+
+            let group = groups
+                .entry((token_mint, action))
+                .or_insert_with(|| ConsensusGroup {
+                    token_symbol: row.try_get("token_symbol").ok(),
+                    first_seen: timestamp,
+                    last_updated: timestamp,
+                    ..ConsensusGroup::default()
+                });
+            group.last_updated = timestamp;
+            group.total_volume += amount_usd;
+            if let Ok(Some(price)) = row.try_get::<Option<f64>, _>("price") {
+                group.price_sum += price;
+                group.price_count += 1;
+            }
+
+            let wallet_address: String = row.try_get("wallet_address").unwrap_or_default();
+            let wallet = group
+                .wallets
+                .entry(wallet_address.clone())
+                .or_insert_with(|| SmartMoneyContribution {
+                    wallet_address,
+                    label: row
+                        .try_get::<Option<String>, _>("label")
+                        .unwrap_or(None)
+                        .filter(|label| !label.is_empty()),
+                    volume_usd: 0.0,
+                    last_activity: timestamp,
+                    accuracy: shrunk_accuracy(
+                        row.try_get::<Option<f64>, _>("weighted_hits")
+                            .unwrap_or(None)
+                            .unwrap_or(0.0),
+                        row.try_get::<Option<f64>, _>("weighted_total")
+                            .unwrap_or(None)
+                            .unwrap_or(0.0),
+                    ),
+                    accuracy_weight: 0.0,
+                    decay_weight: 0.0,
+                    contribution: 0.0,
+                });
+            wallet.volume_usd += amount_usd;
+            wallet.last_activity = timestamp;
         }
 
+        let mut consensus_list: Vec<SmartMoneyConsensus> = groups
+            .into_iter()
+            .filter(|(_, group)| group.wallets.len() >= 3)
+            .map(|((token_mint, action), group)| {
+                let mut contributions: Vec<SmartMoneyContribution> = group
+                    .wallets
+                    .into_values()
+                    .map(|mut wallet| {
+                        let age_hours = (now - wallet.last_activity).num_seconds() as f64 / 3600.0;
+                        wallet.accuracy_weight = wallet.accuracy / ACCURACY_PRIOR_MEAN;
+                        wallet.decay_weight = signal_decay(age_hours);
+                        wallet.contribution = wallet.accuracy_weight * wallet.decay_weight;
+                        wallet
+                    })
+                    .collect();
+                contributions.sort_by(|a, b| b.contribution.total_cmp(&a.contribution));
+
+                let weighted_wallets: f64 = contributions.iter().map(|c| c.contribution).sum();
+                let weighted_volume: f64 = contributions
+                    .iter()
+                    .map(|c| c.volume_usd * c.contribution)
+                    .sum();
+                let consensus_strength = (weighted_wallets / 10.0).min(1.0) * 50.0
+                    + (weighted_volume / 100000.0).min(1.0) * 50.0;
+
+                SmartMoneyConsensus {
+                    token_mint,
+                    token_symbol: group.token_symbol,
+                    action,
+                    smart_wallets_count: contributions.len() as i64,
+                    total_volume_usd: group.total_volume,
+                    avg_price: if group.price_count > 0 {
+                        group.price_sum / group.price_count as f64
+                    } else {
+                        0.0
+                    },
+                    consensus_strength,
+                    first_seen: group.first_seen,
+                    last_updated: group.last_updated,
+                    weighted_wallets,
+                    contributions,
+                }
+            })
+            .collect();
+
+        consensus_list.sort_by(|a, b| {
+            b.consensus_strength
+                .total_cmp(&a.consensus_strength)
+                .then(b.smart_wallets_count.cmp(&a.smart_wallets_count))
+        });
+        consensus_list.truncate(20);
+
         Ok(consensus_list)
     }
 
@@ -497,3 +923,75 @@ struct TradeInfo {
     price: f64,
     timestamp: DateTime<Utc>,
 }
+
+#[derive(Default)]
+struct ConsensusGroup {
+    token_symbol: Option<String>,
+    wallets: HashMap<String, SmartMoneyContribution>,
+    total_volume: f64,
+    price_sum: f64,
+    price_count: usize,
+    first_seen: DateTime<Utc>,
+    last_updated: DateTime<Utc>,
+}
+
+fn parse_timestamp(value: Option<String>) -> Option<DateTime<Utc>> {
+    value
+        .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+/// Accuracy pulled toward the prior; wallets with little history stay near 50%.
+fn shrunk_accuracy(weighted_hits: f64, weighted_total: f64) -> f64 {
+    (weighted_hits + ACCURACY_PRIOR_MEAN * ACCURACY_PRIOR_STRENGTH)
+        / (weighted_total + ACCURACY_PRIOR_STRENGTH)
+}
+
+fn signal_decay(age_hours: f64) -> f64 {
+    0.5f64.powf(age_hours.max(0.0) / SIGNAL_HALF_LIFE_HOURS)
+}
+
+/// Adds one outcome observed at `at` to hit counts last decayed at
+/// `updated_at`. Outcomes that arrive late are discounted by their age
+/// instead of rewinding the counts.
+fn fold_outcome(hits: f64, total: f64, updated_at: i64, at: i64, hit: bool) -> (f64, f64, i64) {
+    let hit = if hit { 1.0 } else { 0.0 };
+    if at >= updated_at {
+        let factor = 0.5f64.powf((at - updated_at) as f64 / ACCURACY_HALF_LIFE_SECS);
+        (hits * factor + hit, total * factor + 1.0, at)
+    } else {
+        let weight = 0.5f64.powf((updated_at - at) as f64 / ACCURACY_HALF_LIFE_SECS);
+        (hits + hit * weight, total + weight, updated_at)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_histories_shrink_toward_the_prior() {
+        assert_eq!(shrunk_accuracy(0.0, 0.0), 0.5);
+        let lucky = shrunk_accuracy(2.0, 2.0);
+        let proven = shrunk_accuracy(80.0, 100.0);
+        assert!(lucky > 0.5 && lucky < 0.6);
+        assert!(proven > lucky);
+    }
+
+    #[test]
+    fn old_outcomes_and_signals_decay() {
+        let day = 86_400;
+        let (hits, total, at) = fold_outcome(0.0, 0.0, 0, 0, true);
+        let (hits, total, at) = fold_outcome(hits, total, at, 30 * day, false);
+        assert_eq!(at, 30 * day);
+        assert!((hits - 0.5).abs() < 1e-9);
+        assert!((total - 1.5).abs() < 1e-9);
+
+        let (late_hits, late_total, unchanged) = fold_outcome(hits, total, at, 0, true);
+        assert_eq!(unchanged, at);
+        assert!((late_hits - 1.0).abs() < 1e-9 && (late_total - 2.0).abs() < 1e-9);
+
+        assert!(signal_decay(7.0 * 24.0) < 0.1);
+        assert_eq!(signal_decay(0.0), 1.0);
+    }
+}
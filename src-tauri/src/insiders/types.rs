@@ -172,6 +172,93 @@ pub struct WalletStatistics {
     pub total_volume_usd: f64,
     pub avg_transaction_size: f64,
     pub last_activity: Option<DateTime<Utc>>,
+    /// Set when the statistics cover every wallet of a cluster.
+    #[serde(default)]
+    pub cluster_id: Option<String>,
+    #[serde(default)]
+    pub cluster_wallets: Vec<String>,
+}
+
+/// A SOL transfer that funded `funded` from `funder`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FundingEdge {
+    pub funder: String,
+    pub funded: String,
+    pub lamports: u64,
+    pub signature: String,
+    pub timestamp: i64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ClusterCandidateStatus {
+    Pending,
+    Accepted,
+    Rejected,
+}
+
+impl ClusterCandidateStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ClusterCandidateStatus::Pending => "pending",
+            ClusterCandidateStatus::Accepted => "accepted",
+            ClusterCandidateStatus::Rejected => "rejected",
+        }
+    }
+
+    pub fn from_db(s: &str) -> Self {
+        match s {
+            "accepted" => ClusterCandidateStatus::Accepted,
+            "rejected" => ClusterCandidateStatus::Rejected,
+            _ => ClusterCandidateStatus::Pending,
+        }
+    }
+}
+
+/// A wallet that shares a funder with a monitored wallet.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClusterCandidate {
+    pub id: String,
+    pub seed_wallet: String,
+    pub candidate_wallet: String,
+    pub shared_funder: String,
+    /// Hops from the monitored wallet up to the shared funder.
+    pub hops: u32,
+    pub confidence: f64,
+    pub status: ClusterCandidateStatus,
+    pub funding_signature: String,
+    pub created_at: DateTime<Utc>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClusterInferenceRequest {
+    /// Monitored wallets to analyze; all active ones when omitted.
+    pub wallet_addresses: Option<Vec<String>>,
+    pub max_hops: Option<u32>,
+    pub min_confidence: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClusterInferenceReport {
+    pub wallets_analyzed: usize,
+    pub rpc_calls: usize,
+    pub cache_hits: usize,
+    pub candidates: Vec<ClusterCandidate>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClusterCandidateDecision {
+    pub candidate_id: String,
+    pub accept: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InsiderCluster {
+    pub id: String,
+    pub label: Option<String>,
+    pub wallets: Vec<String>,
+    pub created_at: DateTime<Utc>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -235,6 +322,56 @@ pub struct SmartMoneyConsensus {
     pub consensus_strength: f64,
     pub first_seen: DateTime<Utc>,
     pub last_updated: DateTime<Utc>,
+    /// Sum of the wallets' accuracy and recency weights.
+    #[serde(default)]
+    pub weighted_wallets: f64,
+    #[serde(default)]
+    pub contributions: Vec<SmartMoneyContribution>,
+}
+
+/// How much one wallet moved a consensus entry.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SmartMoneyContribution {
+    pub wallet_address: String,
+    pub label: Option<String>,
+    pub volume_usd: f64,
+    pub last_activity: DateTime<Utc>,
+    /// Shrunk historical accuracy, 0..1.
+    pub accuracy: f64,
+    /// `accuracy` scaled so an average wallet counts as 1.
+    pub accuracy_weight: f64,
+    /// Exponential decay by the age of the wallet's latest activity.
+    pub decay_weight: f64,
+    pub contribution: f64,
+}
+
+/// A tracked buy and how the token moved one and seven days later.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SmartMoneyEntryOutcome {
+    pub activity_id: String,
+    pub token_mint: String,
+    pub entry_price: f64,
+    pub entry_at: DateTime<Utc>,
+    pub return_1d: Option<f64>,
+    pub return_7d: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SmartMoneyAccuracy {
+    pub wallet_address: String,
+    pub entries_tracked: i64,
+    pub evaluated_1d: i64,
+    pub hits_1d: i64,
+    pub evaluated_7d: i64,
+    pub hits_7d: i64,
+    /// Time-decayed hit rate before shrinkage, if anything was evaluated.
+    pub raw_accuracy: Option<f64>,
+    pub accuracy: f64,
+    pub accuracy_weight: f64,
+    /// Pseudo-observations of the 50% prior mixed into `accuracy`.
+    pub prior_strength: f64,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub recent_entries: Vec<SmartMoneyEntryOutcome>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -414,6 +551,41 @@ impl WalletMonitorDatabase {
         .execute(&self.pool)
         .await?;
 
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS smart_money_entries (
+                activity_id TEXT PRIMARY KEY,
+                wallet_address TEXT NOT NULL,
+                token_mint TEXT NOT NULL,
+                quote_mint TEXT NOT NULL,
+                entry_price REAL NOT NULL,
+                entry_at TEXT NOT NULL,
+                return_1d REAL,
+                return_7d REAL,
+                resolved_1d INTEGER NOT NULL DEFAULT 0,
+                resolved_7d INTEGER NOT NULL DEFAULT 0
+            );
+            CREATE INDEX IF NOT EXISTS idx_smart_money_entries_wallet ON smart_money_entries(wallet_address, entry_at);
+            CREATE INDEX IF NOT EXISTS idx_smart_money_entries_pending ON smart_money_entries(resolved_1d, resolved_7d);
+            CREATE TABLE IF NOT EXISTS smart_money_accuracy (
+                wallet_address TEXT PRIMARY KEY,
+                weighted_hits REAL NOT NULL DEFAULT 0,
+                weighted_total REAL NOT NULL DEFAULT 0,
+                evaluated_1d INTEGER NOT NULL DEFAULT 0,
+                hits_1d INTEGER NOT NULL DEFAULT 0,
+                evaluated_7d INTEGER NOT NULL DEFAULT 0,
+                hits_7d INTEGER NOT NULL DEFAULT 0,
+                updated_at INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS smart_money_accuracy_cursor (
+                id INTEGER PRIMARY KEY CHECK (id = 1),
+                last_rowid INTEGER NOT NULL
+            );
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
         self.insert_default_alert_configs().await?;
 
         Ok(())
@@ -610,7 +782,20 @@ impl WalletMonitorDatabase {
         &self,
         wallet_address: &str,
     ) -> Result<WalletStatistics, sqlx::Error> {
-        let row = sqlx::query(
+        let mut stats = self
+            .get_statistics_for_wallets(&[wallet_address.to_string()])
+            .await?;
+        stats.wallet_address = wallet_address.to_string();
+        Ok(stats)
+    }
+
+    /// Activity statistics summed over several wallets.
+    pub async fn get_statistics_for_wallets(
+        &self,
+        wallets: &[String],
+    ) -> Result<WalletStatistics, sqlx::Error> {
+        let placeholders: Vec<String> = (1..=wallets.len()).map(|i| format!("?{}", i)).collect();
+        let query = format!(
             r#"
             SELECT
                 COUNT(*) as total,
@@ -621,15 +806,19 @@ impl WalletMonitorDatabase {
                 COALESCE(AVG(amount_usd), 0) as avg_size,
                 MAX(timestamp) as last_activity
             FROM wallet_activities
-            WHERE wallet_address = ?1
+            WHERE wallet_address IN ({})
             "#,
-        )
-        .bind(wallet_address)
-        .fetch_one(&self.pool)
-        .await?;
+            placeholders.join(",")
+        );
+
+        let mut q = sqlx::query(&query);
+        for wallet in wallets {
+            q = q.bind(wallet);
+        }
+        let row = q.fetch_one(&self.pool).await?;
 
         Ok(WalletStatistics {
-            wallet_address: wallet_address.to_string(),
+            wallet_address: wallets.first().cloned().unwrap_or_default(),
             total_transactions: row.try_get("total").unwrap_or(0),
             buy_count: row.try_get("buys").unwrap_or(0),
             sell_count: row.try_get("sells").unwrap_or(0),
@@ -641,6 +830,8 @@ impl WalletMonitorDatabase {
                 .ok()
                 .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
                 .map(|dt| dt.with_timezone(&Utc)),
+            cluster_id: None,
+            cluster_wallets: Vec::new(),
         })
     }
 
@@ -1,6 +1,8 @@
-use super::{types::*, AlertManager, SmartMoneyDetector};
+use super::{types::*, AlertManager, ClusterInference, SmartMoneyDetector};
+use crate::config::DataPaths;
 use crate::core::WebSocketManager;
-use crate::websocket::types::{StreamEvent, TransactionUpdate};
+use crate::websocket::activity::{self, DecodedActivity};
+use crate::websocket::types::TransactionUpdate;
 use chrono::Utc;
 use serde_json::json;
 use sqlx::SqlitePool;
@@ -84,15 +86,13 @@ impl WalletMonitor {
             .app_handle
             .listen("transaction_update", move |event| {
                 let payload = event.payload();
-                if let Ok(stream_event) = serde_json::from_str::<StreamEvent>(payload) {
-                    if let StreamEvent::TransactionUpdate(tx) = stream_event {
-                        let monitor_clone = monitor.clone();
-                        tauri::async_runtime::spawn(async move {
-                            if let Err(err) = monitor_clone.process_transaction(tx).await {
-                                eprintln!("Failed to process wallet transaction: {err}");
-                            }
-                        });
-                    }
+                if let Ok(tx) = serde_json::from_str::<TransactionUpdate>(payload) {
+                    let monitor_clone = monitor.clone();
+                    tauri::async_runtime::spawn(async move {
+                        if let Err(err) = monitor_clone.process_transaction(tx).await {
+                            eprintln!("Failed to process wallet transaction: {err}");
+                        }
+                    });
                 }
             });
 
@@ -291,6 +291,25 @@ impl WalletMonitor {
             .map_err(|e| format!("Failed to get statistics: {e}"))
     }
 
+    /// Statistics summed over every wallet in `cluster`.
+    pub async fn get_cluster_statistics(
+        &self,
+        wallet_address: &str,
+        cluster: &InsiderCluster,
+    ) -> Result<WalletStatistics, String> {
+        let mut stats = self
+            .db
+            .read()
+            .await
+            .get_statistics_for_wallets(&cluster.wallets)
+            .await
+            .map_err(|e| format!("Failed to get cluster statistics: {e}"))?;
+        stats.wallet_address = wallet_address.to_string();
+        stats.cluster_id = Some(cluster.id.clone());
+        stats.cluster_wallets = cluster.wallets.clone();
+        Ok(stats)
+    }
+
     pub async fn run_batch_processor(self: Arc<Self>) {
         let mut ticker = interval(Duration::from_millis(500));
         loop {
@@ -327,32 +346,20 @@ impl WalletMonitor {
         let to_address = tx.to.clone().unwrap_or_default();
 
         let monitored = self.monitored_wallets.read().await;
-        let relevant_wallet = if monitored.contains(&from_address) {
-            Some(from_address.clone())
-        } else if monitored.contains(&to_address) {
-            Some(to_address.clone())
-        } else {
-            None
+        let relevant_wallet = match &tx.activity {
+            Some(decoded) if monitored.contains(&decoded.wallet) => Some(decoded.wallet.clone()),
+            _ if monitored.contains(&from_address) => Some(from_address.clone()),
+            _ if monitored.contains(&to_address) => Some(to_address.clone()),
+            _ => None,
         };
+        drop(monitored);
 
         if let Some(wallet_address) = relevant_wallet {
-            let action_type = tx.typ.as_deref().unwrap_or("unknown");
-
-            let activity = WalletActivityRecord {
-                id: Uuid::new_v4().to_string(),
-                wallet_address: wallet_address.clone(),
-                tx_signature: tx.signature.clone(),
-                action_type: action_type.to_string(),
-                input_mint: tx.from.clone(),
-                output_mint: tx.to.clone(),
-                input_symbol: None,
-                output_symbol: tx.symbol.clone(),
-                amount: tx.amount,
-                amount_usd: tx.amount,
-                price: Some(1.0),
-                timestamp: chrono::DateTime::from_timestamp(tx.timestamp, 0)
-                    .unwrap_or_else(|| Utc::now()),
+            let decoded = match tx.activity.clone() {
+                Some(decoded) if decoded.wallet == wallet_address => decoded,
+                _ => activity::decode_update(&tx, &wallet_address),
             };
+            let activity = activity_record(&decoded, tx.amount);
 
             self.db
                 .write()
@@ -467,11 +474,38 @@ impl WalletMonitor {
     }
 }
 
+/// Stores a decoded transaction the way the activity feed and smart money
+/// scoring expect it: `input_*` is what the wallet sent, `output_*` what it got.
+fn activity_record(
+    decoded: &DecodedActivity,
+    fallback_amount: Option<f64>,
+) -> WalletActivityRecord {
+    let mint = |leg: Option<&activity::TokenAmount>| leg.map(|t| t.mint.clone());
+    let symbol = |leg: Option<&activity::TokenAmount>| leg.and_then(|t| t.symbol.clone());
+    let amount = decoded.amount().or(fallback_amount);
+
+    WalletActivityRecord {
+        id: Uuid::new_v4().to_string(),
+        wallet_address: decoded.wallet.clone(),
+        tx_signature: decoded.signature.clone(),
+        action_type: decoded.action().to_string(),
+        input_mint: mint(decoded.token_out.as_ref()),
+        output_mint: mint(decoded.token_in.as_ref()),
+        input_symbol: symbol(decoded.token_out.as_ref()),
+        output_symbol: symbol(decoded.token_in.as_ref()),
+        amount,
+        amount_usd: decoded.usd_value().or(amount),
+        price: decoded.implied_price(),
+        timestamp: chrono::DateTime::from_timestamp(decoded.timestamp, 0).unwrap_or_else(Utc::now),
+    }
+}
+
 pub struct WalletMonitorState {
     pub db: Arc<RwLock<WalletMonitorDatabase>>,
     pub monitor: Arc<WalletMonitor>,
     pub smart_money_detector: Arc<SmartMoneyDetector>,
     pub alert_manager: Arc<AlertManager>,
+    pub cluster_inference: Arc<ClusterInference>,
 }
 
 static WALLET_MONITOR_STATE: OnceCell<WalletMonitorState> = OnceCell::const_new();
@@ -483,8 +517,7 @@ pub async fn init_wallet_monitor(app_handle: &AppHandle) -> Result<(), String> {
 
     let app = app_handle.clone();
     let app_dir = app
-        .path()
-        .app_data_dir()
+        .app_data_root()
         .map_err(|e| format!("Unable to resolve app data directory: {}", e))?;
     std::fs::create_dir_all(&app_dir)
         .map_err(|e| format!("Failed to create app data directory: {e}"))?;
@@ -510,6 +543,12 @@ pub async fn init_wallet_monitor(app_handle: &AppHandle) -> Result<(), String> {
 
     let alert_manager = Arc::new(AlertManager::new(pool.clone(), app_handle.clone()));
 
+    let cluster_inference = Arc::new(
+        ClusterInference::new(pool.clone(), app_handle.clone())
+            .await
+            .map_err(|e| format!("Failed to initialize cluster inference: {e}"))?,
+    );
+
     let monitor = Arc::new(WalletMonitor::new(
         shared_db.clone(),
         app_handle.clone(),
@@ -536,6 +575,7 @@ pub async fn init_wallet_monitor(app_handle: &AppHandle) -> Result<(), String> {
             monitor: monitor.clone(),
             smart_money_detector: smart_money_detector.clone(),
             alert_manager: alert_manager.clone(),
+            cluster_inference,
         })
         .map_err(|_| "Wallet monitor state already initialized".to_string())?;
 
@@ -591,11 +631,22 @@ pub async fn wallet_monitor_get_activities(
     state.monitor.get_activities(filter, limit, offset).await
 }
 
+/// With `per_cluster`, the statistics cover the whole cluster the wallet
+/// belongs to, if any.
 #[tauri::command]
 pub async fn wallet_monitor_get_statistics(
     wallet_address: String,
+    per_cluster: Option<bool>,
 ) -> Result<WalletStatistics, String> {
     let state = require_state()?;
+    if per_cluster.unwrap_or(false) {
+        if let Some(cluster) = state.cluster_inference.cluster_of(&wallet_address).await? {
+            return state
+                .monitor
+                .get_cluster_statistics(&wallet_address, &cluster)
+                .await;
+        }
+    }
     state.monitor.get_wallet_statistics(&wallet_address).await
 }
 
@@ -99,6 +99,8 @@ mod tests {
             total_volume_usd: 500000.0,
             avg_transaction_size: 5000.0,
             last_activity: Some(Utc::now()),
+            cluster_id: None,
+            cluster_wallets: Vec::new(),
         };
 
         assert_eq!(stats.total_transactions, 100);
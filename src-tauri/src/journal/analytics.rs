@@ -164,6 +164,7 @@ impl JournalAnalytics {
             psychological_insights,
             recommendations,
             created_at: now,
+            fee_summary: None,
         }
     }
 
@@ -0,0 +1,680 @@
+use crate::config::DataPaths;
+use base64::{engine::general_purpose::STANDARD as BASE64_ENGINE, Engine};
+use chrono::Utc;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tauri::{AppHandle, Manager, State};
+
+use super::database::SharedJournalDatabase;
+
+const ATTACHMENTS_DIR: &str = "journal_attachments";
+const MANIFEST_FILE: &str = "manifest.json";
+const MAX_ATTACHMENT_BYTES: usize = 25 * 1024 * 1024;
+const PNG_MAGIC: &[u8] = &[0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+/// A stored file. The id is the SHA-256 of the content and doubles as the
+/// blob's file name, so attaching the same screenshot twice stores it once.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct JournalAttachment {
+    pub id: String,
+    pub file_name: String,
+    pub mime_type: String,
+    pub size_bytes: u64,
+    pub created_at: i64,
+}
+
+/// An attachment together with its content, as carried in backups.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct JournalAttachmentBlob {
+    #[serde(flatten)]
+    pub attachment: JournalAttachment,
+    /// Base64-encoded file content.
+    pub data: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct JournalAttachmentBackup {
+    pub attachments: Vec<JournalAttachmentBlob>,
+    /// Entry id to the attachment ids linked to it.
+    pub links: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct JournalAttachmentUsage {
+    pub attachment_count: usize,
+    pub total_bytes: u64,
+    pub linked_entries: usize,
+    /// Attachments no entry links to any more.
+    pub orphaned_count: usize,
+    pub orphaned_bytes: u64,
+    /// Files in the attachment directory the manifest does not know about.
+    pub untracked_files: usize,
+    pub untracked_bytes: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct JournalAttachmentCleanup {
+    pub removed_links: usize,
+    pub removed_attachments: usize,
+    pub removed_files: usize,
+    pub freed_bytes: u64,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum JournalAttachmentError {
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+    #[error("invalid attachment: {0}")]
+    Invalid(String),
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct Manifest {
+    attachments: BTreeMap<String, JournalAttachment>,
+    links: BTreeMap<String, BTreeSet<String>>,
+}
+
+/// Content-addressed attachment storage for journal entries. Blobs live next
+/// to a JSON manifest holding their metadata and the many-to-many links to
+/// entries. Removing an attachment from an entry only drops the link; blobs
+/// are deleted by `cleanup` once nothing references them.
+pub struct JournalAttachmentStore {
+    dir: PathBuf,
+    manifest: Manifest,
+}
+
+pub type SharedJournalAttachmentStore = Arc<RwLock<JournalAttachmentStore>>;
+
+impl JournalAttachmentStore {
+    pub fn new(app: &AppHandle) -> Result<Self, JournalAttachmentError> {
+        let mut dir = app.app_data_root().map_err(|e| {
+            JournalAttachmentError::Io(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("App data directory not found: {}", e),
+            ))
+        })?;
+        dir.push(ATTACHMENTS_DIR);
+        Self::load(dir)
+    }
+
+    pub fn load(dir: PathBuf) -> Result<Self, JournalAttachmentError> {
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(MANIFEST_FILE);
+        let manifest = if path.exists() {
+            serde_json::from_str(&fs::read_to_string(&path)?)?
+        } else {
+            Manifest::default()
+        };
+        Ok(Self { dir, manifest })
+    }
+
+    fn blob_path(&self, id: &str) -> PathBuf {
+        self.dir.join(id)
+    }
+
+    fn save(&self) -> Result<(), JournalAttachmentError> {
+        let path = self.dir.join(MANIFEST_FILE);
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_vec_pretty(&self.manifest)?)?;
+        fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    fn write_blob(&self, id: &str, bytes: &[u8]) -> Result<(), JournalAttachmentError> {
+        let path = self.blob_path(id);
+        if path.exists() {
+            return Ok(());
+        }
+        let tmp = self.dir.join(format!("{}.tmp", id));
+        fs::write(&tmp, bytes)?;
+        fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    /// Stores `bytes` (if not already present) and links them to the entry.
+    pub fn attach_bytes(
+        &mut self,
+        entry_id: &str,
+        file_name: &str,
+        bytes: &[u8],
+    ) -> Result<JournalAttachment, JournalAttachmentError> {
+        let entry_id = entry_id.trim();
+        if entry_id.is_empty() {
+            return Err(JournalAttachmentError::Invalid(
+                "entry id cannot be empty".to_string(),
+            ));
+        }
+        if bytes.is_empty() {
+            return Err(JournalAttachmentError::Invalid("file is empty".to_string()));
+        }
+        if bytes.len() > MAX_ATTACHMENT_BYTES {
+            return Err(JournalAttachmentError::Invalid(format!(
+                "file exceeds {} MB",
+                MAX_ATTACHMENT_BYTES / (1024 * 1024)
+            )));
+        }
+
+        let id = content_id(bytes);
+        self.write_blob(&id, bytes)?;
+
+        let attachment = self
+            .manifest
+            .attachments
+            .entry(id.clone())
+            .or_insert_with(|| JournalAttachment {
+                id: id.clone(),
+                file_name: sanitize_file_name(file_name),
+                mime_type: mime_type(file_name, bytes).to_string(),
+                size_bytes: bytes.len() as u64,
+                created_at: Utc::now().timestamp(),
+            })
+            .clone();
+        self.manifest
+            .links
+            .entry(entry_id.to_string())
+            .or_default()
+            .insert(id);
+        self.save()?;
+        Ok(attachment)
+    }
+
+    pub fn attach_file(
+        &mut self,
+        entry_id: &str,
+        path: &Path,
+    ) -> Result<JournalAttachment, JournalAttachmentError> {
+        let metadata = fs::metadata(path)?;
+        if !metadata.is_file() {
+            return Err(JournalAttachmentError::Invalid(format!(
+                "{} is not a file",
+                path.display()
+            )));
+        }
+        if metadata.len() > MAX_ATTACHMENT_BYTES as u64 {
+            return Err(JournalAttachmentError::Invalid(format!(
+                "file exceeds {} MB",
+                MAX_ATTACHMENT_BYTES / (1024 * 1024)
+            )));
+        }
+        let bytes = fs::read(path)?;
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "attachment".to_string());
+        self.attach_bytes(entry_id, &file_name, &bytes)
+    }
+
+    /// Saves a chart screenshot captured by the frontend.
+    pub fn attach_chart(
+        &mut self,
+        entry_id: &str,
+        png: &[u8],
+        label: Option<&str>,
+    ) -> Result<JournalAttachment, JournalAttachmentError> {
+        if !png.starts_with(PNG_MAGIC) {
+            return Err(JournalAttachmentError::Invalid(
+                "chart capture is not a PNG image".to_string(),
+            ));
+        }
+        let label = label
+            .map(str::trim)
+            .filter(|label| !label.is_empty())
+            .unwrap_or("chart");
+        let file_name = format!("{}-{}.png", label, Utc::now().format("%Y%m%d-%H%M%S"));
+        self.attach_bytes(entry_id, &file_name, png)
+    }
+
+    pub fn attachments_for(&self, entry_id: &str) -> Vec<JournalAttachment> {
+        let mut attachments: Vec<JournalAttachment> = self
+            .manifest
+            .links
+            .get(entry_id)
+            .into_iter()
+            .flatten()
+            .filter_map(|id| self.manifest.attachments.get(id).cloned())
+            .collect();
+        attachments.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+        attachments
+    }
+
+    /// Unlinks an attachment from an entry. Returns whether a link existed.
+    pub fn remove(
+        &mut self,
+        entry_id: &str,
+        attachment_id: &str,
+    ) -> Result<bool, JournalAttachmentError> {
+        let Some(ids) = self.manifest.links.get_mut(entry_id) else {
+            return Ok(false);
+        };
+        let removed = ids.remove(attachment_id);
+        if ids.is_empty() {
+            self.manifest.links.remove(entry_id);
+        }
+        if removed {
+            self.save()?;
+        }
+        Ok(removed)
+    }
+
+    /// Drops every link held by a deleted entry.
+    pub fn unlink_entry(&mut self, entry_id: &str) -> Result<usize, JournalAttachmentError> {
+        match self.manifest.links.remove(entry_id) {
+            Some(ids) => {
+                self.save()?;
+                Ok(ids.len())
+            }
+            None => Ok(0),
+        }
+    }
+
+    fn referenced(&self) -> HashSet<&String> {
+        self.manifest.links.values().flatten().collect()
+    }
+
+    /// Files in the directory that are neither the manifest nor a known blob.
+    fn untracked_files(&self) -> Result<Vec<(PathBuf, u64)>, JournalAttachmentError> {
+        let mut untracked = Vec::new();
+        for item in fs::read_dir(&self.dir)? {
+            let item = item?;
+            let name = item.file_name().to_string_lossy().into_owned();
+            if name == MANIFEST_FILE || self.manifest.attachments.contains_key(&name) {
+                continue;
+            }
+            let metadata = item.metadata()?;
+            if metadata.is_file() {
+                untracked.push((item.path(), metadata.len()));
+            }
+        }
+        Ok(untracked)
+    }
+
+    pub fn usage(&self) -> Result<JournalAttachmentUsage, JournalAttachmentError> {
+        let referenced = self.referenced();
+        let (orphaned_count, orphaned_bytes) = self
+            .manifest
+            .attachments
+            .values()
+            .filter(|attachment| !referenced.contains(&attachment.id))
+            .fold((0, 0), |(count, bytes), attachment| {
+                (count + 1, bytes + attachment.size_bytes)
+            });
+        let untracked = self.untracked_files()?;
+
+        Ok(JournalAttachmentUsage {
+            attachment_count: self.manifest.attachments.len(),
+            total_bytes: self
+                .manifest
+                .attachments
+                .values()
+                .map(|attachment| attachment.size_bytes)
+                .sum(),
+            linked_entries: self.manifest.links.len(),
+            orphaned_count,
+            orphaned_bytes,
+            untracked_files: untracked.len(),
+            untracked_bytes: untracked.iter().map(|(_, size)| size).sum(),
+        })
+    }
+
+    /// Drops links to entries that no longer exist (when `live_entries` is
+    /// given), then deletes every unreferenced blob and stray file.
+    pub fn cleanup(
+        &mut self,
+        live_entries: Option<&HashSet<String>>,
+    ) -> Result<JournalAttachmentCleanup, JournalAttachmentError> {
+        let mut report = JournalAttachmentCleanup::default();
+
+        if let Some(live) = live_entries {
+            let before = self.manifest.links.len();
+            self.manifest
+                .links
+                .retain(|entry_id, _| live.contains(entry_id));
+            report.removed_links = before - self.manifest.links.len();
+        }
+
+        let referenced: HashSet<String> = self.referenced().into_iter().cloned().collect();
+        let orphaned: Vec<String> = self
+            .manifest
+            .attachments
+            .keys()
+            .filter(|id| !referenced.contains(*id))
+            .cloned()
+            .collect();
+        for id in orphaned {
+            if let Some(attachment) = self.manifest.attachments.remove(&id) {
+                match fs::remove_file(self.blob_path(&id)) {
+                    Ok(()) => report.freed_bytes += attachment.size_bytes,
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                    Err(e) => return Err(e.into()),
+                }
+                report.removed_attachments += 1;
+            }
+        }
+
+        for (path, size) in self.untracked_files()? {
+            fs::remove_file(path)?;
+            report.removed_files += 1;
+            report.freed_bytes += size;
+        }
+
+        self.save()?;
+        Ok(report)
+    }
+
+    /// Every linked attachment with its content, for inclusion in backups.
+    pub fn export(&self) -> Result<JournalAttachmentBackup, JournalAttachmentError> {
+        let referenced = self.referenced();
+        let mut attachments = Vec::new();
+        for attachment in self.manifest.attachments.values() {
+            if !referenced.contains(&attachment.id) {
+                continue;
+            }
+            let bytes = fs::read(self.blob_path(&attachment.id))?;
+            attachments.push(JournalAttachmentBlob {
+                attachment: attachment.clone(),
+                data: BASE64_ENGINE.encode(bytes),
+            });
+        }
+
+        Ok(JournalAttachmentBackup {
+            attachments,
+            links: self
+                .manifest
+                .links
+                .iter()
+                .map(|(entry_id, ids)| (entry_id.clone(), ids.iter().cloned().collect()))
+                .collect(),
+        })
+    }
+
+    /// Restores attachments from a backup. Without `merge` existing links are
+    /// replaced; blobs are kept until the next cleanup either way. Content
+    /// whose hash does not match its id is rejected.
+    pub fn import(
+        &mut self,
+        backup: JournalAttachmentBackup,
+        merge: bool,
+    ) -> Result<usize, JournalAttachmentError> {
+        let mut decoded = Vec::with_capacity(backup.attachments.len());
+        for blob in backup.attachments {
+            let bytes = BASE64_ENGINE
+                .decode(blob.data.as_bytes())
+                .map_err(|e| JournalAttachmentError::Invalid(e.to_string()))?;
+            if content_id(&bytes) != blob.attachment.id {
+                return Err(JournalAttachmentError::Invalid(format!(
+                    "content of {} does not match its hash",
+                    blob.attachment.file_name
+                )));
+            }
+            decoded.push((blob.attachment, bytes));
+        }
+
+        let count = decoded.len();
+        for (attachment, bytes) in decoded {
+            self.write_blob(&attachment.id, &bytes)?;
+            self.manifest
+                .attachments
+                .entry(attachment.id.clone())
+                .or_insert(attachment);
+        }
+
+        if !merge {
+            self.manifest.links.clear();
+        }
+        for (entry_id, ids) in backup.links {
+            let links = self.manifest.links.entry(entry_id).or_default();
+            links.extend(
+                ids.into_iter()
+                    .filter(|id| self.manifest.attachments.contains_key(id)),
+            );
+        }
+        self.manifest.links.retain(|_, ids| !ids.is_empty());
+
+        self.save()?;
+        Ok(count)
+    }
+}
+
+fn content_id(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
+
+fn sanitize_file_name(name: &str) -> String {
+    let name: String = name
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_control() || c == '/' || c == '\\' {
+                '_'
+            } else {
+                c
+            }
+        })
+        .take(200)
+        .collect();
+    if name.is_empty() {
+        "attachment".to_string()
+    } else {
+        name
+    }
+}
+
+fn mime_type(file_name: &str, bytes: &[u8]) -> &'static str {
+    if bytes.starts_with(PNG_MAGIC) {
+        return "image/png";
+    }
+    let extension = Path::new(file_name)
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "pdf" => "application/pdf",
+        "csv" => "text/csv",
+        "txt" | "md" => "text/plain",
+        "json" => "application/json",
+        _ => "application/octet-stream",
+    }
+}
+
+async fn ensure_entry_exists(db: &SharedJournalDatabase, entry_id: &str) -> Result<(), String> {
+    let exists = db
+        .read()
+        .await
+        .get_entry(entry_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .is_some();
+    if exists {
+        Ok(())
+    } else {
+        Err(format!("Journal entry {} not found", entry_id))
+    }
+}
+
+// Tauri commands
+#[tauri::command]
+pub async fn journal_attach_file(
+    entry_id: String,
+    path: String,
+    db: State<'_, SharedJournalDatabase>,
+    store: State<'_, SharedJournalAttachmentStore>,
+) -> Result<JournalAttachment, String> {
+    ensure_entry_exists(&db, &entry_id).await?;
+    store
+        .write()
+        .attach_file(&entry_id, Path::new(&path))
+        .map_err(|e| e.to_string())
+}
+
+/// Saves a PNG chart capture from the frontend and links it in one call.
+#[tauri::command]
+pub async fn journal_attach_chart(
+    entry_id: String,
+    png: Vec<u8>,
+    label: Option<String>,
+    db: State<'_, SharedJournalDatabase>,
+    store: State<'_, SharedJournalAttachmentStore>,
+) -> Result<JournalAttachment, String> {
+    ensure_entry_exists(&db, &entry_id).await?;
+    store
+        .write()
+        .attach_chart(&entry_id, &png, label.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn journal_get_attachments(
+    entry_id: String,
+    store: State<'_, SharedJournalAttachmentStore>,
+) -> Result<Vec<JournalAttachment>, String> {
+    Ok(store.read().attachments_for(&entry_id))
+}
+
+#[tauri::command]
+pub async fn journal_remove_attachment(
+    entry_id: String,
+    attachment_id: String,
+    store: State<'_, SharedJournalAttachmentStore>,
+) -> Result<bool, String> {
+    store
+        .write()
+        .remove(&entry_id, &attachment_id)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn journal_attachment_usage(
+    store: State<'_, SharedJournalAttachmentStore>,
+) -> Result<JournalAttachmentUsage, String> {
+    store.read().usage().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn journal_cleanup_attachments(
+    db: State<'_, SharedJournalDatabase>,
+    store: State<'_, SharedJournalAttachmentStore>,
+) -> Result<JournalAttachmentCleanup, String> {
+    let live_entries: HashSet<String> = db
+        .read()
+        .await
+        .entry_ids()
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .collect();
+    store
+        .write()
+        .cleanup(Some(&live_entries))
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn temp_store() -> (PathBuf, JournalAttachmentStore) {
+        let dir = std::env::temp_dir().join(format!("journal-attachments-{}", Uuid::new_v4()));
+        let store = JournalAttachmentStore::load(dir.clone()).unwrap();
+        (dir, store)
+    }
+
+    fn png(payload: &[u8]) -> Vec<u8> {
+        let mut bytes = PNG_MAGIC.to_vec();
+        bytes.extend_from_slice(payload);
+        bytes
+    }
+
+    #[test]
+    fn test_identical_content_is_stored_once_and_shared_between_entries() {
+        let (dir, mut store) = temp_store();
+        let chart = png(b"breakout");
+
+        let first = store
+            .attach_chart("entry-a", &chart, Some("SOL 1h"))
+            .unwrap();
+        let second = store.attach_bytes("entry-b", "copy.png", &chart).unwrap();
+        assert_eq!(first.id, second.id);
+        assert_eq!(first.mime_type, "image/png");
+        assert_eq!(store.attachments_for("entry-a"), vec![first.clone()]);
+        assert_eq!(store.attachments_for("entry-b"), vec![first.clone()]);
+
+        let usage = store.usage().unwrap();
+        assert_eq!(usage.attachment_count, 1);
+        assert_eq!(usage.total_bytes, chart.len() as u64);
+        assert_eq!(usage.linked_entries, 2);
+
+        assert!(store.attach_chart("entry-a", b"not a png", None).is_err());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_cleanup_removes_only_unreferenced_blobs_and_stray_files() {
+        let (dir, mut store) = temp_store();
+        let kept = store.attach_bytes("entry-a", "notes.txt", b"keep").unwrap();
+        let dropped = store.attach_bytes("entry-a", "old.txt", b"drop").unwrap();
+        let deleted_entry = store
+            .attach_bytes("entry-gone", "gone.txt", b"gone")
+            .unwrap();
+        fs::write(dir.join("stray.tmp"), b"partial").unwrap();
+
+        assert!(store.remove("entry-a", &dropped.id).unwrap());
+        assert!(!store.remove("entry-a", &dropped.id).unwrap());
+        let usage = store.usage().unwrap();
+        assert_eq!(usage.orphaned_count, 1);
+        assert_eq!(usage.untracked_files, 1);
+
+        let live: HashSet<String> = ["entry-a".to_string()].into_iter().collect();
+        let report = store.cleanup(Some(&live)).unwrap();
+        assert_eq!(report.removed_links, 1);
+        assert_eq!(report.removed_attachments, 2);
+        assert_eq!(report.removed_files, 1);
+        assert!(dir.join(&kept.id).exists());
+        assert!(!dir.join(&dropped.id).exists());
+        assert!(!dir.join(&deleted_entry.id).exists());
+
+        let reloaded = JournalAttachmentStore::load(dir.clone()).unwrap();
+        assert_eq!(reloaded.attachments_for("entry-a"), vec![kept]);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_backup_round_trip_restores_content_and_links() {
+        let (dir, mut store) = temp_store();
+        let chart = store.attach_chart("entry-a", &png(b"range"), None).unwrap();
+        store.attach_bytes("entry-b", "plan.md", b"# plan").unwrap();
+        let backup = store.export().unwrap();
+        assert_eq!(backup.attachments.len(), 2);
+
+        let (other_dir, mut other) = temp_store();
+        other
+            .attach_bytes("entry-c", "local.txt", b"local")
+            .unwrap();
+        assert_eq!(other.import(backup.clone(), true).unwrap(), 2);
+        assert_eq!(other.attachments_for("entry-a"), vec![chart.clone()]);
+        assert_eq!(other.attachments_for("entry-c").len(), 1);
+        assert_eq!(fs::read(other_dir.join(&chart.id)).unwrap(), png(b"range"));
+
+        other.import(backup.clone(), false).unwrap();
+        assert!(other.attachments_for("entry-c").is_empty());
+
+        let mut tampered = backup;
+        tampered.attachments[0].data = BASE64_ENGINE.encode(b"swapped");
+        assert!(other.import(tampered, true).is_err());
+
+        fs::remove_dir_all(dir).unwrap();
+        fs::remove_dir_all(other_dir).unwrap();
+    }
+}
@@ -1,7 +1,22 @@
 use super::analytics::JournalAnalytics;
+use super::attachments::SharedJournalAttachmentStore;
 use super::database::SharedJournalDatabase;
+use super::ideas;
 use super::types::*;
-use chrono::Utc;
+use crate::portfolio::SharedTokenAnnotationStore;
+use crate::wallet::fee_audit::SharedFeeAuditor;
+use chrono::{DateTime, Utc};
+
+/// Attaches the current annotation summary to entries that name a token.
+fn annotate_entries(entries: &mut [JournalEntry], annotations: &SharedTokenAnnotationStore) {
+    let store = annotations.read();
+    for entry in entries {
+        entry.token_annotation = entry
+            .token_mint
+            .as_deref()
+            .and_then(|mint| store.summary(mint));
+    }
+}
 
 #[tauri::command]
 pub async fn create_journal_entry(
@@ -34,9 +49,14 @@ pub async fn create_journal_entry(
 pub async fn get_journal_entry(
     id: String,
     db: tauri::State<'_, SharedJournalDatabase>,
+    annotations: tauri::State<'_, SharedTokenAnnotationStore>,
 ) -> Result<Option<JournalEntry>, String> {
     let db_lock = db.read().await;
-    db_lock.get_entry(&id).await.map_err(|e| e.to_string())
+    let mut entry = db_lock.get_entry(&id).await.map_err(|e| e.to_string())?;
+    if let Some(entry) = entry.as_mut() {
+        annotate_entries(std::slice::from_mut(entry), &annotations);
+    }
+    Ok(entry)
 }
 
 #[tauri::command]
@@ -60,9 +80,18 @@ pub async fn update_journal_entry(
 pub async fn delete_journal_entry(
     id: String,
     db: tauri::State<'_, SharedJournalDatabase>,
+    attachments: tauri::State<'_, SharedJournalAttachmentStore>,
 ) -> Result<(), String> {
     let db_lock = db.write().await;
-    db_lock.delete_entry(&id).await.map_err(|e| e.to_string())
+    db_lock.delete_entry(&id).await.map_err(|e| e.to_string())?;
+    drop(db_lock);
+
+    // Blobs are left for `journal_cleanup_attachments`; only the links go.
+    attachments
+        .write()
+        .unlink_entry(&id)
+        .map_err(|e| e.to_string())?;
+    Ok(())
 }
 
 #[tauri::command]
@@ -71,12 +100,15 @@ pub async fn get_journal_entries(
     limit: i64,
     offset: i64,
     db: tauri::State<'_, SharedJournalDatabase>,
+    annotations: tauri::State<'_, SharedTokenAnnotationStore>,
 ) -> Result<Vec<JournalEntry>, String> {
     let db_lock = db.read().await;
-    db_lock
+    let mut entries = db_lock
         .get_entries(&filters, limit, offset)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(|e| e.to_string())?;
+    annotate_entries(&mut entries, &annotations);
+    Ok(entries)
 }
 
 #[tauri::command]
@@ -91,10 +123,37 @@ pub async fn get_journal_entries_count(
         .map_err(|e| e.to_string())
 }
 
+/// Fee figures for the report. The audit hits the RPC, so a failure only
+/// leaves them out rather than failing the report.
+async fn weekly_fee_summary(
+    auditor: &SharedFeeAuditor,
+    wallet: String,
+    since: i64,
+    until: i64,
+) -> Option<WeeklyFeeSummary> {
+    let since = DateTime::from_timestamp(since, 0)?;
+    let until = DateTime::from_timestamp(until, 0)?;
+    match auditor.audit(&wallet, since, until).await {
+        Ok(audit) => Some(WeeklyFeeSummary {
+            wallet,
+            total_fees_usd: audit.totals.total_usd,
+            trades: audit.trades,
+            fee_per_trade_usd: audit.fee_per_trade_usd,
+            fees_pct_of_pnl: audit.fees_pct_of_pnl,
+        }),
+        Err(e) => {
+            eprintln!("Failed to audit fees for weekly report: {}", e);
+            None
+        }
+    }
+}
+
 #[tauri::command]
 pub async fn generate_weekly_report(
     week_start: Option<i64>,
+    wallet: Option<String>,
     db: tauri::State<'_, SharedJournalDatabase>,
+    auditor: tauri::State<'_, SharedFeeAuditor>,
 ) -> Result<WeeklyReport, String> {
     let now = Utc::now().timestamp();
     let week_start = week_start.unwrap_or(now - (7 * 24 * 60 * 60));
@@ -121,7 +180,11 @@ pub async fn generate_weekly_report(
         .map_err(|e| e.to_string())?;
     drop(db_lock);
 
-    let report = JournalAnalytics::generate_weekly_report(&entries);
+    let mut report = JournalAnalytics::generate_weekly_report(&entries);
+    if let Some(wallet) = wallet {
+        report.fee_summary =
+            weekly_fee_summary(&auditor, wallet, week_start, week_end.min(now)).await;
+    }
 
     let db_lock = db.write().await;
     db_lock
@@ -324,3 +387,98 @@ pub async fn get_journal_stats(
         overall_discipline_score,
     })
 }
+
+#[tauri::command]
+pub async fn create_trade_idea(
+    idea: NewTradeIdea,
+    db: tauri::State<'_, SharedJournalDatabase>,
+) -> Result<TradeIdea, String> {
+    let idea = ideas::new_trade_idea(
+        idea,
+        uuid::Uuid::new_v4().to_string(),
+        Utc::now().timestamp(),
+    )?;
+
+    let db_lock = db.write().await;
+    db_lock
+        .save_trade_idea(&idea)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(idea)
+}
+
+#[tauri::command]
+pub async fn get_trade_ideas(
+    window: Option<i64>,
+    open_only: Option<bool>,
+    db: tauri::State<'_, SharedJournalDatabase>,
+) -> Result<Vec<TradeIdea>, String> {
+    let since = ideas::window_start(window, Utc::now().timestamp());
+    let db_lock = db.read().await;
+    db_lock
+        .get_trade_ideas(since, open_only.unwrap_or(false))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn delete_trade_idea(
+    id: String,
+    db: tauri::State<'_, SharedJournalDatabase>,
+) -> Result<(), String> {
+    let db_lock = db.write().await;
+    db_lock
+        .delete_trade_idea(&id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Links an order placed on the idea, by the same id journal entries use as
+/// their `trade_id`. Grading is unaffected; linked ideas count as traded.
+#[tauri::command]
+pub async fn link_trade_idea_order(
+    idea_id: String,
+    order_id: String,
+    db: tauri::State<'_, SharedJournalDatabase>,
+) -> Result<TradeIdea, String> {
+    let order_id = order_id.trim().to_string();
+    if order_id.is_empty() {
+        return Err("Order id is required".to_string());
+    }
+
+    let db_lock = db.write().await;
+    let mut idea = db_lock
+        .get_trade_idea(&idea_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Trade idea {} not found", idea_id))?;
+    if !idea.linked_order_ids.contains(&order_id) {
+        idea.linked_order_ids.push(order_id);
+        idea.updated_at = Utc::now().timestamp();
+        db_lock
+            .save_trade_idea(&idea)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    Ok(idea)
+}
+
+/// Hit rate and average R of ideas created in the last `window` days, or
+/// of all ideas.
+#[tauri::command]
+pub async fn get_idea_statistics(
+    window: Option<i64>,
+    db: tauri::State<'_, SharedJournalDatabase>,
+) -> Result<IdeaStatistics, String> {
+    let since = ideas::window_start(window, Utc::now().timestamp());
+    let db_lock = db.read().await;
+    let all = db_lock
+        .get_trade_ideas(since, false)
+        .await
+        .map_err(|e| e.to_string())?;
+    drop(db_lock);
+
+    Ok(ideas::idea_statistics(&all, window))
+}
@@ -1,4 +1,5 @@
 use super::types::*;
+use crate::utils::ensure_column;
 use serde_json;
 use sqlx::{Pool, Row, Sqlite, SqlitePool};
 use std::path::PathBuf;
@@ -29,6 +30,14 @@ impl JournalDatabase {
         Ok(db)
     }
 
+    /// Folds the write-ahead log back into the database file.
+    pub async fn checkpoint(&self) -> Result<(), sqlx::Error> {
+        sqlx::query("PRAGMA wal_checkpoint(TRUNCATE)")
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
     async fn initialize(&self) -> Result<(), sqlx::Error> {
         sqlx::query(
             r#"
@@ -101,6 +110,48 @@ impl JournalDatabase {
         .execute(&self.pool)
         .await?;
 
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS trade_ideas (
+                id TEXT PRIMARY KEY,
+                token_symbol TEXT NOT NULL,
+                token_mint TEXT,
+                direction TEXT NOT NULL,
+                thesis TEXT NOT NULL,
+                entry_low REAL NOT NULL,
+                entry_high REAL NOT NULL,
+                targets TEXT NOT NULL,
+                invalidation REAL NOT NULL,
+                expires_at INTEGER NOT NULL,
+                status TEXT NOT NULL,
+                triggered_at INTEGER,
+                entry_price REAL,
+                targets_hit INTEGER NOT NULL,
+                closed_at INTEGER,
+                exit_price REAL,
+                r_multiple REAL,
+                last_price REAL,
+                linked_order_ids TEXT NOT NULL,
+                created_at INTEGER NOT NULL,
+                updated_at INTEGER NOT NULL
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_trade_ideas_open ON trade_ideas(closed_at, token_symbol);
+            CREATE INDEX IF NOT EXISTS idx_trade_ideas_created_at ON trade_ideas(created_at);
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        ensure_column(&self.pool, "journal_entries", "token_mint", "TEXT").await?;
+        ensure_column(&self.pool, "weekly_reports", "fee_summary", "TEXT").await?;
+
         Ok(())
     }
 
@@ -121,9 +172,9 @@ impl JournalDatabase {
                 id, timestamp, trade_id, entry_type, strategy_tags,
                 emotions, notes, market_conditions, confidence_level,
                 position_size, entry_price, exit_price, outcome,
-                lessons_learned, attachments, created_at, updated_at
+                lessons_learned, attachments, created_at, updated_at, token_mint
             ) VALUES (
-                ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17
+                ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18
             )
             "#,
         )
@@ -144,6 +195,7 @@ impl JournalDatabase {
         .bind(attachments_json)
         .bind(entry.created_at)
         .bind(entry.updated_at)
+        .bind(&entry.token_mint)
         .execute(&self.pool)
         .await?;
 
@@ -176,7 +228,7 @@ impl JournalDatabase {
                 timestamp = ?2, trade_id = ?3, entry_type = ?4, strategy_tags = ?5,
                 emotions = ?6, notes = ?7, market_conditions = ?8, confidence_level = ?9,
                 position_size = ?10, entry_price = ?11, exit_price = ?12, outcome = ?13,
-                lessons_learned = ?14, attachments = ?15, updated_at = ?16
+                lessons_learned = ?14, attachments = ?15, updated_at = ?16, token_mint = ?17
             WHERE id = ?1
             "#,
         )
@@ -196,6 +248,7 @@ impl JournalDatabase {
         .bind(&entry.lessons_learned)
         .bind(attachments_json)
         .bind(entry.updated_at)
+        .bind(&entry.token_mint)
         .execute(&self.pool)
         .await?;
 
@@ -211,6 +264,14 @@ impl JournalDatabase {
         Ok(())
     }
 
+    pub async fn entry_ids(&self) -> Result<Vec<String>, sqlx::Error> {
+        let rows = sqlx::query("SELECT id FROM journal_entries")
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.iter().map(|r| r.get("id")).collect())
+    }
+
     pub async fn get_entries(
         &self,
         filters: &JournalFilters,
@@ -280,6 +341,32 @@ impl JournalDatabase {
         Ok(entries)
     }
 
+    /// Entries about `mint` with timestamps in `[start, end]`, oldest first.
+    pub async fn get_entries_for_mint(
+        &self,
+        mint: &str,
+        start: i64,
+        end: i64,
+        limit: i64,
+    ) -> Result<Vec<JournalEntry>, sqlx::Error> {
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM journal_entries
+            WHERE token_mint = ?1 AND timestamp >= ?2 AND timestamp <= ?3
+            ORDER BY timestamp ASC
+            LIMIT ?4
+            "#,
+        )
+        .bind(mint)
+        .bind(start)
+        .bind(end)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.iter().map(|r| self.row_to_entry(r)).collect())
+    }
+
     pub async fn get_entries_count(&self, filters: &JournalFilters) -> Result<i64, sqlx::Error> {
         let mut query = String::from("SELECT COUNT(*) as count FROM journal_entries WHERE 1=1");
 
@@ -351,6 +438,10 @@ impl JournalDatabase {
             serde_json::to_string(&report.psychological_insights).unwrap_or_default();
         let recommendations_json =
             serde_json::to_string(&report.recommendations).unwrap_or_default();
+        let fee_summary_json = report
+            .fee_summary
+            .as_ref()
+            .and_then(|summary| serde_json::to_string(summary).ok());
 
         sqlx::query(
             r#"
@@ -359,9 +450,9 @@ impl JournalDatabase {
                 trades_won, trades_lost, win_rate, total_pnl,
                 average_confidence, emotion_breakdown, discipline_metrics,
                 pattern_insights, strategy_performance, psychological_insights,
-                recommendations, created_at
+                recommendations, created_at, fee_summary
             ) VALUES (
-                ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17
+                ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18
             )
             "#,
         )
@@ -382,6 +473,7 @@ impl JournalDatabase {
         .bind(psychological_insights_json)
         .bind(recommendations_json)
         .bind(report.created_at)
+        .bind(fee_summary_json)
         .execute(&self.pool)
         .await?;
 
@@ -411,6 +503,126 @@ impl JournalDatabase {
         Ok(reports)
     }
 
+    /// Inserts `idea`, or overwrites the stored idea with the same id.
+    pub async fn save_trade_idea(&self, idea: &TradeIdea) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO trade_ideas (
+                id, token_symbol, token_mint, direction, thesis, entry_low, entry_high,
+                targets, invalidation, expires_at, status, triggered_at, entry_price,
+                targets_hit, closed_at, exit_price, r_multiple, last_price,
+                linked_order_ids, created_at, updated_at
+            ) VALUES (
+                ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17,
+                ?18, ?19, ?20, ?21
+            )
+            "#,
+        )
+        .bind(&idea.id)
+        .bind(&idea.token_symbol)
+        .bind(&idea.token_mint)
+        .bind(idea.direction.as_str())
+        .bind(&idea.thesis)
+        .bind(idea.entry_low)
+        .bind(idea.entry_high)
+        .bind(serde_json::to_string(&idea.targets).unwrap_or_default())
+        .bind(idea.invalidation)
+        .bind(idea.expires_at)
+        .bind(idea.status.as_str())
+        .bind(idea.triggered_at)
+        .bind(idea.entry_price)
+        .bind(idea.targets_hit as i64)
+        .bind(idea.closed_at)
+        .bind(idea.exit_price)
+        .bind(idea.r_multiple)
+        .bind(idea.last_price)
+        .bind(serde_json::to_string(&idea.linked_order_ids).unwrap_or_default())
+        .bind(idea.created_at)
+        .bind(idea.updated_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_trade_idea(&self, id: &str) -> Result<Option<TradeIdea>, sqlx::Error> {
+        let row = sqlx::query("SELECT * FROM trade_ideas WHERE id = ?1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(row.map(|r| self.row_to_trade_idea(&r)))
+    }
+
+    pub async fn delete_trade_idea(&self, id: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM trade_ideas WHERE id = ?1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Ideas created at or after `since`, newest first.
+    pub async fn get_trade_ideas(
+        &self,
+        since: i64,
+        open_only: bool,
+    ) -> Result<Vec<TradeIdea>, sqlx::Error> {
+        let mut query = String::from("SELECT * FROM trade_ideas WHERE created_at >= ?1");
+        if open_only {
+            query.push_str(" AND closed_at IS NULL");
+        }
+        query.push_str(" ORDER BY created_at DESC");
+
+        let rows = sqlx::query(&query)
+            .bind(since)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.iter().map(|r| self.row_to_trade_idea(r)).collect())
+    }
+
+    /// Ideas on `symbol` still being graded.
+    pub async fn get_open_trade_ideas_for_symbol(
+        &self,
+        symbol: &str,
+    ) -> Result<Vec<TradeIdea>, sqlx::Error> {
+        let rows =
+            sqlx::query("SELECT * FROM trade_ideas WHERE closed_at IS NULL AND token_symbol = ?1")
+                .bind(symbol)
+                .fetch_all(&self.pool)
+                .await?;
+
+        Ok(rows.iter().map(|r| self.row_to_trade_idea(r)).collect())
+    }
+
+    fn row_to_trade_idea(&self, row: &sqlx::sqlite::SqliteRow) -> TradeIdea {
+        TradeIdea {
+            id: row.get("id"),
+            token_symbol: row.get("token_symbol"),
+            token_mint: row.get("token_mint"),
+            direction: IdeaDirection::from_str(row.get("direction")).unwrap_or(IdeaDirection::Long),
+            thesis: row.get("thesis"),
+            entry_low: row.get("entry_low"),
+            entry_high: row.get("entry_high"),
+            targets: serde_json::from_str(row.get("targets")).unwrap_or_default(),
+            invalidation: row.get("invalidation"),
+            expires_at: row.get("expires_at"),
+            status: IdeaStatus::from_str(row.get("status")).unwrap_or(IdeaStatus::Pending),
+            triggered_at: row.get("triggered_at"),
+            entry_price: row.get("entry_price"),
+            targets_hit: row.get::<i64, _>("targets_hit") as usize,
+            closed_at: row.get("closed_at"),
+            exit_price: row.get("exit_price"),
+            r_multiple: row.get("r_multiple"),
+            last_price: row.get("last_price"),
+            linked_order_ids: serde_json::from_str(row.get("linked_order_ids")).unwrap_or_default(),
+            created_at: row.get("created_at"),
+            updated_at: row.get("updated_at"),
+        }
+    }
+
     fn row_to_entry(&self, row: &sqlx::sqlite::SqliteRow) -> JournalEntry {
         JournalEntry {
             id: row.get("id"),
@@ -419,28 +631,10 @@ impl JournalDatabase {
             entry_type: serde_json::from_str(row.get("entry_type"))
                 .unwrap_or(EntryType::Reflection),
             strategy_tags: serde_json::from_str(row.get("strategy_tags")).unwrap_or_default(),
-            emotions: serde_json::from_str(row.get("emotions")).unwrap_or_else(|_| {
-                EmotionTracking {
-                    primary_emotion: Emotion::Neutral,
-                    intensity: 0.5,
-                    secondary_emotions: vec![],
-                    stress_level: 0.5,
-                    clarity_level: 0.5,
-                    fomo_level: 0.0,
-                    revenge_trading: false,
-                    discipline_score: 0.5,
-                }
-            }),
+            emotions: serde_json::from_str(row.get("emotions")).unwrap_or_default(),
             notes: row.get("notes"),
-            market_conditions: serde_json::from_str(row.get("market_conditions")).unwrap_or_else(
-                |_| MarketConditions {
-                    trend: MarketTrend::Neutral,
-                    volatility: Volatility::Medium,
-                    volume: VolumeLevel::Medium,
-                    news_sentiment: 0.0,
-                    notes: String::new(),
-                },
-            ),
+            market_conditions: serde_json::from_str(row.get("market_conditions"))
+                .unwrap_or_default(),
             confidence_level: row.get("confidence_level"),
             position_size: row.get("position_size"),
             entry_price: row.get("entry_price"),
@@ -452,6 +646,8 @@ impl JournalDatabase {
             attachments: serde_json::from_str(row.get("attachments")).unwrap_or_default(),
             created_at: row.get("created_at"),
             updated_at: row.get("updated_at"),
+            token_mint: row.get("token_mint"),
+            token_annotation: None,
         }
     }
 
@@ -500,6 +696,9 @@ impl JournalDatabase {
                 }),
             recommendations: serde_json::from_str(row.get("recommendations")).unwrap_or_default(),
             created_at: row.get("created_at"),
+            fee_summary: row
+                .get::<Option<String>, _>("fee_summary")
+                .and_then(|s| serde_json::from_str(&s).ok()),
         }
     }
 }
@@ -0,0 +1,459 @@
+//! Trade idea grading.
+//!
+//! Ideas ride on the alert price feed: every price the feed evaluates alerts
+//! with is also run through the open ideas on that symbol, so no separate
+//! polling happens here. An idea triggers when price reaches its entry zone
+//! and is graded in R, the planned risk from entry to invalidation, on the
+//! best target it reached.
+
+use super::database::JournalDatabase;
+use super::types::*;
+use crate::portfolio::analytics::classify_sector;
+use std::collections::{BTreeMap, HashSet};
+
+/// An open idea's last price is written back at most this often when
+/// nothing else about it changes.
+const LAST_PRICE_SAVE_SECS: i64 = 60;
+const SECONDS_PER_DAY: i64 = 86_400;
+
+pub fn new_trade_idea(idea: NewTradeIdea, id: String, now: i64) -> Result<TradeIdea, String> {
+    let NewTradeIdea {
+        token_symbol,
+        token_mint,
+        direction,
+        thesis,
+        entry_low,
+        entry_high,
+        mut targets,
+        invalidation,
+        horizon_hours,
+    } = idea;
+
+    let token_symbol = token_symbol.trim().to_uppercase();
+    if token_symbol.is_empty() {
+        return Err("An idea needs a token".to_string());
+    }
+    if targets
+        .iter()
+        .chain([&entry_low, &entry_high, &invalidation])
+        .any(|price| !price.is_finite() || *price <= 0.0)
+    {
+        return Err("Entry zone, targets and invalidation must be positive prices".to_string());
+    }
+    if entry_low > entry_high {
+        return Err("Entry zone low must not be above its high".to_string());
+    }
+    if targets.is_empty() {
+        return Err("An idea needs at least one target".to_string());
+    }
+    if horizon_hours <= 0 {
+        return Err("Time horizon must be at least an hour".to_string());
+    }
+    match direction {
+        IdeaDirection::Long => {
+            if invalidation >= entry_low || targets.iter().any(|target| *target <= entry_high) {
+                return Err(
+                    "A long idea needs its invalidation below and targets above the entry zone"
+                        .to_string(),
+                );
+            }
+            targets.sort_by(|a, b| a.total_cmp(b));
+        }
+        IdeaDirection::Short => {
+            if invalidation <= entry_high || targets.iter().any(|target| *target >= entry_low) {
+                return Err(
+                    "A short idea needs its invalidation above and targets below the entry zone"
+                        .to_string(),
+                );
+            }
+            targets.sort_by(|a, b| b.total_cmp(a));
+        }
+    }
+
+    Ok(TradeIdea {
+        id,
+        token_symbol,
+        token_mint,
+        direction,
+        thesis,
+        entry_low,
+        entry_high,
+        targets,
+        invalidation,
+        expires_at: now + horizon_hours * 3600,
+        status: IdeaStatus::Pending,
+        triggered_at: None,
+        entry_price: None,
+        targets_hit: 0,
+        closed_at: None,
+        exit_price: None,
+        r_multiple: None,
+        last_price: None,
+        linked_order_ids: Vec::new(),
+        created_at: now,
+        updated_at: now,
+    })
+}
+
+/// Whether `price` is at or beyond `level` in the idea's favour.
+fn reached(direction: IdeaDirection, price: f64, level: f64) -> bool {
+    match direction {
+        IdeaDirection::Long => price >= level,
+        IdeaDirection::Short => price <= level,
+    }
+}
+
+fn invalidated(idea: &TradeIdea, price: f64) -> bool {
+    match idea.direction {
+        IdeaDirection::Long => price <= idea.invalidation,
+        IdeaDirection::Short => price >= idea.invalidation,
+    }
+}
+
+/// Price at or past the near edge of the entry zone. Price beyond the far
+/// edge still triggers, as long as the idea is not invalidated.
+fn entered(idea: &TradeIdea, price: f64) -> bool {
+    match idea.direction {
+        IdeaDirection::Long => price <= idea.entry_high,
+        IdeaDirection::Short => price >= idea.entry_low,
+    }
+}
+
+fn r_multiple(idea: &TradeIdea, exit: f64) -> Option<f64> {
+    let entry = idea.entry_price?;
+    let risk = (entry - idea.invalidation).abs();
+    if risk <= 0.0 {
+        return None;
+    }
+    Some(match idea.direction {
+        IdeaDirection::Long => (exit - entry) / risk,
+        IdeaDirection::Short => (entry - exit) / risk,
+    })
+}
+
+fn close(idea: &mut TradeIdea, status: IdeaStatus, exit: Option<f64>, now: i64) {
+    idea.status = status;
+    idea.closed_at = Some(now);
+    idea.exit_price = exit;
+    let r = exit.and_then(|exit| r_multiple(idea, exit));
+    idea.r_multiple = r;
+}
+
+fn best_target(idea: &TradeIdea) -> Option<f64> {
+    idea.targets_hit
+        .checked_sub(1)
+        .and_then(|index| idea.targets.get(index).copied())
+}
+
+/// Closes an idea whose horizon has passed. Ideas that reached a target are
+/// graded on the best one; triggered ideas at the last seen price.
+fn expire(idea: &mut TradeIdea, now: i64) {
+    match idea.status {
+        IdeaStatus::Pending => close(idea, IdeaStatus::Expired, None, now),
+        IdeaStatus::Triggered => {
+            let exit = idea.last_price.or(idea.entry_price);
+            close(idea, IdeaStatus::Expired, exit, now);
+        }
+        IdeaStatus::TargetHit => {
+            let exit = best_target(idea);
+            close(idea, IdeaStatus::TargetHit, exit, now);
+        }
+        IdeaStatus::Invalidated | IdeaStatus::Expired => {}
+    }
+}
+
+/// Moves an open idea along with a new `price`. Returns whether the idea
+/// should be saved.
+pub fn advance_idea(idea: &mut TradeIdea, price: f64, now: i64) -> bool {
+    if idea.closed_at.is_some() || !price.is_finite() || price <= 0.0 {
+        return false;
+    }
+    let before = (idea.status, idea.targets_hit);
+    idea.last_price = Some(price);
+
+    if now >= idea.expires_at {
+        expire(idea, now);
+    } else {
+        match idea.status {
+            IdeaStatus::Pending => {
+                if invalidated(idea, price) {
+                    close(idea, IdeaStatus::Invalidated, None, now);
+                } else if entered(idea, price) {
+                    idea.status = IdeaStatus::Triggered;
+                    idea.triggered_at = Some(now);
+                    idea.entry_price = Some(price);
+                }
+            }
+            IdeaStatus::Triggered | IdeaStatus::TargetHit => {
+                let hit = idea
+                    .targets
+                    .iter()
+                    .take_while(|target| reached(idea.direction, price, **target))
+                    .count();
+                if hit > idea.targets_hit {
+                    idea.targets_hit = hit;
+                    idea.status = IdeaStatus::TargetHit;
+                }
+                if idea.targets_hit == idea.targets.len() {
+                    let exit = best_target(idea);
+                    close(idea, IdeaStatus::TargetHit, exit, now);
+                } else if invalidated(idea, price) {
+                    if idea.targets_hit == 0 {
+                        let exit = Some(idea.invalidation);
+                        close(idea, IdeaStatus::Invalidated, exit, now);
+                    } else {
+                        let exit = best_target(idea);
+                        close(idea, IdeaStatus::TargetHit, exit, now);
+                    }
+                }
+            }
+            IdeaStatus::Invalidated | IdeaStatus::Expired => {}
+        }
+    }
+
+    let changed = (idea.status, idea.targets_hit) != before
+        || idea.closed_at.is_some()
+        || now - idea.updated_at >= LAST_PRICE_SAVE_SECS;
+    if changed {
+        idea.updated_at = now;
+    }
+    changed
+}
+
+/// Grades the open ideas on `symbol` against `price`. Returns the ideas
+/// that changed state.
+pub async fn grade_trade_ideas(
+    db: &JournalDatabase,
+    symbol: &str,
+    price: f64,
+    now: i64,
+) -> Result<Vec<TradeIdea>, sqlx::Error> {
+    let mut changed = Vec::new();
+    for mut idea in db.get_open_trade_ideas_for_symbol(symbol).await? {
+        let status = (idea.status, idea.targets_hit, idea.closed_at);
+        if advance_idea(&mut idea, price, now) {
+            db.save_trade_idea(&idea).await?;
+            if (idea.status, idea.targets_hit, idea.closed_at) != status {
+                changed.push(idea);
+            }
+        }
+    }
+    Ok(changed)
+}
+
+/// Closes open ideas past their horizon, including those on symbols that
+/// have stopped streaming.
+pub async fn expire_trade_ideas(db: &JournalDatabase, now: i64) -> Result<usize, sqlx::Error> {
+    let mut expired = 0;
+    for mut idea in db.get_trade_ideas(0, true).await? {
+        if now >= idea.expires_at {
+            expire(&mut idea, now);
+            idea.updated_at = now;
+            db.save_trade_idea(&idea).await?;
+            expired += 1;
+        }
+    }
+    Ok(expired)
+}
+
+/// Symbols with ideas still being graded, for the price feed to watch.
+pub async fn open_trade_idea_symbols(db: &JournalDatabase) -> Result<HashSet<String>, sqlx::Error> {
+    Ok(db
+        .get_trade_ideas(0, true)
+        .await?
+        .into_iter()
+        .map(|idea| idea.token_symbol)
+        .collect())
+}
+
+/// Start of a window of `window_days`, or of time for `None`.
+pub fn window_start(window_days: Option<i64>, now: i64) -> i64 {
+    window_days.map_or(0, |days| now - days.max(0) * SECONDS_PER_DAY)
+}
+
+fn category_stats(category: String, ideas: &[&TradeIdea]) -> IdeaCategoryStats {
+    let graded: Vec<f64> = ideas.iter().filter_map(|idea| idea.r_multiple).collect();
+    let hits = ideas
+        .iter()
+        .filter(|idea| idea.r_multiple.is_some() && idea.targets_hit > 0)
+        .count();
+    let (hit_rate, average_r) = if graded.is_empty() {
+        (0.0, 0.0)
+    } else {
+        (
+            hits as f64 / graded.len() as f64 * 100.0,
+            graded.iter().sum::<f64>() / graded.len() as f64,
+        )
+    };
+    IdeaCategoryStats {
+        category,
+        ideas: ideas.len(),
+        graded: graded.len(),
+        hit_rate,
+        average_r,
+    }
+}
+
+/// Hit rate and average R of `ideas`, overall and by token category.
+pub fn idea_statistics(ideas: &[TradeIdea], window_days: Option<i64>) -> IdeaStatistics {
+    let count = |status: IdeaStatus| ideas.iter().filter(|idea| idea.status == status).count();
+
+    let mut categories: BTreeMap<String, Vec<&TradeIdea>> = BTreeMap::new();
+    for idea in ideas {
+        categories
+            .entry(classify_sector(&idea.token_symbol))
+            .or_default()
+            .push(idea);
+    }
+    let mut by_category: Vec<IdeaCategoryStats> = categories
+        .into_iter()
+        .map(|(category, ideas)| category_stats(category, &ideas))
+        .collect();
+    by_category.sort_by(|a, b| b.graded.cmp(&a.graded));
+
+    IdeaStatistics {
+        window_days,
+        total_ideas: ideas.len(),
+        open_ideas: ideas.iter().filter(|idea| idea.closed_at.is_none()).count(),
+        traded_ideas: ideas
+            .iter()
+            .filter(|idea| !idea.linked_order_ids.is_empty())
+            .count(),
+        triggered: ideas
+            .iter()
+            .filter(|idea| idea.triggered_at.is_some())
+            .count(),
+        target_hit: count(IdeaStatus::TargetHit),
+        invalidated: count(IdeaStatus::Invalidated),
+        expired: count(IdeaStatus::Expired),
+        overall: category_stats("All".to_string(), &ideas.iter().collect::<Vec<_>>()),
+        by_category,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn idea(
+        symbol: &str,
+        direction: IdeaDirection,
+        entry: (f64, f64),
+        targets: Vec<f64>,
+        invalidation: f64,
+    ) -> TradeIdea {
+        new_trade_idea(
+            NewTradeIdea {
+                token_symbol: symbol.to_string(),
+                token_mint: None,
+                direction,
+                thesis: "breakout".to_string(),
+                entry_low: entry.0,
+                entry_high: entry.1,
+                targets,
+                invalidation,
+                horizon_hours: 24,
+            },
+            format!("{symbol}-idea"),
+            0,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn long_idea_is_graded_on_its_best_target() {
+        let mut long = idea(
+            "BONK",
+            IdeaDirection::Long,
+            (9.0, 10.0),
+            vec![14.0, 12.0],
+            8.0,
+        );
+        assert_eq!(long.targets, vec![12.0, 14.0]);
+
+        assert!(!advance_idea(&mut long, 11.0, 10));
+        assert_eq!(long.status, IdeaStatus::Pending);
+        assert!(advance_idea(&mut long, 10.0, 20));
+        assert_eq!(long.status, IdeaStatus::Triggered);
+        assert!(advance_idea(&mut long, 12.5, 30));
+        assert_eq!(long.status, IdeaStatus::TargetHit);
+        assert_eq!(long.closed_at, None);
+
+        // Falling to invalidation after the first target keeps the target.
+        assert!(advance_idea(&mut long, 7.5, 40));
+        assert_eq!(long.status, IdeaStatus::TargetHit);
+        assert_eq!(long.closed_at, Some(40));
+        assert_eq!(long.r_multiple, Some(1.0));
+        assert!(!advance_idea(&mut long, 20.0, 50));
+    }
+
+    #[test]
+    fn ideas_invalidate_and_expire() {
+        let mut short = idea(
+            "SOL",
+            IdeaDirection::Short,
+            (100.0, 105.0),
+            vec![90.0],
+            110.0,
+        );
+        advance_idea(&mut short, 101.0, 10);
+        advance_idea(&mut short, 111.0, 20);
+        assert_eq!(short.status, IdeaStatus::Invalidated);
+        assert_eq!(short.r_multiple, Some(-1.0));
+
+        let mut untriggered = idea("JUP", IdeaDirection::Long, (1.0, 1.1), vec![1.5], 0.9);
+        advance_idea(&mut untriggered, 0.85, 10);
+        assert_eq!(untriggered.status, IdeaStatus::Invalidated);
+        assert_eq!(untriggered.r_multiple, None);
+
+        let mut stale = idea("WIF", IdeaDirection::Long, (2.0, 2.2), vec![3.0], 1.8);
+        advance_idea(&mut stale, 2.1, 10);
+        advance_idea(&mut stale, 2.4, 24 * 3600);
+        assert_eq!(stale.status, IdeaStatus::Expired);
+        assert!((stale.r_multiple.unwrap() - 1.0).abs() < 1e-9);
+
+        assert!(new_trade_idea(
+            NewTradeIdea {
+                token_symbol: "SOL".to_string(),
+                token_mint: None,
+                direction: IdeaDirection::Long,
+                thesis: String::new(),
+                entry_low: 100.0,
+                entry_high: 105.0,
+                targets: vec![104.0],
+                invalidation: 95.0,
+                horizon_hours: 24,
+            },
+            "bad".to_string(),
+            0,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn statistics_group_by_token_category() {
+        let mut meme_win = idea("BONK", IdeaDirection::Long, (9.0, 10.0), vec![13.0], 8.0);
+        advance_idea(&mut meme_win, 10.0, 10);
+        advance_idea(&mut meme_win, 13.0, 20);
+        let mut meme_loss = idea("WIF", IdeaDirection::Long, (9.0, 10.0), vec![12.0], 8.0);
+        advance_idea(&mut meme_loss, 10.0, 10);
+        advance_idea(&mut meme_loss, 8.0, 20);
+        meme_loss.linked_order_ids.push("order-1".to_string());
+        let pending = idea("SOL", IdeaDirection::Long, (90.0, 100.0), vec![120.0], 80.0);
+
+        let stats = idea_statistics(&[meme_win, meme_loss, pending], None);
+        assert_eq!(stats.total_ideas, 3);
+        assert_eq!(stats.open_ideas, 1);
+        assert_eq!(stats.traded_ideas, 1);
+        assert_eq!(stats.overall.graded, 2);
+        assert_eq!(stats.overall.hit_rate, 50.0);
+        assert_eq!(stats.overall.average_r, 0.25);
+
+        let meme = &stats.by_category[0];
+        assert_eq!(meme.category, "Meme");
+        assert_eq!(meme.graded, 2);
+        let layer1 = &stats.by_category[1];
+        assert_eq!(layer1.category, "Layer 1");
+        assert_eq!(layer1.graded, 0);
+    }
+}
@@ -1,8 +1,11 @@
 pub mod analytics;
+pub mod attachments;
 pub mod commands;
 pub mod database;
+pub mod ideas;
 pub mod types;
 
+pub use attachments::*;
 pub use commands::*;
 pub use database::{JournalDatabase, SharedJournalDatabase};
 pub use types::*;
@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::portfolio::TokenAnnotationSummary;
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct JournalEntry {
     pub id: String,
@@ -19,6 +21,12 @@ pub struct JournalEntry {
     pub attachments: Vec<String>,
     pub created_at: i64,
     pub updated_at: i64,
+    /// Mint the entry is about, if any.
+    #[serde(default)]
+    pub token_mint: Option<String>,
+    /// Latest note and tags for `token_mint`, attached when the entry is read.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_annotation: Option<TokenAnnotationSummary>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
@@ -44,6 +52,21 @@ pub struct EmotionTracking {
     pub discipline_score: f32,
 }
 
+impl Default for EmotionTracking {
+    fn default() -> Self {
+        Self {
+            primary_emotion: Emotion::Neutral,
+            intensity: 0.5,
+            secondary_emotions: vec![],
+            stress_level: 0.5,
+            clarity_level: 0.5,
+            fomo_level: 0.0,
+            revenge_trading: false,
+            discipline_score: 0.5,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum Emotion {
@@ -70,6 +93,18 @@ pub struct MarketConditions {
     pub notes: String,
 }
 
+impl Default for MarketConditions {
+    fn default() -> Self {
+        Self {
+            trend: MarketTrend::Neutral,
+            volatility: Volatility::Medium,
+            volume: VolumeLevel::Medium,
+            news_sentiment: 0.0,
+            notes: String::new(),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum MarketTrend {
@@ -127,6 +162,8 @@ pub struct DateRange {
     pub end: i64,
 }
 
+/// Aggregates only: entry notes and attachments never appear in a report, so
+/// it can be shared without redaction.
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct WeeklyReport {
     pub id: String,
@@ -146,6 +183,19 @@ pub struct WeeklyReport {
     pub psychological_insights: PsychologicalInsights,
     pub recommendations: Vec<String>,
     pub created_at: i64,
+    /// Set when the report was generated for a wallet.
+    #[serde(default)]
+    pub fee_summary: Option<WeeklyFeeSummary>,
+}
+
+/// What the wallet paid in fees over the report's week, from the fee audit.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct WeeklyFeeSummary {
+    pub wallet: String,
+    pub total_fees_usd: f64,
+    pub trades: usize,
+    pub fee_per_trade_usd: Option<f64>,
+    pub fees_pct_of_pnl: Option<f64>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -269,3 +319,136 @@ pub struct EmotionUsage {
     pub count: usize,
     pub percentage: f32,
 }
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum IdeaDirection {
+    Long,
+    Short,
+}
+
+impl IdeaDirection {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IdeaDirection::Long => "long",
+            IdeaDirection::Short => "short",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "long" => Some(IdeaDirection::Long),
+            "short" => Some(IdeaDirection::Short),
+            _ => None,
+        }
+    }
+}
+
+/// `Pending` until price enters the entry zone. A `TargetHit` idea stays
+/// open for its further targets; every other state after `Triggered` is final.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum IdeaStatus {
+    Pending,
+    Triggered,
+    TargetHit,
+    Invalidated,
+    Expired,
+}
+
+impl IdeaStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IdeaStatus::Pending => "pending",
+            IdeaStatus::Triggered => "triggered",
+            IdeaStatus::TargetHit => "target_hit",
+            IdeaStatus::Invalidated => "invalidated",
+            IdeaStatus::Expired => "expired",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "pending" => Some(IdeaStatus::Pending),
+            "triggered" => Some(IdeaStatus::Triggered),
+            "target_hit" => Some(IdeaStatus::TargetHit),
+            "invalidated" => Some(IdeaStatus::Invalidated),
+            "expired" => Some(IdeaStatus::Expired),
+            _ => None,
+        }
+    }
+}
+
+/// A hypothesis about a token with its plan, graded against the price
+/// stream whether or not it was traded.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TradeIdea {
+    pub id: String,
+    pub token_symbol: String,
+    #[serde(default)]
+    pub token_mint: Option<String>,
+    pub direction: IdeaDirection,
+    pub thesis: String,
+    pub entry_low: f64,
+    pub entry_high: f64,
+    /// Ordered nearest first.
+    pub targets: Vec<f64>,
+    pub invalidation: f64,
+    /// End of the time horizon.
+    pub expires_at: i64,
+    pub status: IdeaStatus,
+    pub triggered_at: Option<i64>,
+    /// Price when the entry zone was reached.
+    pub entry_price: Option<f64>,
+    pub targets_hit: usize,
+    pub closed_at: Option<i64>,
+    pub exit_price: Option<f64>,
+    /// Hypothetical result in units of the planned risk, set once closed.
+    pub r_multiple: Option<f64>,
+    pub last_price: Option<f64>,
+    /// Orders placed on the idea; the same ids journal entries carry as
+    /// `trade_id`.
+    pub linked_order_ids: Vec<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct NewTradeIdea {
+    pub token_symbol: String,
+    #[serde(default)]
+    pub token_mint: Option<String>,
+    pub direction: IdeaDirection,
+    pub thesis: String,
+    pub entry_low: f64,
+    pub entry_high: f64,
+    pub targets: Vec<f64>,
+    pub invalidation: f64,
+    pub horizon_hours: i64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct IdeaCategoryStats {
+    pub category: String,
+    pub ideas: usize,
+    /// Ideas that triggered and have closed.
+    pub graded: usize,
+    /// Share of graded ideas that reached at least one target, in percent.
+    pub hit_rate: f64,
+    pub average_r: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct IdeaStatistics {
+    /// Days covered, or `None` for all ideas.
+    pub window_days: Option<i64>,
+    pub total_ideas: usize,
+    pub open_ideas: usize,
+    pub traded_ideas: usize,
+    pub triggered: usize,
+    pub target_hit: usize,
+    pub invalidated: usize,
+    pub expired: usize,
+    pub overall: IdeaCategoryStats,
+    pub by_category: Vec<IdeaCategoryStats>,
+}
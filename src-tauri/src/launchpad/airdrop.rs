@@ -50,10 +50,10 @@ impl AirdropManager {
         let mut airdrops = self.airdrops.write();
         let airdrop = airdrops
             .get_mut(airdrop_id)
-            .ok_or_else(|| AppError::NotFound("Airdrop not found".to_string()))?;
+            .ok_or_else(|| AppError::not_found("Airdrop not found".to_string()))?;
 
         if airdrop.status != AirdropStatus::Pending {
-            return Err(AppError::Validation(
+            return Err(AppError::validation(
                 "Only pending airdrops can be activated".to_string(),
             ));
         }
@@ -70,15 +70,15 @@ impl AirdropManager {
         let mut airdrops = self.airdrops.write();
         let airdrop = airdrops
             .get_mut(airdrop_id)
-            .ok_or_else(|| AppError::NotFound("Airdrop not found".to_string()))?;
+            .ok_or_else(|| AppError::not_found("Airdrop not found".to_string()))?;
 
         if airdrop.status != AirdropStatus::Active {
-            return Err(AppError::Validation("Airdrop is not active".to_string()));
+            return Err(AppError::validation("Airdrop is not active".to_string()));
         }
 
         if let Some(end_date) = airdrop.end_date {
             if Utc::now() > end_date {
-                return Err(AppError::Validation("Airdrop has ended".to_string()));
+                return Err(AppError::validation("Airdrop has ended".to_string()));
             }
         }
 
@@ -86,10 +86,10 @@ impl AirdropManager {
             .recipients
             .iter_mut()
             .find(|r| r.address == recipient_address)
-            .ok_or_else(|| AppError::NotFound("Recipient not found".to_string()))?;
+            .ok_or_else(|| AppError::not_found("Recipient not found".to_string()))?;
 
         if recipient.claimed {
-            return Err(AppError::Validation("Already claimed".to_string()));
+            return Err(AppError::validation("Already claimed".to_string()));
         }
 
         recipient.claimed = true;
@@ -102,7 +102,7 @@ impl AirdropManager {
         let mut airdrops = self.airdrops.write();
         let airdrop = airdrops
             .get_mut(airdrop_id)
-            .ok_or_else(|| AppError::NotFound("Airdrop not found".to_string()))?;
+            .ok_or_else(|| AppError::not_found("Airdrop not found".to_string()))?;
 
         airdrop.status = AirdropStatus::Cancelled;
         Ok(airdrop.clone())
@@ -113,7 +113,7 @@ impl AirdropManager {
             .read()
             .get(airdrop_id)
             .cloned()
-            .ok_or_else(|| AppError::NotFound("Airdrop not found".to_string()))
+            .ok_or_else(|| AppError::not_found("Airdrop not found".to_string()))
     }
 
     pub fn get_airdrops_for_mint(&self, mint: &str) -> Vec<AirdropConfig> {
@@ -143,7 +143,7 @@ impl AirdropManager {
         let airdrops = self.airdrops.read();
         let airdrop = airdrops
             .get(airdrop_id)
-            .ok_or_else(|| AppError::NotFound("Airdrop not found".to_string()))?;
+            .ok_or_else(|| AppError::not_found("Airdrop not found".to_string()))?;
 
         let claimed_count = airdrop.recipients.iter().filter(|r| r.claimed).count() as u32;
         let claimed_amount: u64 = airdrop
@@ -165,14 +165,14 @@ impl AirdropManager {
 
     fn validate_request(&self, request: &CreateAirdropRequest) -> Result<(), AppError> {
         if request.recipients.is_empty() {
-            return Err(AppError::Validation(
+            return Err(AppError::validation(
                 "Recipients list cannot be empty".to_string(),
             ));
         }
 
         for recipient in &request.recipients {
             if recipient.amount == 0 {
-                return Err(AppError::Validation(
+                return Err(AppError::validation(
                     "Recipient amount must be greater than 0".to_string(),
                 ));
             }
@@ -180,7 +180,7 @@ impl AirdropManager {
 
         if let Some(end_date) = request.end_date {
             if end_date <= request.start_date {
-                return Err(AppError::Validation(
+                return Err(AppError::validation(
                     "End date must be after start date".to_string(),
                 ));
             }
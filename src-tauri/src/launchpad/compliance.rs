@@ -57,7 +57,7 @@ impl ComplianceChecker {
 
         perform_audit(token_mint, audit_metadata)
             .await
-            .map_err(|e| AppError::Generic(e))
+            .map_err(|e| AppError::internal(e))
     }
 
     pub fn check_vesting_compliance(
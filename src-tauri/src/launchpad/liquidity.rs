@@ -36,22 +36,22 @@ impl LiquidityLocker {
     ) -> Result<LiquidityLockConfig, AppError> {
         // Validate addresses
         let _mint_pubkey = Pubkey::from_str(&request.token_mint)
-            .map_err(|e| AppError::Generic(format!("Invalid token mint: {}", e)))?;
+            .map_err(|e| AppError::internal(format!("Invalid token mint: {}", e)))?;
         let _pool_pubkey = Pubkey::from_str(&request.pool_address)
-            .map_err(|e| AppError::Generic(format!("Invalid pool address: {}", e)))?;
+            .map_err(|e| AppError::internal(format!("Invalid pool address: {}", e)))?;
         let _beneficiary_pubkey = Pubkey::from_str(&request.beneficiary)
-            .map_err(|e| AppError::Generic(format!("Invalid beneficiary address: {}", e)))?;
+            .map_err(|e| AppError::internal(format!("Invalid beneficiary address: {}", e)))?;
 
         // Validate amounts
         if request.amount == 0 {
-            return Err(AppError::Validation(
+            return Err(AppError::validation(
                 "Lock amount must be greater than 0".to_string(),
             ));
         }
 
         if request.duration_seconds < 86400 {
             // At least 1 day
-            return Err(AppError::Validation(
+            return Err(AppError::validation(
                 "Lock duration must be at least 1 day".to_string(),
             ));
         }
@@ -60,12 +60,13 @@ impl LiquidityLocker {
         let keystore: tauri::State<Keystore> = app.try_state::<Keystore>().unwrap();
         let _authority_secret = keystore
             .retrieve_secret("wallet_keypair")
-            .map_err(|e| AppError::Generic(format!("Failed to retrieve keypair: {}", e)))?;
+            .map_err(|e| AppError::internal(format!("Failed to retrieve keypair: {}", e)))?;
 
         let lock_id = Uuid::new_v4().to_string();
         let unlock_date = Utc::now() + Duration::seconds(request.duration_seconds as i64);
 
         let lock_config = LiquidityLockConfig {
+            token_mint: Some(request.token_mint.clone()),
             pool_address: Some(request.pool_address.clone()),
             lock_amount: request.amount,
             lock_duration_seconds: request.duration_seconds,
@@ -93,11 +94,11 @@ impl LiquidityLocker {
         let lock = locks
             .iter_mut()
             .find(|l| l.lock_id.as_deref() == Some(lock_id))
-            .ok_or_else(|| AppError::NotFound("Lock not found".to_string()))?;
+            .ok_or_else(|| AppError::not_found("Lock not found".to_string()))?;
 
         // Check if unlock date has passed
         if Utc::now() < lock.unlock_date {
-            return Err(AppError::Validation(
+            return Err(AppError::validation(
                 "Lock period has not expired yet".to_string(),
             ));
         }
@@ -106,7 +107,7 @@ impl LiquidityLocker {
         let keystore: tauri::State<Keystore> = app.try_state::<Keystore>().unwrap();
         let _beneficiary_secret = keystore
             .retrieve_secret("wallet_keypair")
-            .map_err(|e| AppError::Generic(format!("Failed to retrieve keypair: {}", e)))?;
+            .map_err(|e| AppError::internal(format!("Failed to retrieve keypair: {}", e)))?;
 
         lock.status = LockStatus::Unlocked;
 
@@ -122,17 +123,17 @@ impl LiquidityLocker {
         let lock = locks
             .iter_mut()
             .find(|l| l.lock_id.as_deref() == Some(lock_id))
-            .ok_or_else(|| AppError::NotFound("Lock not found".to_string()))?;
+            .ok_or_else(|| AppError::not_found("Lock not found".to_string()))?;
 
         if !lock.is_revocable {
-            return Err(AppError::Validation("Lock is not revocable".to_string()));
+            return Err(AppError::validation("Lock is not revocable".to_string()));
         }
 
         // Get authority keypair from keystore
         let keystore: tauri::State<Keystore> = app.try_state::<Keystore>().unwrap();
         let _authority_secret = keystore
             .retrieve_secret("wallet_keypair")
-            .map_err(|e| AppError::Generic(format!("Failed to retrieve keypair: {}", e)))?;
+            .map_err(|e| AppError::internal(format!("Failed to retrieve keypair: {}", e)))?;
 
         lock.status = LockStatus::Revoked;
 
@@ -149,7 +150,7 @@ impl LiquidityLocker {
             .iter()
             .find(|l| l.lock_id.as_deref() == Some(lock_id))
             .cloned()
-            .ok_or_else(|| AppError::NotFound("Lock not found".to_string()))
+            .ok_or_else(|| AppError::not_found("Lock not found".to_string()))
     }
 
     pub fn get_all_locks(&self) -> Vec<LiquidityLockConfig> {
@@ -31,11 +31,11 @@ impl LaunchpadKeyManager {
         let keystore: tauri::State<Keystore> = app.try_state::<Keystore>().unwrap();
         keystore
             .store_secret(&format!("{}{}", LAUNCH_KEY_PREFIX, key_id), &key)
-            .map_err(|e| AppError::Generic(format!("Failed to store key: {}", e)))?;
+            .map_err(|e| AppError::internal(format!("Failed to store key: {}", e)))?;
 
         self.cache
             .lock()
-            .map_err(|_| AppError::Generic("Failed to access key cache".to_string()))?
+            .map_err(|_| AppError::internal("Failed to access key cache".to_string()))?
             .insert(key_id.clone(), Zeroizing::new(key));
 
         Ok(KeyDescriptor {
@@ -55,7 +55,7 @@ impl LaunchpadKeyManager {
         let keystore: tauri::State<Keystore> = app.try_state::<Keystore>().unwrap();
         let secret = keystore
             .retrieve_secret(&format!("{}{}", LAUNCH_KEY_PREFIX, key_id))
-            .map_err(|e| AppError::Generic(format!("Failed to retrieve key: {}", e)))?;
+            .map_err(|e| AppError::internal(format!("Failed to retrieve key: {}", e)))?;
 
         Ok(secret)
     }
@@ -64,7 +64,7 @@ impl LaunchpadKeyManager {
         let keystore: tauri::State<Keystore> = app.state::<Keystore>();
         keystore
             .remove_secret(&format!("{}{}", LAUNCH_KEY_PREFIX, key_id))
-            .map_err(|e| AppError::Generic(format!("Failed to remove key: {}", e)))?;
+            .map_err(|e| AppError::internal(format!("Failed to remove key: {}", e)))?;
 
         if let Ok(mut cache) = self.cache.lock() {
             cache.remove(key_id);
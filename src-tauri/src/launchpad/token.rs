@@ -30,7 +30,7 @@ impl TokenManager {
         let keystore: tauri::State<Keystore> = app.try_state::<Keystore>().unwrap();
         let creator_secret = keystore
             .retrieve_secret("wallet_keypair")
-            .map_err(|e| AppError::Generic(format!("Failed to retrieve keypair: {}", e)))?;
+            .map_err(|e| AppError::internal(format!("Failed to retrieve keypair: {}", e)))?;
 
         // In production, this would create actual SPL token
         // For now, we simulate the creation
@@ -82,7 +82,7 @@ impl TokenManager {
     pub async fn get_token_info(&self, mint_address: &str) -> Result<TokenInfo, AppError> {
         // In production, fetch real token info from Solana
         let pubkey = Pubkey::from_str(mint_address)
-            .map_err(|e| AppError::Generic(format!("Invalid mint address: {}", e)))?;
+            .map_err(|e| AppError::internal(format!("Invalid mint address: {}", e)))?;
 
         // Mock token info
         Ok(TokenInfo {
@@ -104,15 +104,15 @@ impl TokenManager {
     ) -> Result<String, AppError> {
         // Validate addresses
         let _mint_pubkey = Pubkey::from_str(mint_address)
-            .map_err(|e| AppError::Generic(format!("Invalid mint address: {}", e)))?;
+            .map_err(|e| AppError::internal(format!("Invalid mint address: {}", e)))?;
         let _dest_pubkey = Pubkey::from_str(destination)
-            .map_err(|e| AppError::Generic(format!("Invalid destination address: {}", e)))?;
+            .map_err(|e| AppError::internal(format!("Invalid destination address: {}", e)))?;
 
         // Get authority keypair from keystore
         let keystore: tauri::State<Keystore> = app.try_state::<Keystore>().unwrap();
         let _authority_secret = keystore
             .retrieve_secret("mint_authority")
-            .map_err(|e| AppError::Generic(format!("Failed to retrieve mint authority: {}", e)))?;
+            .map_err(|e| AppError::internal(format!("Failed to retrieve mint authority: {}", e)))?;
 
         // In production, execute actual mint transaction
         let signature = Self::generate_mock_signature();
@@ -127,13 +127,13 @@ impl TokenManager {
     ) -> Result<String, AppError> {
         // Validate address
         let _mint_pubkey = Pubkey::from_str(mint_address)
-            .map_err(|e| AppError::Generic(format!("Invalid mint address: {}", e)))?;
+            .map_err(|e| AppError::internal(format!("Invalid mint address: {}", e)))?;
 
         // Get authority keypair from keystore
         let keystore: tauri::State<Keystore> = app.state::<Keystore>();
         let _authority_secret = keystore
             .retrieve_secret("mint_authority")
-            .map_err(|e| AppError::Generic(format!("Failed to retrieve mint authority: {}", e)))?;
+            .map_err(|e| AppError::internal(format!("Failed to retrieve mint authority: {}", e)))?;
 
         // In production, execute actual revoke transaction
         let signature = Self::generate_mock_signature();
@@ -143,23 +143,23 @@ impl TokenManager {
 
     fn validate_token_request(&self, request: &CreateTokenRequest) -> Result<(), AppError> {
         if request.name.is_empty() || request.name.len() > 32 {
-            return Err(AppError::Generic(
+            return Err(AppError::internal(
                 "Token name must be between 1 and 32 characters".to_string(),
             ));
         }
 
         if request.symbol.is_empty() || request.symbol.len() > 10 {
-            return Err(AppError::Generic(
+            return Err(AppError::internal(
                 "Token symbol must be between 1 and 10 characters".to_string(),
             ));
         }
 
         if request.decimals > 9 {
-            return Err(AppError::Generic("Decimals cannot exceed 9".to_string()));
+            return Err(AppError::internal("Decimals cannot exceed 9".to_string()));
         }
 
         if request.total_supply == 0 {
-            return Err(AppError::Generic(
+            return Err(AppError::internal(
                 "Total supply must be greater than 0".to_string(),
             ));
         }
@@ -37,6 +37,8 @@ pub enum LaunchStatus {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LiquidityLockConfig {
+    #[serde(default)]
+    pub token_mint: Option<String>,
     pub pool_address: Option<String>,
     pub lock_amount: u64,
     pub lock_duration_seconds: u64,
@@ -50,7 +50,7 @@ impl VestingManager {
         amount: u64,
     ) -> Result<VestingSchedule, AppError> {
         if amount == 0 {
-            return Err(AppError::Validation(
+            return Err(AppError::validation(
                 "Release amount must be greater than 0".to_string(),
             ));
         }
@@ -58,17 +58,17 @@ impl VestingManager {
         let mut schedules = self.schedules.write();
         let schedule = schedules
             .get_mut(schedule_id)
-            .ok_or_else(|| AppError::NotFound("Vesting schedule not found".to_string()))?;
+            .ok_or_else(|| AppError::not_found("Vesting schedule not found".to_string()))?;
 
         if schedule.revoked {
-            return Err(AppError::Validation(
+            return Err(AppError::validation(
                 "Vesting schedule has been revoked".to_string(),
             ));
         }
 
         let releasable = self.releasable_amount(schedule);
         if amount > releasable {
-            return Err(AppError::Validation(
+            return Err(AppError::validation(
                 "Requested amount exceeds releasable tokens".to_string(),
             ));
         }
@@ -82,7 +82,7 @@ impl VestingManager {
         let mut schedules = self.schedules.write();
         let schedule = schedules
             .get_mut(schedule_id)
-            .ok_or_else(|| AppError::NotFound("Vesting schedule not found".to_string()))?;
+            .ok_or_else(|| AppError::not_found("Vesting schedule not found".to_string()))?;
 
         schedule.revoked = true;
 
@@ -94,7 +94,7 @@ impl VestingManager {
             .read()
             .get(schedule_id)
             .cloned()
-            .ok_or_else(|| AppError::NotFound("Vesting schedule not found".to_string()))
+            .ok_or_else(|| AppError::not_found("Vesting schedule not found".to_string()))
     }
 
     pub fn get_schedules_for_mint(&self, mint: &str) -> Vec<VestingSchedule> {
@@ -117,13 +117,13 @@ impl VestingManager {
 
     fn validate_request(&self, request: &CreateVestingRequest) -> Result<(), AppError> {
         if request.total_amount == 0 {
-            return Err(AppError::Validation(
+            return Err(AppError::validation(
                 "Total vesting amount must be greater than 0".to_string(),
             ));
         }
 
         if request.vesting_duration_seconds == 0 {
-            return Err(AppError::Validation(
+            return Err(AppError::validation(
                 "Vesting duration must be greater than 0".to_string(),
             ));
         }
@@ -131,21 +131,21 @@ impl VestingManager {
         if request.cliff_duration_seconds.is_some()
             && request.cliff_duration_seconds.unwrap() >= request.vesting_duration_seconds
         {
-            return Err(AppError::Validation(
+            return Err(AppError::validation(
                 "Cliff duration must be less than vesting duration".to_string(),
             ));
         }
 
         if let Some(stages) = &request.stages {
             if request.vesting_type != VestingType::Staged {
-                return Err(AppError::Validation(
+                return Err(AppError::validation(
                     "Stages provided but vesting type is not staged".to_string(),
                 ));
             }
 
             let total_percentage: u32 = stages.iter().map(|s| s.percentage as u32).sum();
             if total_percentage != 100 {
-                return Err(AppError::Validation(
+                return Err(AppError::validation(
                     "Total stage percentages must equal 100".to_string(),
                 ));
             }
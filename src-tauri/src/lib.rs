@@ -20,6 +20,7 @@ mod compiler;
 mod config;
 mod core;
 mod data;
+mod deep_link;
 mod defi;
 mod dev_tools;
 mod diagnostics;
@@ -114,11 +115,16 @@ pub use tray::*;
 pub use ui::theme_engine::*;
 pub use updater::*;
 pub use voice::*;
+pub use wallet::account_cleanup::*;
+pub use wallet::airdrop::*;
+pub use wallet::balances::*;
+pub use wallet::fee_audit::*;
 pub use wallet::hardware_wallet::*;
 pub use wallet::ledger::*;
 pub use wallet::multi_wallet::*;
 pub use wallet::operations::*;
 pub use wallet::phantom::*;
+pub use wallet::swap_reconciliation::*;
 pub use webhooks::*;
 
 pub use wallet::multisig::*;
@@ -130,15 +136,19 @@ use ai_legacy::launch_predictor::{
     get_launch_prediction_history, load_latest_launch_model, predict_launch_success,
     retrain_launch_model, LaunchPredictor, SharedLaunchPredictor,
 };
-use alerts::{AlertManager, SharedAlertManager, SharedSmartAlertManager, SmartAlertManager};
+use alerts::{
+    AlertHistoryManager, AlertManager, SharedAlertHistoryManager, SharedAlertManager,
+    SharedSmartAlertManager, SmartAlertManager,
+};
 use api::{ApiHealthMonitor, SharedApiHealthMonitor};
 use auth::session_manager::SessionManager;
 use auth::two_factor::TwoFactorManager;
 use auto_start::{AutoStartManager, SharedAutoStartManager};
 use bridges::{BridgeManager, SharedBridgeManager};
 use chains::{ChainManager, SharedChainManager};
-use chrono::{Timelike, Utc};
+use chrono::Utc;
 use collab::state::CollabState;
+use config::privacy_mode::{PrivacyMode, SharedPrivacyMode};
 use config::settings_manager::{SettingsManager, SharedSettingsManager};
 use core::cache_manager::{CacheType, SharedCacheManager};
 use data::event_store::{EventStore, SharedEventStore};
@@ -150,7 +160,8 @@ use journal::{JournalDatabase, SharedJournalDatabase};
 use market::{HolderAnalyzer, SharedHolderAnalyzer};
 use mobile::{
     MobileAuthManager, MobileSyncManager, MobileTradeEngine, PushNotificationManager,
-    SharedMobileAuthManager, SharedMobileSyncManager, SharedPushNotificationManager, WidgetManager,
+    SharedMobileAuthManager, SharedMobileSyncManager, SharedPushNotificationManager,
+    SharedWidgetManager, WidgetManager,
 };
 use notifications::router::{NotificationRouter, SharedNotificationRouter};
 use p2p::init_p2p_system;
@@ -169,6 +180,7 @@ use tauri::Manager;
 use tokio::sync::RwLock;
 use tray::{attach_window_listeners, SharedTrayManager, TrayManager};
 use voice::commands::{SharedVoiceState, VoiceState};
+use wallet::balances::{SharedTokenBalanceService, TokenBalanceService};
 use wallet::hardware_wallet::HardwareWalletState;
 use wallet::ledger::LedgerState;
 use wallet::multi_wallet::MultiWalletManager;
@@ -176,6 +188,9 @@ use wallet::multisig::{MultisigDatabase, SharedMultisigDatabase};
 use wallet::operations::WalletOperationsManager;
 use wallet::performance::{PerformanceDatabase, SharedPerformanceDatabase};
 use wallet::phantom::{hydrate_wallet_state, WalletState};
+use wallet::account_cleanup::{AccountCleanup, SharedAccountCleanup};
+use wallet::fee_audit::{FeeAuditor, SharedFeeAuditor};
+use wallet::swap_reconciliation::{SharedSwapReconciler, SwapReconciler};
 use webhooks::{SharedWebhookManager, WebhookManager};
 use updater::{SharedUpdaterState, UpdaterState};
 
@@ -253,9 +268,28 @@ async fn warm_cache_on_startup(
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     startup_log!("run() invoked");
+    {
+        let logger_defaults = logger::LoggerConfig::default();
+        logger::init_tracing(logger_defaults.min_level, &logger_defaults.module_levels);
+    }
+    startup_log!("Tracing subscriber initialized");
     let builder = tauri::Builder::default();
     startup_log!("Base Tauri builder created");
 
+    // Must come first: a second launch hands its arguments, including any
+    // eclipse:// link, to the running instance and exits.
+    #[cfg(desktop)]
+    let builder = builder.plugin(tauri_plugin_single_instance::init(|app, _argv, _cwd| {
+        if let Some(window) = app.get_webview_window("main") {
+            let _ = window.show();
+            let _ = window.unminimize();
+            let _ = window.set_focus();
+        }
+    }));
+
+    let builder = builder.plugin(tauri_plugin_deep_link::init());
+    startup_log!("Deep link plugin registered");
+
     let builder =
         builder.plugin(tauri_plugin_global_shortcut::Builder::new().build());
     startup_log!("Global shortcut plugin registered");
@@ -274,6 +308,48 @@ pub fn run() {
 
     let builder = builder.setup(|app| {
             startup_log!("setup() closure entered");
+            // The environment namespaces the data root, so it is resolved first.
+            let environment = config::initialize_environment(&app.handle()).map_err(|e| {
+                startup_error!("Failed to resolve network environment: {}", e);
+                Box::new(e) as Box<dyn Error>
+            })?;
+            if !environment.is_mainnet() {
+                startup_log!("Running on {}", environment.rpc_url());
+            }
+
+            // Resolved first: every manager below places its files under it.
+            let data_root = config::DataRoot::initialize(&app.handle()).map_err(|e| {
+                startup_error!("Failed to resolve data root: {}", e);
+                Box::new(e) as Box<dyn Error>
+            })?;
+            if let Some(reason) = data_root.status().unavailable_reason {
+                startup_error!("Data directory unavailable, running on the default: {}", reason);
+            }
+            manage_state!(app, data_root, "DataRoot");
+
+            // Managed before any background task starts so each can take a listener.
+            let shutdown_coordinator: errors::SharedShutdownCoordinator =
+                Arc::new(errors::ShutdownCoordinator::new());
+            manage_state!(app, shutdown_coordinator, "ShutdownCoordinator");
+
+            // Periodic maintenance registers here so settings can list and control it.
+            let job_scheduler: core::SharedJobScheduler =
+                Arc::new(core::JobScheduler::new(&app.handle()));
+            manage_state!(app, job_scheduler.clone(), "JobScheduler");
+
+            // Modules queue work here while offline, so it is managed before them.
+            startup_log!("Initializing outbox");
+            let outbox = core::Outbox::new(&app.handle()).map_err(|e| {
+                startup_error!("Failed to initialize outbox: {}", e);
+                Box::new(e) as Box<dyn Error>
+            })?;
+            let outbox: core::SharedOutbox = Arc::new(outbox);
+            manage_state!(app, outbox.clone(), "Outbox");
+            let outbox_app = app.handle().clone();
+            errors::spawn_supervised(&app.handle(), "outbox", move || {
+                core::run_outbox(outbox_app.clone())
+            });
+
             if let Err(e) = hydrate_wallet_state(&app.handle()) {
                 startup_error!("Failed to hydrate wallet state: {}", e);
             }
@@ -286,8 +362,7 @@ pub fn run() {
             startup_log!("Keystore initialized");
 
             let app_data_dir = app
-                .path()
-                .app_data_dir()
+                .app_data_root()
                 .map_err(|e| Box::new(e) as Box<dyn Error>)?;
             std::fs::create_dir_all(&app_data_dir)
                 .map_err(|e| Box::new(e) as Box<dyn Error>)?;
@@ -370,6 +445,28 @@ pub fn run() {
             startup_log!("P2P system initialized");
             manage_state!(app, p2p_db.clone(), "P2PDatabase");
 
+            let arbitration_app = app.handle().clone();
+            let arbitration_db = p2p_db.clone();
+            errors::spawn_supervised(&app.handle(), "p2p_arbitration_timer", move || {
+                p2p::arbitration::run_arbitration_timer(
+                    arbitration_app.clone(),
+                    arbitration_db.clone(),
+                )
+            });
+
+            let p2p_oracle: SharedP2PPriceOracle = Arc::new(RwLock::new(P2PPriceOracle::new()));
+            let oracle_feed = p2p_oracle.clone();
+            let oracle_db = p2p_db.clone();
+            let oracle_ws = ws_manager.clone();
+            errors::spawn_supervised(&app.handle(), "p2p_oracle_feed", move || {
+                p2p::oracle::run_p2p_oracle_feed(
+                    oracle_feed.clone(),
+                    oracle_db.clone(),
+                    oracle_ws.clone(),
+                )
+            });
+            manage_state!(app, p2p_oracle, "SharedP2PPriceOracle");
+
             // Initialize academy engine
             startup_log!("Initializing academy engine");
             let academy_engine = tauri::async_runtime::block_on(async {
@@ -388,6 +485,20 @@ pub fn run() {
                 Arc::new(RwLock::new(academy_engine));
             manage_state!(app, shared_academy_engine.clone(), "SharedAcademyEngine");
 
+            let challenge_app = app.handle().clone();
+            let challenge_academy = shared_academy_engine.clone();
+            errors::spawn_supervised(&app.handle(), "academy_paper_challenges", move || {
+                academy::paper_challenge::run_paper_challenge_evaluator(
+                    challenge_app.clone(),
+                    challenge_academy.clone(),
+                )
+            });
+
+            let season_academy = shared_academy_engine.clone();
+            errors::spawn_supervised(&app.handle(), "academy_season_rollover", move || {
+                academy::seasons::run_season_rollover(season_academy.clone())
+            });
+
             // Initialize API config manager
             let api_config_manager = api_config::ApiConfigManager::new();
             startup_log!("API config manager created");
@@ -418,10 +529,45 @@ pub fn run() {
             manage_state!(app, ws_manager, "WebSocketManager");
             manage_state!(app, activity_logger, "ActivityLogger");
             manage_state!(app, api_config_manager, "ApiConfigManager");
+            api::install_http_key_source(&app.handle());
             manage_state!(app, api_health_state.clone(), "ApiHealthMonitor");
 
+            let token_balance_service: SharedTokenBalanceService = Arc::new(
+                TokenBalanceService::new(&app.handle()).map_err(|e| {
+                    startup_error!("Failed to initialize token balance service: {}", e);
+                    Box::new(e) as Box<dyn Error>
+                })?,
+            );
+            token_balance_service.start_listener();
+            manage_state!(app, token_balance_service, "TokenBalanceService");
+
+            let swap_reconciler: SharedSwapReconciler =
+                Arc::new(SwapReconciler::new(&app.handle()));
+            manage_state!(app, swap_reconciler.clone(), "SwapReconciler");
+            let reconcile_app = app.handle().clone();
+            errors::spawn_supervised(&app.handle(), "swap_history_reconciliation", move || {
+                wallet::swap_reconciliation::run_swap_reconciliation(
+                    reconcile_app.clone(),
+                    swap_reconciler.clone(),
+                )
+            });
+
+            let fee_auditor: SharedFeeAuditor = Arc::new(FeeAuditor::new(&app.handle()));
+            manage_state!(app, fee_auditor, "FeeAuditor");
+
+            let account_cleanup: SharedAccountCleanup =
+                Arc::new(AccountCleanup::new(&app.handle()));
+            manage_state!(app, account_cleanup, "AccountCleanup");
+
+            let rotation_app = app.handle().clone();
+            errors::spawn_supervised(&app.handle(), "key_rotation_supervisor", move || {
+                api_config::run_key_rotation_supervisor(rotation_app.clone())
+            });
+
             startup_log!("Creating chain manager");
-            let chain_manager: SharedChainManager = Arc::new(RwLock::new(ChainManager::new()));
+            let mut chain_manager = ChainManager::new();
+            chain_manager.apply_environment(&environment);
+            let chain_manager: SharedChainManager = Arc::new(RwLock::new(chain_manager));
             manage_state!(app, chain_manager.clone(), "ChainManager");
 
             startup_log!("Creating bridge manager");
@@ -444,11 +590,16 @@ pub fn run() {
                 Box::new(e) as Box<dyn Error>
             })?;
             startup_log!("Settings manager initialized");
+            let privacy_settings = settings_manager.get_all_settings().data_privacy.privacy_mode;
             let settings_state: SharedSettingsManager = Arc::new(RwLock::new(settings_manager));
             manage_state!(app, settings_state.clone(), "SettingsManager");
 
+            let privacy_mode: SharedPrivacyMode =
+                Arc::new(std::sync::RwLock::new(PrivacyMode::new(privacy_settings)));
+            manage_state!(app, privacy_mode, "PrivacyMode");
+
             // Initialize launchpad state
-            let rpc_url = "https://api.mainnet-beta.solana.com".to_string();
+            let rpc_url = config::default_rpc_url();
             startup_log!("Creating launchpad state");
             let launchpad_state = launchpad::commands::create_launchpad_state(rpc_url);
             manage_state!(app, launchpad_state, "LaunchpadState");
@@ -456,39 +607,116 @@ pub fn run() {
             // Initialize collaborative rooms state
             startup_log!("Initializing collaborative rooms state");
             let collab_websocket = collab::websocket::CollabWebSocketManager::new(app.handle().clone());
-            let collab_state = CollabState::new(collab_websocket);
+            let collab_history = tauri::async_runtime::block_on(
+                collab::history::MessageHistoryStore::new(app_data_dir.join("collab_messages.db")),
+            )
+            .map_err(|e| {
+                startup_error!("Failed to initialize collab message history: {}", e);
+                Box::new(std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))
+                    as Box<dyn Error>
+            })?;
+            let collab_state = CollabState::new(collab_websocket, collab_history);
+            collab::orders::attach_order_listener(collab_state.clone());
+            let scoring_app = app.handle().clone();
+            let scoring_state = collab_state.clone();
+            errors::spawn_supervised(&app.handle(), "collab_competition_scoring", move || {
+                collab::competition::run_competition_scoring(
+                    scoring_app.clone(),
+                    scoring_state.clone(),
+                )
+            });
             manage_state!(app, collab_state, "CollabState");
 
-            startup_log!("Spawning activity log cleanup task");
-            tauri::async_runtime::spawn(async move {
-                use tokio::time::{sleep, Duration};
-
-                if let Err(err) = cleanup_logger.cleanup_old_logs(None).await {
-                    startup_error!("Failed to run initial activity log cleanup: {}", err);
-                }
-
-                loop {
-                    sleep(Duration::from_secs(24 * 60 * 60)).await;
-                    if let Err(err) = cleanup_logger.cleanup_old_logs(None).await {
-                        startup_error!("Failed to run scheduled activity log cleanup: {}", err);
+            startup_log!("Scheduling activity log cleanup");
+            if let Err(e) = job_scheduler.register(
+                "activity_log_cleanup",
+                core::JobSchedule::every_from_start(std::time::Duration::from_secs(24 * 60 * 60)),
+                std::time::Duration::from_secs(10 * 60),
+                move || {
+                    let cleanup_logger = cleanup_logger.clone();
+                    async move {
+                        cleanup_logger
+                            .cleanup_old_logs(None)
+                            .await
+                            .map(|_| ())
+                            .map_err(|e| e.to_string())
                     }
-                }
-            });
+                },
+            ) {
+                startup_error!("Failed to schedule activity log cleanup: {}", e);
+            }
 
             startup_log!("Registering trading states");
+            let order_event_app = app.handle().clone();
+            errors::spawn_supervised(&app.handle(), "order_event_forwarder", move || {
+                trading::events::forward_order_events(order_event_app.clone())
+            });
             trading::register_trading_state(&app.handle());
             trading::register_paper_trading_state(&app.handle());
             trading::register_auto_trading_state(&app);
             trading::register_optimizer_state(&app);
+            match app.app_data_root() {
+                Ok(data_dir) => match tauri::async_runtime::block_on(trading::BacktestStore::new(
+                    data_dir.join(trading::BACKTEST_DB_FILE),
+                )) {
+                    Ok(store) => {
+                        let store: trading::SharedBacktestStore = Arc::new(store);
+                        manage_state!(app, store, "BacktestStore");
+                    }
+                    Err(e) => startup_error!("Failed to initialize backtest store: {}", e),
+                },
+                Err(e) => startup_error!("Failed to resolve backtest database path: {}", e),
+            }
             startup_log!("Trading states registered");
 
             // Initialize safety engine
             let default_policy = trading::safety::policy::SafetyPolicy::default();
-            let safety_engine = trading::SafetyEngine::new(default_policy, 30);
+            let mut safety_engine = trading::SafetyEngine::new(default_policy, 30);
             startup_log!("Safety engine created");
+            match app.app_data_root() {
+                Ok(data_dir) => {
+                    let safety_db_path = data_dir.join("safety.db");
+                    let attached = tauri::async_runtime::block_on(async {
+                        let store = trading::safety::cooldown::CooldownStore::new(safety_db_path)
+                            .await
+                            .map_err(|e| e.to_string())?;
+                        safety_engine.attach_store(store).await
+                    });
+                    if let Err(e) = attached {
+                        startup_error!("Failed to restore safety counters: {}", e);
+                    }
+                }
+                Err(e) => startup_error!("Failed to resolve safety database path: {}", e),
+            }
             let safety_state: trading::SharedSafetyEngine = Arc::new(RwLock::new(safety_engine));
             manage_state!(app, safety_state.clone(), "SafetyEngine");
 
+            match app.app_data_root() {
+                Ok(data_dir) => {
+                    match tauri::async_runtime::block_on(trading::safety::InsuranceLedger::new(
+                        data_dir.join("insurance.db"),
+                    )) {
+                        Ok(ledger) => {
+                            let ledger: trading::safety::SharedInsuranceLedger = Arc::new(ledger);
+                            manage_state!(app, ledger.clone(), "InsuranceLedger");
+                            let monitor_app = app.handle().clone();
+                            errors::spawn_supervised(
+                                &app.handle(),
+                                "insurance_policy_monitor",
+                                move || {
+                                    trading::safety::coverage::monitor_insurance_policies(
+                                        monitor_app.clone(),
+                                        ledger.clone(),
+                                    )
+                                },
+                            );
+                        }
+                        Err(e) => startup_error!("Failed to initialize insurance ledger: {}", e),
+                    }
+                }
+                Err(e) => startup_error!("Failed to resolve insurance database path: {}", e),
+            }
+
             // Initialize wallet monitor
             let monitor_handle = app.handle().clone();
             startup_log!("Spawning wallet monitor task");
@@ -500,8 +728,7 @@ pub fn run() {
 
             // Initialize multisig database
             let mut multisig_db_path = app
-                .path()
-                .app_data_dir()
+                .app_data_root()
                 .map_err(|e| Box::new(e) as Box<dyn Error>)?;
 
             std::fs::create_dir_all(&multisig_db_path)
@@ -527,8 +754,7 @@ pub fn run() {
 
             // Initialize performance database
             let mut performance_db_path = app
-                .path()
-                .app_data_dir()
+                .app_data_root()
                 .map_err(|e| Box::new(e) as Box<dyn Error>)?;
 
             std::fs::create_dir_all(&performance_db_path)
@@ -551,8 +777,7 @@ pub fn run() {
 
             // Initialize journal database
             let mut journal_db_path = app
-                .path()
-                .app_data_dir()
+                .app_data_root()
                 .map_err(|e| Box::new(e) as Box<dyn Error>)?;
 
             journal_db_path.push("journal.db");
@@ -567,6 +792,20 @@ pub fn run() {
 
             let journal_state: SharedJournalDatabase = Arc::new(RwLock::new(journal_db));
             manage_state!(app, journal_state.clone(), "JournalDatabase");
+            let journal_checkpoint = journal_state.clone();
+            errors::register_flush(&app.handle(), "journal_db", move || {
+                let journal = journal_checkpoint.clone();
+                async move { journal.read().await.checkpoint().await.map_err(|e| e.to_string()) }
+            });
+
+            let journal_attachments = journal::JournalAttachmentStore::new(&app.handle())
+                .map_err(|e| {
+                    startup_error!("Failed to load journal attachments: {}", e);
+                    Box::new(e) as Box<dyn Error>
+                })?;
+            let journal_attachments_state: journal::SharedJournalAttachmentStore =
+                Arc::new(parking_lot::RwLock::new(journal_attachments));
+            manage_state!(app, journal_attachments_state, "JournalAttachmentStore");
 
             // Initialize backup service and scheduler
             startup_log!("Initializing backup service");
@@ -595,11 +834,30 @@ pub fn run() {
                 if let Err(err) = trading::init_copy_trading(&automation_handle).await {
                     startup_error!("Failed to initialize copy trading: {}", err);
                 }
+                match wallet::performance::backfill_bot_history(&automation_handle).await {
+                    Ok(report) if report.inserted > 0 => {
+                        startup_log!(
+                            "Back-filled {} bot trades into performance history",
+                            report.inserted
+                        );
+                    }
+                    Ok(_) => {}
+                    Err(err) => startup_error!("Failed to back-fill bot performance history: {}", err),
+                }
             });
 
             let portfolio_data = portfolio::PortfolioDataState::new();
             let rebalancer_state = portfolio::RebalancerState::default();
-            let tax_lots_state = portfolio::TaxLotsState::default();
+            let mut tax_lots_state = portfolio::TaxLotsState::default();
+
+            let mut exchange_imports = portfolio::ExchangeImportManager::new(&app.handle())
+                .map_err(|e| {
+                    startup_error!("Failed to load exchange imports: {}", e);
+                    Box::new(e) as Box<dyn Error>
+                })?;
+            exchange_imports.replay_into(&mut tax_lots_state);
+            let exchange_imports_state: portfolio::SharedExchangeImportManager =
+                Arc::new(parking_lot::RwLock::new(exchange_imports));
 
             startup_log!("Registering portfolio state containers");
             manage_state!(
@@ -613,8 +871,27 @@ pub fn run() {
                 "RebalancerState"
             );
             manage_state!(app, std::sync::Mutex::new(tax_lots_state), "TaxLotsState");
+            manage_state!(app, exchange_imports_state, "ExchangeImportManager");
             manage_state!(app, tax_engine.clone(), "TaxEngine");
 
+            // Initialize scheduled reports
+            startup_log!("Initializing reports manager");
+            let reports_manager =
+                tauri::async_runtime::block_on(portfolio::ReportsManager::new(&app.handle()))
+                    .map_err(|e| {
+                        startup_error!("Failed to initialize reports manager: {}", e);
+                        Box::new(std::io::Error::new(std::io::ErrorKind::Other, e))
+                            as Box<dyn Error>
+                    })?;
+            let reports_state: portfolio::SharedReportsManager =
+                Arc::new(RwLock::new(reports_manager));
+            manage_state!(app, reports_state, "ReportsManager");
+
+            let report_scheduler_app = app.handle().clone();
+            errors::spawn_supervised(&app.handle(), "report_scheduler", move || {
+                portfolio::run_report_scheduler(report_scheduler_app.clone())
+            });
+
             // Initialize new coins scanner
             startup_log!("Initializing new coins scanner");
             let new_coins_scanner = tauri::async_runtime::block_on(async {
@@ -632,13 +909,17 @@ pub fn run() {
 
             // Start background scanning task
             let scanner_for_loop = scanner_state.clone();
-            market::start_new_coins_scanner(scanner_for_loop);
+            market::start_new_coins_scanner(&app.handle(), scanner_for_loop);
 
             startup_log!("Creating top coins cache");
             let top_coins_cache: market::SharedTopCoinsCache =
                 Arc::new(RwLock::new(market::TopCoinsCache::new()));
             manage_state!(app, top_coins_cache.clone(), "TopCoinsCache");
 
+            let orderbook_service: market::SharedOrderBookService =
+                Arc::new(market::OrderBookService::new(&app.handle()));
+            manage_state!(app, orderbook_service, "OrderBookService");
+
             // Initialize watchlist manager
             startup_log!("Initializing watchlist manager");
             let watchlist_manager = tauri::async_runtime::block_on(async {
@@ -653,6 +934,15 @@ pub fn run() {
             let watchlist_state: SharedWatchlistManager = Arc::new(RwLock::new(watchlist_manager));
             manage_state!(app, watchlist_state.clone(), "WatchlistManager");
 
+            let token_annotations = portfolio::TokenAnnotationStore::new(&app.handle())
+                .map_err(|e| {
+                    startup_error!("Failed to load token annotations: {}", e);
+                    Box::new(e) as Box<dyn Error>
+                })?;
+            let token_annotations_state: portfolio::SharedTokenAnnotationStore =
+                Arc::new(parking_lot::RwLock::new(token_annotations));
+            manage_state!(app, token_annotations_state, "TokenAnnotationStore");
+
             let token_flow_state = token_flow::commands::create_token_flow_state();
             manage_state!(app, token_flow_state.clone(), "TokenFlowState");
 
@@ -669,6 +959,24 @@ pub fn run() {
             let alert_state: SharedAlertManager = Arc::new(RwLock::new(alert_manager));
             manage_state!(app, alert_state.clone(), "AlertManager");
 
+            startup_log!("Initializing alert history");
+            let alert_history = tauri::async_runtime::block_on(async {
+                AlertHistoryManager::new(&app.handle()).await
+            })
+            .map_err(|e| {
+                startup_error!("Failed to initialize alert history: {}", e);
+                Box::new(e) as Box<dyn Error>
+            })?;
+            let alert_history_state: SharedAlertHistoryManager =
+                Arc::new(RwLock::new(alert_history));
+            manage_state!(app, alert_history_state.clone(), "AlertHistoryManager");
+
+            // Sample prices after alert triggers to track their outcomes
+            startup_log!("Spawning alert outcome sampler");
+            errors::spawn_supervised(&app.handle(), "alert_outcome_sampler", move || {
+                alerts::run_alert_outcome_sampler(alert_history_state.clone())
+            });
+
             startup_log!("Initializing smart alert manager");
             let smart_alert_manager = tauri::async_runtime::block_on(async {
                 SmartAlertManager::new(&app.handle()).await
@@ -685,16 +993,69 @@ pub fn run() {
 
             // Start alert cooldown reset task
             let alert_reset_state = alert_state.clone();
-            startup_log!("Spawning alert cooldown reset task");
-            tauri::async_runtime::spawn(async move {
-                use tokio::time::{sleep, Duration};
-                loop {
-                    sleep(Duration::from_secs(60)).await; // Check every minute
-                    let mgr = alert_reset_state.read().await;
-                    if let Err(err) = mgr.reset_cooldowns().await {
-                        startup_error!("Failed to reset alert cooldowns: {}", err);
+            startup_log!("Scheduling alert cooldown reset");
+            if let Err(e) = job_scheduler.register(
+                "alert_cooldown_reset",
+                core::JobSchedule::every(std::time::Duration::from_secs(60)),
+                std::time::Duration::from_secs(30),
+                move || {
+                    let alert_reset_state = alert_reset_state.clone();
+                    async move {
+                        let mgr = alert_reset_state.read().await;
+                        mgr.reset_cooldowns().await.map_err(|e| e.to_string())?;
+                        mgr.clear_expired_silences()
+                            .await
+                            .map(|_| ())
+                            .map_err(|e| e.to_string())
                     }
-                }
+                },
+            ) {
+                startup_error!("Failed to schedule alert cooldown reset: {}", e);
+            }
+
+            // Evaluate alerts against the live price stream
+            let alert_feed_state = alert_state.clone();
+            let alert_feed_ws = app.state::<WebSocketManager>().inner().clone();
+            let alert_feed_journal = journal_state.clone();
+            startup_log!("Spawning alert price feed");
+            errors::spawn_supervised(&app.handle(), "alert_price_feed", move || {
+                alerts::run_alert_price_feed(
+                    alert_feed_state.clone(),
+                    alert_feed_ws.clone(),
+                    alert_feed_journal.clone(),
+                )
+            });
+
+            // Watch the stream health of tokens with armed orders
+            startup_log!("Loading order protection policy");
+            let order_protection = trading::OrderProtection::new(&app.handle()).map_err(|e| {
+                startup_error!("Failed to load order protection policy: {}", e);
+                Box::new(e) as Box<dyn Error>
+            })?;
+            let shared_order_protection: trading::SharedOrderProtection =
+                Arc::new(order_protection);
+            manage_state!(app, shared_order_protection.clone(), "OrderProtection");
+            let protection_app = app.handle().clone();
+            let protection_ws = app.state::<WebSocketManager>().inner().clone();
+            errors::spawn_supervised(&app.handle(), "order_protection", move || {
+                trading::run_order_protection(
+                    protection_app.clone(),
+                    protection_ws.clone(),
+                    shared_order_protection.clone(),
+                )
+            });
+
+            // Bracket filled buys with stop-loss/take-profit orders
+            startup_log!("Loading auto bracket policy");
+            let auto_brackets = trading::AutoBrackets::new(&app.handle()).map_err(|e| {
+                startup_error!("Failed to load auto bracket policy: {}", e);
+                Box::new(e) as Box<dyn Error>
+            })?;
+            let shared_auto_brackets: trading::SharedAutoBrackets = Arc::new(auto_brackets);
+            manage_state!(app, shared_auto_brackets.clone(), "AutoBrackets");
+            let brackets_app = app.handle().clone();
+            errors::spawn_supervised(&app.handle(), "auto_brackets", move || {
+                trading::run_auto_brackets(brackets_app.clone(), shared_auto_brackets.clone())
             });
 
             // Initialize notification router
@@ -711,18 +1072,58 @@ pub fn run() {
             let notification_state: SharedNotificationRouter =
                 Arc::new(RwLock::new(notification_router));
             manage_state!(app, notification_state.clone(), "NotificationRouter");
+            notifications::router::register_outbox(&app.handle(), &outbox);
 
             // Initialize indicator manager
             let app_data_dir = app
-                .path()
-                .app_data_dir()
+                .app_data_root()
                 .map_err(|e| Box::new(e) as Box<dyn Error>)?;
 
+            // Persisted cluster subscriptions and their trigger history
+            startup_log!("Initializing cluster trigger store");
+            let cluster_trigger_store = tauri::async_runtime::block_on(async {
+                let store = token_flow::ClusterTriggerStore::new(
+                    app_data_dir.join(token_flow::CLUSTER_TRIGGER_DB_FILE),
+                )
+                .await?;
+                token_flow_state.write().await.subscriptions = store.load_subscriptions().await?;
+                Ok::<_, sqlx::Error>(store)
+            })
+            .map_err(|e| {
+                startup_error!("Failed to initialize cluster trigger store: {}", e);
+                Box::new(e) as Box<dyn Error>
+            })?;
+            manage_state!(app, cluster_trigger_store.clone(), "ClusterTriggerStore");
+
+            let trigger_app = app.handle().clone();
+            let trigger_flows = token_flow_state.clone();
+            startup_log!("Spawning cluster trigger monitor");
+            errors::spawn_supervised(&app.handle(), "cluster_trigger_monitor", move || {
+                token_flow::run_cluster_trigger_monitor(
+                    trigger_app.clone(),
+                    trigger_flows.clone(),
+                    cluster_trigger_store.clone(),
+                )
+            });
+
             startup_log!("Initializing indicator manager");
             let indicator_manager = IndicatorManager::new(app_data_dir.clone());
             let indicator_state: SharedIndicatorManager = Arc::new(RwLock::new(indicator_manager));
             manage_state!(app, indicator_state.clone(), "IndicatorManager");
 
+            // Evaluate indicator alerts on candle close, with or without a chart open
+            let indicator_monitor_app = app.handle().clone();
+            let indicator_monitor_state = indicator_state.clone();
+            let indicator_monitor_ws = app.state::<WebSocketManager>().inner().clone();
+            startup_log!("Spawning indicator alert monitor");
+            errors::spawn_supervised(&app.handle(), "indicator_alert_monitor", move || {
+                indicators::run_indicator_alert_monitor(
+                    indicator_monitor_app.clone(),
+                    indicator_monitor_state.clone(),
+                    indicator_monitor_ws.clone(),
+                )
+            });
+
             // Initialize drawing manager
             startup_log!("Initializing drawing manager");
             let drawing_manager = DrawingManager::new(app_data_dir.clone());
@@ -741,12 +1142,21 @@ pub fn run() {
 
             let webhook_state: SharedWebhookManager = Arc::new(RwLock::new(webhook_manager));
             manage_state!(app, webhook_state.clone(), "WebhookManager");
+            let webhook_fill_app = app.handle().clone();
+            errors::spawn_supervised(&app.handle(), "webhook_order_fills", move || {
+                webhooks::events::forward_order_fills(webhook_fill_app.clone())
+            });
 
             // Initialize cache manager
             startup_log!("Initializing cache manager");
             let cache_manager = core::cache_manager::CacheManager::new(100, 1000);
             let shared_cache_manager = Arc::new(RwLock::new(cache_manager));
             manage_state!(app, shared_cache_manager.clone(), "CacheManager");
+            let cache_to_persist = shared_cache_manager.clone();
+            errors::register_flush(&app.handle(), "cache", move || {
+                let cache = cache_to_persist.clone();
+                async move { cache.read().await.persist_all().await.map(|_| ()) }
+            });
 
             // Start background cache warming
             let app_handle = app.handle().clone();
@@ -783,8 +1193,7 @@ pub fn run() {
 
             // Initialize social analysis service
             let mut social_data_dir = app
-                .path()
-                .app_data_dir()
+                .app_data_root()
                 .map_err(|e| Box::new(e) as Box<dyn Error>)?;
             social_data_dir.push("social");
             std::fs::create_dir_all(&social_data_dir)
@@ -827,10 +1236,18 @@ pub fn run() {
                 Arc::new(RwLock::new(anomaly_detector));
             manage_state!(app, anomaly_state.clone(), "AnomalyDetector");
 
+            if let Err(e) = anomalies::schedule_social_spike_checks(
+                &job_scheduler,
+                app.handle().clone(),
+                analysis_state,
+                anomaly_state,
+            ) {
+                startup_error!("Failed to schedule social spike checks: {}", e);
+            }
+
             // Initialize event store
             let mut event_store_path = app
-                .path()
-                .app_data_dir()
+                .app_data_root()
                 .map_err(|e| Box::new(e) as Box<dyn Error>)?;
 
             event_store_path.push("events.db");
@@ -845,11 +1262,15 @@ pub fn run() {
 
             let shared_event_store: SharedEventStore = Arc::new(RwLock::new(event_store));
             manage_state!(app, shared_event_store.clone(), "EventStore");
+            let event_store_checkpoint = shared_event_store.clone();
+            errors::register_flush(&app.handle(), "event_store", move || {
+                let store = event_store_checkpoint.clone();
+                async move { store.read().await.checkpoint().await.map_err(|e| e.to_string()) }
+            });
 
             // Initialize compression manager
             let mut compression_db_path = app
-                .path()
-                .app_data_dir()
+                .app_data_root()
                 .map_err(|e| Box::new(e) as Box<dyn Error>)?;
 
             compression_db_path.push("events.db");
@@ -863,6 +1284,11 @@ pub fn run() {
                     })?;
             startup_log!("Compression manager initialized");
 
+            let compression_config =
+                tauri::async_runtime::block_on(compression_manager.get_config());
+            tauri::async_runtime::block_on(shared_event_store.read())
+                .set_warm_query_row_limit(compression_config.warm_query_row_limit);
+
             let shared_compression_manager: SharedCompressionManager =
                 Arc::new(RwLock::new(compression_manager));
             manage_state!(app, shared_compression_manager.clone(), "CompressionManager");
@@ -881,11 +1307,68 @@ pub fn run() {
                 Arc::new(RwLock::new(holder_analyzer));
             manage_state!(app, shared_holder_analyzer.clone(), "HolderAnalyzer");
 
+            // Token unlock calendar feeds the risk model and unlock alerts
+            startup_log!("Loading token unlock calendar");
+            let unlock_calendar = market::TokenUnlockCalendar::new(&app.handle()).map_err(|e| {
+                startup_error!("Failed to load token unlock calendar: {}", e);
+                Box::new(e) as Box<dyn Error>
+            })?;
+            let shared_unlock_calendar: market::SharedTokenUnlockCalendar =
+                Arc::new(unlock_calendar);
+            manage_state!(app, shared_unlock_calendar.clone(), "TokenUnlockCalendar");
+
+            if let Err(e) = market::schedule_unlock_alerts(
+                &job_scheduler,
+                app.handle().clone(),
+                shared_unlock_calendar,
+            ) {
+                startup_error!("Failed to schedule token unlock alerts: {}", e);
+            }
+
+            // Liquidity lock monitor feeds safety reports, the risk model and unlock alerts
+            startup_log!("Loading liquidity lock monitor");
+            let lock_monitor = market::LiquidityLockMonitor::new(&app.handle()).map_err(|e| {
+                startup_error!("Failed to load liquidity lock monitor: {}", e);
+                Box::new(e) as Box<dyn Error>
+            })?;
+            let shared_lock_monitor: market::SharedLiquidityLockMonitor = Arc::new(lock_monitor);
+            manage_state!(app, shared_lock_monitor.clone(), "LiquidityLockMonitor");
+
+            if let Err(e) = market::schedule_liquidity_lock_alerts(
+                &job_scheduler,
+                app.handle().clone(),
+                shared_lock_monitor,
+            ) {
+                startup_error!("Failed to schedule liquidity lock alerts: {}", e);
+            }
+
+            // Stablecoin depeg monitor raises tier alerts and feeds trade blocks to the safety engine
+            startup_log!("Loading stablecoin depeg monitor");
+            let depeg_monitor = market::StablecoinDepegMonitor::new(&app.handle()).map_err(|e| {
+                startup_error!("Failed to load stablecoin depeg monitor: {}", e);
+                Box::new(e) as Box<dyn Error>
+            })?;
+            let shared_depeg_monitor: market::SharedStablecoinDepegMonitor =
+                Arc::new(depeg_monitor);
+            manage_state!(app, shared_depeg_monitor.clone(), "StablecoinDepegMonitor");
+
+            if let Err(e) = market::schedule_stablecoin_depeg_checks(
+                &job_scheduler,
+                app.handle().clone(),
+                shared_depeg_monitor,
+            ) {
+                startup_error!("Failed to schedule stablecoin depeg checks: {}", e);
+            }
+
             // Initialize stock cache state
             startup_log!("Initializing stock cache state");
             let stock_cache: stocks::SharedStockCache =
                 Arc::new(RwLock::new(stocks::StockCache::default()));
             manage_state!(app, stock_cache.clone(), "StockCache");
+
+            let asset_search_cache: market::SharedAssetSearchCache =
+                Arc::new(RwLock::new(market::AssetSearchCache::new()));
+            manage_state!(app, asset_search_cache, "AssetSearchCache");
             // Initialize risk analyzer
             startup_log!("Initializing risk analyzer");
             let risk_analyzer = tauri::async_runtime::block_on(async {
@@ -954,6 +1437,21 @@ pub fn run() {
             let shared_updater_state: SharedUpdaterState = Arc::new(updater_state);
             manage_state!(app, shared_updater_state.clone(), "UpdaterState");
 
+            // Kiosk state is read while the tray menu is built
+            startup_log!("Loading kiosk mode state");
+            let kiosk_mode = security::kiosk::KioskMode::new(&app.handle()).map_err(|e| {
+                startup_error!("Failed to load kiosk mode state: {}", e);
+                Box::new(std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))
+                    as Box<dyn Error>
+            })?;
+            let shared_kiosk_mode: security::kiosk::SharedKioskMode = Arc::new(kiosk_mode);
+            manage_state!(app, shared_kiosk_mode.clone(), "KioskMode");
+
+            let kiosk_app = app.handle().clone();
+            errors::spawn_supervised(&app.handle(), "kiosk_auto_exit", move || {
+                security::kiosk::run_kiosk_auto_exit(kiosk_app.clone())
+            });
+
             // Initialize system tray manager
             startup_log!("Initializing system tray manager");
             let tray_manager = TrayManager::new();
@@ -961,18 +1459,28 @@ pub fn run() {
             let shared_tray_manager: SharedTrayManager = Arc::new(tray_manager);
             manage_state!(app, shared_tray_manager.clone(), "TrayManager");
 
+            // The badge counts unread critical notifications, so the pruner
+            // starts once the tray exists
+            if let Err(e) = notifications::schedule_notification_history_pruner(
+                &job_scheduler,
+                app.handle().clone(),
+            ) {
+                startup_error!("Failed to schedule notification history pruning: {}", e);
+            }
+
+            let self_test_app = app.handle().clone();
+            errors::spawn_supervised(&app.handle(), "notification_self_test_scheduler", move || {
+                notifications::run_channel_self_test_scheduler(self_test_app.clone())
+            });
+
+            let tray_stats_app = app.handle().clone();
+            errors::spawn_supervised(&app.handle(), "tray_stats_aggregator", move || {
+                tray::run_tray_stats_aggregator(tray_stats_app.clone())
+            });
+
             // Initialize auto-start manager
             startup_log!("Preparing auto-start manager");
-            let app_name = "Eclipse Market Pro";
-            let app_path = std::env::current_exe()
-                .ok()
-                .and_then(|p| p.to_str().map(|s| s.to_string()))
-                .unwrap_or_else(|| "eclipse-market-pro".to_string());
-
-            let auto_start_manager = AutoStartManager::new(app_name, &app_path).map_err(|e| {
-                startup_error!("Failed to initialize auto-start manager: {}", e);
-                Box::new(std::io::Error::new(std::io::ErrorKind::Other, e)) as Box<dyn Error>
-            })?;
+            let auto_start_manager = AutoStartManager::new("Eclipse Market Pro");
             auto_start_manager.initialize(&app.handle());
             let shared_auto_start_manager: SharedAutoStartManager = Arc::new(auto_start_manager);
             manage_state!(app, shared_auto_start_manager.clone(), "AutoStartManager");
@@ -1009,6 +1517,10 @@ pub fn run() {
                 Arc::new(std::sync::Mutex::new(theme_engine));
             manage_state!(app, shared_theme_engine.clone(), "ThemeEngine");
 
+            let dashboard_snapshot_cache: ui::dashboard::SharedDashboardSnapshotCache =
+                Arc::new(ui::dashboard::DashboardSnapshotCache::default());
+            manage_state!(app, dashboard_snapshot_cache, "DashboardSnapshotCache");
+
             // Attach tray window listeners
             if let Some(window) = app.get_webview_window("main") {
                 attach_window_listeners(&window, shared_tray_manager.clone());
@@ -1041,48 +1553,61 @@ pub fn run() {
                 }
             }
 
-            // Start background compression job (runs daily at 3 AM)
-            let compression_job = shared_compression_manager.clone();
-            startup_log!("Spawning compression maintenance task");
-            tauri::async_runtime::spawn(async move {
-                use tokio::time::{sleep, Duration};
-
-                loop {
-                    let now = chrono::Utc::now();
-
-                    // Calculate time until 3 AM
-                    let mut next_run = match now.date_naive().and_hms_opt(3, 0, 0) {
-                        Some(time) => time.and_utc(),
-                        None => {
-                            startup_error!("Failed to create 3 AM schedule time - using fallback");
-                            now + chrono::Duration::hours(1) // Fallback: run in 1 hour
-                        }
-                    };
-
-                    if now.hour() >= 3 {
-                        next_run = next_run + chrono::Duration::days(1);
-                    }
-
-                    let duration_until_next = next_run.signed_duration_since(now);
-                    let sleep_secs = duration_until_next.num_seconds().max(0) as u64;
-
-                    sleep(Duration::from_secs(sleep_secs)).await;
+            // A kiosk left active at shutdown comes back up locked, ahead of
+            // the auto-start behavior above
+            if shared_kiosk_mode.is_active() {
+                security::kiosk::apply_kiosk_presentation(
+                    &app.handle(),
+                    &shared_kiosk_mode.status(),
+                );
+            }
 
-                    // Run compression
-                    let manager = compression_job.read().await;
-                    let config = manager.get_config().await;
+            // Links are queued until the webview reports it is listening
+            let deep_link_queue: deep_link::SharedDeepLinkQueue =
+                Arc::new(deep_link::DeepLinkQueue::default());
+            manage_state!(app, deep_link_queue, "DeepLinkQueue");
+            deep_link::register_deep_link_handler(&app.handle());
 
-                    if config.enabled && config.auto_compress {
-                        if let Err(err) = manager.compress_old_events().await {
-                            startup_error!("Failed to compress old events: {}", err);
-                        }
-                        if let Err(err) = manager.compress_old_trades().await {
-                            startup_error!("Failed to compress old trades: {}", err);
+            // Background compression runs daily at 3 AM UTC
+            let compression_job = shared_compression_manager.clone();
+            startup_log!("Scheduling compression maintenance");
+            if let Err(e) = core::JobSchedule::cron("0 0 3 * * *").and_then(|schedule| {
+                job_scheduler.register(
+                    "compression_maintenance",
+                    schedule,
+                    std::time::Duration::from_secs(60 * 60),
+                    move || {
+                        let compression_job = compression_job.clone();
+                        async move {
+                            let manager = compression_job.read().await;
+                            let config = manager.get_config().await;
+                            if !(config.enabled && config.auto_compress) {
+                                return Ok(());
+                            }
+
+                            // Every step runs even when an earlier one fails
+                            let mut failures = Vec::new();
+                            if let Err(err) = manager.compress_old_events().await {
+                                failures.push(format!("compress old events: {}", err));
+                            }
+                            if let Err(err) = manager.compress_old_trades().await {
+                                failures.push(format!("compress old trades: {}", err));
+                            }
+                            if let Err(err) = manager.archive_cold_events().await {
+                                failures.push(format!("archive cold events: {}", err));
+                            }
+                            manager.cleanup_cache().await;
+                            if failures.is_empty() {
+                                Ok(())
+                            } else {
+                                Err(failures.join("; "))
+                            }
                         }
-                        manager.cleanup_cache().await;
-                    }
-                }
-            });
+                    },
+                )
+            }) {
+                startup_error!("Failed to schedule compression maintenance: {}", e);
+            }
 
             // Initialize prediction market service
             startup_log!("Initializing prediction market service");
@@ -1104,17 +1629,29 @@ pub fn run() {
             manage_state!(app, diagnostics_engine.clone(), "DiagnosticsEngine");
 
             let diagnostics_state = diagnostics_engine.clone();
-            startup_log!("Spawning diagnostics maintenance task");
-            tauri::async_runtime::spawn(async move {
-                use tokio::time::{sleep, Duration};
-                loop {
-                    {
+            let diagnostics_handle = app.handle().clone();
+            startup_log!("Scheduling diagnostics maintenance");
+            if let Err(e) = job_scheduler.register(
+                "diagnostics_maintenance",
+                core::JobSchedule::every_from_start(std::time::Duration::from_secs(60 * 60)),
+                std::time::Duration::from_secs(10 * 60),
+                move || {
+                    let diagnostics_state = diagnostics_state.clone();
+                    let diagnostics_handle = diagnostics_handle.clone();
+                    async move {
+                        diagnostics::tauri_commands::refresh_network_targets(
+                            &diagnostics_handle,
+                            &diagnostics_state,
+                        )
+                        .await;
                         let mut engine = diagnostics_state.write().await;
-                        let _ = engine.run_full_diagnostics().await;
+                        engine.run_full_diagnostics().await;
+                        Ok(())
                     }
-                    sleep(Duration::from_secs(60 * 60)).await;
-                }
-            });
+                },
+            ) {
+                startup_error!("Failed to schedule diagnostics maintenance: {}", e);
+            }
             // Initialize dev tools
             startup_log!("Initializing comprehensive logger");
             let logger = logger::ComprehensiveLogger::new(&app.handle()).map_err(|e| {
@@ -1162,8 +1699,7 @@ pub fn run() {
 
             // Initialize mobile managers
             let mut mobile_data_dir = app
-                .path()
-                .app_data_dir()
+                .app_data_root()
                 .map_err(|e| Box::new(e) as Box<dyn Error>)?;
             mobile_data_dir.push("mobile");
             std::fs::create_dir_all(&mobile_data_dir)
@@ -1181,10 +1717,27 @@ pub fn run() {
             manage_state!(app, mobile_auth_state.clone(), "MobileAuthManager");
 
             startup_log!("Initializing mobile push notification manager");
-            let push_notification_manager = PushNotificationManager::new(1000);
+            let pending_push_path = mobile_data_dir.join("pending_push.json");
+            let mut push_notification_manager = PushNotificationManager::new(1000);
+            if let Err(e) = push_notification_manager.load_pending(&pending_push_path) {
+                startup_error!("Failed to restore held push notifications: {}", e);
+            }
             let push_notification_state: SharedPushNotificationManager =
                 Arc::new(RwLock::new(push_notification_manager));
             manage_state!(app, push_notification_state.clone(), "PushNotificationManager");
+            let pending_push_state = push_notification_state.clone();
+            errors::register_flush(&app.handle(), "pending_push_notifications", move || {
+                let pushes = pending_push_state.clone();
+                let path = pending_push_path.clone();
+                async move {
+                    pushes
+                        .read()
+                        .await
+                        .save_pending(&path)
+                        .map(|_| ())
+                        .map_err(|e| e.to_string())
+                }
+            });
 
             startup_log!("Initializing mobile sync manager");
             let mobile_sync_manager = MobileSyncManager::new();
@@ -1193,16 +1746,34 @@ pub fn run() {
             manage_state!(app, mobile_sync_state.clone(), "MobileSyncManager");
 
             startup_log!("Initializing mobile trade engine");
-            let mobile_trade_engine = MobileTradeEngine::new();
+            let mut mobile_trade_engine = MobileTradeEngine::new(mobile_data_dir.clone());
+            if let Err(e) = tauri::async_runtime::block_on(mobile_trade_engine.load()) {
+                startup_error!("Failed to load mobile trade history: {}", e);
+            }
             let mobile_trade_state: Arc<RwLock<MobileTradeEngine>> =
                 Arc::new(RwLock::new(mobile_trade_engine));
             manage_state!(app, mobile_trade_state.clone(), "MobileTradeEngine");
 
             startup_log!("Initializing widget manager");
-            let widget_manager = WidgetManager::new();
-            let widget_state: Arc<RwLock<WidgetManager>> = Arc::new(RwLock::new(widget_manager));
+            let mut widget_manager = WidgetManager::new(mobile_data_dir.clone());
+            if let Err(e) = tauri::async_runtime::block_on(widget_manager.load()) {
+                startup_error!("Failed to load mobile widgets: {}", e);
+            }
+            let widget_state: SharedWidgetManager = Arc::new(RwLock::new(widget_manager));
             manage_state!(app, widget_state.clone(), "WidgetManager");
 
+            let widget_refresh_state = widget_state.clone();
+            let widget_refresh_ws = app.state::<WebSocketManager>().inner().clone();
+            let widget_refresh_app = app.handle().clone();
+            startup_log!("Spawning mobile widget refresh");
+            errors::spawn_supervised(&app.handle(), "mobile_widget_refresh", move || {
+                mobile::run_widget_refresh(
+                    widget_refresh_app.clone(),
+                    widget_refresh_state.clone(),
+                    widget_refresh_ws.clone(),
+                )
+            });
+
             // Initialize governance manager
             startup_log!("Initializing governance manager");
             let governance_manager = governance::GovernanceManager::new();
@@ -1212,8 +1783,7 @@ pub fn run() {
 
             // Initialize feature flags database
             let mut features_db_path = app
-                .path()
-                .app_data_dir()
+                .app_data_root()
                 .map_err(|e| Box::new(e) as Box<dyn Error>)?;
 
             features_db_path.push("features.db");
@@ -1248,7 +1818,7 @@ pub fn run() {
         });
     startup_log!("Setup closure attached");
 
-    let builder = builder.invoke_handler(tauri::generate_handler![
+    let handler = tauri::generate_handler![
              // Wallet
              phantom_connect,
              phantom_disconnect,
@@ -1274,6 +1844,7 @@ pub fn run() {
             ledger_clear_devices,
             // Multi-Wallet
             multi_wallet_add,
+            multi_wallet_add_watch_only,
             multi_wallet_update,
             multi_wallet_remove,
             multi_wallet_set_active,
@@ -1288,6 +1859,8 @@ pub fn run() {
             multi_wallet_get_aggregated,
             // Wallet Operations
             wallet_get_token_balances,
+            wallet_get_token_balances_batch,
+            wallet_request_airdrop,
             wallet_estimate_fee,
             wallet_send_transaction,
             wallet_generate_qr,
@@ -1301,12 +1874,21 @@ pub fn run() {
             address_book_import,
             swap_history_add_entry,
             swap_history_get_recent,
+            swap_history_reconcile,
+            get_fee_audit,
+            list_reclaimable_accounts,
+            close_token_accounts,
+            submit_token_account_closures,
             wallet_get_bridge_providers,
             // Wallet Performance
             record_trade,
             calculate_wallet_performance,
+            get_performance_by_source,
+            backfill_bot_performance_history,
             get_wallet_performance_data,
             get_performance_score_history,
+            update_performance_score_weights,
+            recompute_score_history,
             get_token_performance_breakdown,
             get_timing_analysis_data,
             get_best_worst_trades_data,
@@ -1351,12 +1933,17 @@ pub fn run() {
             test_api_connection,
             get_api_status,
             rotate_api_key,
+            start_key_rotation,
+            get_key_rotation_status,
+            cancel_key_rotation,
             check_rotation_reminders,
             export_api_keys,
             import_api_keys,
             // API Analytics
             record_api_usage,
             get_api_analytics,
+            get_api_budgets,
+            set_api_budget,
             get_fair_use_status,
             // AI & Sentiment
             assess_risk,
@@ -1372,6 +1959,8 @@ pub fn run() {
             get_token_risk_score,
             get_risk_history,
             get_latest_risk_score,
+            batch_risk_scores,
+            get_portfolio_risk_summary,
             // Social Data
             // TODO: Re-enable when social commands are implemented
             // social_fetch_reddit,
@@ -1411,6 +2000,7 @@ pub fn run() {
             get_coin_price,
             get_price_history,
             search_tokens,
+            market::search_assets,
             get_trending_coins,
             get_coin_sentiment,
             refresh_trending,
@@ -1418,9 +2008,14 @@ pub fn run() {
             get_new_coins,
             get_coin_safety_report,
             scan_for_new_coins,
+            get_scan_progress,
+            get_new_coin_screen,
+            set_new_coin_screen,
             // Top Coins
             get_top_coins,
             refresh_top_coins,
+            // Market Heatmap
+            get_market_heatmap,
             // Portfolio & Analytics
             get_portfolio_metrics,
             get_positions,
@@ -1439,11 +2034,23 @@ pub fn run() {
             generate_tax_report,
             export_tax_report,
             get_tax_loss_harvesting_suggestions,
+            exchange_import_preview,
+            exchange_import_commit,
+            exchange_import_batches,
+            remove_import_batch,
             get_tax_center_summary,
             update_tax_settings,
             export_tax_center_report,
+            export_portfolio_state,
+            get_portfolio_export_schema,
+            reports_configure_schedule,
+            reports_get_schedules,
+            reports_list,
+            reports_get,
+            reports_generate_now,
             calculate_portfolio_analytics,
             get_concentration_alerts,
+            get_portfolio_correlations,
             get_sector_allocation,
             clear_portfolio_cache,
             watchlist_create,
@@ -1456,6 +2063,13 @@ pub fn run() {
             watchlist_reorder_items,
             watchlist_export,
             watchlist_import,
+            watchlist_import_preview,
+            watchlist_import_commit,
+            token_notes_add,
+            token_notes_list,
+            token_tags_set,
+            token_tags_rename,
+            tokens_by_tag,
             // AI Portfolio Advisor
             save_risk_profile,
             get_risk_profile,
@@ -1475,6 +2089,18 @@ pub fn run() {
             alert_test,
             alert_check_triggers,
             alert_reset_cooldowns,
+            alert_snooze,
+            alerts_mute_token,
+            alerts_mute_all,
+            alert_history_list,
+            alert_history_get,
+            alert_history_update,
+            alert_history_delete,
+            alert_history_stats,
+            alert_history_export_csv,
+            alert_get_trigger_history,
+            alert_mark_trigger,
+            alerts_effectiveness_report,
             smart_alert_create_rule,
             smart_alert_update_rule,
             smart_alert_delete_rule,
@@ -1482,6 +2108,11 @@ pub fn run() {
             smart_alert_get_rule,
             smart_alert_dry_run,
             smart_alert_execute,
+            smart_alert_export_pack,
+            smart_alert_preview_pack,
+            smart_alert_import_pack,
+            smart_alert_list_packs,
+            smart_alert_remove_pack,
             // Chat Integrations
             chat_integration_get_settings,
             chat_integration_save_settings,
@@ -1499,6 +2130,17 @@ pub fn run() {
             chat_integration_test_discord,
             chat_integration_get_delivery_logs,
             chat_integration_clear_delivery_logs,
+            notifications_list,
+            notifications_mark_read,
+            notifications_mark_all_read,
+            notifications_clear,
+            notifications_get_retention,
+            notifications_set_retention,
+            notifications_test_all_channels,
+            notifications_get_self_test_reports,
+            notifications_get_self_test_schedule,
+            notifications_set_self_test_schedule,
+            notifications_get_degraded_channels,
             chat_integration_get_rate_limits,
             // Webhooks
             list_webhooks,
@@ -1509,6 +2151,7 @@ pub fn run() {
             trigger_webhook,
             test_webhook,
             list_webhook_delivery_logs,
+            list_webhook_event_types,
             // API Health
             get_api_health_dashboard,
             get_service_health_metrics,
@@ -1539,7 +2182,12 @@ pub fn run() {
             get_order_history,
             get_order,
             acknowledge_order,
+            get_execution_audit,
             update_order_prices,
+            get_order_protection_policy,
+            set_order_protection_policy,
+            get_auto_bracket_policy,
+            set_auto_bracket_policy,
             // Auto Trading Engine
             auto_trading_create_strategy,
             auto_trading_update_strategy,
@@ -1555,6 +2203,13 @@ pub fn run() {
             auto_trading_apply_parameters,
             // Backtesting & Optimization
             backtest_run,
+            backtest_list_runs,
+            backtest_get_run,
+            backtest_update_run,
+            backtest_compare_runs,
+            backtest_delete_runs,
+            backtest_get_retention,
+            backtest_set_retention,
             optimizer_start,
             optimizer_cancel,
             optimizer_get_runs,
@@ -1568,6 +2223,10 @@ pub fn run() {
             get_paper_trade_history,
             get_paper_performance,
             update_paper_position_prices,
+            paper_account_create,
+            paper_account_list,
+            paper_account_delete,
+            paper_accounts_compare,
             // DCA Bots
             dca_init,
             dca_create,
@@ -1590,6 +2249,7 @@ pub fn run() {
             copy_trading_performance,
             copy_trading_process_activity,
             copy_trading_followed_wallets,
+            copy_trading_get_leader_profile,
             // Wallet Monitor
             wallet_monitor_init,
             wallet_monitor_add_wallet,
@@ -1598,10 +2258,15 @@ pub fn run() {
             wallet_monitor_list_wallets,
             wallet_monitor_get_activities,
             wallet_monitor_get_statistics,
+            wallet_monitor_infer_clusters,
+            wallet_monitor_list_cluster_candidates,
+            wallet_monitor_review_cluster_candidates,
+            wallet_monitor_list_clusters,
             // Smart Money & Whale Alerts
             classify_smart_money_wallet,
             get_smart_money_wallets,
             get_smart_money_consensus,
+            get_smart_money_wallet_accuracy,
             get_sentiment_comparison,
             get_alert_configs,
             update_alert_config,
@@ -1615,6 +2280,12 @@ pub fn run() {
             security::activity_log::cleanup_activity_logs,
             security::activity_log::get_activity_retention,
             security::activity_log::set_activity_retention,
+            security::kiosk::kiosk_mode_enable,
+            security::kiosk::kiosk_mode_exit,
+            security::kiosk::kiosk_mode_status,
+            // Deep links
+            deep_link::deep_link_frontend_ready,
+            deep_link::validate_deep_link,
             // Smart Contract Security
             security::audit::scan_contract,
             security::audit::get_cached_audit,
@@ -1645,6 +2316,10 @@ pub fn run() {
             academy::get_course_lessons,
             academy::create_quiz,
             academy::get_quiz,
+            academy::create_question_bank,
+            academy::get_question_bank,
+            academy::update_question_bank,
+            academy::get_question_analytics,
             academy::create_challenge,
             academy::list_challenges,
             academy::create_webinar,
@@ -1652,6 +2327,9 @@ pub fn run() {
             academy::create_mentor,
             academy::list_mentors,
             academy::get_content_stats,
+            academy::academy_export_course,
+            academy::academy_preview_course_import,
+            academy::academy_import_course,
             academy::start_course,
             academy::get_user_progress,
             academy::complete_course,
@@ -1659,15 +2337,22 @@ pub fn run() {
             academy::get_lesson_progress,
             academy::update_lesson_progress,
             academy::complete_lesson,
+            academy::start_quiz_attempt,
             academy::submit_quiz,
             academy::get_quiz_attempts,
+            academy::get_quiz_standing,
             academy::submit_challenge,
             academy::get_challenge_submissions,
+            academy::start_paper_challenge,
+            academy::get_challenge_progress,
             academy::record_webinar_attendance,
             academy::create_mentor_session,
             academy::get_user_mentor_sessions,
             academy::get_user_stats,
             academy::get_leaderboard,
+            academy::get_all_time_leaderboard,
+            academy::create_season,
+            academy::list_seasons,
             academy::create_badge,
             academy::get_badge,
             academy::list_badges,
@@ -1676,14 +2361,30 @@ pub fn run() {
             academy::issue_certificate,
             academy::get_user_certificates,
             academy::verify_certificate,
+            academy::get_certificate_proof,
+            academy::verify_certificate_proof,
+            academy::get_certificate_signing_keys,
+            academy::rotate_certificate_signing_key,
+            academy::prepare_certificate_anchor,
+            academy::submit_certificate_anchor,
             academy::get_user_rewards,
             academy::claim_reward,
             academy::claim_all_rewards,
             academy::get_reward_stats,
             // Performance & Diagnostics
             get_performance_metrics,
+            set_performance_tracing,
             run_performance_test,
             reset_performance_stats,
+            // Background jobs
+            jobs_list,
+            jobs_run_now,
+            jobs_pause,
+            jobs_resume,
+            jobs_get_history,
+            // Offline outbox
+            outbox_list,
+            outbox_discard,
             // Cache Management
             cache_commands::get_cache_statistics,
             cache_commands::clear_cache,
@@ -1701,6 +2402,14 @@ pub fn run() {
             update_anomaly_detection_config,
             get_anomaly_detection_config,
             get_anomaly_statistics,
+            list_anomaly_profiles,
+            save_anomaly_profile,
+            delete_anomaly_profile,
+            assign_anomaly_profile,
+            get_anomaly_profile_assignments,
+            export_anomaly_baselines,
+            import_anomaly_baselines,
+            get_social_spike_history,
             generate_mock_anomaly_data,
             // Event Sourcing & Audit Trail
             data::event_store::get_events_command,
@@ -1716,6 +2425,8 @@ pub fn run() {
             data::compression_commands::get_compression_config,
             data::compression_commands::decompress_data,
             data::compression_commands::get_database_size,
+            data::compression_commands::list_event_archives,
+            data::compression_commands::restore_event_archive,
             // Email Notifications
             email_save_config,
             email_get_config,
@@ -1741,6 +2452,8 @@ pub fn run() {
             twitter_get_tweet_history,
             // Token Flow Intelligence
             token_flow::commands::analyze_token_flows,
+            token_flow::commands::trace_token_flows,
+            token_flow::commands::ingest_token_flows,
             token_flow::commands::export_flow_analysis,
             token_flow::commands::list_cluster_subscriptions,
             token_flow::commands::upsert_cluster_subscription,
@@ -1752,7 +2465,28 @@ pub fn run() {
             market::holders::get_token_metadata,
             market::holders::get_verification_status,
             market::holders::export_holder_data,
+            market::holders::get_holder_cohorts,
             market::holders::export_metadata_snapshot,
+            // Token Unlock Calendar
+            market::token_unlocks::get_unlock_calendar,
+            market::token_unlocks::get_unlock_schedule,
+            market::token_unlocks::set_unlock_override,
+            market::token_unlocks::remove_unlock_override,
+            market::token_unlocks::get_unlock_alert_settings,
+            market::token_unlocks::set_unlock_alert_settings,
+            // Liquidity Lock Monitoring
+            market::liquidity_locks::get_liquidity_lock_status,
+            market::liquidity_locks::get_liquidity_lock_settings,
+            market::liquidity_locks::set_liquidity_lock_settings,
+            // Stablecoin Depeg Monitoring
+            market::stablecoin_depeg::get_stablecoin_status,
+            market::stablecoin_depeg::get_stablecoin_depeg_settings,
+            market::stablecoin_depeg::set_stablecoin_depeg_settings,
+            // Order Book Depth
+            market::get_orderbook_snapshot,
+            market::subscribe_orderbook,
+            market::unsubscribe_orderbook,
+            market::register_orderbook_market,
             // Prediction Markets
             market::get_prediction_markets,
             market::search_prediction_markets,
@@ -1772,11 +2506,21 @@ pub fn run() {
             indicator_create_alert,
             indicator_delete_alert,
             indicator_update_alert,
+            indicator_export_preset,
+            indicator_import_preset,
+            indicator_validate_formula,
+            indicator_evaluate_formula,
+            indicator_evaluate_preset,
+            indicator_evaluate_alerts,
             drawing_list,
             drawing_save,
             drawing_sync,
             drawing_list_templates,
             drawing_save_templates,
+            drawing_changes,
+            drawing_get_history,
+            drawing_restore_version,
+            get_chart_annotations,
             // Chain management
             chain_get_active,
             chain_set_active,
@@ -1828,6 +2572,7 @@ pub fn run() {
             stocks::get_stock_news,
             stocks::get_institutional_holdings,
             stocks::get_insider_activity,
+            stocks::search_stocks,
             stocks::create_stock_alert,
             stocks::get_stock_alerts,
             // DeFi commands
@@ -1848,6 +2593,8 @@ pub fn run() {
             get_defi_portfolio_summary,
             get_defi_risk_metrics,
             get_defi_snapshot,
+            stress_test_positions,
+            get_stress_test_presets,
             get_auto_compound_recommendations,
             configure_auto_compound,
             get_auto_compound_config,
@@ -1860,6 +2607,9 @@ pub fn run() {
             get_update_settings,
             save_update_settings,
             dismiss_update,
+            check_for_updates,
+            download_update,
+            install_update,
             get_rollback_info,
             rollback_update,
             // Windowing & Multi-monitor commands
@@ -1902,11 +2652,21 @@ pub fn run() {
             config::commands::list_settings_profiles,
             config::commands::get_settings_change_history,
             config::commands::get_config_settings_template,
+            config::privacy_mode::privacy_mode_set,
+            config::privacy_mode::privacy_mode_set_wallet,
+            config::privacy_mode::privacy_mode_status,
+            // Data Directory
+            config::data_root::get_data_root_status,
+            config::data_root::migrate_data_directory,
+            config::data_root::retry_data_directory,
+            config::data_root::reset_data_directory,
+            // Network Environment
+            config::environment::get_environment,
+            config::environment::set_environment,
             // System Tray
             get_tray_settings,
             update_tray_settings,
             update_tray_stats,
-            update_tray_badge,
             minimize_to_tray,
             restore_from_tray,
             // Auto-start
@@ -1922,6 +2682,8 @@ pub fn run() {
             historical_compute_counterfactual,
             historical_get_cache_stats,
             historical_clear_old_data,
+            historical_get_store_settings,
+            historical_update_store_settings,
             historical_set_api_key,
             // Voice Interaction
             voice_request_permissions,
@@ -1946,6 +2708,10 @@ pub fn run() {
             voice_pause_speaking,
             voice_resume_speaking,
             voice_get_tts_status,
+            voice_speech_finished,
+            voice_configure_readout,
+            voice_get_readout_config,
+            voice_get_readout_queue,
             voice_get_tts_config,
             voice_update_tts_config,
             voice_get_available_voices,
@@ -1978,11 +2744,18 @@ pub fn run() {
             approve_trade,
             get_safety_policy,
             update_safety_policy,
+            override_trading_hours,
             get_cooldown_status,
             reset_daily_limits,
+            reset_cooldowns,
             get_insurance_quote,
             select_insurance,
             list_insurance_providers,
+            get_active_policies,
+            get_policy,
+            create_claim_draft,
+            list_claim_drafts,
+            export_claim_package,
             // Theme Engine
             theme_get_presets,
             theme_get_settings,
@@ -1992,6 +2765,8 @@ pub fn run() {
             theme_export,
             theme_import,
             theme_get_os_preference,
+            // Dashboard
+            ui::dashboard::get_dashboard_snapshot,
             // Mobile companion commands
             mobile_register_device,
             mobile_create_biometric_challenge,
@@ -2004,11 +2779,23 @@ pub fn run() {
             mobile_queue_notification,
             mobile_get_pending_notifications,
             mobile_dequeue_notification,
+            mobile_set_device_policy,
+            mobile_get_device_policy,
             mobile_sync_data,
+            mobile_sync_delta,
+            mobile_get_sync_conflicts,
+            mobile_resolve_sync_conflict,
             mobile_get_last_sync,
             mobile_get_cached_sync_data,
             mobile_execute_quick_trade,
             mobile_safety_checks,
+            mobile_get_device_safety_policy,
+            mobile_set_device_safety_policy,
+            mobile_get_device_trade_history,
+            mobile_register_widget,
+            mobile_configure_widget,
+            mobile_remove_widget,
+            mobile_list_widgets,
             mobile_get_widget_data,
             mobile_get_all_widgets,
             // Collaborative Rooms
@@ -2020,13 +2807,23 @@ pub fn run() {
             collab::commands::collab_leave_room,
             collab::commands::collab_get_participants,
             collab::commands::collab_update_permissions,
+            collab::commands::collab_set_role,
+            collab::commands::collab_transfer_ownership,
+            collab::commands::collab_get_my_permissions,
+            collab::commands::collab_get_role_audit,
             collab::commands::collab_send_message,
             collab::commands::collab_get_messages,
+            collab::commands::collab_search_messages,
+            collab::commands::collab_edit_message,
+            collab::commands::collab_delete_message,
+            collab::commands::collab_get_message_retention,
+            collab::commands::collab_set_message_retention,
             collab::commands::collab_share_watchlist,
             collab::commands::collab_get_watchlists,
             collab::commands::collab_share_order,
             collab::commands::collab_get_orders,
             collab::commands::collab_update_order,
+            collab::commands::collab_unshare_order,
             collab::commands::collab_share_strategy,
             collab::commands::collab_send_webrtc_signal,
             collab::commands::collab_get_webrtc_signals,
@@ -2034,6 +2831,7 @@ pub fn run() {
             collab::commands::collab_get_room_state,
             collab::commands::collab_set_competition,
             collab::commands::collab_get_competition,
+            collab::commands::collab_join_competition,
             collab::commands::collab_update_leaderboard,
             // Diagnostics & Troubleshooter
             diagnostics::tauri_commands::run_diagnostics,
@@ -2065,6 +2863,7 @@ pub fn run() {
             create_governance_reminder,
             get_governance_summary,
             get_governance_deadlines,
+            simulate_governance_vote,
             prepare_vote_signature,
             verify_vote_signature,
             prepare_vote_transaction,
@@ -2080,24 +2879,43 @@ pub fn run() {
             get_weekly_reports,
             get_behavioral_analytics,
             get_journal_stats,
+            journal_attach_file,
+            journal_attach_chart,
+            journal_get_attachments,
+            journal_remove_attachment,
+            journal_attachment_usage,
+            journal_cleanup_attachments,
+            create_trade_idea,
+            get_trade_ideas,
+            delete_trade_idea,
+            link_trade_idea_order,
+            get_idea_statistics,
             // Dev Tools
             compile_now,
+            start_compiler_watch,
+            stop_compiler_watch,
+            get_compiler_watch_status,
             get_build_status,
             get_compile_errors,
             auto_fix_errors,
             get_fix_stats,
             get_fix_attempts,
             clear_fix_history,
+            revert_auto_fix,
             get_logs,
             clear_logs,
             export_logs,
             log_message,
             get_logger_config,
             set_logger_config,
+            start_log_stream,
+            stop_log_stream,
+            list_log_streams,
             get_error_stats,
             report_crash,
             get_crash_report,
             list_crash_reports,
+            get_breadcrumbs,
             force_gc,
             restart_service,
             get_dev_settings,
@@ -2120,6 +2938,11 @@ pub fn run() {
             get_p2p_dispute,
             submit_dispute_evidence,
             resolve_p2p_dispute,
+            prepare_p2p_escrow_transaction,
+            sign_p2p_escrow_transaction,
+            get_p2p_pending_transaction,
+            get_p2p_escrow_config,
+            set_p2p_escrow_config,
             send_p2p_message,
             get_p2p_messages,
             get_trader_profile,
@@ -2130,12 +2953,22 @@ pub fn run() {
             enable_feature_flag,
             disable_feature_flag,
             is_feature_enabled,
-        ]);
+        ];
+    // Measured outside the kiosk guard, so rejected commands count as errors.
+    let builder = builder.invoke_handler(move |invoke| {
+        monitor::instrument_invoke(invoke, &|invoke| {
+            security::kiosk::guard_invoke(invoke, &handler)
+        })
+    });
 
     startup_log!("Invoke handler attached");
     startup_log!("Launching Tauri application loop");
-    if let Err(e) = builder.run(tauri::generate_context!()) {
-        startup_error!("Failed to run Tauri application: {}", e);
-        std::process::exit(1);
-    }
+    let app = match builder.build(tauri::generate_context!()) {
+        Ok(app) => app,
+        Err(e) => {
+            startup_error!("Failed to run Tauri application: {}", e);
+            std::process::exit(1);
+        }
+    };
+    app.run(|handle, event| errors::handle_run_event(handle, &event));
 }
@@ -0,0 +1,92 @@
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+
+const DEFAULT_PER_SUBSYSTEM: usize = 25;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Breadcrumb {
+    pub timestamp: DateTime<Utc>,
+    pub subsystem: String,
+    pub message: String,
+    pub data: Option<serde_json::Value>,
+}
+
+/// Ring buffer of the last significant actions, kept separately per subsystem so a
+/// chatty subsystem cannot push out the history of a quiet one.
+#[derive(Debug)]
+pub struct BreadcrumbTrail {
+    trails: RwLock<HashMap<String, VecDeque<Breadcrumb>>>,
+    per_subsystem: usize,
+}
+
+impl BreadcrumbTrail {
+    pub fn new(per_subsystem: Option<usize>) -> Self {
+        Self {
+            trails: RwLock::new(HashMap::new()),
+            per_subsystem: per_subsystem.unwrap_or(DEFAULT_PER_SUBSYSTEM).max(1),
+        }
+    }
+
+    pub fn record(&self, subsystem: &str, message: &str, data: Option<serde_json::Value>) {
+        let mut trails = self.trails.write();
+        let trail = trails
+            .entry(subsystem.to_string())
+            .or_insert_with(|| VecDeque::with_capacity(self.per_subsystem));
+        if trail.len() >= self.per_subsystem {
+            trail.pop_front();
+        }
+        trail.push_back(Breadcrumb {
+            timestamp: Utc::now(),
+            subsystem: subsystem.to_string(),
+            message: message.to_string(),
+            data,
+        });
+    }
+
+    /// All breadcrumbs (or one subsystem's) in chronological order.
+    pub fn snapshot(&self, subsystem: Option<&str>) -> Vec<Breadcrumb> {
+        let trails = self.trails.read();
+        let mut crumbs: Vec<Breadcrumb> = match subsystem {
+            Some(name) => trails
+                .get(name)
+                .map(|trail| trail.iter().cloned().collect())
+                .unwrap_or_default(),
+            None => trails.values().flat_map(|t| t.iter().cloned()).collect(),
+        };
+        crumbs.sort_by_key(|crumb| crumb.timestamp);
+        crumbs
+    }
+
+    pub fn clear(&self) {
+        self.trails.write().clear();
+    }
+}
+
+impl Default for BreadcrumbTrail {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_last_entries_per_subsystem() {
+        let trail = BreadcrumbTrail::new(Some(2));
+        trail.record("alerts", "one", None);
+        trail.record("alerts", "two", None);
+        trail.record("alerts", "three", None);
+        trail.record("scanner", "scan", None);
+
+        let alerts = trail.snapshot(Some("alerts"));
+        assert_eq!(alerts.len(), 2);
+        assert_eq!(alerts[0].message, "two");
+        assert_eq!(alerts[1].message, "three");
+        assert_eq!(trail.snapshot(None).len(), 3);
+    }
+}
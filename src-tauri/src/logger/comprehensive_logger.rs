@@ -1,7 +1,12 @@
-use crate::logger::{LogBuffer, LogEntry, LogLevel, SharedLogBuffer};
+use crate::config::DataPaths;
+use crate::logger::{
+    apply_tracing_filter, Breadcrumb, BreadcrumbTrail, LogBuffer, LogEntry, LogLevel,
+    LogStreamFilter, LogStreamHub, LogStreamInfo, SharedLogBuffer,
+};
 use chrono::Utc;
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs::{File, OpenOptions};
 use std::io::Write;
 use std::path::{Path, PathBuf};
@@ -24,6 +29,10 @@ pub struct LoggerConfig {
     pub include_metadata: bool,
     pub max_file_size_mb: u64,
     pub max_files: usize,
+    /// Overrides `min_level` for a category or module path; the longest matching
+    /// prefix wins, so `trading::safety` can be more verbose than `trading`.
+    #[serde(default)]
+    pub module_levels: HashMap<String, LogLevel>,
 }
 
 impl Default for LoggerConfig {
@@ -37,14 +46,37 @@ impl Default for LoggerConfig {
             include_metadata: true,
             max_file_size_mb: 100,
             max_files: 10,
+            module_levels: HashMap::new(),
         }
     }
 }
 
+impl LoggerConfig {
+    /// Level in effect for `category`, falling back to `min_level`.
+    pub fn level_for(&self, category: Option<&str>) -> LogLevel {
+        let Some(category) = category else {
+            return self.min_level;
+        };
+        self.module_levels
+            .iter()
+            .filter(|(module, _)| {
+                category == module.as_str()
+                    || category
+                        .strip_prefix(module.as_str())
+                        .is_some_and(|rest| rest.starts_with("::"))
+            })
+            .max_by_key(|(module, _)| module.len())
+            .map(|(_, level)| *level)
+            .unwrap_or(self.min_level)
+    }
+}
+
 #[derive(Debug)]
 pub struct ComprehensiveLogger {
     config: RwLock<LoggerConfig>,
     buffer: SharedLogBuffer,
+    breadcrumbs: BreadcrumbTrail,
+    streams: LogStreamHub,
     log_dir: PathBuf,
     current_log_file: RwLock<Option<File>>,
     session_id: String,
@@ -54,7 +86,7 @@ pub struct ComprehensiveLogger {
 
 impl ComprehensiveLogger {
     pub fn new(app: &AppHandle) -> Result<Self, std::io::Error> {
-        let mut log_dir = app.path().app_data_dir().map_err(|err| {
+        let mut log_dir = app.app_data_root().map_err(|err| {
             std::io::Error::new(
                 std::io::ErrorKind::NotFound,
                 format!("App data dir not found: {err}"),
@@ -74,6 +106,8 @@ impl ComprehensiveLogger {
         Ok(Self {
             config: RwLock::new(LoggerConfig::default()),
             buffer: Arc::new(LogBuffer::new(Some(5000))),
+            breadcrumbs: BreadcrumbTrail::default(),
+            streams: LogStreamHub::default(),
             log_dir,
             current_log_file: RwLock::new(Some(log_file)),
             session_id,
@@ -91,6 +125,7 @@ impl ComprehensiveLogger {
     }
 
     pub fn set_config(&self, config: LoggerConfig) {
+        apply_tracing_filter(config.min_level, &config.module_levels);
         *self.config.write() = config;
     }
 
@@ -150,7 +185,7 @@ impl ComprehensiveLogger {
     ) {
         let config = self.config.read();
 
-        if level < config.min_level {
+        if level < config.level_for(category) {
             return;
         }
 
@@ -188,6 +223,17 @@ impl ComprehensiveLogger {
             self.buffer.push(entry.clone());
         }
 
+        // Warnings and errors from a known subsystem are always worth a breadcrumb.
+        if level >= LogLevel::Warn && level <= LogLevel::Fatal {
+            if let Some(category) = category {
+                self.breadcrumbs.record(category, message, None);
+            }
+        }
+
+        if !self.streams.is_empty() {
+            self.streams.publish(&entry);
+        }
+
         if config.console_enabled {
             self.log_to_console(&entry, config.colored_output);
         }
@@ -254,6 +300,27 @@ impl ComprehensiveLogger {
         )
     }
 
+    /// Records a significant action for `subsystem`; the trail is attached to crash reports.
+    pub fn add_breadcrumb(&self, subsystem: &str, message: &str, data: Option<serde_json::Value>) {
+        self.breadcrumbs.record(subsystem, message, data);
+    }
+
+    pub fn start_log_stream(&self, app: AppHandle, filter: LogStreamFilter) -> String {
+        self.streams.start(app, filter)
+    }
+
+    pub fn stop_log_stream(&self, stream_id: &str) -> Option<LogStreamInfo> {
+        self.streams.stop(stream_id)
+    }
+
+    pub fn list_log_streams(&self) -> Vec<LogStreamInfo> {
+        self.streams.list()
+    }
+
+    pub fn get_breadcrumbs(&self, subsystem: Option<&str>) -> Vec<Breadcrumb> {
+        self.breadcrumbs.snapshot(subsystem)
+    }
+
     pub fn get_buffer(&self) -> SharedLogBuffer {
         self.buffer.clone()
     }
@@ -303,12 +370,28 @@ impl ComprehensiveLogger {
 
     pub fn export_logs(&self, format: &str) -> Result<String, String> {
         let logs = self.buffer.iter();
+        // Exports carry the filter that produced them so a reader knows what was omitted.
+        let filter = {
+            let config = self.config.read();
+            serde_json::json!({
+                "minLevel": config.min_level,
+                "moduleLevels": config.module_levels,
+            })
+        };
 
         match format {
-            "json" => serde_json::to_string_pretty(&logs)
-                .map_err(|e| format!("Failed to serialize logs: {}", e)),
+            "json" => serde_json::to_string_pretty(&serde_json::json!({
+                "filterConfig": filter,
+                "exportedAt": Utc::now().to_rfc3339(),
+                "logs": logs,
+            }))
+            .map_err(|e| format!("Failed to serialize logs: {}", e)),
             "csv" => {
-                let mut csv = String::from("timestamp,level,message,category,details\n");
+                let mut csv = format!(
+                    "# filter: {}\n",
+                    serde_json::to_string(&filter).unwrap_or_default()
+                );
+                csv.push_str("timestamp,level,message,category,details\n");
                 for log in logs {
                     let details = log
                         .details
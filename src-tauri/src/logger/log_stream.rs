@@ -0,0 +1,156 @@
+use crate::logger::{LogEntry, LogLevel};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+/// Records buffered per stream before new ones are dropped.
+const STREAM_CAPACITY: usize = 256;
+pub const LOG_STREAM_EVENT: &str = "log-stream";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogStreamFilter {
+    pub min_level: Option<LogLevel>,
+    #[serde(default)]
+    pub categories: Vec<String>,
+    pub search: Option<String>,
+}
+
+impl LogStreamFilter {
+    pub fn matches(&self, entry: &LogEntry) -> bool {
+        if let Some(min_level) = self.min_level {
+            if entry.level < min_level {
+                return false;
+            }
+        }
+        if !self.categories.is_empty() {
+            let category = entry.category.as_deref().unwrap_or_default();
+            if !self
+                .categories
+                .iter()
+                .any(|c| category.starts_with(c.as_str()))
+            {
+                return false;
+            }
+        }
+        if let Some(search) = self.search.as_deref().filter(|s| !s.is_empty()) {
+            if !entry
+                .message
+                .to_lowercase()
+                .contains(&search.to_lowercase())
+            {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogStreamEvent {
+    pub stream_id: String,
+    pub entry: LogEntry,
+    /// Records dropped on this stream so far because the UI fell behind.
+    pub dropped: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogStreamInfo {
+    pub stream_id: String,
+    pub filter: LogStreamFilter,
+    pub dropped: u64,
+}
+
+#[derive(Debug)]
+struct LogStream {
+    filter: LogStreamFilter,
+    tx: mpsc::Sender<LogEntry>,
+    dropped: Arc<AtomicU64>,
+}
+
+/// Fans log records out to live UI subscribers. Each stream has a bounded queue;
+/// when it is full the record is counted as dropped instead of buffered.
+#[derive(Debug, Default)]
+pub struct LogStreamHub {
+    streams: RwLock<HashMap<String, LogStream>>,
+}
+
+impl LogStreamHub {
+    pub fn publish(&self, entry: &LogEntry) {
+        let streams = self.streams.read();
+        for stream in streams.values() {
+            if !stream.filter.matches(entry) {
+                continue;
+            }
+            if stream.tx.try_send(entry.clone()).is_err() {
+                stream.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.streams.read().is_empty()
+    }
+
+    pub fn start(&self, app: AppHandle, filter: LogStreamFilter) -> String {
+        let stream_id = Uuid::new_v4().to_string();
+        let (tx, mut rx) = mpsc::channel::<LogEntry>(STREAM_CAPACITY);
+        let dropped = Arc::new(AtomicU64::new(0));
+
+        self.streams.write().insert(
+            stream_id.clone(),
+            LogStream {
+                filter,
+                tx,
+                dropped: dropped.clone(),
+            },
+        );
+
+        let id = stream_id.clone();
+        tauri::async_runtime::spawn(async move {
+            // Ends once `stop` drops the sender.
+            while let Some(entry) = rx.recv().await {
+                let event = LogStreamEvent {
+                    stream_id: id.clone(),
+                    entry,
+                    dropped: dropped.load(Ordering::Relaxed),
+                };
+                if app.emit(LOG_STREAM_EVENT, &event).is_err() {
+                    break;
+                }
+            }
+        });
+
+        stream_id
+    }
+
+    pub fn stop(&self, stream_id: &str) -> Option<LogStreamInfo> {
+        self.streams
+            .write()
+            .remove(stream_id)
+            .map(|stream| LogStreamInfo {
+                stream_id: stream_id.to_string(),
+                filter: stream.filter,
+                dropped: stream.dropped.load(Ordering::Relaxed),
+            })
+    }
+
+    pub fn list(&self) -> Vec<LogStreamInfo> {
+        self.streams
+            .read()
+            .iter()
+            .map(|(id, stream)| LogStreamInfo {
+                stream_id: id.clone(),
+                filter: stream.filter.clone(),
+                dropped: stream.dropped.load(Ordering::Relaxed),
+            })
+            .collect()
+    }
+}
@@ -1,7 +1,13 @@
+pub mod breadcrumbs;
 pub mod comprehensive_logger;
 pub mod log_buffer;
 pub mod log_level;
+pub mod log_stream;
+pub mod tracing_filter;
 
+pub use breadcrumbs::*;
 pub use comprehensive_logger::*;
 pub use log_buffer::*;
 pub use log_level::*;
+pub use log_stream::*;
+pub use tracing_filter::*;
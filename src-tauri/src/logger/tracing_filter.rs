@@ -0,0 +1,89 @@
+use crate::logger::LogLevel;
+use crate::monitor::{is_command_metrics_span, CommandMetricsLayer};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use tracing_subscriber::filter::{filter_fn, LevelFilter, Targets};
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::{fmt, reload, Registry};
+
+const CRATE_TARGET: &str = "app_lib";
+
+static FILTER_HANDLE: OnceLock<reload::Handle<Targets, Registry>> = OnceLock::new();
+
+/// Installs the global tracing subscriber behind a reload handle so module levels
+/// can be changed at runtime. Safe to call more than once; later calls are no-ops.
+/// The levels only filter what is logged; command metrics see their spans at
+/// any level.
+pub fn init_tracing(min_level: LogLevel, module_levels: &HashMap<String, LogLevel>) {
+    if FILTER_HANDLE.get().is_some() {
+        return;
+    }
+
+    let (filter, handle) = reload::Layer::new(build_targets(min_level, module_levels));
+    let installed = tracing_subscriber::registry()
+        .with(fmt::layer().with_filter(filter))
+        .with(CommandMetricsLayer.with_filter(filter_fn(is_command_metrics_span)))
+        .try_init()
+        .is_ok();
+
+    if installed {
+        let _ = FILTER_HANDLE.set(handle);
+    }
+}
+
+/// Swaps the active tracing filter. Returns false when no reloadable subscriber
+/// is installed (another subscriber won the race at startup).
+pub fn apply_tracing_filter(
+    min_level: LogLevel,
+    module_levels: &HashMap<String, LogLevel>,
+) -> bool {
+    match FILTER_HANDLE.get() {
+        Some(handle) => handle
+            .reload(build_targets(min_level, module_levels))
+            .is_ok(),
+        None => false,
+    }
+}
+
+fn build_targets(min_level: LogLevel, module_levels: &HashMap<String, LogLevel>) -> Targets {
+    let mut targets = Targets::new().with_default(to_level_filter(min_level));
+    for (module, level) in module_levels {
+        targets = targets.with_target(qualify_module(module), to_level_filter(*level));
+    }
+    targets
+}
+
+/// `trading::safety` and `app_lib::trading::safety` name the same module.
+fn qualify_module(module: &str) -> String {
+    let module = module.trim().trim_matches(':');
+    if module == CRATE_TARGET || module.starts_with(&format!("{}::", CRATE_TARGET)) {
+        return module.to_string();
+    }
+    let module = module.strip_prefix("crate::").unwrap_or(module);
+    format!("{}::{}", CRATE_TARGET, module)
+}
+
+fn to_level_filter(level: LogLevel) -> LevelFilter {
+    match level {
+        LogLevel::Trace => LevelFilter::TRACE,
+        LogLevel::Debug => LevelFilter::DEBUG,
+        LogLevel::Info | LogLevel::Success | LogLevel::Performance => LevelFilter::INFO,
+        LogLevel::Warn => LevelFilter::WARN,
+        LogLevel::Error | LogLevel::Fatal => LevelFilter::ERROR,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn qualifies_module_paths() {
+        assert_eq!(qualify_module("trading"), "app_lib::trading");
+        assert_eq!(
+            qualify_module("crate::trading::safety"),
+            "app_lib::trading::safety"
+        );
+        assert_eq!(qualify_module("app_lib::websocket"), "app_lib::websocket");
+    }
+}
@@ -0,0 +1,604 @@
+use super::predictions::{PredictionMarket, SharedPredictionMarketService};
+use super::top_coins::{fetch_top_coins, SharedTopCoinsCache, TopCoin};
+use super::TokenSearchResult;
+use crate::stocks::{search_stock_symbols, SharedStockCache, StockSymbolMatch};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+
+const DEFAULT_SEARCH_LIMIT: usize = 20;
+const MAX_SEARCH_LIMIT: usize = 100;
+const SEARCH_CACHE_TTL: Duration = Duration::from_secs(60);
+const SEARCH_CACHE_CAPACITY: usize = 200;
+/// Top coins scanned for token matches on top of the token search itself.
+const TOKEN_UNIVERSE_SIZE: usize = 100;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum AssetClass {
+    Token,
+    Stock,
+    PredictionMarket,
+}
+
+impl AssetClass {
+    pub fn all() -> Vec<AssetClass> {
+        vec![
+            AssetClass::Token,
+            AssetClass::Stock,
+            AssetClass::PredictionMarket,
+        ]
+    }
+}
+
+/// How a result matched the query, best first. Ranking never lets a weaker
+/// kind outrank a stronger one, whatever the popularity.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchKind {
+    ExactSymbol,
+    SymbolPrefix,
+    ExactName,
+    NamePrefix,
+    NameContains,
+    Fuzzy,
+}
+
+impl MatchKind {
+    fn base_score(&self) -> f64 {
+        match self {
+            MatchKind::ExactSymbol => 1000.0,
+            MatchKind::SymbolPrefix => 800.0,
+            MatchKind::ExactName => 600.0,
+            MatchKind::NamePrefix => 400.0,
+            MatchKind::NameContains => 200.0,
+            MatchKind::Fuzzy => 0.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetSearchResult {
+    /// Ticker; prediction markets have none.
+    pub symbol: Option<String>,
+    pub name: String,
+    pub asset_class: AssetClass,
+    /// Mint address, stock ticker or prediction market id.
+    pub identifier: String,
+    pub price: Option<f64>,
+    /// 24h volume or liquidity in USD, whichever the source reports.
+    pub popularity: f64,
+    pub match_kind: MatchKind,
+    pub score: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetSearchWarning {
+    pub asset_class: AssetClass,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetSearchResponse {
+    pub query: String,
+    pub results: Vec<AssetSearchResult>,
+    /// Asset classes whose search failed; their results are missing.
+    pub warnings: Vec<AssetSearchWarning>,
+    pub cached: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct SearchKey {
+    query: String,
+    asset_classes: Vec<AssetClass>,
+    limit: usize,
+}
+
+/// Recent searches. Responses with warnings are not cached so a failing
+/// source is retried on the next keystroke.
+pub struct AssetSearchCache {
+    entries: HashMap<SearchKey, (AssetSearchResponse, Instant)>,
+    ttl: Duration,
+    capacity: usize,
+}
+
+pub type SharedAssetSearchCache = Arc<RwLock<AssetSearchCache>>;
+
+impl AssetSearchCache {
+    pub fn new() -> Self {
+        Self {
+            entries: HashMap::new(),
+            ttl: SEARCH_CACHE_TTL,
+            capacity: SEARCH_CACHE_CAPACITY,
+        }
+    }
+
+    fn get(&self, key: &SearchKey) -> Option<AssetSearchResponse> {
+        self.entries
+            .get(key)
+            .filter(|(_, stored_at)| stored_at.elapsed() < self.ttl)
+            .map(|(response, _)| response.clone())
+    }
+
+    fn insert(&mut self, key: SearchKey, response: AssetSearchResponse) {
+        let ttl = self.ttl;
+        self.entries
+            .retain(|_, (_, stored_at)| stored_at.elapsed() < ttl);
+        if self.entries.len() >= self.capacity {
+            if let Some(oldest) = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (_, stored_at))| *stored_at)
+                .map(|(key, _)| key.clone())
+            {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.entries.insert(key, (response, Instant::now()));
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// Every character of `query` appears in `text` in order; returns the share
+/// of `text` covered so tighter matches score higher.
+fn subsequence_ratio(query: &str, text: &str) -> Option<f64> {
+    let mut chars = text.chars();
+    for wanted in query.chars().filter(|c| !c.is_whitespace()) {
+        chars.find(|c| *c == wanted)?;
+    }
+    let text_len = text.chars().filter(|c| !c.is_whitespace()).count().max(1);
+    Some((query.chars().count() as f64 / text_len as f64).min(1.0))
+}
+
+/// Classifies how `query` (already lowercased and trimmed) matches a result
+/// and returns the match with a 0..1 closeness used to order fuzzy matches.
+pub fn match_quality(query: &str, symbol: Option<&str>, name: &str) -> Option<(MatchKind, f64)> {
+    if query.is_empty() {
+        return None;
+    }
+
+    if let Some(symbol) = symbol.map(str::to_lowercase) {
+        if symbol == query {
+            return Some((MatchKind::ExactSymbol, 1.0));
+        }
+        if symbol.starts_with(query) {
+            return Some((
+                MatchKind::SymbolPrefix,
+                query.len() as f64 / symbol.len() as f64,
+            ));
+        }
+    }
+
+    let name = name.to_lowercase();
+    if name == query {
+        return Some((MatchKind::ExactName, 1.0));
+    }
+    if name.starts_with(query) {
+        return Some((
+            MatchKind::NamePrefix,
+            query.len() as f64 / name.len() as f64,
+        ));
+    }
+    if name
+        .split(|c: char| !c.is_alphanumeric())
+        .any(|word| word.starts_with(query))
+        || name.contains(query)
+    {
+        return Some((
+            MatchKind::NameContains,
+            query.len() as f64 / name.len() as f64,
+        ));
+    }
+
+    // Typos in longer queries only; two characters match almost anything
+    if query.chars().count() < 3 {
+        return None;
+    }
+    let by_symbol = symbol.and_then(|symbol| subsequence_ratio(query, &symbol.to_lowercase()));
+    let by_name = subsequence_ratio(query, &name);
+    by_symbol
+        .into_iter()
+        .chain(by_name)
+        .reduce(f64::max)
+        .map(|closeness| (MatchKind::Fuzzy, closeness))
+}
+
+/// Match quality dominates; closeness and popularity only order results
+/// within the same match kind.
+pub fn rank_score(kind: MatchKind, closeness: f64, popularity: f64) -> f64 {
+    let popularity_bonus = (popularity.max(0.0) + 1.0).log10().min(13.0) * 10.0;
+    kind.base_score() + closeness * 50.0 + popularity_bonus
+}
+
+struct Candidate {
+    symbol: Option<String>,
+    name: String,
+    asset_class: AssetClass,
+    identifier: String,
+    price: Option<f64>,
+    popularity: f64,
+}
+
+impl Candidate {
+    fn rank(self, query: &str) -> Option<AssetSearchResult> {
+        let (match_kind, closeness) = match_quality(query, self.symbol.as_deref(), &self.name)?;
+        Some(AssetSearchResult {
+            score: rank_score(match_kind, closeness, self.popularity),
+            symbol: self.symbol,
+            name: self.name,
+            asset_class: self.asset_class,
+            identifier: self.identifier,
+            price: self.price,
+            popularity: self.popularity,
+            match_kind,
+        })
+    }
+}
+
+fn token_candidates(found: Vec<TokenSearchResult>, top_coins: Vec<TopCoin>) -> Vec<Candidate> {
+    let mut seen = HashSet::new();
+    let mut candidates = Vec::new();
+
+    for coin in top_coins {
+        if seen.insert(coin.address.clone()) {
+            candidates.push(Candidate {
+                symbol: Some(coin.symbol),
+                name: coin.name,
+                asset_class: AssetClass::Token,
+                identifier: coin.address,
+                price: Some(coin.price),
+                popularity: coin.liquidity.unwrap_or(0.0).max(coin.volume_24h),
+            });
+        }
+    }
+    for token in found {
+        if seen.insert(token.address.clone()) {
+            candidates.push(Candidate {
+                symbol: Some(token.symbol),
+                name: token.name,
+                asset_class: AssetClass::Token,
+                identifier: token.address,
+                price: None,
+                popularity: 0.0,
+            });
+        }
+    }
+
+    candidates
+}
+
+fn stock_candidates(matches: Vec<StockSymbolMatch>) -> Vec<Candidate> {
+    matches
+        .into_iter()
+        .map(|stock| Candidate {
+            identifier: stock.symbol.clone(),
+            symbol: Some(stock.symbol),
+            name: stock.name,
+            asset_class: AssetClass::Stock,
+            price: stock.price,
+            popularity: stock
+                .volume
+                .zip(stock.price)
+                .map(|(volume, price)| volume * price)
+                .or(stock.market_cap.map(|cap| cap / 100.0))
+                .unwrap_or(0.0),
+        })
+        .collect()
+}
+
+fn prediction_candidates(markets: Vec<PredictionMarket>) -> Vec<Candidate> {
+    markets
+        .into_iter()
+        .filter(|market| !market.resolved)
+        .map(|market| Candidate {
+            symbol: None,
+            name: market.title,
+            asset_class: AssetClass::PredictionMarket,
+            identifier: market.id,
+            // Probability of the first outcome
+            price: market.outcome_prices.first().copied(),
+            popularity: market.volume_24h.max(market.liquidity),
+        })
+        .collect()
+}
+
+/// Ranks candidates from all classes together, best first, and keeps `limit`.
+fn rank_candidates(
+    query: &str,
+    candidates: Vec<Candidate>,
+    limit: usize,
+) -> Vec<AssetSearchResult> {
+    let mut results: Vec<AssetSearchResult> = candidates
+        .into_iter()
+        .filter_map(|candidate| candidate.rank(query))
+        .collect();
+    results.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    results.truncate(limit);
+    results
+}
+
+async fn search_token_candidates(
+    normalized: &str,
+    top_coins: &SharedTopCoinsCache,
+) -> Result<Vec<Candidate>, String> {
+    let (found, coins) = tokio::join!(
+        super::search_tokens(normalized.to_string()),
+        fetch_top_coins(top_coins, TOKEN_UNIVERSE_SIZE, 0, None)
+    );
+    // Either source alone still gives useful token results
+    match (found, coins) {
+        (Err(search_error), Err(_)) => Err(search_error),
+        (found, coins) => Ok(token_candidates(
+            found.unwrap_or_default(),
+            coins.unwrap_or_default(),
+        )),
+    }
+}
+
+/// Token-only search ranked like the unified search, for callers that need
+/// to map a ticker to a mint.
+pub async fn search_token_assets(
+    query: &str,
+    limit: usize,
+    top_coins: &SharedTopCoinsCache,
+) -> Result<Vec<AssetSearchResult>, String> {
+    let normalized = query.trim().to_lowercase();
+    let candidates = search_token_candidates(&normalized, top_coins).await?;
+    Ok(rank_candidates(&normalized, candidates, limit))
+}
+
+pub async fn search_all_assets(
+    query: &str,
+    asset_classes: &[AssetClass],
+    limit: usize,
+    top_coins: &SharedTopCoinsCache,
+    stock_cache: &SharedStockCache,
+    predictions: &SharedPredictionMarketService,
+) -> AssetSearchResponse {
+    let normalized = query.trim().to_lowercase();
+    let wants = |class: AssetClass| asset_classes.contains(&class);
+
+    let tokens = async {
+        if !wants(AssetClass::Token) {
+            return Ok(Vec::new());
+        }
+        search_token_candidates(&normalized, top_coins).await
+    };
+    let stocks = async {
+        if !wants(AssetClass::Stock) {
+            return Ok(Vec::new());
+        }
+        search_stock_symbols(stock_cache, &normalized)
+            .await
+            .map(stock_candidates)
+    };
+    let markets = async {
+        if !wants(AssetClass::PredictionMarket) {
+            return Ok(Vec::new());
+        }
+        let service = predictions.read().await;
+        service
+            .search_markets(&normalized, false)
+            .await
+            .map(prediction_candidates)
+    };
+
+    let (tokens, stocks, markets) = tokio::join!(tokens, stocks, markets);
+
+    let mut candidates = Vec::new();
+    let mut warnings = Vec::new();
+    for (asset_class, outcome) in [
+        (AssetClass::Token, tokens),
+        (AssetClass::Stock, stocks),
+        (AssetClass::PredictionMarket, markets),
+    ] {
+        match outcome {
+            Ok(found) => candidates.extend(found),
+            Err(message) => warnings.push(AssetSearchWarning {
+                asset_class,
+                message,
+            }),
+        }
+    }
+
+    AssetSearchResponse {
+        query: query.to_string(),
+        results: rank_candidates(&normalized, candidates, limit),
+        warnings,
+        cached: false,
+    }
+}
+
+#[tauri::command]
+pub async fn search_assets(
+    query: String,
+    asset_classes: Option<Vec<AssetClass>>,
+    limit: Option<usize>,
+    cache: tauri::State<'_, SharedAssetSearchCache>,
+    top_coins: tauri::State<'_, SharedTopCoinsCache>,
+    stock_cache: tauri::State<'_, SharedStockCache>,
+    predictions: tauri::State<'_, SharedPredictionMarketService>,
+) -> Result<AssetSearchResponse, String> {
+    let mut asset_classes = asset_classes.unwrap_or_else(AssetClass::all);
+    asset_classes.sort();
+    asset_classes.dedup();
+    let limit = limit
+        .unwrap_or(DEFAULT_SEARCH_LIMIT)
+        .clamp(1, MAX_SEARCH_LIMIT);
+
+    if query.trim().is_empty() {
+        return Ok(AssetSearchResponse {
+            query,
+            results: Vec::new(),
+            warnings: Vec::new(),
+            cached: false,
+        });
+    }
+
+    let key = SearchKey {
+        query: query.trim().to_lowercase(),
+        asset_classes: asset_classes.clone(),
+        limit,
+    };
+    if let Some(mut response) = cache.read().await.get(&key) {
+        response.query = query;
+        response.cached = true;
+        return Ok(response);
+    }
+
+    let response = search_all_assets(
+        &query,
+        &asset_classes,
+        limit,
+        &top_coins,
+        &stock_cache,
+        &predictions,
+    )
+    .await;
+    if response.warnings.is_empty() {
+        cache.write().await.insert(key, response.clone());
+    }
+
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(
+        class: AssetClass,
+        symbol: Option<&str>,
+        name: &str,
+        popularity: f64,
+    ) -> Candidate {
+        Candidate {
+            symbol: symbol.map(str::to_string),
+            name: name.to_string(),
+            asset_class: class,
+            identifier: symbol.unwrap_or(name).to_string(),
+            price: None,
+            popularity,
+        }
+    }
+
+    #[test]
+    fn test_match_quality_kinds() {
+        assert_eq!(
+            match_quality("sol", Some("SOL"), "Solana").unwrap().0,
+            MatchKind::ExactSymbol
+        );
+        assert_eq!(
+            match_quality("so", Some("SOL"), "Solana").unwrap().0,
+            MatchKind::SymbolPrefix
+        );
+        assert_eq!(
+            match_quality("solana", Some("SOL"), "Solana").unwrap().0,
+            MatchKind::ExactName
+        );
+        assert_eq!(
+            match_quality("bon", None, "Bonk").unwrap().0,
+            MatchKind::NamePrefix
+        );
+        assert_eq!(
+            match_quality("network", Some("PYTH"), "Pyth Network")
+                .unwrap()
+                .0,
+            MatchKind::NameContains
+        );
+        assert_eq!(
+            match_quality("jpter", Some("JUP"), "Jupiter").unwrap().0,
+            MatchKind::Fuzzy
+        );
+        assert!(match_quality("xyz", Some("SOL"), "Solana").is_none());
+        assert!(match_quality("sl", Some("BONK"), "Solana").is_none());
+    }
+
+    #[test]
+    fn test_exact_ticker_outranks_popular_fuzzy_matches() {
+        let candidates = vec![
+            candidate(AssetClass::Token, Some("SOL"), "Solana", 45_000_000_000.0),
+            candidate(
+                AssetClass::Stock,
+                Some("COIN"),
+                "Coinbase Global Inc.",
+                450_000_000.0,
+            ),
+            candidate(
+                AssetClass::PredictionMarket,
+                None,
+                "Will COIN close above $300?",
+                9e12,
+            ),
+        ];
+
+        let results = rank_candidates("coin", candidates, 10);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].asset_class, AssetClass::Stock);
+        assert_eq!(results[0].match_kind, MatchKind::ExactSymbol);
+        assert_eq!(results[1].asset_class, AssetClass::PredictionMarket);
+    }
+
+    #[test]
+    fn test_popularity_orders_same_match_kind() {
+        let candidates = vec![
+            candidate(
+                AssetClass::Stock,
+                Some("SOL"),
+                "Emeren Group Ltd.",
+                1_000_000.0,
+            ),
+            candidate(AssetClass::Token, Some("SOL"), "Solana", 45_000_000_000.0),
+        ];
+
+        let results = rank_candidates("sol", candidates, 10);
+        assert_eq!(results[0].asset_class, AssetClass::Token);
+        assert_eq!(results[1].asset_class, AssetClass::Stock);
+
+        let results = rank_candidates(
+            "sol",
+            vec![candidate(AssetClass::Token, Some("SOL"), "Solana", 1.0)],
+            0,
+        );
+        assert!(results.is_empty());
+    }
+
+    #[test]
+    fn test_cache_expires_and_is_bounded() {
+        let mut cache = AssetSearchCache::new();
+        cache.capacity = 2;
+        let response = AssetSearchResponse {
+            query: "sol".to_string(),
+            results: Vec::new(),
+            warnings: Vec::new(),
+            cached: false,
+        };
+        let key = |query: &str| SearchKey {
+            query: query.to_string(),
+            asset_classes: AssetClass::all(),
+            limit: 20,
+        };
+
+        cache.insert(key("sol"), response.clone());
+        cache.insert(key("bonk"), response.clone());
+        cache.insert(key("jup"), response.clone());
+        assert!(cache.get(&key("sol")).is_none());
+        assert!(cache.get(&key("jup")).is_some());
+
+        cache.ttl = Duration::ZERO;
+        assert!(cache.get(&key("jup")).is_none());
+    }
+}
@@ -0,0 +1,337 @@
+use serde::{Deserialize, Serialize};
+
+use super::PricePoint;
+
+pub const DEFAULT_TARGET_POINTS: usize = 1000;
+pub const MIN_TARGET_POINTS: usize = 10;
+pub const MAX_TARGET_POINTS: usize = 5000;
+
+/// How many source points per output point the granularity choice allows, so
+/// the downsampler has real candidates to pick from without fetching every
+/// minute of a year.
+const OVERSAMPLE: i64 = 4;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum HistoryGranularity {
+    #[serde(rename = "1m")]
+    OneMinute,
+    #[serde(rename = "5m")]
+    FiveMinutes,
+    #[serde(rename = "15m")]
+    FifteenMinutes,
+    #[serde(rename = "1h")]
+    OneHour,
+    #[serde(rename = "4h")]
+    FourHours,
+    #[serde(rename = "1d")]
+    OneDay,
+}
+
+impl HistoryGranularity {
+    pub const ALL: [HistoryGranularity; 6] = [
+        HistoryGranularity::OneMinute,
+        HistoryGranularity::FiveMinutes,
+        HistoryGranularity::FifteenMinutes,
+        HistoryGranularity::OneHour,
+        HistoryGranularity::FourHours,
+        HistoryGranularity::OneDay,
+    ];
+
+    pub fn seconds(&self) -> i64 {
+        match self {
+            HistoryGranularity::OneMinute => 60,
+            HistoryGranularity::FiveMinutes => 300,
+            HistoryGranularity::FifteenMinutes => 900,
+            HistoryGranularity::OneHour => 3_600,
+            HistoryGranularity::FourHours => 14_400,
+            HistoryGranularity::OneDay => 86_400,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HistoryGranularity::OneMinute => "1m",
+            HistoryGranularity::FiveMinutes => "5m",
+            HistoryGranularity::FifteenMinutes => "15m",
+            HistoryGranularity::OneHour => "1h",
+            HistoryGranularity::FourHours => "4h",
+            HistoryGranularity::OneDay => "1d",
+        }
+    }
+
+    /// Interval name used by Birdeye's OHLCV endpoint.
+    pub fn birdeye_type(&self) -> &'static str {
+        match self {
+            HistoryGranularity::OneMinute => "1m",
+            HistoryGranularity::FiveMinutes => "5m",
+            HistoryGranularity::FifteenMinutes => "15m",
+            HistoryGranularity::OneHour => "1H",
+            HistoryGranularity::FourHours => "4H",
+            HistoryGranularity::OneDay => "1D",
+        }
+    }
+
+    /// Finest granularity that keeps `range_secs` within `OVERSAMPLE` times
+    /// the target point count. Very long ranges fall back to daily candles.
+    pub fn for_range(range_secs: i64, target_points: usize) -> Self {
+        let budget = (target_points as i64).max(1) * OVERSAMPLE;
+        Self::ALL
+            .into_iter()
+            .find(|granularity| range_secs.max(0) / granularity.seconds() <= budget)
+            .unwrap_or(HistoryGranularity::OneDay)
+    }
+
+    pub fn bucket_start(&self, timestamp: i64) -> i64 {
+        timestamp - timestamp.rem_euclid(self.seconds())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DownsampleMethod {
+    /// Largest-Triangle-Three-Buckets on the close. Keeps the visual shape,
+    /// including single-candle spikes.
+    #[default]
+    Lttb,
+    /// Merges consecutive candles into OHLC buckets. Extremes survive in the
+    /// wicks but the close line is smoothed.
+    Ohlc,
+}
+
+impl DownsampleMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DownsampleMethod::Lttb => "lttb",
+            DownsampleMethod::Ohlc => "ohlc",
+        }
+    }
+}
+
+pub fn clamp_target_points(target_points: Option<usize>) -> usize {
+    target_points
+        .unwrap_or(DEFAULT_TARGET_POINTS)
+        .clamp(MIN_TARGET_POINTS, MAX_TARGET_POINTS)
+}
+
+/// Reduces oldest-first `points` to at most `target` points. Series already
+/// at or below the target are returned unchanged.
+pub fn downsample(
+    points: &[PricePoint],
+    target: usize,
+    method: DownsampleMethod,
+) -> Vec<PricePoint> {
+    match method {
+        DownsampleMethod::Lttb => lttb(points, target),
+        DownsampleMethod::Ohlc => ohlc_buckets(points, target),
+    }
+}
+
+/// Largest-Triangle-Three-Buckets over (timestamp, close). The first and last
+/// points are always kept; every other bucket contributes the point forming
+/// the largest triangle with the previously kept point and the average of the
+/// next bucket. Selected candles are returned as-is.
+pub fn lttb(points: &[PricePoint], threshold: usize) -> Vec<PricePoint> {
+    let len = points.len();
+    if threshold >= len || threshold < 3 {
+        return points.to_vec();
+    }
+
+    let bucket_size = (len - 2) as f64 / (threshold - 2) as f64;
+    let bucket_bounds = |bucket: usize| {
+        let start = (bucket as f64 * bucket_size) as usize + 1;
+        let end = (((bucket + 1) as f64 * bucket_size) as usize + 1).min(len - 1);
+        (start, end)
+    };
+
+    let mut sampled = Vec::with_capacity(threshold);
+    sampled.push(points[0].clone());
+    let mut selected = 0;
+
+    for bucket in 0..threshold - 2 {
+        let (start, end) = bucket_bounds(bucket);
+
+        // The last bucket looks ahead to the final point only.
+        let (next_start, next_end) = if bucket + 1 < threshold - 2 {
+            bucket_bounds(bucket + 1)
+        } else {
+            (len - 1, len)
+        };
+        let next = &points[next_start..next_end];
+        let avg_x = next.iter().map(|p| p.timestamp as f64).sum::<f64>() / next.len() as f64;
+        let avg_y = next.iter().map(|p| p.close).sum::<f64>() / next.len() as f64;
+
+        let anchor = &points[selected];
+        let (ax, ay) = (anchor.timestamp as f64, anchor.close);
+
+        let mut best = start;
+        let mut best_area = -1.0;
+        for (index, point) in points.iter().enumerate().take(end).skip(start) {
+            let area = ((ax - avg_x) * (point.close - ay)
+                - (ax - point.timestamp as f64) * (avg_y - ay))
+                .abs();
+            if area > best_area {
+                best_area = area;
+                best = index;
+            }
+        }
+
+        sampled.push(points[best].clone());
+        selected = best;
+    }
+
+    sampled.push(points[len - 1].clone());
+    sampled
+}
+
+/// Merges runs of consecutive candles so at most `target` remain. Each bucket
+/// takes the first open and timestamp, the last close, the extremes and the
+/// summed volume.
+pub fn ohlc_buckets(points: &[PricePoint], target: usize) -> Vec<PricePoint> {
+    if target == 0 || points.len() <= target {
+        return points.to_vec();
+    }
+
+    let bucket_len = points.len().div_ceil(target);
+    points
+        .chunks(bucket_len)
+        .map(|chunk| {
+            let first = &chunk[0];
+            let last = &chunk[chunk.len() - 1];
+            PricePoint {
+                timestamp: first.timestamp,
+                open: first.open,
+                high: chunk.iter().map(|p| p.high).fold(f64::MIN, f64::max),
+                low: chunk.iter().map(|p| p.low).fold(f64::MAX, f64::min),
+                close: last.close,
+                volume: chunk.iter().map(|p| p.volume).sum(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(timestamp: i64, close: f64) -> PricePoint {
+        PricePoint {
+            timestamp,
+            open: close,
+            high: close,
+            low: close,
+            close,
+            volume: 1.0,
+        }
+    }
+
+    /// A gently oscillating series with a one-candle spike and a one-candle
+    /// crash, the shape naive bucketing flattens.
+    fn spiky_fixture() -> Vec<PricePoint> {
+        (0..100)
+            .map(|i| {
+                let close = match i {
+                    37 => 200.0,
+                    71 => 10.0,
+                    _ => 100.0 + (i as f64 / 5.0).sin() * 3.0,
+                };
+                point(i * 60, close)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_lttb_matches_known_fixture() {
+        let closes = [
+            10.0, 11.0, 30.0, 12.0, 11.0, 10.0, 9.0, 2.0, 10.0, 11.0, 12.0, 11.0,
+        ];
+        let points: Vec<PricePoint> = closes
+            .iter()
+            .enumerate()
+            .map(|(i, close)| point(i as i64, *close))
+            .collect();
+
+        let sampled = lttb(&points, 5);
+        let timestamps: Vec<i64> = sampled.iter().map(|p| p.timestamp).collect();
+        assert_eq!(timestamps, vec![0, 2, 4, 7, 11]);
+    }
+
+    #[test]
+    fn test_lttb_preserves_peaks_and_troughs() {
+        let fixture = spiky_fixture();
+        let sampled = lttb(&fixture, 20);
+        assert_eq!(sampled.len(), 20);
+        assert_eq!(sampled.first().unwrap().timestamp, 0);
+        assert_eq!(sampled.last().unwrap().timestamp, 99 * 60);
+        assert!(sampled.iter().any(|p| p.close == 200.0));
+        assert!(sampled.iter().any(|p| p.close == 10.0));
+        assert!(sampled.windows(2).all(|w| w[0].timestamp < w[1].timestamp));
+
+        // Bucketing keeps the extremes in the wicks only; the close line
+        // loses both.
+        let bucketed = ohlc_buckets(&fixture, 20);
+        assert!(bucketed.iter().all(|p| p.close != 200.0 && p.close != 10.0));
+        assert!(bucketed.iter().any(|p| p.high == 200.0));
+        assert!(bucketed.iter().any(|p| p.low == 10.0));
+    }
+
+    #[test]
+    fn test_lttb_passthrough_for_short_series() {
+        let fixture: Vec<PricePoint> = (0..5).map(|i| point(i, i as f64)).collect();
+        assert_eq!(lttb(&fixture, 10).len(), 5);
+        assert_eq!(lttb(&fixture, 2).len(), 5);
+    }
+
+    #[test]
+    fn test_ohlc_buckets_aggregate_candles() {
+        let fixture: Vec<PricePoint> = (0..10)
+            .map(|i| PricePoint {
+                timestamp: i * 60,
+                open: i as f64,
+                high: i as f64 + 1.0,
+                low: i as f64 - 1.0,
+                close: i as f64 + 0.5,
+                volume: 2.0,
+            })
+            .collect();
+
+        let bucketed = ohlc_buckets(&fixture, 4);
+        assert_eq!(bucketed.len(), 4);
+        assert_eq!(bucketed[0].timestamp, 0);
+        assert_eq!(bucketed[0].open, 0.0);
+        assert_eq!(bucketed[0].high, 3.0);
+        assert_eq!(bucketed[0].low, -1.0);
+        assert_eq!(bucketed[0].close, 2.5);
+        assert_eq!(bucketed[0].volume, 6.0);
+        assert_eq!(bucketed[3].timestamp, 540);
+        assert_eq!(bucketed[3].volume, 2.0);
+    }
+
+    #[test]
+    fn test_granularity_for_range() {
+        let year = 365 * 86_400;
+        assert_eq!(
+            HistoryGranularity::for_range(3_600, 1000),
+            HistoryGranularity::OneMinute
+        );
+        assert_eq!(
+            HistoryGranularity::for_range(7 * 86_400, 1000),
+            HistoryGranularity::FiveMinutes
+        );
+        assert_eq!(
+            HistoryGranularity::for_range(year, 1000),
+            HistoryGranularity::FourHours
+        );
+        assert_eq!(
+            HistoryGranularity::for_range(10 * year, 100),
+            HistoryGranularity::OneDay
+        );
+        assert_eq!(HistoryGranularity::OneHour.bucket_start(7_201), 7_200);
+    }
+
+    #[test]
+    fn test_clamp_target_points() {
+        assert_eq!(clamp_target_points(None), DEFAULT_TARGET_POINTS);
+        assert_eq!(clamp_target_points(Some(1)), MIN_TARGET_POINTS);
+        assert_eq!(clamp_target_points(Some(1_000_000)), MAX_TARGET_POINTS);
+    }
+}
@@ -0,0 +1,577 @@
+//! Market heatmap: returns per asset over a few timeframes, grouped by sector.
+//!
+//! Returns are computed from an hourly price series per asset, kept in the
+//! cache manager with other price history. Series missing from the cache, or
+//! older than ten minutes, are refreshed together in one bounded batch; a
+//! series that fails to refresh is served stale rather than dropped.
+//! Every cell carries the time its series was fetched so the UI can mark
+//! stale values.
+
+use super::{fetch_birdeye_history, fetch_top_coins, HistoryGranularity, PricePoint};
+use crate::core::cache_manager::{CacheType, SharedCacheManager};
+use crate::errors::AppError;
+use crate::market::SharedTopCoinsCache;
+use crate::portfolio::analytics::classify_sector;
+use crate::portfolio::SharedWatchlistManager;
+use crate::wallet::balances::SharedTokenBalanceService;
+use chrono::{DateTime, Duration, Utc};
+use futures_util::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use tauri::{AppHandle, Manager, State};
+
+/// Assets in one heatmap; larger scopes are cut to the first this many.
+pub const MAX_HEATMAP_ASSETS: usize = 100;
+const DEFAULT_TOP_COINS: usize = 50;
+/// Cached series older than this are refreshed before use.
+const SERIES_REFRESH_AFTER_SECS: i64 = 10 * 60;
+const REFRESH_CONCURRENCY: usize = 8;
+/// Volume change compares the last day against the one before, so series
+/// always cover at least two days.
+const MIN_SERIES_SPAN_SECS: i64 = 2 * 86_400;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum HeatmapScope {
+    /// The top coins by market cap.
+    TopCoins {
+        limit: Option<usize>,
+    },
+    Watchlist {
+        id: String,
+    },
+    /// A wallet's current token balances.
+    Holdings {
+        address: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum HeatmapTimeframe {
+    #[serde(rename = "1h")]
+    OneHour,
+    #[serde(rename = "24h")]
+    OneDay,
+    #[serde(rename = "7d")]
+    SevenDays,
+    #[serde(rename = "30d")]
+    ThirtyDays,
+}
+
+impl HeatmapTimeframe {
+    pub const ALL: [HeatmapTimeframe; 4] = [
+        HeatmapTimeframe::OneHour,
+        HeatmapTimeframe::OneDay,
+        HeatmapTimeframe::SevenDays,
+        HeatmapTimeframe::ThirtyDays,
+    ];
+
+    pub fn seconds(&self) -> i64 {
+        match self {
+            HeatmapTimeframe::OneHour => 3_600,
+            HeatmapTimeframe::OneDay => 86_400,
+            HeatmapTimeframe::SevenDays => 7 * 86_400,
+            HeatmapTimeframe::ThirtyDays => 30 * 86_400,
+        }
+    }
+}
+
+/// How assets are weighted in their category's aggregate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CategoryWeighting {
+    MarketCap,
+    HoldingValue,
+    /// Used when some asset in scope has no known market cap.
+    Equal,
+}
+
+/// An asset to place on the heatmap, with what it is weighted by.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeatmapAsset {
+    pub mint: String,
+    pub symbol: String,
+    pub market_cap: Option<f64>,
+    pub holding_value: Option<f64>,
+}
+
+/// Hourly candles for one asset and when they were fetched.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HeatmapSeries {
+    pub fetched_at: DateTime<Utc>,
+    pub span_secs: i64,
+    pub points: Vec<PricePoint>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HeatmapCell {
+    /// Percent change over the timeframe; `None` without enough history.
+    pub return_pct: Option<f64>,
+    /// When the series behind the value was fetched.
+    pub as_of: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HeatmapRow {
+    pub mint: String,
+    pub symbol: String,
+    pub weight: f64,
+    /// One cell per requested timeframe, in the heatmap's order.
+    pub cells: Vec<HeatmapCell>,
+    /// Volume over the last 24 hours against the 24 hours before.
+    pub volume_change_pct: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HeatmapCategory {
+    pub name: String,
+    pub total_weight: f64,
+    /// Weighted return per timeframe over the rows that have one.
+    pub returns: Vec<Option<f64>>,
+    pub volume_change_pct: Option<f64>,
+    pub rows: Vec<HeatmapRow>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarketHeatmap {
+    pub scope: HeatmapScope,
+    pub timeframes: Vec<HeatmapTimeframe>,
+    pub weighting: CategoryWeighting,
+    /// Heaviest category first.
+    pub categories: Vec<HeatmapCategory>,
+    /// Whether the scope had more than [`MAX_HEATMAP_ASSETS`] assets.
+    pub truncated: bool,
+    pub generated_at: DateTime<Utc>,
+}
+
+/// Percent change from the last close at or before `seconds` ago to the
+/// latest close. `points` must be sorted by timestamp.
+pub fn series_return(points: &[PricePoint], seconds: i64) -> Option<f64> {
+    let latest = points.last()?;
+    let start = latest.timestamp - seconds;
+    let past = points.iter().rev().find(|point| point.timestamp <= start)?;
+    (past.close > 0.0).then(|| (latest.close / past.close - 1.0) * 100.0)
+}
+
+/// Volume over the last day against the day before, in percent.
+pub fn volume_change(points: &[PricePoint]) -> Option<f64> {
+    let latest = points.last()?.timestamp;
+    let day = HeatmapTimeframe::OneDay.seconds();
+    let (mut recent, mut previous) = (0.0, 0.0);
+    for point in points {
+        let age = latest - point.timestamp;
+        if age < day {
+            recent += point.volume;
+        } else if age < 2 * day {
+            previous += point.volume;
+        }
+    }
+    (previous > 0.0).then(|| (recent / previous - 1.0) * 100.0)
+}
+
+fn weighted_mean(values: impl Iterator<Item = (f64, Option<f64>)>) -> Option<f64> {
+    let (sum, weight) = values
+        .filter_map(|(weight, value)| value.map(|value| (weight, value)))
+        .filter(|(weight, _)| *weight > 0.0)
+        .fold((0.0, 0.0), |(sum, total), (weight, value)| {
+            (sum + weight * value, total + weight)
+        });
+    (weight > 0.0).then(|| sum / weight)
+}
+
+/// Lays the assets out by sector. Assets without a series get empty cells.
+pub fn build_heatmap(
+    scope: HeatmapScope,
+    assets: &[HeatmapAsset],
+    series: &HashMap<String, HeatmapSeries>,
+    timeframes: Vec<HeatmapTimeframe>,
+    weighting: CategoryWeighting,
+    truncated: bool,
+    now: DateTime<Utc>,
+) -> MarketHeatmap {
+    let mut grouped: BTreeMap<String, Vec<HeatmapRow>> = BTreeMap::new();
+    for asset in assets {
+        let series = series.get(&asset.mint);
+        let points = series.map(|series| series.points.as_slice()).unwrap_or(&[]);
+        let cells = timeframes
+            .iter()
+            .map(|timeframe| HeatmapCell {
+                return_pct: series_return(points, timeframe.seconds()),
+                as_of: series.map(|series| series.fetched_at),
+            })
+            .collect();
+        let weight = match weighting {
+            CategoryWeighting::MarketCap => asset.market_cap.unwrap_or_default(),
+            CategoryWeighting::HoldingValue => asset.holding_value.unwrap_or_default(),
+            CategoryWeighting::Equal => 1.0,
+        };
+        grouped
+            .entry(classify_sector(&asset.symbol))
+            .or_default()
+            .push(HeatmapRow {
+                mint: asset.mint.clone(),
+                symbol: asset.symbol.clone(),
+                weight,
+                cells,
+                volume_change_pct: volume_change(points),
+            });
+    }
+
+    let mut categories: Vec<HeatmapCategory> = grouped
+        .into_iter()
+        .map(|(name, mut rows)| {
+            rows.sort_by(|a, b| b.weight.total_cmp(&a.weight));
+            let returns = (0..timeframes.len())
+                .map(|index| {
+                    weighted_mean(
+                        rows.iter()
+                            .map(|row| (row.weight, row.cells[index].return_pct)),
+                    )
+                })
+                .collect();
+            HeatmapCategory {
+                name,
+                total_weight: rows.iter().map(|row| row.weight).sum(),
+                returns,
+                volume_change_pct: weighted_mean(
+                    rows.iter().map(|row| (row.weight, row.volume_change_pct)),
+                ),
+                rows,
+            }
+        })
+        .collect();
+    categories.sort_by(|a, b| b.total_weight.total_cmp(&a.total_weight));
+
+    MarketHeatmap {
+        scope,
+        timeframes,
+        weighting,
+        categories,
+        truncated,
+        generated_at: now,
+    }
+}
+
+/// Requested timeframes without duplicates, shortest first; all of them when
+/// none are given.
+fn normalize_timeframes(timeframes: Vec<HeatmapTimeframe>) -> Vec<HeatmapTimeframe> {
+    if timeframes.is_empty() {
+        return HeatmapTimeframe::ALL.to_vec();
+    }
+    let mut timeframes = timeframes;
+    timeframes.sort();
+    timeframes.dedup();
+    timeframes
+}
+
+async fn scope_assets(
+    app: &AppHandle,
+    scope: &HeatmapScope,
+    top_coins: &SharedTopCoinsCache,
+    api_key: Option<String>,
+) -> Result<Vec<HeatmapAsset>, AppError> {
+    match scope {
+        HeatmapScope::TopCoins { limit } => {
+            let limit = limit
+                .unwrap_or(DEFAULT_TOP_COINS)
+                .clamp(1, MAX_HEATMAP_ASSETS);
+            let coins = fetch_top_coins(top_coins, limit, 0, api_key)
+                .await
+                .map_err(|e| AppError::upstream("birdeye", e, true))?;
+            Ok(coins
+                .into_iter()
+                .map(|coin| HeatmapAsset {
+                    mint: coin.address,
+                    symbol: coin.symbol,
+                    market_cap: Some(coin.market_cap),
+                    holding_value: None,
+                })
+                .collect())
+        }
+        HeatmapScope::Watchlist { id } => {
+            let watchlists = app
+                .try_state::<SharedWatchlistManager>()
+                .ok_or_else(|| AppError::internal("Watchlists are unavailable"))?;
+            let watchlist = watchlists
+                .read()
+                .await
+                .get_watchlist(id)
+                .await
+                .map_err(|e| AppError::not_found(e.to_string()).with_code("watchlist_not_found"))?;
+            // Market caps come from the top coins list when it has the token.
+            let caps: HashMap<String, f64> = top_coins
+                .read()
+                .await
+                .get()
+                .unwrap_or_default()
+                .into_iter()
+                .map(|coin| (coin.address, coin.market_cap))
+                .collect();
+            Ok(watchlist
+                .items
+                .into_iter()
+                .map(|item| HeatmapAsset {
+                    market_cap: caps.get(&item.mint).copied(),
+                    mint: item.mint,
+                    symbol: item.symbol,
+                    holding_value: None,
+                })
+                .collect())
+        }
+        HeatmapScope::Holdings { address } => {
+            let balances = app
+                .try_state::<SharedTokenBalanceService>()
+                .ok_or_else(|| AppError::internal("Token balances are unavailable"))?;
+            let view = balances
+                .balances(address, false)
+                .await
+                .map_err(|e| AppError::internal(e.to_string()))?;
+            Ok(view
+                .balances
+                .into_iter()
+                .filter(|balance| balance.balance > 0.0)
+                .map(|balance| HeatmapAsset {
+                    mint: balance.mint,
+                    symbol: balance.symbol,
+                    market_cap: None,
+                    holding_value: Some(balance.usd_value),
+                })
+                .collect())
+        }
+    }
+}
+
+fn series_cache_key(mint: &str) -> String {
+    format!("heatmap_series_{}", mint)
+}
+
+/// Series for every asset: cached ones as they are, the rest fetched in one
+/// batch. A failed refresh keeps the stale cached series when there is one.
+async fn load_series(
+    cache_manager: &SharedCacheManager,
+    assets: &[HeatmapAsset],
+    span_secs: i64,
+    now: DateTime<Utc>,
+) -> HashMap<String, HeatmapSeries> {
+    let mut series = HashMap::new();
+    let mut missing = Vec::new();
+    {
+        let cache = cache_manager.read().await;
+        for asset in assets {
+            let cached = cache
+                .get(&series_cache_key(&asset.mint), CacheType::PriceHistory)
+                .await
+                .and_then(|value| serde_json::from_value::<HeatmapSeries>(value).ok())
+                .filter(|cached| cached.span_secs >= span_secs);
+            match cached {
+                Some(cached)
+                    if now - cached.fetched_at < Duration::seconds(SERIES_REFRESH_AFTER_SECS) =>
+                {
+                    series.insert(asset.mint.clone(), cached);
+                }
+                stale => {
+                    if let Some(stale) = stale {
+                        series.insert(asset.mint.clone(), stale);
+                    }
+                    missing.push(asset.mint.clone());
+                }
+            }
+        }
+    }
+
+    let granularity = HistoryGranularity::OneHour;
+    let to = now.timestamp();
+    let from = to - span_secs;
+    let fetched: Vec<(String, Result<Vec<PricePoint>, String>)> = stream::iter(missing)
+        .map(|mint| async move {
+            let points = fetch_birdeye_history(&mint, granularity, from, to, "").await;
+            (mint, points)
+        })
+        .buffer_unordered(REFRESH_CONCURRENCY)
+        .collect()
+        .await;
+
+    let cache = cache_manager.read().await;
+    for (mint, points) in fetched {
+        let points = match points {
+            Ok(points) => points,
+            Err(err) => {
+                eprintln!("Failed to refresh heatmap series for {}: {}", mint, err);
+                continue;
+            }
+        };
+        let fresh = HeatmapSeries {
+            fetched_at: now,
+            span_secs,
+            points,
+        };
+        if let Ok(value) = serde_json::to_value(&fresh) {
+            if let Err(err) = cache
+                .set(series_cache_key(&mint), value, CacheType::PriceHistory)
+                .await
+            {
+                eprintln!("Failed to cache heatmap series: {}", err);
+            }
+        }
+        series.insert(mint, fresh);
+    }
+    series
+}
+
+/// Returns for the assets in `scope` over `timeframes` (all four when empty),
+/// grouped by sector. Categories aggregate by market cap, or by holding value
+/// for holdings; `weighting` in the response says which.
+#[tauri::command]
+pub async fn get_market_heatmap(
+    app: AppHandle,
+    scope: HeatmapScope,
+    timeframes: Vec<HeatmapTimeframe>,
+    api_key: Option<String>,
+    cache_manager: State<'_, SharedCacheManager>,
+    top_coins: State<'_, SharedTopCoinsCache>,
+) -> Result<MarketHeatmap, AppError> {
+    let timeframes = normalize_timeframes(timeframes);
+    let mut assets = scope_assets(&app, &scope, &top_coins, api_key).await?;
+    let mut seen = HashSet::new();
+    assets.retain(|asset| seen.insert(asset.mint.clone()));
+    let truncated = assets.len() > MAX_HEATMAP_ASSETS;
+    assets.truncate(MAX_HEATMAP_ASSETS);
+
+    let weighting = match scope {
+        HeatmapScope::Holdings { .. } => CategoryWeighting::HoldingValue,
+        _ if assets.iter().all(|asset| asset.market_cap.is_some()) => CategoryWeighting::MarketCap,
+        _ => CategoryWeighting::Equal,
+    };
+    let longest = timeframes.iter().map(HeatmapTimeframe::seconds).max();
+    // One extra candle so the longest timeframe has a starting close.
+    let span_secs = longest.unwrap_or_default().max(MIN_SERIES_SPAN_SECS)
+        + HistoryGranularity::OneHour.seconds();
+
+    let now = Utc::now();
+    let series = load_series(&cache_manager, &assets, span_secs, now).await;
+    Ok(build_heatmap(
+        scope, &assets, &series, timeframes, weighting, truncated, now,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hourly(closes: &[f64], volume: f64) -> Vec<PricePoint> {
+        closes
+            .iter()
+            .enumerate()
+            .map(|(hour, close)| PricePoint {
+                timestamp: hour as i64 * 3_600,
+                open: *close,
+                high: *close,
+                low: *close,
+                close: *close,
+                volume,
+            })
+            .collect()
+    }
+
+    fn asset(mint: &str, symbol: &str, market_cap: Option<f64>) -> HeatmapAsset {
+        HeatmapAsset {
+            mint: mint.to_string(),
+            symbol: symbol.to_string(),
+            market_cap,
+            holding_value: None,
+        }
+    }
+
+    fn series(points: Vec<PricePoint>, fetched_at: DateTime<Utc>) -> HeatmapSeries {
+        HeatmapSeries {
+            fetched_at,
+            span_secs: MIN_SERIES_SPAN_SECS,
+            points,
+        }
+    }
+
+    #[test]
+    fn returns_start_from_the_close_a_timeframe_ago() {
+        let mut closes = vec![100.0; 24];
+        closes.extend([110.0, 121.0]);
+        let points = hourly(&closes, 1.0);
+        let hour = series_return(&points, 3_600).unwrap();
+        assert!((hour - 10.0).abs() < 1e-9);
+        let day = series_return(&points, 86_400).unwrap();
+        assert!((day - 21.0).abs() < 1e-9);
+        // Not enough history for a week.
+        assert_eq!(series_return(&points, 7 * 86_400), None);
+        assert_eq!(series_return(&[], 3_600), None);
+    }
+
+    #[test]
+    fn volume_change_compares_consecutive_days() {
+        let mut points = hourly(&[1.0; 24], 10.0);
+        points.extend(hourly(&[1.0; 24], 15.0).into_iter().map(|mut point| {
+            point.timestamp += 86_400;
+            point
+        }));
+        let change = volume_change(&points).unwrap();
+        assert!((change - 50.0).abs() < 1e-9);
+        assert_eq!(volume_change(&hourly(&[1.0; 24], 10.0)), None);
+    }
+
+    #[test]
+    fn categories_aggregate_by_weight() {
+        let now = Utc::now();
+        let assets = vec![
+            asset("sol", "SOL", Some(300.0)),
+            asset("eth", "ETH", Some(100.0)),
+            asset("bonk", "BONK", Some(50.0)),
+            asset("new", "NEW", Some(10.0)),
+        ];
+        let mut history = HashMap::new();
+        history.insert("sol".to_string(), series(hourly(&[100.0, 110.0], 1.0), now));
+        history.insert("eth".to_string(), series(hourly(&[100.0, 130.0], 1.0), now));
+        history.insert("bonk".to_string(), series(hourly(&[100.0, 90.0], 1.0), now));
+        let heatmap = build_heatmap(
+            HeatmapScope::TopCoins { limit: None },
+            &assets,
+            &history,
+            vec![HeatmapTimeframe::OneHour, HeatmapTimeframe::OneDay],
+            CategoryWeighting::MarketCap,
+            false,
+            now,
+        );
+
+        let names: Vec<&str> = heatmap.categories.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["Layer 1", "Meme", "Other"]);
+        let layer_one = &heatmap.categories[0];
+        assert_eq!(layer_one.rows[0].symbol, "SOL");
+        // (300 * 10 + 100 * 30) / 400
+        assert!((layer_one.returns[0].unwrap() - 15.0).abs() < 1e-9);
+        assert_eq!(layer_one.returns[1], None);
+        assert_eq!(layer_one.rows[0].cells[0].as_of, Some(now));
+
+        // No series: empty cells and no aggregate.
+        let other = &heatmap.categories[2];
+        assert_eq!(other.returns, vec![None, None]);
+        assert_eq!(other.rows[0].cells[0].as_of, None);
+    }
+
+    #[test]
+    fn timeframes_are_deduplicated_in_order() {
+        assert_eq!(
+            normalize_timeframes(Vec::new()),
+            HeatmapTimeframe::ALL.to_vec()
+        );
+        assert_eq!(
+            normalize_timeframes(vec![
+                HeatmapTimeframe::ThirtyDays,
+                HeatmapTimeframe::OneHour,
+                HeatmapTimeframe::ThirtyDays,
+            ]),
+            vec![HeatmapTimeframe::OneHour, HeatmapTimeframe::ThirtyDays]
+        );
+    }
+}
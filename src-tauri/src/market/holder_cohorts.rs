@@ -0,0 +1,418 @@
+//! Holder cohort classification. Cohorts are rebuilt by replaying the
+//! per-holder balance changes recorded on each distribution refresh, so a
+//! holder's acquisition history survives across refreshes.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+pub const DEFAULT_WHALE_PERCENTILE: f64 = 95.0;
+/// Peak-to-trough price drop a holder must sit through to count as a
+/// diamond hand.
+pub const DIAMOND_HANDS_DRAWDOWN: f64 = 0.30;
+/// The new-holder cohort has collapsed when it falls below this fraction of
+/// its peak within the window.
+const NEW_HOLDER_COLLAPSE_RATIO: f64 = 0.5;
+/// Smaller peaks are noise rather than a cohort that can collapse.
+const NEW_HOLDER_COLLAPSE_MIN_PEAK: u64 = 10;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum HolderCohort {
+    /// First acquired within the window.
+    New,
+    /// Re-entered within the window after fully exiting.
+    Returning,
+    /// Held through a drawdown of more than 30% without exiting.
+    DiamondHands,
+    /// Balance at or above the whale percentile.
+    Whale,
+}
+
+impl HolderCohort {
+    pub const ALL: [HolderCohort; 4] = [
+        HolderCohort::New,
+        HolderCohort::Returning,
+        HolderCohort::DiamondHands,
+        HolderCohort::Whale,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            HolderCohort::New => "new",
+            HolderCohort::Returning => "returning",
+            HolderCohort::DiamondHands => "diamond hands",
+            HolderCohort::Whale => "whale",
+        }
+    }
+}
+
+/// A holder's balance after a refresh. Zero means the holder exited.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HolderBalanceChange {
+    pub holder_address: String,
+    pub timestamp: DateTime<Utc>,
+    pub balance: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HolderRefreshPoint {
+    pub timestamp: DateTime<Utc>,
+    pub price: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CohortStat {
+    pub cohort: HolderCohort,
+    pub count: u64,
+    /// Percent of the balance held by all holders at that time.
+    pub balance_share: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HolderCohortPoint {
+    pub timestamp: String,
+    pub holder_count: u64,
+    pub cohorts: Vec<CohortStat>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CohortDelta {
+    pub cohort: HolderCohort,
+    pub count_start: u64,
+    pub count_end: u64,
+    pub count_peak: u64,
+    /// Percentage points of balance share gained or lost over the window.
+    pub balance_share_change: f64,
+}
+
+/// Cohort changes over a window, as attached to holder and safety reports.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HolderCohortSummary {
+    pub window_days: u32,
+    pub deltas: Vec<CohortDelta>,
+    pub new_holder_collapse: bool,
+    pub flags: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HolderCohortReport {
+    pub token_address: String,
+    pub window_days: u32,
+    pub whale_percentile: f64,
+    /// One point per refresh inside the window. Cohorts overlap, so counts
+    /// do not add up to the holder count.
+    pub points: Vec<HolderCohortPoint>,
+    pub summary: HolderCohortSummary,
+    /// False when no refresh in the history had a price, in which case no
+    /// holder can be classed as a diamond hand.
+    pub price_history_available: bool,
+}
+
+#[derive(Debug, Default)]
+struct HolderState {
+    balance: f64,
+    first_acquired: Option<DateTime<Utc>>,
+    holding_since: Option<DateTime<Utc>>,
+    /// Set when the current holding run started after a full exit.
+    reentered_at: Option<DateTime<Utc>>,
+    has_exited: bool,
+    peak_price: Option<f64>,
+    max_drawdown: f64,
+}
+
+impl HolderState {
+    fn apply(&mut self, change: &HolderBalanceChange) {
+        let was_holding = self.balance > 0.0;
+        self.balance = change.balance.max(0.0);
+        if self.balance > 0.0 && !was_holding {
+            self.first_acquired.get_or_insert(change.timestamp);
+            self.holding_since = Some(change.timestamp);
+            self.reentered_at = self.has_exited.then_some(change.timestamp);
+            self.peak_price = None;
+            self.max_drawdown = 0.0;
+        } else if self.balance == 0.0 && was_holding {
+            self.has_exited = true;
+            self.holding_since = None;
+        }
+    }
+
+    fn observe_price(&mut self, price: f64) {
+        if self.balance <= 0.0 || price <= 0.0 {
+            return;
+        }
+        let peak = self.peak_price.map_or(price, |peak| peak.max(price));
+        self.peak_price = Some(peak);
+        self.max_drawdown = self.max_drawdown.max(1.0 - price / peak);
+    }
+}
+
+/// Smallest balance that counts as a whale: the value at `percentile` of
+/// the sorted balances.
+pub fn whale_threshold(balances: &[f64], percentile: f64) -> Option<f64> {
+    if balances.is_empty() {
+        return None;
+    }
+    let mut sorted = balances.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let rank = (percentile.clamp(0.0, 100.0) / 100.0 * sorted.len() as f64).ceil() as usize;
+    Some(sorted[rank.saturating_sub(1).min(sorted.len() - 1)])
+}
+
+/// Replays `changes` against `refreshes` (both oldest first) and classifies
+/// holders at every refresh from `window_start` on.
+pub fn compute_cohort_points(
+    changes: &[HolderBalanceChange],
+    refreshes: &[HolderRefreshPoint],
+    window_start: DateTime<Utc>,
+    whale_percentile: f64,
+) -> Vec<HolderCohortPoint> {
+    let mut states: HashMap<&str, HolderState> = HashMap::new();
+    let mut next_change = 0;
+    let mut points = Vec::new();
+
+    for refresh in refreshes {
+        while let Some(change) = changes.get(next_change) {
+            if change.timestamp > refresh.timestamp {
+                break;
+            }
+            states
+                .entry(change.holder_address.as_str())
+                .or_default()
+                .apply(change);
+            next_change += 1;
+        }
+        if let Some(price) = refresh.price {
+            for state in states.values_mut() {
+                state.observe_price(price);
+            }
+        }
+        if refresh.timestamp < window_start {
+            continue;
+        }
+
+        let holding: Vec<&HolderState> = states.values().filter(|s| s.balance > 0.0).collect();
+        let total_balance: f64 = holding.iter().map(|s| s.balance).sum();
+        let balances: Vec<f64> = holding.iter().map(|s| s.balance).collect();
+        let whale_min = whale_threshold(&balances, whale_percentile);
+
+        let in_window = |at: Option<DateTime<Utc>>| at.is_some_and(|at| at >= window_start);
+        let cohorts = HolderCohort::ALL
+            .iter()
+            .map(|cohort| {
+                let members = holding.iter().filter(|state| match cohort {
+                    HolderCohort::New => in_window(state.first_acquired),
+                    HolderCohort::Returning => in_window(state.reentered_at),
+                    HolderCohort::DiamondHands => state.max_drawdown > DIAMOND_HANDS_DRAWDOWN,
+                    HolderCohort::Whale => whale_min.is_some_and(|min| state.balance >= min),
+                });
+                let (count, balance) = members.fold((0u64, 0.0), |(count, balance), state| {
+                    (count + 1, balance + state.balance)
+                });
+                CohortStat {
+                    cohort: *cohort,
+                    count,
+                    balance_share: if total_balance > 0.0 {
+                        balance / total_balance * 100.0
+                    } else {
+                        0.0
+                    },
+                }
+            })
+            .collect();
+
+        points.push(HolderCohortPoint {
+            timestamp: refresh.timestamp.to_rfc3339(),
+            holder_count: holding.len() as u64,
+            cohorts,
+        });
+    }
+
+    points
+}
+
+pub fn summarize_cohorts(points: &[HolderCohortPoint], window_days: u32) -> HolderCohortSummary {
+    let stat = |point: &HolderCohortPoint, cohort: HolderCohort| {
+        point
+            .cohorts
+            .iter()
+            .find(|stat| stat.cohort == cohort)
+            .map(|stat| (stat.count, stat.balance_share))
+            .unwrap_or((0, 0.0))
+    };
+
+    let deltas: Vec<CohortDelta> = match (points.first(), points.last()) {
+        (Some(first), Some(last)) => HolderCohort::ALL
+            .iter()
+            .map(|cohort| {
+                let (count_start, share_start) = stat(first, *cohort);
+                let (count_end, share_end) = stat(last, *cohort);
+                CohortDelta {
+                    cohort: *cohort,
+                    count_start,
+                    count_end,
+                    count_peak: points.iter().map(|p| stat(p, *cohort).0).max().unwrap_or(0),
+                    balance_share_change: share_end - share_start,
+                }
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    let mut flags = Vec::new();
+    let new_holder_collapse = deltas.iter().any(|delta| {
+        delta.cohort == HolderCohort::New
+            && delta.count_peak >= NEW_HOLDER_COLLAPSE_MIN_PEAK
+            && (delta.count_end as f64) < delta.count_peak as f64 * NEW_HOLDER_COLLAPSE_RATIO
+    });
+    if new_holder_collapse {
+        if let Some(delta) = deltas.iter().find(|d| d.cohort == HolderCohort::New) {
+            flags.push(format!(
+                "New holder cohort collapsed from {} to {} within {} days",
+                delta.count_peak, delta.count_end, window_days
+            ));
+        }
+    }
+    for delta in &deltas {
+        if delta.balance_share_change <= -20.0 {
+            flags.push(format!(
+                "{} cohort lost {:.1} points of balance share",
+                delta.cohort.label(),
+                -delta.balance_share_change
+            ));
+        }
+    }
+
+    HolderCohortSummary {
+        window_days,
+        deltas,
+        new_holder_collapse,
+        flags,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    fn day(n: i64) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 10, 1, 0, 0, 0).unwrap() + Duration::days(n)
+    }
+
+    fn change(holder: &str, at: i64, balance: f64) -> HolderBalanceChange {
+        HolderBalanceChange {
+            holder_address: holder.to_string(),
+            timestamp: day(at),
+            balance,
+        }
+    }
+
+    fn refresh(at: i64, price: f64) -> HolderRefreshPoint {
+        HolderRefreshPoint {
+            timestamp: day(at),
+            price: Some(price),
+        }
+    }
+
+    fn count(point: &HolderCohortPoint, cohort: HolderCohort) -> u64 {
+        point
+            .cohorts
+            .iter()
+            .find(|s| s.cohort == cohort)
+            .unwrap()
+            .count
+    }
+
+    #[test]
+    fn test_classifies_each_cohort() {
+        // "old" held from before the window through a 50% drawdown, "flip"
+        // exited and came back, "fresh" arrived inside the window.
+        let changes = vec![
+            change("old", 0, 1_000.0),
+            change("flip", 0, 10.0),
+            change("flip", 2, 0.0),
+            change("flip", 6, 20.0),
+            change("fresh", 6, 5.0),
+        ];
+        let refreshes = vec![
+            refresh(0, 1.0),
+            refresh(2, 2.0),
+            refresh(4, 1.0),
+            refresh(6, 1.2),
+        ];
+
+        let points = compute_cohort_points(&changes, &refreshes, day(5), 90.0);
+        assert_eq!(points.len(), 1);
+        let point = &points[0];
+        assert_eq!(point.holder_count, 3);
+        assert_eq!(count(point, HolderCohort::New), 1);
+        assert_eq!(count(point, HolderCohort::Returning), 1);
+        assert_eq!(count(point, HolderCohort::DiamondHands), 1);
+        assert_eq!(count(point, HolderCohort::Whale), 1);
+
+        let whale_share = point
+            .cohorts
+            .iter()
+            .find(|s| s.cohort == HolderCohort::Whale)
+            .unwrap()
+            .balance_share;
+        assert!((whale_share - 1_000.0 / 1_025.0 * 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_reentry_resets_drawdown() {
+        let changes = vec![
+            change("a", 0, 1.0),
+            change("a", 2, 0.0),
+            change("a", 3, 1.0),
+        ];
+        let refreshes = vec![refresh(0, 2.0), refresh(1, 1.0), refresh(3, 1.0)];
+        let points = compute_cohort_points(&changes, &refreshes, day(0), 95.0);
+        assert_eq!(count(&points[1], HolderCohort::DiamondHands), 1);
+        assert_eq!(count(&points[2], HolderCohort::DiamondHands), 0);
+        assert_eq!(count(&points[2], HolderCohort::Returning), 1);
+    }
+
+    #[test]
+    fn test_flags_new_holder_collapse() {
+        let mut changes = Vec::new();
+        for i in 0..20 {
+            changes.push(change(&format!("h{}", i), 1, 1.0));
+        }
+        for i in 0..15 {
+            changes.push(change(&format!("h{}", i), 3, 0.0));
+        }
+        let refreshes = vec![refresh(1, 1.0), refresh(2, 1.0), refresh(3, 1.0)];
+        let points = compute_cohort_points(&changes, &refreshes, day(0), 95.0);
+        let summary = summarize_cohorts(&points, 7);
+
+        let new = summary
+            .deltas
+            .iter()
+            .find(|d| d.cohort == HolderCohort::New)
+            .unwrap();
+        assert_eq!(
+            (new.count_start, new.count_peak, new.count_end),
+            (20, 20, 5)
+        );
+        assert!(summary.new_holder_collapse);
+        assert!(summary.flags[0].contains("from 20 to 5"));
+
+        let steady = summarize_cohorts(&points[..2], 7);
+        assert!(!steady.new_holder_collapse);
+    }
+
+    #[test]
+    fn test_whale_threshold() {
+        let balances: Vec<f64> = (1..=100).map(|b| b as f64).collect();
+        assert_eq!(whale_threshold(&balances, 95.0), Some(95.0));
+        assert_eq!(whale_threshold(&balances, 100.0), Some(100.0));
+        assert_eq!(whale_threshold(&[], 95.0), None);
+    }
+}
@@ -1,12 +1,18 @@
-use chrono::Utc;
+use crate::config::DataPaths;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::{Pool, Row, Sqlite, SqlitePool};
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
-use tauri::{AppHandle, Manager, State};
+use tauri::{AppHandle, State};
 use tokio::sync::RwLock;
 
+use super::holder_cohorts::{
+    compute_cohort_points, summarize_cohorts, HolderBalanceChange, HolderCohortReport,
+    HolderRefreshPoint, DEFAULT_WHALE_PERCENTILE,
+};
+
 const HOLDERS_DB_FILE: &str = "holders.db";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -115,6 +121,7 @@ pub struct HolderDataExport {
     pub distribution: HolderDistribution,
     pub trends: Vec<HolderTrend>,
     pub large_transfers: Vec<LargeTransfer>,
+    pub cohorts: HolderCohortReport,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -279,11 +286,64 @@ impl HolderAnalyzer {
         .execute(&self.pool)
         .await?;
 
+        // Per-holder acquisition history, updated incrementally on each
+        // distribution refresh
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS holder_history (
+                token_address TEXT NOT NULL,
+                holder_address TEXT NOT NULL,
+                balance REAL NOT NULL,
+                first_acquired_at TEXT NOT NULL,
+                holding_since TEXT,
+                exited_at TEXT,
+                reentry_count INTEGER NOT NULL DEFAULT 0,
+                updated_at TEXT NOT NULL,
+                PRIMARY KEY (token_address, holder_address)
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // Balance after each refresh that changed it; zero marks an exit
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS holder_balance_changes (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                token_address TEXT NOT NULL,
+                holder_address TEXT NOT NULL,
+                timestamp TEXT NOT NULL,
+                balance REAL NOT NULL
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS holder_refreshes (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                token_address TEXT NOT NULL,
+                timestamp TEXT NOT NULL,
+                holder_count INTEGER NOT NULL,
+                price REAL
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
         // Create indexes
         sqlx::query(
             r#"
             CREATE INDEX IF NOT EXISTS idx_holders_token 
             ON holders(token_address);
+            CREATE INDEX IF NOT EXISTS idx_holder_balance_changes_token
+            ON holder_balance_changes(token_address, timestamp);
+            CREATE INDEX IF NOT EXISTS idx_holder_refreshes_token
+            ON holder_refreshes(token_address, timestamp);
             CREATE INDEX IF NOT EXISTS idx_holder_trends_token 
             ON holder_trends(token_address, timestamp);
             CREATE INDEX IF NOT EXISTS idx_large_transfers_token 
@@ -348,6 +408,17 @@ impl HolderAnalyzer {
             holder.rank = (i + 1) as u32;
         }
 
+        let price = crate::core::price_engine::get_price_engine().get_price(token_address);
+        if let Err(err) = self
+            .record_holder_refresh(token_address, &holders, price, Utc::now())
+            .await
+        {
+            eprintln!(
+                "Failed to record holder history for {}: {}",
+                token_address, err
+            );
+        }
+
         let total_holders = holders.len() as u64;
         let top_10_percentage: f64 = holders.iter().take(10).map(|h| h.percentage).sum();
         let top_50_percentage: f64 = holders.iter().take(50).map(|h| h.percentage).sum();
@@ -377,6 +448,219 @@ impl HolderAnalyzer {
         })
     }
 
+    /// Folds a distribution refresh into the holder history: new holders,
+    /// re-entries, balance changes and exits are written, unchanged holders
+    /// are left alone.
+    pub async fn record_holder_refresh(
+        &self,
+        token_address: &str,
+        holders: &[HolderInfo],
+        price: Option<f64>,
+        at: DateTime<Utc>,
+    ) -> Result<(), HolderError> {
+        let now = at.to_rfc3339();
+        let mut tx = self.pool.begin().await?;
+
+        let rows = sqlx::query(
+            r#"
+            SELECT holder_address, balance, exited_at
+            FROM holder_history
+            WHERE token_address = ?1
+            "#,
+        )
+        .bind(token_address)
+        .fetch_all(&mut *tx)
+        .await?;
+        let mut known: HashMap<String, (f64, bool)> = HashMap::new();
+        for row in rows {
+            let exited_at: Option<String> = row.try_get("exited_at")?;
+            known.insert(
+                row.try_get("holder_address")?,
+                (row.try_get("balance")?, exited_at.is_some()),
+            );
+        }
+
+        let mut changes: Vec<(&str, f64)> = Vec::new();
+        for holder in holders.iter().filter(|h| h.balance > 0.0) {
+            match known.remove(&holder.address) {
+                None => {
+                    sqlx::query(
+                        r#"
+                        INSERT INTO holder_history (
+                            token_address, holder_address, balance, first_acquired_at,
+                            holding_since, updated_at
+                        ) VALUES (?1, ?2, ?3, ?4, ?4, ?4)
+                        "#,
+                    )
+                    .bind(token_address)
+                    .bind(&holder.address)
+                    .bind(holder.balance)
+                    .bind(&now)
+                    .execute(&mut *tx)
+                    .await?;
+                    changes.push((holder.address.as_str(), holder.balance));
+                }
+                Some((_, true)) => {
+                    sqlx::query(
+                        r#"
+                        UPDATE holder_history
+                        SET balance = ?1, holding_since = ?2, exited_at = NULL,
+                            reentry_count = reentry_count + 1, updated_at = ?2
+                        WHERE token_address = ?3 AND holder_address = ?4
+                        "#,
+                    )
+                    .bind(holder.balance)
+                    .bind(&now)
+                    .bind(token_address)
+                    .bind(&holder.address)
+                    .execute(&mut *tx)
+                    .await?;
+                    changes.push((holder.address.as_str(), holder.balance));
+                }
+                Some((balance, false)) if balance != holder.balance => {
+                    sqlx::query(
+                        r#"
+                        UPDATE holder_history SET balance = ?1, updated_at = ?2
+                        WHERE token_address = ?3 AND holder_address = ?4
+                        "#,
+                    )
+                    .bind(holder.balance)
+                    .bind(&now)
+                    .bind(token_address)
+                    .bind(&holder.address)
+                    .execute(&mut *tx)
+                    .await?;
+                    changes.push((holder.address.as_str(), holder.balance));
+                }
+                Some(_) => {}
+            }
+        }
+
+        // Whoever is left and still marked as holding has exited
+        for (address, (_, exited)) in &known {
+            if *exited {
+                continue;
+            }
+            sqlx::query(
+                r#"
+                UPDATE holder_history
+                SET balance = 0, holding_since = NULL, exited_at = ?1, updated_at = ?1
+                WHERE token_address = ?2 AND holder_address = ?3
+                "#,
+            )
+            .bind(&now)
+            .bind(token_address)
+            .bind(address)
+            .execute(&mut *tx)
+            .await?;
+            changes.push((address.as_str(), 0.0));
+        }
+
+        for (address, balance) in changes {
+            sqlx::query(
+                r#"
+                INSERT INTO holder_balance_changes (token_address, holder_address, timestamp, balance)
+                VALUES (?1, ?2, ?3, ?4)
+                "#,
+            )
+            .bind(token_address)
+            .bind(address)
+            .bind(&now)
+            .bind(balance)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO holder_refreshes (token_address, timestamp, holder_count, price)
+            VALUES (?1, ?2, ?3, ?4)
+            "#,
+        )
+        .bind(token_address)
+        .bind(&now)
+        .bind(holders.iter().filter(|h| h.balance > 0.0).count() as i64)
+        .bind(price)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Cohort counts and balance shares at each refresh in the last
+    /// `window_days`, classified from the recorded holder history.
+    pub async fn get_holder_cohorts(
+        &self,
+        token_address: &str,
+        window_days: u32,
+        whale_percentile: Option<f64>,
+    ) -> Result<HolderCohortReport, HolderError> {
+        let whale_percentile = whale_percentile.unwrap_or(DEFAULT_WHALE_PERCENTILE);
+        if !(0.0..=100.0).contains(&whale_percentile) {
+            return Err(HolderError::Internal(format!(
+                "Whale percentile must be between 0 and 100, got {}",
+                whale_percentile
+            )));
+        }
+        let window_start = Utc::now() - chrono::Duration::days(window_days as i64);
+
+        let parse_time = |value: String| {
+            DateTime::parse_from_rfc3339(&value)
+                .map(|t| t.with_timezone(&Utc))
+                .map_err(|e| HolderError::Internal(format!("Invalid timestamp {}: {}", value, e)))
+        };
+
+        let mut changes = Vec::new();
+        for row in sqlx::query(
+            r#"
+            SELECT holder_address, timestamp, balance
+            FROM holder_balance_changes
+            WHERE token_address = ?1
+            ORDER BY timestamp ASC, id ASC
+            "#,
+        )
+        .bind(token_address)
+        .fetch_all(&self.pool)
+        .await?
+        {
+            changes.push(HolderBalanceChange {
+                holder_address: row.try_get("holder_address")?,
+                timestamp: parse_time(row.try_get("timestamp")?)?,
+                balance: row.try_get("balance")?,
+            });
+        }
+
+        let mut refreshes = Vec::new();
+        for row in sqlx::query(
+            r#"
+            SELECT timestamp, price
+            FROM holder_refreshes
+            WHERE token_address = ?1
+            ORDER BY timestamp ASC, id ASC
+            "#,
+        )
+        .bind(token_address)
+        .fetch_all(&self.pool)
+        .await?
+        {
+            refreshes.push(HolderRefreshPoint {
+                timestamp: parse_time(row.try_get("timestamp")?)?,
+                price: row.try_get("price")?,
+            });
+        }
+
+        let points = compute_cohort_points(&changes, &refreshes, window_start, whale_percentile);
+        Ok(HolderCohortReport {
+            token_address: token_address.to_string(),
+            window_days,
+            whale_percentile,
+            summary: summarize_cohorts(&points, window_days),
+            points,
+            price_history_available: refreshes.iter().any(|r| r.price.is_some()),
+        })
+    }
+
     fn generate_mock_holders(&self, _token_address: &str) -> Vec<HolderInfo> {
         // Known wallets for identification
         let known_wallets = vec![
@@ -625,6 +909,7 @@ impl HolderAnalyzer {
         let distribution = self.get_holder_distribution(token_address).await?;
         let trends = self.get_holder_trends(token_address, days).await?;
         let large_transfers = self.get_large_transfers(token_address, days).await?;
+        let cohorts = self.get_holder_cohorts(token_address, days, None).await?;
 
         Ok(HolderDataExport {
             token_address: token_address.to_string(),
@@ -632,6 +917,7 @@ impl HolderAnalyzer {
             distribution,
             trends,
             large_transfers,
+            cohorts,
         })
     }
 
@@ -652,7 +938,7 @@ impl HolderAnalyzer {
 }
 
 fn holder_db_path(app: &AppHandle) -> Result<PathBuf, HolderError> {
-    let mut path = app.path().app_data_dir().map_err(|err| {
+    let mut path = app.app_data_root().map_err(|err| {
         HolderError::Internal(format!("Unable to resolve app data directory: {err}"))
     })?;
 
@@ -690,6 +976,21 @@ pub async fn get_holder_trends(
         .map_err(|e| e.to_string())
 }
 
+/// Cohort breakdown for `mint` over the last `window` days.
+#[tauri::command]
+pub async fn get_holder_cohorts(
+    mint: String,
+    window: u32,
+    whale_percentile: Option<f64>,
+    analyzer: State<'_, SharedHolderAnalyzer>,
+) -> Result<HolderCohortReport, String> {
+    let analyzer = analyzer.read().await;
+    analyzer
+        .get_holder_cohorts(&mint, window, whale_percentile)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn get_large_transfers(
     token_address: String,
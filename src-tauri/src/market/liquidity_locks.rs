@@ -0,0 +1,931 @@
+//! Liquidity lock status for tokens launched elsewhere. Locks come from the
+//! launchpad's own lock records and from the largest holders of the token's
+//! main pool LP mint, which count as locked when a known locker program
+//! controls them or when they were sent to the incinerator.
+//!
+//! Locker program accounts are not decoded, so on-chain locks carry no unlock
+//! date; only launchpad records do. On-chain results are cached for a day.
+
+use super::token_unlocks::{scope_mints, UnlockScope};
+use crate::api_config::{rpc_endpoint, solana_rpc};
+use crate::config::{default_rpc_url, DataPaths};
+use crate::core::{JobSchedule, JobScheduler};
+use crate::deep_link::DeepLinkIntent;
+use crate::errors::AppError;
+use crate::launchpad::{LockStatus, SharedLaunchpadState};
+use crate::notifications::router::SharedNotificationRouter;
+use crate::notifications::types::{AlertPriority, HistoryCategory, NewNotification};
+use chrono::{DateTime, Duration, Utc};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+const LOCK_STORE_FILE: &str = "liquidity_locks.json";
+const RAYDIUM_POOLS_URL: &str = "https://api-v3.raydium.io/pools/info/mint";
+/// LP tokens sent here are burned, so the liquidity is locked for good.
+const INCINERATOR: &str = "1nc1nerator11111111111111111111111111111111";
+const LAUNCHPAD_LOCKER: &str = "Launchpad";
+const BURN_LOCKER: &str = "Burned";
+/// Locker programs checked out of the box, as (name, program id).
+const DEFAULT_LOCKERS: &[(&str, &str)] =
+    &[("Streamflow", "strmRqUCoQUgGUan5YhzUZa6KqdzwX5L6FpUxfmKg5m")];
+/// Resolved pools are refreshed daily.
+const CACHE_TTL_HOURS: i64 = 24;
+/// Lookups that failed are retried sooner.
+const UNKNOWN_RETRY_MINUTES: i64 = 60;
+/// Share of the pool treated as unlocked when the lock status is unknown.
+const UNKNOWN_EXPOSURE: f64 = 0.5;
+const MAX_LEAD_DAYS: u32 = 365;
+const ALERT_CHECK_INTERVAL_SECS: u64 = 6 * 60 * 60;
+const ALERT_CHECK_TIMEOUT_SECS: u64 = 15 * 60;
+
+#[derive(Debug, thiserror::Error)]
+pub enum LiquidityLockError {
+    #[error("invalid liquidity lock settings: {0}")]
+    Invalid(String),
+    #[error("lookup failed: {0}")]
+    Lookup(String),
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+}
+
+impl From<LiquidityLockError> for AppError {
+    fn from(err: LiquidityLockError) -> Self {
+        match err {
+            LiquidityLockError::Invalid(message) => {
+                AppError::validation(message).with_code("invalid_liquidity_lock_settings")
+            }
+            LiquidityLockError::Lookup(message) => AppError::internal(message),
+            LiquidityLockError::Io(err) => err.into(),
+            LiquidityLockError::Serialization(err) => err.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LiquidityLockState {
+    /// Significant locks hold past the alert lead time.
+    Locked,
+    /// A significant lock releases within the alert lead time.
+    UnlockingSoon,
+    /// The pool was resolved and less than the alert threshold is locked.
+    Unlocked,
+    /// Neither the pool's holders nor a launchpad lock could be found.
+    #[default]
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LockSource {
+    Launchpad,
+    OnChain,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LiquidityLock {
+    pub locker: String,
+    pub source: LockSource,
+    /// LP tokens in base units.
+    pub amount: f64,
+    /// `None` when the lock is on a pool other than the resolved one.
+    pub pool_share_percent: Option<f64>,
+    /// `None` for burns and for on-chain locks, whose schedules are not decoded.
+    pub unlock_at: Option<DateTime<Utc>>,
+    /// Launchpad lock id or the LP token account holding the lock.
+    pub reference: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LiquidityPool {
+    pub pool_address: String,
+    pub lp_mint: String,
+    /// LP supply in base units.
+    pub lp_supply: f64,
+    pub liquidity_usd: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LiquidityLockStatus {
+    pub mint: String,
+    pub state: LiquidityLockState,
+    pub pool: Option<LiquidityPool>,
+    /// Locks that have not released yet.
+    pub locks: Vec<LiquidityLock>,
+    /// Share of the resolved pool under active locks.
+    pub locked_percent: Option<f64>,
+    pub next_unlock_at: Option<DateTime<Utc>>,
+    /// Share of the pool unlocked or releasing within the alert lead time,
+    /// 0-1. Feeds the risk model.
+    pub exposure: f64,
+    /// Why the on-chain lookup failed, when it did.
+    pub error: Option<String>,
+    pub checked_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LockerProgram {
+    pub name: String,
+    pub program_id: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LiquidityLockSettings {
+    pub alerts_enabled: bool,
+    /// Locks of at least this share of the pool are significant: they decide
+    /// the state and raise unlock alerts.
+    pub min_pool_percent: f64,
+    /// How far ahead of an unlock it counts as soon and raises an alert.
+    pub lead_days: u32,
+    pub scopes: Vec<UnlockScope>,
+    /// Locker programs checked in addition to the built-in ones.
+    #[serde(default)]
+    pub extra_lockers: Vec<LockerProgram>,
+}
+
+impl Default for LiquidityLockSettings {
+    fn default() -> Self {
+        Self {
+            alerts_enabled: false,
+            min_pool_percent: 10.0,
+            lead_days: 7,
+            scopes: Vec::new(),
+            extra_lockers: Vec::new(),
+        }
+    }
+}
+
+impl LiquidityLockSettings {
+    fn validate(&self) -> Result<(), LiquidityLockError> {
+        if !self.min_pool_percent.is_finite()
+            || self.min_pool_percent <= 0.0
+            || self.min_pool_percent > 100.0
+        {
+            return Err(LiquidityLockError::Invalid(
+                "Alert threshold must be between 0 and 100 percent of the pool".to_string(),
+            ));
+        }
+        if self.lead_days == 0 || self.lead_days > MAX_LEAD_DAYS {
+            return Err(LiquidityLockError::Invalid(format!(
+                "Alert lead time must be between 1 and {} days",
+                MAX_LEAD_DAYS
+            )));
+        }
+        if self
+            .extra_lockers
+            .iter()
+            .any(|locker| locker.name.trim().is_empty() || locker.program_id.trim().is_empty())
+        {
+            return Err(LiquidityLockError::Invalid(
+                "Locker programs need a name and a program id".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn lockers(&self) -> HashMap<String, String> {
+        DEFAULT_LOCKERS
+            .iter()
+            .map(|(name, program)| (program.to_string(), name.to_string()))
+            .chain(
+                self.extra_lockers
+                    .iter()
+                    .map(|locker| (locker.program_id.clone(), locker.name.clone())),
+            )
+            .collect()
+    }
+}
+
+/// What the on-chain lookup found for one mint.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OnChainLocks {
+    pool: Option<LiquidityPool>,
+    locks: Vec<LiquidityLock>,
+    error: Option<String>,
+    checked_at: DateTime<Utc>,
+}
+
+impl OnChainLocks {
+    fn is_fresh(&self, now: DateTime<Utc>) -> bool {
+        let ttl = if self.pool.is_some() {
+            Duration::hours(CACHE_TTL_HOURS)
+        } else {
+            Duration::minutes(UNKNOWN_RETRY_MINUTES)
+        };
+        now - self.checked_at < ttl
+    }
+}
+
+fn is_significant(lock: &LiquidityLock, settings: &LiquidityLockSettings) -> bool {
+    lock.pool_share_percent
+        .map_or(true, |share| share >= settings.min_pool_percent)
+}
+
+fn unlocks_within_lead(
+    lock: &LiquidityLock,
+    settings: &LiquidityLockSettings,
+    now: DateTime<Utc>,
+) -> bool {
+    lock.unlock_at
+        .is_some_and(|at| at <= now + Duration::days(settings.lead_days as i64))
+}
+
+fn pool_share<'a>(locks: impl Iterator<Item = &'a LiquidityLock>) -> f64 {
+    locks
+        .filter_map(|lock| lock.pool_share_percent)
+        .sum::<f64>()
+        .min(100.0)
+}
+
+/// Combines the on-chain result with launchpad records. Expired locks are
+/// dropped; a lock with no known pool share counts as significant.
+fn assess(
+    mint: &str,
+    on_chain: &OnChainLocks,
+    launchpad: Vec<LiquidityLock>,
+    settings: &LiquidityLockSettings,
+    now: DateTime<Utc>,
+) -> LiquidityLockStatus {
+    let mut locks: Vec<LiquidityLock> = on_chain
+        .locks
+        .iter()
+        .cloned()
+        .chain(launchpad)
+        .filter(|lock| lock.unlock_at.map_or(true, |at| at > now))
+        .collect();
+    // Dated locks soonest first, then the open-ended ones.
+    locks.sort_by_key(|lock| (lock.unlock_at.is_none(), lock.unlock_at));
+
+    let locked_percent = on_chain.pool.as_ref().map(|_| pool_share(locks.iter()));
+    let unlocking: Vec<&LiquidityLock> = locks
+        .iter()
+        .filter(|lock| unlocks_within_lead(lock, settings, now))
+        .collect();
+    let unlocking_percent = pool_share(unlocking.iter().copied());
+    let unlocking_soon = unlocking.iter().any(|lock| is_significant(lock, settings));
+
+    let (state, exposure) = match locked_percent {
+        None if locks.is_empty() => (LiquidityLockState::Unknown, UNKNOWN_EXPOSURE),
+        // Launchpad locks on a pool the lookup did not resolve.
+        None if unlocking_soon => (LiquidityLockState::UnlockingSoon, 1.0),
+        None => (LiquidityLockState::Locked, 0.0),
+        Some(locked) => {
+            let exposure = ((100.0 - locked + unlocking_percent) / 100.0).clamp(0.0, 1.0);
+            let state = if locked < settings.min_pool_percent {
+                LiquidityLockState::Unlocked
+            } else if unlocking_soon {
+                LiquidityLockState::UnlockingSoon
+            } else {
+                LiquidityLockState::Locked
+            };
+            (state, exposure)
+        }
+    };
+
+    LiquidityLockStatus {
+        mint: mint.to_string(),
+        state,
+        pool: on_chain.pool.clone(),
+        next_unlock_at: locks.iter().find_map(|lock| lock.unlock_at),
+        locks,
+        locked_percent,
+        exposure,
+        error: on_chain.error.clone(),
+        checked_at: on_chain.checked_at,
+    }
+}
+
+/// A significant lock releasing within the alert lead time.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LiquidityUnlockAlert {
+    pub id: String,
+    pub mint: String,
+    pub locker: String,
+    pub unlock_at: DateTime<Utc>,
+    pub amount: f64,
+    pub pool_share_percent: Option<f64>,
+}
+
+fn upcoming_alerts(
+    status: &LiquidityLockStatus,
+    settings: &LiquidityLockSettings,
+    now: DateTime<Utc>,
+) -> Vec<LiquidityUnlockAlert> {
+    status
+        .locks
+        .iter()
+        .filter(|lock| unlocks_within_lead(lock, settings, now) && is_significant(lock, settings))
+        .filter_map(|lock| {
+            let unlock_at = lock.unlock_at?;
+            Some(LiquidityUnlockAlert {
+                id: format!(
+                    "{}:{}:{}",
+                    status.mint,
+                    unlock_at.timestamp(),
+                    lock.reference
+                ),
+                mint: status.mint.clone(),
+                locker: lock.locker.clone(),
+                unlock_at,
+                amount: lock.amount,
+                pool_share_percent: lock.pool_share_percent,
+            })
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PersistedLocks {
+    settings: LiquidityLockSettings,
+    cache: HashMap<String, OnChainLocks>,
+    /// Unlocks already alerted on, so each raises one alert.
+    alerted: HashSet<String>,
+}
+
+pub struct LiquidityLockMonitor {
+    path: Option<PathBuf>,
+    app: Option<AppHandle>,
+    client: reqwest::Client,
+    state: RwLock<PersistedLocks>,
+}
+
+pub type SharedLiquidityLockMonitor = Arc<LiquidityLockMonitor>;
+
+impl LiquidityLockMonitor {
+    pub fn new(app: &AppHandle) -> Result<Self, LiquidityLockError> {
+        let dir = app.app_data_root().map_err(|e| {
+            LiquidityLockError::Invalid(format!("App data directory not found: {}", e))
+        })?;
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(LOCK_STORE_FILE);
+        let state = if path.exists() {
+            serde_json::from_str(&fs::read_to_string(&path)?)?
+        } else {
+            PersistedLocks::default()
+        };
+        Ok(Self {
+            path: Some(path),
+            app: Some(app.clone()),
+            client: reqwest::Client::new(),
+            state: RwLock::new(state),
+        })
+    }
+
+    /// A monitor without launchpad records or persistence.
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            app: None,
+            client: reqwest::Client::new(),
+            state: RwLock::new(PersistedLocks::default()),
+        }
+    }
+
+    fn persist(&self, state: &PersistedLocks) -> Result<(), LiquidityLockError> {
+        if let Some(path) = &self.path {
+            let tmp = path.with_extension("json.tmp");
+            fs::write(&tmp, serde_json::to_vec_pretty(state)?)?;
+            fs::rename(&tmp, path)?;
+        }
+        Ok(())
+    }
+
+    pub fn settings(&self) -> LiquidityLockSettings {
+        self.state.read().settings.clone()
+    }
+
+    pub fn set_settings(&self, settings: LiquidityLockSettings) -> Result<(), LiquidityLockError> {
+        settings.validate()?;
+        let mut state = self.state.write();
+        // Locker changes alter what counts as locked.
+        if state.settings.extra_lockers != settings.extra_lockers {
+            state.cache.clear();
+        }
+        state.settings = settings;
+        self.persist(&state)
+    }
+
+    /// Lock status of `mint`, looking the pool up again when the cached
+    /// result is stale or `refresh` is set. Lookup failures report `unknown`.
+    pub async fn status(&self, mint: &str, refresh: bool) -> LiquidityLockStatus {
+        let now = Utc::now();
+        let cached = self
+            .state
+            .read()
+            .cache
+            .get(mint)
+            .filter(|entry| !refresh && entry.is_fresh(now))
+            .cloned();
+        let on_chain = match cached {
+            Some(entry) => entry,
+            None => {
+                let entry = self.look_up(mint, now).await;
+                let mut state = self.state.write();
+                state.cache.insert(mint.to_string(), entry.clone());
+                if let Err(e) = self.persist(&state) {
+                    eprintln!("Failed to persist liquidity lock cache: {}", e);
+                }
+                entry
+            }
+        };
+
+        let launchpad = self.launchpad_locks(mint, on_chain.pool.as_ref()).await;
+        assess(mint, &on_chain, launchpad, &self.settings(), now)
+    }
+
+    async fn look_up(&self, mint: &str, now: DateTime<Utc>) -> OnChainLocks {
+        let lockers = self.settings().lockers();
+        match self.on_chain_locks(mint, &lockers).await {
+            Ok((pool, locks)) => OnChainLocks {
+                pool: Some(pool),
+                locks,
+                error: None,
+                checked_at: now,
+            },
+            Err(e) => OnChainLocks {
+                pool: None,
+                locks: Vec::new(),
+                error: Some(e.to_string()),
+                checked_at: now,
+            },
+        }
+    }
+
+    /// Active launchpad locks on `mint`. Pool shares are known only for
+    /// locks on the resolved pool.
+    async fn launchpad_locks(
+        &self,
+        mint: &str,
+        pool: Option<&LiquidityPool>,
+    ) -> Vec<LiquidityLock> {
+        let Some(launchpad) = self
+            .app
+            .as_ref()
+            .and_then(|app| app.try_state::<SharedLaunchpadState>())
+        else {
+            return Vec::new();
+        };
+        let locker = launchpad.read().await.liquidity_locker.clone();
+        locker
+            .get_all_locks()
+            .into_iter()
+            .filter(|lock| lock.token_mint.as_deref() == Some(mint))
+            .filter(|lock| lock.status == LockStatus::Locked)
+            .map(|lock| {
+                let pool_share_percent = pool
+                    .filter(|pool| lock.pool_address.as_deref() == Some(pool.pool_address.as_str()))
+                    .filter(|pool| pool.lp_supply > 0.0)
+                    .map(|pool| lock.lock_amount as f64 / pool.lp_supply * 100.0);
+                LiquidityLock {
+                    locker: LAUNCHPAD_LOCKER.to_string(),
+                    source: LockSource::Launchpad,
+                    amount: lock.lock_amount as f64,
+                    pool_share_percent,
+                    unlock_at: Some(lock.unlock_date),
+                    reference: lock.lock_id.unwrap_or_default(),
+                }
+            })
+            .collect()
+    }
+
+    /// Resolves the deepest pool for `mint` and classifies its largest LP
+    /// holders by the program that controls them.
+    async fn on_chain_locks(
+        &self,
+        mint: &str,
+        lockers: &HashMap<String, String>,
+    ) -> Result<(LiquidityPool, Vec<LiquidityLock>), LiquidityLockError> {
+        let mut pool = self.deepest_pool(mint).await?;
+
+        let supply = self.rpc("getTokenSupply", json!([pool.lp_mint])).await?;
+        pool.lp_supply = parse_amount(&supply["value"]["amount"]);
+
+        let largest = self
+            .rpc("getTokenLargestAccounts", json!([pool.lp_mint]))
+            .await?;
+        let holders: Vec<(String, f64)> = largest["value"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|account| {
+                Some((
+                    account["address"].as_str()?.to_string(),
+                    parse_amount(&account["amount"]),
+                ))
+            })
+            .filter(|(_, amount)| *amount > 0.0)
+            .collect();
+        if holders.is_empty() {
+            return Ok((pool, Vec::new()));
+        }
+
+        let accounts: Vec<&str> = holders
+            .iter()
+            .map(|(address, _)| address.as_str())
+            .collect();
+        let parsed = self
+            .rpc(
+                "getMultipleAccounts",
+                json!([accounts, { "encoding": "jsonParsed" }]),
+            )
+            .await?;
+        let authorities: Vec<Option<String>> = parsed["value"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|account| {
+                account["data"]["parsed"]["info"]["owner"]
+                    .as_str()
+                    .map(str::to_string)
+            })
+            .collect();
+
+        let lookup: Vec<&str> = authorities
+            .iter()
+            .flatten()
+            .map(String::as_str)
+            .filter(|authority| *authority != INCINERATOR)
+            .collect();
+        let programs: HashMap<String, String> = if lookup.is_empty() {
+            HashMap::new()
+        } else {
+            // Only the owning program is needed, not the account data.
+            let config = json!({
+                "encoding": "base64",
+                "dataSlice": { "offset": 0, "length": 0 },
+            });
+            let owners = self
+                .rpc("getMultipleAccounts", json!([lookup, config]))
+                .await?;
+            lookup
+                .iter()
+                .zip(owners["value"].as_array().into_iter().flatten())
+                .filter_map(|(authority, account)| {
+                    Some((
+                        authority.to_string(),
+                        account["owner"].as_str()?.to_string(),
+                    ))
+                })
+                .collect()
+        };
+
+        let locks = holders
+            .iter()
+            .zip(&authorities)
+            .filter_map(|((address, amount), authority)| {
+                let authority = authority.as_deref()?;
+                let locker = if authority == INCINERATOR {
+                    BURN_LOCKER.to_string()
+                } else {
+                    lockers.get(programs.get(authority)?)?.clone()
+                };
+                Some(LiquidityLock {
+                    locker,
+                    source: LockSource::OnChain,
+                    amount: *amount,
+                    pool_share_percent: (pool.lp_supply > 0.0)
+                        .then(|| amount / pool.lp_supply * 100.0),
+                    unlock_at: None,
+                    reference: address.clone(),
+                })
+            })
+            .collect();
+        Ok((pool, locks))
+    }
+
+    async fn deepest_pool(&self, mint: &str) -> Result<LiquidityPool, LiquidityLockError> {
+        let response = self
+            .client
+            .get(RAYDIUM_POOLS_URL)
+            .query(&[
+                ("mint1", mint),
+                ("poolType", "all"),
+                ("poolSortField", "liquidity"),
+                ("sortType", "desc"),
+                ("pageSize", "1"),
+                ("page", "1"),
+            ])
+            .send()
+            .await
+            .map_err(|e| LiquidityLockError::Lookup(format!("pool lookup: {e}")))?;
+        let body: Value = response
+            .json()
+            .await
+            .map_err(|e| LiquidityLockError::Lookup(format!("pool lookup: {e}")))?;
+        let pool = &body["data"]["data"][0];
+        let (Some(pool_address), Some(lp_mint)) =
+            (pool["id"].as_str(), pool["lpMint"]["address"].as_str())
+        else {
+            return Err(LiquidityLockError::Lookup(format!(
+                "no pool with an LP mint found for {}",
+                mint
+            )));
+        };
+        Ok(LiquidityPool {
+            pool_address: pool_address.to_string(),
+            lp_mint: lp_mint.to_string(),
+            lp_supply: 0.0,
+            liquidity_usd: pool["tvl"].as_f64(),
+        })
+    }
+
+    async fn rpc(&self, method: &str, params: Value) -> Result<Value, LiquidityLockError> {
+        let url = self
+            .app
+            .as_ref()
+            .map(rpc_endpoint)
+            .unwrap_or_else(default_rpc_url);
+        solana_rpc(&url, method, params)
+            .await
+            .map_err(LiquidityLockError::Lookup)
+    }
+
+    /// Alerts from `alerts` not raised before, marking them as raised. Past
+    /// unlocks are forgotten.
+    fn take_new_alerts(
+        &self,
+        alerts: Vec<LiquidityUnlockAlert>,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<LiquidityUnlockAlert>, LiquidityLockError> {
+        let mut state = self.state.write();
+        let fresh: Vec<LiquidityUnlockAlert> = alerts
+            .into_iter()
+            .filter(|alert| !state.alerted.contains(&alert.id))
+            .collect();
+
+        let before = state.alerted.len();
+        let now_secs = now.timestamp();
+        state.alerted.retain(|id| {
+            id.split(':')
+                .nth(1)
+                .and_then(|at| at.parse::<i64>().ok())
+                .map_or(false, |at| at >= now_secs)
+        });
+        let pruned = state.alerted.len() != before;
+        for alert in &fresh {
+            state.alerted.insert(alert.id.clone());
+        }
+        if !fresh.is_empty() || pruned {
+            self.persist(&state)?;
+        }
+        Ok(fresh)
+    }
+}
+
+/// Token amounts come back as strings of base units.
+fn parse_amount(value: &Value) -> f64 {
+    value
+        .as_str()
+        .and_then(|amount| amount.parse::<f64>().ok())
+        .or_else(|| value.as_f64())
+        .unwrap_or(0.0)
+}
+
+async fn deliver_unlock_alert(app: &AppHandle, alert: &LiquidityUnlockAlert) {
+    if let Err(e) = app.emit("liquidity_unlock_alert", alert) {
+        eprintln!("Failed to emit liquidity unlock alert: {}", e);
+    }
+    let Some(router) = app.try_state::<SharedNotificationRouter>() else {
+        return;
+    };
+    let share = alert
+        .pool_share_percent
+        .map(|share| format!("{:.1}% of the pool", share))
+        .unwrap_or_else(|| "an unknown share of the pool".to_string());
+    let notification = NewNotification {
+        category: HistoryCategory::LiquidityLock,
+        priority: AlertPriority::High,
+        title: format!(
+            "Liquidity unlock for {} on {}",
+            alert.mint,
+            alert.unlock_at.format("%Y-%m-%d")
+        ),
+        message: format!("{} lock releasing {}", alert.locker, share),
+        entity: None,
+        link: Some(
+            DeepLinkIntent::Token {
+                mint: alert.mint.clone(),
+            }
+            .to_url(),
+        ),
+    };
+    if let Err(e) = router
+        .read()
+        .await
+        .send_event_notification(&notification)
+        .await
+    {
+        eprintln!("Failed to route liquidity unlock alert {}: {}", alert.id, e);
+    }
+}
+
+/// Raises an alert for every significant lock on the configured scopes that
+/// releases within the lead time.
+pub async fn check_liquidity_unlock_alerts(
+    app: &AppHandle,
+    monitor: &LiquidityLockMonitor,
+) -> Result<(), LiquidityLockError> {
+    let settings = monitor.settings();
+    if !settings.alerts_enabled {
+        return Ok(());
+    }
+    let mut mints = HashSet::new();
+    for scope in &settings.scopes {
+        match scope_mints(app, scope).await {
+            Ok(scope_mints) => mints.extend(scope_mints),
+            Err(e) => eprintln!("Skipping liquidity lock scope {:?}: {}", scope, e),
+        }
+    }
+
+    let now = Utc::now();
+    let mut alerts = Vec::new();
+    for mint in mints {
+        let status = monitor.status(&mint, false).await;
+        alerts.extend(upcoming_alerts(&status, &settings, now));
+    }
+    for alert in &monitor.take_new_alerts(alerts, now)? {
+        deliver_unlock_alert(app, alert).await;
+    }
+    Ok(())
+}
+
+pub fn schedule_liquidity_lock_alerts(
+    scheduler: &JobScheduler,
+    app: AppHandle,
+    monitor: SharedLiquidityLockMonitor,
+) -> Result<(), String> {
+    scheduler.register(
+        "liquidity_lock_alerts",
+        JobSchedule::every_from_start(std::time::Duration::from_secs(ALERT_CHECK_INTERVAL_SECS)),
+        std::time::Duration::from_secs(ALERT_CHECK_TIMEOUT_SECS),
+        move || {
+            let app = app.clone();
+            let monitor = monitor.clone();
+            async move {
+                check_liquidity_unlock_alerts(&app, &monitor)
+                    .await
+                    .map_err(|e| e.to_string())
+            }
+        },
+    )
+}
+
+#[tauri::command]
+pub async fn get_liquidity_lock_status(
+    mint: String,
+    refresh: Option<bool>,
+    monitor: State<'_, SharedLiquidityLockMonitor>,
+) -> Result<LiquidityLockStatus, AppError> {
+    Ok(monitor.status(mint.trim(), refresh.unwrap_or(false)).await)
+}
+
+#[tauri::command]
+pub async fn get_liquidity_lock_settings(
+    monitor: State<'_, SharedLiquidityLockMonitor>,
+) -> Result<LiquidityLockSettings, AppError> {
+    Ok(monitor.settings())
+}
+
+#[tauri::command]
+pub async fn set_liquidity_lock_settings(
+    settings: LiquidityLockSettings,
+    monitor: State<'_, SharedLiquidityLockMonitor>,
+) -> Result<(), AppError> {
+    Ok(monitor.set_settings(settings)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(text: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(text)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn lock(share: Option<f64>, unlock_at: Option<&str>, reference: &str) -> LiquidityLock {
+        LiquidityLock {
+            locker: "Streamflow".to_string(),
+            source: LockSource::OnChain,
+            amount: 1_000.0,
+            pool_share_percent: share,
+            unlock_at: unlock_at.map(at),
+            reference: reference.to_string(),
+        }
+    }
+
+    fn resolved(locks: Vec<LiquidityLock>) -> OnChainLocks {
+        OnChainLocks {
+            pool: Some(LiquidityPool {
+                pool_address: "POOL".to_string(),
+                lp_mint: "LP".to_string(),
+                lp_supply: 10_000.0,
+                liquidity_usd: None,
+            }),
+            locks,
+            error: None,
+            checked_at: at("2026-01-01T00:00:00Z"),
+        }
+    }
+
+    #[test]
+    fn unknown_is_distinct_from_unlocked() {
+        let settings = LiquidityLockSettings::default();
+        let now = at("2026-01-01T00:00:00Z");
+
+        let failed = OnChainLocks {
+            pool: None,
+            locks: Vec::new(),
+            error: Some("no pool".to_string()),
+            checked_at: now,
+        };
+        let unknown = assess("MINT", &failed, Vec::new(), &settings, now);
+        assert_eq!(unknown.state, LiquidityLockState::Unknown);
+        assert_eq!(unknown.exposure, UNKNOWN_EXPOSURE);
+        assert!(!failed.is_fresh(now + Duration::hours(2)));
+
+        // The pool resolved, but only 4% of it is locked.
+        let thin = resolved(vec![lock(Some(4.0), None, "a")]);
+        let unlocked = assess("MINT", &thin, Vec::new(), &settings, now);
+        assert_eq!(unlocked.state, LiquidityLockState::Unlocked);
+        assert!((unlocked.exposure - 0.96).abs() < 1e-9);
+        assert!(thin.is_fresh(now + Duration::hours(2)));
+    }
+
+    #[test]
+    fn significant_unlocks_within_the_lead_time_are_flagged() {
+        let settings = LiquidityLockSettings::default();
+        let now = at("2026-01-01T00:00:00Z");
+        let on_chain = resolved(vec![lock(Some(60.0), None, "burn")]);
+        let launchpad = vec![
+            lock(Some(30.0), Some("2026-01-05T00:00:00Z"), "soon"),
+            lock(Some(2.0), Some("2026-01-03T00:00:00Z"), "small"),
+            lock(Some(5.0), Some("2025-12-01T00:00:00Z"), "expired"),
+        ];
+
+        let status = assess("MINT", &on_chain, launchpad, &settings, now);
+        assert_eq!(status.state, LiquidityLockState::UnlockingSoon);
+        assert_eq!(status.locks.len(), 3);
+        assert_eq!(status.locked_percent, Some(92.0));
+        assert_eq!(status.next_unlock_at, Some(at("2026-01-03T00:00:00Z")));
+        // 8% unlocked now plus 32% releasing within the week.
+        assert!((status.exposure - 0.40).abs() < 1e-9);
+
+        let later = assess(
+            "MINT",
+            &on_chain,
+            vec![lock(Some(30.0), Some("2026-03-01T00:00:00Z"), "later")],
+            &settings,
+            now,
+        );
+        assert_eq!(later.state, LiquidityLockState::Locked);
+    }
+
+    #[test]
+    fn unlock_alerts_fire_once_per_lock() {
+        let monitor = LiquidityLockMonitor::in_memory();
+        let settings = monitor.settings();
+        let now = at("2026-01-01T00:00:00Z");
+        let status = assess(
+            "MINT",
+            &resolved(Vec::new()),
+            vec![
+                lock(Some(30.0), Some("2026-01-05T00:00:00Z"), "soon"),
+                lock(Some(2.0), Some("2026-01-03T00:00:00Z"), "small"),
+                // Launchpad lock on another pool: share unknown, still alerted.
+                lock(None, Some("2026-01-06T00:00:00Z"), "other"),
+            ],
+            &settings,
+            now,
+        );
+
+        let alerts = upcoming_alerts(&status, &settings, now);
+        assert_eq!(alerts.len(), 2);
+        assert_eq!(
+            monitor.take_new_alerts(alerts.clone(), now).unwrap().len(),
+            2
+        );
+        assert!(monitor.take_new_alerts(alerts, now).unwrap().is_empty());
+
+        let mut invalid = settings;
+        invalid.lead_days = 0;
+        assert!(monitor.set_settings(invalid).is_err());
+    }
+}
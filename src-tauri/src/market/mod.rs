@@ -1,26 +1,47 @@
 mod trending_coins;
 pub use trending_coins::*;
+pub mod asset_search;
+pub mod downsampling;
 pub mod drift_adapter;
+pub mod heatmap;
+pub mod holder_cohorts;
 pub mod holders;
+pub mod liquidity_locks;
 pub mod new_coins_scanner_clean;
+pub mod orderbook;
 pub mod polymarket_adapter;
 pub mod predictions;
+pub mod stablecoin_depeg;
+pub mod token_unlocks;
 pub mod top_coins;
 
+pub use asset_search::*;
+pub use downsampling::*;
 pub use drift_adapter::*;
+pub use heatmap::*;
+pub use holder_cohorts::*;
 pub use holders::*;
+pub use liquidity_locks::*;
 // Exclude HolderInfo from new_coins_scanner_clean to avoid conflict with holders::HolderInfo
 pub use new_coins_scanner_clean::{
     CreatorInfo, LiquidityInfo, NewCoin, NewCoinsScanner, NewCoinsScannerError, SafetyAnalysis,
     SafetyChecks, SafetyReport, SharedNewCoinsScanner, start_new_coins_scanner,
-    get_new_coins, get_coin_safety_report, scan_for_new_coins,
+    get_new_coins, get_coin_safety_report, scan_for_new_coins, get_scan_progress,
+    get_new_coin_screen, set_new_coin_screen, NewCoinDiscovery, NewCoinScreen, NewCoinUpdate,
+    SafetyReportStatus, ScanPhase, ScanProgress,
 };
+pub use orderbook::*;
 pub use polymarket_adapter::*;
 pub use predictions::*;
+pub use stablecoin_depeg::*;
+pub use token_unlocks::*;
 pub use top_coins::*;
 
-use reqwest;
 use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::api::http_client::{ProviderClient, ProviderProfile};
+use crate::core::cache_manager::{CacheType, SharedCacheManager};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct CoinPrice {
@@ -52,14 +73,19 @@ pub struct TokenSearchResult {
     pub logo_uri: Option<String>,
 }
 
+/// Birdeye client using the caller's key, or the configured one when it is
+/// empty.
+pub(crate) fn birdeye_client(api_key: &str) -> ProviderClient {
+    ProviderClient::builder(ProviderProfile::BIRDEYE)
+        .api_key(Some(api_key.to_string()))
+        .build()
+}
+
 // Birdeye API integration
 async fn fetch_birdeye_price(token: &str, api_key: &str) -> Result<CoinPrice, String> {
-    let client = reqwest::Client::new();
-    let url = format!("https://public-api.birdeye.so/defi/price?address={}", token);
-
-    let response = client
-        .get(&url)
-        .header("X-API-KEY", api_key)
+    let response = birdeye_client(api_key)
+        .get("/defi/price")
+        .query(&[("address", token)])
         .send()
         .await
         .map_err(|e| format!("Request failed: {}", e))?;
@@ -114,30 +140,128 @@ fn generate_mock_price(symbol: &str) -> CoinPrice {
     }
 }
 
-fn generate_mock_history(hours: i64) -> Vec<PricePoint> {
+fn generate_mock_history(from: i64, to: i64, step: i64) -> Vec<PricePoint> {
     let mut history = Vec::new();
     let mut price = 100.0;
 
-    let now = chrono::Utc::now().timestamp();
-
-    for i in (0..hours).rev() {
+    let mut timestamp = from;
+    while timestamp <= to {
         let change = rand::random_range(-2.0..2.0);
         price += change;
         let volatility = rand::random_range(0.5..2.0);
 
         history.push(PricePoint {
-            timestamp: now - (i * 3600),
+            timestamp,
             open: price,
             high: price + volatility,
             low: price - volatility,
             close: price + rand::random_range(-1.0..1.0),
             volume: rand::random_range(10000.0..100000.0),
         });
+        timestamp += step;
     }
 
     history
 }
 
+async fn fetch_birdeye_history(
+    token: &str,
+    granularity: HistoryGranularity,
+    from: i64,
+    to: i64,
+    api_key: &str,
+) -> Result<Vec<PricePoint>, String> {
+    let response = birdeye_client(api_key)
+        .get("/defi/ohlcv")
+        .query(&[
+            ("address", token.to_string()),
+            ("type", granularity.birdeye_type().to_string()),
+            ("time_from", from.to_string()),
+            ("time_to", to.to_string()),
+        ])
+        .send()
+        .await
+        .map_err(|e| format!("Request failed: {}", e))?;
+
+    #[derive(Deserialize)]
+    struct BirdeyeResponse {
+        data: BirdeyeData,
+    }
+
+    #[derive(Deserialize)]
+    struct BirdeyeData {
+        items: Vec<BirdeyeCandle>,
+    }
+
+    #[derive(Deserialize)]
+    struct BirdeyeCandle {
+        #[serde(rename = "unixTime")]
+        unix_time: i64,
+        o: f64,
+        h: f64,
+        l: f64,
+        c: f64,
+        v: Option<f64>,
+    }
+
+    let data: BirdeyeResponse = response
+        .json()
+        .await
+        .map_err(|e| format!("Parse failed: {}", e))?;
+
+    let mut points: Vec<PricePoint> = data
+        .data
+        .items
+        .into_iter()
+        .map(|candle| PricePoint {
+            timestamp: candle.unix_time,
+            open: candle.o,
+            high: candle.h,
+            low: candle.l,
+            close: candle.c,
+            volume: candle.v.unwrap_or_default(),
+        })
+        .collect();
+    points.sort_by_key(|point| point.timestamp);
+    Ok(points)
+}
+
+/// Daily candles for `address` covering the last `days` days, fetched with
+/// the configured Birdeye key and cached with other price history. Unlike
+/// `get_price_history` this never falls back to mock data, so callers can
+/// tell a token without history apart from one with it.
+pub(crate) async fn daily_price_history(
+    cache_manager: &SharedCacheManager,
+    address: &str,
+    days: i64,
+) -> Result<Vec<PricePoint>, String> {
+    let granularity = HistoryGranularity::OneDay;
+    let to = granularity.bucket_start(chrono::Utc::now().timestamp());
+    let from = to - days * granularity.seconds();
+
+    let cache_key = format!("daily_history_{}_{}_{}", address, from, to);
+    if let Some(cached) = cache_manager
+        .read()
+        .await
+        .get(&cache_key, CacheType::PriceHistory)
+        .await
+    {
+        if let Ok(points) = serde_json::from_value::<Vec<PricePoint>>(cached) {
+            return Ok(points);
+        }
+    }
+
+    let points = fetch_birdeye_history(address, granularity, from, to, "").await?;
+    if let Ok(value) = serde_json::to_value(&points) {
+        let cache = cache_manager.read().await;
+        if let Err(err) = cache.set(cache_key, value, CacheType::PriceHistory).await {
+            eprintln!("Failed to cache daily price history: {}", err);
+        }
+    }
+
+    Ok(points)
+}
+
 #[tauri::command]
 pub async fn get_coin_price(address: String, api_key: Option<String>) -> Result<CoinPrice, String> {
     // If API key provided, use real API
@@ -154,23 +278,132 @@ pub async fn get_coin_price(address: String, api_key: Option<String>) -> Result<
     Ok(generate_mock_price(&address))
 }
 
-#[tauri::command]
-pub async fn get_price_history(
-    address: String,
-    timeframe: String,
-    _api_key: Option<String>,
-) -> Result<Vec<PricePoint>, String> {
-    let hours = match timeframe.as_str() {
+/// A price series reduced to roughly `target_points`, with the granularity it
+/// was fetched at so the chart can ask for a narrower range to zoom in.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PriceHistory {
+    pub address: String,
+    pub from: i64,
+    pub to: i64,
+    pub granularity: HistoryGranularity,
+    pub method: DownsampleMethod,
+    pub target_points: usize,
+    /// Points at `granularity` before downsampling.
+    pub source_points: usize,
+    pub downsampled: bool,
+    pub points: Vec<PricePoint>,
+}
+
+fn timeframe_seconds(timeframe: &str) -> i64 {
+    let hours = match timeframe {
         "1H" => 1,
         "4H" => 4,
         "1D" => 24,
         "1W" => 168,
         "1M" => 720,
+        "3M" => 2_160,
+        "1Y" => 8_760,
         _ => 24,
     };
+    hours * 3_600
+}
+
+/// Cache key for a downsampled series. Both ends are snapped to the effective
+/// granularity so repeated requests within the same candle share an entry.
+fn price_history_cache_key(
+    address: &str,
+    granularity: HistoryGranularity,
+    method: DownsampleMethod,
+    from: i64,
+    to: i64,
+    target_points: usize,
+) -> String {
+    format!(
+        "history_{}_{}_{}_{}_{}_{}",
+        address,
+        granularity.as_str(),
+        method.as_str(),
+        granularity.bucket_start(from),
+        granularity.bucket_start(to),
+        target_points
+    )
+}
+
+/// Returns history for `timeframe`, or for `from`..`to` (unix seconds) when
+/// both are given, downsampled server-side to about `target_points`.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn get_price_history(
+    address: String,
+    timeframe: String,
+    api_key: Option<String>,
+    target_points: Option<usize>,
+    method: Option<DownsampleMethod>,
+    from: Option<i64>,
+    to: Option<i64>,
+    cache_manager: State<'_, SharedCacheManager>,
+) -> Result<PriceHistory, String> {
+    let (from, to) = match (from, to) {
+        (Some(from), Some(to)) if from < to => (from, to),
+        (Some(_), Some(_)) => return Err("`from` must be before `to`".to_string()),
+        _ => {
+            let to = chrono::Utc::now().timestamp();
+            (to - timeframe_seconds(&timeframe), to)
+        }
+    };
+    let target_points = clamp_target_points(target_points);
+    let method = method.unwrap_or_default();
+    let granularity = HistoryGranularity::for_range(to - from, target_points);
+
+    let cache_key = price_history_cache_key(&address, granularity, method, from, to, target_points);
+    if let Some(cached) = cache_manager
+        .read()
+        .await
+        .get(&cache_key, CacheType::PriceHistory)
+        .await
+    {
+        if let Ok(history) = serde_json::from_value::<PriceHistory>(cached) {
+            return Ok(history);
+        }
+    }
+
+    // Without a key, or when Birdeye fails, fall back to mock data. A
+    // failed fetch is not cached so the next request retries it.
+    let (fetched, cacheable) = match api_key.as_deref().filter(|key| !key.is_empty()) {
+        Some(key) => match fetch_birdeye_history(&address, granularity, from, to, key).await {
+            Ok(points) => (Some(points), true),
+            Err(_) => (None, false),
+        },
+        None => (None, true),
+    };
+    let source = fetched.unwrap_or_else(|| {
+        generate_mock_history(granularity.bucket_start(from), to, granularity.seconds())
+    });
+
+    let points = downsample(&source, target_points, method);
+    let history = PriceHistory {
+        address,
+        from,
+        to,
+        granularity,
+        method,
+        target_points,
+        source_points: source.len(),
+        downsampled: points.len() < source.len(),
+        points,
+    };
+
+    if let Some(value) = cacheable
+        .then(|| serde_json::to_value(&history).ok())
+        .flatten()
+    {
+        let cache = cache_manager.read().await;
+        if let Err(err) = cache.set(cache_key, value, CacheType::PriceHistory).await {
+            eprintln!("Failed to cache price history: {}", err);
+        }
+    }
 
-    // For now, return mock data
-    Ok(generate_mock_history(hours))
+    Ok(history)
 }
 
 #[tauri::command]
@@ -1,14 +1,28 @@
-use chrono::{Duration as ChronoDuration, Utc};
+use crate::config::DataPaths;
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use parking_lot::RwLock as SyncRwLock;
 use serde::{Deserialize, Serialize};
 use sqlx::{Pool, Row, Sqlite, SqlitePool};
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
-use tauri::{AppHandle, Emitter, Manager};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
 use tokio::sync::RwLock;
 
+use super::holder_cohorts::HolderCohortSummary;
+use super::holders::SharedHolderAnalyzer;
+use super::liquidity_locks::{LiquidityLockState, LiquidityLockStatus, SharedLiquidityLockMonitor};
+use crate::portfolio::{SharedTokenAnnotationStore, TokenAnnotationSummary};
+
 const NEW_COINS_DB_FILE: &str = "new_coins.db";
 const SCAN_INTERVAL_SECS: u64 = 300; // 5 minutes
+const SAFETY_COHORT_WINDOW_DAYS: u32 = 7;
+/// Coins below this liquidity never reach the event stream.
+const MIN_DISCOVERY_LIQUIDITY: f64 = 1000.0;
+/// Discovery and update events are batched into at most one emit per window.
+const EVENT_BATCH_WINDOW: Duration = Duration::from_secs(1);
+const SCREEN_SETTING_KEY: &str = "active_screen";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -30,6 +44,135 @@ pub struct NewCoin {
     pub detected_at: String,
 }
 
+/// User-defined screen applied to both `get_new_coins` and the discovery
+/// event stream. Unset limits are ignored.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NewCoinScreen {
+    pub min_safety_score: Option<i64>,
+    pub min_liquidity: Option<f64>,
+    pub min_holders: Option<i64>,
+    pub max_top_holder_percent: Option<f64>,
+    pub require_mint_revoked: bool,
+    pub require_freeze_revoked: bool,
+}
+
+impl NewCoinScreen {
+    pub fn matches(&self, coin: &NewCoin) -> bool {
+        self.min_safety_score
+            .is_none_or(|min| coin.safety_score >= min)
+            && self.min_liquidity.is_none_or(|min| coin.liquidity >= min)
+            && self.min_holders.is_none_or(|min| coin.holder_count >= min)
+            && self
+                .max_top_holder_percent
+                .is_none_or(|max| coin.top_holder_percent <= max)
+            && (!self.require_mint_revoked || coin.mint_authority_revoked)
+            && (!self.require_freeze_revoked || coin.freeze_authority_revoked)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SafetyReportStatus {
+    Pending,
+    Complete,
+}
+
+/// Payload item of the `new-coin-discovered` event.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewCoinDiscovery {
+    pub coin: NewCoin,
+    pub safety_report_status: SafetyReportStatus,
+}
+
+/// Payload item of the `new-coin-updated` event, sent once the full safety
+/// report for a discovered coin is available.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewCoinUpdate {
+    pub address: String,
+    pub safety_report_status: SafetyReportStatus,
+    pub report: SafetyReport,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScanPhase {
+    Idle,
+    Discovering,
+    Validating,
+    Analyzing,
+    Complete,
+    Failed,
+}
+
+/// Where the current (or last) scan cycle is.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanProgress {
+    pub cycle: u64,
+    pub phase: ScanPhase,
+    pub started_at: Option<String>,
+    pub completed_at: Option<String>,
+    pub discovered: usize,
+    pub validated: usize,
+    pub filtered_out: usize,
+    pub reports_pending: usize,
+    pub reports_complete: usize,
+}
+
+impl Default for ScanProgress {
+    fn default() -> Self {
+        Self {
+            cycle: 0,
+            phase: ScanPhase::Idle,
+            started_at: None,
+            completed_at: None,
+            discovered: 0,
+            validated: 0,
+            filtered_out: 0,
+            reports_pending: 0,
+            reports_complete: 0,
+        }
+    }
+}
+
+/// Collects items and releases them at most once per `window`, so bursty
+/// launch periods produce one event per second instead of one per coin.
+struct EventBatcher<T> {
+    window: Duration,
+    pending: Vec<T>,
+    last_flush: Option<Instant>,
+}
+
+impl<T> EventBatcher<T> {
+    fn new(window: Duration) -> Self {
+        Self {
+            window,
+            pending: Vec::new(),
+            last_flush: None,
+        }
+    }
+
+    fn push(&mut self, item: T, now: Instant) -> Option<Vec<T>> {
+        self.pending.push(item);
+        let due = self
+            .last_flush
+            .is_none_or(|last| now.duration_since(last) >= self.window);
+        if due {
+            self.last_flush = Some(now);
+            Some(std::mem::take(&mut self.pending))
+        } else {
+            None
+        }
+    }
+
+    fn drain(&mut self) -> Option<Vec<T>> {
+        (!self.pending.is_empty()).then(|| std::mem::take(&mut self.pending))
+    }
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct SafetyAnalysis {
     pub is_safe: bool,
@@ -49,6 +192,9 @@ pub struct SafetyReport {
     pub liquidity_info: LiquidityInfo,
     pub holder_info: HolderInfo,
     pub creator_info: CreatorInfo,
+    pub holder_cohorts: Option<HolderCohortSummary>,
+    /// The user's latest note and tags for this token.
+    pub annotation: Option<TokenAnnotationSummary>,
     pub recommendation: String,
 }
 
@@ -61,6 +207,11 @@ pub struct SafetyChecks {
     pub holder_distribution_healthy: bool,
     pub creator_reputation_good: bool,
     pub not_flagged_as_spam: bool,
+    /// False when new-holder inflow collapsed over the cohort window.
+    pub new_holder_inflow_healthy: bool,
+    /// False when pool liquidity is unlocked or a significant lock releases
+    /// within the alert lead time. An unknown lock status does not fail it.
+    pub liquidity_lock_holding: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -69,6 +220,9 @@ pub struct LiquidityInfo {
     pub total_liquidity: f64,
     pub pool_address: Option<String>,
     pub liquidity_locked: bool,
+    pub lock_state: LiquidityLockState,
+    pub locked_percent: Option<f64>,
+    pub next_unlock_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -103,6 +257,8 @@ pub enum NewCoinsScannerError {
 pub struct NewCoinsScanner {
     pool: Pool<Sqlite>,
     app_handle: Option<AppHandle>,
+    screen: SyncRwLock<NewCoinScreen>,
+    progress: SyncRwLock<ScanProgress>,
 }
 
 impl NewCoinsScanner {
@@ -111,12 +267,23 @@ impl NewCoinsScanner {
         let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
         let pool = SqlitePool::connect(&db_url).await?;
 
+        Self::with_pool(pool, Some(app.clone())).await
+    }
+
+    async fn with_pool(
+        pool: Pool<Sqlite>,
+        app_handle: Option<AppHandle>,
+    ) -> Result<Self, NewCoinsScannerError> {
         let scanner = Self {
             pool,
-            app_handle: Some(app.clone()),
+            app_handle,
+            screen: SyncRwLock::new(NewCoinScreen::default()),
+            progress: SyncRwLock::new(ScanProgress::default()),
         };
 
         scanner.initialize().await?;
+        let screen = scanner.load_screen().await?;
+        *scanner.screen.write() = screen;
         Ok(scanner)
     }
 
@@ -156,9 +323,51 @@ impl NewCoinsScanner {
         .execute(&self.pool)
         .await?;
 
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS scanner_settings (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
         Ok(())
     }
 
+    async fn load_screen(&self) -> Result<NewCoinScreen, NewCoinsScannerError> {
+        let value: Option<String> =
+            sqlx::query_scalar("SELECT value FROM scanner_settings WHERE key = ?1")
+                .bind(SCREEN_SETTING_KEY)
+                .fetch_optional(&self.pool)
+                .await?;
+
+        Ok(match value {
+            Some(json) => serde_json::from_str(&json)?,
+            None => NewCoinScreen::default(),
+        })
+    }
+
+    pub fn screen(&self) -> NewCoinScreen {
+        self.screen.read().clone()
+    }
+
+    pub async fn set_screen(&self, screen: NewCoinScreen) -> Result<(), NewCoinsScannerError> {
+        sqlx::query("INSERT OR REPLACE INTO scanner_settings (key, value) VALUES (?1, ?2)")
+            .bind(SCREEN_SETTING_KEY)
+            .bind(serde_json::to_string(&screen)?)
+            .execute(&self.pool)
+            .await?;
+        *self.screen.write() = screen;
+        Ok(())
+    }
+
+    pub fn scan_progress(&self) -> ScanProgress {
+        self.progress.read().clone()
+    }
+
     pub async fn scan_for_new_tokens(&self) -> Result<Vec<NewCoin>, NewCoinsScannerError> {
         // Mock implementation - In production, this would:
         // 1. Query Solana blockchain for new token mint accounts
@@ -168,11 +377,89 @@ impl NewCoinsScanner {
         // 5. Analyze holder distribution
         // 6. Check mint/freeze authorities
 
+        {
+            let mut progress = self.progress.write();
+            *progress = ScanProgress {
+                cycle: progress.cycle + 1,
+                phase: ScanPhase::Discovering,
+                started_at: Some(Utc::now().to_rfc3339()),
+                ..ScanProgress::default()
+            };
+        }
+
+        let result = self.run_scan_cycle().await;
+
+        let mut progress = self.progress.write();
+        progress.phase = if result.is_ok() {
+            ScanPhase::Complete
+        } else {
+            ScanPhase::Failed
+        };
+        progress.completed_at = Some(Utc::now().to_rfc3339());
+        result
+    }
+
+    async fn run_scan_cycle(&self) -> Result<Vec<NewCoin>, NewCoinsScannerError> {
         let mock_coins = self.generate_mock_new_coins().await?;
+        self.progress.write().discovered = mock_coins.len();
+        self.set_phase(ScanPhase::Validating);
+
+        let screen = self.screen();
+        let mut discoveries = EventBatcher::new(EVENT_BATCH_WINDOW);
+        let mut accepted = Vec::new();
 
-        // Store new coins in database
         for coin in &mock_coins {
             self.store_coin(coin).await?;
+
+            let passes =
+                !coin.is_spam && coin.liquidity >= MIN_DISCOVERY_LIQUIDITY && screen.matches(coin);
+            {
+                let mut progress = self.progress.write();
+                progress.validated += 1;
+                if passes {
+                    progress.reports_pending += 1;
+                } else {
+                    progress.filtered_out += 1;
+                }
+            }
+            if !passes {
+                continue;
+            }
+
+            let discovery = NewCoinDiscovery {
+                coin: coin.clone(),
+                safety_report_status: SafetyReportStatus::Pending,
+            };
+            if let Some(batch) = discoveries.push(discovery, Instant::now()) {
+                self.emit_batch("new-coin-discovered", batch);
+            }
+            accepted.push(coin);
+        }
+        if let Some(batch) = discoveries.drain() {
+            self.emit_batch("new-coin-discovered", batch);
+        }
+
+        self.set_phase(ScanPhase::Analyzing);
+        let mut updates = EventBatcher::new(EVENT_BATCH_WINDOW);
+        for coin in accepted {
+            let report = self.get_safety_report(&coin.address, None, None).await?;
+            {
+                let mut progress = self.progress.write();
+                progress.reports_pending = progress.reports_pending.saturating_sub(1);
+                progress.reports_complete += 1;
+            }
+
+            let update = NewCoinUpdate {
+                address: coin.address.clone(),
+                safety_report_status: SafetyReportStatus::Complete,
+                report,
+            };
+            if let Some(batch) = updates.push(update, Instant::now()) {
+                self.emit_batch("new-coin-updated", batch);
+            }
+        }
+        if let Some(batch) = updates.drain() {
+            self.emit_batch("new-coin-updated", batch);
         }
 
         // Emit event for high-safety coins
@@ -187,6 +474,16 @@ impl NewCoinsScanner {
         Ok(mock_coins)
     }
 
+    fn set_phase(&self, phase: ScanPhase) {
+        self.progress.write().phase = phase;
+    }
+
+    fn emit_batch<T: Serialize + Clone>(&self, event: &str, batch: Vec<T>) {
+        if let Some(app) = &self.app_handle {
+            let _ = app.emit(event, batch);
+        }
+    }
+
     async fn generate_mock_new_coins(&self) -> Result<Vec<NewCoin>, NewCoinsScannerError> {
         let now = Utc::now();
 
@@ -327,14 +624,20 @@ impl NewCoinsScanner {
                 is_spam: row.get::<i32, _>("is_spam") != 0,
                 detected_at: row.get("detected_at"),
             })
-            .collect();
+            .collect::<Vec<_>>();
 
-        Ok(coins)
+        let screen = self.screen();
+        Ok(coins
+            .into_iter()
+            .filter(|coin| screen.matches(coin))
+            .collect())
     }
 
     pub async fn get_safety_report(
         &self,
         token_address: &str,
+        holder_cohorts: Option<HolderCohortSummary>,
+        lock_status: Option<&LiquidityLockStatus>,
     ) -> Result<SafetyReport, NewCoinsScannerError> {
         let row = sqlx::query("SELECT * FROM new_coins WHERE address = ?1")
             .bind(token_address)
@@ -354,6 +657,7 @@ impl NewCoinsScanner {
         let safety_score: i64 = coin.get("safety_score");
         let is_spam = coin.get::<i32, _>("is_spam") != 0;
 
+        let lock_state = lock_status.map_or(LiquidityLockState::Unknown, |status| status.state);
         let checks = SafetyChecks {
             mint_authority_revoked: mint_revoked,
             freeze_authority_revoked: freeze_revoked,
@@ -361,12 +665,27 @@ impl NewCoinsScanner {
             holder_distribution_healthy: top_holder_percent < 50.0,
             creator_reputation_good: creator_reputation >= 0.5,
             not_flagged_as_spam: !is_spam,
+            new_holder_inflow_healthy: !holder_cohorts
+                .as_ref()
+                .is_some_and(|summary| summary.new_holder_collapse),
+            liquidity_lock_holding: !matches!(
+                lock_state,
+                LiquidityLockState::Unlocked | LiquidityLockState::UnlockingSoon
+            ),
         };
 
         let liquidity_info = LiquidityInfo {
             total_liquidity: liquidity,
-            pool_address: None,
-            liquidity_locked: false, // Mock data
+            pool_address: lock_status
+                .and_then(|status| status.pool.as_ref())
+                .map(|pool| pool.pool_address.clone()),
+            liquidity_locked: matches!(
+                lock_state,
+                LiquidityLockState::Locked | LiquidityLockState::UnlockingSoon
+            ),
+            lock_state,
+            locked_percent: lock_status.and_then(|status| status.locked_percent),
+            next_unlock_at: lock_status.and_then(|status| status.next_unlock_at),
         };
 
         let holder_info = HolderInfo {
@@ -382,13 +701,23 @@ impl NewCoinsScanner {
             suspicious_activity: creator_reputation < 0.3,
         };
 
-        let recommendation = if safety_score >= 80 {
+        let mut recommendation = if safety_score >= 80 {
             "Safe - Low risk for investment".to_string()
         } else if safety_score >= 50 {
             "Moderate - Exercise caution, do your own research".to_string()
         } else {
             "High Risk - Not recommended, likely scam".to_string()
         };
+        if !checks.new_holder_inflow_healthy {
+            recommendation.push_str(" (new holder inflow has collapsed)");
+        }
+        match lock_state {
+            LiquidityLockState::Unlocked => recommendation.push_str(" (liquidity is not locked)"),
+            LiquidityLockState::UnlockingSoon => {
+                recommendation.push_str(" (locked liquidity releases soon)")
+            }
+            LiquidityLockState::Locked | LiquidityLockState::Unknown => {}
+        }
 
         Ok(SafetyReport {
             address: coin.get("address"),
@@ -399,6 +728,8 @@ impl NewCoinsScanner {
             liquidity_info,
             holder_info,
             creator_info,
+            holder_cohorts,
+            annotation: None,
             recommendation,
         })
     }
@@ -417,23 +748,26 @@ impl NewCoinsScanner {
 
 pub type SharedNewCoinsScanner = Arc<RwLock<NewCoinsScanner>>;
 
-pub fn start_new_coins_scanner(scanner: SharedNewCoinsScanner) {
-    tauri::async_runtime::spawn(async move {
-        loop {
-            {
-                let scanner_guard = scanner.read().await;
-                if let Err(e) = scanner_guard.scan_for_new_tokens().await {
-                    eprintln!("Failed to scan for new tokens: {}", e);
+pub fn start_new_coins_scanner(app: &AppHandle, scanner: SharedNewCoinsScanner) {
+    crate::errors::spawn_supervised(app, "new_coins_scanner", move || {
+        let scanner = scanner.clone();
+        async move {
+            loop {
+                {
+                    let scanner_guard = scanner.read().await;
+                    if let Err(e) = scanner_guard.scan_for_new_tokens().await {
+                        eprintln!("Failed to scan for new tokens: {}", e);
+                    }
                 }
-            }
 
-            tokio::time::sleep(tokio::time::Duration::from_secs(SCAN_INTERVAL_SECS)).await;
+                tokio::time::sleep(tokio::time::Duration::from_secs(SCAN_INTERVAL_SECS)).await;
+            }
         }
     });
 }
 
 fn get_new_coins_db_path(app: &AppHandle) -> Result<PathBuf, NewCoinsScannerError> {
-    let mut path = app.path().app_data_dir().map_err(|err| {
+    let mut path = app.app_data_root().map_err(|err| {
         NewCoinsScannerError::Internal(format!("Unable to resolve app data directory: {err}"))
     })?;
 
@@ -459,13 +793,56 @@ pub async fn get_new_coins(
 #[tauri::command]
 pub async fn get_coin_safety_report(
     scanner: tauri::State<'_, SharedNewCoinsScanner>,
+    holder_analyzer: tauri::State<'_, SharedHolderAnalyzer>,
+    annotations: tauri::State<'_, SharedTokenAnnotationStore>,
+    liquidity_locks: tauri::State<'_, SharedLiquidityLockMonitor>,
     token_address: String,
 ) -> Result<SafetyReport, String> {
+    // Cohort history is best-effort; a token without recorded refreshes
+    // still gets a report.
+    let holder_cohorts = holder_analyzer
+        .read()
+        .await
+        .get_holder_cohorts(&token_address, SAFETY_COHORT_WINDOW_DAYS, None)
+        .await
+        .ok()
+        .map(|report| report.summary);
+    let lock_status = liquidity_locks.status(&token_address, false).await;
+
+    let scanner = scanner.read().await;
+    let mut report = scanner
+        .get_safety_report(&token_address, holder_cohorts, Some(&lock_status))
+        .await
+        .map_err(|e| e.to_string())?;
+    report.annotation = annotations.read().summary(&token_address);
+    Ok(report)
+}
+
+#[tauri::command]
+pub async fn get_scan_progress(
+    scanner: tauri::State<'_, SharedNewCoinsScanner>,
+) -> Result<ScanProgress, String> {
+    Ok(scanner.read().await.scan_progress())
+}
+
+#[tauri::command]
+pub async fn get_new_coin_screen(
+    scanner: tauri::State<'_, SharedNewCoinsScanner>,
+) -> Result<NewCoinScreen, String> {
+    Ok(scanner.read().await.screen())
+}
+
+#[tauri::command]
+pub async fn set_new_coin_screen(
+    scanner: tauri::State<'_, SharedNewCoinsScanner>,
+    screen: NewCoinScreen,
+) -> Result<NewCoinScreen, String> {
     let scanner = scanner.read().await;
     scanner
-        .get_safety_report(&token_address)
+        .set_screen(screen.clone())
         .await
-        .map_err(|e| e.to_string())
+        .map_err(|e| e.to_string())?;
+    Ok(screen)
 }
 
 #[tauri::command]
@@ -478,3 +855,65 @@ pub async fn scan_for_new_coins(
         .await
         .map_err(|e| e.to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_pool;
+
+    async fn scanner() -> NewCoinsScanner {
+        let pool = test_pool().await;
+        NewCoinsScanner::with_pool(pool, None).await.unwrap()
+    }
+
+    #[test]
+    fn test_batcher_releases_once_per_window() {
+        let start = Instant::now();
+        let mut batcher = EventBatcher::new(Duration::from_secs(1));
+
+        assert_eq!(batcher.push(1, start), Some(vec![1]));
+        assert_eq!(batcher.push(2, start + Duration::from_millis(200)), None);
+        assert_eq!(batcher.push(3, start + Duration::from_millis(600)), None);
+        assert_eq!(
+            batcher.push(4, start + Duration::from_millis(1100)),
+            Some(vec![2, 3, 4])
+        );
+        assert_eq!(batcher.push(5, start + Duration::from_millis(1300)), None);
+        assert_eq!(batcher.drain(), Some(vec![5]));
+        assert_eq!(batcher.drain(), None);
+    }
+
+    #[tokio::test]
+    async fn test_scan_progress_and_screen() {
+        let scanner = scanner().await;
+        assert_eq!(scanner.scan_progress().phase, ScanPhase::Idle);
+
+        scanner
+            .set_screen(NewCoinScreen {
+                min_safety_score: Some(80),
+                ..NewCoinScreen::default()
+            })
+            .await
+            .unwrap();
+        let coins = scanner.scan_for_new_tokens().await.unwrap();
+
+        let progress = scanner.scan_progress();
+        assert_eq!(progress.cycle, 1);
+        assert_eq!(progress.phase, ScanPhase::Complete);
+        assert_eq!(progress.discovered, coins.len());
+        assert_eq!(progress.validated, coins.len());
+        // Only SAFE (92) and MOON (85) pass the basic filters and the screen.
+        assert_eq!(progress.reports_complete, 2);
+        assert_eq!(progress.reports_pending, 0);
+        assert_eq!(progress.filtered_out, coins.len() - 2);
+
+        let listed = scanner.get_new_coins(None, None).await.unwrap();
+        assert!(listed.iter().all(|coin| coin.safety_score >= 80));
+
+        // The screen survives a reload from the settings table.
+        assert_eq!(
+            scanner.load_screen().await.unwrap().min_safety_score,
+            Some(80)
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use crate::config::DataPaths;
 use chrono::{Duration as ChronoDuration, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::{Pool, Row, Sqlite, SqlitePool};
@@ -436,7 +437,7 @@ pub fn start_new_coins_scanner(scanner: SharedNewCoinsScanner) {
 }
 
 fn get_new_coins_db_path(app: &AppHandle) -> Result<PathBuf, NewCoinsScannerError> {
-    let mut path = app.path().app_data_dir().map_err(|err| {
+    let mut path = app.app_data_root().map_err(|err| {
         NewCoinsScannerError::Internal(format!("Unable to resolve app data directory: {err}"))
     })?;
 
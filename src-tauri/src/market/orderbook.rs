@@ -0,0 +1,1017 @@
+//! Order book depth for tokens with an Openbook v2 or Phoenix market quoted
+//! in USDC or USDT. Markets are resolved through a registry persisted in the
+//! app data directory, which is filled by on-chain discovery or by hand.
+//! Books are decoded straight from the market accounts; Openbook's
+//! oracle-pegged orders are left out.
+
+use crate::config::DataPaths;
+use base64::engine::general_purpose::STANDARD as BASE64_ENGINE;
+use base64::Engine;
+use chrono::Utc;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, State};
+
+use crate::api_config::{rpc_endpoint, solana_rpc};
+
+const REGISTRY_FILE: &str = "clob_markets.json";
+
+const PHOENIX_PROGRAM_ID: &str = "PhoeNiXZ8ByJGLkxNfZRnkUfjvmuYqLR89jjFHGqdXY";
+const OPENBOOK_V2_PROGRAM_ID: &str = "opnb2LAfJYbRMAHHvqjCwQxanZn7ReEHp1k81EohpZb";
+const USDC_MINT: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+const USDT_MINT: &str = "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB";
+/// Quote mints whose books can be read as USD depth.
+const STABLE_QUOTES: [&str; 2] = [USDC_MINT, USDT_MINT];
+
+/// Bands around the mid price that depth is measured within, in percent.
+pub const DEPTH_BANDS_PCT: [f64; 2] = [1.0, 2.0];
+/// Levels kept per side in a snapshot.
+const SNAPSHOT_LEVELS: usize = 50;
+const SNAPSHOT_TTL: Duration = Duration::from_secs(2);
+/// Subscribed books are re-read at this interval and emitted only when they
+/// changed, so updates are never more frequent than this.
+const UPDATE_THROTTLE: Duration = Duration::from_secs(2);
+/// How long a failed discovery is remembered before it is retried.
+const DISCOVERY_MISS_TTL: Duration = Duration::from_secs(3_600);
+
+// Phoenix market layout: a 576-byte header, the FIFO market's fixed fields,
+// then the bid and ask red-black trees sized by the header.
+const PHOENIX_BIDS_SIZE_OFFSET: usize = 16;
+const PHOENIX_ASKS_SIZE_OFFSET: usize = 24;
+const PHOENIX_BASE_DECIMALS_OFFSET: usize = 40;
+const PHOENIX_BASE_MINT_OFFSET: usize = 48;
+const PHOENIX_BASE_LOT_SIZE_OFFSET: usize = 112;
+const PHOENIX_QUOTE_DECIMALS_OFFSET: usize = 120;
+const PHOENIX_QUOTE_MINT_OFFSET: usize = 128;
+const PHOENIX_TICK_SIZE_OFFSET: usize = 200;
+const PHOENIX_RAW_BASE_UNITS_OFFSET: usize = 312;
+const PHOENIX_BOOK_OFFSET: usize = 880;
+const PHOENIX_TREE_HEADER: usize = 32;
+const PHOENIX_NODE_SIZE: usize = 64;
+
+// Openbook v2 market and book side layouts, after the account discriminator.
+const OPENBOOK_BASE_DECIMALS_OFFSET: usize = 9;
+const OPENBOOK_QUOTE_DECIMALS_OFFSET: usize = 10;
+const OPENBOOK_BIDS_OFFSET: usize = 200;
+const OPENBOOK_ASKS_OFFSET: usize = 232;
+const OPENBOOK_QUOTE_LOT_SIZE_OFFSET: usize = 448;
+const OPENBOOK_BASE_LOT_SIZE_OFFSET: usize = 456;
+const OPENBOOK_BASE_MINT_OFFSET: usize = 576;
+const OPENBOOK_QUOTE_MINT_OFFSET: usize = 608;
+const OPENBOOK_FIXED_ROOT_OFFSET: usize = 8;
+const OPENBOOK_NODES_OFFSET: usize = 840;
+const OPENBOOK_NODE_SIZE: usize = 88;
+const OPENBOOK_MAX_NODES: usize = 1024;
+const OPENBOOK_INNER_NODE: u8 = 1;
+const OPENBOOK_LEAF_NODE: u8 = 2;
+
+#[derive(Debug, thiserror::Error)]
+pub enum OrderBookError {
+    #[error("rpc error: {0}")]
+    Rpc(String),
+    #[error("decode error: {0}")]
+    Decode(String),
+    #[error("registry error: {0}")]
+    Registry(String),
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ClobVenue {
+    Openbook,
+    Phoenix,
+}
+
+impl ClobVenue {
+    const ALL: [ClobVenue; 2] = [ClobVenue::Phoenix, ClobVenue::Openbook];
+
+    fn program_id(&self) -> &'static str {
+        match self {
+            ClobVenue::Openbook => OPENBOOK_V2_PROGRAM_ID,
+            ClobVenue::Phoenix => PHOENIX_PROGRAM_ID,
+        }
+    }
+
+    fn base_mint_offset(&self) -> usize {
+        match self {
+            ClobVenue::Openbook => OPENBOOK_BASE_MINT_OFFSET,
+            ClobVenue::Phoenix => PHOENIX_BASE_MINT_OFFSET,
+        }
+    }
+
+    fn quote_mint_offset(&self) -> usize {
+        match self {
+            ClobVenue::Openbook => OPENBOOK_QUOTE_MINT_OFFSET,
+            ClobVenue::Phoenix => PHOENIX_QUOTE_MINT_OFFSET,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum MarketSource {
+    Discovered,
+    Manual,
+}
+
+/// A token's central limit order book market.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ClobMarket {
+    pub mint: String,
+    pub venue: ClobVenue,
+    pub market: String,
+    pub quote_mint: String,
+    pub source: MarketSource,
+    pub added_at: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BookLevel {
+    pub price: f64,
+    pub size: f64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum BookSide {
+    Bids,
+    Asks,
+}
+
+/// Resting USD value within `band_pct` of the mid price.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DepthBand {
+    pub band_pct: f64,
+    pub bid_depth_usd: f64,
+    pub ask_depth_usd: f64,
+    /// `(bids - asks) / (bids + asks)`: 1.0 is all bids, -1.0 all asks.
+    pub imbalance: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderBookDepth {
+    pub mint: String,
+    pub venue: ClobVenue,
+    pub market: String,
+    pub quote_mint: String,
+    pub best_bid: Option<f64>,
+    pub best_ask: Option<f64>,
+    pub mid_price: Option<f64>,
+    /// Spread as a percentage of the mid price.
+    pub spread_pct: Option<f64>,
+    /// Imbalance within the widest depth band.
+    pub imbalance: Option<f64>,
+    pub bands: Vec<DepthBand>,
+    /// Best first, up to 50 levels per side.
+    pub bids: Vec<BookLevel>,
+    pub asks: Vec<BookLevel>,
+    pub fetched_at: String,
+}
+
+impl OrderBookDepth {
+    fn from_levels(market: &ClobMarket, bids: Vec<BookLevel>, asks: Vec<BookLevel>) -> Self {
+        let best_bid = bids.first().map(|level| level.price);
+        let best_ask = asks.first().map(|level| level.price);
+        let mid_price = match (best_bid, best_ask) {
+            (Some(bid), Some(ask)) => Some((bid + ask) / 2.0),
+            (bid, ask) => bid.or(ask),
+        };
+        let spread_pct = match (best_bid, best_ask, mid_price) {
+            (Some(bid), Some(ask), Some(mid)) if mid > 0.0 => Some((ask - bid) / mid * 100.0),
+            _ => None,
+        };
+
+        let bands: Vec<DepthBand> = mid_price
+            .map(|mid| {
+                DEPTH_BANDS_PCT
+                    .iter()
+                    .map(|&band_pct| depth_band(&bids, &asks, mid, band_pct))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self {
+            mint: market.mint.clone(),
+            venue: market.venue,
+            market: market.market.clone(),
+            quote_mint: market.quote_mint.clone(),
+            best_bid,
+            best_ask,
+            mid_price,
+            spread_pct,
+            imbalance: bands.last().and_then(|band| band.imbalance),
+            bands,
+            bids: bids.into_iter().take(SNAPSHOT_LEVELS).collect(),
+            asks: asks.into_iter().take(SNAPSHOT_LEVELS).collect(),
+            fetched_at: Utc::now().to_rfc3339(),
+        }
+    }
+
+    /// USD depth on one side within `band_pct` of the mid price.
+    pub fn depth_usd(&self, side: BookSide, band_pct: f64) -> f64 {
+        self.bands
+            .iter()
+            .find(|band| band.band_pct == band_pct)
+            .map(|band| match side {
+                BookSide::Bids => band.bid_depth_usd,
+                BookSide::Asks => band.ask_depth_usd,
+            })
+            .unwrap_or(0.0)
+    }
+
+    /// Whether the top of the book or the band depths moved.
+    fn differs_from(&self, other: &OrderBookDepth) -> bool {
+        self.best_bid != other.best_bid
+            || self.best_ask != other.best_ask
+            || self.bands != other.bands
+    }
+}
+
+fn depth_band(bids: &[BookLevel], asks: &[BookLevel], mid: f64, band_pct: f64) -> DepthBand {
+    let floor = mid * (1.0 - band_pct / 100.0);
+    let ceiling = mid * (1.0 + band_pct / 100.0);
+    let bid_depth_usd: f64 = bids
+        .iter()
+        .take_while(|level| level.price >= floor)
+        .map(|level| level.price * level.size)
+        .sum();
+    let ask_depth_usd: f64 = asks
+        .iter()
+        .take_while(|level| level.price <= ceiling)
+        .map(|level| level.price * level.size)
+        .sum();
+    let total = bid_depth_usd + ask_depth_usd;
+
+    DepthBand {
+        band_pct,
+        bid_depth_usd,
+        ask_depth_usd,
+        imbalance: (total > 0.0).then(|| (bid_depth_usd - ask_depth_usd) / total),
+    }
+}
+
+/// `Unsupported` for tokens without a central limit order book, which is an
+/// answer rather than a failure.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "camelCase")]
+pub enum OrderBookResult {
+    Supported { snapshot: OrderBookDepth },
+    Unsupported { mint: String, reason: String },
+}
+
+/// Converts venue lots into token prices and sizes.
+#[derive(Debug, Clone, Copy)]
+struct LotScale {
+    price: f64,
+    size: f64,
+}
+
+/// Sums resting orders of `(price_lots, size_lots)` into levels, best first.
+fn aggregate_levels(orders: Vec<(u64, u64)>, scale: LotScale, side: BookSide) -> Vec<BookLevel> {
+    let mut by_price: BTreeMap<u64, u64> = BTreeMap::new();
+    for (price_lots, size_lots) in orders {
+        *by_price.entry(price_lots).or_default() += size_lots;
+    }
+    let levels = by_price
+        .into_iter()
+        .map(|(price_lots, size_lots)| BookLevel {
+            price: price_lots as f64 * scale.price,
+            size: size_lots as f64 * scale.size,
+        });
+    match side {
+        BookSide::Bids => levels.rev().collect(),
+        BookSide::Asks => levels.collect(),
+    }
+}
+
+fn read_bytes<const N: usize>(data: &[u8], offset: usize) -> Result<[u8; N], OrderBookError> {
+    data.get(offset..offset + N)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| OrderBookError::Decode(format!("account data ends before offset {offset}")))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32, OrderBookError> {
+    read_bytes(data, offset).map(u32::from_le_bytes)
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Result<u64, OrderBookError> {
+    read_bytes(data, offset).map(u64::from_le_bytes)
+}
+
+fn read_pubkey(data: &[u8], offset: usize) -> Result<String, OrderBookError> {
+    read_bytes::<32>(data, offset).map(|key| bs58::encode(key).into_string())
+}
+
+fn decimals_scale(decimals: u32) -> f64 {
+    10f64.powi(decimals as i32)
+}
+
+/// Decodes a Phoenix market account into bids and asks.
+fn decode_phoenix_book(data: &[u8]) -> Result<(Vec<BookLevel>, Vec<BookLevel>), OrderBookError> {
+    let bids_size = read_u64(data, PHOENIX_BIDS_SIZE_OFFSET)? as usize;
+    let asks_size = read_u64(data, PHOENIX_ASKS_SIZE_OFFSET)? as usize;
+    let base_lot_size = read_u64(data, PHOENIX_BASE_LOT_SIZE_OFFSET)?;
+    let tick_size = read_u64(data, PHOENIX_TICK_SIZE_OFFSET)?;
+    let raw_base_units = read_u32(data, PHOENIX_RAW_BASE_UNITS_OFFSET)?.max(1);
+    let scale = LotScale {
+        price: tick_size as f64
+            / decimals_scale(read_u32(data, PHOENIX_QUOTE_DECIMALS_OFFSET)?)
+            / f64::from(raw_base_units),
+        size: base_lot_size as f64 / decimals_scale(read_u32(data, PHOENIX_BASE_DECIMALS_OFFSET)?),
+    };
+
+    let asks_offset = PHOENIX_BOOK_OFFSET + PHOENIX_TREE_HEADER + PHOENIX_NODE_SIZE * bids_size;
+    let bids = phoenix_orders(data, PHOENIX_BOOK_OFFSET, bids_size)?;
+    let asks = phoenix_orders(data, asks_offset, asks_size)?;
+    Ok((
+        aggregate_levels(bids, scale, BookSide::Bids),
+        aggregate_levels(asks, scale, BookSide::Asks),
+    ))
+}
+
+/// Walks a Phoenix order tree from its root. Nodes are numbered from 1, with
+/// 0 as the empty sentinel; each holds its left and right children, then the
+/// order's price in ticks and, after the sequence number and trader, its
+/// size in base lots.
+fn phoenix_orders(
+    data: &[u8],
+    tree_offset: usize,
+    capacity: usize,
+) -> Result<Vec<(u64, u64)>, OrderBookError> {
+    let nodes_offset = tree_offset + PHOENIX_TREE_HEADER;
+    let mut orders = Vec::new();
+    let mut pending = vec![read_u32(data, tree_offset)? as usize];
+    while let Some(index) = pending.pop() {
+        if index == 0 {
+            continue;
+        }
+        if index > capacity || orders.len() >= capacity {
+            return Err(OrderBookError::Decode(
+                "malformed Phoenix order tree".to_string(),
+            ));
+        }
+        let node = nodes_offset + (index - 1) * PHOENIX_NODE_SIZE;
+        pending.push(read_u32(data, node)? as usize);
+        pending.push(read_u32(data, node + 4)? as usize);
+        orders.push((read_u64(data, node + 16)?, read_u64(data, node + 40)?));
+    }
+    Ok(orders)
+}
+
+/// The parts of an Openbook v2 market needed to read its book.
+#[derive(Debug, Clone)]
+struct OpenbookMarket {
+    bids: String,
+    asks: String,
+    scale: LotScale,
+}
+
+fn decode_openbook_market(data: &[u8]) -> Result<OpenbookMarket, OrderBookError> {
+    let base_decimals = read_bytes::<1>(data, OPENBOOK_BASE_DECIMALS_OFFSET)?[0];
+    let quote_decimals = read_bytes::<1>(data, OPENBOOK_QUOTE_DECIMALS_OFFSET)?[0];
+    let quote_lot_size = read_u64(data, OPENBOOK_QUOTE_LOT_SIZE_OFFSET)? as f64;
+    let base_lot_size = read_u64(data, OPENBOOK_BASE_LOT_SIZE_OFFSET)? as f64;
+    if base_lot_size <= 0.0 {
+        return Err(OrderBookError::Decode(
+            "Openbook market has no lot size".to_string(),
+        ));
+    }
+    let base_scale = decimals_scale(u32::from(base_decimals));
+
+    Ok(OpenbookMarket {
+        bids: read_pubkey(data, OPENBOOK_BIDS_OFFSET)?,
+        asks: read_pubkey(data, OPENBOOK_ASKS_OFFSET)?,
+        scale: LotScale {
+            price: quote_lot_size / base_lot_size * base_scale
+                / decimals_scale(u32::from(quote_decimals)),
+            size: base_lot_size / base_scale,
+        },
+    })
+}
+
+/// Walks the fixed-price tree of an Openbook v2 book side. Leaf keys carry
+/// the price in lots in their upper 64 bits.
+fn openbook_orders(data: &[u8]) -> Result<Vec<(u64, u64)>, OrderBookError> {
+    let root = read_u32(data, OPENBOOK_FIXED_ROOT_OFFSET)? as usize;
+    if read_u32(data, OPENBOOK_FIXED_ROOT_OFFSET + 4)? == 0 {
+        return Ok(Vec::new());
+    }
+
+    let mut orders = Vec::new();
+    let mut pending = vec![root];
+    let mut visited = 0;
+    while let Some(index) = pending.pop() {
+        visited += 1;
+        if index >= OPENBOOK_MAX_NODES || visited > OPENBOOK_MAX_NODES {
+            return Err(OrderBookError::Decode(
+                "malformed Openbook order tree".to_string(),
+            ));
+        }
+        let node = OPENBOOK_NODES_OFFSET + index * OPENBOOK_NODE_SIZE;
+        match read_bytes::<1>(data, node)?[0] {
+            OPENBOOK_INNER_NODE => {
+                pending.push(read_u32(data, node + 24)? as usize);
+                pending.push(read_u32(data, node + 28)? as usize);
+            }
+            OPENBOOK_LEAF_NODE => {
+                orders.push((read_u64(data, node + 16)?, read_u64(data, node + 56)?));
+            }
+            tag => {
+                return Err(OrderBookError::Decode(format!(
+                    "unexpected Openbook node tag {tag}"
+                )))
+            }
+        }
+    }
+    Ok(orders)
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RegistryFile {
+    markets: Vec<ClobMarket>,
+}
+
+/// Maps token mints to their order book market. Persisted when a path is
+/// available; a registry that cannot be saved still works for the session.
+struct MarketRegistry {
+    path: Option<PathBuf>,
+    markets: HashMap<String, ClobMarket>,
+}
+
+impl MarketRegistry {
+    fn load(path: Option<PathBuf>) -> Self {
+        let markets = path
+            .as_ref()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|contents| serde_json::from_str::<RegistryFile>(&contents).ok())
+            .map(|file| {
+                file.markets
+                    .into_iter()
+                    .map(|market| (market.mint.clone(), market))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self { path, markets }
+    }
+
+    fn get(&self, mint: &str) -> Option<ClobMarket> {
+        self.markets.get(mint).cloned()
+    }
+
+    fn insert(&mut self, market: ClobMarket) -> Result<(), OrderBookError> {
+        self.markets.insert(market.mint.clone(), market);
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let mut markets: Vec<ClobMarket> = self.markets.values().cloned().collect();
+        markets.sort_by(|a, b| a.mint.cmp(&b.mint));
+        let contents = serde_json::to_string_pretty(&RegistryFile { markets })
+            .map_err(|e| OrderBookError::Registry(e.to_string()))?;
+        std::fs::write(path, contents).map_err(|e| OrderBookError::Registry(e.to_string()))
+    }
+}
+
+fn registry_path(app: &AppHandle) -> Option<PathBuf> {
+    let dir = app.app_data_root().ok()?;
+    std::fs::create_dir_all(&dir).ok()?;
+    Some(dir.join(REGISTRY_FILE))
+}
+
+struct Subscription {
+    subscribers: usize,
+    task: tauri::async_runtime::JoinHandle<()>,
+}
+
+pub struct OrderBookService {
+    app: AppHandle,
+    registry: Mutex<MarketRegistry>,
+    discovery_misses: Mutex<HashMap<String, Instant>>,
+    openbook_markets: Mutex<HashMap<String, OpenbookMarket>>,
+    cache: Mutex<HashMap<String, (Instant, OrderBookDepth)>>,
+    subscriptions: Mutex<HashMap<String, Subscription>>,
+}
+
+pub type SharedOrderBookService = Arc<OrderBookService>;
+
+impl OrderBookService {
+    pub fn new(app: &AppHandle) -> Self {
+        Self {
+            app: app.clone(),
+            registry: Mutex::new(MarketRegistry::load(registry_path(app))),
+            discovery_misses: Mutex::new(HashMap::new()),
+            openbook_markets: Mutex::new(HashMap::new()),
+            cache: Mutex::new(HashMap::new()),
+            subscriptions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The current book for `mint`, at most two seconds old.
+    pub async fn snapshot(&self, mint: &str) -> Result<OrderBookResult, OrderBookError> {
+        if let Some((fetched, snapshot)) = self.cache.lock().get(mint) {
+            if fetched.elapsed() < SNAPSHOT_TTL {
+                return Ok(OrderBookResult::Supported {
+                    snapshot: snapshot.clone(),
+                });
+            }
+        }
+
+        let Some(market) = self.resolve_market(mint).await? else {
+            let reason = if STABLE_QUOTES.contains(&mint) {
+                "Stablecoins are the quote side of order books, not a market of their own"
+            } else {
+                "No Openbook or Phoenix market quoted in USDC or USDT is known for this token"
+            };
+            return Ok(OrderBookResult::Unsupported {
+                mint: mint.to_string(),
+                reason: reason.to_string(),
+            });
+        };
+
+        let (bids, asks) = self.fetch_levels(&market).await?;
+        let snapshot = OrderBookDepth::from_levels(&market, bids, asks);
+        self.cache
+            .lock()
+            .insert(mint.to_string(), (Instant::now(), snapshot.clone()));
+        Ok(OrderBookResult::Supported { snapshot })
+    }
+
+    /// USD depth on `side` within `band_pct` of the mid price, or `None` for
+    /// tokens without an order book.
+    pub async fn depth_usd(
+        &self,
+        mint: &str,
+        side: BookSide,
+        band_pct: f64,
+    ) -> Result<Option<f64>, OrderBookError> {
+        Ok(match self.snapshot(mint).await? {
+            OrderBookResult::Supported { snapshot } => Some(snapshot.depth_usd(side, band_pct)),
+            OrderBookResult::Unsupported { .. } => None,
+        })
+    }
+
+    /// Starts streaming `mint`'s book as `orderbook_update` events, or adds a
+    /// subscriber to a running stream. Returns the current book.
+    pub async fn subscribe(
+        self: &Arc<Self>,
+        mint: &str,
+    ) -> Result<OrderBookResult, OrderBookError> {
+        let result = self.snapshot(mint).await?;
+        let OrderBookResult::Supported { snapshot } = &result else {
+            return Ok(result);
+        };
+
+        let mut subscriptions = self.subscriptions.lock();
+        match subscriptions.get_mut(mint) {
+            Some(subscription) => subscription.subscribers += 1,
+            None => {
+                let task = tauri::async_runtime::spawn(
+                    self.clone().stream(mint.to_string(), snapshot.clone()),
+                );
+                subscriptions.insert(
+                    mint.to_string(),
+                    Subscription {
+                        subscribers: 1,
+                        task,
+                    },
+                );
+            }
+        }
+        Ok(result)
+    }
+
+    /// Drops a subscriber; the stream stops with the last one. Returns
+    /// whether the stream stopped.
+    pub fn unsubscribe(&self, mint: &str) -> bool {
+        let mut subscriptions = self.subscriptions.lock();
+        let Some(subscription) = subscriptions.get_mut(mint) else {
+            return false;
+        };
+        subscription.subscribers = subscription.subscribers.saturating_sub(1);
+        if subscription.subscribers > 0 {
+            return false;
+        }
+        if let Some(subscription) = subscriptions.remove(mint) {
+            subscription.task.abort();
+        }
+        true
+    }
+
+    async fn stream(self: Arc<Self>, mint: String, mut last: OrderBookDepth) {
+        let mut tick = tokio::time::interval(UPDATE_THROTTLE);
+        tick.tick().await;
+        loop {
+            tick.tick().await;
+            let snapshot = match self.snapshot(&mint).await {
+                Ok(OrderBookResult::Supported { snapshot }) => snapshot,
+                Ok(OrderBookResult::Unsupported { .. }) => continue,
+                Err(e) => {
+                    eprintln!("Failed to refresh order book for {}: {}", mint, e);
+                    continue;
+                }
+            };
+            if !snapshot.differs_from(&last) {
+                continue;
+            }
+            if let Err(e) = self.app.emit("orderbook_update", &snapshot) {
+                eprintln!("Failed to emit order book update: {}", e);
+            }
+            last = snapshot;
+        }
+    }
+
+    /// Adds or replaces the market used for `mint` after checking that the
+    /// account belongs to the venue and trades `mint` against USDC or USDT.
+    pub async fn register_market(
+        &self,
+        mint: &str,
+        venue: ClobVenue,
+        market: &str,
+    ) -> Result<ClobMarket, OrderBookError> {
+        let (owner, data) = self
+            .fetch_accounts(&[market.to_string()])
+            .await?
+            .pop()
+            .flatten()
+            .ok_or_else(|| OrderBookError::Registry(format!("market {market} not found")))?;
+        if owner != venue.program_id() {
+            return Err(OrderBookError::Registry(format!(
+                "{market} is not a {venue:?} market"
+            )));
+        }
+        if read_pubkey(&data, venue.base_mint_offset())? != mint {
+            return Err(OrderBookError::Registry(format!(
+                "{market} does not trade {mint}"
+            )));
+        }
+        let quote_mint = read_pubkey(&data, venue.quote_mint_offset())?;
+        if !STABLE_QUOTES.contains(&quote_mint.as_str()) {
+            return Err(OrderBookError::Registry(format!(
+                "{market} is quoted in {quote_mint}, not USDC or USDT"
+            )));
+        }
+
+        let entry = ClobMarket {
+            mint: mint.to_string(),
+            venue,
+            market: market.to_string(),
+            quote_mint,
+            source: MarketSource::Manual,
+            added_at: Utc::now().to_rfc3339(),
+        };
+        self.registry.lock().insert(entry.clone())?;
+        self.cache.lock().remove(mint);
+        self.discovery_misses.lock().remove(mint);
+        Ok(entry)
+    }
+
+    async fn resolve_market(&self, mint: &str) -> Result<Option<ClobMarket>, OrderBookError> {
+        if let Some(market) = self.registry.lock().get(mint) {
+            return Ok(Some(market));
+        }
+        if STABLE_QUOTES.contains(&mint) {
+            return Ok(None);
+        }
+        if let Some(missed) = self.discovery_misses.lock().get(mint) {
+            if missed.elapsed() < DISCOVERY_MISS_TTL {
+                return Ok(None);
+            }
+        }
+
+        for venue in ClobVenue::ALL {
+            for quote_mint in STABLE_QUOTES {
+                if let Some(market) = self.discover(venue, mint, quote_mint).await? {
+                    let entry = ClobMarket {
+                        mint: mint.to_string(),
+                        venue,
+                        market,
+                        quote_mint: quote_mint.to_string(),
+                        source: MarketSource::Discovered,
+                        added_at: Utc::now().to_rfc3339(),
+                    };
+                    if let Err(e) = self.registry.lock().insert(entry.clone()) {
+                        eprintln!("Failed to save order book market for {}: {}", mint, e);
+                    }
+                    return Ok(Some(entry));
+                }
+            }
+        }
+
+        self.discovery_misses
+            .lock()
+            .insert(mint.to_string(), Instant::now());
+        Ok(None)
+    }
+
+    /// Finds a `venue` market trading `mint` against `quote_mint`.
+    async fn discover(
+        &self,
+        venue: ClobVenue,
+        mint: &str,
+        quote_mint: &str,
+    ) -> Result<Option<String>, OrderBookError> {
+        let accounts = self
+            .rpc(
+                "getProgramAccounts",
+                json!([
+                    venue.program_id(),
+                    {
+                        "encoding": "base64",
+                        "dataSlice": { "offset": 0, "length": 0 },
+                        "filters": [
+                            { "memcmp": { "offset": venue.base_mint_offset(), "bytes": mint } },
+                            { "memcmp": { "offset": venue.quote_mint_offset(), "bytes": quote_mint } },
+                        ],
+                    },
+                ]),
+            )
+            .await?;
+        Ok(accounts
+            .as_array()
+            .and_then(|accounts| accounts.first())
+            .and_then(|account| account.get("pubkey"))
+            .and_then(Value::as_str)
+            .map(str::to_string))
+    }
+
+    async fn fetch_levels(
+        &self,
+        market: &ClobMarket,
+    ) -> Result<(Vec<BookLevel>, Vec<BookLevel>), OrderBookError> {
+        match market.venue {
+            ClobVenue::Phoenix => {
+                let data = self.fetch_account(&market.market).await?;
+                decode_phoenix_book(&data)
+            }
+            ClobVenue::Openbook => {
+                let layout = self.openbook_market(&market.market).await?;
+                let mut sides = self
+                    .fetch_accounts(&[layout.bids.clone(), layout.asks.clone()])
+                    .await?
+                    .into_iter();
+                let mut side =
+                    |name: &str| {
+                        sides.next().flatten().map(|(_, data)| data).ok_or_else(|| {
+                            OrderBookError::Rpc(format!("Openbook {name} not found"))
+                        })
+                    };
+                let bids = openbook_orders(&side("bids")?)?;
+                let asks = openbook_orders(&side("asks")?)?;
+                Ok((
+                    aggregate_levels(bids, layout.scale, BookSide::Bids),
+                    aggregate_levels(asks, layout.scale, BookSide::Asks),
+                ))
+            }
+        }
+    }
+
+    /// Openbook market layouts never change, so each is read once.
+    async fn openbook_market(&self, market: &str) -> Result<OpenbookMarket, OrderBookError> {
+        if let Some(layout) = self.openbook_markets.lock().get(market) {
+            return Ok(layout.clone());
+        }
+        let layout = decode_openbook_market(&self.fetch_account(market).await?)?;
+        self.openbook_markets
+            .lock()
+            .insert(market.to_string(), layout.clone());
+        Ok(layout)
+    }
+
+    async fn fetch_account(&self, address: &str) -> Result<Vec<u8>, OrderBookError> {
+        self.fetch_accounts(&[address.to_string()])
+            .await?
+            .pop()
+            .flatten()
+            .map(|(_, data)| data)
+            .ok_or_else(|| OrderBookError::Rpc(format!("account {address} not found")))
+    }
+
+    /// Owner and data of each account, `None` for accounts that don't exist.
+    async fn fetch_accounts(
+        &self,
+        addresses: &[String],
+    ) -> Result<Vec<Option<(String, Vec<u8>)>>, OrderBookError> {
+        let result = self
+            .rpc(
+                "getMultipleAccounts",
+                json!([addresses, { "encoding": "base64", "commitment": "confirmed" }]),
+            )
+            .await?;
+        let accounts = result["value"].as_array().cloned().unwrap_or_default();
+        Ok(accounts
+            .iter()
+            .map(|account| {
+                let owner = account.get("owner")?.as_str()?.to_string();
+                let encoded = account.get("data")?.get(0)?.as_str()?;
+                let data = BASE64_ENGINE.decode(encoded).ok()?;
+                Some((owner, data))
+            })
+            .collect())
+    }
+
+    async fn rpc(&self, method: &str, params: Value) -> Result<Value, OrderBookError> {
+        solana_rpc(&rpc_endpoint(&self.app), method, params)
+            .await
+            .map_err(OrderBookError::Rpc)
+    }
+}
+
+// Tauri commands
+#[tauri::command]
+pub async fn get_orderbook_snapshot(
+    mint: String,
+    orderbook: State<'_, SharedOrderBookService>,
+) -> Result<OrderBookResult, String> {
+    orderbook.snapshot(&mint).await.map_err(|e| e.to_string())
+}
+
+/// Streams `mint`'s book as `orderbook_update` events, at most one every two
+/// seconds and only when it changed. Returns the current book.
+#[tauri::command]
+pub async fn subscribe_orderbook(
+    mint: String,
+    orderbook: State<'_, SharedOrderBookService>,
+) -> Result<OrderBookResult, String> {
+    orderbook
+        .inner()
+        .subscribe(&mint)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn unsubscribe_orderbook(
+    mint: String,
+    orderbook: State<'_, SharedOrderBookService>,
+) -> Result<bool, String> {
+    Ok(orderbook.unsubscribe(&mint))
+}
+
+#[tauri::command]
+pub async fn register_orderbook_market(
+    mint: String,
+    venue: ClobVenue,
+    market: String,
+    orderbook: State<'_, SharedOrderBookService>,
+) -> Result<ClobMarket, String> {
+    orderbook
+        .register_market(&mint, venue, &market)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn market() -> ClobMarket {
+        ClobMarket {
+            mint: "mint".to_string(),
+            venue: ClobVenue::Phoenix,
+            market: "market".to_string(),
+            quote_mint: USDC_MINT.to_string(),
+            source: MarketSource::Manual,
+            added_at: Utc::now().to_rfc3339(),
+        }
+    }
+
+    fn level(price: f64, size: f64) -> BookLevel {
+        BookLevel { price, size }
+    }
+
+    fn put(data: &mut [u8], offset: usize, bytes: &[u8]) {
+        data[offset..offset + bytes.len()].copy_from_slice(bytes);
+    }
+
+    fn assert_levels(actual: &[BookLevel], expected: &[(f64, f64)]) {
+        assert_eq!(actual.len(), expected.len());
+        for (level, (price, size)) in actual.iter().zip(expected) {
+            assert!((level.price - price).abs() < 1e-9, "{level:?}");
+            assert!((level.size - size).abs() < 1e-9, "{level:?}");
+        }
+    }
+
+    #[test]
+    fn snapshot_measures_spread_and_depth_within_bands() {
+        let bids = vec![level(99.5, 10.0), level(98.5, 10.0), level(97.0, 100.0)];
+        let asks = vec![level(100.5, 2.0), level(101.5, 4.0), level(103.0, 50.0)];
+        let snapshot = OrderBookDepth::from_levels(&market(), bids, asks);
+
+        assert_eq!(snapshot.mid_price, Some(100.0));
+        assert!((snapshot.spread_pct.unwrap() - 1.0).abs() < 1e-9);
+
+        // ±1%: 99.5 bid against 100.5 ask.
+        let tight = &snapshot.bands[0];
+        assert_eq!(tight.bid_depth_usd, 995.0);
+        assert_eq!(tight.ask_depth_usd, 201.0);
+        // ±2%: both of the first two levels.
+        assert_eq!(snapshot.depth_usd(BookSide::Bids, 2.0), 995.0 + 985.0);
+        assert_eq!(snapshot.depth_usd(BookSide::Asks, 2.0), 201.0 + 406.0);
+        let imbalance = snapshot.imbalance.unwrap();
+        assert!((imbalance - (1980.0 - 607.0) / (1980.0 + 607.0)).abs() < 1e-9);
+
+        let one_sided = OrderBookDepth::from_levels(&market(), vec![level(1.0, 5.0)], vec![]);
+        assert_eq!(one_sided.spread_pct, None);
+        assert_eq!(one_sided.imbalance, Some(1.0));
+    }
+
+    #[test]
+    fn decodes_phoenix_order_trees() {
+        let bids_size = 4;
+        let asks_size = 2;
+        let mut data =
+            vec![0u8; PHOENIX_BOOK_OFFSET + 2 * PHOENIX_TREE_HEADER + PHOENIX_NODE_SIZE * 6];
+        let data = &mut data;
+        put(
+            data,
+            PHOENIX_BIDS_SIZE_OFFSET,
+            &(bids_size as u64).to_le_bytes(),
+        );
+        put(
+            data,
+            PHOENIX_ASKS_SIZE_OFFSET,
+            &(asks_size as u64).to_le_bytes(),
+        );
+        put(data, PHOENIX_BASE_DECIMALS_OFFSET, &9u32.to_le_bytes());
+        put(
+            data,
+            PHOENIX_BASE_LOT_SIZE_OFFSET,
+            &1_000_000u64.to_le_bytes(),
+        );
+        put(data, PHOENIX_QUOTE_DECIMALS_OFFSET, &6u32.to_le_bytes());
+        put(data, PHOENIX_TICK_SIZE_OFFSET, &1_000u64.to_le_bytes());
+        put(data, PHOENIX_RAW_BASE_UNITS_OFFSET, &1u32.to_le_bytes());
+
+        // Bids: root 2 with children 1 and 3, two of them at the same price.
+        let bid_nodes = PHOENIX_BOOK_OFFSET + PHOENIX_TREE_HEADER;
+        put(data, PHOENIX_BOOK_OFFSET, &2u32.to_le_bytes());
+        let node = |index: usize| bid_nodes + (index - 1) * PHOENIX_NODE_SIZE;
+        put(data, node(2), &1u32.to_le_bytes());
+        put(data, node(2) + 4, &3u32.to_le_bytes());
+        for (index, ticks, lots) in [
+            (1, 149_000u64, 500u64),
+            (2, 150_000, 1_000),
+            (3, 150_000, 250),
+        ] {
+            put(data, node(index) + 16, &ticks.to_le_bytes());
+            put(data, node(index) + 40, &lots.to_le_bytes());
+        }
+        // Asks: a single order.
+        let asks_tree = bid_nodes + PHOENIX_NODE_SIZE * bids_size;
+        put(data, asks_tree, &1u32.to_le_bytes());
+        put(
+            data,
+            asks_tree + PHOENIX_TREE_HEADER + 16,
+            &151_000u64.to_le_bytes(),
+        );
+        put(
+            data,
+            asks_tree + PHOENIX_TREE_HEADER + 40,
+            &2_000u64.to_le_bytes(),
+        );
+
+        let (bids, asks) = decode_phoenix_book(data).unwrap();
+        assert_levels(&bids, &[(150.0, 1.25), (149.0, 0.5)]);
+        assert_levels(&asks, &[(151.0, 2.0)]);
+
+        // A tree pointing past its capacity is rejected rather than read.
+        put(data, PHOENIX_BOOK_OFFSET, &9u32.to_le_bytes());
+        assert!(decode_phoenix_book(data).is_err());
+    }
+
+    #[test]
+    fn decodes_openbook_fixed_price_leaves() {
+        let mut data = vec![0u8; OPENBOOK_NODES_OFFSET + OPENBOOK_NODE_SIZE * 3];
+        let data = &mut data;
+        put(data, OPENBOOK_FIXED_ROOT_OFFSET, &0u32.to_le_bytes());
+        put(data, OPENBOOK_FIXED_ROOT_OFFSET + 4, &2u32.to_le_bytes());
+        let node = |index: usize| OPENBOOK_NODES_OFFSET + index * OPENBOOK_NODE_SIZE;
+        put(data, node(0), &[OPENBOOK_INNER_NODE]);
+        put(data, node(0) + 24, &1u32.to_le_bytes());
+        put(data, node(0) + 28, &2u32.to_le_bytes());
+        for (index, price_lots, quantity) in [(1, 1_500u64, 3u64), (2, 1_490, 7)] {
+            put(data, node(index), &[OPENBOOK_LEAF_NODE]);
+            put(data, node(index) + 16, &price_lots.to_le_bytes());
+            put(data, node(index) + 56, &quantity.to_le_bytes());
+        }
+
+        let scale = LotScale {
+            price: 0.1,
+            size: 0.01,
+        };
+        let bids = aggregate_levels(openbook_orders(data).unwrap(), scale, BookSide::Bids);
+        assert_levels(&bids, &[(150.0, 0.03), (149.0, 0.07)]);
+
+        // An empty tree is an empty side, whatever its root points at.
+        put(data, OPENBOOK_FIXED_ROOT_OFFSET + 4, &0u32.to_le_bytes());
+        assert!(openbook_orders(data).unwrap().is_empty());
+    }
+}
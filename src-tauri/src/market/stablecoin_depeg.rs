@@ -0,0 +1,814 @@
+//! Stablecoin depeg monitoring.
+//!
+//! Each configured stablecoin is priced on several venues and the median is
+//! compared with its peg, so a single bad feed neither raises nor hides a
+//! depeg. Deviations are graded into severity tiers with hysteresis on the
+//! way down, alerts escalate with the tier, and a deep enough depeg can block
+//! trades that buy into the stablecoin through the safety engine.
+
+use crate::api_analytics::{send_metered, RequestPriority};
+use crate::config::DataPaths;
+use crate::core::{get_price_engine, JobSchedule, JobScheduler};
+use crate::deep_link::DeepLinkIntent;
+use crate::errors::AppError;
+use crate::notifications::router::SharedNotificationRouter;
+use crate::notifications::types::{AlertPriority, HistoryCategory, NewNotification};
+use crate::trading::SharedSafetyEngine;
+use chrono::{DateTime, Duration, Utc};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+const DEPEG_STORE_FILE: &str = "stablecoin_depeg.json";
+const JUPITER_PRICE_URL: &str = "https://lite-api.jup.ag/price/v2";
+const RAYDIUM_PRICE_URL: &str = "https://api-v3.raydium.io/mint/price";
+const USDC_MINT: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+const USDT_MINT: &str = "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB";
+const CHECK_INTERVAL_SECS: u64 = 2 * 60;
+const CHECK_TIMEOUT_SECS: u64 = 60;
+const HISTORY_HOURS: i64 = 24;
+/// Streamed prices older than this are not used as a source.
+const STREAM_MAX_AGE_MS: u64 = 5 * 60 * 1000;
+
+#[derive(Debug, thiserror::Error)]
+pub enum StablecoinDepegError {
+    #[error("invalid stablecoin depeg settings: {0}")]
+    Invalid(String),
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+}
+
+impl From<StablecoinDepegError> for AppError {
+    fn from(err: StablecoinDepegError) -> Self {
+        match err {
+            StablecoinDepegError::Invalid(message) => {
+                AppError::validation(message).with_code("invalid_stablecoin_depeg_settings")
+            }
+            StablecoinDepegError::Io(err) => err.into(),
+            StablecoinDepegError::Serialization(err) => err.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DepegSeverity {
+    #[default]
+    Pegged,
+    Minor,
+    Moderate,
+    Severe,
+}
+
+impl DepegSeverity {
+    fn label(&self) -> &'static str {
+        match self {
+            DepegSeverity::Pegged => "back on peg",
+            DepegSeverity::Minor => "minor depeg",
+            DepegSeverity::Moderate => "moderate depeg",
+            DepegSeverity::Severe => "severe depeg",
+        }
+    }
+
+    fn priority(&self) -> AlertPriority {
+        match self {
+            DepegSeverity::Pegged => AlertPriority::Medium,
+            DepegSeverity::Minor => AlertPriority::Low,
+            DepegSeverity::Moderate => AlertPriority::High,
+            DepegSeverity::Severe => AlertPriority::Critical,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MonitoredStablecoin {
+    pub mint: String,
+    pub symbol: String,
+    /// Price in USD the coin is meant to hold.
+    pub peg: f64,
+}
+
+impl MonitoredStablecoin {
+    fn new(mint: &str, symbol: &str) -> Self {
+        Self {
+            mint: mint.to_string(),
+            symbol: symbol.to_string(),
+            peg: 1.0,
+        }
+    }
+}
+
+/// Tier thresholds are the absolute deviation from the peg, in percent, at
+/// which each tier starts.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StablecoinDepegSettings {
+    pub alerts_enabled: bool,
+    pub stablecoins: Vec<MonitoredStablecoin>,
+    pub minor_percent: f64,
+    pub moderate_percent: f64,
+    pub severe_percent: f64,
+    /// How far below a tier's threshold the deviation must fall before the
+    /// tier is left, so a price hovering on the boundary does not flap.
+    pub hysteresis_percent: f64,
+    /// Whether the safety engine blocks trades buying a depegging stablecoin.
+    pub block_trades: bool,
+    pub block_at_percent: f64,
+}
+
+impl Default for StablecoinDepegSettings {
+    fn default() -> Self {
+        Self {
+            alerts_enabled: true,
+            stablecoins: vec![
+                MonitoredStablecoin::new(USDC_MINT, "USDC"),
+                MonitoredStablecoin::new(USDT_MINT, "USDT"),
+            ],
+            minor_percent: 0.1,
+            moderate_percent: 0.5,
+            severe_percent: 2.0,
+            hysteresis_percent: 0.05,
+            block_trades: false,
+            block_at_percent: 2.0,
+        }
+    }
+}
+
+impl StablecoinDepegSettings {
+    fn validate(&self) -> Result<(), StablecoinDepegError> {
+        let thresholds = [
+            self.minor_percent,
+            self.moderate_percent,
+            self.severe_percent,
+        ];
+        if thresholds
+            .iter()
+            .any(|value| !value.is_finite() || *value <= 0.0)
+            || self.minor_percent >= self.moderate_percent
+            || self.moderate_percent >= self.severe_percent
+        {
+            return Err(StablecoinDepegError::Invalid(
+                "Tier thresholds must be positive and increase from minor to severe".to_string(),
+            ));
+        }
+        if !self.hysteresis_percent.is_finite()
+            || self.hysteresis_percent < 0.0
+            || self.hysteresis_percent >= self.minor_percent
+        {
+            return Err(StablecoinDepegError::Invalid(
+                "Hysteresis must be at least zero and below the minor threshold".to_string(),
+            ));
+        }
+        if !self.block_at_percent.is_finite() || self.block_at_percent <= 0.0 {
+            return Err(StablecoinDepegError::Invalid(
+                "Trade blocking threshold must be positive".to_string(),
+            ));
+        }
+        let mut mints = HashSet::new();
+        for coin in &self.stablecoins {
+            if coin.mint.trim().is_empty() || coin.symbol.trim().is_empty() {
+                return Err(StablecoinDepegError::Invalid(
+                    "Stablecoins need a mint and a symbol".to_string(),
+                ));
+            }
+            if !coin.peg.is_finite() || coin.peg <= 0.0 {
+                return Err(StablecoinDepegError::Invalid(format!(
+                    "Peg of {} must be positive",
+                    coin.symbol
+                )));
+            }
+            if !mints.insert(coin.mint.as_str()) {
+                return Err(StablecoinDepegError::Invalid(format!(
+                    "{} is listed more than once",
+                    coin.symbol
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn tier(&self, deviation_percent: f64) -> DepegSeverity {
+        let deviation = deviation_percent.abs();
+        if deviation >= self.severe_percent {
+            DepegSeverity::Severe
+        } else if deviation >= self.moderate_percent {
+            DepegSeverity::Moderate
+        } else if deviation >= self.minor_percent {
+            DepegSeverity::Minor
+        } else {
+            DepegSeverity::Pegged
+        }
+    }
+
+    /// Tier for `deviation_percent` coming from `current`: tiers are entered
+    /// at their threshold but only left once the deviation is the hysteresis
+    /// margin below it.
+    fn severity(&self, deviation_percent: f64, current: DepegSeverity) -> DepegSeverity {
+        let entered = self.tier(deviation_percent);
+        if entered >= current {
+            entered
+        } else {
+            self.tier(deviation_percent.abs() + self.hysteresis_percent)
+                .min(current)
+        }
+    }
+}
+
+/// One venue's price for a stablecoin.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SourcePrice {
+    pub source: String,
+    pub price: f64,
+    pub deviation_percent: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DeviationSample {
+    at: DateTime<Utc>,
+    deviation_percent: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StablecoinStatus {
+    pub mint: String,
+    pub symbol: String,
+    pub peg: f64,
+    /// Median of the source prices.
+    pub price: Option<f64>,
+    /// Signed deviation of the median from the peg, in percent.
+    pub deviation_percent: Option<f64>,
+    pub severity: DepegSeverity,
+    pub min_deviation_24h: Option<f64>,
+    pub max_deviation_24h: Option<f64>,
+    pub sources: Vec<SourcePrice>,
+    /// Sources whose own price is at least a minor depeg.
+    pub deviating_sources: Vec<String>,
+    pub trades_blocked: bool,
+    /// Sources that could not be priced on the last check.
+    #[serde(default)]
+    pub errors: Vec<String>,
+    pub checked_at: Option<DateTime<Utc>>,
+}
+
+impl StablecoinStatus {
+    fn unchecked(coin: &MonitoredStablecoin) -> Self {
+        Self {
+            mint: coin.mint.clone(),
+            symbol: coin.symbol.clone(),
+            peg: coin.peg,
+            price: None,
+            deviation_percent: None,
+            severity: DepegSeverity::Pegged,
+            min_deviation_24h: None,
+            max_deviation_24h: None,
+            sources: Vec::new(),
+            deviating_sources: Vec::new(),
+            trades_blocked: false,
+            errors: Vec::new(),
+            checked_at: None,
+        }
+    }
+}
+
+/// A change of tier worth telling the user about.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DepegAlert {
+    pub mint: String,
+    pub symbol: String,
+    pub severity: DepegSeverity,
+    pub previous: DepegSeverity,
+    pub price: f64,
+    pub deviation_percent: f64,
+    pub deviating_sources: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StablecoinTrack {
+    status: Option<StablecoinStatus>,
+    history: Vec<DeviationSample>,
+    /// Highest tier alerted on since the coin last got back on its peg.
+    alerted: DepegSeverity,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PersistedDepegState {
+    settings: StablecoinDepegSettings,
+    tracks: HashMap<String, StablecoinTrack>,
+}
+
+fn median(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let mid = sorted.len() / 2;
+    Some(if sorted.len() % 2 == 0 {
+        (sorted[mid - 1] + sorted[mid]) / 2.0
+    } else {
+        sorted[mid]
+    })
+}
+
+fn deviation_percent(price: f64, peg: f64) -> f64 {
+    (price - peg) / peg * 100.0
+}
+
+pub struct StablecoinDepegMonitor {
+    path: Option<PathBuf>,
+    client: reqwest::Client,
+    state: RwLock<PersistedDepegState>,
+}
+
+pub type SharedStablecoinDepegMonitor = Arc<StablecoinDepegMonitor>;
+
+impl StablecoinDepegMonitor {
+    pub fn new(app: &AppHandle) -> Result<Self, StablecoinDepegError> {
+        let dir = app.app_data_root().map_err(|e| {
+            StablecoinDepegError::Invalid(format!("App data directory not found: {}", e))
+        })?;
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(DEPEG_STORE_FILE);
+        let state = if path.exists() {
+            serde_json::from_str(&fs::read_to_string(&path)?)?
+        } else {
+            PersistedDepegState::default()
+        };
+        Ok(Self {
+            path: Some(path),
+            client: reqwest::Client::new(),
+            state: RwLock::new(state),
+        })
+    }
+
+    /// A monitor that keeps its state in memory only.
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            client: reqwest::Client::new(),
+            state: RwLock::new(PersistedDepegState::default()),
+        }
+    }
+
+    fn persist(&self, state: &PersistedDepegState) -> Result<(), StablecoinDepegError> {
+        if let Some(path) = &self.path {
+            let tmp = path.with_extension("json.tmp");
+            fs::write(&tmp, serde_json::to_vec_pretty(state)?)?;
+            fs::rename(&tmp, path)?;
+        }
+        Ok(())
+    }
+
+    pub fn settings(&self) -> StablecoinDepegSettings {
+        self.state.read().settings.clone()
+    }
+
+    pub fn set_settings(
+        &self,
+        settings: StablecoinDepegSettings,
+    ) -> Result<(), StablecoinDepegError> {
+        settings.validate()?;
+        let mut state = self.state.write();
+        let monitored: HashSet<&str> = settings
+            .stablecoins
+            .iter()
+            .map(|coin| coin.mint.as_str())
+            .collect();
+        state
+            .tracks
+            .retain(|mint, _| monitored.contains(mint.as_str()));
+        for track in state.tracks.values_mut() {
+            if let Some(status) = track.status.as_mut() {
+                status.trades_blocked = settings.block_trades
+                    && status.deviation_percent.map_or(false, |deviation| {
+                        deviation.abs() >= settings.block_at_percent
+                    });
+            }
+        }
+        state.settings = settings;
+        self.persist(&state)
+    }
+
+    /// Latest status of every monitored stablecoin, in settings order.
+    pub fn status(&self) -> Vec<StablecoinStatus> {
+        let state = self.state.read();
+        state
+            .settings
+            .stablecoins
+            .iter()
+            .map(|coin| {
+                state
+                    .tracks
+                    .get(&coin.mint)
+                    .and_then(|track| track.status.clone())
+                    .unwrap_or_else(|| StablecoinStatus::unchecked(coin))
+            })
+            .collect()
+    }
+
+    /// Stablecoins the safety engine should refuse to buy, with the reason.
+    pub fn trade_blocks(&self) -> HashMap<String, String> {
+        self.status()
+            .into_iter()
+            .filter(|status| status.trades_blocked)
+            .map(|status| {
+                let reason = format!(
+                    "{} is {:.2}% off its peg; buying more is blocked until it recovers",
+                    status.symbol,
+                    status.deviation_percent.unwrap_or_default().abs()
+                );
+                (status.mint, reason)
+            })
+            .collect()
+    }
+
+    /// Prices `coin` on every source that answers. Failed sources are
+    /// returned separately.
+    async fn fetch_prices(&self, coin: &MonitoredStablecoin) -> (Vec<(String, f64)>, Vec<String>) {
+        let mut prices = Vec::new();
+        let mut errors = Vec::new();
+        let (birdeye, jupiter, raydium) = tokio::join!(
+            super::fetch_birdeye_price(&coin.mint, ""),
+            self.jupiter_price(&coin.mint),
+            self.raydium_price(&coin.mint),
+        );
+        for (source, result) in [
+            ("birdeye", birdeye.map(|price| price.price)),
+            ("jupiter", jupiter),
+            ("raydium", raydium),
+        ] {
+            match result {
+                Ok(price) if price.is_finite() && price > 0.0 => {
+                    prices.push((source.to_string(), price))
+                }
+                Ok(price) => errors.push(format!("{source}: unusable price {price}")),
+                Err(e) => errors.push(format!("{source}: {e}")),
+            }
+        }
+        if let Some(price) = stream_price(coin) {
+            prices.push(("stream".to_string(), price));
+        }
+        (prices, errors)
+    }
+
+    async fn jupiter_price(&self, mint: &str) -> Result<f64, String> {
+        let request = self.client.get(JUPITER_PRICE_URL).query(&[("ids", mint)]);
+        let body: Value = send_metered("jupiter", RequestPriority::Standard, request)
+            .await
+            .map_err(|e| e.to_string())?
+            .json()
+            .await
+            .map_err(|e| e.to_string())?;
+        parse_price(&body["data"][mint]["price"]).ok_or_else(|| "no price in response".to_string())
+    }
+
+    async fn raydium_price(&self, mint: &str) -> Result<f64, String> {
+        let request = self.client.get(RAYDIUM_PRICE_URL).query(&[("mints", mint)]);
+        let body: Value = send_metered("raydium", RequestPriority::Standard, request)
+            .await
+            .map_err(|e| e.to_string())?
+            .json()
+            .await
+            .map_err(|e| e.to_string())?;
+        parse_price(&body["data"][mint]).ok_or_else(|| "no price in response".to_string())
+    }
+
+    /// Records a check of `coin` at `now` and returns the alert it raises,
+    /// if any. Without any source price the previous tier is kept.
+    fn record(
+        &self,
+        coin: &MonitoredStablecoin,
+        prices: Vec<(String, f64)>,
+        errors: Vec<String>,
+        now: DateTime<Utc>,
+    ) -> Result<Option<DepegAlert>, StablecoinDepegError> {
+        let mut state = self.state.write();
+        let settings = state.settings.clone();
+        let track = state.tracks.entry(coin.mint.clone()).or_default();
+
+        let cutoff = now - Duration::hours(HISTORY_HOURS);
+        track.history.retain(|sample| sample.at > cutoff);
+
+        let sources: Vec<SourcePrice> = prices
+            .into_iter()
+            .map(|(source, price)| SourcePrice {
+                source,
+                price,
+                deviation_percent: deviation_percent(price, coin.peg),
+            })
+            .collect();
+        let price = median(
+            &sources
+                .iter()
+                .map(|source| source.price)
+                .collect::<Vec<_>>(),
+        );
+        let deviation = price.map(|price| deviation_percent(price, coin.peg));
+        if let Some(deviation) = deviation {
+            track.history.push(DeviationSample {
+                at: now,
+                deviation_percent: deviation,
+            });
+        }
+
+        let previous = track
+            .status
+            .as_ref()
+            .map(|status| status.severity)
+            .unwrap_or_default();
+        let severity = deviation
+            .map(|deviation| settings.severity(deviation, previous))
+            .unwrap_or(previous);
+        let deviating_sources: Vec<String> = sources
+            .iter()
+            .filter(|source| source.deviation_percent.abs() >= settings.minor_percent)
+            .map(|source| source.source.clone())
+            .collect();
+        let history = track.history.iter().map(|sample| sample.deviation_percent);
+        let status = StablecoinStatus {
+            mint: coin.mint.clone(),
+            symbol: coin.symbol.clone(),
+            peg: coin.peg,
+            price,
+            deviation_percent: deviation,
+            severity,
+            min_deviation_24h: history.clone().reduce(f64::min),
+            max_deviation_24h: history.reduce(f64::max),
+            sources,
+            deviating_sources,
+            trades_blocked: settings.block_trades
+                && deviation.map_or(false, |deviation| {
+                    deviation.abs() >= settings.block_at_percent
+                }),
+            errors,
+            checked_at: Some(now),
+        };
+
+        // Alert when the tier climbs past the highest one alerted on, and
+        // once more when the coin gets back on its peg after an alert.
+        let alert = match (price, deviation) {
+            (Some(price), Some(deviation))
+                if severity > track.alerted
+                    || (severity == DepegSeverity::Pegged && track.alerted > severity) =>
+            {
+                Some(DepegAlert {
+                    mint: coin.mint.clone(),
+                    symbol: coin.symbol.clone(),
+                    severity,
+                    previous: track.alerted,
+                    price,
+                    deviation_percent: deviation,
+                    deviating_sources: status.deviating_sources.clone(),
+                })
+            }
+            _ => None,
+        };
+        if alert.is_some() || severity == DepegSeverity::Pegged {
+            track.alerted = severity;
+        }
+        track.status = Some(status);
+        self.persist(&state)?;
+        Ok(if settings.alerts_enabled { alert } else { None })
+    }
+}
+
+/// Venues report prices as numbers or numeric strings.
+fn parse_price(value: &Value) -> Option<f64> {
+    value
+        .as_f64()
+        .or_else(|| value.as_str().and_then(|price| price.parse().ok()))
+}
+
+/// Fresh price from the streaming feed, keyed by mint or symbol.
+fn stream_price(coin: &MonitoredStablecoin) -> Option<f64> {
+    let engine = get_price_engine();
+    let cached = engine
+        .get_cached_price(&coin.mint)
+        .or_else(|| engine.get_cached_price(&coin.symbol))?;
+    let now_ms = Utc::now().timestamp_millis().max(0) as u64;
+    (now_ms.saturating_sub(cached.timestamp) <= STREAM_MAX_AGE_MS && cached.price > 0.0)
+        .then_some(cached.price)
+}
+
+async fn deliver_depeg_alert(app: &AppHandle, alert: &DepegAlert) {
+    if let Err(e) = app.emit("stablecoin_depeg_alert", alert) {
+        eprintln!("Failed to emit stablecoin depeg alert: {}", e);
+    }
+    let Some(router) = app.try_state::<SharedNotificationRouter>() else {
+        return;
+    };
+    let venues = if alert.deviating_sources.is_empty() {
+        "no single venue".to_string()
+    } else {
+        alert.deviating_sources.join(", ")
+    };
+    let notification = NewNotification {
+        category: HistoryCategory::StablecoinDepeg,
+        priority: alert.severity.priority(),
+        title: format!("{} {}", alert.symbol, alert.severity.label()),
+        message: format!(
+            "{} at ${:.4} ({:+.2}% from peg), deviating on {}",
+            alert.symbol, alert.price, alert.deviation_percent, venues
+        ),
+        entity: None,
+        link: Some(
+            DeepLinkIntent::Token {
+                mint: alert.mint.clone(),
+            }
+            .to_url(),
+        ),
+    };
+    if let Err(e) = router
+        .read()
+        .await
+        .send_event_notification(&notification)
+        .await
+    {
+        eprintln!(
+            "Failed to route stablecoin depeg alert for {}: {}",
+            alert.mint, e
+        );
+    }
+}
+
+async fn sync_trade_blocks(app: &AppHandle, monitor: &StablecoinDepegMonitor) {
+    if let Some(safety) = app.try_state::<SharedSafetyEngine>() {
+        safety
+            .write()
+            .await
+            .set_depeg_blocks(monitor.trade_blocks());
+    }
+}
+
+/// Prices every monitored stablecoin, raises tier alerts and hands the
+/// resulting trade blocks to the safety engine.
+pub async fn check_stablecoin_depegs(
+    app: &AppHandle,
+    monitor: &StablecoinDepegMonitor,
+) -> Result<(), StablecoinDepegError> {
+    for coin in monitor.settings().stablecoins {
+        let (prices, errors) = monitor.fetch_prices(&coin).await;
+        if let Some(alert) = monitor.record(&coin, prices, errors, Utc::now())? {
+            deliver_depeg_alert(app, &alert).await;
+        }
+    }
+    sync_trade_blocks(app, monitor).await;
+    Ok(())
+}
+
+pub fn schedule_stablecoin_depeg_checks(
+    scheduler: &JobScheduler,
+    app: AppHandle,
+    monitor: SharedStablecoinDepegMonitor,
+) -> Result<(), String> {
+    scheduler.register(
+        "stablecoin_depeg_checks",
+        JobSchedule::every_from_start(std::time::Duration::from_secs(CHECK_INTERVAL_SECS)),
+        std::time::Duration::from_secs(CHECK_TIMEOUT_SECS),
+        move || {
+            let app = app.clone();
+            let monitor = monitor.clone();
+            async move {
+                check_stablecoin_depegs(&app, &monitor)
+                    .await
+                    .map_err(|e| e.to_string())
+            }
+        },
+    )
+}
+
+#[tauri::command]
+pub async fn get_stablecoin_status(
+    monitor: State<'_, SharedStablecoinDepegMonitor>,
+) -> Result<Vec<StablecoinStatus>, AppError> {
+    Ok(monitor.status())
+}
+
+#[tauri::command]
+pub async fn get_stablecoin_depeg_settings(
+    monitor: State<'_, SharedStablecoinDepegMonitor>,
+) -> Result<StablecoinDepegSettings, AppError> {
+    Ok(monitor.settings())
+}
+
+#[tauri::command]
+pub async fn set_stablecoin_depeg_settings(
+    settings: StablecoinDepegSettings,
+    app: AppHandle,
+    monitor: State<'_, SharedStablecoinDepegMonitor>,
+) -> Result<(), AppError> {
+    monitor.set_settings(settings)?;
+    sync_trade_blocks(&app, &monitor).await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(minutes: i64) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2026-01-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc)
+            + Duration::minutes(minutes)
+    }
+
+    fn prices(values: &[(&str, f64)]) -> Vec<(String, f64)> {
+        values
+            .iter()
+            .map(|(source, price)| (source.to_string(), *price))
+            .collect()
+    }
+
+    #[test]
+    fn median_ignores_a_single_bad_feed() {
+        let monitor = StablecoinDepegMonitor::in_memory();
+        let usdt = MonitoredStablecoin::new(USDT_MINT, "USDT");
+
+        let alert = monitor
+            .record(
+                &usdt,
+                prices(&[("birdeye", 1.0001), ("jupiter", 0.9999), ("raydium", 0.90)]),
+                vec!["stream: no price".to_string()],
+                at(0),
+            )
+            .unwrap();
+        assert!(alert.is_none());
+
+        let status = &monitor.status()[1];
+        assert_eq!(status.severity, DepegSeverity::Pegged);
+        assert_eq!(status.price, Some(0.9999));
+        assert_eq!(status.deviating_sources, vec!["raydium".to_string()]);
+        assert_eq!(status.errors.len(), 1);
+        assert!(monitor.trade_blocks().is_empty());
+    }
+
+    #[test]
+    fn tiers_escalate_with_hysteresis() {
+        let monitor = StablecoinDepegMonitor::in_memory();
+        let mut settings = monitor.settings();
+        settings.block_trades = true;
+        monitor.set_settings(settings).unwrap();
+        let usdc = MonitoredStablecoin::new(USDC_MINT, "USDC");
+        let check = |price: f64, minute: i64| {
+            monitor
+                .record(&usdc, prices(&[("birdeye", price)]), Vec::new(), at(minute))
+                .unwrap()
+                .map(|alert| alert.severity)
+        };
+
+        assert_eq!(check(0.994, 0), Some(DepegSeverity::Moderate));
+        // Hovering around the moderate threshold neither leaves the tier
+        // nor alerts again.
+        assert_eq!(check(0.9953, 2), None);
+        assert_eq!(check(0.9948, 4), None);
+        assert_eq!(monitor.status()[0].severity, DepegSeverity::Moderate);
+        assert_eq!(check(0.975, 6), Some(DepegSeverity::Severe));
+        assert!(monitor.trade_blocks().contains_key(USDC_MINT));
+
+        // Back near the peg, but still within the hysteresis of minor.
+        assert_eq!(check(0.9993, 7), None);
+        assert_eq!(monitor.status()[0].severity, DepegSeverity::Minor);
+        assert_eq!(check(0.9996, 8), Some(DepegSeverity::Pegged));
+        let status = &monitor.status()[0];
+        assert!(!status.trades_blocked);
+        assert!((status.min_deviation_24h.unwrap() + 2.5).abs() < 1e-9);
+        assert!((status.max_deviation_24h.unwrap() + 0.04).abs() < 1e-9);
+
+        // Samples older than a day drop out of the range.
+        check(1.0, 8 + 24 * 60);
+        assert_eq!(monitor.status()[0].min_deviation_24h, Some(0.0));
+    }
+
+    #[test]
+    fn settings_are_validated() {
+        let monitor = StablecoinDepegMonitor::in_memory();
+        let mut settings = monitor.settings();
+        settings.moderate_percent = 3.0;
+        assert!(monitor.set_settings(settings).is_err());
+
+        let mut settings = monitor.settings();
+        settings.hysteresis_percent = settings.minor_percent;
+        assert!(monitor.set_settings(settings).is_err());
+
+        let mut settings = monitor.settings();
+        settings
+            .stablecoins
+            .push(MonitoredStablecoin::new(USDC_MINT, "USDC"));
+        assert!(monitor.set_settings(settings).is_err());
+    }
+}
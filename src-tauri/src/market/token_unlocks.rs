@@ -0,0 +1,797 @@
+//! Unlock calendar for tokens launched elsewhere. Vesting schedules come from
+//! a bundled dataset of published tokenomics, which a user-edited override
+//! replaces per mint. Every event carries the source and age of the schedule
+//! it was computed from.
+
+use crate::config::DataPaths;
+use crate::core::{JobSchedule, JobScheduler};
+use crate::deep_link::DeepLinkIntent;
+use crate::errors::AppError;
+use crate::notifications::router::SharedNotificationRouter;
+use crate::notifications::types::{AlertPriority, HistoryCategory, NewNotification};
+use crate::portfolio::SharedWatchlistManager;
+use crate::wallet::balances::SharedTokenBalanceService;
+use chrono::{DateTime, Duration, Utc};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+pub const DEFAULT_CALENDAR_DAYS: u32 = 90;
+/// Unlocks within this many days count towards a token's risk score.
+pub const LARGE_UNLOCK_WINDOW_DAYS: i64 = 14;
+/// Schedules not updated for this long are flagged as stale.
+pub const STALE_AFTER_DAYS: i64 = 120;
+const DEFAULT_PERIOD_DAYS: u32 = 30;
+const MAX_CALENDAR_DAYS: u32 = 730;
+const UNLOCK_STORE_FILE: &str = "token_unlocks.json";
+const BUNDLED_SCHEDULES: &str = include_str!("data/token_unlocks.json");
+const ALERT_CHECK_INTERVAL_SECS: u64 = 6 * 60 * 60;
+const ALERT_CHECK_TIMEOUT_SECS: u64 = 5 * 60;
+
+#[derive(Debug, thiserror::Error)]
+pub enum TokenUnlockError {
+    #[error("invalid unlock schedule: {0}")]
+    Invalid(String),
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+}
+
+impl From<TokenUnlockError> for AppError {
+    fn from(err: TokenUnlockError) -> Self {
+        match err {
+            TokenUnlockError::Invalid(message) => {
+                AppError::validation(message).with_code("invalid_unlock_schedule")
+            }
+            TokenUnlockError::Io(err) => err.into(),
+            TokenUnlockError::Serialization(err) => err.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum UnlockOrigin {
+    Bundled,
+    UserOverride,
+}
+
+/// Tokens released by one allocation. Without `end` the whole amount unlocks
+/// at `start`; otherwise it is split evenly over periods from `start` to
+/// `end`, each unlocking when its period ends.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnlockTranche {
+    pub label: String,
+    pub amount: f64,
+    pub start: DateTime<Utc>,
+    #[serde(default)]
+    pub end: Option<DateTime<Utc>>,
+    /// Defaults to 30 days.
+    #[serde(default)]
+    pub period_days: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnlockSchedule {
+    pub mint: String,
+    pub symbol: String,
+    /// Circulating supply as of `updated_at`.
+    pub circulating_supply: f64,
+    pub tranches: Vec<UnlockTranche>,
+    /// Where the schedule was taken from, e.g. the project's tokenomics page.
+    pub source: String,
+    #[serde(default)]
+    pub source_url: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Where an event's schedule came from and how old it is.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnlockProvenance {
+    pub origin: UnlockOrigin,
+    pub source: String,
+    pub source_url: Option<String>,
+    pub updated_at: DateTime<Utc>,
+    /// Not updated for [`STALE_AFTER_DAYS`].
+    pub stale: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnlockEvent {
+    pub id: String,
+    pub mint: String,
+    pub symbol: String,
+    pub label: String,
+    pub unlock_at: DateTime<Utc>,
+    pub amount: f64,
+    /// Share of the supply circulating just before the unlock.
+    pub percent_of_circulating: f64,
+    pub provenance: UnlockProvenance,
+}
+
+/// Tokens the calendar covers: a wallet's holdings, a watchlist, or mints.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum UnlockScope {
+    Wallet { address: String },
+    Watchlist { id: String },
+    Mints { mints: Vec<String> },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnlockScheduleEntry {
+    pub schedule: UnlockSchedule,
+    pub origin: UnlockOrigin,
+}
+
+/// The schedule used for one mint of the calendar.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnlockCoverage {
+    pub mint: String,
+    pub symbol: String,
+    pub provenance: UnlockProvenance,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnlockCalendar {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    /// Soonest first.
+    pub events: Vec<UnlockEvent>,
+    pub covered: Vec<UnlockCoverage>,
+    /// Mints in scope with no known schedule.
+    pub missing: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnlockAlertSettings {
+    pub enabled: bool,
+    /// Unlocks of at least this share of circulating supply raise an alert.
+    pub min_supply_percent: f64,
+    /// How far ahead of an unlock the alert is raised.
+    pub lead_days: u32,
+    pub scopes: Vec<UnlockScope>,
+}
+
+impl Default for UnlockAlertSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_supply_percent: 2.0,
+            lead_days: 7,
+            scopes: Vec::new(),
+        }
+    }
+}
+
+fn positive(value: f64) -> bool {
+    value.is_finite() && value > 0.0
+}
+
+impl UnlockAlertSettings {
+    fn validate(&self) -> Result<(), TokenUnlockError> {
+        if !positive(self.min_supply_percent) || self.min_supply_percent > 100.0 {
+            return Err(TokenUnlockError::Invalid(
+                "Alert threshold must be between 0 and 100 percent of supply".to_string(),
+            ));
+        }
+        if self.lead_days == 0 || self.lead_days > MAX_CALENDAR_DAYS {
+            return Err(TokenUnlockError::Invalid(format!(
+                "Alert lead time must be between 1 and {} days",
+                MAX_CALENDAR_DAYS
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl UnlockTranche {
+    fn unlocks(&self) -> Vec<(DateTime<Utc>, f64)> {
+        let Some(end) = self.end else {
+            return vec![(self.start, self.amount)];
+        };
+        let period = Duration::days(self.period_days.unwrap_or(DEFAULT_PERIOD_DAYS) as i64);
+        let span = (end - self.start).num_seconds();
+        let periods = (span + period.num_seconds() - 1) / period.num_seconds();
+        let per_period = self.amount / periods as f64;
+        (1..=periods as i32)
+            .map(|i| ((self.start + period * i).min(end), per_period))
+            .collect()
+    }
+}
+
+impl UnlockSchedule {
+    pub fn validate(&self) -> Result<(), TokenUnlockError> {
+        let invalid = |message: &str| Err(TokenUnlockError::Invalid(message.to_string()));
+        if self.mint.trim().is_empty() {
+            return invalid("Mint is required");
+        }
+        if !positive(self.circulating_supply) {
+            return invalid("Circulating supply must be positive");
+        }
+        if self.source.trim().is_empty() {
+            return invalid("Source is required");
+        }
+        if self.tranches.is_empty() {
+            return invalid("At least one tranche is required");
+        }
+        for tranche in &self.tranches {
+            if !positive(tranche.amount) {
+                return invalid("Tranche amounts must be positive");
+            }
+            if tranche.end.map_or(false, |end| end <= tranche.start) {
+                return invalid("Tranche end must be after its start");
+            }
+            if tranche.period_days == Some(0) {
+                return invalid("Tranche period must be at least one day");
+            }
+        }
+        Ok(())
+    }
+
+    fn provenance(&self, origin: UnlockOrigin, now: DateTime<Utc>) -> UnlockProvenance {
+        UnlockProvenance {
+            origin,
+            source: self.source.clone(),
+            source_url: self.source_url.clone(),
+            updated_at: self.updated_at,
+            stale: now - self.updated_at > Duration::days(STALE_AFTER_DAYS),
+        }
+    }
+
+    /// Unlocks in `[from, to)`. Percentages are of `circulating_supply` plus
+    /// everything unlocked after `updated_at` and before the event.
+    pub fn events_between(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        origin: UnlockOrigin,
+        now: DateTime<Utc>,
+    ) -> Vec<UnlockEvent> {
+        let mut unlocks: Vec<(DateTime<Utc>, f64, &str)> = self
+            .tranches
+            .iter()
+            .flat_map(|tranche| {
+                tranche
+                    .unlocks()
+                    .into_iter()
+                    .map(move |(at, amount)| (at, amount, tranche.label.as_str()))
+            })
+            .filter(|(at, _, _)| *at > self.updated_at && *at < to)
+            .collect();
+        unlocks.sort_by_key(|(at, _, _)| *at);
+
+        let provenance = self.provenance(origin, now);
+        let mut circulating = self.circulating_supply;
+        let mut events = Vec::new();
+        for (at, amount, label) in unlocks {
+            if at >= from {
+                events.push(UnlockEvent {
+                    id: format!("{}:{}:{}", self.mint, at.timestamp(), label),
+                    mint: self.mint.clone(),
+                    symbol: self.symbol.clone(),
+                    label: label.to_string(),
+                    unlock_at: at,
+                    amount,
+                    percent_of_circulating: amount / circulating * 100.0,
+                    provenance: provenance.clone(),
+                });
+            }
+            circulating += amount;
+        }
+        events
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PersistedUnlocks {
+    overrides: HashMap<String, UnlockSchedule>,
+    alerts: UnlockAlertSettings,
+    /// Events already alerted on, so each raises one alert.
+    alerted: HashSet<String>,
+}
+
+pub struct TokenUnlockCalendar {
+    path: Option<PathBuf>,
+    bundled: HashMap<String, UnlockSchedule>,
+    state: RwLock<PersistedUnlocks>,
+}
+
+pub type SharedTokenUnlockCalendar = Arc<TokenUnlockCalendar>;
+
+fn bundled_schedules() -> HashMap<String, UnlockSchedule> {
+    let schedules: Vec<UnlockSchedule> =
+        serde_json::from_str(BUNDLED_SCHEDULES).expect("bundled unlock schedules are valid");
+    schedules
+        .into_iter()
+        .map(|schedule| (schedule.mint.clone(), schedule))
+        .collect()
+}
+
+impl TokenUnlockCalendar {
+    pub fn new(app: &AppHandle) -> Result<Self, TokenUnlockError> {
+        let dir = app.app_data_root().map_err(|e| {
+            TokenUnlockError::Invalid(format!("App data directory not found: {}", e))
+        })?;
+        fs::create_dir_all(&dir)?;
+        Self::load(dir.join(UNLOCK_STORE_FILE))
+    }
+
+    pub fn load(path: PathBuf) -> Result<Self, TokenUnlockError> {
+        let state = if path.exists() {
+            serde_json::from_str(&fs::read_to_string(&path)?)?
+        } else {
+            PersistedUnlocks::default()
+        };
+        Ok(Self {
+            path: Some(path),
+            bundled: bundled_schedules(),
+            state: RwLock::new(state),
+        })
+    }
+
+    /// A calendar with only the bundled schedules, never written to disk.
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            bundled: bundled_schedules(),
+            state: RwLock::new(PersistedUnlocks::default()),
+        }
+    }
+
+    fn persist(&self, state: &PersistedUnlocks) -> Result<(), TokenUnlockError> {
+        if let Some(path) = &self.path {
+            let tmp = path.with_extension("json.tmp");
+            fs::write(&tmp, serde_json::to_vec_pretty(state)?)?;
+            fs::rename(&tmp, path)?;
+        }
+        Ok(())
+    }
+
+    /// The override for `mint` if there is one, else the bundled schedule.
+    pub fn schedule(&self, mint: &str) -> Option<(UnlockSchedule, UnlockOrigin)> {
+        if let Some(schedule) = self.state.read().overrides.get(mint) {
+            return Some((schedule.clone(), UnlockOrigin::UserOverride));
+        }
+        self.bundled
+            .get(mint)
+            .map(|schedule| (schedule.clone(), UnlockOrigin::Bundled))
+    }
+
+    pub fn calendar(
+        &self,
+        mints: &[String],
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> UnlockCalendar {
+        let mut seen = HashSet::new();
+        let mut calendar = UnlockCalendar {
+            from,
+            to,
+            events: Vec::new(),
+            covered: Vec::new(),
+            missing: Vec::new(),
+        };
+        for mint in mints.iter().filter(|mint| seen.insert(mint.as_str())) {
+            match self.schedule(mint) {
+                Some((schedule, origin)) => {
+                    calendar
+                        .events
+                        .extend(schedule.events_between(from, to, origin, now));
+                    calendar.covered.push(UnlockCoverage {
+                        mint: mint.clone(),
+                        symbol: schedule.symbol.clone(),
+                        provenance: schedule.provenance(origin, now),
+                    });
+                }
+                None => calendar.missing.push(mint.clone()),
+            }
+        }
+        calendar.events.sort_by_key(|event| event.unlock_at);
+        calendar
+    }
+
+    /// Share of circulating supply unlocking for `mint` within
+    /// [`LARGE_UNLOCK_WINDOW_DAYS`]; 0 without a schedule.
+    pub fn upcoming_unlock_percent(&self, mint: &str, now: DateTime<Utc>) -> f64 {
+        self.schedule(mint)
+            .map(|(schedule, origin)| {
+                schedule
+                    .events_between(
+                        now,
+                        now + Duration::days(LARGE_UNLOCK_WINDOW_DAYS),
+                        origin,
+                        now,
+                    )
+                    .iter()
+                    .map(|event| event.percent_of_circulating)
+                    .sum()
+            })
+            .unwrap_or(0.0)
+    }
+
+    pub fn set_override(&self, mut schedule: UnlockSchedule) -> Result<(), TokenUnlockError> {
+        schedule.mint = schedule.mint.trim().to_string();
+        schedule.validate()?;
+        let mut state = self.state.write();
+        state.overrides.insert(schedule.mint.clone(), schedule);
+        self.persist(&state)
+    }
+
+    /// Returns whether an override existed; the bundled schedule applies again.
+    pub fn remove_override(&self, mint: &str) -> Result<bool, TokenUnlockError> {
+        let mut state = self.state.write();
+        let removed = state.overrides.remove(mint).is_some();
+        if removed {
+            self.persist(&state)?;
+        }
+        Ok(removed)
+    }
+
+    pub fn alert_settings(&self) -> UnlockAlertSettings {
+        self.state.read().alerts.clone()
+    }
+
+    pub fn set_alert_settings(
+        &self,
+        settings: UnlockAlertSettings,
+    ) -> Result<(), TokenUnlockError> {
+        settings.validate()?;
+        let mut state = self.state.write();
+        state.alerts = settings;
+        self.persist(&state)
+    }
+
+    /// Events from `events` that reach the alert threshold and have not been
+    /// alerted on yet, marking them as alerted. Past events are forgotten.
+    pub fn take_new_alerts(
+        &self,
+        events: Vec<UnlockEvent>,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<UnlockEvent>, TokenUnlockError> {
+        let mut state = self.state.write();
+        let threshold = state.alerts.min_supply_percent;
+        let fresh: Vec<UnlockEvent> = events
+            .into_iter()
+            .filter(|event| event.percent_of_circulating >= threshold)
+            .filter(|event| !state.alerted.contains(&event.id))
+            .collect();
+
+        let before = state.alerted.len();
+        let now_secs = now.timestamp();
+        state.alerted.retain(|id| {
+            id.split(':')
+                .nth(1)
+                .and_then(|at| at.parse::<i64>().ok())
+                .map_or(false, |at| at >= now_secs)
+        });
+        let pruned = state.alerted.len() != before;
+        for event in &fresh {
+            state.alerted.insert(event.id.clone());
+        }
+        if !fresh.is_empty() || pruned {
+            self.persist(&state)?;
+        }
+        Ok(fresh)
+    }
+}
+
+/// Mints covered by `scope`. Wallets use their current token balances.
+pub async fn scope_mints(app: &AppHandle, scope: &UnlockScope) -> Result<Vec<String>, AppError> {
+    match scope {
+        UnlockScope::Mints { mints } => Ok(mints
+            .iter()
+            .map(|mint| mint.trim().to_string())
+            .filter(|mint| !mint.is_empty())
+            .collect()),
+        UnlockScope::Wallet { address } => {
+            let balances = app
+                .try_state::<SharedTokenBalanceService>()
+                .ok_or_else(|| AppError::internal("Token balances are unavailable"))?;
+            let view = balances
+                .balances(address, false)
+                .await
+                .map_err(|e| AppError::internal(e.to_string()))?;
+            Ok(view
+                .balances
+                .into_iter()
+                .filter(|balance| balance.balance > 0.0)
+                .map(|balance| balance.mint)
+                .collect())
+        }
+        UnlockScope::Watchlist { id } => {
+            let watchlists = app
+                .try_state::<SharedWatchlistManager>()
+                .ok_or_else(|| AppError::internal("Watchlists are unavailable"))?;
+            let watchlist = watchlists
+                .read()
+                .await
+                .get_watchlist(id)
+                .await
+                .map_err(|e| AppError::not_found(e.to_string()).with_code("watchlist_not_found"))?;
+            Ok(watchlist.items.into_iter().map(|item| item.mint).collect())
+        }
+    }
+}
+
+async fn deliver_unlock_alert(app: &AppHandle, event: &UnlockEvent) {
+    if let Err(e) = app.emit("token_unlock_alert", event) {
+        eprintln!("Failed to emit token unlock alert: {}", e);
+    }
+    let Some(router) = app.try_state::<SharedNotificationRouter>() else {
+        return;
+    };
+    let notification = NewNotification {
+        category: HistoryCategory::TokenUnlock,
+        priority: AlertPriority::High,
+        title: format!(
+            "{} unlock on {}",
+            event.symbol,
+            event.unlock_at.format("%Y-%m-%d")
+        ),
+        message: format!(
+            "{}: {:.0} {} ({:.2}% of circulating supply). Source: {}",
+            event.label,
+            event.amount,
+            event.symbol,
+            event.percent_of_circulating,
+            event.provenance.source
+        ),
+        entity: None,
+        link: Some(
+            DeepLinkIntent::Token {
+                mint: event.mint.clone(),
+            }
+            .to_url(),
+        ),
+    };
+    if let Err(e) = router
+        .read()
+        .await
+        .send_event_notification(&notification)
+        .await
+    {
+        eprintln!("Failed to route token unlock alert {}: {}", event.id, e);
+    }
+}
+
+/// Raises an alert for every large unlock within the lead time of the
+/// configured scopes.
+pub async fn check_unlock_alerts(
+    app: &AppHandle,
+    calendar: &TokenUnlockCalendar,
+) -> Result<(), TokenUnlockError> {
+    let settings = calendar.alert_settings();
+    if !settings.enabled {
+        return Ok(());
+    }
+    let mut mints = Vec::new();
+    for scope in &settings.scopes {
+        match scope_mints(app, scope).await {
+            Ok(scope_mints) => mints.extend(scope_mints),
+            Err(e) => eprintln!("Skipping unlock alert scope {:?}: {}", scope, e),
+        }
+    }
+
+    let now = Utc::now();
+    let upcoming = calendar.calendar(
+        &mints,
+        now,
+        now + Duration::days(settings.lead_days as i64),
+        now,
+    );
+    for event in &calendar.take_new_alerts(upcoming.events, now)? {
+        deliver_unlock_alert(app, event).await;
+    }
+    Ok(())
+}
+
+pub fn schedule_unlock_alerts(
+    scheduler: &JobScheduler,
+    app: AppHandle,
+    calendar: SharedTokenUnlockCalendar,
+) -> Result<(), String> {
+    scheduler.register(
+        "token_unlock_alerts",
+        JobSchedule::every_from_start(std::time::Duration::from_secs(ALERT_CHECK_INTERVAL_SECS)),
+        std::time::Duration::from_secs(ALERT_CHECK_TIMEOUT_SECS),
+        move || {
+            let app = app.clone();
+            let calendar = calendar.clone();
+            async move {
+                check_unlock_alerts(&app, &calendar)
+                    .await
+                    .map_err(|e| e.to_string())
+            }
+        },
+    )
+}
+
+#[tauri::command]
+pub async fn get_unlock_calendar(
+    app: AppHandle,
+    scope: UnlockScope,
+    days: Option<u32>,
+    calendar: State<'_, SharedTokenUnlockCalendar>,
+) -> Result<UnlockCalendar, AppError> {
+    let days = days
+        .unwrap_or(DEFAULT_CALENDAR_DAYS)
+        .clamp(1, MAX_CALENDAR_DAYS);
+    let mints = scope_mints(&app, &scope).await?;
+    let now = Utc::now();
+    Ok(calendar.calendar(&mints, now, now + Duration::days(days as i64), now))
+}
+
+#[tauri::command]
+pub async fn get_unlock_schedule(
+    mint: String,
+    calendar: State<'_, SharedTokenUnlockCalendar>,
+) -> Result<Option<UnlockScheduleEntry>, AppError> {
+    Ok(calendar
+        .schedule(mint.trim())
+        .map(|(schedule, origin)| UnlockScheduleEntry { schedule, origin }))
+}
+
+#[tauri::command]
+pub async fn set_unlock_override(
+    schedule: UnlockSchedule,
+    calendar: State<'_, SharedTokenUnlockCalendar>,
+) -> Result<(), AppError> {
+    Ok(calendar.set_override(schedule)?)
+}
+
+#[tauri::command]
+pub async fn remove_unlock_override(
+    mint: String,
+    calendar: State<'_, SharedTokenUnlockCalendar>,
+) -> Result<bool, AppError> {
+    Ok(calendar.remove_override(mint.trim())?)
+}
+
+#[tauri::command]
+pub async fn get_unlock_alert_settings(
+    calendar: State<'_, SharedTokenUnlockCalendar>,
+) -> Result<UnlockAlertSettings, AppError> {
+    Ok(calendar.alert_settings())
+}
+
+#[tauri::command]
+pub async fn set_unlock_alert_settings(
+    settings: UnlockAlertSettings,
+    calendar: State<'_, SharedTokenUnlockCalendar>,
+) -> Result<(), AppError> {
+    Ok(calendar.set_alert_settings(settings)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(text: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(text)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn schedule(tranches: Vec<UnlockTranche>) -> UnlockSchedule {
+        UnlockSchedule {
+            mint: "MINT".to_string(),
+            symbol: "TKN".to_string(),
+            circulating_supply: 1_000.0,
+            tranches,
+            source: "test".to_string(),
+            source_url: None,
+            updated_at: at("2026-01-01T00:00:00Z"),
+        }
+    }
+
+    fn cliff(amount: f64, start: &str) -> UnlockTranche {
+        UnlockTranche {
+            label: "Cliff".to_string(),
+            amount,
+            start: at(start),
+            end: None,
+            period_days: None,
+        }
+    }
+
+    #[test]
+    fn bundled_schedules_are_valid() {
+        for schedule in bundled_schedules().values() {
+            schedule.validate().unwrap();
+        }
+    }
+
+    #[test]
+    fn percentages_grow_the_circulating_supply() {
+        let schedule = schedule(vec![
+            cliff(100.0, "2026-02-01T00:00:00Z"),
+            cliff(110.0, "2026-03-01T00:00:00Z"),
+            // Already in the circulating supply.
+            cliff(500.0, "2025-12-01T00:00:00Z"),
+        ]);
+        let now = at("2026-01-15T00:00:00Z");
+        let events =
+            schedule.events_between(now, at("2027-01-01T00:00:00Z"), UnlockOrigin::Bundled, now);
+
+        assert_eq!(events.len(), 2);
+        assert!((events[0].percent_of_circulating - 10.0).abs() < 1e-9);
+        assert!((events[1].percent_of_circulating - 10.0).abs() < 1e-9);
+        assert!(!events[0].provenance.stale);
+    }
+
+    #[test]
+    fn linear_tranches_unlock_each_period() {
+        let tranche = UnlockTranche {
+            label: "Team".to_string(),
+            amount: 90.0,
+            start: at("2026-01-01T00:00:00Z"),
+            end: Some(at("2026-03-27T00:00:00Z")),
+            period_days: Some(30),
+        };
+        let unlocks = tranche.unlocks();
+
+        // 85 days in 30-day periods; the last one ends at `end`.
+        assert_eq!(unlocks.len(), 3);
+        assert_eq!(unlocks[0].0, at("2026-01-31T00:00:00Z"));
+        assert_eq!(unlocks[2].0, at("2026-03-27T00:00:00Z"));
+        assert!((unlocks.iter().map(|(_, amount)| amount).sum::<f64>() - 90.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn overrides_replace_bundled_schedules_and_alert_once() {
+        let calendar = TokenUnlockCalendar::in_memory();
+        let mut custom = schedule(vec![cliff(50.0, "2026-02-01T00:00:00Z")]);
+        custom.source = "My research".to_string();
+        calendar.set_override(custom).unwrap();
+        calendar
+            .set_alert_settings(UnlockAlertSettings {
+                enabled: true,
+                min_supply_percent: 2.0,
+                ..UnlockAlertSettings::default()
+            })
+            .unwrap();
+
+        let now = at("2026-01-20T00:00:00Z");
+        let result = calendar.calendar(
+            &["MINT".to_string(), "UNKNOWN".to_string()],
+            now,
+            now + Duration::days(30),
+            now,
+        );
+        assert_eq!(result.events.len(), 1);
+        assert_eq!(
+            result.events[0].provenance.origin,
+            UnlockOrigin::UserOverride
+        );
+        assert_eq!(result.missing, vec!["UNKNOWN".to_string()]);
+        assert!((calendar.upcoming_unlock_percent("MINT", now) - 5.0).abs() < 1e-9);
+
+        let first = calendar
+            .take_new_alerts(result.events.clone(), now)
+            .unwrap();
+        assert_eq!(first.len(), 1);
+        assert!(calendar
+            .take_new_alerts(result.events, now)
+            .unwrap()
+            .is_empty());
+
+        assert!(calendar.remove_override("MINT").unwrap());
+        assert!(calendar.schedule("MINT").is_none());
+    }
+}
@@ -96,15 +96,9 @@ impl TopCoinsCache {
     }
 
     async fn fetch_from_birdeye(&self, api_key: &str) -> Result<Vec<TopCoin>, String> {
-        let client = reqwest::Client::new();
-        let url = format!(
-            "https://public-api.birdeye.so/defi/market-cap?limit={}",
-            self.page_size
-        );
-
-        let response = client
-            .get(&url)
-            .header("X-API-KEY", api_key)
+        let response = super::birdeye_client(api_key)
+            .get("/defi/market-cap")
+            .query(&[("limit", self.page_size)])
             .send()
             .await
             .map_err(|e| format!("Request failed: {}", e))?;
@@ -339,15 +333,10 @@ async fn fetch_birdeye_top_coins(
     limit: usize,
     offset: usize,
 ) -> Result<Vec<TopCoin>, String> {
-    let client = reqwest::Client::new();
-    let url = format!(
-        "https://public-api.birdeye.so/defi/tokenlist?sort_by=mc&sort_type=desc&offset={}&limit={}",
-        offset, limit
-    );
-
-    let response = client
-        .get(&url)
-        .header("X-API-KEY", api_key)
+    let response = super::birdeye_client(api_key)
+        .get("/defi/tokenlist")
+        .query(&[("sort_by", "mc"), ("sort_type", "desc")])
+        .query(&[("offset", offset), ("limit", limit)])
         .send()
         .await
         .map_err(|e| format!("Request failed: {}", e))?;
@@ -2,6 +2,8 @@ use serde::{Deserialize, Serialize};
 use std::time::{Duration, SystemTime};
 use tokio::sync::RwLock;
 
+use crate::api_analytics::{send_metered, RequestPriority};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TrendingCoin {
     pub address: String,
@@ -74,10 +76,8 @@ impl TrendingCoinsCache {
         let client = reqwest::Client::new();
         let url = "https://public-api.birdeye.so/defi/trending";
 
-        let response = client
-            .get(url)
-            .header("X-API-KEY", api_key)
-            .send()
+        let request = client.get(url).header("X-API-KEY", api_key);
+        let response = send_metered("birdeye", RequestPriority::Standard, request)
             .await
             .map_err(|e| format!("Request failed: {}", e))?;
 
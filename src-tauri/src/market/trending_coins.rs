@@ -1,4 +1,3 @@
-use reqwest;
 use serde::{Deserialize, Serialize};
 use std::sync::{Arc, RwLock};
 use std::time::{Duration, SystemTime};
@@ -98,15 +97,10 @@ lazy_static::lazy_static! {
 }
 
 async fn fetch_birdeye_trending(api_key: &str, limit: usize) -> Result<Vec<TrendingCoin>, String> {
-    let client = reqwest::Client::new();
-    let url = format!(
-        "https://public-api.birdeye.so/defi/token_trending?sort_by=rank&sort_type=asc&offset=0&limit={}",
-        limit
-    );
-
-    let response = client
-        .get(&url)
-        .header("X-API-KEY", api_key)
+    let response = super::birdeye_client(api_key)
+        .get("/defi/token_trending")
+        .query(&[("sort_by", "rank"), ("sort_type", "asc"), ("offset", "0")])
+        .query(&[("limit", limit)])
         .send()
         .await
         .map_err(|e| format!("Request failed: {}", e))?;
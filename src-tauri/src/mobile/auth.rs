@@ -1,4 +1,4 @@
-use crate::mobile::{MobileDevice, MobileSession};
+use crate::mobile::{MobileDevice, MobileSafetyPolicy, MobileSession, PushDeliveryPolicy};
 use crate::security::keystore::Keystore;
 use anyhow::{anyhow, Result};
 use chrono::Utc;
@@ -37,6 +37,8 @@ pub struct MobileAuthManager {
     devices: HashMap<String, MobileDevice>,
     sessions: HashMap<String, MobileSession>,
     challenges: HashMap<String, BiometricChallenge>,
+    /// Last successful biometric verification per device.
+    biometric_verified_at: HashMap<String, i64>,
     data_dir: PathBuf,
 }
 
@@ -46,6 +48,7 @@ impl MobileAuthManager {
             devices: HashMap::new(),
             sessions: HashMap::new(),
             challenges: HashMap::new(),
+            biometric_verified_at: HashMap::new(),
             data_dir,
         }
     }
@@ -65,6 +68,8 @@ impl MobileAuthManager {
             push_token: None,
             last_sync: None,
             biometric_enabled: req.biometric_public_key.is_some(),
+            delivery_policy: Default::default(),
+            safety_policy: Default::default(),
         };
 
         self.devices.insert(device_id.clone(), device.clone());
@@ -131,6 +136,8 @@ impl MobileAuthManager {
         }
 
         self.challenges.remove(&challenge_id);
+        self.biometric_verified_at
+            .insert(device.device_id.clone(), now);
 
         // Create session
         let session_token = Uuid::new_v4().to_string();
@@ -203,6 +210,50 @@ impl MobileAuthManager {
         self.devices.values().cloned().collect()
     }
 
+    pub fn get_device(&self, device_id: &str) -> Option<MobileDevice> {
+        self.devices.get(device_id).cloned()
+    }
+
+    /// Update the push delivery policy stored with the device registration
+    pub async fn set_delivery_policy(
+        &mut self,
+        device_id: &str,
+        policy: PushDeliveryPolicy,
+    ) -> Result<MobileDevice> {
+        let device = self
+            .devices
+            .get_mut(device_id)
+            .ok_or_else(|| anyhow!("Device not found"))?;
+
+        device.delivery_policy = policy;
+        let device = device.clone();
+        self.save_devices().await?;
+
+        Ok(device)
+    }
+
+    /// Update the mobile trading limits stored with the device registration
+    pub async fn set_safety_policy(
+        &mut self,
+        device_id: &str,
+        policy: MobileSafetyPolicy,
+    ) -> Result<MobileDevice> {
+        let device = self
+            .devices
+            .get_mut(device_id)
+            .ok_or_else(|| anyhow!("Device not found"))?;
+
+        device.safety_policy = policy;
+        let device = device.clone();
+        self.save_devices().await?;
+
+        Ok(device)
+    }
+
+    pub fn last_biometric_verification(&self, device_id: &str) -> Option<i64> {
+        self.biometric_verified_at.get(device_id).copied()
+    }
+
     /// Remove a device
     pub async fn remove_device(&mut self, device_id: String) -> Result<()> {
         self.devices.remove(&device_id);
@@ -0,0 +1,680 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+/// Origin used for changes made on the desktop itself.
+pub const DESKTOP_ORIGIN: &str = "desktop";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncCategory {
+    Watchlists,
+    Alerts,
+    JournalDrafts,
+    /// Per-mint notes and tags, keyed by mint.
+    TokenNotes,
+}
+
+impl SyncCategory {
+    pub const ALL: [SyncCategory; 4] = [
+        SyncCategory::Watchlists,
+        SyncCategory::Alerts,
+        SyncCategory::JournalDrafts,
+        SyncCategory::TokenNotes,
+    ];
+}
+
+/// Per-category change counters. A device that has applied everything up to
+/// `versions[category]` only needs records with a higher version.
+pub type SyncVersions = BTreeMap<SyncCategory, u64>;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SyncRecord {
+    pub category: SyncCategory,
+    pub id: String,
+    pub version: u64,
+    /// Unix millis of the change that produced this version.
+    pub updated_at: i64,
+    /// Device id that made the change, or `desktop`.
+    pub origin: String,
+    pub deleted: bool,
+    pub data: Value,
+}
+
+/// A change pushed by a device (or ingested from the desktop).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordChange {
+    pub category: SyncCategory,
+    pub id: String,
+    /// Version of the record the change was made against; 0 for new records.
+    #[serde(default)]
+    pub base_version: u64,
+    pub updated_at: i64,
+    #[serde(default)]
+    pub deleted: bool,
+    #[serde(default)]
+    pub data: Value,
+}
+
+/// Both sides changed the same structural field (a list or nested object) and
+/// neither can win automatically. The current value is kept until resolved.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SyncConflict {
+    pub conflict_id: String,
+    pub category: SyncCategory,
+    pub record_id: String,
+    pub field: String,
+    pub device_id: String,
+    pub device_value: Value,
+    pub current_value: Value,
+    pub detected_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "strategy", content = "value", rename_all = "snake_case")]
+pub enum ConflictResolution {
+    KeepCurrent,
+    KeepDevice,
+    Custom(Value),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum ChangeOutcome {
+    Applied { version: u64 },
+    Unchanged,
+    Conflicted { conflict_ids: Vec<String> },
+}
+
+#[derive(Debug, Clone, Copy)]
+struct FieldStamp {
+    version: u64,
+    updated_at: i64,
+}
+
+#[derive(Debug, Clone)]
+struct StoredRecord {
+    record: SyncRecord,
+    fields: HashMap<String, FieldStamp>,
+}
+
+/// Versioned record store behind delta sync. Concurrent edits merge field by
+/// field: scalar fields are last-writer-wins on `updated_at`, while lists and
+/// nested objects changed on both sides become [`SyncConflict`]s.
+#[derive(Debug, Default)]
+pub struct SyncStore {
+    records: HashMap<(SyncCategory, String), StoredRecord>,
+    versions: SyncVersions,
+    conflicts: Vec<SyncConflict>,
+}
+
+impl SyncStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn versions(&self) -> SyncVersions {
+        SyncCategory::ALL
+            .into_iter()
+            .map(|category| (category, self.version(category)))
+            .collect()
+    }
+
+    pub fn version(&self, category: SyncCategory) -> u64 {
+        self.versions.get(&category).copied().unwrap_or(0)
+    }
+
+    pub fn get(&self, category: SyncCategory, id: &str) -> Option<&SyncRecord> {
+        self.records
+            .get(&(category, id.to_string()))
+            .map(|stored| &stored.record)
+    }
+
+    pub fn conflicts(&self) -> &[SyncConflict] {
+        &self.conflicts
+    }
+
+    /// Records changed after `since`, ordered by category then version.
+    pub fn changes_since(&self, since: &SyncVersions) -> Vec<SyncRecord> {
+        let mut changed: Vec<SyncRecord> = self
+            .records
+            .values()
+            .filter(|stored| {
+                stored.record.version > since.get(&stored.record.category).copied().unwrap_or(0)
+            })
+            .map(|stored| stored.record.clone())
+            .collect();
+        changed.sort_by_key(|record| (record.category, record.version));
+        changed
+    }
+
+    fn bump(&mut self, category: SyncCategory) -> u64 {
+        let version = self.versions.entry(category).or_insert(0);
+        *version += 1;
+        *version
+    }
+
+    pub fn apply_change(&mut self, origin: &str, change: RecordChange, now: i64) -> ChangeOutcome {
+        let key = (change.category, change.id.clone());
+        let Some(stored) = self.records.get(&key).cloned() else {
+            if change.deleted {
+                return ChangeOutcome::Unchanged;
+            }
+            let version = self.bump(change.category);
+            let stamp = FieldStamp {
+                version,
+                updated_at: change.updated_at,
+            };
+            let fields = field_names(&change.data)
+                .into_iter()
+                .map(|name| (name, stamp))
+                .collect();
+            self.records.insert(
+                key,
+                StoredRecord {
+                    record: SyncRecord {
+                        category: change.category,
+                        id: change.id,
+                        version,
+                        updated_at: change.updated_at,
+                        origin: origin.to_string(),
+                        deleted: false,
+                        data: change.data,
+                    },
+                    fields,
+                },
+            );
+            return ChangeOutcome::Applied { version };
+        };
+
+        let concurrent = change.base_version < stored.record.version;
+
+        // Deletes and undeletes are whole-record changes, settled by last writer.
+        if change.deleted || stored.record.deleted {
+            if change.deleted == stored.record.deleted
+                && (change.deleted || change.data == stored.record.data)
+            {
+                return ChangeOutcome::Unchanged;
+            }
+            if concurrent && change.updated_at < stored.record.updated_at {
+                return ChangeOutcome::Unchanged;
+            }
+            let version = self.bump(change.category);
+            let stamp = FieldStamp {
+                version,
+                updated_at: change.updated_at,
+            };
+            let entry = self.records.get_mut(&key).expect("record exists");
+            entry.fields = field_names(&change.data)
+                .into_iter()
+                .map(|name| (name, stamp))
+                .collect();
+            entry.record.version = version;
+            entry.record.updated_at = change.updated_at;
+            entry.record.origin = origin.to_string();
+            entry.record.deleted = change.deleted;
+            if !change.deleted {
+                entry.record.data = change.data;
+            }
+            return ChangeOutcome::Applied { version };
+        }
+
+        let (Some(current), Some(incoming)) =
+            (stored.record.data.as_object(), change.data.as_object())
+        else {
+            // Non-object payloads are a single value.
+            if change.data == stored.record.data
+                || (concurrent && change.updated_at < stored.record.updated_at)
+            {
+                return ChangeOutcome::Unchanged;
+            }
+            let version = self.bump(change.category);
+            let entry = self.records.get_mut(&key).expect("record exists");
+            entry.record.version = version;
+            entry.record.updated_at = change.updated_at;
+            entry.record.origin = origin.to_string();
+            entry.record.data = change.data;
+            return ChangeOutcome::Applied { version };
+        };
+
+        let mut merged = current.clone();
+        let mut changed_fields = Vec::new();
+        let mut conflicts = Vec::new();
+
+        let mut names: Vec<&String> = current.keys().chain(incoming.keys()).collect();
+        names.sort();
+        names.dedup();
+
+        for name in names {
+            let ours = current.get(name);
+            let theirs = incoming.get(name);
+            if ours == theirs {
+                continue;
+            }
+            let stamp = stored.fields.get(name).copied();
+            let changed_since_base = stamp.is_some_and(|s| s.version > change.base_version);
+            if !concurrent || !changed_since_base {
+                set_field(&mut merged, name, theirs);
+                changed_fields.push(name.clone());
+                continue;
+            }
+            if is_structural(ours) || is_structural(theirs) {
+                conflicts.push(SyncConflict {
+                    conflict_id: Uuid::new_v4().to_string(),
+                    category: change.category,
+                    record_id: change.id.clone(),
+                    field: name.clone(),
+                    device_id: origin.to_string(),
+                    device_value: theirs.cloned().unwrap_or(Value::Null),
+                    current_value: ours.cloned().unwrap_or(Value::Null),
+                    detected_at: now,
+                });
+            } else if stamp.map(|s| s.updated_at).unwrap_or(i64::MIN) <= change.updated_at {
+                set_field(&mut merged, name, theirs);
+                changed_fields.push(name.clone());
+            }
+        }
+
+        let conflict_ids: Vec<String> = conflicts.iter().map(|c| c.conflict_id.clone()).collect();
+        for conflict in conflicts {
+            self.record_conflict(conflict);
+        }
+
+        if changed_fields.is_empty() {
+            return if conflict_ids.is_empty() {
+                ChangeOutcome::Unchanged
+            } else {
+                ChangeOutcome::Conflicted { conflict_ids }
+            };
+        }
+
+        let version = self.bump(change.category);
+        let stamp = FieldStamp {
+            version,
+            updated_at: change.updated_at,
+        };
+        let entry = self.records.get_mut(&key).expect("record exists");
+        for name in changed_fields {
+            entry.fields.insert(name, stamp);
+        }
+        entry.record.version = version;
+        entry.record.updated_at = entry.record.updated_at.max(change.updated_at);
+        entry.record.origin = origin.to_string();
+        entry.record.data = Value::Object(merged);
+
+        if conflict_ids.is_empty() {
+            ChangeOutcome::Applied { version }
+        } else {
+            ChangeOutcome::Conflicted { conflict_ids }
+        }
+    }
+
+    /// A newer conflict on the same field from the same device replaces the old one.
+    fn record_conflict(&mut self, conflict: SyncConflict) {
+        self.conflicts.retain(|existing| {
+            !(existing.category == conflict.category
+                && existing.record_id == conflict.record_id
+                && existing.field == conflict.field
+                && existing.device_id == conflict.device_id)
+        });
+        self.conflicts.push(conflict);
+    }
+
+    pub fn resolve_conflict(
+        &mut self,
+        conflict_id: &str,
+        resolution: ConflictResolution,
+        now: i64,
+    ) -> Result<SyncRecord, String> {
+        let index = self
+            .conflicts
+            .iter()
+            .position(|c| c.conflict_id == conflict_id)
+            .ok_or_else(|| format!("Conflict {} not found", conflict_id))?;
+        let conflict = self.conflicts.remove(index);
+        let key = (conflict.category, conflict.record_id.clone());
+
+        let value = match resolution {
+            ConflictResolution::KeepCurrent => None,
+            ConflictResolution::KeepDevice => Some(conflict.device_value),
+            ConflictResolution::Custom(value) => Some(value),
+        };
+
+        let Some(value) = value else {
+            return self
+                .get(conflict.category, &conflict.record_id)
+                .cloned()
+                .ok_or_else(|| format!("Record {} no longer exists", conflict.record_id));
+        };
+
+        let version = self.bump(conflict.category);
+        let entry = self
+            .records
+            .get_mut(&key)
+            .ok_or_else(|| format!("Record {} no longer exists", conflict.record_id))?;
+        if let Some(object) = entry.record.data.as_object_mut() {
+            object.insert(conflict.field.clone(), value);
+        }
+        entry.fields.insert(
+            conflict.field,
+            FieldStamp {
+                version,
+                updated_at: now,
+            },
+        );
+        entry.record.version = version;
+        entry.record.updated_at = now;
+        Ok(entry.record.clone())
+    }
+
+    /// Diffs a full snapshot of one category from the desktop against what was
+    /// ingested last time. Only records that changed on the desktop since then
+    /// are applied, so device edits are not overwritten by an unchanged snapshot;
+    /// records missing from the snapshot are deleted.
+    pub fn ingest_snapshot(
+        &mut self,
+        category: SyncCategory,
+        snapshot: Vec<(String, Value)>,
+        last_ingested: &mut HashMap<String, (Value, u64)>,
+        now: i64,
+    ) {
+        let mut seen = Vec::with_capacity(snapshot.len());
+        for (id, data) in snapshot {
+            seen.push(id.clone());
+            let base_version = match last_ingested.get(&id) {
+                Some((previous, _)) if *previous == data => continue,
+                Some((_, version)) => *version,
+                None => self.get(category, &id).map(|r| r.version).unwrap_or(0),
+            };
+            let change = RecordChange {
+                category,
+                id: id.clone(),
+                base_version,
+                updated_at: now,
+                deleted: false,
+                data: data.clone(),
+            };
+            self.apply_change(DESKTOP_ORIGIN, change, now);
+            let version = self.get(category, &id).map(|r| r.version).unwrap_or(0);
+            last_ingested.insert(id, (data, version));
+        }
+
+        let removed: Vec<String> = last_ingested
+            .keys()
+            .filter(|id| !seen.contains(id))
+            .cloned()
+            .collect();
+        for id in removed {
+            let base_version = last_ingested.remove(&id).map(|(_, v)| v).unwrap_or(0);
+            self.apply_change(
+                DESKTOP_ORIGIN,
+                RecordChange {
+                    category,
+                    id,
+                    base_version,
+                    updated_at: now,
+                    deleted: true,
+                    data: Value::Null,
+                },
+                now,
+            );
+        }
+    }
+}
+
+fn field_names(data: &Value) -> Vec<String> {
+    data.as_object()
+        .map(|object| object.keys().cloned().collect())
+        .unwrap_or_default()
+}
+
+fn set_field(object: &mut Map<String, Value>, name: &str, value: Option<&Value>) {
+    match value {
+        Some(value) => {
+            object.insert(name.to_string(), value.clone());
+        }
+        None => {
+            object.remove(name);
+        }
+    }
+}
+
+fn is_structural(value: Option<&Value>) -> bool {
+    matches!(value, Some(Value::Array(_)) | Some(Value::Object(_)))
+}
+
+/// A paged transfer of one delta. The record set is fixed when the session
+/// starts, so a device that drops mid-transfer resumes from its last cursor
+/// and gets the same records regardless of changes made in the meantime.
+#[derive(Debug, Clone)]
+pub struct SyncSession {
+    pub session_id: String,
+    pub device_id: String,
+    pub target_versions: SyncVersions,
+    pub records: Vec<SyncRecord>,
+    pub cursor: usize,
+    pub started_at: i64,
+    pub updated_at: i64,
+}
+
+impl SyncSession {
+    pub fn new(
+        device_id: String,
+        records: Vec<SyncRecord>,
+        target: SyncVersions,
+        now: i64,
+    ) -> Self {
+        Self {
+            session_id: Uuid::new_v4().to_string(),
+            device_id,
+            target_versions: target,
+            records,
+            cursor: 0,
+            started_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// Returns the page starting at `cursor` (clamped to what exists).
+    pub fn page(&mut self, cursor: usize, page_size: usize, now: i64) -> &[SyncRecord] {
+        self.cursor = cursor.min(self.records.len());
+        self.updated_at = now;
+        let end = (self.cursor + page_size.max(1)).min(self.records.len());
+        &self.records[self.cursor..end]
+    }
+
+    pub fn is_complete(&self, cursor: usize) -> bool {
+        cursor >= self.records.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn change(id: &str, base: u64, at: i64, data: Value) -> RecordChange {
+        RecordChange {
+            category: SyncCategory::Watchlists,
+            id: id.to_string(),
+            base_version: base,
+            updated_at: at,
+            deleted: false,
+            data,
+        }
+    }
+
+    #[test]
+    fn deltas_only_include_newer_records() {
+        let mut store = SyncStore::new();
+        store.apply_change("phone", change("a", 0, 1, json!({ "name": "A" })), 1);
+        store.apply_change("phone", change("b", 0, 2, json!({ "name": "B" })), 2);
+        store.apply_change(
+            "phone",
+            RecordChange {
+                category: SyncCategory::Alerts,
+                ..change("x", 0, 3, json!({ "value": 1 }))
+            },
+            3,
+        );
+
+        let mut since = SyncVersions::new();
+        since.insert(SyncCategory::Watchlists, 1);
+        let delta = store.changes_since(&since);
+        assert_eq!(delta.len(), 2);
+        assert_eq!(delta[0].id, "b");
+        assert_eq!(delta[1].category, SyncCategory::Alerts);
+        assert_eq!(store.versions()[&SyncCategory::JournalDrafts], 0);
+    }
+
+    #[test]
+    fn concurrent_scalar_edits_are_last_writer_wins() {
+        let mut store = SyncStore::new();
+        store.apply_change(
+            "desktop",
+            change("w", 0, 10, json!({ "name": "Main", "pinned": false })),
+            10,
+        );
+
+        // Both sides edit version 1; the desktop renames, the phone renames later
+        // and also pins, which the desktop did not touch.
+        store.apply_change(
+            "desktop",
+            change("w", 1, 20, json!({ "name": "Desk", "pinned": false })),
+            20,
+        );
+        let outcome = store.apply_change(
+            "phone",
+            change("w", 1, 30, json!({ "name": "Phone", "pinned": true })),
+            30,
+        );
+        assert!(matches!(outcome, ChangeOutcome::Applied { version: 3 }));
+        let record = store.get(SyncCategory::Watchlists, "w").unwrap();
+        assert_eq!(record.data, json!({ "name": "Phone", "pinned": true }));
+
+        // An older concurrent edit loses the name but nothing is lost silently.
+        store.apply_change(
+            "tablet",
+            change("w", 1, 15, json!({ "name": "Tab", "pinned": true })),
+            40,
+        );
+        assert_eq!(
+            store.get(SyncCategory::Watchlists, "w").unwrap().data["name"],
+            "Phone"
+        );
+        assert!(store.conflicts().is_empty());
+    }
+
+    #[test]
+    fn reorders_on_both_sides_become_conflicts() {
+        let mut store = SyncStore::new();
+        store.apply_change(
+            "desktop",
+            change("w", 0, 1, json!({ "items": ["SOL", "BONK", "JUP"] })),
+            1,
+        );
+        store.apply_change(
+            "desktop",
+            change("w", 1, 2, json!({ "items": ["JUP", "SOL", "BONK"] })),
+            2,
+        );
+
+        let outcome = store.apply_change(
+            "phone",
+            change("w", 1, 3, json!({ "items": ["BONK", "SOL", "JUP"] })),
+            3,
+        );
+        let ChangeOutcome::Conflicted { conflict_ids } = outcome else {
+            panic!("expected conflict, got {:?}", outcome);
+        };
+        assert_eq!(conflict_ids.len(), 1);
+        assert_eq!(
+            store.get(SyncCategory::Watchlists, "w").unwrap().data["items"],
+            json!(["JUP", "SOL", "BONK"])
+        );
+
+        let conflict = store.conflicts()[0].clone();
+        assert_eq!(conflict.field, "items");
+        assert_eq!(conflict.device_id, "phone");
+
+        let resolved = store
+            .resolve_conflict(&conflict.conflict_id, ConflictResolution::KeepDevice, 4)
+            .unwrap();
+        assert_eq!(resolved.data["items"], json!(["BONK", "SOL", "JUP"]));
+        assert_eq!(resolved.version, 3);
+        assert!(store.conflicts().is_empty());
+
+        // A reorder made against the latest version applies directly.
+        let outcome = store.apply_change(
+            "phone",
+            change("w", 3, 5, json!({ "items": ["SOL", "BONK", "JUP"] })),
+            5,
+        );
+        assert!(matches!(outcome, ChangeOutcome::Applied { .. }));
+    }
+
+    #[test]
+    fn snapshot_ingest_keeps_device_edits_and_tracks_deletes() {
+        let mut store = SyncStore::new();
+        let mut ingested = HashMap::new();
+        store.ingest_snapshot(
+            SyncCategory::Alerts,
+            vec![
+                ("a1".into(), json!({ "value": 100 })),
+                ("a2".into(), json!({ "value": 5 })),
+            ],
+            &mut ingested,
+            1,
+        );
+        store.apply_change(
+            "phone",
+            RecordChange {
+                category: SyncCategory::Alerts,
+                ..change("a1", 1, 2, json!({ "value": 120 }))
+            },
+            2,
+        );
+
+        // Same desktop state again: the phone's edit stands, a2 is gone.
+        store.ingest_snapshot(
+            SyncCategory::Alerts,
+            vec![("a1".into(), json!({ "value": 100 }))],
+            &mut ingested,
+            3,
+        );
+        assert_eq!(
+            store.get(SyncCategory::Alerts, "a1").unwrap().data["value"],
+            120
+        );
+        assert!(store.get(SyncCategory::Alerts, "a2").unwrap().deleted);
+    }
+
+    #[test]
+    fn sessions_resume_from_cursor() {
+        let mut store = SyncStore::new();
+        for i in 0..5 {
+            store.apply_change(
+                "desktop",
+                change(&format!("w{i}"), 0, i, json!({ "n": i })),
+                i,
+            );
+        }
+        let mut session = SyncSession::new(
+            "phone".into(),
+            store.changes_since(&SyncVersions::new()),
+            store.versions(),
+            0,
+        );
+
+        assert_eq!(session.page(0, 2, 1).len(), 2);
+        // The connection dropped before the second page was applied; the device
+        // resumes from cursor 2 and later changes do not leak in.
+        store.apply_change("desktop", change("w9", 0, 9, json!({ "n": 9 })), 9);
+        let page: Vec<String> = session.page(2, 2, 2).iter().map(|r| r.id.clone()).collect();
+        assert_eq!(page, vec!["w2", "w3"]);
+        assert_eq!(session.page(4, 2, 3).len(), 1);
+        assert!(session.is_complete(5));
+        assert_eq!(session.target_versions[&SyncCategory::Watchlists], 5);
+    }
+}
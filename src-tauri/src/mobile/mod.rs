@@ -1,10 +1,12 @@
 pub mod auth;
+pub mod delta;
 pub mod push;
 pub mod sync;
 pub mod trades;
 pub mod widgets;
 
 pub use auth::*;
+pub use delta::*;
 pub use push::*;
 pub use sync::*;
 pub use trades::*;
@@ -22,6 +24,10 @@ pub struct MobileDevice {
     pub push_token: Option<String>,
     pub last_sync: Option<i64>,
     pub biometric_enabled: bool,
+    #[serde(default)]
+    pub delivery_policy: push::PushDeliveryPolicy,
+    #[serde(default)]
+    pub safety_policy: trades::MobileSafetyPolicy,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -37,3 +43,4 @@ pub struct MobileSession {
 pub type SharedMobileAuthManager = Arc<RwLock<auth::MobileAuthManager>>;
 pub type SharedPushNotificationManager = Arc<RwLock<push::PushNotificationManager>>;
 pub type SharedMobileSyncManager = Arc<RwLock<sync::MobileSyncManager>>;
+pub type SharedWidgetManager = Arc<RwLock<widgets::WidgetManager>>;
@@ -1,25 +1,41 @@
 use crate::mobile::{MobileDevice, SharedMobileAuthManager};
 use anyhow::{anyhow, Result};
-use chrono::Utc;
+use chrono::{NaiveTime, Timelike, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, VecDeque};
+use std::path::Path;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
+const SECONDS_PER_DAY: i64 = 86_400;
+const DEFAULT_BATCH_WINDOW_SECS: i64 = 300;
+/// Titles listed in a summary body before it falls back to "and N more".
+const SUMMARY_TITLE_LIMIT: usize = 3;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PushNotification {
     pub notification_id: String,
     pub device_id: String,
     pub category: NotificationCategory,
+    #[serde(default)]
+    pub priority: PushPriority,
     pub title: String,
     pub body: String,
     pub payload: serde_json::Value,
     pub created_at: i64,
     pub delivered_at: Option<i64>,
+    #[serde(default)]
+    pub state: PushDeliveryState,
+    /// When a held or batched notification is released for delivery.
+    #[serde(default)]
+    pub release_at: Option<i64>,
+    /// Ids of the notifications folded into this summary push.
+    #[serde(default)]
+    pub coalesced: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum NotificationCategory {
     Alert,
@@ -29,6 +45,135 @@ pub enum NotificationCategory {
     System,
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PushPriority {
+    Low,
+    #[default]
+    Normal,
+    High,
+    /// Delivered immediately, even during quiet hours.
+    Critical,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PushDeliveryState {
+    /// Ready to be picked up by the device.
+    #[default]
+    InFlight,
+    HeldForQuietHours,
+    Batched,
+}
+
+/// Quiet hours in the device's local time, as `HH:MM`. Overnight ranges such
+/// as 22:00-07:00 are supported.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuietHours {
+    pub start: String,
+    pub end: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PushDeliveryPolicy {
+    #[serde(default)]
+    pub quiet_hours: Option<QuietHours>,
+    /// The device's current offset from UTC in minutes. Devices report it with
+    /// the policy and again when it changes (travel, daylight saving).
+    #[serde(default)]
+    pub utc_offset_minutes: i32,
+    /// Low-priority pushes arriving within this window go out as one summary.
+    /// Zero disables batching.
+    #[serde(default = "default_batch_window_secs")]
+    pub batch_window_secs: i64,
+}
+
+fn default_batch_window_secs() -> i64 {
+    DEFAULT_BATCH_WINDOW_SECS
+}
+
+impl Default for PushDeliveryPolicy {
+    fn default() -> Self {
+        Self {
+            quiet_hours: None,
+            utc_offset_minutes: 0,
+            batch_window_secs: DEFAULT_BATCH_WINDOW_SECS,
+        }
+    }
+}
+
+fn parse_local_time(value: &str) -> Result<i64> {
+    let time = NaiveTime::parse_from_str(value, "%H:%M")
+        .map_err(|_| anyhow!("Invalid time '{}', expected HH:MM", value))?;
+    Ok(time.num_seconds_from_midnight() as i64)
+}
+
+impl PushDeliveryPolicy {
+    pub fn validate(&self) -> Result<()> {
+        if let Some(quiet) = &self.quiet_hours {
+            parse_local_time(&quiet.start)?;
+            parse_local_time(&quiet.end)?;
+        }
+        if !(-14 * 60..=14 * 60).contains(&self.utc_offset_minutes) {
+            return Err(anyhow!("UTC offset must be within ±14 hours"));
+        }
+        if self.batch_window_secs < 0 {
+            return Err(anyhow!("Batch window cannot be negative"));
+        }
+        Ok(())
+    }
+
+    /// If `at` (unix seconds) falls inside quiet hours, the unix time they end.
+    pub fn quiet_until(&self, at: i64) -> Option<i64> {
+        let quiet = self.quiet_hours.as_ref()?;
+        let start = parse_local_time(&quiet.start).ok()?;
+        let end = parse_local_time(&quiet.end).ok()?;
+        if start == end {
+            return None;
+        }
+
+        let offset = self.utc_offset_minutes as i64 * 60;
+        let local = at + offset;
+        let time_of_day = local.rem_euclid(SECONDS_PER_DAY);
+        let quiet_now = if start < end {
+            time_of_day >= start && time_of_day < end
+        } else {
+            time_of_day >= start || time_of_day < end
+        };
+        if !quiet_now {
+            return None;
+        }
+
+        let until_end = if time_of_day < end {
+            end - time_of_day
+        } else {
+            SECONDS_PER_DAY - time_of_day + end
+        };
+        Some(at + until_end)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingPushNotifications {
+    pub in_flight: Vec<PushNotification>,
+    pub held_for_quiet_hours: Vec<PushNotification>,
+    pub batched: Vec<PushNotification>,
+    /// Earliest time a held or batched notification is released.
+    pub next_release_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuePushRequest {
+    pub device_id: String,
+    pub category: NotificationCategory,
+    #[serde(default)]
+    pub priority: PushPriority,
+    pub title: String,
+    pub body: String,
+    #[serde(default)]
+    pub payload: serde_json::Value,
+}
+
 pub struct PushNotificationManager {
     pub notifications: HashMap<String, PushNotification>,
     pub queue: VecDeque<String>,
@@ -46,28 +191,66 @@ impl PushNotificationManager {
 
     pub fn create_notification(
         &mut self,
-        device_id: String,
-        category: NotificationCategory,
-        title: String,
-        body: String,
-        payload: serde_json::Value,
+        request: QueuePushRequest,
+        policy: &PushDeliveryPolicy,
     ) -> PushNotification {
-        let notification = PushNotification {
+        self.create_notification_at(request, policy, Utc::now().timestamp())
+    }
+
+    fn create_notification_at(
+        &mut self,
+        request: QueuePushRequest,
+        policy: &PushDeliveryPolicy,
+        now: i64,
+    ) -> PushNotification {
+        let mut notification = PushNotification {
             notification_id: Uuid::new_v4().to_string(),
-            device_id,
-            category,
-            title,
-            body,
-            payload,
-            created_at: Utc::now().timestamp(),
+            device_id: request.device_id,
+            category: request.category,
+            priority: request.priority,
+            title: request.title,
+            body: request.body,
+            payload: request.payload,
+            created_at: now,
             delivered_at: None,
+            state: PushDeliveryState::InFlight,
+            release_at: None,
+            coalesced: Vec::new(),
         };
 
+        let (state, release_at) = self.route(&notification, policy, now);
+        notification.state = state;
+        notification.release_at = release_at;
         self.queue_notification(notification.clone());
 
         notification
     }
 
+    fn route(
+        &self,
+        notification: &PushNotification,
+        policy: &PushDeliveryPolicy,
+        now: i64,
+    ) -> (PushDeliveryState, Option<i64>) {
+        if notification.priority == PushPriority::Critical {
+            return (PushDeliveryState::InFlight, None);
+        }
+        if let Some(until) = policy.quiet_until(now) {
+            return (PushDeliveryState::HeldForQuietHours, Some(until));
+        }
+        if notification.priority == PushPriority::Low && policy.batch_window_secs > 0 {
+            // Join the device's open batch so the whole window shares one push.
+            let open_batch = self
+                .device_notifications(&notification.device_id)
+                .filter(|n| n.state == PushDeliveryState::Batched)
+                .filter_map(|n| n.release_at)
+                .min();
+            let release_at = open_batch.unwrap_or(now + policy.batch_window_secs);
+            return (PushDeliveryState::Batched, Some(release_at));
+        }
+        (PushDeliveryState::InFlight, None)
+    }
+
     fn queue_notification(&mut self, notification: PushNotification) {
         if self.queue.len() >= self.max_queue_size {
             if let Some(oldest) = self.queue.pop_front() {
@@ -80,30 +263,127 @@ impl PushNotificationManager {
             .insert(notification.notification_id.clone(), notification);
     }
 
+    fn device_notifications<'a>(
+        &'a self,
+        device_id: &'a str,
+    ) -> impl Iterator<Item = &'a PushNotification> + 'a {
+        self.queue
+            .iter()
+            .filter_map(|id| self.notifications.get(id))
+            .filter(move |n| n.device_id == device_id && n.delivered_at.is_none())
+    }
+
+    /// Releases held and batched notifications whose time has come. Anything
+    /// released during quiet hours is held again until they end; several
+    /// notifications released together are coalesced into one summary push.
+    pub fn release_due(&mut self, device_id: &str, policy: &PushDeliveryPolicy, now: i64) {
+        let due: Vec<String> = self
+            .device_notifications(device_id)
+            .filter(|n| n.state != PushDeliveryState::InFlight)
+            .filter(|n| n.release_at.is_some_and(|at| at <= now))
+            .map(|n| n.notification_id.clone())
+            .collect();
+        if due.is_empty() {
+            return;
+        }
+
+        if let Some(until) = policy.quiet_until(now) {
+            for id in &due {
+                if let Some(notification) = self.notifications.get_mut(id) {
+                    notification.state = PushDeliveryState::HeldForQuietHours;
+                    notification.release_at = Some(until);
+                }
+            }
+            return;
+        }
+
+        if let [only] = due.as_slice() {
+            if let Some(notification) = self.notifications.get_mut(only) {
+                notification.state = PushDeliveryState::InFlight;
+                notification.release_at = None;
+            }
+            return;
+        }
+
+        let released: Vec<PushNotification> = due
+            .iter()
+            .filter_map(|id| self.notifications.remove(id))
+            .collect();
+        self.queue.retain(|id| !due.contains(id));
+        self.queue_notification(summarize(device_id, released, now));
+    }
+
     pub fn mark_delivered(&mut self, notification_id: &str) {
         if let Some(notification) = self.notifications.get_mut(notification_id) {
             notification.delivered_at = Some(Utc::now().timestamp());
         }
     }
 
-    pub fn get_pending_notifications(&self, device_id: &str) -> Vec<PushNotification> {
-        self.queue
+    pub fn get_pending_notifications(&self, device_id: &str) -> PendingPushNotifications {
+        let mut pending = PendingPushNotifications {
+            in_flight: Vec::new(),
+            held_for_quiet_hours: Vec::new(),
+            batched: Vec::new(),
+            next_release_at: None,
+        };
+
+        for notification in self.device_notifications(device_id) {
+            if notification.state != PushDeliveryState::InFlight {
+                pending.next_release_at = match (pending.next_release_at, notification.release_at) {
+                    (Some(a), Some(b)) => Some(a.min(b)),
+                    (a, b) => a.or(b),
+                };
+            }
+            let bucket = match notification.state {
+                PushDeliveryState::InFlight => &mut pending.in_flight,
+                PushDeliveryState::HeldForQuietHours => &mut pending.held_for_quiet_hours,
+                PushDeliveryState::Batched => &mut pending.batched,
+            };
+            bucket.push(notification.clone());
+        }
+
+        pending
+    }
+
+    /// Writes undelivered notifications, in queue order, so pushes held for
+    /// quiet hours or waiting in a batch survive a restart.
+    pub fn save_pending(&self, path: &Path) -> Result<usize> {
+        let pending: Vec<&PushNotification> = self
+            .queue
             .iter()
             .filter_map(|id| self.notifications.get(id))
-            .filter(|notification| {
-                notification.device_id == device_id && notification.delivered_at.is_none()
-            })
-            .cloned()
-            .collect()
+            .filter(|n| n.delivered_at.is_none())
+            .collect();
+        std::fs::write(path, serde_json::to_vec(&pending)?)?;
+        Ok(pending.len())
     }
 
+    /// Re-queues notifications written by `save_pending`. The file is removed
+    /// so a later crash cannot queue them twice.
+    pub fn load_pending(&mut self, path: &Path) -> Result<usize> {
+        if !path.exists() {
+            return Ok(0);
+        }
+        let pending: Vec<PushNotification> = serde_json::from_slice(&std::fs::read(path)?)?;
+        let restored = pending.len();
+        for notification in pending {
+            self.queue_notification(notification);
+        }
+        std::fs::remove_file(path)?;
+        Ok(restored)
+    }
+
+    /// Takes the next in-flight notification, highest priority first.
     pub fn dequeue_next(&mut self, device_id: &str) -> Option<PushNotification> {
-        let position = self.queue.iter().position(|id| {
-            self.notifications
-                .get(id)
-                .map(|n| n.device_id.as_str() == device_id)
-                .unwrap_or(false)
-        });
+        let position = self
+            .queue
+            .iter()
+            .enumerate()
+            .filter_map(|(pos, id)| Some((pos, self.notifications.get(id)?)))
+            .filter(|(_, n)| n.device_id == device_id && n.state == PushDeliveryState::InFlight)
+            // Earliest position wins among equal priorities.
+            .max_by_key(|(pos, n)| (n.priority, std::cmp::Reverse(*pos)))
+            .map(|(pos, _)| pos);
 
         if let Some(pos) = position {
             if let Some(id) = self.queue.remove(pos) {
@@ -115,28 +395,88 @@ impl PushNotificationManager {
     }
 }
 
+fn summarize(device_id: &str, released: Vec<PushNotification>, now: i64) -> PushNotification {
+    let held = released
+        .iter()
+        .any(|n| n.state == PushDeliveryState::HeldForQuietHours);
+    let category = match released.first() {
+        Some(first) if released.iter().all(|n| n.category == first.category) => {
+            first.category.clone()
+        }
+        _ => NotificationCategory::System,
+    };
+    let priority = released
+        .iter()
+        .map(|n| n.priority)
+        .max()
+        .unwrap_or_default();
+
+    let mut titles: Vec<&str> = released
+        .iter()
+        .take(SUMMARY_TITLE_LIMIT)
+        .map(|n| n.title.as_str())
+        .collect();
+    let more = released.len().saturating_sub(SUMMARY_TITLE_LIMIT);
+    let more_text = format!("{} more", more);
+    if more > 0 {
+        titles.push(&more_text);
+    }
+
+    let title = if held {
+        format!("{} notifications during quiet hours", released.len())
+    } else {
+        format!("{} new notifications", released.len())
+    };
+    let items: Vec<serde_json::Value> = released
+        .iter()
+        .map(|n| {
+            serde_json::json!({
+                "notification_id": n.notification_id,
+                "category": n.category,
+                "title": n.title,
+                "body": n.body,
+                "payload": n.payload,
+                "created_at": n.created_at,
+            })
+        })
+        .collect();
+
+    PushNotification {
+        notification_id: Uuid::new_v4().to_string(),
+        device_id: device_id.to_string(),
+        category,
+        priority,
+        title,
+        body: titles.join(", "),
+        payload: serde_json::json!({ "notifications": items }),
+        created_at: now,
+        delivered_at: None,
+        state: PushDeliveryState::InFlight,
+        release_at: None,
+        coalesced: released.into_iter().map(|n| n.notification_id).collect(),
+    }
+}
+
+async fn device_policy(
+    mobile_auth: &Arc<RwLock<crate::mobile::auth::MobileAuthManager>>,
+    device_id: &str,
+) -> Result<PushDeliveryPolicy, String> {
+    let auth = mobile_auth.read().await;
+    auth.get_device(device_id)
+        .map(|device| device.delivery_policy)
+        .ok_or_else(|| "Device not registered".to_string())
+}
+
 #[tauri::command]
 pub async fn mobile_queue_notification(
-    device_id: String,
-    category: NotificationCategory,
-    title: String,
-    body: String,
-    payload: serde_json::Value,
+    request: QueuePushRequest,
     push_manager: tauri::State<'_, Arc<RwLock<PushNotificationManager>>>,
     mobile_auth: tauri::State<'_, Arc<RwLock<crate::mobile::auth::MobileAuthManager>>>,
 ) -> Result<PushNotification, String> {
-    let devices = {
-        let auth = mobile_auth.read().await;
-        auth.get_devices()
-    };
-
-    let device_registered = devices.iter().any(|device| device.device_id == device_id);
-    if !device_registered {
-        return Err("Device not registered".into());
-    }
+    let policy = device_policy(&mobile_auth, &request.device_id).await?;
 
     let mut manager = push_manager.write().await;
-    Ok(manager.create_notification(device_id, category, title, body, payload))
+    Ok(manager.create_notification(request, &policy))
 }
 
 #[tauri::command]
@@ -144,18 +484,11 @@ pub async fn mobile_get_pending_notifications(
     device_id: String,
     push_manager: tauri::State<'_, Arc<RwLock<PushNotificationManager>>>,
     mobile_auth: tauri::State<'_, Arc<RwLock<crate::mobile::auth::MobileAuthManager>>>,
-) -> Result<Vec<PushNotification>, String> {
-    let devices = {
-        let auth = mobile_auth.read().await;
-        auth.get_devices()
-    };
+) -> Result<PendingPushNotifications, String> {
+    let policy = device_policy(&mobile_auth, &device_id).await?;
 
-    let device_registered = devices.iter().any(|device| device.device_id == device_id);
-    if !device_registered {
-        return Err("Device not registered".into());
-    }
-
-    let manager = push_manager.read().await;
+    let mut manager = push_manager.write().await;
+    manager.release_due(&device_id, &policy, Utc::now().timestamp());
     Ok(manager.get_pending_notifications(&device_id))
 }
 
@@ -165,16 +498,169 @@ pub async fn mobile_dequeue_notification(
     push_manager: tauri::State<'_, Arc<RwLock<PushNotificationManager>>>,
     mobile_auth: tauri::State<'_, Arc<RwLock<crate::mobile::auth::MobileAuthManager>>>,
 ) -> Result<Option<PushNotification>, String> {
-    let devices = {
-        let auth = mobile_auth.read().await;
-        auth.get_devices()
-    };
-
-    let device_registered = devices.iter().any(|device| device.device_id == device_id);
-    if !device_registered {
-        return Err("Device not registered".into());
-    }
+    let policy = device_policy(&mobile_auth, &device_id).await?;
 
     let mut manager = push_manager.write().await;
+    manager.release_due(&device_id, &policy, Utc::now().timestamp());
     Ok(manager.dequeue_next(&device_id))
 }
+
+#[tauri::command]
+pub async fn mobile_set_device_policy(
+    device_id: String,
+    policy: PushDeliveryPolicy,
+    mobile_auth: tauri::State<'_, Arc<RwLock<crate::mobile::auth::MobileAuthManager>>>,
+) -> Result<PushDeliveryPolicy, String> {
+    policy.validate().map_err(|e| e.to_string())?;
+
+    let mut auth = mobile_auth.write().await;
+    auth.set_delivery_policy(&device_id, policy)
+        .await
+        .map(|device| device.delivery_policy)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn mobile_get_device_policy(
+    device_id: String,
+    mobile_auth: tauri::State<'_, Arc<RwLock<crate::mobile::auth::MobileAuthManager>>>,
+) -> Result<PushDeliveryPolicy, String> {
+    device_policy(&mobile_auth, &device_id).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(priority: PushPriority, title: &str) -> QueuePushRequest {
+        QueuePushRequest {
+            device_id: "phone".to_string(),
+            category: NotificationCategory::Alert,
+            priority,
+            title: title.to_string(),
+            body: String::new(),
+            payload: serde_json::Value::Null,
+        }
+    }
+
+    fn quiet_policy(offset_minutes: i32) -> PushDeliveryPolicy {
+        PushDeliveryPolicy {
+            quiet_hours: Some(QuietHours {
+                start: "22:00".to_string(),
+                end: "07:00".to_string(),
+            }),
+            utc_offset_minutes: offset_minutes,
+            batch_window_secs: 300,
+        }
+    }
+
+    #[test]
+    fn quiet_hours_follow_device_offset() {
+        // 2024-01-01 03:00 UTC is 22:00 in UTC-5, the start of quiet hours.
+        let at = 1_704_078_000;
+        let policy = quiet_policy(-5 * 60);
+        assert_eq!(policy.quiet_until(at), Some(at + 9 * 3600));
+        assert_eq!(policy.quiet_until(at - 60), None);
+        assert_eq!(quiet_policy(0).quiet_until(at), Some(at + 4 * 3600));
+        assert!(PushDeliveryPolicy::default().quiet_until(at).is_none());
+
+        let bad = PushDeliveryPolicy {
+            quiet_hours: Some(QuietHours {
+                start: "25:00".to_string(),
+                end: "07:00".to_string(),
+            }),
+            ..PushDeliveryPolicy::default()
+        };
+        assert!(bad.validate().is_err());
+    }
+
+    #[test]
+    fn low_priority_pushes_coalesce_into_one_summary() {
+        let mut manager = PushNotificationManager::new(100);
+        let policy = PushDeliveryPolicy::default();
+
+        for i in 0..5 {
+            manager.create_notification(request(PushPriority::Low, &format!("Alert {i}")), &policy);
+        }
+        manager.create_notification(request(PushPriority::High, "Urgent"), &policy);
+
+        let pending = manager.get_pending_notifications("phone");
+        assert_eq!(pending.batched.len(), 5);
+        assert_eq!(pending.in_flight.len(), 1);
+        let release_at = pending.next_release_at.unwrap();
+        assert!(pending
+            .batched
+            .iter()
+            .all(|n| n.release_at == Some(release_at)));
+
+        manager.release_due("phone", &policy, release_at);
+        let pending = manager.get_pending_notifications("phone");
+        assert!(pending.batched.is_empty());
+        assert_eq!(pending.in_flight.len(), 2);
+
+        assert_eq!(manager.dequeue_next("phone").unwrap().title, "Urgent");
+        let summary = manager.dequeue_next("phone").unwrap();
+        assert_eq!(summary.coalesced.len(), 5);
+        assert_eq!(summary.body, "Alert 0, Alert 1, Alert 2, 2 more");
+        assert_eq!(summary.category, NotificationCategory::Alert);
+    }
+
+    #[test]
+    fn quiet_hours_hold_everything_but_critical() {
+        let mut manager = PushNotificationManager::new(100);
+        let policy = quiet_policy(0);
+        // 2024-01-01 03:00 UTC.
+        let at = 1_704_078_000;
+
+        manager.create_notification_at(request(PushPriority::High, "Price alert"), &policy, at);
+        manager.create_notification_at(request(PushPriority::Low, "Watchlist"), &policy, at);
+        manager.create_notification_at(request(PushPriority::Critical, "Liquidation"), &policy, at);
+
+        let pending = manager.get_pending_notifications("phone");
+        assert_eq!(pending.held_for_quiet_hours.len(), 2);
+        assert_eq!(pending.in_flight.len(), 1);
+        assert_eq!(pending.in_flight[0].title, "Liquidation");
+
+        // Released after quiet hours as a single digest.
+        let release_at = pending.next_release_at.unwrap();
+        assert_eq!(release_at, at + 4 * 3600);
+        manager.release_due("phone", &policy, release_at - 1);
+        assert_eq!(
+            manager.get_pending_notifications("phone").in_flight.len(),
+            1
+        );
+
+        manager.release_due("phone", &policy, release_at);
+        assert!(manager.dequeue_next("phone").unwrap().coalesced.is_empty());
+        let digest = manager.dequeue_next("phone").unwrap();
+        assert_eq!(digest.coalesced.len(), 2);
+        assert_eq!(digest.priority, PushPriority::High);
+        assert!(digest.title.contains("quiet hours"));
+    }
+
+    #[test]
+    fn held_pushes_survive_a_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("pending_push.json");
+        let policy = quiet_policy(0);
+        let at = 1_704_078_000;
+
+        let mut manager = PushNotificationManager::new(100);
+        manager.create_notification_at(request(PushPriority::High, "Price alert"), &policy, at);
+        let delivered = manager.create_notification_at(
+            request(PushPriority::Critical, "Liquidation"),
+            &policy,
+            at,
+        );
+        manager.mark_delivered(&delivered.notification_id);
+        assert_eq!(manager.save_pending(&path).unwrap(), 1);
+
+        let mut restarted = PushNotificationManager::new(100);
+        assert_eq!(restarted.load_pending(&path).unwrap(), 1);
+        assert!(!path.exists());
+        let pending = restarted.get_pending_notifications("phone");
+        assert_eq!(pending.held_for_quiet_hours.len(), 1);
+        assert_eq!(pending.next_release_at, Some(at + 4 * 3600));
+        assert_eq!(restarted.load_pending(&path).unwrap(), 0);
+    }
+}
@@ -1,10 +1,23 @@
-use anyhow::Result;
+use crate::alerts::SharedAlertManager;
+use crate::mobile::delta::{
+    ChangeOutcome, ConflictResolution, RecordChange, SyncCategory, SyncConflict, SyncRecord,
+    SyncSession, SyncStore, SyncVersions,
+};
+use crate::portfolio::{SharedTokenAnnotationStore, SharedWatchlistManager};
+use anyhow::{anyhow, Result};
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::collections::HashMap;
 use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager};
 use tokio::sync::RwLock;
 
+const DEFAULT_DELTA_PAGE_SIZE: usize = 200;
+const MAX_DELTA_PAGE_SIZE: usize = 1000;
+/// Paged sessions can be resumed for this long after their last page.
+const SYNC_SESSION_TTL_MS: i64 = 30 * 60 * 1000;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ReducedMarketData {
     pub symbol: String,
@@ -48,9 +61,70 @@ pub struct MobileSyncData {
     pub last_sync: i64,
 }
 
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DeltaSyncRequest {
+    /// Per-category versions the device has fully applied.
+    #[serde(default)]
+    pub since_versions: SyncVersions,
+    /// Local edits made on the device since its last sync.
+    #[serde(default)]
+    pub changes: Vec<RecordChange>,
+    /// Set to resume an interrupted transfer.
+    #[serde(default)]
+    pub session_id: Option<String>,
+    #[serde(default)]
+    pub cursor: usize,
+    #[serde(default)]
+    pub page_size: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangeReport {
+    pub category: SyncCategory,
+    pub id: String,
+    pub outcome: ChangeOutcome,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncDelta {
+    pub session_id: String,
+    pub records: Vec<SyncRecord>,
+    /// Pass back as `cursor` to fetch the next page or resume after a drop.
+    pub cursor: usize,
+    pub total: usize,
+    pub has_more: bool,
+    /// Versions the device is at once every page of this session is applied.
+    pub versions: SyncVersions,
+    pub changes: Vec<ChangeReport>,
+    pub conflicts: Vec<SyncConflict>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncSessionProgress {
+    pub session_id: String,
+    pub cursor: usize,
+    pub total: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceSyncStatus {
+    pub device_id: String,
+    pub last_sync: Option<i64>,
+    /// Per-category versions the device has fully applied.
+    pub versions: SyncVersions,
+    /// Current per-category versions on the desktop.
+    pub latest_versions: SyncVersions,
+    pub pending_conflicts: usize,
+    pub session: Option<SyncSessionProgress>,
+}
+
 pub struct MobileSyncManager {
     last_sync_times: HashMap<String, i64>,
     cached_sync_data: HashMap<String, MobileSyncData>,
+    store: SyncStore,
+    device_versions: HashMap<String, SyncVersions>,
+    desktop_snapshots: HashMap<SyncCategory, HashMap<String, (Value, u64)>>,
+    sessions: HashMap<String, SyncSession>,
 }
 
 impl MobileSyncManager {
@@ -58,7 +132,137 @@ impl MobileSyncManager {
         Self {
             last_sync_times: HashMap::new(),
             cached_sync_data: HashMap::new(),
+            store: SyncStore::new(),
+            device_versions: HashMap::new(),
+            desktop_snapshots: HashMap::new(),
+            sessions: HashMap::new(),
+        }
+    }
+
+    /// Feeds the current desktop state of a category into the version store.
+    pub fn ingest_desktop(&mut self, category: SyncCategory, snapshot: Vec<(String, Value)>) {
+        let now = Utc::now().timestamp_millis();
+        let last = self.desktop_snapshots.entry(category).or_default();
+        self.store.ingest_snapshot(category, snapshot, last, now);
+    }
+
+    /// Applies a device's pending changes and returns one page of what changed
+    /// since its `since_versions`. Passing a `session_id` from an earlier
+    /// response resumes that transfer at `cursor` instead of starting over.
+    pub fn sync_delta(&mut self, device_id: &str, request: DeltaSyncRequest) -> Result<SyncDelta> {
+        let DeltaSyncRequest {
+            since_versions: since,
+            changes,
+            session_id,
+            cursor,
+            page_size,
+        } = request;
+        let page_size = page_size.unwrap_or(DEFAULT_DELTA_PAGE_SIZE);
+        let now = Utc::now().timestamp_millis();
+        self.sessions
+            .retain(|_, session| now - session.updated_at < SYNC_SESSION_TTL_MS);
+
+        let changes = changes
+            .into_iter()
+            .map(|change| ChangeReport {
+                category: change.category,
+                id: change.id.clone(),
+                outcome: self.store.apply_change(device_id, change, now),
+            })
+            .collect();
+
+        let resumable = session_id
+            .as_ref()
+            .and_then(|id| self.sessions.get(id))
+            .is_some_and(|session| session.device_id == device_id);
+        let (session_id, cursor) = match session_id {
+            Some(id) if resumable => (id, cursor),
+            _ => {
+                self.sessions
+                    .retain(|_, session| session.device_id != device_id);
+                let session = SyncSession::new(
+                    device_id.to_string(),
+                    self.store.changes_since(&since),
+                    self.store.versions(),
+                    now,
+                );
+                let id = session.session_id.clone();
+                self.sessions.insert(id.clone(), session);
+                self.device_versions.insert(device_id.to_string(), since);
+                (id, 0)
+            }
+        };
+
+        let session = self
+            .sessions
+            .get_mut(&session_id)
+            .ok_or_else(|| anyhow!("Sync session {} disappeared", session_id))?;
+        let records = session
+            .page(cursor, page_size.clamp(1, MAX_DELTA_PAGE_SIZE), now)
+            .to_vec();
+        let next_cursor = session.cursor + records.len();
+        let total = session.records.len();
+        let versions = session.target_versions.clone();
+
+        let has_more = !session.is_complete(next_cursor);
+        if !has_more {
+            self.device_versions
+                .insert(device_id.to_string(), versions.clone());
+            self.last_sync_times
+                .insert(device_id.to_string(), now / 1000);
         }
+
+        Ok(SyncDelta {
+            session_id,
+            records,
+            cursor: next_cursor,
+            total,
+            has_more,
+            versions,
+            changes,
+            conflicts: self.store.conflicts().to_vec(),
+        })
+    }
+
+    pub fn conflicts(&self) -> Vec<SyncConflict> {
+        self.store.conflicts().to_vec()
+    }
+
+    pub fn resolve_conflict(
+        &mut self,
+        conflict_id: &str,
+        resolution: ConflictResolution,
+    ) -> Result<SyncRecord> {
+        self.store
+            .resolve_conflict(conflict_id, resolution, Utc::now().timestamp_millis())
+            .map_err(|e| anyhow!(e))
+    }
+
+    pub fn device_status(&self, device_id: &str) -> Option<DeviceSyncStatus> {
+        let last_sync = self.get_last_sync(device_id);
+        let versions = self.device_versions.get(device_id).cloned();
+        if last_sync.is_none() && versions.is_none() {
+            return None;
+        }
+
+        let session = self
+            .sessions
+            .values()
+            .find(|session| session.device_id == device_id)
+            .map(|session| SyncSessionProgress {
+                session_id: session.session_id.clone(),
+                cursor: session.cursor,
+                total: session.records.len(),
+            });
+
+        Some(DeviceSyncStatus {
+            device_id: device_id.to_string(),
+            last_sync,
+            versions: versions.unwrap_or_default(),
+            latest_versions: self.store.versions(),
+            pending_conflicts: self.store.conflicts().len(),
+            session,
+        })
     }
 
     pub async fn sync_device(&mut self, device_id: String) -> Result<MobileSyncData> {
@@ -149,6 +353,67 @@ impl MobileSyncManager {
     }
 }
 
+async fn ensure_registered(
+    device_id: &str,
+    mobile_auth: &Arc<RwLock<crate::mobile::auth::MobileAuthManager>>,
+) -> Result<(), String> {
+    let devices = {
+        let auth = mobile_auth.read().await;
+        auth.get_devices()
+    };
+
+    if devices.iter().any(|device| device.device_id == device_id) {
+        Ok(())
+    } else {
+        Err("Device not registered".into())
+    }
+}
+
+fn keyed_snapshot<T: Serialize>(items: &[T], id: impl Fn(&T) -> String) -> Vec<(String, Value)> {
+    items
+        .iter()
+        .filter_map(|item| Some((id(item), serde_json::to_value(item).ok()?)))
+        .collect()
+}
+
+/// Reads watchlists, alerts and token notes from their stores. Journal drafts
+/// only exist on devices, so there is nothing to ingest for them.
+async fn desktop_snapshots(app: &AppHandle) -> Vec<(SyncCategory, Vec<(String, Value)>)> {
+    let mut snapshots = Vec::new();
+
+    if let Some(watchlists) = app.try_state::<SharedWatchlistManager>() {
+        let manager = watchlists.read().await;
+        match manager.list_watchlists().await {
+            Ok(lists) => snapshots.push((
+                SyncCategory::Watchlists,
+                keyed_snapshot(&lists, |list| list.id.clone()),
+            )),
+            Err(e) => eprintln!("Mobile sync: failed to read watchlists: {}", e),
+        }
+    }
+
+    if let Some(alerts) = app.try_state::<SharedAlertManager>() {
+        let manager = alerts.read().await;
+        match manager.list_alerts().await {
+            Ok(list) => snapshots.push((
+                SyncCategory::Alerts,
+                keyed_snapshot(&list, |alert| alert.id.clone()),
+            )),
+            Err(e) => eprintln!("Mobile sync: failed to read alerts: {}", e),
+        }
+    }
+
+    if let Some(annotations) = app.try_state::<SharedTokenAnnotationStore>() {
+        let records = annotations.read().all();
+        snapshots.push((
+            SyncCategory::TokenNotes,
+            keyed_snapshot(&records, |record| record.mint.clone()),
+        ));
+    }
+
+    snapshots
+}
+
 // Tauri commands
 #[tauri::command]
 pub async fn mobile_sync_data(
@@ -156,20 +421,66 @@ pub async fn mobile_sync_data(
     sync_manager: tauri::State<'_, Arc<RwLock<MobileSyncManager>>>,
     mobile_auth: tauri::State<'_, Arc<RwLock<crate::mobile::auth::MobileAuthManager>>>,
 ) -> Result<MobileSyncData, String> {
-    let devices = {
-        let auth = mobile_auth.read().await;
-        auth.get_devices()
+    ensure_registered(&device_id, &mobile_auth).await?;
+
+    let mut manager = sync_manager.write().await;
+    manager
+        .sync_device(device_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn mobile_sync_delta(
+    app: AppHandle,
+    device_id: String,
+    request: DeltaSyncRequest,
+    sync_manager: tauri::State<'_, Arc<RwLock<MobileSyncManager>>>,
+    mobile_auth: tauri::State<'_, Arc<RwLock<crate::mobile::auth::MobileAuthManager>>>,
+) -> Result<SyncDelta, String> {
+    ensure_registered(&device_id, &mobile_auth).await?;
+
+    let snapshots = desktop_snapshots(&app).await;
+    let delta = {
+        let mut manager = sync_manager.write().await;
+        for (category, snapshot) in snapshots {
+            manager.ingest_desktop(category, snapshot);
+        }
+        manager
+            .sync_delta(&device_id, request)
+            .map_err(|e| e.to_string())?
     };
 
-    let device_registered = devices.iter().any(|device| device.device_id == device_id);
-    if !device_registered {
-        return Err("Device not registered".into());
+    // Device edits live in the sync store; desktop views pick them up from here.
+    let applied: Vec<&ChangeReport> = delta
+        .changes
+        .iter()
+        .filter(|report| !matches!(report.outcome, ChangeOutcome::Unchanged))
+        .collect();
+    if !applied.is_empty() {
+        let _ = app.emit("mobile_sync_changes", &applied);
     }
 
+    Ok(delta)
+}
+
+#[tauri::command]
+pub async fn mobile_get_sync_conflicts(
+    sync_manager: tauri::State<'_, Arc<RwLock<MobileSyncManager>>>,
+) -> Result<Vec<SyncConflict>, String> {
+    let manager = sync_manager.read().await;
+    Ok(manager.conflicts())
+}
+
+#[tauri::command]
+pub async fn mobile_resolve_sync_conflict(
+    conflict_id: String,
+    resolution: ConflictResolution,
+    sync_manager: tauri::State<'_, Arc<RwLock<MobileSyncManager>>>,
+) -> Result<SyncRecord, String> {
     let mut manager = sync_manager.write().await;
     manager
-        .sync_device(device_id)
-        .await
+        .resolve_conflict(&conflict_id, resolution)
         .map_err(|e| e.to_string())
 }
 
@@ -177,9 +488,9 @@ pub async fn mobile_sync_data(
 pub async fn mobile_get_last_sync(
     device_id: String,
     sync_manager: tauri::State<'_, Arc<RwLock<MobileSyncManager>>>,
-) -> Result<Option<i64>, String> {
+) -> Result<Option<DeviceSyncStatus>, String> {
     let manager = sync_manager.read().await;
-    Ok(manager.get_last_sync(&device_id))
+    Ok(manager.device_status(&device_id))
 }
 
 #[tauri::command]
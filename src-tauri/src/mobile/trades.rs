@@ -1,11 +1,22 @@
 use crate::mobile::{MobileSession, SharedMobileAuthManager};
-use crate::trading::safety::policy::{SafetyCheck, SafetyPolicy};
+use crate::trading::safety::policy::{PolicyViolation, SafetyCheck, SafetyPolicy};
+use crate::trading::safety::OutsideTradingHours;
+use crate::trading::{SafetyCheckError, SafetyCheckRequest, SharedSafetyEngine, TradeOrigin};
 use anyhow::{anyhow, Result};
 use chrono::Utc;
-use serde::{Deserialize, Serialize};
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Serialize, Serializer};
+use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::Arc;
+use tauri::{AppHandle, Manager};
 use tokio::sync::RwLock;
 
+const TRADE_HISTORY_FILE: &str = "mobile_trade_history.json";
+const MAX_HISTORY_PER_DEVICE: usize = 500;
+const QUOTE_SYMBOL: &str = "USDC";
+const DEFAULT_SLIPPAGE_BPS: u64 = 50;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QuickTradeRequest {
     pub session_token: String,
@@ -13,6 +24,8 @@ pub struct QuickTradeRequest {
     pub side: TradeSide,
     pub amount: f64,
     pub biometric_signature: String,
+    #[serde(default)]
+    pub slippage_bps: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -41,53 +54,307 @@ pub enum TradeStatus {
     Pending,
 }
 
+/// Limits applied to trades placed from one device, on top of the desktop
+/// safety policy.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MobileSafetyPolicy {
+    pub max_trade_usd: f64,
+    /// Symbols this device may trade. Empty allows any symbol.
+    #[serde(default)]
+    pub allowed_tokens: Vec<String>,
+    pub max_daily_trades: u32,
+    /// A biometric verification must have happened this recently.
+    pub biometric_window_minutes: i64,
+}
+
+impl Default for MobileSafetyPolicy {
+    fn default() -> Self {
+        Self {
+            max_trade_usd: 500.0,
+            allowed_tokens: Vec::new(),
+            max_daily_trades: 10,
+            biometric_window_minutes: 5,
+        }
+    }
+}
+
+impl MobileSafetyPolicy {
+    pub fn validate(&self) -> Result<()> {
+        if self.max_trade_usd.is_nan() || self.max_trade_usd <= 0.0 {
+            return Err(anyhow!("Max trade size must be positive"));
+        }
+        if self.biometric_window_minutes < 1 {
+            return Err(anyhow!("Biometric window must be at least one minute"));
+        }
+        Ok(())
+    }
+
+    fn allows_token(&self, symbol: &str) -> bool {
+        self.allowed_tokens.is_empty()
+            || self
+                .allowed_tokens
+                .iter()
+                .any(|allowed| allowed.eq_ignore_ascii_case(symbol))
+    }
+}
+
+/// Why a mobile trade was refused. Serialized as `{ code, message, details }`
+/// so the mobile client can render each case.
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum MobileTradeError {
+    #[error("Session invalid: {0}")]
+    Unauthorized(String),
+    #[error("Biometric verification required")]
+    BiometricRequired { window_minutes: i64 },
+    #[error("Biometric verification is older than {window_minutes} minutes")]
+    BiometricStale {
+        verified_at: i64,
+        window_minutes: i64,
+    },
+    #[error("Trade of ${amount_usd} exceeds this device's limit of ${max_usd}")]
+    AmountExceedsLimit { amount_usd: f64, max_usd: f64 },
+    #[error("{symbol} cannot be traded from this device")]
+    TokenNotAllowed { symbol: String },
+    #[error("Daily mobile trade limit of {limit} reached")]
+    DailyLimitReached { limit: u32, used: u32 },
+    #[error("Trade violates safety policies")]
+    SafetyRejected { violations: Vec<PolicyViolation> },
+    #[error(transparent)]
+    OutsideTradingHours(OutsideTradingHours),
+    #[error("Trade failed: {0}")]
+    Execution(String),
+}
+
+impl MobileTradeError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            MobileTradeError::Unauthorized(_) => "unauthorized",
+            MobileTradeError::BiometricRequired { .. } => "biometric_required",
+            MobileTradeError::BiometricStale { .. } => "biometric_stale",
+            MobileTradeError::AmountExceedsLimit { .. } => "amount_exceeds_limit",
+            MobileTradeError::TokenNotAllowed { .. } => "token_not_allowed",
+            MobileTradeError::DailyLimitReached { .. } => "daily_limit_reached",
+            MobileTradeError::SafetyRejected { .. } => "safety_rejected",
+            MobileTradeError::OutsideTradingHours(_) => "outside_trading_hours",
+            MobileTradeError::Execution(_) => "execution_failed",
+        }
+    }
+
+    fn details(&self) -> serde_json::Value {
+        use serde_json::json;
+        match self {
+            MobileTradeError::Unauthorized(_) | MobileTradeError::Execution(_) => json!({}),
+            MobileTradeError::BiometricRequired { window_minutes } => {
+                json!({ "window_minutes": window_minutes })
+            }
+            MobileTradeError::BiometricStale {
+                verified_at,
+                window_minutes,
+            } => json!({ "verified_at": verified_at, "window_minutes": window_minutes }),
+            MobileTradeError::AmountExceedsLimit {
+                amount_usd,
+                max_usd,
+            } => json!({ "amount_usd": amount_usd, "max_usd": max_usd }),
+            MobileTradeError::TokenNotAllowed { symbol } => json!({ "symbol": symbol }),
+            MobileTradeError::DailyLimitReached { limit, used } => {
+                json!({ "limit": limit, "used": used })
+            }
+            MobileTradeError::SafetyRejected { violations } => {
+                json!({ "violations": violations })
+            }
+            MobileTradeError::OutsideTradingHours(err) => json!({
+                "timezone": err.timezone,
+                "next_allowed_at": err.next_allowed_at.map(|at| at.timestamp()),
+            }),
+        }
+    }
+}
+
+impl From<SafetyCheckError> for MobileTradeError {
+    fn from(err: SafetyCheckError) -> Self {
+        match err {
+            SafetyCheckError::OutsideTradingHours(err) => {
+                MobileTradeError::OutsideTradingHours(err)
+            }
+            SafetyCheckError::Simulation(message) => MobileTradeError::Execution(message),
+        }
+    }
+}
+
+impl Serialize for MobileTradeError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("MobileTradeError", 3)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.serialize_field("details", &self.details())?;
+        state.end()
+    }
+}
+
+/// Audit entry for every trade a device attempted, executed or not.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MobileTradeRecord {
+    pub trade_id: String,
+    pub device_id: String,
+    pub symbol: String,
+    pub side: TradeSide,
+    pub amount: f64,
+    pub status: TradeStatus,
+    pub executed_price: Option<f64>,
+    pub rejection_code: Option<String>,
+    pub rejection_message: Option<String>,
+    pub timestamp: i64,
+}
+
 pub struct MobileTradeEngine {
     safety_policy: SafetyPolicy,
+    history: HashMap<String, Vec<MobileTradeRecord>>,
+    data_dir: PathBuf,
 }
 
 impl MobileTradeEngine {
-    pub fn new() -> Self {
+    pub fn new(data_dir: PathBuf) -> Self {
         Self {
             safety_policy: SafetyPolicy::default(),
+            history: HashMap::new(),
+            data_dir,
         }
     }
 
+    pub async fn load(&mut self) -> Result<()> {
+        let path = self.data_dir.join(TRADE_HISTORY_FILE);
+        if path.exists() {
+            let content = tokio::fs::read_to_string(path).await?;
+            self.history = serde_json::from_str(&content)?;
+        }
+        Ok(())
+    }
+
+    async fn save_history(&self) -> Result<()> {
+        let path = self.data_dir.join(TRADE_HISTORY_FILE);
+        let json = serde_json::to_string_pretty(&self.history)?;
+        tokio::fs::write(path, json).await?;
+        Ok(())
+    }
+
     pub async fn execute_quick_trade(
-        &self,
+        &mut self,
         trade: QuickTradeRequest,
         mobile_auth: SharedMobileAuthManager,
-    ) -> Result<QuickTradeConfirmation> {
-        if trade.biometric_signature.is_empty() {
-            return Err(anyhow!("Biometric signature required"));
-        }
-
-        let session = {
+        safety_engine: Option<SharedSafetyEngine>,
+    ) -> Result<QuickTradeConfirmation, MobileTradeError> {
+        let (session, policy, biometric_at) = {
             let auth = mobile_auth.read().await;
-            auth.authenticate_session(trade.session_token.clone())
-                .await?
+            let session = auth
+                .authenticate_session(trade.session_token.clone())
+                .await
+                .map_err(|e| MobileTradeError::Unauthorized(e.to_string()))?;
+            let device = auth
+                .get_device(&session.device_id)
+                .ok_or_else(|| MobileTradeError::Unauthorized("Device not registered".into()))?;
+            let biometric_at = auth.last_biometric_verification(&session.device_id);
+            (session, device.safety_policy, biometric_at)
         };
 
-        self.enforce_safety_checks(&session, &trade)?;
+        let now = Utc::now().timestamp();
+        let outcome = match self.enforce_device_policy(&session, &policy, biometric_at, &trade, now)
+        {
+            Ok(()) => {
+                self.enforce_safety_checks(&session, &trade, safety_engine)
+                    .await
+            }
+            Err(err) => Err(err),
+        };
 
+        let trade_id = uuid::Uuid::new_v4().to_string();
         // Simulated execution
-        let confirmation = QuickTradeConfirmation {
-            trade_id: uuid::Uuid::new_v4().to_string(),
+        let executed_price = 123.45;
+        let record = MobileTradeRecord {
+            trade_id: trade_id.clone(),
+            device_id: session.device_id.clone(),
+            symbol: trade.symbol.clone(),
+            side: trade.side.clone(),
+            amount: trade.amount,
+            status: if outcome.is_ok() {
+                TradeStatus::Executed
+            } else {
+                TradeStatus::Rejected
+            },
+            executed_price: outcome.is_ok().then_some(executed_price),
+            rejection_code: outcome.as_ref().err().map(|e| e.code().to_string()),
+            rejection_message: outcome.as_ref().err().map(|e| e.to_string()),
+            timestamp: now,
+        };
+        self.record_trade(record).await;
+        outcome?;
+
+        Ok(QuickTradeConfirmation {
+            trade_id,
             symbol: trade.symbol,
             side: trade.side,
             amount: trade.amount,
-            executed_price: 123.45,
-            timestamp: Utc::now().timestamp(),
+            executed_price,
+            timestamp: now,
             status: TradeStatus::Executed,
-        };
+        })
+    }
+
+    fn enforce_device_policy(
+        &self,
+        session: &MobileSession,
+        policy: &MobileSafetyPolicy,
+        biometric_at: Option<i64>,
+        trade: &QuickTradeRequest,
+        now: i64,
+    ) -> Result<(), MobileTradeError> {
+        let window_minutes = policy.biometric_window_minutes;
+        if trade.biometric_signature.is_empty() {
+            return Err(MobileTradeError::BiometricRequired { window_minutes });
+        }
+        match biometric_at {
+            None => return Err(MobileTradeError::BiometricRequired { window_minutes }),
+            Some(verified_at) if now - verified_at > window_minutes * 60 => {
+                return Err(MobileTradeError::BiometricStale {
+                    verified_at,
+                    window_minutes,
+                });
+            }
+            Some(_) => {}
+        }
+
+        if !policy.allows_token(&trade.symbol) {
+            return Err(MobileTradeError::TokenNotAllowed {
+                symbol: trade.symbol.clone(),
+            });
+        }
+
+        if trade.amount > policy.max_trade_usd {
+            return Err(MobileTradeError::AmountExceedsLimit {
+                amount_usd: trade.amount,
+                max_usd: policy.max_trade_usd,
+            });
+        }
+
+        let used = self.trades_today(&session.device_id, now);
+        if used >= policy.max_daily_trades {
+            return Err(MobileTradeError::DailyLimitReached {
+                limit: policy.max_daily_trades,
+                used,
+            });
+        }
 
-        Ok(confirmation)
+        Ok(())
     }
 
-    fn enforce_safety_checks(
+    async fn enforce_safety_checks(
         &self,
-        _session: &MobileSession,
+        session: &MobileSession,
         trade: &QuickTradeRequest,
-    ) -> Result<()> {
+        safety_engine: Option<SharedSafetyEngine>,
+    ) -> Result<(), MobileTradeError> {
         let checks = vec![
             SafetyCheck::MaxNotionalValue(50_000.0),
             SafetyCheck::MaxOrderSize(1_000.0),
@@ -96,24 +363,103 @@ impl MobileTradeEngine {
         for check in checks {
             self.safety_policy
                 .check_mobile_quick_trade(check, trade.amount)
-                .map_err(|_| anyhow!("Trade violates safety policies"))?;
+                .map_err(|violation| MobileTradeError::SafetyRejected {
+                    violations: vec![violation],
+                })?;
         }
 
+        let Some(safety_engine) = safety_engine else {
+            return Ok(());
+        };
+
+        let (input, output) = match trade.side {
+            TradeSide::Buy => (QUOTE_SYMBOL.to_string(), trade.symbol.clone()),
+            TradeSide::Sell => (trade.symbol.clone(), QUOTE_SYMBOL.to_string()),
+        };
+        // Cooldowns and daily counts in the desktop engine are tracked per device.
+        let wallet_address = format!("mobile:{}", session.device_id);
+        let request = SafetyCheckRequest {
+            wallet_address: wallet_address.clone(),
+            input_amount: trade.amount,
+            input_mint: input.clone(),
+            output_mint: output.clone(),
+            input_symbol: input,
+            output_symbol: output.clone(),
+            amount_usd: trade.amount,
+            slippage_bps: trade.slippage_bps.unwrap_or(DEFAULT_SLIPPAGE_BPS),
+            price_impact_percent: 0.0,
+            security_score: None,
+            // Token-scoped cooldowns and limits track the token the trade buys.
+            token_mint: Some(output.clone()),
+            strategy_id: None,
+            origin: TradeOrigin::MobileQuickTrade,
+        };
+
+        let mut engine = safety_engine.write().await;
+        let result = engine.check_trade_safety(request).await?;
+        if !result.allowed {
+            return Err(MobileTradeError::SafetyRejected {
+                violations: result.policy_result.violations,
+            });
+        }
+        engine
+            .approve_trade(&wallet_address, Some(&output), None)
+            .await;
+
         Ok(())
     }
+
+    /// Executed trades for the device since midnight UTC.
+    fn trades_today(&self, device_id: &str, now: i64) -> u32 {
+        let day_start = now - now.rem_euclid(86_400);
+        self.history
+            .get(device_id)
+            .map(|records| {
+                records
+                    .iter()
+                    .filter(|r| matches!(r.status, TradeStatus::Executed))
+                    .filter(|r| r.timestamp >= day_start)
+                    .count() as u32
+            })
+            .unwrap_or(0)
+    }
+
+    async fn record_trade(&mut self, record: MobileTradeRecord) {
+        let records = self.history.entry(record.device_id.clone()).or_default();
+        records.push(record);
+        if records.len() > MAX_HISTORY_PER_DEVICE {
+            let excess = records.len() - MAX_HISTORY_PER_DEVICE;
+            records.drain(..excess);
+        }
+        if let Err(e) = self.save_history().await {
+            eprintln!("Failed to save mobile trade history: {}", e);
+        }
+    }
+
+    /// Most recent first.
+    pub fn device_history(&self, device_id: &str, limit: usize) -> Vec<MobileTradeRecord> {
+        self.history
+            .get(device_id)
+            .map(|records| records.iter().rev().take(limit).cloned().collect())
+            .unwrap_or_default()
+    }
 }
 
 #[tauri::command]
 pub async fn mobile_execute_quick_trade(
+    app: AppHandle,
     trade: QuickTradeRequest,
     trade_engine: tauri::State<'_, Arc<RwLock<MobileTradeEngine>>>,
     mobile_auth: tauri::State<'_, Arc<RwLock<crate::mobile::auth::MobileAuthManager>>>,
-) -> Result<QuickTradeConfirmation, String> {
-    let engine = trade_engine.read().await;
+) -> Result<QuickTradeConfirmation, MobileTradeError> {
+    let safety_engine = app
+        .try_state::<SharedSafetyEngine>()
+        .map(|state| state.inner().clone());
+
+    let mut engine = trade_engine.write().await;
     engine
-        .execute_quick_trade(trade, mobile_auth.inner().clone())
+        .execute_quick_trade(trade, mobile_auth.inner().clone(), safety_engine)
         .await
-        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -128,3 +474,133 @@ pub async fn mobile_safety_checks(
         .map(|rule| format!("{}", rule))
         .collect())
 }
+
+#[tauri::command]
+pub async fn mobile_get_device_safety_policy(
+    device_id: String,
+    mobile_auth: tauri::State<'_, Arc<RwLock<crate::mobile::auth::MobileAuthManager>>>,
+) -> Result<MobileSafetyPolicy, String> {
+    let auth = mobile_auth.read().await;
+    auth.get_device(&device_id)
+        .map(|device| device.safety_policy)
+        .ok_or_else(|| "Device not registered".to_string())
+}
+
+#[tauri::command]
+pub async fn mobile_set_device_safety_policy(
+    device_id: String,
+    policy: MobileSafetyPolicy,
+    mobile_auth: tauri::State<'_, Arc<RwLock<crate::mobile::auth::MobileAuthManager>>>,
+) -> Result<MobileSafetyPolicy, String> {
+    policy.validate().map_err(|e| e.to_string())?;
+
+    let mut auth = mobile_auth.write().await;
+    auth.set_safety_policy(&device_id, policy)
+        .await
+        .map(|device| device.safety_policy)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn mobile_get_device_trade_history(
+    device_id: String,
+    limit: Option<usize>,
+    trade_engine: tauri::State<'_, Arc<RwLock<MobileTradeEngine>>>,
+) -> Result<Vec<MobileTradeRecord>, String> {
+    let engine = trade_engine.read().await;
+    Ok(engine.device_history(&device_id, limit.unwrap_or(MAX_HISTORY_PER_DEVICE)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session() -> MobileSession {
+        MobileSession {
+            session_id: "s".to_string(),
+            device_id: "phone".to_string(),
+            user_id: "default_user".to_string(),
+            created_at: 0,
+            expires_at: i64::MAX,
+            is_active: true,
+        }
+    }
+
+    fn trade(symbol: &str, amount: f64) -> QuickTradeRequest {
+        QuickTradeRequest {
+            session_token: "s".to_string(),
+            symbol: symbol.to_string(),
+            side: TradeSide::Buy,
+            amount,
+            biometric_signature: "sig".to_string(),
+            slippage_bps: None,
+        }
+    }
+
+    #[test]
+    fn device_policy_rejections_are_typed() {
+        let mut engine = MobileTradeEngine::new(std::env::temp_dir());
+        let policy = MobileSafetyPolicy {
+            allowed_tokens: vec!["SOL".to_string()],
+            max_daily_trades: 1,
+            ..MobileSafetyPolicy::default()
+        };
+        let now = 1_700_000_000;
+        let fresh = Some(now - 60);
+        let check = |engine: &MobileTradeEngine, trade: &QuickTradeRequest, bio: Option<i64>| {
+            engine
+                .enforce_device_policy(&session(), &policy, bio, trade, now)
+                .map_err(|e| e.code())
+        };
+
+        assert_eq!(check(&engine, &trade("sol", 100.0), fresh), Ok(()));
+        assert_eq!(
+            check(&engine, &trade("SOL", 100.0), None),
+            Err("biometric_required")
+        );
+        assert_eq!(
+            check(&engine, &trade("SOL", 100.0), Some(now - 600)),
+            Err("biometric_stale")
+        );
+        assert_eq!(
+            check(&engine, &trade("BONK", 100.0), fresh),
+            Err("token_not_allowed")
+        );
+        assert_eq!(
+            check(&engine, &trade("SOL", 900.0), fresh),
+            Err("amount_exceeds_limit")
+        );
+
+        engine.history.insert(
+            "phone".to_string(),
+            vec![MobileTradeRecord {
+                trade_id: "t1".to_string(),
+                device_id: "phone".to_string(),
+                symbol: "SOL".to_string(),
+                side: TradeSide::Buy,
+                amount: 50.0,
+                status: TradeStatus::Executed,
+                executed_price: Some(1.0),
+                rejection_code: None,
+                rejection_message: None,
+                timestamp: now - 10,
+            }],
+        );
+        assert_eq!(
+            check(&engine, &trade("SOL", 100.0), fresh),
+            Err("daily_limit_reached")
+        );
+    }
+
+    #[test]
+    fn errors_serialize_with_code_and_details() {
+        let err = MobileTradeError::AmountExceedsLimit {
+            amount_usd: 900.0,
+            max_usd: 500.0,
+        };
+        let value = serde_json::to_value(&err).unwrap();
+        assert_eq!(value["code"], "amount_exceeds_limit");
+        assert_eq!(value["details"]["max_usd"], 500.0);
+        assert!(value["message"].as_str().unwrap().contains("$900"));
+    }
+}
@@ -1,7 +1,30 @@
-use chrono::Utc;
+use super::SharedWidgetManager;
+use crate::alerts::{AlertState, PriceAlert, SharedAlertManager};
+use crate::config::privacy_mode::{Masked, PrivacyMasked, SharedPrivacyMode};
+use crate::core::{WebSocketManager, WIDGET_CONSUMER};
+use crate::portfolio::{SharedWatchlistManager, WatchlistError};
+use crate::tray::{PnlAttribution, SharedTrayManager};
+use crate::websocket::types::PriceDelta;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
-use tokio::sync::RwLock;
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+use tokio::time::{interval, Duration};
+
+const WIDGETS_FILE: &str = "mobile_widgets.json";
+/// Upper bound on list entries in any widget payload, so snapshots stay small
+/// enough for home-screen extensions to decode.
+pub const MAX_WIDGET_ITEMS: usize = 10;
+const DEFAULT_WIDGET_ITEMS: usize = 5;
+const MIN_REFRESH_INTERVAL_SECS: u64 = 30;
+const MAX_REFRESH_INTERVAL_SECS: u64 = 24 * 60 * 60;
+const DEFAULT_REFRESH_INTERVAL_SECS: u64 = 300;
+const DEFAULT_WIDGET_TOKENS: [&str; 3] = ["SOL", "USDC", "BONK"];
+const WIDGET_REFRESH_TICK: Duration = Duration::from_secs(5);
+const WIDGET_PRICE_THROTTLE: Duration = Duration::from_secs(5);
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WidgetData {
@@ -9,9 +32,31 @@ pub struct WidgetData {
     pub widget_type: WidgetType,
     pub data: serde_json::Value,
     pub last_update: i64,
+    /// When the scheduler will next recompute this snapshot.
+    #[serde(default)]
+    pub next_refresh_at: i64,
+    /// Set once a snapshot has missed a full refresh interval, or was never computed.
+    #[serde(default)]
+    pub is_stale: bool,
+    /// Why the configured source couldn't be used and defaults were shown instead.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fallback_reason: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl PrivacyMasked for WidgetData {
+    const MASKED_FIELDS: &'static [&'static str] =
+        &["total_value", "total_change_24h", "pnl_value"];
+
+    fn wallets(&self) -> Vec<String> {
+        self.data
+            .pointer("/scope/address")
+            .and_then(|address| address.as_str())
+            .map(|address| vec![address.to_string()])
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum WidgetType {
     PriceWatch,
@@ -21,134 +66,882 @@ pub enum WidgetType {
     QuickActions,
 }
 
+impl WidgetType {
+    pub const ALL: [WidgetType; 5] = [
+        WidgetType::PriceWatch,
+        WidgetType::PortfolioSummary,
+        WidgetType::Alerts,
+        WidgetType::TopMovers,
+        WidgetType::QuickActions,
+    ];
+
+    /// Id of the shared widget registered for this type on every install.
+    pub fn builtin_id(&self) -> &'static str {
+        match self {
+            WidgetType::PriceWatch => "price_watch",
+            WidgetType::PortfolioSummary => "portfolio_summary",
+            WidgetType::Alerts => "alerts",
+            WidgetType::TopMovers => "top_movers",
+            WidgetType::QuickActions => "quick_actions",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum WidgetTokenSource {
+    #[default]
+    Default,
+    Watchlist {
+        watchlist_id: String,
+    },
+    Tokens {
+        symbols: Vec<String>,
+    },
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum WidgetPortfolioScope {
+    #[default]
+    All,
+    Wallet {
+        address: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WidgetConfig {
+    #[serde(default)]
+    pub tokens: WidgetTokenSource,
+    #[serde(default)]
+    pub portfolio_scope: WidgetPortfolioScope,
+    #[serde(default = "default_refresh_interval_secs")]
+    pub refresh_interval_secs: u64,
+    #[serde(default = "default_max_items")]
+    pub max_items: usize,
+}
+
+fn default_refresh_interval_secs() -> u64 {
+    DEFAULT_REFRESH_INTERVAL_SECS
+}
+
+fn default_max_items() -> usize {
+    DEFAULT_WIDGET_ITEMS
+}
+
+impl Default for WidgetConfig {
+    fn default() -> Self {
+        Self {
+            tokens: WidgetTokenSource::Default,
+            portfolio_scope: WidgetPortfolioScope::All,
+            refresh_interval_secs: DEFAULT_REFRESH_INTERVAL_SECS,
+            max_items: DEFAULT_WIDGET_ITEMS,
+        }
+    }
+}
+
+impl WidgetConfig {
+    /// Clamps limits into range and tidies token lists. Widgets run unattended, so
+    /// out-of-range values are corrected rather than rejected.
+    pub fn normalized(mut self) -> Self {
+        self.refresh_interval_secs = self
+            .refresh_interval_secs
+            .clamp(MIN_REFRESH_INTERVAL_SECS, MAX_REFRESH_INTERVAL_SECS);
+        self.max_items = self.max_items.clamp(1, MAX_WIDGET_ITEMS);
+        if let WidgetTokenSource::Tokens { symbols } = &mut self.tokens {
+            *symbols = normalize_symbols(symbols.iter().map(String::as_str));
+        }
+        if let WidgetPortfolioScope::Wallet { address } = &self.portfolio_scope {
+            if address.trim().is_empty() {
+                self.portfolio_scope = WidgetPortfolioScope::All;
+            }
+        }
+        self
+    }
+}
+
+fn normalize_symbols<'a>(symbols: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    symbols
+        .into_iter()
+        .map(|symbol| symbol.trim().to_string())
+        .filter(|symbol| !symbol.is_empty() && seen.insert(symbol.clone()))
+        .take(MAX_WIDGET_ITEMS)
+        .collect()
+}
+
+fn default_tokens() -> Vec<String> {
+    DEFAULT_WIDGET_TOKENS
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegisteredWidget {
+    pub widget_id: String,
+    pub widget_type: WidgetType,
+    /// Device that placed the widget; `None` for the shared built-in widgets.
+    #[serde(default)]
+    pub device_id: Option<String>,
+    #[serde(default)]
+    pub config: WidgetConfig,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+impl RegisteredWidget {
+    fn builtin(widget_type: WidgetType, now: i64) -> Self {
+        Self {
+            widget_id: widget_type.builtin_id().to_string(),
+            widget_type,
+            device_id: None,
+            config: WidgetConfig::default(),
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    pub fn is_builtin(&self) -> bool {
+        self.device_id.is_none() && self.widget_id == self.widget_type.builtin_id()
+    }
+}
+
+/// Source data gathered for one refresh. Resolved outside the manager lock because
+/// it reads watchlists and alerts from their own stores.
+#[derive(Debug, Clone, Default)]
+pub struct WidgetInputs {
+    pub tokens: Vec<String>,
+    pub alerts: Vec<PriceAlert>,
+    /// Today's P&L by source, for portfolio summaries covering all wallets.
+    pub attribution: Option<PnlAttribution>,
+    pub fallback_reason: Option<String>,
+}
+
+impl WidgetInputs {
+    /// Uses the resolved token list, or the default tokens along with the reason
+    /// the configured source was unusable.
+    pub fn from_tokens(resolved: std::result::Result<Vec<String>, String>) -> Self {
+        let (tokens, fallback_reason) = match resolved {
+            Ok(tokens) if !tokens.is_empty() => (tokens, None),
+            Ok(_) => (
+                default_tokens(),
+                Some("Configured token source is empty".to_string()),
+            ),
+            Err(reason) => (default_tokens(), Some(reason)),
+        };
+        Self {
+            tokens,
+            alerts: Vec::new(),
+            attribution: None,
+            fallback_reason,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct WidgetQuote {
+    price: Option<f64>,
+    change_pct: Option<f64>,
+}
+
 pub struct WidgetManager {
-    widgets: Vec<WidgetData>,
+    data_dir: PathBuf,
+    widgets: HashMap<String, RegisteredWidget>,
+    snapshots: HashMap<String, WidgetData>,
+    quotes: HashMap<String, WidgetQuote>,
+    /// Tokens each widget resolved to on its last refresh; drives price subscriptions.
+    resolved_tokens: HashMap<String, Vec<String>>,
 }
 
 impl WidgetManager {
-    pub fn new() -> Self {
+    pub fn new(data_dir: PathBuf) -> Self {
+        let now = Utc::now().timestamp();
+        let widgets = WidgetType::ALL
+            .iter()
+            .map(|widget_type| {
+                let widget = RegisteredWidget::builtin(*widget_type, now);
+                (widget.widget_id.clone(), widget)
+            })
+            .collect();
         Self {
-            widgets: Vec::new(),
+            data_dir,
+            widgets,
+            snapshots: HashMap::new(),
+            quotes: HashMap::new(),
+            resolved_tokens: HashMap::new(),
         }
     }
 
-    pub async fn get_widget_data(&self, widget_type: WidgetType) -> Option<WidgetData> {
-        match widget_type {
-            WidgetType::PriceWatch => Some(self.generate_price_watch_widget()),
-            WidgetType::PortfolioSummary => Some(self.generate_portfolio_summary_widget()),
-            WidgetType::Alerts => Some(self.generate_alerts_widget()),
-            WidgetType::TopMovers => Some(self.generate_top_movers_widget()),
-            WidgetType::QuickActions => Some(self.generate_quick_actions_widget()),
+    pub async fn load(&mut self) -> Result<()> {
+        let path = self.data_dir.join(WIDGETS_FILE);
+        if path.exists() {
+            let content = tokio::fs::read_to_string(path).await?;
+            let stored: Vec<RegisteredWidget> = serde_json::from_str(&content)?;
+            for widget in stored {
+                self.widgets.insert(widget.widget_id.clone(), widget);
+            }
         }
+        Ok(())
     }
 
-    fn generate_price_watch_widget(&self) -> WidgetData {
+    async fn save(&self) -> Result<()> {
+        let path = self.data_dir.join(WIDGETS_FILE);
+        let json = serde_json::to_string_pretty(&self.list_widgets(None))?;
+        tokio::fs::write(path, json).await?;
+        Ok(())
+    }
+
+    pub async fn register_widget(
+        &mut self,
+        widget_type: WidgetType,
+        device_id: Option<String>,
+        config: WidgetConfig,
+    ) -> Result<RegisteredWidget> {
         let now = Utc::now().timestamp();
-        WidgetData {
-            widget_id: "price_watch".to_string(),
-            widget_type: WidgetType::PriceWatch,
-            data: serde_json::json!({
-                "assets": [
-                    {"symbol": "SOL", "price": 145.67, "change_pct": 5.23},
-                    {"symbol": "USDC", "price": 1.00, "change_pct": 0.01},
-                    {"symbol": "BONK", "price": 0.000023, "change_pct": -2.45},
-                ]
-            }),
-            last_update: now,
+        let widget = RegisteredWidget {
+            widget_id: format!("{}_{}", widget_type.builtin_id(), uuid::Uuid::new_v4()),
+            widget_type,
+            device_id,
+            config: config.normalized(),
+            created_at: now,
+            updated_at: now,
+        };
+        self.widgets
+            .insert(widget.widget_id.clone(), widget.clone());
+        self.save().await?;
+        Ok(widget)
+    }
+
+    /// Replaces a widget's config and drops its snapshot so the next refresh
+    /// recomputes it.
+    pub async fn configure_widget(
+        &mut self,
+        widget_id: &str,
+        config: WidgetConfig,
+    ) -> Result<RegisteredWidget> {
+        let widget = self
+            .widgets
+            .get_mut(widget_id)
+            .ok_or_else(|| anyhow!("Widget not found: {}", widget_id))?;
+        widget.config = config.normalized();
+        widget.updated_at = Utc::now().timestamp();
+        let widget = widget.clone();
+        self.snapshots.remove(widget_id);
+        self.save().await?;
+        Ok(widget)
+    }
+
+    pub async fn remove_widget(&mut self, widget_id: &str) -> Result<()> {
+        let widget = self
+            .widgets
+            .get(widget_id)
+            .ok_or_else(|| anyhow!("Widget not found: {}", widget_id))?;
+        if widget.is_builtin() {
+            return Err(anyhow!("Built-in widget {} cannot be removed", widget_id));
         }
+        self.widgets.remove(widget_id);
+        self.snapshots.remove(widget_id);
+        self.resolved_tokens.remove(widget_id);
+        self.save().await
     }
 
-    fn generate_portfolio_summary_widget(&self) -> WidgetData {
-        let now = Utc::now().timestamp();
-        WidgetData {
-            widget_id: "portfolio_summary".to_string(),
-            widget_type: WidgetType::PortfolioSummary,
-            data: serde_json::json!({
-                "total_value": 12345.67,
-                "total_change_24h": 234.56,
-                "total_change_pct": 1.93,
-                "top_asset": "SOL"
-            }),
+    pub fn get_widget(&self, widget_id: &str) -> Option<RegisteredWidget> {
+        self.widgets.get(widget_id).cloned()
+    }
+
+    /// Widgets visible to a device: the shared ones plus its own. `None` lists all.
+    pub fn list_widgets(&self, device_id: Option<&str>) -> Vec<RegisteredWidget> {
+        let mut widgets: Vec<RegisteredWidget> = self
+            .widgets
+            .values()
+            .filter(|widget| match (device_id, widget.device_id.as_deref()) {
+                (Some(device), Some(owner)) => device == owner,
+                _ => true,
+            })
+            .cloned()
+            .collect();
+        widgets.sort_by(|a, b| (a.created_at, &a.widget_id).cmp(&(b.created_at, &b.widget_id)));
+        widgets
+    }
+
+    pub fn due_widgets(&self, now: i64) -> Vec<RegisteredWidget> {
+        self.widgets
+            .values()
+            .filter(|widget| {
+                self.snapshots
+                    .get(&widget.widget_id)
+                    .map_or(true, |snapshot| snapshot.next_refresh_at <= now)
+            })
+            .cloned()
+            .collect()
+    }
+
+    pub fn record_quote(&mut self, delta: &PriceDelta) {
+        let quote = self
+            .quotes
+            .entry(delta.symbol.clone())
+            .or_insert(WidgetQuote {
+                price: None,
+                change_pct: None,
+            });
+        if delta.price.is_some() {
+            quote.price = delta.price;
+        }
+        if delta.change.is_some() {
+            quote.change_pct = delta.change;
+        }
+    }
+
+    pub fn watched_tokens(&self) -> HashSet<String> {
+        self.resolved_tokens.values().flatten().cloned().collect()
+    }
+
+    /// Stores a freshly computed snapshot. Returns `None` if the widget was removed
+    /// while its inputs were being resolved.
+    pub fn apply_refresh(
+        &mut self,
+        widget_id: &str,
+        inputs: WidgetInputs,
+        now: i64,
+    ) -> Option<WidgetData> {
+        let widget = self.widgets.get(widget_id)?;
+        let snapshot = WidgetData {
+            widget_id: widget.widget_id.clone(),
+            widget_type: widget.widget_type,
+            data: build_payload(widget, &inputs, &self.quotes, now),
             last_update: now,
+            next_refresh_at: now + widget.config.refresh_interval_secs as i64,
+            is_stale: false,
+            fallback_reason: inputs.fallback_reason,
+        };
+        self.resolved_tokens
+            .insert(widget_id.to_string(), inputs.tokens);
+        self.snapshots
+            .insert(widget_id.to_string(), snapshot.clone());
+        Some(snapshot)
+    }
+
+    /// Cheap read of the last precomputed snapshot, flagged stale if the scheduler
+    /// has fallen behind. Widgets not yet computed return an empty stale snapshot.
+    pub fn snapshot(&self, widget_id: &str, now: i64) -> Option<WidgetData> {
+        let widget = self.widgets.get(widget_id)?;
+        let Some(snapshot) = self.snapshots.get(widget_id) else {
+            return Some(WidgetData {
+                widget_id: widget.widget_id.clone(),
+                widget_type: widget.widget_type,
+                data: Value::Null,
+                last_update: 0,
+                next_refresh_at: now,
+                is_stale: true,
+                fallback_reason: None,
+            });
+        };
+        let mut snapshot = snapshot.clone();
+        snapshot.is_stale =
+            now >= snapshot.next_refresh_at + widget.config.refresh_interval_secs as i64;
+        Some(snapshot)
+    }
+
+    pub fn snapshots(&self, device_id: Option<&str>, now: i64) -> Vec<WidgetData> {
+        self.list_widgets(device_id)
+            .iter()
+            .filter_map(|widget| self.snapshot(&widget.widget_id, now))
+            .collect()
+    }
+}
+
+fn build_payload(
+    widget: &RegisteredWidget,
+    inputs: &WidgetInputs,
+    quotes: &HashMap<String, WidgetQuote>,
+    now: i64,
+) -> Value {
+    let limit = widget.config.max_items;
+    match widget.widget_type {
+        WidgetType::PriceWatch => {
+            let assets: Vec<Value> = inputs
+                .tokens
+                .iter()
+                .take(limit)
+                .map(|symbol| {
+                    let quote = quotes.get(symbol);
+                    json!({
+                        "symbol": symbol,
+                        "price": quote.and_then(|q| q.price),
+                        "change_pct": quote.and_then(|q| q.change_pct),
+                    })
+                })
+                .collect();
+            json!({ "assets": assets })
+        }
+        // Holdings aren't tracked on the backend yet, so the totals stay fixed;
+        // the scope is echoed so the widget can label itself.
+        WidgetType::PortfolioSummary => json!({
+            "scope": widget.config.portfolio_scope,
+            "total_value": 12345.67,
+            "total_change_24h": 234.56,
+            "total_change_pct": 1.93,
+            "top_asset": "SOL",
+            "attribution": inputs
+                .attribution
+                .as_ref()
+                .map(|attribution| attribution_payload(attribution, limit)),
+        }),
+        WidgetType::Alerts => alerts_payload(widget, inputs, limit, now),
+        WidgetType::TopMovers => {
+            let universe: Vec<&String> = match widget.config.tokens {
+                WidgetTokenSource::Default => quotes.keys().collect(),
+                _ => inputs.tokens.iter().collect(),
+            };
+            let mut movers: Vec<(&String, f64)> = universe
+                .into_iter()
+                .filter_map(|symbol| Some((symbol, quotes.get(symbol)?.change_pct?)))
+                .collect();
+            movers.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+            let entry = |(symbol, change): &(&String, f64)| json!({ "symbol": symbol, "change_pct": change });
+            let gainers: Vec<Value> = movers
+                .iter()
+                .filter(|(_, change)| *change > 0.0)
+                .take(limit)
+                .map(entry)
+                .collect();
+            let losers: Vec<Value> = movers
+                .iter()
+                .rev()
+                .filter(|(_, change)| *change < 0.0)
+                .take(limit)
+                .map(entry)
+                .collect();
+            json!({ "gainers": gainers, "losers": losers })
+        }
+        WidgetType::QuickActions => {
+            let mut actions: Vec<Value> = inputs
+                .tokens
+                .iter()
+                .flat_map(|symbol| {
+                    [
+                        json!({
+                            "id": format!("buy_{}", symbol.to_lowercase()),
+                            "label": format!("Buy {}", symbol),
+                            "enabled": true
+                        }),
+                        json!({
+                            "id": format!("sell_{}", symbol.to_lowercase()),
+                            "label": format!("Sell {}", symbol),
+                            "enabled": true
+                        }),
+                    ]
+                })
+                .take(limit.saturating_sub(1))
+                .collect();
+            actions
+                .push(json!({"id": "view_portfolio", "label": "View Portfolio", "enabled": true}));
+            json!({ "actions": actions })
         }
     }
+}
 
-    fn generate_alerts_widget(&self) -> WidgetData {
-        let now = Utc::now().timestamp();
-        WidgetData {
-            widget_id: "alerts".to_string(),
-            widget_type: WidgetType::Alerts,
-            data: serde_json::json!({
-                "active_alerts": 3,
-                "triggered_today": 1,
-                "latest": {
-                    "symbol": "SOL",
-                    "condition": "above",
-                    "value": 150.0
+/// Compact breakdown: the tray's collapsed contributions, capped at the
+/// widget's item limit.
+fn attribution_payload(attribution: &PnlAttribution, limit: usize) -> Value {
+    let sources: Vec<Value> = attribution
+        .contributions
+        .iter()
+        .take(limit)
+        .map(|contribution| {
+            json!({
+                "source": contribution.source,
+                "label": contribution.label,
+                "pnl_value": contribution.pnl_value,
+            })
+        })
+        .collect();
+    json!({
+        "date": attribution.date,
+        "pnl_value": attribution.pnl_value,
+        "top_source": attribution.top_contributor().map(|top| top.source),
+        "sources": sources,
+    })
+}
+
+fn alerts_payload(
+    widget: &RegisteredWidget,
+    inputs: &WidgetInputs,
+    limit: usize,
+    now: i64,
+) -> Value {
+    let filter_tokens = !matches!(widget.config.tokens, WidgetTokenSource::Default);
+    let alerts: Vec<&PriceAlert> = inputs
+        .alerts
+        .iter()
+        .filter(|alert| !filter_tokens || inputs.tokens.contains(&alert.symbol))
+        .collect();
+    let today = DateTime::from_timestamp(now, 0).map(|at| at.date_naive());
+
+    let active = alerts
+        .iter()
+        .filter(|alert| matches!(alert.state, AlertState::Active | AlertState::Cooldown))
+        .count();
+    let mut triggered: Vec<(DateTime<Utc>, &PriceAlert)> = alerts
+        .iter()
+        .filter_map(|alert| {
+            let at = DateTime::parse_from_rfc3339(alert.last_triggered_at.as_deref()?).ok()?;
+            Some((at.with_timezone(&Utc), *alert))
+        })
+        .collect();
+    triggered.sort_by(|a, b| b.0.cmp(&a.0));
+    let triggered_today = triggered
+        .iter()
+        .filter(|(at, _)| Some(at.date_naive()) == today)
+        .count();
+    let recent: Vec<Value> = triggered
+        .iter()
+        .take(limit)
+        .map(|(at, alert)| {
+            json!({
+                "id": alert.id,
+                "name": alert.name,
+                "symbol": alert.symbol,
+                "triggered_at": at.timestamp(),
+            })
+        })
+        .collect();
+
+    json!({
+        "active_alerts": active,
+        "triggered_today": triggered_today,
+        "latest": recent.first().cloned(),
+        "recent": recent,
+    })
+}
+
+/// Resolves a widget's configured sources, falling back to defaults (with a reason)
+/// when a watchlist was deleted or a store is unavailable.
+async fn resolve_inputs(app: &AppHandle, widget: &RegisteredWidget) -> WidgetInputs {
+    let resolved = match &widget.config.tokens {
+        WidgetTokenSource::Default => Ok(default_tokens()),
+        WidgetTokenSource::Tokens { symbols } => Ok(symbols.clone()),
+        WidgetTokenSource::Watchlist { watchlist_id } => watchlist_symbols(app, watchlist_id).await,
+    };
+    let mut inputs = WidgetInputs::from_tokens(resolved);
+
+    if widget.widget_type == WidgetType::PortfolioSummary
+        && widget.config.portfolio_scope == WidgetPortfolioScope::All
+    {
+        inputs.attribution = app
+            .try_state::<SharedTrayManager>()
+            .map(|tray| tray.get_stats().attribution);
+    }
+
+    if widget.widget_type == WidgetType::Alerts {
+        match app.try_state::<SharedAlertManager>() {
+            Some(alerts) => match alerts.read().await.list_alerts().await {
+                Ok(list) => inputs.alerts = list,
+                Err(e) => {
+                    eprintln!("Widget {}: failed to read alerts: {}", widget.widget_id, e);
+                    inputs.fallback_reason = Some("Alerts are temporarily unavailable".to_string());
                 }
-            }),
-            last_update: now,
+            },
+            None => inputs.fallback_reason = Some("Alerts are unavailable".to_string()),
         }
     }
 
-    fn generate_top_movers_widget(&self) -> WidgetData {
-        let now = Utc::now().timestamp();
-        WidgetData {
-            widget_id: "top_movers".to_string(),
-            widget_type: WidgetType::TopMovers,
-            data: serde_json::json!({
-                "gainers": [
-                    {"symbol": "SOL", "change_pct": 5.23},
-                    {"symbol": "JUP", "change_pct": 3.45},
-                ],
-                "losers": [
-                    {"symbol": "BONK", "change_pct": -2.45},
-                ]
-            }),
-            last_update: now,
+    inputs
+}
+
+async fn watchlist_symbols(
+    app: &AppHandle,
+    watchlist_id: &str,
+) -> std::result::Result<Vec<String>, String> {
+    let Some(watchlists) = app.try_state::<SharedWatchlistManager>() else {
+        return Err("Watchlists are unavailable".to_string());
+    };
+    let manager = watchlists.read().await;
+    match manager.get_watchlist(watchlist_id).await {
+        Ok(mut watchlist) => {
+            watchlist.items.sort_by_key(|item| item.position);
+            Ok(normalize_symbols(
+                watchlist.items.iter().map(|item| item.symbol.as_str()),
+            ))
         }
+        Err(WatchlistError::NotFound(_)) => {
+            Err(format!("Watchlist {} no longer exists", watchlist_id))
+        }
+        Err(e) => Err(format!("Failed to read watchlist {}: {}", watchlist_id, e)),
     }
+}
 
-    fn generate_quick_actions_widget(&self) -> WidgetData {
-        let now = Utc::now().timestamp();
-        WidgetData {
-            widget_id: "quick_actions".to_string(),
-            widget_type: WidgetType::QuickActions,
-            data: serde_json::json!({
-                "actions": [
-                    {"id": "buy_sol", "label": "Buy SOL", "enabled": true},
-                    {"id": "sell_sol", "label": "Sell SOL", "enabled": true},
-                    {"id": "view_portfolio", "label": "View Portfolio", "enabled": true},
-                ]
-            }),
-            last_update: now,
+/// Recomputes a single widget immediately, e.g. right after it was configured.
+pub async fn refresh_widget(
+    app: &AppHandle,
+    widgets: &SharedWidgetManager,
+    widget_id: &str,
+) -> Option<WidgetData> {
+    let widget = widgets.read().await.get_widget(widget_id)?;
+    let inputs = resolve_inputs(app, &widget).await;
+    widgets
+        .write()
+        .await
+        .apply_refresh(widget_id, inputs, Utc::now().timestamp())
+}
+
+async fn refresh_due_widgets(app: &AppHandle, widgets: &SharedWidgetManager) {
+    let due = widgets.read().await.due_widgets(Utc::now().timestamp());
+    for widget in due {
+        let inputs = resolve_inputs(app, &widget).await;
+        widgets
+            .write()
+            .await
+            .apply_refresh(&widget.widget_id, inputs, Utc::now().timestamp());
+    }
+}
+
+/// Precomputes widget snapshots on each widget's schedule and keeps a price cache
+/// fed from the multiplexer as the `widgets` consumer, so reads never hit a store.
+pub async fn run_widget_refresh(
+    app: AppHandle,
+    widgets: SharedWidgetManager,
+    ws_manager: WebSocketManager,
+) {
+    let mut feed = ws_manager.price_feed(WIDGET_CONSUMER, WIDGET_PRICE_THROTTLE);
+    let mut watched: HashSet<String> = ws_manager
+        .price_consumer_tokens(WIDGET_CONSUMER)
+        .into_iter()
+        .collect();
+    let mut tick = interval(WIDGET_REFRESH_TICK);
+
+    loop {
+        tokio::select! {
+            _ = tick.tick() => {
+                refresh_due_widgets(&app, &widgets).await;
+                sync_widget_symbols(&widgets, &ws_manager, &mut watched).await;
+            }
+            delta = feed.recv() => {
+                let Some(delta) = delta else {
+                    break;
+                };
+                widgets.write().await.record_quote(&delta);
+            }
         }
     }
+}
 
-    pub async fn get_all_widget_data(&self) -> Vec<WidgetData> {
-        vec![
-            self.generate_price_watch_widget(),
-            self.generate_portfolio_summary_widget(),
-            self.generate_alerts_widget(),
-            self.generate_top_movers_widget(),
-            self.generate_quick_actions_widget(),
-        ]
+async fn sync_widget_symbols(
+    widgets: &SharedWidgetManager,
+    ws_manager: &WebSocketManager,
+    watched: &mut HashSet<String>,
+) {
+    let wanted = widgets.read().await.watched_tokens();
+    let added: Vec<String> = wanted.difference(watched).cloned().collect();
+    let removed: Vec<String> = watched.difference(&wanted).cloned().collect();
+
+    if !added.is_empty() {
+        match ws_manager
+            .subscribe_prices_as(WIDGET_CONSUMER, added.clone())
+            .await
+        {
+            Ok(()) => watched.extend(added),
+            Err(err) => eprintln!("Failed to subscribe widget symbols: {}", err),
+        }
+    }
+    if !removed.is_empty() {
+        if let Err(err) = ws_manager
+            .unsubscribe_prices_as(WIDGET_CONSUMER, removed.clone())
+            .await
+        {
+            eprintln!("Failed to unsubscribe widget symbols: {}", err);
+        }
+        for symbol in removed {
+            watched.remove(&symbol);
+        }
     }
 }
 
 #[tauri::command]
-pub async fn mobile_get_widget_data(
+pub async fn mobile_register_widget(
     widget_type: WidgetType,
-    widget_manager: tauri::State<'_, Arc<RwLock<WidgetManager>>>,
-) -> Result<Option<WidgetData>, String> {
+    device_id: Option<String>,
+    config: Option<WidgetConfig>,
+    app: AppHandle,
+    widget_manager: tauri::State<'_, SharedWidgetManager>,
+    privacy: tauri::State<'_, SharedPrivacyMode>,
+) -> Result<Masked<WidgetData>, String> {
+    let widget = widget_manager
+        .write()
+        .await
+        .register_widget(widget_type, device_id, config.unwrap_or_default())
+        .await
+        .map_err(|e| e.to_string())?;
+    let data = refresh_widget(&app, widget_manager.inner(), &widget.widget_id)
+        .await
+        .ok_or_else(|| format!("Widget not found: {}", widget.widget_id))?;
+    let privacy = privacy.read().map_err(|e| e.to_string())?;
+    Ok(privacy.apply(data))
+}
+
+#[tauri::command]
+pub async fn mobile_configure_widget(
+    widget_id: String,
+    config: WidgetConfig,
+    app: AppHandle,
+    widget_manager: tauri::State<'_, SharedWidgetManager>,
+    privacy: tauri::State<'_, SharedPrivacyMode>,
+) -> Result<Masked<WidgetData>, String> {
+    widget_manager
+        .write()
+        .await
+        .configure_widget(&widget_id, config)
+        .await
+        .map_err(|e| e.to_string())?;
+    let data = refresh_widget(&app, widget_manager.inner(), &widget_id)
+        .await
+        .ok_or_else(|| format!("Widget not found: {}", widget_id))?;
+    let privacy = privacy.read().map_err(|e| e.to_string())?;
+    Ok(privacy.apply(data))
+}
+
+#[tauri::command]
+pub async fn mobile_remove_widget(
+    widget_id: String,
+    widget_manager: tauri::State<'_, SharedWidgetManager>,
+) -> Result<(), String> {
+    widget_manager
+        .write()
+        .await
+        .remove_widget(&widget_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn mobile_list_widgets(
+    device_id: Option<String>,
+    widget_manager: tauri::State<'_, SharedWidgetManager>,
+) -> Result<Vec<RegisteredWidget>, String> {
     let manager = widget_manager.read().await;
-    Ok(manager.get_widget_data(widget_type).await)
+    Ok(manager.list_widgets(device_id.as_deref()))
+}
+
+/// Looks up a widget by id, or the built-in widget for `widget_type`.
+#[tauri::command]
+pub async fn mobile_get_widget_data(
+    widget_id: Option<String>,
+    widget_type: Option<WidgetType>,
+    widget_manager: tauri::State<'_, SharedWidgetManager>,
+    privacy: tauri::State<'_, SharedPrivacyMode>,
+) -> Result<Masked<Option<WidgetData>>, String> {
+    let widget_id = match (widget_id, widget_type) {
+        (Some(id), _) => id,
+        (None, Some(widget_type)) => widget_type.builtin_id().to_string(),
+        (None, None) => return Err("Either widget_id or widget_type is required".to_string()),
+    };
+    let snapshot = widget_manager
+        .read()
+        .await
+        .snapshot(&widget_id, Utc::now().timestamp());
+    let privacy = privacy.read().map_err(|e| e.to_string())?;
+    Ok(privacy.apply(snapshot))
 }
 
 #[tauri::command]
 pub async fn mobile_get_all_widgets(
-    widget_manager: tauri::State<'_, Arc<RwLock<WidgetManager>>>,
-) -> Result<Vec<WidgetData>, String> {
-    let manager = widget_manager.read().await;
-    Ok(manager.get_all_widget_data().await)
+    device_id: Option<String>,
+    widget_manager: tauri::State<'_, SharedWidgetManager>,
+    privacy: tauri::State<'_, SharedPrivacyMode>,
+) -> Result<Masked<Vec<WidgetData>>, String> {
+    let snapshots = widget_manager
+        .read()
+        .await
+        .snapshots(device_id.as_deref(), Utc::now().timestamp());
+    let privacy = privacy.read().map_err(|e| e.to_string())?;
+    Ok(privacy.apply(snapshots))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manager() -> WidgetManager {
+        WidgetManager::new(std::env::temp_dir())
+    }
+
+    #[test]
+    fn test_deleted_watchlist_falls_back_to_defaults() {
+        let mut manager = manager();
+        manager
+            .widgets
+            .get_mut("price_watch")
+            .unwrap()
+            .config
+            .tokens = WidgetTokenSource::Watchlist {
+            watchlist_id: "wl-1".to_string(),
+        };
+
+        let inputs = WidgetInputs::from_tokens(Err("Watchlist wl-1 no longer exists".to_string()));
+        let snapshot = manager.apply_refresh("price_watch", inputs, 1_000).unwrap();
+
+        assert_eq!(
+            snapshot.fallback_reason.as_deref(),
+            Some("Watchlist wl-1 no longer exists")
+        );
+        let symbols: Vec<&str> = snapshot.data["assets"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|asset| asset["symbol"].as_str().unwrap())
+            .collect();
+        assert_eq!(symbols, DEFAULT_WIDGET_TOKENS);
+    }
+
+    #[test]
+    fn test_payload_is_bounded_and_staleness_tracked() {
+        let mut manager = manager();
+        let tokens: Vec<String> = (0..40).map(|i| format!("TOK{}", i)).collect();
+        for (i, symbol) in tokens.iter().enumerate() {
+            manager.record_quote(&PriceDelta {
+                symbol: symbol.clone(),
+                price: Some(1.0),
+                change: Some(i as f64 - 20.0),
+                volume: None,
+                ts: 0,
+                snapshot: true,
+            });
+        }
+
+        let custom = WidgetConfig {
+            tokens: WidgetTokenSource::Tokens { symbols: tokens },
+            ..WidgetConfig::default()
+        }
+        .normalized();
+        assert!(matches!(
+            custom.tokens,
+            WidgetTokenSource::Tokens { ref symbols } if symbols.len() == MAX_WIDGET_ITEMS
+        ));
+
+        let config = WidgetConfig {
+            refresh_interval_secs: 5,
+            max_items: 100,
+            ..WidgetConfig::default()
+        }
+        .normalized();
+        assert_eq!(config.max_items, MAX_WIDGET_ITEMS);
+        assert_eq!(config.refresh_interval_secs, MIN_REFRESH_INTERVAL_SECS);
+        manager.widgets.get_mut("top_movers").unwrap().config = config;
+
+        // Default-sourced movers rank every quoted symbol.
+        let inputs = WidgetInputs::from_tokens(Ok(default_tokens()));
+        let snapshot = manager.apply_refresh("top_movers", inputs, 1_000).unwrap();
+        assert_eq!(
+            snapshot.data["gainers"].as_array().unwrap().len(),
+            MAX_WIDGET_ITEMS
+        );
+        assert_eq!(snapshot.data["gainers"][0]["symbol"], "TOK39");
+        assert_eq!(snapshot.data["losers"][0]["symbol"], "TOK0");
+
+        assert!(!manager.snapshot("top_movers", 1_030).unwrap().is_stale);
+        assert!(manager.snapshot("top_movers", 1_060).unwrap().is_stale);
+        assert!(manager.snapshot("alerts", 1_000).unwrap().is_stale);
+    }
 }
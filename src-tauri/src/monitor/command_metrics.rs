@@ -0,0 +1,449 @@
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Instant;
+use tauri::ipc::{Invoke, InvokeBody};
+use tauri::Runtime;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Metadata, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+const SAMPLE_CAPACITY: usize = 512;
+const DEFAULT_TRACE_THRESHOLD_MS: u64 = 250;
+const SHAPE_MAX_DEPTH: usize = 4;
+
+/// The span `instrument_invoke` opens around each command.
+const COMMAND_SPAN: &str = "command";
+/// Tauri's span around an async command's future or a blocking command's
+/// body. It is a child of the command span and keeps it open until the
+/// command responds.
+const TAURI_RUN_SPAN: &str = "ipc::request::run";
+/// Tauri's span around the response sent back to the webview.
+const TAURI_RESPONSE_SPAN: &str = "ipc::request::response";
+/// Argument names whose values are left out of traces entirely, compared
+/// lowercase with underscores removed.
+const SECRET_ARGS: &[&str] = &[
+    "apikey",
+    "password",
+    "passphrase",
+    "pin",
+    "secret",
+    "privatekey",
+    "mnemonic",
+    "seedphrase",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandStats {
+    pub command: String,
+    pub invocations: u64,
+    pub errors: u64,
+    pub error_rate: f64,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub p99_ms: f64,
+    pub mean_ms: f64,
+    pub max_ms: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TracingConfig {
+    pub enabled: bool,
+    pub threshold_ms: u64,
+}
+
+#[derive(Default)]
+struct CommandSamples {
+    invocations: u64,
+    errors: u64,
+    durations_us: VecDeque<u64>,
+}
+
+/// Per-command invocation counts and latency samples.
+pub struct CommandMetrics {
+    commands: RwLock<HashMap<String, CommandSamples>>,
+    tracing_enabled: AtomicBool,
+    trace_threshold_ms: AtomicU64,
+}
+
+lazy_static::lazy_static! {
+    static ref COMMAND_METRICS: CommandMetrics = CommandMetrics::new();
+}
+
+pub fn command_metrics() -> &'static CommandMetrics {
+    &COMMAND_METRICS
+}
+
+impl CommandMetrics {
+    fn new() -> Self {
+        Self {
+            commands: RwLock::new(HashMap::new()),
+            tracing_enabled: AtomicBool::new(false),
+            trace_threshold_ms: AtomicU64::new(DEFAULT_TRACE_THRESHOLD_MS),
+        }
+    }
+
+    pub fn record(&self, command: &str, duration_us: u64, ok: bool) {
+        let mut commands = self.commands.write();
+        if !commands.contains_key(command) {
+            commands.insert(command.to_string(), CommandSamples::default());
+        }
+        let samples = commands.get_mut(command).expect("inserted above");
+        samples.invocations += 1;
+        if !ok {
+            samples.errors += 1;
+        }
+        if samples.durations_us.len() >= SAMPLE_CAPACITY {
+            samples.durations_us.pop_front();
+        }
+        samples.durations_us.push_back(duration_us);
+    }
+
+    pub fn set_tracing(&self, enabled: bool, threshold_ms: u64) {
+        self.trace_threshold_ms
+            .store(threshold_ms, Ordering::Relaxed);
+        self.tracing_enabled.store(enabled, Ordering::Release);
+    }
+
+    pub fn tracing_config(&self) -> TracingConfig {
+        TracingConfig {
+            enabled: self.tracing_enabled.load(Ordering::Acquire),
+            threshold_ms: self.trace_threshold_ms.load(Ordering::Relaxed),
+        }
+    }
+
+    #[inline]
+    pub fn tracing_enabled(&self) -> bool {
+        self.tracing_enabled.load(Ordering::Relaxed)
+    }
+
+    /// Stats for every command seen so far, slowest p95 first.
+    pub fn snapshot(&self) -> Vec<CommandStats> {
+        let commands = self.commands.read();
+        let mut stats: Vec<CommandStats> = commands
+            .iter()
+            .map(|(command, samples)| summarize(command, samples))
+            .collect();
+        stats.sort_by(|a, b| {
+            b.p95_ms
+                .partial_cmp(&a.p95_ms)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        stats
+    }
+
+    pub fn reset(&self) {
+        self.commands.write().clear();
+    }
+}
+
+fn summarize(command: &str, samples: &CommandSamples) -> CommandStats {
+    let mut sorted: Vec<u64> = samples.durations_us.iter().copied().collect();
+    sorted.sort_unstable();
+
+    let to_ms = |us: u64| us as f64 / 1000.0;
+    let percentile = |p: f64| -> f64 {
+        if sorted.is_empty() {
+            return 0.0;
+        }
+        let idx = ((sorted.len() as f64 - 1.0) * p).round() as usize;
+        to_ms(sorted[idx.min(sorted.len() - 1)])
+    };
+    let mean_ms = if sorted.is_empty() {
+        0.0
+    } else {
+        to_ms(sorted.iter().sum::<u64>()) / sorted.len() as f64
+    };
+
+    CommandStats {
+        command: command.to_string(),
+        invocations: samples.invocations,
+        errors: samples.errors,
+        error_rate: if samples.invocations == 0 {
+            0.0
+        } else {
+            samples.errors as f64 / samples.invocations as f64
+        },
+        p50_ms: percentile(0.50),
+        p95_ms: percentile(0.95),
+        p99_ms: percentile(0.99),
+        mean_ms,
+        max_ms: sorted.last().copied().map(to_ms).unwrap_or(0.0),
+    }
+}
+
+/// Wraps the invoke handler so every command is measured. The span opened
+/// here is the parent of Tauri's run span for the command, so it closes when
+/// the command responds rather than when it is dispatched, and
+/// [`CommandMetricsLayer`] records it then. The argument shape is only built
+/// while slow-command tracing is on.
+pub fn instrument_invoke<R: Runtime>(
+    invoke: Invoke<R>,
+    handler: &dyn Fn(Invoke<R>) -> bool,
+) -> bool {
+    let span = tracing::info_span!(
+        COMMAND_SPAN,
+        command = invoke.message.command(),
+        args = tracing::field::Empty,
+    );
+    if command_metrics().tracing_enabled() {
+        if let InvokeBody::Json(args) = invoke.message.payload() {
+            span.record("args", tracing::field::display(shape_of(args, 0)));
+        }
+    }
+    let _entered = span.enter();
+    handler(invoke)
+}
+
+/// One command between dispatch and response.
+struct CommandSpan {
+    command: String,
+    started: Instant,
+    ok: bool,
+    arg_shape: Option<String>,
+}
+
+impl CommandSpan {
+    fn finish(self) {
+        let metrics = command_metrics();
+        let total_us = self.started.elapsed().as_micros() as u64;
+        metrics.record(&self.command, total_us, self.ok);
+
+        let Some(arg_shape) = self.arg_shape else {
+            return;
+        };
+        let config = metrics.tracing_config();
+        if !config.enabled || total_us < config.threshold_ms * 1000 {
+            return;
+        }
+        tracing::warn!(
+            command = %self.command,
+            duration_ms = total_us as f64 / 1000.0,
+            ok = self.ok,
+            args = %arg_shape,
+            "slow command"
+        );
+    }
+}
+
+#[derive(Default)]
+struct SpanFields {
+    command: Option<String>,
+    args: Option<String>,
+    response_is_error: bool,
+}
+
+impl Visit for SpanFields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "command" => self.command = Some(value.to_string()),
+            "args" => self.args = Some(value.to_string()),
+            // Tauri records a rejected command's response as the
+            // `InvokeError`'s debug output.
+            "response" => self.response_is_error = value.starts_with("InvokeError"),
+            _ => {}
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "args" {
+            self.args = Some(format!("{:?}", value));
+        }
+    }
+}
+
+/// Feeds the command spans into [`command_metrics`]. Installed with
+/// [`is_command_metrics_span`] as its filter, so it never sees other spans.
+pub struct CommandMetricsLayer;
+
+pub fn is_command_metrics_span(metadata: &Metadata<'_>) -> bool {
+    metadata.is_span()
+        && matches!(
+            metadata.name(),
+            COMMAND_SPAN | TAURI_RUN_SPAN | TAURI_RESPONSE_SPAN
+        )
+}
+
+impl<S> Layer<S> for CommandMetricsLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let mut fields = SpanFields::default();
+        attrs.record(&mut fields);
+        match attrs.metadata().name() {
+            COMMAND_SPAN => {
+                if let Some(span) = ctx.span(id) {
+                    span.extensions_mut().insert(CommandSpan {
+                        command: fields.command.unwrap_or_default(),
+                        started: Instant::now(),
+                        ok: true,
+                        arg_shape: fields.args,
+                    });
+                }
+            }
+            TAURI_RESPONSE_SPAN if fields.response_is_error => {
+                // The response is sent from inside the command's run span.
+                let Some(current) = ctx.lookup_current() else {
+                    return;
+                };
+                for span in current.scope() {
+                    if let Some(command) = span.extensions_mut().get_mut::<CommandSpan>() {
+                        command.ok = false;
+                        return;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let mut fields = SpanFields::default();
+        values.record(&mut fields);
+        let (Some(args), Some(span)) = (fields.args, ctx.span(id)) else {
+            return;
+        };
+        if let Some(command) = span.extensions_mut().get_mut::<CommandSpan>() {
+            command.arg_shape = Some(args);
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let command = span.extensions_mut().remove::<CommandSpan>();
+        if let Some(command) = command {
+            command.finish();
+        }
+    }
+}
+
+fn is_secret_arg(name: &str) -> bool {
+    let normalized = name.replace('_', "").to_ascii_lowercase();
+    SECRET_ARGS.contains(&normalized.as_str())
+}
+
+/// Replaces values with their type names so traces never contain user data.
+/// Secret arguments are not even described.
+fn shape_of(value: &Value, depth: usize) -> Value {
+    match value {
+        Value::Null => Value::String("null".to_string()),
+        Value::Bool(_) => Value::String("bool".to_string()),
+        Value::Number(_) => Value::String("number".to_string()),
+        Value::String(s) => Value::String(format!("string({})", s.len())),
+        Value::Array(items) => {
+            if depth >= SHAPE_MAX_DEPTH || items.is_empty() {
+                Value::String(format!("array({})", items.len()))
+            } else {
+                Value::Array(vec![
+                    shape_of(&items[0], depth + 1),
+                    Value::String(format!("len={}", items.len())),
+                ])
+            }
+        }
+        Value::Object(map) => {
+            if depth >= SHAPE_MAX_DEPTH {
+                return Value::String(format!("object({})", map.len()));
+            }
+            Value::Object(
+                map.iter()
+                    .map(|(key, v)| {
+                        let shape = if is_secret_arg(key) {
+                            Value::String("redacted".to_string())
+                        } else {
+                            shape_of(v, depth + 1)
+                        };
+                        (key.clone(), shape)
+                    })
+                    .collect(),
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::filter::filter_fn;
+    use tracing_subscriber::prelude::*;
+
+    fn stats(command: &str) -> Option<CommandStats> {
+        command_metrics()
+            .snapshot()
+            .into_iter()
+            .find(|stats| stats.command == command)
+    }
+
+    #[test]
+    fn command_span_lasts_until_the_command_responds() {
+        let subscriber = tracing_subscriber::registry()
+            .with(CommandMetricsLayer.with_filter(filter_fn(is_command_metrics_span)));
+        tracing::subscriber::with_default(subscriber, || {
+            let run = {
+                let command = tracing::info_span!(
+                    COMMAND_SPAN,
+                    command = "test_rejected_command",
+                    args = tracing::field::Empty,
+                );
+                let _entered = command.enter();
+                tracing::debug_span!(TAURI_RUN_SPAN)
+            };
+            // Dispatched, but still running.
+            assert!(stats("test_rejected_command").is_none());
+
+            {
+                let _entered = run.enter();
+                let _response =
+                    tracing::trace_span!(TAURI_RESPONSE_SPAN, response = "InvokeError(\"no\")")
+                        .entered();
+            }
+            drop(run);
+        });
+
+        let stats = stats("test_rejected_command").unwrap();
+        assert_eq!(stats.invocations, 1);
+        assert_eq!(stats.errors, 1);
+    }
+
+    #[test]
+    fn shape_hides_values() {
+        let value =
+            serde_json::json!({ "wallet": "secret-address", "amount": 12.5, "tags": ["a"] });
+        let shape = shape_of(&value, 0);
+        assert_eq!(shape["wallet"], "string(14)");
+        assert_eq!(shape["amount"], "number");
+        assert!(!shape.to_string().contains("secret"));
+
+        let args = serde_json::json!({ "address": "mint", "apiKey": "sk-123", "api_key": "x" });
+        let shape = shape_of(&args, 0);
+        assert_eq!(shape["address"], "string(4)");
+        assert_eq!(shape["apiKey"], "redacted");
+        assert_eq!(shape["api_key"], "redacted");
+    }
+
+    #[test]
+    fn percentiles_and_error_rate() {
+        let mut samples = CommandSamples::default();
+        for us in 1..=100u64 {
+            samples.invocations += 1;
+            samples.durations_us.push_back(us * 1000);
+        }
+        samples.errors = 5;
+
+        let stats = summarize("cmd", &samples);
+        assert_eq!(stats.invocations, 100);
+        assert!((stats.error_rate - 0.05).abs() < f64::EPSILON);
+        assert!((stats.p50_ms - 51.0).abs() < 1.0);
+        assert!((stats.p99_ms - 99.0).abs() < 1.0);
+        assert_eq!(stats.max_ms, 100.0);
+    }
+}
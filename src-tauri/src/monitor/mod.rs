@@ -1,3 +1,5 @@
+pub mod command_metrics;
 pub mod performance;
 
+pub use command_metrics::*;
 pub use performance::*;
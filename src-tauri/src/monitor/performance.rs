@@ -143,4 +143,8 @@ impl PerformanceMonitor {
     pub fn subscribe(&self) -> broadcast::Receiver<PerformanceMetrics> {
         self.subscribers.subscribe()
     }
+
+    pub fn command_stats(&self) -> Vec<super::CommandStats> {
+        super::command_metrics().snapshot()
+    }
 }
@@ -1,9 +1,12 @@
+use chrono::DateTime;
 use tauri::State;
 
 use super::router::SharedNotificationRouter;
+use super::self_test::{ChannelTestReport, DegradedChannel, SelfTestSchedule, SelfTestTrigger};
 use super::types::{
-    ChatIntegrationSettings, DeliveryLog, DiscordConfig, RateLimitStatus, SlackConfig,
-    TelegramConfig, TestMessageResult,
+    ChatIntegrationSettings, DeliveryLog, DiscordConfig, HistoryCategory, NotificationFilter,
+    NotificationPage, NotificationRetention, RateLimitStatus, SlackConfig, TelegramConfig,
+    TestMessageResult,
 };
 
 #[tauri::command]
@@ -214,3 +217,141 @@ pub async fn chat_integration_get_rate_limits(
     let limiter = rate_limiter.read().await;
     Ok(limiter.get_statuses().await)
 }
+
+#[tauri::command]
+pub async fn notifications_list(
+    filter: Option<NotificationFilter>,
+    cursor: Option<String>,
+    router: State<'_, SharedNotificationRouter>,
+) -> Result<NotificationPage, String> {
+    let router = router.read().await;
+    router
+        .list_history(&filter.unwrap_or_default(), cursor.as_deref())
+        .await
+        .map_err(|e| format!("Failed to list notifications: {}", e))
+}
+
+#[tauri::command]
+pub async fn notifications_mark_read(
+    ids: Vec<String>,
+    router: State<'_, SharedNotificationRouter>,
+) -> Result<usize, String> {
+    let router = router.read().await;
+    router
+        .mark_read(&ids)
+        .await
+        .map_err(|e| format!("Failed to mark notifications read: {}", e))
+}
+
+#[tauri::command]
+pub async fn notifications_mark_all_read(
+    category: Option<HistoryCategory>,
+    router: State<'_, SharedNotificationRouter>,
+) -> Result<usize, String> {
+    let router = router.read().await;
+    router
+        .mark_all_read(category)
+        .await
+        .map_err(|e| format!("Failed to mark notifications read: {}", e))
+}
+
+/// `older_than` is an RFC 3339 timestamp; without it the history is emptied.
+#[tauri::command]
+pub async fn notifications_clear(
+    older_than: Option<String>,
+    router: State<'_, SharedNotificationRouter>,
+) -> Result<usize, String> {
+    let older_than = older_than
+        .map(|ts| {
+            DateTime::parse_from_rfc3339(&ts)
+                .map(|ts| ts.timestamp_millis())
+                .map_err(|e| format!("Invalid timestamp {}: {}", ts, e))
+        })
+        .transpose()?;
+
+    let router = router.read().await;
+    router
+        .clear_history(older_than)
+        .await
+        .map_err(|e| format!("Failed to clear notifications: {}", e))
+}
+
+#[tauri::command]
+pub async fn notifications_get_retention(
+    router: State<'_, SharedNotificationRouter>,
+) -> Result<NotificationRetention, String> {
+    let router = router.read().await;
+    router
+        .get_history_retention()
+        .await
+        .map_err(|e| format!("Failed to get notification retention: {}", e))
+}
+
+#[tauri::command]
+pub async fn notifications_set_retention(
+    retention: NotificationRetention,
+    router: State<'_, SharedNotificationRouter>,
+) -> Result<(), String> {
+    let router = router.read().await;
+    router
+        .set_history_retention(&retention)
+        .await
+        .map_err(|e| format!("Failed to set notification retention: {}", e))
+}
+
+#[tauri::command]
+pub async fn notifications_test_all_channels(
+    router: State<'_, SharedNotificationRouter>,
+) -> Result<ChannelTestReport, String> {
+    let router = router.read().await;
+    router
+        .test_all_channels(SelfTestTrigger::Manual)
+        .await
+        .map_err(|e| format!("Failed to test notification channels: {}", e))
+}
+
+#[tauri::command]
+pub async fn notifications_get_self_test_reports(
+    limit: Option<i32>,
+    router: State<'_, SharedNotificationRouter>,
+) -> Result<Vec<ChannelTestReport>, String> {
+    let router = router.read().await;
+    router
+        .get_delivery_logger()
+        .list_self_tests(limit.unwrap_or(20))
+        .await
+        .map_err(|e| format!("Failed to get self-test reports: {}", e))
+}
+
+#[tauri::command]
+pub async fn notifications_get_self_test_schedule(
+    router: State<'_, SharedNotificationRouter>,
+) -> Result<SelfTestSchedule, String> {
+    let router = router.read().await;
+    router
+        .get_delivery_logger()
+        .get_self_test_schedule()
+        .await
+        .map_err(|e| format!("Failed to get self-test schedule: {}", e))
+}
+
+#[tauri::command]
+pub async fn notifications_set_self_test_schedule(
+    schedule: SelfTestSchedule,
+    router: State<'_, SharedNotificationRouter>,
+) -> Result<(), String> {
+    let router = router.read().await;
+    router
+        .get_delivery_logger()
+        .set_self_test_schedule(&schedule)
+        .await
+        .map_err(|e| format!("Failed to set self-test schedule: {}", e))
+}
+
+#[tauri::command]
+pub async fn notifications_get_degraded_channels(
+    router: State<'_, SharedNotificationRouter>,
+) -> Result<Vec<DegradedChannel>, String> {
+    let router = router.read().await;
+    Ok(router.degraded_channels())
+}
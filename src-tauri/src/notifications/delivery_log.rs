@@ -1,9 +1,12 @@
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use sqlx::{Pool, Row, Sqlite};
 use uuid::Uuid;
 
+use super::self_test::{ChannelTestReport, SelfTestSchedule, SelfTestTrigger};
 use super::types::{ChatServiceType, DeliveryLog, DeliveryStatus, NotificationError};
 
+const SELF_TEST_SCHEDULE_KEY: &str = "self_test_schedule";
+
 pub struct DeliveryLogger {
     pool: Pool<Sqlite>,
 }
@@ -45,6 +48,24 @@ impl DeliveryLogger {
         .execute(&self.pool)
         .await?;
 
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS channel_self_tests (
+                id TEXT PRIMARY KEY,
+                triggered_by TEXT NOT NULL,
+                started_at TEXT NOT NULL,
+                report TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_channel_self_tests_started ON channel_self_tests(started_at);
+            CREATE TABLE IF NOT EXISTS delivery_log_settings (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            );
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
         Ok(())
     }
 
@@ -134,6 +155,9 @@ impl DeliveryLogger {
         sqlx::query("DELETE FROM delivery_logs")
             .execute(&self.pool)
             .await?;
+        sqlx::query("DELETE FROM channel_self_tests")
+            .execute(&self.pool)
+            .await?;
         Ok(())
     }
 
@@ -144,8 +168,108 @@ impl DeliveryLogger {
             .bind(&cutoff)
             .execute(&self.pool)
             .await?;
+        let self_tests = sqlx::query("DELETE FROM channel_self_tests WHERE started_at < ?1")
+            .bind(&cutoff)
+            .execute(&self.pool)
+            .await?;
 
-        Ok(result.rows_affected() as usize)
+        Ok((result.rows_affected() + self_tests.rows_affected()) as usize)
+    }
+
+    /// Stores a full channel self-test report next to the per-delivery rows.
+    pub async fn record_self_test(
+        &self,
+        report: &ChannelTestReport,
+    ) -> Result<(), NotificationError> {
+        sqlx::query(
+            r#"
+            INSERT INTO channel_self_tests (id, triggered_by, started_at, report)
+            VALUES (?1, ?2, ?3, ?4)
+            "#,
+        )
+        .bind(&report.id)
+        .bind(report.trigger.as_str())
+        .bind(report.started_at.to_rfc3339())
+        .bind(serde_json::to_string(report)?)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn list_self_tests(
+        &self,
+        limit: i32,
+    ) -> Result<Vec<ChannelTestReport>, NotificationError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT report FROM channel_self_tests
+            ORDER BY started_at DESC
+            LIMIT ?1
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| -> Result<ChannelTestReport, NotificationError> {
+                let report: String = row.try_get("report")?;
+                Ok(serde_json::from_str(&report)?)
+            })
+            .collect()
+    }
+
+    pub async fn last_self_test_at(
+        &self,
+        trigger: SelfTestTrigger,
+    ) -> Result<Option<DateTime<Utc>>, NotificationError> {
+        let row = sqlx::query(
+            "SELECT MAX(started_at) AS started_at FROM channel_self_tests WHERE triggered_by = ?1",
+        )
+        .bind(trigger.as_str())
+        .fetch_one(&self.pool)
+        .await?;
+
+        let started_at: Option<String> = row.try_get("started_at")?;
+        Ok(started_at
+            .and_then(|ts| DateTime::parse_from_rfc3339(&ts).ok())
+            .map(|ts| ts.with_timezone(&Utc)))
+    }
+
+    pub async fn get_self_test_schedule(&self) -> Result<SelfTestSchedule, NotificationError> {
+        let row = sqlx::query("SELECT value FROM delivery_log_settings WHERE key = ?1")
+            .bind(SELF_TEST_SCHEDULE_KEY)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        match row {
+            Some(row) => {
+                let value: String = row.try_get("value")?;
+                Ok(serde_json::from_str(&value)?)
+            }
+            None => Ok(SelfTestSchedule::default()),
+        }
+    }
+
+    pub async fn set_self_test_schedule(
+        &self,
+        schedule: &SelfTestSchedule,
+    ) -> Result<(), NotificationError> {
+        schedule.validate()?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO delivery_log_settings (key, value) VALUES (?1, ?2)
+            ON CONFLICT(key) DO UPDATE SET value = excluded.value
+            "#,
+        )
+        .bind(SELF_TEST_SCHEDULE_KEY)
+        .bind(serde_json::to_string(schedule)?)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
     }
 
     fn row_to_log(&self, row: sqlx::sqlite::SqliteRow) -> Result<DeliveryLog, NotificationError> {
@@ -99,6 +99,7 @@ impl DiscordClient {
         symbol: &str,
         current_price: f64,
         condition: &str,
+        link: Option<&str>,
     ) -> Result<(), NotificationError> {
         let role_mentions = config
             .role_mentions
@@ -118,27 +119,36 @@ impl DiscordClient {
             None
         };
 
+        let mut fields = vec![
+            EmbedField {
+                name: "Symbol".to_string(),
+                value: symbol.to_string(),
+                inline: true,
+            },
+            EmbedField {
+                name: "Price".to_string(),
+                value: format!("${:.4}", current_price),
+                inline: true,
+            },
+            EmbedField {
+                name: "Condition".to_string(),
+                value: condition.to_string(),
+                inline: false,
+            },
+        ];
+        if let Some(link) = link {
+            fields.push(EmbedField {
+                name: "Open in app".to_string(),
+                value: format!("`{}`", link),
+                inline: false,
+            });
+        }
+
         let embed = DiscordEmbed {
             title: "🚨 Price Alert Triggered".to_string(),
             description: format!("Alert **{}** has been triggered", alert_name),
             color: 0xFF0000,
-            fields: Some(vec![
-                EmbedField {
-                    name: "Symbol".to_string(),
-                    value: symbol.to_string(),
-                    inline: true,
-                },
-                EmbedField {
-                    name: "Price".to_string(),
-                    value: format!("${:.4}", current_price),
-                    inline: true,
-                },
-                EmbedField {
-                    name: "Condition".to_string(),
-                    value: condition.to_string(),
-                    inline: false,
-                },
-            ]),
+            fields: Some(fields),
             timestamp: chrono::Utc::now().to_rfc3339(),
         };
 
@@ -1,5 +1,6 @@
+use crate::config::DataPaths;
 use chrono::Utc;
-use lettre::message::{header, MultiPart, SinglePart};
+use lettre::message::{header, Attachment, MultiPart, SinglePart};
 use lettre::transport::smtp::authentication::Credentials;
 use lettre::{Message, SmtpTransport, Transport};
 use serde::{Deserialize, Serialize};
@@ -293,7 +294,27 @@ impl EmailManager {
             multipart = multipart.singlepart(html_part);
         }
 
-        let message = message_builder.multipart(multipart)?;
+        // Attachments wrap the text/HTML alternatives in a mixed multipart.
+        let message = match req.attachments.as_ref().filter(|a| !a.is_empty()) {
+            Some(attachments) => {
+                let mut mixed = MultiPart::mixed().multipart(multipart);
+                for attachment in attachments {
+                    let content_type =
+                        header::ContentType::parse(&attachment.mime_type).map_err(|e| {
+                            EmailError::Internal(format!(
+                                "Invalid attachment type {}: {}",
+                                attachment.mime_type, e
+                            ))
+                        })?;
+                    mixed = mixed.singlepart(
+                        Attachment::new(attachment.filename.clone())
+                            .body(attachment.content.clone(), content_type),
+                    );
+                }
+                message_builder.multipart(mixed)?
+            }
+            None => message_builder.multipart(multipart)?,
+        };
 
         // Send with retry logic
         let mailer = self.build_mailer(config)?;
@@ -553,10 +574,9 @@ impl EmailManager {
 
 fn email_db_path(app: &AppHandle) -> Result<PathBuf, EmailError> {
     let app_handle = app.clone();
-    let app_dir = app_handle
-        .path()
-        .app_data_dir()
-        .map_err(|e| EmailError::Internal(format!("Unable to resolve app data directory: {}", e)))?;
+    let app_dir = app_handle.app_data_root().map_err(|e| {
+        EmailError::Internal(format!("Unable to resolve app data directory: {}", e))
+    })?;
 
     std::fs::create_dir_all(&app_dir)
         .map_err(|e| EmailError::Internal(format!("Failed to create app data directory: {}", e)))?;
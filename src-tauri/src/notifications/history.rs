@@ -0,0 +1,453 @@
+use chrono::{TimeZone, Utc};
+use sqlx::{Pool, Row, Sqlite};
+use tauri::{AppHandle, Manager};
+use uuid::Uuid;
+
+use super::router::SharedNotificationRouter;
+use super::types::{
+    AlertPriority, ChannelOutcome, HistoryCategory, NewNotification, NotificationEntity,
+    NotificationError, NotificationFilter, NotificationPage, NotificationRecord,
+    NotificationRetention,
+};
+use crate::core::{JobSchedule, JobScheduler};
+
+const DEFAULT_PAGE_SIZE: u32 = 50;
+const MAX_PAGE_SIZE: u32 = 500;
+const RETENTION_KEY: &str = "retention";
+const PRUNE_INTERVAL_SECS: u64 = 60 * 60;
+const PRUNE_TIMEOUT_SECS: u64 = 5 * 60;
+
+/// Every notification the router handled, whether or not any chat
+/// integration delivered it.
+pub struct NotificationHistory {
+    pool: Pool<Sqlite>,
+}
+
+impl NotificationHistory {
+    pub fn new(pool: Pool<Sqlite>) -> Self {
+        Self { pool }
+    }
+
+    pub async fn initialize(&self) -> Result<(), NotificationError> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS notification_history (
+                id TEXT PRIMARY KEY,
+                category TEXT NOT NULL,
+                priority TEXT NOT NULL,
+                title TEXT NOT NULL,
+                message TEXT NOT NULL,
+                entity_kind TEXT,
+                entity_id TEXT,
+                channels TEXT NOT NULL,
+                read INTEGER NOT NULL DEFAULT 0,
+                created_at INTEGER NOT NULL,
+                read_at INTEGER
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_notification_history_created ON notification_history(created_at);
+            CREATE INDEX IF NOT EXISTS idx_notification_history_unread ON notification_history(read, priority);
+            CREATE TABLE IF NOT EXISTS notification_history_settings (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            );
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn record(
+        &self,
+        notification: &NewNotification,
+        channels: &[ChannelOutcome],
+    ) -> Result<String, NotificationError> {
+        let id = Uuid::new_v4().to_string();
+        let channels = serde_json::to_string(channels)?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO notification_history (
+                id, category, priority, title, message, entity_kind, entity_id,
+                channels, read, created_at
+            )
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, 0, ?9)
+            "#,
+        )
+        .bind(&id)
+        .bind(notification.category.as_str())
+        .bind(notification.priority.as_str())
+        .bind(&notification.title)
+        .bind(&notification.message)
+        .bind(notification.entity.as_ref().map(NotificationEntity::kind))
+        .bind(notification.entity.as_ref().map(NotificationEntity::id))
+        .bind(&channels)
+        .bind(Utc::now().timestamp_millis())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    /// Newest first. The cursor is the rowid of the last item of the previous
+    /// page, so inserts between calls don't shift pages.
+    pub async fn list(
+        &self,
+        filter: &NotificationFilter,
+        cursor: Option<&str>,
+    ) -> Result<NotificationPage, NotificationError> {
+        let before = cursor
+            .map(|cursor| {
+                cursor
+                    .parse::<i64>()
+                    .map_err(|_| NotificationError::Internal(format!("Invalid cursor: {}", cursor)))
+            })
+            .transpose()?;
+        let limit = filter
+            .limit
+            .unwrap_or(DEFAULT_PAGE_SIZE)
+            .clamp(1, MAX_PAGE_SIZE);
+
+        // One extra row tells whether another page exists
+        let rows = sqlx::query(
+            r#"
+            SELECT rowid AS seq, id, category, priority, title, message, entity_kind, entity_id,
+                   channels, read, created_at, read_at
+            FROM notification_history
+            WHERE (?1 IS NULL OR category = ?1)
+              AND (?2 IS NULL OR priority = ?2)
+              AND (?3 = 0 OR read = 0)
+              AND (?4 IS NULL OR rowid < ?4)
+            ORDER BY rowid DESC
+            LIMIT ?5
+            "#,
+        )
+        .bind(filter.category.as_ref().map(HistoryCategory::as_str))
+        .bind(filter.priority.as_ref().map(AlertPriority::as_str))
+        .bind(filter.unread_only)
+        .bind(before)
+        .bind(limit as i64 + 1)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let has_more = rows.len() > limit as usize;
+        let mut items = Vec::new();
+        let mut last = None;
+        for row in rows.into_iter().take(limit as usize) {
+            last = Some(row.try_get::<i64, _>("seq")?);
+            items.push(row_to_record(row)?);
+        }
+
+        Ok(NotificationPage {
+            items,
+            next_cursor: last.filter(|_| has_more).map(|seq| seq.to_string()),
+        })
+    }
+
+    pub async fn mark_read(&self, ids: &[String]) -> Result<usize, NotificationError> {
+        let now = Utc::now().timestamp_millis();
+        let mut tx = self.pool.begin().await?;
+        let mut updated = 0;
+
+        for id in ids {
+            let result = sqlx::query(
+                "UPDATE notification_history SET read = 1, read_at = ?1 WHERE id = ?2 AND read = 0",
+            )
+            .bind(now)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+            updated += result.rows_affected() as usize;
+        }
+
+        tx.commit().await?;
+        Ok(updated)
+    }
+
+    pub async fn mark_all_read(
+        &self,
+        category: Option<HistoryCategory>,
+    ) -> Result<usize, NotificationError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE notification_history
+            SET read = 1, read_at = ?1
+            WHERE read = 0 AND (?2 IS NULL OR category = ?2)
+            "#,
+        )
+        .bind(Utc::now().timestamp_millis())
+        .bind(category.as_ref().map(HistoryCategory::as_str))
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() as usize)
+    }
+
+    /// Deletes entries created before `older_than` (milliseconds since the
+    /// epoch), or everything when it is `None`.
+    pub async fn clear(&self, older_than: Option<i64>) -> Result<usize, NotificationError> {
+        let result =
+            sqlx::query("DELETE FROM notification_history WHERE ?1 IS NULL OR created_at < ?1")
+                .bind(older_than)
+                .execute(&self.pool)
+                .await?;
+
+        Ok(result.rows_affected() as usize)
+    }
+
+    pub async fn unread_count(&self, priority: AlertPriority) -> Result<u32, NotificationError> {
+        let row = sqlx::query(
+            "SELECT COUNT(*) AS count FROM notification_history WHERE read = 0 AND priority = ?1",
+        )
+        .bind(priority.as_str())
+        .fetch_one(&self.pool)
+        .await?;
+
+        let count: i64 = row.try_get("count")?;
+        Ok(count as u32)
+    }
+
+    pub async fn get_retention(&self) -> Result<NotificationRetention, NotificationError> {
+        let row = sqlx::query("SELECT value FROM notification_history_settings WHERE key = ?1")
+            .bind(RETENTION_KEY)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        match row {
+            Some(row) => {
+                let value: String = row.try_get("value")?;
+                Ok(serde_json::from_str(&value)?)
+            }
+            None => Ok(NotificationRetention::default()),
+        }
+    }
+
+    pub async fn set_retention(
+        &self,
+        retention: &NotificationRetention,
+    ) -> Result<(), NotificationError> {
+        if retention.max_age_days == 0 || retention.max_entries == 0 {
+            return Err(NotificationError::Internal(
+                "Retention limits must be greater than zero".to_string(),
+            ));
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO notification_history_settings (key, value) VALUES (?1, ?2)
+            ON CONFLICT(key) DO UPDATE SET value = excluded.value
+            "#,
+        )
+        .bind(RETENTION_KEY)
+        .bind(serde_json::to_string(retention)?)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Applies the configured retention: drops entries past the age limit,
+    /// then the oldest ones beyond the entry cap.
+    pub async fn prune(&self) -> Result<usize, NotificationError> {
+        let retention = self.get_retention().await?;
+        let cutoff =
+            (Utc::now() - chrono::Duration::days(retention.max_age_days as i64)).timestamp_millis();
+        let mut removed = self.clear(Some(cutoff)).await?;
+
+        let result = sqlx::query(
+            r#"
+            DELETE FROM notification_history
+            WHERE id NOT IN (
+                SELECT id FROM notification_history
+                ORDER BY rowid DESC
+                LIMIT ?1
+            )
+            "#,
+        )
+        .bind(retention.max_entries as i64)
+        .execute(&self.pool)
+        .await?;
+        removed += result.rows_affected() as usize;
+
+        Ok(removed)
+    }
+}
+
+/// Applies the retention policy at startup and then hourly. The first pass
+/// also restores the tray badge from unread notifications left last session.
+pub fn schedule_notification_history_pruner(
+    scheduler: &JobScheduler,
+    app: AppHandle,
+) -> Result<(), String> {
+    scheduler.register(
+        "notification_history_pruner",
+        JobSchedule::every_from_start(std::time::Duration::from_secs(PRUNE_INTERVAL_SECS)),
+        std::time::Duration::from_secs(PRUNE_TIMEOUT_SECS),
+        move || {
+            let app = app.clone();
+            async move {
+                let Some(router) = app.try_state::<SharedNotificationRouter>() else {
+                    return Ok(());
+                };
+                let removed = router
+                    .read()
+                    .await
+                    .prune_history()
+                    .await
+                    .map_err(|e| e.to_string())?;
+                if removed > 0 {
+                    eprintln!("Pruned {} notification history entries", removed);
+                }
+                Ok(())
+            }
+        },
+    )
+}
+
+fn millis_to_rfc3339(millis: i64) -> String {
+    Utc.timestamp_millis_opt(millis)
+        .single()
+        .unwrap_or_default()
+        .to_rfc3339()
+}
+
+fn row_to_record(row: sqlx::sqlite::SqliteRow) -> Result<NotificationRecord, NotificationError> {
+    let category: String = row.try_get("category")?;
+    let priority: String = row.try_get("priority")?;
+    let entity_kind: Option<String> = row.try_get("entity_kind")?;
+    let entity_id: Option<String> = row.try_get("entity_id")?;
+    let channels: String = row.try_get("channels")?;
+    let created_at: i64 = row.try_get("created_at")?;
+    let read_at: Option<i64> = row.try_get("read_at")?;
+
+    Ok(NotificationRecord {
+        id: row.try_get("id")?,
+        category: HistoryCategory::from_str(&category).ok_or_else(|| {
+            NotificationError::Internal(format!("Invalid category: {}", category))
+        })?,
+        priority: AlertPriority::from_str(&priority).ok_or_else(|| {
+            NotificationError::Internal(format!("Invalid priority: {}", priority))
+        })?,
+        title: row.try_get("title")?,
+        message: row.try_get("message")?,
+        entity: entity_kind
+            .zip(entity_id)
+            .and_then(|(kind, id)| NotificationEntity::from_parts(&kind, id)),
+        channels: serde_json::from_str(&channels)?,
+        read: row.try_get("read")?,
+        created_at: millis_to_rfc3339(created_at),
+        read_at: read_at.map(millis_to_rfc3339),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_pool;
+
+    async fn history() -> NotificationHistory {
+        let pool = test_pool().await;
+        let history = NotificationHistory::new(pool);
+        history.initialize().await.unwrap();
+        history
+    }
+
+    fn notification(priority: AlertPriority, alert_id: &str) -> NewNotification {
+        NewNotification {
+            category: HistoryCategory::PriceAlert,
+            priority,
+            title: format!("Alert {}", alert_id),
+            message: "SOL crossed $150".to_string(),
+            entity: Some(NotificationEntity::Alert(alert_id.to_string())),
+            link: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn pages_newest_first_and_tracks_unread_criticals() {
+        let history = history().await;
+        for i in 0..5 {
+            let priority = if i % 2 == 0 {
+                AlertPriority::Critical
+            } else {
+                AlertPriority::Low
+            };
+            history
+                .record(&notification(priority, &i.to_string()), &[])
+                .await
+                .unwrap();
+        }
+
+        let filter = NotificationFilter {
+            limit: Some(2),
+            ..Default::default()
+        };
+        let mut seen = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = history.list(&filter, cursor.as_deref()).await.unwrap();
+            seen.extend(page.items);
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+        assert_eq!(seen.len(), 5);
+        assert_eq!(
+            seen[0].entity,
+            Some(NotificationEntity::Alert("4".to_string()))
+        );
+
+        assert_eq!(
+            history.unread_count(AlertPriority::Critical).await.unwrap(),
+            3
+        );
+        history.mark_read(&[seen[0].id.clone()]).await.unwrap();
+        assert_eq!(
+            history.unread_count(AlertPriority::Critical).await.unwrap(),
+            2
+        );
+        history
+            .mark_all_read(Some(HistoryCategory::PriceAlert))
+            .await
+            .unwrap();
+        assert_eq!(
+            history.unread_count(AlertPriority::Critical).await.unwrap(),
+            0
+        );
+    }
+
+    #[tokio::test]
+    async fn prune_enforces_entry_cap() {
+        let history = history().await;
+        history
+            .set_retention(&NotificationRetention {
+                max_age_days: 30,
+                max_entries: 2,
+            })
+            .await
+            .unwrap();
+        for i in 0..4 {
+            history
+                .record(&notification(AlertPriority::High, &i.to_string()), &[])
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(history.prune().await.unwrap(), 2);
+        let page = history
+            .list(&NotificationFilter::default(), None)
+            .await
+            .unwrap();
+        assert_eq!(page.items.len(), 2);
+        assert!(page.next_cursor.is_none());
+    }
+}
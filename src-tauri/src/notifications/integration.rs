@@ -1,4 +1,5 @@
 use super::router::SharedNotificationRouter;
+use super::types::NewNotification;
 use crate::alerts::price_alerts::{AlertTriggerEvent, NotificationChannel};
 
 pub async fn send_alert_notifications(
@@ -15,11 +16,23 @@ pub async fn send_alert_notifications(
         )
     });
 
+    let router_guard = router.read().await;
+
+    // In-app only alerts still belong in the notification history
     if !should_send_chat {
+        let notification = NewNotification::price_alert(
+            &event.alert_id,
+            &event.alert_name,
+            &event.symbol,
+            event.current_price,
+            &event.conditions_met,
+        );
+        if let Err(e) = router_guard.record_notification(&notification, &[]).await {
+            eprintln!("Failed to record alert notification: {}", e);
+        }
         return;
     }
 
-    let router_guard = router.read().await;
     if let Err(e) = router_guard
         .send_alert_notification(
             &event.alert_id,
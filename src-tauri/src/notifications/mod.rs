@@ -6,9 +6,11 @@ pub use twitter::*;
 pub mod commands;
 pub mod delivery_log;
 pub mod discord;
+pub mod history;
 pub mod integration;
 pub mod rate_limiter;
 pub mod router;
+pub mod self_test;
 pub mod slack;
 pub mod telegram;
 pub mod types;
@@ -16,9 +18,11 @@ pub mod types;
 pub use commands::*;
 pub use delivery_log::*;
 pub use discord::*;
+pub use history::*;
 pub use integration::*;
 pub use rate_limiter::*;
 pub use router::*;
+pub use self_test::*;
 pub use slack::*;
 pub use telegram::*;
 pub use types::*;
@@ -1,18 +1,139 @@
+use chrono::Utc;
+use futures_util::future::{join_all, BoxFuture, FutureExt};
+use serde::{Deserialize, Serialize};
 use sqlx::{Pool, Row, Sqlite, SqlitePool};
+use std::collections::HashMap;
 use std::sync::Arc;
-use tauri::{AppHandle, Manager};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
 use super::delivery_log::DeliveryLogger;
 use super::discord::DiscordClient;
+use super::history::NotificationHistory;
 use super::rate_limiter::RateLimiter;
+use super::self_test::{
+    self, run_check, ChannelKind, ChannelTestReport, ChannelTestResult, ChannelTestStatus,
+    CheckOutcome, DegradedChannel, DegradedChannels, SelfTestTrigger, CHANNEL_TEST_TIMEOUT_SECS,
+    SELF_TEST_MESSAGE, SELF_TEST_TITLE,
+};
 use super::slack::SlackClient;
 use super::telegram::{format_alert_message, TelegramClient};
 use super::types::{
-    notifications_db_path, ChatIntegrationSettings, ChatServiceType, DeliveryStatus, DiscordConfig,
-    NotificationError, SlackConfig, TelegramConfig, TestMessageResult,
+    notifications_db_path, AlertPriority, ChannelOutcome, ChatIntegrationSettings, ChatServiceType,
+    DeliveryStatus, DiscordConfig, HistoryCategory, NewNotification, NotificationEntity,
+    NotificationError, NotificationFilter, NotificationPage, NotificationRetention, SlackConfig,
+    TelegramConfig, TestMessageResult,
 };
+use crate::core::{Outbox, OutboxDispatch, SharedOutbox};
+use crate::deep_link::DeepLinkIntent;
+use crate::mobile::PushPriority;
+use crate::tray::SharedTrayManager;
+use crate::webhooks::SharedWebhookManager;
+
+const NOTIFICATION_OUTBOX_KIND: &str = "chat_notification";
+/// Chat deliveries queued while offline are dropped after this long. The
+/// notification still reaches the in-app history.
+const NOTIFICATION_OUTBOX_MAX_AGE: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// A chat delivery held in the outbox while offline.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum QueuedDelivery {
+    Alert {
+        alert_id: String,
+        alert_name: String,
+        symbol: String,
+        current_price: f64,
+        condition: String,
+    },
+    Event {
+        notification: NewNotification,
+    },
+}
+
+impl QueuedDelivery {
+    fn notification(&self) -> NewNotification {
+        match self {
+            QueuedDelivery::Alert {
+                alert_id,
+                alert_name,
+                symbol,
+                current_price,
+                condition,
+            } => NewNotification::price_alert(
+                alert_id,
+                alert_name,
+                symbol,
+                *current_price,
+                condition,
+            ),
+            QueuedDelivery::Event { notification } => notification.clone(),
+        }
+    }
+
+    async fn dispatch(
+        self,
+        router: &NotificationRouter,
+        dispatch: OutboxDispatch,
+    ) -> Result<(), NotificationError> {
+        match (dispatch, self) {
+            (
+                OutboxDispatch::Submit,
+                QueuedDelivery::Alert {
+                    alert_id,
+                    alert_name,
+                    symbol,
+                    current_price,
+                    condition,
+                },
+            ) => {
+                router
+                    .send_alert_notification(
+                        &alert_id,
+                        &alert_name,
+                        &symbol,
+                        current_price,
+                        &condition,
+                    )
+                    .await
+            }
+            (OutboxDispatch::Submit, QueuedDelivery::Event { notification }) => {
+                router.send_event_notification(&notification).await
+            }
+            (OutboxDispatch::Abandon(_), delivery) => router
+                .record_notification(&delivery.notification(), &[])
+                .await
+                .map(|_| ()),
+        }
+    }
+}
+
+/// Lets the outbox submit chat deliveries queued while offline.
+pub fn register_outbox(app: &AppHandle, outbox: &Outbox) {
+    let app = app.clone();
+    outbox.register(
+        NOTIFICATION_OUTBOX_KIND,
+        Some(NOTIFICATION_OUTBOX_MAX_AGE),
+        move |dispatch, payload| {
+            let app = app.clone();
+            async move {
+                let delivery: QueuedDelivery = serde_json::from_value(payload)
+                    .map_err(|e| format!("Invalid queued notification: {}", e))?;
+                let router = app
+                    .try_state::<SharedNotificationRouter>()
+                    .map(|router| router.inner().clone())
+                    .ok_or_else(|| "Notification router not initialized".to_string())?;
+                let router = router.read().await;
+                delivery
+                    .dispatch(&router, dispatch)
+                    .await
+                    .map_err(|e| e.to_string())
+            }
+        },
+    );
+}
 
 pub struct NotificationRouter {
     pool: Pool<Sqlite>,
@@ -21,6 +142,9 @@ pub struct NotificationRouter {
     discord_client: DiscordClient,
     rate_limiter: Arc<RwLock<RateLimiter>>,
     delivery_logger: DeliveryLogger,
+    history: NotificationHistory,
+    degraded: DegradedChannels,
+    app: AppHandle,
 }
 
 pub type SharedNotificationRouter = Arc<RwLock<NotificationRouter>>;
@@ -34,6 +158,9 @@ impl NotificationRouter {
         let delivery_logger = DeliveryLogger::new(pool.clone());
         delivery_logger.initialize().await?;
 
+        let history = NotificationHistory::new(pool.clone());
+        history.initialize().await?;
+
         let router = Self {
             pool,
             telegram_client: TelegramClient::new(),
@@ -41,6 +168,9 @@ impl NotificationRouter {
             discord_client: DiscordClient::new(),
             rate_limiter: Arc::new(RwLock::new(RateLimiter::new())),
             delivery_logger,
+            history,
+            degraded: DegradedChannels::default(),
+            app: app.clone(),
         };
 
         router.initialize().await?;
@@ -522,6 +652,17 @@ impl NotificationRouter {
         condition: &str,
     ) -> Result<(), NotificationError> {
         let settings = self.get_settings().await?;
+        if self.queue_while_offline(&settings, || QueuedDelivery::Alert {
+            alert_id: alert_id.to_string(),
+            alert_name: alert_name.to_string(),
+            symbol: symbol.to_string(),
+            current_price,
+            condition: condition.to_string(),
+        }) {
+            return Ok(());
+        }
+        let degraded_before = self.has_degraded_integration(&settings);
+        let mut outcomes = Vec::new();
 
         for config in settings.telegram.iter().filter(|c| c.enabled) {
             let result = self
@@ -535,16 +676,18 @@ impl NotificationRouter {
                 )
                 .await;
 
-            self.log_delivery(
-                ChatServiceType::Telegram,
-                &config.id,
-                &config.name,
-                Some(alert_id),
-                Some(alert_name),
-                "Alert notification",
-                &result,
-            )
-            .await;
+            let outcome = self
+                .log_delivery(
+                    ChatServiceType::Telegram,
+                    &config.id,
+                    &config.name,
+                    Some(alert_id),
+                    Some(alert_name),
+                    "Alert notification",
+                    &result,
+                )
+                .await;
+            outcomes.push(outcome);
         }
 
         for config in settings.slack.iter().filter(|c| c.enabled) {
@@ -559,16 +702,18 @@ impl NotificationRouter {
                 )
                 .await;
 
-            self.log_delivery(
-                ChatServiceType::Slack,
-                &config.id,
-                &config.name,
-                Some(alert_id),
-                Some(alert_name),
-                "Alert notification",
-                &result,
-            )
-            .await;
+            let outcome = self
+                .log_delivery(
+                    ChatServiceType::Slack,
+                    &config.id,
+                    &config.name,
+                    Some(alert_id),
+                    Some(alert_name),
+                    "Alert notification",
+                    &result,
+                )
+                .await;
+            outcomes.push(outcome);
         }
 
         for config in settings.discord.iter().filter(|c| c.enabled) {
@@ -583,25 +728,703 @@ impl NotificationRouter {
                 )
                 .await;
 
+            let outcome = self
+                .log_delivery(
+                    ChatServiceType::Discord,
+                    &config.id,
+                    &config.name,
+                    Some(alert_id),
+                    Some(alert_name),
+                    "Alert notification",
+                    &result,
+                )
+                .await;
+            outcomes.push(outcome);
+        }
+
+        let notification =
+            NewNotification::price_alert(alert_id, alert_name, symbol, current_price, condition);
+        self.track_delivery_health(&notification, &outcomes, degraded_before)
+            .await;
+        self.record_notification(&notification, &outcomes).await?;
+        Ok(())
+    }
+
+    /// Sends a plain-text notification for events that are not price alerts
+    /// to every enabled chat integration.
+    pub async fn send_event_notification(
+        &self,
+        notification: &NewNotification,
+    ) -> Result<(), NotificationError> {
+        let settings = self.get_settings().await?;
+        if self.queue_while_offline(&settings, || QueuedDelivery::Event {
+            notification: notification.clone(),
+        }) {
+            return Ok(());
+        }
+        let event_id = notification.entity.as_ref().map(NotificationEntity::id);
+        let title = notification.title.as_str();
+        let text = match &notification.link {
+            Some(link) => format!(
+                "{}\n\n{}\n\nOpen in app: {}",
+                title, notification.message, link
+            ),
+            None => format!("{}\n\n{}", title, notification.message),
+        };
+        let degraded_before = self.has_degraded_integration(&settings);
+        let mut outcomes = Vec::new();
+
+        for config in settings.telegram.iter().filter(|c| c.enabled) {
+            let result = match self.acquire(ChatServiceType::Telegram, &config.id).await {
+                Ok(()) => {
+                    let result = self
+                        .telegram_client
+                        .send_message(config, &text, false)
+                        .await;
+                    self.register_result(ChatServiceType::Telegram, &config.id, result)
+                        .await
+                }
+                Err(e) => Err(e),
+            };
+
+            let outcome = self
+                .log_delivery(
+                    ChatServiceType::Telegram,
+                    &config.id,
+                    &config.name,
+                    event_id,
+                    Some(title),
+                    "Event notification",
+                    &result,
+                )
+                .await;
+            outcomes.push(outcome);
+        }
+
+        for config in settings.slack.iter().filter(|c| c.enabled) {
+            let result = match self.acquire(ChatServiceType::Slack, &config.id).await {
+                Ok(()) => {
+                    let result = self.slack_client.send_message(config, &text).await;
+                    self.register_result(ChatServiceType::Slack, &config.id, result)
+                        .await
+                }
+                Err(e) => Err(e),
+            };
+
+            let outcome = self
+                .log_delivery(
+                    ChatServiceType::Slack,
+                    &config.id,
+                    &config.name,
+                    event_id,
+                    Some(title),
+                    "Event notification",
+                    &result,
+                )
+                .await;
+            outcomes.push(outcome);
+        }
+
+        for config in settings.discord.iter().filter(|c| c.enabled) {
+            let result = match self.acquire(ChatServiceType::Discord, &config.id).await {
+                Ok(()) => {
+                    let result = self.discord_client.send_message(config, &text, false).await;
+                    self.register_result(ChatServiceType::Discord, &config.id, result)
+                        .await
+                }
+                Err(e) => Err(e),
+            };
+
+            let outcome = self
+                .log_delivery(
+                    ChatServiceType::Discord,
+                    &config.id,
+                    &config.name,
+                    event_id,
+                    Some(title),
+                    "Event notification",
+                    &result,
+                )
+                .await;
+            outcomes.push(outcome);
+        }
+
+        self.track_delivery_health(notification, &outcomes, degraded_before)
+            .await;
+        self.record_notification(notification, &outcomes).await?;
+        Ok(())
+    }
+
+    /// Sends a synthetic notification through every configured channel at
+    /// once, each under its own timeout. Failing channels are marked degraded
+    /// and raise an in-app warning; the report is kept with the delivery logs.
+    pub async fn test_all_channels(
+        &self,
+        trigger: SelfTestTrigger,
+    ) -> Result<ChannelTestReport, NotificationError> {
+        let started_at = Utc::now();
+        let timeout = Duration::from_secs(CHANNEL_TEST_TIMEOUT_SECS);
+        let settings = self.get_settings().await?;
+        let text = format!("{}\n\n{}", SELF_TEST_TITLE, SELF_TEST_MESSAGE);
+        let devices = self_test::registered_devices(&self.app).await;
+        let webhooks = match self.app.try_state::<SharedWebhookManager>() {
+            Some(manager) => {
+                let manager = manager.inner().clone();
+                let configs = manager.read().await.list_webhooks().await;
+                Some((manager, configs))
+            }
+            None => None,
+        };
+
+        let mut results = Vec::new();
+        let mut checks: Vec<BoxFuture<'_, ChannelTestResult>> = Vec::new();
+
+        checks.push(
+            run_check(
+                ChannelKind::InApp,
+                None,
+                "In-app".to_string(),
+                timeout,
+                async {
+                    let notification = NewNotification {
+                        category: HistoryCategory::System,
+                        priority: AlertPriority::Low,
+                        title: SELF_TEST_TITLE.to_string(),
+                        message: SELF_TEST_MESSAGE.to_string(),
+                        entity: None,
+                        link: None,
+                    };
+                    self.record_notification(&notification, &[])
+                        .await
+                        .map(|_| CheckOutcome::Delivered)
+                        .map_err(|e| e.to_string())
+                },
+            )
+            .boxed(),
+        );
+
+        if devices.is_empty() {
+            results.push(ChannelTestResult::skipped(
+                ChannelKind::Push,
+                None,
+                "Push",
+                "No devices registered",
+            ));
+        }
+        for device in &devices {
+            checks.push(
+                run_check(
+                    ChannelKind::Push,
+                    Some(device.device_id.clone()),
+                    device.device_name.clone(),
+                    timeout,
+                    async {
+                        self_test::queue_push(
+                            &self.app,
+                            device,
+                            PushPriority::Normal,
+                            SELF_TEST_TITLE,
+                            SELF_TEST_MESSAGE,
+                            None,
+                        )
+                        .await
+                        .map(|_| CheckOutcome::Delivered)
+                    },
+                )
+                .boxed(),
+            );
+        }
+
+        if settings.telegram.is_empty() {
+            results.push(ChannelTestResult::skipped(
+                ChannelKind::Telegram,
+                None,
+                "Telegram",
+                "Not configured",
+            ));
+        }
+        for config in &settings.telegram {
+            if !config.enabled {
+                results.push(ChannelTestResult::skipped(
+                    ChannelKind::Telegram,
+                    Some(config.id.clone()),
+                    &config.name,
+                    "Integration disabled",
+                ));
+                continue;
+            }
+            checks.push(
+                run_check(
+                    ChannelKind::Telegram,
+                    Some(config.id.clone()),
+                    config.name.clone(),
+                    timeout,
+                    async {
+                        self.telegram_client
+                            .send_message(config, &text, false)
+                            .await
+                            .map(|_| CheckOutcome::Delivered)
+                            .map_err(|e| e.to_string())
+                    },
+                )
+                .boxed(),
+            );
+        }
+
+        if settings.slack.is_empty() {
+            results.push(ChannelTestResult::skipped(
+                ChannelKind::Slack,
+                None,
+                "Slack",
+                "Not configured",
+            ));
+        }
+        for config in &settings.slack {
+            if !config.enabled {
+                results.push(ChannelTestResult::skipped(
+                    ChannelKind::Slack,
+                    Some(config.id.clone()),
+                    &config.name,
+                    "Integration disabled",
+                ));
+                continue;
+            }
+            checks.push(
+                run_check(
+                    ChannelKind::Slack,
+                    Some(config.id.clone()),
+                    config.name.clone(),
+                    timeout,
+                    async {
+                        self.slack_client
+                            .send_message(config, &text)
+                            .await
+                            .map(|_| CheckOutcome::Delivered)
+                            .map_err(|e| e.to_string())
+                    },
+                )
+                .boxed(),
+            );
+        }
+
+        if settings.discord.is_empty() {
+            results.push(ChannelTestResult::skipped(
+                ChannelKind::Discord,
+                None,
+                "Discord",
+                "Not configured",
+            ));
+        }
+        for config in &settings.discord {
+            if !config.enabled {
+                results.push(ChannelTestResult::skipped(
+                    ChannelKind::Discord,
+                    Some(config.id.clone()),
+                    &config.name,
+                    "Integration disabled",
+                ));
+                continue;
+            }
+            checks.push(
+                run_check(
+                    ChannelKind::Discord,
+                    Some(config.id.clone()),
+                    config.name.clone(),
+                    timeout,
+                    async {
+                        self.discord_client
+                            .send_message(config, &text, false)
+                            .await
+                            .map(|_| CheckOutcome::Delivered)
+                            .map_err(|e| e.to_string())
+                    },
+                )
+                .boxed(),
+            );
+        }
+
+        checks.push(
+            run_check(
+                ChannelKind::Email,
+                None,
+                "Email".to_string(),
+                timeout,
+                async {
+                    match self_test::send_email_to_self(
+                        &self.app,
+                        SELF_TEST_TITLE,
+                        SELF_TEST_MESSAGE,
+                    )
+                    .await
+                    {
+                        Ok(true) => Ok(CheckOutcome::Delivered),
+                        Ok(false) => Ok(CheckOutcome::Skipped("Not configured".to_string())),
+                        Err(e) => Err(e),
+                    }
+                },
+            )
+            .boxed(),
+        );
+
+        match &webhooks {
+            None => results.push(ChannelTestResult::skipped(
+                ChannelKind::Webhook,
+                None,
+                "Webhooks",
+                "Webhooks are not available",
+            )),
+            Some((_, Err(e))) => results.push(ChannelTestResult {
+                channel: ChannelKind::Webhook,
+                target_id: None,
+                name: "Webhooks".to_string(),
+                status: ChannelTestStatus::Failed,
+                detail: Some(format!("Failed to load webhooks: {}", e)),
+                latency_ms: None,
+            }),
+            Some((_, Ok(configs))) if configs.is_empty() => {
+                results.push(ChannelTestResult::skipped(
+                    ChannelKind::Webhook,
+                    None,
+                    "Webhooks",
+                    "Not configured",
+                ))
+            }
+            Some((manager, Ok(configs))) => {
+                for config in configs {
+                    if !config.enabled {
+                        results.push(ChannelTestResult::skipped(
+                            ChannelKind::Webhook,
+                            Some(config.id.clone()),
+                            &config.name,
+                            "Webhook disabled",
+                        ));
+                        continue;
+                    }
+                    checks.push(
+                        run_check(
+                            ChannelKind::Webhook,
+                            Some(config.id.clone()),
+                            config.name.clone(),
+                            timeout,
+                            async {
+                                let tested = manager
+                                    .read()
+                                    .await
+                                    .test_webhook(&config.id, HashMap::new())
+                                    .await;
+                                match tested {
+                                    Ok(result) if result.success => Ok(CheckOutcome::Delivered),
+                                    Ok(result) => Err(match result.response_code {
+                                        Some(code) => format!("{} (HTTP {})", result.message, code),
+                                        None => result.message,
+                                    }),
+                                    Err(e) => Err(e.to_string()),
+                                }
+                            },
+                        )
+                        .boxed(),
+                    );
+                }
+            }
+        }
+
+        results.extend(join_all(checks).await);
+        let report = ChannelTestReport::new(trigger, started_at, Utc::now(), results);
+
+        for result in &report.results {
+            let Some(service_type) = result.channel.chat_service() else {
+                continue;
+            };
+            let delivery = match (&result.status, &result.detail) {
+                (ChannelTestStatus::Delivered, _) => Ok(()),
+                (ChannelTestStatus::Failed, detail) => Err(NotificationError::Internal(
+                    detail.clone().unwrap_or_default(),
+                )),
+                (ChannelTestStatus::Skipped, _) => continue,
+            };
             self.log_delivery(
-                ChatServiceType::Discord,
-                &config.id,
-                &config.name,
-                Some(alert_id),
-                Some(alert_name),
-                "Alert notification",
-                &result,
+                service_type,
+                result.target_id.as_deref().unwrap_or_default(),
+                &result.name,
+                None,
+                Some(SELF_TEST_TITLE),
+                "Channel self-test",
+                &delivery,
             )
             .await;
         }
 
+        self.degraded.apply(&report.results);
+        self.delivery_logger.record_self_test(&report).await?;
+
+        if report.failed > 0 {
+            let failed: Vec<&str> = report.failures().map(|r| r.name.as_str()).collect();
+            let warning = NewNotification {
+                category: HistoryCategory::System,
+                priority: AlertPriority::High,
+                title: "Notification channels failing".to_string(),
+                message: format!(
+                    "{} failed the {} self-test. Critical alerts will also go out through a \
+                     fallback channel until they recover.",
+                    failed.join(", "),
+                    trigger.as_str()
+                ),
+                entity: None,
+                link: None,
+            };
+            if let Err(e) = self.record_notification(&warning, &[]).await {
+                eprintln!("Failed to record self-test warning: {}", e);
+            }
+        }
+
+        let _ = self.app.emit("notification_self_test_completed", &report);
+        Ok(report)
+    }
+
+    pub fn degraded_channels(&self) -> Vec<DegradedChannel> {
+        self.degraded.list()
+    }
+
+    /// Holds the delivery in the outbox when the app is offline and a chat
+    /// integration is enabled, returning whether it was queued. In-app only
+    /// notifications need no network and are never queued.
+    fn queue_while_offline(
+        &self,
+        settings: &ChatIntegrationSettings,
+        delivery: impl FnOnce() -> QueuedDelivery,
+    ) -> bool {
+        let Some(outbox) = self.app.try_state::<SharedOutbox>() else {
+            return false;
+        };
+        let has_chat = settings.telegram.iter().any(|c| c.enabled)
+            || settings.slack.iter().any(|c| c.enabled)
+            || settings.discord.iter().any(|c| c.enabled);
+        if outbox.is_online() || !has_chat {
+            return false;
+        }
+
+        let delivery = delivery();
+        let description = format!("Chat notification: {}", delivery.notification().title);
+        match outbox.enqueue(
+            NOTIFICATION_OUTBOX_KIND,
+            &format!("notification:{}", Uuid::new_v4()),
+            description,
+            &delivery,
+            Utc::now(),
+        ) {
+            Ok(_) => true,
+            Err(e) => {
+                eprintln!("Failed to queue chat notification: {}", e);
+                false
+            }
+        }
+    }
+
+    fn has_degraded_integration(&self, settings: &ChatIntegrationSettings) -> bool {
+        let telegram = settings
+            .telegram
+            .iter()
+            .filter(|c| c.enabled)
+            .map(|c| (ChannelKind::Telegram, &c.id));
+        let slack = settings
+            .slack
+            .iter()
+            .filter(|c| c.enabled)
+            .map(|c| (ChannelKind::Slack, &c.id));
+        let discord = settings
+            .discord
+            .iter()
+            .filter(|c| c.enabled)
+            .map(|c| (ChannelKind::Discord, &c.id));
+
+        telegram
+            .chain(slack)
+            .chain(discord)
+            .any(|(channel, id)| self.degraded.is_degraded(channel, Some(id.as_str())))
+    }
+
+    /// Real deliveries update channel health just like self-tests do. A
+    /// critical notification that hit a degraded or failing integration is
+    /// also sent through a fallback channel.
+    async fn track_delivery_health(
+        &self,
+        notification: &NewNotification,
+        outcomes: &[ChannelOutcome],
+        degraded_before: bool,
+    ) {
+        let mut failed = false;
+        for outcome in outcomes {
+            let channel = ChannelKind::from(&outcome.service_type);
+            match outcome.status {
+                DeliveryStatus::Sent => self.degraded.clear(channel, Some(&outcome.config_id)),
+                DeliveryStatus::Failed => {
+                    failed = true;
+                    self.degraded.mark(
+                        channel,
+                        Some(&outcome.config_id),
+                        &outcome.config_name,
+                        outcome.error.as_deref().unwrap_or("Delivery failed"),
+                    );
+                }
+                DeliveryStatus::Pending | DeliveryStatus::RateLimited => {}
+            }
+        }
+
+        if notification.priority == AlertPriority::Critical && (degraded_before || failed) {
+            self.send_fallback(notification).await;
+        }
+    }
+
+    /// Email first, then a critical push to every registered device.
+    async fn send_fallback(&self, notification: &NewNotification) {
+        if !self.degraded.is_degraded(ChannelKind::Email, None) {
+            let body = match &notification.link {
+                Some(link) => format!("{}\n\nOpen in app: {}", notification.message, link),
+                None => notification.message.clone(),
+            };
+            match self_test::send_email_to_self(&self.app, &notification.title, &body).await {
+                Ok(true) => return,
+                Ok(false) => {}
+                Err(e) => {
+                    eprintln!("Fallback email failed: {}", e);
+                    self.degraded.mark(ChannelKind::Email, None, "Email", &e);
+                }
+            }
+        }
+
+        let devices = self_test::registered_devices(&self.app).await;
+        if devices.is_empty() {
+            eprintln!(
+                "No fallback channel available for critical notification '{}'",
+                notification.title
+            );
+            return;
+        }
+        for device in &devices {
+            if let Err(e) = self_test::queue_push(
+                &self.app,
+                device,
+                PushPriority::Critical,
+                &notification.title,
+                &notification.message,
+                notification.link.as_deref(),
+            )
+            .await
+            {
+                eprintln!("Fallback push to {} failed: {}", device.device_name, e);
+            }
+        }
+    }
+
+    /// Stores a routed notification in the history, together with how each
+    /// chat integration handled it. Notifications shown only in the app are
+    /// recorded with no channel outcomes.
+    pub async fn record_notification(
+        &self,
+        notification: &NewNotification,
+        outcomes: &[ChannelOutcome],
+    ) -> Result<String, NotificationError> {
+        let id = self.history.record(notification, outcomes).await?;
+        self.history_changed().await;
+        crate::voice::announce_notification(&self.app, notification).await;
+        Ok(id)
+    }
+
+    pub async fn list_history(
+        &self,
+        filter: &NotificationFilter,
+        cursor: Option<&str>,
+    ) -> Result<NotificationPage, NotificationError> {
+        self.history.list(filter, cursor).await
+    }
+
+    pub async fn mark_read(&self, ids: &[String]) -> Result<usize, NotificationError> {
+        let updated = self.history.mark_read(ids).await?;
+        self.history_changed().await;
+        Ok(updated)
+    }
+
+    pub async fn mark_all_read(
+        &self,
+        category: Option<HistoryCategory>,
+    ) -> Result<usize, NotificationError> {
+        let updated = self.history.mark_all_read(category).await?;
+        self.history_changed().await;
+        Ok(updated)
+    }
+
+    pub async fn clear_history(&self, older_than: Option<i64>) -> Result<usize, NotificationError> {
+        let removed = self.history.clear(older_than).await?;
+        self.history_changed().await;
+        Ok(removed)
+    }
+
+    pub async fn get_history_retention(&self) -> Result<NotificationRetention, NotificationError> {
+        self.history.get_retention().await
+    }
+
+    pub async fn set_history_retention(
+        &self,
+        retention: &NotificationRetention,
+    ) -> Result<(), NotificationError> {
+        self.history.set_retention(retention).await?;
+        self.prune_history().await?;
         Ok(())
     }
 
+    pub async fn prune_history(&self) -> Result<usize, NotificationError> {
+        let removed = self.history.prune().await?;
+        self.history_changed().await;
+        Ok(removed)
+    }
+
+    /// The tray badge counts unread critical notifications, so it is
+    /// recomputed whenever the history changes.
+    async fn history_changed(&self) {
+        let unread = match self.history.unread_count(AlertPriority::Critical).await {
+            Ok(count) => count,
+            Err(e) => {
+                eprintln!("Failed to count unread notifications: {}", e);
+                return;
+            }
+        };
+
+        if let Some(tray) = self.app.try_state::<SharedTrayManager>() {
+            if let Err(e) = tray.update_badge(&self.app, unread) {
+                eprintln!("Failed to update tray badge: {}", e);
+            }
+        }
+        let _ = self.app.emit("notification_history_updated", unread);
+    }
+
+    async fn acquire(
+        &self,
+        service_type: ChatServiceType,
+        config_id: &str,
+    ) -> Result<(), NotificationError> {
+        let rate_limiter = self.rate_limiter.read().await;
+        rate_limiter.acquire(&service_type, config_id).await
+    }
+
+    async fn register_result(
+        &self,
+        service_type: ChatServiceType,
+        config_id: &str,
+        result: Result<(), NotificationError>,
+    ) -> Result<(), NotificationError> {
+        if result.is_err() {
+            let rate_limiter = self.rate_limiter.read().await;
+            rate_limiter
+                .register_failure(&service_type, config_id)
+                .await;
+        }
+        result
+    }
+
     async fn send_telegram_alert(
         &self,
         config: &TelegramConfig,
-        _alert_id: &str,
+        alert_id: &str,
         alert_name: &str,
         symbol: &str,
         current_price: f64,
@@ -613,7 +1436,15 @@ impl NotificationRouter {
             .await?;
         drop(rate_limiter);
 
-        let message = format_alert_message(alert_name, symbol, current_price, condition, true);
+        let link = alert_link(alert_id);
+        let message = format_alert_message(
+            alert_name,
+            symbol,
+            current_price,
+            condition,
+            Some(&link),
+            true,
+        );
 
         match self
             .telegram_client
@@ -634,7 +1465,7 @@ impl NotificationRouter {
     async fn send_slack_alert(
         &self,
         config: &SlackConfig,
-        _alert_id: &str,
+        alert_id: &str,
         alert_name: &str,
         symbol: &str,
         current_price: f64,
@@ -651,12 +1482,14 @@ impl NotificationRouter {
             *Alert:* {}\n\
             *Symbol:* {}\n\
             *Price:* ${:.4}\n\
-            *Condition:* {}\n\n\
+            *Condition:* {}\n\
+            *Open in app:* `{}`\n\n\
             _Triggered at: {}_",
             alert_name,
             symbol,
             current_price,
             condition,
+            alert_link(alert_id),
             chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC")
         );
 
@@ -675,7 +1508,7 @@ impl NotificationRouter {
     async fn send_discord_alert(
         &self,
         config: &DiscordConfig,
-        _alert_id: &str,
+        alert_id: &str,
         alert_name: &str,
         symbol: &str,
         current_price: f64,
@@ -689,7 +1522,14 @@ impl NotificationRouter {
 
         match self
             .discord_client
-            .send_alert_embed(config, alert_name, symbol, current_price, condition)
+            .send_alert_embed(
+                config,
+                alert_name,
+                symbol,
+                current_price,
+                condition,
+                Some(&alert_link(alert_id)),
+            )
             .await
         {
             Ok(_) => Ok(()),
@@ -712,34 +1552,40 @@ impl NotificationRouter {
         alert_name: Option<&str>,
         message: &str,
         result: &Result<(), NotificationError>,
-    ) {
+    ) -> ChannelOutcome {
         let (status, error) = match result {
             Ok(_) => (DeliveryStatus::Sent, None),
             Err(NotificationError::RateLimited(e)) => {
-                (DeliveryStatus::RateLimited, Some(e.as_str()))
-            }
-            Err(e) => {
-                (DeliveryStatus::Failed, None)
+                (DeliveryStatus::RateLimited, Some(e.clone()))
             }
+            Err(e) => (DeliveryStatus::Failed, Some(e.to_string())),
         };
 
         if let Err(e) = self
             .delivery_logger
             .log(
-                service_type,
+                service_type.clone(),
                 config_id,
                 config_name,
                 alert_id,
                 alert_name,
                 message,
-                status,
-                error,
+                status.clone(),
+                error.as_deref(),
                 0,
             )
             .await
         {
             eprintln!("Failed to log delivery: {}", e);
         }
+
+        ChannelOutcome {
+            service_type,
+            config_id: config_id.to_string(),
+            config_name: config_name.to_string(),
+            status,
+            error,
+        }
     }
 
     pub fn get_delivery_logger(&self) -> &DeliveryLogger {
@@ -750,3 +1596,10 @@ impl NotificationRouter {
         Arc::clone(&self.rate_limiter)
     }
 }
+
+fn alert_link(alert_id: &str) -> String {
+    DeepLinkIntent::Alert {
+        alert_id: alert_id.to_string(),
+    }
+    .to_url()
+}
@@ -0,0 +1,570 @@
+use chrono::{DateTime, Datelike, Duration, NaiveTime, TimeZone, Utc};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::time::Instant;
+use tauri::{AppHandle, Manager};
+use uuid::Uuid;
+
+use super::email::{EmailError, EmailManager, SendEmailRequest};
+use super::router::SharedNotificationRouter;
+use super::types::{ChatServiceType, NotificationError};
+use crate::mobile::{
+    MobileDevice, NotificationCategory, PushPriority, QueuePushRequest, SharedMobileAuthManager,
+    SharedPushNotificationManager,
+};
+use crate::security::keystore::Keystore;
+
+/// Each channel gets this long to deliver the test before it counts as failed.
+pub const CHANNEL_TEST_TIMEOUT_SECS: u64 = 20;
+const SCHEDULE_CHECK_INTERVAL_SECS: u64 = 15 * 60;
+
+pub const SELF_TEST_TITLE: &str = "Notification test";
+pub const SELF_TEST_MESSAGE: &str =
+    "This is a test notification from Eclipse Market Pro. No action is needed.";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ChannelKind {
+    InApp,
+    Push,
+    Telegram,
+    Slack,
+    Discord,
+    Email,
+    Webhook,
+}
+
+impl ChannelKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChannelKind::InApp => "in_app",
+            ChannelKind::Push => "push",
+            ChannelKind::Telegram => "telegram",
+            ChannelKind::Slack => "slack",
+            ChannelKind::Discord => "discord",
+            ChannelKind::Email => "email",
+            ChannelKind::Webhook => "webhook",
+        }
+    }
+
+    pub fn chat_service(&self) -> Option<ChatServiceType> {
+        match self {
+            ChannelKind::Telegram => Some(ChatServiceType::Telegram),
+            ChannelKind::Slack => Some(ChatServiceType::Slack),
+            ChannelKind::Discord => Some(ChatServiceType::Discord),
+            _ => None,
+        }
+    }
+}
+
+impl From<&ChatServiceType> for ChannelKind {
+    fn from(service_type: &ChatServiceType) -> Self {
+        match service_type {
+            ChatServiceType::Telegram => ChannelKind::Telegram,
+            ChatServiceType::Slack => ChannelKind::Slack,
+            ChatServiceType::Discord => ChannelKind::Discord,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ChannelTestStatus {
+    Delivered,
+    Failed,
+    /// Nothing is configured for the channel, or the integration is disabled.
+    Skipped,
+}
+
+/// How one channel, or one configured target of it, handled the test.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelTestResult {
+    pub channel: ChannelKind,
+    /// Integration, device or webhook id for channels with several targets.
+    pub target_id: Option<String>,
+    pub name: String,
+    pub status: ChannelTestStatus,
+    /// Error for failures, reason for skips.
+    pub detail: Option<String>,
+    pub latency_ms: Option<u64>,
+}
+
+impl ChannelTestResult {
+    pub fn skipped(
+        channel: ChannelKind,
+        target_id: Option<String>,
+        name: &str,
+        reason: &str,
+    ) -> Self {
+        Self {
+            channel,
+            target_id,
+            name: name.to_string(),
+            status: ChannelTestStatus::Skipped,
+            detail: Some(reason.to_string()),
+            latency_ms: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SelfTestTrigger {
+    Manual,
+    Scheduled,
+}
+
+impl SelfTestTrigger {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SelfTestTrigger::Manual => "manual",
+            SelfTestTrigger::Scheduled => "scheduled",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelTestReport {
+    pub id: String,
+    pub trigger: SelfTestTrigger,
+    pub started_at: DateTime<Utc>,
+    pub completed_at: DateTime<Utc>,
+    pub delivered: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    pub results: Vec<ChannelTestResult>,
+}
+
+impl ChannelTestReport {
+    pub fn new(
+        trigger: SelfTestTrigger,
+        started_at: DateTime<Utc>,
+        completed_at: DateTime<Utc>,
+        mut results: Vec<ChannelTestResult>,
+    ) -> Self {
+        results.sort_by(|a, b| a.channel.cmp(&b.channel).then_with(|| a.name.cmp(&b.name)));
+        let count = |status| results.iter().filter(|r| r.status == status).count();
+
+        Self {
+            id: Uuid::new_v4().to_string(),
+            trigger,
+            started_at,
+            completed_at,
+            delivered: count(ChannelTestStatus::Delivered),
+            failed: count(ChannelTestStatus::Failed),
+            skipped: count(ChannelTestStatus::Skipped),
+            results,
+        }
+    }
+
+    pub fn failures(&self) -> impl Iterator<Item = &ChannelTestResult> {
+        self.results
+            .iter()
+            .filter(|r| r.status == ChannelTestStatus::Failed)
+    }
+}
+
+/// Weekly self-test slot, in UTC.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SelfTestSchedule {
+    pub enabled: bool,
+    /// 0 = Sunday.
+    pub weekday: u8,
+    pub hour: u8,
+}
+
+impl Default for SelfTestSchedule {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            weekday: 0,
+            hour: 9,
+        }
+    }
+}
+
+impl SelfTestSchedule {
+    pub fn validate(&self) -> Result<(), NotificationError> {
+        if self.weekday > 6 {
+            return Err(NotificationError::Internal(format!(
+                "Weekday must be 0-6 (Sunday-Saturday), got {}",
+                self.weekday
+            )));
+        }
+        if self.hour > 23 {
+            return Err(NotificationError::Internal(format!(
+                "Hour must be 0-23, got {}",
+                self.hour
+            )));
+        }
+        Ok(())
+    }
+
+    /// The latest scheduled slot at or before `now`.
+    pub fn last_slot(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        let days_back =
+            (now.weekday().num_days_from_sunday() as i64 - self.weekday as i64).rem_euclid(7);
+        let date = now.date_naive() - Duration::days(days_back);
+        let slot = Utc.from_utc_datetime(&date.and_time(NaiveTime::MIN))
+            + Duration::hours(self.hour as i64);
+        if slot > now {
+            slot - Duration::days(7)
+        } else {
+            slot
+        }
+    }
+
+    /// A run is due once the latest slot has passed without a scheduled run
+    /// since. Enabling the schedule therefore tests the channels right away.
+    pub fn is_due(&self, last_run: Option<DateTime<Utc>>, now: DateTime<Utc>) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        last_run.map_or(true, |last| last < self.last_slot(now))
+    }
+}
+
+/// A channel that failed its last delivery or self-test.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DegradedChannel {
+    pub channel: ChannelKind,
+    pub target_id: Option<String>,
+    pub name: String,
+    pub error: String,
+    pub since: DateTime<Utc>,
+}
+
+/// Channel health as seen by the router. A channel stays degraded until it
+/// delivers again, and critical notifications routed to a degraded channel
+/// also go out through a fallback.
+#[derive(Default)]
+pub struct DegradedChannels {
+    channels: RwLock<HashMap<(ChannelKind, Option<String>), DegradedChannel>>,
+}
+
+impl DegradedChannels {
+    pub fn mark(&self, channel: ChannelKind, target_id: Option<&str>, name: &str, error: &str) {
+        let key = (channel, target_id.map(str::to_string));
+        self.channels
+            .write()
+            .entry(key)
+            .and_modify(|entry| entry.error = error.to_string())
+            .or_insert_with(|| DegradedChannel {
+                channel,
+                target_id: target_id.map(str::to_string),
+                name: name.to_string(),
+                error: error.to_string(),
+                since: Utc::now(),
+            });
+    }
+
+    pub fn clear(&self, channel: ChannelKind, target_id: Option<&str>) {
+        self.channels
+            .write()
+            .remove(&(channel, target_id.map(str::to_string)));
+    }
+
+    pub fn is_degraded(&self, channel: ChannelKind, target_id: Option<&str>) -> bool {
+        self.channels
+            .read()
+            .contains_key(&(channel, target_id.map(str::to_string)))
+    }
+
+    /// Applies self-test results; skipped channels keep their current state.
+    pub fn apply(&self, results: &[ChannelTestResult]) {
+        for result in results {
+            let target_id = result.target_id.as_deref();
+            match result.status {
+                ChannelTestStatus::Delivered => self.clear(result.channel, target_id),
+                ChannelTestStatus::Failed => self.mark(
+                    result.channel,
+                    target_id,
+                    &result.name,
+                    result.detail.as_deref().unwrap_or("Delivery failed"),
+                ),
+                ChannelTestStatus::Skipped => {}
+            }
+        }
+    }
+
+    pub fn list(&self) -> Vec<DegradedChannel> {
+        let mut channels: Vec<DegradedChannel> = self.channels.read().values().cloned().collect();
+        channels.sort_by(|a, b| a.channel.cmp(&b.channel).then_with(|| a.name.cmp(&b.name)));
+        channels
+    }
+}
+
+/// What a channel check resolved to, before timing is attached.
+pub enum CheckOutcome {
+    Delivered,
+    Skipped(String),
+}
+
+/// Runs one channel check under `timeout` and measures how long it took.
+pub async fn run_check<F>(
+    channel: ChannelKind,
+    target_id: Option<String>,
+    name: String,
+    timeout: std::time::Duration,
+    check: F,
+) -> ChannelTestResult
+where
+    F: Future<Output = Result<CheckOutcome, String>>,
+{
+    let start = Instant::now();
+    let outcome = tokio::time::timeout(timeout, check).await;
+    let latency_ms = Some(start.elapsed().as_millis() as u64);
+
+    let (status, detail, latency_ms) = match outcome {
+        Ok(Ok(CheckOutcome::Delivered)) => (ChannelTestStatus::Delivered, None, latency_ms),
+        Ok(Ok(CheckOutcome::Skipped(reason))) => (ChannelTestStatus::Skipped, Some(reason), None),
+        Ok(Err(error)) => (ChannelTestStatus::Failed, Some(error), latency_ms),
+        Err(_) => (
+            ChannelTestStatus::Failed,
+            Some(format!("Timed out after {}ms", timeout.as_millis())),
+            latency_ms,
+        ),
+    };
+
+    ChannelTestResult {
+        channel,
+        target_id,
+        name,
+        status,
+        detail,
+        latency_ms,
+    }
+}
+
+/// Sends a plain-text email from the configured SMTP account to its own
+/// address. Returns `Ok(false)` when no account is configured.
+pub async fn send_email_to_self(
+    app: &AppHandle,
+    subject: &str,
+    body: &str,
+) -> Result<bool, String> {
+    let Some(keystore) = app.try_state::<Keystore>() else {
+        return Ok(false);
+    };
+    let manager = EmailManager::new(app).await.map_err(|e| e.to_string())?;
+    let config = match manager.get_config(&keystore).await {
+        Ok(config) => config,
+        Err(EmailError::ConfigNotFound) => return Ok(false),
+        Err(e) => return Err(e.to_string()),
+    };
+
+    let request = SendEmailRequest {
+        to: vec![config.from_address.clone()],
+        subject: subject.to_string(),
+        html_body: None,
+        text_body: Some(body.to_string()),
+        template: None,
+        template_vars: None,
+        attachments: None,
+        include_unsubscribe: false,
+    };
+    manager
+        .send_email(request, &config)
+        .await
+        .map(|_| true)
+        .map_err(|e| e.to_string())
+}
+
+pub async fn registered_devices(app: &AppHandle) -> Vec<MobileDevice> {
+    match app.try_state::<SharedMobileAuthManager>() {
+        Some(auth) => auth.read().await.get_devices(),
+        None => Vec::new(),
+    }
+}
+
+/// Queues a push for one device under its delivery policy. A link is passed
+/// in the payload for the mobile companion to open.
+pub async fn queue_push(
+    app: &AppHandle,
+    device: &MobileDevice,
+    priority: PushPriority,
+    title: &str,
+    body: &str,
+    link: Option<&str>,
+) -> Result<(), String> {
+    let push = app
+        .try_state::<SharedPushNotificationManager>()
+        .ok_or_else(|| "Push notifications are not available".to_string())?;
+
+    let request = QueuePushRequest {
+        device_id: device.device_id.clone(),
+        category: NotificationCategory::System,
+        priority,
+        title: title.to_string(),
+        body: body.to_string(),
+        payload: link
+            .map(|link| serde_json::json!({ "link": link }))
+            .unwrap_or(serde_json::Value::Null),
+    };
+    push.write()
+        .await
+        .create_notification(request, &device.delivery_policy);
+    Ok(())
+}
+
+/// Runs the weekly channel self-test whenever its slot has passed.
+pub async fn run_channel_self_test_scheduler(app: AppHandle) {
+    let mut interval =
+        tokio::time::interval(std::time::Duration::from_secs(SCHEDULE_CHECK_INTERVAL_SECS));
+    loop {
+        interval.tick().await;
+
+        let Some(router) = app.try_state::<SharedNotificationRouter>() else {
+            continue;
+        };
+        let router = router.read().await;
+        let logger = router.get_delivery_logger();
+        let due = match (
+            logger.get_self_test_schedule().await,
+            logger.last_self_test_at(SelfTestTrigger::Scheduled).await,
+        ) {
+            (Ok(schedule), Ok(last_run)) => schedule.is_due(last_run, Utc::now()),
+            (Err(e), _) | (_, Err(e)) => {
+                eprintln!("Failed to load notification self-test schedule: {}", e);
+                false
+            }
+        };
+        if !due {
+            continue;
+        }
+
+        match router.test_all_channels(SelfTestTrigger::Scheduled).await {
+            Ok(report) if report.failed > 0 => eprintln!(
+                "Scheduled notification self-test: {} channel(s) failed",
+                report.failed
+            ),
+            Ok(_) => {}
+            Err(e) => eprintln!("Scheduled notification self-test failed: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(rfc3339: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(rfc3339)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn result(channel: ChannelKind, id: &str, status: ChannelTestStatus) -> ChannelTestResult {
+        ChannelTestResult {
+            channel,
+            target_id: Some(id.to_string()),
+            name: id.to_string(),
+            status,
+            detail: (status == ChannelTestStatus::Failed).then(|| "401 Unauthorized".to_string()),
+            latency_ms: Some(10),
+        }
+    }
+
+    #[test]
+    fn weekly_schedule_is_due_once_per_slot() {
+        // Sunday 09:00 UTC; 2026-10-11 is a Sunday.
+        let schedule = SelfTestSchedule {
+            enabled: true,
+            weekday: 0,
+            hour: 9,
+        };
+        let wednesday = at("2026-10-14T12:00:00Z");
+        assert_eq!(schedule.last_slot(wednesday), at("2026-10-11T09:00:00Z"));
+        assert_eq!(
+            schedule.last_slot(at("2026-10-11T08:59:00Z")),
+            at("2026-10-04T09:00:00Z")
+        );
+
+        assert!(schedule.is_due(None, wednesday));
+        assert!(schedule.is_due(Some(at("2026-10-11T08:00:00Z")), wednesday));
+        assert!(!schedule.is_due(Some(at("2026-10-11T09:00:30Z")), wednesday));
+
+        let disabled = SelfTestSchedule {
+            enabled: false,
+            ..schedule.clone()
+        };
+        assert!(!disabled.is_due(None, wednesday));
+    }
+
+    #[test]
+    fn schedule_validation_rejects_out_of_range_slots() {
+        let mut schedule = SelfTestSchedule::default();
+        assert!(schedule.validate().is_ok());
+        schedule.weekday = 7;
+        assert!(schedule.validate().is_err());
+        schedule.weekday = 6;
+        schedule.hour = 24;
+        assert!(schedule.validate().is_err());
+    }
+
+    #[test]
+    fn failures_degrade_channels_until_they_deliver() {
+        let degraded = DegradedChannels::default();
+        degraded.apply(&[
+            result(ChannelKind::Telegram, "tg", ChannelTestStatus::Failed),
+            result(ChannelKind::Slack, "sl", ChannelTestStatus::Delivered),
+        ]);
+        assert!(degraded.is_degraded(ChannelKind::Telegram, Some("tg")));
+        assert!(!degraded.is_degraded(ChannelKind::Slack, Some("sl")));
+
+        // Skips leave the state alone
+        degraded.apply(&[result(
+            ChannelKind::Telegram,
+            "tg",
+            ChannelTestStatus::Skipped,
+        )]);
+        assert_eq!(degraded.list().len(), 1);
+
+        degraded.apply(&[result(
+            ChannelKind::Telegram,
+            "tg",
+            ChannelTestStatus::Delivered,
+        )]);
+        assert!(degraded.list().is_empty());
+    }
+
+    #[test]
+    fn report_counts_and_orders_results() {
+        let report = ChannelTestReport::new(
+            SelfTestTrigger::Manual,
+            Utc::now(),
+            Utc::now(),
+            vec![
+                result(ChannelKind::Webhook, "hook", ChannelTestStatus::Failed),
+                ChannelTestResult::skipped(ChannelKind::Email, None, "Email", "Not configured"),
+                result(ChannelKind::InApp, "in-app", ChannelTestStatus::Delivered),
+            ],
+        );
+
+        assert_eq!((report.delivered, report.failed, report.skipped), (1, 1, 1));
+        assert_eq!(report.results[0].channel, ChannelKind::InApp);
+        assert_eq!(report.failures().count(), 1);
+    }
+
+    #[tokio::test]
+    async fn checks_that_hang_fail_with_a_timeout() {
+        let result = run_check(
+            ChannelKind::Webhook,
+            Some("slow".to_string()),
+            "Slow hook".to_string(),
+            std::time::Duration::from_millis(20),
+            std::future::pending::<Result<CheckOutcome, String>>(),
+        )
+        .await;
+
+        assert_eq!(result.status, ChannelTestStatus::Failed);
+        assert_eq!(result.detail.as_deref(), Some("Timed out after 20ms"));
+        assert!(result.latency_ms.is_some());
+    }
+}
@@ -127,20 +127,28 @@ pub fn format_alert_message(
     symbol: &str,
     current_price: f64,
     condition: &str,
+    link: Option<&str>,
     use_markdown: bool,
 ) -> String {
     if use_markdown {
+        // Telegram does not linkify custom schemes; a code span keeps the
+        // link intact and easy to copy.
+        let link = link
+            .map(|link| format!("*Open in app:* `{}`\n", link))
+            .unwrap_or_default();
         format!(
             "*🚨 Price Alert Triggered*\n\n\
             *Alert:* {}\n\
             *Symbol:* {}\n\
             *Price:* ${:.4}\n\
-            *Condition:* {}\n\n\
+            *Condition:* {}\n\
+            {}\n\
             _Triggered at: {}_",
             TelegramClient::escape_markdown(alert_name),
             TelegramClient::escape_markdown(symbol),
             current_price,
             TelegramClient::escape_markdown(condition),
+            link,
             TelegramClient::escape_markdown(
                 &chrono::Utc::now()
                     .format("%Y-%m-%d %H:%M:%S UTC")
@@ -148,17 +156,22 @@ pub fn format_alert_message(
             )
         )
     } else {
+        let link = link
+            .map(|link| format!("Open in app: {}\n", link))
+            .unwrap_or_default();
         format!(
             "🚨 Price Alert Triggered\n\n\
             Alert: {}\n\
             Symbol: {}\n\
             Price: ${:.4}\n\
-            Condition: {}\n\n\
+            Condition: {}\n\
+            {}\n\
             Triggered at: {}",
             alert_name,
             symbol,
             current_price,
             condition,
+            link,
             chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC")
         )
     }
@@ -1,3 +1,4 @@
+use crate::config::DataPaths;
 use chrono::{DateTime, Utc};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
@@ -781,7 +782,7 @@ impl TwitterManager {
 }
 
 fn twitter_db_path(app: &AppHandle) -> Result<PathBuf, TwitterError> {
-    let app_dir = app.path().app_data_dir().map_err(|err| {
+    let app_dir = app.app_data_root().map_err(|err| {
         TwitterError::Internal(format!("Unable to resolve app data directory: {err}"))
     })?;
 
@@ -1,7 +1,9 @@
+use crate::config::DataPaths;
+use crate::deep_link::DeepLinkIntent;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
-use tauri::{AppHandle, Manager};
+use tauri::AppHandle;
 
 pub const NOTIFICATIONS_DB_FILE: &str = "chat_integrations.db";
 
@@ -32,7 +34,7 @@ impl ChatServiceType {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[serde(rename_all = "lowercase")]
 pub enum AlertPriority {
     Low,
@@ -170,7 +172,7 @@ pub enum NotificationError {
 }
 
 pub fn notifications_db_path(app: &AppHandle) -> Result<PathBuf, NotificationError> {
-    let mut path = app.path().app_data_dir().map_err(|err| {
+    let mut path = app.app_data_root().map_err(|err| {
         NotificationError::Internal(format!("Unable to resolve app data directory: {err}"))
     })?;
 
@@ -179,3 +181,213 @@ pub fn notifications_db_path(app: &AppHandle) -> Result<PathBuf, NotificationErr
     path.push(NOTIFICATIONS_DB_FILE);
     Ok(path)
 }
+
+impl AlertPriority {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AlertPriority::Low => "low",
+            AlertPriority::Medium => "medium",
+            AlertPriority::High => "high",
+            AlertPriority::Critical => "critical",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "low" => Some(AlertPriority::Low),
+            "medium" => Some(AlertPriority::Medium),
+            "high" => Some(AlertPriority::High),
+            "critical" => Some(AlertPriority::Critical),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum HistoryCategory {
+    PriceAlert,
+    ClusterTrigger,
+    Order,
+    Governance,
+    CopyTrading,
+    TokenUnlock,
+    LiquidityLock,
+    StablecoinDepeg,
+    System,
+}
+
+impl HistoryCategory {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HistoryCategory::PriceAlert => "price_alert",
+            HistoryCategory::ClusterTrigger => "cluster_trigger",
+            HistoryCategory::Order => "order",
+            HistoryCategory::Governance => "governance",
+            HistoryCategory::CopyTrading => "copy_trading",
+            HistoryCategory::TokenUnlock => "token_unlock",
+            HistoryCategory::LiquidityLock => "liquidity_lock",
+            HistoryCategory::StablecoinDepeg => "stablecoin_depeg",
+            HistoryCategory::System => "system",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "price_alert" => Some(HistoryCategory::PriceAlert),
+            "cluster_trigger" => Some(HistoryCategory::ClusterTrigger),
+            "order" => Some(HistoryCategory::Order),
+            "governance" => Some(HistoryCategory::Governance),
+            "copy_trading" => Some(HistoryCategory::CopyTrading),
+            "token_unlock" => Some(HistoryCategory::TokenUnlock),
+            "liquidity_lock" => Some(HistoryCategory::LiquidityLock),
+            "stablecoin_depeg" => Some(HistoryCategory::StablecoinDepeg),
+            "system" => Some(HistoryCategory::System),
+            _ => None,
+        }
+    }
+}
+
+/// What a notification is about, so the UI can deep-link to it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", content = "id", rename_all = "snake_case")]
+pub enum NotificationEntity {
+    Alert(String),
+    Order(String),
+    Proposal(String),
+    ClusterTrigger(String),
+    LeaderWallet(String),
+}
+
+impl NotificationEntity {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            NotificationEntity::Alert(_) => "alert",
+            NotificationEntity::Order(_) => "order",
+            NotificationEntity::Proposal(_) => "proposal",
+            NotificationEntity::ClusterTrigger(_) => "cluster_trigger",
+            NotificationEntity::LeaderWallet(_) => "leader_wallet",
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        match self {
+            NotificationEntity::Alert(id)
+            | NotificationEntity::Order(id)
+            | NotificationEntity::Proposal(id)
+            | NotificationEntity::ClusterTrigger(id)
+            | NotificationEntity::LeaderWallet(id) => id,
+        }
+    }
+
+    pub fn from_parts(kind: &str, id: String) -> Option<Self> {
+        match kind {
+            "alert" => Some(NotificationEntity::Alert(id)),
+            "order" => Some(NotificationEntity::Order(id)),
+            "proposal" => Some(NotificationEntity::Proposal(id)),
+            "cluster_trigger" => Some(NotificationEntity::ClusterTrigger(id)),
+            "leader_wallet" => Some(NotificationEntity::LeaderWallet(id)),
+            _ => None,
+        }
+    }
+}
+
+/// A notification about to be routed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewNotification {
+    pub category: HistoryCategory,
+    pub priority: AlertPriority,
+    pub title: String,
+    pub message: String,
+    pub entity: Option<NotificationEntity>,
+    /// `eclipse://` link to the relevant view, included in chat and email
+    /// deliveries.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub link: Option<String>,
+}
+
+impl NewNotification {
+    /// Triggered price alerts are always critical, so they count towards the
+    /// tray badge until read.
+    pub fn price_alert(
+        alert_id: &str,
+        alert_name: &str,
+        symbol: &str,
+        current_price: f64,
+        condition: &str,
+    ) -> Self {
+        Self {
+            category: HistoryCategory::PriceAlert,
+            priority: AlertPriority::Critical,
+            title: alert_name.to_string(),
+            message: format!("{} at ${:.4}: {}", symbol, current_price, condition),
+            entity: Some(NotificationEntity::Alert(alert_id.to_string())),
+            link: Some(
+                DeepLinkIntent::Alert {
+                    alert_id: alert_id.to_string(),
+                }
+                .to_url(),
+            ),
+        }
+    }
+}
+
+/// How one chat integration handled a routed notification.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelOutcome {
+    pub service_type: ChatServiceType,
+    pub config_id: String,
+    pub config_name: String,
+    pub status: DeliveryStatus,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationRecord {
+    pub id: String,
+    pub category: HistoryCategory,
+    pub priority: AlertPriority,
+    pub title: String,
+    pub message: String,
+    pub entity: Option<NotificationEntity>,
+    pub channels: Vec<ChannelOutcome>,
+    pub read: bool,
+    pub created_at: String,
+    pub read_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NotificationFilter {
+    pub category: Option<HistoryCategory>,
+    pub priority: Option<AlertPriority>,
+    pub unread_only: bool,
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationPage {
+    pub items: Vec<NotificationRecord>,
+    /// Pass back as `cursor` to fetch the next, older page.
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationRetention {
+    pub max_age_days: u32,
+    pub max_entries: u32,
+}
+
+impl Default for NotificationRetention {
+    fn default() -> Self {
+        Self {
+            max_age_days: 30,
+            max_entries: 5_000,
+        }
+    }
+}
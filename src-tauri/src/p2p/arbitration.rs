@@ -0,0 +1,194 @@
+use super::{
+    database::P2PDatabase,
+    onchain::{
+        decode_transaction, describe_pending, encode_transaction, escrow_arbiter, OnChainEscrow,
+        ARBITER_KEYSTORE_KEY,
+    },
+    reputation::{dispute_winner, refresh_trader_reputation},
+    types::*,
+    SharedP2PDatabase,
+};
+use crate::security::keystore::Keystore;
+use crate::security::reputation::{ReputationEngine, SharedReputationEngine};
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use solana_sdk::signature::{Keypair, Signer};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::time::{interval, Duration};
+
+const ARBITRATION_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Closes a dispute and its escrow after a ruling or a default resolution,
+/// and updates both parties' reputation with the outcome.
+pub async fn resolve_dispute_records(
+    db: &P2PDatabase,
+    reputation: &ReputationEngine,
+    dispute: &Dispute,
+    escrow: &Escrow,
+    resolution: String,
+    outcome: DisputeResolution,
+    by_default: bool,
+) -> Result<()> {
+    db.update_dispute_status(&dispute.id, DisputeStatus::Resolved, Some(resolution))
+        .await?;
+    db.record_dispute_outcome(&dispute.id, outcome, by_default)
+        .await?;
+    db.update_escrow_state(&escrow.id, EscrowState::Completed, None, None)
+        .await?;
+
+    let disputed = true;
+    db.update_trader_stats(&escrow.buyer, false, false, disputed, None)
+        .await?;
+    db.update_trader_stats(&escrow.seller, false, false, disputed, None)
+        .await?;
+
+    let winner = dispute_winner(escrow, outcome);
+    let details = format!(
+        "Dispute {} on escrow {}: {}",
+        dispute.id, escrow.id, outcome
+    );
+    for party in [&escrow.buyer, &escrow.seller] {
+        let event_type = if by_default {
+            "p2p_dispute_defaulted"
+        } else if party == winner {
+            "p2p_dispute_won"
+        } else {
+            "p2p_dispute_lost"
+        };
+        refresh_trader_reputation(db, reputation, party, event_type, Some(&details)).await?;
+    }
+
+    Ok(())
+}
+
+fn load_arbiter_keypair(app: &AppHandle) -> Option<Keypair> {
+    let keystore = app.try_state::<Keystore>()?;
+    let secret = keystore.retrieve_secret(ARBITER_KEYSTORE_KEY).ok()?;
+    Keypair::from_bytes(&secret).ok()
+}
+
+/// Moves an on-chain default resolution forward. Returns the settlement
+/// signature once it lands; until then the arbiter-signed transaction waits
+/// for the favoured party's signature.
+async fn advance_onchain_default(
+    app: &AppHandle,
+    db: &P2PDatabase,
+    escrow: &Escrow,
+    resolution: DisputeResolution,
+) -> Result<Option<String>> {
+    let config = db.get_escrow_config().await?;
+    let chain = OnChainEscrow::new(config.rpc_url);
+    let action = resolution.action();
+
+    if let Some(pending) = db.get_pending_transaction(&escrow.id).await? {
+        if pending.action == action {
+            let pending = describe_pending(pending)?;
+            if !pending.missing_signers.is_empty() {
+                return Ok(None);
+            }
+            let submitted = chain.submit(&pending);
+            if submitted.is_err() {
+                // A stale blockhash cannot be fixed by retrying; prepare afresh.
+                db.delete_pending_transaction(&escrow.id).await?;
+            }
+            return submitted.map(Some);
+        }
+    }
+
+    let arbiter = escrow_arbiter(escrow)?;
+    let arbiter_key = load_arbiter_keypair(app)
+        .filter(|key| key.pubkey() == arbiter)
+        .ok_or_else(|| anyhow!("Arbiter key for {} is not held on this device", arbiter))?;
+
+    let pending = chain.prepare_settlement(escrow, action, &arbiter)?;
+    let mut transaction = decode_transaction(&pending.transaction)?;
+    let blockhash = transaction.message.recent_blockhash;
+    transaction.partial_sign(&[&arbiter_key], blockhash);
+    let pending = describe_pending(PendingEscrowTransaction {
+        transaction: encode_transaction(&transaction)?,
+        ..pending
+    })?;
+    db.save_pending_transaction(&pending).await?;
+    let _ = app.emit("p2p_default_resolution_pending", &pending);
+
+    Ok(None)
+}
+
+async fn apply_overdue_defaults(app: &AppHandle, db: &SharedP2PDatabase) -> Result<()> {
+    let reputation = app
+        .try_state::<SharedReputationEngine>()
+        .ok_or_else(|| anyhow!("Reputation engine is not available"))?;
+    let reputation = reputation.read().await;
+    let db = db.read().await;
+
+    for dispute in db.list_overdue_disputes(Utc::now()).await? {
+        let Some(resolution) = dispute.default_resolution else {
+            continue;
+        };
+        let Some(escrow) = db.get_escrow(&dispute.escrow_id).await? else {
+            continue;
+        };
+        let note = format!(
+            "Default resolution ({}) applied after the arbitration deadline passed",
+            resolution
+        );
+
+        match escrow.mode {
+            EscrowMode::Local => {
+                resolve_dispute_records(
+                    &db,
+                    &reputation,
+                    &dispute,
+                    &escrow,
+                    note,
+                    resolution,
+                    true,
+                )
+                .await?;
+            }
+            EscrowMode::OnChain => {
+                match advance_onchain_default(app, &db, &escrow, resolution).await {
+                    Ok(Some(signature)) => {
+                        db.record_escrow_signature(&escrow.id, resolution.action(), &signature)
+                            .await?;
+                        db.delete_pending_transaction(&escrow.id).await?;
+                        resolve_dispute_records(
+                            &db,
+                            &reputation,
+                            &dispute,
+                            &escrow,
+                            note,
+                            resolution,
+                            true,
+                        )
+                        .await?;
+                    }
+                    Ok(None) => continue,
+                    Err(err) => {
+                        eprintln!(
+                            "Default resolution for dispute {} is blocked: {}",
+                            dispute.id, err
+                        );
+                        continue;
+                    }
+                }
+            }
+        }
+
+        let _ = app.emit("p2p_dispute_resolved", &dispute.id);
+    }
+
+    Ok(())
+}
+
+/// Applies each dispute's default resolution once its arbitration deadline
+/// passes without a ruling.
+pub async fn run_arbitration_timer(app: AppHandle, db: SharedP2PDatabase) {
+    let mut tick = interval(ARBITRATION_CHECK_INTERVAL);
+    loop {
+        tick.tick().await;
+        if let Err(err) = apply_overdue_defaults(&app, &db).await {
+            eprintln!("Failed to apply P2P arbitration defaults: {}", err);
+        }
+    }
+}
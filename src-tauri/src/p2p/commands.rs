@@ -1,12 +1,21 @@
 use super::{
+    arbitration::resolve_dispute_records,
     compliance::ComplianceChecker,
     database::P2PDatabase,
     escrow::{EscrowSmartContract, EscrowStateMachine},
-    matching::LocalMatcher,
+    matching::{check_fill, is_self_match, meets_reputation, LocalMatcher},
+    onchain::{
+        decode_transaction, describe_pending, encode_transaction, escrow_arbiter, merge_signatures,
+        parse_pubkey, reconcile, OnChainEscrow,
+    },
+    oracle::{validate_pricing, SharedP2PPriceOracle},
+    reputation::{refresh_trader_reputation, trader_reputation},
     types::*,
 };
 use crate::security::reputation::SharedReputationEngine;
+use crate::wallet::multi_wallet::MultiWalletManager;
 use anyhow::Result;
+use std::collections::HashSet;
 use std::sync::Arc;
 use tauri::State;
 use tokio::sync::RwLock;
@@ -19,6 +28,13 @@ pub async fn create_p2p_offer(
     db: State<'_, SharedP2PDatabase>,
     reputation: State<'_, SharedReputationEngine>,
 ) -> Result<P2POffer, String> {
+    validate_pricing(&request.pricing, &request.fiat_currency).map_err(|e| e.to_string())?;
+    if let (Some(min), Some(max)) = (request.min_amount, request.max_amount) {
+        if min > max {
+            return Err("Minimum fill cannot exceed the maximum fill".to_string());
+        }
+    }
+
     let reputation_guard = reputation.read().await;
     let creator_rep = reputation_guard
         .get_wallet_reputation(&request.creator)
@@ -43,10 +59,12 @@ pub async fn create_p2p_offer(
                 terms: request.terms.clone(),
                 time_limit: request.time_limit,
                 created_at: chrono::Utc::now(),
-                expires_at: None,
+                expires_at: request.expires_at,
                 is_active: true,
                 completed_trades: 0,
                 reputation_required: request.reputation_required,
+                pricing: request.pricing,
+                remaining_amount: request.amount,
             },
             creator_rep.as_ref(),
         )
@@ -76,18 +94,35 @@ pub async fn get_p2p_offer(
         .map_err(|e| e.to_string())
 }
 
+/// With a `viewer_address`, offers whose minimum reputation the viewer does
+/// not meet are left out, except the viewer's own.
 #[tauri::command]
 pub async fn list_p2p_offers(
     offer_type: Option<String>,
     token_address: Option<String>,
     active_only: bool,
+    viewer_address: Option<String>,
     db: State<'_, SharedP2PDatabase>,
 ) -> Result<Vec<P2POffer>, String> {
     let db_guard = db.read().await;
-    db_guard
+    let offers = db_guard
         .list_offers(offer_type, token_address, active_only)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(|e| e.to_string())?;
+
+    let Some(viewer) = viewer_address else {
+        return Ok(offers);
+    };
+    let viewer_score = db_guard
+        .get_or_create_trader_profile(&viewer)
+        .await
+        .map_err(|e| e.to_string())?
+        .reputation_score;
+
+    Ok(offers
+        .into_iter()
+        .filter(|offer| offer.creator == viewer || meets_reputation(offer, viewer_score))
+        .collect())
 }
 
 #[tauri::command]
@@ -103,16 +138,35 @@ pub async fn update_offer_status(
         .map_err(|e| e.to_string())
 }
 
+fn own_wallets(wallets: &MultiWalletManager) -> HashSet<String> {
+    wallets
+        .list_wallets()
+        .map(|list| list.into_iter().map(|wallet| wallet.public_key).collect())
+        .unwrap_or_default()
+}
+
 #[tauri::command]
 pub async fn match_p2p_offers(
     user_address: String,
     offers: Vec<P2POffer>,
     db: State<'_, SharedP2PDatabase>,
     reputation: State<'_, SharedReputationEngine>,
+    oracle: State<'_, SharedP2PPriceOracle>,
+    wallets: State<'_, MultiWalletManager>,
 ) -> Result<Vec<super::matching::TraderMatch>, String> {
     let db_guard = db.read().await;
     let reputation_guard = reputation.read().await;
 
+    // Match against the stored offers so remaining size reflects earlier fills.
+    let mut current = Vec::with_capacity(offers.len());
+    for offer in offers {
+        match db_guard.get_offer(&offer.id).await {
+            Ok(Some(stored)) => current.push(stored),
+            Ok(None) => {}
+            Err(e) => return Err(e.to_string()),
+        }
+    }
+
     let user_profile = db_guard
         .get_or_create_trader_profile(&user_address)
         .await
@@ -126,18 +180,106 @@ pub async fn match_p2p_offers(
     let matcher = LocalMatcher::new()
         .with_payment_priority("Bank Transfer", 90)
         .with_payment_priority("PayPal", 80)
-        .with_payment_priority("Cash", 70);
-
-    let matches = matcher.match_offers(&offers, &user_profile, user_reputation.as_ref());
+        .with_payment_priority("Cash", 70)
+        .with_own_wallets(own_wallets(&wallets));
+
+    let oracle_guard = oracle.read().await;
+    let matches = matcher.match_offers(
+        &current,
+        &user_profile,
+        user_reputation.as_ref(),
+        &oracle_guard,
+        chrono::Utc::now(),
+    );
 
     Ok(matches)
 }
 
+/// Fills in the arbiter for an on-chain escrow and checks that every party is a
+/// valid address, since they become multisig signers.
+fn prepare_onchain_request(
+    request: &mut CreateEscrowRequest,
+    offer: &P2POffer,
+    config: &P2PEscrowConfig,
+) -> Result<(), String> {
+    if request.mode != EscrowMode::OnChain {
+        return Ok(());
+    }
+
+    let arbiter = request
+        .arbiter
+        .clone()
+        .or_else(|| config.arbiter.clone())
+        .ok_or_else(|| "On-chain escrows need an arbiter".to_string())?;
+    let buyer = parse_pubkey("buyer", &request.buyer).map_err(|e| e.to_string())?;
+    let seller = parse_pubkey("seller", &request.seller).map_err(|e| e.to_string())?;
+    let arbiter_key = parse_pubkey("arbiter", &arbiter).map_err(|e| e.to_string())?;
+    parse_pubkey("token", &offer.token_address).map_err(|e| e.to_string())?;
+
+    if arbiter_key == buyer || arbiter_key == seller || buyer == seller {
+        return Err("Buyer, seller and arbiter must be different addresses".to_string());
+    }
+
+    request.arbiter = Some(arbiter);
+    Ok(())
+}
+
+async fn refresh_parties(
+    db: &P2PDatabase,
+    reputation: &SharedReputationEngine,
+    escrow: &Escrow,
+    event_type: &str,
+) -> Result<(), String> {
+    let engine = reputation.read().await;
+    let details = format!("Escrow {}", escrow.id);
+    for party in [&escrow.buyer, &escrow.seller] {
+        refresh_trader_reputation(db, &engine, party, event_type, Some(&details))
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+async fn submit_onchain(
+    db: &P2PDatabase,
+    escrow: &Escrow,
+    action: EscrowAction,
+) -> Result<(String, PendingEscrowTransaction), String> {
+    let pending = db
+        .get_pending_transaction(&escrow.id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("No prepared {} transaction for this escrow", action))?;
+    if pending.action != action {
+        return Err(format!(
+            "The prepared transaction is a {} transaction, not {}",
+            pending.action, action
+        ));
+    }
+
+    let config = db.get_escrow_config().await.map_err(|e| e.to_string())?;
+    let signature = OnChainEscrow::new(config.rpc_url)
+        .submit(&pending)
+        .map_err(|e| e.to_string())?;
+
+    db.record_escrow_signature(&escrow.id, action, &signature)
+        .await
+        .map_err(|e| e.to_string())?;
+    db.delete_pending_transaction(&escrow.id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok((signature, pending))
+}
+
+/// Each escrow is one fill of an offer, taken by a counterparty of its creator.
 #[tauri::command]
 pub async fn create_p2p_escrow(
-    request: CreateEscrowRequest,
+    mut request: CreateEscrowRequest,
     db: State<'_, SharedP2PDatabase>,
     reputation: State<'_, SharedReputationEngine>,
+    oracle: State<'_, SharedP2PPriceOracle>,
+    wallets: State<'_, MultiWalletManager>,
 ) -> Result<Escrow, String> {
     let reputation_guard = reputation.read().await;
 
@@ -160,6 +302,41 @@ pub async fn create_p2p_escrow(
         .map_err(|e| e.to_string())?
         .ok_or_else(|| "Offer not found".to_string())?;
 
+    let counterparty = match offer.offer_type {
+        OfferType::Sell if offer.creator == request.seller => &request.buyer,
+        OfferType::Buy if offer.creator == request.buyer => &request.seller,
+        _ => return Err("The offer creator must be on the offer's side of the escrow".to_string()),
+    };
+    if is_self_match(&offer.creator, counterparty, &own_wallets(&wallets)) {
+        return Err("Cannot match an offer from one of your own wallets".to_string());
+    }
+    let counterparty_score = db_guard
+        .get_or_create_trader_profile(counterparty)
+        .await
+        .map_err(|e| e.to_string())?
+        .reputation_score;
+    if !meets_reputation(&offer, counterparty_score) {
+        return Err(format!(
+            "This offer requires a P2P reputation of at least {:.0}",
+            offer.reputation_required.unwrap_or_default()
+        ));
+    }
+
+    let now = chrono::Utc::now();
+    check_fill(&offer, request.amount, now).map_err(|e| e.to_string())?;
+    let quote = oracle
+        .read()
+        .await
+        .quote(&offer, now)
+        .map_err(|e| e.to_string())?;
+    request.fiat_amount = request.amount * quote.unit_price;
+
+    let config = db_guard
+        .get_escrow_config()
+        .await
+        .map_err(|e| e.to_string())?;
+    prepare_onchain_request(&mut request, &offer, &config)?;
+
     let escrow = Escrow {
         id: String::new(),
         offer_id: request.offer_id.clone(),
@@ -176,8 +353,13 @@ pub async fn create_p2p_escrow(
         funded_at: None,
         released_at: None,
         timeout_at: chrono::Utc::now() + chrono::Duration::minutes(offer.time_limit as i64),
-        arbitrators: vec![],
+        arbitrators: request.arbiter.clone().into_iter().collect(),
         fee_rate: 0.01,
+        mode: request.mode,
+        funding_signature: None,
+        settlement_signature: None,
+        price_quote: Some(quote.clone()),
+        confirmed_at: None,
     };
 
     let compliance = checker
@@ -192,7 +374,7 @@ pub async fn create_p2p_escrow(
     drop(db_guard);
     let db_guard = db.write().await;
     db_guard
-        .create_escrow(request)
+        .create_escrow(request, quote)
         .await
         .map_err(|e| e.to_string())
 }
@@ -238,6 +420,22 @@ pub async fn fund_p2p_escrow(
         .transition(EscrowState::Funded)
         .map_err(|e| e.to_string())?;
 
+    if escrow.mode == EscrowMode::OnChain {
+        drop(db_guard);
+        let db_guard = db.write().await;
+        let (signature, pending) = submit_onchain(&db_guard, &escrow, EscrowAction::Fund).await?;
+        db_guard
+            .update_escrow_state(
+                &escrow_id,
+                EscrowState::Funded,
+                pending.multisig_address,
+                pending.vault_address,
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+        return Ok(signature);
+    }
+
     let contract = EscrowSmartContract::new(None);
     let (multisig_address, escrow_pubkey) = contract
         .create_multisig_escrow(
@@ -304,6 +502,7 @@ pub async fn confirm_payment_p2p(
 pub async fn release_p2p_escrow(
     escrow_id: String,
     db: State<'_, SharedP2PDatabase>,
+    reputation: State<'_, SharedReputationEngine>,
 ) -> Result<String, String> {
     let db_guard = db.read().await;
     let escrow = db_guard
@@ -317,18 +516,24 @@ pub async fn release_p2p_escrow(
         .transition(EscrowState::Released)
         .map_err(|e| e.to_string())?;
 
-    let contract = EscrowSmartContract::new(None);
-    let tx_signature = contract
-        .release_funds(
-            escrow.escrow_pubkey.as_ref().unwrap(),
-            &escrow.seller,
-            escrow.amount,
-        )
-        .await
-        .map_err(|e| e.to_string())?;
-
     drop(db_guard);
     let db_guard = db.write().await;
+
+    let tx_signature = if escrow.mode == EscrowMode::OnChain {
+        submit_onchain(&db_guard, &escrow, EscrowAction::Release)
+            .await?
+            .0
+    } else {
+        let contract = EscrowSmartContract::new(None);
+        contract
+            .release_funds(
+                escrow.escrow_pubkey.as_ref().unwrap(),
+                &escrow.seller,
+                escrow.amount,
+            )
+            .await
+            .map_err(|e| e.to_string())?
+    };
     db_guard
         .update_escrow_state(&escrow_id, EscrowState::Released, None, None)
         .await
@@ -344,6 +549,8 @@ pub async fn release_p2p_escrow(
         .await
         .map_err(|e| e.to_string())?;
 
+    refresh_parties(&db_guard, &reputation, &escrow, "p2p_trade_completed").await?;
+
     Ok(tx_signature)
 }
 
@@ -351,6 +558,7 @@ pub async fn release_p2p_escrow(
 pub async fn cancel_p2p_escrow(
     escrow_id: String,
     db: State<'_, SharedP2PDatabase>,
+    reputation: State<'_, SharedReputationEngine>,
 ) -> Result<(), String> {
     let db_guard = db.read().await;
     let escrow = db_guard
@@ -366,11 +574,29 @@ pub async fn cancel_p2p_escrow(
 
     drop(db_guard);
     let db_guard = db.write().await;
+
+    // Tokens already locked on chain go back to the buyer before the record closes.
+    if escrow.mode == EscrowMode::OnChain {
+        if escrow.state == EscrowState::Created {
+            db_guard
+                .delete_pending_transaction(&escrow_id)
+                .await
+                .map_err(|e| e.to_string())?;
+        } else {
+            submit_onchain(&db_guard, &escrow, EscrowAction::Refund).await?;
+        }
+    }
+
     db_guard
         .update_escrow_state(&escrow_id, EscrowState::Cancelled, None, None)
         .await
         .map_err(|e| e.to_string())?;
 
+    db_guard
+        .release_offer_amount(&escrow.offer_id, escrow.amount)
+        .await
+        .map_err(|e| e.to_string())?;
+
     db_guard
         .update_trader_stats(&escrow.buyer, false, true, false, None)
         .await
@@ -381,6 +607,8 @@ pub async fn cancel_p2p_escrow(
         .await
         .map_err(|e| e.to_string())?;
 
+    refresh_parties(&db_guard, &reputation, &escrow, "p2p_trade_cancelled").await?;
+
     Ok(())
 }
 
@@ -401,6 +629,12 @@ pub async fn file_p2p_dispute(
         .transition(EscrowState::Disputed)
         .map_err(|e| e.to_string())?;
 
+    let config = db_guard
+        .get_escrow_config()
+        .await
+        .map_err(|e| e.to_string())?;
+    let deadline = chrono::Utc::now() + chrono::Duration::hours(config.arbitration_timeout_hours);
+
     drop(db_guard);
     let db_guard = db.write().await;
 
@@ -410,7 +644,7 @@ pub async fn file_p2p_dispute(
         .map_err(|e| e.to_string())?;
 
     db_guard
-        .create_dispute(request)
+        .create_dispute(request, deadline, config.default_resolution)
         .await
         .map_err(|e| e.to_string())
 }
@@ -445,6 +679,7 @@ pub async fn resolve_p2p_dispute(
     resolution: String,
     release_to: String,
     db: State<'_, SharedP2PDatabase>,
+    reputation: State<'_, SharedReputationEngine>,
 ) -> Result<String, String> {
     let db_guard = db.read().await;
     let dispute = db_guard
@@ -459,42 +694,192 @@ pub async fn resolve_p2p_dispute(
         .map_err(|e| e.to_string())?
         .ok_or_else(|| "Escrow not found".to_string())?;
 
-    let contract = EscrowSmartContract::new(None);
-    let tx_signature = contract
-        .resolve_dispute(
-            escrow.escrow_pubkey.as_ref().unwrap(),
-            "arbitrator_address",
-            &release_to,
-            escrow.amount,
-        )
-        .await
-        .map_err(|e| e.to_string())?;
+    let outcome = if release_to == escrow.seller {
+        DisputeResolution::ReleaseToSeller
+    } else if release_to == escrow.buyer {
+        DisputeResolution::RefundToBuyer
+    } else {
+        return Err("Funds can only be released to the buyer or the seller".to_string());
+    };
 
     drop(db_guard);
     let db_guard = db.write().await;
 
-    db_guard
-        .update_dispute_status(&dispute_id, DisputeStatus::Resolved, Some(resolution))
+    let tx_signature = if escrow.mode == EscrowMode::OnChain {
+        submit_onchain(&db_guard, &escrow, outcome.action())
+            .await?
+            .0
+    } else {
+        let contract = EscrowSmartContract::new(None);
+        contract
+            .resolve_dispute(
+                escrow.escrow_pubkey.as_ref().unwrap(),
+                "arbitrator_address",
+                &release_to,
+                escrow.amount,
+            )
+            .await
+            .map_err(|e| e.to_string())?
+    };
+
+    let reputation_guard = reputation.read().await;
+    resolve_dispute_records(
+        &db_guard,
+        &reputation_guard,
+        &dispute,
+        &escrow,
+        resolution,
+        outcome,
+        false,
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(tx_signature)
+}
+
+#[tauri::command]
+pub async fn prepare_p2p_escrow_transaction(
+    escrow_id: String,
+    action: EscrowAction,
+    db: State<'_, SharedP2PDatabase>,
+    wallets: State<'_, MultiWalletManager>,
+) -> Result<PendingEscrowTransaction, String> {
+    let db_guard = db.write().await;
+    let escrow = db_guard
+        .get_escrow(&escrow_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Escrow not found".to_string())?;
+    if escrow.mode != EscrowMode::OnChain {
+        return Err("Escrow is not held on chain".to_string());
+    }
+
+    let config = db_guard
+        .get_escrow_config()
         .await
         .map_err(|e| e.to_string())?;
+    let chain = OnChainEscrow::new(config.rpc_url);
+    let buyer = parse_pubkey("buyer", &escrow.buyer).map_err(|e| e.to_string())?;
+    let seller = parse_pubkey("seller", &escrow.seller).map_err(|e| e.to_string())?;
+
+    // Funding is signed by the buyer. Settlements are signed by the
+    // recipient and co-signed by the other party, or by the arbiter on a
+    // dispute.
+    let cosigner = match (action, &escrow.state) {
+        (EscrowAction::Fund, EscrowState::Created) => None,
+        (EscrowAction::Release, EscrowState::Confirmed) => Some(buyer),
+        (EscrowAction::Refund, EscrowState::Funded) => Some(seller),
+        (EscrowAction::Release | EscrowAction::Refund, EscrowState::Disputed) => {
+            Some(escrow_arbiter(&escrow).map_err(|e| e.to_string())?)
+        }
+        _ => {
+            return Err(format!(
+                "Cannot prepare a {} transaction while the escrow is {}",
+                action, escrow.state
+            ))
+        }
+    };
+    let signers = match (cosigner, action) {
+        (None, _) => vec![buyer],
+        (Some(cosigner), EscrowAction::Release) => vec![seller, cosigner],
+        (Some(cosigner), _) => vec![buyer, cosigner],
+    };
+    for signer in &signers {
+        wallets
+            .ensure_can_sign(&signer.to_string())
+            .map_err(|e| e.to_string())?;
+    }
+
+    let pending = match cosigner {
+        None => chain.prepare_funding(&escrow),
+        Some(cosigner) => chain.prepare_settlement(&escrow, action, &cosigner),
+    }
+    .map_err(|e| e.to_string())?;
 
     db_guard
-        .update_escrow_state(&escrow.id, EscrowState::Completed, None, None)
+        .save_pending_transaction(&pending)
         .await
         .map_err(|e| e.to_string())?;
 
-    let disputed = true;
+    Ok(pending)
+}
+
+/// Adds the signatures from a wallet-signed copy of the prepared transaction.
+#[tauri::command]
+pub async fn sign_p2p_escrow_transaction(
+    escrow_id: String,
+    signed_transaction: String,
+    db: State<'_, SharedP2PDatabase>,
+) -> Result<PendingEscrowTransaction, String> {
+    let db_guard = db.write().await;
+    let pending = db_guard
+        .get_pending_transaction(&escrow_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "No prepared transaction for this escrow".to_string())?;
+
+    let mut transaction = decode_transaction(&pending.transaction).map_err(|e| e.to_string())?;
+    let signed = decode_transaction(&signed_transaction).map_err(|e| e.to_string())?;
+    merge_signatures(&mut transaction, &signed).map_err(|e| e.to_string())?;
+
+    let pending = describe_pending(PendingEscrowTransaction {
+        transaction: encode_transaction(&transaction).map_err(|e| e.to_string())?,
+        ..pending
+    })
+    .map_err(|e| e.to_string())?;
+
     db_guard
-        .update_trader_stats(&escrow.buyer, false, false, disputed, None)
+        .save_pending_transaction(&pending)
         .await
         .map_err(|e| e.to_string())?;
 
+    Ok(pending)
+}
+
+#[tauri::command]
+pub async fn get_p2p_pending_transaction(
+    escrow_id: String,
+    db: State<'_, SharedP2PDatabase>,
+) -> Result<Option<PendingEscrowTransaction>, String> {
+    let db_guard = db.read().await;
     db_guard
-        .update_trader_stats(&escrow.seller, false, false, disputed, None)
+        .get_pending_transaction(&escrow_id)
         .await
-        .map_err(|e| e.to_string())?;
+        .map_err(|e| e.to_string())?
+        .map(describe_pending)
+        .transpose()
+        .map_err(|e| e.to_string())
+}
 
-    Ok(tx_signature)
+#[tauri::command]
+pub async fn get_p2p_escrow_config(
+    db: State<'_, SharedP2PDatabase>,
+) -> Result<P2PEscrowConfig, String> {
+    let db_guard = db.read().await;
+    db_guard
+        .get_escrow_config()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_p2p_escrow_config(
+    config: P2PEscrowConfig,
+    db: State<'_, SharedP2PDatabase>,
+) -> Result<(), String> {
+    if config.arbitration_timeout_hours <= 0 {
+        return Err("Arbitration timeout must be at least one hour".to_string());
+    }
+    if let Some(arbiter) = &config.arbiter {
+        parse_pubkey("arbiter", arbiter).map_err(|e| e.to_string())?;
+    }
+
+    let db_guard = db.write().await;
+    db_guard
+        .set_escrow_config(&config)
+        .await
+        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -527,10 +912,16 @@ pub async fn get_trader_profile(
     db: State<'_, SharedP2PDatabase>,
 ) -> Result<TraderProfile, String> {
     let db_guard = db.read().await;
-    db_guard
+    let mut profile = db_guard
         .get_or_create_trader_profile(&address)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(|e| e.to_string())?;
+    let breakdown = trader_reputation(&db_guard, &address)
+        .await
+        .map_err(|e| e.to_string())?;
+    profile.reputation_score = breakdown.score;
+    profile.reputation_breakdown = Some(breakdown);
+    Ok(profile)
 }
 
 #[tauri::command]
@@ -577,10 +968,33 @@ pub async fn check_p2p_compliance(
             .await
             .ok();
 
-        checker
+        let mut check = checker
             .check_escrow(&escrow, buyer_rep.as_ref(), seller_rep.as_ref())
             .await
-            .map_err(|e| e.to_string())
+            .map_err(|e| e.to_string())?;
+
+        if escrow.mode == EscrowMode::OnChain {
+            check.checks_performed.push("onchain_state".to_string());
+            let config = db_guard
+                .get_escrow_config()
+                .await
+                .map_err(|e| e.to_string())?;
+            match OnChainEscrow::new(config.rpc_url).vault_snapshot(&escrow) {
+                Ok(vault) => {
+                    let divergences = reconcile(&escrow, vault.as_ref());
+                    if !divergences.is_empty() {
+                        check.errors.extend(divergences);
+                        check.passed = false;
+                        check.risk_level = "critical".to_string();
+                    }
+                }
+                Err(err) => check
+                    .warnings
+                    .push(format!("On-chain state could not be verified: {}", err)),
+            }
+        }
+
+        Ok(check)
     } else {
         Err("Either offer_id or escrow_id must be provided".to_string())
     }
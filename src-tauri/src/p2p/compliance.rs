@@ -319,6 +319,8 @@ mod tests {
             is_active: true,
             completed_trades: 0,
             reputation_required: Some(50.0),
+            pricing: OfferPricing::Fixed,
+            remaining_amount: 100.0,
         }
     }
 
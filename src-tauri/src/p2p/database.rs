@@ -1,4 +1,7 @@
+use super::matching::FILL_EPSILON;
+use super::reputation::NEUTRAL_P2P_SCORE;
 use super::types::*;
+use crate::utils::ensure_column;
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use sqlx::{Pool, Row, Sqlite, SqlitePool};
@@ -184,6 +187,57 @@ impl P2PDatabase {
         .execute(&self.pool)
         .await?;
 
+        ensure_column(&self.pool, "p2p_offers", "pricing", "TEXT").await?;
+        ensure_column(&self.pool, "p2p_offers", "remaining_amount", "REAL").await?;
+        ensure_column(&self.pool, "p2p_escrows", "price_quote", "TEXT").await?;
+        ensure_column(&self.pool, "p2p_escrows", "confirmed_at", "TEXT").await?;
+        ensure_column(&self.pool, "p2p_disputes", "outcome", "TEXT").await?;
+        ensure_column(
+            &self.pool,
+            "p2p_escrows",
+            "mode",
+            "TEXT NOT NULL DEFAULT 'local'",
+        )
+        .await?;
+        ensure_column(&self.pool, "p2p_escrows", "funding_signature", "TEXT").await?;
+        ensure_column(&self.pool, "p2p_escrows", "settlement_signature", "TEXT").await?;
+        ensure_column(&self.pool, "p2p_disputes", "arbitration_deadline", "TEXT").await?;
+        ensure_column(&self.pool, "p2p_disputes", "default_resolution", "TEXT").await?;
+        ensure_column(
+            &self.pool,
+            "p2p_disputes",
+            "resolved_by_default",
+            "INTEGER NOT NULL DEFAULT 0",
+        )
+        .await?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS p2p_pending_transactions (
+                escrow_id TEXT PRIMARY KEY,
+                action TEXT NOT NULL,
+                transaction_data TEXT NOT NULL,
+                multisig_address TEXT,
+                vault_address TEXT,
+                prepared_at TEXT NOT NULL,
+                FOREIGN KEY (escrow_id) REFERENCES p2p_escrows(id)
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS p2p_settings (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
         Ok(())
     }
 
@@ -203,22 +257,26 @@ impl P2PDatabase {
             terms: request.terms,
             time_limit: request.time_limit,
             created_at: Utc::now(),
-            expires_at: None,
+            expires_at: request.expires_at,
             is_active: true,
             completed_trades: 0,
             reputation_required: request.reputation_required,
+            pricing: request.pricing,
+            remaining_amount: request.amount,
         };
 
         let payment_methods_json = serde_json::to_string(&request.payment_methods)?;
+        let pricing_json = serde_json::to_string(&offer.pricing)?;
 
         sqlx::query(
             r#"
             INSERT INTO p2p_offers (
                 id, creator, offer_type, token_address, token_symbol, amount, price, 
                 fiat_currency, payment_methods, min_amount, max_amount, terms, time_limit,
-                created_at, expires_at, is_active, completed_trades, reputation_required
+                created_at, expires_at, is_active, completed_trades, reputation_required,
+                pricing, remaining_amount
             )
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20)
             "#,
         )
         .bind(&offer.id)
@@ -239,6 +297,8 @@ impl P2PDatabase {
         .bind(if offer.is_active { 1 } else { 0 })
         .bind(offer.completed_trades)
         .bind(offer.reputation_required)
+        .bind(&pricing_json)
+        .bind(offer.remaining_amount)
         .execute(&self.pool)
         .await?;
 
@@ -302,6 +362,11 @@ impl P2PDatabase {
     fn row_to_offer(&self, row: sqlx::sqlite::SqliteRow) -> Result<P2POffer> {
         let payment_methods_json: String = row.try_get("payment_methods")?;
         let payment_methods: Vec<String> = serde_json::from_str(&payment_methods_json)?;
+        let pricing = match row.try_get::<Option<String>, _>("pricing")? {
+            Some(json) => serde_json::from_str(&json)?,
+            None => OfferPricing::Fixed,
+        };
+        let amount: f64 = row.try_get("amount")?;
 
         Ok(P2POffer {
             id: row.try_get("id")?,
@@ -309,7 +374,7 @@ impl P2PDatabase {
             offer_type: OfferType::from_str(&row.try_get::<String, _>("offer_type")?)?,
             token_address: row.try_get("token_address")?,
             token_symbol: row.try_get("token_symbol")?,
-            amount: row.try_get("amount")?,
+            amount,
             price: row.try_get("price")?,
             fiat_currency: row.try_get("fiat_currency")?,
             payment_methods,
@@ -326,6 +391,10 @@ impl P2PDatabase {
             is_active: row.try_get::<i64, _>("is_active")? != 0,
             completed_trades: row.try_get("completed_trades")?,
             reputation_required: row.try_get("reputation_required")?,
+            pricing,
+            remaining_amount: row
+                .try_get::<Option<f64>, _>("remaining_amount")?
+                .unwrap_or(amount),
         })
     }
 
@@ -343,7 +412,14 @@ impl P2PDatabase {
         Ok(())
     }
 
-    pub async fn create_escrow(&self, request: CreateEscrowRequest) -> Result<Escrow> {
+    /// Creates the escrow for one fill of an offer, taking its amount out of
+    /// the offer's remaining size in the same transaction. Fails if another
+    /// fill has already taken what was left.
+    pub async fn create_escrow(
+        &self,
+        request: CreateEscrowRequest,
+        quote: PriceQuote,
+    ) -> Result<Escrow> {
         let offer = self
             .get_offer(&request.offer_id)
             .await?
@@ -356,7 +432,7 @@ impl P2PDatabase {
             seller: request.seller,
             amount: request.amount,
             token_address: offer.token_address,
-            fiat_amount: request.fiat_amount,
+            fiat_amount: request.amount * quote.unit_price,
             fiat_currency: offer.fiat_currency,
             state: EscrowState::Created,
             multisig_address: None,
@@ -365,20 +441,51 @@ impl P2PDatabase {
             funded_at: None,
             released_at: None,
             timeout_at: Utc::now() + chrono::Duration::minutes(offer.time_limit as i64),
-            arbitrators: vec![],
+            arbitrators: request.arbiter.into_iter().collect(),
             fee_rate: 0.01,
+            mode: request.mode,
+            funding_signature: None,
+            settlement_signature: None,
+            price_quote: Some(quote),
+            confirmed_at: None,
         };
 
         let arbitrators_json = serde_json::to_string(&escrow.arbitrators)?;
+        let quote_json = serde_json::to_string(&escrow.price_quote)?;
+
+        let mut tx = self.pool.begin().await?;
+
+        let reserved = sqlx::query(
+            r#"
+            UPDATE p2p_offers
+            SET remaining_amount = COALESCE(remaining_amount, amount) - ?1,
+                is_active = CASE
+                    WHEN COALESCE(remaining_amount, amount) - ?1 <= ?2 THEN 0
+                    ELSE is_active
+                END
+            WHERE id = ?3 AND is_active = 1 AND COALESCE(remaining_amount, amount) >= ?1 - ?2
+            "#,
+        )
+        .bind(escrow.amount)
+        .bind(FILL_EPSILON)
+        .bind(&escrow.offer_id)
+        .execute(&mut tx)
+        .await?;
+        if reserved.rows_affected() == 0 {
+            return Err(anyhow::anyhow!(
+                "Offer no longer has {} available",
+                escrow.amount
+            ));
+        }
 
         sqlx::query(
             r#"
             INSERT INTO p2p_escrows (
                 id, offer_id, buyer, seller, amount, token_address, fiat_amount, fiat_currency,
                 state, multisig_address, escrow_pubkey, created_at, funded_at, released_at,
-                timeout_at, arbitrators, fee_rate
+                timeout_at, arbitrators, fee_rate, mode, price_quote
             )
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19)
             "#,
         )
         .bind(&escrow.id)
@@ -398,12 +505,41 @@ impl P2PDatabase {
         .bind(escrow.timeout_at.to_rfc3339())
         .bind(&arbitrators_json)
         .bind(escrow.fee_rate)
-        .execute(&self.pool)
+        .bind(escrow.mode.to_string())
+        .bind(&quote_json)
+        .execute(&mut tx)
         .await?;
 
+        tx.commit().await?;
+
         Ok(escrow)
     }
 
+    /// Returns a cancelled fill's amount to its offer. An offer that was closed
+    /// by running out reopens if it has not expired.
+    pub async fn release_offer_amount(&self, offer_id: &str, amount: f64) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE p2p_offers
+            SET is_active = CASE
+                    WHEN COALESCE(remaining_amount, amount) <= ?1
+                        AND (expires_at IS NULL OR expires_at > ?2) THEN 1
+                    ELSE is_active
+                END,
+                remaining_amount = MIN(amount, COALESCE(remaining_amount, amount) + ?3)
+            WHERE id = ?4
+            "#,
+        )
+        .bind(FILL_EPSILON)
+        .bind(Utc::now().to_rfc3339())
+        .bind(amount)
+        .bind(offer_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
     pub async fn get_escrow(&self, escrow_id: &str) -> Result<Option<Escrow>> {
         let row = sqlx::query(
             r#"
@@ -468,9 +604,143 @@ impl P2PDatabase {
                 .map(|dt| dt.with_timezone(&Utc))?,
             arbitrators,
             fee_rate: row.try_get("fee_rate")?,
+            mode: EscrowMode::from_str(&row.try_get::<String, _>("mode")?)?,
+            funding_signature: row.try_get("funding_signature")?,
+            settlement_signature: row.try_get("settlement_signature")?,
+            price_quote: match row.try_get::<Option<String>, _>("price_quote")? {
+                Some(json) => serde_json::from_str(&json)?,
+                None => None,
+            },
+            confirmed_at: row
+                .try_get::<Option<String>, _>("confirmed_at")?
+                .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+                .map(|dt| dt.with_timezone(&Utc)),
         })
     }
 
+    pub async fn record_escrow_signature(
+        &self,
+        escrow_id: &str,
+        action: EscrowAction,
+        signature: &str,
+    ) -> Result<()> {
+        let column = match action {
+            EscrowAction::Fund => "funding_signature",
+            EscrowAction::Release | EscrowAction::Refund => "settlement_signature",
+        };
+        sqlx::query(&format!(
+            "UPDATE p2p_escrows SET {} = ?1 WHERE id = ?2",
+            column
+        ))
+        .bind(signature)
+        .bind(escrow_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn save_pending_transaction(&self, pending: &PendingEscrowTransaction) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO p2p_pending_transactions (
+                escrow_id, action, transaction_data, multisig_address, vault_address, prepared_at
+            )
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            ON CONFLICT(escrow_id) DO UPDATE SET
+                action = excluded.action,
+                transaction_data = excluded.transaction_data,
+                multisig_address = excluded.multisig_address,
+                vault_address = excluded.vault_address,
+                prepared_at = excluded.prepared_at
+            "#,
+        )
+        .bind(&pending.escrow_id)
+        .bind(pending.action.to_string())
+        .bind(&pending.transaction)
+        .bind(&pending.multisig_address)
+        .bind(&pending.vault_address)
+        .bind(pending.prepared_at.to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Returns the stored transaction; signer lists are filled in by the caller
+    /// from the decoded transaction.
+    pub async fn get_pending_transaction(
+        &self,
+        escrow_id: &str,
+    ) -> Result<Option<PendingEscrowTransaction>> {
+        let row = sqlx::query(
+            r#"
+            SELECT * FROM p2p_pending_transactions WHERE escrow_id = ?1
+            "#,
+        )
+        .bind(escrow_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+
+        Ok(Some(PendingEscrowTransaction {
+            escrow_id: row.try_get("escrow_id")?,
+            action: EscrowAction::from_str(&row.try_get::<String, _>("action")?)?,
+            transaction: row.try_get("transaction_data")?,
+            required_signers: vec![],
+            missing_signers: vec![],
+            multisig_address: row.try_get("multisig_address")?,
+            vault_address: row.try_get("vault_address")?,
+            prepared_at: DateTime::parse_from_rfc3339(&row.try_get::<String, _>("prepared_at")?)
+                .map(|dt| dt.with_timezone(&Utc))?,
+        }))
+    }
+
+    pub async fn delete_pending_transaction(&self, escrow_id: &str) -> Result<()> {
+        sqlx::query(
+            r#"
+            DELETE FROM p2p_pending_transactions WHERE escrow_id = ?1
+            "#,
+        )
+        .bind(escrow_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_escrow_config(&self) -> Result<P2PEscrowConfig> {
+        let value: Option<String> = sqlx::query_scalar(
+            r#"
+            SELECT value FROM p2p_settings WHERE key = 'escrow_config'
+            "#,
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match value {
+            Some(json) => Ok(serde_json::from_str(&json)?),
+            None => Ok(P2PEscrowConfig::default()),
+        }
+    }
+
+    pub async fn set_escrow_config(&self, config: &P2PEscrowConfig) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO p2p_settings (key, value) VALUES ('escrow_config', ?1)
+            ON CONFLICT(key) DO UPDATE SET value = excluded.value
+            "#,
+        )
+        .bind(serde_json::to_string(config)?)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
     pub async fn update_escrow_state(
         &self,
         escrow_id: &str,
@@ -484,6 +754,9 @@ impl P2PDatabase {
         if state == EscrowState::Funded {
             query.push_str(", funded_at = ?2");
             params.push(Utc::now().to_rfc3339());
+        } else if state == EscrowState::Confirmed {
+            query.push_str(", confirmed_at = ?2");
+            params.push(Utc::now().to_rfc3339());
         } else if state == EscrowState::Released || state == EscrowState::Completed {
             query.push_str(", released_at = ?2");
             params.push(Utc::now().to_rfc3339());
@@ -504,7 +777,12 @@ impl P2PDatabase {
         Ok(())
     }
 
-    pub async fn create_dispute(&self, request: FileDisputeRequest) -> Result<Dispute> {
+    pub async fn create_dispute(
+        &self,
+        request: FileDisputeRequest,
+        arbitration_deadline: DateTime<Utc>,
+        default_resolution: DisputeResolution,
+    ) -> Result<Dispute> {
         let dispute = Dispute {
             id: format!("dispute_{}", Uuid::new_v4()),
             escrow_id: request.escrow_id,
@@ -518,15 +796,19 @@ impl P2PDatabase {
             resolved_at: None,
             resolution: None,
             votes: vec![],
+            arbitration_deadline: Some(arbitration_deadline),
+            default_resolution: Some(default_resolution),
+            resolved_by_default: false,
+            outcome: None,
         };
 
         sqlx::query(
             r#"
             INSERT INTO p2p_disputes (
                 id, escrow_id, filed_by, reason, description, status, arbitrator,
-                created_at, resolved_at, resolution
+                created_at, resolved_at, resolution, arbitration_deadline, default_resolution
             )
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
             "#,
         )
         .bind(&dispute.id)
@@ -539,6 +821,8 @@ impl P2PDatabase {
         .bind(dispute.created_at.to_rfc3339())
         .bind(dispute.resolved_at.map(|dt| dt.to_rfc3339()))
         .bind(&dispute.resolution)
+        .bind(arbitration_deadline.to_rfc3339())
+        .bind(default_resolution.to_string())
         .execute(&self.pool)
         .await?;
 
@@ -556,33 +840,113 @@ impl P2PDatabase {
         .await?;
 
         if let Some(row) = row {
-            let dispute_id: String = row.try_get("id")?;
-            let evidence = self.get_dispute_evidence(&dispute_id).await?;
-            let votes = self.get_dispute_votes(&dispute_id).await?;
-
-            Ok(Some(Dispute {
-                id: dispute_id,
-                escrow_id: row.try_get("escrow_id")?,
-                filed_by: row.try_get("filed_by")?,
-                reason: row.try_get("reason")?,
-                description: row.try_get("description")?,
-                evidence,
-                status: DisputeStatus::from_str(&row.try_get::<String, _>("status")?)?,
-                arbitrator: row.try_get("arbitrator")?,
-                created_at: DateTime::parse_from_rfc3339(&row.try_get::<String, _>("created_at")?)
-                    .map(|dt| dt.with_timezone(&Utc))?,
-                resolved_at: row
-                    .try_get::<Option<String>, _>("resolved_at")?
-                    .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
-                    .map(|dt| dt.with_timezone(&Utc)),
-                resolution: row.try_get("resolution")?,
-                votes,
-            }))
+            Ok(Some(self.row_to_dispute(row).await?))
         } else {
             Ok(None)
         }
     }
 
+    /// Unresolved disputes whose arbitration deadline has passed.
+    pub async fn list_overdue_disputes(&self, now: DateTime<Utc>) -> Result<Vec<Dispute>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM p2p_disputes
+            WHERE status != 'resolved'
+              AND arbitration_deadline IS NOT NULL
+              AND arbitration_deadline <= ?1
+            ORDER BY arbitration_deadline ASC
+            "#,
+        )
+        .bind(now.to_rfc3339())
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut disputes = Vec::new();
+        for row in rows {
+            disputes.push(self.row_to_dispute(row).await?);
+        }
+
+        Ok(disputes)
+    }
+
+    pub async fn record_dispute_outcome(
+        &self,
+        dispute_id: &str,
+        outcome: DisputeResolution,
+        by_default: bool,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE p2p_disputes SET outcome = ?1, resolved_by_default = ?2 WHERE id = ?3
+            "#,
+        )
+        .bind(outcome.to_string())
+        .bind(if by_default { 1 } else { 0 })
+        .bind(dispute_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Disputes on any escrow the trader was a party to.
+    pub async fn list_trader_disputes(&self, address: &str) -> Result<Vec<Dispute>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT d.* FROM p2p_disputes d
+            JOIN p2p_escrows e ON e.id = d.escrow_id
+            WHERE e.buyer = ?1 OR e.seller = ?1
+            ORDER BY d.created_at DESC
+            "#,
+        )
+        .bind(address)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut disputes = Vec::new();
+        for row in rows {
+            disputes.push(self.row_to_dispute(row).await?);
+        }
+
+        Ok(disputes)
+    }
+
+    async fn row_to_dispute(&self, row: sqlx::sqlite::SqliteRow) -> Result<Dispute> {
+        let dispute_id: String = row.try_get("id")?;
+        let evidence = self.get_dispute_evidence(&dispute_id).await?;
+        let votes = self.get_dispute_votes(&dispute_id).await?;
+
+        Ok(Dispute {
+            id: dispute_id,
+            escrow_id: row.try_get("escrow_id")?,
+            filed_by: row.try_get("filed_by")?,
+            reason: row.try_get("reason")?,
+            description: row.try_get("description")?,
+            evidence,
+            status: DisputeStatus::from_str(&row.try_get::<String, _>("status")?)?,
+            arbitrator: row.try_get("arbitrator")?,
+            created_at: DateTime::parse_from_rfc3339(&row.try_get::<String, _>("created_at")?)
+                .map(|dt| dt.with_timezone(&Utc))?,
+            resolved_at: row
+                .try_get::<Option<String>, _>("resolved_at")?
+                .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+                .map(|dt| dt.with_timezone(&Utc)),
+            resolution: row.try_get("resolution")?,
+            votes,
+            arbitration_deadline: row
+                .try_get::<Option<String>, _>("arbitration_deadline")?
+                .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+                .map(|dt| dt.with_timezone(&Utc)),
+            default_resolution: row
+                .try_get::<Option<String>, _>("default_resolution")?
+                .and_then(|s| DisputeResolution::from_str(&s).ok()),
+            resolved_by_default: row.try_get::<i64, _>("resolved_by_default")? != 0,
+            outcome: row
+                .try_get::<Option<String>, _>("outcome")?
+                .and_then(|s| DisputeResolution::from_str(&s).ok()),
+        })
+    }
+
     pub async fn submit_evidence(&self, request: SubmitEvidenceRequest) -> Result<DisputeEvidence> {
         let evidence = DisputeEvidence {
             id: format!("evidence_{}", Uuid::new_v4()),
@@ -798,12 +1162,13 @@ impl P2PDatabase {
                     .map(|dt| dt.with_timezone(&Utc)),
                 verified: row.try_get::<i64, _>("verified")? != 0,
                 verification_level: row.try_get("verification_level")?,
+                reputation_breakdown: None,
             })
         } else {
             let profile = TraderProfile {
                 address: address.to_string(),
                 username: None,
-                reputation_score: 50.0,
+                reputation_score: NEUTRAL_P2P_SCORE,
                 total_trades: 0,
                 successful_trades: 0,
                 cancelled_trades: 0,
@@ -813,6 +1178,7 @@ impl P2PDatabase {
                 last_trade_at: None,
                 verified: false,
                 verification_level: 0,
+                reputation_breakdown: None,
             };
 
             sqlx::query(
@@ -857,14 +1223,6 @@ impl P2PDatabase {
             profile.disputed_trades
         };
 
-        let success_rate = new_successful as f64 / new_total as f64;
-        let dispute_rate = new_disputed as f64 / new_total as f64;
-        let cancel_rate = new_cancelled as f64 / new_total as f64;
-
-        let reputation_score =
-            50.0 + (success_rate * 40.0) - (dispute_rate * 30.0) - (cancel_rate * 20.0);
-        let reputation_score = reputation_score.max(0.0).min(100.0);
-
         let now = Utc::now().to_rfc3339();
         let first_trade = profile
             .first_trade_at
@@ -874,13 +1232,11 @@ impl P2PDatabase {
         sqlx::query(
             r#"
             UPDATE p2p_trader_profiles
-            SET reputation_score = ?1, total_trades = ?2, successful_trades = ?3,
-                cancelled_trades = ?4, disputed_trades = ?5, first_trade_at = ?6,
-                last_trade_at = ?7
-            WHERE address = ?8
+            SET total_trades = ?1, successful_trades = ?2, cancelled_trades = ?3,
+                disputed_trades = ?4, first_trade_at = ?5, last_trade_at = ?6
+            WHERE address = ?7
             "#,
         )
-        .bind(reputation_score)
         .bind(new_total)
         .bind(new_successful)
         .bind(new_cancelled)
@@ -894,6 +1250,22 @@ impl P2PDatabase {
         Ok(())
     }
 
+    /// Stores the score computed by `reputation::refresh_trader_reputation`.
+    pub async fn set_trader_reputation_score(&self, address: &str, score: f64) -> Result<()> {
+        self.get_or_create_trader_profile(address).await?;
+        sqlx::query(
+            r#"
+            UPDATE p2p_trader_profiles SET reputation_score = ?1 WHERE address = ?2
+            "#,
+        )
+        .bind(score)
+        .bind(address)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
     pub async fn get_stats(&self) -> Result<P2PStats> {
         let total_offers: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM p2p_offers")
             .fetch_one(&self.pool)
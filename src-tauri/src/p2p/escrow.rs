@@ -202,6 +202,11 @@ mod tests {
             timeout_at: Utc::now() + chrono::Duration::minutes(30),
             arbitrators: vec![],
             fee_rate: 0.01,
+            mode: EscrowMode::Local,
+            funding_signature: None,
+            settlement_signature: None,
+            price_quote: None,
+            confirmed_at: None,
         }
     }
 
@@ -1,13 +1,66 @@
+use super::oracle::P2PPriceOracle;
 use super::types::*;
 use crate::security::reputation::WalletReputation;
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use chrono::{DateTime, Utc};
 use rand::{seq::SliceRandom, Rng};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+
+/// Tolerance for comparing token amounts, so float rounding never leaves an
+/// unfillable sliver on an offer.
+pub const FILL_EPSILON: f64 = 1e-9;
+
+/// Checks that `amount` can be taken from the offer as a single fill. A fill
+/// below the creator's minimum is only allowed when it takes the whole remainder.
+pub fn check_fill(offer: &P2POffer, amount: f64, now: DateTime<Utc>) -> Result<()> {
+    if !offer.is_active {
+        return Err(anyhow!("Offer is no longer active"));
+    }
+    if offer.expires_at.is_some_and(|expires| expires <= now) {
+        return Err(anyhow!("Offer has expired"));
+    }
+    if !amount.is_finite() || amount <= 0.0 {
+        return Err(anyhow!("Fill amount must be positive"));
+    }
+    if amount > offer.remaining_amount + FILL_EPSILON {
+        return Err(anyhow!(
+            "Only {} {} remains on this offer",
+            offer.remaining_amount,
+            offer.token_symbol
+        ));
+    }
+    if let Some(max) = offer.max_amount {
+        if amount > max + FILL_EPSILON {
+            return Err(anyhow!("Fill exceeds the maximum of {}", max));
+        }
+    }
+    if let Some(min) = offer.min_amount {
+        let takes_remainder = (offer.remaining_amount - amount).abs() <= FILL_EPSILON;
+        if amount + FILL_EPSILON < min && !takes_remainder {
+            return Err(anyhow!("Fill is below the minimum of {}", min));
+        }
+    }
+    Ok(())
+}
+
+/// Whether a trader's P2P reputation meets the minimum the offer creator set.
+pub fn meets_reputation(offer: &P2POffer, p2p_score: f64) -> bool {
+    offer
+        .reputation_required
+        .map_or(true, |required| p2p_score >= required)
+}
+
+/// Whether both sides of a trade belong to the same user: the same address, or
+/// two wallets managed on this device.
+pub fn is_self_match(creator: &str, counterparty: &str, own_wallets: &HashSet<String>) -> bool {
+    creator == counterparty || (own_wallets.contains(creator) && own_wallets.contains(counterparty))
+}
 
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct TraderMatch {
     pub offer: P2POffer,
+    pub quote: PriceQuote,
+    pub available_amount: f64,
     pub compatibility_score: f64,
     pub reputation_delta: f64,
     pub geographic_score: f64,
@@ -18,6 +71,7 @@ pub struct TraderMatch {
 pub struct LocalMatcher {
     max_distance_km: f64,
     payment_priority: HashMap<String, i32>,
+    own_wallets: HashSet<String>,
 }
 
 impl LocalMatcher {
@@ -25,9 +79,17 @@ impl LocalMatcher {
         Self {
             max_distance_km: 100.0,
             payment_priority: HashMap::new(),
+            own_wallets: HashSet::new(),
         }
     }
 
+    /// Wallets that belong to the matching user; offers created by any of
+    /// them are never matched.
+    pub fn with_own_wallets(mut self, wallets: impl IntoIterator<Item = String>) -> Self {
+        self.own_wallets.extend(wallets);
+        self
+    }
+
     pub fn with_max_distance(mut self, distance_km: f64) -> Self {
         self.max_distance_km = distance_km;
         self
@@ -43,20 +105,33 @@ impl LocalMatcher {
         offers: &[P2POffer],
         user_profile: &TraderProfile,
         user_reputation: Option<&WalletReputation>,
+        oracle: &P2PPriceOracle,
+        now: DateTime<Utc>,
     ) -> Vec<TraderMatch> {
         let mut matches = Vec::new();
+        let mut own_wallets = self.own_wallets.clone();
+        own_wallets.insert(user_profile.address.clone());
 
         for offer in offers {
-            if offer.creator == user_profile.address {
+            if is_self_match(&offer.creator, &user_profile.address, &own_wallets) {
+                continue;
+            }
+
+            // The largest single fill still available.
+            let available_amount = offer.max_amount.map_or(offer.remaining_amount, |max| {
+                max.min(offer.remaining_amount)
+            });
+            if available_amount <= FILL_EPSILON || check_fill(offer, available_amount, now).is_err()
+            {
                 continue;
             }
 
-            if let Some(required) = offer.reputation_required {
-                if let Some(rep) = user_reputation {
-                    if rep.trust_score < required {
-                        continue;
-                    }
-                }
+            let Ok(quote) = oracle.quote(offer, now) else {
+                continue;
+            };
+
+            if !meets_reputation(offer, user_profile.reputation_score) {
+                continue;
             }
 
             let compatibility_score = rand::random_range(0.6..0.95);
@@ -77,6 +152,8 @@ impl LocalMatcher {
 
             matches.push(TraderMatch {
                 offer: offer.clone(),
+                quote,
+                available_amount,
                 compatibility_score,
                 reputation_delta,
                 geographic_score,
@@ -94,6 +171,7 @@ impl LocalMatcher {
                     last_trade_at: Some(Utc::now()),
                     verified: rand::random::<f64>() < 0.6,
                     verification_level: rand::random_range(0..3),
+                    reputation_breakdown: None,
                 },
             });
         }
@@ -112,3 +190,91 @@ impl Default for LocalMatcher {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn offer(remaining_amount: f64) -> P2POffer {
+        P2POffer {
+            id: "offer_1".to_string(),
+            creator: "creator_wallet".to_string(),
+            offer_type: OfferType::Sell,
+            token_address: "sol_mint".to_string(),
+            token_symbol: "SOL".to_string(),
+            amount: 100.0,
+            price: 150.0,
+            fiat_currency: "USD".to_string(),
+            payment_methods: vec!["Bank Transfer".to_string()],
+            min_amount: Some(20.0),
+            max_amount: None,
+            terms: None,
+            time_limit: 30,
+            created_at: Utc::now(),
+            expires_at: None,
+            is_active: true,
+            completed_trades: 0,
+            reputation_required: None,
+            pricing: OfferPricing::Fixed,
+            remaining_amount,
+        }
+    }
+
+    #[test]
+    fn test_partial_fills_respect_minimum_and_remainder() {
+        let now = Utc::now();
+        assert!(check_fill(&offer(100.0), 60.0, now).is_ok());
+        assert!(check_fill(&offer(100.0), 120.0, now).is_err());
+        assert!(check_fill(&offer(40.0), 10.0, now).is_err());
+        assert!(check_fill(&offer(40.0), 40.0, now).is_ok());
+        // A remainder under the minimum can still be taken whole.
+        assert!(check_fill(&offer(15.0), 15.0, now).is_ok());
+
+        let mut expired = offer(100.0);
+        expired.expires_at = Some(now - chrono::Duration::minutes(1));
+        assert!(check_fill(&expired, 60.0, now).is_err());
+    }
+
+    #[test]
+    fn test_self_match_spans_own_wallets() {
+        let own: HashSet<String> = ["wallet_a", "wallet_b"]
+            .iter()
+            .map(|w| w.to_string())
+            .collect();
+        assert!(is_self_match("wallet_a", "wallet_a", &HashSet::new()));
+        assert!(is_self_match("wallet_a", "wallet_b", &own));
+        assert!(!is_self_match("wallet_a", "stranger", &own));
+
+        let mut oracle = P2PPriceOracle::new();
+        oracle.record("sol_mint", 150.0, Utc::now());
+        let profile = TraderProfile {
+            address: "wallet_b".to_string(),
+            username: None,
+            reputation_score: 50.0,
+            total_trades: 0,
+            successful_trades: 0,
+            cancelled_trades: 0,
+            disputed_trades: 0,
+            avg_completion_time: 0,
+            first_trade_at: None,
+            last_trade_at: None,
+            verified: false,
+            verification_level: 0,
+            reputation_breakdown: None,
+        };
+        let mut own_offer = offer(100.0);
+        own_offer.creator = "wallet_a".to_string();
+        let matcher = LocalMatcher::new().with_own_wallets(own);
+
+        let matches = matcher.match_offers(
+            &[own_offer, offer(100.0)],
+            &profile,
+            None,
+            &oracle,
+            Utc::now(),
+        );
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].offer.creator, "creator_wallet");
+        assert_eq!(matches[0].quote.unit_price, 150.0);
+    }
+}
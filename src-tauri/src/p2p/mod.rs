@@ -1,8 +1,12 @@
+pub mod arbitration;
 pub mod commands;
 pub mod compliance;
 pub mod database;
 pub mod escrow;
 pub mod matching;
+pub mod onchain;
+pub mod oracle;
+pub mod reputation;
 pub mod types;
 
 pub use commands::*;
@@ -10,10 +14,12 @@ pub use compliance::ComplianceChecker;
 pub use database::P2PDatabase;
 pub use escrow::{EscrowSmartContract, EscrowStateMachine};
 pub use matching::LocalMatcher;
+pub use oracle::{P2PPriceOracle, SharedP2PPriceOracle};
 pub use types::*;
 
+use crate::config::DataPaths;
 use std::sync::Arc;
-use tauri::{AppHandle, Manager};
+use tauri::AppHandle;
 use tokio::sync::RwLock;
 
 pub type SharedP2PDatabase = Arc<RwLock<P2PDatabase>>;
@@ -21,9 +27,7 @@ pub type SharedP2PDatabase = Arc<RwLock<P2PDatabase>>;
 pub async fn init_p2p_system(
     app_handle: &AppHandle,
 ) -> Result<SharedP2PDatabase, Box<dyn std::error::Error>> {
-    let app_dir = app_handle
-        .path()
-        .app_data_dir()?;
+    let app_dir = app_handle.app_data_root()?;
 
     std::fs::create_dir_all(&app_dir)?;
     let db_path = app_dir.join("p2p.db");
@@ -0,0 +1,546 @@
+//! On-chain custody for P2P escrows.
+//!
+//! Escrowed tokens sit in a vault token account owned by an SPL Token multisig
+//! with the buyer, seller and arbiter as signers and a threshold of two, so no
+//! single party can move them. Transactions are prepared here, collect their
+//! signatures from each party's wallet, and are submitted once complete.
+
+use super::types::*;
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose, Engine as _};
+use chrono::Utc;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+    system_instruction, system_program,
+    transaction::Transaction,
+};
+use std::str::FromStr;
+
+pub const TOKEN_PROGRAM_ID: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: &str = "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL";
+/// Keystore entry holding the arbiter keypair when this install acts as arbiter.
+pub const ARBITER_KEYSTORE_KEY: &str = "p2p_arbiter_keypair";
+
+const MULTISIG_ACCOUNT_LEN: usize = 355;
+const MULTISIG_THRESHOLD: u8 = 2;
+const TOKEN_ACCOUNT_LEN: usize = 165;
+const MINT_DECIMALS_OFFSET: usize = 44;
+
+// SPL Token instruction tags.
+const TRANSFER_CHECKED: u8 = 12;
+const INITIALIZE_MULTISIG_2: u8 = 19;
+// Associated Token Account instruction tags.
+const CREATE_IDEMPOTENT: u8 = 1;
+
+pub fn parse_pubkey(label: &str, value: &str) -> Result<Pubkey> {
+    Pubkey::from_str(value).map_err(|_| anyhow!("Invalid {} address: {}", label, value))
+}
+
+fn token_program() -> Pubkey {
+    Pubkey::from_str(TOKEN_PROGRAM_ID).expect("valid token program id")
+}
+
+fn associated_token_program() -> Pubkey {
+    Pubkey::from_str(ASSOCIATED_TOKEN_PROGRAM_ID).expect("valid associated token program id")
+}
+
+pub fn associated_token_address(owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+    let token_program = token_program();
+    Pubkey::find_program_address(
+        &[owner.as_ref(), token_program.as_ref(), mint.as_ref()],
+        &associated_token_program(),
+    )
+    .0
+}
+
+fn initialize_multisig(multisig: &Pubkey, signers: &[Pubkey], threshold: u8) -> Instruction {
+    let mut accounts = vec![AccountMeta::new(*multisig, false)];
+    accounts.extend(signers.iter().map(|s| AccountMeta::new_readonly(*s, false)));
+    Instruction {
+        program_id: token_program(),
+        accounts,
+        data: vec![INITIALIZE_MULTISIG_2, threshold],
+    }
+}
+
+fn create_associated_token_account(payer: &Pubkey, owner: &Pubkey, mint: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: associated_token_program(),
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(associated_token_address(owner, mint), false),
+            AccountMeta::new_readonly(*owner, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(token_program(), false),
+        ],
+        data: vec![CREATE_IDEMPOTENT],
+    }
+}
+
+/// `multisig_signers` is empty when `authority` signs directly.
+fn transfer_checked(
+    source: &Pubkey,
+    mint: &Pubkey,
+    destination: &Pubkey,
+    authority: &Pubkey,
+    multisig_signers: &[Pubkey],
+    amount: u64,
+    decimals: u8,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*source, false),
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new(*destination, false),
+        AccountMeta::new_readonly(*authority, multisig_signers.is_empty()),
+    ];
+    accounts.extend(
+        multisig_signers
+            .iter()
+            .map(|s| AccountMeta::new_readonly(*s, true)),
+    );
+
+    let mut data = vec![TRANSFER_CHECKED];
+    data.extend_from_slice(&amount.to_le_bytes());
+    data.push(decimals);
+
+    Instruction {
+        program_id: token_program(),
+        accounts,
+        data,
+    }
+}
+
+pub fn to_base_units(amount: f64, decimals: u8) -> Result<u64> {
+    let scaled = (amount * 10f64.powi(decimals as i32)).round();
+    if !scaled.is_finite() || scaled <= 0.0 || scaled > u64::MAX as f64 {
+        return Err(anyhow!("Invalid escrow amount: {}", amount));
+    }
+    Ok(scaled as u64)
+}
+
+/// The arbiter is the third multisig signer, recorded in `arbitrators`.
+pub fn escrow_arbiter(escrow: &Escrow) -> Result<Pubkey> {
+    let arbiter = escrow
+        .arbitrators
+        .first()
+        .ok_or_else(|| anyhow!("On-chain escrow has no arbiter"))?;
+    parse_pubkey("arbiter", arbiter)
+}
+
+pub fn encode_transaction(transaction: &Transaction) -> Result<String> {
+    Ok(general_purpose::STANDARD.encode(bincode::serialize(transaction)?))
+}
+
+pub fn decode_transaction(encoded: &str) -> Result<Transaction> {
+    let bytes = general_purpose::STANDARD
+        .decode(encoded.as_bytes())
+        .map_err(|e| anyhow!("Invalid base64 transaction: {}", e))?;
+    bincode::deserialize(&bytes).map_err(|e| anyhow!("Invalid transaction bytes: {}", e))
+}
+
+pub fn required_signers(transaction: &Transaction) -> Vec<Pubkey> {
+    let count = transaction.message.header.num_required_signatures as usize;
+    transaction.message.account_keys[..count].to_vec()
+}
+
+pub fn missing_signers(transaction: &Transaction) -> Vec<Pubkey> {
+    required_signers(transaction)
+        .into_iter()
+        .zip(transaction.signatures.iter())
+        .filter(|(_, signature)| **signature == Signature::default())
+        .map(|(key, _)| key)
+        .collect()
+}
+
+/// Copies the signatures present on `signed` into `pending`. The transactions
+/// must carry the same message, and every copied signature must verify.
+pub fn merge_signatures(pending: &mut Transaction, signed: &Transaction) -> Result<usize> {
+    let message = pending.message_data();
+    if signed.message_data() != message {
+        return Err(anyhow!(
+            "Signed transaction does not match the prepared transaction"
+        ));
+    }
+
+    let signers = required_signers(pending);
+    let mut added = 0;
+    for (index, key) in signers.iter().enumerate() {
+        let Some(signature) = signed.signatures.get(index) else {
+            continue;
+        };
+        if *signature == Signature::default() || pending.signatures[index] == *signature {
+            continue;
+        }
+        if !signature.verify(key.as_ref(), &message) {
+            return Err(anyhow!("Invalid signature for {}", key));
+        }
+        pending.signatures[index] = *signature;
+        added += 1;
+    }
+
+    Ok(added)
+}
+
+/// Fills in the signer lists of a stored pending transaction.
+pub fn describe_pending(mut pending: PendingEscrowTransaction) -> Result<PendingEscrowTransaction> {
+    let transaction = decode_transaction(&pending.transaction)?;
+    pending.required_signers = required_signers(&transaction)
+        .iter()
+        .map(|k| k.to_string())
+        .collect();
+    pending.missing_signers = missing_signers(&transaction)
+        .iter()
+        .map(|k| k.to_string())
+        .collect();
+    Ok(pending)
+}
+
+/// The token account backing an on-chain escrow, as currently seen on chain.
+#[derive(Debug, Clone)]
+pub struct VaultSnapshot {
+    pub mint: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub decimals: u8,
+}
+
+/// Differences between the local escrow record and the vault on chain.
+pub fn reconcile(escrow: &Escrow, vault: Option<&VaultSnapshot>) -> Vec<String> {
+    let mut divergences = Vec::new();
+    let holds_funds = matches!(
+        escrow.state,
+        EscrowState::Funded | EscrowState::Confirmed | EscrowState::Disputed
+    );
+
+    let Some(vault) = vault else {
+        if holds_funds {
+            divergences.push("Escrow is marked funded but its vault does not exist".to_string());
+        }
+        return divergences;
+    };
+
+    if vault.mint.to_string() != escrow.token_address {
+        divergences.push(format!(
+            "Vault holds {} instead of {}",
+            vault.mint, escrow.token_address
+        ));
+    }
+    if escrow.multisig_address.as_deref() != Some(vault.owner.to_string().as_str()) {
+        divergences.push(format!(
+            "Vault is owned by {} instead of the escrow multisig",
+            vault.owner
+        ));
+    }
+
+    let expected = if holds_funds {
+        to_base_units(escrow.amount, vault.decimals).unwrap_or(0)
+    } else {
+        0
+    };
+    if matches!(escrow.state, EscrowState::Created) && vault.amount > 0 {
+        divergences.push("Vault holds tokens before the escrow was marked funded".to_string());
+    } else if !matches!(escrow.state, EscrowState::Created) && vault.amount != expected {
+        divergences.push(format!(
+            "Vault balance is {} base units, local record expects {}",
+            vault.amount, expected
+        ));
+    }
+    if holds_funds && escrow.funding_signature.is_none() {
+        divergences.push("Escrow is funded but no funding signature is recorded".to_string());
+    }
+
+    divergences
+}
+
+pub struct OnChainEscrow {
+    rpc_client: RpcClient,
+}
+
+impl OnChainEscrow {
+    pub fn new(rpc_url: String) -> Self {
+        Self {
+            rpc_client: RpcClient::new(rpc_url),
+        }
+    }
+
+    fn mint_decimals(&self, mint: &Pubkey) -> Result<u8> {
+        let data = self.rpc_client.get_account_data(mint)?;
+        data.get(MINT_DECIMALS_OFFSET)
+            .copied()
+            .ok_or_else(|| anyhow!("{} is not a token mint", mint))
+    }
+
+    /// Builds the funding transaction: creates the 2-of-3 multisig, its vault
+    /// token account, and moves the buyer's tokens into the vault. The new
+    /// multisig account signs here; the buyer's wallet adds the other signature.
+    pub fn prepare_funding(&self, escrow: &Escrow) -> Result<PendingEscrowTransaction> {
+        let buyer = parse_pubkey("buyer", &escrow.buyer)?;
+        let seller = parse_pubkey("seller", &escrow.seller)?;
+        let arbiter = escrow_arbiter(escrow)?;
+        let mint = parse_pubkey("token", &escrow.token_address)?;
+        let decimals = self.mint_decimals(&mint)?;
+        let amount = to_base_units(escrow.amount, decimals)?;
+
+        let multisig = Keypair::new();
+        let vault = associated_token_address(&multisig.pubkey(), &mint);
+        let rent = self
+            .rpc_client
+            .get_minimum_balance_for_rent_exemption(MULTISIG_ACCOUNT_LEN)?;
+
+        let instructions = vec![
+            system_instruction::create_account(
+                &buyer,
+                &multisig.pubkey(),
+                rent,
+                MULTISIG_ACCOUNT_LEN as u64,
+                &token_program(),
+            ),
+            initialize_multisig(
+                &multisig.pubkey(),
+                &[buyer, seller, arbiter],
+                MULTISIG_THRESHOLD,
+            ),
+            create_associated_token_account(&buyer, &multisig.pubkey(), &mint),
+            transfer_checked(
+                &associated_token_address(&buyer, &mint),
+                &mint,
+                &vault,
+                &buyer,
+                &[],
+                amount,
+                decimals,
+            ),
+        ];
+
+        let mut transaction = Transaction::new_with_payer(&instructions, Some(&buyer));
+        let blockhash = self.rpc_client.get_latest_blockhash()?;
+        transaction.partial_sign(&[&multisig], blockhash);
+
+        describe_pending(PendingEscrowTransaction {
+            escrow_id: escrow.id.clone(),
+            action: EscrowAction::Fund,
+            transaction: encode_transaction(&transaction)?,
+            required_signers: vec![],
+            missing_signers: vec![],
+            multisig_address: Some(multisig.pubkey().to_string()),
+            vault_address: Some(vault.to_string()),
+            prepared_at: Utc::now(),
+        })
+    }
+
+    /// Builds a release (to the seller) or refund (to the buyer) out of the
+    /// vault. The recipient pays the fee and signs with `cosigner`, which is
+    /// the other party for a mutual settlement or the arbiter for a ruling.
+    pub fn prepare_settlement(
+        &self,
+        escrow: &Escrow,
+        action: EscrowAction,
+        cosigner: &Pubkey,
+    ) -> Result<PendingEscrowTransaction> {
+        let recipient = match action {
+            EscrowAction::Release => parse_pubkey("seller", &escrow.seller)?,
+            EscrowAction::Refund => parse_pubkey("buyer", &escrow.buyer)?,
+            EscrowAction::Fund => return Err(anyhow!("Funding is not a settlement")),
+        };
+        if *cosigner == recipient {
+            return Err(anyhow!("Settlement needs two different signers"));
+        }
+        let multisig = parse_pubkey(
+            "multisig",
+            escrow
+                .multisig_address
+                .as_deref()
+                .ok_or_else(|| anyhow!("Escrow has not been funded on chain"))?,
+        )?;
+        let vault = parse_pubkey(
+            "vault",
+            escrow
+                .escrow_pubkey
+                .as_deref()
+                .ok_or_else(|| anyhow!("Escrow has not been funded on chain"))?,
+        )?;
+        let mint = parse_pubkey("token", &escrow.token_address)?;
+        let decimals = self.mint_decimals(&mint)?;
+        let amount = to_base_units(escrow.amount, decimals)?;
+
+        let instructions = vec![
+            create_associated_token_account(&recipient, &recipient, &mint),
+            transfer_checked(
+                &vault,
+                &mint,
+                &associated_token_address(&recipient, &mint),
+                &multisig,
+                &[recipient, *cosigner],
+                amount,
+                decimals,
+            ),
+        ];
+
+        let mut transaction = Transaction::new_with_payer(&instructions, Some(&recipient));
+        transaction.message.recent_blockhash = self.rpc_client.get_latest_blockhash()?;
+
+        describe_pending(PendingEscrowTransaction {
+            escrow_id: escrow.id.clone(),
+            action,
+            transaction: encode_transaction(&transaction)?,
+            required_signers: vec![],
+            missing_signers: vec![],
+            multisig_address: escrow.multisig_address.clone(),
+            vault_address: escrow.escrow_pubkey.clone(),
+            prepared_at: Utc::now(),
+        })
+    }
+
+    /// Submits a fully signed pending transaction and returns its signature.
+    pub fn submit(&self, pending: &PendingEscrowTransaction) -> Result<String> {
+        let transaction = decode_transaction(&pending.transaction)?;
+        let missing = missing_signers(&transaction);
+        if !missing.is_empty() {
+            return Err(anyhow!(
+                "Transaction is still missing signatures from: {}",
+                missing
+                    .iter()
+                    .map(|k| k.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+
+        let signature = self
+            .rpc_client
+            .send_and_confirm_transaction(&transaction)
+            .map_err(|e| {
+                anyhow!(
+                    "Failed to submit {} transaction (prepare it again if the blockhash expired): {}",
+                    pending.action,
+                    e
+                )
+            })?;
+        Ok(signature.to_string())
+    }
+
+    pub fn vault_snapshot(&self, escrow: &Escrow) -> Result<Option<VaultSnapshot>> {
+        let Some(vault) = escrow.escrow_pubkey.as_deref() else {
+            return Ok(None);
+        };
+        let vault = parse_pubkey("vault", vault)?;
+        let Some(account) = self
+            .rpc_client
+            .get_account_with_commitment(&vault, self.rpc_client.commitment())?
+            .value
+        else {
+            return Ok(None);
+        };
+        if account.owner != token_program() || account.data.len() < TOKEN_ACCOUNT_LEN {
+            return Err(anyhow!("{} is not a token account", vault));
+        }
+
+        let data = &account.data;
+        let mint = Pubkey::try_from(&data[0..32]).map_err(|_| anyhow!("Invalid vault data"))?;
+        let owner = Pubkey::try_from(&data[32..64]).map_err(|_| anyhow!("Invalid vault data"))?;
+        let amount = u64::from_le_bytes(data[64..72].try_into()?);
+
+        Ok(Some(VaultSnapshot {
+            mint,
+            owner,
+            amount,
+            decimals: self.mint_decimals(&mint)?,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::hash::Hash;
+
+    fn escrow(state: EscrowState, mint: &Pubkey, multisig: &Pubkey) -> Escrow {
+        Escrow {
+            id: "escrow_1".to_string(),
+            offer_id: "offer_1".to_string(),
+            buyer: Pubkey::new_unique().to_string(),
+            seller: Pubkey::new_unique().to_string(),
+            amount: 2.5,
+            token_address: mint.to_string(),
+            fiat_amount: 250.0,
+            fiat_currency: "USD".to_string(),
+            state,
+            multisig_address: Some(multisig.to_string()),
+            escrow_pubkey: Some(Pubkey::new_unique().to_string()),
+            created_at: Utc::now(),
+            funded_at: None,
+            released_at: None,
+            timeout_at: Utc::now(),
+            arbitrators: vec![Pubkey::new_unique().to_string()],
+            fee_rate: 0.01,
+            mode: EscrowMode::OnChain,
+            funding_signature: Some("sig".to_string()),
+            settlement_signature: None,
+            price_quote: None,
+            confirmed_at: None,
+        }
+    }
+
+    #[test]
+    fn test_signatures_merge_until_complete() {
+        let payer = Keypair::new();
+        let cosigner = Keypair::new();
+        let instruction = transfer_checked(
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &[payer.pubkey(), cosigner.pubkey()],
+            10,
+            6,
+        );
+        let mut pending = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+        pending.message.recent_blockhash = Hash::new_unique();
+        assert_eq!(missing_signers(&pending).len(), 2);
+
+        let mut from_payer = pending.clone();
+        from_payer.partial_sign(&[&payer], pending.message.recent_blockhash);
+        assert_eq!(merge_signatures(&mut pending, &from_payer).unwrap(), 1);
+        assert_eq!(missing_signers(&pending), vec![cosigner.pubkey()]);
+
+        let mut from_cosigner = pending.clone();
+        from_cosigner.partial_sign(&[&cosigner], pending.message.recent_blockhash);
+        merge_signatures(&mut pending, &from_cosigner).unwrap();
+        assert!(missing_signers(&pending).is_empty());
+        assert!(pending.verify().is_ok());
+
+        // A transaction with a different message is rejected.
+        let mut other = from_cosigner.clone();
+        other.message.recent_blockhash = Hash::new_unique();
+        assert!(merge_signatures(&mut pending, &other).is_err());
+
+        let round_trip = decode_transaction(&encode_transaction(&pending).unwrap()).unwrap();
+        assert_eq!(round_trip.signatures, pending.signatures);
+    }
+
+    #[test]
+    fn test_reconcile_flags_divergence() {
+        let mint = Pubkey::new_unique();
+        let multisig = Pubkey::new_unique();
+        let funded = escrow(EscrowState::Funded, &mint, &multisig);
+        let vault = VaultSnapshot {
+            mint,
+            owner: multisig,
+            amount: 2_500_000,
+            decimals: 6,
+        };
+        assert!(reconcile(&funded, Some(&vault)).is_empty());
+        assert_eq!(reconcile(&funded, None).len(), 1);
+
+        // Released locally but the tokens are still in the vault.
+        let released = escrow(EscrowState::Released, &mint, &multisig);
+        assert_eq!(reconcile(&released, Some(&vault)).len(), 1);
+
+        let drained = VaultSnapshot { amount: 0, ..vault };
+        assert_eq!(reconcile(&funded, Some(&drained)).len(), 1);
+    }
+}
@@ -0,0 +1,249 @@
+//! Live token prices for oracle-pegged P2P offers.
+//!
+//! Prices come from the shared price stream under the `p2p` consumer, limited
+//! to tokens that have an active pegged offer. Each reading is timestamped on
+//! arrival so a match can refuse a price older than the offer allows.
+
+use super::{types::*, SharedP2PDatabase};
+use crate::core::{WebSocketManager, P2P_CONSUMER};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tokio::time::{interval, Duration};
+
+const ORACLE_PRICE_THROTTLE: Duration = Duration::from_secs(1);
+const PEGGED_OFFER_RESYNC_INTERVAL: Duration = Duration::from_secs(60);
+/// Oracle prices are quoted in USD, so pegged offers must be too.
+pub const ORACLE_QUOTE_CURRENCY: &str = "USD";
+
+pub type SharedP2PPriceOracle = Arc<RwLock<P2PPriceOracle>>;
+
+#[derive(Debug, Default)]
+pub struct P2PPriceOracle {
+    readings: HashMap<String, OracleReading>,
+}
+
+impl P2PPriceOracle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, token_address: &str, price: f64, observed_at: DateTime<Utc>) {
+        if !price.is_finite() || price <= 0.0 {
+            return;
+        }
+        self.readings.insert(
+            token_address.to_string(),
+            OracleReading {
+                token_address: token_address.to_string(),
+                price,
+                observed_at,
+            },
+        );
+    }
+
+    pub fn reading(&self, token_address: &str) -> Option<&OracleReading> {
+        self.readings.get(token_address)
+    }
+
+    pub fn quote(&self, offer: &P2POffer, now: DateTime<Utc>) -> Result<PriceQuote> {
+        quote_offer(offer, self.reading(&offer.token_address), now)
+    }
+}
+
+pub fn validate_pricing(pricing: &OfferPricing, fiat_currency: &str) -> Result<()> {
+    let OfferPricing::OraclePegged {
+        premium_percent,
+        max_staleness_secs,
+    } = *pricing
+    else {
+        return Ok(());
+    };
+
+    if !fiat_currency.eq_ignore_ascii_case(ORACLE_QUOTE_CURRENCY) {
+        return Err(anyhow!(
+            "Pegged offers must be priced in {}",
+            ORACLE_QUOTE_CURRENCY
+        ));
+    }
+    if !premium_percent.is_finite() || premium_percent <= -100.0 {
+        return Err(anyhow!("Invalid premium: {}%", premium_percent));
+    }
+    if max_staleness_secs <= 0 {
+        return Err(anyhow!("Maximum oracle staleness must be positive"));
+    }
+    Ok(())
+}
+
+/// The unit price an offer can be matched at right now.
+pub fn quote_offer(
+    offer: &P2POffer,
+    reading: Option<&OracleReading>,
+    now: DateTime<Utc>,
+) -> Result<PriceQuote> {
+    let (unit_price, oracle) = match offer.pricing {
+        OfferPricing::Fixed => (offer.price, None),
+        OfferPricing::OraclePegged {
+            premium_percent,
+            max_staleness_secs,
+        } => {
+            let reading =
+                reading.ok_or_else(|| anyhow!("No oracle price for {} yet", offer.token_symbol))?;
+            let age = (now - reading.observed_at).num_seconds();
+            if age > max_staleness_secs {
+                return Err(anyhow!(
+                    "Oracle price for {} is {}s old; the offer accepts at most {}s",
+                    offer.token_symbol,
+                    age,
+                    max_staleness_secs
+                ));
+            }
+            (
+                reading.price * (1.0 + premium_percent / 100.0),
+                Some(reading.clone()),
+            )
+        }
+    };
+
+    if !unit_price.is_finite() || unit_price <= 0.0 {
+        return Err(anyhow!("Offer has no valid price"));
+    }
+
+    Ok(PriceQuote {
+        unit_price,
+        pricing: offer.pricing,
+        oracle,
+        quoted_at: now,
+    })
+}
+
+/// Keeps oracle readings current for every token with an active pegged offer.
+pub async fn run_p2p_oracle_feed(
+    oracle: SharedP2PPriceOracle,
+    db: SharedP2PDatabase,
+    ws_manager: WebSocketManager,
+) {
+    let mut feed = ws_manager.price_feed(P2P_CONSUMER, ORACLE_PRICE_THROTTLE);
+    let mut watched: HashSet<String> = ws_manager
+        .price_consumer_tokens(P2P_CONSUMER)
+        .into_iter()
+        .collect();
+    let mut resync = interval(PEGGED_OFFER_RESYNC_INTERVAL);
+
+    loop {
+        tokio::select! {
+            _ = resync.tick() => {
+                sync_pegged_tokens(&db, &ws_manager, &mut watched).await;
+            }
+            delta = feed.recv() => {
+                let Some(delta) = delta else {
+                    break;
+                };
+                if let Some(price) = delta.price {
+                    oracle.write().await.record(&delta.symbol, price, Utc::now());
+                }
+            }
+        }
+    }
+}
+
+async fn sync_pegged_tokens(
+    db: &SharedP2PDatabase,
+    ws_manager: &WebSocketManager,
+    watched: &mut HashSet<String>,
+) {
+    let wanted: HashSet<String> = match db.read().await.list_offers(None, None, true).await {
+        Ok(offers) => offers
+            .into_iter()
+            .filter(|offer| matches!(offer.pricing, OfferPricing::OraclePegged { .. }))
+            .map(|offer| offer.token_address)
+            .collect(),
+        Err(err) => {
+            eprintln!("Failed to list pegged P2P offers: {}", err);
+            return;
+        }
+    };
+
+    let added: Vec<String> = wanted.difference(watched).cloned().collect();
+    let removed: Vec<String> = watched.difference(&wanted).cloned().collect();
+
+    if !added.is_empty() {
+        match ws_manager
+            .subscribe_prices_as(P2P_CONSUMER, added.clone())
+            .await
+        {
+            Ok(()) => watched.extend(added),
+            Err(err) => eprintln!("Failed to subscribe P2P oracle tokens: {}", err),
+        }
+    }
+    if !removed.is_empty() {
+        if let Err(err) = ws_manager
+            .unsubscribe_prices_as(P2P_CONSUMER, removed.clone())
+            .await
+        {
+            eprintln!("Failed to unsubscribe P2P oracle tokens: {}", err);
+        }
+        for token in removed {
+            watched.remove(&token);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pegged_offer(premium_percent: f64) -> P2POffer {
+        P2POffer {
+            id: "offer_1".to_string(),
+            creator: "creator".to_string(),
+            offer_type: OfferType::Sell,
+            token_address: "sol_mint".to_string(),
+            token_symbol: "SOL".to_string(),
+            amount: 10.0,
+            price: 0.0,
+            fiat_currency: "USD".to_string(),
+            payment_methods: vec!["Bank Transfer".to_string()],
+            min_amount: None,
+            max_amount: None,
+            terms: None,
+            time_limit: 30,
+            created_at: Utc::now(),
+            expires_at: None,
+            is_active: true,
+            completed_trades: 0,
+            reputation_required: None,
+            pricing: OfferPricing::OraclePegged {
+                premium_percent,
+                max_staleness_secs: 30,
+            },
+            remaining_amount: 10.0,
+        }
+    }
+
+    #[test]
+    fn test_pegged_quote_applies_premium_and_staleness() {
+        let now = Utc::now();
+        let offer = pegged_offer(1.5);
+        let mut oracle = P2PPriceOracle::new();
+        assert!(oracle.quote(&offer, now).is_err());
+
+        oracle.record("sol_mint", 200.0, now - chrono::Duration::seconds(10));
+        let quote = oracle.quote(&offer, now).unwrap();
+        assert!((quote.unit_price - 203.0).abs() < 1e-9);
+        assert_eq!(quote.oracle.as_ref().map(|r| r.price), Some(200.0));
+
+        let later = now + chrono::Duration::seconds(60);
+        assert!(oracle.quote(&offer, later).is_err());
+    }
+
+    #[test]
+    fn test_pegged_offers_require_usd() {
+        let pricing = pegged_offer(-2.0).pricing;
+        assert!(validate_pricing(&pricing, "USD").is_ok());
+        assert!(validate_pricing(&pricing, "EUR").is_err());
+        assert!(validate_pricing(&OfferPricing::Fixed, "EUR").is_ok());
+    }
+}
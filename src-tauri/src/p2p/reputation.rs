@@ -0,0 +1,254 @@
+//! P2P trader reputation, earned from the local escrow and dispute history.
+//!
+//! The score is stored on the trader profile and fed into the general
+//! `ReputationEngine`, so a wallet's trust score reflects its P2P conduct.
+
+use super::{database::P2PDatabase, types::*};
+use crate::security::reputation::ReputationEngine;
+use anyhow::Result;
+use std::collections::HashMap;
+
+pub const NEUTRAL_P2P_SCORE: f64 = 50.0;
+/// Finished trades needed before completion and release speed count in full.
+const FULL_CONFIDENCE_TRADES: f64 = 10.0;
+const MAX_DISPUTE_BONUS: f64 = 6.0;
+const MAX_DISPUTE_PENALTY: f64 = 30.0;
+
+pub fn volume_tier(completed_volume: f64) -> VolumeTier {
+    match completed_volume {
+        v if v >= 1_000_000.0 => VolumeTier::Platinum,
+        v if v >= 100_000.0 => VolumeTier::Gold,
+        v if v >= 10_000.0 => VolumeTier::Silver,
+        v if v >= 1_000.0 => VolumeTier::Bronze,
+        _ => VolumeTier::Starter,
+    }
+}
+
+fn tier_bonus(tier: VolumeTier) -> f64 {
+    match tier {
+        VolumeTier::Starter => 0.0,
+        VolumeTier::Bronze => 2.0,
+        VolumeTier::Silver => 4.0,
+        VolumeTier::Gold => 7.0,
+        VolumeTier::Platinum => 10.0,
+    }
+}
+
+fn release_speed_adjustment(median_release_secs: Option<i64>) -> f64 {
+    match median_release_secs {
+        None => 0.0,
+        Some(secs) if secs <= 15 * 60 => 10.0,
+        Some(secs) if secs <= 60 * 60 => 5.0,
+        Some(secs) if secs <= 6 * 60 * 60 => 0.0,
+        Some(secs) if secs <= 24 * 60 * 60 => -5.0,
+        Some(_) => -10.0,
+    }
+}
+
+fn median(values: &mut [i64]) -> Option<i64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_unstable();
+    let mid = values.len() / 2;
+    Some(if values.len() % 2 == 0 {
+        (values[mid - 1] + values[mid]) / 2
+    } else {
+        values[mid]
+    })
+}
+
+/// The side of a resolved dispute that got the funds.
+pub fn dispute_winner<'a>(escrow: &'a Escrow, outcome: DisputeResolution) -> &'a str {
+    match outcome {
+        DisputeResolution::ReleaseToSeller => &escrow.seller,
+        DisputeResolution::RefundToBuyer => &escrow.buyer,
+    }
+}
+
+pub fn compute_reputation(
+    address: &str,
+    escrows: &[Escrow],
+    disputes: &[Dispute],
+) -> P2PReputationBreakdown {
+    let mut finished_trades = 0;
+    let mut completed_trades = 0;
+    let mut completed_volume = 0.0;
+    let mut release_times = Vec::new();
+
+    for escrow in escrows {
+        match escrow.state {
+            EscrowState::Released | EscrowState::Completed => {
+                finished_trades += 1;
+                completed_trades += 1;
+                completed_volume += escrow.fiat_amount;
+            }
+            EscrowState::Cancelled | EscrowState::Refunded => finished_trades += 1,
+            _ => {}
+        }
+
+        if escrow.seller == address {
+            if let (Some(confirmed), Some(released)) = (escrow.confirmed_at, escrow.released_at) {
+                release_times.push((released - confirmed).num_seconds().max(0));
+            }
+        }
+    }
+
+    let by_id: HashMap<&str, &Escrow> = escrows.iter().map(|e| (e.id.as_str(), e)).collect();
+    let mut record = DisputeRecord::default();
+    for dispute in disputes {
+        record.total += 1;
+        if dispute.status != DisputeStatus::Resolved {
+            record.open += 1;
+            continue;
+        }
+        let (Some(outcome), Some(escrow)) =
+            (dispute.outcome, by_id.get(dispute.escrow_id.as_str()))
+        else {
+            continue;
+        };
+        if dispute.resolved_by_default {
+            record.defaulted += 1;
+        } else if dispute_winner(escrow, outcome) == address {
+            record.won += 1;
+        } else {
+            record.lost += 1;
+        }
+    }
+
+    let completion_rate = if finished_trades > 0 {
+        completed_trades as f64 / finished_trades as f64
+    } else {
+        0.0
+    };
+    let median_release_secs = median(&mut release_times);
+    let confidence = (finished_trades as f64 / FULL_CONFIDENCE_TRADES).min(1.0);
+    let tier = volume_tier(completed_volume);
+
+    let conduct = (completion_rate - 0.5) * 40.0 + release_speed_adjustment(median_release_secs);
+    let disputes_adjustment = (record.won as f64 * 2.0).min(MAX_DISPUTE_BONUS)
+        - (record.lost as f64 * 8.0 + record.defaulted as f64 * 4.0).min(MAX_DISPUTE_PENALTY);
+    let score = NEUTRAL_P2P_SCORE + confidence * conduct + disputes_adjustment + tier_bonus(tier);
+
+    P2PReputationBreakdown {
+        score: score.clamp(0.0, 100.0),
+        finished_trades,
+        completed_trades,
+        completion_rate,
+        median_release_secs,
+        disputes: record,
+        completed_volume,
+        volume_tier: tier,
+        confidence,
+    }
+}
+
+pub async fn trader_reputation(db: &P2PDatabase, address: &str) -> Result<P2PReputationBreakdown> {
+    let escrows = db.list_escrows(Some(address.to_string())).await?;
+    let disputes = db.list_trader_disputes(address).await?;
+    Ok(compute_reputation(address, &escrows, &disputes))
+}
+
+/// Recomputes a trader's P2P reputation after an escrow event and logs the
+/// change in the wallet's reputation history.
+pub async fn refresh_trader_reputation(
+    db: &P2PDatabase,
+    engine: &ReputationEngine,
+    address: &str,
+    event_type: &str,
+    details: Option<&str>,
+) -> Result<P2PReputationBreakdown> {
+    let breakdown = trader_reputation(db, address).await?;
+    db.set_trader_reputation_score(address, breakdown.score)
+        .await?;
+    engine
+        .record_p2p_reputation(address, breakdown.score, event_type, details)
+        .await?;
+    Ok(breakdown)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+
+    fn escrow(id: &str, state: EscrowState, release_after_mins: Option<i64>) -> Escrow {
+        let confirmed = Utc::now() - Duration::hours(2);
+        Escrow {
+            id: id.to_string(),
+            offer_id: "offer_1".to_string(),
+            buyer: "buyer".to_string(),
+            seller: "seller".to_string(),
+            amount: 10.0,
+            token_address: "sol_mint".to_string(),
+            fiat_amount: 1_500.0,
+            fiat_currency: "USD".to_string(),
+            state,
+            multisig_address: None,
+            escrow_pubkey: None,
+            created_at: confirmed,
+            funded_at: None,
+            released_at: release_after_mins.map(|mins| confirmed + Duration::minutes(mins)),
+            timeout_at: confirmed + Duration::minutes(30),
+            arbitrators: vec![],
+            fee_rate: 0.01,
+            mode: EscrowMode::Local,
+            funding_signature: None,
+            settlement_signature: None,
+            price_quote: None,
+            confirmed_at: release_after_mins.map(|_| confirmed),
+        }
+    }
+
+    fn resolved_dispute(escrow_id: &str, outcome: DisputeResolution) -> Dispute {
+        Dispute {
+            id: format!("dispute_{}", escrow_id),
+            escrow_id: escrow_id.to_string(),
+            filed_by: "buyer".to_string(),
+            reason: "non_release".to_string(),
+            description: String::new(),
+            evidence: vec![],
+            status: DisputeStatus::Resolved,
+            arbitrator: None,
+            created_at: Utc::now(),
+            resolved_at: Some(Utc::now()),
+            resolution: None,
+            votes: vec![],
+            arbitration_deadline: None,
+            default_resolution: None,
+            resolved_by_default: false,
+            outcome: Some(outcome),
+        }
+    }
+
+    #[test]
+    fn test_new_trader_starts_neutral() {
+        let breakdown = compute_reputation("newcomer", &[], &[]);
+        assert_eq!(breakdown.score, NEUTRAL_P2P_SCORE);
+        assert_eq!(breakdown.confidence, 0.0);
+        assert_eq!(breakdown.volume_tier, VolumeTier::Starter);
+    }
+
+    #[test]
+    fn test_history_and_disputes_move_the_score() {
+        let mut escrows: Vec<Escrow> = (0..10)
+            .map(|i| escrow(&format!("e{}", i), EscrowState::Released, Some(10)))
+            .collect();
+        let fast_seller = compute_reputation("seller", &escrows, &[]);
+        assert_eq!(fast_seller.completion_rate, 1.0);
+        assert_eq!(fast_seller.median_release_secs, Some(600));
+        assert_eq!(fast_seller.volume_tier, VolumeTier::Silver);
+        assert!(fast_seller.score > 80.0);
+
+        escrows.push(escrow("disputed", EscrowState::Completed, None));
+        let disputes = vec![resolved_dispute(
+            "disputed",
+            DisputeResolution::RefundToBuyer,
+        )];
+        let seller = compute_reputation("seller", &escrows, &disputes);
+        let buyer = compute_reputation("buyer", &escrows, &disputes);
+        assert_eq!(seller.disputes.lost, 1);
+        assert_eq!(buyer.disputes.won, 1);
+        assert!(seller.score < fast_seller.score);
+    }
+}
@@ -113,6 +113,142 @@ impl FromStr for DisputeStatus {
     }
 }
 
+/// Where escrowed tokens are held. `Local` only tracks the trade in the local
+/// database; `OnChain` locks the tokens in a multisig-owned token account.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum EscrowMode {
+    #[default]
+    Local,
+    OnChain,
+}
+
+impl std::fmt::Display for EscrowMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EscrowMode::Local => write!(f, "local"),
+            EscrowMode::OnChain => write!(f, "onchain"),
+        }
+    }
+}
+
+impl FromStr for EscrowMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "local" => Ok(EscrowMode::Local),
+            "onchain" => Ok(EscrowMode::OnChain),
+            _ => Err(anyhow::anyhow!("Invalid escrow mode: {}", s)),
+        }
+    }
+}
+
+/// An on-chain escrow transaction: locking the tokens, paying them out to the
+/// seller, or returning them to the buyer.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum EscrowAction {
+    Fund,
+    Release,
+    Refund,
+}
+
+impl std::fmt::Display for EscrowAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EscrowAction::Fund => write!(f, "fund"),
+            EscrowAction::Release => write!(f, "release"),
+            EscrowAction::Refund => write!(f, "refund"),
+        }
+    }
+}
+
+impl FromStr for EscrowAction {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "fund" => Ok(EscrowAction::Fund),
+            "release" => Ok(EscrowAction::Release),
+            "refund" => Ok(EscrowAction::Refund),
+            _ => Err(anyhow::anyhow!("Invalid escrow action: {}", s)),
+        }
+    }
+}
+
+/// What happens to a disputed escrow when the arbiter does not rule in time.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DisputeResolution {
+    ReleaseToSeller,
+    RefundToBuyer,
+}
+
+impl DisputeResolution {
+    pub fn action(&self) -> EscrowAction {
+        match self {
+            DisputeResolution::ReleaseToSeller => EscrowAction::Release,
+            DisputeResolution::RefundToBuyer => EscrowAction::Refund,
+        }
+    }
+}
+
+impl std::fmt::Display for DisputeResolution {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DisputeResolution::ReleaseToSeller => write!(f, "release_to_seller"),
+            DisputeResolution::RefundToBuyer => write!(f, "refund_to_buyer"),
+        }
+    }
+}
+
+impl FromStr for DisputeResolution {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "release_to_seller" => Ok(DisputeResolution::ReleaseToSeller),
+            "refund_to_buyer" => Ok(DisputeResolution::RefundToBuyer),
+            _ => Err(anyhow::anyhow!("Invalid dispute resolution: {}", s)),
+        }
+    }
+}
+
+/// How an offer's unit price is set. Pegged offers follow the live oracle price
+/// plus a premium (negative for a discount) and are re-priced at match time.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum OfferPricing {
+    #[default]
+    Fixed,
+    #[serde(rename_all = "camelCase")]
+    OraclePegged {
+        premium_percent: f64,
+        max_staleness_secs: i64,
+    },
+}
+
+/// A live price as seen by the P2P oracle, quoted in USD.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OracleReading {
+    pub token_address: String,
+    pub price: f64,
+    pub observed_at: DateTime<Utc>,
+}
+
+/// The unit price a match was made at, kept on the escrow so a dispute can
+/// refer back to it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceQuote {
+    pub unit_price: f64,
+    pub pricing: OfferPricing,
+    pub oracle: Option<OracleReading>,
+    pub quoted_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct P2POffer {
@@ -134,6 +270,12 @@ pub struct P2POffer {
     pub is_active: bool,
     pub completed_trades: i32,
     pub reputation_required: Option<f64>,
+    #[serde(default)]
+    pub pricing: OfferPricing,
+    /// Amount not yet taken by a match. Each fill reserves part of it for its
+    /// own escrow; the offer closes once it reaches zero.
+    #[serde(default)]
+    pub remaining_amount: f64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -156,6 +298,18 @@ pub struct Escrow {
     pub timeout_at: DateTime<Utc>,
     pub arbitrators: Vec<String>,
     pub fee_rate: f64,
+    #[serde(default)]
+    pub mode: EscrowMode,
+    #[serde(default)]
+    pub funding_signature: Option<String>,
+    /// Signature of the release or refund that emptied the on-chain vault.
+    #[serde(default)]
+    pub settlement_signature: Option<String>,
+    #[serde(default)]
+    pub price_quote: Option<PriceQuote>,
+    /// When the buyer confirmed the fiat payment.
+    #[serde(default)]
+    pub confirmed_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -173,6 +327,16 @@ pub struct Dispute {
     pub resolved_at: Option<DateTime<Utc>>,
     pub resolution: Option<String>,
     pub votes: Vec<DisputeVote>,
+    /// When the configured default resolution applies if the arbiter has not ruled.
+    #[serde(default)]
+    pub arbitration_deadline: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub default_resolution: Option<DisputeResolution>,
+    #[serde(default)]
+    pub resolved_by_default: bool,
+    /// Which way the dispute was settled, once resolved.
+    #[serde(default)]
+    pub outcome: Option<DisputeResolution>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -226,6 +390,50 @@ pub struct TraderProfile {
     pub last_trade_at: Option<DateTime<Utc>>,
     pub verified: bool,
     pub verification_level: i32,
+    /// How `reputation_score` was earned; filled in by `get_trader_profile`.
+    #[serde(default)]
+    pub reputation_breakdown: Option<P2PReputationBreakdown>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum VolumeTier {
+    Starter,
+    Bronze,
+    Silver,
+    Gold,
+    Platinum,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DisputeRecord {
+    pub total: i32,
+    pub won: i32,
+    pub lost: i32,
+    /// Closed by the default resolution after the arbitration deadline, on
+    /// either side.
+    pub defaulted: i32,
+    pub open: i32,
+}
+
+/// The components of a trader's P2P reputation, all derived from the local
+/// escrow and dispute history.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct P2PReputationBreakdown {
+    pub score: f64,
+    pub finished_trades: i32,
+    pub completed_trades: i32,
+    pub completion_rate: f64,
+    /// Median time a seller took to release after payment was confirmed.
+    pub median_release_secs: Option<i64>,
+    pub disputes: DisputeRecord,
+    pub completed_volume: f64,
+    pub volume_tier: VolumeTier,
+    /// How much history backs the score, from 0 (none) to 1. New traders stay
+    /// close to the neutral score until they build a record.
+    pub confidence: f64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -254,6 +462,10 @@ pub struct CreateOfferRequest {
     pub terms: Option<String>,
     pub time_limit: i32,
     pub reputation_required: Option<f64>,
+    #[serde(default)]
+    pub pricing: OfferPricing,
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -263,7 +475,13 @@ pub struct CreateEscrowRequest {
     pub buyer: String,
     pub seller: String,
     pub amount: f64,
+    /// Recalculated from the offer's price when the match is made.
     pub fiat_amount: f64,
+    #[serde(default)]
+    pub mode: EscrowMode,
+    /// Third multisig signer for on-chain escrows; defaults to the configured arbiter.
+    #[serde(default)]
+    pub arbiter: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -307,3 +525,39 @@ pub struct P2PStats {
     pub total_volume: f64,
     pub avg_completion_time: i64,
 }
+
+/// An escrow transaction waiting for signatures. `transaction` is a
+/// base64-encoded, bincode-serialized legacy transaction.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingEscrowTransaction {
+    pub escrow_id: String,
+    pub action: EscrowAction,
+    pub transaction: String,
+    pub required_signers: Vec<String>,
+    pub missing_signers: Vec<String>,
+    pub multisig_address: Option<String>,
+    pub vault_address: Option<String>,
+    pub prepared_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct P2PEscrowConfig {
+    pub rpc_url: String,
+    /// Arbiter added as the third multisig signer when an escrow names none.
+    pub arbiter: Option<String>,
+    pub arbitration_timeout_hours: i64,
+    pub default_resolution: DisputeResolution,
+}
+
+impl Default for P2PEscrowConfig {
+    fn default() -> Self {
+        Self {
+            rpc_url: crate::config::default_rpc_url(),
+            arbiter: None,
+            arbitration_timeout_hours: 72,
+            default_resolution: DisputeResolution::RefundToBuyer,
+        }
+    }
+}
@@ -1,9 +1,10 @@
+use crate::config::DataPaths;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::{Pool, Row, Sqlite, SqlitePool};
 use std::collections::HashMap;
 use std::sync::Arc;
-use tauri::{AppHandle, Manager, State};
+use tauri::{AppHandle, State};
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
@@ -105,7 +106,7 @@ pub type SharedAIPortfolioAdvisor = Arc<RwLock<AIPortfolioAdvisor>>;
 
 impl AIPortfolioAdvisor {
     pub async fn new(app: &AppHandle) -> Result<Self, sqlx::Error> {
-        let mut db_path = app.path().app_data_dir().map_err(|err| {
+        let mut db_path = app.app_data_root().map_err(|err| {
             sqlx::Error::Io(std::io::Error::new(
                 std::io::ErrorKind::NotFound,
                 "App data dir not found",
@@ -3,9 +3,13 @@ use lazy_static::lazy_static;
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use tauri::State;
 
+use super::correlations::{correlation_alerts, load_portfolio_correlations, CorrelationWindow};
 use super::types::Position;
+use crate::core::cache_manager::SharedCacheManager;
 use crate::market::PricePoint;
+use crate::wallet::balances::SharedTokenBalanceService;
 
 // ==================== Data Types ====================
 
@@ -179,7 +183,7 @@ fn covariance(x: &[f64], y: &[f64]) -> f64 {
     cov / x.len() as f64
 }
 
-fn correlation(x: &[f64], y: &[f64]) -> f64 {
+pub(super) fn correlation(x: &[f64], y: &[f64]) -> f64 {
     let cov = covariance(x, y);
     let std_x = std_dev(x);
     let std_y = std_dev(y);
@@ -615,11 +619,28 @@ pub async fn calculate_portfolio_analytics(
     Ok(analytics)
 }
 
+/// Alerts for oversized positions. With `wallet`, also alerts on clusters
+/// of correlated holdings and on low diversification over `window`.
 #[tauri::command]
 pub async fn get_concentration_alerts(
     positions: Vec<Position>,
+    wallet: Option<String>,
+    window: Option<CorrelationWindow>,
+    balances: State<'_, SharedTokenBalanceService>,
+    cache_manager: State<'_, SharedCacheManager>,
 ) -> Result<Vec<ConcentrationAlert>, String> {
-    Ok(check_concentration_alerts(&positions))
+    let mut alerts = check_concentration_alerts(&positions);
+    if let Some(wallet) = wallet {
+        let correlations = load_portfolio_correlations(
+            &wallet,
+            window.unwrap_or_default(),
+            balances.inner(),
+            cache_manager.inner(),
+        )
+        .await?;
+        alerts.extend(correlation_alerts(&correlations));
+    }
+    Ok(alerts)
 }
 
 #[tauri::command]
@@ -0,0 +1,568 @@
+use chrono::{DateTime, Utc};
+use futures_util::future::join_all;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use tauri::State;
+
+use super::analytics::{correlation, ConcentrationAlert};
+use crate::core::cache_manager::SharedCacheManager;
+use crate::market::{daily_price_history, PricePoint};
+use crate::wallet::balances::SharedTokenBalanceService;
+
+/// Holdings whose returns correlate at least this strongly are clustered.
+pub const CLUSTER_CORRELATION: f64 = 0.8;
+/// Effective independent bets at which the diversification score reaches 100.
+pub const FULLY_DIVERSIFIED_BETS: f64 = 5.0;
+/// Scores below this raise a portfolio-wide diversification alert.
+pub const LOW_DIVERSIFICATION_SCORE: f64 = 40.0;
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum CorrelationWindow {
+    #[default]
+    #[serde(rename = "30d")]
+    Days30,
+    #[serde(rename = "90d")]
+    Days90,
+}
+
+impl CorrelationWindow {
+    pub fn days(&self) -> i64 {
+        match self {
+            CorrelationWindow::Days30 => 30,
+            CorrelationWindow::Days90 => 90,
+        }
+    }
+
+    /// Daily returns a token needs inside the window to be included.
+    pub fn min_returns(&self) -> usize {
+        (self.days() * 2 / 3) as usize
+    }
+}
+
+/// A token held by the wallet, valued in USD.
+#[derive(Debug, Clone)]
+pub struct CorrelationHolding {
+    pub symbol: String,
+    pub mint: String,
+    pub value: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExcludedHolding {
+    pub symbol: String,
+    pub mint: String,
+    pub allocation: f64,
+    pub reason: String,
+}
+
+/// Holdings whose returns move together, linked by pairwise correlations
+/// of at least [`CLUSTER_CORRELATION`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CorrelationCluster {
+    pub symbols: Vec<String>,
+    /// Combined share of the portfolio, in percent.
+    pub allocation: f64,
+    pub avg_correlation: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CorrelationDiversification {
+    /// 0-100; reaches 100 at [`FULLY_DIVERSIFIED_BETS`] effective bets.
+    pub score: f64,
+    /// `1 / wᵀCw` for weights `w` and correlation matrix `C`: the number of
+    /// uncorrelated, equally weighted holdings with the same spread of risk.
+    pub effective_bets: f64,
+    pub holdings: usize,
+    pub avg_correlation: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortfolioCorrelations {
+    pub wallet: String,
+    pub window: CorrelationWindow,
+    pub symbols: Vec<String>,
+    /// Share of the portfolio held in each of `symbols`, in percent.
+    pub allocations: Vec<f64>,
+    /// Pairwise correlations of daily returns, in `symbols` order.
+    pub matrix: Vec<Vec<f64>>,
+    pub diversification: CorrelationDiversification,
+    pub clusters: Vec<CorrelationCluster>,
+    pub excluded: Vec<ExcludedHolding>,
+    pub calculated_at: DateTime<Utc>,
+}
+
+/// Daily returns keyed by the day they end on.
+fn daily_returns(points: &[PricePoint]) -> BTreeMap<i64, f64> {
+    let closes: BTreeMap<i64, f64> = points
+        .iter()
+        .filter(|point| point.close > 0.0)
+        .map(|point| (point.timestamp.div_euclid(SECONDS_PER_DAY), point.close))
+        .collect();
+
+    closes
+        .iter()
+        .filter_map(|(day, close)| {
+            let previous = closes.get(&(day - 1))?;
+            Some((*day, close / previous - 1.0))
+        })
+        .collect()
+}
+
+/// Correlation over the days both series have a return for.
+fn pairwise_correlation(a: &BTreeMap<i64, f64>, b: &BTreeMap<i64, f64>) -> Option<f64> {
+    let (xs, ys): (Vec<f64>, Vec<f64>) = a
+        .iter()
+        .filter_map(|(day, x)| b.get(day).map(|y| (*x, *y)))
+        .unzip();
+    (xs.len() >= 2).then(|| correlation(&xs, &ys))
+}
+
+fn clusters(
+    symbols: &[String],
+    allocations: &[f64],
+    matrix: &[Vec<f64>],
+) -> Vec<CorrelationCluster> {
+    let n = symbols.len();
+    let mut parent: Vec<usize> = (0..n).collect();
+    fn root(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+
+    for i in 0..n {
+        for j in (i + 1)..n {
+            if matrix[i][j] >= CLUSTER_CORRELATION {
+                let (a, b) = (root(&mut parent, i), root(&mut parent, j));
+                parent[a] = b;
+            }
+        }
+    }
+
+    let mut groups: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for i in 0..n {
+        let r = root(&mut parent, i);
+        groups.entry(r).or_default().push(i);
+    }
+
+    let mut clusters: Vec<CorrelationCluster> = groups
+        .into_values()
+        .filter(|members| members.len() > 1)
+        .map(|members| {
+            let mut sum = 0.0;
+            let mut pairs = 0;
+            for (k, &i) in members.iter().enumerate() {
+                for &j in &members[k + 1..] {
+                    sum += matrix[i][j];
+                    pairs += 1;
+                }
+            }
+            CorrelationCluster {
+                symbols: members.iter().map(|&i| symbols[i].clone()).collect(),
+                allocation: members.iter().map(|&i| allocations[i]).sum(),
+                avg_correlation: sum / pairs as f64,
+            }
+        })
+        .collect();
+    clusters.sort_by(|a, b| b.allocation.total_cmp(&a.allocation));
+    clusters
+}
+
+fn diversification(weights: &[f64], matrix: &[Vec<f64>]) -> CorrelationDiversification {
+    let n = weights.len();
+    if n == 0 {
+        return CorrelationDiversification {
+            score: 0.0,
+            effective_bets: 0.0,
+            holdings: 0,
+            avg_correlation: 0.0,
+        };
+    }
+
+    let variance: f64 = weights
+        .iter()
+        .zip(matrix)
+        .map(|(wi, row)| wi * row.iter().zip(weights).map(|(c, wj)| c * wj).sum::<f64>())
+        .sum();
+    // Pairwise estimates need not form a positive semi-definite matrix, so
+    // the bound keeps the result within 1..=n.
+    let effective_bets = (1.0 / variance.max(1.0 / n as f64)).max(1.0);
+
+    let mut sum = 0.0;
+    let mut pairs = 0;
+    for (i, row) in matrix.iter().enumerate() {
+        for corr in &row[i + 1..] {
+            sum += corr;
+            pairs += 1;
+        }
+    }
+
+    CorrelationDiversification {
+        score: ((effective_bets - 1.0) / (FULLY_DIVERSIFIED_BETS - 1.0) * 100.0).clamp(0.0, 100.0),
+        effective_bets,
+        holdings: n,
+        avg_correlation: if pairs > 0 { sum / pairs as f64 } else { 0.0 },
+    }
+}
+
+/// Correlates the daily returns of `holdings` over `window`. `histories`
+/// maps mints to daily candles; holdings missing from it, or with fewer
+/// than [`CorrelationWindow::min_returns`] returns, are listed as excluded
+/// with `unavailable` giving the reason a history could not be loaded.
+pub fn analyze_correlations(
+    wallet: &str,
+    window: CorrelationWindow,
+    holdings: &[CorrelationHolding],
+    histories: &HashMap<String, Vec<PricePoint>>,
+    unavailable: &HashMap<String, String>,
+    now: DateTime<Utc>,
+) -> PortfolioCorrelations {
+    let total: f64 = holdings.iter().map(|holding| holding.value.max(0.0)).sum();
+    let allocation = |value: f64| {
+        if total > 0.0 {
+            value / total * 100.0
+        } else {
+            0.0
+        }
+    };
+
+    let mut included = Vec::new();
+    let mut excluded = Vec::new();
+    for holding in holdings.iter().filter(|holding| holding.value > 0.0) {
+        let returns = histories
+            .get(&holding.mint)
+            .map(|points| daily_returns(points))
+            .unwrap_or_default();
+        if returns.len() >= window.min_returns() {
+            included.push((holding, returns));
+            continue;
+        }
+        let reason = match unavailable.get(&holding.mint) {
+            Some(err) => format!("Price history unavailable: {}", err),
+            None => format!(
+                "Insufficient history: {} of {} daily returns required",
+                returns.len(),
+                window.min_returns()
+            ),
+        };
+        excluded.push(ExcludedHolding {
+            symbol: holding.symbol.clone(),
+            mint: holding.mint.clone(),
+            allocation: allocation(holding.value),
+            reason,
+        });
+    }
+
+    let n = included.len();
+    let mut matrix = vec![vec![0.0; n]; n];
+    for i in 0..n {
+        matrix[i][i] = 1.0;
+        for j in (i + 1)..n {
+            let corr = pairwise_correlation(&included[i].1, &included[j].1).unwrap_or(0.0);
+            matrix[i][j] = corr;
+            matrix[j][i] = corr;
+        }
+    }
+
+    let symbols: Vec<String> = included.iter().map(|(h, _)| h.symbol.clone()).collect();
+    let allocations: Vec<f64> = included.iter().map(|(h, _)| allocation(h.value)).collect();
+    let included_value: f64 = included.iter().map(|(h, _)| h.value).sum();
+    let weights: Vec<f64> = included
+        .iter()
+        .map(|(h, _)| h.value / included_value)
+        .collect();
+
+    PortfolioCorrelations {
+        wallet: wallet.to_string(),
+        window,
+        clusters: clusters(&symbols, &allocations, &matrix),
+        diversification: diversification(&weights, &matrix),
+        symbols,
+        allocations,
+        matrix,
+        excluded,
+        calculated_at: now,
+    }
+}
+
+/// Concentration alerts for correlated holdings: a cluster is held to the
+/// same 30%/40% limits as a single position, and a low diversification
+/// score warns even when every cluster is small.
+pub fn correlation_alerts(correlations: &PortfolioCorrelations) -> Vec<ConcentrationAlert> {
+    let now = Utc::now().to_rfc3339();
+    let mut alerts = Vec::new();
+
+    for cluster in &correlations.clusters {
+        let (severity, threshold) = if cluster.allocation >= 40.0 {
+            ("critical", 40.0)
+        } else if cluster.allocation >= 30.0 {
+            ("warning", 30.0)
+        } else {
+            continue;
+        };
+        alerts.push(ConcentrationAlert {
+            id: uuid::Uuid::new_v4().to_string(),
+            symbol: cluster.symbols.join(", "),
+            allocation: cluster.allocation,
+            severity: severity.to_string(),
+            message: format!(
+                "{} move together (average correlation {:.2}) and make up {:.1}% of your \
+                portfolio. Treat them as one position when sizing.",
+                cluster.symbols.join(", "),
+                cluster.avg_correlation,
+                cluster.allocation
+            ),
+            threshold,
+            created_at: now.clone(),
+        });
+    }
+
+    let diversification = &correlations.diversification;
+    if diversification.holdings > 1 && diversification.score < LOW_DIVERSIFICATION_SCORE {
+        alerts.push(ConcentrationAlert {
+            id: uuid::Uuid::new_v4().to_string(),
+            symbol: "PORTFOLIO".to_string(),
+            allocation: correlations.allocations.iter().sum(),
+            severity: "warning".to_string(),
+            message: format!(
+                "Your {} holdings behave like {:.1} independent positions over the last {} days. \
+                Diversification score: {:.0}/100.",
+                diversification.holdings,
+                diversification.effective_bets,
+                correlations.window.days(),
+                diversification.score
+            ),
+            threshold: LOW_DIVERSIFICATION_SCORE,
+            created_at: now,
+        });
+    }
+
+    alerts
+}
+
+/// Loads the wallet's holdings and their daily history, then correlates them.
+pub(crate) async fn load_portfolio_correlations(
+    wallet: &str,
+    window: CorrelationWindow,
+    balances: &SharedTokenBalanceService,
+    cache_manager: &SharedCacheManager,
+) -> Result<PortfolioCorrelations, String> {
+    let view = balances
+        .balances(wallet, false)
+        .await
+        .map_err(|e| e.to_string())?;
+    let holdings: Vec<CorrelationHolding> = view
+        .balances
+        .iter()
+        .map(|balance| CorrelationHolding {
+            symbol: balance.symbol.clone(),
+            mint: balance.mint.clone(),
+            value: balance.usd_value,
+        })
+        .collect();
+
+    let fetches = holdings
+        .iter()
+        .filter(|holding| holding.value > 0.0)
+        .map(|holding| async move {
+            let result = daily_price_history(cache_manager, &holding.mint, window.days()).await;
+            (holding.mint.clone(), result)
+        });
+
+    let mut histories = HashMap::new();
+    let mut unavailable = HashMap::new();
+    for (mint, result) in join_all(fetches).await {
+        match result {
+            Ok(points) => {
+                histories.insert(mint, points);
+            }
+            Err(err) => {
+                unavailable.insert(mint, err);
+            }
+        }
+    }
+
+    Ok(analyze_correlations(
+        wallet,
+        window,
+        &holdings,
+        &histories,
+        &unavailable,
+        Utc::now(),
+    ))
+}
+
+#[tauri::command]
+pub async fn get_portfolio_correlations(
+    wallet: String,
+    window: Option<CorrelationWindow>,
+    balances: State<'_, SharedTokenBalanceService>,
+    cache_manager: State<'_, SharedCacheManager>,
+) -> Result<PortfolioCorrelations, String> {
+    load_portfolio_correlations(
+        &wallet,
+        window.unwrap_or_default(),
+        balances.inner(),
+        cache_manager.inner(),
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: i64 = SECONDS_PER_DAY;
+
+    fn series(closes: &[f64]) -> Vec<PricePoint> {
+        closes
+            .iter()
+            .enumerate()
+            .map(|(i, &close)| PricePoint {
+                timestamp: 1_700_006_400 + i as i64 * DAY,
+                open: close,
+                high: close,
+                low: close,
+                close,
+                volume: 0.0,
+            })
+            .collect()
+    }
+
+    /// Deterministic, uncorrelated-looking daily closes.
+    fn walk(seed: u64, days: usize, scale: f64) -> Vec<f64> {
+        let mut state = seed;
+        let mut price = 100.0;
+        (0..days)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                let step = ((state >> 33) as f64 / (1u64 << 31) as f64) - 0.5;
+                price *= 1.0 + step * 0.1 * scale;
+                price
+            })
+            .collect()
+    }
+
+    fn holding(symbol: &str, value: f64) -> CorrelationHolding {
+        CorrelationHolding {
+            symbol: symbol.to_string(),
+            mint: format!("{symbol}-mint"),
+            value,
+        }
+    }
+
+    #[test]
+    fn correlated_holdings_form_a_cluster_and_alert() {
+        let base = walk(1, 31, 1.0);
+        let mut histories = HashMap::new();
+        // DOG1..DOG3 are the same walk at different scales: perfectly correlated.
+        for (i, symbol) in ["DOG1", "DOG2", "DOG3"].iter().enumerate() {
+            let scaled: Vec<f64> = base.iter().map(|p| p * (i + 1) as f64).collect();
+            histories.insert(format!("{symbol}-mint"), series(&scaled));
+        }
+        let holdings = vec![
+            holding("DOG1", 25.0),
+            holding("DOG2", 25.0),
+            holding("DOG3", 25.0),
+            holding("NEW", 25.0),
+        ];
+
+        let result = analyze_correlations(
+            "wallet",
+            CorrelationWindow::Days30,
+            &holdings,
+            &histories,
+            &HashMap::new(),
+            Utc::now(),
+        );
+
+        assert_eq!(result.symbols, vec!["DOG1", "DOG2", "DOG3"]);
+        assert!((result.matrix[0][2] - 1.0).abs() < 1e-9);
+        assert_eq!(result.clusters.len(), 1);
+        assert!((result.clusters[0].allocation - 75.0).abs() < 1e-9);
+        assert!((result.diversification.effective_bets - 1.0).abs() < 1e-6);
+        assert_eq!(result.diversification.score, 0.0);
+
+        assert_eq!(result.excluded.len(), 1);
+        assert_eq!(result.excluded[0].symbol, "NEW");
+        assert!(result.excluded[0]
+            .reason
+            .starts_with("Insufficient history"));
+
+        let alerts = correlation_alerts(&result);
+        assert!(alerts
+            .iter()
+            .any(|alert| alert.severity == "critical" && alert.symbol == "DOG1, DOG2, DOG3"));
+        assert!(alerts.iter().any(|alert| alert.symbol == "PORTFOLIO"));
+    }
+
+    #[test]
+    fn independent_holdings_score_higher() {
+        let mut histories = HashMap::new();
+        let symbols = ["A", "B", "C", "D", "E"];
+        for (i, symbol) in symbols.iter().enumerate() {
+            histories.insert(
+                format!("{symbol}-mint"),
+                series(&walk(i as u64 * 7919 + 3, 91, 1.0)),
+            );
+        }
+        let holdings: Vec<_> = symbols.iter().map(|s| holding(s, 20.0)).collect();
+
+        let result = analyze_correlations(
+            "wallet",
+            CorrelationWindow::Days90,
+            &holdings,
+            &histories,
+            &HashMap::new(),
+            Utc::now(),
+        );
+
+        assert_eq!(result.symbols.len(), 5);
+        assert!(result.diversification.effective_bets > 3.0);
+        assert!(result.diversification.score > LOW_DIVERSIFICATION_SCORE);
+        assert!(result.clusters.is_empty());
+        assert!(correlation_alerts(&result).is_empty());
+    }
+
+    #[test]
+    fn returns_skip_missing_days() {
+        let mut points = series(&[100.0, 110.0, 121.0, 133.1]);
+        points.remove(2);
+        let returns = daily_returns(&points);
+
+        assert_eq!(returns.len(), 1);
+        assert!((returns.values().next().unwrap() - 0.1).abs() < 1e-9);
+    }
+
+    #[test]
+    fn unavailable_history_is_reported() {
+        let holdings = vec![holding("SOL", 10.0)];
+        let unavailable = HashMap::from([("SOL-mint".to_string(), "timeout".to_string())]);
+
+        let result = analyze_correlations(
+            "wallet",
+            CorrelationWindow::Days30,
+            &holdings,
+            &HashMap::new(),
+            &unavailable,
+            Utc::now(),
+        );
+
+        assert!(result.symbols.is_empty());
+        assert_eq!(
+            result.excluded[0].reason,
+            "Price history unavailable: timeout"
+        );
+        assert!((result.excluded[0].allocation - 100.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,1818 @@
+//! Import of centralized-exchange history (Binance, Coinbase, Kraken or a
+//! mapped generic CSV). A preview parses the export and sorts every record
+//! into new, duplicate or needs-review against what is already recorded;
+//! the commit feeds trades into tax lots and performance history as one
+//! batch that can be removed again.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, State};
+
+use super::tax_lots::{LotDraw, SharedTaxLotsState, TaxLotsState};
+use super::types::TaxLot;
+use super::watchlist_import::{split_csv_line, CsvColumn, ImportRowError};
+use crate::config::DataPaths;
+use crate::wallet::performance::{RecordTradeRequest, SharedPerformanceDatabase};
+
+const EXCHANGE_IMPORTS_FILE: &str = "exchange_imports.json";
+const PREVIEW_TTL_MINUTES: i64 = 30;
+/// Records this close in time and amount to an existing one are the same.
+const EXACT_MATCH_SECONDS: i64 = 1;
+const EXACT_AMOUNT_TOLERANCE: f64 = 1e-6;
+/// Records within this window and tolerance are flagged for review.
+const REVIEW_WINDOW_MINUTES: i64 = 10;
+const REVIEW_AMOUNT_TOLERANCE: f64 = 0.02;
+/// Quote currencies a trade price can be taken as a USD value from.
+const USD_QUOTES: [&str; 7] = ["USD", "USDT", "USDC", "BUSD", "FDUSD", "TUSD", "DAI"];
+/// Kraken pair suffixes for pairs without the X/Z-prefixed legacy codes.
+const KRAKEN_QUOTES: [&str; 7] = ["USDT", "USDC", "USD", "EUR", "GBP", "XBT", "ETH"];
+
+const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+const USDC_MINT: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+const USDT_MINT: &str = "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB";
+
+#[derive(Debug, thiserror::Error)]
+pub enum ExchangeImportError {
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+    #[error("not found: {0}")]
+    NotFound(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExchangeCsvMapping {
+    pub timestamp: CsvColumn,
+    /// Column holding buy/sell/deposit/withdrawal (or send/receive).
+    pub kind: CsvColumn,
+    pub asset: CsvColumn,
+    pub amount: CsvColumn,
+    /// Unit price in the quote currency; required for trades.
+    pub price: Option<CsvColumn>,
+    /// Quote currency per row; USD when absent.
+    pub quote_asset: Option<CsvColumn>,
+    /// Fee in the quote currency.
+    pub fee: Option<CsvColumn>,
+    pub reference: Option<CsvColumn>,
+    #[serde(default = "default_has_header")]
+    pub has_header: bool,
+    pub delimiter: Option<char>,
+    /// chrono format for the timestamp column, e.g. `%d/%m/%Y %H:%M`.
+    pub date_format: Option<String>,
+    /// Tag stored as the records' external source; `generic` when absent.
+    pub source_name: Option<String>,
+}
+
+fn default_has_header() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ExchangeImportFormat {
+    /// Spot trade history or an account statement; told apart by header.
+    Binance,
+    /// Transaction history report, including its preamble lines.
+    Coinbase,
+    /// `trades.csv` or `ledgers.csv`; told apart by header.
+    Kraken,
+    Generic {
+        mapping: ExchangeCsvMapping,
+    },
+}
+
+impl ExchangeImportFormat {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ExchangeImportFormat::Binance => "binance",
+            ExchangeImportFormat::Coinbase => "coinbase",
+            ExchangeImportFormat::Kraken => "kraken",
+            ExchangeImportFormat::Generic { .. } => "generic",
+        }
+    }
+
+    /// Tag stored on every imported record.
+    fn external_source(&self) -> String {
+        match self {
+            ExchangeImportFormat::Generic { mapping } => mapping
+                .source_name
+                .as_deref()
+                .map(|name| name.trim().to_lowercase())
+                .filter(|name| !name.is_empty())
+                .unwrap_or_else(|| "generic".to_string()),
+            other => other.as_str().to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum ExchangeRecordKind {
+    Buy,
+    Sell,
+    Deposit,
+    Withdrawal,
+}
+
+impl ExchangeRecordKind {
+    fn is_trade(self) -> bool {
+        matches!(self, ExchangeRecordKind::Buy | ExchangeRecordKind::Sell)
+    }
+
+    fn side(self) -> Option<&'static str> {
+        match self {
+            ExchangeRecordKind::Buy => Some("buy"),
+            ExchangeRecordKind::Sell => Some("sell"),
+            _ => None,
+        }
+    }
+}
+
+/// One normalized movement from an exchange export.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ExchangeRecord {
+    /// 1-based line in the source.
+    pub row: usize,
+    /// Position within the row; a Coinbase convert yields a sell and a buy.
+    pub leg: usize,
+    pub raw: String,
+    pub kind: ExchangeRecordKind,
+    pub asset: String,
+    pub amount: f64,
+    /// USD price per unit; always set for trades.
+    pub price_usd: Option<f64>,
+    pub fee_usd: f64,
+    pub executed_at: DateTime<Utc>,
+    /// The exchange's own id for the row, when the export has one.
+    pub external_ref: Option<String>,
+}
+
+impl ExchangeRecord {
+    fn key(&self) -> (usize, usize) {
+        (self.row, self.leg)
+    }
+}
+
+/// Something already recorded that an imported row may repeat.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ExistingRecord {
+    /// Trade id, or `row N` for an earlier row of the same import.
+    pub reference: String,
+    pub kind: ExchangeRecordKind,
+    pub asset: String,
+    pub amount: f64,
+    pub executed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExchangeDuplicate {
+    pub record: ExchangeRecord,
+    pub matched: ExistingRecord,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExchangeReviewItem {
+    pub record: ExchangeRecord,
+    pub candidates: Vec<ExistingRecord>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExchangeImportPreview {
+    pub id: String,
+    pub format: String,
+    pub external_source: String,
+    /// Wallet the imported trades are attributed to in performance history.
+    pub wallet_address: String,
+    pub records: Vec<ExchangeRecord>,
+    /// Skipped on commit.
+    pub duplicates: Vec<ExchangeDuplicate>,
+    /// Possible duplicates; each needs an import or skip decision.
+    pub review: Vec<ExchangeReviewItem>,
+    /// Rows the format carries but that are not imported, such as statement
+    /// trade legs that have no price.
+    pub ignored: Vec<ImportRowError>,
+    pub errors: Vec<ImportRowError>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl ExchangeImportPreview {
+    pub(crate) fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        now >= self.expires_at
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "camelCase")]
+pub enum ExchangeReviewDecision {
+    Import {
+        row: usize,
+        #[serde(default)]
+        leg: usize,
+    },
+    Skip {
+        row: usize,
+        #[serde(default)]
+        leg: usize,
+    },
+}
+
+impl ExchangeReviewDecision {
+    fn key(&self) -> (usize, usize) {
+        match self {
+            ExchangeReviewDecision::Import { row, leg }
+            | ExchangeReviewDecision::Skip { row, leg } => (*row, *leg),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExchangeImportBatch {
+    pub id: String,
+    pub format: String,
+    pub external_source: String,
+    pub wallet_address: String,
+    pub imported_at: DateTime<Utc>,
+    pub records: Vec<ExchangeRecord>,
+    pub trades_recorded: usize,
+    /// Quantities this batch's sells took from lots outside the batch.
+    #[serde(default)]
+    pub lot_draws: Vec<LotDraw>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExchangeImportRemoval {
+    pub batch_id: String,
+    pub trades_removed: u64,
+    pub lots_removed: usize,
+}
+
+/// Prefix of every trade external id and tax lot id a batch creates.
+pub(crate) fn batch_prefix(batch_id: &str) -> String {
+    format!("import:{}:", batch_id)
+}
+
+fn record_id(batch_id: &str, record: &ExchangeRecord) -> String {
+    format!("{}{}:{}", batch_prefix(batch_id), record.row, record.leg)
+}
+
+/// Best-effort mint for an exchange ticker; unknown tickers keep the symbol.
+fn exchange_mint(symbol: &str) -> String {
+    match symbol {
+        "SOL" => SOL_MINT.to_string(),
+        "USDC" => USDC_MINT.to_string(),
+        "USDT" => USDT_MINT.to_string(),
+        other => other.to_string(),
+    }
+}
+
+fn is_usd_quote(asset: &str) -> bool {
+    USD_QUOTES.contains(&asset)
+}
+
+/// Reads exchange timestamps as UTC: RFC 3339, `2021-03-04 12:30:45[.1234]`,
+/// a trailing ` UTC`, and Binance's two-digit-year `21-03-04 12:30:45`.
+pub(crate) fn parse_exchange_time(value: &str, format: Option<&str>) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if let Some(format) = format {
+        return NaiveDateTime::parse_from_str(value, format)
+            .map(|dt| dt.and_utc())
+            .or_else(|_| {
+                NaiveDate::parse_from_str(value, format)
+                    .map(|date| date.and_time(NaiveTime::MIN).and_utc())
+            })
+            .ok();
+    }
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Some(dt.with_timezone(&Utc));
+    }
+
+    let value = value.strip_suffix("UTC").unwrap_or(value).trim();
+    // chrono's %Y happily reads "21" as the year 21, so pick the year width
+    // from the text itself.
+    let two_digit_year = value
+        .split(['-', '/'])
+        .next()
+        .map(|year| year.len() == 2)
+        .unwrap_or(false);
+    let formats: &[&str] = if two_digit_year {
+        &["%y-%m-%d %H:%M:%S"]
+    } else {
+        &[
+            "%Y-%m-%d %H:%M:%S%.f",
+            "%Y-%m-%dT%H:%M:%S%.f",
+            "%Y/%m/%d %H:%M:%S",
+        ]
+    };
+    formats
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .map(|dt| dt.and_utc())
+        .or_else(|| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .ok()
+                .map(|date| date.and_time(NaiveTime::MIN).and_utc())
+        })
+}
+
+/// `$21,800.00` -> 21800.0. Empty fields read as `None`.
+pub(crate) fn parse_number(value: &str) -> Option<f64> {
+    let cleaned: String = value
+        .trim()
+        .chars()
+        .filter(|c| !matches!(c, '$' | ',' | ' '))
+        .collect();
+    if cleaned.is_empty() {
+        return None;
+    }
+    cleaned.parse().ok()
+}
+
+/// Binance writes quantities with the asset glued on: `135.2USDT`.
+pub(crate) fn split_amount_asset(value: &str) -> Option<(f64, String)> {
+    let value = value.trim();
+    let split = value.find(|c: char| c.is_ascii_alphabetic())?;
+    let amount = parse_number(&value[..split])?;
+    let asset = value[split..].trim().to_uppercase();
+    (!asset.is_empty()).then_some((amount, asset))
+}
+
+/// `XXBT` -> `BTC`, `ZUSD` -> `USD`, `XDG` -> `DOGE`.
+pub(crate) fn kraken_asset(code: &str) -> String {
+    let code = code.trim().to_uppercase();
+    let code = match code.len() {
+        4 if code.starts_with('X') || code.starts_with('Z') => code[1..].to_string(),
+        _ => code,
+    };
+    match code.as_str() {
+        "XBT" => "BTC".to_string(),
+        "XDG" => "DOGE".to_string(),
+        _ => code,
+    }
+}
+
+/// Splits a Kraken pair into normalized base and quote assets.
+pub(crate) fn kraken_pair(pair: &str) -> Option<(String, String)> {
+    let pair = pair.trim().to_uppercase();
+    if let Some((base, quote)) = pair.split_once('/') {
+        return Some((kraken_asset(base), kraken_asset(quote)));
+    }
+    let bytes = pair.as_bytes();
+    if bytes.len() == 8 && matches!(bytes[0], b'X' | b'Z') && matches!(bytes[4], b'X' | b'Z') {
+        return Some((kraken_asset(&pair[..4]), kraken_asset(&pair[4..])));
+    }
+    KRAKEN_QUOTES.iter().find_map(|quote| {
+        pair.strip_suffix(quote)
+            .filter(|base| !base.is_empty())
+            .map(|base| (kraken_asset(base), kraken_asset(quote)))
+    })
+}
+
+/// Outcome of parsing one export.
+#[derive(Debug, Default)]
+pub(crate) struct ParsedExport {
+    pub records: Vec<ExchangeRecord>,
+    pub ignored: Vec<ImportRowError>,
+    pub errors: Vec<ImportRowError>,
+}
+
+impl ParsedExport {
+    fn error(&mut self, row: usize, raw: &str, message: impl Into<String>) {
+        self.errors.push(ImportRowError {
+            row,
+            raw: raw.to_string(),
+            message: message.into(),
+        });
+    }
+
+    fn ignore(&mut self, row: usize, raw: &str, message: impl Into<String>) {
+        self.ignored.push(ImportRowError {
+            row,
+            raw: raw.to_string(),
+            message: message.into(),
+        });
+    }
+}
+
+/// A CSV table with its header and the 1-based line of every data row.
+struct CsvTable<'a> {
+    header: Vec<String>,
+    rows: Vec<(usize, &'a str, Vec<String>)>,
+}
+
+impl CsvTable<'_> {
+    fn column(&self, name: &str) -> Result<usize, String> {
+        self.header
+            .iter()
+            .position(|h| h.eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("Export has no '{}' column", name))
+    }
+}
+
+/// Reads `source` as CSV whose header is the first line accepted by
+/// `is_header`; anything before it (Coinbase's preamble) is skipped.
+fn read_table(
+    source: &str,
+    delimiter: char,
+    is_header: impl Fn(&str) -> bool,
+) -> Result<CsvTable<'_>, String> {
+    let mut lines = source
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty());
+    let header = lines
+        .by_ref()
+        .find(|(_, line)| is_header(line))
+        .map(|(_, line)| split_csv_line(line.trim_start_matches('\u{feff}'), delimiter))
+        .ok_or_else(|| "Could not find the export's header row".to_string())?;
+    let rows = lines
+        .map(|(index, line)| (index + 1, line, split_csv_line(line, delimiter)))
+        .collect();
+    Ok(CsvTable { header, rows })
+}
+
+fn field(fields: &[String], index: usize) -> &str {
+    fields.get(index).map(String::as_str).unwrap_or("")
+}
+
+pub(crate) fn parse_export(
+    source: &str,
+    format: &ExchangeImportFormat,
+) -> Result<ParsedExport, String> {
+    match format {
+        ExchangeImportFormat::Binance => {
+            let header = source.lines().next().unwrap_or("");
+            if header.contains("Operation") {
+                parse_binance_statement(source)
+            } else {
+                parse_binance_trades(source)
+            }
+        }
+        ExchangeImportFormat::Coinbase => parse_coinbase(source),
+        ExchangeImportFormat::Kraken => {
+            let header = source.lines().next().unwrap_or("");
+            if header.contains("refid") {
+                parse_kraken_ledgers(source)
+            } else {
+                parse_kraken_trades(source)
+            }
+        }
+        ExchangeImportFormat::Generic { mapping } => parse_generic(source, mapping),
+    }
+}
+
+/// Builds a trade record from a price quoted in `quote`, rejecting quotes
+/// that have no USD value.
+#[allow(clippy::too_many_arguments)]
+fn trade_record(
+    row: usize,
+    raw: &str,
+    kind: ExchangeRecordKind,
+    asset: String,
+    amount: f64,
+    price: f64,
+    quote: &str,
+    fee_usd: f64,
+    executed_at: DateTime<Utc>,
+    external_ref: Option<String>,
+) -> Result<ExchangeRecord, String> {
+    if !is_usd_quote(quote) {
+        return Err(format!(
+            "{} trade is priced in {}; only USD-quoted trades can be valued",
+            asset, quote
+        ));
+    }
+    if amount <= 0.0 || price <= 0.0 {
+        return Err("Trade has no amount or price".to_string());
+    }
+    Ok(ExchangeRecord {
+        row,
+        leg: 0,
+        raw: raw.trim().to_string(),
+        kind,
+        asset,
+        amount,
+        price_usd: Some(price),
+        fee_usd,
+        executed_at,
+        external_ref,
+    })
+}
+
+fn transfer_record(
+    row: usize,
+    raw: &str,
+    kind: ExchangeRecordKind,
+    asset: String,
+    amount: f64,
+    executed_at: DateTime<Utc>,
+    external_ref: Option<String>,
+) -> ExchangeRecord {
+    ExchangeRecord {
+        row,
+        leg: 0,
+        raw: raw.trim().to_string(),
+        kind,
+        asset,
+        amount: amount.abs(),
+        price_usd: None,
+        fee_usd: 0.0,
+        executed_at,
+        external_ref,
+    }
+}
+
+/// Binance spot trade history: `Date(UTC),Pair,Side,Price,Executed,Amount,Fee`.
+fn parse_binance_trades(source: &str) -> Result<ParsedExport, String> {
+    let table = read_table(source, ',', |line| line.contains("Pair"))?;
+    let (date, side, price) = (
+        table.column("Date(UTC)")?,
+        table.column("Side")?,
+        table.column("Price")?,
+    );
+    let (executed, total, fee) = (
+        table.column("Executed")?,
+        table.column("Amount")?,
+        table.column("Fee")?,
+    );
+
+    let mut parsed = ParsedExport::default();
+    for (row, raw, fields) in &table.rows {
+        let Some(executed_at) = parse_exchange_time(field(fields, date), None) else {
+            parsed.error(
+                *row,
+                raw,
+                format!("Unreadable date '{}'", field(fields, date)),
+            );
+            continue;
+        };
+        let kind = match field(fields, side).to_uppercase().as_str() {
+            "BUY" => ExchangeRecordKind::Buy,
+            "SELL" => ExchangeRecordKind::Sell,
+            other => {
+                parsed.error(*row, raw, format!("Unknown side '{}'", other));
+                continue;
+            }
+        };
+        let (Some((amount, base)), Some((_, quote)), Some(price)) = (
+            split_amount_asset(field(fields, executed)),
+            split_amount_asset(field(fields, total)),
+            parse_number(field(fields, price)),
+        ) else {
+            parsed.error(*row, raw, "Could not read the executed amount or price");
+            continue;
+        };
+        let fee_usd = match split_amount_asset(field(fields, fee)) {
+            Some((fee, asset)) if asset == quote => fee,
+            Some((fee, asset)) if asset == base => fee * price,
+            _ => 0.0,
+        };
+        match trade_record(
+            *row,
+            raw,
+            kind,
+            base,
+            amount,
+            price,
+            &quote,
+            fee_usd,
+            executed_at,
+            None,
+        ) {
+            Ok(record) => parsed.records.push(record),
+            Err(message) => parsed.error(*row, raw, message),
+        }
+    }
+    Ok(parsed)
+}
+
+/// Binance account statement: `User_ID,UTC_Time,Account,Operation,Coin,Change,Remark`.
+/// Only deposits and withdrawals are taken; trade legs carry no price.
+fn parse_binance_statement(source: &str) -> Result<ParsedExport, String> {
+    let table = read_table(source, ',', |line| line.contains("Operation"))?;
+    let (time, operation, coin, change) = (
+        table.column("UTC_Time")?,
+        table.column("Operation")?,
+        table.column("Coin")?,
+        table.column("Change")?,
+    );
+
+    let mut parsed = ParsedExport::default();
+    for (row, raw, fields) in &table.rows {
+        let kind = match field(fields, operation).to_lowercase().as_str() {
+            "deposit" => ExchangeRecordKind::Deposit,
+            "withdraw" | "withdrawal" => ExchangeRecordKind::Withdrawal,
+            other => {
+                parsed.ignore(
+                    *row,
+                    raw,
+                    format!(
+                        "Statement '{}' rows have no price; import the trade history export for trades",
+                        other
+                    ),
+                );
+                continue;
+            }
+        };
+        let (Some(executed_at), Some(amount)) = (
+            parse_exchange_time(field(fields, time), None),
+            parse_number(field(fields, change)),
+        ) else {
+            parsed.error(*row, raw, "Could not read the date or change");
+            continue;
+        };
+        parsed.records.push(transfer_record(
+            *row,
+            raw,
+            kind,
+            field(fields, coin).to_uppercase(),
+            amount,
+            executed_at,
+            None,
+        ));
+    }
+    Ok(parsed)
+}
+
+/// `Converted 0.5 ETH to 12 SOL` -> `(12.0, "SOL")`.
+fn coinbase_convert_target(notes: &str) -> Option<(f64, String)> {
+    let (_, target) = notes.split_once(" to ")?;
+    let mut parts = target.split_whitespace();
+    let amount = parse_number(parts.next()?)?;
+    let asset = parts.next()?.trim_end_matches('.').to_uppercase();
+    Some((amount, asset))
+}
+
+/// Coinbase transaction history. The header follows a few preamble lines;
+/// timestamps are RFC 3339 in older reports and `... UTC` in newer ones.
+fn parse_coinbase(source: &str) -> Result<ParsedExport, String> {
+    let table = read_table(source, ',', |line| line.starts_with("Timestamp"))?;
+    let (time, kind_col, asset_col, quantity) = (
+        table.column("Timestamp")?,
+        table.column("Transaction Type")?,
+        table.column("Asset")?,
+        table.column("Quantity Transacted")?,
+    );
+    let currency = table.column("Spot Price Currency")?;
+    let spot = table
+        .header
+        .iter()
+        .position(|h| h.starts_with("Spot Price at Transaction"))
+        .ok_or_else(|| "Export has no 'Spot Price at Transaction' column".to_string())?;
+    let (subtotal, fees, notes) = (
+        table.column("Subtotal")?,
+        table
+            .header
+            .iter()
+            .position(|h| h.starts_with("Fees"))
+            .ok_or_else(|| "Export has no 'Fees' column".to_string())?,
+        table.column("Notes")?,
+    );
+
+    let mut parsed = ParsedExport::default();
+    for (row, raw, fields) in &table.rows {
+        let (Some(executed_at), Some(quantity)) = (
+            parse_exchange_time(field(fields, time), None),
+            parse_number(field(fields, quantity)),
+        ) else {
+            parsed.error(*row, raw, "Could not read the timestamp or quantity");
+            continue;
+        };
+        let asset = field(fields, asset_col).to_uppercase();
+        let amount = quantity.abs();
+        let quote = field(fields, currency).to_uppercase();
+        let fee_usd = parse_number(field(fields, fees)).unwrap_or(0.0);
+        // Subtotal excludes fees, so it prices the asset itself.
+        let price = parse_number(field(fields, subtotal))
+            .filter(|_| amount > 0.0)
+            .map(|subtotal| subtotal / amount)
+            .or_else(|| parse_number(field(fields, spot)));
+
+        let transaction = field(fields, kind_col).to_lowercase();
+        let kind = match transaction.as_str() {
+            "buy" | "advanced trade buy" | "rewards income" | "staking income"
+            | "learning reward" | "inflation reward" => ExchangeRecordKind::Buy,
+            "sell" | "advanced trade sell" => ExchangeRecordKind::Sell,
+            "receive" | "deposit" => {
+                parsed.records.push(transfer_record(
+                    *row,
+                    raw,
+                    ExchangeRecordKind::Deposit,
+                    asset,
+                    amount,
+                    executed_at,
+                    None,
+                ));
+                continue;
+            }
+            "send" | "withdrawal" => {
+                parsed.records.push(transfer_record(
+                    *row,
+                    raw,
+                    ExchangeRecordKind::Withdrawal,
+                    asset,
+                    amount,
+                    executed_at,
+                    None,
+                ));
+                continue;
+            }
+            "convert" => {
+                let Some((target_amount, target)) = coinbase_convert_target(field(fields, notes))
+                else {
+                    parsed.error(
+                        *row,
+                        raw,
+                        "Could not read the converted-to asset from the notes",
+                    );
+                    continue;
+                };
+                let Some(price) = price else {
+                    parsed.error(*row, raw, "Convert has no value");
+                    continue;
+                };
+                let value = price * amount;
+                let legs = trade_record(
+                    *row,
+                    raw,
+                    ExchangeRecordKind::Sell,
+                    asset,
+                    amount,
+                    price,
+                    &quote,
+                    fee_usd,
+                    executed_at,
+                    None,
+                )
+                .and_then(|sell| {
+                    let buy = trade_record(
+                        *row,
+                        raw,
+                        ExchangeRecordKind::Buy,
+                        target,
+                        target_amount,
+                        value / target_amount,
+                        &quote,
+                        0.0,
+                        executed_at,
+                        None,
+                    )?;
+                    Ok([sell, ExchangeRecord { leg: 1, ..buy }])
+                });
+                match legs {
+                    Ok(legs) => parsed.records.extend(legs),
+                    Err(message) => parsed.error(*row, raw, message),
+                }
+                continue;
+            }
+            other => {
+                parsed.ignore(
+                    *row,
+                    raw,
+                    format!("'{}' transactions are not imported", other),
+                );
+                continue;
+            }
+        };
+
+        let Some(price) = price else {
+            parsed.error(*row, raw, "Trade has no price");
+            continue;
+        };
+        match trade_record(
+            *row,
+            raw,
+            kind,
+            asset,
+            amount,
+            price,
+            &quote,
+            fee_usd,
+            executed_at,
+            None,
+        ) {
+            Ok(record) => parsed.records.push(record),
+            Err(message) => parsed.error(*row, raw, message),
+        }
+    }
+    Ok(parsed)
+}
+
+/// Kraken `trades.csv`: one row per fill, fees in the quote currency.
+fn parse_kraken_trades(source: &str) -> Result<ParsedExport, String> {
+    let table = read_table(source, ',', |line| line.contains("pair"))?;
+    let (txid, pair, time, side) = (
+        table.column("txid")?,
+        table.column("pair")?,
+        table.column("time")?,
+        table.column("type")?,
+    );
+    let (price, fee, vol) = (
+        table.column("price")?,
+        table.column("fee")?,
+        table.column("vol")?,
+    );
+
+    let mut parsed = ParsedExport::default();
+    for (row, raw, fields) in &table.rows {
+        let Some((base, quote)) = kraken_pair(field(fields, pair)) else {
+            parsed.error(*row, raw, format!("Unknown pair '{}'", field(fields, pair)));
+            continue;
+        };
+        let kind = match field(fields, side).to_lowercase().as_str() {
+            "buy" => ExchangeRecordKind::Buy,
+            "sell" => ExchangeRecordKind::Sell,
+            other => {
+                parsed.error(*row, raw, format!("Unknown side '{}'", other));
+                continue;
+            }
+        };
+        let (Some(executed_at), Some(amount), Some(price)) = (
+            parse_exchange_time(field(fields, time), None),
+            parse_number(field(fields, vol)),
+            parse_number(field(fields, price)),
+        ) else {
+            parsed.error(*row, raw, "Could not read the time, volume or price");
+            continue;
+        };
+        match trade_record(
+            *row,
+            raw,
+            kind,
+            base,
+            amount,
+            price,
+            &quote,
+            parse_number(field(fields, fee)).unwrap_or(0.0),
+            executed_at,
+            Some(field(fields, txid).to_string()).filter(|id| !id.is_empty()),
+        ) {
+            Ok(record) => parsed.records.push(record),
+            Err(message) => parsed.error(*row, raw, message),
+        }
+    }
+    Ok(parsed)
+}
+
+/// Kraken `ledgers.csv`. Deposits and withdrawals are taken; trade legs are
+/// covered by `trades.csv`.
+fn parse_kraken_ledgers(source: &str) -> Result<ParsedExport, String> {
+    let table = read_table(source, ',', |line| line.contains("refid"))?;
+    let (txid, time, kind_col, asset, amount) = (
+        table.column("txid")?,
+        table.column("time")?,
+        table.column("type")?,
+        table.column("asset")?,
+        table.column("amount")?,
+    );
+
+    let mut parsed = ParsedExport::default();
+    for (row, raw, fields) in &table.rows {
+        let kind = match field(fields, kind_col).to_lowercase().as_str() {
+            "deposit" => ExchangeRecordKind::Deposit,
+            "withdrawal" => ExchangeRecordKind::Withdrawal,
+            other => {
+                parsed.ignore(
+                    *row,
+                    raw,
+                    format!(
+                        "Ledger '{}' entries are not imported; use the trades export for trades",
+                        other
+                    ),
+                );
+                continue;
+            }
+        };
+        let (Some(executed_at), Some(amount)) = (
+            parse_exchange_time(field(fields, time), None),
+            parse_number(field(fields, amount)),
+        ) else {
+            parsed.error(*row, raw, "Could not read the time or amount");
+            continue;
+        };
+        parsed.records.push(transfer_record(
+            *row,
+            raw,
+            kind,
+            kraken_asset(field(fields, asset)),
+            amount,
+            executed_at,
+            Some(field(fields, txid).to_string()).filter(|id| !id.is_empty()),
+        ));
+    }
+    Ok(parsed)
+}
+
+fn parse_generic(source: &str, mapping: &ExchangeCsvMapping) -> Result<ParsedExport, String> {
+    let delimiter = mapping.delimiter.unwrap_or(',');
+    let table = if mapping.has_header {
+        read_table(source, delimiter, |_| true)?
+    } else {
+        CsvTable {
+            header: Vec::new(),
+            rows: source
+                .lines()
+                .enumerate()
+                .filter(|(_, line)| !line.trim().is_empty())
+                .map(|(index, line)| (index + 1, line, split_csv_line(line, delimiter)))
+                .collect(),
+        }
+    };
+    let index = |column: &CsvColumn| match column {
+        CsvColumn::Index(index) => Ok(*index),
+        CsvColumn::Name(name) => table.column(name),
+    };
+    let optional = |column: &Option<CsvColumn>| column.as_ref().map(&index).transpose();
+    let (time, kind_col, asset_col, amount) = (
+        index(&mapping.timestamp)?,
+        index(&mapping.kind)?,
+        index(&mapping.asset)?,
+        index(&mapping.amount)?,
+    );
+    let (price, quote_col, fee, reference) = (
+        optional(&mapping.price)?,
+        optional(&mapping.quote_asset)?,
+        optional(&mapping.fee)?,
+        optional(&mapping.reference)?,
+    );
+
+    let mut parsed = ParsedExport::default();
+    for (row, raw, fields) in &table.rows {
+        let cell = |col: Option<usize>| col.map(|i| field(fields, i)).unwrap_or("");
+        let (Some(executed_at), Some(quantity)) = (
+            parse_exchange_time(field(fields, time), mapping.date_format.as_deref()),
+            parse_number(field(fields, amount)),
+        ) else {
+            parsed.error(*row, raw, "Could not read the timestamp or amount");
+            continue;
+        };
+        let asset = field(fields, asset_col).to_uppercase();
+        let external_ref = Some(cell(reference).to_string()).filter(|r| !r.is_empty());
+        let kind = match field(fields, kind_col).to_lowercase().as_str() {
+            "buy" => ExchangeRecordKind::Buy,
+            "sell" => ExchangeRecordKind::Sell,
+            "deposit" | "receive" => ExchangeRecordKind::Deposit,
+            "withdraw" | "withdrawal" | "send" => ExchangeRecordKind::Withdrawal,
+            other => {
+                parsed.error(*row, raw, format!("Unknown type '{}'", other));
+                continue;
+            }
+        };
+        if !kind.is_trade() {
+            parsed.records.push(transfer_record(
+                *row,
+                raw,
+                kind,
+                asset,
+                quantity,
+                executed_at,
+                external_ref,
+            ));
+            continue;
+        }
+
+        let Some(unit_price) = parse_number(cell(price)) else {
+            parsed.error(*row, raw, "Trade has no price");
+            continue;
+        };
+        let quote = Some(cell(quote_col).to_uppercase())
+            .filter(|q| !q.is_empty())
+            .unwrap_or_else(|| "USD".to_string());
+        match trade_record(
+            *row,
+            raw,
+            kind,
+            asset,
+            quantity.abs(),
+            unit_price,
+            &quote,
+            parse_number(cell(fee)).unwrap_or(0.0),
+            executed_at,
+            external_ref,
+        ) {
+            Ok(record) => parsed.records.push(record),
+            Err(message) => parsed.error(*row, raw, message),
+        }
+    }
+    Ok(parsed)
+}
+
+fn relative_difference(a: f64, b: f64) -> f64 {
+    let scale = a.abs().max(b.abs());
+    if scale == 0.0 {
+        0.0
+    } else {
+        (a - b).abs() / scale
+    }
+}
+
+pub(crate) struct ClassifiedRecords {
+    pub records: Vec<ExchangeRecord>,
+    pub duplicates: Vec<ExchangeDuplicate>,
+    pub review: Vec<ExchangeReviewItem>,
+}
+
+/// Matches records against `existing` by (timestamp, asset, amount). One
+/// near-exact match makes a duplicate; anything looser, several matches, or
+/// a repeat of an earlier row in the same file goes to review.
+pub(crate) fn classify_records(
+    records: Vec<ExchangeRecord>,
+    existing: &[ExistingRecord],
+) -> ClassifiedRecords {
+    let mut classified = ClassifiedRecords {
+        records: Vec::new(),
+        duplicates: Vec::new(),
+        review: Vec::new(),
+    };
+    let mut earlier_rows: Vec<ExistingRecord> = Vec::new();
+    let window = Duration::minutes(REVIEW_WINDOW_MINUTES);
+
+    for record in records {
+        let near = |candidate: &&ExistingRecord| {
+            candidate.kind == record.kind
+                && candidate.asset.eq_ignore_ascii_case(&record.asset)
+                && (candidate.executed_at - record.executed_at).abs() <= window
+                && relative_difference(candidate.amount, record.amount) <= REVIEW_AMOUNT_TOLERANCE
+        };
+        let stored: Vec<ExistingRecord> = existing.iter().filter(near).cloned().collect();
+        let repeated: Vec<ExistingRecord> = earlier_rows.iter().filter(near).cloned().collect();
+
+        earlier_rows.push(ExistingRecord {
+            reference: format!("row {}", record.row),
+            kind: record.kind,
+            asset: record.asset.clone(),
+            amount: record.amount,
+            executed_at: record.executed_at,
+        });
+
+        let exact = |candidate: &ExistingRecord| {
+            (candidate.executed_at - record.executed_at)
+                .num_seconds()
+                .abs()
+                <= EXACT_MATCH_SECONDS
+                && relative_difference(candidate.amount, record.amount) <= EXACT_AMOUNT_TOLERANCE
+        };
+        let duplicate_of = match stored.as_slice() {
+            [only] if repeated.is_empty() && exact(only) => Some(only.clone()),
+            _ => None,
+        };
+        if stored.is_empty() && repeated.is_empty() {
+            classified.records.push(record);
+        } else if let Some(matched) = duplicate_of {
+            classified
+                .duplicates
+                .push(ExchangeDuplicate { record, matched });
+        } else {
+            classified.review.push(ExchangeReviewItem {
+                candidates: stored.into_iter().chain(repeated).collect(),
+                record,
+            });
+        }
+    }
+
+    classified
+}
+
+/// Records to import once every review item has a decision, oldest first.
+pub(crate) fn apply_decisions(
+    preview: &ExchangeImportPreview,
+    decisions: Vec<ExchangeReviewDecision>,
+) -> Result<Vec<ExchangeRecord>, String> {
+    let review_keys: HashSet<(usize, usize)> = preview
+        .review
+        .iter()
+        .map(|item| item.record.key())
+        .collect();
+
+    let mut by_key = HashMap::new();
+    for decision in decisions {
+        let key = decision.key();
+        if !review_keys.contains(&key) {
+            return Err(format!("Row {} is not awaiting review", key.0));
+        }
+        if by_key.insert(key, decision).is_some() {
+            return Err(format!("Row {} has more than one decision", key.0));
+        }
+    }
+
+    let undecided: Vec<String> = preview
+        .review
+        .iter()
+        .filter(|item| !by_key.contains_key(&item.record.key()))
+        .map(|item| item.record.row.to_string())
+        .collect();
+    if !undecided.is_empty() {
+        return Err(format!(
+            "Rows {} need an import or skip decision",
+            undecided.join(", ")
+        ));
+    }
+
+    let mut records: Vec<ExchangeRecord> = preview
+        .records
+        .iter()
+        .cloned()
+        .chain(
+            preview
+                .review
+                .iter()
+                .filter(|item| {
+                    matches!(
+                        by_key.get(&item.record.key()),
+                        Some(ExchangeReviewDecision::Import { .. })
+                    )
+                })
+                .map(|item| item.record.clone()),
+        )
+        .collect();
+    records.sort_by_key(|record| (record.executed_at, record.row, record.leg));
+    Ok(records)
+}
+
+/// Adds a batch's buys as lots and its sells as disposals. Returns what the
+/// sells took from lots the batch did not create.
+pub(crate) fn apply_batch_to_lots(
+    lots: &mut TaxLotsState,
+    batch_id: &str,
+    records: &[ExchangeRecord],
+) -> Vec<LotDraw> {
+    let prefix = batch_prefix(batch_id);
+    let mut draws = Vec::new();
+    for record in records {
+        let Some(price) = record.price_usd else {
+            continue;
+        };
+        let id = record_id(batch_id, record);
+        match record.kind {
+            ExchangeRecordKind::Buy => lots.add_lot(TaxLot {
+                id,
+                symbol: record.asset.clone(),
+                mint: exchange_mint(&record.asset),
+                amount: record.amount,
+                cost_basis: record.amount * price + record.fee_usd,
+                price_per_unit: price,
+                acquired_at: record.executed_at.to_rfc3339(),
+                disposed_amount: None,
+                disposed_at: None,
+                realized_gain: None,
+            }),
+            ExchangeRecordKind::Sell => {
+                let proceeds = price - record.fee_usd / record.amount;
+                let taken = lots.record_sale(
+                    &record.asset,
+                    record.amount,
+                    proceeds,
+                    record.executed_at,
+                    &format!("{}:", id),
+                );
+                draws.extend(taken.into_iter().filter(|d| !d.lot_id.starts_with(&prefix)));
+            }
+            _ => {}
+        }
+    }
+    draws
+}
+
+fn trade_requests(batch: &ExchangeImportBatch) -> Vec<RecordTradeRequest> {
+    batch
+        .records
+        .iter()
+        .filter_map(|record| {
+            let side = record.kind.side()?;
+            Some(RecordTradeRequest {
+                wallet_address: batch.wallet_address.clone(),
+                token_mint: exchange_mint(&record.asset),
+                token_symbol: record.asset.clone(),
+                side: side.to_string(),
+                amount: record.amount,
+                price: record.price_usd?,
+                fee: record.fee_usd,
+                tx_signature: record.external_ref.clone().unwrap_or_default(),
+                strategy_source: None,
+                external_id: Some(record_id(&batch.id, record)),
+                external_source: Some(batch.external_source.clone()),
+                executed_at: Some(record.executed_at),
+            })
+        })
+        .collect()
+}
+
+/// Committed import batches, persisted as one JSON document, plus the
+/// previews awaiting a commit.
+pub struct ExchangeImportManager {
+    path: Option<PathBuf>,
+    batches: Vec<ExchangeImportBatch>,
+    previews: HashMap<String, ExchangeImportPreview>,
+}
+
+pub type SharedExchangeImportManager = Arc<RwLock<ExchangeImportManager>>;
+
+impl ExchangeImportManager {
+    pub fn new(app: &AppHandle) -> Result<Self, ExchangeImportError> {
+        let mut path = app.app_data_root().map_err(|e| {
+            ExchangeImportError::Io(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("App data directory not found: {}", e),
+            ))
+        })?;
+        fs::create_dir_all(&path)?;
+        path.push(EXCHANGE_IMPORTS_FILE);
+        Self::load(path)
+    }
+
+    pub fn load(path: PathBuf) -> Result<Self, ExchangeImportError> {
+        let batches = if path.exists() {
+            serde_json::from_str(&fs::read_to_string(&path)?)?
+        } else {
+            Vec::new()
+        };
+        Ok(Self {
+            path: Some(path),
+            batches,
+            previews: HashMap::new(),
+        })
+    }
+
+    /// A manager that is never written to disk.
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            batches: Vec::new(),
+            previews: HashMap::new(),
+        }
+    }
+
+    fn persist(&self, batches: &[ExchangeImportBatch]) -> Result<(), ExchangeImportError> {
+        if let Some(path) = &self.path {
+            let tmp = path.with_extension("json.tmp");
+            fs::write(&tmp, serde_json::to_vec_pretty(batches)?)?;
+            fs::rename(&tmp, path)?;
+        }
+        Ok(())
+    }
+
+    pub fn batches(&self) -> Vec<ExchangeImportBatch> {
+        self.batches.clone()
+    }
+
+    /// Deposits and withdrawals from earlier batches, which performance
+    /// history does not hold, as dedupe candidates.
+    pub fn recorded_transfers(&self) -> Vec<ExistingRecord> {
+        self.batches
+            .iter()
+            .flat_map(|batch| {
+                batch
+                    .records
+                    .iter()
+                    .filter(|r| !r.kind.is_trade())
+                    .map(|r| ExistingRecord {
+                        reference: record_id(&batch.id, r),
+                        kind: r.kind,
+                        asset: r.asset.clone(),
+                        amount: r.amount,
+                        executed_at: r.executed_at,
+                    })
+            })
+            .collect()
+    }
+
+    /// Re-creates the lots of every stored batch; tax lots live in memory
+    /// only, so this runs at startup.
+    pub fn replay_into(&mut self, lots: &mut TaxLotsState) {
+        for batch in &mut self.batches {
+            batch.lot_draws = apply_batch_to_lots(lots, &batch.id, &batch.records);
+        }
+    }
+
+    pub fn store_preview(&mut self, preview: ExchangeImportPreview, now: DateTime<Utc>) {
+        self.previews
+            .retain(|_, existing| !existing.is_expired(now));
+        self.previews.insert(preview.id.clone(), preview);
+    }
+
+    pub fn preview(
+        &mut self,
+        preview_id: &str,
+    ) -> Result<ExchangeImportPreview, ExchangeImportError> {
+        self.previews
+            .retain(|_, existing| !existing.is_expired(Utc::now()));
+        self.previews.get(preview_id).cloned().ok_or_else(|| {
+            ExchangeImportError::NotFound(format!(
+                "Import preview {} (it may have expired)",
+                preview_id
+            ))
+        })
+    }
+
+    pub fn add_batch(
+        &mut self,
+        preview_id: &str,
+        batch: ExchangeImportBatch,
+    ) -> Result<(), ExchangeImportError> {
+        let mut next = self.batches.clone();
+        next.push(batch);
+        self.persist(&next)?;
+        self.batches = next;
+        self.previews.remove(preview_id);
+        Ok(())
+    }
+
+    pub fn take_batch(
+        &mut self,
+        batch_id: &str,
+    ) -> Result<ExchangeImportBatch, ExchangeImportError> {
+        let index = self
+            .batches
+            .iter()
+            .position(|batch| batch.id == batch_id)
+            .ok_or_else(|| ExchangeImportError::NotFound(format!("Import batch {}", batch_id)))?;
+        let mut next = self.batches.clone();
+        let batch = next.remove(index);
+        self.persist(&next)?;
+        self.batches = next;
+        Ok(batch)
+    }
+}
+
+#[tauri::command]
+pub async fn exchange_import_preview(
+    manager: State<'_, SharedExchangeImportManager>,
+    performance: State<'_, SharedPerformanceDatabase>,
+    source: String,
+    format: ExchangeImportFormat,
+    wallet_address: String,
+) -> Result<ExchangeImportPreview, String> {
+    let wallet_address = wallet_address.trim().to_string();
+    if wallet_address.is_empty() {
+        return Err("A wallet address is required to attribute imported trades".to_string());
+    }
+    let parsed = parse_export(&source, &format)?;
+
+    let mut existing = manager.read().recorded_transfers();
+    let window = Duration::minutes(REVIEW_WINDOW_MINUTES);
+    let times = parsed.records.iter().map(|r| r.executed_at);
+    if let (Some(from), Some(to)) = (times.clone().min(), times.max()) {
+        let trades = performance
+            .read()
+            .await
+            .get_trades_between(&wallet_address, from - window, to + window)
+            .await
+            .map_err(|e| e.to_string())?;
+        existing.extend(trades.into_iter().filter_map(|trade| {
+            let kind = match trade.side.as_str() {
+                "buy" => ExchangeRecordKind::Buy,
+                "sell" => ExchangeRecordKind::Sell,
+                _ => return None,
+            };
+            Some(ExistingRecord {
+                reference: trade.id,
+                kind,
+                asset: trade.token_symbol,
+                amount: trade.amount,
+                executed_at: trade.timestamp,
+            })
+        }));
+    }
+
+    let classified = classify_records(parsed.records, &existing);
+    let now = Utc::now();
+    let preview = ExchangeImportPreview {
+        id: uuid::Uuid::new_v4().to_string(),
+        format: format.as_str().to_string(),
+        external_source: format.external_source(),
+        wallet_address,
+        records: classified.records,
+        duplicates: classified.duplicates,
+        review: classified.review,
+        ignored: parsed.ignored,
+        errors: parsed.errors,
+        created_at: now,
+        expires_at: now + Duration::minutes(PREVIEW_TTL_MINUTES),
+    };
+    manager.write().store_preview(preview.clone(), now);
+    Ok(preview)
+}
+
+#[tauri::command]
+pub async fn exchange_import_commit(
+    manager: State<'_, SharedExchangeImportManager>,
+    performance: State<'_, SharedPerformanceDatabase>,
+    tax_lots: State<'_, SharedTaxLotsState>,
+    preview_id: String,
+    decisions: Vec<ExchangeReviewDecision>,
+) -> Result<ExchangeImportBatch, String> {
+    let preview = manager
+        .write()
+        .preview(&preview_id)
+        .map_err(|e| e.to_string())?;
+    let records = apply_decisions(&preview, decisions)?;
+
+    let mut batch = ExchangeImportBatch {
+        id: uuid::Uuid::new_v4().to_string(),
+        format: preview.format.clone(),
+        external_source: preview.external_source.clone(),
+        wallet_address: preview.wallet_address.clone(),
+        imported_at: Utc::now(),
+        records,
+        trades_recorded: 0,
+        lot_draws: Vec::new(),
+    };
+
+    let db = performance.read().await;
+    let prefix = batch_prefix(&batch.id);
+    match db.backfill_trades(trade_requests(&batch)).await {
+        Ok(report) => batch.trades_recorded = report.inserted,
+        Err(err) => {
+            let _ = db.delete_trades_by_external_prefix(&prefix).await;
+            return Err(err.to_string());
+        }
+    }
+
+    {
+        let mut lots = tax_lots
+            .lock()
+            .map_err(|_| "Tax lots unavailable".to_string())?;
+        batch.lot_draws = apply_batch_to_lots(&mut lots, &batch.id, &batch.records);
+    }
+
+    let stored = manager.write().add_batch(&preview_id, batch.clone());
+    if let Err(err) = stored {
+        let _ = db.delete_trades_by_external_prefix(&prefix).await;
+        if let Ok(mut lots) = tax_lots.lock() {
+            lots.remove_lots_with_prefix(&prefix);
+            batch
+                .lot_draws
+                .iter()
+                .for_each(|draw| lots.restore_lot(draw));
+        }
+        return Err(err.to_string());
+    }
+    Ok(batch)
+}
+
+#[tauri::command]
+pub async fn exchange_import_batches(
+    manager: State<'_, SharedExchangeImportManager>,
+) -> Result<Vec<ExchangeImportBatch>, String> {
+    Ok(manager.read().batches())
+}
+
+/// Undoes one import: its trades leave performance history, its lots and
+/// disposals are dropped and quantity its sells took from other lots is
+/// given back.
+#[tauri::command]
+pub async fn remove_import_batch(
+    manager: State<'_, SharedExchangeImportManager>,
+    performance: State<'_, SharedPerformanceDatabase>,
+    tax_lots: State<'_, SharedTaxLotsState>,
+    id: String,
+) -> Result<ExchangeImportRemoval, String> {
+    let batch = manager.write().take_batch(&id).map_err(|e| e.to_string())?;
+    let prefix = batch_prefix(&batch.id);
+
+    let lots_removed = {
+        let mut lots = tax_lots
+            .lock()
+            .map_err(|_| "Tax lots unavailable".to_string())?;
+        let removed = lots.remove_lots_with_prefix(&prefix);
+        batch
+            .lot_draws
+            .iter()
+            .for_each(|draw| lots.restore_lot(draw));
+        removed
+    };
+    let trades_removed = performance
+        .read()
+        .await
+        .delete_trades_by_external_prefix(&prefix)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(ExchangeImportRemoval {
+        batch_id: batch.id,
+        trades_removed,
+        lots_removed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(y: i32, mo: u32, d: u32, h: u32, mi: u32, s: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, mo, d, h, mi, s).unwrap()
+    }
+
+    fn fixture(name: &str) -> &'static str {
+        match name {
+            "binance_trades" => include_str!("fixtures/exchange_import/binance_trades.csv"),
+            "binance_statement" => include_str!("fixtures/exchange_import/binance_statement.csv"),
+            "coinbase" => include_str!("fixtures/exchange_import/coinbase.csv"),
+            "kraken_trades" => include_str!("fixtures/exchange_import/kraken_trades.csv"),
+            "kraken_ledgers" => include_str!("fixtures/exchange_import/kraken_ledgers.csv"),
+            other => panic!("unknown fixture {}", other),
+        }
+    }
+
+    fn existing(
+        kind: ExchangeRecordKind,
+        asset: &str,
+        amount: f64,
+        when: DateTime<Utc>,
+    ) -> ExistingRecord {
+        ExistingRecord {
+            reference: "trade_1".to_string(),
+            kind,
+            asset: asset.to_string(),
+            amount,
+            executed_at: when,
+        }
+    }
+
+    #[test]
+    fn test_exchange_dates() {
+        assert_eq!(
+            parse_exchange_time("21-03-04 12:30:45", None),
+            Some(at(2021, 3, 4, 12, 30, 45))
+        );
+        assert_eq!(
+            parse_exchange_time("2023-01-05 14:22:10 UTC", None),
+            Some(at(2023, 1, 5, 14, 22, 10))
+        );
+        assert_eq!(
+            parse_exchange_time("2021-03-04T12:30:45Z", None),
+            Some(at(2021, 3, 4, 12, 30, 45))
+        );
+        let fractional = parse_exchange_time("2021-03-04 12:30:45.1234", None).unwrap();
+        assert_eq!(fractional.timestamp_subsec_micros(), 123_400);
+        assert_eq!(
+            parse_exchange_time("04/03/2021 12:30", Some("%d/%m/%Y %H:%M")),
+            Some(at(2021, 3, 4, 12, 30, 0))
+        );
+        assert_eq!(parse_exchange_time("yesterday", None), None);
+    }
+
+    #[test]
+    fn test_binance_trade_history() {
+        let parsed =
+            parse_export(fixture("binance_trades"), &ExchangeImportFormat::Binance).unwrap();
+        assert_eq!(parsed.records.len(), 2);
+        let buy = &parsed.records[0];
+        assert_eq!(buy.row, 2);
+        assert_eq!(buy.kind, ExchangeRecordKind::Buy);
+        assert_eq!(buy.asset, "SOL");
+        assert_eq!(buy.amount, 10.0);
+        assert_eq!(buy.price_usd, Some(13.52));
+        assert!((buy.fee_usd - 0.1352).abs() < 1e-9);
+        assert_eq!(parsed.records[1].fee_usd, 0.06);
+        // ETHBTC has no USD value.
+        assert_eq!(parsed.errors.len(), 1);
+        assert!(parsed.errors[0].message.contains("BTC"));
+    }
+
+    #[test]
+    fn test_binance_statement() {
+        let parsed =
+            parse_export(fixture("binance_statement"), &ExchangeImportFormat::Binance).unwrap();
+        assert_eq!(parsed.records.len(), 2);
+        assert_eq!(parsed.records[0].kind, ExchangeRecordKind::Deposit);
+        assert_eq!(parsed.records[0].executed_at, at(2021, 3, 1, 8, 0, 0));
+        assert_eq!(parsed.records[1].kind, ExchangeRecordKind::Withdrawal);
+        assert_eq!(parsed.records[1].amount, 30.5);
+        assert_eq!(parsed.ignored.len(), 2);
+        assert!(parsed.errors.is_empty());
+    }
+
+    #[test]
+    fn test_coinbase_report() {
+        let parsed = parse_export(fixture("coinbase"), &ExchangeImportFormat::Coinbase).unwrap();
+        assert!(parsed.errors.is_empty(), "{:?}", parsed.errors);
+        let kinds: Vec<_> = parsed
+            .records
+            .iter()
+            .map(|r| (r.row, r.leg, r.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (5, 0, ExchangeRecordKind::Buy),
+                (6, 0, ExchangeRecordKind::Sell),
+                (7, 0, ExchangeRecordKind::Deposit),
+                (8, 0, ExchangeRecordKind::Sell),
+                (8, 1, ExchangeRecordKind::Buy),
+                (9, 0, ExchangeRecordKind::Withdrawal),
+            ]
+        );
+        let buy = &parsed.records[0];
+        assert_eq!(buy.executed_at, at(2021, 3, 4, 12, 30, 45));
+        assert!((buy.price_usd.unwrap() - 13.6).abs() < 1e-9);
+        assert_eq!(buy.fee_usd, 0.99);
+        let sell = &parsed.records[1];
+        assert_eq!(sell.amount, 12.5);
+        assert_eq!(sell.executed_at, at(2023, 1, 5, 14, 22, 10));
+        assert_eq!(parsed.records[2].amount, 0.015);
+
+        let convert_buy = &parsed.records[4];
+        assert_eq!(convert_buy.asset, "SOL");
+        assert_eq!(convert_buy.amount, 12.0);
+        assert!((convert_buy.price_usd.unwrap() * 12.0 - 770.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_kraken_trades_and_ledgers() {
+        let trades = parse_export(fixture("kraken_trades"), &ExchangeImportFormat::Kraken).unwrap();
+        assert!(trades.errors.is_empty(), "{:?}", trades.errors);
+        assert_eq!(trades.records.len(), 2);
+        let buy = &trades.records[0];
+        assert_eq!(buy.asset, "BTC");
+        assert_eq!(buy.amount, 0.02);
+        assert_eq!(buy.fee_usd, 2.496);
+        assert_eq!(buy.external_ref.as_deref(), Some("TQWERT-ABCDE-FGHIJK"));
+        assert_eq!(trades.records[1].asset, "SOL");
+        assert_eq!(trades.records[1].kind, ExchangeRecordKind::Sell);
+        assert_eq!(trades.records[1].executed_at.timestamp_subsec_millis(), 500);
+
+        let ledgers =
+            parse_export(fixture("kraken_ledgers"), &ExchangeImportFormat::Kraken).unwrap();
+        assert_eq!(ledgers.records.len(), 2);
+        assert_eq!(ledgers.records[0].asset, "USD");
+        assert_eq!(ledgers.records[0].kind, ExchangeRecordKind::Deposit);
+        assert_eq!(ledgers.records[1].asset, "BTC");
+        assert_eq!(ledgers.records[1].amount, 0.015);
+        assert_eq!(ledgers.ignored.len(), 1);
+
+        assert_eq!(
+            kraken_pair("XETHZEUR"),
+            Some(("ETH".to_string(), "EUR".to_string()))
+        );
+        assert_eq!(kraken_asset("XXDG"), "DOGE");
+    }
+
+    #[test]
+    fn test_generic_mapping() {
+        let source = "when;what;coin;qty;px;id\n2022-07-01;BUY;jup;100;0.5;a1\n2022-07-02;send;JUP;-40;;a2\n2022-07-03;stake;JUP;1;;a3\n";
+        let mapping = ExchangeCsvMapping {
+            timestamp: CsvColumn::Name("when".to_string()),
+            kind: CsvColumn::Name("what".to_string()),
+            asset: CsvColumn::Index(2),
+            amount: CsvColumn::Name("qty".to_string()),
+            price: Some(CsvColumn::Name("px".to_string())),
+            quote_asset: None,
+            fee: None,
+            reference: Some(CsvColumn::Name("id".to_string())),
+            has_header: true,
+            delimiter: Some(';'),
+            date_format: None,
+            source_name: Some(" Bybit ".to_string()),
+        };
+        let format = ExchangeImportFormat::Generic { mapping };
+        assert_eq!(format.external_source(), "bybit");
+
+        let parsed = parse_export(source, &format).unwrap();
+        assert_eq!(parsed.records.len(), 2);
+        assert_eq!(parsed.records[0].asset, "JUP");
+        assert_eq!(parsed.records[0].external_ref.as_deref(), Some("a1"));
+        assert_eq!(parsed.records[1].kind, ExchangeRecordKind::Withdrawal);
+        assert_eq!(parsed.records[1].amount, 40.0);
+        assert_eq!(parsed.errors.len(), 1);
+    }
+
+    #[test]
+    fn test_dedupe_and_review() {
+        let parsed =
+            parse_export(fixture("binance_trades"), &ExchangeImportFormat::Binance).unwrap();
+        let buy_at = parsed.records[0].executed_at;
+        let sell_at = parsed.records[1].executed_at;
+        let stored = vec![
+            existing(ExchangeRecordKind::Buy, "SOL", 10.0, buy_at),
+            existing(
+                ExchangeRecordKind::Sell,
+                "SOL",
+                3.95,
+                sell_at + Duration::minutes(3),
+            ),
+        ];
+        let classified = classify_records(parsed.records.clone(), &stored);
+        assert!(classified.records.is_empty());
+        assert_eq!(classified.duplicates.len(), 1);
+        assert_eq!(classified.duplicates[0].record.row, 2);
+        assert_eq!(classified.review.len(), 1);
+        assert_eq!(classified.review[0].record.row, 3);
+
+        // The same fill twice in one file is flagged rather than dropped.
+        let mut repeated = parsed.records.clone();
+        repeated.push(ExchangeRecord {
+            row: 9,
+            ..parsed.records[0].clone()
+        });
+        let classified = classify_records(repeated, &[]);
+        assert_eq!(classified.records.len(), 2);
+        assert_eq!(classified.review[0].candidates[0].reference, "row 2");
+    }
+
+    #[test]
+    fn test_commit_decisions_and_lot_feed() {
+        let parsed = parse_export(fixture("coinbase"), &ExchangeImportFormat::Coinbase).unwrap();
+        let review_at = parsed.records[0].executed_at;
+        let classified = classify_records(
+            parsed.records,
+            &[existing(ExchangeRecordKind::Buy, "SOL", 2.49, review_at)],
+        );
+        let now = Utc::now();
+        let preview = ExchangeImportPreview {
+            id: "preview".to_string(),
+            format: "coinbase".to_string(),
+            external_source: "coinbase".to_string(),
+            wallet_address: "wallet".to_string(),
+            records: classified.records,
+            duplicates: classified.duplicates,
+            review: classified.review,
+            ignored: Vec::new(),
+            errors: Vec::new(),
+            created_at: now,
+            expires_at: now + Duration::minutes(PREVIEW_TTL_MINUTES),
+        };
+        assert_eq!(preview.review.len(), 1);
+        assert!(apply_decisions(&preview, Vec::new())
+            .unwrap_err()
+            .contains("Rows 5"));
+        assert!(apply_decisions(
+            &preview,
+            vec![ExchangeReviewDecision::Skip { row: 6, leg: 0 }]
+        )
+        .is_err());
+
+        let records = apply_decisions(
+            &preview,
+            vec![ExchangeReviewDecision::Import { row: 5, leg: 0 }],
+        )
+        .unwrap();
+        assert_eq!(records.len(), 6);
+        assert!(records
+            .windows(2)
+            .all(|w| w[0].executed_at <= w[1].executed_at));
+
+        let mut lots = TaxLotsState::default();
+        lots.add_lot(TaxLot {
+            id: "old-sol".to_string(),
+            symbol: "SOL".to_string(),
+            mint: SOL_MINT.to_string(),
+            amount: 20.0,
+            cost_basis: 200.0,
+            price_per_unit: 10.0,
+            acquired_at: at(2020, 1, 1, 0, 0, 0).to_rfc3339(),
+            disposed_amount: None,
+            disposed_at: None,
+            realized_gain: None,
+        });
+        let draws = apply_batch_to_lots(&mut lots, "b1", &records);
+        // FIFO sells the 12.5 SOL out of the older lot, not the imported buy.
+        assert_eq!(
+            draws,
+            vec![LotDraw {
+                lot_id: "old-sol".to_string(),
+                amount: 12.5,
+                cost_basis: 125.0,
+            }]
+        );
+        let all = lots.all_lots();
+        let sale = all
+            .iter()
+            .find(|l| l.id == "import:b1:6:0:old-sol")
+            .unwrap();
+        // $168.50 less the $2.00 fee against $125 of cost.
+        assert!((sale.realized_gain.unwrap() - 41.5).abs() < 1e-9);
+        assert!(all
+            .iter()
+            .any(|l| l.id == "import:b1:5:0" && l.amount == 2.5));
+        assert!(all
+            .iter()
+            .any(|l| l.id == "import:b1:8:1" && l.amount == 12.0));
+
+        let removed = lots.remove_lots_with_prefix(&batch_prefix("b1"));
+        draws.iter().for_each(|draw| lots.restore_lot(draw));
+        assert_eq!(removed, 4);
+        let old = lots
+            .all_lots()
+            .into_iter()
+            .find(|l| l.id == "old-sol")
+            .unwrap();
+        assert_eq!((old.amount, old.cost_basis), (20.0, 200.0));
+    }
+}
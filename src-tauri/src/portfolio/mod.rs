@@ -1,13 +1,25 @@
 pub mod ai_advisor;
 pub mod analytics;
+pub mod correlations;
+pub mod exchange_import;
 pub mod rebalancer;
+pub mod reports;
+pub mod state_export;
 pub mod tax_lots;
+pub mod token_annotations;
 pub mod types;
+pub mod watchlist_import;
 pub mod watchlists;
 
 pub use ai_advisor::*;
 pub use analytics::*;
+pub use correlations::*;
+pub use exchange_import::*;
 pub use rebalancer::*;
+pub use reports::*;
+pub use state_export::*;
 pub use tax_lots::*;
+pub use token_annotations::*;
 pub use types::*;
+pub use watchlist_import::*;
 pub use watchlists::*;
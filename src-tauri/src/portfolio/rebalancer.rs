@@ -5,6 +5,9 @@ use chrono::{DateTime, Duration, Utc};
 use serde::Deserialize;
 use tauri::State;
 
+use crate::config::privacy_mode::{Masked, SharedPrivacyMode};
+use crate::wallet::multi_wallet::MultiWalletManager;
+
 use super::types::{
     AllocationTarget, PortfolioMetrics, Position, RebalanceAction, RebalanceHistory,
     RebalanceProfile,
@@ -74,7 +77,7 @@ impl Default for RebalancerState {
 }
 
 impl RebalancerState {
-    fn list_profiles(&self) -> Vec<RebalanceProfile> {
+    pub(crate) fn list_profiles(&self) -> Vec<RebalanceProfile> {
         self.profiles
             .values()
             .map(|p| p.profile.clone())
@@ -454,17 +457,27 @@ pub fn check_rebalance_triggers_internal(
 #[tauri::command]
 pub fn get_portfolio_metrics(
     data: State<'_, SharedPortfolioData>,
-) -> Result<PortfolioMetrics, String> {
-    data.lock()
+    privacy: State<'_, SharedPrivacyMode>,
+) -> Result<Masked<PortfolioMetrics>, String> {
+    let metrics = data
+        .lock()
         .map_err(|_| "Portfolio data locked".to_string())
-        .map(|guard| guard.metrics())
+        .map(|guard| guard.metrics())?;
+    let privacy = privacy.read().map_err(|e| e.to_string())?;
+    Ok(privacy.apply(metrics))
 }
 
 #[tauri::command]
-pub fn get_positions(data: State<'_, SharedPortfolioData>) -> Result<Vec<Position>, String> {
-    data.lock()
+pub fn get_positions(
+    data: State<'_, SharedPortfolioData>,
+    privacy: State<'_, SharedPrivacyMode>,
+) -> Result<Masked<Vec<Position>>, String> {
+    let positions = data
+        .lock()
         .map_err(|_| "Portfolio data locked".to_string())
-        .map(|guard| guard.positions())
+        .map(|guard| guard.positions())?;
+    let privacy = privacy.read().map_err(|e| e.to_string())?;
+    Ok(privacy.apply(positions))
 }
 
 #[tauri::command]
@@ -541,7 +554,15 @@ pub fn execute_rebalance(
     dry_run: bool,
     state: State<'_, SharedRebalancerState>,
     data: State<'_, SharedPortfolioData>,
+    wallets: State<'_, MultiWalletManager>,
 ) -> Result<RebalanceHistory, String> {
+    // Rebalancing trades from the active wallet.
+    if !dry_run {
+        wallets
+            .ensure_active_can_sign()
+            .map_err(|e| e.to_string())?;
+    }
+
     let mut rebalancer = state
         .lock()
         .map_err(|_| "Rebalancer unavailable".to_string())?;
@@ -0,0 +1,756 @@
+//! Section builders for scheduled reports. Each builder works with whatever
+//! data could be gathered and flags its section incomplete rather than
+//! failing, so one missing source never sinks the whole report.
+
+use chrono::{DateTime, Datelike, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::portfolio::types::{AllocationTarget, PortfolioMetrics, Position, TaxLot};
+
+const TOP_MOVERS: usize = 3;
+const DEFAULT_HOLDING_PERIOD_DAYS: i64 = 365;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportSection {
+    pub id: String,
+    pub title: String,
+    /// One line, used for chat deliveries.
+    pub summary: String,
+    /// Markdown body, without the heading.
+    pub body: String,
+    pub data: serde_json::Value,
+    pub incomplete: bool,
+    pub issues: Vec<String>,
+}
+
+impl ReportSection {
+    fn new(id: &str, title: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            title: title.to_string(),
+            summary: String::new(),
+            body: String::new(),
+            data: serde_json::Value::Null,
+            incomplete: false,
+            issues: Vec::new(),
+        }
+    }
+
+    fn flag(&mut self, issue: impl Into<String>) {
+        self.incomplete = true;
+        self.issues.push(issue.into());
+    }
+
+    /// A section with nothing to show because its source failed.
+    fn unavailable(mut self, issue: String) -> Self {
+        self.flag(issue);
+        self.summary = "Unavailable".to_string();
+        self.body = "No data was available for this section.".to_string();
+        self
+    }
+}
+
+/// Data gathered for a weekly portfolio report. Each source keeps its own
+/// error so the sections depending on it can say why they are incomplete.
+#[derive(Debug, Clone)]
+pub struct PortfolioReportInputs {
+    pub metrics: Result<PortfolioMetrics, String>,
+    pub positions: Result<Vec<Position>, String>,
+    pub targets: Result<Vec<AllocationTarget>, String>,
+    pub triggered_alerts: Result<usize, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AllocationDrift {
+    pub symbol: String,
+    pub current_percent: f64,
+    pub target_percent: f64,
+    /// Percentage points above (positive) or below the target.
+    pub drift: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportMover {
+    pub symbol: String,
+    pub pnl_percent: f64,
+    pub pnl: f64,
+}
+
+pub fn portfolio_sections(inputs: &PortfolioReportInputs) -> Vec<ReportSection> {
+    let (priced, unpriced): (Vec<&Position>, Vec<&Position>) = match &inputs.positions {
+        Ok(positions) => positions.iter().partition(|p| is_priced(p)),
+        Err(_) => (Vec::new(), Vec::new()),
+    };
+    let unpriced: Vec<String> = unpriced.iter().map(|p| p.symbol.clone()).collect();
+
+    vec![
+        pnl_section(&inputs.metrics, &unpriced),
+        allocation_section(&inputs.positions, &priced, &unpriced, &inputs.targets),
+        movers_section(&inputs.positions, &priced, &unpriced),
+        alerts_section(&inputs.triggered_alerts),
+    ]
+}
+
+fn is_priced(position: &Position) -> bool {
+    position.current_price.is_finite() && position.current_price > 0.0
+}
+
+fn unpriced_issue(symbols: &[String]) -> String {
+    format!(
+        "No price for {}, left out of the figures",
+        symbols.join(", ")
+    )
+}
+
+fn pnl_section(metrics: &Result<PortfolioMetrics, String>, unpriced: &[String]) -> ReportSection {
+    let section = ReportSection::new("pnl", "Profit & Loss");
+    let metrics = match metrics {
+        Ok(metrics) => metrics,
+        Err(e) => return section.unavailable(format!("Portfolio metrics unavailable: {}", e)),
+    };
+
+    let mut section = section;
+    if !unpriced.is_empty() {
+        section.flag(unpriced_issue(unpriced));
+    }
+    section.summary = format!(
+        "{} ({}) this week, portfolio at {}",
+        signed_money(metrics.weekly_pnl),
+        signed_percent(metrics.weekly_pnl_percent),
+        money(metrics.total_value)
+    );
+    section.body = [
+        format!("- Portfolio value: {}", money(metrics.total_value)),
+        format!(
+            "- This week: {} ({})",
+            signed_money(metrics.weekly_pnl),
+            signed_percent(metrics.weekly_pnl_percent)
+        ),
+        format!(
+            "- All time: {} ({})",
+            signed_money(metrics.all_time_pnl),
+            signed_percent(metrics.all_time_pnl_percent)
+        ),
+        format!("- Realized: {}", signed_money(metrics.realized_pnl)),
+        format!("- Unrealized: {}", signed_money(metrics.unrealized_pnl)),
+    ]
+    .join("\n");
+    section.data = json!({
+        "totalValue": metrics.total_value,
+        "weeklyPnl": metrics.weekly_pnl,
+        "weeklyPnlPercent": metrics.weekly_pnl_percent,
+        "allTimePnl": metrics.all_time_pnl,
+        "allTimePnlPercent": metrics.all_time_pnl_percent,
+        "realizedPnl": metrics.realized_pnl,
+        "unrealizedPnl": metrics.unrealized_pnl,
+    });
+    section
+}
+
+fn allocation_section(
+    positions: &Result<Vec<Position>, String>,
+    priced: &[&Position],
+    unpriced: &[String],
+    targets: &Result<Vec<AllocationTarget>, String>,
+) -> ReportSection {
+    let section = ReportSection::new("allocation_drift", "Allocation Drift");
+    if let Err(e) = positions {
+        return section.unavailable(format!("Positions unavailable: {}", e));
+    }
+    let targets = match targets {
+        Ok(targets) if !targets.is_empty() => targets,
+        Ok(_) => {
+            return section
+                .unavailable("No enabled rebalance profile to compare against".to_string())
+        }
+        Err(e) => return section.unavailable(format!("Rebalance targets unavailable: {}", e)),
+    };
+    let total: f64 = priced.iter().map(|p| p.amount * p.current_price).sum();
+    if total <= 0.0 {
+        return section.unavailable("No priced holdings to weigh".to_string());
+    }
+
+    let mut section = section;
+    if !unpriced.is_empty() {
+        section.flag(unpriced_issue(unpriced));
+    }
+
+    let weight = |symbol: &str| {
+        let value: f64 = priced
+            .iter()
+            .filter(|p| p.symbol == symbol)
+            .map(|p| p.amount * p.current_price)
+            .sum();
+        value / total * 100.0
+    };
+    let mut drifts: Vec<AllocationDrift> = targets
+        .iter()
+        .map(|target| {
+            let current_percent = weight(&target.symbol);
+            AllocationDrift {
+                symbol: target.symbol.clone(),
+                current_percent,
+                target_percent: target.target_percent,
+                drift: current_percent - target.target_percent,
+            }
+        })
+        .collect();
+    // Holdings without a target drift from zero.
+    for position in priced {
+        if drifts.iter().all(|d| d.symbol != position.symbol) {
+            let current_percent = weight(&position.symbol);
+            drifts.push(AllocationDrift {
+                symbol: position.symbol.clone(),
+                current_percent,
+                target_percent: 0.0,
+                drift: current_percent,
+            });
+        }
+    }
+    drifts.sort_by(|a, b| b.drift.abs().total_cmp(&a.drift.abs()));
+
+    section.summary = match drifts.first() {
+        Some(largest) => format!(
+            "Largest drift {} at {:+.1} pts",
+            largest.symbol, largest.drift
+        ),
+        None => "No holdings".to_string(),
+    };
+    section.body = drifts
+        .iter()
+        .map(|d| {
+            format!(
+                "- {}: {:.1}% (target {:.1}%, {:+.1} pts)",
+                d.symbol, d.current_percent, d.target_percent, d.drift
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    section.data = json!({ "drifts": drifts });
+    section
+}
+
+fn movers_section(
+    positions: &Result<Vec<Position>, String>,
+    priced: &[&Position],
+    unpriced: &[String],
+) -> ReportSection {
+    let section = ReportSection::new("movers", "Top Winners & Losers");
+    if let Err(e) = positions {
+        return section.unavailable(format!("Positions unavailable: {}", e));
+    }
+
+    let mut section = section;
+    if !unpriced.is_empty() {
+        section.flag(unpriced_issue(unpriced));
+    }
+
+    let mut ranked: Vec<ReportMover> = priced
+        .iter()
+        .filter(|p| p.unrealized_pnl_percent.is_finite())
+        .map(|p| ReportMover {
+            symbol: p.symbol.clone(),
+            pnl_percent: p.unrealized_pnl_percent,
+            pnl: p.unrealized_pnl,
+        })
+        .collect();
+    ranked.sort_by(|a, b| b.pnl_percent.total_cmp(&a.pnl_percent));
+    let winners: Vec<ReportMover> = ranked
+        .iter()
+        .filter(|m| m.pnl_percent > 0.0)
+        .take(TOP_MOVERS)
+        .cloned()
+        .collect();
+    let losers: Vec<ReportMover> = ranked
+        .iter()
+        .rev()
+        .filter(|m| m.pnl_percent < 0.0)
+        .take(TOP_MOVERS)
+        .cloned()
+        .collect();
+
+    let list = |movers: &[ReportMover]| {
+        if movers.is_empty() {
+            return "- None".to_string();
+        }
+        movers
+            .iter()
+            .map(|m| {
+                format!(
+                    "- {}: {} ({})",
+                    m.symbol,
+                    signed_percent(m.pnl_percent),
+                    signed_money(m.pnl)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    let best = |movers: &[ReportMover]| {
+        movers
+            .first()
+            .map(|m| format!("{} {}", m.symbol, signed_percent(m.pnl_percent)))
+            .unwrap_or_else(|| "none".to_string())
+    };
+    section.summary = format!("Best {}, worst {}", best(&winners), best(&losers));
+    section.body = format!(
+        "**Winners**\n{}\n\n**Losers**\n{}",
+        list(&winners),
+        list(&losers)
+    );
+    section.data = json!({ "winners": winners, "losers": losers });
+    section
+}
+
+fn alerts_section(triggered: &Result<usize, String>) -> ReportSection {
+    let section = ReportSection::new("alerts", "Alerts");
+    let count = match triggered {
+        Ok(count) => *count,
+        Err(e) => return section.unavailable(format!("Alert history unavailable: {}", e)),
+    };
+
+    let mut section = section;
+    section.summary = format!("{} alert(s) triggered", count);
+    section.body = format!("- Alerts triggered this week: {}", count);
+    section.data = json!({ "triggered": count });
+    section
+}
+
+/// Rates the monthly estimate applies. Custom rates in the tax settings take
+/// precedence over the jurisdiction's.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EstimateRates {
+    pub short_term_rate: f64,
+    pub long_term_rate: f64,
+    pub state_rate: f64,
+    pub holding_period_days: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct TaxReportInputs {
+    pub lots: Result<Vec<TaxLot>, String>,
+    pub rates: Result<EstimateRates, String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GainsByTerm {
+    pub short_term: f64,
+    pub long_term: f64,
+}
+
+impl GainsByTerm {
+    pub fn total(&self) -> f64 {
+        self.short_term + self.long_term
+    }
+
+    fn add(&mut self, gain: f64, long_term: bool) {
+        if long_term {
+            self.long_term += gain;
+        } else {
+            self.short_term += gain;
+        }
+    }
+}
+
+pub fn tax_sections(inputs: &TaxReportInputs, now: DateTime<Utc>) -> Vec<ReportSection> {
+    let holding_period_days = inputs
+        .rates
+        .as_ref()
+        .map(|rates| rates.holding_period_days)
+        .unwrap_or(DEFAULT_HOLDING_PERIOD_DAYS);
+    let gains = gains_section(&inputs.lots, holding_period_days, now);
+    let liability = liability_section(&gains, &inputs.rates);
+    vec![gains, liability]
+}
+
+fn gains_section(
+    lots: &Result<Vec<TaxLot>, String>,
+    holding_period_days: i64,
+    now: DateTime<Utc>,
+) -> ReportSection {
+    let section = ReportSection::new("realized_gains", "Realized Gains");
+    let lots = match lots {
+        Ok(lots) => lots,
+        Err(e) => return section.unavailable(format!("Tax lots unavailable: {}", e)),
+    };
+
+    let mut section = section;
+    let mut month = GainsByTerm::default();
+    let mut year = GainsByTerm::default();
+    for lot in lots {
+        let Some(disposed_at) = lot.disposed_at.as_deref() else {
+            continue;
+        };
+        let (Ok(disposed), Ok(acquired)) = (
+            disposed_at.parse::<DateTime<Utc>>(),
+            lot.acquired_at.parse::<DateTime<Utc>>(),
+        ) else {
+            section.flag(format!(
+                "Lot {} ({}) has unreadable dates",
+                lot.id, lot.symbol
+            ));
+            continue;
+        };
+        if disposed.year() != now.year() || disposed > now {
+            continue;
+        }
+        let Some(gain) = lot.realized_gain.filter(|g| g.is_finite()) else {
+            section.flag(format!(
+                "Lot {} ({}) was disposed without a realized gain",
+                lot.id, lot.symbol
+            ));
+            continue;
+        };
+
+        let long_term = (disposed - acquired).num_days() > holding_period_days;
+        year.add(gain, long_term);
+        if disposed.month() == now.month() {
+            month.add(gain, long_term);
+        }
+    }
+
+    section.summary = format!(
+        "{} this month, {} this year",
+        signed_money(month.total()),
+        signed_money(year.total())
+    );
+    section.body = [
+        format!(
+            "- Month to date: {} short-term, {} long-term",
+            signed_money(month.short_term),
+            signed_money(month.long_term)
+        ),
+        format!(
+            "- Year to date: {} short-term, {} long-term",
+            signed_money(year.short_term),
+            signed_money(year.long_term)
+        ),
+    ]
+    .join("\n");
+    section.data = json!({ "monthToDate": month, "yearToDate": year });
+    section
+}
+
+/// Estimates tax on year-to-date gains. Each term's net loss is floored at
+/// zero; state tax applies to the combined net gain.
+pub fn estimate_liability(gains: &GainsByTerm, rates: &EstimateRates) -> f64 {
+    gains.short_term.max(0.0) * rates.short_term_rate
+        + gains.long_term.max(0.0) * rates.long_term_rate
+        + gains.total().max(0.0) * rates.state_rate
+}
+
+fn liability_section(
+    gains: &ReportSection,
+    rates: &Result<EstimateRates, String>,
+) -> ReportSection {
+    let section = ReportSection::new("estimated_liability", "Estimated Liability");
+    let year: Option<GainsByTerm> = gains
+        .data
+        .get("yearToDate")
+        .and_then(|value| serde_json::from_value(value.clone()).ok());
+    let Some(year) = year else {
+        return section.unavailable("Realized gains unavailable".to_string());
+    };
+    let rates = match rates {
+        Ok(rates) => rates,
+        Err(e) => return section.unavailable(format!("Tax rates unavailable: {}", e)),
+    };
+
+    let mut section = section;
+    if gains.incomplete {
+        section.flag("Based on incomplete realized gains");
+    }
+    let estimate = estimate_liability(&year, rates);
+    section.summary = format!("About {} owed on this year's gains", money(estimate));
+    section.body = [
+        format!("- Estimated liability: {}", money(estimate)),
+        format!(
+            "- Rates: {:.1}% short-term, {:.1}% long-term, {:.1}% state",
+            rates.short_term_rate * 100.0,
+            rates.long_term_rate * 100.0,
+            rates.state_rate * 100.0
+        ),
+    ]
+    .join("\n");
+    section.data = json!({ "estimate": estimate, "rates": rates });
+    section
+}
+
+fn money(value: f64) -> String {
+    let cents = (value.abs() * 100.0).round() as u64;
+    let digits = (cents / 100).to_string();
+    let mut grouped = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(c);
+    }
+    let sign = if value < 0.0 && cents > 0 { "-" } else { "" };
+    format!("{}${}.{:02}", sign, grouped, cents % 100)
+}
+
+fn signed_money(value: f64) -> String {
+    if value >= 0.0 {
+        format!("+{}", money(value))
+    } else {
+        money(value)
+    }
+}
+
+fn signed_percent(value: f64) -> String {
+    format!("{:+.2}%", value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    fn position(symbol: &str, amount: f64, price: f64, entry: f64) -> Position {
+        let total_value = amount * price;
+        let cost = amount * entry;
+        Position {
+            symbol: symbol.to_string(),
+            mint: format!("{}-mint", symbol),
+            amount,
+            current_price: price,
+            avg_entry_price: entry,
+            total_value,
+            unrealized_pnl: total_value - cost,
+            unrealized_pnl_percent: (total_value - cost) / cost * 100.0,
+            allocation: 0.0,
+        }
+    }
+
+    fn metrics() -> PortfolioMetrics {
+        PortfolioMetrics {
+            total_value: 10_000.0,
+            daily_pnl: 50.0,
+            daily_pnl_percent: 0.5,
+            weekly_pnl: 420.0,
+            weekly_pnl_percent: 4.2,
+            monthly_pnl: 900.0,
+            monthly_pnl_percent: 9.0,
+            all_time_pnl: 2_500.0,
+            all_time_pnl_percent: 25.0,
+            realized_pnl: 1_000.0,
+            unrealized_pnl: 1_500.0,
+            last_updated: Utc::now().to_rfc3339(),
+        }
+    }
+
+    fn target(symbol: &str, percent: f64) -> AllocationTarget {
+        AllocationTarget {
+            symbol: symbol.to_string(),
+            target_percent: percent,
+        }
+    }
+
+    fn inputs(positions: Vec<Position>) -> PortfolioReportInputs {
+        PortfolioReportInputs {
+            metrics: Ok(metrics()),
+            positions: Ok(positions),
+            targets: Ok(vec![target("SOL", 50.0), target("BTC", 50.0)]),
+            triggered_alerts: Ok(3),
+        }
+    }
+
+    fn section<'a>(sections: &'a [ReportSection], id: &str) -> &'a ReportSection {
+        sections.iter().find(|s| s.id == id).unwrap()
+    }
+
+    #[test]
+    fn test_money_formatting() {
+        assert_eq!(money(1234567.891), "$1,234,567.89");
+        assert_eq!(money(-42.5), "-$42.50");
+        assert_eq!(money(0.0), "$0.00");
+        assert_eq!(signed_money(999.999), "+$1,000.00");
+    }
+
+    #[test]
+    fn test_allocation_drift_against_targets() {
+        let sections = portfolio_sections(&inputs(vec![
+            position("SOL", 60.0, 100.0, 80.0),
+            position("BTC", 1.0, 4_000.0, 5_000.0),
+        ]));
+        let drift = section(&sections, "allocation_drift");
+        assert!(!drift.incomplete);
+        let drifts: Vec<AllocationDrift> =
+            serde_json::from_value(drift.data["drifts"].clone()).unwrap();
+        assert_eq!(drifts[0].symbol, "SOL");
+        assert!((drifts[0].current_percent - 60.0).abs() < 1e-9);
+        assert!((drifts[0].drift - 10.0).abs() < 1e-9);
+        assert!((drifts[1].drift + 10.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_unpriced_holding_flags_sections_without_aborting() {
+        let sections = portfolio_sections(&inputs(vec![
+            position("SOL", 60.0, 100.0, 80.0),
+            position("BTC", 1.0, 0.0, 5_000.0),
+        ]));
+        assert_eq!(sections.len(), 4);
+
+        let drift = section(&sections, "allocation_drift");
+        assert!(drift.incomplete);
+        assert!(drift.issues[0].contains("BTC"));
+        // Weights are taken over what could be priced.
+        assert!(drift.body.contains("SOL: 100.0%"));
+
+        assert!(section(&sections, "pnl").incomplete);
+        assert!(!section(&sections, "alerts").incomplete);
+    }
+
+    #[test]
+    fn test_failed_sources_mark_only_their_sections() {
+        let mut inputs = inputs(vec![position("SOL", 1.0, 100.0, 80.0)]);
+        inputs.targets = Err("rebalancer locked".to_string());
+        inputs.triggered_alerts = Err("no alert manager".to_string());
+
+        let sections = portfolio_sections(&inputs);
+        assert!(section(&sections, "allocation_drift").incomplete);
+        assert!(section(&sections, "alerts").incomplete);
+        assert_eq!(section(&sections, "alerts").summary, "Unavailable");
+        assert!(!section(&sections, "pnl").incomplete);
+        assert!(!section(&sections, "movers").incomplete);
+    }
+
+    #[test]
+    fn test_movers_split_winners_and_losers() {
+        let sections = portfolio_sections(&inputs(vec![
+            position("SOL", 10.0, 150.0, 100.0),
+            position("JUP", 100.0, 1.1, 1.0),
+            position("BTC", 1.0, 4_000.0, 5_000.0),
+        ]));
+        let movers = section(&sections, "movers");
+        let winners: Vec<ReportMover> =
+            serde_json::from_value(movers.data["winners"].clone()).unwrap();
+        let losers: Vec<ReportMover> =
+            serde_json::from_value(movers.data["losers"].clone()).unwrap();
+        assert_eq!(
+            winners
+                .iter()
+                .map(|m| m.symbol.as_str())
+                .collect::<Vec<_>>(),
+            vec!["SOL", "JUP"]
+        );
+        assert_eq!(losers.len(), 1);
+        assert_eq!(losers[0].symbol, "BTC");
+    }
+
+    fn lot(id: &str, held_days: i64, disposed: DateTime<Utc>, gain: Option<f64>) -> TaxLot {
+        TaxLot {
+            id: id.to_string(),
+            symbol: "SOL".to_string(),
+            mint: "sol-mint".to_string(),
+            amount: 1.0,
+            cost_basis: 100.0,
+            price_per_unit: 100.0,
+            acquired_at: (disposed - Duration::days(held_days)).to_rfc3339(),
+            disposed_amount: Some(1.0),
+            disposed_at: Some(disposed.to_rfc3339()),
+            realized_gain: gain,
+        }
+    }
+
+    fn rates() -> EstimateRates {
+        EstimateRates {
+            short_term_rate: 0.3,
+            long_term_rate: 0.15,
+            state_rate: 0.05,
+            holding_period_days: 365,
+        }
+    }
+
+    #[test]
+    fn test_gains_split_by_term_and_period() {
+        let now = Utc.with_ymd_and_hms(2026, 10, 15, 9, 0, 0).unwrap();
+        let this_month = Utc.with_ymd_and_hms(2026, 10, 3, 0, 0, 0).unwrap();
+        let earlier = Utc.with_ymd_and_hms(2026, 4, 3, 0, 0, 0).unwrap();
+        let last_year = Utc.with_ymd_and_hms(2025, 10, 3, 0, 0, 0).unwrap();
+        let inputs = TaxReportInputs {
+            lots: Ok(vec![
+                lot("a", 30, this_month, Some(1_000.0)),
+                lot("b", 400, this_month, Some(500.0)),
+                lot("c", 30, earlier, Some(-200.0)),
+                lot("d", 30, last_year, Some(9_999.0)),
+            ]),
+            rates: Ok(rates()),
+        };
+
+        let sections = tax_sections(&inputs, now);
+        let gains = section(&sections, "realized_gains");
+        assert!(!gains.incomplete);
+        let month: GainsByTerm = serde_json::from_value(gains.data["monthToDate"].clone()).unwrap();
+        let year: GainsByTerm = serde_json::from_value(gains.data["yearToDate"].clone()).unwrap();
+        assert_eq!(
+            month,
+            GainsByTerm {
+                short_term: 1_000.0,
+                long_term: 500.0
+            }
+        );
+        assert_eq!(
+            year,
+            GainsByTerm {
+                short_term: 800.0,
+                long_term: 500.0
+            }
+        );
+
+        let liability = section(&sections, "estimated_liability");
+        let estimate = liability.data["estimate"].as_f64().unwrap();
+        assert!((estimate - (800.0 * 0.3 + 500.0 * 0.15 + 1_300.0 * 0.05)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_estimate_floors_net_losses() {
+        let gains = GainsByTerm {
+            short_term: -500.0,
+            long_term: 300.0,
+        };
+        assert!((estimate_liability(&gains, &rates()) - 45.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_missing_gain_marks_estimate_incomplete() {
+        let now = Utc.with_ymd_and_hms(2026, 10, 15, 9, 0, 0).unwrap();
+        let disposed = Utc.with_ymd_and_hms(2026, 10, 3, 0, 0, 0).unwrap();
+        let inputs = TaxReportInputs {
+            lots: Ok(vec![
+                lot("a", 30, disposed, Some(1_000.0)),
+                lot("b", 30, disposed, None),
+            ]),
+            rates: Ok(rates()),
+        };
+
+        let sections = tax_sections(&inputs, now);
+        let gains = section(&sections, "realized_gains");
+        assert!(gains.incomplete);
+        assert!(gains.issues[0].contains("Lot b"));
+        let liability = section(&sections, "estimated_liability");
+        assert!(liability.incomplete);
+        assert!(liability.data["estimate"].as_f64().unwrap() > 0.0);
+    }
+
+    #[test]
+    fn test_missing_rates_keep_gains() {
+        let now = Utc.with_ymd_and_hms(2026, 10, 15, 9, 0, 0).unwrap();
+        let disposed = Utc.with_ymd_and_hms(2026, 10, 3, 0, 0, 0).unwrap();
+        let inputs = TaxReportInputs {
+            lots: Ok(vec![lot("a", 30, disposed, Some(1_000.0))]),
+            rates: Err("tax engine unavailable".to_string()),
+        };
+
+        let sections = tax_sections(&inputs, now);
+        assert!(!section(&sections, "realized_gains").incomplete);
+        assert!(section(&sections, "estimated_liability").incomplete);
+    }
+}
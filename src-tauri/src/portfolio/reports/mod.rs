@@ -0,0 +1,918 @@
+//! Scheduled reports: a weekly portfolio report and a monthly tax estimate,
+//! rendered to markdown and PDF, kept in a history and optionally delivered
+//! by email or to the chat integrations.
+
+pub mod builder;
+pub mod render;
+pub mod store;
+
+pub use builder::*;
+pub use render::*;
+pub use store::*;
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::config::DataPaths;
+use base64::{engine::general_purpose::STANDARD as BASE64_ENGINE, Engine};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::sync::RwLock;
+
+use crate::alerts::SharedAlertManager;
+use crate::notifications::{
+    AlertPriority, EmailAttachment, EmailManager, EmailStatus, HistoryCategory, NewNotification,
+    SendEmailRequest, SharedNotificationRouter,
+};
+use crate::portfolio::{SharedPortfolioData, SharedRebalancerState, SharedTaxLotsState};
+use crate::security::keystore::Keystore;
+use crate::tax::SharedTaxPlanningEngine;
+
+const SCHEDULE_FILE: &str = "report_schedules.json";
+const REPORTS_DB_FILE: &str = "reports.db";
+const SCHEDULER_INTERVAL_SECS: u64 = 300;
+/// Reports kept per kind; a year of weekly reports.
+const MAX_REPORTS_PER_KIND: u32 = 52;
+const DEFAULT_LIST_LIMIT: u32 = 50;
+
+pub const REPORT_GENERATED_EVENT: &str = "reports://generated";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportKind {
+    WeeklyPortfolio,
+    MonthlyTax,
+}
+
+impl ReportKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReportKind::WeeklyPortfolio => "weekly_portfolio",
+            ReportKind::MonthlyTax => "monthly_tax",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "weekly_portfolio" => Some(ReportKind::WeeklyPortfolio),
+            "monthly_tax" => Some(ReportKind::MonthlyTax),
+            _ => None,
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            ReportKind::WeeklyPortfolio => "Weekly Portfolio Report",
+            ReportKind::MonthlyTax => "Monthly Tax Estimate",
+        }
+    }
+
+    /// The weekly report covers the trailing seven days; the tax estimate
+    /// runs from the start of the month.
+    pub fn period(&self, now: DateTime<Utc>) -> (DateTime<Utc>, DateTime<Utc>) {
+        let start = match self {
+            ReportKind::WeeklyPortfolio => now - Duration::days(7),
+            ReportKind::MonthlyTax => Utc
+                .with_ymd_and_hms(now.year(), now.month(), 1, 0, 0, 0)
+                .single()
+                .unwrap_or(now),
+        };
+        (start, now)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportTrigger {
+    Scheduled,
+    Manual,
+}
+
+impl ReportTrigger {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReportTrigger::Scheduled => "scheduled",
+            ReportTrigger::Manual => "manual",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "scheduled" => Some(ReportTrigger::Scheduled),
+            "manual" => Some(ReportTrigger::Manual),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ReportDelivery {
+    /// Recipients; empty skips email.
+    pub email_to: Vec<String>,
+    /// Post a summary to every enabled chat integration.
+    pub chat: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportChannel {
+    Email,
+    Chat,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportDeliveryOutcome {
+    pub channel: ReportChannel,
+    pub delivered: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportSchedule {
+    pub enabled: bool,
+    /// Weekly: 0 = Monday through 6 = Sunday. Monthly: day of month, 1-28.
+    pub day: u8,
+    /// Hour of day in UTC.
+    pub hour: u8,
+    #[serde(default)]
+    pub delivery: ReportDelivery,
+    /// Maintained by the scheduler; ignored when configuring.
+    #[serde(default)]
+    pub next_run_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub last_run_at: Option<DateTime<Utc>>,
+}
+
+impl ReportSchedule {
+    fn disabled(day: u8) -> Self {
+        Self {
+            enabled: false,
+            day,
+            hour: 8,
+            delivery: ReportDelivery::default(),
+            next_run_at: None,
+            last_run_at: None,
+        }
+    }
+
+    pub fn validate(&self, kind: ReportKind) -> Result<(), String> {
+        if self.hour > 23 {
+            return Err("Hour must be 0-23".to_string());
+        }
+        match kind {
+            ReportKind::WeeklyPortfolio if self.day > 6 => {
+                return Err("Day must be 0-6 (Monday-Sunday) for weekly reports".to_string());
+            }
+            // Capped at 28 so the report runs in every month.
+            ReportKind::MonthlyTax if !(1..=28).contains(&self.day) => {
+                return Err("Day must be 1-28 for monthly reports".to_string());
+            }
+            _ => {}
+        }
+        if let Some(invalid) = self.delivery.email_to.iter().find(|to| !to.contains('@')) {
+            return Err(format!("Invalid email recipient: {}", invalid));
+        }
+        Ok(())
+    }
+
+    /// First scheduled time strictly after `after`.
+    pub fn next_run(&self, kind: ReportKind, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        if !self.enabled {
+            return None;
+        }
+        let at = |date: NaiveDate| {
+            date.and_hms_opt(self.hour as u32, 0, 0)
+                .map(|time| Utc.from_utc_datetime(&time))
+        };
+        let today = after.date_naive();
+
+        match kind {
+            ReportKind::WeeklyPortfolio => {
+                let offset =
+                    (self.day as i64 - today.weekday().num_days_from_monday() as i64).rem_euclid(7);
+                let candidate = at(today + Duration::days(offset))?;
+                Some(if candidate > after {
+                    candidate
+                } else {
+                    candidate + Duration::days(7)
+                })
+            }
+            ReportKind::MonthlyTax => {
+                let (mut year, mut month) = (today.year(), today.month());
+                loop {
+                    let candidate = at(NaiveDate::from_ymd_opt(year, month, self.day as u32)?)?;
+                    if candidate > after {
+                        return Some(candidate);
+                    }
+                    (year, month) = if month == 12 {
+                        (year + 1, 1)
+                    } else {
+                        (year, month + 1)
+                    };
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportSchedules {
+    pub weekly_portfolio: ReportSchedule,
+    pub monthly_tax: ReportSchedule,
+}
+
+impl Default for ReportSchedules {
+    fn default() -> Self {
+        Self {
+            weekly_portfolio: ReportSchedule::disabled(0),
+            monthly_tax: ReportSchedule::disabled(1),
+        }
+    }
+}
+
+impl ReportSchedules {
+    pub fn get(&self, kind: ReportKind) -> &ReportSchedule {
+        match kind {
+            ReportKind::WeeklyPortfolio => &self.weekly_portfolio,
+            ReportKind::MonthlyTax => &self.monthly_tax,
+        }
+    }
+
+    fn get_mut(&mut self, kind: ReportKind) -> &mut ReportSchedule {
+        match kind {
+            ReportKind::WeeklyPortfolio => &mut self.weekly_portfolio,
+            ReportKind::MonthlyTax => &mut self.monthly_tax,
+        }
+    }
+
+    pub fn due(&self, now: DateTime<Utc>) -> Vec<ReportKind> {
+        [ReportKind::WeeklyPortfolio, ReportKind::MonthlyTax]
+            .into_iter()
+            .filter(|kind| {
+                let schedule = self.get(*kind);
+                schedule.enabled && schedule.next_run_at.is_some_and(|next| next <= now)
+            })
+            .collect()
+    }
+
+    pub fn configure(
+        &mut self,
+        kind: ReportKind,
+        schedule: ReportSchedule,
+        now: DateTime<Utc>,
+    ) -> Result<ReportSchedule, String> {
+        schedule.validate(kind)?;
+        let current = self.get_mut(kind);
+        *current = ReportSchedule {
+            next_run_at: schedule.next_run(kind, now),
+            last_run_at: current.last_run_at,
+            ..schedule
+        };
+        Ok(current.clone())
+    }
+
+    /// Records a scheduled run. A run missed while the app was closed
+    /// happens once at the next tick rather than once per missed period.
+    pub fn mark_run(&mut self, kind: ReportKind, at: DateTime<Utc>) {
+        let schedule = self.get_mut(kind);
+        schedule.last_run_at = Some(at);
+        schedule.next_run_at = schedule.next_run(kind, at);
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportSummary {
+    pub id: String,
+    pub kind: ReportKind,
+    pub title: String,
+    pub trigger: ReportTrigger,
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    pub generated_at: DateTime<Utc>,
+    pub incomplete: bool,
+    pub deliveries: Vec<ReportDeliveryOutcome>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Report {
+    pub id: String,
+    pub kind: ReportKind,
+    pub title: String,
+    pub trigger: ReportTrigger,
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    pub generated_at: DateTime<Utc>,
+    /// Set when any section is incomplete.
+    pub incomplete: bool,
+    pub sections: Vec<ReportSection>,
+    pub markdown: String,
+    pub pdf_base64: String,
+    pub deliveries: Vec<ReportDeliveryOutcome>,
+}
+
+impl Report {
+    fn summary(&self) -> ReportSummary {
+        ReportSummary {
+            id: self.id.clone(),
+            kind: self.kind,
+            title: self.title.clone(),
+            trigger: self.trigger,
+            period_start: self.period_start,
+            period_end: self.period_end,
+            generated_at: self.generated_at,
+            incomplete: self.incomplete,
+            deliveries: self.deliveries.clone(),
+        }
+    }
+
+    /// Short text for chat integrations, which can't take the full report.
+    fn chat_text(&self) -> String {
+        let mut lines = vec![format!(
+            "Period {} to {}",
+            self.period_start.format("%Y-%m-%d"),
+            self.period_end.format("%Y-%m-%d")
+        )];
+        for section in &self.sections {
+            let marker = if section.incomplete {
+                " (incomplete)"
+            } else {
+                ""
+            };
+            lines.push(format!(
+                "- {}: {}{}",
+                section.title, section.summary, marker
+            ));
+        }
+        lines.join("\n")
+    }
+}
+
+pub struct ReportsManager {
+    store: ReportStore,
+    schedules: ReportSchedules,
+    schedule_path: PathBuf,
+}
+
+pub type SharedReportsManager = Arc<RwLock<ReportsManager>>;
+
+impl ReportsManager {
+    pub async fn new(app: &AppHandle) -> Result<Self, String> {
+        let data_dir = app
+            .app_data_root()
+            .map_err(|e| format!("App data directory not found: {}", e))?;
+        fs::create_dir_all(&data_dir).map_err(|e| e.to_string())?;
+
+        let schedule_path = data_dir.join(SCHEDULE_FILE);
+        let schedules = match fs::read_to_string(&schedule_path) {
+            Ok(data) => serde_json::from_str(&data).unwrap_or_else(|e| {
+                eprintln!("Failed to parse report schedules, using defaults: {}", e);
+                ReportSchedules::default()
+            }),
+            Err(_) => ReportSchedules::default(),
+        };
+        let store = ReportStore::new(data_dir.join(REPORTS_DB_FILE))
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(Self {
+            store,
+            schedules,
+            schedule_path,
+        })
+    }
+
+    pub fn schedules(&self) -> &ReportSchedules {
+        &self.schedules
+    }
+
+    pub fn store(&self) -> &ReportStore {
+        &self.store
+    }
+
+    pub fn configure(
+        &mut self,
+        kind: ReportKind,
+        schedule: ReportSchedule,
+    ) -> Result<ReportSchedule, String> {
+        let saved = self.schedules.configure(kind, schedule, Utc::now())?;
+        self.save_schedules()?;
+        Ok(saved)
+    }
+
+    pub fn mark_run(&mut self, kind: ReportKind, at: DateTime<Utc>) -> Result<(), String> {
+        self.schedules.mark_run(kind, at);
+        self.save_schedules()
+    }
+
+    fn save_schedules(&self) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(&self.schedules).map_err(|e| e.to_string())?;
+        fs::write(&self.schedule_path, json).map_err(|e| e.to_string())
+    }
+}
+
+async fn gather_portfolio_inputs(app: &AppHandle, since: DateTime<Utc>) -> PortfolioReportInputs {
+    let (metrics, positions) = match app.try_state::<SharedPortfolioData>() {
+        Some(data) => match data.lock() {
+            Ok(data) => (Ok(data.metrics()), Ok(data.positions())),
+            Err(_) => (
+                Err("Portfolio data locked".to_string()),
+                Err("Portfolio data locked".to_string()),
+            ),
+        },
+        None => (
+            Err("Portfolio data not initialized".to_string()),
+            Err("Portfolio data not initialized".to_string()),
+        ),
+    };
+
+    // Drift is measured against the first enabled rebalance profile by name.
+    let targets = match app.try_state::<SharedRebalancerState>() {
+        Some(state) => state
+            .lock()
+            .map_err(|_| "Rebalancer state locked".to_string())
+            .map(|state| {
+                let mut profiles: Vec<_> = state
+                    .list_profiles()
+                    .into_iter()
+                    .filter(|profile| profile.enabled)
+                    .collect();
+                profiles.sort_by(|a, b| a.name.cmp(&b.name));
+                profiles
+                    .into_iter()
+                    .next()
+                    .map(|profile| profile.targets)
+                    .unwrap_or_default()
+            }),
+        None => Err("Rebalancer not initialized".to_string()),
+    };
+
+    let triggered_alerts = match app.try_state::<SharedAlertManager>() {
+        Some(manager) => manager
+            .read()
+            .await
+            .list_alerts()
+            .await
+            .map_err(|e| e.to_string())
+            .map(|alerts| {
+                alerts
+                    .iter()
+                    .filter_map(|alert| alert.last_triggered_at.as_deref())
+                    .filter_map(|at| at.parse::<DateTime<Utc>>().ok())
+                    .filter(|at| *at >= since)
+                    .count()
+            }),
+        None => Err("Alert manager not initialized".to_string()),
+    };
+
+    PortfolioReportInputs {
+        metrics,
+        positions,
+        targets,
+        triggered_alerts,
+    }
+}
+
+async fn gather_tax_inputs(app: &AppHandle) -> TaxReportInputs {
+    let lots = match app.try_state::<SharedTaxLotsState>() {
+        Some(state) => state
+            .lock()
+            .map(|lots| lots.all_lots())
+            .map_err(|_| "Tax lots locked".to_string()),
+        None => Err("Tax lots not initialized".to_string()),
+    };
+
+    let rates = match app.try_state::<SharedTaxPlanningEngine>() {
+        Some(engine) => {
+            let engine = engine.read().await;
+            let jurisdiction = &engine.settings.jurisdiction;
+            let custom = engine.settings.custom_tax_rates.as_ref();
+            Ok(EstimateRates {
+                short_term_rate: custom
+                    .and_then(|rates| rates.short_term_rate)
+                    .unwrap_or(jurisdiction.short_term_rate),
+                long_term_rate: custom
+                    .and_then(|rates| rates.long_term_rate)
+                    .unwrap_or(jurisdiction.long_term_rate),
+                state_rate: custom.and_then(|rates| rates.state_tax_rate).unwrap_or(0.0),
+                holding_period_days: jurisdiction.holding_period_days,
+            })
+        }
+        None => Err("Tax engine not initialized".to_string()),
+    };
+
+    TaxReportInputs { lots, rates }
+}
+
+async fn email_report(
+    app: &AppHandle,
+    report: &Report,
+    pdf: &[u8],
+    to: &[String],
+) -> Result<(), String> {
+    let keystore = app
+        .try_state::<Keystore>()
+        .ok_or_else(|| "Keystore not initialized".to_string())?;
+    let manager = EmailManager::new(app).await.map_err(|e| e.to_string())?;
+    let config = manager
+        .get_config(&keystore)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let request = SendEmailRequest {
+        to: to.to_vec(),
+        subject: report.title.clone(),
+        html_body: None,
+        text_body: Some(report.markdown.clone()),
+        template: None,
+        template_vars: None,
+        attachments: Some(vec![EmailAttachment {
+            filename: format!(
+                "{}-{}.pdf",
+                report.kind.as_str(),
+                report.generated_at.format("%Y-%m-%d")
+            ),
+            content: pdf.to_vec(),
+            mime_type: "application/pdf".to_string(),
+        }]),
+        include_unsubscribe: false,
+    };
+    let record = manager
+        .send_email(request, &config)
+        .await
+        .map_err(|e| e.to_string())?;
+    match record.status {
+        EmailStatus::Sent => Ok(()),
+        _ => Err(record
+            .error
+            .unwrap_or_else(|| "Email was not sent".to_string())),
+    }
+}
+
+async fn chat_report(app: &AppHandle, report: &Report) -> Result<(), String> {
+    let router = app
+        .try_state::<SharedNotificationRouter>()
+        .ok_or_else(|| "Notification router not initialized".to_string())?;
+    let notification = NewNotification {
+        category: HistoryCategory::System,
+        priority: AlertPriority::Low,
+        title: report.title.clone(),
+        message: report.chat_text(),
+        entity: None,
+        link: None,
+    };
+    let router = router.read().await;
+    router
+        .send_event_notification(&notification)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+async fn deliver_report(
+    app: &AppHandle,
+    report: &Report,
+    pdf: &[u8],
+    delivery: &ReportDelivery,
+) -> Vec<ReportDeliveryOutcome> {
+    let mut outcomes = Vec::new();
+    if !delivery.email_to.is_empty() {
+        let result = email_report(app, report, pdf, &delivery.email_to).await;
+        outcomes.push(ReportDeliveryOutcome {
+            channel: ReportChannel::Email,
+            delivered: result.is_ok(),
+            error: result.err(),
+        });
+    }
+    if delivery.chat {
+        let result = chat_report(app, report).await;
+        outcomes.push(ReportDeliveryOutcome {
+            channel: ReportChannel::Chat,
+            delivered: result.is_ok(),
+            error: result.err(),
+        });
+    }
+    outcomes
+}
+
+/// Builds, stores and, when `deliver` is set, sends a report. Missing data
+/// only marks sections incomplete; errors are limited to storage.
+pub async fn generate_report(
+    app: &AppHandle,
+    kind: ReportKind,
+    trigger: ReportTrigger,
+    deliver: bool,
+) -> Result<Report, String> {
+    let manager = app
+        .try_state::<SharedReportsManager>()
+        .ok_or_else(|| "Reports not initialized".to_string())?;
+    let delivery = manager.read().await.schedules().get(kind).delivery.clone();
+
+    let now = Utc::now();
+    let (period_start, period_end) = kind.period(now);
+    let sections = match kind {
+        ReportKind::WeeklyPortfolio => {
+            portfolio_sections(&gather_portfolio_inputs(app, period_start).await)
+        }
+        ReportKind::MonthlyTax => tax_sections(&gather_tax_inputs(app).await, now),
+    };
+    let markdown = render_markdown(kind.title(), period_start, period_end, now, &sections);
+    let pdf = render_pdf(&markdown);
+
+    let mut report = Report {
+        id: uuid::Uuid::new_v4().to_string(),
+        kind,
+        title: kind.title().to_string(),
+        trigger,
+        period_start,
+        period_end,
+        generated_at: now,
+        incomplete: sections.iter().any(|section| section.incomplete),
+        sections,
+        markdown,
+        pdf_base64: BASE64_ENGINE.encode(&pdf),
+        deliveries: Vec::new(),
+    };
+
+    {
+        let manager = manager.read().await;
+        manager
+            .store()
+            .insert(&report, &pdf)
+            .await
+            .map_err(|e| format!("Failed to store report: {}", e))?;
+        if let Err(e) = manager.store().prune(kind, MAX_REPORTS_PER_KIND).await {
+            eprintln!("Failed to prune report history: {}", e);
+        }
+    }
+
+    if deliver {
+        report.deliveries = deliver_report(app, &report, &pdf, &delivery).await;
+        if !report.deliveries.is_empty() {
+            if let Err(e) = manager
+                .read()
+                .await
+                .store()
+                .set_deliveries(&report.id, &report.deliveries)
+                .await
+            {
+                eprintln!("Failed to record report deliveries: {}", e);
+            }
+        }
+    }
+
+    if let Err(e) = app.emit(REPORT_GENERATED_EVENT, report.summary()) {
+        eprintln!("Failed to emit report event: {}", e);
+    }
+    Ok(report)
+}
+
+pub async fn run_report_scheduler(app: AppHandle) {
+    let mut interval =
+        tokio::time::interval(std::time::Duration::from_secs(SCHEDULER_INTERVAL_SECS));
+    loop {
+        interval.tick().await;
+
+        let Some(manager) = app.try_state::<SharedReportsManager>() else {
+            continue;
+        };
+        let now = Utc::now();
+        let due = manager.read().await.schedules().due(now);
+        for kind in due {
+            // Marked before generating so a failing report waits for its
+            // next slot instead of retrying every tick.
+            if let Err(e) = manager.write().await.mark_run(kind, now) {
+                eprintln!("Failed to save report schedule: {}", e);
+            }
+            if let Err(e) = generate_report(&app, kind, ReportTrigger::Scheduled, true).await {
+                eprintln!("Failed to generate {} report: {}", kind.as_str(), e);
+            }
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn reports_configure_schedule(
+    kind: ReportKind,
+    schedule: ReportSchedule,
+    manager: State<'_, SharedReportsManager>,
+) -> Result<ReportSchedule, String> {
+    manager.write().await.configure(kind, schedule)
+}
+
+#[tauri::command]
+pub async fn reports_get_schedules(
+    manager: State<'_, SharedReportsManager>,
+) -> Result<ReportSchedules, String> {
+    Ok(manager.read().await.schedules().clone())
+}
+
+#[tauri::command]
+pub async fn reports_list(
+    kind: Option<ReportKind>,
+    limit: Option<u32>,
+    manager: State<'_, SharedReportsManager>,
+) -> Result<Vec<ReportSummary>, String> {
+    manager
+        .read()
+        .await
+        .store()
+        .list(kind, limit.unwrap_or(DEFAULT_LIST_LIMIT))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn reports_get(
+    id: String,
+    manager: State<'_, SharedReportsManager>,
+) -> Result<Report, String> {
+    manager
+        .read()
+        .await
+        .store()
+        .get(&id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Report not found: {}", id))
+}
+
+/// Generates a report immediately. Delivery follows the kind's schedule
+/// settings and only happens when `deliver` is set.
+#[tauri::command]
+pub async fn reports_generate_now(
+    app: AppHandle,
+    kind: ReportKind,
+    deliver: Option<bool>,
+) -> Result<Report, String> {
+    generate_report(&app, kind, ReportTrigger::Manual, deliver.unwrap_or(false)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_pool;
+
+    fn schedule(day: u8, hour: u8) -> ReportSchedule {
+        ReportSchedule {
+            enabled: true,
+            hour,
+            ..ReportSchedule::disabled(day)
+        }
+    }
+
+    fn at(y: i32, m: u32, d: u32, h: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, h, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_weekly_next_run() {
+        // 2026-10-15 is a Thursday.
+        let monday = schedule(0, 8);
+        assert_eq!(
+            monday.next_run(ReportKind::WeeklyPortfolio, at(2026, 10, 15, 12)),
+            Some(at(2026, 10, 19, 8))
+        );
+        let thursday = schedule(3, 8);
+        assert_eq!(
+            thursday.next_run(ReportKind::WeeklyPortfolio, at(2026, 10, 15, 7)),
+            Some(at(2026, 10, 15, 8))
+        );
+        assert_eq!(
+            thursday.next_run(ReportKind::WeeklyPortfolio, at(2026, 10, 15, 8)),
+            Some(at(2026, 10, 22, 8))
+        );
+    }
+
+    #[test]
+    fn test_monthly_next_run_rolls_over_year() {
+        let first = schedule(1, 6);
+        assert_eq!(
+            first.next_run(ReportKind::MonthlyTax, at(2026, 12, 15, 0)),
+            Some(at(2027, 1, 1, 6))
+        );
+        assert_eq!(
+            first.next_run(ReportKind::MonthlyTax, at(2026, 10, 1, 5)),
+            Some(at(2026, 10, 1, 6))
+        );
+    }
+
+    #[test]
+    fn test_disabled_schedule_never_runs() {
+        let disabled = ReportSchedule::disabled(0);
+        assert_eq!(
+            disabled.next_run(ReportKind::WeeklyPortfolio, Utc::now()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_validate_schedule() {
+        assert!(schedule(6, 23)
+            .validate(ReportKind::WeeklyPortfolio)
+            .is_ok());
+        assert!(schedule(7, 8)
+            .validate(ReportKind::WeeklyPortfolio)
+            .is_err());
+        assert!(schedule(29, 8).validate(ReportKind::MonthlyTax).is_err());
+        assert!(schedule(0, 8).validate(ReportKind::MonthlyTax).is_err());
+        assert!(schedule(1, 24).validate(ReportKind::MonthlyTax).is_err());
+
+        let mut bad_email = schedule(1, 8);
+        bad_email.delivery.email_to = vec!["not-an-address".to_string()];
+        assert!(bad_email.validate(ReportKind::MonthlyTax).is_err());
+    }
+
+    #[test]
+    fn test_due_and_mark_run() {
+        let mut schedules = ReportSchedules::default();
+        let now = at(2026, 10, 15, 12);
+        let saved = schedules
+            .configure(ReportKind::WeeklyPortfolio, schedule(0, 8), now)
+            .unwrap();
+        assert_eq!(saved.next_run_at, Some(at(2026, 10, 19, 8)));
+        assert!(schedules.due(now).is_empty());
+
+        // Missed while closed: runs once, then moves to the next slot.
+        let late = at(2026, 10, 28, 9);
+        assert_eq!(schedules.due(late), vec![ReportKind::WeeklyPortfolio]);
+        schedules.mark_run(ReportKind::WeeklyPortfolio, late);
+        assert!(schedules.due(late).is_empty());
+        assert_eq!(
+            schedules.weekly_portfolio.next_run_at,
+            Some(at(2026, 11, 2, 8))
+        );
+        assert_eq!(schedules.weekly_portfolio.last_run_at, Some(late));
+    }
+
+    fn report(kind: ReportKind, generated_at: DateTime<Utc>) -> Report {
+        let (period_start, period_end) = kind.period(generated_at);
+        Report {
+            id: uuid::Uuid::new_v4().to_string(),
+            kind,
+            title: kind.title().to_string(),
+            trigger: ReportTrigger::Manual,
+            period_start,
+            period_end,
+            generated_at,
+            incomplete: true,
+            sections: Vec::new(),
+            markdown: "# Report".to_string(),
+            pdf_base64: String::new(),
+            deliveries: Vec::new(),
+        }
+    }
+
+    async fn store() -> ReportStore {
+        let pool = test_pool().await;
+        ReportStore::with_pool(pool).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_store_roundtrip_and_prune() {
+        let store = store().await;
+        let older = report(ReportKind::WeeklyPortfolio, at(2026, 10, 5, 8));
+        let newer = report(ReportKind::WeeklyPortfolio, at(2026, 10, 12, 8));
+        let tax = report(ReportKind::MonthlyTax, at(2026, 10, 1, 8));
+        for report in [&older, &newer, &tax] {
+            store.insert(report, b"%PDF-1.4").await.unwrap();
+        }
+
+        let deliveries = vec![ReportDeliveryOutcome {
+            channel: ReportChannel::Email,
+            delivered: false,
+            error: Some("configuration not found".to_string()),
+        }];
+        store.set_deliveries(&newer.id, &deliveries).await.unwrap();
+
+        let weekly = store
+            .list(Some(ReportKind::WeeklyPortfolio), 10)
+            .await
+            .unwrap();
+        assert_eq!(
+            weekly.iter().map(|r| r.id.as_str()).collect::<Vec<_>>(),
+            vec![newer.id.as_str(), older.id.as_str()]
+        );
+        assert_eq!(weekly[0].deliveries, deliveries);
+        assert_eq!(store.list(None, 10).await.unwrap().len(), 3);
+
+        let fetched = store.get(&newer.id).await.unwrap().unwrap();
+        assert!(fetched.incomplete);
+        assert_eq!(fetched.markdown, "# Report");
+        assert_eq!(fetched.pdf_base64, "JVBERi0xLjQ=");
+        assert!(store.get("missing").await.unwrap().is_none());
+
+        assert_eq!(
+            store.prune(ReportKind::WeeklyPortfolio, 1).await.unwrap(),
+            1
+        );
+        assert!(store.get(&older.id).await.unwrap().is_none());
+        assert!(store.get(&tax.id).await.unwrap().is_some());
+    }
+}
@@ -0,0 +1,269 @@
+//! Markdown and PDF rendering for generated reports. The PDF writer is a
+//! minimal text-only one: headings in bold, everything else wrapped plain
+//! text on US Letter pages.
+
+use chrono::{DateTime, Utc};
+
+use super::builder::ReportSection;
+
+const PAGE_WIDTH: f64 = 612.0;
+const PAGE_HEIGHT: f64 = 792.0;
+const MARGIN: f64 = 54.0;
+const WRAP_COLUMNS: usize = 90;
+
+pub fn render_markdown(
+    title: &str,
+    period_start: DateTime<Utc>,
+    period_end: DateTime<Utc>,
+    generated_at: DateTime<Utc>,
+    sections: &[ReportSection],
+) -> String {
+    let mut out = format!(
+        "# {}\n\nPeriod: {} to {}. Generated {}.\n",
+        title,
+        period_start.format("%Y-%m-%d"),
+        period_end.format("%Y-%m-%d"),
+        generated_at.format("%Y-%m-%d %H:%M UTC")
+    );
+    if sections.iter().any(|s| s.incomplete) {
+        out.push_str("\n> Some sections are incomplete. See the notes under each.\n");
+    }
+    for section in sections {
+        out.push_str(&format!("\n## {}\n\n{}\n", section.title, section.body));
+        for issue in &section.issues {
+            out.push_str(&format!("\n> Incomplete: {}\n", issue));
+        }
+    }
+    out
+}
+
+struct PdfLine {
+    text: String,
+    bold: bool,
+    size: f64,
+}
+
+impl PdfLine {
+    fn height(&self) -> f64 {
+        self.size + 4.0
+    }
+}
+
+fn pdf_lines(markdown: &str) -> Vec<PdfLine> {
+    let mut lines = Vec::new();
+    for raw in markdown.lines() {
+        let (text, bold, size) = if let Some(heading) = raw.strip_prefix("# ") {
+            (heading, true, 16.0)
+        } else if let Some(heading) = raw.strip_prefix("## ") {
+            (heading, true, 13.0)
+        } else if let Some(quote) = raw.strip_prefix("> ") {
+            (quote, false, 10.0)
+        } else {
+            (raw, false, 10.0)
+        };
+        let bold = bold || (text.starts_with("**") && text.ends_with("**") && text.len() > 4);
+        let text = text.replace("**", "");
+
+        for wrapped in wrap(&text, WRAP_COLUMNS) {
+            lines.push(PdfLine {
+                text: wrapped,
+                bold,
+                size,
+            });
+        }
+    }
+    lines
+}
+
+fn wrap(text: &str, columns: usize) -> Vec<String> {
+    let mut lines = vec![String::new()];
+    for word in text.split_whitespace() {
+        let current = lines.last_mut().expect("lines is never empty");
+        if !current.is_empty() && current.len() + 1 + word.len() > columns {
+            lines.push(word.to_string());
+        } else {
+            if !current.is_empty() {
+                current.push(' ');
+            }
+            current.push_str(word);
+        }
+    }
+    lines
+}
+
+/// Escapes a string for a PDF literal. The standard fonts only cover
+/// single-byte text, so anything outside ASCII becomes `?`.
+fn escape_pdf_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | '(' | ')' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            c if c.is_ascii() && !c.is_ascii_control() => escaped.push(c),
+            _ => escaped.push('?'),
+        }
+    }
+    escaped
+}
+
+pub fn render_pdf(markdown: &str) -> Vec<u8> {
+    let mut pages: Vec<String> = Vec::new();
+    let mut content = String::new();
+    let mut y = PAGE_HEIGHT - MARGIN;
+    for line in pdf_lines(markdown) {
+        if y - line.height() < MARGIN {
+            pages.push(std::mem::take(&mut content));
+            y = PAGE_HEIGHT - MARGIN;
+        }
+        y -= line.height();
+        if !line.text.is_empty() {
+            content.push_str(&format!(
+                "BT /{} {} Tf {} {:.1} Td ({}) Tj ET\n",
+                if line.bold { "F2" } else { "F1" },
+                line.size,
+                MARGIN,
+                y,
+                escape_pdf_text(&line.text)
+            ));
+        }
+    }
+    pages.push(content);
+
+    // Objects 1-4 are the catalog, page tree and two fonts; each page then
+    // takes a page object followed by its content stream.
+    let page_ids: Vec<usize> = (0..pages.len()).map(|i| 5 + i * 2).collect();
+    let mut objects = vec![
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            page_ids
+                .iter()
+                .map(|id| format!("{} 0 R", id))
+                .collect::<Vec<_>>()
+                .join(" "),
+            pages.len()
+        ),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>"
+            .to_string(),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold /Encoding /WinAnsiEncoding >>"
+            .to_string(),
+    ];
+    for (page, id) in pages.iter().zip(&page_ids) {
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] \
+             /Resources << /Font << /F1 3 0 R /F2 4 0 R >> >> /Contents {} 0 R >>",
+            PAGE_WIDTH,
+            PAGE_HEIGHT,
+            id + 1
+        ));
+        objects.push(format!(
+            "<< /Length {} >>\nstream\n{}endstream",
+            page.len(),
+            page
+        ));
+    }
+
+    let mut pdf = b"%PDF-1.4\n".to_vec();
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.extend_from_slice(format!("{} 0 obj\n{}\nendobj\n", i + 1, object).as_bytes());
+    }
+    let xref_offset = pdf.len();
+    let mut trailer = format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+    for offset in offsets {
+        trailer.push_str(&format!("{:010} 00000 n \n", offset));
+    }
+    trailer.push_str(&format!(
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+        objects.len() + 1,
+        xref_offset
+    ));
+    pdf.extend_from_slice(trailer.as_bytes());
+    pdf
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn section(title: &str, body: &str, issues: &[&str]) -> ReportSection {
+        ReportSection {
+            id: title.to_lowercase(),
+            title: title.to_string(),
+            summary: String::new(),
+            body: body.to_string(),
+            data: serde_json::Value::Null,
+            incomplete: !issues.is_empty(),
+            issues: issues.iter().map(|i| i.to_string()).collect(),
+        }
+    }
+
+    fn markdown(sections: &[ReportSection]) -> String {
+        let start = Utc.with_ymd_and_hms(2026, 10, 8, 8, 0, 0).unwrap();
+        let end = Utc.with_ymd_and_hms(2026, 10, 15, 8, 0, 0).unwrap();
+        render_markdown("Weekly Portfolio Report", start, end, end, sections)
+    }
+
+    #[test]
+    fn test_markdown_notes_incomplete_sections() {
+        let out = markdown(&[
+            section("Alerts", "- Alerts triggered this week: 2", &[]),
+            section("Allocation Drift", "- SOL: 100.0%", &["No price for BTC"]),
+        ]);
+        assert!(out.starts_with("# Weekly Portfolio Report\n"));
+        assert!(out.contains("Period: 2026-10-08 to 2026-10-15."));
+        assert!(out.contains("> Some sections are incomplete."));
+        assert!(
+            out.contains("## Allocation Drift\n\n- SOL: 100.0%\n\n> Incomplete: No price for BTC")
+        );
+
+        let complete = markdown(&[section("Alerts", "- none", &[])]);
+        assert!(!complete.contains("incomplete"));
+    }
+
+    #[test]
+    fn test_pdf_structure_and_offsets() {
+        let pdf = render_pdf(&markdown(&[section("Alerts", "- (2) alerts", &[])]));
+        let text = String::from_utf8(pdf.clone()).unwrap();
+        assert!(text.starts_with("%PDF-1.4\n"));
+        assert!(text.ends_with("%%EOF\n"));
+        assert!(text.contains("/Count 1"));
+        assert!(text.contains("(- \\(2\\) alerts) Tj"));
+
+        // Every xref entry points at the start of its object.
+        let xref = text.rfind("xref\n").unwrap();
+        let entries: Vec<usize> = text[xref..]
+            .lines()
+            .skip(3)
+            .take_while(|line| line.ends_with(" n "))
+            .map(|line| line[..10].parse().unwrap())
+            .collect();
+        assert_eq!(entries.len(), 6);
+        for (i, offset) in entries.iter().enumerate() {
+            assert!(text[*offset..].starts_with(&format!("{} 0 obj", i + 1)));
+        }
+    }
+
+    #[test]
+    fn test_pdf_paginates_long_reports() {
+        let body = (0..200)
+            .map(|i| format!("- Line {}", i))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let text =
+            String::from_utf8(render_pdf(&markdown(&[section("Long", &body, &[])]))).unwrap();
+        assert!(text.contains("/Count 5"));
+        assert!(text.contains("(- Line 199) Tj"));
+    }
+
+    #[test]
+    fn test_wrap_and_escape() {
+        assert_eq!(wrap("aaa bbb ccc", 7), vec!["aaa bbb", "ccc"]);
+        assert_eq!(wrap("", 10), vec![String::new()]);
+        assert_eq!(escape_pdf_text("a\\b (c) €"), "a\\\\b \\(c\\) ?");
+    }
+}
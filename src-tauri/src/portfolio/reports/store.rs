@@ -0,0 +1,213 @@
+use std::path::PathBuf;
+
+use base64::{engine::general_purpose::STANDARD as BASE64_ENGINE, Engine};
+use chrono::{DateTime, Utc};
+use sqlx::{sqlite::SqliteRow, Pool, Row, Sqlite, SqlitePool};
+
+use super::{Report, ReportDeliveryOutcome, ReportKind, ReportSummary, ReportTrigger};
+
+pub struct ReportStore {
+    pool: Pool<Sqlite>,
+}
+
+impl ReportStore {
+    pub async fn new(db_path: PathBuf) -> Result<Self, sqlx::Error> {
+        let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+
+        let pool = match SqlitePool::connect(&db_url).await {
+            Ok(pool) => pool,
+            Err(e) => {
+                eprintln!(
+                    "Warning: ReportStore failed to connect to {:?}: {}",
+                    db_path, e
+                );
+                eprintln!("ReportStore using in-memory database for this session");
+                SqlitePool::connect("sqlite::memory:").await?
+            }
+        };
+
+        Self::with_pool(pool).await
+    }
+
+    pub async fn with_pool(pool: Pool<Sqlite>) -> Result<Self, sqlx::Error> {
+        let store = Self { pool };
+        store.initialize().await?;
+        Ok(store)
+    }
+
+    async fn initialize(&self) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS reports (
+                id TEXT PRIMARY KEY,
+                kind TEXT NOT NULL,
+                title TEXT NOT NULL,
+                triggered_by TEXT NOT NULL,
+                period_start TEXT NOT NULL,
+                period_end TEXT NOT NULL,
+                generated_at TEXT NOT NULL,
+                incomplete INTEGER NOT NULL,
+                sections TEXT NOT NULL,
+                markdown TEXT NOT NULL,
+                pdf BLOB NOT NULL,
+                deliveries TEXT NOT NULL
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS idx_reports_kind_generated ON reports(kind, generated_at)",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn insert(&self, report: &Report, pdf: &[u8]) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO reports (
+                id, kind, title, triggered_by, period_start, period_end, generated_at,
+                incomplete, sections, markdown, pdf, deliveries
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
+            "#,
+        )
+        .bind(&report.id)
+        .bind(report.kind.as_str())
+        .bind(&report.title)
+        .bind(report.trigger.as_str())
+        .bind(report.period_start.to_rfc3339())
+        .bind(report.period_end.to_rfc3339())
+        .bind(report.generated_at.to_rfc3339())
+        .bind(report.incomplete)
+        .bind(to_json(&report.sections)?)
+        .bind(&report.markdown)
+        .bind(pdf)
+        .bind(to_json(&report.deliveries)?)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn set_deliveries(
+        &self,
+        id: &str,
+        deliveries: &[ReportDeliveryOutcome],
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE reports SET deliveries = ?1 WHERE id = ?2")
+            .bind(to_json(deliveries)?)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Newest first.
+    pub async fn list(
+        &self,
+        kind: Option<ReportKind>,
+        limit: u32,
+    ) -> Result<Vec<ReportSummary>, sqlx::Error> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, kind, title, triggered_by, period_start, period_end, generated_at,
+                   incomplete, deliveries
+            FROM reports
+            WHERE ?1 IS NULL OR kind = ?1
+            ORDER BY generated_at DESC
+            LIMIT ?2
+            "#,
+        )
+        .bind(kind.map(|kind| kind.as_str()))
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(row_to_summary).collect()
+    }
+
+    pub async fn get(&self, id: &str) -> Result<Option<Report>, sqlx::Error> {
+        let row = sqlx::query("SELECT * FROM reports WHERE id = ?1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+        let Some(row) = row else {
+            return Ok(None);
+        };
+
+        let sections: String = row.try_get("sections")?;
+        let pdf: Vec<u8> = row.try_get("pdf")?;
+        let summary = row_to_summary(&row)?;
+        Ok(Some(Report {
+            id: summary.id,
+            kind: summary.kind,
+            title: summary.title,
+            trigger: summary.trigger,
+            period_start: summary.period_start,
+            period_end: summary.period_end,
+            generated_at: summary.generated_at,
+            incomplete: summary.incomplete,
+            sections: from_json(&sections)?,
+            markdown: row.try_get("markdown")?,
+            pdf_base64: BASE64_ENGINE.encode(pdf),
+            deliveries: summary.deliveries,
+        }))
+    }
+
+    /// Keeps the newest `keep` reports of a kind.
+    pub async fn prune(&self, kind: ReportKind, keep: u32) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM reports
+            WHERE kind = ?1 AND id NOT IN (
+                SELECT id FROM reports WHERE kind = ?1 ORDER BY generated_at DESC LIMIT ?2
+            )
+            "#,
+        )
+        .bind(kind.as_str())
+        .bind(keep)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
+
+fn to_json<T: serde::Serialize + ?Sized>(value: &T) -> Result<String, sqlx::Error> {
+    serde_json::to_string(value).map_err(|e| sqlx::Error::Protocol(e.to_string()))
+}
+
+fn from_json<T: serde::de::DeserializeOwned>(value: &str) -> Result<T, sqlx::Error> {
+    serde_json::from_str(value).map_err(|e| sqlx::Error::Decode(Box::new(e)))
+}
+
+fn parse_time(value: &str) -> Result<DateTime<Utc>, sqlx::Error> {
+    value
+        .parse::<DateTime<Utc>>()
+        .map_err(|e| sqlx::Error::Decode(Box::new(e)))
+}
+
+fn row_to_summary(row: &SqliteRow) -> Result<ReportSummary, sqlx::Error> {
+    let kind: String = row.try_get("kind")?;
+    let trigger: String = row.try_get("triggered_by")?;
+    let period_start: String = row.try_get("period_start")?;
+    let period_end: String = row.try_get("period_end")?;
+    let generated_at: String = row.try_get("generated_at")?;
+    let deliveries: String = row.try_get("deliveries")?;
+
+    Ok(ReportSummary {
+        id: row.try_get("id")?,
+        kind: ReportKind::from_str(&kind)
+            .ok_or_else(|| sqlx::Error::Protocol(format!("Unknown report kind: {}", kind)))?,
+        title: row.try_get("title")?,
+        trigger: ReportTrigger::from_str(&trigger)
+            .ok_or_else(|| sqlx::Error::Protocol(format!("Unknown report trigger: {}", trigger)))?,
+        period_start: parse_time(&period_start)?,
+        period_end: parse_time(&period_end)?,
+        generated_at: parse_time(&generated_at)?,
+        incomplete: row.try_get("incomplete")?,
+        deliveries: from_json(&deliveries)?,
+    })
+}
@@ -0,0 +1,1584 @@
+//! Export of the full portfolio state for external tools: positions with
+//! their lot cost basis, tax lots, open orders, DeFi and staking positions,
+//! trades in a window and realized P&L per token. The export is either one
+//! JSON document or a zip of CSVs, written section by section straight to
+//! disk. Every section follows a column schema versioned by
+//! [`PORTFOLIO_EXPORT_SCHEMA_VERSION`], and the manifest records its row
+//! count and SHA-256: of the CSV file in a zip, or of the section's array
+//! exactly as written in a JSON document.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, SecondsFormat, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Emitter, Manager};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use super::rebalancer::SharedPortfolioData;
+use super::tax_lots::SharedTaxLotsState;
+use super::types::{LotStrategy, Position, TaxLot};
+use crate::config::privacy_mode::{warn_unmasked_export, SharedPrivacyMode};
+use crate::defi::position_manager::PositionManager;
+use crate::defi::types::{DeFiPosition, PositionType, Protocol};
+use crate::errors::AppError;
+use crate::trading::limit_orders::require_state;
+use crate::trading::types::Order;
+use crate::wallet::multi_wallet::MultiWalletManager;
+use crate::wallet::performance::{SharedPerformanceDatabase, Trade};
+
+pub const PORTFOLIO_EXPORT_SCHEMA_VERSION: u32 = 1;
+const PROGRESS_EVENT: &str = "portfolio_export_progress";
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+const TRADE_PAGE_SIZE: i64 = 500;
+/// Name the export goes by in the privacy mode exemption list.
+const PRIVACY_EXPORT: &str = "portfolio_state";
+
+#[derive(Debug, thiserror::Error)]
+pub enum PortfolioExportError {
+    #[error("invalid portfolio export: {0}")]
+    Invalid(String),
+    #[error("privacy mode is on; confirm the export to write unmasked balances")]
+    ConfirmationRequired,
+    #[error("failed to read portfolio data: {0}")]
+    Data(String),
+    #[error("io error: {0}")]
+    Io(#[from] io::Error),
+    #[error("zip error: {0}")]
+    Zip(#[from] zip::result::ZipError),
+    #[error("serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+    #[error("database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+impl From<PortfolioExportError> for AppError {
+    fn from(err: PortfolioExportError) -> Self {
+        match err {
+            PortfolioExportError::Invalid(message) => {
+                AppError::validation(message).with_code("invalid_portfolio_export")
+            }
+            PortfolioExportError::ConfirmationRequired => {
+                AppError::validation(err.to_string()).with_code("privacy_confirmation_required")
+            }
+            PortfolioExportError::Data(message) => AppError::internal(message),
+            PortfolioExportError::Io(err) => err.into(),
+            PortfolioExportError::Zip(err) => AppError::internal(err.to_string()),
+            PortfolioExportError::Serialization(err) => err.into(),
+            PortfolioExportError::Database(err) => err.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PortfolioExportFormat {
+    /// One JSON document holding every section.
+    Json,
+    /// A zip with one CSV per section.
+    Csv,
+}
+
+/// What an export covers. Positions and tax lots are the app's own
+/// portfolio state; the rest is read per wallet.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortfolioExportScope {
+    /// Wallets whose orders, DeFi positions, staking and trades are
+    /// exported; every wallet that can sign when empty.
+    #[serde(default)]
+    pub wallets: Vec<String>,
+    /// Start of the window for trades, disposed lots and realized P&L;
+    /// all history when unset.
+    #[serde(default)]
+    pub from: Option<DateTime<Utc>>,
+    /// End of the window; the time of the export when unset.
+    #[serde(default)]
+    pub to: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportSection {
+    Positions,
+    TaxLots,
+    OpenOrders,
+    DefiPositions,
+    Staking,
+    Trades,
+    RealizedPnl,
+}
+
+impl ExportSection {
+    pub fn schema(self) -> &'static SectionSchema {
+        SECTION_SCHEMAS
+            .iter()
+            .find(|schema| schema.section == self)
+            .expect("every section has a schema")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColumnKind {
+    String,
+    Number,
+    Integer,
+    /// RFC 3339 in UTC, to the second.
+    Timestamp,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ColumnSpec {
+    pub name: &'static str,
+    pub kind: ColumnKind,
+    /// Empty in CSV and `null` in JSON when there is no value.
+    pub nullable: bool,
+    pub description: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SectionSchema {
+    pub section: ExportSection,
+    /// File name of the section in a CSV export.
+    pub file: &'static str,
+    pub description: &'static str,
+    pub columns: &'static [ColumnSpec],
+}
+
+/// The documented field layout of every section.
+#[derive(Debug, Serialize)]
+pub struct PortfolioExportSchema {
+    pub schema_version: u32,
+    pub sections: &'static [SectionSchema],
+}
+
+impl ColumnSpec {
+    const fn new(name: &'static str, kind: ColumnKind, description: &'static str) -> Self {
+        Self {
+            name,
+            kind,
+            nullable: false,
+            description,
+        }
+    }
+
+    const fn string(name: &'static str, description: &'static str) -> Self {
+        Self::new(name, ColumnKind::String, description)
+    }
+
+    const fn number(name: &'static str, description: &'static str) -> Self {
+        Self::new(name, ColumnKind::Number, description)
+    }
+
+    const fn integer(name: &'static str, description: &'static str) -> Self {
+        Self::new(name, ColumnKind::Integer, description)
+    }
+
+    const fn timestamp(name: &'static str, description: &'static str) -> Self {
+        Self::new(name, ColumnKind::Timestamp, description)
+    }
+
+    const fn nullable(mut self) -> Self {
+        self.nullable = true;
+        self
+    }
+}
+
+const POSITION_COLUMNS: &[ColumnSpec] = &[
+    ColumnSpec::string("mint", "Token mint address"),
+    ColumnSpec::string("symbol", "Token symbol"),
+    ColumnSpec::number("amount", "Units held"),
+    ColumnSpec::number("current_price", "Last price in USD"),
+    ColumnSpec::number("market_value", "Value of the units held in USD"),
+    ColumnSpec::string(
+        "lot_strategy",
+        "Lot selection strategy sales were matched with: FIFO, LIFO, HIFO or SPECIFIC",
+    ),
+    ColumnSpec::integer("open_lots", "Open tax lots of the token"),
+    ColumnSpec::number("cost_basis", "USD cost of the units held"),
+    ColumnSpec::string(
+        "cost_basis_source",
+        "`lots` when summed from open tax lots, `average_entry` when the token has none",
+    ),
+    ColumnSpec::number("unrealized_pnl", "market_value minus cost_basis in USD"),
+];
+
+const TAX_LOT_COLUMNS: &[ColumnSpec] = &[
+    ColumnSpec::string("lot_id", "Tax lot id"),
+    ColumnSpec::string("mint", "Token mint address"),
+    ColumnSpec::string("symbol", "Token symbol"),
+    ColumnSpec::string("status", "`open` or `disposed`"),
+    ColumnSpec::number("amount", "Units in the lot"),
+    ColumnSpec::number("cost_basis", "USD cost of the units in the lot"),
+    ColumnSpec::number("price_per_unit", "USD paid per unit"),
+    ColumnSpec::timestamp("acquired_at", "When the units were acquired"),
+    ColumnSpec::number("disposed_amount", "Units sold out of the lot").nullable(),
+    ColumnSpec::timestamp("disposed_at", "When the lot was sold").nullable(),
+    ColumnSpec::number("realized_gain", "USD gain realized by the sale").nullable(),
+];
+
+const OPEN_ORDER_COLUMNS: &[ColumnSpec] = &[
+    ColumnSpec::string("order_id", "Order id"),
+    ColumnSpec::string("wallet_address", "Wallet placing the order"),
+    ColumnSpec::string(
+        "order_type",
+        "market, limit, stop_loss, take_profit or trailing_stop",
+    ),
+    ColumnSpec::string("side", "buy or sell"),
+    ColumnSpec::string("status", "pending or partially_filled"),
+    ColumnSpec::string("input_mint", "Mint the order spends"),
+    ColumnSpec::string("input_symbol", "Symbol the order spends"),
+    ColumnSpec::string("output_mint", "Mint the order receives"),
+    ColumnSpec::string("output_symbol", "Symbol the order receives"),
+    ColumnSpec::number("amount", "Units of the input token to trade"),
+    ColumnSpec::number("filled_amount", "Units traded so far"),
+    ColumnSpec::number("limit_price", "Limit price in USD").nullable(),
+    ColumnSpec::number("stop_price", "Stop price in USD").nullable(),
+    ColumnSpec::number("trailing_percent", "Trailing stop distance in percent").nullable(),
+    ColumnSpec::timestamp("created_at", "When the order was placed"),
+];
+
+const DEFI_POSITION_COLUMNS: &[ColumnSpec] = &[
+    ColumnSpec::string("position_id", "Position id at the protocol"),
+    ColumnSpec::string("wallet_address", "Wallet holding the position"),
+    ColumnSpec::string("protocol", "Protocol name"),
+    ColumnSpec::string(
+        "position_type",
+        "lending, borrowing, liquidity_pool or farming",
+    ),
+    ColumnSpec::string("asset", "Asset or pool symbol"),
+    ColumnSpec::number("amount", "Units supplied, borrowed or deposited"),
+    ColumnSpec::number("value_usd", "Position value in USD"),
+    ColumnSpec::number("apy", "Current APY in percent"),
+    ColumnSpec::number("pending_rewards_usd", "Unclaimed rewards in USD"),
+    ColumnSpec::number("health_factor", "Health factor of a lending account").nullable(),
+    ColumnSpec::timestamp("opened_at", "When the position was opened"),
+    ColumnSpec::timestamp("updated_at", "When the protocol last reported it"),
+];
+
+const STAKING_COLUMNS: &[ColumnSpec] = &[
+    ColumnSpec::string("position_id", "Stake account or position id"),
+    ColumnSpec::string("wallet_address", "Wallet holding the stake"),
+    ColumnSpec::string("protocol", "Protocol name"),
+    ColumnSpec::string("asset", "Staked asset symbol"),
+    ColumnSpec::number("amount", "Units staked"),
+    ColumnSpec::number("value_usd", "Stake value in USD"),
+    ColumnSpec::number("apy", "Current APY in percent"),
+    ColumnSpec::string("reward_tokens", "Reward token symbols, `;`-separated"),
+    ColumnSpec::number("pending_rewards_usd", "Unclaimed rewards in USD"),
+    ColumnSpec::timestamp("opened_at", "When the stake was opened"),
+    ColumnSpec::timestamp("updated_at", "When the protocol last reported it"),
+];
+
+const TRADE_COLUMNS: &[ColumnSpec] = &[
+    ColumnSpec::string("trade_id", "Trade id"),
+    ColumnSpec::string("wallet_address", "Wallet that traded"),
+    ColumnSpec::timestamp("executed_at", "When the trade executed"),
+    ColumnSpec::string("token_mint", "Token mint address"),
+    ColumnSpec::string("token_symbol", "Token symbol"),
+    ColumnSpec::string("side", "buy or sell"),
+    ColumnSpec::number("amount", "Units traded"),
+    ColumnSpec::number("price", "USD price per unit"),
+    ColumnSpec::number("total_value", "USD value of the trade"),
+    ColumnSpec::number("fee", "Fee in USD"),
+    ColumnSpec::number("realized_pnl", "USD P&L realized by a closing trade").nullable(),
+    ColumnSpec::integer(
+        "hold_duration_seconds",
+        "How long the closed units were held",
+    )
+    .nullable(),
+    ColumnSpec::string("strategy_source", "Part of the app that placed the trade"),
+    ColumnSpec::string("tx_signature", "Transaction signature"),
+];
+
+const REALIZED_PNL_COLUMNS: &[ColumnSpec] = &[
+    ColumnSpec::string("wallet_address", "Wallet that traded"),
+    ColumnSpec::string("token_mint", "Token mint address"),
+    ColumnSpec::string("token_symbol", "Token symbol"),
+    ColumnSpec::integer("trades", "Trades of the token in the window"),
+    ColumnSpec::integer("closing_trades", "Trades in the window that realized P&L"),
+    ColumnSpec::integer("winning_trades", "Closing trades with a positive P&L"),
+    ColumnSpec::number("realized_pnl", "USD P&L realized in the window"),
+    ColumnSpec::number("fees", "USD fees paid in the window"),
+    ColumnSpec::timestamp("period_start", "Start of the window"),
+    ColumnSpec::timestamp("period_end", "End of the window"),
+];
+
+pub static SECTION_SCHEMAS: [SectionSchema; 7] = [
+    SectionSchema {
+        section: ExportSection::Positions,
+        file: "positions.csv",
+        description: "Current holdings with their cost basis",
+        columns: POSITION_COLUMNS,
+    },
+    SectionSchema {
+        section: ExportSection::TaxLots,
+        file: "tax_lots.csv",
+        description: "Open lots and lots disposed in the window",
+        columns: TAX_LOT_COLUMNS,
+    },
+    SectionSchema {
+        section: ExportSection::OpenOrders,
+        file: "open_orders.csv",
+        description: "Orders still waiting to trigger or fill",
+        columns: OPEN_ORDER_COLUMNS,
+    },
+    SectionSchema {
+        section: ExportSection::DefiPositions,
+        file: "defi_positions.csv",
+        description: "Lending, borrowing, liquidity and farming positions",
+        columns: DEFI_POSITION_COLUMNS,
+    },
+    SectionSchema {
+        section: ExportSection::Staking,
+        file: "staking.csv",
+        description: "Staked positions",
+        columns: STAKING_COLUMNS,
+    },
+    SectionSchema {
+        section: ExportSection::Trades,
+        file: "trades.csv",
+        description: "Trades executed in the window",
+        columns: TRADE_COLUMNS,
+    },
+    SectionSchema {
+        section: ExportSection::RealizedPnl,
+        file: "realized_pnl.csv",
+        description: "Realized P&L per wallet and token over the window",
+        columns: REALIZED_PNL_COLUMNS,
+    },
+];
+
+pub fn portfolio_export_schema() -> PortfolioExportSchema {
+    PortfolioExportSchema {
+        schema_version: PORTFOLIO_EXPORT_SCHEMA_VERSION,
+        sections: &SECTION_SCHEMAS,
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SectionManifest {
+    pub section: ExportSection,
+    /// The section's file inside a CSV export.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    pub rows: u64,
+    pub sha256: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortfolioExportManifest {
+    pub schema_version: u32,
+    pub format: PortfolioExportFormat,
+    pub generated_at: DateTime<Utc>,
+    pub wallets: Vec<String>,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub sections: Vec<SectionManifest>,
+    /// Sources that could not be read; their sections are short or empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortfolioExportResult {
+    pub path: PathBuf,
+    pub bytes: u64,
+    /// SHA-256 of the whole export file.
+    pub sha256: String,
+    pub manifest: PortfolioExportManifest,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortfolioExportProgress {
+    pub section: ExportSection,
+    pub sections_done: usize,
+    pub sections_total: usize,
+    /// Rows written to the current section.
+    pub rows_written: u64,
+    pub total_rows: u64,
+}
+
+/// Rows are written in the order of the section's columns.
+trait ExportSink {
+    type Output;
+
+    fn begin_section(&mut self, schema: &'static SectionSchema)
+        -> Result<(), PortfolioExportError>;
+    fn write_row(&mut self, row: &[Value]) -> Result<(), PortfolioExportError>;
+    fn end_section(&mut self) -> Result<SectionManifest, PortfolioExportError>;
+    /// Writes the manifest and hands back the underlying writer.
+    fn finish(
+        self,
+        manifest: &PortfolioExportManifest,
+    ) -> Result<Self::Output, PortfolioExportError>;
+}
+
+struct OpenSection {
+    schema: &'static SectionSchema,
+    rows: u64,
+    hasher: Sha256,
+}
+
+impl OpenSection {
+    fn new(schema: &'static SectionSchema) -> Self {
+        Self {
+            schema,
+            rows: 0,
+            hasher: Sha256::new(),
+        }
+    }
+
+    fn close(self, file: Option<String>) -> SectionManifest {
+        SectionManifest {
+            section: self.schema.section,
+            file,
+            rows: self.rows,
+            sha256: hex::encode(self.hasher.finalize()),
+        }
+    }
+}
+
+fn no_open_section() -> PortfolioExportError {
+    PortfolioExportError::Invalid("no export section is open".to_string())
+}
+
+/// Writes `{"schema_version", "generated_at", "schema", "sections": {...},
+/// "manifest"}` with each section an array of objects keyed by column.
+struct JsonSink<W: Write> {
+    out: W,
+    sections_written: usize,
+    open: Option<OpenSection>,
+}
+
+impl<W: Write> JsonSink<W> {
+    fn new(mut out: W, generated_at: DateTime<Utc>) -> Result<Self, PortfolioExportError> {
+        out.write_all(b"{\"schema_version\":")?;
+        serde_json::to_writer(&mut out, &PORTFOLIO_EXPORT_SCHEMA_VERSION)?;
+        out.write_all(b",\"generated_at\":")?;
+        serde_json::to_writer(&mut out, &timestamp(generated_at))?;
+        out.write_all(b",\"schema\":")?;
+        serde_json::to_writer(&mut out, &portfolio_export_schema().sections)?;
+        out.write_all(b",\"sections\":{")?;
+        Ok(Self {
+            out,
+            sections_written: 0,
+            open: None,
+        })
+    }
+
+    /// Writes bytes that belong to the open section's checksum.
+    fn emit(&mut self, bytes: &[u8]) -> Result<(), PortfolioExportError> {
+        let open = self.open.as_mut().ok_or_else(no_open_section)?;
+        open.hasher.update(bytes);
+        self.out.write_all(bytes)?;
+        Ok(())
+    }
+}
+
+impl<W: Write> ExportSink for JsonSink<W> {
+    type Output = W;
+
+    fn begin_section(
+        &mut self,
+        schema: &'static SectionSchema,
+    ) -> Result<(), PortfolioExportError> {
+        if self.sections_written > 0 {
+            self.out.write_all(b",")?;
+        }
+        serde_json::to_writer(&mut self.out, &schema.section)?;
+        self.out.write_all(b":")?;
+        self.open = Some(OpenSection::new(schema));
+        self.emit(b"[")
+    }
+
+    fn write_row(&mut self, row: &[Value]) -> Result<(), PortfolioExportError> {
+        let open = self.open.as_ref().ok_or_else(no_open_section)?;
+        let mut object = String::from(if open.rows > 0 { ",{" } else { "{" });
+        for (index, (column, value)) in open.schema.columns.iter().zip(row).enumerate() {
+            if index > 0 {
+                object.push(',');
+            }
+            object.push_str(&serde_json::to_string(column.name)?);
+            object.push(':');
+            object.push_str(&serde_json::to_string(value)?);
+        }
+        object.push('}');
+        self.emit(object.as_bytes())?;
+        if let Some(open) = self.open.as_mut() {
+            open.rows += 1;
+        }
+        Ok(())
+    }
+
+    fn end_section(&mut self) -> Result<SectionManifest, PortfolioExportError> {
+        self.emit(b"]")?;
+        let open = self.open.take().ok_or_else(no_open_section)?;
+        self.sections_written += 1;
+        Ok(open.close(None))
+    }
+
+    fn finish(mut self, manifest: &PortfolioExportManifest) -> Result<W, PortfolioExportError> {
+        self.out.write_all(b"},\"manifest\":")?;
+        serde_json::to_writer(&mut self.out, manifest)?;
+        self.out.write_all(b"}")?;
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
+
+/// Writes one CSV per section, then `schema.json` and `manifest.json`.
+struct CsvZipSink<W: Write + Seek> {
+    zip: ZipWriter<W>,
+    options: SimpleFileOptions,
+    open: Option<OpenSection>,
+}
+
+impl<W: Write + Seek> CsvZipSink<W> {
+    fn new(out: W) -> Self {
+        Self {
+            zip: ZipWriter::new(out),
+            options: SimpleFileOptions::default().compression_method(CompressionMethod::Deflated),
+            open: None,
+        }
+    }
+
+    fn emit(&mut self, text: &str) -> Result<(), PortfolioExportError> {
+        let open = self.open.as_mut().ok_or_else(no_open_section)?;
+        open.hasher.update(text.as_bytes());
+        self.zip.write_all(text.as_bytes())?;
+        Ok(())
+    }
+}
+
+impl<W: Write + Seek> ExportSink for CsvZipSink<W> {
+    type Output = W;
+
+    fn begin_section(
+        &mut self,
+        schema: &'static SectionSchema,
+    ) -> Result<(), PortfolioExportError> {
+        self.zip.start_file(schema.file, self.options)?;
+        self.open = Some(OpenSection::new(schema));
+        self.emit(&csv_header(schema))
+    }
+
+    fn write_row(&mut self, row: &[Value]) -> Result<(), PortfolioExportError> {
+        self.emit(&csv_line(row))?;
+        if let Some(open) = self.open.as_mut() {
+            open.rows += 1;
+        }
+        Ok(())
+    }
+
+    fn end_section(&mut self) -> Result<SectionManifest, PortfolioExportError> {
+        let open = self.open.take().ok_or_else(no_open_section)?;
+        let file = open.schema.file.to_string();
+        Ok(open.close(Some(file)))
+    }
+
+    fn finish(mut self, manifest: &PortfolioExportManifest) -> Result<W, PortfolioExportError> {
+        self.zip.start_file("schema.json", self.options)?;
+        self.zip
+            .write_all(&serde_json::to_vec_pretty(&portfolio_export_schema())?)?;
+        self.zip.start_file("manifest.json", self.options)?;
+        self.zip.write_all(&serde_json::to_vec_pretty(manifest)?)?;
+        Ok(self.zip.finish()?)
+    }
+}
+
+/// A `#schema_version=N` comment line followed by the column names.
+fn csv_header(schema: &SectionSchema) -> String {
+    let names: Vec<&str> = schema.columns.iter().map(|column| column.name).collect();
+    format!(
+        "#schema_version={}\n{}\n",
+        PORTFOLIO_EXPORT_SCHEMA_VERSION,
+        names.join(",")
+    )
+}
+
+fn csv_line(row: &[Value]) -> String {
+    let cells: Vec<String> = row.iter().map(csv_cell).collect();
+    format!("{}\n", cells.join(","))
+}
+
+fn csv_cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => csv_escape(text),
+        Value::Number(number) => number.to_string(),
+        Value::Bool(flag) => flag.to_string(),
+        other => csv_escape(&other.to_string()),
+    }
+}
+
+fn csv_escape(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+fn timestamp(at: DateTime<Utc>) -> Value {
+    Value::String(at.to_rfc3339_opts(SecondsFormat::Secs, true))
+}
+
+fn unix_timestamp(seconds: i64) -> Value {
+    Utc.timestamp_opt(seconds, 0)
+        .single()
+        .map(timestamp)
+        .unwrap_or(Value::Null)
+}
+
+/// Lot times are stored as RFC 3339 strings; they are normalized when they
+/// parse and passed through otherwise.
+fn lot_timestamp(value: &str) -> Value {
+    DateTime::parse_from_rfc3339(value)
+        .map(|at| timestamp(at.with_timezone(&Utc)))
+        .unwrap_or_else(|_| Value::String(value.to_string()))
+}
+
+fn text(value: impl Into<String>) -> Value {
+    Value::String(value.into())
+}
+
+fn strategy_name(strategy: &LotStrategy) -> &'static str {
+    match strategy {
+        LotStrategy::FIFO => "FIFO",
+        LotStrategy::LIFO => "LIFO",
+        LotStrategy::HIFO => "HIFO",
+        LotStrategy::SPECIFIC => "SPECIFIC",
+    }
+}
+
+fn protocol_name(protocol: &Protocol) -> String {
+    match protocol {
+        Protocol::Solend => "Solend".to_string(),
+        Protocol::MarginFi => "MarginFi".to_string(),
+        Protocol::Kamino => "Kamino".to_string(),
+        Protocol::Raydium => "Raydium".to_string(),
+        Protocol::Orca => "Orca".to_string(),
+        Protocol::Other(name) => name.clone(),
+    }
+}
+
+fn position_type_name(position_type: &PositionType) -> &'static str {
+    match position_type {
+        PositionType::Lending => "lending",
+        PositionType::Borrowing => "borrowing",
+        PositionType::LiquidityPool => "liquidity_pool",
+        PositionType::Staking => "staking",
+        PositionType::Farming => "farming",
+    }
+}
+
+fn position_row(position: &Position, open_lots: &[&TaxLot], strategy: &LotStrategy) -> Vec<Value> {
+    let (cost_basis, source) = if open_lots.is_empty() {
+        (position.avg_entry_price * position.amount, "average_entry")
+    } else {
+        (open_lots.iter().map(|lot| lot.cost_basis).sum(), "lots")
+    };
+    let market_value = position.amount * position.current_price;
+    vec![
+        text(&position.mint),
+        text(&position.symbol),
+        Value::from(position.amount),
+        Value::from(position.current_price),
+        Value::from(market_value),
+        text(strategy_name(strategy)),
+        Value::from(open_lots.len()),
+        Value::from(cost_basis),
+        text(source),
+        Value::from(market_value - cost_basis),
+    ]
+}
+
+fn lot_row(lot: &TaxLot) -> Vec<Value> {
+    let status = if lot.disposed_at.is_some() {
+        "disposed"
+    } else {
+        "open"
+    };
+    vec![
+        text(&lot.id),
+        text(&lot.mint),
+        text(&lot.symbol),
+        text(status),
+        Value::from(lot.amount),
+        Value::from(lot.cost_basis),
+        Value::from(lot.price_per_unit),
+        lot_timestamp(&lot.acquired_at),
+        Value::from(lot.disposed_amount),
+        lot.disposed_at
+            .as_deref()
+            .map(lot_timestamp)
+            .unwrap_or(Value::Null),
+        Value::from(lot.realized_gain),
+    ]
+}
+
+fn order_row(order: &Order) -> Vec<Value> {
+    vec![
+        text(&order.id),
+        text(&order.wallet_address),
+        text(order.order_type.to_string()),
+        text(order.side.to_string()),
+        text(order.status.to_string()),
+        text(&order.input_mint),
+        text(&order.input_symbol),
+        text(&order.output_mint),
+        text(&order.output_symbol),
+        Value::from(order.amount),
+        Value::from(order.filled_amount),
+        Value::from(order.limit_price),
+        Value::from(order.stop_price),
+        Value::from(order.trailing_percent),
+        timestamp(order.created_at),
+    ]
+}
+
+fn pending_rewards_usd(position: &DeFiPosition) -> f64 {
+    position.rewards.iter().map(|reward| reward.value_usd).sum()
+}
+
+fn defi_row(wallet: &str, position: &DeFiPosition) -> Vec<Value> {
+    vec![
+        text(&position.id),
+        text(wallet),
+        text(protocol_name(&position.protocol)),
+        text(position_type_name(&position.position_type)),
+        text(&position.asset),
+        Value::from(position.amount),
+        Value::from(position.value_usd),
+        Value::from(position.apy),
+        Value::from(pending_rewards_usd(position)),
+        Value::from(position.health_factor),
+        unix_timestamp(position.created_at),
+        unix_timestamp(position.last_updated),
+    ]
+}
+
+fn staking_row(wallet: &str, position: &DeFiPosition) -> Vec<Value> {
+    let reward_tokens: Vec<&str> = position
+        .rewards
+        .iter()
+        .map(|reward| reward.token.as_str())
+        .collect();
+    vec![
+        text(&position.id),
+        text(wallet),
+        text(protocol_name(&position.protocol)),
+        text(&position.asset),
+        Value::from(position.amount),
+        Value::from(position.value_usd),
+        Value::from(position.apy),
+        text(reward_tokens.join(";")),
+        Value::from(pending_rewards_usd(position)),
+        unix_timestamp(position.created_at),
+        unix_timestamp(position.last_updated),
+    ]
+}
+
+fn trade_row(trade: &Trade) -> Vec<Value> {
+    vec![
+        text(&trade.id),
+        text(&trade.wallet_address),
+        timestamp(trade.timestamp),
+        text(&trade.token_mint),
+        text(&trade.token_symbol),
+        text(&trade.side),
+        Value::from(trade.amount),
+        Value::from(trade.price),
+        Value::from(trade.total_value),
+        Value::from(trade.fee),
+        Value::from(trade.pnl),
+        Value::from(trade.hold_duration_seconds),
+        text(&trade.strategy_source),
+        text(&trade.tx_signature),
+    ]
+}
+
+/// Realized P&L of one token in one wallet, summed while trades stream.
+#[derive(Debug, Default)]
+struct RealizedSummary {
+    token_symbol: String,
+    trades: u64,
+    closing_trades: u64,
+    winning_trades: u64,
+    realized_pnl: f64,
+    fees: f64,
+}
+
+impl RealizedSummary {
+    fn add(&mut self, trade: &Trade) {
+        self.token_symbol = trade.token_symbol.clone();
+        self.trades += 1;
+        self.fees += trade.fee;
+        if let Some(pnl) = trade.pnl {
+            self.closing_trades += 1;
+            if pnl > 0.0 {
+                self.winning_trades += 1;
+            }
+            self.realized_pnl += pnl;
+        }
+    }
+}
+
+fn realized_row(
+    wallet: &str,
+    mint: &str,
+    summary: &RealizedSummary,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Vec<Value> {
+    vec![
+        text(wallet),
+        text(mint),
+        text(&summary.token_symbol),
+        Value::from(summary.trades),
+        Value::from(summary.closing_trades),
+        Value::from(summary.winning_trades),
+        Value::from(summary.realized_pnl),
+        Value::from(summary.fees),
+        timestamp(from),
+        timestamp(to),
+    ]
+}
+
+struct ResolvedScope {
+    wallets: Vec<String>,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    generated_at: DateTime<Utc>,
+}
+
+fn resolve_scope(
+    app: &AppHandle,
+    scope: PortfolioExportScope,
+) -> Result<ResolvedScope, PortfolioExportError> {
+    let generated_at = Utc::now();
+    let from = scope.from.unwrap_or(DateTime::UNIX_EPOCH);
+    let to = scope.to.unwrap_or(generated_at);
+    if from > to {
+        return Err(PortfolioExportError::Invalid(
+            "the export window starts after it ends".to_string(),
+        ));
+    }
+
+    let mut wallets: Vec<String> = Vec::new();
+    for wallet in scope.wallets {
+        let wallet = wallet.trim().to_string();
+        if !wallet.is_empty() && !wallets.contains(&wallet) {
+            wallets.push(wallet);
+        }
+    }
+    if wallets.is_empty() {
+        if let Some(manager) = app.try_state::<MultiWalletManager>() {
+            wallets = manager
+                .list_wallets()
+                .map_err(|err| PortfolioExportError::Data(err.to_string()))?
+                .into_iter()
+                .filter(|wallet| !wallet.watch_only)
+                .map(|wallet| wallet.public_key)
+                .collect();
+        }
+    }
+
+    Ok(ResolvedScope {
+        wallets,
+        from,
+        to,
+        generated_at,
+    })
+}
+
+/// Drives a sink through the sections, collecting their manifests and
+/// reporting progress.
+struct ExportRun<'a, S: ExportSink> {
+    app: &'a AppHandle,
+    sink: S,
+    sections: Vec<SectionManifest>,
+    warnings: Vec<String>,
+    progress: PortfolioExportProgress,
+    last_emit: Option<Instant>,
+}
+
+impl<'a, S: ExportSink> ExportRun<'a, S> {
+    fn new(app: &'a AppHandle, sink: S) -> Self {
+        Self {
+            app,
+            sink,
+            sections: Vec::new(),
+            warnings: Vec::new(),
+            progress: PortfolioExportProgress {
+                section: ExportSection::Positions,
+                sections_done: 0,
+                sections_total: SECTION_SCHEMAS.len(),
+                rows_written: 0,
+                total_rows: 0,
+            },
+            last_emit: None,
+        }
+    }
+
+    fn begin(&mut self, section: ExportSection) -> Result<(), PortfolioExportError> {
+        self.sink.begin_section(section.schema())?;
+        self.progress.section = section;
+        self.progress.rows_written = 0;
+        self.emit_progress(true);
+        Ok(())
+    }
+
+    fn row(&mut self, row: Vec<Value>) -> Result<(), PortfolioExportError> {
+        self.sink.write_row(&row)?;
+        self.progress.rows_written += 1;
+        self.progress.total_rows += 1;
+        self.emit_progress(false);
+        Ok(())
+    }
+
+    fn end(&mut self) -> Result<(), PortfolioExportError> {
+        let manifest = self.sink.end_section()?;
+        self.sections.push(manifest);
+        self.progress.sections_done += 1;
+        self.emit_progress(true);
+        Ok(())
+    }
+
+    fn warn(&mut self, message: impl Into<String>) {
+        self.warnings.push(message.into());
+    }
+
+    fn emit_progress(&mut self, force: bool) {
+        let due = match self.last_emit {
+            Some(at) => at.elapsed() >= PROGRESS_INTERVAL,
+            None => true,
+        };
+        if force || due {
+            self.last_emit = Some(Instant::now());
+            let _ = self.app.emit(PROGRESS_EVENT, &self.progress);
+        }
+    }
+}
+
+fn tax_lot_state(
+    app: &AppHandle,
+) -> Result<Option<(Vec<TaxLot>, LotStrategy)>, PortfolioExportError> {
+    let Some(state) = app.try_state::<SharedTaxLotsState>() else {
+        return Ok(None);
+    };
+    let guard = state
+        .lock()
+        .map_err(|_| PortfolioExportError::Data("tax lot state is poisoned".to_string()))?;
+    Ok(Some((guard.all_lots(), guard.strategy())))
+}
+
+fn write_positions<S: ExportSink>(run: &mut ExportRun<'_, S>) -> Result<(), PortfolioExportError> {
+    let app = run.app;
+    run.begin(ExportSection::Positions)?;
+    let positions = match app.try_state::<SharedPortfolioData>() {
+        Some(data) => data
+            .lock()
+            .map(|guard| guard.positions())
+            .map_err(|_| PortfolioExportError::Data("portfolio data is poisoned".to_string()))?,
+        None => {
+            run.warn("portfolio data is not available; positions omitted");
+            Vec::new()
+        }
+    };
+    let (lots, strategy) = tax_lot_state(app)?.unwrap_or((Vec::new(), LotStrategy::FIFO));
+
+    let mut open_by_mint: HashMap<&str, Vec<&TaxLot>> = HashMap::new();
+    for lot in lots
+        .iter()
+        .filter(|lot| lot.disposed_at.is_none() && lot.amount > 0.0)
+    {
+        open_by_mint.entry(lot.mint.as_str()).or_default().push(lot);
+    }
+    for position in &positions {
+        let open_lots = open_by_mint
+            .get(position.mint.as_str())
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        run.row(position_row(position, open_lots, &strategy))?;
+    }
+    run.end()
+}
+
+fn write_tax_lots<S: ExportSink>(
+    run: &mut ExportRun<'_, S>,
+    scope: &ResolvedScope,
+) -> Result<(), PortfolioExportError> {
+    let app = run.app;
+    run.begin(ExportSection::TaxLots)?;
+    let Some((lots, _)) = tax_lot_state(app)? else {
+        run.warn("tax lots are not available; tax lots omitted");
+        return run.end();
+    };
+    for lot in &lots {
+        let in_window = match lot.disposed_at.as_deref() {
+            None => true,
+            Some(disposed_at) => DateTime::parse_from_rfc3339(disposed_at)
+                .map(|at| {
+                    let at = at.with_timezone(&Utc);
+                    at >= scope.from && at <= scope.to
+                })
+                .unwrap_or(true),
+        };
+        if in_window {
+            run.row(lot_row(lot))?;
+        }
+    }
+    run.end()
+}
+
+async fn write_open_orders<S: ExportSink>(
+    run: &mut ExportRun<'_, S>,
+    scope: &ResolvedScope,
+) -> Result<(), PortfolioExportError> {
+    run.begin(ExportSection::OpenOrders)?;
+    match require_state() {
+        Ok(trading) => {
+            for wallet in &scope.wallets {
+                let orders = trading
+                    .manager
+                    .get_active_orders(wallet)
+                    .await
+                    .map_err(|err| PortfolioExportError::Data(err.to_string()))?;
+                for order in &orders {
+                    run.row(order_row(order))?;
+                }
+            }
+        }
+        Err(_) => run.warn("trading is not initialized; open orders omitted"),
+    }
+    run.end()
+}
+
+/// DeFi positions and staking come from the same protocol fetch; a wallet
+/// whose fetch fails is left out of both with a warning.
+async fn write_defi_sections<S: ExportSink>(
+    run: &mut ExportRun<'_, S>,
+    scope: &ResolvedScope,
+) -> Result<(), PortfolioExportError> {
+    let manager = PositionManager::new();
+    let mut positions: Vec<(&str, DeFiPosition)> = Vec::new();
+    for wallet in &scope.wallets {
+        match manager.fetch_positions(wallet).await {
+            Ok(found) => positions.extend(found.into_iter().map(|p| (wallet.as_str(), p))),
+            Err(err) => run.warn(format!(
+                "DeFi positions of {} are unavailable: {}",
+                wallet, err
+            )),
+        }
+    }
+
+    run.begin(ExportSection::DefiPositions)?;
+    for (wallet, position) in &positions {
+        if position.position_type != PositionType::Staking {
+            run.row(defi_row(wallet, position))?;
+        }
+    }
+    run.end()?;
+
+    run.begin(ExportSection::Staking)?;
+    for (wallet, position) in &positions {
+        if position.position_type == PositionType::Staking {
+            run.row(staking_row(wallet, position))?;
+        }
+    }
+    run.end()
+}
+
+/// Trades are paged out of the performance database and summed into the
+/// realized P&L section as they are written.
+async fn write_trade_sections<S: ExportSink>(
+    run: &mut ExportRun<'_, S>,
+    scope: &ResolvedScope,
+) -> Result<(), PortfolioExportError> {
+    let app = run.app;
+    let mut realized: BTreeMap<(String, String), RealizedSummary> = BTreeMap::new();
+
+    run.begin(ExportSection::Trades)?;
+    match app.try_state::<SharedPerformanceDatabase>() {
+        Some(db) => {
+            for wallet in &scope.wallets {
+                let mut offset = 0;
+                loop {
+                    let page = db
+                        .read()
+                        .await
+                        .get_trades_page(wallet, scope.from, scope.to, offset, TRADE_PAGE_SIZE)
+                        .await?;
+                    for trade in &page {
+                        realized
+                            .entry((trade.wallet_address.clone(), trade.token_mint.clone()))
+                            .or_default()
+                            .add(trade);
+                        run.row(trade_row(trade))?;
+                    }
+                    if (page.len() as i64) < TRADE_PAGE_SIZE {
+                        break;
+                    }
+                    offset += TRADE_PAGE_SIZE;
+                }
+            }
+        }
+        None => run.warn("performance history is not available; trades omitted"),
+    }
+    run.end()?;
+
+    run.begin(ExportSection::RealizedPnl)?;
+    for ((wallet, mint), summary) in &realized {
+        run.row(realized_row(wallet, mint, summary, scope.from, scope.to))?;
+    }
+    run.end()
+}
+
+async fn run_export<S: ExportSink>(
+    app: &AppHandle,
+    sink: S,
+    format: PortfolioExportFormat,
+    scope: &ResolvedScope,
+) -> Result<PortfolioExportManifest, PortfolioExportError> {
+    let mut run = ExportRun::new(app, sink);
+    write_positions(&mut run)?;
+    write_tax_lots(&mut run, scope)?;
+    write_open_orders(&mut run, scope).await?;
+    write_defi_sections(&mut run, scope).await?;
+    write_trade_sections(&mut run, scope).await?;
+
+    let ExportRun {
+        sink,
+        sections,
+        warnings,
+        ..
+    } = run;
+    let manifest = PortfolioExportManifest {
+        schema_version: PORTFOLIO_EXPORT_SCHEMA_VERSION,
+        format,
+        generated_at: scope.generated_at,
+        wallets: scope.wallets.clone(),
+        from: scope.from,
+        to: scope.to,
+        sections,
+        warnings,
+    };
+    sink.finish(&manifest)?;
+    Ok(manifest)
+}
+
+async fn write_export(
+    app: &AppHandle,
+    format: PortfolioExportFormat,
+    scope: &ResolvedScope,
+    path: &Path,
+) -> Result<PortfolioExportManifest, PortfolioExportError> {
+    let file = File::create(path)?;
+    match format {
+        PortfolioExportFormat::Json => {
+            let sink = JsonSink::new(BufWriter::new(file), scope.generated_at)?;
+            run_export(app, sink, format, scope).await
+        }
+        PortfolioExportFormat::Csv => run_export(app, CsvZipSink::new(file), format, scope).await,
+    }
+}
+
+/// The export is written next to its target and renamed into place once
+/// complete, so a failed export never leaves a plausible-looking file.
+fn partial_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".partial");
+    path.with_file_name(name)
+}
+
+fn file_sha256(path: &Path) -> io::Result<(String, u64)> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 64 * 1024];
+    let mut bytes = 0u64;
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        bytes += read as u64;
+    }
+    Ok((hex::encode(hasher.finalize()), bytes))
+}
+
+fn privacy_active(app: &AppHandle) -> bool {
+    app.try_state::<SharedPrivacyMode>()
+        .map(|privacy| {
+            privacy
+                .read()
+                .map(|privacy| privacy.is_active())
+                .unwrap_or(false)
+        })
+        .unwrap_or(false)
+}
+
+#[tauri::command]
+pub async fn export_portfolio_state(
+    app: AppHandle,
+    format: PortfolioExportFormat,
+    scope: Option<PortfolioExportScope>,
+    path: String,
+    confirm_unmasked: Option<bool>,
+) -> Result<PortfolioExportResult, AppError> {
+    let target = PathBuf::from(path.trim());
+    if target.file_name().is_none() {
+        return Err(
+            PortfolioExportError::Invalid("an export file path is required".to_string()).into(),
+        );
+    }
+    if privacy_active(&app) && !confirm_unmasked.unwrap_or(false) {
+        return Err(PortfolioExportError::ConfirmationRequired.into());
+    }
+    let scope = resolve_scope(&app, scope.unwrap_or_default())?;
+    warn_unmasked_export(&app, PRIVACY_EXPORT);
+
+    if let Some(parent) = target
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent)?;
+    }
+    let partial = partial_path(&target);
+    let manifest = match write_export(&app, format, &scope, &partial).await {
+        Ok(manifest) => manifest,
+        Err(err) => {
+            let _ = fs::remove_file(&partial);
+            return Err(err.into());
+        }
+    };
+    fs::rename(&partial, &target)?;
+    let (sha256, bytes) = file_sha256(&target)?;
+
+    Ok(PortfolioExportResult {
+        path: target,
+        bytes,
+        sha256,
+        manifest,
+    })
+}
+
+#[tauri::command]
+pub fn get_portfolio_export_schema() -> PortfolioExportSchema {
+    portfolio_export_schema()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::defi::types::Reward;
+    use crate::trading::types::{OrderSide, OrderStatus, OrderType};
+    use std::io::Cursor;
+
+    const SOL: &str = "So11111111111111111111111111111111111111112";
+    const USDC: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+    const WALLET: &str = "7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU";
+
+    fn at(seconds: i64) -> DateTime<Utc> {
+        Utc.timestamp_opt(seconds, 0).unwrap()
+    }
+
+    fn fixture(section: ExportSection) -> &'static str {
+        match section {
+            ExportSection::Positions => include_str!("fixtures/state_export/positions.csv"),
+            ExportSection::TaxLots => include_str!("fixtures/state_export/tax_lots.csv"),
+            ExportSection::OpenOrders => include_str!("fixtures/state_export/open_orders.csv"),
+            ExportSection::DefiPositions => {
+                include_str!("fixtures/state_export/defi_positions.csv")
+            }
+            ExportSection::Staking => include_str!("fixtures/state_export/staking.csv"),
+            ExportSection::Trades => include_str!("fixtures/state_export/trades.csv"),
+            ExportSection::RealizedPnl => include_str!("fixtures/state_export/realized_pnl.csv"),
+        }
+    }
+
+    fn sample_position() -> Position {
+        Position {
+            symbol: "SOL".to_string(),
+            mint: SOL.to_string(),
+            amount: 10.0,
+            current_price: 150.0,
+            avg_entry_price: 120.0,
+            total_value: 1500.0,
+            unrealized_pnl: 300.0,
+            unrealized_pnl_percent: 25.0,
+            allocation: 60.0,
+        }
+    }
+
+    fn sample_lot(id: &str, amount: f64, cost_basis: f64) -> TaxLot {
+        TaxLot {
+            id: id.to_string(),
+            symbol: "SOL".to_string(),
+            mint: SOL.to_string(),
+            amount,
+            cost_basis,
+            price_per_unit: cost_basis / amount,
+            acquired_at: "2024-01-15T09:30:00+00:00".to_string(),
+            disposed_amount: None,
+            disposed_at: None,
+            realized_gain: None,
+        }
+    }
+
+    fn sample_order() -> Order {
+        Order {
+            id: "order-1".to_string(),
+            order_type: OrderType::Limit,
+            side: OrderSide::Sell,
+            status: OrderStatus::Pending,
+            input_mint: SOL.to_string(),
+            output_mint: USDC.to_string(),
+            input_symbol: "SOL".to_string(),
+            output_symbol: "USDC".to_string(),
+            amount: 2.5,
+            filled_amount: 0.0,
+            fill_price: None,
+            limit_price: Some(175.5),
+            stop_price: None,
+            trailing_percent: None,
+            highest_price: None,
+            lowest_price: None,
+            linked_order_id: None,
+            min_book_depth_usd: None,
+            slippage_bps: 50,
+            priority_fee_micro_lamports: 1000,
+            wallet_address: WALLET.to_string(),
+            created_at: at(1_717_200_000),
+            updated_at: at(1_717_200_000),
+            triggered_at: None,
+            tx_signature: None,
+            error_message: None,
+            correlation_id: None,
+            origin: None,
+        }
+    }
+
+    fn sample_defi(position_type: PositionType) -> DeFiPosition {
+        DeFiPosition {
+            id: "kamino-usdc-1".to_string(),
+            protocol: Protocol::Kamino,
+            position_type,
+            asset: "USDC".to_string(),
+            amount: 1000.0,
+            value_usd: 1000.5,
+            apy: 7.25,
+            rewards: vec![
+                Reward {
+                    token: "KMNO".to_string(),
+                    amount: 12.0,
+                    value_usd: 1.5,
+                },
+                Reward {
+                    token: "JTO".to_string(),
+                    amount: 0.5,
+                    value_usd: 1.25,
+                },
+            ],
+            health_factor: Some(1.8),
+            created_at: 1_717_000_000,
+            last_updated: 1_717_200_000,
+        }
+    }
+
+    fn sample_trade() -> Trade {
+        Trade {
+            id: "trade-1".to_string(),
+            wallet_address: WALLET.to_string(),
+            token_mint: SOL.to_string(),
+            token_symbol: "SOL".to_string(),
+            side: "sell".to_string(),
+            amount: 4.0,
+            price: 160.0,
+            total_value: 640.0,
+            fee: 0.5,
+            tx_signature: "5sig".to_string(),
+            timestamp: at(1_717_300_000),
+            pnl: Some(160.0),
+            hold_duration_seconds: Some(86_400),
+            strategy_source: "manual".to_string(),
+        }
+    }
+
+    fn sample_row(section: ExportSection) -> Vec<Value> {
+        match section {
+            ExportSection::Positions => {
+                let lots = [
+                    sample_lot("lot-1", 6.0, 660.0),
+                    sample_lot("lot-2", 4.0, 520.0),
+                ];
+                let open: Vec<&TaxLot> = lots.iter().collect();
+                position_row(&sample_position(), &open, &LotStrategy::HIFO)
+            }
+            ExportSection::TaxLots => {
+                let mut lot = sample_lot("lot-3", 2.0, 200.0);
+                lot.disposed_amount = Some(2.0);
+                lot.disposed_at = Some("2024-06-01T12:00:00.250+00:00".to_string());
+                lot.realized_gain = Some(120.0);
+                lot_row(&lot)
+            }
+            ExportSection::OpenOrders => order_row(&sample_order()),
+            ExportSection::DefiPositions => defi_row(WALLET, &sample_defi(PositionType::Lending)),
+            ExportSection::Staking => staking_row(WALLET, &sample_defi(PositionType::Staking)),
+            ExportSection::Trades => trade_row(&sample_trade()),
+            ExportSection::RealizedPnl => {
+                let mut summary = RealizedSummary::default();
+                summary.add(&sample_trade());
+                let mut losing = sample_trade();
+                losing.pnl = Some(-40.0);
+                summary.add(&losing);
+                let mut opening = sample_trade();
+                opening.side = "buy".to_string();
+                opening.pnl = None;
+                summary.add(&opening);
+                realized_row(WALLET, SOL, &summary, at(1_704_067_200), at(1_719_792_000))
+            }
+        }
+    }
+
+    #[test]
+    fn csv_sections_match_golden_files() {
+        for schema in &SECTION_SCHEMAS {
+            let row = sample_row(schema.section);
+            assert_eq!(row.len(), schema.columns.len(), "{:?}", schema.section);
+            let rendered = format!("{}{}", csv_header(schema), csv_line(&row));
+            assert_eq!(
+                rendered,
+                fixture(schema.section),
+                "{} drifted; bump the schema version and update the fixture if intended",
+                schema.file
+            );
+        }
+    }
+
+    #[test]
+    fn position_cost_basis_falls_back_to_average_entry_without_lots() {
+        let row = position_row(&sample_position(), &[], &LotStrategy::FIFO);
+        assert_eq!(row[6], Value::from(0usize));
+        assert_eq!(row[7], Value::from(1200.0));
+        assert_eq!(row[8], text("average_entry"));
+        assert_eq!(row[9], Value::from(300.0));
+    }
+
+    #[test]
+    fn csv_cells_are_quoted_when_needed() {
+        let row = vec![
+            text("WIF, the dog"),
+            text("say \"gm\""),
+            Value::Null,
+            Value::from(1.5),
+        ];
+        assert_eq!(csv_line(&row), "\"WIF, the dog\",\"say \"\"gm\"\"\",,1.5\n");
+    }
+
+    fn write_sections<S: ExportSink>(sink: &mut S) -> Vec<SectionManifest> {
+        let trade = sample_trade();
+        let mut manifests = Vec::new();
+        sink.begin_section(ExportSection::Trades.schema()).unwrap();
+        sink.write_row(&trade_row(&trade)).unwrap();
+        sink.write_row(&trade_row(&trade)).unwrap();
+        manifests.push(sink.end_section().unwrap());
+        sink.begin_section(ExportSection::Staking.schema()).unwrap();
+        manifests.push(sink.end_section().unwrap());
+        manifests
+    }
+
+    fn manifest(
+        format: PortfolioExportFormat,
+        sections: Vec<SectionManifest>,
+    ) -> PortfolioExportManifest {
+        PortfolioExportManifest {
+            schema_version: PORTFOLIO_EXPORT_SCHEMA_VERSION,
+            format,
+            generated_at: at(1_719_792_000),
+            wallets: vec![WALLET.to_string()],
+            from: DateTime::UNIX_EPOCH,
+            to: at(1_719_792_000),
+            sections,
+            warnings: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn json_export_holds_sections_and_manifest_checksums() {
+        let mut sink = JsonSink::new(Vec::new(), at(1_719_792_000)).unwrap();
+        let sections = write_sections(&mut sink);
+        let out = sink
+            .finish(&manifest(PortfolioExportFormat::Json, sections))
+            .unwrap();
+
+        let document: Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(document["schema_version"], Value::from(1));
+        assert_eq!(document["schema"].as_array().unwrap().len(), 7);
+        let trades = document["sections"]["trades"].as_array().unwrap();
+        assert_eq!(trades.len(), 2);
+        assert_eq!(trades[0]["trade_id"], text("trade-1"));
+        assert_eq!(trades[0]["executed_at"], text("2024-06-02T03:46:40Z"));
+        assert_eq!(document["sections"]["staking"], Value::Array(Vec::new()));
+
+        let entries = document["manifest"]["sections"].as_array().unwrap();
+        assert_eq!(entries[0]["rows"], Value::from(2));
+        let raw = String::from_utf8(out).unwrap();
+        let start = raw.find("\"trades\":").unwrap() + "\"trades\":".len();
+        let end = raw.find(",\"staking\":").unwrap();
+        assert_eq!(
+            entries[0]["sha256"],
+            text(hex::encode(Sha256::digest(&raw[start..end])))
+        );
+        assert_eq!(
+            entries[1]["sha256"],
+            text(hex::encode(Sha256::digest(b"[]")))
+        );
+    }
+
+    #[test]
+    fn csv_export_zips_sections_with_manifest() {
+        let mut sink = CsvZipSink::new(Cursor::new(Vec::new()));
+        let sections = write_sections(&mut sink);
+        let out = sink
+            .finish(&manifest(PortfolioExportFormat::Csv, sections))
+            .unwrap();
+
+        let mut archive = zip::ZipArchive::new(out).unwrap();
+        let mut stored = String::new();
+        archive
+            .by_name("manifest.json")
+            .unwrap()
+            .read_to_string(&mut stored)
+            .unwrap();
+        let stored: PortfolioExportManifest = serde_json::from_str(&stored).unwrap();
+        assert_eq!(stored.sections[0].file.as_deref(), Some("trades.csv"));
+        assert_eq!(stored.sections[0].rows, 2);
+        assert_eq!(stored.sections[1].rows, 0);
+
+        let mut trades = String::new();
+        archive
+            .by_name("trades.csv")
+            .unwrap()
+            .read_to_string(&mut trades)
+            .unwrap();
+        assert!(trades.starts_with("#schema_version=1\ntrade_id,"));
+        assert_eq!(trades.lines().count(), 4);
+        assert_eq!(
+            stored.sections[0].sha256,
+            hex::encode(Sha256::digest(trades.as_bytes()))
+        );
+        assert!(archive.by_name("schema.json").is_ok());
+    }
+}
@@ -2,8 +2,10 @@ use std::collections::HashMap;
 use std::sync::Mutex;
 
 use chrono::{DateTime, Datelike, Duration, Utc};
-use serde::Deserialize;
-use tauri::State;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, State};
+
+use crate::config::privacy_mode::warn_unmasked_export;
 
 use super::types::{LotStrategy, TaxLossHarvestingSuggestion, TaxLot, TaxReport};
 
@@ -132,7 +134,7 @@ impl TaxLotsState {
     pub fn open_lots(&self) -> Vec<TaxLot> {
         self.lots
             .iter()
-            .filter(|l| l.disposed_at.is_none())
+            .filter(|l| l.disposed_at.is_none() && l.amount > 0.0)
             .cloned()
             .collect()
     }
@@ -141,10 +143,120 @@ impl TaxLotsState {
         self.lots.clone()
     }
 
+    pub fn strategy(&self) -> LotStrategy {
+        self.strategy.clone()
+    }
+
     pub fn add_lot(&mut self, lot: TaxLot) {
         self.lots.push(lot);
     }
 
+    /// Drops every lot whose id starts with `prefix`, returning how many went.
+    pub fn remove_lots_with_prefix(&mut self, prefix: &str) -> usize {
+        let before = self.lots.len();
+        self.lots.retain(|lot| !lot.id.starts_with(prefix));
+        before - self.lots.len()
+    }
+
+    /// Sells `amount` of `symbol` out of the lots open at `sold_at`, picked
+    /// by the current strategy. Each lot drawn from shrinks and the sold part
+    /// becomes a disposed lot with id `{id_prefix}{lot id}`; quantity no lot
+    /// covers is disposed with a zero cost basis under `{id_prefix}unmatched`.
+    pub fn record_sale(
+        &mut self,
+        symbol: &str,
+        amount: f64,
+        proceeds_per_unit: f64,
+        sold_at: DateTime<Utc>,
+        id_prefix: &str,
+    ) -> Vec<LotDraw> {
+        let mut candidates: Vec<usize> = self
+            .lots
+            .iter()
+            .enumerate()
+            .filter(|(_, lot)| {
+                lot.disposed_at.is_none()
+                    && lot.amount > 0.0
+                    && lot.symbol.eq_ignore_ascii_case(symbol)
+                    && parse_datetime(&lot.acquired_at)
+                        .map(|acquired| acquired <= sold_at)
+                        .unwrap_or(true)
+            })
+            .map(|(index, _)| index)
+            .collect();
+        let acquired = |index: &usize| parse_datetime(&self.lots[*index].acquired_at).ok();
+        match self.strategy {
+            LotStrategy::LIFO => candidates.sort_by_key(|i| std::cmp::Reverse(acquired(i))),
+            LotStrategy::HIFO => candidates.sort_by(|a, b| {
+                self.lots[*b]
+                    .price_per_unit
+                    .partial_cmp(&self.lots[*a].price_per_unit)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            }),
+            LotStrategy::FIFO | LotStrategy::SPECIFIC => candidates.sort_by_key(acquired),
+        }
+
+        let mut remaining = amount;
+        let mut draws = Vec::new();
+        let mut disposed = Vec::new();
+        for index in candidates {
+            if remaining <= 0.0 {
+                break;
+            }
+            let lot = &mut self.lots[index];
+            let take = remaining.min(lot.amount);
+            let cost = lot.cost_basis / lot.amount * take;
+            lot.amount -= take;
+            lot.cost_basis -= cost;
+            remaining -= take;
+
+            draws.push(LotDraw {
+                lot_id: lot.id.clone(),
+                amount: take,
+                cost_basis: cost,
+            });
+            disposed.push(TaxLot {
+                id: format!("{}{}", id_prefix, lot.id),
+                symbol: lot.symbol.clone(),
+                mint: lot.mint.clone(),
+                amount: take,
+                cost_basis: cost,
+                price_per_unit: lot.price_per_unit,
+                acquired_at: lot.acquired_at.clone(),
+                disposed_amount: Some(take),
+                disposed_at: Some(sold_at.to_rfc3339()),
+                realized_gain: Some(take * proceeds_per_unit - cost),
+            });
+        }
+
+        if remaining > 0.0 {
+            disposed.push(TaxLot {
+                id: format!("{}unmatched", id_prefix),
+                symbol: symbol.to_string(),
+                mint: String::new(),
+                amount: remaining,
+                cost_basis: 0.0,
+                price_per_unit: 0.0,
+                acquired_at: sold_at.to_rfc3339(),
+                disposed_amount: Some(remaining),
+                disposed_at: Some(sold_at.to_rfc3339()),
+                realized_gain: Some(remaining * proceeds_per_unit),
+            });
+        }
+
+        self.lots.extend(disposed);
+        draws
+    }
+
+    /// Gives back what a [`LotDraw`] took. Draws from lots that no longer
+    /// exist are ignored.
+    pub fn restore_lot(&mut self, draw: &LotDraw) {
+        if let Some(lot) = self.lots.iter_mut().find(|l| l.id == draw.lot_id) {
+            lot.amount += draw.amount;
+            lot.cost_basis += draw.cost_basis;
+        }
+    }
+
     fn set_strategy(&mut self, strategy: LotStrategy) {
         self.strategy = strategy;
     }
@@ -295,6 +407,16 @@ impl TaxLotsState {
 
 pub type SharedTaxLotsState = Mutex<TaxLotsState>;
 
+/// Quantity and cost taken from an existing lot by [`TaxLotsState::record_sale`],
+/// kept so the sale can be undone with [`TaxLotsState::restore_lot`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LotDraw {
+    pub lot_id: String,
+    pub amount: f64,
+    pub cost_basis: f64,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct DisposeLotInput {
     #[serde(rename = "lotId")]
@@ -388,10 +510,12 @@ pub fn generate_tax_report(
 
 #[tauri::command]
 pub fn export_tax_report(
+    app: AppHandle,
     params: TaxReportParams,
     format: String,
     state: State<'_, SharedTaxLotsState>,
 ) -> Result<String, String> {
+    warn_unmasked_export(&app, "tax_report");
     state
         .lock()
         .map_err(|_| "Tax lots unavailable".to_string())
@@ -629,6 +753,54 @@ mod tests {
         assert_eq!(report.long_term_gains, 300.0);
     }
 
+    #[test]
+    fn record_sale_splits_lots_and_restores() {
+        let mut state = TaxLotsState::default();
+        let sold_at = Utc::now();
+        let before: f64 = state
+            .open_lots()
+            .iter()
+            .filter(|l| l.symbol == "SOL")
+            .map(|l| l.amount)
+            .sum();
+
+        let draws = state.record_sale("sol", 200.0, 200.0, sold_at, "sale:");
+        // FIFO: all of lot-sol-1 (150) then 50 of lot-sol-2.
+        assert_eq!(draws.len(), 2);
+        assert_eq!(draws[0].lot_id, "lot-sol-1");
+        assert_eq!(draws[1].amount, 50.0);
+        assert!((draws[1].cost_basis - 7500.0).abs() < 1e-6);
+        assert!(state.open_lots().iter().all(|l| l.id != "lot-sol-1"));
+
+        let sold = state
+            .all_lots()
+            .into_iter()
+            .find(|l| l.id == "sale:lot-sol-2")
+            .unwrap();
+        assert!((sold.realized_gain.unwrap() - 2500.0).abs() < 1e-6);
+
+        state.remove_lots_with_prefix("sale:");
+        for draw in &draws {
+            state.restore_lot(draw);
+        }
+        let after: f64 = state
+            .open_lots()
+            .iter()
+            .filter(|l| l.symbol == "SOL")
+            .map(|l| l.amount)
+            .sum();
+        assert!((after - before).abs() < 1e-9);
+
+        let uncovered = state.record_sale("NONE", 5.0, 2.0, sold_at, "x:");
+        assert!(uncovered.is_empty());
+        let unmatched = state
+            .all_lots()
+            .into_iter()
+            .find(|l| l.id == "x:unmatched")
+            .unwrap();
+        assert_eq!(unmatched.realized_gain, Some(10.0));
+    }
+
     #[test]
     fn tax_loss_harvesting_detects_losses() {
         let state = TaxLotsState::default();
@@ -0,0 +1,568 @@
+use crate::config::DataPaths;
+use chrono::Utc;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager, State};
+use uuid::Uuid;
+
+const TOKEN_ANNOTATIONS_FILE: &str = "token_annotations.json";
+pub const TOKEN_ANNOTATIONS_EVENT: &str = "token_annotations_changed";
+
+const MAX_NOTE_CHARS: usize = 4_000;
+const MAX_TAG_CHARS: usize = 32;
+const MAX_TAGS_PER_TOKEN: usize = 20;
+/// Length of the latest-note excerpt attached to watchlists, journal entries,
+/// safety reports and alert payloads.
+const SUMMARY_NOTE_CHARS: usize = 140;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenNote {
+    pub id: String,
+    pub mint: String,
+    pub text: String,
+    pub created_at: String,
+}
+
+/// Everything recorded against one mint.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenAnnotation {
+    pub mint: String,
+    /// Oldest first.
+    pub notes: Vec<TokenNote>,
+    pub tags: Vec<String>,
+    pub updated_at: String,
+}
+
+/// Compact view of a token's annotations for embedding in other payloads.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenAnnotationSummary {
+    pub mint: String,
+    pub latest_note: Option<String>,
+    pub latest_note_at: Option<String>,
+    pub note_count: usize,
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenAnnotationsChanged {
+    pub mints: Vec<String>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum TokenAnnotationError {
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+    #[error("invalid annotation: {0}")]
+    Invalid(String),
+}
+
+impl TokenAnnotation {
+    fn new(mint: &str) -> Self {
+        Self {
+            mint: mint.to_string(),
+            notes: Vec::new(),
+            tags: Vec::new(),
+            updated_at: Utc::now().to_rfc3339(),
+        }
+    }
+
+    pub fn summary(&self) -> TokenAnnotationSummary {
+        let latest = self.notes.last();
+        TokenAnnotationSummary {
+            mint: self.mint.clone(),
+            latest_note: latest.map(|note| excerpt(&note.text)),
+            latest_note_at: latest.map(|note| note.created_at.clone()),
+            note_count: self.notes.len(),
+            tags: self.tags.clone(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.notes.is_empty() && self.tags.is_empty()
+    }
+}
+
+/// Notes and tags per mint, persisted as a single JSON document. Every
+/// mutation builds the next state, writes it to disk and only then swaps it
+/// in, so a failed write leaves both the file and memory untouched and a tag
+/// rename is never visible half-applied.
+pub struct TokenAnnotationStore {
+    path: Option<PathBuf>,
+    annotations: BTreeMap<String, TokenAnnotation>,
+}
+
+pub type SharedTokenAnnotationStore = Arc<RwLock<TokenAnnotationStore>>;
+
+impl TokenAnnotationStore {
+    pub fn new(app: &AppHandle) -> Result<Self, TokenAnnotationError> {
+        let mut path = app.app_data_root().map_err(|e| {
+            TokenAnnotationError::Io(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("App data directory not found: {}", e),
+            ))
+        })?;
+        fs::create_dir_all(&path)?;
+        path.push(TOKEN_ANNOTATIONS_FILE);
+        Self::load(path)
+    }
+
+    pub fn load(path: PathBuf) -> Result<Self, TokenAnnotationError> {
+        let annotations = if path.exists() {
+            let records: Vec<TokenAnnotation> = serde_json::from_str(&fs::read_to_string(&path)?)?;
+            records
+                .into_iter()
+                .map(|record| (record.mint.clone(), record))
+                .collect()
+        } else {
+            BTreeMap::new()
+        };
+
+        Ok(Self {
+            path: Some(path),
+            annotations,
+        })
+    }
+
+    /// A store that is never written to disk.
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            annotations: BTreeMap::new(),
+        }
+    }
+
+    fn commit(
+        &mut self,
+        mut next: BTreeMap<String, TokenAnnotation>,
+    ) -> Result<(), TokenAnnotationError> {
+        next.retain(|_, annotation| !annotation.is_empty());
+        if let Some(path) = &self.path {
+            let records: Vec<&TokenAnnotation> = next.values().collect();
+            let tmp = path.with_extension("json.tmp");
+            fs::write(&tmp, serde_json::to_vec_pretty(&records)?)?;
+            fs::rename(&tmp, path)?;
+        }
+        self.annotations = next;
+        Ok(())
+    }
+
+    pub fn add_note(&mut self, mint: &str, text: &str) -> Result<TokenNote, TokenAnnotationError> {
+        let mint = normalize_mint(mint)?;
+        let text = text.trim();
+        if text.is_empty() {
+            return Err(TokenAnnotationError::Invalid("note text is empty".into()));
+        }
+        if text.chars().count() > MAX_NOTE_CHARS {
+            return Err(TokenAnnotationError::Invalid(format!(
+                "note is longer than {} characters",
+                MAX_NOTE_CHARS
+            )));
+        }
+
+        let now = Utc::now().to_rfc3339();
+        let note = TokenNote {
+            id: Uuid::new_v4().to_string(),
+            mint: mint.clone(),
+            text: text.to_string(),
+            created_at: now.clone(),
+        };
+
+        let mut next = self.annotations.clone();
+        let annotation = next
+            .entry(mint.clone())
+            .or_insert_with(|| TokenAnnotation::new(&mint));
+        annotation.notes.push(note.clone());
+        annotation.updated_at = now;
+        self.commit(next)?;
+        Ok(note)
+    }
+
+    /// Notes for `mint`, newest first.
+    pub fn list_notes(&self, mint: &str) -> Vec<TokenNote> {
+        self.annotations
+            .get(mint.trim())
+            .map(|annotation| annotation.notes.iter().rev().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Replaces the tags of `mint`. Tags are trimmed and de-duplicated
+    /// case-insensitively, keeping the first spelling.
+    pub fn set_tags(
+        &mut self,
+        mint: &str,
+        tags: Vec<String>,
+    ) -> Result<Vec<String>, TokenAnnotationError> {
+        let mint = normalize_mint(mint)?;
+        let tags = normalize_tags(tags)?;
+
+        let mut next = self.annotations.clone();
+        let annotation = next
+            .entry(mint.clone())
+            .or_insert_with(|| TokenAnnotation::new(&mint));
+        annotation.tags = tags.clone();
+        annotation.updated_at = Utc::now().to_rfc3339();
+        self.commit(next)?;
+        Ok(tags)
+    }
+
+    /// Tokens carrying `tag`, matched case-insensitively.
+    pub fn tokens_by_tag(&self, tag: &str) -> Vec<TokenAnnotationSummary> {
+        let wanted = tag.trim().to_lowercase();
+        self.annotations
+            .values()
+            .filter(|annotation| annotation.tags.iter().any(|t| t.to_lowercase() == wanted))
+            .map(TokenAnnotation::summary)
+            .collect()
+    }
+
+    /// Renames `from` to `to` on every token in one write. Tokens that
+    /// already carry `to` end up with a single copy. Returns the mints that
+    /// changed.
+    pub fn rename_tag(
+        &mut self,
+        from: &str,
+        to: &str,
+    ) -> Result<Vec<String>, TokenAnnotationError> {
+        let from = from.trim().to_lowercase();
+        let to = normalize_tag(to)?;
+        let now = Utc::now().to_rfc3339();
+
+        let mut next = self.annotations.clone();
+        let mut changed = Vec::new();
+        for annotation in next.values_mut() {
+            if !annotation.tags.iter().any(|tag| tag.to_lowercase() == from) {
+                continue;
+            }
+            let renamed = annotation
+                .tags
+                .iter()
+                .map(|tag| {
+                    if tag.to_lowercase() == from {
+                        to.clone()
+                    } else {
+                        tag.clone()
+                    }
+                })
+                .collect();
+            annotation.tags = normalize_tags(renamed)?;
+            annotation.updated_at = now.clone();
+            changed.push(annotation.mint.clone());
+        }
+
+        if !changed.is_empty() {
+            self.commit(next)?;
+        }
+        Ok(changed)
+    }
+
+    pub fn summary(&self, mint: &str) -> Option<TokenAnnotationSummary> {
+        self.annotations
+            .get(mint.trim())
+            .map(TokenAnnotation::summary)
+    }
+
+    /// Summaries for every annotated mint in `mints`.
+    pub fn summaries<'a>(
+        &self,
+        mints: impl IntoIterator<Item = &'a str>,
+    ) -> HashMap<String, TokenAnnotationSummary> {
+        mints
+            .into_iter()
+            .filter_map(|mint| Some((mint.to_string(), self.summary(mint)?)))
+            .collect()
+    }
+
+    pub fn all(&self) -> Vec<TokenAnnotation> {
+        self.annotations.values().cloned().collect()
+    }
+
+    /// Restores annotations from a backup. Without `merge` the store is
+    /// replaced; with it, notes are combined by id and tags are unioned.
+    pub fn import(
+        &mut self,
+        records: Vec<TokenAnnotation>,
+        merge: bool,
+    ) -> Result<usize, TokenAnnotationError> {
+        let mut next = if merge {
+            self.annotations.clone()
+        } else {
+            BTreeMap::new()
+        };
+
+        let count = records.len();
+        for record in records {
+            let mint = normalize_mint(&record.mint)?;
+            match next.get_mut(&mint) {
+                Some(existing) => {
+                    for note in record.notes {
+                        if !existing.notes.iter().any(|n| n.id == note.id) {
+                            existing.notes.push(note);
+                        }
+                    }
+                    existing
+                        .notes
+                        .sort_by(|a, b| a.created_at.cmp(&b.created_at));
+                    let mut tags = existing.tags.clone();
+                    tags.extend(record.tags);
+                    existing.tags = normalize_tags(tags)?;
+                    existing.updated_at = existing.updated_at.clone().max(record.updated_at);
+                }
+                None => {
+                    let tags = normalize_tags(record.tags.clone())?;
+                    next.insert(
+                        mint.clone(),
+                        TokenAnnotation {
+                            mint,
+                            tags,
+                            ..record
+                        },
+                    );
+                }
+            }
+        }
+
+        self.commit(next)?;
+        Ok(count)
+    }
+}
+
+fn normalize_mint(mint: &str) -> Result<String, TokenAnnotationError> {
+    let mint = mint.trim();
+    if mint.is_empty() {
+        return Err(TokenAnnotationError::Invalid("mint is empty".into()));
+    }
+    Ok(mint.to_string())
+}
+
+fn normalize_tag(tag: &str) -> Result<String, TokenAnnotationError> {
+    let tag = tag.trim();
+    if tag.is_empty() {
+        return Err(TokenAnnotationError::Invalid("tag is empty".into()));
+    }
+    if tag.chars().count() > MAX_TAG_CHARS {
+        return Err(TokenAnnotationError::Invalid(format!(
+            "tag '{}' is longer than {} characters",
+            tag, MAX_TAG_CHARS
+        )));
+    }
+    Ok(tag.to_string())
+}
+
+fn normalize_tags(tags: Vec<String>) -> Result<Vec<String>, TokenAnnotationError> {
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = normalize_tag(&tag)?;
+        if !normalized
+            .iter()
+            .any(|existing| existing.to_lowercase() == tag.to_lowercase())
+        {
+            normalized.push(tag);
+        }
+    }
+    if normalized.len() > MAX_TAGS_PER_TOKEN {
+        return Err(TokenAnnotationError::Invalid(format!(
+            "at most {} tags per token",
+            MAX_TAGS_PER_TOKEN
+        )));
+    }
+    Ok(normalized)
+}
+
+fn excerpt(text: &str) -> String {
+    if text.chars().count() <= SUMMARY_NOTE_CHARS {
+        return text.to_string();
+    }
+    let cut: String = text.chars().take(SUMMARY_NOTE_CHARS - 1).collect();
+    format!("{}…", cut.trim_end())
+}
+
+/// Annotation summary for `mint`, if the store is managed and has one.
+pub fn token_annotation_summary(app: &AppHandle, mint: &str) -> Option<TokenAnnotationSummary> {
+    app.try_state::<SharedTokenAnnotationStore>()?
+        .read()
+        .summary(mint)
+}
+
+fn notify_changed(app: &AppHandle, mints: Vec<String>) {
+    if mints.is_empty() {
+        return;
+    }
+    if let Err(e) = app.emit(TOKEN_ANNOTATIONS_EVENT, TokenAnnotationsChanged { mints }) {
+        eprintln!("Failed to emit token annotation change: {}", e);
+    }
+}
+
+// Tauri commands
+#[tauri::command]
+pub async fn token_notes_add(
+    app: AppHandle,
+    store: State<'_, SharedTokenAnnotationStore>,
+    mint: String,
+    text: String,
+) -> Result<TokenNote, String> {
+    let note = store
+        .write()
+        .add_note(&mint, &text)
+        .map_err(|e| e.to_string())?;
+    notify_changed(&app, vec![note.mint.clone()]);
+    Ok(note)
+}
+
+#[tauri::command]
+pub async fn token_notes_list(
+    store: State<'_, SharedTokenAnnotationStore>,
+    mint: String,
+) -> Result<Vec<TokenNote>, String> {
+    Ok(store.read().list_notes(&mint))
+}
+
+#[tauri::command]
+pub async fn token_tags_set(
+    app: AppHandle,
+    store: State<'_, SharedTokenAnnotationStore>,
+    mint: String,
+    tags: Vec<String>,
+) -> Result<Vec<String>, String> {
+    let tags = store
+        .write()
+        .set_tags(&mint, tags)
+        .map_err(|e| e.to_string())?;
+    notify_changed(&app, vec![mint.trim().to_string()]);
+    Ok(tags)
+}
+
+#[tauri::command]
+pub async fn tokens_by_tag(
+    store: State<'_, SharedTokenAnnotationStore>,
+    tag: String,
+) -> Result<Vec<TokenAnnotationSummary>, String> {
+    Ok(store.read().tokens_by_tag(&tag))
+}
+
+#[tauri::command]
+pub async fn token_tags_rename(
+    app: AppHandle,
+    store: State<'_, SharedTokenAnnotationStore>,
+    from: String,
+    to: String,
+) -> Result<Vec<String>, String> {
+    let changed = store
+        .write()
+        .rename_tag(&from, &to)
+        .map_err(|e| e.to_string())?;
+    notify_changed(&app, changed.clone());
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BONK: &str = "DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263";
+    const JUP: &str = "JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN";
+
+    #[test]
+    fn test_notes_are_listed_newest_first_and_summarized() {
+        let mut store = TokenAnnotationStore::in_memory();
+        store.add_note(BONK, "team doxxed 2024-03").unwrap();
+        store
+            .add_note(BONK, &"unlock cliff in June ".repeat(20))
+            .unwrap();
+
+        let notes = store.list_notes(BONK);
+        assert_eq!(notes.len(), 2);
+        assert!(notes[0].text.starts_with("unlock cliff"));
+        assert_eq!(notes[1].text, "team doxxed 2024-03");
+
+        let summary = store.summary(BONK).unwrap();
+        assert_eq!(summary.note_count, 2);
+        let latest = summary.latest_note.unwrap();
+        assert!(latest.ends_with('…'));
+        assert!(latest.chars().count() <= SUMMARY_NOTE_CHARS);
+
+        assert!(store.add_note(BONK, "   ").is_err());
+        assert!(store.add_note(" ", "note").is_err());
+    }
+
+    #[test]
+    fn test_tags_are_normalized_and_queryable() {
+        let mut store = TokenAnnotationStore::in_memory();
+        let tags = store
+            .set_tags(
+                BONK,
+                vec![" Meme ".into(), "meme".into(), "unlock-risk".into()],
+            )
+            .unwrap();
+        assert_eq!(tags, vec!["Meme", "unlock-risk"]);
+        store.set_tags(JUP, vec!["defi".into()]).unwrap();
+
+        let memes = store.tokens_by_tag("MEME");
+        assert_eq!(memes.len(), 1);
+        assert_eq!(memes[0].mint, BONK);
+
+        // Clearing the only data for a token drops it entirely.
+        store.set_tags(JUP, Vec::new()).unwrap();
+        assert!(store.summary(JUP).is_none());
+    }
+
+    #[test]
+    fn test_rename_tag_applies_to_every_token_and_merges_duplicates() {
+        let mut store = TokenAnnotationStore::in_memory();
+        store
+            .set_tags(BONK, vec!["watch".into(), "meme".into()])
+            .unwrap();
+        store
+            .set_tags(JUP, vec!["watch".into(), "Watching".into()])
+            .unwrap();
+
+        let changed = store.rename_tag("WATCH", "watching").unwrap();
+        assert_eq!(changed.len(), 2);
+        assert_eq!(store.summary(BONK).unwrap().tags, vec!["watching", "meme"]);
+        assert_eq!(store.summary(JUP).unwrap().tags, vec!["watching"]);
+        assert!(store.tokens_by_tag("watch").is_empty());
+
+        assert!(store.rename_tag("meme", "  ").is_err());
+        assert_eq!(store.summary(BONK).unwrap().tags, vec!["watching", "meme"]);
+    }
+
+    #[test]
+    fn test_persisted_store_round_trips_and_imports() {
+        let dir = std::env::temp_dir().join(format!("token-annotations-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(TOKEN_ANNOTATIONS_FILE);
+
+        let mut store = TokenAnnotationStore::load(path.clone()).unwrap();
+        store.add_note(BONK, "team doxxed").unwrap();
+        store.set_tags(BONK, vec!["meme".into()]).unwrap();
+        let backup = store.all();
+
+        let reloaded = TokenAnnotationStore::load(path.clone()).unwrap();
+        assert_eq!(reloaded.all(), backup);
+
+        let mut other = TokenAnnotationStore::in_memory();
+        other.set_tags(BONK, vec!["cliff".into()]).unwrap();
+        other.import(backup.clone(), true).unwrap();
+        let merged = other.summary(BONK).unwrap();
+        assert_eq!(merged.tags, vec!["cliff", "meme"]);
+        assert_eq!(merged.note_count, 1);
+
+        // Importing the same backup again does not duplicate notes.
+        other.import(backup.clone(), true).unwrap();
+        assert_eq!(other.summary(BONK).unwrap().note_count, 1);
+
+        other.import(backup, false).unwrap();
+        assert_eq!(other.summary(BONK).unwrap().tags, vec!["meme"]);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
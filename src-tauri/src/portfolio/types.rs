@@ -1,3 +1,4 @@
+use crate::config::privacy_mode::PrivacyMasked;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -46,6 +47,22 @@ pub struct PortfolioMetrics {
     pub last_updated: String,
 }
 
+impl PrivacyMasked for Position {
+    const MASKED_FIELDS: &'static [&'static str] = &["amount", "totalValue", "unrealizedPnl"];
+}
+
+impl PrivacyMasked for PortfolioMetrics {
+    const MASKED_FIELDS: &'static [&'static str] = &[
+        "totalValue",
+        "dailyPnl",
+        "weeklyPnl",
+        "monthlyPnl",
+        "allTimePnl",
+        "realizedPnl",
+        "unrealizedPnl",
+    ];
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AllocationTarget {
     pub symbol: String,
@@ -0,0 +1,984 @@
+//! Two-phase watchlist import from external formats. A preview parses the
+//! source, resolves tickers to mints and reports everything it could not
+//! place; the commit applies the user's choices for those rows.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use super::watchlists::{SharedWatchlistManager, Watchlist};
+use crate::market::{search_token_assets, AssetSearchResult, MatchKind, SharedTopCoinsCache};
+
+const PREVIEW_TTL_MINUTES: i64 = 30;
+const MAX_CANDIDATES: usize = 5;
+const DEFAULT_IMPORT_NAME: &str = "Imported Watchlist";
+/// Quote currencies stripped from TradingView pair tickers, longest first.
+const QUOTE_SUFFIXES: [&str; 3] = ["USDT", "USDC", "USD"];
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum CsvColumn {
+    Index(usize),
+    Name(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CsvColumnMapping {
+    pub symbol: Option<CsvColumn>,
+    pub mint: Option<CsvColumn>,
+    pub label: Option<CsvColumn>,
+    #[serde(default = "default_has_header")]
+    pub has_header: bool,
+    pub delimiter: Option<char>,
+}
+
+fn default_has_header() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum WatchlistImportFormat {
+    /// Our own `watchlist_export` JSON.
+    Native,
+    /// TradingView watchlist text: `EXCHANGE:SYMBOL` entries separated by
+    /// commas or newlines, with optional `###SECTION` headers.
+    TradingView,
+    Csv {
+        mapping: CsvColumnMapping,
+    },
+    /// JSON list of `{ address, label }` objects (e.g. exported Solscan
+    /// labels) or an object mapping address to label.
+    AddressLabels,
+}
+
+impl WatchlistImportFormat {
+    fn as_str(&self) -> &'static str {
+        match self {
+            WatchlistImportFormat::Native => "native",
+            WatchlistImportFormat::TradingView => "tradingView",
+            WatchlistImportFormat::Csv { .. } => "csv",
+            WatchlistImportFormat::AddressLabels => "addressLabels",
+        }
+    }
+}
+
+/// What a source row said before any lookup.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum ImportEntry {
+    Address {
+        mint: String,
+        symbol: Option<String>,
+        label: Option<String>,
+    },
+    Symbol {
+        symbol: String,
+        label: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ParsedRow {
+    /// 1-based line or entry number in the source.
+    pub row: usize,
+    pub raw: String,
+    pub entry: Result<ImportEntry, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportedItem {
+    pub row: usize,
+    pub raw: String,
+    pub symbol: String,
+    pub mint: String,
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportCandidate {
+    pub symbol: Option<String>,
+    pub name: String,
+    pub mint: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportAmbiguity {
+    pub row: usize,
+    pub raw: String,
+    pub query: String,
+    pub candidates: Vec<ImportCandidate>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum DuplicateReason {
+    AlreadyInWatchlist,
+    RepeatedInImport,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportDuplicate {
+    pub row: usize,
+    pub raw: String,
+    pub symbol: String,
+    pub mint: String,
+    pub reason: DuplicateReason,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportRowError {
+    pub row: usize,
+    pub raw: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchlistImportPreview {
+    pub id: String,
+    pub format: String,
+    /// Existing watchlist to add to; a new one is created when absent.
+    pub watchlist_id: Option<String>,
+    pub watchlist_name: String,
+    pub resolved: Vec<ImportedItem>,
+    /// Rows whose ticker matched several tokens, or none exactly.
+    pub ambiguities: Vec<ImportAmbiguity>,
+    /// Skipped on commit unless a resolution maps them elsewhere.
+    pub duplicates: Vec<ImportDuplicate>,
+    /// Rows that could not be parsed or resolved. Each needs a mapping or an
+    /// explicit skip before the import can be committed.
+    pub errors: Vec<ImportRowError>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl WatchlistImportPreview {
+    pub(crate) fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        now >= self.expires_at
+    }
+
+    fn pending_rows(&self) -> Vec<usize> {
+        let mut rows: Vec<usize> = self
+            .ambiguities
+            .iter()
+            .map(|a| a.row)
+            .chain(self.errors.iter().map(|e| e.row))
+            .collect();
+        rows.sort_unstable();
+        rows
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "camelCase")]
+pub enum ImportResolution {
+    Map {
+        row: usize,
+        mint: String,
+        symbol: Option<String>,
+    },
+    Skip {
+        row: usize,
+    },
+}
+
+impl ImportResolution {
+    fn row(&self) -> usize {
+        match self {
+            ImportResolution::Map { row, .. } | ImportResolution::Skip { row } => *row,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchlistImportResult {
+    pub watchlist: Watchlist,
+    pub added: Vec<ImportedItem>,
+    /// Rows skipped by choice or because the mint was already present.
+    pub skipped_rows: Vec<usize>,
+}
+
+pub(crate) fn parse_source(
+    source: &str,
+    format: &WatchlistImportFormat,
+) -> Result<Vec<ParsedRow>, String> {
+    match format {
+        WatchlistImportFormat::Native => parse_native(source),
+        WatchlistImportFormat::TradingView => Ok(parse_tradingview(source)),
+        WatchlistImportFormat::Csv { mapping } => parse_csv(source, mapping),
+        WatchlistImportFormat::AddressLabels => parse_address_labels(source),
+    }
+}
+
+fn parse_native(source: &str) -> Result<Vec<ParsedRow>, String> {
+    let watchlist: Watchlist =
+        serde_json::from_str(source).map_err(|e| format!("Invalid watchlist export: {}", e))?;
+    Ok(watchlist
+        .items
+        .into_iter()
+        .enumerate()
+        .map(|(index, item)| ParsedRow {
+            row: index + 1,
+            raw: format!("{} {}", item.symbol, item.mint),
+            entry: Ok(ImportEntry::Address {
+                mint: item.mint,
+                symbol: Some(item.symbol),
+                label: None,
+            }),
+        })
+        .collect())
+}
+
+fn parse_tradingview(source: &str) -> Vec<ParsedRow> {
+    source
+        .split([',', '\n'])
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty() && !entry.starts_with("###"))
+        .enumerate()
+        .map(|(index, entry)| ParsedRow {
+            row: index + 1,
+            raw: entry.to_string(),
+            entry: tradingview_symbol(entry)
+                .map(|symbol| ImportEntry::Symbol {
+                    symbol,
+                    label: None,
+                })
+                .ok_or_else(|| format!("Could not read a ticker from '{}'", entry)),
+        })
+        .collect()
+}
+
+/// `BINANCE:SOLUSDT` -> `SOL`, `BYBIT:JUPUSDT.P` -> `JUP`.
+pub(crate) fn tradingview_symbol(entry: &str) -> Option<String> {
+    let ticker = entry.rsplit(':').next()?.trim().to_uppercase();
+    let ticker = ticker.strip_suffix(".P").unwrap_or(&ticker);
+    let ticker = ticker.strip_suffix("PERP").unwrap_or(ticker);
+    let base = QUOTE_SUFFIXES
+        .iter()
+        .find_map(|quote| ticker.strip_suffix(quote).filter(|base| !base.is_empty()))
+        .unwrap_or(ticker);
+    let valid = !base.is_empty() && base.chars().all(|c| c.is_ascii_alphanumeric());
+    valid.then(|| base.to_string())
+}
+
+/// Splits one CSV line, honouring double-quoted fields and `""` escapes.
+pub(crate) fn split_csv_line(line: &str, delimiter: char) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            c if c == delimiter && !in_quotes => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields.into_iter().map(|f| f.trim().to_string()).collect()
+}
+
+fn parse_csv(source: &str, mapping: &CsvColumnMapping) -> Result<Vec<ParsedRow>, String> {
+    if mapping.symbol.is_none() && mapping.mint.is_none() {
+        return Err("CSV mapping needs a symbol or mint column".to_string());
+    }
+    let delimiter = mapping.delimiter.unwrap_or(',');
+    let mut lines = source
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty());
+
+    let header = if mapping.has_header {
+        let (_, line) = lines
+            .next()
+            .ok_or_else(|| "CSV source is empty".to_string())?;
+        split_csv_line(line, delimiter)
+    } else {
+        Vec::new()
+    };
+    let column_index = |column: &Option<CsvColumn>| -> Result<Option<usize>, String> {
+        match column {
+            None => Ok(None),
+            Some(CsvColumn::Index(index)) => Ok(Some(*index)),
+            Some(CsvColumn::Name(name)) => header
+                .iter()
+                .position(|h| h.eq_ignore_ascii_case(name))
+                .map(Some)
+                .ok_or_else(|| format!("CSV has no '{}' column", name)),
+        }
+    };
+    let symbol_col = column_index(&mapping.symbol)?;
+    let mint_col = column_index(&mapping.mint)?;
+    let label_col = column_index(&mapping.label)?;
+
+    Ok(lines
+        .map(|(index, line)| {
+            let fields = split_csv_line(line, delimiter);
+            let field = |col: Option<usize>| {
+                col.and_then(|i| fields.get(i))
+                    .filter(|value| !value.is_empty())
+                    .cloned()
+            };
+            let (symbol, mint, label) = (field(symbol_col), field(mint_col), field(label_col));
+            let entry = match (mint, symbol) {
+                (Some(mint), symbol) => Ok(ImportEntry::Address {
+                    mint,
+                    symbol,
+                    label,
+                }),
+                (None, Some(symbol)) => Ok(ImportEntry::Symbol { symbol, label }),
+                (None, None) => Err("Row has neither a symbol nor a mint".to_string()),
+            };
+            ParsedRow {
+                row: index + 1,
+                raw: line.trim().to_string(),
+                entry,
+            }
+        })
+        .collect())
+}
+
+fn parse_address_labels(source: &str) -> Result<Vec<ParsedRow>, String> {
+    let value: serde_json::Value =
+        serde_json::from_str(source).map_err(|e| format!("Invalid JSON: {}", e))?;
+
+    let entries: Vec<(String, serde_json::Value)> = match value {
+        serde_json::Value::Array(items) => items
+            .into_iter()
+            .map(|item| (item.to_string(), item))
+            .collect(),
+        serde_json::Value::Object(map) => map
+            .into_iter()
+            .map(|(address, label)| {
+                let raw = format!("{}: {}", address, label);
+                (
+                    raw,
+                    serde_json::json!({ "address": address, "label": label }),
+                )
+            })
+            .collect(),
+        _ => return Err("Expected a JSON array or object of labelled addresses".to_string()),
+    };
+
+    Ok(entries
+        .into_iter()
+        .enumerate()
+        .map(|(index, (raw, item))| {
+            let text = |keys: &[&str]| {
+                keys.iter()
+                    .find_map(|key| item.get(*key).and_then(|v| v.as_str()))
+                    .map(|v| v.trim().to_string())
+                    .filter(|v| !v.is_empty())
+            };
+            let entry = match text(&["address", "mint", "account"]) {
+                Some(mint) => Ok(ImportEntry::Address {
+                    mint,
+                    symbol: text(&["symbol", "ticker"]),
+                    label: text(&["label", "name", "tag"]),
+                }),
+                None => Err("Entry has no address".to_string()),
+            };
+            ParsedRow {
+                row: index + 1,
+                raw,
+                entry,
+            }
+        })
+        .collect())
+}
+
+pub(crate) fn is_valid_mint(mint: &str) -> bool {
+    bs58::decode(mint)
+        .into_vec()
+        .map(|bytes| bytes.len() == 32)
+        .unwrap_or(false)
+}
+
+/// Display symbol for an address-only row: the label, else a shortened mint.
+fn fallback_symbol(mint: &str, label: Option<&str>) -> String {
+    match label {
+        Some(label) => label.to_string(),
+        None if mint.len() > 8 => format!("{}…{}", &mint[..4], &mint[mint.len() - 4..]),
+        None => mint.to_string(),
+    }
+}
+
+/// Ticker searches the preview needs, deduplicated and uppercased.
+pub(crate) fn symbol_queries(rows: &[ParsedRow]) -> Vec<String> {
+    let mut seen = HashSet::new();
+    rows.iter()
+        .filter_map(|row| match &row.entry {
+            Ok(ImportEntry::Symbol { symbol, .. }) => Some(symbol.to_uppercase()),
+            _ => None,
+        })
+        .filter(|symbol| seen.insert(symbol.clone()))
+        .collect()
+}
+
+fn candidate(result: &AssetSearchResult) -> ImportCandidate {
+    ImportCandidate {
+        symbol: result.symbol.clone(),
+        name: result.name.clone(),
+        mint: result.identifier.clone(),
+    }
+}
+
+/// Tracks mints already placed so later rows become duplicates.
+struct DuplicateTracker {
+    existing: HashSet<String>,
+    seen: HashSet<String>,
+}
+
+impl DuplicateTracker {
+    fn check(&mut self, mint: &str) -> Option<DuplicateReason> {
+        if self.existing.contains(mint) {
+            Some(DuplicateReason::AlreadyInWatchlist)
+        } else if !self.seen.insert(mint.to_string()) {
+            Some(DuplicateReason::RepeatedInImport)
+        } else {
+            None
+        }
+    }
+}
+
+pub(crate) struct PreviewParts {
+    pub resolved: Vec<ImportedItem>,
+    pub ambiguities: Vec<ImportAmbiguity>,
+    pub duplicates: Vec<ImportDuplicate>,
+    pub errors: Vec<ImportRowError>,
+}
+
+/// Sorts parsed rows into resolved items, ambiguities, duplicates and
+/// errors. `lookups` holds the token search outcome per uppercased ticker.
+pub(crate) fn build_preview_parts(
+    rows: Vec<ParsedRow>,
+    lookups: &HashMap<String, Result<Vec<AssetSearchResult>, String>>,
+    existing_mints: HashSet<String>,
+) -> PreviewParts {
+    let mut parts = PreviewParts {
+        resolved: Vec::new(),
+        ambiguities: Vec::new(),
+        duplicates: Vec::new(),
+        errors: Vec::new(),
+    };
+    let mut tracker = DuplicateTracker {
+        existing: existing_mints,
+        seen: HashSet::new(),
+    };
+
+    for ParsedRow { row, raw, entry } in rows {
+        let item = match entry {
+            Err(message) => {
+                parts.errors.push(ImportRowError { row, raw, message });
+                continue;
+            }
+            Ok(ImportEntry::Address {
+                mint,
+                symbol,
+                label,
+            }) => {
+                if !is_valid_mint(&mint) {
+                    parts.errors.push(ImportRowError {
+                        row,
+                        raw,
+                        message: format!("'{}' is not a valid Solana address", mint),
+                    });
+                    continue;
+                }
+                let symbol = symbol.unwrap_or_else(|| fallback_symbol(&mint, label.as_deref()));
+                ImportedItem {
+                    row,
+                    raw,
+                    symbol,
+                    mint,
+                    label,
+                }
+            }
+            Ok(ImportEntry::Symbol { symbol, label }) => {
+                let query = symbol.to_uppercase();
+                let results = match lookups.get(&query) {
+                    Some(Ok(results)) => results,
+                    Some(Err(error)) => {
+                        parts.errors.push(ImportRowError {
+                            row,
+                            raw,
+                            message: format!("Token search failed for {}: {}", query, error),
+                        });
+                        continue;
+                    }
+                    None => {
+                        parts.errors.push(ImportRowError {
+                            row,
+                            raw,
+                            message: format!("{} was not searched", query),
+                        });
+                        continue;
+                    }
+                };
+
+                let exact: Vec<&AssetSearchResult> = results
+                    .iter()
+                    .filter(|r| r.match_kind == MatchKind::ExactSymbol)
+                    .collect();
+                match exact.as_slice() {
+                    [only] => ImportedItem {
+                        row,
+                        raw,
+                        symbol: only.symbol.clone().unwrap_or(query),
+                        mint: only.identifier.clone(),
+                        label,
+                    },
+                    [] if results.is_empty() => {
+                        parts.errors.push(ImportRowError {
+                            row,
+                            raw,
+                            message: format!("No token found for {}", query),
+                        });
+                        continue;
+                    }
+                    [] => {
+                        parts.ambiguities.push(ImportAmbiguity {
+                            row,
+                            raw,
+                            query,
+                            candidates: results
+                                .iter()
+                                .take(MAX_CANDIDATES)
+                                .map(candidate)
+                                .collect(),
+                        });
+                        continue;
+                    }
+                    several => {
+                        parts.ambiguities.push(ImportAmbiguity {
+                            row,
+                            raw,
+                            query,
+                            candidates: several
+                                .iter()
+                                .copied()
+                                .take(MAX_CANDIDATES)
+                                .map(candidate)
+                                .collect(),
+                        });
+                        continue;
+                    }
+                }
+            }
+        };
+
+        match tracker.check(&item.mint) {
+            Some(reason) => parts.duplicates.push(ImportDuplicate {
+                row: item.row,
+                raw: item.raw,
+                symbol: item.symbol,
+                mint: item.mint,
+                reason,
+            }),
+            None => parts.resolved.push(item),
+        }
+    }
+
+    parts
+}
+
+/// Applies `resolutions` to a preview and returns the items to add, in row
+/// order, plus the rows that end up skipped. Every ambiguous or failed row
+/// needs a resolution; mapped rows are checked for validity and duplicates.
+pub(crate) fn apply_resolutions(
+    preview: &WatchlistImportPreview,
+    resolutions: Vec<ImportResolution>,
+    existing_mints: HashSet<String>,
+) -> Result<(Vec<ImportedItem>, Vec<usize>), String> {
+    let known_rows: HashSet<usize> = preview
+        .resolved
+        .iter()
+        .map(|i| i.row)
+        .chain(preview.duplicates.iter().map(|d| d.row))
+        .chain(preview.pending_rows())
+        .collect();
+
+    let mut by_row = HashMap::new();
+    for resolution in resolutions {
+        let row = resolution.row();
+        if !known_rows.contains(&row) {
+            return Err(format!("Row {} is not part of this import", row));
+        }
+        if by_row.insert(row, resolution).is_some() {
+            return Err(format!("Row {} has more than one resolution", row));
+        }
+    }
+
+    let unresolved: Vec<String> = preview
+        .pending_rows()
+        .into_iter()
+        .filter(|row| !by_row.contains_key(row))
+        .map(|row| row.to_string())
+        .collect();
+    if !unresolved.is_empty() {
+        return Err(format!(
+            "Rows {} need a mapping or an explicit skip",
+            unresolved.join(", ")
+        ));
+    }
+
+    let raw_by_row: HashMap<usize, &str> = preview
+        .resolved
+        .iter()
+        .map(|i| (i.row, i.raw.as_str()))
+        .chain(preview.duplicates.iter().map(|d| (d.row, d.raw.as_str())))
+        .chain(preview.ambiguities.iter().map(|a| (a.row, a.raw.as_str())))
+        .chain(preview.errors.iter().map(|e| (e.row, e.raw.as_str())))
+        .collect();
+    let candidate_symbol = |row: usize, mint: &str| {
+        preview
+            .ambiguities
+            .iter()
+            .find(|a| a.row == row)
+            .and_then(|a| a.candidates.iter().find(|c| c.mint == mint))
+            .and_then(|c| c.symbol.clone())
+    };
+
+    let mut items: Vec<ImportedItem> = preview
+        .resolved
+        .iter()
+        .filter(|item| !by_row.contains_key(&item.row))
+        .cloned()
+        .collect();
+    let mut skipped: Vec<usize> = preview
+        .duplicates
+        .iter()
+        .filter(|d| !by_row.contains_key(&d.row))
+        .map(|d| d.row)
+        .collect();
+
+    for (row, resolution) in by_row {
+        match resolution {
+            ImportResolution::Skip { .. } => skipped.push(row),
+            ImportResolution::Map { mint, symbol, .. } => {
+                let mint = mint.trim().to_string();
+                if !is_valid_mint(&mint) {
+                    return Err(format!(
+                        "Row {}: '{}' is not a valid Solana address",
+                        row, mint
+                    ));
+                }
+                let symbol = symbol
+                    .filter(|s| !s.trim().is_empty())
+                    .or_else(|| candidate_symbol(row, &mint))
+                    .unwrap_or_else(|| fallback_symbol(&mint, None));
+                items.push(ImportedItem {
+                    row,
+                    raw: raw_by_row
+                        .get(&row)
+                        .copied()
+                        .unwrap_or_default()
+                        .to_string(),
+                    symbol,
+                    mint,
+                    label: None,
+                });
+            }
+        }
+    }
+
+    items.sort_by_key(|item| item.row);
+    let mut tracker = DuplicateTracker {
+        existing: existing_mints,
+        seen: HashSet::new(),
+    };
+    let mut added = Vec::new();
+    for item in items {
+        if tracker.check(&item.mint).is_some() {
+            skipped.push(item.row);
+        } else {
+            added.push(item);
+        }
+    }
+    skipped.sort_unstable();
+    Ok((added, skipped))
+}
+
+#[tauri::command]
+pub async fn watchlist_import_preview(
+    manager: State<'_, SharedWatchlistManager>,
+    top_coins: State<'_, SharedTopCoinsCache>,
+    source: String,
+    format: WatchlistImportFormat,
+    watchlist_id: Option<String>,
+    name: Option<String>,
+) -> Result<WatchlistImportPreview, String> {
+    let rows = parse_source(&source, &format)?;
+
+    let mut lookups = HashMap::new();
+    for query in symbol_queries(&rows) {
+        let outcome = search_token_assets(&query, MAX_CANDIDATES * 2, &top_coins).await;
+        lookups.insert(query, outcome);
+    }
+
+    let mgr = manager.read().await;
+    let (existing_mints, watchlist_name) = match &watchlist_id {
+        Some(id) => {
+            let watchlist = mgr.get_watchlist(id).await.map_err(|e| e.to_string())?;
+            let mints = watchlist.items.into_iter().map(|i| i.mint).collect();
+            (mints, watchlist.name)
+        }
+        None => {
+            let native_name = matches!(format, WatchlistImportFormat::Native)
+                .then(|| serde_json::from_str::<Watchlist>(&source).ok())
+                .flatten()
+                .map(|w| w.name);
+            let name = name
+                .filter(|n| !n.trim().is_empty())
+                .or(native_name)
+                .unwrap_or_else(|| DEFAULT_IMPORT_NAME.to_string());
+            (HashSet::new(), name)
+        }
+    };
+
+    let parts = build_preview_parts(rows, &lookups, existing_mints);
+    let now = Utc::now();
+    let preview = WatchlistImportPreview {
+        id: uuid::Uuid::new_v4().to_string(),
+        format: format.as_str().to_string(),
+        watchlist_id,
+        watchlist_name,
+        resolved: parts.resolved,
+        ambiguities: parts.ambiguities,
+        duplicates: parts.duplicates,
+        errors: parts.errors,
+        created_at: now,
+        expires_at: now + Duration::minutes(PREVIEW_TTL_MINUTES),
+    };
+    mgr.store_import_preview(preview.clone(), now);
+    Ok(preview)
+}
+
+#[tauri::command]
+pub async fn watchlist_import_commit(
+    manager: State<'_, SharedWatchlistManager>,
+    preview_id: String,
+    resolutions: Vec<ImportResolution>,
+) -> Result<WatchlistImportResult, String> {
+    let mgr = manager.read().await;
+    mgr.commit_import_preview(&preview_id, resolutions)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOL: &str = "So11111111111111111111111111111111111111112";
+    const JUP: &str = "JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN";
+    const BONK: &str = "DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263";
+
+    fn result(symbol: &str, mint: &str, kind: MatchKind) -> AssetSearchResult {
+        AssetSearchResult {
+            symbol: Some(symbol.to_string()),
+            name: format!("{} Token", symbol),
+            asset_class: crate::market::AssetClass::Token,
+            identifier: mint.to_string(),
+            price: None,
+            popularity: 0.0,
+            match_kind: kind,
+            score: 0.0,
+        }
+    }
+
+    fn preview(parts: PreviewParts) -> WatchlistImportPreview {
+        let now = Utc::now();
+        WatchlistImportPreview {
+            id: "preview".to_string(),
+            format: "tradingView".to_string(),
+            watchlist_id: None,
+            watchlist_name: DEFAULT_IMPORT_NAME.to_string(),
+            resolved: parts.resolved,
+            ambiguities: parts.ambiguities,
+            duplicates: parts.duplicates,
+            errors: parts.errors,
+            created_at: now,
+            expires_at: now + Duration::minutes(PREVIEW_TTL_MINUTES),
+        }
+    }
+
+    #[test]
+    fn test_tradingview_symbols() {
+        assert_eq!(
+            tradingview_symbol("BINANCE:SOLUSDT").as_deref(),
+            Some("SOL")
+        );
+        assert_eq!(
+            tradingview_symbol("BYBIT:JUPUSDT.P").as_deref(),
+            Some("JUP")
+        );
+        assert_eq!(
+            tradingview_symbol("COINBASE:USDCUSD").as_deref(),
+            Some("USDC")
+        );
+        assert_eq!(tradingview_symbol("BONK").as_deref(), Some("BONK"));
+        assert_eq!(tradingview_symbol("BINANCE:"), None);
+
+        let rows = parse_tradingview("###CRYPTO,BINANCE:SOLUSDT,\nBINANCE:??\n");
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].row, 1);
+        assert!(rows[1].entry.is_err());
+    }
+
+    #[test]
+    fn test_csv_with_column_mapping() {
+        let source = "Ticker;Address;Note\nSOL;;\"main, L1\"\n;JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN;dex\n;;\n";
+        let mapping = CsvColumnMapping {
+            symbol: Some(CsvColumn::Name("ticker".to_string())),
+            mint: Some(CsvColumn::Index(1)),
+            label: Some(CsvColumn::Name("Note".to_string())),
+            has_header: true,
+            delimiter: Some(';'),
+        };
+        let rows = parse_csv(source, &mapping).unwrap();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(
+            rows[0].entry,
+            Ok(ImportEntry::Symbol {
+                symbol: "SOL".to_string(),
+                label: Some("main, L1".to_string()),
+            })
+        );
+        assert!(matches!(rows[1].entry, Ok(ImportEntry::Address { .. })));
+        assert_eq!(rows[2].row, 4);
+        assert!(rows[2].entry.is_err());
+
+        let missing = CsvColumnMapping {
+            symbol: Some(CsvColumn::Name("Symbol".to_string())),
+            mint: None,
+            label: None,
+            has_header: true,
+            delimiter: None,
+        };
+        assert!(parse_csv("Ticker\nSOL", &missing).is_err());
+    }
+
+    #[test]
+    fn test_address_labels_array_and_object() {
+        let array = format!(
+            r#"[{{"address": "{}", "label": "Jupiter"}}, {{"label": "no address"}}]"#,
+            JUP
+        );
+        let rows = parse_address_labels(&array).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert!(rows[1].entry.is_err());
+
+        let object = format!(r#"{{"{}": "Bonk"}}"#, BONK);
+        let rows = parse_address_labels(&object).unwrap();
+        assert_eq!(
+            rows[0].entry,
+            Ok(ImportEntry::Address {
+                mint: BONK.to_string(),
+                symbol: None,
+                label: Some("Bonk".to_string()),
+            })
+        );
+    }
+
+    #[test]
+    fn test_preview_sorts_rows_and_keeps_failures() {
+        let rows = parse_tradingview(
+            "BINANCE:SOLUSDT,BINANCE:JUPUSDT,BINANCE:WIFUSDT,BINANCE:NOPEUSDT,BINANCE:SOLUSDC,X:BONKUSD",
+        );
+        let mut lookups = HashMap::new();
+        lookups.insert(
+            "SOL".to_string(),
+            Ok(vec![result("SOL", SOL, MatchKind::ExactSymbol)]),
+        );
+        lookups.insert(
+            "JUP".to_string(),
+            Ok(vec![
+                result("JUP", JUP, MatchKind::ExactSymbol),
+                result("JUP", BONK, MatchKind::ExactSymbol),
+            ]),
+        );
+        lookups.insert(
+            "WIF".to_string(),
+            Ok(vec![result("WIFE", BONK, MatchKind::SymbolPrefix)]),
+        );
+        lookups.insert("NOPE".to_string(), Ok(vec![]));
+        lookups.insert("BONK".to_string(), Err("rate limited".to_string()));
+
+        let existing = HashSet::new();
+        let parts = build_preview_parts(rows, &lookups, existing);
+        assert_eq!(parts.resolved.len(), 1);
+        assert_eq!(parts.resolved[0].mint, SOL);
+        assert_eq!(parts.ambiguities.len(), 2);
+        assert_eq!(parts.ambiguities[0].candidates.len(), 2);
+        assert_eq!(parts.duplicates.len(), 1);
+        assert_eq!(parts.duplicates[0].row, 5);
+        assert_eq!(
+            parts.duplicates[0].reason,
+            DuplicateReason::RepeatedInImport
+        );
+        // Nothing is dropped: every row lands somewhere.
+        assert_eq!(parts.errors.len(), 2);
+        assert!(parts.errors[1].message.contains("rate limited"));
+    }
+
+    #[test]
+    fn test_commit_requires_choices_for_pending_rows() {
+        let rows = parse_tradingview("SOL,JUP,NOPE");
+        let mut lookups = HashMap::new();
+        lookups.insert(
+            "SOL".to_string(),
+            Ok(vec![result("SOL", SOL, MatchKind::ExactSymbol)]),
+        );
+        lookups.insert(
+            "JUP".to_string(),
+            Ok(vec![
+                result("JUP", JUP, MatchKind::ExactSymbol),
+                result("JUP", BONK, MatchKind::ExactSymbol),
+            ]),
+        );
+        lookups.insert("NOPE".to_string(), Ok(vec![]));
+        let preview = preview(build_preview_parts(rows, &lookups, HashSet::new()));
+
+        let partial = vec![ImportResolution::Skip { row: 3 }];
+        let err = apply_resolutions(&preview, partial, HashSet::new()).unwrap_err();
+        assert!(err.contains("Rows 2"));
+
+        let choices = vec![
+            ImportResolution::Map {
+                row: 2,
+                mint: JUP.to_string(),
+                symbol: None,
+            },
+            ImportResolution::Skip { row: 3 },
+        ];
+        let existing: HashSet<String> = [SOL.to_string()].into_iter().collect();
+        let (added, skipped) = apply_resolutions(&preview, choices, existing).unwrap();
+        assert_eq!(added.len(), 1);
+        assert_eq!(added[0].symbol, "JUP");
+        assert_eq!(skipped, vec![1, 3]);
+
+        let invalid = vec![
+            ImportResolution::Map {
+                row: 2,
+                mint: "not-a-mint".to_string(),
+                symbol: None,
+            },
+            ImportResolution::Skip { row: 3 },
+        ];
+        assert!(apply_resolutions(&preview, invalid, HashSet::new()).is_err());
+    }
+}
@@ -1,11 +1,20 @@
-use chrono::Utc;
+use crate::config::DataPaths;
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
 use sqlx::{Pool, Row, Sqlite, SqlitePool};
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 use std::sync::Arc;
-use tauri::{AppHandle, Manager, State};
+use tauri::{AppHandle, State};
 use tokio::sync::RwLock;
 
+use super::token_annotations::{SharedTokenAnnotationStore, TokenAnnotationSummary};
+use super::watchlist_import::{
+    apply_resolutions, ImportResolution, ImportedItem, WatchlistImportPreview,
+    WatchlistImportResult,
+};
+
 const WATCHLIST_DB_FILE: &str = "watchlists.db";
 const MAX_WATCHLISTS: usize = 10;
 
@@ -16,6 +25,10 @@ pub struct WatchlistItem {
     pub mint: String,
     pub position: i32,
     pub added_at: String,
+    /// Latest note and tags for the mint; filled in when the watchlist is
+    /// returned to the UI, never stored with the item.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub annotation: Option<TokenAnnotationSummary>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,6 +41,16 @@ pub struct Watchlist {
     pub updated_at: String,
 }
 
+impl Watchlist {
+    pub fn with_annotations(mut self, annotations: &SharedTokenAnnotationStore) -> Self {
+        let store = annotations.read();
+        for item in &mut self.items {
+            item.annotation = store.summary(&item.mint);
+        }
+        self
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct WatchlistStats {
@@ -93,6 +116,8 @@ pub enum WatchlistError {
     MaxWatchlistsReached(usize),
     #[error("duplicate item: {0}")]
     DuplicateItem(String),
+    #[error("invalid import: {0}")]
+    InvalidImport(String),
     #[error("internal error: {0}")]
     Internal(String),
 }
@@ -100,6 +125,7 @@ pub enum WatchlistError {
 #[derive(Clone)]
 pub struct WatchlistManager {
     pool: Pool<Sqlite>,
+    import_previews: Arc<Mutex<HashMap<String, WatchlistImportPreview>>>,
 }
 
 pub type SharedWatchlistManager = Arc<RwLock<WatchlistManager>>;
@@ -110,7 +136,10 @@ impl WatchlistManager {
         let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
         let pool = SqlitePool::connect(&db_url).await?;
 
-        let manager = Self { pool };
+        let manager = Self {
+            pool,
+            import_previews: Arc::new(Mutex::new(HashMap::new())),
+        };
         manager.initialize().await?;
         Ok(manager)
     }
@@ -427,6 +456,7 @@ impl WatchlistManager {
                 mint: row.try_get("mint")?,
                 position: row.try_get("position")?,
                 added_at: row.try_get("added_at")?,
+                annotation: None,
             });
         }
 
@@ -488,10 +518,113 @@ impl WatchlistManager {
 
         Ok(watchlist)
     }
+
+    /// Appends items after the current last position, ignoring mints that
+    /// are already present. Returns how many rows were inserted.
+    async fn add_items(
+        &self,
+        watchlist_id: &str,
+        items: &[ImportedItem],
+    ) -> Result<u64, WatchlistError> {
+        let mut tx = self.pool.begin().await?;
+
+        let max_position: Option<i32> =
+            sqlx::query_scalar("SELECT MAX(position) FROM watchlist_items WHERE watchlist_id = ?1")
+                .bind(watchlist_id)
+                .fetch_one(&mut *tx)
+                .await?;
+        let mut position = max_position.map(|p| p + 1).unwrap_or(0);
+        let now = Utc::now().to_rfc3339();
+
+        let mut inserted = 0;
+        for item in items {
+            let result = sqlx::query(
+                r#"
+                INSERT OR IGNORE INTO watchlist_items (watchlist_id, symbol, mint, position, added_at)
+                VALUES (?1, ?2, ?3, ?4, ?5)
+                "#,
+            )
+            .bind(watchlist_id)
+            .bind(&item.symbol)
+            .bind(&item.mint)
+            .bind(position)
+            .bind(&now)
+            .execute(&mut *tx)
+            .await?;
+            if result.rows_affected() > 0 {
+                inserted += 1;
+                position += 1;
+            }
+        }
+
+        sqlx::query("UPDATE watchlists SET updated_at = ?1 WHERE id = ?2")
+            .bind(&now)
+            .bind(watchlist_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(inserted)
+    }
+
+    /// Keeps a preview for a later commit and drops expired ones.
+    pub fn store_import_preview(&self, preview: WatchlistImportPreview, now: DateTime<Utc>) {
+        let mut previews = self.import_previews.lock();
+        previews.retain(|_, existing| !existing.is_expired(now));
+        previews.insert(preview.id.clone(), preview);
+    }
+
+    pub async fn commit_import_preview(
+        &self,
+        preview_id: &str,
+        resolutions: Vec<ImportResolution>,
+    ) -> Result<WatchlistImportResult, WatchlistError> {
+        let preview = {
+            let mut previews = self.import_previews.lock();
+            previews.retain(|_, existing| !existing.is_expired(Utc::now()));
+            previews.get(preview_id).cloned().ok_or_else(|| {
+                WatchlistError::NotFound(format!(
+                    "Import preview {} (it may have expired)",
+                    preview_id
+                ))
+            })?
+        };
+
+        // The target may have changed since the preview was built.
+        let existing_mints: HashSet<String> = match &preview.watchlist_id {
+            Some(id) => self
+                .get_watchlist(id)
+                .await?
+                .items
+                .into_iter()
+                .map(|item| item.mint)
+                .collect(),
+            None => HashSet::new(),
+        };
+        let (added, skipped_rows) = apply_resolutions(&preview, resolutions, existing_mints)
+            .map_err(WatchlistError::InvalidImport)?;
+
+        let watchlist_id = match &preview.watchlist_id {
+            Some(id) => id.clone(),
+            None => {
+                self.create_watchlist(preview.watchlist_name.clone())
+                    .await?
+                    .id
+            }
+        };
+        self.add_items(&watchlist_id, &added).await?;
+        self.import_previews.lock().remove(preview_id);
+
+        Ok(WatchlistImportResult {
+            watchlist: self.get_watchlist(&watchlist_id).await?,
+            added,
+            skipped_rows,
+        })
+    }
 }
 
 fn watchlist_db_path(app: &AppHandle) -> Result<PathBuf, WatchlistError> {
-    let app_data_dir = app.path().app_data_dir().map_err(|err| {
+    let app_data_dir = app.app_data_root().map_err(|err| {
         WatchlistError::Internal(format!("Unable to resolve app data directory: {err}"))
     })?;
 
@@ -512,18 +645,25 @@ pub async fn watchlist_create(
 #[tauri::command]
 pub async fn watchlist_list(
     manager: State<'_, SharedWatchlistManager>,
+    annotations: State<'_, SharedTokenAnnotationStore>,
 ) -> Result<Vec<Watchlist>, String> {
     let mgr = manager.read().await;
-    mgr.list_watchlists().await.map_err(|e| e.to_string())
+    let watchlists = mgr.list_watchlists().await.map_err(|e| e.to_string())?;
+    Ok(watchlists
+        .into_iter()
+        .map(|watchlist| watchlist.with_annotations(&annotations))
+        .collect())
 }
 
 #[tauri::command]
 pub async fn watchlist_get(
     manager: State<'_, SharedWatchlistManager>,
+    annotations: State<'_, SharedTokenAnnotationStore>,
     id: String,
 ) -> Result<Watchlist, String> {
     let mgr = manager.read().await;
-    mgr.get_watchlist(&id).await.map_err(|e| e.to_string())
+    let watchlist = mgr.get_watchlist(&id).await.map_err(|e| e.to_string())?;
+    Ok(watchlist.with_annotations(&annotations))
 }
 
 #[tauri::command]
@@ -1,3 +1,4 @@
+use crate::config::DataPaths;
 use chrono::{DateTime, Duration as ChronoDuration, Utc};
 use serde::ser::Serialize as SerializeValue;
 use serde::{Deserialize, Serialize};
@@ -7,7 +8,7 @@ use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, RwLock};
-use tauri::{AppHandle, Manager};
+use tauri::AppHandle;
 
 const ACTIVITY_DB_FILE: &str = "activity_logs.db";
 const ACTIVITY_CONFIG_FILE: &str = "activity_log_config.json";
@@ -24,6 +25,10 @@ pub enum ActivityAction {
     Swap,
     Approve,
     Reject,
+    ResetLimits,
+    KioskEnable,
+    KioskExit,
+    TradingHoursOverride,
 }
 
 impl ActivityAction {
@@ -36,6 +41,10 @@ impl ActivityAction {
             ActivityAction::Swap => "swap",
             ActivityAction::Approve => "approve",
             ActivityAction::Reject => "reject",
+            ActivityAction::ResetLimits => "reset_limits",
+            ActivityAction::KioskEnable => "kiosk_enable",
+            ActivityAction::KioskExit => "kiosk_exit",
+            ActivityAction::TradingHoursOverride => "trading_hours_override",
         }
     }
 }
@@ -689,7 +698,7 @@ fn bind_values<'q>(
 }
 
 fn activity_log_path(app: &AppHandle) -> Result<PathBuf, ActivityLogError> {
-    let mut path = app.path().app_data_dir().map_err(|err| {
+    let mut path = app.app_data_root().map_err(|err| {
         ActivityLogError::Internal(format!("Unable to resolve app data directory: {err}"))
     })?;
     if !path.exists() {
@@ -700,7 +709,7 @@ fn activity_log_path(app: &AppHandle) -> Result<PathBuf, ActivityLogError> {
 }
 
 fn activity_config_path(app: &AppHandle) -> Result<PathBuf, ActivityLogError> {
-    let mut path = app.path().app_data_dir().map_err(|err| {
+    let mut path = app.app_data_root().map_err(|err| {
         ActivityLogError::Internal(format!("Unable to resolve app data directory: {err}"))
     })?;
     if !path.exists() {
@@ -5,6 +5,7 @@ use std::{
     sync::{Mutex, MutexGuard},
 };
 
+use crate::config::DataPaths;
 use aes_gcm::aead::generic_array::GenericArray;
 use aes_gcm::{
     aead::{Aead, KeyInit},
@@ -16,7 +17,7 @@ use chrono::{DateTime, Utc};
 use keyring::Entry;
 use rand_core::{OsRng, RngCore};
 use serde::{Deserialize, Serialize};
-use tauri::{AppHandle, Manager};
+use tauri::AppHandle;
 use zeroize::{Zeroize, Zeroizing};
 
 const KEYRING_SERVICE: &str = "EclipseMarketPro";
@@ -110,6 +111,15 @@ impl Keystore {
         })
     }
 
+    /// An empty keystore at `path`, for tests that never unlock a secret.
+    #[cfg(test)]
+    pub(crate) fn at(path: PathBuf) -> Self {
+        Self {
+            path,
+            document: Mutex::new(KeystoreDocument::default()),
+        }
+    }
+
     pub fn store_secret(&self, key: &str, secret: &[u8]) -> Result<(), KeystoreError> {
         let mut guard = self.lock_document()?;
         let mut salt = [0u8; SALT_LEN];
@@ -365,8 +375,7 @@ fn persist_document(path: &PathBuf, document: &KeystoreDocument) -> Result<(), K
 fn keystore_path(app: &AppHandle) -> Result<PathBuf, KeystoreError> {
     let app_handle = app.clone();
     let mut path = app_handle
-        .path()
-        .app_data_dir()
+        .app_data_root()
         .map_err(|_| KeystoreError::Internal)?;
     if !path.exists() {
         fs::create_dir_all(&path)?;
@@ -0,0 +1,531 @@
+//! Read-only kiosk mode for wall-mounted displays. While active, every
+//! command missing from the allow-list in `kiosk_commands` is rejected by
+//! `guard_invoke`, which wraps the app's invoke handler, so new commands are
+//! blocked until they are classified. Leaving the mode takes the PIN set on
+//! entry, whose Argon2 hash lives in the keystore, or the optional auto-exit
+//! timeout.
+
+use crate::collab::crypto::{hash_password, verify_password};
+use crate::config::DataPaths;
+use crate::errors::AppError;
+use crate::security::activity_log::{ActivityAction, ActivityLogger};
+use crate::security::keystore::{Keystore, KeystoreError};
+use crate::security::kiosk_commands::READ_ONLY_COMMANDS;
+use crate::tray::SharedTrayManager;
+use chrono::{DateTime, Duration, Utc};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tauri::ipc::Invoke;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+
+const KIOSK_STATE_FILE: &str = "kiosk_mode.json";
+const KIOSK_PIN_KEY: &str = "kiosk_pin_hash";
+pub const KIOSK_MODE_EVENT: &str = "kiosk_mode_changed";
+
+const MIN_PIN_DIGITS: usize = 4;
+const MAX_PIN_DIGITS: usize = 12;
+const MAX_AUTO_EXIT_MINUTES: u32 = 7 * 24 * 60;
+const MAX_FAILED_ATTEMPTS: u32 = 5;
+const LOCKOUT_MINUTES: i64 = 5;
+const AUTO_EXIT_CHECK_SECS: u64 = 15;
+/// Recorded as the activity log's wallet address; kiosk events are not tied
+/// to a wallet.
+const ACTIVITY_SUBJECT: &str = "local";
+
+/// Whether `command` may run in kiosk mode. Only commands on the explicit
+/// allow-list do; anything unlisted is treated as mutating.
+pub fn is_read_only_command(command: &str) -> bool {
+    READ_ONLY_COMMANDS.binary_search(&command).is_ok()
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum KioskError {
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+    #[error("keystore error: {0}")]
+    Keystore(#[from] KeystoreError),
+    #[error("{0}")]
+    Invalid(String),
+}
+
+impl From<KioskError> for AppError {
+    fn from(err: KioskError) -> Self {
+        match err {
+            KioskError::Invalid(message) => AppError::validation(message),
+            KioskError::Keystore(err) => err.into(),
+            err => AppError::internal(err.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PersistedKiosk {
+    active: bool,
+    enabled_at: Option<DateTime<Utc>>,
+    auto_exit_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Default)]
+struct PinAttempts {
+    failed: u32,
+    locked_until: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KioskStatus {
+    pub active: bool,
+    pub enabled_at: Option<DateTime<Utc>>,
+    pub auto_exit_at: Option<DateTime<Utc>>,
+    /// Set after too many wrong PINs; exit attempts fail until then.
+    pub locked_until: Option<DateTime<Utc>>,
+}
+
+/// Kiosk state, persisted so a restart comes back locked.
+pub struct KioskMode {
+    path: Option<PathBuf>,
+    state: RwLock<PersistedKiosk>,
+    attempts: Mutex<PinAttempts>,
+}
+
+pub type SharedKioskMode = Arc<KioskMode>;
+
+impl KioskMode {
+    pub fn new(app: &AppHandle) -> Result<Self, KioskError> {
+        let dir = app
+            .app_data_root()
+            .map_err(|e| KioskError::Invalid(format!("App data directory not found: {}", e)))?;
+        fs::create_dir_all(&dir)?;
+        Self::load(dir.join(KIOSK_STATE_FILE))
+    }
+
+    pub fn load(path: PathBuf) -> Result<Self, KioskError> {
+        let state = if path.exists() {
+            serde_json::from_str(&fs::read_to_string(&path)?)?
+        } else {
+            PersistedKiosk::default()
+        };
+        Ok(Self {
+            path: Some(path),
+            state: RwLock::new(state),
+            attempts: Mutex::new(PinAttempts::default()),
+        })
+    }
+
+    /// A kiosk state that is never written to disk.
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            state: RwLock::new(PersistedKiosk::default()),
+            attempts: Mutex::new(PinAttempts::default()),
+        }
+    }
+
+    fn persist(&self, state: &PersistedKiosk) -> Result<(), KioskError> {
+        if let Some(path) = &self.path {
+            let tmp = path.with_extension("json.tmp");
+            fs::write(&tmp, serde_json::to_vec_pretty(state)?)?;
+            fs::rename(&tmp, path)?;
+        }
+        Ok(())
+    }
+
+    /// Active and not past its auto-exit time.
+    pub fn is_active(&self) -> bool {
+        let state = self.state.read();
+        state.active && state.auto_exit_at.map_or(true, |at| Utc::now() < at)
+    }
+
+    pub fn status(&self) -> KioskStatus {
+        let state = self.state.read();
+        let locked_until = self
+            .attempts
+            .lock()
+            .locked_until
+            .filter(|until| Utc::now() < *until);
+        KioskStatus {
+            active: self.is_active(),
+            enabled_at: state.enabled_at.filter(|_| state.active),
+            auto_exit_at: state.auto_exit_at.filter(|_| state.active),
+            locked_until,
+        }
+    }
+
+    pub fn check_command(&self, command: &str) -> Result<(), AppError> {
+        if self.is_active() && !is_read_only_command(command) {
+            return Err(AppError::kiosk_mode(format!(
+                "'{}' is unavailable while kiosk mode is active",
+                command
+            ))
+            .with_detail("command", command));
+        }
+        Ok(())
+    }
+
+    fn activate(&self, auto_exit_minutes: Option<u32>) -> Result<KioskStatus, KioskError> {
+        if let Some(minutes) = auto_exit_minutes {
+            if minutes == 0 || minutes > MAX_AUTO_EXIT_MINUTES {
+                return Err(KioskError::Invalid(format!(
+                    "Auto-exit must be between 1 and {} minutes",
+                    MAX_AUTO_EXIT_MINUTES
+                )));
+            }
+        }
+        if self.is_active() {
+            return Err(KioskError::Invalid("Kiosk mode is already active".into()));
+        }
+
+        let now = Utc::now();
+        let next = PersistedKiosk {
+            active: true,
+            enabled_at: Some(now),
+            auto_exit_at: auto_exit_minutes.map(|minutes| now + Duration::minutes(minutes as i64)),
+        };
+        self.persist(&next)?;
+        *self.state.write() = next;
+        *self.attempts.lock() = PinAttempts::default();
+        Ok(self.status())
+    }
+
+    fn deactivate(&self) -> Result<(), KioskError> {
+        let next = PersistedKiosk::default();
+        self.persist(&next)?;
+        *self.state.write() = next;
+        *self.attempts.lock() = PinAttempts::default();
+        Ok(())
+    }
+
+    /// Clears an active state whose auto-exit time has passed. Returns
+    /// whether it did.
+    fn take_expired(&self) -> Result<bool, KioskError> {
+        let expired = {
+            let state = self.state.read();
+            state.active && state.auto_exit_at.is_some_and(|at| Utc::now() >= at)
+        };
+        if expired {
+            self.deactivate()?;
+        }
+        Ok(expired)
+    }
+
+    fn lockout(&self) -> Option<DateTime<Utc>> {
+        self.attempts
+            .lock()
+            .locked_until
+            .filter(|until| Utc::now() < *until)
+    }
+
+    /// Counts a wrong PIN. Returns the attempts left before a lockout, or
+    /// zero once the lockout starts.
+    fn record_failure(&self) -> u32 {
+        let mut attempts = self.attempts.lock();
+        attempts.failed += 1;
+        if attempts.failed >= MAX_FAILED_ATTEMPTS {
+            attempts.failed = 0;
+            attempts.locked_until = Some(Utc::now() + Duration::minutes(LOCKOUT_MINUTES));
+            return 0;
+        }
+        MAX_FAILED_ATTEMPTS - attempts.failed
+    }
+}
+
+/// Wraps the generated invoke handler so every command passes the kiosk
+/// check before it runs.
+pub fn guard_invoke<R: Runtime>(invoke: Invoke<R>, handler: &dyn Fn(Invoke<R>) -> bool) -> bool {
+    let rejection = invoke
+        .message
+        .webview_ref()
+        .try_state::<SharedKioskMode>()
+        .and_then(|kiosk| kiosk.check_command(invoke.message.command()).err());
+    match rejection {
+        Some(error) => {
+            invoke.resolver.reject(error);
+            true
+        }
+        None => handler(invoke),
+    }
+}
+
+/// For entry points outside the invoke handler, such as tray menu items and
+/// global shortcuts.
+pub fn is_kiosk_active<R: Runtime>(app: &impl Manager<R>) -> bool {
+    app.try_state::<SharedKioskMode>()
+        .is_some_and(|kiosk| kiosk.is_active())
+}
+
+/// Puts the main window, tray and frontend into or out of kiosk
+/// presentation.
+pub fn apply_kiosk_presentation(app: &AppHandle, status: &KioskStatus) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.set_fullscreen(status.active);
+        if status.active {
+            let _ = window.show();
+            let _ = window.unminimize();
+            let _ = window.set_focus();
+        }
+    }
+    if let Some(tray) = app.try_state::<SharedTrayManager>() {
+        tray.set_kiosk_mode(app, status.active);
+    }
+    let _ = app.emit(KIOSK_MODE_EVENT, status);
+}
+
+async fn log_kiosk_event(
+    logger: &ActivityLogger,
+    action: ActivityAction,
+    details: Value,
+    success: bool,
+) {
+    if let Err(e) = logger
+        .log_activity(ACTIVITY_SUBJECT, action, details, success, None)
+        .await
+    {
+        eprintln!("Failed to log kiosk mode event: {}", e);
+    }
+}
+
+fn validate_pin(pin: &str) -> Result<(), KioskError> {
+    let digits = pin.chars().count();
+    if !(MIN_PIN_DIGITS..=MAX_PIN_DIGITS).contains(&digits)
+        || !pin.chars().all(|c| c.is_ascii_digit())
+    {
+        return Err(KioskError::Invalid(format!(
+            "PIN must be {} to {} digits",
+            MIN_PIN_DIGITS, MAX_PIN_DIGITS
+        )));
+    }
+    Ok(())
+}
+
+/// Ends kiosk mode once its auto-exit time passes.
+pub async fn run_kiosk_auto_exit(app: AppHandle) {
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(AUTO_EXIT_CHECK_SECS));
+    loop {
+        interval.tick().await;
+
+        let Some(kiosk) = app.try_state::<SharedKioskMode>() else {
+            continue;
+        };
+        match kiosk.take_expired() {
+            Ok(true) => {}
+            Ok(false) => continue,
+            Err(e) => {
+                eprintln!("Failed to end expired kiosk mode: {}", e);
+                continue;
+            }
+        }
+
+        if let Some(keystore) = app.try_state::<Keystore>() {
+            if let Err(e) = keystore.remove_secret(KIOSK_PIN_KEY) {
+                eprintln!("Failed to remove kiosk PIN: {}", e);
+            }
+        }
+        apply_kiosk_presentation(&app, &kiosk.status());
+        if let Some(logger) = app.try_state::<ActivityLogger>() {
+            log_kiosk_event(
+                &logger,
+                ActivityAction::KioskExit,
+                json!({ "reason": "timeout" }),
+                true,
+            )
+            .await;
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn kiosk_mode_enable(
+    pin: String,
+    auto_exit_minutes: Option<u32>,
+    app: AppHandle,
+    kiosk: State<'_, SharedKioskMode>,
+    keystore: State<'_, Keystore>,
+    logger: State<'_, ActivityLogger>,
+) -> Result<KioskStatus, AppError> {
+    validate_pin(&pin)?;
+    // Checked before the PIN is stored so that enabling again cannot
+    // replace the PIN of an active kiosk.
+    if kiosk.is_active() {
+        return Err(AppError::kiosk_mode("Kiosk mode is already active"));
+    }
+    let hash = hash_password(&pin).map_err(|e| AppError::internal(e.to_string()))?;
+    keystore.store_secret(KIOSK_PIN_KEY, hash.as_bytes())?;
+
+    let status = match kiosk.activate(auto_exit_minutes) {
+        Ok(status) => status,
+        Err(err) => {
+            log_kiosk_event(
+                &logger,
+                ActivityAction::KioskEnable,
+                json!({ "error": err.to_string() }),
+                false,
+            )
+            .await;
+            return Err(err.into());
+        }
+    };
+
+    apply_kiosk_presentation(&app, &status);
+    log_kiosk_event(
+        &logger,
+        ActivityAction::KioskEnable,
+        json!({ "autoExitAt": status.auto_exit_at }),
+        true,
+    )
+    .await;
+    Ok(status)
+}
+
+#[tauri::command]
+pub async fn kiosk_mode_exit(
+    pin: String,
+    app: AppHandle,
+    kiosk: State<'_, SharedKioskMode>,
+    keystore: State<'_, Keystore>,
+    logger: State<'_, ActivityLogger>,
+) -> Result<KioskStatus, AppError> {
+    if !kiosk.is_active() {
+        return Ok(kiosk.status());
+    }
+
+    if let Some(locked_until) = kiosk.lockout() {
+        log_kiosk_event(
+            &logger,
+            ActivityAction::KioskExit,
+            json!({ "reason": "locked_out", "lockedUntil": locked_until }),
+            false,
+        )
+        .await;
+        return Err(AppError::unauthorized("Too many incorrect PINs")
+            .with_code("kiosk_locked_out")
+            .with_detail("lockedUntil", locked_until));
+    }
+
+    let hash = keystore.retrieve_secret(KIOSK_PIN_KEY)?;
+    let hash = String::from_utf8_lossy(&hash);
+    let valid = verify_password(&pin, &hash).map_err(|e| AppError::internal(e.to_string()))?;
+    if !valid {
+        let remaining = kiosk.record_failure();
+        log_kiosk_event(
+            &logger,
+            ActivityAction::KioskExit,
+            json!({ "reason": "invalid_pin", "attemptsRemaining": remaining }),
+            false,
+        )
+        .await;
+        return Err(AppError::unauthorized("Incorrect PIN")
+            .with_code("kiosk_invalid_pin")
+            .with_detail("attemptsRemaining", remaining));
+    }
+
+    kiosk.deactivate()?;
+    keystore.remove_secret(KIOSK_PIN_KEY)?;
+    let status = kiosk.status();
+    apply_kiosk_presentation(&app, &status);
+    log_kiosk_event(
+        &logger,
+        ActivityAction::KioskExit,
+        json!({ "reason": "pin" }),
+        true,
+    )
+    .await;
+    Ok(status)
+}
+
+#[tauri::command]
+pub async fn kiosk_mode_status(kiosk: State<'_, SharedKioskMode>) -> Result<KioskStatus, AppError> {
+    Ok(kiosk.status())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_classification_fails_closed() {
+        for command in [
+            "get_portfolio_correlations",
+            "backtest_get_run",
+            "list_webhook_event_types",
+            "privacy_mode_status",
+            "copy_trading_history",
+            "phantom_balance",
+            "refresh_trending",
+            "kiosk_mode_exit",
+            "kiosk_mode_enable",
+        ] {
+            assert!(is_read_only_command(command), "{command}");
+        }
+        for command in [
+            "create_order",
+            "wallet_send_transaction",
+            "update_setting",
+            "alert_update",
+            "alert_history_delete",
+            "swap_history_add_entry",
+            "jupiter_swap",
+            "mobile_resolve_sync_conflict",
+            "collab_get_webrtc_signals",
+            "load_settings_profile",
+            "get_future_command",
+        ] {
+            assert!(!is_read_only_command(command), "{command}");
+        }
+    }
+
+    #[test]
+    fn test_active_kiosk_rejects_mutations_with_kiosk_error() {
+        let kiosk = KioskMode::in_memory();
+        assert!(kiosk.check_command("create_order").is_ok());
+
+        kiosk.activate(None).unwrap();
+        let err = kiosk.check_command("create_order").unwrap_err();
+        assert_eq!(err.kind(), "kiosk_mode");
+        assert!(kiosk.check_command("get_active_orders").is_ok());
+        assert!(kiosk.activate(None).is_err());
+
+        kiosk.deactivate().unwrap();
+        assert!(kiosk.check_command("create_order").is_ok());
+    }
+
+    #[test]
+    fn test_auto_exit_and_persistence() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(KIOSK_STATE_FILE);
+        let kiosk = KioskMode::load(path.clone()).unwrap();
+        kiosk.activate(Some(30)).unwrap();
+        assert!(KioskMode::load(path.clone()).unwrap().is_active());
+
+        kiosk.state.write().auto_exit_at = Some(Utc::now() - Duration::seconds(1));
+        assert!(!kiosk.is_active());
+        assert!(kiosk.take_expired().unwrap());
+        assert!(!kiosk.take_expired().unwrap());
+        assert!(!KioskMode::load(path).unwrap().is_active());
+
+        assert!(kiosk.activate(Some(0)).is_err());
+    }
+
+    #[test]
+    fn test_repeated_wrong_pins_lock_out() {
+        let kiosk = KioskMode::in_memory();
+        kiosk.activate(None).unwrap();
+        for expected in (1..MAX_FAILED_ATTEMPTS).rev() {
+            assert_eq!(kiosk.record_failure(), expected);
+            assert!(kiosk.lockout().is_none());
+        }
+        assert_eq!(kiosk.record_failure(), 0);
+        assert!(kiosk.lockout().is_some());
+        assert!(kiosk.status().locked_until.is_some());
+
+        assert!(validate_pin("1234").is_ok());
+        assert!(validate_pin("12a4").is_err());
+        assert!(validate_pin("123").is_err());
+    }
+}
@@ -0,0 +1,1089 @@
+//! The kiosk classification of every registered command. A command runs in
+//! kiosk mode only if it is listed in [`READ_ONLY_COMMANDS`]; anything else,
+//! including a command added to the invoke handler without being listed here,
+//! is rejected. The test below fails until each registered command is placed
+//! in one of the two lists.
+
+/// Commands allowed in kiosk mode, sorted for binary search. Besides queries
+/// this includes refreshes, stream subscriptions and diagnostics reporting, so
+/// a dashboard keeps its data current.
+pub(crate) const READ_ONLY_COMMANDS: &[&str] = &[
+    "academy_preview_course_import",
+    "address_book_list_contacts",
+    "address_book_search_contacts",
+    "ai_get_conversations",
+    "ai_get_pattern_warnings",
+    "ai_get_usage_stats",
+    "ai_is_configured",
+    "alert_get",
+    "alert_get_trigger_history",
+    "alert_history_get",
+    "alert_history_list",
+    "alert_history_stats",
+    "alert_list",
+    "alerts_effectiveness_report",
+    "analyze_governance_proposal",
+    "analyze_text_sentiment",
+    "analyze_token_flows",
+    "assess_risk",
+    "auto_trading_get_executions",
+    "auto_trading_get_strategies",
+    "auto_trading_get_strategy",
+    "backtest_compare_runs",
+    "backtest_get_retention",
+    "backtest_get_run",
+    "backtest_list_runs",
+    "batch_risk_scores",
+    "biometric_get_status",
+    "bridge_get_quote",
+    "bridge_get_transaction",
+    "bridge_list_transactions",
+    "bridge_list_transactions_by_status",
+    "calculate_portfolio_analytics",
+    "calculate_wallet_performance",
+    "chain_get_active",
+    "chain_get_balance",
+    "chain_get_cross_chain_portfolio",
+    "chain_get_fee_estimate",
+    "chain_get_status",
+    "chain_list_chains",
+    "chain_list_enabled",
+    "chat_integration_get_delivery_logs",
+    "chat_integration_get_rate_limits",
+    "chat_integration_get_settings",
+    "check_auto_start_enabled",
+    "check_for_updates",
+    "check_launch_safety",
+    "check_liquidity_lock_compliance",
+    "check_p2p_compliance",
+    "check_rebalance_triggers",
+    "check_risk_threshold",
+    "check_suspicious_activity",
+    "check_tauri_health",
+    "check_vesting_compliance",
+    "check_voice_permission",
+    "collab_get_competition",
+    "collab_get_message_retention",
+    "collab_get_messages",
+    "collab_get_my_permissions",
+    "collab_get_orders",
+    "collab_get_participants",
+    "collab_get_role_audit",
+    "collab_get_room",
+    "collab_get_room_state",
+    "collab_get_watchlists",
+    "collab_list_rooms",
+    "collab_search_messages",
+    "copy_trading_followed_wallets",
+    "copy_trading_get",
+    "copy_trading_get_leader_profile",
+    "copy_trading_history",
+    "copy_trading_list",
+    "copy_trading_performance",
+    "dca_get",
+    "dca_history",
+    "dca_list",
+    "dca_performance",
+    "drawing_changes",
+    "drawing_get_history",
+    "drawing_list",
+    "drawing_list_templates",
+    "email_get_config",
+    "email_get_history",
+    "email_get_stats",
+    "estimate_compound_apy_boost",
+    "fetch_social_mentions",
+    "get_active_anomalies",
+    "get_active_orders",
+    "get_active_policies",
+    "get_activity_logs",
+    "get_activity_retention",
+    "get_activity_stats",
+    "get_airdrop",
+    "get_airdrop_metrics",
+    "get_alert_configs",
+    "get_all_active_governance_proposals",
+    "get_all_settings",
+    "get_all_time_leaderboard",
+    "get_all_token_sentiments",
+    "get_anomalies",
+    "get_anomaly_detection_config",
+    "get_anomaly_profile_assignments",
+    "get_anomaly_statistics",
+    "get_api_analytics",
+    "get_api_budgets",
+    "get_api_health_dashboard",
+    "get_api_status",
+    "get_auto_bracket_policy",
+    "get_auto_compound_config",
+    "get_auto_compound_recommendations",
+    "get_auto_start_settings",
+    "get_backup_schedule",
+    "get_backup_status",
+    "get_badge",
+    "get_behavioral_analytics",
+    "get_benchmark_comparison_data",
+    "get_best_worst_trades_data",
+    "get_blacklist",
+    "get_breadcrumbs",
+    "get_build_status",
+    "get_cache_statistics",
+    "get_cached_audit",
+    "get_certificate_proof",
+    "get_certificate_signing_keys",
+    "get_challenge_progress",
+    "get_challenge_submissions",
+    "get_chart_annotations",
+    "get_chart_subscriptions",
+    "get_coin_price",
+    "get_coin_safety_report",
+    "get_coin_sentiment",
+    "get_compile_errors",
+    "get_compiler_watch_status",
+    "get_compound_history",
+    "get_compression_config",
+    "get_compression_stats",
+    "get_concentration_alerts",
+    "get_config_settings_template",
+    "get_consensus_data",
+    "get_content_stats",
+    "get_cooldown_status",
+    "get_course",
+    "get_course_lessons",
+    "get_crash_report",
+    "get_current_price",
+    "get_custom_predictions",
+    "get_dashboard_snapshot",
+    "get_data_root_status",
+    "get_database_size",
+    "get_defi_portfolio_summary",
+    "get_defi_risk_metrics",
+    "get_defi_snapshot",
+    "get_dev_settings",
+    "get_diagnostics_settings",
+    "get_distribution_metrics",
+    "get_earnings_calendar",
+    "get_environment",
+    "get_error_stats",
+    "get_event_stats",
+    "get_events_command",
+    "get_execution_audit",
+    "get_fair_use_status",
+    "get_farming_opportunities",
+    "get_farming_positions",
+    "get_feature_flags",
+    "get_fee_audit",
+    "get_firmware_version",
+    "get_fix_attempts",
+    "get_fix_stats",
+    "get_governance_deadlines",
+    "get_governance_delegations",
+    "get_governance_memberships",
+    "get_governance_participation",
+    "get_governance_proposals",
+    "get_governance_summary",
+    "get_hardware_wallet_address",
+    "get_health_report",
+    "get_holder_cohorts",
+    "get_holder_distribution",
+    "get_holder_trends",
+    "get_idea_statistics",
+    "get_insider_activity",
+    "get_institutional_holdings",
+    "get_insurance_quote",
+    "get_journal_entries",
+    "get_journal_entries_count",
+    "get_journal_entry",
+    "get_journal_stats",
+    "get_kamino_farms",
+    "get_kamino_positions",
+    "get_kamino_vaults",
+    "get_key_rotation_status",
+    "get_large_transfers",
+    "get_latest_risk_score",
+    "get_launch_bias_report",
+    "get_launch_config",
+    "get_launch_prediction_history",
+    "get_leaderboard",
+    "get_lesson_progress",
+    "get_liquidity_lock",
+    "get_liquidity_lock_settings",
+    "get_liquidity_lock_status",
+    "get_logger_config",
+    "get_logs",
+    "get_marginfi_banks",
+    "get_marginfi_positions",
+    "get_market_heatmap",
+    "get_market_summary",
+    "get_monitors",
+    "get_multisig_wallet",
+    "get_network_congestion",
+    "get_new_coin_screen",
+    "get_new_coins",
+    "get_new_ipos",
+    "get_open_tax_lots",
+    "get_order",
+    "get_order_history",
+    "get_order_protection_policy",
+    "get_orderbook_snapshot",
+    "get_p2p_dispute",
+    "get_p2p_escrow",
+    "get_p2p_escrow_config",
+    "get_p2p_messages",
+    "get_p2p_offer",
+    "get_p2p_pending_transaction",
+    "get_p2p_stats",
+    "get_paper_account",
+    "get_paper_performance",
+    "get_paper_positions",
+    "get_paper_trade_history",
+    "get_performance_alerts",
+    "get_performance_by_source",
+    "get_performance_history",
+    "get_performance_metrics",
+    "get_performance_score_history",
+    "get_policy",
+    "get_portfolio_comparison",
+    "get_portfolio_correlations",
+    "get_portfolio_data",
+    "get_portfolio_export_schema",
+    "get_portfolio_metrics",
+    "get_portfolio_recommendations",
+    "get_portfolio_risk_summary",
+    "get_positions",
+    "get_prediction_markets",
+    "get_price_history",
+    "get_priority_fee_estimates",
+    "get_question_analytics",
+    "get_question_bank",
+    "get_quiz",
+    "get_quiz_attempts",
+    "get_quiz_standing",
+    "get_rebalance_history",
+    "get_recent_whale_alerts",
+    "get_repair_history",
+    "get_reputation_history",
+    "get_reputation_settings",
+    "get_reputation_stats",
+    "get_reward_stats",
+    "get_risk_history",
+    "get_risk_profile",
+    "get_rollback_info",
+    "get_safety_policy",
+    "get_scan_progress",
+    "get_sector_allocation",
+    "get_sentiment_alert_config",
+    "get_sentiment_alerts",
+    "get_sentiment_comparison",
+    "get_service_health_metrics",
+    "get_setting_schema",
+    "get_settings_change_history",
+    "get_settings_template",
+    "get_smart_money_consensus",
+    "get_smart_money_wallet_accuracy",
+    "get_smart_money_wallets",
+    "get_social_spike_history",
+    "get_solend_pools",
+    "get_solend_positions",
+    "get_solend_reserves",
+    "get_stablecoin_depeg_settings",
+    "get_stablecoin_status",
+    "get_staking_pools",
+    "get_staking_positions",
+    "get_staking_schedule",
+    "get_state_at_time_command",
+    "get_stock_alerts",
+    "get_stock_news",
+    "get_stream_status",
+    "get_stress_test_presets",
+    "get_tax_center_summary",
+    "get_tax_loss_harvesting_suggestions",
+    "get_tax_lot_strategy",
+    "get_tax_lots",
+    "get_timing_analysis_data",
+    "get_token_metadata",
+    "get_token_performance_breakdown",
+    "get_token_reputation",
+    "get_token_risk_score",
+    "get_token_sentiment",
+    "get_top_coins",
+    "get_top_movers",
+    "get_trade_ideas",
+    "get_trader_profile",
+    "get_tray_settings",
+    "get_trending_coins",
+    "get_trending_stocks",
+    "get_ttl_config",
+    "get_unlock_alert_settings",
+    "get_unlock_calendar",
+    "get_unlock_schedule",
+    "get_update_settings",
+    "get_user_badges",
+    "get_user_certificates",
+    "get_user_mentor_sessions",
+    "get_user_progress",
+    "get_user_rewards",
+    "get_user_stats",
+    "get_verification_status",
+    "get_vesting_schedule",
+    "get_voice_capabilities",
+    "get_vouches",
+    "get_wallet_performance_data",
+    "get_wallet_reputation",
+    "get_wallet_voting_power",
+    "get_webhook",
+    "get_weekly_portfolio_updates",
+    "get_weekly_report",
+    "get_weekly_reports",
+    "get_window_position",
+    "get_window_size",
+    "get_yield_farms",
+    "historical_fetch_dataset",
+    "historical_fetch_orderbooks",
+    "historical_get_cache_stats",
+    "historical_get_store_settings",
+    "indicator_evaluate_alerts",
+    "indicator_evaluate_formula",
+    "indicator_evaluate_preset",
+    "indicator_list_alerts",
+    "indicator_list_presets",
+    "indicator_validate_formula",
+    "is_feature_enabled",
+    "jobs_get_history",
+    "jobs_list",
+    "journal_attachment_usage",
+    "journal_get_attachments",
+    "jupiter_quote",
+    "kiosk_mode_enable",
+    "kiosk_mode_exit",
+    "kiosk_mode_status",
+    "ledger_get_active_device",
+    "ledger_get_device",
+    "ledger_list_devices",
+    "ledger_validate_transaction",
+    "list_alerts",
+    "list_anomaly_profiles",
+    "list_backups",
+    "list_badges",
+    "list_challenges",
+    "list_claim_drafts",
+    "list_cluster_subscriptions",
+    "list_courses",
+    "list_crash_reports",
+    "list_event_archives",
+    "list_hardware_wallets",
+    "list_insurance_providers",
+    "list_launches",
+    "list_liquidity_locks",
+    "list_log_streams",
+    "list_mentors",
+    "list_multisig_wallets",
+    "list_p2p_escrows",
+    "list_p2p_offers",
+    "list_proposals",
+    "list_rebalance_profiles",
+    "list_reclaimable_accounts",
+    "list_seasons",
+    "list_settings_profiles",
+    "list_vesting_schedules",
+    "list_webhook_delivery_logs",
+    "list_webhook_event_types",
+    "list_webhooks",
+    "list_webinars",
+    "log_message",
+    "mobile_get_all_widgets",
+    "mobile_get_cached_sync_data",
+    "mobile_get_device_policy",
+    "mobile_get_device_safety_policy",
+    "mobile_get_device_trade_history",
+    "mobile_get_devices",
+    "mobile_get_last_sync",
+    "mobile_get_sync_conflicts",
+    "mobile_get_widget_data",
+    "mobile_list_widgets",
+    "multi_wallet_get_active",
+    "multi_wallet_get_aggregated",
+    "multi_wallet_list",
+    "multi_wallet_list_groups",
+    "notifications_get_degraded_channels",
+    "notifications_get_retention",
+    "notifications_get_self_test_reports",
+    "notifications_get_self_test_schedule",
+    "notifications_list",
+    "optimizer_get_run",
+    "optimizer_get_runs",
+    "outbox_list",
+    "paper_account_list",
+    "paper_accounts_compare",
+    "phantom_balance",
+    "phantom_session",
+    "predict_launch_success",
+    "preview_rebalance",
+    "privacy_mode_status",
+    "reconnect_stream",
+    "refresh_top_coins",
+    "refresh_trending",
+    "report_crash",
+    "reports_get",
+    "reports_get_schedules",
+    "reports_list",
+    "scan_contract",
+    "search_assets",
+    "search_prediction_markets",
+    "search_stocks",
+    "search_tokens",
+    "simulate_governance_vote",
+    "simulate_token_creation",
+    "smart_alert_get_rule",
+    "smart_alert_list_packs",
+    "smart_alert_list_rules",
+    "smart_alert_preview_pack",
+    "subscribe_chart_prices",
+    "subscribe_orderbook",
+    "subscribe_price_stream",
+    "subscribe_wallet_stream",
+    "swap_history_get_recent",
+    "theme_get_os_preference",
+    "theme_get_presets",
+    "theme_get_settings",
+    "token_notes_list",
+    "tokens_by_tag",
+    "twitter_get_config",
+    "twitter_get_sentiment_history",
+    "twitter_get_stats",
+    "twitter_get_tweet_history",
+    "twitter_list_influencers",
+    "twitter_list_keywords",
+    "unsubscribe_chart_prices",
+    "unsubscribe_orderbook",
+    "unsubscribe_price_stream",
+    "unsubscribe_wallet_stream",
+    "validate_deep_link",
+    "validate_trade_thresholds",
+    "verify_backup_integrity",
+    "verify_certificate",
+    "verify_certificate_proof",
+    "verify_integrity",
+    "verify_vote_signature",
+    "voice_get_audio_status",
+    "voice_get_available_voices",
+    "voice_get_readout_config",
+    "voice_get_readout_queue",
+    "voice_get_stt_config",
+    "voice_get_supported_languages",
+    "voice_get_tts_config",
+    "voice_get_tts_status",
+    "voice_get_wake_word_config",
+    "wallet_estimate_fee",
+    "wallet_get_bridge_providers",
+    "wallet_get_token_balances",
+    "wallet_get_token_balances_batch",
+    "wallet_monitor_get_activities",
+    "wallet_monitor_get_statistics",
+    "wallet_monitor_list_cluster_candidates",
+    "wallet_monitor_list_clusters",
+    "wallet_monitor_list_wallets",
+    "watchlist_get",
+    "watchlist_list",
+];
+
+/// Commands rejected in kiosk mode, sorted. Listed so that every registered
+/// command is classified on purpose; unlisted commands are rejected too.
+pub(crate) const BLOCKED_COMMANDS: &[&str] = &[
+    "academy_export_course",
+    "academy_import_course",
+    "acknowledge_order",
+    "activate_airdrop",
+    "add_launch_training_data",
+    "add_price_data",
+    "add_to_blacklist",
+    "add_transaction_data",
+    "add_vouch",
+    "address_book_add_contact",
+    "address_book_delete_contact",
+    "address_book_export",
+    "address_book_import",
+    "address_book_update_contact",
+    "ai_apply_optimization",
+    "ai_chat",
+    "ai_chat_message",
+    "ai_chat_message_stream",
+    "ai_delete_conversation",
+    "ai_dismiss_pattern_warning",
+    "ai_execute_quick_action",
+    "ai_optimize_portfolio",
+    "ai_set_api_key",
+    "ai_submit_feedback",
+    "alert_check_triggers",
+    "alert_create",
+    "alert_delete",
+    "alert_history_delete",
+    "alert_history_export_csv",
+    "alert_history_update",
+    "alert_mark_trigger",
+    "alert_reset_cooldowns",
+    "alert_snooze",
+    "alert_test",
+    "alert_update",
+    "alerts_mute_all",
+    "alerts_mute_token",
+    "apply_portfolio_recommendation",
+    "approve_trade",
+    "assign_anomaly_profile",
+    "auto_fix_errors",
+    "auto_repair",
+    "auto_repair_issue",
+    "auto_trading_activate_kill_switch",
+    "auto_trading_apply_parameters",
+    "auto_trading_create_strategy",
+    "auto_trading_deactivate_kill_switch",
+    "auto_trading_delete_strategy",
+    "auto_trading_pause_strategy",
+    "auto_trading_start_strategy",
+    "auto_trading_stop_strategy",
+    "auto_trading_update_strategy",
+    "award_badge",
+    "backfill_bot_performance_history",
+    "backtest_delete_runs",
+    "backtest_run",
+    "backtest_set_retention",
+    "backtest_update_run",
+    "backup_before_repair",
+    "biometric_disable",
+    "biometric_enroll",
+    "biometric_verify",
+    "biometric_verify_fallback",
+    "bridge_create_transaction",
+    "bridge_poll_status",
+    "bridge_update_transaction_hash",
+    "bridge_update_transaction_status",
+    "bulk_update_settings",
+    "cancel_key_rotation",
+    "cancel_order",
+    "cancel_p2p_escrow",
+    "cancel_proposal",
+    "chain_set_active",
+    "chain_update_config",
+    "chat_integration_add_discord",
+    "chat_integration_add_slack",
+    "chat_integration_add_telegram",
+    "chat_integration_clear_delivery_logs",
+    "chat_integration_delete_discord",
+    "chat_integration_delete_slack",
+    "chat_integration_delete_telegram",
+    "chat_integration_save_settings",
+    "chat_integration_test_discord",
+    "chat_integration_test_slack",
+    "chat_integration_test_telegram",
+    "chat_integration_update_discord",
+    "chat_integration_update_slack",
+    "chat_integration_update_telegram",
+    "check_rotation_reminders",
+    "check_trade_safety",
+    "claim_airdrop_tokens",
+    "claim_all_rewards",
+    "claim_reward",
+    "classify_smart_money_wallet",
+    "cleanup_activity_logs",
+    "cleanup_health_records",
+    "clear_audit_cache",
+    "clear_cache",
+    "clear_fix_history",
+    "clear_logs",
+    "clear_portfolio_cache",
+    "close_floating_window",
+    "close_token_accounts",
+    "collab_create_room",
+    "collab_delete_message",
+    "collab_delete_room",
+    "collab_edit_message",
+    "collab_get_webrtc_signals",
+    "collab_join_competition",
+    "collab_join_room",
+    "collab_leave_room",
+    "collab_moderate_user",
+    "collab_send_message",
+    "collab_send_webrtc_signal",
+    "collab_set_competition",
+    "collab_set_message_retention",
+    "collab_set_role",
+    "collab_share_order",
+    "collab_share_strategy",
+    "collab_share_watchlist",
+    "collab_transfer_ownership",
+    "collab_unshare_order",
+    "collab_update_leaderboard",
+    "collab_update_order",
+    "collab_update_permissions",
+    "compile_now",
+    "complete_course",
+    "complete_lesson",
+    "compress_old_data",
+    "configure_auto_compound",
+    "confirm_payment_p2p",
+    "connect_hardware_wallet",
+    "connect_phantom",
+    "copy_trading_create",
+    "copy_trading_delete",
+    "copy_trading_init",
+    "copy_trading_pause",
+    "copy_trading_process_activity",
+    "copy_trading_resume",
+    "create_airdrop",
+    "create_backup",
+    "create_badge",
+    "create_challenge",
+    "create_claim_draft",
+    "create_course",
+    "create_custom_prediction",
+    "create_floating_window",
+    "create_governance_reminder",
+    "create_journal_entry",
+    "create_launch_config",
+    "create_lesson",
+    "create_liquidity_lock",
+    "create_mentor",
+    "create_mentor_session",
+    "create_multisig_wallet",
+    "create_order",
+    "create_p2p_escrow",
+    "create_p2p_offer",
+    "create_price_alert",
+    "create_proposal",
+    "create_question_bank",
+    "create_quiz",
+    "create_season",
+    "create_settings_profile",
+    "create_snapshot_command",
+    "create_stock_alert",
+    "create_trade_idea",
+    "create_vesting_schedule",
+    "create_webhook",
+    "create_webinar",
+    "dca_create",
+    "dca_delete",
+    "dca_init",
+    "dca_pause",
+    "dca_resume",
+    "decompress_data",
+    "deep_link_frontend_ready",
+    "delegate_governance_votes",
+    "delete_anomaly_profile",
+    "delete_backup",
+    "delete_journal_entry",
+    "delete_rebalance_profile",
+    "delete_settings_profile",
+    "delete_trade_idea",
+    "delete_webhook",
+    "disable_auto_start",
+    "disable_feature_flag",
+    "disconnect_hardware_wallet",
+    "dismiss_anomaly",
+    "dismiss_sentiment_alert",
+    "dismiss_update",
+    "dispose_tax_lot",
+    "download_missing",
+    "download_update",
+    "drawing_restore_version",
+    "drawing_save",
+    "drawing_save_templates",
+    "drawing_sync",
+    "email_delete_config",
+    "email_save_config",
+    "email_send",
+    "email_test_connection",
+    "enable_auto_start",
+    "enable_feature_flag",
+    "exchange_import_batches",
+    "exchange_import_commit",
+    "exchange_import_preview",
+    "execute_paper_trade",
+    "execute_proposal",
+    "execute_rebalance",
+    "execute_voice_trade",
+    "export_activity_logs",
+    "export_anomaly_baselines",
+    "export_api_keys",
+    "export_audit_trail_command",
+    "export_claim_package",
+    "export_config_settings",
+    "export_diagnostics_report",
+    "export_flow_analysis",
+    "export_holder_data",
+    "export_logs",
+    "export_metadata_snapshot",
+    "export_portfolio_state",
+    "export_settings",
+    "export_tax_center_report",
+    "export_tax_report",
+    "extract_token_features",
+    "file_p2p_dispute",
+    "force_gc",
+    "fund_p2p_escrow",
+    "generate_mock_anomaly_data",
+    "generate_portfolio_recommendation",
+    "generate_tax_report",
+    "generate_weekly_portfolio_update",
+    "generate_weekly_report",
+    "historical_clear_old_data",
+    "historical_compute_counterfactual",
+    "historical_run_simulation",
+    "historical_set_api_key",
+    "historical_update_store_settings",
+    "import_anomaly_baselines",
+    "import_api_keys",
+    "import_config_settings",
+    "import_settings",
+    "indicator_create_alert",
+    "indicator_delete_alert",
+    "indicator_delete_preset",
+    "indicator_export_preset",
+    "indicator_import_preset",
+    "indicator_save_preset",
+    "indicator_save_state",
+    "indicator_update_alert",
+    "indicator_update_preset",
+    "ingest_social_data",
+    "ingest_token_flows",
+    "initialize_token_reputation",
+    "install_update",
+    "issue_certificate",
+    "jobs_pause",
+    "jobs_resume",
+    "jobs_run_now",
+    "journal_attach_chart",
+    "journal_attach_file",
+    "journal_cleanup_attachments",
+    "journal_remove_attachment",
+    "jupiter_swap",
+    "launchpad_create_token",
+    "ledger_clear_devices",
+    "ledger_connect_device",
+    "ledger_disconnect_device",
+    "ledger_register_device",
+    "ledger_remove_device",
+    "ledger_update_device_address",
+    "link_trade_idea_order",
+    "load_latest_launch_model",
+    "load_settings_profile",
+    "manual_repair",
+    "match_p2p_offers",
+    "maximize_window",
+    "migrate_data_directory",
+    "minimize_to_tray",
+    "minimize_window",
+    "mobile_authenticate_session",
+    "mobile_configure_widget",
+    "mobile_create_biometric_challenge",
+    "mobile_dequeue_notification",
+    "mobile_execute_quick_trade",
+    "mobile_get_pending_notifications",
+    "mobile_queue_notification",
+    "mobile_register_device",
+    "mobile_register_widget",
+    "mobile_remove_device",
+    "mobile_remove_widget",
+    "mobile_resolve_sync_conflict",
+    "mobile_revoke_session",
+    "mobile_safety_checks",
+    "mobile_set_device_policy",
+    "mobile_set_device_safety_policy",
+    "mobile_sync_data",
+    "mobile_sync_delta",
+    "mobile_update_push_token",
+    "mobile_verify_biometric",
+    "multi_wallet_add",
+    "multi_wallet_add_watch_only",
+    "multi_wallet_create_group",
+    "multi_wallet_delete_group",
+    "multi_wallet_remove",
+    "multi_wallet_set_active",
+    "multi_wallet_update",
+    "multi_wallet_update_balance",
+    "multi_wallet_update_group",
+    "multi_wallet_update_performance",
+    "notifications_clear",
+    "notifications_mark_all_read",
+    "notifications_mark_read",
+    "notifications_set_retention",
+    "notifications_set_self_test_schedule",
+    "notifications_test_all_channels",
+    "optimizer_cancel",
+    "optimizer_start",
+    "outbox_discard",
+    "override_trading_hours",
+    "paper_account_create",
+    "paper_account_delete",
+    "paper_trading_init",
+    "phantom_connect",
+    "phantom_disconnect",
+    "phantom_sign_message",
+    "phantom_sign_transaction",
+    "prepare_certificate_anchor",
+    "prepare_p2p_escrow_transaction",
+    "prepare_vote_signature",
+    "prepare_vote_transaction",
+    "privacy_mode_set",
+    "privacy_mode_set_wallet",
+    "recompute_score_history",
+    "record_api_usage",
+    "record_prediction_performance",
+    "record_trade",
+    "record_webinar_attendance",
+    "register_orderbook_market",
+    "release_p2p_escrow",
+    "release_vested_tokens",
+    "remove_api_key",
+    "remove_cluster_subscription",
+    "remove_from_blacklist",
+    "remove_import_batch",
+    "remove_unlock_override",
+    "remove_vouch",
+    "replay_events_command",
+    "reports_configure_schedule",
+    "reports_generate_now",
+    "reset_config_settings",
+    "reset_cooldowns",
+    "reset_daily_limits",
+    "reset_data_directory",
+    "reset_paper_account",
+    "reset_performance_stats",
+    "reset_settings",
+    "reset_ttl_config",
+    "resolve_p2p_dispute",
+    "restart_service",
+    "restore_backup",
+    "restore_defaults",
+    "restore_event_archive",
+    "restore_from_tray",
+    "retrain_launch_model",
+    "retry_data_directory",
+    "revert_auto_fix",
+    "revoke_governance_delegation",
+    "rollback_repair",
+    "rollback_update",
+    "rotate_api_key",
+    "rotate_certificate_signing_key",
+    "run_diagnostics",
+    "run_performance_test",
+    "save_anomaly_profile",
+    "save_api_key",
+    "save_diagnostics_settings",
+    "save_rebalance_profile",
+    "save_risk_profile",
+    "save_update_settings",
+    "scan_for_new_coins",
+    "scan_wallets_for_smart_money",
+    "select_insurance",
+    "send_p2p_message",
+    "set_activity_retention",
+    "set_api_budget",
+    "set_auto_bracket_policy",
+    "set_environment",
+    "set_liquidity_lock_settings",
+    "set_logger_config",
+    "set_new_coin_screen",
+    "set_order_protection_policy",
+    "set_p2p_escrow_config",
+    "set_performance_tracing",
+    "set_stablecoin_depeg_settings",
+    "set_tax_lot_strategy",
+    "set_unlock_alert_settings",
+    "set_unlock_override",
+    "set_use_default_key",
+    "set_window_always_on_top",
+    "set_window_position",
+    "set_window_size",
+    "sign_p2p_escrow_transaction",
+    "sign_proposal",
+    "sign_with_hardware_wallet",
+    "smart_alert_create_rule",
+    "smart_alert_delete_rule",
+    "smart_alert_dry_run",
+    "smart_alert_execute",
+    "smart_alert_export_pack",
+    "smart_alert_import_pack",
+    "smart_alert_remove_pack",
+    "smart_alert_update_rule",
+    "snap_window_to_edge",
+    "start_compiler_watch",
+    "start_course",
+    "start_key_rotation",
+    "start_lesson",
+    "start_log_stream",
+    "start_paper_challenge",
+    "start_quiz_attempt",
+    "stop_compiler_watch",
+    "stop_log_stream",
+    "stress_test_positions",
+    "submit_certificate_anchor",
+    "submit_challenge",
+    "submit_dispute_evidence",
+    "submit_quiz",
+    "submit_reputation_report",
+    "submit_signed_vote",
+    "submit_token_account_closures",
+    "submit_with_mev_protection",
+    "swap_history_add_entry",
+    "swap_history_reconcile",
+    "sync_governance_memberships",
+    "sync_governance_proposals",
+    "synthesize_speech",
+    "test_api_connection",
+    "test_cache_performance",
+    "test_webhook",
+    "theme_delete_custom",
+    "theme_export",
+    "theme_import",
+    "theme_save_custom",
+    "theme_update_settings",
+    "token_notes_add",
+    "token_tags_rename",
+    "token_tags_set",
+    "trace_token_flows",
+    "track_recommendation_performance",
+    "trading_init",
+    "trigger_manual_backup",
+    "trigger_webhook",
+    "twitter_add_influencer",
+    "twitter_add_keyword",
+    "twitter_delete_config",
+    "twitter_fetch_sentiment",
+    "twitter_remove_influencer",
+    "twitter_remove_keyword",
+    "twitter_save_config",
+    "twitter_test_connection",
+    "unlock_liquidity",
+    "update_alert_config",
+    "update_anomaly_detection_config",
+    "update_auto_start_settings",
+    "update_backup_schedule",
+    "update_compression_config",
+    "update_custom_prediction",
+    "update_dev_settings",
+    "update_journal_entry",
+    "update_launch_config",
+    "update_lesson_progress",
+    "update_offer_status",
+    "update_order_prices",
+    "update_paper_position_prices",
+    "update_performance_score_weights",
+    "update_question_bank",
+    "update_reputation_settings",
+    "update_safety_policy",
+    "update_sentiment_alert_config",
+    "update_setting",
+    "update_tax_settings",
+    "update_token_metrics",
+    "update_tray_settings",
+    "update_tray_stats",
+    "update_ttl_config",
+    "update_wallet_behavior",
+    "update_webhook",
+    "upsert_cluster_subscription",
+    "validate_voice_mfa",
+    "voice_configure_readout",
+    "voice_pause_speaking",
+    "voice_process_audio_for_wake_word",
+    "voice_request_permissions",
+    "voice_resume_speaking",
+    "voice_revoke_permissions",
+    "voice_set_pitch",
+    "voice_set_rate",
+    "voice_set_stt_language",
+    "voice_set_voice",
+    "voice_set_volume",
+    "voice_simulate_transcription",
+    "voice_speak",
+    "voice_speech_finished",
+    "voice_start_microphone",
+    "voice_start_recognition",
+    "voice_start_wake_word",
+    "voice_stop_microphone",
+    "voice_stop_recognition",
+    "voice_stop_speaking",
+    "voice_stop_wake_word",
+    "voice_update_stt_config",
+    "voice_update_tts_config",
+    "voice_update_wake_word_config",
+    "vote_on_proposal",
+    "wallet_generate_qr",
+    "wallet_generate_solana_pay_qr",
+    "wallet_monitor_add_wallet",
+    "wallet_monitor_infer_clusters",
+    "wallet_monitor_init",
+    "wallet_monitor_remove_wallet",
+    "wallet_monitor_review_cluster_candidates",
+    "wallet_monitor_update_wallet",
+    "wallet_request_airdrop",
+    "wallet_send_transaction",
+    "warm_cache",
+    "watchlist_add_item",
+    "watchlist_create",
+    "watchlist_delete",
+    "watchlist_export",
+    "watchlist_import",
+    "watchlist_import_commit",
+    "watchlist_import_preview",
+    "watchlist_remove_item",
+    "watchlist_reorder_items",
+    "watchlist_update",
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    fn registered_commands() -> BTreeSet<&'static str> {
+        let source = include_str!("../lib.rs");
+        let start = source
+            .find("tauri::generate_handler![")
+            .expect("lib.rs registers commands");
+        let body = &source[start..];
+        let body = &body[body.find('[').unwrap() + 1..body.find("];").unwrap()];
+        body.lines()
+            .map(|line| {
+                line.split("//")
+                    .next()
+                    .unwrap()
+                    .trim()
+                    .trim_end_matches(',')
+            })
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| entry.rsplit("::").next().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_lists_are_sorted_and_disjoint() {
+        for list in [READ_ONLY_COMMANDS, BLOCKED_COMMANDS] {
+            assert!(list.windows(2).all(|pair| pair[0] < pair[1]));
+        }
+        for command in READ_ONLY_COMMANDS {
+            assert!(
+                BLOCKED_COMMANDS.binary_search(command).is_err(),
+                "{command}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_every_registered_command_is_classified() {
+        let registered = registered_commands();
+        assert!(registered.contains("kiosk_mode_exit"));
+        for command in &registered {
+            assert!(
+                READ_ONLY_COMMANDS.binary_search(command).is_ok()
+                    || BLOCKED_COMMANDS.binary_search(command).is_ok(),
+                "'{command}' is registered but not classified for kiosk mode"
+            );
+        }
+        for command in READ_ONLY_COMMANDS.iter().chain(BLOCKED_COMMANDS) {
+            assert!(
+                registered.contains(command),
+                "'{command}' is classified but not registered"
+            );
+        }
+    }
+}
@@ -11,6 +11,8 @@ pub mod keystore;
 pub mod audit;
 pub mod activity_log;
 pub mod reputation;
+pub mod kiosk;
+pub mod kiosk_commands;
 
 pub use types::*;
 pub use audit_logger::AuditLogger;
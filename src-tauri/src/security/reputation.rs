@@ -1,3 +1,4 @@
+use crate::config::DataPaths;
 use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::{Pool, Row, Sqlite, SqlitePool};
@@ -8,10 +9,19 @@ use tauri::{AppHandle, Manager};
 use tokio::sync::RwLock;
 
 const REPUTATION_DB_FILE: &str = "reputation.db";
+/// Share of a wallet's trust score that comes from its P2P conduct.
+const P2P_SCORE_WEIGHT: f64 = 0.3;
 
 // Shared type for the reputation engine state
 pub type SharedReputationEngine = Arc<RwLock<ReputationEngine>>;
 
+fn blend_p2p_score(behavior_score: f64, p2p_score: Option<f64>) -> f64 {
+    match p2p_score {
+        Some(p2p) => behavior_score * (1.0 - P2P_SCORE_WEIGHT) + p2p * P2P_SCORE_WEIGHT,
+        None => behavior_score,
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum ReputationLevel {
@@ -50,6 +60,9 @@ pub struct WalletReputation {
     pub total_volume: f64,
     pub age_days: i64,
     pub risk_flags: Vec<String>,
+    /// Score earned from P2P marketplace conduct, blended into `trust_score`.
+    #[serde(default)]
+    pub p2p_score: Option<f64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -161,7 +174,7 @@ pub struct ReputationEngine {
 
 impl ReputationEngine {
     pub async fn new(app_handle: &AppHandle) -> Result<Self, ReputationError> {
-        let app_dir = app_handle.path().app_data_dir().map_err(|err| {
+        let app_dir = app_handle.app_data_root().map_err(|err| {
             ReputationError::Io(std::io::Error::new(
                 std::io::ErrorKind::NotFound,
                 "Unable to resolve app data directory",
@@ -228,6 +241,18 @@ impl ReputationEngine {
         .execute(&pool)
         .await?;
 
+        let wallet_columns = sqlx::query("PRAGMA table_info(wallet_reputation)")
+            .fetch_all(&pool)
+            .await?;
+        if !wallet_columns
+            .iter()
+            .any(|row| row.get::<String, _>("name") == "p2p_score")
+        {
+            sqlx::query("ALTER TABLE wallet_reputation ADD COLUMN p2p_score REAL")
+                .execute(&pool)
+                .await?;
+        }
+
         sqlx::query(
             r#"
             CREATE TABLE IF NOT EXISTS token_reputation (
@@ -360,7 +385,7 @@ impl ReputationEngine {
             r#"
             SELECT address, trust_score, vouches_received, vouches_given, is_blacklisted,
                    blacklist_reason, first_seen, last_updated, transaction_count, total_volume,
-                   age_days, risk_flags
+                   age_days, risk_flags, p2p_score
             FROM wallet_reputation
             WHERE address = ?
             "#,
@@ -397,6 +422,7 @@ impl ReputationEngine {
                     total_volume: row.get("total_volume"),
                     age_days: row.get("age_days"),
                     risk_flags,
+                    p2p_score: row.get("p2p_score"),
                 })
             }
             None => {
@@ -417,6 +443,7 @@ impl ReputationEngine {
                     total_volume: 0.0,
                     age_days: 0,
                     risk_flags: vec![],
+                    p2p_score: None,
                 })
             }
         }
@@ -455,11 +482,14 @@ impl ReputationEngine {
         let new_age = age_days.unwrap_or(current.age_days);
 
         // Calculate new trust score based on behavior
-        let trust_score = self.calculate_wallet_trust_score(
-            new_tx_count,
-            new_volume,
-            new_age,
-            current.vouches_received,
+        let trust_score = blend_p2p_score(
+            self.calculate_wallet_trust_score(
+                new_tx_count,
+                new_volume,
+                new_age,
+                current.vouches_received,
+            ),
+            current.p2p_score,
         );
 
         let now = Utc::now().to_rfc3339();
@@ -485,6 +515,50 @@ impl ReputationEngine {
         Ok(())
     }
 
+    /// Stores a wallet's P2P reputation and re-blends its trust score, logging
+    /// the change under `event_type`. Blacklisted wallets keep a zero score.
+    pub async fn record_p2p_reputation(
+        &self,
+        address: &str,
+        p2p_score: f64,
+        event_type: &str,
+        details: Option<&str>,
+    ) -> Result<f64, ReputationError> {
+        let current = self.get_wallet_reputation(address).await?;
+        let trust_score = if current.is_blacklisted {
+            0.0
+        } else {
+            blend_p2p_score(
+                self.calculate_wallet_trust_score(
+                    current.transaction_count,
+                    current.total_volume,
+                    current.age_days,
+                    current.vouches_received,
+                ),
+                Some(p2p_score),
+            )
+        };
+
+        let now = Utc::now().to_rfc3339();
+        sqlx::query(
+            r#"
+            UPDATE wallet_reputation
+            SET p2p_score = ?, trust_score = ?, last_updated = ?
+            WHERE address = ?
+            "#,
+        )
+        .bind(p2p_score)
+        .bind(trust_score)
+        .bind(&now)
+        .bind(address)
+        .execute(&self.pool)
+        .await?;
+
+        self.record_history(address, trust_score, event_type, details)
+            .await?;
+        Ok(trust_score)
+    }
+
     fn calculate_wallet_trust_score(
         &self,
         transaction_count: i64,
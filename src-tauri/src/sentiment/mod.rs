@@ -432,6 +432,8 @@ pub async fn fetch_social_mentions(token_address: String) -> Result<Vec<SocialPo
                 timestamp: now - (i as i64 * 3600),
                 sentiment,
                 engagement: (100 + i * 50) as i32,
+                author_followers: None,
+                author_created_at: None,
             }
         })
         .collect();
@@ -476,6 +478,8 @@ mod tests {
             timestamp: Utc::now().timestamp(),
             sentiment: analyze_sentiment("Great project!"),
             engagement: 100,
+            author_followers: None,
+            author_created_at: None,
         }];
 
         manager.add_sentiment_data(token_address.clone(), posts);
@@ -501,6 +505,8 @@ mod tests {
             timestamp: Utc::now().timestamp(),
             sentiment: analyze_sentiment("Amazing! Great! Excellent! Bullish! Moon! Rocket!"),
             engagement: 100,
+            author_followers: None,
+            author_created_at: None,
         }];
 
         manager.add_sentiment_data(token_address.clone(), posts);
@@ -2,6 +2,7 @@ pub mod gauges;
 pub mod influencer;
 pub mod sentiment_engine;
 pub mod service;
+pub mod spike_engine;
 pub mod trend_engine;
 
 pub use gauges::{GaugeEngine, GaugeReading};
@@ -10,4 +11,8 @@ pub use sentiment_engine::{LexiconEntry, SentimentEngine, SentimentSnapshot};
 pub use service::{
     AnalysisError, AnalysisSummary, SharedSocialAnalysisService, SocialAnalysisService,
 };
+pub use spike_engine::{
+    AccountAgeDistribution, SocialSpike, SocialSpikeEvidence, SpikeEngine, SpikePattern, SpikePost,
+    SpikeThresholds,
+};
 pub use trend_engine::{TrendEngine, TrendRecord, DEFAULT_WINDOWS};
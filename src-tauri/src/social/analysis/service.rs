@@ -1,3 +1,4 @@
+use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use serde_json;
 use sqlx::Row;
@@ -7,6 +8,7 @@ use tokio::sync::RwLock;
 use super::gauges::{GaugeEngine, GaugeReading};
 use super::influencer::{InfluencerEngine, InfluencerScore};
 use super::sentiment_engine::{SentimentEngine, SentimentSnapshot};
+use super::spike_engine::{SocialSpike, SpikeEngine, SpikeThresholds};
 use super::trend_engine::{TrendEngine, TrendRecord, DEFAULT_WINDOWS};
 use crate::social::cache::SocialCache;
 use crate::social::models::SocialPost;
@@ -36,6 +38,7 @@ pub struct SocialAnalysisService {
     trend_engine: TrendEngine,
     influencer_engine: InfluencerEngine,
     gauge_engine: GaugeEngine,
+    spike_engine: SpikeEngine,
     cache: SocialCache,
 }
 
@@ -45,12 +48,14 @@ impl SocialAnalysisService {
         let trend_engine = TrendEngine::new(DEFAULT_WINDOWS.to_vec());
         let influencer_engine = InfluencerEngine::default();
         let gauge_engine = GaugeEngine::new();
+        let spike_engine = SpikeEngine::new(SpikeThresholds::default());
 
         Self {
             sentiment_engine,
             trend_engine,
             influencer_engine,
             gauge_engine,
+            spike_engine,
             cache,
         }
     }
@@ -248,4 +253,28 @@ impl SocialAnalysisService {
         let pool = self.cache.pool();
         Ok(self.gauge_engine.fetch_gauges(pool, token).await?)
     }
+
+    /// Checks each token for a mention spike, recording the ones found.
+    pub async fn detect_social_spikes(
+        &mut self,
+        tokens: &[String],
+    ) -> Result<Vec<SocialSpike>, AnalysisError> {
+        let pool = self.cache.pool();
+        let now = Utc::now().timestamp();
+        let mut spikes = Vec::new();
+        for token in tokens {
+            if let Some(spike) = self.spike_engine.detect(pool, token, now).await? {
+                spikes.push(spike);
+            }
+        }
+        Ok(spikes)
+    }
+
+    pub async fn get_social_spike_history(
+        &self,
+        token: &str,
+    ) -> Result<Vec<SocialSpike>, AnalysisError> {
+        let pool = self.cache.pool();
+        Ok(self.spike_engine.fetch_history(pool, token).await?)
+    }
 }
@@ -0,0 +1,488 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{Row, SqlitePool};
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+
+use crate::social::models::SocialPost;
+
+const HOUR: i64 = 3600;
+const DAY: i64 = 86400;
+const TOP_POSTS: usize = 5;
+const MAX_POST_CHARS: usize = 280;
+
+/// Spikes with sharply rising sentiment have tended to run ahead of pumps,
+/// sharply falling sentiment ahead of dumps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpikePattern {
+    PrePump,
+    PreDump,
+}
+
+impl SpikePattern {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SpikePattern::PrePump => "pre_pump",
+            SpikePattern::PreDump => "pre_dump",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SpikeThresholds {
+    /// Trailing window each token's own baseline is computed over.
+    pub baseline_days: i64,
+    /// Baseline history a token needs before it is evaluated.
+    pub min_baseline_hours: i64,
+    /// Tokens with fewer baseline mentions or authors have too little social
+    /// footprint to judge and are skipped.
+    pub min_baseline_mentions: usize,
+    pub min_baseline_authors: usize,
+    pub min_spike_mentions: usize,
+    /// Mentions in the last hour over the baseline hourly rate.
+    pub velocity_ratio: f64,
+    /// Change in average sentiment that marks a pre-pump or pre-dump spike.
+    pub sentiment_shift: f64,
+    pub low_follower_count: i64,
+    pub new_account_days: i64,
+    /// Share of spike authors that are new, low-follower accounts for the
+    /// spike to count as coordinated shilling.
+    pub coordinated_share: f64,
+    /// Authors with a known follower count and account age needed before
+    /// the shilling heuristic applies.
+    pub min_known_authors: usize,
+    /// A token is not flagged again within this period unless the spike
+    /// escalates.
+    pub cooldown_hours: i64,
+}
+
+impl Default for SpikeThresholds {
+    fn default() -> Self {
+        Self {
+            baseline_days: 7,
+            min_baseline_hours: 12,
+            min_baseline_mentions: 24,
+            min_baseline_authors: 5,
+            min_spike_mentions: 10,
+            velocity_ratio: 3.0,
+            sentiment_shift: 0.25,
+            low_follower_count: 500,
+            new_account_days: 30,
+            coordinated_share: 0.5,
+            min_known_authors: 5,
+            cooldown_hours: 6,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpikePost {
+    pub id: String,
+    pub source: String,
+    pub author: String,
+    pub text: String,
+    pub engagement: i32,
+    pub sentiment: f32,
+    pub author_followers: Option<i64>,
+    pub author_age_days: Option<i64>,
+}
+
+/// Distinct spike authors bucketed by account age.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountAgeDistribution {
+    pub under_7_days: usize,
+    pub under_30_days: usize,
+    pub under_180_days: usize,
+    pub older: usize,
+    pub unknown: usize,
+}
+
+impl AccountAgeDistribution {
+    fn add(&mut self, age_days: Option<i64>) {
+        match age_days {
+            None => self.unknown += 1,
+            Some(days) if days < 7 => self.under_7_days += 1,
+            Some(days) if days < 30 => self.under_30_days += 1,
+            Some(days) if days < 180 => self.under_180_days += 1,
+            Some(_) => self.older += 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SocialSpikeEvidence {
+    /// `None` when only the shilling heuristic fired.
+    pub pattern: Option<SpikePattern>,
+    pub mentions_last_hour: usize,
+    pub baseline_per_hour: f64,
+    pub velocity_ratio: f64,
+    /// Ratio the spike had to reach to be considered.
+    pub velocity_threshold: f64,
+    pub sentiment_baseline: f64,
+    pub sentiment_now: f64,
+    pub sentiment_shift: f64,
+    pub distinct_authors: usize,
+    pub coordinated_shilling: bool,
+    /// Share of authors with known metadata that are new, low-follower
+    /// accounts.
+    pub new_low_follower_share: f64,
+    pub account_ages: AccountAgeDistribution,
+    pub top_posts: Vec<SpikePost>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SocialSpike {
+    pub id: String,
+    pub token: String,
+    pub detected_at: i64,
+    pub severity: String,
+    pub evidence: SocialSpikeEvidence,
+}
+
+fn severity_rank(severity: &str) -> u8 {
+    match severity {
+        "high" => 2,
+        _ => 1,
+    }
+}
+
+fn mean_sentiment(posts: &[&SocialPost]) -> f64 {
+    if posts.is_empty() {
+        return 0.0;
+    }
+    posts
+        .iter()
+        .map(|post| post.sentiment.score as f64)
+        .sum::<f64>()
+        / posts.len() as f64
+}
+
+fn account_age_days(post: &SocialPost, now: i64) -> Option<i64> {
+    post.author_created_at
+        .map(|created| (now - created).max(0) / DAY)
+}
+
+fn spike_post(post: &SocialPost, now: i64) -> SpikePost {
+    SpikePost {
+        id: post.id.clone(),
+        source: post.source.clone(),
+        author: post.author.clone(),
+        text: post.text.chars().take(MAX_POST_CHARS).collect(),
+        engagement: post.engagement,
+        sentiment: post.sentiment.score,
+        author_followers: post.author_followers,
+        author_age_days: account_age_days(post, now),
+    }
+}
+
+/// Flags mention spikes per token against that token's own trailing
+/// baseline.
+pub struct SpikeEngine {
+    thresholds: SpikeThresholds,
+    /// Time and severity rank of each token's last flag.
+    last_flagged: HashMap<String, (i64, u8)>,
+}
+
+impl SpikeEngine {
+    pub fn new(thresholds: SpikeThresholds) -> Self {
+        Self {
+            thresholds,
+            last_flagged: HashMap::new(),
+        }
+    }
+
+    /// Judges the last hour of `posts` against the baseline before it.
+    /// Returns `None` for tokens with too little footprint, for spikes that
+    /// match no pre-pump, pre-dump or shilling pattern, and for repeats
+    /// within the cooldown.
+    pub fn evaluate(&mut self, token: &str, posts: &[SocialPost], now: i64) -> Option<SocialSpike> {
+        let t = &self.thresholds;
+        let spike_start = now - HOUR;
+        let baseline_start = now - t.baseline_days * DAY;
+        let (recent, history): (Vec<&SocialPost>, Vec<&SocialPost>) = posts
+            .iter()
+            .filter(|post| post.timestamp >= baseline_start && post.timestamp <= now)
+            .partition(|post| post.timestamp > spike_start);
+
+        let history_authors: HashSet<&str> =
+            history.iter().map(|post| post.author.as_str()).collect();
+        if history.len() < t.min_baseline_mentions || history_authors.len() < t.min_baseline_authors
+        {
+            return None;
+        }
+        let earliest = history.iter().map(|post| post.timestamp).min()?;
+        let baseline_hours = (spike_start - earliest) as f64 / HOUR as f64;
+        if baseline_hours < t.min_baseline_hours as f64 {
+            return None;
+        }
+        let baseline_per_hour = history.len() as f64 / baseline_hours;
+        let velocity_ratio = recent.len() as f64 / baseline_per_hour;
+        if recent.len() < t.min_spike_mentions || velocity_ratio < t.velocity_ratio {
+            return None;
+        }
+
+        let sentiment_baseline = mean_sentiment(&history);
+        let sentiment_now = mean_sentiment(&recent);
+        let sentiment_shift = sentiment_now - sentiment_baseline;
+        let pattern = if sentiment_shift >= t.sentiment_shift {
+            Some(SpikePattern::PrePump)
+        } else if sentiment_shift <= -t.sentiment_shift {
+            Some(SpikePattern::PreDump)
+        } else {
+            None
+        };
+
+        let mut authors: HashMap<&str, &SocialPost> = HashMap::new();
+        for &post in &recent {
+            authors.entry(post.author.as_str()).or_insert(post);
+        }
+        let mut account_ages = AccountAgeDistribution::default();
+        let mut known = 0usize;
+        let mut suspicious = 0usize;
+        for post in authors.values() {
+            let age_days = account_age_days(post, now);
+            account_ages.add(age_days);
+            if let (Some(followers), Some(age)) = (post.author_followers, age_days) {
+                known += 1;
+                if followers < t.low_follower_count && age < t.new_account_days {
+                    suspicious += 1;
+                }
+            }
+        }
+        let new_low_follower_share = if known == 0 {
+            0.0
+        } else {
+            suspicious as f64 / known as f64
+        };
+        let coordinated_shilling =
+            known >= t.min_known_authors && new_low_follower_share >= t.coordinated_share;
+        if pattern.is_none() && !coordinated_shilling {
+            return None;
+        }
+
+        let severity = if coordinated_shilling || velocity_ratio >= t.velocity_ratio * 2.0 {
+            "high"
+        } else {
+            "medium"
+        };
+        let rank = severity_rank(severity);
+        if let Some((at, last_rank)) = self.last_flagged.get(token) {
+            if now - at < t.cooldown_hours * HOUR && rank <= *last_rank {
+                return None;
+            }
+        }
+
+        let mut top = recent.clone();
+        top.sort_by_key(|post| Reverse(post.engagement));
+        let top_posts = top
+            .into_iter()
+            .take(TOP_POSTS)
+            .map(|post| spike_post(post, now))
+            .collect();
+
+        let spike = SocialSpike {
+            id: uuid::Uuid::new_v4().to_string(),
+            token: token.to_string(),
+            detected_at: now,
+            severity: severity.to_string(),
+            evidence: SocialSpikeEvidence {
+                pattern,
+                mentions_last_hour: recent.len(),
+                baseline_per_hour,
+                velocity_ratio,
+                velocity_threshold: t.velocity_ratio,
+                sentiment_baseline,
+                sentiment_now,
+                sentiment_shift,
+                distinct_authors: authors.len(),
+                coordinated_shilling,
+                new_low_follower_share,
+                account_ages,
+                top_posts,
+            },
+        };
+        self.last_flagged.insert(token.to_string(), (now, rank));
+        Some(spike)
+    }
+
+    /// Evaluates `token` against its cached posts and records any spike.
+    pub async fn detect(
+        &mut self,
+        pool: &SqlitePool,
+        token: &str,
+        now: i64,
+    ) -> Result<Option<SocialSpike>, sqlx::Error> {
+        if !self.last_flagged.contains_key(token) {
+            if let Some(last) = self.fetch_history(pool, token).await?.first() {
+                self.last_flagged.insert(
+                    token.to_string(),
+                    (last.detected_at, severity_rank(&last.severity)),
+                );
+            }
+        }
+
+        let cutoff = now - self.thresholds.baseline_days * DAY;
+        let rows =
+            sqlx::query("SELECT post_data FROM social_posts WHERE token = ?1 AND timestamp >= ?2")
+                .bind(token)
+                .bind(cutoff)
+                .fetch_all(pool)
+                .await?;
+
+        let mut posts = Vec::with_capacity(rows.len());
+        for row in rows {
+            let data: String = row.try_get("post_data")?;
+            let post: SocialPost =
+                serde_json::from_str(&data).map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+            posts.push(post);
+        }
+
+        let Some(spike) = self.evaluate(token, &posts, now) else {
+            return Ok(None);
+        };
+        let data = serde_json::to_string(&spike).map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+        sqlx::query(
+            "INSERT INTO social_spikes (id, token, detected_at, spike_data) VALUES (?1, ?2, ?3, ?4)",
+        )
+        .bind(&spike.id)
+        .bind(token)
+        .bind(spike.detected_at)
+        .bind(data)
+        .execute(pool)
+        .await?;
+        Ok(Some(spike))
+    }
+
+    /// Recorded spikes for a token, newest first.
+    pub async fn fetch_history(
+        &self,
+        pool: &SqlitePool,
+        token: &str,
+    ) -> Result<Vec<SocialSpike>, sqlx::Error> {
+        let rows = sqlx::query(
+            "SELECT spike_data FROM social_spikes WHERE token = ?1 ORDER BY detected_at DESC",
+        )
+        .bind(token)
+        .fetch_all(pool)
+        .await?;
+
+        let mut spikes = Vec::with_capacity(rows.len());
+        for row in rows {
+            let data: String = row.try_get("spike_data")?;
+            spikes.push(serde_json::from_str(&data).map_err(|e| sqlx::Error::Decode(Box::new(e)))?);
+        }
+        Ok(spikes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::social::models::SentimentResult;
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn post(id: usize, author: &str, timestamp: i64, score: f32) -> SocialPost {
+        SocialPost {
+            id: format!("post_{}", id),
+            text: format!("mention {}", id),
+            source: "twitter".to_string(),
+            author: author.to_string(),
+            timestamp,
+            sentiment: SentimentResult {
+                score,
+                label: "neutral".to_string(),
+                confidence: 0.5,
+            },
+            engagement: id as i32,
+            author_followers: None,
+            author_created_at: None,
+        }
+    }
+
+    /// One mention every two hours over the last two days from a handful of
+    /// regulars.
+    fn baseline() -> Vec<SocialPost> {
+        (0..24)
+            .map(|i| {
+                let author = format!("regular_{}", i % 6);
+                post(i, &author, NOW - 2 * HOUR - i as i64 * 2 * HOUR, 0.0)
+            })
+            .collect()
+    }
+
+    fn burst(count: usize, score: f32) -> Vec<SocialPost> {
+        (0..count)
+            .map(|i| {
+                let author = format!("burst_{}", i);
+                post(100 + i, &author, NOW - 60 * i as i64, score)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn flags_positive_spike_once_per_cooldown() {
+        let mut engine = SpikeEngine::new(SpikeThresholds::default());
+        let mut posts = baseline();
+        posts.extend(burst(12, 0.6));
+
+        let spike = engine.evaluate("bonk", &posts, NOW).unwrap();
+        assert_eq!(spike.evidence.pattern, Some(SpikePattern::PrePump));
+        assert_eq!(spike.evidence.mentions_last_hour, 12);
+        assert!(spike.evidence.velocity_ratio > 20.0);
+        assert_eq!(spike.severity, "high");
+        assert_eq!(spike.evidence.top_posts.len(), TOP_POSTS);
+        assert_eq!(spike.evidence.top_posts[0].id, "post_111");
+        assert_eq!(spike.evidence.account_ages.unknown, 12);
+        assert!(engine.evaluate("bonk", &posts, NOW + 60).is_none());
+
+        let neutral = {
+            let mut posts = baseline();
+            posts.extend(burst(12, 0.05));
+            posts
+        };
+        assert!(engine.evaluate("wif", &neutral, NOW).is_none());
+    }
+
+    #[test]
+    fn skips_tokens_without_social_footprint() {
+        let mut engine = SpikeEngine::new(SpikeThresholds::default());
+        let mut posts: Vec<SocialPost> = baseline().into_iter().take(10).collect();
+        posts.extend(burst(40, 0.8));
+        assert!(engine.evaluate("quiet", &posts, NOW).is_none());
+
+        let mut few_authors: Vec<SocialPost> = baseline()
+            .into_iter()
+            .map(|mut post| {
+                post.author = "only_fan".to_string();
+                post
+            })
+            .collect();
+        few_authors.extend(burst(40, 0.8));
+        assert!(engine.evaluate("quiet", &few_authors, NOW).is_none());
+    }
+
+    #[test]
+    fn detects_coordinated_shilling_from_new_low_follower_accounts() {
+        let mut engine = SpikeEngine::new(SpikeThresholds::default());
+        let mut posts = baseline();
+        let mut shills = burst(12, 0.1);
+        for (i, post) in shills.iter_mut().enumerate() {
+            post.author_followers = Some(if i < 9 { 40 } else { 12_000 });
+            post.author_created_at = Some(if i < 9 {
+                NOW - 3 * DAY
+            } else {
+                NOW - 900 * DAY
+            });
+        }
+        posts.extend(shills);
+
+        let spike = engine.evaluate("shill", &posts, NOW).unwrap();
+        assert!(spike.evidence.coordinated_shilling);
+        assert_eq!(spike.evidence.pattern, None);
+        assert_eq!(spike.severity, "high");
+        assert_eq!(spike.evidence.account_ages.under_7_days, 9);
+        assert_eq!(spike.evidence.account_ages.older, 3);
+        assert!((spike.evidence.new_low_follower_share - 0.75).abs() < 1e-9);
+    }
+}
@@ -204,12 +204,19 @@ impl SocialCache {
                 drivers TEXT NOT NULL,
                 updated_at INTEGER NOT NULL
             );
+            CREATE TABLE IF NOT EXISTS social_spikes (
+                id TEXT PRIMARY KEY,
+                token TEXT NOT NULL,
+                detected_at INTEGER NOT NULL,
+                spike_data TEXT NOT NULL
+            );
             CREATE INDEX IF NOT EXISTS idx_sentiment_scores_token_time ON sentiment_scores(token, timestamp);
             CREATE INDEX IF NOT EXISTS idx_sentiment_scores_label ON sentiment_scores(label);
             CREATE INDEX IF NOT EXISTS idx_social_trends_token ON social_trends(token);
             CREATE INDEX IF NOT EXISTS idx_social_trends_updated ON social_trends(updated_at);
             CREATE INDEX IF NOT EXISTS idx_social_influencer_scores_impact ON social_influencer_scores(impact_score);
             CREATE INDEX IF NOT EXISTS idx_social_gauges_token ON social_gauges(token);
+            CREATE INDEX IF NOT EXISTS idx_social_spikes_token_time ON social_spikes(token, detected_at);
             CREATE INDEX IF NOT EXISTS idx_sentiment_lexicon_category ON sentiment_lexicon(category);
             "#,
         )
@@ -18,6 +18,12 @@ pub struct SocialPost {
     pub timestamp: i64,
     pub sentiment: SentimentResult,
     pub engagement: i32,
+    /// Follower count of the author, when the platform reports it.
+    #[serde(default)]
+    pub author_followers: Option<i64>,
+    /// Unix timestamp the author's account was created, when known.
+    #[serde(default)]
+    pub author_created_at: Option<i64>,
 }
 
 /// Rate limit information from social platforms
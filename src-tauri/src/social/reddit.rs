@@ -1,15 +1,12 @@
 use chrono::Utc;
-use reqwest::Client;
 use serde::{Deserialize, Serialize};
-use std::time::Duration;
 
+use crate::api::http_client::{ProviderClient, ProviderProfile};
+use crate::api_analytics::ApiBudgetError;
 use crate::sentiment::analyze_sentiment;
 
 use super::models::{FetchMetadata, RateLimitInfo, SocialFetchResult, SocialPost};
 
-const REDDIT_BASE_URL: &str = "https://www.reddit.com";
-const USER_AGENT: &str = "eclipse-market-pro:v0.1.0";
-
 #[derive(Debug, Deserialize)]
 struct RedditResponse {
     data: RedditListingData,
@@ -41,8 +38,8 @@ struct RedditPost {
 
 #[derive(Debug, thiserror::Error)]
 pub enum RedditError {
-    #[error("http error: {0}")]
-    Http(#[from] reqwest::Error),
+    #[error(transparent)]
+    Request(#[from] ApiBudgetError),
     #[error("rate limit exceeded")]
     RateLimitExceeded,
     #[error("parse error: {0}")]
@@ -50,30 +47,22 @@ pub enum RedditError {
 }
 
 pub struct RedditClient {
-    client: Client,
-    base_url: String,
+    http: ProviderClient,
 }
 
 impl RedditClient {
     pub fn new() -> Result<Self, RedditError> {
-        Self::with_base_url(REDDIT_BASE_URL)
+        Self::with_base_url(ProviderProfile::REDDIT.base_url)
     }
 
     pub fn with_base_url(base_url: impl Into<String>) -> Result<Self, RedditError> {
-        let client = Self::build_client()?;
         Ok(Self {
-            client,
-            base_url: base_url.into(),
+            http: ProviderClient::builder(ProviderProfile::REDDIT)
+                .base_url(base_url)
+                .build(),
         })
     }
 
-    fn build_client() -> Result<Client, RedditError> {
-        Ok(Client::builder()
-            .user_agent(USER_AGENT)
-            .timeout(Duration::from_secs(10))
-            .build()?)
-    }
-
     pub async fn fetch_subreddit_posts(
         &self,
         subreddit: &str,
@@ -85,19 +74,18 @@ impl RedditClient {
         let url = match query {
             Some(q) => {
                 format!(
-                    "{}/r/{}/search.json?q={}&restrict_sr=1&limit={}&sort=new",
-                    self.base_url,
+                    "/r/{}/search.json?q={}&restrict_sr=1&limit={}&sort=new",
                     subreddit,
                     urlencoding::encode(q),
                     limit
                 )
             }
             None => {
-                format!("{}/r/{}/new.json?limit={}", self.base_url, subreddit, limit)
+                format!("/r/{}/new.json?limit={}", subreddit, limit)
             }
         };
 
-        let response = self.client.get(&url).send().await?;
+        let response = self.http.get(&url).send().await?;
 
         let rate_limit = extract_rate_limit_info(&response);
 
@@ -174,6 +162,8 @@ fn normalize_reddit_post(post: RedditPost) -> SocialPost {
         timestamp: post.created_utc as i64,
         sentiment,
         engagement,
+        author_followers: None,
+        author_created_at: None,
     }
 }
 
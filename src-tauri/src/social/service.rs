@@ -1,6 +1,7 @@
 use std::sync::Arc;
 
-use tauri::{AppHandle, Manager};
+use crate::config::DataPaths;
+use tauri::AppHandle;
 use tokio::sync::RwLock;
 
 use crate::security::keystore::Keystore;
@@ -24,7 +25,7 @@ impl SocialDataService {
         let reddit_client = RedditClient::new().map_err(SocialError::from)?;
         let twitter_client = TwitterClient::new().map_err(SocialError::from)?;
 
-        let mut data_dir = app.path().app_data_dir().map_err(|err| {
+        let mut data_dir = app.app_data_root().map_err(|err| {
             SocialError::Internal("Failed to resolve app data directory".to_string())
         })?;
 
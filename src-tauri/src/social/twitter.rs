@@ -1,16 +1,16 @@
 use chrono::Utc;
-use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
-use std::time::Duration;
 use tokio::sync::{RwLock, Semaphore};
 
+use crate::api::http_client::{ProviderClient, ProviderProfile};
+use crate::api_analytics::ApiBudgetError;
 use crate::security::keystore::Keystore;
 use crate::sentiment::analyze_sentiment;
 
 use super::models::{FetchMetadata, RateLimitInfo, SocialFetchResult, SocialPost};
 
-const TWITTER_API_BASE: &str = "https://api.twitter.com/2";
 const KEY_TWITTER_BEARER: &str = "twitter_bearer_token";
 const RATE_LIMIT_WINDOW_SECS: u64 = 900;
 const MAX_REQUESTS_PER_WINDOW: usize = 450;
@@ -18,9 +18,28 @@ const MAX_REQUESTS_PER_WINDOW: usize = 450;
 #[derive(Debug, Deserialize)]
 struct TwitterSearchResponse {
     data: Option<Vec<TwitterTweet>>,
+    includes: Option<TwitterIncludes>,
     meta: TwitterSearchMeta,
 }
 
+#[derive(Debug, Deserialize)]
+struct TwitterIncludes {
+    #[serde(default)]
+    users: Vec<TwitterUser>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TwitterUser {
+    id: String,
+    created_at: Option<String>,
+    public_metrics: Option<TwitterUserMetrics>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TwitterUserMetrics {
+    followers_count: i64,
+}
+
 #[derive(Debug, Deserialize)]
 struct TwitterTweet {
     id: String,
@@ -47,8 +66,8 @@ struct TwitterSearchMeta {
 
 #[derive(Debug, thiserror::Error)]
 pub enum TwitterError {
-    #[error("http error: {0}")]
-    Http(#[from] reqwest::Error),
+    #[error(transparent)]
+    Request(#[from] ApiBudgetError),
     #[error("rate limit exceeded")]
     RateLimitExceeded,
     #[error("authentication failed: {0}")]
@@ -60,16 +79,15 @@ pub enum TwitterError {
 }
 
 pub struct TwitterClient {
-    client: Client,
+    http: ProviderClient,
     rate_limiter: Arc<Semaphore>,
     last_reset: Arc<RwLock<std::time::Instant>>,
-    base_url: String,
     max_requests_per_window: usize,
 }
 
 impl TwitterClient {
     pub fn new() -> Result<Self, TwitterError> {
-        Self::with_base_url_and_limit(TWITTER_API_BASE, MAX_REQUESTS_PER_WINDOW)
+        Self::with_base_url_and_limit(ProviderProfile::TWITTER.base_url, MAX_REQUESTS_PER_WINDOW)
     }
 
     pub fn with_base_url_and_limit(
@@ -77,16 +95,12 @@ impl TwitterClient {
         max_requests_per_window: usize,
     ) -> Result<Self, TwitterError> {
         let max_requests_per_window = max_requests_per_window.max(1);
-        let client = Client::builder()
-            .user_agent("eclipse-market-pro/0.1.0")
-            .timeout(Duration::from_secs(10))
-            .build()?;
-
         Ok(Self {
-            client,
+            http: ProviderClient::builder(ProviderProfile::TWITTER)
+                .base_url(base_url)
+                .build(),
             rate_limiter: Arc::new(Semaphore::new(max_requests_per_window)),
             last_reset: Arc::new(RwLock::new(std::time::Instant::now())),
-            base_url: base_url.into(),
             max_requests_per_window,
         })
     }
@@ -126,16 +140,16 @@ impl TwitterClient {
 
         let max_results = max_results.unwrap_or(10).min(100);
 
-        let url = format!("{}/tweets/search/recent", self.base_url);
-
         let response = self
-            .client
-            .get(&url)
+            .http
+            .get("/tweets/search/recent")
             .bearer_auth(bearer_token)
             .query(&[
                 ("query", query),
                 ("max_results", &max_results.to_string()),
                 ("tweet.fields", "created_at,public_metrics,author_id"),
+                ("expansions", "author_id"),
+                ("user.fields", "created_at,public_metrics"),
             ])
             .send()
             .await?;
@@ -159,11 +173,19 @@ impl TwitterClient {
             .await
             .map_err(|e| TwitterError::Parse(e.to_string()))?;
 
+        let users: HashMap<String, TwitterUser> = twitter_response
+            .includes
+            .map(|includes| includes.users)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|user| (user.id.clone(), user))
+            .collect();
+
         let posts = twitter_response
             .data
             .unwrap_or_default()
             .into_iter()
-            .map(normalize_twitter_post)
+            .map(|tweet| normalize_twitter_post(tweet, &users))
             .collect::<Vec<_>>();
 
         let result_count = posts.len();
@@ -210,7 +232,13 @@ impl TwitterClient {
     }
 }
 
-fn normalize_twitter_post(tweet: TwitterTweet) -> SocialPost {
+fn parse_twitter_time(value: Option<&str>) -> Option<i64> {
+    value
+        .and_then(|dt| chrono::DateTime::parse_from_rfc3339(dt).ok())
+        .map(|dt| dt.timestamp())
+}
+
+fn normalize_twitter_post(tweet: TwitterTweet, users: &HashMap<String, TwitterUser>) -> SocialPost {
     let sentiment = analyze_sentiment(&tweet.text);
 
     let engagement = tweet
@@ -219,12 +247,9 @@ fn normalize_twitter_post(tweet: TwitterTweet) -> SocialPost {
         .map(|m| m.retweet_count + m.reply_count + m.like_count + m.quote_count)
         .unwrap_or(0);
 
-    let timestamp = tweet
-        .created_at
-        .as_ref()
-        .and_then(|dt| chrono::DateTime::parse_from_rfc3339(dt).ok())
-        .map(|dt| dt.timestamp())
-        .unwrap_or_else(|| Utc::now().timestamp());
+    let timestamp =
+        parse_twitter_time(tweet.created_at.as_deref()).unwrap_or_else(|| Utc::now().timestamp());
+    let author = tweet.author_id.as_ref().and_then(|id| users.get(id));
 
     SocialPost {
         id: format!("twitter_{}", tweet.id),
@@ -234,6 +259,10 @@ fn normalize_twitter_post(tweet: TwitterTweet) -> SocialPost {
         timestamp,
         sentiment,
         engagement,
+        author_followers: author
+            .and_then(|user| user.public_metrics.as_ref())
+            .map(|m| m.followers_count),
+        author_created_at: author.and_then(|user| parse_twitter_time(user.created_at.as_deref())),
     }
 }
 
@@ -332,7 +332,104 @@ impl StockApiClient {
         Ok(self.generate_mock_insider_activity(symbol))
     }
 
+    pub async fn search_symbols(&self, query: &str) -> Result<Vec<StockSymbolMatch>, String> {
+        if let Some(api_key) = &self.finnhub_key {
+            return self.fetch_finnhub_symbol_search(api_key, query).await;
+        }
+
+        Ok(self.generate_mock_symbol_matches(query))
+    }
+
+    async fn fetch_finnhub_symbol_search(
+        &self,
+        api_key: &str,
+        query: &str,
+    ) -> Result<Vec<StockSymbolMatch>, String> {
+        let url = format!("{}/search", FINNHUB_BASE_URL);
+
+        let response = self
+            .client
+            .get(&url)
+            .query(&[("q", query), ("token", api_key)])
+            .send()
+            .await
+            .map_err(|e| format!("Finnhub request failed: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(format!("Finnhub API error: {}", response.status()));
+        }
+
+        #[derive(Deserialize)]
+        struct FinnhubSymbol {
+            symbol: String,
+            description: String,
+            #[serde(rename = "type")]
+            security_type: String,
+        }
+
+        #[derive(Deserialize)]
+        struct FinnhubSearchResponse {
+            result: Vec<FinnhubSymbol>,
+        }
+
+        let data: FinnhubSearchResponse = response
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse Finnhub response: {}", e))?;
+
+        Ok(data
+            .result
+            .into_iter()
+            .map(|item| StockSymbolMatch {
+                symbol: item.symbol,
+                name: item.description,
+                security_type: item.security_type,
+                price: None,
+                volume: None,
+                market_cap: None,
+            })
+            .collect())
+    }
+
     // Mock data generators for fallback
+    fn generate_mock_symbol_matches(&self, query: &str) -> Vec<StockSymbolMatch> {
+        let listings = [
+            ("AAPL", "Apple Inc.", 2_800_000_000_000.0),
+            ("MSFT", "Microsoft Corporation", 2_900_000_000_000.0),
+            ("NVDA", "NVIDIA Corporation", 1_200_000_000_000.0),
+            ("AMZN", "Amazon.com Inc.", 1_500_000_000_000.0),
+            ("GOOGL", "Alphabet Inc.", 1_700_000_000_000.0),
+            ("META", "Meta Platforms Inc.", 900_000_000_000.0),
+            ("TSLA", "Tesla Inc.", 770_000_000_000.0),
+            ("AMD", "Advanced Micro Devices Inc.", 250_000_000_000.0),
+            ("COIN", "Coinbase Global Inc.", 45_000_000_000.0),
+            ("MSTR", "MicroStrategy Inc.", 30_000_000_000.0),
+            ("SOL", "Emeren Group Ltd.", 100_000_000.0),
+            ("SPY", "SPDR S&P 500 ETF Trust", 450_000_000_000.0),
+        ];
+
+        let query = query.to_lowercase();
+        listings
+            .iter()
+            .filter(|(symbol, name, _)| {
+                symbol.to_lowercase().contains(&query) || name.to_lowercase().contains(&query)
+            })
+            .map(|(symbol, name, market_cap)| StockSymbolMatch {
+                symbol: symbol.to_string(),
+                name: name.to_string(),
+                security_type: if *symbol == "SPY" {
+                    "ETP"
+                } else {
+                    "Common Stock"
+                }
+                .to_string(),
+                price: None,
+                volume: None,
+                market_cap: Some(*market_cap),
+            })
+            .collect()
+    }
+
     fn generate_mock_trending_stocks(&self) -> Vec<TrendingStock> {
         vec![
             TrendingStock {
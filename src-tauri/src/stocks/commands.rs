@@ -164,6 +164,37 @@ pub async fn get_stock_news(
     Ok(news)
 }
 
+/// Looks up listed symbols and fills in price and volume from the cached
+/// trending list where the symbol is in it.
+pub async fn search_stock_symbols(
+    cache: &SharedStockCache,
+    query: &str,
+) -> Result<Vec<StockSymbolMatch>, String> {
+    let client = StockApiClient::new(None, None, None, None);
+    let mut matches = client.search_symbols(query).await?;
+
+    let cache_guard = cache.read().await;
+    if let Some((trending, _)) = &cache_guard.trending_stocks {
+        for found in matches.iter_mut() {
+            if let Some(stock) = trending.iter().find(|stock| stock.symbol == found.symbol) {
+                found.price = Some(stock.price);
+                found.volume = Some(stock.volume);
+                found.market_cap = found.market_cap.or(stock.market_cap);
+            }
+        }
+    }
+
+    Ok(matches)
+}
+
+#[tauri::command]
+pub async fn search_stocks(
+    query: String,
+    cache: State<'_, SharedStockCache>,
+) -> Result<Vec<StockSymbolMatch>, String> {
+    search_stock_symbols(&cache, &query).await
+}
+
 #[tauri::command]
 pub async fn get_institutional_holdings(
     symbol: String,
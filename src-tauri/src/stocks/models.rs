@@ -181,6 +181,19 @@ pub enum TransactionType {
     Gift,
 }
 
+/// A listed security matching a symbol lookup. Price and volume are only
+/// filled in when already known from cached market data.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StockSymbolMatch {
+    pub symbol: String,
+    pub name: String,
+    pub security_type: String,
+    pub price: Option<f64>,
+    pub volume: Option<f64>,
+    pub market_cap: Option<f64>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct StockAlert {
@@ -1,4 +1,5 @@
 use crate::core::WebSocketManager;
+use crate::trading::SharedOrderProtection;
 use crate::websocket::types::{StreamProvider, StreamStatus};
 use tauri::State;
 
@@ -49,8 +50,17 @@ pub async fn unsubscribe_wallet_stream(
 #[tauri::command]
 pub async fn get_stream_status(
     manager: State<'_, WebSocketManager>,
+    protection: State<'_, SharedOrderProtection>,
 ) -> Result<Vec<StreamStatus>, String> {
-    Ok(manager.get_status().await)
+    let mut statuses = manager.get_status().await;
+    // Order triggers run on prices, which are streamed over Birdeye.
+    if let Some(status) = statuses
+        .iter_mut()
+        .find(|status| status.provider == StreamProvider::Birdeye)
+    {
+        status.order_protection = protection.statuses();
+    }
+    Ok(statuses)
 }
 
 #[tauri::command]
@@ -13,9 +13,10 @@ use serde::{Deserialize, Serialize};
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
+use crate::config::privacy_mode::warn_unmasked_export;
 use crate::portfolio::{SharedTaxLotsState, TaxLot, TaxReportParams};
 use crate::security::keystore::Keystore;
-use tauri::State;
+use tauri::{AppHandle, State};
 
 use calculator::TaxCalculator;
 use exports::TaxExportService;
@@ -240,10 +241,12 @@ pub async fn update_tax_settings(
 
 #[tauri::command]
 pub async fn export_tax_center_report(
+    app: AppHandle,
     format: String,
     params: TaxReportParams,
     tax_lot_state: State<'_, SharedTaxLotsState>,
 ) -> Result<TaxExportFormat, String> {
+    warn_unmasked_export(&app, "tax_center_report");
     let export_service = TaxExportService::new();
     let lots_state = tax_lot_state
         .lock()
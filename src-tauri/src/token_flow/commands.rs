@@ -1,3 +1,4 @@
+use crate::security::reputation::SharedReputationEngine;
 use crate::token_flow::clustering::{
     assess_cluster_risk, build_wallet_clusters, detect_cluster_performance,
     perform_louvain_clustering, LouvainConfig,
@@ -6,10 +7,16 @@ use crate::token_flow::detection::{
     detect_circular_flows, detect_wash_trading, generate_alerts_from_patterns,
 };
 use crate::token_flow::graph::{generate_sankey_data, TransactionGraph};
+use crate::token_flow::subscription_triggers::{ClusterTriggerStore, FLOW_FEED_SIZE};
+use crate::token_flow::trace::{
+    builtin_known_entities, flow_graph_to_graphml, flow_trace_to_graphml, trace_cache_key,
+    trace_flows,
+};
 use crate::token_flow::types::*;
 use base64::{engine::general_purpose, Engine as _};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use tauri::{AppHandle, Manager};
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
@@ -35,7 +42,10 @@ pub struct FlowAnalysisResponse {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ExportFlowRequest {
-    pub analysis: FlowAnalysisResponse,
+    #[serde(default)]
+    pub analysis: Option<FlowAnalysisResponse>,
+    #[serde(default)]
+    pub trace: Option<FlowTrace>,
     pub format: FlowExportFormat,
     pub filters: HashMap<String, serde_json::Value>,
 }
@@ -50,8 +60,15 @@ pub struct ExportFlowResponse {
 pub struct FlowAnalysisState {
     pub snapshots: Vec<FlowSnapshot>,
     pub subscriptions: Vec<ClusterSubscription>,
+    /// Recent traces keyed by `trace_cache_key`, oldest first.
+    pub trace_cache: Vec<(String, FlowTrace)>,
+    /// Incoming transfers that subscription triggers are evaluated against,
+    /// oldest first.
+    pub flow_feed: Vec<TokenTransaction>,
 }
 
+const TRACE_CACHE_SIZE: usize = 32;
+
 pub type SharedFlowAnalysisState = std::sync::Arc<RwLock<FlowAnalysisState>>;
 
 pub fn create_token_flow_state() -> SharedFlowAnalysisState {
@@ -90,24 +107,97 @@ pub async fn analyze_token_flows(
         circular_flows,
     };
 
-    persist_snapshot(state, &response).await;
+    persist_snapshot(&state, &response).await;
+    append_flow_feed(&mut *state.write().await, request.transactions);
 
     Ok(response)
 }
 
+/// Adds transfers from a live source to the feed that cluster subscription
+/// triggers watch. Returns how many were new.
+#[tauri::command]
+pub async fn ingest_token_flows(
+    state: tauri::State<'_, SharedFlowAnalysisState>,
+    transactions: Vec<TokenTransaction>,
+) -> Result<usize, String> {
+    Ok(append_flow_feed(&mut *state.write().await, transactions))
+}
+
+/// Follows a wallet's outgoing flows over several hops, labeling exchange,
+/// bridge, burn, mixer and blacklisted endpoints.
+#[tauri::command]
+pub async fn trace_token_flows(
+    app: AppHandle,
+    state: tauri::State<'_, SharedFlowAnalysisState>,
+    request: FlowTraceRequest,
+) -> Result<FlowTrace, String> {
+    if request.source_wallet.trim().is_empty() {
+        return Err("A source wallet is required".to_string());
+    }
+
+    let mut entities = builtin_known_entities();
+    if let Some(reputation) = app.try_state::<SharedReputationEngine>() {
+        match reputation
+            .read()
+            .await
+            .get_blacklist(Some("wallet".to_string()))
+            .await
+        {
+            Ok(blacklist) => entities.extend(blacklist.into_iter().map(|entry| KnownEntity {
+                address: entry.address,
+                name: entry.reason,
+                category: EndpointCategory::Blacklisted,
+            })),
+            Err(err) => eprintln!("Token flow trace is running without the blacklist: {}", err),
+        }
+    }
+    entities.extend(request.known_entities.iter().cloned());
+
+    let key = trace_cache_key(&request, &entities);
+    if let Some((_, trace)) = state
+        .read()
+        .await
+        .trace_cache
+        .iter()
+        .find(|(k, _)| *k == key)
+    {
+        return Ok(FlowTrace {
+            cached: true,
+            ..trace.clone()
+        });
+    }
+
+    let trace = trace_flows(&request, &entities);
+
+    let mut state = state.write().await;
+    if state.trace_cache.len() >= TRACE_CACHE_SIZE {
+        state.trace_cache.remove(0);
+    }
+    state.trace_cache.push((key, trace.clone()));
+
+    Ok(trace)
+}
+
 #[tauri::command]
 pub async fn export_flow_analysis(
     request: ExportFlowRequest,
 ) -> Result<ExportFlowResponse, String> {
     let ExportFlowRequest {
         analysis,
+        trace,
         format,
         filters,
     } = request;
 
+    let time_range = match (&analysis, &trace) {
+        (Some(analysis), _) => analysis.graph.time_range.clone(),
+        (None, Some(trace)) => trace.time_range.clone(),
+        (None, None) => return Err("Nothing to export".to_string()),
+    };
+
     let metadata = FlowExportMetadata {
         exported_at: chrono::Utc::now().timestamp(),
-        time_range: analysis.graph.time_range.clone(),
+        time_range,
         filters,
     };
 
@@ -117,11 +207,22 @@ pub async fn export_flow_analysis(
         None
     };
 
+    let graphml = if format == FlowExportFormat::GraphMl {
+        trace
+            .as_ref()
+            .map(flow_trace_to_graphml)
+            .or_else(|| analysis.as_ref().map(|a| flow_graph_to_graphml(&a.graph)))
+    } else {
+        None
+    };
+
     let data = FlowExportContent {
-        graph: Some(analysis.graph.clone()),
-        clusters: Some(analysis.clusters.clone()),
-        alerts: Some(analysis.alerts.clone()),
+        graph: analysis.as_ref().map(|a| a.graph.clone()),
+        clusters: analysis.as_ref().map(|a| a.clusters.clone()),
+        alerts: analysis.as_ref().map(|a| a.alerts.clone()),
         snapshot,
+        trace,
+        graphml,
     };
 
     Ok(ExportFlowResponse {
@@ -162,7 +263,7 @@ fn build_timeline_frames(edges: &[TokenFlowEdge]) -> Vec<TimelineFrame> {
 }
 
 async fn persist_snapshot(
-    state: tauri::State<'_, SharedFlowAnalysisState>,
+    state: &tauri::State<'_, SharedFlowAnalysisState>,
     response: &FlowAnalysisResponse,
 ) {
     let mut state = state.write().await;
@@ -180,6 +281,26 @@ async fn persist_snapshot(
     });
 }
 
+fn append_flow_feed(state: &mut FlowAnalysisState, transactions: Vec<TokenTransaction>) -> usize {
+    let mut known: HashSet<String> = state
+        .flow_feed
+        .iter()
+        .map(|tx| tx.transaction_hash.clone())
+        .collect();
+    let before = state.flow_feed.len();
+    for tx in transactions {
+        if known.insert(tx.transaction_hash.clone()) {
+            state.flow_feed.push(tx);
+        }
+    }
+    let added = state.flow_feed.len() - before;
+
+    state.flow_feed.sort_by_key(|tx| tx.timestamp);
+    let excess = state.flow_feed.len().saturating_sub(FLOW_FEED_SIZE);
+    state.flow_feed.drain(..excess);
+    added
+}
+
 fn generate_snapshot_placeholder(format: FlowExportFormat) -> String {
     match format {
         FlowExportFormat::Png => general_purpose::STANDARD.encode("PNG_SNAPSHOT_PLACEHOLDER"),
@@ -191,16 +312,29 @@ fn generate_snapshot_placeholder(format: FlowExportFormat) -> String {
 #[tauri::command]
 pub async fn list_cluster_subscriptions(
     state: tauri::State<'_, SharedFlowAnalysisState>,
+    store: tauri::State<'_, ClusterTriggerStore>,
 ) -> Result<Vec<ClusterSubscription>, String> {
-    let state = state.read().await;
-    Ok(state.subscriptions.clone())
+    let mut subscriptions = state.read().await.subscriptions.clone();
+    for subscription in &mut subscriptions {
+        subscription.trigger_history = store
+            .recent_events(&subscription.id)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(subscriptions)
 }
 
 #[tauri::command]
 pub async fn upsert_cluster_subscription(
     state: tauri::State<'_, SharedFlowAnalysisState>,
+    store: tauri::State<'_, ClusterTriggerStore>,
     subscription: ClusterSubscription,
 ) -> Result<(), String> {
+    store
+        .save_subscription(&subscription)
+        .await
+        .map_err(|e| e.to_string())?;
+
     let mut state = state.write().await;
     if let Some(existing) = state
         .subscriptions
@@ -217,8 +351,14 @@ pub async fn upsert_cluster_subscription(
 #[tauri::command]
 pub async fn remove_cluster_subscription(
     state: tauri::State<'_, SharedFlowAnalysisState>,
+    store: tauri::State<'_, ClusterTriggerStore>,
     subscription_id: String,
 ) -> Result<(), String> {
+    store
+        .remove_subscription(&subscription_id)
+        .await
+        .map_err(|e| e.to_string())?;
+
     let mut state = state.write().await;
     state.subscriptions.retain(|sub| sub.id != subscription_id);
     Ok(())
@@ -202,6 +202,7 @@ fn detect_circular_wash_trading(edges: &[TokenFlowEdge]) -> Vec<WashTradingPatte
                 timestamp: e.timestamp,
                 token_address: e.token_address.clone(),
                 transaction_hash: e.transaction_hash.clone(),
+                usd_value: None,
             })
             .collect(),
     );
@@ -263,6 +264,7 @@ fn detect_layered_pattern(edges: &[TokenFlowEdge]) -> Vec<WashTradingPattern> {
                 timestamp: e.timestamp,
                 token_address: e.token_address.clone(),
                 transaction_hash: e.transaction_hash.clone(),
+                usd_value: None,
             })
             .collect(),
     );
@@ -288,6 +288,7 @@ mod tests {
                 timestamp: 1000,
                 token_address: "TOKEN1".to_string(),
                 transaction_hash: "tx1".to_string(),
+                usd_value: None,
             },
             TokenTransaction {
                 source: "B".to_string(),
@@ -296,6 +297,7 @@ mod tests {
                 timestamp: 2000,
                 token_address: "TOKEN1".to_string(),
                 transaction_hash: "tx2".to_string(),
+                usd_value: None,
             },
         ];
 
@@ -314,6 +316,7 @@ mod tests {
             timestamp: 1000,
             token_address: "TOKEN1".to_string(),
             transaction_hash: "tx1".to_string(),
+            usd_value: None,
         }];
 
         let graph = TransactionGraph::from_transactions(transactions);
@@ -2,10 +2,14 @@ pub mod clustering;
 pub mod commands;
 pub mod detection;
 pub mod graph;
+pub mod subscription_triggers;
+pub mod trace;
 pub mod types;
 
 pub use clustering::*;
 pub use commands::*;
 pub use detection::*;
 pub use graph::*;
+pub use subscription_triggers::*;
+pub use trace::*;
 pub use types::*;
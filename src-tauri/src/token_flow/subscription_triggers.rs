@@ -0,0 +1,724 @@
+use crate::notifications::router::SharedNotificationRouter;
+use crate::notifications::types::{
+    AlertPriority, HistoryCategory, NewNotification, NotificationEntity,
+};
+use crate::security::reputation::SharedReputationEngine;
+use crate::token_flow::commands::{FlowAnalysisState, SharedFlowAnalysisState};
+use crate::token_flow::trace::builtin_known_entities;
+use crate::token_flow::types::*;
+use sha2::{Digest, Sha256};
+use sqlx::{Row, SqlitePool};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use uuid::Uuid;
+
+pub const CLUSTER_TRIGGER_DB_FILE: &str = "cluster_triggers.db";
+/// Incoming transfers kept in memory for trigger evaluation.
+pub const FLOW_FEED_SIZE: usize = 5000;
+const TRIGGER_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+const TRIGGER_HISTORY_LIMIT: i64 = 20;
+/// Transfers attached to a blacklisted-member alert.
+const BLACKLIST_CONTEXT_TRANSFERS: usize = 10;
+
+/// A trigger whose condition holds for transfers or wallets it has not fired
+/// for yet.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TriggerMatch {
+    pub trigger_id: String,
+    pub kind: &'static str,
+    pub title: String,
+    pub message: String,
+    pub wallets: Vec<String>,
+    pub transactions: Vec<TokenTransaction>,
+    /// Transaction hashes, or wallet addresses for blacklist triggers, that
+    /// are marked as handled once the trigger fires.
+    pub keys: Vec<String>,
+    pub value_usd: Option<f64>,
+}
+
+pub struct TriggerInputs<'a> {
+    pub members: &'a HashSet<String>,
+    pub transfers: &'a [TokenTransaction],
+    pub blacklist: &'a HashSet<String>,
+    pub exchanges: &'a HashSet<String>,
+    pub now: i64,
+}
+
+/// Checks one trigger. Transfers and wallets in `fired` already produced an
+/// alert for this trigger and are ignored, so an outflow alert needs another
+/// `threshold_usd` of fresh outflow before it fires again.
+pub fn evaluate_trigger(
+    trigger: &ClusterTrigger,
+    inputs: &TriggerInputs<'_>,
+    fired: &HashSet<String>,
+) -> Option<TriggerMatch> {
+    if !trigger.enabled {
+        return None;
+    }
+
+    match &trigger.condition {
+        ClusterTriggerCondition::ExchangeOutflow {
+            threshold_usd,
+            window_hours,
+            price_usd,
+            exchange_addresses,
+        } => {
+            let cutoff = inputs.now - i64::from(*window_hours) * 3600;
+            let mut total = 0.0;
+            let mut transactions = Vec::new();
+            for tx in inputs.transfers {
+                let to_exchange = inputs.exchanges.contains(&tx.target)
+                    || exchange_addresses.contains(&tx.target);
+                if tx.timestamp < cutoff
+                    || !to_exchange
+                    || !inputs.members.contains(&tx.source)
+                    || fired.contains(&tx.transaction_hash)
+                {
+                    continue;
+                }
+                let Some(value) = tx.usd_value.or(price_usd.map(|price| tx.amount * price)) else {
+                    continue;
+                };
+                total += value;
+                transactions.push(tx.clone());
+            }
+            if total <= *threshold_usd {
+                return None;
+            }
+
+            Some(trigger_match(
+                trigger,
+                "Cluster outflow to exchanges",
+                format!(
+                    "${:.2} moved to exchange deposit addresses in {} transfers over the last {}h (threshold ${:.2})",
+                    total,
+                    transactions.len(),
+                    window_hours,
+                    threshold_usd
+                ),
+                transactions,
+                Some(total),
+            ))
+        }
+        ClusterTriggerCondition::DeployerFunding {
+            mint_address,
+            deployer,
+        } => {
+            let transactions: Vec<TokenTransaction> = inputs
+                .transfers
+                .iter()
+                .filter(|tx| {
+                    tx.source == *deployer
+                        && tx.token_address == *mint_address
+                        && inputs.members.contains(&tx.target)
+                        && !fired.contains(&tx.transaction_hash)
+                })
+                .cloned()
+                .collect();
+            if transactions.is_empty() {
+                return None;
+            }
+
+            let amount: f64 = transactions.iter().map(|tx| tx.amount).sum();
+            let value_usd = transactions
+                .iter()
+                .map(|tx| tx.usd_value)
+                .sum::<Option<f64>>();
+            Some(trigger_match(
+                trigger,
+                "Cluster funded by token deployer",
+                format!(
+                    "The deployer of {} sent {} tokens to cluster members in {} transfers",
+                    mint_address,
+                    amount,
+                    transactions.len()
+                ),
+                transactions,
+                value_usd,
+            ))
+        }
+        ClusterTriggerCondition::MemberBlacklisted => {
+            let mut wallets: Vec<String> = inputs
+                .members
+                .iter()
+                .filter(|wallet| inputs.blacklist.contains(*wallet) && !fired.contains(*wallet))
+                .cloned()
+                .collect();
+            if wallets.is_empty() {
+                return None;
+            }
+            wallets.sort();
+
+            let mut transactions: Vec<TokenTransaction> = inputs
+                .transfers
+                .iter()
+                .filter(|tx| wallets.contains(&tx.source) || wallets.contains(&tx.target))
+                .cloned()
+                .collect();
+            transactions.sort_by_key(|tx| std::cmp::Reverse(tx.timestamp));
+            transactions.truncate(BLACKLIST_CONTEXT_TRANSFERS);
+
+            Some(TriggerMatch {
+                trigger_id: trigger.id.clone(),
+                kind: trigger.condition.kind(),
+                title: "Cluster member blacklisted".to_string(),
+                message: format!("Blacklisted cluster members: {}", wallets.join(", ")),
+                keys: wallets.clone(),
+                wallets,
+                transactions,
+                value_usd: None,
+            })
+        }
+    }
+}
+
+fn trigger_match(
+    trigger: &ClusterTrigger,
+    title: &str,
+    message: String,
+    mut transactions: Vec<TokenTransaction>,
+    value_usd: Option<f64>,
+) -> TriggerMatch {
+    transactions.sort_by_key(|tx| tx.timestamp);
+    let mut wallets: Vec<String> = transactions
+        .iter()
+        .flat_map(|tx| [tx.source.clone(), tx.target.clone()])
+        .collect();
+    wallets.sort();
+    wallets.dedup();
+
+    TriggerMatch {
+        trigger_id: trigger.id.clone(),
+        kind: trigger.condition.kind(),
+        title: title.to_string(),
+        message,
+        wallets,
+        keys: transactions
+            .iter()
+            .map(|tx| tx.transaction_hash.clone())
+            .collect(),
+        transactions,
+        value_usd,
+    }
+}
+
+fn fingerprint(keys: &[String]) -> String {
+    let mut sorted: Vec<&String> = keys.iter().collect();
+    sorted.sort();
+    let mut hasher = Sha256::new();
+    for key in sorted {
+        hasher.update(key.as_bytes());
+        hasher.update([0]);
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+/// Persists cluster subscriptions together with what their triggers have
+/// fired for, so a restart neither loses subscriptions nor repeats alerts.
+#[derive(Clone)]
+pub struct ClusterTriggerStore {
+    pool: SqlitePool,
+}
+
+impl ClusterTriggerStore {
+    pub async fn new(db_path: PathBuf) -> Result<Self, sqlx::Error> {
+        let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+        let pool = SqlitePool::connect(&db_url).await?;
+        Self::with_pool(pool).await
+    }
+
+    pub async fn with_pool(pool: SqlitePool) -> Result<Self, sqlx::Error> {
+        let store = Self { pool };
+        store.initialize().await?;
+        Ok(store)
+    }
+
+    async fn initialize(&self) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS cluster_subscriptions (
+                id TEXT PRIMARY KEY,
+                data TEXT NOT NULL,
+                updated_at INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS cluster_trigger_events (
+                id TEXT PRIMARY KEY,
+                subscription_id TEXT NOT NULL,
+                trigger_id TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                fingerprint TEXT NOT NULL,
+                title TEXT NOT NULL,
+                message TEXT NOT NULL,
+                wallets TEXT NOT NULL,
+                transactions TEXT NOT NULL,
+                value_usd REAL,
+                fired_at INTEGER NOT NULL,
+                UNIQUE(subscription_id, trigger_id, fingerprint)
+            );
+            CREATE INDEX IF NOT EXISTS idx_cluster_trigger_events_sub
+                ON cluster_trigger_events(subscription_id, fired_at);
+            CREATE TABLE IF NOT EXISTS cluster_trigger_keys (
+                subscription_id TEXT NOT NULL,
+                trigger_id TEXT NOT NULL,
+                item_key TEXT NOT NULL,
+                PRIMARY KEY (subscription_id, trigger_id, item_key)
+            );
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn load_subscriptions(&self) -> Result<Vec<ClusterSubscription>, sqlx::Error> {
+        let rows = sqlx::query("SELECT data FROM cluster_subscriptions ORDER BY updated_at")
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut subscriptions = Vec::new();
+        for row in rows {
+            let data: String = row.try_get("data")?;
+            match serde_json::from_str::<ClusterSubscription>(&data) {
+                Ok(subscription) => subscriptions.push(subscription),
+                Err(err) => eprintln!("Skipping unreadable cluster subscription: {}", err),
+            }
+        }
+        Ok(subscriptions)
+    }
+
+    pub async fn save_subscription(
+        &self,
+        subscription: &ClusterSubscription,
+    ) -> Result<(), sqlx::Error> {
+        let stored = ClusterSubscription {
+            trigger_history: Vec::new(),
+            ..subscription.clone()
+        };
+        let data = serde_json::to_string(&stored).unwrap_or_default();
+        sqlx::query(
+            r#"
+            INSERT INTO cluster_subscriptions (id, data, updated_at) VALUES (?1, ?2, ?3)
+            ON CONFLICT(id) DO UPDATE SET data = excluded.data, updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(&subscription.id)
+        .bind(data)
+        .bind(chrono::Utc::now().timestamp())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn remove_subscription(&self, subscription_id: &str) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM cluster_subscriptions WHERE id = ?1")
+            .bind(subscription_id)
+            .execute(&mut tx)
+            .await?;
+        sqlx::query("DELETE FROM cluster_trigger_events WHERE subscription_id = ?1")
+            .bind(subscription_id)
+            .execute(&mut tx)
+            .await?;
+        sqlx::query("DELETE FROM cluster_trigger_keys WHERE subscription_id = ?1")
+            .bind(subscription_id)
+            .execute(&mut tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn fired_keys(
+        &self,
+        subscription_id: &str,
+        trigger_id: &str,
+    ) -> Result<HashSet<String>, sqlx::Error> {
+        let rows = sqlx::query(
+            "SELECT item_key FROM cluster_trigger_keys WHERE subscription_id = ?1 AND trigger_id = ?2",
+        )
+        .bind(subscription_id)
+        .bind(trigger_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(|row| row.try_get("item_key")).collect()
+    }
+
+    /// Records a firing. Returns `None` when the same set of transfers was
+    /// already recorded, in which case no alert should go out.
+    pub async fn record_event(
+        &self,
+        subscription_id: &str,
+        matched: &TriggerMatch,
+        now: i64,
+    ) -> Result<Option<ClusterTriggerEvent>, sqlx::Error> {
+        let event = ClusterTriggerEvent {
+            id: Uuid::new_v4().to_string(),
+            subscription_id: subscription_id.to_string(),
+            trigger_id: matched.trigger_id.clone(),
+            kind: matched.kind.to_string(),
+            title: matched.title.clone(),
+            message: matched.message.clone(),
+            wallets: matched.wallets.clone(),
+            transactions: matched.transactions.clone(),
+            value_usd: matched.value_usd,
+            fired_at: now,
+        };
+
+        let mut tx = self.pool.begin().await?;
+        let inserted = sqlx::query(
+            r#"
+            INSERT OR IGNORE INTO cluster_trigger_events
+                (id, subscription_id, trigger_id, kind, fingerprint, title, message,
+                 wallets, transactions, value_usd, fired_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+            "#,
+        )
+        .bind(&event.id)
+        .bind(&event.subscription_id)
+        .bind(&event.trigger_id)
+        .bind(&event.kind)
+        .bind(fingerprint(&matched.keys))
+        .bind(&event.title)
+        .bind(&event.message)
+        .bind(serde_json::to_string(&event.wallets).unwrap_or_default())
+        .bind(serde_json::to_string(&event.transactions).unwrap_or_default())
+        .bind(event.value_usd)
+        .bind(event.fired_at)
+        .execute(&mut tx)
+        .await?
+        .rows_affected();
+
+        for key in &matched.keys {
+            sqlx::query(
+                "INSERT OR IGNORE INTO cluster_trigger_keys (subscription_id, trigger_id, item_key) VALUES (?1, ?2, ?3)",
+            )
+            .bind(subscription_id)
+            .bind(&matched.trigger_id)
+            .bind(key)
+            .execute(&mut tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        Ok((inserted > 0).then_some(event))
+    }
+
+    pub async fn recent_events(
+        &self,
+        subscription_id: &str,
+    ) -> Result<Vec<ClusterTriggerEvent>, sqlx::Error> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, subscription_id, trigger_id, kind, title, message, wallets,
+                   transactions, value_usd, fired_at
+            FROM cluster_trigger_events
+            WHERE subscription_id = ?1
+            ORDER BY fired_at DESC
+            LIMIT ?2
+            "#,
+        )
+        .bind(subscription_id)
+        .bind(TRIGGER_HISTORY_LIMIT)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                let wallets: String = row.try_get("wallets")?;
+                let transactions: String = row.try_get("transactions")?;
+                Ok(ClusterTriggerEvent {
+                    id: row.try_get("id")?,
+                    subscription_id: row.try_get("subscription_id")?,
+                    trigger_id: row.try_get("trigger_id")?,
+                    kind: row.try_get("kind")?,
+                    title: row.try_get("title")?,
+                    message: row.try_get("message")?,
+                    wallets: serde_json::from_str(&wallets).unwrap_or_default(),
+                    transactions: serde_json::from_str(&transactions).unwrap_or_default(),
+                    value_usd: row.try_get("value_usd")?,
+                    fired_at: row.try_get("fired_at")?,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Wallets of the cluster in the most recent snapshot that contains it.
+fn cluster_members(state: &FlowAnalysisState, cluster_id: &str) -> HashSet<String> {
+    state
+        .snapshots
+        .iter()
+        .rev()
+        .find_map(|snapshot| snapshot.clusters.iter().find(|c| c.id == cluster_id))
+        .map(|cluster| cluster.wallets.iter().cloned().collect())
+        .unwrap_or_default()
+}
+
+async fn wallet_blacklist(app: &AppHandle) -> HashSet<String> {
+    let Some(reputation) = app.try_state::<SharedReputationEngine>() else {
+        return HashSet::new();
+    };
+    let blacklist = reputation
+        .read()
+        .await
+        .get_blacklist(Some("wallet".to_string()))
+        .await;
+    match blacklist {
+        Ok(entries) => entries.into_iter().map(|entry| entry.address).collect(),
+        Err(err) => {
+            eprintln!(
+                "Cluster triggers are running without the blacklist: {}",
+                err
+            );
+            HashSet::new()
+        }
+    }
+}
+
+async fn deliver(app: &AppHandle, subscription: &ClusterSubscription, event: &ClusterTriggerEvent) {
+    if subscription
+        .notification_channels
+        .contains(&NotificationChannel::Ui)
+    {
+        if let Err(err) = app.emit("cluster_trigger_fired", event) {
+            eprintln!("Failed to emit cluster trigger event: {}", err);
+        }
+    }
+
+    let Some(router) = app.try_state::<SharedNotificationRouter>() else {
+        return;
+    };
+    let external = subscription
+        .notification_channels
+        .iter()
+        .any(|channel| *channel != NotificationChannel::Ui);
+
+    let mut message = format!("Cluster {}: {}", subscription.cluster_id, event.message);
+    for tx in &event.transactions {
+        message.push_str(&format!(
+            "\n• {} → {}: {} ({})",
+            tx.source, tx.target, tx.amount, tx.transaction_hash
+        ));
+    }
+    let notification = NewNotification {
+        category: HistoryCategory::ClusterTrigger,
+        priority: AlertPriority::High,
+        title: event.title.clone(),
+        message,
+        entity: Some(NotificationEntity::ClusterTrigger(event.id.clone())),
+        link: None,
+    };
+    let router = router.read().await;
+    let result = if external {
+        router.send_event_notification(&notification).await
+    } else {
+        // UI-only triggers are not sent anywhere, but are kept in the history
+        router
+            .record_notification(&notification, &[])
+            .await
+            .map(|_| ())
+    };
+    if let Err(err) = result {
+        eprintln!("Failed to route cluster trigger {}: {}", event.id, err);
+    }
+}
+
+/// Evaluates every subscription trigger against the incoming flow feed once.
+pub async fn check_cluster_triggers(
+    app: &AppHandle,
+    flows: &SharedFlowAnalysisState,
+    store: &ClusterTriggerStore,
+    now: i64,
+) -> Result<Vec<ClusterTriggerEvent>, sqlx::Error> {
+    let work: Vec<(ClusterSubscription, HashSet<String>, Vec<TokenTransaction>)> = {
+        let state = flows.read().await;
+        state
+            .subscriptions
+            .iter()
+            .filter(|sub| sub.triggers.iter().any(|t| t.enabled))
+            .map(|sub| {
+                let members = cluster_members(&state, &sub.cluster_id);
+                let transfers = state
+                    .flow_feed
+                    .iter()
+                    .filter(|tx| members.contains(&tx.source) || members.contains(&tx.target))
+                    .cloned()
+                    .collect();
+                (sub.clone(), members, transfers)
+            })
+            .filter(|(_, members, _)| !members.is_empty())
+            .collect()
+    };
+    if work.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let blacklist = wallet_blacklist(app).await;
+    let exchanges: HashSet<String> = builtin_known_entities()
+        .into_iter()
+        .filter(|entity| entity.category == EndpointCategory::CexDeposit)
+        .map(|entity| entity.address)
+        .collect();
+
+    let mut fired = Vec::new();
+    for (subscription, members, transfers) in work {
+        let inputs = TriggerInputs {
+            members: &members,
+            transfers: &transfers,
+            blacklist: &blacklist,
+            exchanges: &exchanges,
+            now,
+        };
+        for trigger in &subscription.triggers {
+            let handled = store.fired_keys(&subscription.id, &trigger.id).await?;
+            let Some(matched) = evaluate_trigger(trigger, &inputs, &handled) else {
+                continue;
+            };
+            if let Some(event) = store.record_event(&subscription.id, &matched, now).await? {
+                deliver(app, &subscription, &event).await;
+                fired.push(event);
+            }
+        }
+    }
+    Ok(fired)
+}
+
+pub async fn run_cluster_trigger_monitor(
+    app: AppHandle,
+    flows: SharedFlowAnalysisState,
+    store: ClusterTriggerStore,
+) {
+    let mut ticker = tokio::time::interval(TRIGGER_CHECK_INTERVAL);
+    loop {
+        ticker.tick().await;
+        let now = chrono::Utc::now().timestamp();
+        if let Err(err) = check_cluster_triggers(&app, &flows, &store, now).await {
+            eprintln!("Failed to evaluate cluster triggers: {}", err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_pool;
+
+    fn tx(hash: &str, source: &str, target: &str, usd: f64, timestamp: i64) -> TokenTransaction {
+        TokenTransaction {
+            source: source.to_string(),
+            target: target.to_string(),
+            amount: usd,
+            timestamp,
+            token_address: "MINT".to_string(),
+            transaction_hash: hash.to_string(),
+            usd_value: Some(usd),
+        }
+    }
+
+    fn outflow_trigger() -> ClusterTrigger {
+        ClusterTrigger {
+            id: "outflow".to_string(),
+            condition: ClusterTriggerCondition::ExchangeOutflow {
+                threshold_usd: 1000.0,
+                window_hours: 24,
+                price_usd: None,
+                exchange_addresses: Vec::new(),
+            },
+            enabled: true,
+        }
+    }
+
+    fn set(items: &[&str]) -> HashSet<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn outflow_fires_once_per_set_of_transfers() {
+        let members = set(&["A", "B"]);
+        let exchanges = set(&["CEX"]);
+        let blacklist = HashSet::new();
+        let now = 100_000;
+        let transfers = vec![
+            tx("t1", "A", "CEX", 600.0, now - 3600),
+            tx("t2", "B", "CEX", 600.0, now - 1800),
+            tx("old", "A", "CEX", 5000.0, now - 48 * 3600),
+            tx("t3", "A", "OTHER", 5000.0, now - 60),
+        ];
+        let inputs = TriggerInputs {
+            members: &members,
+            transfers: &transfers,
+            blacklist: &blacklist,
+            exchanges: &exchanges,
+            now,
+        };
+
+        let matched = evaluate_trigger(&outflow_trigger(), &inputs, &HashSet::new()).unwrap();
+        assert_eq!(matched.keys, vec!["t1".to_string(), "t2".to_string()]);
+        assert_eq!(matched.value_usd, Some(1200.0));
+
+        let handled: HashSet<String> = matched.keys.into_iter().collect();
+        assert!(evaluate_trigger(&outflow_trigger(), &inputs, &handled).is_none());
+    }
+
+    #[test]
+    fn blacklisted_member_fires_once_per_wallet() {
+        let members = set(&["A", "B"]);
+        let blacklist = set(&["B", "Z"]);
+        let exchanges = HashSet::new();
+        let transfers = vec![tx("t1", "B", "C", 10.0, 5)];
+        let inputs = TriggerInputs {
+            members: &members,
+            transfers: &transfers,
+            blacklist: &blacklist,
+            exchanges: &exchanges,
+            now: 10,
+        };
+        let trigger = ClusterTrigger {
+            id: "bl".to_string(),
+            condition: ClusterTriggerCondition::MemberBlacklisted,
+            enabled: true,
+        };
+
+        let matched = evaluate_trigger(&trigger, &inputs, &HashSet::new()).unwrap();
+        assert_eq!(matched.wallets, vec!["B".to_string()]);
+        assert_eq!(matched.transactions.len(), 1);
+        assert!(evaluate_trigger(&trigger, &inputs, &set(&["B"])).is_none());
+    }
+
+    #[tokio::test]
+    async fn recorded_transfer_sets_do_not_fire_twice() {
+        let pool = test_pool().await;
+        let store = ClusterTriggerStore::with_pool(pool).await.unwrap();
+        let matched = TriggerMatch {
+            trigger_id: "outflow".to_string(),
+            kind: "exchange_outflow",
+            title: "Cluster outflow to exchanges".to_string(),
+            message: String::new(),
+            wallets: vec!["A".to_string()],
+            transactions: vec![tx("t1", "A", "CEX", 2000.0, 1)],
+            keys: vec!["t1".to_string()],
+            value_usd: Some(2000.0),
+        };
+
+        assert!(store
+            .record_event("sub", &matched, 10)
+            .await
+            .unwrap()
+            .is_some());
+        assert!(store
+            .record_event("sub", &matched, 20)
+            .await
+            .unwrap()
+            .is_none());
+        assert_eq!(
+            store.fired_keys("sub", "outflow").await.unwrap(),
+            set(&["t1"])
+        );
+
+        let history = store.recent_events("sub").await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].transactions[0].transaction_hash, "t1");
+    }
+}
@@ -0,0 +1,436 @@
+//! Multi-hop tracing of a wallet's outgoing token flows.
+//!
+//! Starting at the source wallet, transfers are followed hop by hop, only
+//! forward in time: a wallet's outflows count once funds have reached it.
+//! Wallets matching a known entity (exchange deposit address, bridge, burn
+//! address, mixer, or a blacklisted wallet) end their branch of the trace.
+
+use super::types::*;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, VecDeque};
+
+pub const MAX_TRACE_DEPTH: usize = 10;
+pub const MAX_TRACE_NODES: usize = 2_000;
+
+/// Well-known Solana endpoints. Traces add the reputation blacklist and any
+/// entities supplied with the request on top of these.
+pub fn builtin_known_entities() -> Vec<KnownEntity> {
+    [
+        (
+            "1nc1nerator11111111111111111111111111111111",
+            "Incinerator",
+            EndpointCategory::Burn,
+        ),
+        (
+            "11111111111111111111111111111111",
+            "System Program",
+            EndpointCategory::Burn,
+        ),
+        (
+            "5tzFkiKscXHK5ZXCGbXZxdw7gTjjD1mBwuoFbhUvuAi9",
+            "Binance",
+            EndpointCategory::CexDeposit,
+        ),
+        (
+            "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM",
+            "Binance",
+            EndpointCategory::CexDeposit,
+        ),
+        (
+            "H8sMJSCQxfKiFTCfDR3DUMLPwcRbM61LGFJ8N4dK3WjS",
+            "Coinbase",
+            EndpointCategory::CexDeposit,
+        ),
+        (
+            "FWznbcNXWQuHTawe9RxvQ2LdCENssh12dsznf4RiouN5",
+            "Kraken",
+            EndpointCategory::CexDeposit,
+        ),
+        (
+            "5VCwKtCXgCJ6kit5FybXjvriW3xELsFDhYrPSqtJNmcD",
+            "OKX",
+            EndpointCategory::CexDeposit,
+        ),
+        (
+            "wormDTUJ6AWPNvk59vGQbDvGJmqbDTdgWgAqcLBCgUb",
+            "Wormhole Token Bridge",
+            EndpointCategory::Bridge,
+        ),
+        (
+            "worm2ZoG2kUd4vFXhvjh93UUH596ayRfgQ2MgjNMTth",
+            "Wormhole Core Bridge",
+            EndpointCategory::Bridge,
+        ),
+        (
+            "DEbrdGj3HsRsAzx6uH4MKyREKxVAfBydijLUF3ygsFfh",
+            "deBridge",
+            EndpointCategory::Bridge,
+        ),
+    ]
+    .into_iter()
+    .map(|(address, name, category)| KnownEntity {
+        address: address.to_string(),
+        name: name.to_string(),
+        category,
+    })
+    .collect()
+}
+
+fn hop_threshold(min_hop_values: &[f64], hop: usize) -> f64 {
+    min_hop_values
+        .get(hop - 1)
+        .or(min_hop_values.last())
+        .copied()
+        .unwrap_or(0.0)
+}
+
+/// Traces flows out of `request.source_wallet`. Later entries in `entities`
+/// take precedence for the same address.
+pub fn trace_flows(request: &FlowTraceRequest, entities: &[KnownEntity]) -> FlowTrace {
+    let max_depth = request.max_depth.clamp(1, MAX_TRACE_DEPTH);
+    let max_nodes = request.max_nodes.clamp(1, MAX_TRACE_NODES);
+    let labels: HashMap<&str, &KnownEntity> = entities
+        .iter()
+        .map(|entity| (entity.address.as_str(), entity))
+        .collect();
+
+    let mut outgoing: HashMap<&str, Vec<&TokenTransaction>> = HashMap::new();
+    for tx in request.transactions.iter().filter(|tx| {
+        tx.token_address == request.token_address
+            && request.start_time.is_none_or(|start| tx.timestamp >= start)
+            && request.end_time.is_none_or(|end| tx.timestamp <= end)
+    }) {
+        outgoing.entry(tx.source.as_str()).or_default().push(tx);
+    }
+    for txs in outgoing.values_mut() {
+        txs.sort_by_key(|tx| tx.timestamp);
+    }
+
+    let mut nodes = vec![FlowTraceNode {
+        id: request.source_wallet.clone(),
+        address: request.source_wallet.clone(),
+        label: labels
+            .get(request.source_wallet.as_str())
+            .map(|e| e.name.clone()),
+        category: labels
+            .get(request.source_wallet.as_str())
+            .map(|e| e.category),
+        depth: 0,
+        cumulative_value: 0.0,
+        terminal: false,
+    }];
+    let mut index: HashMap<String, usize> = HashMap::from([(request.source_wallet.clone(), 0)]);
+    let mut edges = Vec::new();
+    let mut truncated = false;
+
+    // (address, depth, time funds first arrived)
+    let mut queue = VecDeque::from([(request.source_wallet.clone(), 0, i64::MIN)]);
+    while let Some((address, depth, arrived_at)) = queue.pop_front() {
+        let hop = depth + 1;
+        let threshold = hop_threshold(&request.min_hop_values, hop);
+
+        for tx in outgoing.get(address.as_str()).into_iter().flatten() {
+            if tx.timestamp < arrived_at || tx.amount < threshold || tx.target == address {
+                continue;
+            }
+
+            let target = match index.get(&tx.target) {
+                Some(&i) => i,
+                None => {
+                    if nodes.len() >= max_nodes {
+                        truncated = true;
+                        continue;
+                    }
+                    let entity = labels.get(tx.target.as_str());
+                    nodes.push(FlowTraceNode {
+                        id: tx.target.clone(),
+                        address: tx.target.clone(),
+                        label: entity.map(|e| e.name.clone()),
+                        category: entity.map(|e| e.category),
+                        depth: hop,
+                        cumulative_value: 0.0,
+                        terminal: entity.is_some(),
+                    });
+                    index.insert(tx.target.clone(), nodes.len() - 1);
+                    if entity.is_none() && hop < max_depth {
+                        queue.push_back((tx.target.clone(), hop, tx.timestamp));
+                    }
+                    nodes.len() - 1
+                }
+            };
+
+            nodes[target].cumulative_value += tx.amount;
+            edges.push(FlowTraceEdge {
+                id: tx.transaction_hash.clone(),
+                source: address.clone(),
+                target: tx.target.clone(),
+                amount: tx.amount,
+                timestamp: tx.timestamp,
+                transaction_hash: tx.transaction_hash.clone(),
+                hop,
+            });
+        }
+    }
+
+    let start = edges.iter().map(|e| e.timestamp).min();
+    let end = edges.iter().map(|e| e.timestamp).max();
+    FlowTrace {
+        source_wallet: request.source_wallet.clone(),
+        token_address: request.token_address.clone(),
+        time_range: TimeRange {
+            start: request.start_time.or(start).unwrap_or(0),
+            end: request.end_time.or(end).unwrap_or(0),
+        },
+        max_depth,
+        nodes,
+        edges,
+        truncated,
+        cached: false,
+    }
+}
+
+/// Identifies a trace by its inputs, so a repeated trace can be served from
+/// the cache.
+pub fn trace_cache_key(request: &FlowTraceRequest, entities: &[KnownEntity]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(serde_json::to_vec(request).unwrap_or_default());
+    hasher.update(serde_json::to_vec(entities).unwrap_or_default());
+    format!("{:x}", hasher.finalize())
+}
+
+struct GraphMlWriter {
+    out: String,
+}
+
+impl GraphMlWriter {
+    fn new(keys: &[(&str, &str, &str)], graph_id: &str) -> Self {
+        let mut out = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+        );
+        for (id, domain, kind) in keys {
+            out.push_str(&format!(
+                "  <key id=\"{id}\" for=\"{domain}\" attr.name=\"{id}\" attr.type=\"{kind}\"/>\n"
+            ));
+        }
+        out.push_str(&format!(
+            "  <graph id=\"{}\" edgedefault=\"directed\">\n",
+            escape_xml(graph_id)
+        ));
+        Self { out }
+    }
+
+    fn data(&mut self, data: &[(&str, String)]) {
+        for (key, value) in data {
+            self.out.push_str(&format!(
+                "      <data key=\"{}\">{}</data>\n",
+                key,
+                escape_xml(value)
+            ));
+        }
+    }
+
+    fn node(&mut self, id: &str, data: &[(&str, String)]) {
+        self.out
+            .push_str(&format!("    <node id=\"{}\">\n", escape_xml(id)));
+        self.data(data);
+        self.out.push_str("    </node>\n");
+    }
+
+    fn edge(&mut self, id: &str, source: &str, target: &str, data: &[(&str, String)]) {
+        self.out.push_str(&format!(
+            "    <edge id=\"{}\" source=\"{}\" target=\"{}\">\n",
+            escape_xml(id),
+            escape_xml(source),
+            escape_xml(target)
+        ));
+        self.data(data);
+        self.out.push_str("    </edge>\n");
+    }
+
+    fn finish(mut self) -> String {
+        self.out.push_str("  </graph>\n</graphml>\n");
+        self.out
+    }
+}
+
+fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn category_name(category: EndpointCategory) -> String {
+    serde_json::to_value(category)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+pub fn flow_trace_to_graphml(trace: &FlowTrace) -> String {
+    let mut writer = GraphMlWriter::new(
+        &[
+            ("label", "node", "string"),
+            ("category", "node", "string"),
+            ("depth", "node", "int"),
+            ("cumulativeValue", "node", "double"),
+            ("terminal", "node", "boolean"),
+            ("amount", "edge", "double"),
+            ("timestamp", "edge", "long"),
+            ("transactionHash", "edge", "string"),
+            ("hop", "edge", "int"),
+        ],
+        &trace.source_wallet,
+    );
+
+    for node in &trace.nodes {
+        let mut data = vec![
+            ("depth", node.depth.to_string()),
+            ("cumulativeValue", node.cumulative_value.to_string()),
+            ("terminal", node.terminal.to_string()),
+        ];
+        if let Some(label) = &node.label {
+            data.push(("label", label.clone()));
+        }
+        if let Some(category) = node.category {
+            data.push(("category", category_name(category)));
+        }
+        writer.node(&node.id, &data);
+    }
+    for (i, edge) in trace.edges.iter().enumerate() {
+        writer.edge(
+            &format!("e{}", i),
+            &edge.source,
+            &edge.target,
+            &[
+                ("amount", edge.amount.to_string()),
+                ("timestamp", edge.timestamp.to_string()),
+                ("transactionHash", edge.transaction_hash.clone()),
+                ("hop", edge.hop.to_string()),
+            ],
+        );
+    }
+
+    writer.finish()
+}
+
+pub fn flow_graph_to_graphml(graph: &TokenFlowGraph) -> String {
+    let mut writer = GraphMlWriter::new(
+        &[
+            ("label", "node", "string"),
+            ("balance", "node", "double"),
+            ("clusterId", "node", "string"),
+            ("amount", "edge", "double"),
+            ("timestamp", "edge", "long"),
+            ("transactionHash", "edge", "string"),
+        ],
+        &graph.token_address,
+    );
+
+    for node in &graph.nodes {
+        let mut data = vec![("balance", node.balance.to_string())];
+        if let Some(label) = &node.label {
+            data.push(("label", label.clone()));
+        }
+        if let Some(cluster_id) = &node.cluster_id {
+            data.push(("clusterId", cluster_id.clone()));
+        }
+        writer.node(&node.id, &data);
+    }
+    for (i, edge) in graph.edges.iter().enumerate() {
+        writer.edge(
+            &format!("e{}", i),
+            &edge.source,
+            &edge.target,
+            &[
+                ("amount", edge.amount.to_string()),
+                ("timestamp", edge.timestamp.to_string()),
+                ("transactionHash", edge.transaction_hash.clone()),
+            ],
+        );
+    }
+
+    writer.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tx(source: &str, target: &str, amount: f64, timestamp: i64) -> TokenTransaction {
+        TokenTransaction {
+            source: source.to_string(),
+            target: target.to_string(),
+            amount,
+            timestamp,
+            token_address: "MINT".to_string(),
+            transaction_hash: format!("{}-{}-{}", source, target, timestamp),
+            usd_value: None,
+        }
+    }
+
+    fn request(transactions: Vec<TokenTransaction>) -> FlowTraceRequest {
+        FlowTraceRequest {
+            source_wallet: "A".to_string(),
+            token_address: "MINT".to_string(),
+            transactions,
+            max_depth: 3,
+            min_hop_values: vec![],
+            max_nodes: 250,
+            start_time: None,
+            end_time: None,
+            known_entities: vec![],
+        }
+    }
+
+    #[test]
+    fn test_trace_follows_hops_forward_in_time_and_stops_at_endpoints() {
+        let mut req = request(vec![
+            tx("A", "B", 100.0, 10),
+            tx("B", "C", 90.0, 20),
+            tx("B", "D", 1.0, 25),
+            tx("B", "X", 50.0, 5), // left B before the funds arrived
+            tx("C", "CEX", 80.0, 30),
+            tx("CEX", "E", 80.0, 40),
+        ]);
+        req.min_hop_values = vec![0.0, 5.0];
+        let entities = vec![KnownEntity {
+            address: "CEX".to_string(),
+            name: "Exchange".to_string(),
+            category: EndpointCategory::CexDeposit,
+        }];
+
+        let trace = trace_flows(&req, &entities);
+        let ids: Vec<&str> = trace.nodes.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids, vec!["A", "B", "C", "CEX"]);
+
+        let cex = trace.nodes.iter().find(|n| n.id == "CEX").unwrap();
+        assert!(cex.terminal);
+        assert_eq!(cex.depth, 3);
+        assert_eq!(cex.category, Some(EndpointCategory::CexDeposit));
+        assert_eq!(cex.cumulative_value, 80.0);
+        assert!(!trace.truncated);
+        assert!(flow_trace_to_graphml(&trace).contains("<data key=\"category\">cex_deposit</data>"));
+    }
+
+    #[test]
+    fn test_trace_is_bounded_by_max_nodes() {
+        let mut req = request(
+            (0..10)
+                .map(|i| tx("A", &format!("W{}", i), 10.0, i))
+                .collect(),
+        );
+        req.max_nodes = 4;
+
+        let trace = trace_flows(&req, &[]);
+        assert_eq!(trace.nodes.len(), 4);
+        assert_eq!(trace.edges.len(), 3);
+        assert!(trace.truncated);
+        assert_ne!(
+            trace_cache_key(&req, &[]),
+            trace_cache_key(&request(vec![]), &[])
+        );
+    }
+}
@@ -10,6 +10,9 @@ pub struct TokenTransaction {
     pub timestamp: i64,
     pub token_address: String,
     pub transaction_hash: String,
+    /// USD value at transfer time, when the feed knows it.
+    #[serde(default)]
+    pub usd_value: Option<f64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -184,6 +187,11 @@ pub struct ClusterSubscription {
     pub cluster_id: String,
     pub alerts: ClusterSubscriptionAlerts,
     pub notification_channels: Vec<NotificationChannel>,
+    #[serde(default)]
+    pub triggers: Vec<ClusterTrigger>,
+    /// Most recent firings, newest first. Filled in when listing.
+    #[serde(default)]
+    pub trigger_history: Vec<ClusterTriggerEvent>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -203,6 +211,73 @@ pub enum NotificationChannel {
     Webhook,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ClusterTrigger {
+    pub id: String,
+    pub condition: ClusterTriggerCondition,
+    #[serde(default = "default_trigger_enabled")]
+    pub enabled: bool,
+}
+
+fn default_trigger_enabled() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(
+    tag = "kind",
+    rename_all = "snake_case",
+    rename_all_fields = "camelCase"
+)]
+pub enum ClusterTriggerCondition {
+    /// Cluster members sent more than `threshold_usd` to exchange deposit
+    /// addresses within the last `window_hours`.
+    ExchangeOutflow {
+        threshold_usd: f64,
+        window_hours: u32,
+        /// Used for transfers that arrive without a USD value.
+        #[serde(default)]
+        price_usd: Option<f64>,
+        /// Extra deposit addresses on top of the built-in exchange labels.
+        #[serde(default)]
+        exchange_addresses: Vec<String>,
+    },
+    /// The mint's deployer sent tokens of that mint to a cluster member.
+    DeployerFunding {
+        mint_address: String,
+        deployer: String,
+    },
+    /// A cluster member appears on the wallet blacklist.
+    MemberBlacklisted,
+}
+
+impl ClusterTriggerCondition {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ClusterTriggerCondition::ExchangeOutflow { .. } => "exchange_outflow",
+            ClusterTriggerCondition::DeployerFunding { .. } => "deployer_funding",
+            ClusterTriggerCondition::MemberBlacklisted => "member_blacklisted",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ClusterTriggerEvent {
+    pub id: String,
+    pub subscription_id: String,
+    pub trigger_id: String,
+    pub kind: String,
+    pub title: String,
+    pub message: String,
+    pub wallets: Vec<String>,
+    /// The transfers that caused the trigger to fire.
+    pub transactions: Vec<TokenTransaction>,
+    pub value_usd: Option<f64>,
+    pub fired_at: i64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct SankeyNode {
@@ -266,6 +341,8 @@ pub enum FlowExportFormat {
     Csv,
     Png,
     Svg,
+    #[serde(rename = "graphml")]
+    GraphMl,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -275,4 +352,100 @@ pub struct FlowExportContent {
     pub clusters: Option<Vec<WalletCluster>>,
     pub alerts: Option<Vec<TokenFlowAlert>>,
     pub snapshot: Option<String>,
+    #[serde(default)]
+    pub trace: Option<FlowTrace>,
+    #[serde(default)]
+    pub graphml: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum EndpointCategory {
+    CexDeposit,
+    Bridge,
+    Burn,
+    Mixer,
+    Blacklisted,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct KnownEntity {
+    pub address: String,
+    pub name: String,
+    pub category: EndpointCategory,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FlowTraceRequest {
+    pub source_wallet: String,
+    pub token_address: String,
+    pub transactions: Vec<TokenTransaction>,
+    #[serde(default = "default_trace_depth")]
+    pub max_depth: usize,
+    /// Minimum amount for a transfer to be followed at each hop; hops past
+    /// the end of the list use its last value.
+    #[serde(default)]
+    pub min_hop_values: Vec<f64>,
+    #[serde(default = "default_trace_max_nodes")]
+    pub max_nodes: usize,
+    #[serde(default)]
+    pub start_time: Option<i64>,
+    #[serde(default)]
+    pub end_time: Option<i64>,
+    /// Extra labels on top of the built-in entities and the blacklist.
+    #[serde(default)]
+    pub known_entities: Vec<KnownEntity>,
+}
+
+fn default_trace_depth() -> usize {
+    3
+}
+
+fn default_trace_max_nodes() -> usize {
+    250
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FlowTraceNode {
+    pub id: String,
+    pub address: String,
+    pub label: Option<String>,
+    pub category: Option<EndpointCategory>,
+    /// Hops from the source wallet.
+    pub depth: usize,
+    /// Traced value that reached this node.
+    pub cumulative_value: f64,
+    /// Labeled endpoints are not traced further.
+    pub terminal: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FlowTraceEdge {
+    pub id: String,
+    pub source: String,
+    pub target: String,
+    pub amount: f64,
+    pub timestamp: i64,
+    pub transaction_hash: String,
+    /// 1 for transfers out of the source wallet.
+    pub hop: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FlowTrace {
+    pub source_wallet: String,
+    pub token_address: String,
+    pub time_range: TimeRange,
+    pub max_depth: usize,
+    pub nodes: Vec<FlowTraceNode>,
+    pub edges: Vec<FlowTraceEdge>,
+    /// The node limit was hit and some flows were left out.
+    pub truncated: bool,
+    #[serde(default)]
+    pub cached: bool,
 }
@@ -0,0 +1,850 @@
+//! Automatic position manager: brackets filled buys with exit orders.
+//!
+//! When a buy fills through a source the policy covers (manual orders, DCA
+//! or copy trading), the bought amount gets a sell stop-loss and/or
+//! take-profit at an offset from the fill price, either a percentage or a
+//! multiple of the token's ATR. Both legs carry the buy's id as their
+//! `linked_order_id`, so the order engine cancels one when the other fills
+//! or is cancelled. The legs are tagged [`OrderOrigin::AutoBracket`], which
+//! is how `get_active_orders` tells them apart from the user's own orders.
+
+use crate::api::http_client::{ProviderClient, ProviderProfile};
+use crate::chart_stream::{self, CandleResolution};
+use crate::config::DataPaths;
+use crate::errors::AppError;
+use crate::indicators::{Formula, FormulaEvaluator};
+use crate::trading::events::{
+    order_events, OrderEvent, OrderEventKind, OrderEventSource, OrderRoute,
+};
+use crate::trading::limit_orders::require_state;
+use crate::trading::order_manager::OrderManager;
+use crate::trading::types::{CreateOrderRequest, Order, OrderOrigin, OrderSide, OrderType};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::iter;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Runtime, State};
+use tokio::sync::broadcast::error::RecvError;
+
+const AUTO_BRACKET_POLICY_FILE: &str = "auto_bracket.json";
+/// Emitted with a [`BracketOutcome`] for every fill the policy acted on or
+/// declined.
+pub const AUTO_BRACKET_EVENT: &str = "auto_bracket_outcome";
+
+#[derive(Debug, thiserror::Error)]
+pub enum AutoBracketError {
+    #[error("invalid bracket policy: {0}")]
+    Invalid(String),
+    #[error("{0} open bracket order(s) exist; choose whether to keep or cancel them")]
+    ConfirmationRequired(usize),
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+}
+
+impl From<AutoBracketError> for AppError {
+    fn from(err: AutoBracketError) -> Self {
+        match err {
+            AutoBracketError::Invalid(message) => {
+                AppError::validation(message).with_code("invalid_bracket_policy")
+            }
+            AutoBracketError::ConfirmationRequired(open_orders) => {
+                AppError::validation(err.to_string())
+                    .with_code("bracket_confirmation_required")
+                    .with_detail("openOrders", open_orders)
+            }
+            AutoBracketError::Io(err) => err.into(),
+            AutoBracketError::Serialization(err) => err.into(),
+        }
+    }
+}
+
+/// How far from the fill price a bracket leg sits.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum BracketOffset {
+    /// A percentage of the fill price.
+    Percent { percent: f64 },
+    /// A multiple of the token's average true range.
+    Atr { multiplier: f64 },
+}
+
+impl BracketOffset {
+    fn uses_atr(&self) -> bool {
+        matches!(self, BracketOffset::Atr { .. })
+    }
+
+    /// Price distance from `fill_price`; `None` for an ATR offset when no
+    /// ATR is known.
+    fn distance(&self, fill_price: f64, atr: Option<f64>) -> Option<f64> {
+        match self {
+            BracketOffset::Percent { percent } => Some(fill_price * percent / 100.0),
+            BracketOffset::Atr { multiplier } => atr.map(|atr| atr * multiplier),
+        }
+    }
+
+    fn validate(&self, leg: &str) -> Result<(), AutoBracketError> {
+        let value = match self {
+            BracketOffset::Percent { percent } => *percent,
+            BracketOffset::Atr { multiplier } => *multiplier,
+        };
+        if !value.is_finite() || value <= 0.0 {
+            return Err(AutoBracketError::Invalid(format!(
+                "The {} offset must be positive",
+                leg
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Which kinds of fill get bracketed. Paper trades never are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BracketSources {
+    pub manual: bool,
+    pub dca: bool,
+    pub copy_trade: bool,
+}
+
+impl BracketSources {
+    fn covers(&self, source: OrderEventSource) -> bool {
+        match source {
+            OrderEventSource::Live => self.manual,
+            OrderEventSource::Dca => self.dca,
+            OrderEventSource::CopyTrade => self.copy_trade,
+            OrderEventSource::Paper => false,
+        }
+    }
+}
+
+/// Replaces the policy's offsets for one token.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenBracketOverride {
+    /// False leaves the token's buys unbracketed.
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub stop_loss: Option<BracketOffset>,
+    #[serde(default)]
+    pub take_profit: Option<BracketOffset>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BracketPolicy {
+    pub enabled: bool,
+    pub sources: BracketSources,
+    /// `None` places no stop-loss.
+    pub stop_loss: Option<BracketOffset>,
+    /// `None` places no take-profit.
+    pub take_profit: Option<BracketOffset>,
+    /// Candles ATR offsets are measured over, and their resolution.
+    pub atr_period: usize,
+    pub atr_resolution: CandleResolution,
+    /// Open orders the policy may have placed at once. Buys that would go
+    /// past it are left unbracketed.
+    pub max_open_orders: usize,
+    /// Tokens with less pool liquidity than this are not bracketed.
+    pub min_liquidity_usd: f64,
+    pub slippage_bps: i32,
+    /// Offsets by token mint.
+    #[serde(default)]
+    pub overrides: HashMap<String, TokenBracketOverride>,
+}
+
+impl Default for BracketPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            sources: BracketSources {
+                manual: true,
+                dca: true,
+                copy_trade: true,
+            },
+            stop_loss: Some(BracketOffset::Percent { percent: 10.0 }),
+            take_profit: Some(BracketOffset::Percent { percent: 25.0 }),
+            atr_period: 14,
+            atr_resolution: CandleResolution::OneHour,
+            max_open_orders: 20,
+            min_liquidity_usd: 50_000.0,
+            slippage_bps: 100,
+            overrides: HashMap::new(),
+        }
+    }
+}
+
+impl BracketPolicy {
+    fn validate(&self) -> Result<(), AutoBracketError> {
+        let stops = iter::once(self.stop_loss).chain(self.overrides.values().map(|o| o.stop_loss));
+        for offset in stops.flatten() {
+            offset.validate("stop-loss")?;
+            // A stop 100% below the fill would sit at zero.
+            if matches!(offset, BracketOffset::Percent { percent } if percent >= 100.0) {
+                return Err(AutoBracketError::Invalid(
+                    "A stop-loss must be less than 100% below the fill".to_string(),
+                ));
+            }
+        }
+        let takes =
+            iter::once(self.take_profit).chain(self.overrides.values().map(|o| o.take_profit));
+        for offset in takes.flatten() {
+            offset.validate("take-profit")?;
+        }
+        if !(1..=200).contains(&self.atr_period) {
+            return Err(AutoBracketError::Invalid(
+                "ATR period must be between 1 and 200 candles".to_string(),
+            ));
+        }
+        if self.atr_resolution.birdeye_type().is_none() {
+            return Err(AutoBracketError::Invalid(
+                "ATR resolution must be 1m or longer".to_string(),
+            ));
+        }
+        if self.max_open_orders == 0 {
+            return Err(AutoBracketError::Invalid(
+                "At least one open bracket order must be allowed".to_string(),
+            ));
+        }
+        if !self.min_liquidity_usd.is_finite() || self.min_liquidity_usd < 0.0 {
+            return Err(AutoBracketError::Invalid(
+                "Minimum liquidity cannot be negative".to_string(),
+            ));
+        }
+        if !(0..=5_000).contains(&self.slippage_bps) {
+            return Err(AutoBracketError::Invalid(
+                "Slippage must be between 0 and 5000 bps".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// The stop-loss and take-profit offsets for `mint`, or `None` when the
+    /// token is excluded.
+    fn legs_for(&self, mint: &str) -> Option<(Option<BracketOffset>, Option<BracketOffset>)> {
+        match self.overrides.get(mint) {
+            Some(token) if !token.enabled => None,
+            Some(token) => Some((
+                token.stop_loss.or(self.stop_loss),
+                token.take_profit.or(self.take_profit),
+            )),
+            None => Some((self.stop_loss, self.take_profit)),
+        }
+    }
+}
+
+/// Whether `next` turns off a policy that still has orders in the market.
+/// The orders are only kept or cancelled on the user's say.
+fn requires_confirmation(current: &BracketPolicy, next: &BracketPolicy, open: usize) -> bool {
+    current.enabled && !next.enabled && open > 0
+}
+
+/// What to do with the open bracket orders when turning the policy off.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExistingBrackets {
+    Keep,
+    Cancel,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BracketPrices {
+    pub stop_loss: Option<f64>,
+    pub take_profit: Option<f64>,
+}
+
+fn bracket_prices(
+    fill_price: f64,
+    stop_loss: Option<BracketOffset>,
+    take_profit: Option<BracketOffset>,
+    atr: Option<f64>,
+) -> Result<BracketPrices, String> {
+    let distance = |offset: BracketOffset| {
+        offset
+            .distance(fill_price, atr)
+            .ok_or_else(|| "No ATR is available for the token".to_string())
+    };
+    let stop_loss = match stop_loss {
+        Some(offset) => {
+            let stop = fill_price - distance(offset)?;
+            if stop <= 0.0 {
+                return Err(format!(
+                    "Stop-loss offset puts the stop at or below zero for a fill at {}",
+                    fill_price
+                ));
+            }
+            Some(stop)
+        }
+        None => None,
+    };
+    let take_profit = match take_profit {
+        Some(offset) => Some(fill_price + distance(offset)?),
+        None => None,
+    };
+    Ok(BracketPrices {
+        stop_loss,
+        take_profit,
+    })
+}
+
+/// A filled buy the policy looked at.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BracketOutcome {
+    pub buy_order_id: String,
+    pub source: OrderEventSource,
+    pub mint: String,
+    pub symbol: String,
+    pub stop_loss_order_id: Option<String>,
+    pub take_profit_order_id: Option<String>,
+    /// Why the buy, or one of its legs, went unbracketed.
+    pub skipped: Option<String>,
+}
+
+fn is_bracket_order(order: &Order) -> bool {
+    order.origin == Some(OrderOrigin::AutoBracket)
+}
+
+pub struct AutoBrackets {
+    path: Option<PathBuf>,
+    policy: RwLock<BracketPolicy>,
+}
+
+pub type SharedAutoBrackets = Arc<AutoBrackets>;
+
+impl AutoBrackets {
+    pub fn new(app: &AppHandle) -> Result<Self, AutoBracketError> {
+        let dir = app.app_data_root().map_err(|e| {
+            AutoBracketError::Invalid(format!("App data directory not found: {}", e))
+        })?;
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(AUTO_BRACKET_POLICY_FILE);
+        let policy = if path.exists() {
+            serde_json::from_str(&fs::read_to_string(&path)?)?
+        } else {
+            BracketPolicy::default()
+        };
+        Ok(Self {
+            path: Some(path),
+            policy: RwLock::new(policy),
+        })
+    }
+
+    /// Brackets whose policy is kept in memory only.
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            policy: RwLock::new(BracketPolicy::default()),
+        }
+    }
+
+    pub fn policy(&self) -> BracketPolicy {
+        self.policy.read().clone()
+    }
+
+    pub fn set_policy(&self, policy: BracketPolicy) -> Result<(), AutoBracketError> {
+        policy.validate()?;
+        if let Some(path) = &self.path {
+            let tmp = path.with_extension("json.tmp");
+            fs::write(&tmp, serde_json::to_vec_pretty(&policy)?)?;
+            fs::rename(&tmp, path)?;
+        }
+        *self.policy.write() = policy;
+        Ok(())
+    }
+
+    /// Brackets the buy `event` reports filled, if the policy covers it.
+    async fn on_fill(&self, app: &AppHandle, manager: &OrderManager, event: &OrderEvent) {
+        let policy = self.policy();
+        if !policy.enabled || event.side != OrderSide::Buy || !policy.sources.covers(event.source) {
+            return;
+        }
+        let Some(route) = event.route() else {
+            return;
+        };
+
+        let mut outcome = BracketOutcome {
+            buy_order_id: event.order_id.clone(),
+            source: event.source,
+            mint: route.output_mint.clone(),
+            symbol: route.output_symbol.clone(),
+            stop_loss_order_id: None,
+            take_profit_order_id: None,
+            skipped: None,
+        };
+        let placed = self
+            .place_bracket(manager, &policy, event, &route, &mut outcome)
+            .await;
+        match placed {
+            Ok(false) => return,
+            Ok(true) => {}
+            Err(reason) => outcome.skipped = Some(reason),
+        }
+        if let Err(e) = app.emit(AUTO_BRACKET_EVENT, &outcome) {
+            eprintln!("Failed to emit bracket outcome: {}", e);
+        }
+    }
+
+    /// Places the legs, recording them in `outcome`. Returns false when the
+    /// token is excluded, which isn't reported.
+    async fn place_bracket<R: Runtime>(
+        &self,
+        manager: &OrderManager<R>,
+        policy: &BracketPolicy,
+        event: &OrderEvent,
+        route: &OrderRoute,
+        outcome: &mut BracketOutcome,
+    ) -> Result<bool, String> {
+        let OrderEventKind::Filled {
+            filled_amount,
+            fill_price,
+            ..
+        } = event.kind
+        else {
+            return Ok(false);
+        };
+        let Some((stop_offset, take_offset)) = policy.legs_for(&outcome.mint) else {
+            return Ok(false);
+        };
+        if stop_offset.is_none() && take_offset.is_none() {
+            return Ok(false);
+        }
+        let fill_price = fill_price
+            .filter(|price| *price > 0.0)
+            .ok_or("The fill has no price")?;
+
+        if policy.min_liquidity_usd > 0.0 {
+            let liquidity = token_liquidity_usd(&outcome.mint).await?;
+            if liquidity < policy.min_liquidity_usd {
+                return Err(format!(
+                    "Liquidity of ${:.0} is below the ${:.0} minimum",
+                    liquidity, policy.min_liquidity_usd
+                ));
+            }
+        }
+
+        let uses_atr = [stop_offset, take_offset]
+            .iter()
+            .flatten()
+            .any(BracketOffset::uses_atr);
+        let atr = if uses_atr {
+            Some(token_atr(&outcome.mint, policy.atr_resolution, policy.atr_period).await?)
+        } else {
+            None
+        };
+        let prices = bracket_prices(fill_price, stop_offset, take_offset, atr)?;
+
+        let open = manager
+            .get_all_active_orders()
+            .await
+            .map_err(|e| e.to_string())?
+            .iter()
+            .filter(|order| is_bracket_order(order))
+            .count();
+        let legs = [prices.stop_loss, prices.take_profit]
+            .iter()
+            .flatten()
+            .count();
+        if open + legs > policy.max_open_orders {
+            return Err(format!(
+                "{} bracket orders are already open; the limit is {}",
+                open, policy.max_open_orders
+            ));
+        }
+
+        let leg = |order_type: OrderType, limit_price: Option<f64>, stop_price: Option<f64>| {
+            CreateOrderRequest {
+                order_type,
+                side: OrderSide::Sell,
+                input_mint: route.output_mint.clone(),
+                output_mint: route.input_mint.clone(),
+                input_symbol: route.output_symbol.clone(),
+                output_symbol: route.input_symbol.clone(),
+                amount: filled_amount,
+                limit_price,
+                stop_price,
+                trailing_percent: None,
+                linked_order_id: Some(event.order_id.clone()),
+                min_book_depth_usd: None,
+                slippage_bps: policy.slippage_bps,
+                priority_fee_micro_lamports: event
+                    .order
+                    .as_ref()
+                    .map(|order| order.priority_fee_micro_lamports)
+                    .unwrap_or(0),
+                wallet_address: route.wallet_address.clone(),
+                client_request_id: None,
+                correlation_id: None,
+                origin: Some(OrderOrigin::AutoBracket),
+            }
+        };
+
+        if let Some(stop) = prices.stop_loss {
+            let order = manager
+                .create_order(leg(OrderType::StopLoss, None, Some(stop)))
+                .await
+                .map_err(|e| format!("Failed to place stop-loss: {}", e))?;
+            outcome.stop_loss_order_id = Some(order.id);
+        }
+        if let Some(take) = prices.take_profit {
+            match manager
+                .create_order(leg(OrderType::TakeProfit, Some(take), None))
+                .await
+            {
+                Ok(order) => outcome.take_profit_order_id = Some(order.id),
+                Err(e) => outcome.skipped = Some(format!("Failed to place take-profit: {}", e)),
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// Pool liquidity of `mint` in USD, from Birdeye.
+async fn token_liquidity_usd(mint: &str) -> Result<f64, String> {
+    let client = ProviderClient::new(ProviderProfile::BIRDEYE);
+    if !client.has_api_key() {
+        return Err("Liquidity is unknown: Birdeye API key not configured".to_string());
+    }
+    let body: Value = client
+        .get("/defi/price")
+        .query(&[("address", mint), ("include_liquidity", "true")])
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch liquidity: {}", e))?
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse liquidity: {}", e))?;
+    body["data"]["liquidity"]
+        .as_f64()
+        .ok_or_else(|| "Liquidity is unknown for the token".to_string())
+}
+
+/// The latest `atr(period)` of `mint`, from the chart stream's candles when it
+/// has enough and from Birdeye otherwise.
+async fn token_atr(mint: &str, resolution: CandleResolution, period: usize) -> Result<f64, String> {
+    let formula = Formula::compile(&format!("atr({})", period)).map_err(|e| e.to_string())?;
+    let bars = formula.warmup_bars();
+    let mut candles = chart_stream::recent_candles(mint, resolution, bars);
+    if candles.len() < bars {
+        candles = chart_stream::fetch_recent_candles(mint, resolution, bars)
+            .await
+            .map_err(|e| format!("Failed to load candles for the ATR: {}", e))?;
+    }
+    let values = FormulaEvaluator::new(&candles)?.evaluate(&formula)?;
+    values
+        .last()
+        .copied()
+        .flatten()
+        .filter(|atr| *atr > 0.0)
+        .ok_or_else(|| format!("Not enough {} candles for the ATR", resolution.as_str()))
+}
+
+/// Brackets buys as they fill. Fills arriving before the trading module is
+/// initialized are left alone.
+pub async fn run_auto_brackets(app: AppHandle, brackets: SharedAutoBrackets) {
+    let mut events = order_events();
+    loop {
+        match events.recv().await {
+            Ok(event) => {
+                if !matches!(event.kind, OrderEventKind::Filled { .. }) {
+                    continue;
+                }
+                let Ok(state) = require_state() else {
+                    continue;
+                };
+                brackets.on_fill(&app, &state.manager, &event).await;
+            }
+            Err(RecvError::Lagged(skipped)) => {
+                eprintln!("Auto brackets skipped {} order events", skipped);
+            }
+            Err(RecvError::Closed) => break,
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn get_auto_bracket_policy(
+    brackets: State<'_, SharedAutoBrackets>,
+) -> Result<BracketPolicy, AppError> {
+    Ok(brackets.policy())
+}
+
+/// Saves the policy. Turning it off while bracket orders are open needs
+/// `existing` to say whether they stay; returns how many were cancelled.
+#[tauri::command]
+pub async fn set_auto_bracket_policy(
+    policy: BracketPolicy,
+    existing: Option<ExistingBrackets>,
+    brackets: State<'_, SharedAutoBrackets>,
+) -> Result<usize, AppError> {
+    policy.validate()?;
+    let open: Vec<Order> = match require_state() {
+        Ok(state) => state
+            .manager
+            .get_all_active_orders()
+            .await?
+            .into_iter()
+            .filter(is_bracket_order)
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    let confirm = requires_confirmation(&brackets.policy(), &policy, open.len());
+    if confirm && existing.is_none() {
+        return Err(AutoBracketError::ConfirmationRequired(open.len()).into());
+    }
+    brackets.set_policy(policy)?;
+
+    if !confirm || existing != Some(ExistingBrackets::Cancel) {
+        return Ok(0);
+    }
+    let state = require_state()?;
+    for order in &open {
+        // Cancelling one leg already cancels its sibling.
+        match state.manager.cancel_order(&order.id).await {
+            Err(e) if e.code() != "order_not_cancellable" => return Err(e),
+            _ => {}
+        }
+    }
+    Ok(open.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trading::database::OrderDatabase;
+    use crate::trading::types::OrderStatus;
+    use tauri::test::{mock_app, MockRuntime};
+
+    fn percent(percent: f64) -> Option<BracketOffset> {
+        Some(BracketOffset::Percent { percent })
+    }
+
+    async fn order_manager(dir: &tempfile::TempDir) -> OrderManager<MockRuntime> {
+        let db = OrderDatabase::new(dir.path().join("orders.db"))
+            .await
+            .unwrap();
+        OrderManager::new(
+            Arc::new(tokio::sync::RwLock::new(db)),
+            mock_app().handle().clone(),
+        )
+    }
+
+    fn filled_buy(order_id: &str) -> OrderEvent {
+        OrderEvent::new(
+            OrderEventSource::Dca,
+            order_id,
+            "BONK",
+            OrderSide::Buy,
+            1_000.0,
+            OrderEventKind::Filled {
+                filled_amount: 1_000.0,
+                fill_price: Some(2.0),
+                tx_signature: None,
+            },
+        )
+        .with_route(OrderRoute {
+            wallet_address: "wallet".to_string(),
+            input_mint: "usdc".to_string(),
+            output_mint: "bonk".to_string(),
+            input_symbol: "USDC".to_string(),
+            output_symbol: "BONK".to_string(),
+        })
+    }
+
+    async fn bracket(
+        brackets: &AutoBrackets,
+        manager: &OrderManager<MockRuntime>,
+        event: &OrderEvent,
+    ) -> Result<BracketOutcome, String> {
+        let route = event.route().unwrap();
+        let mut outcome = BracketOutcome {
+            buy_order_id: event.order_id.clone(),
+            source: event.source,
+            mint: route.output_mint.clone(),
+            symbol: route.output_symbol.clone(),
+            stop_loss_order_id: None,
+            take_profit_order_id: None,
+            skipped: None,
+        };
+        let policy = brackets.policy();
+        brackets
+            .place_bracket(manager, &policy, event, &route, &mut outcome)
+            .await?;
+        Ok(outcome)
+    }
+
+    fn test_brackets(max_open_orders: usize) -> AutoBrackets {
+        let brackets = AutoBrackets::in_memory();
+        brackets
+            .set_policy(BracketPolicy {
+                enabled: true,
+                max_open_orders,
+                min_liquidity_usd: 0.0,
+                ..BracketPolicy::default()
+            })
+            .unwrap();
+        brackets
+    }
+
+    #[tokio::test]
+    async fn fills_are_bracketed_with_linked_legs() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = order_manager(&dir).await;
+        let brackets = test_brackets(20);
+
+        let outcome = bracket(&brackets, &manager, &filled_buy("buy-1"))
+            .await
+            .unwrap();
+        assert_eq!(outcome.skipped, None);
+        let stop = manager
+            .get_order(&outcome.stop_loss_order_id.unwrap())
+            .await
+            .unwrap();
+        let take = manager
+            .get_order(&outcome.take_profit_order_id.unwrap())
+            .await
+            .unwrap();
+        for leg in [&stop, &take] {
+            assert_eq!(leg.linked_order_id.as_deref(), Some("buy-1"));
+            assert_eq!(leg.origin, Some(OrderOrigin::AutoBracket));
+            assert_eq!(leg.side, OrderSide::Sell);
+            assert_eq!(leg.input_mint, "bonk");
+            assert_eq!(leg.amount, 1_000.0);
+        }
+        assert!((stop.stop_price.unwrap() - 1.8).abs() < 1e-9);
+        assert!((take.limit_price.unwrap() - 2.5).abs() < 1e-9);
+
+        // The stop fills, which cancels the take-profit.
+        manager.update_price("bonk", 1.7).await;
+        manager.check_and_trigger_orders().await.unwrap();
+        let stop = manager.get_order(&stop.id).await.unwrap();
+        let take = manager.get_order(&take.id).await.unwrap();
+        assert_eq!(stop.status, OrderStatus::Filled);
+        assert_eq!(take.status, OrderStatus::Cancelled);
+    }
+
+    #[tokio::test]
+    async fn open_order_limit_leaves_fills_unbracketed() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = order_manager(&dir).await;
+        let brackets = test_brackets(3);
+
+        bracket(&brackets, &manager, &filled_buy("buy-1"))
+            .await
+            .unwrap();
+        let err = bracket(&brackets, &manager, &filled_buy("buy-2"))
+            .await
+            .unwrap_err();
+        assert!(err.contains("the limit is 3"), "{err}");
+        let open = manager.get_all_active_orders().await.unwrap();
+        assert_eq!(
+            open.iter().filter(|order| is_bracket_order(order)).count(),
+            2
+        );
+    }
+
+    #[test]
+    fn percent_offsets_bracket_the_fill() {
+        let prices = bracket_prices(2.0, percent(10.0), percent(50.0), None).unwrap();
+        assert!((prices.stop_loss.unwrap() - 1.8).abs() < 1e-9);
+        assert!((prices.take_profit.unwrap() - 3.0).abs() < 1e-9);
+
+        let stop_only = bracket_prices(2.0, percent(10.0), None, None).unwrap();
+        assert_eq!(stop_only.take_profit, None);
+    }
+
+    #[test]
+    fn atr_offsets_need_an_atr() {
+        let atr = Some(BracketOffset::Atr { multiplier: 2.0 });
+        let prices = bracket_prices(10.0, atr, atr, Some(0.5)).unwrap();
+        assert_eq!(prices.stop_loss, Some(9.0));
+        assert_eq!(prices.take_profit, Some(11.0));
+
+        assert!(bracket_prices(10.0, atr, None, None).is_err());
+        // A stop wider than the price would sit below zero.
+        assert!(bracket_prices(10.0, atr, None, Some(6.0)).is_err());
+    }
+
+    #[test]
+    fn overrides_replace_or_exclude_tokens() {
+        let mut policy = BracketPolicy::default();
+        policy.overrides.insert(
+            "bonk".to_string(),
+            TokenBracketOverride {
+                enabled: true,
+                stop_loss: percent(30.0),
+                take_profit: None,
+            },
+        );
+        policy.overrides.insert(
+            "scam".to_string(),
+            TokenBracketOverride {
+                enabled: false,
+                stop_loss: None,
+                take_profit: None,
+            },
+        );
+
+        assert_eq!(
+            policy.legs_for("bonk"),
+            Some((percent(30.0), policy.take_profit))
+        );
+        assert_eq!(policy.legs_for("scam"), None);
+        assert_eq!(
+            policy.legs_for("sol"),
+            Some((policy.stop_loss, policy.take_profit))
+        );
+    }
+
+    #[test]
+    fn sources_are_enabled_individually() {
+        let sources = BracketSources {
+            manual: true,
+            dca: false,
+            copy_trade: true,
+        };
+        assert!(sources.covers(OrderEventSource::Live));
+        assert!(!sources.covers(OrderEventSource::Dca));
+        assert!(sources.covers(OrderEventSource::CopyTrade));
+        assert!(!sources.covers(OrderEventSource::Paper));
+    }
+
+    #[test]
+    fn policy_rejects_bad_offsets() {
+        assert!(BracketPolicy::default().validate().is_ok());
+        let full_stop = BracketPolicy {
+            stop_loss: percent(100.0),
+            ..BracketPolicy::default()
+        };
+        assert!(full_stop.validate().is_err());
+        let no_orders = BracketPolicy {
+            max_open_orders: 0,
+            ..BracketPolicy::default()
+        };
+        assert!(no_orders.validate().is_err());
+    }
+
+    #[test]
+    fn disabling_with_open_brackets_needs_confirmation() {
+        let enabled = BracketPolicy {
+            enabled: true,
+            ..BracketPolicy::default()
+        };
+        let disabled = BracketPolicy::default();
+        assert!(requires_confirmation(&enabled, &disabled, 2));
+        assert!(!requires_confirmation(&enabled, &disabled, 0));
+        assert!(!requires_confirmation(&enabled, &enabled, 2));
+        assert!(!requires_confirmation(&disabled, &disabled, 2));
+    }
+}
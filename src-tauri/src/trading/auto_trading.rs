@@ -1,3 +1,4 @@
+use crate::webhooks::events::{emit_webhook_event, KillSwitchActivatedPayload, WebhookEventType};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -56,6 +57,10 @@ pub struct TradingStrategyInput {
     pub position_sizing: PositionSizingConfig,
     pub risk_controls: RiskControls,
     pub allowed_symbols: Vec<String>,
+    /// Paper account that shadow-mode runs trade against; `None` uses the
+    /// primary paper account.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paper_account_id: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -70,6 +75,10 @@ pub struct TradingStrategy {
     pub position_sizing: PositionSizingConfig,
     pub risk_controls: RiskControls,
     pub allowed_symbols: Vec<String>,
+    /// Paper account that shadow-mode runs trade against; `None` uses the
+    /// primary paper account.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paper_account_id: Option<String>,
     #[serde(default, skip_serializing_if = "is_param_empty")]
     pub optimized_parameters: HashMap<String, f64>,
     #[serde(with = "chrono::serde::ts_milliseconds")]
@@ -90,6 +99,8 @@ pub struct TradingStrategyUpdate {
     pub risk_controls: Option<RiskControls>,
     pub allowed_symbols: Option<Vec<String>>,
     pub optimized_parameters: Option<HashMap<String, f64>>,
+    /// An empty string clears the target back to the primary paper account.
+    pub paper_account_id: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -97,6 +108,8 @@ pub struct StrategyExecution {
     pub id: String,
     pub strategy_id: String,
     pub strategy_name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paper_account_id: Option<String>,
     pub status: ExecutionStatus,
     #[serde(with = "chrono::serde::ts_milliseconds")]
     pub started_at: DateTime<Utc>,
@@ -171,6 +184,7 @@ impl AutoTradingEngine {
             position_sizing: input.position_sizing,
             risk_controls: input.risk_controls,
             allowed_symbols: input.allowed_symbols,
+            paper_account_id: input.paper_account_id,
             optimized_parameters: HashMap::new(),
             created_at: now,
             updated_at: now,
@@ -221,6 +235,9 @@ impl AutoTradingEngine {
         if let Some(params) = updates.optimized_parameters {
             strategy.optimized_parameters = params;
         }
+        if let Some(account_id) = updates.paper_account_id {
+            strategy.paper_account_id = Some(account_id).filter(|id| !id.is_empty());
+        }
 
         strategy.updated_at = Utc::now();
         Ok(strategy.clone())
@@ -256,6 +273,7 @@ impl AutoTradingEngine {
             id: Uuid::new_v4().to_string(),
             strategy_id: strategy_id.to_string(),
             strategy_name: strategy.name.clone(),
+            paper_account_id: strategy.paper_account_id.clone(),
             status: ExecutionStatus::Running,
             started_at: Utc::now(),
             stopped_at: None,
@@ -294,14 +312,18 @@ impl AutoTradingEngine {
         }
     }
 
-    pub fn activate_kill_switch(&mut self) {
+    /// Stops every running strategy and returns how many were stopped.
+    pub fn activate_kill_switch(&mut self) -> usize {
         self.kill_switch_active = true;
+        let mut stopped = 0;
         for execution in self.executions.values_mut() {
             if execution.status == ExecutionStatus::Running {
                 execution.status = ExecutionStatus::Stopped;
                 execution.stopped_at = Some(Utc::now());
+                stopped += 1;
             }
         }
+        stopped
     }
 
     pub fn deactivate_kill_switch(&mut self) {
@@ -560,10 +582,18 @@ pub async fn auto_trading_pause_strategy(
 
 #[tauri::command]
 pub async fn auto_trading_activate_kill_switch(
+    app: tauri::AppHandle,
     engine: tauri::State<'_, SharedAutoTradingEngine>,
 ) -> Result<(), String> {
-    let mut engine = engine.lock().map_err(|e| e.to_string())?;
-    engine.activate_kill_switch();
+    let stopped_strategies = {
+        let mut engine = engine.lock().map_err(|e| e.to_string())?;
+        engine.activate_kill_switch()
+    };
+    emit_webhook_event(
+        &app,
+        WebhookEventType::KillSwitchActivated,
+        KillSwitchActivatedPayload { stopped_strategies },
+    );
     Ok(())
 }
 
@@ -0,0 +1,1027 @@
+use super::backtesting::{BacktestConfig, BacktestMetrics, BacktestResult, EquityPoint};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::{sqlite::SqliteRow, Pool, Row, Sqlite, SqlitePool};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tauri::State;
+
+pub const BACKTEST_DB_FILE: &str = "backtests.db";
+
+const RETENTION_KEY: &str = "retention";
+const MAX_TAGS: usize = 20;
+const MAX_TAG_CHARS: usize = 32;
+const MAX_NOTES_CHARS: usize = 4_000;
+const MAX_COMPARED_RUNS: usize = 10;
+const DEFAULT_LIST_LIMIT: u32 = 100;
+
+/// Bounds on what the backtest store keeps. Applied after every stored run
+/// and whenever the settings change.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BacktestRetention {
+    /// Newest runs kept; `None` keeps all.
+    pub max_runs: Option<u32>,
+    /// Runs older than this are deleted; `None` keeps them.
+    pub max_age_days: Option<u32>,
+    /// Tagged runs are exempt from both limits.
+    pub keep_tagged: bool,
+    /// Equity curves longer than this are downsampled before storing.
+    pub max_equity_points: u32,
+}
+
+impl Default for BacktestRetention {
+    fn default() -> Self {
+        Self {
+            max_runs: Some(500),
+            max_age_days: None,
+            keep_tagged: true,
+            max_equity_points: 2_000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestRunSummary {
+    pub id: String,
+    /// Hash of the strategy, symbol, costs, interval and parameters. Runs
+    /// with the same hash over the same range are duplicates.
+    pub config_hash: String,
+    pub strategy_id: String,
+    pub symbol: String,
+    pub data_interval: String,
+    #[serde(with = "chrono::serde::ts_milliseconds")]
+    pub start_date: DateTime<Utc>,
+    #[serde(with = "chrono::serde::ts_milliseconds")]
+    pub end_date: DateTime<Utc>,
+    pub parameters: BTreeMap<String, f64>,
+    pub metrics: BacktestMetrics,
+    pub tags: Vec<String>,
+    pub notes: Option<String>,
+    /// Points in the stored, possibly downsampled, equity curve.
+    pub equity_points: u32,
+    #[serde(with = "chrono::serde::ts_milliseconds")]
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestRunRecord {
+    pub summary: BacktestRunSummary,
+    pub result: BacktestResult,
+    /// Set by `backtest_run` when a stored duplicate was returned instead of
+    /// running the backtest again.
+    #[serde(default)]
+    pub reused: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BacktestRunFilter {
+    pub strategy_id: Option<String>,
+    pub symbol: Option<String>,
+    /// Runs must carry every one of these tags.
+    pub tags: Vec<String>,
+    #[serde(with = "optional_ts_milliseconds")]
+    pub created_after: Option<DateTime<Utc>>,
+    #[serde(with = "optional_ts_milliseconds")]
+    pub created_before: Option<DateTime<Utc>>,
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BacktestDeleteSelector {
+    OlderThan(#[serde(with = "chrono::serde::ts_milliseconds")] DateTime<Utc>),
+    Ids(Vec<String>),
+}
+
+/// One run's equity on the shared timeline of a comparison. `None` outside
+/// the run's own date range.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EquitySeries {
+    pub run_id: String,
+    pub equity: Vec<Option<f64>>,
+    /// Return on the run's initial capital.
+    pub return_percent: Vec<Option<f64>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlignedEquityCurves {
+    /// Union of every run's equity timestamps, in milliseconds.
+    pub timestamps: Vec<i64>,
+    pub series: Vec<EquitySeries>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MetricPreference {
+    Higher,
+    Lower,
+    Neutral,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricComparison {
+    pub metric: String,
+    pub preference: MetricPreference,
+    /// In the order of `BacktestComparison::runs`.
+    pub values: Vec<f64>,
+    /// `None` for neutral metrics and ties.
+    pub best_run_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestComparison {
+    pub runs: Vec<BacktestRunSummary>,
+    pub equity: AlignedEquityCurves,
+    pub metrics: Vec<MetricComparison>,
+}
+
+pub type SharedBacktestStore = Arc<BacktestStore>;
+
+/// Every backtest run with its metrics, trades and equity curve.
+pub struct BacktestStore {
+    pool: Pool<Sqlite>,
+}
+
+impl BacktestStore {
+    pub async fn new(db_path: PathBuf) -> Result<Self, sqlx::Error> {
+        let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+        let pool = SqlitePool::connect(&db_url).await?;
+        Self::with_pool(pool).await
+    }
+
+    pub async fn with_pool(pool: Pool<Sqlite>) -> Result<Self, sqlx::Error> {
+        let store = Self { pool };
+        store.initialize().await?;
+        Ok(store)
+    }
+
+    async fn initialize(&self) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS backtest_runs (
+                id TEXT PRIMARY KEY,
+                config_hash TEXT NOT NULL,
+                strategy_id TEXT NOT NULL,
+                symbol TEXT NOT NULL,
+                start_date INTEGER NOT NULL,
+                end_date INTEGER NOT NULL,
+                config TEXT NOT NULL,
+                metrics TEXT NOT NULL,
+                trades TEXT NOT NULL,
+                equity_curve TEXT NOT NULL,
+                equity_points INTEGER NOT NULL,
+                started_at INTEGER NOT NULL,
+                completed_at INTEGER NOT NULL,
+                duration INTEGER NOT NULL,
+                tags TEXT NOT NULL,
+                notes TEXT,
+                created_at INTEGER NOT NULL
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_backtest_runs_dedup
+                ON backtest_runs(config_hash, start_date, end_date);
+            CREATE INDEX IF NOT EXISTS idx_backtest_runs_created ON backtest_runs(created_at);
+            CREATE TABLE IF NOT EXISTS backtest_settings (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            );
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn retention(&self) -> Result<BacktestRetention, sqlx::Error> {
+        let value: Option<String> =
+            sqlx::query_scalar("SELECT value FROM backtest_settings WHERE key = ?1")
+                .bind(RETENTION_KEY)
+                .fetch_optional(&self.pool)
+                .await?;
+        match value {
+            Some(value) => from_json(&value),
+            None => Ok(BacktestRetention::default()),
+        }
+    }
+
+    /// Saves the settings and prunes to them, returning the runs deleted.
+    pub async fn set_retention(&self, retention: &BacktestRetention) -> Result<u64, sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO backtest_settings (key, value) VALUES (?1, ?2)
+            ON CONFLICT(key) DO UPDATE SET value = excluded.value
+            "#,
+        )
+        .bind(RETENTION_KEY)
+        .bind(to_json(retention)?)
+        .execute(&self.pool)
+        .await?;
+        self.prune(retention).await
+    }
+
+    /// Newest stored run with the same config hash and date range.
+    pub async fn find_duplicate(
+        &self,
+        config: &BacktestConfig,
+    ) -> Result<Option<BacktestRunRecord>, sqlx::Error> {
+        let row = sqlx::query(
+            r#"
+            SELECT * FROM backtest_runs
+            WHERE config_hash = ?1 AND start_date = ?2 AND end_date = ?3
+            ORDER BY created_at DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(config_hash(config))
+        .bind(config.start_date.timestamp_millis())
+        .bind(config.end_date.timestamp_millis())
+        .fetch_optional(&self.pool)
+        .await?;
+        row.as_ref().map(row_to_record).transpose()
+    }
+
+    /// Stores `result` under its own id, downsampling the equity curve to the
+    /// retention limit, then prunes.
+    pub async fn insert(
+        &self,
+        result: &BacktestResult,
+        tags: Vec<String>,
+        notes: Option<String>,
+    ) -> Result<BacktestRunRecord, sqlx::Error> {
+        let tags = normalize_tags(tags).map_err(sqlx::Error::Protocol)?;
+        let notes = normalize_notes(notes).map_err(sqlx::Error::Protocol)?;
+        let retention = self.retention().await?;
+
+        let mut stored = result.clone();
+        stored.metrics = storable_metrics(&result.metrics);
+        stored.equity_curve =
+            downsample(&result.equity_curve, retention.max_equity_points as usize);
+        let created_at = Utc::now();
+
+        sqlx::query(
+            r#"
+            INSERT INTO backtest_runs (
+                id, config_hash, strategy_id, symbol, start_date, end_date, config,
+                metrics, trades, equity_curve, equity_points, started_at, completed_at,
+                duration, tags, notes, created_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17)
+            "#,
+        )
+        .bind(&stored.id)
+        .bind(config_hash(&stored.config))
+        .bind(&stored.config.strategy_id)
+        .bind(&stored.config.symbol)
+        .bind(stored.config.start_date.timestamp_millis())
+        .bind(stored.config.end_date.timestamp_millis())
+        .bind(to_json(&stored.config)?)
+        .bind(to_json(&stored.metrics)?)
+        .bind(to_json(&stored.trades)?)
+        .bind(to_json(&stored.equity_curve)?)
+        .bind(stored.equity_curve.len() as i64)
+        .bind(stored.started_at.timestamp_millis())
+        .bind(stored.completed_at.timestamp_millis())
+        .bind(stored.duration)
+        .bind(to_json(&tags)?)
+        .bind(&notes)
+        .bind(created_at.timestamp_millis())
+        .execute(&self.pool)
+        .await?;
+
+        self.prune(&retention).await?;
+
+        Ok(BacktestRunRecord {
+            summary: summarize(&stored, tags, notes, created_at),
+            result: stored,
+            reused: false,
+        })
+    }
+
+    /// Newest first.
+    pub async fn list(
+        &self,
+        filter: &BacktestRunFilter,
+    ) -> Result<Vec<BacktestRunSummary>, sqlx::Error> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, config, metrics, equity_points, tags, notes, created_at
+            FROM backtest_runs
+            WHERE (?1 IS NULL OR strategy_id = ?1)
+              AND (?2 IS NULL OR symbol = ?2)
+              AND (?3 IS NULL OR created_at >= ?3)
+              AND (?4 IS NULL OR created_at < ?4)
+            ORDER BY created_at DESC
+            "#,
+        )
+        .bind(&filter.strategy_id)
+        .bind(&filter.symbol)
+        .bind(filter.created_after.map(|at| at.timestamp_millis()))
+        .bind(filter.created_before.map(|at| at.timestamp_millis()))
+        .fetch_all(&self.pool)
+        .await?;
+
+        let required: Vec<String> = filter.tags.iter().map(|tag| tag.to_lowercase()).collect();
+        let limit = filter.limit.unwrap_or(DEFAULT_LIST_LIMIT) as usize;
+        let mut summaries = Vec::new();
+        for row in &rows {
+            let summary = row_to_summary(row)?;
+            let has_tags = required
+                .iter()
+                .all(|wanted| summary.tags.iter().any(|tag| tag.to_lowercase() == *wanted));
+            if has_tags {
+                summaries.push(summary);
+                if summaries.len() >= limit {
+                    break;
+                }
+            }
+        }
+        Ok(summaries)
+    }
+
+    pub async fn get(&self, id: &str) -> Result<Option<BacktestRunRecord>, sqlx::Error> {
+        let row = sqlx::query("SELECT * FROM backtest_runs WHERE id = ?1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+        row.as_ref().map(row_to_record).transpose()
+    }
+
+    /// Replaces the tags and notes of a run; `None` leaves a field as is.
+    pub async fn update_labels(
+        &self,
+        id: &str,
+        tags: Option<Vec<String>>,
+        notes: Option<String>,
+    ) -> Result<Option<BacktestRunSummary>, sqlx::Error> {
+        let tags = tags
+            .map(normalize_tags)
+            .transpose()
+            .map_err(sqlx::Error::Protocol)?;
+        let notes = notes
+            .map(|notes| normalize_notes(Some(notes)))
+            .transpose()
+            .map_err(sqlx::Error::Protocol)?;
+
+        if let Some(tags) = &tags {
+            sqlx::query("UPDATE backtest_runs SET tags = ?1 WHERE id = ?2")
+                .bind(to_json(tags)?)
+                .bind(id)
+                .execute(&self.pool)
+                .await?;
+        }
+        if let Some(notes) = &notes {
+            sqlx::query("UPDATE backtest_runs SET notes = ?1 WHERE id = ?2")
+                .bind(notes)
+                .bind(id)
+                .execute(&self.pool)
+                .await?;
+        }
+
+        Ok(self.get(id).await?.map(|record| record.summary))
+    }
+
+    pub async fn delete(&self, selector: &BacktestDeleteSelector) -> Result<u64, sqlx::Error> {
+        let deleted = match selector {
+            BacktestDeleteSelector::OlderThan(cutoff) => {
+                sqlx::query("DELETE FROM backtest_runs WHERE created_at < ?1")
+                    .bind(cutoff.timestamp_millis())
+                    .execute(&self.pool)
+                    .await?
+                    .rows_affected()
+            }
+            BacktestDeleteSelector::Ids(ids) => {
+                let mut deleted = 0;
+                for id in ids {
+                    deleted += sqlx::query("DELETE FROM backtest_runs WHERE id = ?1")
+                        .bind(id)
+                        .execute(&self.pool)
+                        .await?
+                        .rows_affected();
+                }
+                deleted
+            }
+        };
+        Ok(deleted)
+    }
+
+    pub async fn prune(&self, retention: &BacktestRetention) -> Result<u64, sqlx::Error> {
+        // With `keep_tagged`, only untagged runs count against the limits.
+        let untagged_only = retention.keep_tagged;
+        let mut deleted = 0;
+
+        if let Some(days) = retention.max_age_days {
+            let cutoff = Utc::now() - Duration::days(days as i64);
+            deleted += sqlx::query(
+                "DELETE FROM backtest_runs WHERE created_at < ?1 AND (?2 = 0 OR tags = '[]')",
+            )
+            .bind(cutoff.timestamp_millis())
+            .bind(untagged_only)
+            .execute(&self.pool)
+            .await?
+            .rows_affected();
+        }
+
+        if let Some(max_runs) = retention.max_runs {
+            deleted += sqlx::query(
+                r#"
+                DELETE FROM backtest_runs
+                WHERE (?2 = 0 OR tags = '[]') AND id NOT IN (
+                    SELECT id FROM backtest_runs
+                    WHERE (?2 = 0 OR tags = '[]')
+                    ORDER BY created_at DESC
+                    LIMIT ?1
+                )
+                "#,
+            )
+            .bind(max_runs)
+            .bind(untagged_only)
+            .execute(&self.pool)
+            .await?
+            .rows_affected();
+        }
+
+        Ok(deleted)
+    }
+}
+
+/// Hash of everything that determines a run's outcome except its date range.
+pub fn config_hash(config: &BacktestConfig) -> String {
+    #[derive(Serialize)]
+    struct Hashed<'a> {
+        strategy_id: &'a str,
+        symbol: &'a str,
+        initial_capital: f64,
+        commission_rate: f64,
+        slippage_rate: f64,
+        data_interval: &'a str,
+        parameters: &'a BTreeMap<String, f64>,
+    }
+
+    let hashed = Hashed {
+        strategy_id: &config.strategy_id,
+        symbol: &config.symbol,
+        initial_capital: config.initial_capital,
+        commission_rate: config.commission_rate,
+        slippage_rate: config.slippage_rate,
+        data_interval: &config.data_interval,
+        parameters: &config.parameters,
+    };
+    let json = serde_json::to_string(&hashed).unwrap_or_default();
+    hex::encode(Sha256::digest(json.as_bytes()))
+}
+
+/// Trims tags and drops case-insensitive duplicates, keeping the first
+/// spelling.
+fn normalize_tags(tags: Vec<String>) -> Result<Vec<String>, String> {
+    let mut seen = BTreeSet::new();
+    let mut normalized = Vec::new();
+    for tag in tags {
+        let tag = tag.trim();
+        if tag.is_empty() {
+            continue;
+        }
+        if tag.chars().count() > MAX_TAG_CHARS {
+            return Err(format!(
+                "tag '{}' is longer than {} characters",
+                tag, MAX_TAG_CHARS
+            ));
+        }
+        if seen.insert(tag.to_lowercase()) {
+            normalized.push(tag.to_string());
+        }
+    }
+    if normalized.len() > MAX_TAGS {
+        return Err(format!("a run can have at most {} tags", MAX_TAGS));
+    }
+    Ok(normalized)
+}
+
+fn normalize_notes(notes: Option<String>) -> Result<Option<String>, String> {
+    let Some(notes) = notes else {
+        return Ok(None);
+    };
+    let notes = notes.trim();
+    if notes.chars().count() > MAX_NOTES_CHARS {
+        return Err(format!(
+            "notes are longer than {} characters",
+            MAX_NOTES_CHARS
+        ));
+    }
+    Ok((!notes.is_empty()).then(|| notes.to_string()))
+}
+
+/// JSON has no infinity, so an unbounded profit factor (no losing trades)
+/// is stored as `f64::MAX`.
+fn storable_metrics(metrics: &BacktestMetrics) -> BacktestMetrics {
+    let mut metrics = metrics.clone();
+    if !metrics.profit_factor.is_finite() {
+        metrics.profit_factor = f64::MAX;
+    }
+    metrics
+}
+
+/// Evenly spaced points of `curve`, always keeping the first and last.
+fn downsample(curve: &[EquityPoint], max_points: usize) -> Vec<EquityPoint> {
+    let max_points = max_points.max(2);
+    if curve.len() <= max_points {
+        return curve.to_vec();
+    }
+    let last = curve.len() - 1;
+    let mut sampled: Vec<EquityPoint> = (0..max_points)
+        .map(|i| curve[i * last / (max_points - 1)].clone())
+        .collect();
+    sampled.dedup_by_key(|point| point.timestamp);
+    sampled
+}
+
+/// Puts every run's curve on the union of their timestamps. Each run holds
+/// its last known equity between its own points.
+pub fn align_equity_curves(runs: &[BacktestRunRecord]) -> AlignedEquityCurves {
+    let timestamps: Vec<i64> = runs
+        .iter()
+        .flat_map(|run| run.result.equity_curve.iter())
+        .map(|point| point.timestamp.timestamp_millis())
+        .collect::<BTreeSet<i64>>()
+        .into_iter()
+        .collect();
+
+    let series = runs
+        .iter()
+        .map(|run| {
+            let curve = &run.result.equity_curve;
+            let capital = run.result.config.initial_capital;
+            let mut next = 0;
+            let mut equity = Vec::with_capacity(timestamps.len());
+            for &at in &timestamps {
+                while next < curve.len() && curve[next].timestamp.timestamp_millis() <= at {
+                    next += 1;
+                }
+                let in_range = curve
+                    .last()
+                    .is_some_and(|last| at <= last.timestamp.timestamp_millis());
+                equity.push(if next > 0 && in_range {
+                    Some(curve[next - 1].equity)
+                } else {
+                    None
+                });
+            }
+            let return_percent = equity
+                .iter()
+                .map(|value| {
+                    value
+                        .filter(|_| capital > 0.0)
+                        .map(|value| (value - capital) / capital * 100.0)
+                })
+                .collect();
+            EquitySeries {
+                run_id: run.summary.id.clone(),
+                equity,
+                return_percent,
+            }
+        })
+        .collect();
+
+    AlignedEquityCurves { timestamps, series }
+}
+
+fn metric_table(metrics: &BacktestMetrics) -> Vec<(&'static str, MetricPreference, f64)> {
+    use MetricPreference::*;
+    vec![
+        ("total_return", Higher, metrics.total_return),
+        ("total_return_percent", Higher, metrics.total_return_percent),
+        ("annualized_return", Higher, metrics.annualized_return),
+        ("sharpe_ratio", Higher, metrics.sharpe_ratio),
+        ("sortino_ratio", Higher, metrics.sortino_ratio),
+        ("max_drawdown", Lower, metrics.max_drawdown),
+        ("max_drawdown_percent", Lower, metrics.max_drawdown_percent),
+        ("win_rate", Higher, metrics.win_rate),
+        ("profit_factor", Higher, metrics.profit_factor),
+        ("total_trades", Neutral, metrics.total_trades as f64),
+        ("winning_trades", Neutral, metrics.winning_trades as f64),
+        ("losing_trades", Neutral, metrics.losing_trades as f64),
+        ("average_win", Higher, metrics.average_win),
+        ("average_loss", Lower, metrics.average_loss),
+        ("largest_win", Higher, metrics.largest_win),
+        ("largest_loss", Lower, metrics.largest_loss),
+        (
+            "average_trade_duration",
+            Neutral,
+            metrics.average_trade_duration as f64,
+        ),
+        ("exposure_time", Neutral, metrics.exposure_time),
+    ]
+}
+
+pub fn compare_metrics(runs: &[BacktestRunRecord]) -> Vec<MetricComparison> {
+    let tables: Vec<_> = runs
+        .iter()
+        .map(|run| metric_table(&run.summary.metrics))
+        .collect();
+    let Some(first) = tables.first() else {
+        return Vec::new();
+    };
+
+    first
+        .iter()
+        .enumerate()
+        .map(|(index, (metric, preference, _))| {
+            let values: Vec<f64> = tables.iter().map(|table| table[index].2).collect();
+            let best = match preference {
+                MetricPreference::Higher => values.iter().copied().reduce(f64::max),
+                MetricPreference::Lower => values.iter().copied().reduce(f64::min),
+                MetricPreference::Neutral => None,
+            };
+            let best_run_id = best.and_then(|best| {
+                let mut winners = values.iter().enumerate().filter(|(_, v)| **v == best);
+                match (winners.next(), winners.next()) {
+                    (Some((winner, _)), None) => Some(runs[winner].summary.id.clone()),
+                    _ => None,
+                }
+            });
+            MetricComparison {
+                metric: metric.to_string(),
+                preference: *preference,
+                values,
+                best_run_id,
+            }
+        })
+        .collect()
+}
+
+fn summarize(
+    result: &BacktestResult,
+    tags: Vec<String>,
+    notes: Option<String>,
+    created_at: DateTime<Utc>,
+) -> BacktestRunSummary {
+    BacktestRunSummary {
+        id: result.id.clone(),
+        config_hash: config_hash(&result.config),
+        strategy_id: result.config.strategy_id.clone(),
+        symbol: result.config.symbol.clone(),
+        data_interval: result.config.data_interval.clone(),
+        start_date: result.config.start_date,
+        end_date: result.config.end_date,
+        parameters: result.config.parameters.clone(),
+        metrics: result.metrics.clone(),
+        tags,
+        notes,
+        equity_points: result.equity_curve.len() as u32,
+        created_at,
+    }
+}
+
+fn row_to_summary(row: &SqliteRow) -> Result<BacktestRunSummary, sqlx::Error> {
+    let config: BacktestConfig = from_json(&row.try_get::<String, _>("config")?)?;
+    let equity_points: i64 = row.try_get("equity_points")?;
+    Ok(BacktestRunSummary {
+        id: row.try_get("id")?,
+        config_hash: config_hash(&config),
+        strategy_id: config.strategy_id.clone(),
+        symbol: config.symbol.clone(),
+        data_interval: config.data_interval.clone(),
+        start_date: config.start_date,
+        end_date: config.end_date,
+        parameters: config.parameters,
+        metrics: from_json(&row.try_get::<String, _>("metrics")?)?,
+        tags: from_json(&row.try_get::<String, _>("tags")?)?,
+        notes: row.try_get("notes")?,
+        equity_points: equity_points as u32,
+        created_at: from_millis(row.try_get("created_at")?)?,
+    })
+}
+
+fn row_to_record(row: &SqliteRow) -> Result<BacktestRunRecord, sqlx::Error> {
+    let summary = row_to_summary(row)?;
+    let result = BacktestResult {
+        id: summary.id.clone(),
+        config: from_json(&row.try_get::<String, _>("config")?)?,
+        metrics: summary.metrics.clone(),
+        trades: from_json(&row.try_get::<String, _>("trades")?)?,
+        equity_curve: from_json(&row.try_get::<String, _>("equity_curve")?)?,
+        started_at: from_millis(row.try_get("started_at")?)?,
+        completed_at: from_millis(row.try_get("completed_at")?)?,
+        duration: row.try_get("duration")?,
+    };
+    Ok(BacktestRunRecord {
+        summary,
+        result,
+        reused: false,
+    })
+}
+
+fn from_millis(value: i64) -> Result<DateTime<Utc>, sqlx::Error> {
+    Utc.timestamp_millis_opt(value)
+        .single()
+        .ok_or_else(|| sqlx::Error::Decode(format!("invalid timestamp {}", value).into()))
+}
+
+fn to_json<T: serde::Serialize + ?Sized>(value: &T) -> Result<String, sqlx::Error> {
+    serde_json::to_string(value).map_err(|e| sqlx::Error::Protocol(e.to_string()))
+}
+
+fn from_json<T: serde::de::DeserializeOwned>(value: &str) -> Result<T, sqlx::Error> {
+    serde_json::from_str(value).map_err(|e| sqlx::Error::Decode(Box::new(e)))
+}
+
+mod optional_ts_milliseconds {
+    use chrono::{DateTime, TimeZone, Utc};
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        value: &Option<DateTime<Utc>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(at) => serializer.serialize_some(&at.timestamp_millis()),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<DateTime<Utc>>, D::Error> {
+        Option::<i64>::deserialize(deserializer)?
+            .map(|millis| {
+                Utc.timestamp_millis_opt(millis)
+                    .single()
+                    .ok_or_else(|| serde::de::Error::custom("invalid timestamp"))
+            })
+            .transpose()
+    }
+}
+
+#[tauri::command]
+pub async fn backtest_list_runs(
+    filter: Option<BacktestRunFilter>,
+    store: State<'_, SharedBacktestStore>,
+) -> Result<Vec<BacktestRunSummary>, String> {
+    store
+        .list(&filter.unwrap_or_default())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn backtest_get_run(
+    id: String,
+    store: State<'_, SharedBacktestStore>,
+) -> Result<BacktestRunRecord, String> {
+    store
+        .get(&id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Backtest run {} not found", id))
+}
+
+#[tauri::command]
+pub async fn backtest_update_run(
+    id: String,
+    tags: Option<Vec<String>>,
+    notes: Option<String>,
+    store: State<'_, SharedBacktestStore>,
+) -> Result<BacktestRunSummary, String> {
+    store
+        .update_labels(&id, tags, notes)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Backtest run {} not found", id))
+}
+
+#[tauri::command]
+pub async fn backtest_compare_runs(
+    ids: Vec<String>,
+    store: State<'_, SharedBacktestStore>,
+) -> Result<BacktestComparison, String> {
+    if ids.len() < 2 || ids.len() > MAX_COMPARED_RUNS {
+        return Err(format!(
+            "Select between 2 and {} runs to compare",
+            MAX_COMPARED_RUNS
+        ));
+    }
+
+    let mut runs = Vec::with_capacity(ids.len());
+    for id in &ids {
+        let run = store
+            .get(id)
+            .await
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("Backtest run {} not found", id))?;
+        runs.push(run);
+    }
+
+    Ok(BacktestComparison {
+        equity: align_equity_curves(&runs),
+        metrics: compare_metrics(&runs),
+        runs: runs.into_iter().map(|run| run.summary).collect(),
+    })
+}
+
+#[tauri::command]
+pub async fn backtest_delete_runs(
+    selector: BacktestDeleteSelector,
+    store: State<'_, SharedBacktestStore>,
+) -> Result<u64, String> {
+    store.delete(&selector).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn backtest_get_retention(
+    store: State<'_, SharedBacktestStore>,
+) -> Result<BacktestRetention, String> {
+    store.retention().await.map_err(|e| e.to_string())
+}
+
+/// Returns the number of runs pruned under the new settings.
+#[tauri::command]
+pub async fn backtest_set_retention(
+    retention: BacktestRetention,
+    store: State<'_, SharedBacktestStore>,
+) -> Result<u64, String> {
+    store
+        .set_retention(&retention)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trading::backtesting::BacktestEngine;
+    use crate::utils::test_pool;
+
+    async fn store() -> BacktestStore {
+        let pool = test_pool().await;
+        BacktestStore::with_pool(pool).await.unwrap()
+    }
+
+    fn config(short_period: f64) -> BacktestConfig {
+        BacktestConfig {
+            strategy_id: "ma_cross".to_string(),
+            symbol: "SOL".to_string(),
+            start_date: Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap(),
+            end_date: Utc.with_ymd_and_hms(2026, 1, 2, 0, 0, 0).unwrap(),
+            initial_capital: 1_000.0,
+            commission_rate: 0.1,
+            slippage_rate: 0.05,
+            data_interval: "1h".to_string(),
+            parameters: BTreeMap::from([("short_period".to_string(), short_period)]),
+        }
+    }
+
+    /// A run whose equity starts at 100 and moves by `step` per hour,
+    /// starting `offset` hours into the range.
+    fn result(config: BacktestConfig, offset: i64, points: i64, step: f64) -> BacktestResult {
+        let start = config.start_date;
+        let mut result = BacktestEngine::new(config).finalize();
+        result.equity_curve = (0..points)
+            .map(|hour| EquityPoint {
+                timestamp: start + Duration::hours(offset + hour),
+                equity: 100.0 + step * hour as f64,
+                drawdown: 0.0,
+                drawdown_percent: 0.0,
+            })
+            .collect();
+        result.metrics.total_return = step * (points - 1) as f64;
+        result
+    }
+
+    #[tokio::test]
+    async fn test_duplicates_are_found_by_hash_and_range() {
+        let store = store().await;
+        let stored = store
+            .insert(
+                &result(config(10.0), 0, 5, 1.0),
+                vec![" baseline ".into(), "Baseline".into()],
+                Some("first try".into()),
+            )
+            .await
+            .unwrap();
+        assert_eq!(stored.summary.tags, vec!["baseline"]);
+
+        let duplicate = store.find_duplicate(&config(10.0)).await.unwrap().unwrap();
+        assert_eq!(duplicate.summary.id, stored.summary.id);
+        assert_eq!(duplicate.result.equity_curve.len(), 5);
+        assert_eq!(duplicate.summary.notes.as_deref(), Some("first try"));
+
+        assert!(store.find_duplicate(&config(12.0)).await.unwrap().is_none());
+        let mut shifted = config(10.0);
+        shifted.end_date = shifted.end_date + Duration::days(1);
+        assert!(store.find_duplicate(&shifted).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_list_filters_by_tags_and_updates_labels() {
+        let store = store().await;
+        let a = store
+            .insert(&result(config(10.0), 0, 3, 1.0), vec!["keep".into()], None)
+            .await
+            .unwrap();
+        store
+            .insert(&result(config(12.0), 0, 3, 1.0), vec![], None)
+            .await
+            .unwrap();
+
+        let filter = BacktestRunFilter {
+            tags: vec!["KEEP".into()],
+            ..Default::default()
+        };
+        let tagged = store.list(&filter).await.unwrap();
+        assert_eq!(tagged.len(), 1);
+        assert_eq!(tagged[0].id, a.summary.id);
+        assert_eq!(store.list(&Default::default()).await.unwrap().len(), 2);
+
+        let updated = store
+            .update_labels(&a.summary.id, Some(vec![]), Some("note".into()))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(updated.tags.is_empty());
+        assert_eq!(updated.notes.as_deref(), Some("note"));
+        assert!(store.list(&filter).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_retention_keeps_tagged_runs_and_downsamples() {
+        let store = store().await;
+        let retention = BacktestRetention {
+            max_runs: Some(1),
+            max_equity_points: 4,
+            ..Default::default()
+        };
+        assert_eq!(store.set_retention(&retention).await.unwrap(), 0);
+
+        let tagged = store
+            .insert(&result(config(10.0), 0, 10, 1.0), vec!["keep".into()], None)
+            .await
+            .unwrap();
+        assert_eq!(tagged.summary.equity_points, 4);
+        let curve = &tagged.result.equity_curve;
+        assert_eq!(curve.first().unwrap().equity, 100.0);
+        assert_eq!(curve.last().unwrap().equity, 109.0);
+
+        for short_period in [11.0, 12.0] {
+            store
+                .insert(&result(config(short_period), 0, 3, 1.0), vec![], None)
+                .await
+                .unwrap();
+        }
+        let runs = store.list(&Default::default()).await.unwrap();
+        assert_eq!(runs.len(), 2);
+        assert!(runs.iter().any(|run| run.id == tagged.summary.id));
+
+        let deleted = store
+            .delete(&BacktestDeleteSelector::Ids(vec![tagged.summary.id]))
+            .await
+            .unwrap();
+        assert_eq!(deleted, 1);
+    }
+
+    #[tokio::test]
+    async fn test_comparison_aligns_curves_and_picks_best_metrics() {
+        let store = store().await;
+        let early = store
+            .insert(&result(config(10.0), 0, 3, 10.0), vec![], None)
+            .await
+            .unwrap();
+        let late = store
+            .insert(&result(config(12.0), 2, 3, -10.0), vec![], None)
+            .await
+            .unwrap();
+        let runs = vec![early, late];
+
+        let aligned = align_equity_curves(&runs);
+        assert_eq!(aligned.timestamps.len(), 5);
+        let (a, b) = (&aligned.series[0], &aligned.series[1]);
+        assert_eq!(a.equity[..3], [Some(100.0), Some(110.0), Some(120.0)]);
+        assert_eq!(a.equity[3..], [None, None]);
+        assert_eq!(b.equity[..2], [None, None]);
+        assert_eq!(b.equity[4], Some(80.0));
+        assert_eq!(b.return_percent[4], Some(-92.0));
+
+        let metrics = compare_metrics(&runs);
+        let total_return = metrics
+            .iter()
+            .find(|metric| metric.metric == "total_return")
+            .unwrap();
+        assert_eq!(
+            total_return.best_run_id.as_deref(),
+            Some(runs[0].summary.id.as_str())
+        );
+        let trades = metrics
+            .iter()
+            .find(|metric| metric.metric == "total_trades")
+            .unwrap();
+        assert!(trades.best_run_id.is_none());
+    }
+}
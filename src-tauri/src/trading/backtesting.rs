@@ -1,8 +1,13 @@
+use super::backtest_store::{BacktestRunRecord, SharedBacktestStore};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use tauri::State;
 use uuid::Uuid;
 
+const DEFAULT_SHORT_PERIOD: usize = 10;
+const DEFAULT_LONG_PERIOD: usize = 30;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BacktestConfig {
     pub strategy_id: String,
@@ -15,6 +20,30 @@ pub struct BacktestConfig {
     pub commission_rate: f64,  // percentage
     pub slippage_rate: f64,    // percentage
     pub data_interval: String, // 1m, 5m, 15m, 1h, 4h, 1d
+    /// Strategy parameters, e.g. `short_period` and `long_period` for the
+    /// moving average crossover.
+    #[serde(default)]
+    pub parameters: BTreeMap<String, f64>,
+}
+
+impl BacktestConfig {
+    fn period(&self, name: &str, default: usize) -> usize {
+        self.parameters
+            .get(name)
+            .filter(|value| value.is_finite() && **value >= 1.0)
+            .map(|value| *value as usize)
+            .unwrap_or(default)
+    }
+}
+
+/// Storage options for `backtest_run`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BacktestRunOptions {
+    pub tags: Vec<String>,
+    pub notes: Option<String>,
+    /// Recompute even when an identical run over the same range is stored.
+    pub force: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -496,8 +525,36 @@ pub fn generate_mock_historical_data(
     data
 }
 
+/// Runs a backtest and stores it. An earlier run with the same config hash
+/// over the same date range is returned instead unless `force` is set.
 #[tauri::command]
-pub async fn backtest_run(config: BacktestConfig) -> Result<BacktestResult, String> {
+pub async fn backtest_run(
+    config: BacktestConfig,
+    options: Option<BacktestRunOptions>,
+    store: State<'_, SharedBacktestStore>,
+) -> Result<BacktestRunRecord, String> {
+    let options = options.unwrap_or_default();
+
+    if !options.force {
+        if let Some(mut existing) = store
+            .find_duplicate(&config)
+            .await
+            .map_err(|e| e.to_string())?
+        {
+            existing.reused = true;
+            return Ok(existing);
+        }
+    }
+
+    let result = simulate_backtest(config).await?;
+    store
+        .insert(&result, options.tags, options.notes)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Runs a backtest without storing it.
+pub async fn simulate_backtest(config: BacktestConfig) -> Result<BacktestResult, String> {
     // In a real implementation, fetch historical data from database or API
     let interval_minutes = match config.data_interval.as_str() {
         "1m" => 1,
@@ -516,14 +573,17 @@ pub async fn backtest_run(config: BacktestConfig) -> Result<BacktestResult, Stri
         100.0, // Initial price
     );
 
-    let mut engine = BacktestEngine::new(config);
-
     // Simple strategy for demonstration: Moving average crossover
-    let short_period = 10;
-    let long_period = 30;
+    let short_period = config.period("short_period", DEFAULT_SHORT_PERIOD);
+    let long_period = config
+        .period("long_period", DEFAULT_LONG_PERIOD)
+        .max(short_period);
+
+    let mut engine = BacktestEngine::new(config);
 
     for (i, data) in historical_data.iter().enumerate() {
-        if i < long_period {
+        // The previous bar's averages need one bar more than the current ones.
+        if i <= long_period {
             engine.update_equity_curve(data.timestamp, data.close);
             continue;
         }
@@ -1,11 +1,29 @@
+use super::events::{
+    publish_order_event, OrderEvent, OrderEventKind, OrderEventSource, OrderRoute,
+};
+use super::leader_profile::{
+    BehaviorChange, BehaviorThresholds, LeaderProfile, LeaderTrade, PROFILE_LOOKBACK_DAYS,
+};
+use crate::config::DataPaths;
+use crate::core::WebSocketManager;
+use crate::errors::ShutdownListener;
+use crate::notifications::router::SharedNotificationRouter;
+use crate::notifications::types::{
+    AlertPriority, HistoryCategory, NewNotification, NotificationEntity,
+};
+use crate::portfolio::analytics::classify_sector;
+use crate::trading::types::OrderSide;
 use crate::utils::Rfc3339DateTime;
+use crate::wallet::performance::{record_bot_trade, RecordTradeRequest, StrategySource};
+use crate::websocket::activity::{is_quote_mint, ActivityKind, DecodedActivity};
+use crate::websocket::types::TransactionUpdate;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, Pool, Row, Sqlite, SqlitePool};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 use std::sync::Arc;
-use tauri::{AppHandle, Emitter, Manager};
+use tauri::{AppHandle, Emitter, Listener, Manager};
 use tokio::sync::{OnceCell, RwLock};
 use tokio::time::{interval, Duration};
 use uuid::Uuid;
@@ -146,6 +164,34 @@ pub struct WalletActivity {
     pub pnl: Option<f64>,
 }
 
+impl WalletActivity {
+    /// Builds an activity from the shared wallet decoder. Only swaps are copied.
+    pub fn from_decoded(decoded: &DecodedActivity) -> Option<Self> {
+        if decoded.kind != ActivityKind::Swap {
+            return None;
+        }
+        let sent = decoded.token_out.as_ref()?;
+        let received = decoded.token_in.as_ref()?;
+        let symbol = |mint: &str, symbol: &Option<String>| {
+            symbol.clone().unwrap_or_else(|| mint.to_string())
+        };
+
+        Some(Self {
+            wallet: decoded.wallet.clone(),
+            tx_signature: decoded.signature.clone(),
+            timestamp: DateTime::from_timestamp(decoded.timestamp, 0).unwrap_or_else(Utc::now),
+            action: decoded.action().to_string(),
+            input_mint: sent.mint.clone(),
+            output_mint: received.mint.clone(),
+            input_symbol: symbol(&sent.mint, &sent.symbol),
+            output_symbol: symbol(&received.mint, &received.symbol),
+            amount: sent.amount,
+            performance_pct: None,
+            pnl: None,
+        })
+    }
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct CopyTradeEvent {
     pub config_id: String,
@@ -157,6 +203,21 @@ pub struct CopyTradeEvent {
     pub tx_signature: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FollowedWallet {
+    pub wallet: String,
+    pub behavior_changed: bool,
+    pub changes: Vec<BehaviorChange>,
+    pub last_trade_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LeaderBehaviorEvent {
+    pub wallet: String,
+    pub change: BehaviorChange,
+}
+
 #[derive(Debug)]
 struct CopyTradeStats {
     total_trades: i64,
@@ -233,6 +294,37 @@ impl CopyTradeDatabase {
 
         sqlx::query(
             r#"
+            CREATE TABLE IF NOT EXISTS copy_trade_leader_trades (
+                tx_signature TEXT PRIMARY KEY,
+                wallet TEXT NOT NULL,
+                traded_at TEXT NOT NULL,
+                amount REAL NOT NULL,
+                symbol TEXT NOT NULL,
+                category TEXT NOT NULL
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // Behavior changes already notified, cleared once the change no
+        // longer holds so a recurrence notifies again.
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS copy_trade_leader_alerts (
+                wallet TEXT NOT NULL,
+                change_key TEXT NOT NULL,
+                notified_at TEXT NOT NULL,
+                PRIMARY KEY (wallet, change_key)
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_copy_trade_leader_trades_wallet ON copy_trade_leader_trades(wallet, traded_at);
             CREATE INDEX IF NOT EXISTS idx_copy_trade_configs_active ON copy_trade_configs(is_active);
             CREATE INDEX IF NOT EXISTS idx_copy_trade_configs_wallet ON copy_trade_configs(wallet_address);
             CREATE INDEX IF NOT EXISTS idx_copy_trade_configs_source ON copy_trade_configs(source_wallet);
@@ -379,6 +471,14 @@ impl CopyTradeDatabase {
         .await
     }
 
+    pub async fn successful_executions(&self) -> Result<Vec<CopyTradeExecution>, sqlx::Error> {
+        sqlx::query_as::<_, CopyTradeExecution>(
+            "SELECT * FROM copy_trade_executions WHERE status = 'success' ORDER BY executed_at ASC",
+        )
+        .fetch_all(&self.pool)
+        .await
+    }
+
     pub async fn daily_trade_count(&self, config_id: &str) -> Result<i64, sqlx::Error> {
         let row = sqlx::query(
             r#"
@@ -432,6 +532,87 @@ impl CopyTradeDatabase {
 
         row.try_get("pnl")
     }
+
+    pub async fn record_leader_trade(&self, trade: &LeaderTrade) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT OR IGNORE INTO copy_trade_leader_trades (
+                tx_signature, wallet, traded_at, amount, symbol, category
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            "#,
+        )
+        .bind(&trade.tx_signature)
+        .bind(&trade.wallet)
+        .bind(trade.traded_at.to_rfc3339())
+        .bind(trade.amount)
+        .bind(&trade.symbol)
+        .bind(&trade.category)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn leader_trades(
+        &self,
+        wallet: &str,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<LeaderTrade>, sqlx::Error> {
+        let rows = sqlx::query(
+            r#"
+            SELECT tx_signature, wallet, traded_at, amount, symbol, category
+            FROM copy_trade_leader_trades
+            WHERE wallet = ?1 AND traded_at >= ?2
+            ORDER BY traded_at ASC
+            "#,
+        )
+        .bind(wallet)
+        .bind(since.to_rfc3339())
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(LeaderTrade {
+                    wallet: row.try_get("wallet")?,
+                    tx_signature: row.try_get("tx_signature")?,
+                    traded_at: Rfc3339DateTime::try_from(row.try_get::<String, _>("traded_at")?)?
+                        .into(),
+                    amount: row.try_get("amount")?,
+                    symbol: row.try_get("symbol")?,
+                    category: row.try_get("category")?,
+                })
+            })
+            .collect()
+    }
+
+    pub async fn leader_alert_keys(&self, wallet: &str) -> Result<HashSet<String>, sqlx::Error> {
+        let rows = sqlx::query("SELECT change_key FROM copy_trade_leader_alerts WHERE wallet = ?1")
+            .bind(wallet)
+            .fetch_all(&self.pool)
+            .await?;
+        rows.iter().map(|row| row.try_get("change_key")).collect()
+    }
+
+    pub async fn insert_leader_alert(&self, wallet: &str, key: &str) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT OR REPLACE INTO copy_trade_leader_alerts (wallet, change_key, notified_at) VALUES (?1, ?2, ?3)",
+        )
+        .bind(wallet)
+        .bind(key)
+        .bind(Utc::now().to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn clear_leader_alert(&self, wallet: &str, key: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM copy_trade_leader_alerts WHERE wallet = ?1 AND change_key = ?2")
+            .bind(wallet)
+            .bind(key)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
 }
 
 pub type SharedCopyTradeDatabase = Arc<RwLock<CopyTradeDatabase>>;
@@ -613,6 +794,13 @@ impl CopyTradeManager {
             }
         }
 
+        if let Err(err) = self.observe_leader_trade(&activity).await {
+            eprintln!(
+                "Failed to update leader profile for {}: {err}",
+                activity.wallet
+            );
+        }
+
         let configs = self
             .db
             .read()
@@ -722,6 +910,8 @@ impl CopyTradeManager {
             .map_err(|e| format!("Failed to record execution: {e}"))?;
 
         self.emit_execution_event(config, &execution);
+        record_bot_trade(&self.app_handle, performance_trade(config, &execution)).await;
+        publish_order_event(fill_event(config, &execution));
 
         Ok(())
     }
@@ -833,12 +1023,44 @@ impl CopyTradeManager {
             .await
             .map_err(|e| format!("Failed to load copy trade configs: {e}"))?;
 
-        let mut wallets = self.monitored_wallets.write().await;
-        wallets.clear();
-        wallets.extend(configs.into_iter().map(|cfg| cfg.source_wallet));
+        let followed: Vec<String> = configs.into_iter().map(|cfg| cfg.source_wallet).collect();
+        {
+            let mut wallets = self.monitored_wallets.write().await;
+            wallets.clear();
+            wallets.extend(followed.iter().cloned());
+        }
+
+        if !followed.is_empty() {
+            if let Some(ws_manager) = self.app_handle.try_state::<WebSocketManager>() {
+                ws_manager
+                    .subscribe_wallets(followed)
+                    .await
+                    .map_err(|e| format!("Failed to subscribe to followed wallets: {e}"))?;
+            }
+        }
         Ok(())
     }
 
+    /// Feeds decoded swaps of followed wallets from the wallet stream into
+    /// `process_wallet_activity`.
+    pub fn attach_stream_listener(manager: Arc<Self>) {
+        let app_handle = manager.app_handle.clone();
+        app_handle.listen("transaction_update", move |event| {
+            let Ok(tx) = serde_json::from_str::<TransactionUpdate>(event.payload()) else {
+                return;
+            };
+            let Some(activity) = tx.activity.as_ref().and_then(WalletActivity::from_decoded) else {
+                return;
+            };
+            let manager = manager.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(err) = manager.process_wallet_activity(activity).await {
+                    eprintln!("Failed to process copy trade activity: {err}");
+                }
+            });
+        });
+    }
+
     pub async fn followed_wallets(&self) -> Vec<String> {
         self.monitored_wallets
             .read()
@@ -848,13 +1070,144 @@ impl CopyTradeManager {
             .collect()
     }
 
-    pub async fn start_monitoring(manager: Arc<Self>) {
+    /// Followed wallets with whether their behavior currently deviates from
+    /// their profile.
+    pub async fn followed_wallet_summaries(&self) -> Result<Vec<FollowedWallet>, String> {
+        let mut wallets = self.followed_wallets().await;
+        wallets.sort();
+        let mut summaries = Vec::with_capacity(wallets.len());
+        for wallet in wallets {
+            let profile = self.leader_profile(&wallet).await?;
+            summaries.push(FollowedWallet {
+                behavior_changed: profile.behavior_changed,
+                changes: profile.changes,
+                last_trade_at: profile.last_trade_at,
+                wallet,
+            });
+        }
+        Ok(summaries)
+    }
+
+    pub async fn leader_profile(&self, wallet: &str) -> Result<LeaderProfile, String> {
+        let now = Utc::now();
+        let trades = self
+            .db
+            .read()
+            .await
+            .leader_trades(wallet, now - chrono::Duration::days(PROFILE_LOOKBACK_DAYS))
+            .await
+            .map_err(|e| format!("Failed to load leader trades: {e}"))?;
+        Ok(LeaderProfile::build(
+            wallet,
+            &trades,
+            now,
+            &BehaviorThresholds::default(),
+        ))
+    }
+
+    async fn observe_leader_trade(&self, activity: &WalletActivity) -> Result<(), String> {
+        // A swap into SOL or a stablecoin exits the input token; anything
+        // else moves into the output token.
+        let symbol = if is_quote_mint(&activity.output_mint) {
+            &activity.input_symbol
+        } else {
+            &activity.output_symbol
+        };
+        let trade = LeaderTrade {
+            wallet: activity.wallet.clone(),
+            tx_signature: activity.tx_signature.clone(),
+            traded_at: activity.timestamp,
+            amount: activity.amount,
+            symbol: symbol.clone(),
+            category: classify_sector(symbol),
+        };
+        self.db
+            .read()
+            .await
+            .record_leader_trade(&trade)
+            .await
+            .map_err(|e| format!("Failed to record leader trade: {e}"))?;
+        self.check_leader_behavior(&activity.wallet).await
+    }
+
+    /// Notifies changes that newly hold for `wallet` and forgets those that
+    /// no longer do.
+    async fn check_leader_behavior(&self, wallet: &str) -> Result<(), String> {
+        let profile = self.leader_profile(wallet).await?;
+        let db = self.db.read().await;
+        let notified = db
+            .leader_alert_keys(wallet)
+            .await
+            .map_err(|e| format!("Failed to load leader alerts: {e}"))?;
+
+        let current: HashSet<String> = profile.changes.iter().map(BehaviorChange::key).collect();
+        for key in notified.difference(&current) {
+            db.clear_leader_alert(wallet, key)
+                .await
+                .map_err(|e| format!("Failed to clear leader alert: {e}"))?;
+        }
+        for change in &profile.changes {
+            let key = change.key();
+            if notified.contains(&key) {
+                continue;
+            }
+            db.insert_leader_alert(wallet, &key)
+                .await
+                .map_err(|e| format!("Failed to record leader alert: {e}"))?;
+            self.notify_behavior_change(wallet, change).await;
+        }
+        Ok(())
+    }
+
+    async fn notify_behavior_change(&self, wallet: &str, change: &BehaviorChange) {
+        let event = LeaderBehaviorEvent {
+            wallet: wallet.to_string(),
+            change: change.clone(),
+        };
+        let _ = self.app_handle.emit("copy_trade_leader_behavior", &event);
+
+        let Some(router) = self.app_handle.try_state::<SharedNotificationRouter>() else {
+            return;
+        };
+        let notification = NewNotification {
+            category: HistoryCategory::CopyTrading,
+            priority: AlertPriority::Medium,
+            title: format!(
+                "Followed wallet {}: {}",
+                short_wallet(wallet),
+                change_title(change)
+            ),
+            message: change.summary.clone(),
+            entity: Some(NotificationEntity::LeaderWallet(wallet.to_string())),
+            link: None,
+        };
+        let result = router
+            .read()
+            .await
+            .send_event_notification(&notification)
+            .await;
+        if let Err(err) = result {
+            eprintln!("Failed to route leader behavior change for {wallet}: {err}");
+        }
+    }
+
+    pub async fn start_monitoring(manager: Arc<Self>, mut shutdown: ShutdownListener) {
         let mut ticker = interval(Duration::from_secs(60));
         loop {
-            ticker.tick().await;
+            tokio::select! {
+                _ = ticker.tick() => {}
+                _ = shutdown.cancelled() => break,
+            }
             if let Err(err) = manager.initialize_monitored_wallets().await {
                 eprintln!("Failed to refresh monitored wallets: {err}");
             }
+            // Dormancy only shows up when nothing happens, so it is checked
+            // on the timer rather than on activity.
+            for wallet in manager.followed_wallets().await {
+                if let Err(err) = manager.check_leader_behavior(&wallet).await {
+                    eprintln!("Failed to check leader behavior for {wallet}: {err}");
+                }
+            }
         }
     }
 }
@@ -931,6 +1284,96 @@ fn evaluate_trade_decision(
     TradeDecision::Proceed
 }
 
+fn short_wallet(wallet: &str) -> String {
+    if wallet.len() > 10 {
+        format!("{}…{}", &wallet[..4], &wallet[wallet.len() - 4..])
+    } else {
+        wallet.to_string()
+    }
+}
+
+fn change_title(change: &BehaviorChange) -> String {
+    use super::leader_profile::BehaviorChangeKind;
+
+    match change.kind {
+        BehaviorChangeKind::FrequencySpike => "trading much more often".to_string(),
+        BehaviorChangeKind::PositionSizeShift if change.recent_value > change.baseline_value => {
+            "trade sizes up".to_string()
+        }
+        BehaviorChangeKind::PositionSizeShift => "trade sizes down".to_string(),
+        BehaviorChangeKind::NewCategory => format!(
+            "started trading {} tokens",
+            change.category.as_deref().unwrap_or("new")
+        ),
+        BehaviorChangeKind::Dormant => "gone quiet".to_string(),
+    }
+}
+
+/// Maps a copied swap onto the wallet performance database. Swaps into a
+/// quote mint (SOL or stablecoins) are sells of the input token; everything
+/// else is a buy of the output token.
+fn performance_trade(
+    config: &CopyTradeConfig,
+    execution: &CopyTradeExecution,
+) -> RecordTradeRequest {
+    let (side, token_mint, token_symbol) = if is_quote_mint(&execution.output_mint) {
+        ("sell", &execution.input_mint, &execution.input_symbol)
+    } else {
+        ("buy", &execution.output_mint, &execution.output_symbol)
+    };
+
+    RecordTradeRequest {
+        wallet_address: config.wallet_address.clone(),
+        token_mint: token_mint.clone(),
+        token_symbol: token_symbol.clone(),
+        side: side.to_string(),
+        amount: execution.copied_amount,
+        price: execution.price,
+        fee: 0.0,
+        tx_signature: execution
+            .copied_tx_signature
+            .clone()
+            .unwrap_or_else(|| execution.source_tx_signature.clone()),
+        strategy_source: Some(StrategySource::Copy(config.id.clone()).tag()),
+        external_id: Some(format!("copy:{}", execution.id)),
+        external_source: None,
+        executed_at: Some(execution.executed_at),
+    }
+}
+
+/// The copied trade on the order event bus. Copies are simulated, so there
+/// is no execution price or received amount yet: `execution.price` is the
+/// ratio of the source amount to the copied one, and the amounts are in
+/// input-token units. The fill goes out without a price, which leaves it
+/// unbracketed by the position manager.
+fn fill_event(config: &CopyTradeConfig, execution: &CopyTradeExecution) -> OrderEvent {
+    let (side, symbol) = if is_quote_mint(&execution.output_mint) {
+        (OrderSide::Sell, &execution.input_symbol)
+    } else {
+        (OrderSide::Buy, &execution.output_symbol)
+    };
+
+    OrderEvent::new(
+        OrderEventSource::CopyTrade,
+        &execution.id,
+        symbol,
+        side,
+        execution.copied_amount,
+        OrderEventKind::Filled {
+            filled_amount: execution.copied_amount,
+            fill_price: None,
+            tx_signature: execution.copied_tx_signature.clone(),
+        },
+    )
+    .with_route(OrderRoute {
+        wallet_address: config.wallet_address.clone(),
+        input_mint: execution.input_mint.clone(),
+        output_mint: execution.output_mint.clone(),
+        input_symbol: execution.input_symbol.clone(),
+        output_symbol: execution.output_symbol.clone(),
+    })
+}
+
 pub struct CopyTradingState {
     pub db: SharedCopyTradeDatabase,
     pub manager: Arc<CopyTradeManager>,
@@ -944,8 +1387,7 @@ pub async fn init_copy_trading(app_handle: &AppHandle) -> Result<(), String> {
     }
 
     let app_dir = app_handle
-        .path()
-        .app_data_dir()
+        .app_data_root()
         .map_err(|e| format!("Unable to resolve app data directory: {}", e))?;
     std::fs::create_dir_all(&app_dir)
         .map_err(|e| format!("Failed to create app data directory: {e}"))?;
@@ -960,11 +1402,13 @@ pub async fn init_copy_trading(app_handle: &AppHandle) -> Result<(), String> {
     let shared_db = Arc::new(RwLock::new(db));
     let manager = Arc::new(CopyTradeManager::new(shared_db.clone(), app_handle.clone()));
     manager.initialize_monitored_wallets().await?;
+    CopyTradeManager::attach_stream_listener(manager.clone());
 
     let handle_for_task = app_handle.clone();
     let manager_for_task = manager.clone();
+    let shutdown = crate::errors::shutdown_listener(app_handle, "copy_trading_monitor");
     tauri::async_runtime::spawn(async move {
-        CopyTradeManager::start_monitoring(manager_for_task).await;
+        CopyTradeManager::start_monitoring(manager_for_task, shutdown).await;
         let _ = handle_for_task.emit(
             "copy_trading_monitor_stopped",
             "Copy trading monitor stopped",
@@ -981,6 +1425,37 @@ pub async fn init_copy_trading(app_handle: &AppHandle) -> Result<(), String> {
     Ok(())
 }
 
+/// Successful copied trades as performance trades, used to back-fill history
+/// recorded before copy trading reported to the performance database.
+pub async fn copy_trade_performance_history() -> Result<Vec<RecordTradeRequest>, String> {
+    let Some(state) = COPY_TRADING_STATE.get() else {
+        return Ok(Vec::new());
+    };
+
+    let db = state.db.read().await;
+    let executions = db
+        .successful_executions()
+        .await
+        .map_err(|e| format!("Failed to load copy trade executions: {e}"))?;
+
+    let mut configs: HashMap<String, Option<CopyTradeConfig>> = HashMap::new();
+    let mut requests = Vec::with_capacity(executions.len());
+    for execution in executions {
+        if !configs.contains_key(&execution.config_id) {
+            let config = db
+                .get_config(&execution.config_id)
+                .await
+                .map_err(|e| format!("Failed to load copy trade config: {e}"))?;
+            configs.insert(execution.config_id.clone(), config);
+        }
+        if let Some(Some(config)) = configs.get(&execution.config_id) {
+            requests.push(performance_trade(config, &execution));
+        }
+    }
+
+    Ok(requests)
+}
+
 fn require_state<'a>() -> Result<&'a CopyTradingState, String> {
     COPY_TRADING_STATE
         .get()
@@ -1049,9 +1524,15 @@ pub async fn copy_trading_process_activity(activity: WalletActivity) -> Result<(
 }
 
 #[tauri::command]
-pub async fn copy_trading_followed_wallets() -> Result<Vec<String>, String> {
+pub async fn copy_trading_followed_wallets() -> Result<Vec<FollowedWallet>, String> {
     let state = require_state()?;
-    Ok(state.manager.followed_wallets().await)
+    state.manager.followed_wallet_summaries().await
+}
+
+#[tauri::command]
+pub async fn copy_trading_get_leader_profile(wallet: String) -> Result<LeaderProfile, String> {
+    let state = require_state()?;
+    state.manager.leader_profile(&wallet).await
 }
 
 #[cfg(test)]
@@ -1097,6 +1578,29 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_activity_from_shared_decoder() {
+        let raw: serde_json::Value =
+            serde_json::from_str(include_str!("../websocket/fixtures/orca_swap.json")).unwrap();
+        let decoded = crate::websocket::activity::decode_transaction(
+            &raw,
+            "7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU",
+        );
+
+        let activity = WalletActivity::from_decoded(&decoded).expect("swap is copyable");
+        assert_eq!(activity.action, "buy");
+        assert_eq!(activity.input_symbol, "USDC");
+        assert_eq!(
+            activity.output_mint,
+            "mSoLzYCxHdYgdzU16g5QSh3i5K3z3KZK7ytfqcJm7So"
+        );
+        assert_eq!(activity.amount, 50.0);
+
+        let mut transfer = decoded.clone();
+        transfer.kind = crate::websocket::activity::ActivityKind::TransferOut;
+        assert!(WalletActivity::from_decoded(&transfer).is_none());
+    }
+
     #[test]
     fn test_stop_loss_triggers_stop_decision() {
         let config = sample_config();
@@ -1,4 +1,7 @@
+use crate::trading::execution_audit::ExecutionAuditLog;
+use crate::trading::idempotency::IdempotencyKeys;
 use crate::trading::types::{Order, OrderStatus, OrderType};
+use crate::utils::ensure_column;
 use chrono::Utc;
 use sqlx::{Pool, Sqlite, SqlitePool};
 use std::path::PathBuf;
@@ -20,6 +23,14 @@ impl OrderDatabase {
         Ok(db)
     }
 
+    /// Copies committed WAL pages into the orders database and truncates the log.
+    pub async fn checkpoint(&self) -> Result<(), sqlx::Error> {
+        sqlx::query("PRAGMA wal_checkpoint(TRUNCATE)")
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
     async fn initialize(&self) -> Result<(), sqlx::Error> {
         sqlx::query(
             r#"
@@ -54,20 +65,37 @@ impl OrderDatabase {
         .execute(&self.pool)
         .await?;
 
+        ensure_column(&self.pool, "orders", "min_book_depth_usd", "REAL").await?;
+        ensure_column(&self.pool, "orders", "fill_price", "REAL").await?;
+        ensure_column(&self.pool, "orders", "origin", "TEXT").await?;
+        ensure_column(&self.pool, "orders", "correlation_id", "TEXT").await?;
+
         sqlx::query(
             r#"
             CREATE INDEX IF NOT EXISTS idx_orders_status ON orders(status);
             CREATE INDEX IF NOT EXISTS idx_orders_wallet ON orders(wallet_address);
             CREATE INDEX IF NOT EXISTS idx_orders_created ON orders(created_at);
             CREATE INDEX IF NOT EXISTS idx_orders_linked ON orders(linked_order_id);
+            CREATE INDEX IF NOT EXISTS idx_orders_correlation ON orders(correlation_id);
             "#,
         )
         .execute(&self.pool)
         .await?;
 
+        IdempotencyKeys::initialize(&self.pool).await?;
+        ExecutionAuditLog::initialize(&self.pool).await?;
+
         Ok(())
     }
 
+    pub fn idempotency_keys(&self) -> IdempotencyKeys {
+        IdempotencyKeys::new(self.pool.clone())
+    }
+
+    pub fn execution_audit(&self) -> ExecutionAuditLog {
+        ExecutionAuditLog::new(self.pool.clone())
+    }
+
     pub async fn create_order(&self, order: &Order) -> Result<(), sqlx::Error> {
         sqlx::query(
             r#"
@@ -77,11 +105,12 @@ impl OrderDatabase {
                 limit_price, stop_price, trailing_percent,
                 highest_price, lowest_price, linked_order_id,
                 slippage_bps, priority_fee_micro_lamports, wallet_address,
-                created_at, updated_at, triggered_at, tx_signature, error_message
+                created_at, updated_at, triggered_at, tx_signature, error_message,
+                min_book_depth_usd, correlation_id, origin
             ) VALUES (
                 ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10,
                 ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19,
-                ?20, ?21, ?22, ?23, ?24
+                ?20, ?21, ?22, ?23, ?24, ?25, ?26, ?27
             )
             "#,
         )
@@ -109,6 +138,9 @@ impl OrderDatabase {
         .bind(order.triggered_at.map(|t| t.to_rfc3339()))
         .bind(&order.tx_signature)
         .bind(&order.error_message)
+        .bind(order.min_book_depth_usd)
+        .bind(&order.correlation_id)
+        .bind(order.origin)
         .execute(&self.pool)
         .await?;
 
@@ -204,6 +236,7 @@ impl OrderDatabase {
         &self,
         id: &str,
         filled_amount: f64,
+        fill_price: Option<f64>,
         status: OrderStatus,
         tx_signature: Option<String>,
     ) -> Result<(), sqlx::Error> {
@@ -212,12 +245,13 @@ impl OrderDatabase {
         sqlx::query(
             r#"
             UPDATE orders 
-            SET filled_amount = ?1, status = ?2, updated_at = ?3,
-                triggered_at = ?4, tx_signature = ?5
-            WHERE id = ?6
+            SET filled_amount = ?1, fill_price = ?2, status = ?3, updated_at = ?4,
+                triggered_at = ?5, tx_signature = ?6
+            WHERE id = ?7
             "#,
         )
         .bind(filled_amount)
+        .bind(fill_price)
         .bind(status.to_string())
         .bind(&now)
         .bind(&now)
@@ -229,6 +263,32 @@ impl OrderDatabase {
         Ok(())
     }
 
+    /// Orders that bought or sold `mint` and were filled, fully or partly,
+    /// between two RFC 3339 times, oldest first.
+    pub async fn get_fills_for_mint(
+        &self,
+        mint: &str,
+        from: &str,
+        to: &str,
+    ) -> Result<Vec<Order>, sqlx::Error> {
+        let orders = sqlx::query_as::<_, Order>(
+            r#"
+            SELECT * FROM orders
+            WHERE ((side = 'buy' AND output_mint = ?1) OR (side = 'sell' AND input_mint = ?1))
+            AND filled_amount > 0
+            AND triggered_at >= ?2 AND triggered_at <= ?3
+            ORDER BY triggered_at ASC
+            "#,
+        )
+        .bind(mint)
+        .bind(from)
+        .bind(to)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(orders)
+    }
+
     pub async fn update_trailing_stop(
         &self,
         id: &str,
@@ -261,6 +321,20 @@ impl OrderDatabase {
             .await
     }
 
+    pub async fn get_open_linked_orders(&self, linked_id: &str) -> Result<Vec<Order>, sqlx::Error> {
+        let orders = sqlx::query_as::<_, Order>(
+            r#"
+            SELECT * FROM orders
+            WHERE linked_order_id = ?1 AND status IN ('pending', 'partially_filled')
+            "#,
+        )
+        .bind(linked_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(orders)
+    }
+
     pub async fn cancel_linked_orders(&self, linked_id: &str) -> Result<(), sqlx::Error> {
         let now = Utc::now().to_rfc3339();
 
@@ -0,0 +1,269 @@
+//! Order lifecycle events from live trading, paper trading, DCA and copy
+//! trading.
+//!
+//! Every event goes through one in-process bus. Backend modules subscribe with
+//! `order_events()` instead of reaching into the trading engine, and
+//! `forward_order_events` relays the bus to the frontend on `order-event`.
+//! Sequence numbers start at 1 and increase by one per order, so a gap (or a
+//! restart at 1 after the app restarts) means the order should be re-read with
+//! `get_order`.
+
+use crate::trading::types::{Order, OrderSide};
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::broadcast::{self, error::RecvError};
+
+pub const ORDER_EVENT: &str = "order-event";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderEventSource {
+    Live,
+    Paper,
+    Dca,
+    CopyTrade,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OrderEventKind {
+    Created,
+    /// A stop price was set or moved; the order fires when price crosses it.
+    TriggerArmed {
+        stop_price: f64,
+    },
+    Triggered {
+        trigger_price: f64,
+    },
+    PartiallyFilled {
+        filled_amount: f64,
+        fill_price: Option<f64>,
+    },
+    Filled {
+        filled_amount: f64,
+        fill_price: Option<f64>,
+        tx_signature: Option<String>,
+    },
+    Cancelled {
+        reason: Option<String>,
+    },
+    Expired,
+    Failed {
+        error: String,
+    },
+}
+
+impl OrderEventKind {
+    /// No further events follow for the order.
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            OrderEventKind::Filled { .. }
+                | OrderEventKind::Cancelled { .. }
+                | OrderEventKind::Expired
+                | OrderEventKind::Failed { .. }
+        )
+    }
+}
+
+/// The wallet and tokens of a bot fill. Live events carry the whole order
+/// instead.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderRoute {
+    pub wallet_address: String,
+    pub input_mint: String,
+    pub output_mint: String,
+    pub input_symbol: String,
+    pub output_symbol: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderEvent {
+    pub order_id: String,
+    /// Assigned by `publish_order_event`.
+    pub seq: u64,
+    pub source: OrderEventSource,
+    /// The token being traded: what a buy acquires or a sell disposes of.
+    pub symbol: String,
+    pub side: OrderSide,
+    pub amount: f64,
+    pub timestamp: DateTime<Utc>,
+    #[serde(flatten)]
+    pub kind: OrderEventKind,
+    /// The order after the change, for live orders.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order: Option<Order>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub route: Option<OrderRoute>,
+}
+
+impl OrderEvent {
+    pub fn new(
+        source: OrderEventSource,
+        order_id: impl Into<String>,
+        symbol: impl Into<String>,
+        side: OrderSide,
+        amount: f64,
+        kind: OrderEventKind,
+    ) -> Self {
+        Self {
+            order_id: order_id.into(),
+            seq: 0,
+            source,
+            symbol: symbol.into(),
+            side,
+            amount,
+            timestamp: Utc::now(),
+            kind,
+            order: None,
+            route: None,
+        }
+    }
+
+    pub fn with_route(mut self, route: OrderRoute) -> Self {
+        self.route = Some(route);
+        self
+    }
+
+    /// The order's wallet and tokens, from the route or the live order.
+    pub fn route(&self) -> Option<OrderRoute> {
+        if let Some(route) = &self.route {
+            return Some(route.clone());
+        }
+        self.order.as_ref().map(|order| OrderRoute {
+            wallet_address: order.wallet_address.clone(),
+            input_mint: order.input_mint.clone(),
+            output_mint: order.output_mint.clone(),
+            input_symbol: order.input_symbol.clone(),
+            output_symbol: order.output_symbol.clone(),
+        })
+    }
+
+    pub fn live(order: &Order, kind: OrderEventKind) -> Self {
+        let symbol = match order.side {
+            OrderSide::Buy => &order.output_symbol,
+            OrderSide::Sell => &order.input_symbol,
+        };
+        Self {
+            order: Some(order.clone()),
+            ..Self::new(
+                OrderEventSource::Live,
+                &order.id,
+                symbol,
+                order.side,
+                order.amount,
+                kind,
+            )
+        }
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref ORDER_EVENTS: broadcast::Sender<OrderEvent> = broadcast::channel(1024).0;
+    /// Last sequence number per open order.
+    static ref ORDER_SEQUENCES: Mutex<HashMap<String, u64>> = Mutex::new(HashMap::new());
+}
+
+/// Every order event published from now on.
+pub fn order_events() -> broadcast::Receiver<OrderEvent> {
+    ORDER_EVENTS.subscribe()
+}
+
+/// Assigns the event its sequence number and publishes it. Returns the
+/// published event.
+pub fn publish_order_event(mut event: OrderEvent) -> OrderEvent {
+    // Held while sending so events of one order reach the bus in order.
+    let mut sequences = ORDER_SEQUENCES.lock();
+    let seq = sequences.entry(event.order_id.clone()).or_insert(0);
+    *seq += 1;
+    event.seq = *seq;
+    if event.kind.is_terminal() {
+        sequences.remove(&event.order_id);
+    }
+    let _ = ORDER_EVENTS.send(event.clone());
+    event
+}
+
+/// Relays the bus to the frontend.
+pub async fn forward_order_events(app: AppHandle) {
+    let mut events = order_events();
+    loop {
+        match events.recv().await {
+            Ok(event) => {
+                if let Err(e) = app.emit(ORDER_EVENT, &event) {
+                    eprintln!("Failed to emit order event for {}: {}", event.order_id, e);
+                }
+            }
+            Err(RecvError::Lagged(skipped)) => {
+                eprintln!("Order event forwarder skipped {} events", skipped);
+            }
+            Err(RecvError::Closed) => break,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paper(order_id: &str, kind: OrderEventKind) -> OrderEvent {
+        OrderEvent::new(
+            OrderEventSource::Paper,
+            order_id,
+            "SOL",
+            OrderSide::Buy,
+            1.0,
+            kind,
+        )
+    }
+
+    #[test]
+    fn sequence_numbers_are_per_order() {
+        let mut events = order_events();
+        let first = publish_order_event(paper("seq-a", OrderEventKind::Created));
+        let other = publish_order_event(paper("seq-b", OrderEventKind::Created));
+        let second = publish_order_event(paper(
+            "seq-a",
+            OrderEventKind::Triggered {
+                trigger_price: 10.0,
+            },
+        ));
+        assert_eq!((first.seq, other.seq, second.seq), (1, 1, 2));
+
+        let received: Vec<(String, u64)> = std::iter::from_fn(|| events.try_recv().ok())
+            .filter(|e| e.order_id.starts_with("seq-"))
+            .map(|e| (e.order_id, e.seq))
+            .collect();
+        assert_eq!(
+            received,
+            vec![
+                ("seq-a".to_string(), 1),
+                ("seq-b".to_string(), 1),
+                ("seq-a".to_string(), 2)
+            ]
+        );
+    }
+
+    #[test]
+    fn payload_is_tagged_by_type() {
+        let event = publish_order_event(paper(
+            "payload-a",
+            OrderEventKind::Filled {
+                filled_amount: 1.0,
+                fill_price: Some(100.0),
+                tx_signature: None,
+            },
+        ));
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["type"], "filled");
+        assert_eq!(json["source"], "paper");
+        assert_eq!(json["fill_price"], 100.0);
+        assert!(json.get("order").is_none());
+
+        // Terminal events end the order's sequence.
+        assert!(!ORDER_SEQUENCES.lock().contains_key("payload-a"));
+    }
+}
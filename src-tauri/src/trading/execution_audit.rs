@@ -0,0 +1,568 @@
+//! Per-order execution records for post-mortems.
+//!
+//! The client picks a correlation id before quoting and passes it to every
+//! command on the order's execution path: quotes, safety checks, fee
+//! estimates, MEV submission and the transaction send. Each command records
+//! what it saw under that id, and `create_order` stores the id on the order,
+//! so `get_execution_audit` can reassemble the whole path from the order id
+//! alone. Records live next to the orders in orders.db.
+
+use crate::data::event_store::Event as AuditEvent;
+use crate::errors::AppError;
+use crate::trading::limit_orders::require_state;
+use crate::trading::types::Order;
+use crate::wallet::fee_audit::{SharedFeeAuditor, TransactionFees};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{Pool, Row, Sqlite};
+use std::collections::{BTreeMap, HashSet};
+use tauri::{AppHandle, Manager};
+
+/// Replaces wallet addresses in redacted exports.
+const REDACTED_WALLET: &str = "[redacted wallet]";
+/// Keys whose string values are wallet addresses wherever they appear.
+const WALLET_KEYS: [&str; 4] = [
+    "wallet_address",
+    "walletAddress",
+    "user_public_key",
+    "userPublicKey",
+];
+
+/// One step of an order's execution path.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "stage", rename_all = "snake_case")]
+pub enum ExecutionStage {
+    /// The order request as the client sent it.
+    Request {
+        request: Value,
+    },
+    SafetyCheck {
+        allowed: bool,
+        result: Value,
+    },
+    Quote {
+        input_mint: String,
+        output_mint: String,
+        input_amount: String,
+        output_amount: String,
+        price_impact_pct: f64,
+        context_slot: u64,
+        /// Fetched right before signing to check the approved quote for drift.
+        requote: bool,
+    },
+    /// Fee presets offered while the trade was prepared, by preset name.
+    PriorityFeeEstimates {
+        congestion: String,
+        micro_lamports: BTreeMap<String, u64>,
+    },
+    /// The fee the swap transaction was built with.
+    PriorityFee {
+        micro_lamports: Option<u64>,
+        prioritization_fee_lamports: Option<u64>,
+    },
+    MevSubmission {
+        protected: bool,
+        method: Option<String>,
+        bundle_id: Option<String>,
+        bundle_status: Option<String>,
+    },
+    Transaction {
+        signature: String,
+        slot: Option<u64>,
+        /// From submission to confirmation.
+        latency_ms: Option<u64>,
+    },
+    Fill {
+        filled_amount: f64,
+        fill_price: Option<f64>,
+    },
+    Error {
+        source: String,
+        message: String,
+        retrying: bool,
+    },
+}
+
+impl ExecutionStage {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ExecutionStage::Request { .. } => "request",
+            ExecutionStage::SafetyCheck { .. } => "safety_check",
+            ExecutionStage::Quote { .. } => "quote",
+            ExecutionStage::PriorityFeeEstimates { .. } => "priority_fee_estimates",
+            ExecutionStage::PriorityFee { .. } => "priority_fee",
+            ExecutionStage::MevSubmission { .. } => "mev_submission",
+            ExecutionStage::Transaction { .. } => "transaction",
+            ExecutionStage::Fill { .. } => "fill",
+            ExecutionStage::Error { .. } => "error",
+        }
+    }
+
+    /// Free-form payloads that may carry wallet addresses.
+    fn payload(&self) -> Option<&Value> {
+        match self {
+            ExecutionStage::Request { request } => Some(request),
+            ExecutionStage::SafetyCheck { result, .. } => Some(result),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExecutionAuditEntry {
+    #[serde(flatten)]
+    pub stage: ExecutionStage,
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// Everything recorded about one order's execution, in recording order
+/// within each section.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionAudit {
+    pub order: Order,
+    /// `None` for orders created before execution paths were correlated;
+    /// their audit holds only the order and its lifecycle events.
+    pub correlation_id: Option<String>,
+    pub request: Option<ExecutionAuditEntry>,
+    pub safety_checks: Vec<ExecutionAuditEntry>,
+    pub quotes: Vec<ExecutionAuditEntry>,
+    pub priority_fees: Vec<ExecutionAuditEntry>,
+    pub mev_submissions: Vec<ExecutionAuditEntry>,
+    pub transactions: Vec<ExecutionAuditEntry>,
+    pub fills: Vec<ExecutionAuditEntry>,
+    pub errors: Vec<ExecutionAuditEntry>,
+    /// Fees actually paid, per confirmed transaction signature.
+    pub fees_paid: Vec<TransactionFees>,
+    /// Placed, filled and cancelled events from the event store.
+    pub lifecycle: Vec<AuditEvent>,
+    pub redacted: bool,
+    pub generated_at: DateTime<Utc>,
+}
+
+impl ExecutionAudit {
+    pub fn assemble(
+        order: Order,
+        entries: Vec<ExecutionAuditEntry>,
+        lifecycle: Vec<AuditEvent>,
+    ) -> Self {
+        let mut audit = Self {
+            correlation_id: order.correlation_id.clone(),
+            order,
+            request: None,
+            safety_checks: Vec::new(),
+            quotes: Vec::new(),
+            priority_fees: Vec::new(),
+            mev_submissions: Vec::new(),
+            transactions: Vec::new(),
+            fills: Vec::new(),
+            errors: Vec::new(),
+            fees_paid: Vec::new(),
+            lifecycle,
+            redacted: false,
+            generated_at: Utc::now(),
+        };
+
+        for entry in entries {
+            let section = match entry.stage {
+                ExecutionStage::Request { .. } => {
+                    // A retried create records the request again; the first is
+                    // the one the order was built from.
+                    audit.request.get_or_insert(entry);
+                    continue;
+                }
+                ExecutionStage::SafetyCheck { .. } => &mut audit.safety_checks,
+                ExecutionStage::Quote { .. } => &mut audit.quotes,
+                ExecutionStage::PriorityFeeEstimates { .. }
+                | ExecutionStage::PriorityFee { .. } => &mut audit.priority_fees,
+                ExecutionStage::MevSubmission { .. } => &mut audit.mev_submissions,
+                ExecutionStage::Transaction { .. } => &mut audit.transactions,
+                ExecutionStage::Fill { .. } => &mut audit.fills,
+                ExecutionStage::Error { .. } => &mut audit.errors,
+            };
+            section.push(entry);
+        }
+        audit
+    }
+
+    /// Signatures of transactions that were actually sent, not simulated or
+    /// mocked.
+    pub fn signatures(&self) -> Vec<String> {
+        let mut signatures: Vec<String> = self
+            .transactions
+            .iter()
+            .filter_map(|entry| match &entry.stage {
+                ExecutionStage::Transaction { signature, .. } => Some(signature.clone()),
+                _ => None,
+            })
+            .chain(self.order.tx_signature.clone())
+            .filter(|signature| {
+                !signature.starts_with("simulated_") && !signature.starts_with("mock_")
+            })
+            .collect();
+        signatures.sort();
+        signatures.dedup();
+        signatures
+    }
+
+    /// Replaces the order's wallet, and any address found under a wallet key
+    /// in the recorded payloads, wherever it appears in the audit. Lifecycle
+    /// events carry no addresses.
+    pub fn redact_wallets(&mut self) {
+        let mut wallets = HashSet::from([self.order.wallet_address.clone()]);
+        for entry in self.entries() {
+            if let Some(payload) = entry.stage.payload() {
+                collect_wallets(payload, &mut wallets);
+            }
+        }
+        wallets.retain(|wallet| !wallet.is_empty());
+
+        self.order.wallet_address = REDACTED_WALLET.to_string();
+        for entry in self.entries_mut() {
+            match &mut entry.stage {
+                ExecutionStage::Request { request: payload }
+                | ExecutionStage::SafetyCheck {
+                    result: payload, ..
+                } => redact_value(payload, &wallets),
+                ExecutionStage::Error { message, .. } => *message = redact_str(message, &wallets),
+                _ => {}
+            }
+        }
+        self.redacted = true;
+    }
+
+    fn entries(&self) -> impl Iterator<Item = &ExecutionAuditEntry> {
+        self.request
+            .iter()
+            .chain(&self.safety_checks)
+            .chain(&self.errors)
+    }
+
+    fn entries_mut(&mut self) -> impl Iterator<Item = &mut ExecutionAuditEntry> {
+        self.request
+            .iter_mut()
+            .chain(&mut self.safety_checks)
+            .chain(&mut self.errors)
+    }
+}
+
+fn collect_wallets(value: &Value, wallets: &mut HashSet<String>) {
+    match value {
+        Value::Array(items) => items.iter().for_each(|item| collect_wallets(item, wallets)),
+        Value::Object(map) => {
+            for (key, field) in map {
+                match field.as_str() {
+                    Some(wallet) if WALLET_KEYS.contains(&key.as_str()) => {
+                        wallets.insert(wallet.to_string());
+                    }
+                    _ => collect_wallets(field, wallets),
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn redact_value(value: &mut Value, wallets: &HashSet<String>) {
+    match value {
+        Value::String(text) => *text = redact_str(text, wallets),
+        Value::Array(items) => items
+            .iter_mut()
+            .for_each(|item| redact_value(item, wallets)),
+        Value::Object(map) => map
+            .values_mut()
+            .for_each(|field| redact_value(field, wallets)),
+        _ => {}
+    }
+}
+
+fn redact_str(text: &str, wallets: &HashSet<String>) -> String {
+    wallets.iter().fold(text.to_string(), |text, wallet| {
+        text.replace(wallet.as_str(), REDACTED_WALLET)
+    })
+}
+
+/// The `execution_audit` table of the orders database.
+pub struct ExecutionAuditLog {
+    pool: Pool<Sqlite>,
+}
+
+impl ExecutionAuditLog {
+    pub fn new(pool: Pool<Sqlite>) -> Self {
+        Self { pool }
+    }
+
+    pub async fn initialize(pool: &Pool<Sqlite>) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS execution_audit (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                correlation_id TEXT NOT NULL,
+                stage TEXT NOT NULL,
+                payload TEXT NOT NULL,
+                recorded_at TEXT NOT NULL
+            )
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS idx_execution_audit_correlation ON execution_audit(correlation_id, id)",
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn record(&self, correlation_id: &str, stage: &ExecutionStage) -> Result<(), String> {
+        let payload = serde_json::to_string(stage).map_err(|e| e.to_string())?;
+        sqlx::query(
+            r#"
+            INSERT INTO execution_audit (correlation_id, stage, payload, recorded_at)
+            VALUES (?1, ?2, ?3, ?4)
+            "#,
+        )
+        .bind(correlation_id)
+        .bind(stage.name())
+        .bind(payload)
+        .bind(Utc::now().to_rfc3339())
+        .execute(&self.pool)
+        .await
+        .map_err(|e| format!("Failed to record execution {}: {e}", stage.name()))?;
+
+        Ok(())
+    }
+
+    /// Entries for `correlation_id` in the order they were recorded. Entries
+    /// that no longer decode are skipped.
+    pub async fn entries(&self, correlation_id: &str) -> Result<Vec<ExecutionAuditEntry>, String> {
+        let rows = sqlx::query(
+            "SELECT payload, recorded_at FROM execution_audit WHERE correlation_id = ?1 ORDER BY id",
+        )
+        .bind(correlation_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| format!("Failed to load execution records: {e}"))?;
+
+        Ok(rows
+            .iter()
+            .filter_map(|row| {
+                let stage = serde_json::from_str(row.get::<&str, _>("payload")).ok()?;
+                let recorded_at = DateTime::parse_from_rfc3339(row.get("recorded_at")).ok()?;
+                Some(ExecutionAuditEntry {
+                    stage,
+                    recorded_at: recorded_at.with_timezone(&Utc),
+                })
+            })
+            .collect())
+    }
+}
+
+/// Records a stage of the execution path tagged `correlation_id`. Untagged
+/// calls, and calls before the trading module is up, are ignored. Failures
+/// are logged, as the stage itself already happened.
+pub async fn record_execution_stage(correlation_id: Option<&str>, stage: ExecutionStage) {
+    let (Some(correlation_id), Ok(state)) = (correlation_id, require_state()) else {
+        return;
+    };
+    let log = state.db.read().await.execution_audit();
+    if let Err(e) = log.record(correlation_id, &stage).await {
+        eprintln!("{e}");
+    }
+}
+
+#[tauri::command]
+pub async fn get_execution_audit(
+    order_id: String,
+    redact_wallets: Option<bool>,
+    app: AppHandle,
+) -> Result<ExecutionAudit, AppError> {
+    let state = require_state()?;
+    let order = state.manager.get_order(&order_id).await?;
+    let entries = match &order.correlation_id {
+        Some(correlation_id) => state
+            .db
+            .read()
+            .await
+            .execution_audit()
+            .entries(correlation_id)
+            .await
+            .map_err(AppError::internal)?,
+        None => Vec::new(),
+    };
+    let lifecycle = state.manager.lifecycle_events(&order_id).await;
+    let mut audit = ExecutionAudit::assemble(order, entries, lifecycle);
+
+    if let Some(auditor) = app.try_state::<SharedFeeAuditor>() {
+        for signature in audit.signatures() {
+            match auditor
+                .transaction_fees(&audit.order.wallet_address, &signature)
+                .await
+            {
+                Ok(Some(fees)) => audit.fees_paid.push(fees),
+                Ok(None) => {}
+                Err(e) => eprintln!("Failed to look up fees for {signature}: {e}"),
+            }
+        }
+    }
+
+    if redact_wallets.unwrap_or(false) {
+        audit.redact_wallets();
+    }
+    Ok(audit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trading::database::OrderDatabase;
+    use crate::trading::types::{OrderSide, OrderStatus, OrderType};
+    use serde_json::json;
+    use uuid::Uuid;
+
+    const WALLET: &str = "7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU";
+
+    fn order(correlation_id: &str) -> Order {
+        Order {
+            id: Uuid::new_v4().to_string(),
+            order_type: OrderType::Market,
+            side: OrderSide::Buy,
+            status: OrderStatus::Filled,
+            input_mint: "So11111111111111111111111111111111111111112".to_string(),
+            output_mint: "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v".to_string(),
+            input_symbol: "SOL".to_string(),
+            output_symbol: "USDC".to_string(),
+            amount: 1.0,
+            filled_amount: 1.0,
+            fill_price: Some(150.0),
+            limit_price: None,
+            stop_price: None,
+            trailing_percent: None,
+            highest_price: None,
+            lowest_price: None,
+            linked_order_id: None,
+            min_book_depth_usd: None,
+            slippage_bps: 50,
+            priority_fee_micro_lamports: 5000,
+            wallet_address: WALLET.to_string(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            triggered_at: None,
+            tx_signature: Some("simulated_1".to_string()),
+            error_message: None,
+            correlation_id: Some(correlation_id.to_string()),
+            origin: None,
+        }
+    }
+
+    fn quote(output_amount: &str, requote: bool) -> ExecutionStage {
+        ExecutionStage::Quote {
+            input_mint: "So11111111111111111111111111111111111111112".to_string(),
+            output_mint: "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v".to_string(),
+            input_amount: "1000000000".to_string(),
+            output_amount: output_amount.to_string(),
+            price_impact_pct: 0.01,
+            context_slot: 42,
+            requote,
+        }
+    }
+
+    #[tokio::test]
+    async fn assembles_recorded_stages_by_section() {
+        let path = std::env::temp_dir().join(format!("execution_audit_{}.db", Uuid::new_v4()));
+        let db = OrderDatabase::new(path.clone())
+            .await
+            .expect("order database");
+        let log = db.execution_audit();
+
+        let stages = [
+            ExecutionStage::Request {
+                request: json!({ "wallet_address": WALLET, "amount": 1.0 }),
+            },
+            quote("150000000", false),
+            ExecutionStage::SafetyCheck {
+                allowed: true,
+                result: json!({ "allowed": true }),
+            },
+            quote("149900000", true),
+            ExecutionStage::Error {
+                source: "jupiter".to_string(),
+                message: "quote drift".to_string(),
+                retrying: true,
+            },
+            ExecutionStage::Transaction {
+                signature: "sig1".to_string(),
+                slot: Some(43),
+                latency_ms: Some(850),
+            },
+            ExecutionStage::Fill {
+                filled_amount: 1.0,
+                fill_price: Some(150.0),
+            },
+        ];
+        for stage in &stages {
+            log.record("corr-1", stage).await.unwrap();
+        }
+        log.record("corr-2", &quote("1", false)).await.unwrap();
+
+        let entries = log.entries("corr-1").await.unwrap();
+        assert_eq!(entries.len(), stages.len());
+
+        let audit = ExecutionAudit::assemble(order("corr-1"), entries, Vec::new());
+        assert!(audit.request.is_some());
+        assert_eq!(audit.quotes.len(), 2);
+        assert!(matches!(
+            audit.quotes[1].stage,
+            ExecutionStage::Quote { requote: true, .. }
+        ));
+        assert_eq!(audit.safety_checks.len(), 1);
+        assert_eq!(audit.errors.len(), 1);
+        assert_eq!(audit.fills.len(), 1);
+        assert_eq!(audit.signatures(), vec!["sig1".to_string()]);
+
+        let json = serde_json::to_value(&audit).unwrap();
+        assert_eq!(json["quotes"][0]["stage"], "quote");
+        assert_eq!(json["transactions"][0]["slot"], 43);
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn redaction_removes_wallets_from_order_and_payloads() {
+        let other = "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM";
+        let entries = vec![
+            ExecutionAuditEntry {
+                stage: ExecutionStage::Request {
+                    request: json!({ "wallet_address": WALLET, "input_symbol": "SOL" }),
+                },
+                recorded_at: Utc::now(),
+            },
+            ExecutionAuditEntry {
+                stage: ExecutionStage::SafetyCheck {
+                    allowed: false,
+                    result: json!({ "blocked_by": format!("cooldown for {other}"), "walletAddress": other }),
+                },
+                recorded_at: Utc::now(),
+            },
+            ExecutionAuditEntry {
+                stage: ExecutionStage::Error {
+                    source: "order_manager".to_string(),
+                    message: format!("{WALLET} has insufficient balance"),
+                    retrying: false,
+                },
+                recorded_at: Utc::now(),
+            },
+        ];
+
+        let mut audit = ExecutionAudit::assemble(order("corr"), entries, Vec::new());
+        audit.redact_wallets();
+
+        let json = serde_json::to_string(&audit).unwrap();
+        assert!(!json.contains(WALLET));
+        assert!(!json.contains(other));
+        assert!(json.contains("SOL"));
+        assert!(audit.redacted);
+    }
+}
@@ -0,0 +1,300 @@
+//! Client-supplied request keys that make create commands safe to retry.
+//!
+//! A key is stored with the result of the first successful request under a
+//! unique `(scope, request_id)` constraint, in the same database as the object
+//! it created. A retry with the same key returns that result, and concurrent
+//! requests with the same key wait on the in-flight attempt instead of racing
+//! it. Keys expire after `IDEMPOTENCY_KEY_TTL_HOURS`.
+
+use crate::errors::AppError;
+use chrono::{DateTime, Duration, Utc};
+use serde::{de::DeserializeOwned, Serialize};
+use sqlx::{Pool, Row, Sqlite};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::{Mutex, OnceCell};
+use uuid::Uuid;
+
+pub const IDEMPOTENCY_KEY_TTL_HOURS: i64 = 24;
+const PRUNE_INTERVAL_MINUTES: i64 = 60;
+
+/// The `idempotency_keys` table of one database.
+pub struct IdempotencyKeys {
+    pool: Pool<Sqlite>,
+}
+
+impl IdempotencyKeys {
+    pub fn new(pool: Pool<Sqlite>) -> Self {
+        Self { pool }
+    }
+
+    pub async fn initialize(pool: &Pool<Sqlite>) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS idempotency_keys (
+                scope TEXT NOT NULL,
+                request_id TEXT NOT NULL,
+                result TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                PRIMARY KEY (scope, request_id)
+            )
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS idx_idempotency_keys_created ON idempotency_keys(created_at)",
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get<T: DeserializeOwned>(
+        &self,
+        scope: &str,
+        request_id: &str,
+    ) -> Result<Option<T>, String> {
+        let row =
+            sqlx::query("SELECT result FROM idempotency_keys WHERE scope = ?1 AND request_id = ?2")
+                .bind(scope)
+                .bind(request_id)
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(|e| format!("Failed to look up request key: {e}"))?;
+
+        row.map(|row| {
+            let result: String = row.get("result");
+            serde_json::from_str(&result)
+                .map_err(|e| format!("Failed to decode stored result for {request_id}: {e}"))
+        })
+        .transpose()
+    }
+
+    /// Returns `false` when the key was already recorded.
+    pub async fn record<T: Serialize>(
+        &self,
+        scope: &str,
+        request_id: &str,
+        result: &T,
+    ) -> Result<bool, String> {
+        let result = serde_json::to_string(result).map_err(|e| e.to_string())?;
+        let inserted = sqlx::query(
+            r#"
+            INSERT OR IGNORE INTO idempotency_keys (scope, request_id, result, created_at)
+            VALUES (?1, ?2, ?3, ?4)
+            "#,
+        )
+        .bind(scope)
+        .bind(request_id)
+        .bind(result)
+        .bind(Utc::now().to_rfc3339())
+        .execute(&self.pool)
+        .await
+        .map_err(|e| format!("Failed to record request key: {e}"))?;
+
+        Ok(inserted.rows_affected() > 0)
+    }
+
+    pub async fn prune_expired(&self) -> Result<u64, String> {
+        let cutoff = Utc::now() - Duration::hours(IDEMPOTENCY_KEY_TTL_HOURS);
+        let pruned = sqlx::query("DELETE FROM idempotency_keys WHERE created_at < ?1")
+            .bind(cutoff.to_rfc3339())
+            .execute(&self.pool)
+            .await
+            .map_err(|e| format!("Failed to prune request keys: {e}"))?;
+
+        Ok(pruned.rows_affected())
+    }
+}
+
+/// Runs create requests at most once per client request id. One guard per
+/// kind of request; `scope` keeps the kinds apart in a shared table.
+pub struct IdempotencyGuard<T> {
+    scope: &'static str,
+    in_flight: Mutex<HashMap<String, Arc<OnceCell<T>>>>,
+    last_pruned: Mutex<Option<DateTime<Utc>>>,
+}
+
+impl<T: Clone + Serialize + DeserializeOwned> IdempotencyGuard<T> {
+    pub fn new(scope: &'static str) -> Self {
+        Self {
+            scope,
+            in_flight: Mutex::new(HashMap::new()),
+            last_pruned: Mutex::new(None),
+        }
+    }
+
+    /// Runs `create` unless `request_id` already has a result, in which case
+    /// that result is returned. Requests without an id always run. A failed
+    /// attempt records nothing, so it can be retried with the same id.
+    pub async fn run<F, Fut, E>(
+        &self,
+        keys: &IdempotencyKeys,
+        request_id: Option<&str>,
+        create: F,
+    ) -> Result<T, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        E: From<String> + From<AppError>,
+    {
+        let Some(request_id) = request_id else {
+            return create().await;
+        };
+        Uuid::parse_str(request_id).map_err(|_| {
+            AppError::validation(format!(
+                "client_request_id must be a UUID, got {request_id}"
+            ))
+            .with_code("invalid_request_id")
+        })?;
+        self.prune_if_due(keys).await;
+
+        let cell = self
+            .in_flight
+            .lock()
+            .await
+            .entry(request_id.to_string())
+            .or_default()
+            .clone();
+
+        let result: Result<T, E> = cell
+            .get_or_try_init(|| async {
+                if let Some(existing) = keys.get(self.scope, request_id).await? {
+                    return Ok(existing);
+                }
+                let created = create().await?;
+                if let Err(e) = keys.record(self.scope, request_id, &created).await {
+                    eprintln!("Request {request_id} succeeded but was not recorded: {e}");
+                }
+                Ok(created)
+            })
+            .await
+            .cloned();
+
+        // Once the result is stored, later retries are answered from the
+        // database. A failed entry is dropped when nobody else is waiting on it.
+        let mut in_flight = self.in_flight.lock().await;
+        let finished = in_flight.get(request_id).is_some_and(|current| {
+            Arc::ptr_eq(current, &cell) && (cell.initialized() || Arc::strong_count(&cell) == 2)
+        });
+        if finished {
+            in_flight.remove(request_id);
+        }
+
+        result
+    }
+
+    async fn prune_if_due(&self, keys: &IdempotencyKeys) {
+        let now = Utc::now();
+        {
+            let mut last_pruned = self.last_pruned.lock().await;
+            if last_pruned.is_some_and(|at| now - at < Duration::minutes(PRUNE_INTERVAL_MINUTES)) {
+                return;
+            }
+            *last_pruned = Some(now);
+        }
+        if let Err(e) = keys.prune_expired().await {
+            eprintln!("{e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trading::database::OrderDatabase;
+    use crate::trading::types::{Order, OrderSide, OrderStatus, OrderType};
+    use std::env;
+
+    fn order(wallet: &str) -> Order {
+        Order {
+            id: Uuid::new_v4().to_string(),
+            order_type: OrderType::Limit,
+            side: OrderSide::Buy,
+            status: OrderStatus::Pending,
+            input_mint: "So11111111111111111111111111111111111111112".to_string(),
+            output_mint: "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v".to_string(),
+            input_symbol: "SOL".to_string(),
+            output_symbol: "USDC".to_string(),
+            amount: 1.0,
+            filled_amount: 0.0,
+            fill_price: None,
+            limit_price: Some(50.0),
+            stop_price: None,
+            trailing_percent: None,
+            highest_price: None,
+            lowest_price: None,
+            linked_order_id: None,
+            min_book_depth_usd: None,
+            slippage_bps: 50,
+            priority_fee_micro_lamports: 5000,
+            wallet_address: wallet.to_string(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            triggered_at: None,
+            tx_signature: None,
+            error_message: None,
+            correlation_id: None,
+            origin: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn concurrent_retries_create_one_order() {
+        let mut path = env::temp_dir();
+        path.push(format!("idempotency_test_{}.db", Uuid::new_v4()));
+        let db = Arc::new(OrderDatabase::new(path).await.expect("order database"));
+        let guard = Arc::new(IdempotencyGuard::<String>::new("create_order"));
+        let request_id = Uuid::new_v4().to_string();
+
+        let attempts = (0..10).map(|_| {
+            let (db, guard, request_id) = (db.clone(), guard.clone(), request_id.clone());
+            tokio::spawn(async move {
+                let keys = db.idempotency_keys();
+                guard
+                    .run(&keys, Some(&request_id), || async {
+                        let order = order("wallet");
+                        db.create_order(&order).await.map_err(|e| e.to_string())?;
+                        Ok::<_, String>(order.id)
+                    })
+                    .await
+            })
+        });
+        let ids: Vec<String> = futures_util::future::join_all(attempts)
+            .await
+            .into_iter()
+            .map(|joined| joined.unwrap().unwrap())
+            .collect();
+
+        assert!(ids.iter().all(|id| id == &ids[0]));
+        assert_eq!(db.get_active_orders("wallet").await.unwrap().len(), 1);
+
+        // A later retry is answered from the stored key.
+        let keys = db.idempotency_keys();
+        let retried = guard
+            .run(&keys, Some(&request_id), || async {
+                Err::<String, _>("should not run".to_string())
+            })
+            .await;
+        assert_eq!(retried.unwrap(), ids[0]);
+    }
+
+    #[tokio::test]
+    async fn rejects_non_uuid_keys() {
+        let mut path = env::temp_dir();
+        path.push(format!("idempotency_test_{}.db", Uuid::new_v4()));
+        let db = OrderDatabase::new(path).await.expect("order database");
+        let guard = IdempotencyGuard::<String>::new("create_order");
+
+        let result = guard
+            .run(&db.idempotency_keys(), Some("retry-1"), || async {
+                Ok::<_, String>("created".to_string())
+            })
+            .await;
+        assert!(result.is_err());
+    }
+}
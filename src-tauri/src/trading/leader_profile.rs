@@ -0,0 +1,377 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// How far back a leader's observed trades feed the profile.
+pub const PROFILE_LOOKBACK_DAYS: i64 = 90;
+
+/// One observed trade of a followed wallet.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LeaderTrade {
+    pub wallet: String,
+    pub tx_signature: String,
+    pub traded_at: DateTime<Utc>,
+    /// Amount sent, in units of the token the leader paid with.
+    pub amount: f64,
+    /// The token bought, or the token sold for a swap into a quote mint.
+    pub symbol: String,
+    pub category: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BehaviorThresholds {
+    /// Trades newer than this form the recent window; older ones the baseline.
+    pub recent_window_days: i64,
+    /// The first observed trade must be at least this old before the
+    /// frequency, size and category detectors compare anything.
+    pub min_history_days: i64,
+    pub min_baseline_trades: usize,
+    pub min_recent_trades: usize,
+    /// Recent trades per day over baseline trades per day.
+    pub frequency_ratio: f64,
+    /// Relative change in average trade size, either direction.
+    pub size_change: f64,
+    pub new_category_min_trades: usize,
+    pub dormant_days: i64,
+}
+
+impl Default for BehaviorThresholds {
+    fn default() -> Self {
+        Self {
+            recent_window_days: 7,
+            min_history_days: 14,
+            min_baseline_trades: 10,
+            min_recent_trades: 5,
+            frequency_ratio: 2.0,
+            size_change: 0.5,
+            new_category_min_trades: 2,
+            dormant_days: 7,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BehaviorChangeKind {
+    FrequencySpike,
+    PositionSizeShift,
+    NewCategory,
+    Dormant,
+}
+
+impl BehaviorChangeKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BehaviorChangeKind::FrequencySpike => "frequency_spike",
+            BehaviorChangeKind::PositionSizeShift => "position_size_shift",
+            BehaviorChangeKind::NewCategory => "new_category",
+            BehaviorChangeKind::Dormant => "dormant",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BehaviorChange {
+    pub kind: BehaviorChangeKind,
+    /// Set for `NewCategory`.
+    pub category: Option<String>,
+    pub baseline_value: f64,
+    pub recent_value: f64,
+    pub summary: String,
+}
+
+impl BehaviorChange {
+    /// Identifies the change for notification de-duplication; each new
+    /// category is its own change.
+    pub fn key(&self) -> String {
+        match &self.category {
+            Some(category) => format!("{}:{}", self.kind.as_str(), category),
+            None => self.kind.as_str().to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowStats {
+    pub trades: usize,
+    pub days: f64,
+    pub trades_per_day: f64,
+    pub avg_trade_size: f64,
+    pub categories: BTreeMap<String, usize>,
+}
+
+impl WindowStats {
+    fn from_trades<'a>(trades: impl Iterator<Item = &'a LeaderTrade>, days: f64) -> Self {
+        let mut stats = WindowStats {
+            days,
+            ..Default::default()
+        };
+        let mut volume = 0.0;
+        for trade in trades {
+            stats.trades += 1;
+            volume += trade.amount;
+            *stats.categories.entry(trade.category.clone()).or_default() += 1;
+        }
+        if stats.trades > 0 {
+            stats.avg_trade_size = volume / stats.trades as f64;
+        }
+        if days > 0.0 {
+            stats.trades_per_day = stats.trades as f64 / days;
+        }
+        stats
+    }
+}
+
+/// Rolling statistics over a followed wallet's observed trades, split into a
+/// recent window and the baseline before it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LeaderProfile {
+    pub wallet: String,
+    pub observed_trades: usize,
+    pub first_trade_at: Option<DateTime<Utc>>,
+    pub last_trade_at: Option<DateTime<Utc>>,
+    pub history_days: f64,
+    pub baseline: WindowStats,
+    pub recent: WindowStats,
+    pub changes: Vec<BehaviorChange>,
+    pub behavior_changed: bool,
+    pub generated_at: DateTime<Utc>,
+}
+
+impl LeaderProfile {
+    pub fn build(
+        wallet: &str,
+        trades: &[LeaderTrade],
+        now: DateTime<Utc>,
+        thresholds: &BehaviorThresholds,
+    ) -> Self {
+        let recent_start = now - Duration::days(thresholds.recent_window_days);
+        let first_trade_at = trades.iter().map(|trade| trade.traded_at).min();
+        let last_trade_at = trades.iter().map(|trade| trade.traded_at).max();
+        let history_days = first_trade_at
+            .map(|first| days_between(first, now))
+            .unwrap_or(0.0);
+        let baseline_days = first_trade_at
+            .map(|first| days_between(first, recent_start))
+            .unwrap_or(0.0);
+
+        let baseline = WindowStats::from_trades(
+            trades.iter().filter(|trade| trade.traded_at < recent_start),
+            baseline_days,
+        );
+        let recent = WindowStats::from_trades(
+            trades
+                .iter()
+                .filter(|trade| trade.traded_at >= recent_start),
+            thresholds.recent_window_days as f64,
+        );
+
+        let mut profile = Self {
+            wallet: wallet.to_string(),
+            observed_trades: trades.len(),
+            first_trade_at,
+            last_trade_at,
+            history_days,
+            baseline,
+            recent,
+            changes: Vec::new(),
+            behavior_changed: false,
+            generated_at: now,
+        };
+        profile.changes = profile.detect_changes(now, thresholds);
+        profile.behavior_changed = !profile.changes.is_empty();
+        profile
+    }
+
+    fn has_baseline(&self, thresholds: &BehaviorThresholds) -> bool {
+        self.history_days >= thresholds.min_history_days as f64
+            && self.baseline.trades >= thresholds.min_baseline_trades
+    }
+
+    fn detect_changes(
+        &self,
+        now: DateTime<Utc>,
+        thresholds: &BehaviorThresholds,
+    ) -> Vec<BehaviorChange> {
+        let mut changes = Vec::new();
+        let window = thresholds.recent_window_days;
+
+        if self.has_baseline(thresholds) && self.recent.trades >= thresholds.min_recent_trades {
+            let (baseline, recent) = (self.baseline.trades_per_day, self.recent.trades_per_day);
+            if baseline > 0.0 && recent >= baseline * thresholds.frequency_ratio {
+                changes.push(BehaviorChange {
+                    kind: BehaviorChangeKind::FrequencySpike,
+                    category: None,
+                    baseline_value: baseline,
+                    recent_value: recent,
+                    summary: format!(
+                        "Trading {:.1} times a day over the last {} days, up from {:.1} before ({:.1}x)",
+                        recent,
+                        window,
+                        baseline,
+                        recent / baseline
+                    ),
+                });
+            }
+
+            let (baseline, recent) = (self.baseline.avg_trade_size, self.recent.avg_trade_size);
+            if baseline > 0.0 && (recent / baseline - 1.0).abs() >= thresholds.size_change {
+                changes.push(BehaviorChange {
+                    kind: BehaviorChangeKind::PositionSizeShift,
+                    category: None,
+                    baseline_value: baseline,
+                    recent_value: recent,
+                    summary: format!(
+                        "Average trade size {:.2} over the last {} days vs {:.2} before ({:+.0}%)",
+                        recent,
+                        window,
+                        baseline,
+                        (recent / baseline - 1.0) * 100.0
+                    ),
+                });
+            }
+        }
+
+        if self.has_baseline(thresholds) {
+            for (category, &count) in &self.recent.categories {
+                if count < thresholds.new_category_min_trades
+                    || self.baseline.categories.contains_key(category)
+                {
+                    continue;
+                }
+                changes.push(BehaviorChange {
+                    kind: BehaviorChangeKind::NewCategory,
+                    category: Some(category.clone()),
+                    baseline_value: 0.0,
+                    recent_value: count as f64,
+                    summary: format!(
+                        "{} trades in {} tokens over the last {} days, none in the {} trades of the {:.0} days before",
+                        count, category, window, self.baseline.trades, self.baseline.days
+                    ),
+                });
+            }
+        }
+
+        // Dormancy needs no baseline split, only proof the wallet was active.
+        if let Some(last) = self.last_trade_at {
+            let idle_days = days_between(last, now);
+            if self.observed_trades >= thresholds.min_baseline_trades
+                && idle_days >= thresholds.dormant_days as f64
+            {
+                changes.push(BehaviorChange {
+                    kind: BehaviorChangeKind::Dormant,
+                    category: None,
+                    baseline_value: self.observed_trades as f64 / self.history_days.max(1.0),
+                    recent_value: 0.0,
+                    summary: format!(
+                        "No trades for {:.0} days; last trade {} after {} observed trades",
+                        idle_days.floor(),
+                        last.format("%Y-%m-%d %H:%M UTC"),
+                        self.observed_trades
+                    ),
+                });
+            }
+        }
+
+        changes
+    }
+}
+
+fn days_between(from: DateTime<Utc>, to: DateTime<Utc>) -> f64 {
+    ((to - from).num_seconds() as f64 / 86_400.0).max(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(now: DateTime<Utc>, days_ago: f64, amount: f64, category: &str) -> LeaderTrade {
+        LeaderTrade {
+            wallet: "leader".into(),
+            tx_signature: format!("sig-{days_ago}-{category}"),
+            traded_at: now - Duration::seconds((days_ago * 86_400.0) as i64),
+            amount,
+            symbol: "TKN".into(),
+            category: category.into(),
+        }
+    }
+
+    /// One DeFi trade a day for 30 days, ending 8 days ago.
+    fn steady_history(now: DateTime<Utc>) -> Vec<LeaderTrade> {
+        (8..38)
+            .map(|day| trade(now, day as f64, 100.0, "DeFi"))
+            .collect()
+    }
+
+    fn kinds(profile: &LeaderProfile) -> Vec<BehaviorChangeKind> {
+        profile.changes.iter().map(|change| change.kind).collect()
+    }
+
+    #[test]
+    fn steady_wallet_reports_no_change() {
+        let now = Utc::now();
+        let mut trades = steady_history(now);
+        trades.extend((0..7).map(|day| trade(now, day as f64 + 0.5, 110.0, "DeFi")));
+
+        let profile = LeaderProfile::build("leader", &trades, now, &BehaviorThresholds::default());
+        assert!(!profile.behavior_changed, "{:?}", profile.changes);
+        assert_eq!(profile.baseline.trades, 30);
+        assert_eq!(profile.recent.trades, 7);
+    }
+
+    #[test]
+    fn detects_frequency_size_and_category_shifts() {
+        let now = Utc::now();
+        let mut trades = steady_history(now);
+        trades.extend((0..21).map(|i| trade(now, i as f64 / 3.0, 300.0, "Meme")));
+
+        let profile = LeaderProfile::build("leader", &trades, now, &BehaviorThresholds::default());
+        assert_eq!(
+            kinds(&profile),
+            vec![
+                BehaviorChangeKind::FrequencySpike,
+                BehaviorChangeKind::PositionSizeShift,
+                BehaviorChangeKind::NewCategory,
+            ]
+        );
+        let frequency = &profile.changes[0];
+        assert!((frequency.recent_value - 3.0).abs() < 1e-9);
+        assert!(frequency.summary.contains("3.0 times a day"));
+        assert_eq!(profile.changes[2].key(), "new_category:Meme");
+    }
+
+    #[test]
+    fn new_followers_need_history_before_changes() {
+        let now = Utc::now();
+        // Busy first day of observation: lots of trades, no baseline yet.
+        let trades: Vec<_> = (0..20)
+            .map(|i| trade(now, i as f64 / 24.0, 500.0, "Meme"))
+            .collect();
+
+        let profile = LeaderProfile::build("leader", &trades, now, &BehaviorThresholds::default());
+        assert!(!profile.behavior_changed, "{:?}", profile.changes);
+    }
+
+    #[test]
+    fn flags_dormant_wallets() {
+        let now = Utc::now();
+        let profile = LeaderProfile::build(
+            "leader",
+            &steady_history(now),
+            now,
+            &BehaviorThresholds::default(),
+        );
+        assert_eq!(kinds(&profile), vec![BehaviorChangeKind::Dormant]);
+        assert!(profile.changes[0]
+            .summary
+            .starts_with("No trades for 8 days"));
+
+        let few: Vec<_> = steady_history(now).into_iter().take(3).collect();
+        let profile = LeaderProfile::build("leader", &few, now, &BehaviorThresholds::default());
+        assert!(!profile.behavior_changed);
+    }
+}
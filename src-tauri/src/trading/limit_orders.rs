@@ -1,7 +1,10 @@
+use crate::config::DataPaths;
+use crate::errors::AppError;
 use crate::trading::database::{OrderDatabase, SharedOrderDatabase};
 use crate::trading::order_manager::{OrderManager, SharedOrderManager};
 use crate::trading::types::{CreateOrderRequest, Order, OrderStatus};
-use std::path::PathBuf;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tauri::{AppHandle, Emitter, Manager};
 use tokio::sync::OnceCell;
@@ -19,8 +22,7 @@ pub async fn init_trading(app_handle: &AppHandle) -> Result<(), String> {
     }
 
     let app_dir = app_handle
-        .path()
-        .app_data_dir()
+        .app_data_root()
         .map_err(|e| format!("Failed to resolve app data directory: {}", e))?;
 
     std::fs::create_dir_all(&app_dir)
@@ -43,9 +45,22 @@ pub async fn init_trading(app_handle: &AppHandle) -> Result<(), String> {
         })
         .map_err(|_| "Trading state already initialized".to_string())?;
 
+    let checkpoint_db = shared_db.clone();
+    crate::errors::register_flush(app_handle, "orders_db", move || {
+        let db = checkpoint_db.clone();
+        async move {
+            db.read()
+                .await
+                .checkpoint()
+                .await
+                .map_err(|e| e.to_string())
+        }
+    });
+
     let handle = app_handle.clone();
+    let shutdown = crate::errors::shutdown_listener(app_handle, "order_monitor");
     tauri::async_runtime::spawn(async move {
-        OrderManager::start_monitoring(manager).await;
+        OrderManager::start_monitoring(manager, shutdown).await;
         let _ = handle.emit("order_monitoring_stopped", "Order monitoring exited");
     });
 
@@ -54,31 +69,72 @@ pub async fn init_trading(app_handle: &AppHandle) -> Result<(), String> {
     Ok(())
 }
 
-pub fn require_state<'a>() -> Result<&'a TradingState, String> {
-    TRADING_STATE
-        .get()
-        .ok_or_else(|| "Trading module not initialized".to_string())
+/// Mints on either side of the wallet's pending and partially filled orders.
+/// Until the trading module is initialized they are read from the orders
+/// database in `data_dir`.
+pub async fn active_order_mints(
+    data_dir: &Path,
+    wallet_address: &str,
+) -> Result<HashSet<String>, String> {
+    let orders = match TRADING_STATE.get() {
+        Some(state) => state.manager.get_active_orders(wallet_address).await?,
+        None => OrderDatabase::new(data_dir.join("orders.db"))
+            .await
+            .map_err(|e| format!("Failed to open order database: {}", e))?
+            .get_active_orders(wallet_address)
+            .await
+            .map_err(|e| e.to_string())?,
+    };
+    Ok(orders
+        .into_iter()
+        .flat_map(|order| [order.input_mint, order.output_mint])
+        .collect())
+}
+
+/// Filled orders for `mint` between two RFC 3339 times. Empty until the
+/// trading module is initialized.
+pub async fn filled_orders_for_mint(
+    mint: &str,
+    from: &str,
+    to: &str,
+) -> Result<Vec<Order>, String> {
+    let Some(state) = TRADING_STATE.get() else {
+        return Ok(Vec::new());
+    };
+    state
+        .db
+        .read()
+        .await
+        .get_fills_for_mint(mint, from, to)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+pub fn require_state<'a>() -> Result<&'a TradingState, AppError> {
+    TRADING_STATE.get().ok_or_else(|| {
+        AppError::internal("Trading module not initialized").with_code("trading_not_initialized")
+    })
 }
 
 #[tauri::command]
-pub async fn trading_init(handle: AppHandle) -> Result<(), String> {
-    init_trading(&handle).await
+pub async fn trading_init(handle: AppHandle) -> Result<(), AppError> {
+    init_trading(&handle).await.map_err(AppError::internal)
 }
 
 #[tauri::command]
-pub async fn create_order(request: CreateOrderRequest) -> Result<Order, String> {
+pub async fn create_order(request: CreateOrderRequest) -> Result<Order, AppError> {
     let state = require_state()?;
     state.manager.create_order(request).await
 }
 
 #[tauri::command]
-pub async fn cancel_order(order_id: String) -> Result<(), String> {
+pub async fn cancel_order(order_id: String) -> Result<(), AppError> {
     let state = require_state()?;
     state.manager.cancel_order(&order_id).await
 }
 
 #[tauri::command]
-pub async fn get_active_orders(wallet_address: String) -> Result<Vec<Order>, String> {
+pub async fn get_active_orders(wallet_address: String) -> Result<Vec<Order>, AppError> {
     let state = require_state()?;
     state.manager.get_active_orders(&wallet_address).await
 }
@@ -87,7 +143,7 @@ pub async fn get_active_orders(wallet_address: String) -> Result<Vec<Order>, Str
 pub async fn get_order_history(
     wallet_address: String,
     limit: Option<i64>,
-) -> Result<Vec<Order>, String> {
+) -> Result<Vec<Order>, AppError> {
     let state = require_state()?;
     state
         .manager
@@ -96,21 +152,20 @@ pub async fn get_order_history(
 }
 
 #[tauri::command]
-pub async fn get_order(order_id: String) -> Result<Order, String> {
+pub async fn get_order(order_id: String) -> Result<Order, AppError> {
     let state = require_state()?;
     state.manager.get_order(&order_id).await
 }
 
 #[tauri::command]
-pub async fn acknowledge_order(order_id: String) -> Result<(), String> {
+pub async fn acknowledge_order(order_id: String) -> Result<(), AppError> {
     let state = require_state()?;
-    state
+    Ok(state
         .db
         .write()
         .await
         .update_order_status(&order_id, OrderStatus::Pending, None)
-        .await
-        .map_err(|e| format!("Failed to acknowledge order: {}", e))
+        .await?)
 }
 
 pub fn register_trading_state(app: &AppHandle) {
@@ -121,3 +176,59 @@ pub fn register_trading_state(app: &AppHandle) {
         }
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trading::types::{OrderSide, OrderType};
+    use chrono::Utc;
+
+    #[tokio::test]
+    async fn active_order_mints_reads_the_database_before_init() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = OrderDatabase::new(dir.path().join("orders.db"))
+            .await
+            .unwrap();
+        let now = Utc::now();
+        db.create_order(&Order {
+            id: "o1".to_string(),
+            order_type: OrderType::Limit,
+            side: OrderSide::Buy,
+            status: OrderStatus::Pending,
+            input_mint: "So11111111111111111111111111111111111111112".to_string(),
+            output_mint: "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v".to_string(),
+            input_symbol: "SOL".to_string(),
+            output_symbol: "USDC".to_string(),
+            amount: 1.0,
+            filled_amount: 0.0,
+            fill_price: None,
+            limit_price: Some(50.0),
+            stop_price: None,
+            trailing_percent: None,
+            highest_price: None,
+            lowest_price: None,
+            linked_order_id: None,
+            min_book_depth_usd: None,
+            slippage_bps: 50,
+            priority_fee_micro_lamports: 5000,
+            wallet_address: "wallet".to_string(),
+            created_at: now,
+            updated_at: now,
+            triggered_at: None,
+            tx_signature: None,
+            error_message: None,
+            correlation_id: None,
+            origin: None,
+        })
+        .await
+        .unwrap();
+
+        assert!(TRADING_STATE.get().is_none());
+        let mints = active_order_mints(dir.path(), "wallet").await.unwrap();
+        assert!(mints.contains("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v"));
+        assert!(active_order_mints(dir.path(), "other")
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -1,29 +1,64 @@
+pub mod auto_bracket;
 pub mod auto_trading;
+pub mod backtest_store;
 pub mod backtesting;
 pub mod copy_trading;
 pub mod database;
+pub mod events;
+pub mod execution_audit;
+pub mod idempotency;
+pub mod leader_profile;
 pub mod limit_orders;
 pub mod optimizer;
 pub mod order_manager;
+pub mod order_protection;
 pub mod paper_trading;
 pub mod price_listener;
 pub mod safety;
 pub mod safety_commands;
 pub mod types;
 
+pub use auto_bracket::{
+    get_auto_bracket_policy, run_auto_brackets, set_auto_bracket_policy, AutoBracketError,
+    AutoBrackets, BracketOffset, BracketOutcome, BracketPolicy, BracketPrices, BracketSources,
+    ExistingBrackets, SharedAutoBrackets, TokenBracketOverride, AUTO_BRACKET_EVENT,
+};
 pub use auto_trading::*;
+pub use backtest_store::{
+    backtest_compare_runs, backtest_delete_runs, backtest_get_retention, backtest_get_run,
+    backtest_list_runs, backtest_set_retention, backtest_update_run, AlignedEquityCurves,
+    BacktestComparison, BacktestDeleteSelector, BacktestRetention, BacktestRunFilter,
+    BacktestRunRecord, BacktestRunSummary, BacktestStore, EquitySeries, MetricComparison,
+    MetricPreference, SharedBacktestStore, BACKTEST_DB_FILE,
+};
 pub use backtesting::*;
 pub use copy_trading::*;
 pub use database::{OrderDatabase, SharedOrderDatabase};
+pub use events::{
+    order_events, publish_order_event, OrderEvent, OrderEventKind, OrderEventSource, OrderRoute,
+    ORDER_EVENT,
+};
+pub use execution_audit::{
+    get_execution_audit, record_execution_stage, ExecutionAudit, ExecutionAuditEntry,
+    ExecutionAuditLog, ExecutionStage,
+};
+pub use idempotency::{IdempotencyGuard, IdempotencyKeys, IDEMPOTENCY_KEY_TTL_HOURS};
+pub use leader_profile::{BehaviorChange, BehaviorChangeKind, LeaderProfile};
 pub use limit_orders::*;
 pub use optimizer::*;
 pub use order_manager::{OrderManager, SharedOrderManager};
+pub use order_protection::{
+    get_order_protection_policy, run_order_protection, set_order_protection_policy, ConvertedStop,
+    FeedHealth, OrderProtection, OrderProtectionError, ProtectionMode, ProtectionPolicy,
+    ProtectiveTriggerOrder, SharedOrderProtection, TokenProtectionStatus, ORDER_PROTECTION_EVENT,
+    PROTECTIVE_TRIGGER_ORDER_EVENT,
+};
 pub use paper_trading::*;
 pub use price_listener::{start_price_listener, update_order_prices, PriceUpdate};
 pub use safety::{
     ImpactPreview, InsuranceProvider, InsuranceQuote, InsuranceSelection, MevRiskLevel,
-    PolicyCheckResult, PolicyViolation, SafetyCheckRequest, SafetyCheckResult, SafetyEngine,
-    SafetyPolicy, SharedSafetyEngine, ViolationSeverity,
+    PolicyCheckResult, PolicyViolation, SafetyCheckError, SafetyCheckRequest, SafetyCheckResult,
+    SafetyEngine, SafetyPolicy, SharedSafetyEngine, TradeOrigin, ViolationSeverity,
 };
 pub use safety_commands::*;
 pub use types::*;
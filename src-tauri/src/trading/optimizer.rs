@@ -1,4 +1,4 @@
-use super::backtesting::{backtest_run, BacktestConfig, BacktestMetrics, BacktestResult};
+use super::backtesting::{simulate_backtest, BacktestConfig, BacktestMetrics, BacktestResult};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
@@ -144,7 +144,7 @@ async fn evaluate_candidate(
         backtest_config.slippage_rate = *slippage;
     }
 
-    let result = backtest_run(backtest_config).await?;
+    let result = simulate_backtest(backtest_config).await?;
     let score = score_metrics(
         &result.metrics,
         &config.optimization_target,
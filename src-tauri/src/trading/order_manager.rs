@@ -1,18 +1,28 @@
+use crate::core::require_online;
 use crate::data::event_store::{Event as AuditEvent, SharedEventStore};
+use crate::errors::{AppError, ShutdownListener};
+use crate::market::orderbook::{BookSide, OrderBookResult, SharedOrderBookService};
 use crate::trading::database::{OrderDatabase, SharedOrderDatabase};
+use crate::trading::events::{publish_order_event, OrderEvent, OrderEventKind};
+use crate::trading::execution_audit::ExecutionStage;
+use crate::trading::idempotency::IdempotencyGuard;
 use crate::trading::types::{
     CreateOrderRequest, Order, OrderFill, OrderSide, OrderStatus, OrderType, OrderUpdate,
     QuickTradeRequest,
 };
+use crate::wallet::multi_wallet::MultiWalletManager;
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
-use tauri::{AppHandle, Emitter, Manager};
+use tauri::{AppHandle, Manager, Runtime, Wry};
 use tokio::sync::RwLock;
-use tokio::time::{interval, Duration};
+use tokio::time::{interval, Duration, Instant};
 use uuid::Uuid;
 
+/// Band around the mid price that an order's depth requirement is checked in.
+const DEPTH_GUARD_BAND_PCT: f64 = 1.0;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PriceUpdate {
     pub symbol: String,
@@ -20,38 +30,88 @@ pub struct PriceUpdate {
     pub timestamp: i64,
 }
 
-#[derive(Debug, Clone, Serialize)]
-pub struct OrderTriggeredEvent {
-    pub order_id: String,
-    pub order_type: OrderType,
-    pub symbol: String,
-    pub side: OrderSide,
-    pub trigger_price: f64,
-    pub amount: f64,
-}
-
-pub struct OrderManager {
+pub struct OrderManager<R: Runtime = Wry> {
     db: SharedOrderDatabase,
-    app_handle: AppHandle,
     current_prices: Arc<RwLock<HashMap<String, f64>>>,
     event_store: Option<SharedEventStore>,
+    orderbook: Option<SharedOrderBookService>,
+    create_guard: IdempotencyGuard<String>,
+    app_handle: AppHandle<R>,
+}
+
+/// The token whose book an order trades against, and the side it takes from.
+fn book_side(order_side: OrderSide, input_mint: &str, output_mint: &str) -> (String, BookSide) {
+    match order_side {
+        OrderSide::Buy => (output_mint.to_string(), BookSide::Asks),
+        OrderSide::Sell => (input_mint.to_string(), BookSide::Bids),
+    }
 }
 
-impl OrderManager {
-    pub fn new(db: SharedOrderDatabase, app_handle: AppHandle) -> Self {
+/// The mint and symbol whose price an order triggers on.
+pub(crate) fn priced_token(order: &Order) -> (&str, &str) {
+    match order.side {
+        OrderSide::Buy => (&order.output_mint, &order.output_symbol),
+        OrderSide::Sell => (&order.input_mint, &order.input_symbol),
+    }
+}
+
+/// Prices streamed by mint take precedence over the ones the frontend
+/// pushes by symbol.
+fn order_price(prices: &HashMap<String, f64>, order: &Order) -> Option<f64> {
+    let (mint, symbol) = priced_token(order);
+    prices.get(mint).or_else(|| prices.get(symbol)).copied()
+}
+
+impl<R: Runtime> OrderManager<R> {
+    pub fn new(db: SharedOrderDatabase, app_handle: AppHandle<R>) -> Self {
         let event_store = app_handle
             .try_state::<SharedEventStore>()
             .map(|state| state.inner().clone());
+        let orderbook = app_handle
+            .try_state::<SharedOrderBookService>()
+            .map(|state| state.inner().clone());
 
         Self {
             db,
-            app_handle,
             current_prices: Arc::new(RwLock::new(HashMap::new())),
             event_store,
+            orderbook,
+            create_guard: IdempotencyGuard::new("create_order"),
+            app_handle,
         }
     }
 
-    pub async fn create_order(&self, request: CreateOrderRequest) -> Result<Order, String> {
+    /// Creates an order, or returns the order already created for the
+    /// request's `client_request_id`.
+    pub async fn create_order(&self, request: CreateOrderRequest) -> Result<Order, AppError> {
+        // Every order is eventually signed by its wallet, so watch-only
+        // wallets are refused before anything is stored.
+        if let Some(wallets) = self.app_handle.try_state::<MultiWalletManager>() {
+            wallets.ensure_can_sign(&request.wallet_address)?;
+        }
+        // Market orders execute at once. Queued until reconnecting, one would
+        // fill at whatever the price is by then, so it fails fast instead.
+        if request.order_type == OrderType::Market {
+            require_online(&self.app_handle)?;
+        }
+
+        let request_id = request.client_request_id.clone();
+        let keys = self.db.read().await.idempotency_keys();
+        let order_id = self
+            .create_guard
+            .run(&keys, request_id.as_deref(), || async {
+                self.insert_order(request).await.map(|order| order.id)
+            })
+            .await?;
+        self.get_order(&order_id).await
+    }
+
+    async fn insert_order(&self, request: CreateOrderRequest) -> Result<Order, AppError> {
+        if request.min_book_depth_usd.is_some() {
+            self.check_book_supported(&request).await?;
+        }
+
+        let request_payload = serde_json::to_value(&request).unwrap_or_default();
         let order = Order {
             id: Uuid::new_v4().to_string(),
             order_type: request.order_type,
@@ -63,12 +123,14 @@ impl OrderManager {
             output_symbol: request.output_symbol,
             amount: request.amount,
             filled_amount: 0.0,
+            fill_price: None,
             limit_price: request.limit_price,
             stop_price: request.stop_price,
             trailing_percent: request.trailing_percent,
             highest_price: None,
             lowest_price: None,
             linked_order_id: request.linked_order_id,
+            min_book_depth_usd: request.min_book_depth_usd,
             slippage_bps: request.slippage_bps,
             priority_fee_micro_lamports: request.priority_fee_micro_lamports,
             wallet_address: request.wallet_address,
@@ -77,14 +139,22 @@ impl OrderManager {
             triggered_at: None,
             tx_signature: None,
             error_message: None,
+            correlation_id: Some(
+                request
+                    .correlation_id
+                    .unwrap_or_else(|| Uuid::new_v4().to_string()),
+            ),
+            origin: request.origin,
         };
 
-        self.db
-            .write()
-            .await
-            .create_order(&order)
-            .await
-            .map_err(|e| format!("Failed to create order: {}", e))?;
+        self.db.write().await.create_order(&order).await?;
+        self.record_execution_stage(
+            &order,
+            ExecutionStage::Request {
+                request: request_payload,
+            },
+        )
+        .await;
 
         // Publish event to event store
         if let Some(ref event_store) = self.event_store {
@@ -111,27 +181,75 @@ impl OrderManager {
                 .await;
         }
 
-        self.emit_order_update(&order);
+        publish_order_event(OrderEvent::live(&order, OrderEventKind::Created));
+        if let (OrderType::TrailingStop | OrderType::StopLoss, Some(stop_price)) =
+            (order.order_type, order.stop_price)
+        {
+            publish_order_event(OrderEvent::live(
+                &order,
+                OrderEventKind::TriggerArmed { stop_price },
+            ));
+        }
 
         Ok(order)
     }
 
-    pub async fn cancel_order(&self, order_id: &str) -> Result<(), String> {
+    /// A depth requirement can only be met by a token with an order book.
+    async fn check_book_supported(&self, request: &CreateOrderRequest) -> Result<(), AppError> {
+        let orderbook = self
+            .orderbook
+            .as_ref()
+            .ok_or_else(|| AppError::internal("Order book data is unavailable"))?;
+        let (mint, _) = book_side(request.side, &request.input_mint, &request.output_mint);
+        match orderbook.snapshot(&mint).await.map_err(|e| {
+            AppError::upstream(
+                "orderbook",
+                format!("Failed to read order book: {}", e),
+                true,
+            )
+        })? {
+            OrderBookResult::Supported { .. } => Ok(()),
+            OrderBookResult::Unsupported { reason, .. } => Err(AppError::validation(format!(
+                "Cannot require order book depth: {}",
+                reason
+            ))
+            .with_code("order_book_unsupported")
+            .with_detail("mint", &mint)),
+        }
+    }
+
+    /// Whether the book shows the depth the order requires. A book that
+    /// can't be read holds the order until the next check.
+    async fn book_has_depth(&self, order: &Order) -> bool {
+        let Some(min_depth) = order.min_book_depth_usd else {
+            return true;
+        };
+        let Some(orderbook) = &self.orderbook else {
+            return false;
+        };
+        let (mint, side) = book_side(order.side, &order.input_mint, &order.output_mint);
+        match orderbook.depth_usd(&mint, side, DEPTH_GUARD_BAND_PCT).await {
+            Ok(depth) => depth.is_some_and(|depth| depth >= min_depth),
+            Err(e) => {
+                eprintln!("Failed to read order book for order {}: {}", order.id, e);
+                false
+            }
+        }
+    }
+
+    pub async fn cancel_order(&self, order_id: &str) -> Result<(), AppError> {
         let order = self.get_order(order_id).await?;
 
         if order.status != OrderStatus::Pending && order.status != OrderStatus::PartiallyFilled {
-            return Err("Order cannot be cancelled".to_string());
+            return Err(AppError::validation("Order cannot be cancelled")
+                .with_code("order_not_cancellable")
+                .with_detail("status", order.status));
         }
 
-        self.db
-            .write()
-            .await
-            .cancel_order(order_id)
-            .await
-            .map_err(|e| format!("Failed to cancel order: {}", e))?;
+        self.db.write().await.cancel_order(order_id).await?;
 
         if let Some(linked_id) = &order.linked_order_id {
-            let _ = self.db.write().await.cancel_linked_orders(linked_id).await;
+            self.cancel_linked_orders(linked_id).await;
         }
 
         // Publish event to event store
@@ -154,41 +272,49 @@ impl OrderManager {
         cancelled_order.status = OrderStatus::Cancelled;
         cancelled_order.updated_at = Utc::now();
 
-        self.emit_order_update(&cancelled_order);
+        publish_order_event(OrderEvent::live(
+            &cancelled_order,
+            OrderEventKind::Cancelled {
+                reason: Some("Manual cancellation".to_string()),
+            },
+        ));
 
         Ok(())
     }
 
-    pub async fn get_order(&self, order_id: &str) -> Result<Order, String> {
+    pub async fn get_order(&self, order_id: &str) -> Result<Order, AppError> {
         self.db
             .read()
             .await
             .get_order(order_id)
-            .await
-            .map_err(|e| format!("Failed to get order: {}", e))?
-            .ok_or_else(|| "Order not found".to_string())
+            .await?
+            .ok_or_else(|| {
+                AppError::not_found("Order not found")
+                    .with_code("order_not_found")
+                    .with_detail("orderId", order_id)
+            })
     }
 
-    pub async fn get_active_orders(&self, wallet_address: &str) -> Result<Vec<Order>, String> {
-        self.db
+    pub async fn get_active_orders(&self, wallet_address: &str) -> Result<Vec<Order>, AppError> {
+        Ok(self
+            .db
             .read()
             .await
             .get_active_orders(wallet_address)
-            .await
-            .map_err(|e| format!("Failed to get active orders: {}", e))
+            .await?)
     }
 
     pub async fn get_order_history(
         &self,
         wallet_address: &str,
         limit: i64,
-    ) -> Result<Vec<Order>, String> {
-        self.db
+    ) -> Result<Vec<Order>, AppError> {
+        Ok(self
+            .db
             .read()
             .await
             .get_order_history(wallet_address, limit)
-            .await
-            .map_err(|e| format!("Failed to get order history: {}", e))
+            .await?)
     }
 
     pub async fn update_price(&self, symbol: &str, price: f64) {
@@ -196,6 +322,11 @@ impl OrderManager {
         prices.insert(symbol.to_string(), price);
     }
 
+    /// Every pending or partially filled order across all wallets.
+    pub async fn get_all_active_orders(&self) -> Result<Vec<Order>, AppError> {
+        Ok(self.db.read().await.get_all_active_orders().await?)
+    }
+
     pub async fn check_and_trigger_orders(&self) -> Result<(), String> {
         let orders = self
             .db
@@ -208,29 +339,85 @@ impl OrderManager {
         let prices = self.current_prices.read().await.clone();
 
         for order in orders {
-            let symbol = if order.side == OrderSide::Buy {
-                &order.output_symbol
-            } else {
-                &order.input_symbol
+            let Some(current_price) = order_price(&prices, &order) else {
+                continue;
             };
-
-            if let Some(&current_price) = prices.get(symbol) {
-                if self.should_trigger_order(&order, current_price).await? {
-                    if let Err(e) = self.execute_order(&order, current_price).await {
-                        eprintln!("Failed to execute order {}: {}", order.id, e);
-                        let _ = self.db.write().await.update_order_status(
-                            &order.id,
-                            OrderStatus::Failed,
-                            Some(e),
-                        );
-                    }
-                }
+            if self.should_trigger_order(&order, current_price).await?
+                && self.book_has_depth(&order).await
+            {
+                self.trigger_order(&order, current_price).await;
             }
         }
 
         Ok(())
     }
 
+    /// Replays a price the monitor missed while the stream was down against
+    /// the orders on `mint`. Orders it would have triggered fill at the live
+    /// price when one is known, since the missed price can no longer be had.
+    /// Returns how many orders were triggered.
+    pub async fn reconcile_missed_price(&self, mint: &str, price: f64) -> Result<usize, String> {
+        let orders = self
+            .db
+            .read()
+            .await
+            .get_all_active_orders()
+            .await
+            .map_err(|e| format!("Failed to get active orders: {}", e))?;
+        let fill_price = self
+            .current_prices
+            .read()
+            .await
+            .get(mint)
+            .copied()
+            .unwrap_or(price);
+
+        let mut triggered = 0;
+        for order in orders {
+            if priced_token(&order).0 != mint {
+                continue;
+            }
+            if self.should_trigger_order(&order, price).await? {
+                self.trigger_order(&order, fill_price).await;
+                triggered += 1;
+            }
+        }
+
+        Ok(triggered)
+    }
+
+    /// Executes a triggered order, marking it failed if execution errors.
+    async fn trigger_order(&self, order: &Order, price: f64) {
+        let Err(e) = self.execute_order(order, price).await else {
+            return;
+        };
+        eprintln!("Failed to execute order {}: {}", order.id, e);
+        let _ = self
+            .db
+            .write()
+            .await
+            .update_order_status(&order.id, OrderStatus::Failed, Some(e.clone()))
+            .await;
+        self.record_execution_stage(
+            order,
+            ExecutionStage::Error {
+                source: "order_manager".to_string(),
+                message: e.clone(),
+                retrying: false,
+            },
+        )
+        .await;
+
+        let mut failed_order = order.clone();
+        failed_order.status = OrderStatus::Failed;
+        failed_order.error_message = Some(e.clone());
+        failed_order.updated_at = Utc::now();
+        publish_order_event(OrderEvent::live(
+            &failed_order,
+            OrderEventKind::Failed { error: e },
+        ));
+    }
+
     async fn should_trigger_order(
         &self,
         order: &Order,
@@ -309,14 +496,30 @@ impl OrderManager {
                 .update_trailing_stop(&order.id, new_highest, new_lowest, new_stop_price)
                 .await
                 .map_err(|e| format!("Failed to update trailing stop: {}", e))?;
+
+            if let Some(stop_price) = new_stop_price.filter(|_| new_stop_price != order.stop_price)
+            {
+                let mut armed_order = order.clone();
+                armed_order.highest_price = new_highest;
+                armed_order.lowest_price = new_lowest;
+                armed_order.stop_price = new_stop_price;
+                publish_order_event(OrderEvent::live(
+                    &armed_order,
+                    OrderEventKind::TriggerArmed { stop_price },
+                ));
+            }
         }
 
         Ok(should_trigger)
     }
 
     async fn execute_order(&self, order: &Order, trigger_price: f64) -> Result<(), String> {
-        self.emit_order_triggered(order, trigger_price);
+        publish_order_event(OrderEvent::live(
+            order,
+            OrderEventKind::Triggered { trigger_price },
+        ));
 
+        let submitted = Instant::now();
         let tx_signature = format!("simulated_{}", Uuid::new_v4());
 
         self.db
@@ -325,20 +528,40 @@ impl OrderManager {
             .update_order_fill(
                 &order.id,
                 order.amount,
+                Some(trigger_price),
                 OrderStatus::Filled,
                 Some(tx_signature.clone()),
             )
             .await
             .map_err(|e| format!("Failed to update order: {}", e))?;
 
+        self.record_execution_stage(
+            order,
+            ExecutionStage::Transaction {
+                signature: tx_signature.clone(),
+                slot: None,
+                latency_ms: Some(submitted.elapsed().as_millis() as u64),
+            },
+        )
+        .await;
+        self.record_execution_stage(
+            order,
+            ExecutionStage::Fill {
+                filled_amount: order.amount,
+                fill_price: Some(trigger_price),
+            },
+        )
+        .await;
+
         if let Some(linked_id) = &order.linked_order_id {
-            let _ = self.db.write().await.cancel_linked_orders(linked_id).await;
+            self.cancel_linked_orders(linked_id).await;
         }
 
         let mut filled_order = order.clone();
         filled_order.status = OrderStatus::Filled;
         filled_order.filled_amount = order.amount;
-        filled_order.tx_signature = Some(tx_signature);
+        filled_order.fill_price = Some(trigger_price);
+        filled_order.tx_signature = Some(tx_signature.clone());
         filled_order.triggered_at = Some(Utc::now());
         filled_order.updated_at = Utc::now();
 
@@ -358,11 +581,70 @@ impl OrderManager {
                 .await;
         }
 
-        self.emit_order_update(&filled_order);
+        publish_order_event(OrderEvent::live(
+            &filled_order,
+            OrderEventKind::Filled {
+                filled_amount: order.amount,
+                fill_price: Some(trigger_price),
+                tx_signature: Some(tx_signature),
+            },
+        ));
 
         Ok(())
     }
 
+    /// Cancels the open orders linked to `linked_id`, publishing an event for
+    /// each.
+    async fn cancel_linked_orders(&self, linked_id: &str) {
+        let db = self.db.write().await;
+        let linked = match db.get_open_linked_orders(linked_id).await {
+            Ok(linked) => linked,
+            Err(e) => {
+                eprintln!("Failed to load orders linked to {}: {}", linked_id, e);
+                return;
+            }
+        };
+        if let Err(e) = db.cancel_linked_orders(linked_id).await {
+            eprintln!("Failed to cancel orders linked to {}: {}", linked_id, e);
+            return;
+        }
+        drop(db);
+
+        for mut linked_order in linked {
+            linked_order.status = OrderStatus::Cancelled;
+            linked_order.updated_at = Utc::now();
+            publish_order_event(OrderEvent::live(
+                &linked_order,
+                OrderEventKind::Cancelled {
+                    reason: Some(format!("Linked order {} closed", linked_id)),
+                },
+            ));
+        }
+    }
+
+    async fn record_execution_stage(&self, order: &Order, stage: ExecutionStage) {
+        let Some(correlation_id) = &order.correlation_id else {
+            return;
+        };
+        let log = self.db.read().await.execution_audit();
+        if let Err(e) = log.record(correlation_id, &stage).await {
+            eprintln!("Order {}: {}", order.id, e);
+        }
+    }
+
+    /// The order's placed, filled and cancelled events from the event store.
+    pub async fn lifecycle_events(&self, order_id: &str) -> Vec<AuditEvent> {
+        let Some(store) = &self.event_store else {
+            return Vec::new();
+        };
+        let aggregate_id = format!("order_{}", order_id);
+        let result = store.read().await.replay_events(&aggregate_id).await;
+        result.unwrap_or_else(|e| {
+            eprintln!("Failed to load events for {}: {}", aggregate_id, e);
+            Vec::new()
+        })
+    }
+
     async fn publish_audit_event(&self, aggregate_id: String, event: AuditEvent) {
         if let Some(store) = &self.event_store {
             let store = store.clone();
@@ -377,32 +659,16 @@ impl OrderManager {
         }
     }
 
-    fn emit_order_update(&self, order: &Order) {
-        let _ = self.app_handle.emit("order_update", order);
-    }
-
-    fn emit_order_triggered(&self, order: &Order, trigger_price: f64) {
-        let event = OrderTriggeredEvent {
-            order_id: order.id.clone(),
-            order_type: order.order_type,
-            symbol: if order.side == OrderSide::Buy {
-                order.output_symbol.clone()
-            } else {
-                order.input_symbol.clone()
-            },
-            side: order.side,
-            trigger_price,
-            amount: order.amount,
-        };
-
-        let _ = self.app_handle.emit("order_triggered", event);
-    }
-
-    pub async fn start_monitoring(manager: Arc<Self>) {
+    /// Stops between checks once shutdown starts, so a trigger in progress
+    /// finishes writing its order state.
+    pub async fn start_monitoring(manager: Arc<Self>, mut shutdown: ShutdownListener) {
         let mut ticker = interval(Duration::from_millis(500));
 
         loop {
-            ticker.tick().await;
+            tokio::select! {
+                _ = ticker.tick() => {}
+                _ = shutdown.cancelled() => break,
+            }
             if let Err(e) = manager.check_and_trigger_orders().await {
                 eprintln!("Error checking orders: {}", e);
             }
@@ -0,0 +1,845 @@
+//! Feed-health awareness for the order trigger engine.
+//!
+//! Every token with armed orders is watched on the price stream as the
+//! `trading` consumer. When a token's stream goes quiet for longer than the
+//! policy allows, it enters protective mode: its price is polled over REST
+//! instead, the user is told protection is degraded and, if the policy asks
+//! for it, sell stops are mirrored as resting Jupiter limit orders so they
+//! hold even if the app dies. The stream coming back ends protective mode,
+//! and the prices backfilled for the gap are replayed against the orders so
+//! triggers missed meanwhile still fire.
+
+use crate::api::jupiter::{JupiterClient, TriggerOrderInput};
+use crate::api_analytics::{send_metered, RequestPriority};
+use crate::config::DataPaths;
+use crate::core::{WebSocketManager, TRADING_CONSUMER};
+use crate::deep_link::DeepLinkIntent;
+use crate::errors::AppError;
+use crate::notifications::router::SharedNotificationRouter;
+use crate::notifications::types::{AlertPriority, HistoryCategory, NewNotification};
+use crate::trading::limit_orders::require_state;
+use crate::trading::order_manager::{priced_token, OrderManager};
+use crate::trading::types::{Order, OrderSide, OrderType};
+use crate::wallet::balances::SharedTokenBalanceService;
+use chrono::{DateTime, Duration, Utc};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::time::interval;
+
+const PROTECTION_POLICY_FILE: &str = "order_protection.json";
+const JUPITER_PRICE_URL: &str = "https://lite-api.jup.ag/price/v2";
+const HEALTH_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+/// Emitted with a [`TokenProtectionStatus`] whenever a token changes mode.
+pub const ORDER_PROTECTION_EVENT: &str = "order_protection_changed";
+/// Emitted with a [`ProtectiveTriggerOrder`] for the wallet to sign.
+pub const PROTECTIVE_TRIGGER_ORDER_EVENT: &str = "order_protection_trigger_order";
+
+#[derive(Debug, thiserror::Error)]
+pub enum OrderProtectionError {
+    #[error("invalid order protection policy: {0}")]
+    Invalid(String),
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+}
+
+impl From<OrderProtectionError> for AppError {
+    fn from(err: OrderProtectionError) -> Self {
+        match err {
+            OrderProtectionError::Invalid(message) => {
+                AppError::validation(message).with_code("invalid_order_protection_policy")
+            }
+            OrderProtectionError::Io(err) => err.into(),
+            OrderProtectionError::Serialization(err) => err.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProtectionPolicy {
+    /// How long a token with armed orders may go without a streamed price
+    /// before it enters protective mode.
+    pub stale_after_secs: u64,
+    /// How often a token in protective mode is priced over REST.
+    pub poll_interval_secs: u64,
+    /// Whether sell stops are mirrored as resting Jupiter limit orders at the
+    /// stop price on entering protective mode. The limit rests on-chain, so
+    /// it fills once the market trades at or above the stop; a price already
+    /// below the stop fills it right away.
+    pub convert_stops_to_limits: bool,
+}
+
+impl Default for ProtectionPolicy {
+    fn default() -> Self {
+        Self {
+            stale_after_secs: 30,
+            poll_interval_secs: 10,
+            convert_stops_to_limits: false,
+        }
+    }
+}
+
+impl ProtectionPolicy {
+    fn validate(&self) -> Result<(), OrderProtectionError> {
+        if self.stale_after_secs < 5 {
+            return Err(OrderProtectionError::Invalid(
+                "Staleness threshold must be at least 5 seconds".to_string(),
+            ));
+        }
+        if self.poll_interval_secs == 0 {
+            return Err(OrderProtectionError::Invalid(
+                "Poll interval must be at least 1 second".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn stale_after(&self) -> Duration {
+        Duration::seconds(self.stale_after_secs as i64)
+    }
+
+    fn poll_interval(&self) -> Duration {
+        Duration::seconds(self.poll_interval_secs as i64)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProtectionMode {
+    /// Orders trigger on the streamed price.
+    #[default]
+    Live,
+    /// The stream is stale; orders trigger on polled prices.
+    Protective,
+}
+
+/// A stop mirrored as a resting limit order, or the reason it couldn't be.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConvertedStop {
+    pub order_id: String,
+    pub trigger_order: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenProtectionStatus {
+    pub mint: String,
+    pub symbol: String,
+    pub mode: ProtectionMode,
+    pub armed_orders: usize,
+    /// When the token started being watched, which staleness is measured
+    /// from until a price is streamed.
+    pub watched_since: DateTime<Utc>,
+    pub last_stream_update: Option<DateTime<Utc>>,
+    pub protective_since: Option<DateTime<Utc>>,
+    pub last_poll: Option<DateTime<Utc>>,
+    pub last_poll_price: Option<f64>,
+    pub last_poll_error: Option<String>,
+    pub converted_orders: Vec<ConvertedStop>,
+    /// Orders triggered by backfilled prices since protective mode began.
+    pub missed_triggers_reconciled: usize,
+}
+
+impl TokenProtectionStatus {
+    fn new(mint: &str, symbol: &str, now: DateTime<Utc>) -> Self {
+        Self {
+            mint: mint.to_string(),
+            symbol: symbol.to_string(),
+            mode: ProtectionMode::Live,
+            armed_orders: 0,
+            watched_since: now,
+            last_stream_update: None,
+            protective_since: None,
+            last_poll: None,
+            last_poll_price: None,
+            last_poll_error: None,
+            converted_orders: Vec::new(),
+            missed_triggers_reconciled: 0,
+        }
+    }
+
+    fn stale_for(&self, now: DateTime<Utc>) -> Duration {
+        now - self.last_stream_update.unwrap_or(self.watched_since)
+    }
+}
+
+/// The unsigned transaction opening a limit order that mirrors a stop.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProtectiveTriggerOrder {
+    pub order_id: String,
+    pub wallet_address: String,
+    pub mint: String,
+    pub trigger_order: String,
+    pub transaction: String,
+    pub request_id: Option<String>,
+}
+
+/// Per-token stream health of the tokens with armed orders.
+#[derive(Debug, Default)]
+pub struct FeedHealth {
+    tokens: HashMap<String, TokenProtectionStatus>,
+}
+
+impl FeedHealth {
+    /// Tracks the tokens in `armed` (mint to symbol and order count) and
+    /// drops the ones without armed orders left.
+    pub fn sync_armed(&mut self, armed: &HashMap<String, (String, usize)>, now: DateTime<Utc>) {
+        self.tokens.retain(|mint, _| armed.contains_key(mint));
+        for (mint, (symbol, count)) in armed {
+            let status = self
+                .tokens
+                .entry(mint.clone())
+                .or_insert_with(|| TokenProtectionStatus::new(mint, symbol, now));
+            status.armed_orders = *count;
+        }
+    }
+
+    pub fn is_watched(&self, mint: &str) -> bool {
+        self.tokens.contains_key(mint)
+    }
+
+    /// Records a streamed price, returning the token's status if this ended
+    /// its protective mode.
+    pub fn record_stream(
+        &mut self,
+        mint: &str,
+        now: DateTime<Utc>,
+    ) -> Option<TokenProtectionStatus> {
+        let status = self.tokens.get_mut(mint)?;
+        status.last_stream_update = Some(now);
+        if status.mode != ProtectionMode::Protective {
+            return None;
+        }
+        status.mode = ProtectionMode::Live;
+        status.protective_since = None;
+        Some(status.clone())
+    }
+
+    /// Moves tokens stale for longer than `stale_after` into protective mode
+    /// and returns their statuses.
+    pub fn evaluate(
+        &mut self,
+        now: DateTime<Utc>,
+        stale_after: Duration,
+    ) -> Vec<TokenProtectionStatus> {
+        let mut degraded: Vec<TokenProtectionStatus> = self
+            .tokens
+            .values_mut()
+            .filter(|status| {
+                status.mode == ProtectionMode::Live && status.stale_for(now) > stale_after
+            })
+            .map(|status| {
+                status.mode = ProtectionMode::Protective;
+                status.protective_since = Some(now);
+                status.missed_triggers_reconciled = 0;
+                status.clone()
+            })
+            .collect();
+        degraded.sort_by(|a, b| a.mint.cmp(&b.mint));
+        degraded
+    }
+
+    /// Protective tokens not polled within the last `poll_interval`.
+    pub fn due_for_poll(&self, now: DateTime<Utc>, poll_interval: Duration) -> Vec<String> {
+        let mut due: Vec<String> = self
+            .tokens
+            .values()
+            .filter(|status| status.mode == ProtectionMode::Protective)
+            .filter(|status| {
+                status
+                    .last_poll
+                    .map_or(true, |at| now - at >= poll_interval)
+            })
+            .map(|status| status.mint.clone())
+            .collect();
+        due.sort();
+        due
+    }
+
+    pub fn record_poll(&mut self, mint: &str, now: DateTime<Utc>, result: &Result<f64, String>) {
+        let Some(status) = self.tokens.get_mut(mint) else {
+            return;
+        };
+        status.last_poll = Some(now);
+        match result {
+            Ok(price) => {
+                status.last_poll_price = Some(*price);
+                status.last_poll_error = None;
+            }
+            Err(e) => status.last_poll_error = Some(e.clone()),
+        }
+    }
+
+    /// Whether `order_id` is already mirrored by a resting limit order.
+    pub fn is_converted(&self, mint: &str, order_id: &str) -> bool {
+        self.tokens.get(mint).is_some_and(|status| {
+            status
+                .converted_orders
+                .iter()
+                .any(|converted| converted.order_id == order_id && converted.error.is_none())
+        })
+    }
+
+    pub fn record_conversion(&mut self, mint: &str, converted: ConvertedStop) {
+        if let Some(status) = self.tokens.get_mut(mint) {
+            status
+                .converted_orders
+                .retain(|existing| existing.order_id != converted.order_id);
+            status.converted_orders.push(converted);
+        }
+    }
+
+    pub fn record_reconciled(&mut self, mint: &str, triggered: usize) {
+        if let Some(status) = self.tokens.get_mut(mint) {
+            status.missed_triggers_reconciled += triggered;
+        }
+    }
+
+    pub fn statuses(&self) -> Vec<TokenProtectionStatus> {
+        let mut statuses: Vec<TokenProtectionStatus> = self.tokens.values().cloned().collect();
+        statuses.sort_by(|a, b| a.mint.cmp(&b.mint));
+        statuses
+    }
+}
+
+/// The mints with armed orders, with the symbol and number of orders each.
+fn armed_tokens(orders: &[Order]) -> HashMap<String, (String, usize)> {
+    let mut armed: HashMap<String, (String, usize)> = HashMap::new();
+    for order in orders
+        .iter()
+        .filter(|order| order.order_type != OrderType::Market)
+    {
+        let (mint, symbol) = priced_token(order);
+        armed
+            .entry(mint.to_string())
+            .or_insert_with(|| (symbol.to_string(), 0))
+            .1 += 1;
+    }
+    armed
+}
+
+/// Only sell stops can rest on-chain as a limit order to sell at the stop.
+fn is_convertible_stop(order: &Order) -> bool {
+    order.side == OrderSide::Sell
+        && matches!(
+            order.order_type,
+            OrderType::StopLoss | OrderType::TrailingStop
+        )
+        && order.stop_price.is_some()
+}
+
+fn base_units(amount: f64, decimals: u8) -> Result<u64, String> {
+    let scaled = (amount * 10f64.powi(decimals as i32)).floor();
+    if !scaled.is_finite() || scaled <= 0.0 || scaled > u64::MAX as f64 {
+        return Err(format!("Invalid order amount: {}", amount));
+    }
+    Ok(scaled as u64)
+}
+
+fn parse_price(value: &Value) -> Option<f64> {
+    value
+        .as_f64()
+        .or_else(|| value.as_str().and_then(|price| price.parse().ok()))
+}
+
+pub struct OrderProtection {
+    path: Option<PathBuf>,
+    client: reqwest::Client,
+    jupiter: JupiterClient,
+    policy: RwLock<ProtectionPolicy>,
+    health: RwLock<FeedHealth>,
+}
+
+pub type SharedOrderProtection = Arc<OrderProtection>;
+
+impl OrderProtection {
+    pub fn new(app: &AppHandle) -> Result<Self, OrderProtectionError> {
+        let dir = app.app_data_root().map_err(|e| {
+            OrderProtectionError::Invalid(format!("App data directory not found: {}", e))
+        })?;
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(PROTECTION_POLICY_FILE);
+        let policy = if path.exists() {
+            serde_json::from_str(&fs::read_to_string(&path)?)?
+        } else {
+            ProtectionPolicy::default()
+        };
+        Ok(Self {
+            path: Some(path),
+            client: reqwest::Client::new(),
+            jupiter: JupiterClient::default(),
+            policy: RwLock::new(policy),
+            health: RwLock::new(FeedHealth::default()),
+        })
+    }
+
+    /// Protection whose policy is kept in memory only.
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            client: reqwest::Client::new(),
+            jupiter: JupiterClient::default(),
+            policy: RwLock::new(ProtectionPolicy::default()),
+            health: RwLock::new(FeedHealth::default()),
+        }
+    }
+
+    pub fn policy(&self) -> ProtectionPolicy {
+        self.policy.read().clone()
+    }
+
+    pub fn set_policy(&self, policy: ProtectionPolicy) -> Result<(), OrderProtectionError> {
+        policy.validate()?;
+        if let Some(path) = &self.path {
+            let tmp = path.with_extension("json.tmp");
+            fs::write(&tmp, serde_json::to_vec_pretty(&policy)?)?;
+            fs::rename(&tmp, path)?;
+        }
+        *self.policy.write() = policy;
+        Ok(())
+    }
+
+    pub fn statuses(&self) -> Vec<TokenProtectionStatus> {
+        self.health.read().statuses()
+    }
+
+    /// Handles a streamed price. Prices older than the staleness threshold
+    /// come from the backfill after a reconnect and are only replayed
+    /// against the orders; fresh ones drive the trigger engine.
+    async fn on_price(
+        &self,
+        app: &AppHandle,
+        manager: &OrderManager,
+        delta_ts: i64,
+        mint: &str,
+        price: f64,
+    ) {
+        if !self.health.read().is_watched(mint) {
+            return;
+        }
+        let now = Utc::now();
+        let observed = DateTime::from_timestamp(delta_ts, 0).unwrap_or(now);
+        if now - observed > self.policy().stale_after() {
+            match manager.reconcile_missed_price(mint, price).await {
+                Ok(0) => {}
+                Ok(triggered) => {
+                    self.health.write().record_reconciled(mint, triggered);
+                    self.emit_status(app, mint);
+                }
+                Err(e) => eprintln!("Failed to reconcile missed triggers for {}: {}", mint, e),
+            }
+            return;
+        }
+
+        manager.update_price(mint, price).await;
+        let recovered = self.health.write().record_stream(mint, now);
+        if let Some(status) = recovered {
+            deliver_protection_change(app, &status).await;
+        }
+    }
+
+    /// Re-syncs the watched tokens with the armed orders, moves stale ones
+    /// into protective mode and polls the ones already in it.
+    async fn check(
+        &self,
+        app: &AppHandle,
+        ws_manager: &WebSocketManager,
+        manager: &OrderManager,
+        watched: &mut HashSet<String>,
+    ) {
+        let orders = match manager.get_all_active_orders().await {
+            Ok(orders) => orders,
+            Err(e) => {
+                eprintln!("Failed to list armed orders for protection: {}", e);
+                return;
+            }
+        };
+        let armed = armed_tokens(&orders);
+        sync_watched_mints(ws_manager, &armed, watched).await;
+
+        let policy = self.policy();
+        let now = Utc::now();
+        let degraded = {
+            let mut health = self.health.write();
+            health.sync_armed(&armed, now);
+            health.evaluate(now, policy.stale_after())
+        };
+        for status in degraded {
+            deliver_protection_change(app, &status).await;
+            if policy.convert_stops_to_limits {
+                self.convert_stops(app, &status.mint, &orders).await;
+            }
+        }
+
+        let due = self.health.read().due_for_poll(now, policy.poll_interval());
+        for mint in due {
+            let result = self.jupiter_price(&mint).await;
+            if let Ok(price) = result {
+                manager.update_price(&mint, price).await;
+            }
+            self.health.write().record_poll(&mint, now, &result);
+        }
+    }
+
+    async fn convert_stops(&self, app: &AppHandle, mint: &str, orders: &[Order]) {
+        for order in orders {
+            if priced_token(order).0 != mint
+                || !is_convertible_stop(order)
+                || self.health.read().is_converted(mint, &order.id)
+            {
+                continue;
+            }
+            let result = self.place_trigger_order(app, order).await;
+            if let Err(e) = &result {
+                eprintln!(
+                    "Failed to convert stop {} to a limit order: {}",
+                    order.id, e
+                );
+            }
+            let (trigger_order, error) = match result {
+                Ok(trigger_order) => (Some(trigger_order), None),
+                Err(e) => (None, Some(e)),
+            };
+            self.health.write().record_conversion(
+                mint,
+                ConvertedStop {
+                    order_id: order.id.clone(),
+                    trigger_order,
+                    error,
+                },
+            );
+        }
+        self.emit_status(app, mint);
+    }
+
+    /// Builds a Jupiter limit order selling what's left of `order` at its
+    /// stop price, and hands the transaction to the wallet to sign.
+    async fn place_trigger_order(&self, app: &AppHandle, order: &Order) -> Result<String, String> {
+        let stop_price = order.stop_price.ok_or("Stop price not set")?;
+        let remaining = order.amount - order.filled_amount;
+        let output_price = self.jupiter_price(&order.output_mint).await?;
+        let balances = app
+            .try_state::<SharedTokenBalanceService>()
+            .ok_or("Token metadata is unavailable")?;
+        let input_decimals = balances
+            .mint_decimals(&order.input_mint)
+            .await
+            .map_err(|e| e.to_string())?;
+        let output_decimals = balances
+            .mint_decimals(&order.output_mint)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let input = TriggerOrderInput {
+            input_mint: order.input_mint.clone(),
+            output_mint: order.output_mint.clone(),
+            maker: order.wallet_address.clone(),
+            making_amount: base_units(remaining, input_decimals)?,
+            taking_amount: base_units(remaining * stop_price / output_price, output_decimals)?,
+        };
+        let response = self.jupiter.create_trigger_order(&input).await?;
+        let payload = ProtectiveTriggerOrder {
+            order_id: order.id.clone(),
+            wallet_address: order.wallet_address.clone(),
+            mint: order.input_mint.clone(),
+            trigger_order: response.order.clone(),
+            transaction: response.transaction,
+            request_id: response.request_id,
+        };
+        if let Err(e) = app.emit(PROTECTIVE_TRIGGER_ORDER_EVENT, &payload) {
+            eprintln!("Failed to emit protective trigger order: {}", e);
+        }
+        Ok(response.order)
+    }
+
+    async fn jupiter_price(&self, mint: &str) -> Result<f64, String> {
+        let request = self.client.get(JUPITER_PRICE_URL).query(&[("ids", mint)]);
+        let body: Value = send_metered("jupiter", RequestPriority::Standard, request)
+            .await
+            .map_err(|e| e.to_string())?
+            .json()
+            .await
+            .map_err(|e| e.to_string())?;
+        parse_price(&body["data"][mint]["price"]).ok_or_else(|| "no price in response".to_string())
+    }
+
+    fn emit_status(&self, app: &AppHandle, mint: &str) {
+        let status = self
+            .health
+            .read()
+            .statuses()
+            .into_iter()
+            .find(|status| status.mint == mint);
+        if let Some(status) = status {
+            if let Err(e) = app.emit(ORDER_PROTECTION_EVENT, &status) {
+                eprintln!("Failed to emit order protection status: {}", e);
+            }
+        }
+    }
+}
+
+/// Subscribes the `trading` consumer to the mints with armed orders.
+async fn sync_watched_mints(
+    ws_manager: &WebSocketManager,
+    armed: &HashMap<String, (String, usize)>,
+    watched: &mut HashSet<String>,
+) {
+    let wanted: HashSet<String> = armed.keys().cloned().collect();
+    let added: Vec<String> = wanted.difference(watched).cloned().collect();
+    let removed: Vec<String> = watched.difference(&wanted).cloned().collect();
+
+    if !added.is_empty() {
+        match ws_manager
+            .subscribe_prices_as(TRADING_CONSUMER, added.clone())
+            .await
+        {
+            Ok(()) => watched.extend(added),
+            Err(err) => eprintln!("Failed to subscribe armed order mints: {}", err),
+        }
+    }
+    if !removed.is_empty() {
+        if let Err(err) = ws_manager
+            .unsubscribe_prices_as(TRADING_CONSUMER, removed.clone())
+            .await
+        {
+            eprintln!("Failed to unsubscribe armed order mints: {}", err);
+        }
+        for mint in removed {
+            watched.remove(&mint);
+        }
+    }
+}
+
+async fn deliver_protection_change(app: &AppHandle, status: &TokenProtectionStatus) {
+    if let Err(e) = app.emit(ORDER_PROTECTION_EVENT, status) {
+        eprintln!("Failed to emit order protection status: {}", e);
+    }
+    let Some(router) = app.try_state::<SharedNotificationRouter>() else {
+        return;
+    };
+    let (priority, title, message) = match status.mode {
+        ProtectionMode::Protective => (
+            AlertPriority::High,
+            format!("{} order protection degraded", status.symbol),
+            format!(
+                "No live price for {} in {}s; {} armed order(s) now run on polled prices",
+                status.symbol,
+                status
+                    .protective_since
+                    .map(|since| status.stale_for(since).num_seconds())
+                    .unwrap_or_default(),
+                status.armed_orders
+            ),
+        ),
+        ProtectionMode::Live => (
+            AlertPriority::Medium,
+            format!("{} order protection restored", status.symbol),
+            format!(
+                "{} is streaming again; missed prices are replayed against {} armed order(s)",
+                status.symbol, status.armed_orders
+            ),
+        ),
+    };
+    let notification = NewNotification {
+        category: HistoryCategory::Order,
+        priority,
+        title,
+        message,
+        entity: None,
+        link: Some(
+            DeepLinkIntent::Token {
+                mint: status.mint.clone(),
+            }
+            .to_url(),
+        ),
+    };
+    if let Err(e) = router
+        .read()
+        .await
+        .send_event_notification(&notification)
+        .await
+    {
+        eprintln!("Failed to send order protection notification: {}", e);
+    }
+}
+
+/// Watches the stream health of tokens with armed orders. Idles until the
+/// trading module is initialized.
+pub async fn run_order_protection(
+    app: AppHandle,
+    ws_manager: WebSocketManager,
+    protection: SharedOrderProtection,
+) {
+    let mut feed = ws_manager.price_feed(TRADING_CONSUMER, std::time::Duration::ZERO);
+    let mut watched: HashSet<String> = ws_manager
+        .price_consumer_tokens(TRADING_CONSUMER)
+        .into_iter()
+        .collect();
+    let mut health_check = interval(HEALTH_CHECK_INTERVAL);
+
+    loop {
+        tokio::select! {
+            _ = health_check.tick() => {
+                let Ok(state) = require_state() else {
+                    continue;
+                };
+                protection
+                    .check(&app, &ws_manager, &state.manager, &mut watched)
+                    .await;
+            }
+            delta = feed.recv() => {
+                let Some(delta) = delta else {
+                    break;
+                };
+                let (Some(price), Ok(state)) = (delta.price, require_state()) else {
+                    continue;
+                };
+                protection
+                    .on_price(&app, &state.manager, delta.ts, &delta.symbol, price)
+                    .await;
+            }
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn get_order_protection_policy(
+    protection: State<'_, SharedOrderProtection>,
+) -> Result<ProtectionPolicy, AppError> {
+    Ok(protection.policy())
+}
+
+#[tauri::command]
+pub async fn set_order_protection_policy(
+    policy: ProtectionPolicy,
+    protection: State<'_, SharedOrderProtection>,
+) -> Result<(), AppError> {
+    protection.set_policy(policy)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(seconds: i64) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2026-01-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc)
+            + Duration::seconds(seconds)
+    }
+
+    fn armed(mints: &[(&str, usize)]) -> HashMap<String, (String, usize)> {
+        mints
+            .iter()
+            .map(|(mint, count)| (mint.to_string(), (mint.to_uppercase(), *count)))
+            .collect()
+    }
+
+    #[test]
+    fn silent_tokens_enter_protective_mode_once() {
+        let mut health = FeedHealth::default();
+        health.sync_armed(&armed(&[("bonk", 2), ("wif", 1)]), at(0));
+        health.record_stream("wif", at(20));
+
+        let degraded = health.evaluate(at(31), Duration::seconds(30));
+        assert_eq!(degraded.len(), 1);
+        assert_eq!(degraded[0].mint, "bonk");
+        assert_eq!(degraded[0].protective_since, Some(at(31)));
+        assert!(health.evaluate(at(45), Duration::seconds(30)).is_empty());
+
+        let degraded = health.evaluate(at(51), Duration::seconds(30));
+        assert_eq!(degraded.len(), 1);
+        assert_eq!(degraded[0].mint, "wif");
+    }
+
+    #[test]
+    fn streamed_price_ends_protective_mode() {
+        let mut health = FeedHealth::default();
+        health.sync_armed(&armed(&[("bonk", 1)]), at(0));
+        assert!(health.record_stream("bonk", at(5)).is_none());
+        health.evaluate(at(40), Duration::seconds(30));
+
+        let recovered = health.record_stream("bonk", at(50)).unwrap();
+        assert_eq!(recovered.mode, ProtectionMode::Live);
+        assert_eq!(recovered.protective_since, None);
+        assert!(health.record_stream("bonk", at(51)).is_none());
+    }
+
+    #[test]
+    fn polls_protective_tokens_on_interval() {
+        let mut health = FeedHealth::default();
+        health.sync_armed(&armed(&[("bonk", 1), ("wif", 1)]), at(0));
+        health.record_stream("wif", at(25));
+        health.evaluate(at(31), Duration::seconds(30));
+
+        let poll_interval = Duration::seconds(10);
+        assert_eq!(health.due_for_poll(at(31), poll_interval), vec!["bonk"]);
+        health.record_poll("bonk", at(31), &Err("timeout".to_string()));
+        assert!(health.due_for_poll(at(35), poll_interval).is_empty());
+        health.record_poll("bonk", at(41), &Ok(0.5));
+
+        let status = &health.statuses()[0];
+        assert_eq!(status.last_poll_price, Some(0.5));
+        assert_eq!(status.last_poll_error, None);
+    }
+
+    #[test]
+    fn unarmed_tokens_are_dropped() {
+        let mut health = FeedHealth::default();
+        health.sync_armed(&armed(&[("bonk", 1), ("wif", 1)]), at(0));
+        health.sync_armed(&armed(&[("wif", 3)]), at(10));
+
+        let statuses = health.statuses();
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].armed_orders, 3);
+        assert_eq!(statuses[0].watched_since, at(0));
+    }
+
+    #[test]
+    fn failed_conversions_are_retried() {
+        let mut health = FeedHealth::default();
+        health.sync_armed(&armed(&[("bonk", 1)]), at(0));
+        health.record_conversion(
+            "bonk",
+            ConvertedStop {
+                order_id: "order-1".to_string(),
+                trigger_order: None,
+                error: Some("no route".to_string()),
+            },
+        );
+        assert!(!health.is_converted("bonk", "order-1"));
+
+        health.record_conversion(
+            "bonk",
+            ConvertedStop {
+                order_id: "order-1".to_string(),
+                trigger_order: Some("trigger-1".to_string()),
+                error: None,
+            },
+        );
+        assert!(health.is_converted("bonk", "order-1"));
+        assert_eq!(health.statuses()[0].converted_orders.len(), 1);
+    }
+
+    #[test]
+    fn policy_rejects_tiny_thresholds() {
+        let policy = ProtectionPolicy {
+            stale_after_secs: 2,
+            ..ProtectionPolicy::default()
+        };
+        assert!(policy.validate().is_err());
+        assert!(ProtectionPolicy::default().validate().is_ok());
+    }
+}
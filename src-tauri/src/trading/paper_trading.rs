@@ -1,3 +1,6 @@
+use crate::config::DataPaths;
+use crate::trading::events::{publish_order_event, OrderEvent, OrderEventKind, OrderEventSource};
+use crate::trading::idempotency::{IdempotencyGuard, IdempotencyKeys};
 use crate::utils::Rfc3339DateTime;
 use chrono::{DateTime, Utc};
 use rand::Rng;
@@ -11,9 +14,13 @@ use tokio::sync::{OnceCell, RwLock};
 use uuid::Uuid;
 
 use crate::trading::types::{OrderSide, OrderType};
+use crate::utils::ensure_column;
 
 const DEFAULT_INITIAL_BALANCE: f64 = 10_000.0;
 const MINIMUM_QUANTITY: f64 = 1e-9;
+const PRIMARY_ACCOUNT_NAME: &str = "Primary";
+/// Number of samples in each equity curve returned by `paper_accounts_compare`.
+const COMPARISON_POINTS: usize = 120;
 
 // ============================================================================
 // Types and Structs
@@ -22,22 +29,36 @@ const MINIMUM_QUANTITY: f64 = 1e-9;
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PaperAccount {
     pub id: String,
+    pub name: String,
+    /// The account used when a command is called without an `account_id`.
+    pub is_primary: bool,
     pub balance: f64,
     pub initial_balance: f64,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Set whenever the account is reset; resets keep the account id.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reset_at: Option<DateTime<Utc>>,
 }
 
 impl<'r> sqlx::FromRow<'r, sqlx::sqlite::SqliteRow> for PaperAccount {
     fn from_row(row: &'r sqlx::sqlite::SqliteRow) -> Result<Self, sqlx::Error> {
         use sqlx::Row;
 
+        let reset_at = match row.try_get::<Option<String>, _>("reset_at")? {
+            Some(value) => Some(Rfc3339DateTime::try_from(value)?.into()),
+            None => None,
+        };
+
         Ok(PaperAccount {
             id: row.try_get("id")?,
+            name: row.try_get("name")?,
+            is_primary: row.try_get::<i64, _>("is_primary")? != 0,
             balance: row.try_get("balance")?,
             initial_balance: row.try_get("initial_balance")?,
             created_at: Rfc3339DateTime::try_from(row.try_get::<String, _>("created_at")?)?.into(),
             updated_at: Rfc3339DateTime::try_from(row.try_get::<String, _>("updated_at")?)?.into(),
+            reset_at,
         })
     }
 }
@@ -136,6 +157,10 @@ pub struct ExecutePaperTradeRequest {
     pub limit_price: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub stop_price: Option<f64>,
+    /// UUID chosen by the client; retrying with the same id returns the
+    /// result of the first execution.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_request_id: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -171,6 +196,35 @@ pub struct PaperPerformance {
     pub return_percentage: f64,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaperAccountSummary {
+    pub starting_equity: f64,
+    pub ending_equity: f64,
+    pub return_percentage: f64,
+    pub max_drawdown_percentage: f64,
+    /// Trades executed inside the comparison window.
+    pub trades: usize,
+    pub fees: f64,
+    pub win_rate: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaperAccountCurve {
+    pub account_id: String,
+    pub name: String,
+    /// One equity value per entry in `PaperAccountComparison::timestamps`.
+    pub equity: Vec<f64>,
+    pub summary: PaperAccountSummary,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaperAccountComparison {
+    pub window_start: DateTime<Utc>,
+    pub window_end: DateTime<Utc>,
+    pub timestamps: Vec<DateTime<Utc>>,
+    pub accounts: Vec<PaperAccountCurve>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SlippageConfig {
     pub small_order_threshold: f64,  // $100
@@ -238,10 +292,13 @@ impl PaperTradingDatabase {
             r#"
             CREATE TABLE IF NOT EXISTS paper_accounts (
                 id TEXT PRIMARY KEY,
+                name TEXT NOT NULL DEFAULT 'Primary',
+                is_primary INTEGER NOT NULL DEFAULT 0,
                 balance REAL NOT NULL,
                 initial_balance REAL NOT NULL,
                 created_at TEXT NOT NULL,
-                updated_at TEXT NOT NULL
+                updated_at TEXT NOT NULL,
+                reset_at TEXT
             )
             "#,
         )
@@ -302,15 +359,80 @@ impl PaperTradingDatabase {
         .execute(&self.pool)
         .await?;
 
+        IdempotencyKeys::initialize(&self.pool).await?;
+
+        ensure_column(
+            &self.pool,
+            "paper_accounts",
+            "name",
+            "TEXT NOT NULL DEFAULT 'Primary'",
+        )
+        .await?;
+        ensure_column(
+            &self.pool,
+            "paper_accounts",
+            "is_primary",
+            "INTEGER NOT NULL DEFAULT 0",
+        )
+        .await?;
+        ensure_column(&self.pool, "paper_accounts", "reset_at", "TEXT").await?;
+        self.migrate_primary_account().await?;
+
         Ok(())
     }
 
-    pub async fn get_or_create_account(
+    /// Databases written before named accounts existed only ever used the
+    /// most recent account row. That row becomes the primary account and
+    /// any leftover rows are folded into it.
+    async fn migrate_primary_account(&self) -> Result<(), sqlx::Error> {
+        let has_primary: Option<String> =
+            sqlx::query_scalar("SELECT id FROM paper_accounts WHERE is_primary = 1 LIMIT 1")
+                .fetch_optional(&self.pool)
+                .await?;
+        if has_primary.is_some() {
+            return Ok(());
+        }
+
+        let latest: Option<String> =
+            sqlx::query_scalar("SELECT id FROM paper_accounts ORDER BY created_at DESC LIMIT 1")
+                .fetch_optional(&self.pool)
+                .await?;
+        let Some(primary_id) = latest else {
+            return Ok(());
+        };
+
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("UPDATE paper_accounts SET is_primary = 1, name = ?1 WHERE id = ?2")
+            .bind(PRIMARY_ACCOUNT_NAME)
+            .bind(&primary_id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("UPDATE paper_trades SET account_id = ?1 WHERE account_id != ?1")
+            .bind(&primary_id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("UPDATE paper_positions SET account_id = ?1 WHERE account_id != ?1")
+            .bind(&primary_id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM paper_accounts WHERE id != ?1")
+            .bind(&primary_id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await
+    }
+
+    pub fn idempotency_keys(&self) -> IdempotencyKeys {
+        IdempotencyKeys::new(self.pool.clone())
+    }
+
+    /// Returns the primary account, creating it on first use.
+    pub async fn get_or_create_primary(
         &self,
         initial_balance: f64,
     ) -> Result<PaperAccount, sqlx::Error> {
         let existing = sqlx::query_as::<_, PaperAccount>(
-            "SELECT * FROM paper_accounts ORDER BY created_at DESC LIMIT 1",
+            "SELECT * FROM paper_accounts WHERE is_primary = 1 LIMIT 1",
         )
         .fetch_optional(&self.pool)
         .await?;
@@ -319,21 +441,46 @@ impl PaperTradingDatabase {
             return Ok(account);
         }
 
+        self.insert_account(PRIMARY_ACCOUNT_NAME, initial_balance, true)
+            .await
+    }
+
+    pub async fn create_account(
+        &self,
+        name: &str,
+        initial_balance: f64,
+    ) -> Result<PaperAccount, sqlx::Error> {
+        self.insert_account(name, initial_balance, false).await
+    }
+
+    async fn insert_account(
+        &self,
+        name: &str,
+        initial_balance: f64,
+        is_primary: bool,
+    ) -> Result<PaperAccount, sqlx::Error> {
+        let now = Utc::now();
         let account = PaperAccount {
             id: Uuid::new_v4().to_string(),
+            name: name.to_string(),
+            is_primary,
             balance: initial_balance,
             initial_balance,
-            created_at: Utc::now(),
-            updated_at: Utc::now(),
+            created_at: now,
+            updated_at: now,
+            reset_at: None,
         };
 
         sqlx::query(
             r#"
-            INSERT INTO paper_accounts (id, balance, initial_balance, created_at, updated_at)
-            VALUES (?1, ?2, ?3, ?4, ?5)
+            INSERT INTO paper_accounts (
+                id, name, is_primary, balance, initial_balance, created_at, updated_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
             "#,
         )
         .bind(&account.id)
+        .bind(&account.name)
+        .bind(account.is_primary as i64)
         .bind(account.balance)
         .bind(account.initial_balance)
         .bind(account.created_at.to_rfc3339())
@@ -344,14 +491,51 @@ impl PaperTradingDatabase {
         Ok(account)
     }
 
-    pub async fn get_account(&self) -> Result<Option<PaperAccount>, sqlx::Error> {
+    pub async fn get_account(&self, account_id: &str) -> Result<Option<PaperAccount>, sqlx::Error> {
+        sqlx::query_as::<_, PaperAccount>("SELECT * FROM paper_accounts WHERE id = ?1")
+            .bind(account_id)
+            .fetch_optional(&self.pool)
+            .await
+    }
+
+    pub async fn find_account_by_name(
+        &self,
+        name: &str,
+    ) -> Result<Option<PaperAccount>, sqlx::Error> {
         sqlx::query_as::<_, PaperAccount>(
-            "SELECT * FROM paper_accounts ORDER BY created_at DESC LIMIT 1",
+            "SELECT * FROM paper_accounts WHERE name = ?1 COLLATE NOCASE LIMIT 1",
         )
+        .bind(name)
         .fetch_optional(&self.pool)
         .await
     }
 
+    pub async fn list_accounts(&self) -> Result<Vec<PaperAccount>, sqlx::Error> {
+        sqlx::query_as::<_, PaperAccount>(
+            "SELECT * FROM paper_accounts ORDER BY is_primary DESC, created_at ASC",
+        )
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Removes an account together with its trades and positions.
+    pub async fn delete_account(&self, account_id: &str) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM paper_positions WHERE account_id = ?1")
+            .bind(account_id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM paper_trades WHERE account_id = ?1")
+            .bind(account_id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM paper_accounts WHERE id = ?1")
+            .bind(account_id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await
+    }
+
     pub async fn update_balance(
         &self,
         account_id: &str,
@@ -373,18 +557,41 @@ impl PaperTradingDatabase {
         Ok(())
     }
 
-    pub async fn reset_account(&self, initial_balance: f64) -> Result<PaperAccount, sqlx::Error> {
-        sqlx::query("DELETE FROM paper_positions")
-            .execute(&self.pool)
-            .await?;
-        sqlx::query("DELETE FROM paper_trades")
-            .execute(&self.pool)
+    /// Clears one account's trades and positions and restores its balance.
+    /// The account keeps its id and name; `reset_at` records when it happened.
+    pub async fn reset_account(
+        &self,
+        account_id: &str,
+        initial_balance: f64,
+    ) -> Result<PaperAccount, sqlx::Error> {
+        let now = Utc::now().to_rfc3339();
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM paper_positions WHERE account_id = ?1")
+            .bind(account_id)
+            .execute(&mut *tx)
             .await?;
-        sqlx::query("DELETE FROM paper_accounts")
-            .execute(&self.pool)
+        sqlx::query("DELETE FROM paper_trades WHERE account_id = ?1")
+            .bind(account_id)
+            .execute(&mut *tx)
             .await?;
+        sqlx::query(
+            r#"
+            UPDATE paper_accounts
+            SET balance = ?1, initial_balance = ?1, updated_at = ?2, reset_at = ?2
+            WHERE id = ?3
+            "#,
+        )
+        .bind(initial_balance)
+        .bind(&now)
+        .bind(account_id)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
 
-        self.get_or_create_account(initial_balance).await
+        sqlx::query_as::<_, PaperAccount>("SELECT * FROM paper_accounts WHERE id = ?1")
+            .bind(account_id)
+            .fetch_one(&self.pool)
+            .await
     }
 
     pub async fn create_trade(&self, trade: &PaperTrade) -> Result<(), sqlx::Error> {
@@ -457,6 +664,16 @@ impl PaperTradingDatabase {
             .await
     }
 
+    pub async fn get_positions_for_symbol(
+        &self,
+        symbol: &str,
+    ) -> Result<Vec<PaperPosition>, sqlx::Error> {
+        sqlx::query_as::<_, PaperPosition>("SELECT * FROM paper_positions WHERE symbol = ?1")
+            .bind(symbol)
+            .fetch_all(&self.pool)
+            .await
+    }
+
     pub async fn upsert_position(&self, position: &PaperPosition) -> Result<(), sqlx::Error> {
         sqlx::query(
             r#"
@@ -656,6 +873,7 @@ pub struct PaperTradingManager {
     slippage_config: SlippageConfig,
     fee_config: FeeConfig,
     current_prices: Arc<RwLock<HashMap<String, f64>>>,
+    execute_guard: IdempotencyGuard<PaperTradeResult>,
 }
 
 impl PaperTradingManager {
@@ -673,6 +891,7 @@ impl PaperTradingManager {
             slippage_config,
             fee_config,
             current_prices: Arc::new(RwLock::new(HashMap::new())),
+            execute_guard: IdempotencyGuard::new("execute_paper_trade"),
         }
     }
 
@@ -770,17 +989,50 @@ impl PaperTradingManager {
         }
     }
 
+    /// Looks up `account_id`, or the primary account when it is `None`.
+    async fn resolve_account(
+        db: &PaperTradingDatabase,
+        account_id: Option<&str>,
+    ) -> Result<PaperAccount, String> {
+        match account_id {
+            Some(id) => db
+                .get_account(id)
+                .await
+                .map_err(|e| format!("Failed to load paper account: {e}"))?
+                .ok_or_else(|| format!("Paper account {id} not found")),
+            None => db
+                .get_or_create_primary(DEFAULT_INITIAL_BALANCE)
+                .await
+                .map_err(|e| format!("Failed to load paper account: {e}")),
+        }
+    }
+
+    /// Executes a trade against `account_id` (the primary account when
+    /// `None`), or returns the result already recorded for the request's
+    /// `client_request_id`.
     pub async fn execute_trade(
         &self,
         request: ExecutePaperTradeRequest,
+        account_id: Option<&str>,
+    ) -> Result<PaperTradeResult, String> {
+        let request_id = request.client_request_id.clone();
+        let keys = self.db.read().await.idempotency_keys();
+        self.execute_guard
+            .run(&keys, request_id.as_deref(), || {
+                self.execute_new_trade(request, account_id)
+            })
+            .await
+    }
+
+    async fn execute_new_trade(
+        &self,
+        request: ExecutePaperTradeRequest,
+        account_id: Option<&str>,
     ) -> Result<PaperTradeResult, String> {
         self.validate_request(&request)?;
 
         let db_read = self.db.read().await;
-        let mut account = db_read
-            .get_or_create_account(DEFAULT_INITIAL_BALANCE)
-            .await
-            .map_err(|e| format!("Failed to load paper account: {e}"))?;
+        let mut account = Self::resolve_account(&db_read, account_id).await?;
 
         let order_value = request.quantity * request.price;
         let slippage = self.calculate_slippage(order_value);
@@ -818,9 +1070,10 @@ impl PaperTradingManager {
             .map_err(|e| format!("Failed to update paper balance: {e}"))?;
 
         account = db_read
-            .get_or_create_account(DEFAULT_INITIAL_BALANCE)
+            .get_account(&account.id)
             .await
-            .map_err(|e| format!("Failed to reload paper account: {e}"))?;
+            .map_err(|e| format!("Failed to reload paper account: {e}"))?
+            .ok_or_else(|| "Paper account was deleted during the trade".to_string())?;
 
         let trade = PaperTrade {
             id: Uuid::new_v4().to_string(),
@@ -848,6 +1101,19 @@ impl PaperTradingManager {
             .update_position(&db_read, &account.id, &request, execution_price)
             .await?;
 
+        publish_order_event(OrderEvent::new(
+            OrderEventSource::Paper,
+            &trade.id,
+            &trade.symbol,
+            request.side,
+            trade.quantity,
+            OrderEventKind::Filled {
+                filled_amount: trade.quantity,
+                fill_price: Some(execution_price),
+                tx_signature: None,
+            },
+        ));
+
         Ok(PaperTradeResult {
             fees: trade.fee_breakdown(),
             trade,
@@ -956,31 +1222,102 @@ impl PaperTradingManager {
         }
     }
 
-    pub async fn get_account(&self) -> Result<PaperAccount, String> {
+    pub async fn get_account(&self, account_id: Option<&str>) -> Result<PaperAccount, String> {
+        let db_read = self.db.read().await;
+        Self::resolve_account(&db_read, account_id).await
+    }
+
+    /// Like `get_account`, but reports a missing account as `None` instead
+    /// of an error.
+    pub async fn find_account(&self, account_id: &str) -> Result<Option<PaperAccount>, String> {
         let db_read = self.db.read().await;
         db_read
-            .get_or_create_account(DEFAULT_INITIAL_BALANCE)
+            .get_account(account_id)
             .await
             .map_err(|e| format!("Failed to load paper account: {e}"))
     }
 
+    pub async fn list_accounts(&self) -> Result<Vec<PaperAccount>, String> {
+        let db_read = self.db.read().await;
+        db_read
+            .get_or_create_primary(DEFAULT_INITIAL_BALANCE)
+            .await
+            .map_err(|e| format!("Failed to load paper account: {e}"))?;
+        db_read
+            .list_accounts()
+            .await
+            .map_err(|e| format!("Failed to list paper accounts: {e}"))
+    }
+
+    pub async fn create_account(
+        &self,
+        name: &str,
+        starting_balance: Option<f64>,
+    ) -> Result<PaperAccount, String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Paper account name cannot be empty".to_string());
+        }
+        let starting_balance = starting_balance.unwrap_or(DEFAULT_INITIAL_BALANCE);
+        if !starting_balance.is_finite() || starting_balance <= 0.0 {
+            return Err("Starting balance must be greater than zero".to_string());
+        }
+
+        let db_read = self.db.read().await;
+        db_read
+            .get_or_create_primary(DEFAULT_INITIAL_BALANCE)
+            .await
+            .map_err(|e| format!("Failed to load paper account: {e}"))?;
+        if db_read
+            .find_account_by_name(name)
+            .await
+            .map_err(|e| format!("Failed to check paper account names: {e}"))?
+            .is_some()
+        {
+            return Err(format!("A paper account named '{name}' already exists"));
+        }
+
+        db_read
+            .create_account(name, starting_balance)
+            .await
+            .map_err(|e| format!("Failed to create paper account: {e}"))
+    }
+
+    pub async fn delete_account(&self, account_id: &str) -> Result<(), String> {
+        let db_read = self.db.read().await;
+        let account = Self::resolve_account(&db_read, Some(account_id)).await?;
+        if account.is_primary {
+            return Err("The primary paper account cannot be deleted".to_string());
+        }
+
+        db_read
+            .delete_account(&account.id)
+            .await
+            .map_err(|e| format!("Failed to delete paper account: {e}"))
+    }
+
     pub async fn reset_account(
         &self,
         initial_balance: Option<f64>,
+        account_id: Option<&str>,
     ) -> Result<PaperAccount, String> {
         let db_read = self.db.read().await;
+        let account = Self::resolve_account(&db_read, account_id).await?;
         db_read
-            .reset_account(initial_balance.unwrap_or(DEFAULT_INITIAL_BALANCE))
+            .reset_account(
+                &account.id,
+                initial_balance.unwrap_or(account.initial_balance),
+            )
             .await
             .map_err(|e| format!("Failed to reset paper account: {e}"))
     }
 
-    pub async fn get_positions(&self) -> Result<Vec<PaperPosition>, String> {
+    pub async fn get_positions(
+        &self,
+        account_id: Option<&str>,
+    ) -> Result<Vec<PaperPosition>, String> {
         let db_read = self.db.read().await;
-        let account = db_read
-            .get_or_create_account(DEFAULT_INITIAL_BALANCE)
-            .await
-            .map_err(|e| format!("Failed to load paper account: {e}"))?;
+        let account = Self::resolve_account(&db_read, account_id).await?;
 
         db_read
             .get_all_positions(&account.id)
@@ -988,12 +1325,12 @@ impl PaperTradingManager {
             .map_err(|e| format!("Failed to load paper positions: {e}"))
     }
 
-    pub async fn get_trade_history(&self) -> Result<Vec<PaperTrade>, String> {
+    pub async fn get_trade_history(
+        &self,
+        account_id: Option<&str>,
+    ) -> Result<Vec<PaperTrade>, String> {
         let db_read = self.db.read().await;
-        let account = db_read
-            .get_or_create_account(DEFAULT_INITIAL_BALANCE)
-            .await
-            .map_err(|e| format!("Failed to load paper account: {e}"))?;
+        let account = Self::resolve_account(&db_read, account_id).await?;
 
         db_read
             .get_trade_history(&account.id)
@@ -1001,12 +1338,12 @@ impl PaperTradingManager {
             .map_err(|e| format!("Failed to load paper trade history: {e}"))
     }
 
-    pub async fn get_performance(&self) -> Result<PaperPerformance, String> {
+    pub async fn get_performance(
+        &self,
+        account_id: Option<&str>,
+    ) -> Result<PaperPerformance, String> {
         let db_read = self.db.read().await;
-        let account = db_read
-            .get_or_create_account(DEFAULT_INITIAL_BALANCE)
-            .await
-            .map_err(|e| format!("Failed to load paper account: {e}"))?;
+        let account = Self::resolve_account(&db_read, account_id).await?;
 
         db_read
             .get_performance(&account.id)
@@ -1014,34 +1351,198 @@ impl PaperTradingManager {
             .map_err(|e| format!("Failed to load paper performance: {e}"))
     }
 
-    pub async fn update_position_prices(&self, symbol: &str, price: f64) -> Result<(), String> {
+    /// Marks positions in `symbol` to `price`. Prices are market-wide, so
+    /// every account is updated unless `account_id` narrows it to one.
+    pub async fn update_position_prices(
+        &self,
+        symbol: &str,
+        price: f64,
+        account_id: Option<&str>,
+    ) -> Result<(), String> {
         self.current_prices
             .write()
             .await
             .insert(symbol.to_string(), price);
 
         let db_read = self.db.read().await;
-        let account = db_read
-            .get_or_create_account(DEFAULT_INITIAL_BALANCE)
-            .await
-            .map_err(|e| format!("Failed to load paper account: {e}"))?;
-
-        if let Some(mut position) = db_read
-            .get_position(&account.id, symbol)
-            .await
-            .map_err(|e| format!("Failed to load paper position: {e}"))?
-        {
-            position.current_price = price;
-            position.unrealized_pnl = (price - position.entry_price) * position.quantity;
+        let positions = match account_id {
+            Some(_) => {
+                let account = Self::resolve_account(&db_read, account_id).await?;
+                db_read
+                    .get_position(&account.id, symbol)
+                    .await
+                    .map_err(|e| format!("Failed to load paper position: {e}"))?
+                    .into_iter()
+                    .collect()
+            }
+            None => db_read
+                .get_positions_for_symbol(symbol)
+                .await
+                .map_err(|e| format!("Failed to load paper positions: {e}"))?,
+        };
 
+        for position in positions {
+            let unrealized_pnl = (price - position.entry_price) * position.quantity;
             db_read
-                .update_position_price(&position.id, price, position.unrealized_pnl)
+                .update_position_price(&position.id, price, unrealized_pnl)
                 .await
                 .map_err(|e| format!("Failed to update paper position price: {e}"))?;
         }
 
         Ok(())
     }
+
+    /// Builds equity curves for `account_ids` over the last `window_days`
+    /// days, sampled on a shared time grid so the accounts line up.
+    pub async fn compare_accounts(
+        &self,
+        account_ids: &[String],
+        window_days: u32,
+    ) -> Result<PaperAccountComparison, String> {
+        if account_ids.is_empty() {
+            return Err("Select at least one paper account to compare".to_string());
+        }
+        if window_days == 0 {
+            return Err("Comparison window must be at least one day".to_string());
+        }
+
+        let window_end = Utc::now();
+        let window_start = window_end - chrono::Duration::days(window_days as i64);
+        let timestamps = comparison_grid(window_start, window_end, COMPARISON_POINTS);
+
+        let db_read = self.db.read().await;
+        let mut accounts = Vec::with_capacity(account_ids.len());
+        for id in account_ids {
+            let account = Self::resolve_account(&db_read, Some(id.as_str())).await?;
+            let trades = db_read
+                .get_trade_history(&account.id)
+                .await
+                .map_err(|e| format!("Failed to load paper trade history: {e}"))?;
+            let positions = db_read
+                .get_all_positions(&account.id)
+                .await
+                .map_err(|e| format!("Failed to load paper positions: {e}"))?;
+            let performance = db_read
+                .get_performance(&account.id)
+                .await
+                .map_err(|e| format!("Failed to load paper performance: {e}"))?;
+
+            let marks: HashMap<String, f64> = positions
+                .iter()
+                .map(|position| (position.symbol.clone(), position.current_price))
+                .collect();
+            let equity = equity_curve(account.initial_balance, &trades, &marks, &timestamps);
+            let windowed: Vec<&PaperTrade> = trades
+                .iter()
+                .filter(|trade| trade.timestamp >= window_start)
+                .collect();
+
+            let starting_equity = equity.first().copied().unwrap_or(account.balance);
+            let ending_equity = equity.last().copied().unwrap_or(account.balance);
+            let summary = PaperAccountSummary {
+                starting_equity,
+                ending_equity,
+                return_percentage: if starting_equity > 0.0 {
+                    (ending_equity - starting_equity) / starting_equity * 100.0
+                } else {
+                    0.0
+                },
+                max_drawdown_percentage: max_drawdown_percentage(&equity),
+                trades: windowed.len(),
+                fees: windowed.iter().map(|trade| trade.fee).sum(),
+                win_rate: performance.win_rate,
+            };
+
+            accounts.push(PaperAccountCurve {
+                account_id: account.id,
+                name: account.name,
+                equity,
+                summary,
+            });
+        }
+
+        Ok(PaperAccountComparison {
+            window_start,
+            window_end,
+            timestamps,
+            accounts,
+        })
+    }
+}
+
+fn comparison_grid(start: DateTime<Utc>, end: DateTime<Utc>, points: usize) -> Vec<DateTime<Utc>> {
+    let span = (end - start).num_seconds().max(0);
+    let steps = points.max(2) as i64 - 1;
+    (0..=steps)
+        .map(|step| start + chrono::Duration::seconds(span * step / steps))
+        .collect()
+}
+
+/// Replays `trades` to value the account at each grid timestamp: cash plus
+/// holdings marked at the last traded price. The final sample uses `marks`
+/// (the positions' current prices) so the curve ends at today's equity.
+fn equity_curve(
+    initial_balance: f64,
+    trades: &[PaperTrade],
+    marks: &HashMap<String, f64>,
+    timestamps: &[DateTime<Utc>],
+) -> Vec<f64> {
+    let mut ordered: Vec<&PaperTrade> = trades.iter().collect();
+    ordered.sort_by_key(|trade| trade.timestamp);
+
+    let mut cash = initial_balance;
+    let mut holdings: HashMap<&str, f64> = HashMap::new();
+    let mut last_price: HashMap<&str, f64> = HashMap::new();
+    let mut next = 0;
+    let mut curve = Vec::with_capacity(timestamps.len());
+
+    for (index, at) in timestamps.iter().enumerate() {
+        while next < ordered.len() && ordered[next].timestamp <= *at {
+            let trade = ordered[next];
+            let quantity = holdings.entry(trade.symbol.as_str()).or_insert(0.0);
+            match trade.side.as_str() {
+                "buy" => {
+                    cash -= trade.total_cost;
+                    *quantity += trade.quantity;
+                }
+                "sell" => {
+                    cash += trade.total_cost;
+                    *quantity = (*quantity - trade.quantity).max(0.0);
+                }
+                _ => {}
+            }
+            last_price.insert(trade.symbol.as_str(), trade.price);
+            next += 1;
+        }
+
+        let is_last = index + 1 == timestamps.len();
+        let holdings_value: f64 = holdings
+            .iter()
+            .map(|(symbol, quantity)| {
+                let price = if is_last {
+                    marks.get(*symbol).or_else(|| last_price.get(symbol))
+                } else {
+                    last_price.get(symbol)
+                };
+                quantity * price.copied().unwrap_or(0.0)
+            })
+            .sum();
+        curve.push(cash + holdings_value);
+    }
+
+    curve
+}
+
+fn max_drawdown_percentage(curve: &[f64]) -> f64 {
+    let mut peak = f64::MIN;
+    let mut worst = 0.0_f64;
+    for value in curve {
+        peak = peak.max(*value);
+        if peak > 0.0 {
+            worst = worst.max((peak - value) / peak * 100.0);
+        }
+    }
+    worst
 }
 
 pub type SharedPaperTradingManager = Arc<PaperTradingManager>;
@@ -1058,8 +1559,7 @@ pub async fn init_paper_trading(app_handle: &AppHandle) -> Result<(), String> {
     }
 
     let app_dir = app_handle
-        .path()
-        .app_data_dir()
+        .app_data_root()
         .map_err(|e| format!("Failed to resolve app data directory: {}", e))?;
 
     std::fs::create_dir_all(&app_dir)
@@ -1082,7 +1582,7 @@ pub async fn init_paper_trading(app_handle: &AppHandle) -> Result<(), String> {
     Ok(())
 }
 
-fn require_state() -> Result<&'static SharedPaperTradingManager, String> {
+pub fn require_state() -> Result<&'static SharedPaperTradingManager, String> {
     PAPER_TRADING_STATE
         .get()
         .ok_or_else(|| "Paper trading module not initialized".to_string())
@@ -1097,48 +1597,96 @@ pub async fn paper_trading_init(handle: AppHandle) -> Result<(), String> {
     init_paper_trading(&handle).await
 }
 
+// Every command below accepts an optional `account_id`; when it is omitted
+// the primary account is used, which keeps single-account callers working.
+
 #[tauri::command]
-pub async fn get_paper_account() -> Result<PaperAccount, String> {
+pub async fn get_paper_account(account_id: Option<String>) -> Result<PaperAccount, String> {
     let manager = require_state()?;
-    manager.get_account().await
+    manager.get_account(account_id.as_deref()).await
 }
 
 #[tauri::command]
-pub async fn reset_paper_account(initial_balance: Option<f64>) -> Result<PaperAccount, String> {
+pub async fn reset_paper_account(
+    initial_balance: Option<f64>,
+    account_id: Option<String>,
+) -> Result<PaperAccount, String> {
     let manager = require_state()?;
-    manager.reset_account(initial_balance).await
+    manager
+        .reset_account(initial_balance, account_id.as_deref())
+        .await
 }
 
 #[tauri::command]
 pub async fn execute_paper_trade(
     request: ExecutePaperTradeRequest,
+    account_id: Option<String>,
 ) -> Result<PaperTradeResult, String> {
     let manager = require_state()?;
-    manager.execute_trade(request).await
+    manager.execute_trade(request, account_id.as_deref()).await
 }
 
 #[tauri::command]
-pub async fn get_paper_positions() -> Result<Vec<PaperPosition>, String> {
+pub async fn get_paper_positions(account_id: Option<String>) -> Result<Vec<PaperPosition>, String> {
     let manager = require_state()?;
-    manager.get_positions().await
+    manager.get_positions(account_id.as_deref()).await
 }
 
 #[tauri::command]
-pub async fn get_paper_trade_history() -> Result<Vec<PaperTrade>, String> {
+pub async fn get_paper_trade_history(
+    account_id: Option<String>,
+) -> Result<Vec<PaperTrade>, String> {
     let manager = require_state()?;
-    manager.get_trade_history().await
+    manager.get_trade_history(account_id.as_deref()).await
 }
 
 #[tauri::command]
-pub async fn get_paper_performance() -> Result<PaperPerformance, String> {
+pub async fn get_paper_performance(account_id: Option<String>) -> Result<PaperPerformance, String> {
     let manager = require_state()?;
-    manager.get_performance().await
+    manager.get_performance(account_id.as_deref()).await
 }
 
 #[tauri::command]
-pub async fn update_paper_position_prices(symbol: String, price: f64) -> Result<(), String> {
+pub async fn update_paper_position_prices(
+    symbol: String,
+    price: f64,
+    account_id: Option<String>,
+) -> Result<(), String> {
+    let manager = require_state()?;
+    manager
+        .update_position_prices(&symbol, price, account_id.as_deref())
+        .await
+}
+
+#[tauri::command]
+pub async fn paper_account_create(
+    name: String,
+    starting_balance: Option<f64>,
+) -> Result<PaperAccount, String> {
+    let manager = require_state()?;
+    manager.create_account(&name, starting_balance).await
+}
+
+#[tauri::command]
+pub async fn paper_account_list() -> Result<Vec<PaperAccount>, String> {
     let manager = require_state()?;
-    manager.update_position_prices(&symbol, price).await
+    manager.list_accounts().await
+}
+
+#[tauri::command]
+pub async fn paper_account_delete(account_id: String) -> Result<(), String> {
+    let manager = require_state()?;
+    manager.delete_account(&account_id).await
+}
+
+/// `window` is the look-back in days.
+#[tauri::command]
+pub async fn paper_accounts_compare(
+    ids: Vec<String>,
+    window: u32,
+) -> Result<PaperAccountComparison, String> {
+    let manager = require_state()?;
+    manager.compare_accounts(&ids, window).await
 }
 
 pub fn register_paper_trading_state(app: &AppHandle) {
@@ -1193,7 +1741,7 @@ mod tests {
             create_manager_with_configs(deterministic_slippage_config(), FeeConfig::default())
                 .await;
 
-        let initial_account = manager.get_account().await.expect("account load");
+        let initial_account = manager.get_account(None).await.expect("account load");
         assert_eq!(initial_account.initial_balance, DEFAULT_INITIAL_BALANCE);
         assert_eq!(initial_account.balance, DEFAULT_INITIAL_BALANCE);
 
@@ -1205,10 +1753,11 @@ mod tests {
             price: 100.0,
             limit_price: None,
             stop_price: None,
+            client_request_id: None,
         };
 
         let result = manager
-            .execute_trade(request)
+            .execute_trade(request, None)
             .await
             .expect("trade execution");
 
@@ -1248,10 +1797,11 @@ mod tests {
             price: 100.0,
             limit_price: None,
             stop_price: None,
+            client_request_id: None,
         };
 
         let result = manager
-            .execute_trade(request)
+            .execute_trade(request, None)
             .await
             .expect("trade execution");
 
@@ -1279,9 +1829,10 @@ mod tests {
             price: 100.0,
             limit_price: None,
             stop_price: None,
+            client_request_id: None,
         };
         manager
-            .execute_trade(buy_request)
+            .execute_trade(buy_request, None)
             .await
             .expect("buy execution");
 
@@ -1293,9 +1844,10 @@ mod tests {
             price: 110.0,
             limit_price: None,
             stop_price: None,
+            client_request_id: None,
         };
         let sell_result = manager
-            .execute_trade(sell_request)
+            .execute_trade(sell_request, None)
             .await
             .expect("sell execution");
 
@@ -1321,9 +1873,10 @@ mod tests {
             price: 50.0,
             limit_price: None,
             stop_price: None,
+            client_request_id: None,
         };
         manager
-            .execute_trade(buy_request)
+            .execute_trade(buy_request, None)
             .await
             .expect("buy execution");
 
@@ -1335,15 +1888,201 @@ mod tests {
             price: 60.0,
             limit_price: None,
             stop_price: None,
+            client_request_id: None,
         };
         manager
-            .execute_trade(sell_request)
+            .execute_trade(sell_request, None)
             .await
             .expect("sell execution");
 
-        let performance = manager.get_performance().await.expect("performance");
+        let performance = manager.get_performance(None).await.expect("performance");
 
         assert_eq!(performance.total_trades, 2);
         assert!(performance.total_pnl > 0.0);
     }
+
+    #[tokio::test]
+    async fn test_retried_trade_executes_once() {
+        let manager = Arc::new(
+            create_manager_with_configs(deterministic_slippage_config(), FeeConfig::default())
+                .await,
+        );
+
+        let request = ExecutePaperTradeRequest {
+            symbol: "SOL".to_string(),
+            side: OrderSide::Buy,
+            order_type: OrderType::Market,
+            quantity: 1.0,
+            price: 100.0,
+            limit_price: None,
+            stop_price: None,
+            client_request_id: Some(Uuid::new_v4().to_string()),
+        };
+
+        let attempts = (0..10).map(|_| {
+            let (manager, request) = (manager.clone(), request.clone());
+            tokio::spawn(async move { manager.execute_trade(request, None).await })
+        });
+        let trade_ids: Vec<String> = futures_util::future::join_all(attempts)
+            .await
+            .into_iter()
+            .map(|joined| joined.unwrap().expect("trade execution").trade.id)
+            .collect();
+
+        assert!(trade_ids.iter().all(|id| id == &trade_ids[0]));
+        let history = manager.get_trade_history(None).await.expect("history");
+        assert_eq!(history.len(), 1);
+    }
+
+    fn market_buy(symbol: &str, quantity: f64, price: f64) -> ExecutePaperTradeRequest {
+        ExecutePaperTradeRequest {
+            symbol: symbol.to_string(),
+            side: OrderSide::Buy,
+            order_type: OrderType::Market,
+            quantity,
+            price,
+            limit_price: None,
+            stop_price: None,
+            client_request_id: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_accounts_track_positions_separately() {
+        let manager =
+            create_manager_with_configs(deterministic_slippage_config(), FeeConfig::default())
+                .await;
+
+        let swing = manager
+            .create_account("Swing", Some(2_500.0))
+            .await
+            .expect("create account");
+        assert!(!swing.is_primary);
+        assert!(manager.create_account("swing", None).await.is_err());
+
+        manager
+            .execute_trade(market_buy("SOL", 1.0, 100.0), Some(swing.id.as_str()))
+            .await
+            .expect("trade on named account");
+
+        let primary = manager.get_account(None).await.expect("primary account");
+        assert!(primary.is_primary);
+        assert_eq!(primary.balance, DEFAULT_INITIAL_BALANCE);
+        assert!(manager.get_positions(None).await.unwrap().is_empty());
+        assert_eq!(
+            manager
+                .get_positions(Some(swing.id.as_str()))
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        let accounts = manager.list_accounts().await.expect("list accounts");
+        assert_eq!(accounts.len(), 2);
+        assert!(accounts[0].is_primary);
+
+        assert!(manager.delete_account(&primary.id).await.is_err());
+        manager
+            .delete_account(&swing.id)
+            .await
+            .expect("delete account");
+        assert!(manager.get_account(Some(swing.id.as_str())).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_reset_is_scoped_to_one_account() {
+        let manager =
+            create_manager_with_configs(deterministic_slippage_config(), FeeConfig::default())
+                .await;
+        let scalp = manager
+            .create_account("Scalp", None)
+            .await
+            .expect("create account");
+
+        manager
+            .execute_trade(market_buy("SOL", 1.0, 100.0), None)
+            .await
+            .expect("primary trade");
+        manager
+            .execute_trade(market_buy("BONK", 10.0, 1.0), Some(scalp.id.as_str()))
+            .await
+            .expect("named trade");
+
+        let reset = manager
+            .reset_account(Some(500.0), Some(scalp.id.as_str()))
+            .await
+            .expect("reset");
+        assert_eq!(reset.id, scalp.id);
+        assert_eq!(reset.balance, 500.0);
+        assert!(reset.reset_at.is_some());
+        assert!(manager
+            .get_trade_history(Some(scalp.id.as_str()))
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(manager.get_trade_history(None).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_legacy_accounts_migrate_into_primary() {
+        let db_path = temp_db_path();
+        let url = format!("sqlite:{}?mode=rwc", db_path.display());
+        let pool = SqlitePool::connect(&url).await.expect("legacy pool");
+        sqlx::query(
+            "CREATE TABLE paper_accounts (id TEXT PRIMARY KEY, balance REAL NOT NULL, \
+             initial_balance REAL NOT NULL, created_at TEXT NOT NULL, updated_at TEXT NOT NULL)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        for (id, created_at) in [
+            ("old", "2024-01-01T00:00:00+00:00"),
+            ("new", "2024-02-01T00:00:00+00:00"),
+        ] {
+            sqlx::query("INSERT INTO paper_accounts VALUES (?1, 9000.0, 10000.0, ?2, ?2)")
+                .bind(id)
+                .bind(created_at)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+        pool.close().await;
+
+        let database = PaperTradingDatabase::new(db_path)
+            .await
+            .expect("migrated database");
+        let accounts = database.list_accounts().await.unwrap();
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(accounts[0].id, "new");
+        assert!(accounts[0].is_primary);
+        assert_eq!(accounts[0].name, PRIMARY_ACCOUNT_NAME);
+    }
+
+    #[test]
+    fn test_equity_curve_replays_trades_on_grid() {
+        let start = Utc::now() - chrono::Duration::days(2);
+        let grid = comparison_grid(start, start + chrono::Duration::days(2), 3);
+        let trade = PaperTrade {
+            id: "t1".to_string(),
+            account_id: "a".to_string(),
+            symbol: "SOL".to_string(),
+            side: "buy".to_string(),
+            order_type: "market".to_string(),
+            quantity: 10.0,
+            price: 100.0,
+            trading_fee: 0.0,
+            network_fee: 0.0,
+            price_impact_fee: 0.0,
+            fee: 0.0,
+            slippage: 0.0,
+            total_cost: 1_000.0,
+            timestamp: start + chrono::Duration::hours(12),
+        };
+        let marks = HashMap::from([("SOL".to_string(), 80.0)]);
+
+        let curve = equity_curve(5_000.0, &[trade], &marks, &grid);
+        assert_eq!(curve, vec![5_000.0, 5_000.0, 4_800.0]);
+        assert!((max_drawdown_percentage(&curve) - 4.0).abs() < 1e-9);
+    }
 }
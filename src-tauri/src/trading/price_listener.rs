@@ -1,3 +1,4 @@
+use crate::errors::AppError;
 use serde::{Deserialize, Serialize};
 use tauri::AppHandle;
 
@@ -15,7 +16,7 @@ pub async fn start_price_listener(_app_handle: AppHandle) {
 }
 
 #[tauri::command]
-pub async fn update_order_prices(symbol: String, price: f64) -> Result<(), String> {
+pub async fn update_order_prices(symbol: String, price: f64) -> Result<(), AppError> {
     use crate::trading::limit_orders::require_state;
 
     let state = require_state()?;
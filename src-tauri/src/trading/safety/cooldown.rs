@@ -1,71 +1,346 @@
+//! Trade cooldowns and daily trade counts, tracked per wallet and per scope.
+//!
+//! Counters live in memory and, once a `CooldownStore` is attached, are
+//! written through to the `safety_counters` table so they survive restarts.
+//! Daily counts roll over at midnight UTC.
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Row, Sqlite, SqlitePool};
 use std::collections::HashMap;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::fmt;
+use std::path::PathBuf;
+
+/// What a limit applies to. A trade falls under the global scope, the scope of
+/// the token it trades and, when placed by a strategy, that strategy's scope.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "id", rename_all = "snake_case")]
+pub enum SafetyScope {
+    Global,
+    Token(String),
+    Strategy(String),
+}
+
+impl SafetyScope {
+    pub fn key(&self) -> String {
+        match self {
+            SafetyScope::Global => "global".to_string(),
+            SafetyScope::Token(mint) => format!("token:{}", mint),
+            SafetyScope::Strategy(id) => format!("strategy:{}", id),
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        match key.split_once(':') {
+            None if key == "global" => Some(SafetyScope::Global),
+            Some(("token", mint)) => Some(SafetyScope::Token(mint.to_string())),
+            Some(("strategy", id)) => Some(SafetyScope::Strategy(id.to_string())),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for SafetyScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SafetyScope::Global => write!(f, "global"),
+            SafetyScope::Token(mint) => write!(f, "token {}", mint),
+            SafetyScope::Strategy(id) => write!(f, "strategy {}", id),
+        }
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CooldownStatus {
     pub wallet_address: String,
+    pub scope: SafetyScope,
     pub cooldown_seconds: u64,
     pub remaining_seconds: u64,
     pub last_trade_timestamp: u64,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScopeCounter {
+    pub last_trade_at: Option<DateTime<Utc>>,
+    pub daily_trades: u32,
+    /// UTC day `daily_trades` counts.
+    pub day: NaiveDate,
+}
+
+impl ScopeCounter {
+    fn new(now: DateTime<Utc>) -> Self {
+        Self {
+            last_trade_at: None,
+            daily_trades: 0,
+            day: now.date_naive(),
+        }
+    }
+
+    pub fn trades_on(&self, day: NaiveDate) -> u32 {
+        if self.day == day {
+            self.daily_trades
+        } else {
+            0
+        }
+    }
+}
+
+/// Counters for one wallet and scope.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScopeCounterEntry {
+    pub wallet_address: String,
+    pub scope: SafetyScope,
+    pub counter: ScopeCounter,
+}
+
+/// The `safety_counters` table.
+#[derive(Clone)]
+pub struct CooldownStore {
+    pool: Pool<Sqlite>,
+}
+
+impl CooldownStore {
+    pub async fn new(db_path: PathBuf) -> Result<Self, sqlx::Error> {
+        let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+        let pool = SqlitePool::connect(&db_url).await?;
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS safety_counters (
+                wallet_address TEXT NOT NULL,
+                scope TEXT NOT NULL,
+                last_trade_at TEXT,
+                daily_trades INTEGER NOT NULL DEFAULT 0,
+                day TEXT NOT NULL,
+                PRIMARY KEY (wallet_address, scope)
+            )
+            "#,
+        )
+        .execute(&pool)
+        .await?;
+        Ok(Self { pool })
+    }
+
+    pub async fn load(&self) -> Result<Vec<ScopeCounterEntry>, sqlx::Error> {
+        let rows = sqlx::query(
+            "SELECT wallet_address, scope, last_trade_at, daily_trades, day FROM safety_counters",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| {
+                let scope = SafetyScope::from_key(&row.get::<String, _>("scope"))?;
+                let last_trade_at = row
+                    .get::<Option<String>, _>("last_trade_at")
+                    .and_then(|t| DateTime::parse_from_rfc3339(&t).ok())
+                    .map(|t| t.with_timezone(&Utc));
+                let day =
+                    NaiveDate::parse_from_str(&row.get::<String, _>("day"), "%Y-%m-%d").ok()?;
+                Some(ScopeCounterEntry {
+                    wallet_address: row.get("wallet_address"),
+                    scope,
+                    counter: ScopeCounter {
+                        last_trade_at,
+                        daily_trades: row.get::<i64, _>("daily_trades") as u32,
+                        day,
+                    },
+                })
+            })
+            .collect())
+    }
+
+    pub async fn save(&self, entries: &[ScopeCounterEntry]) -> Result<(), sqlx::Error> {
+        for entry in entries {
+            sqlx::query(
+                r#"
+                INSERT INTO safety_counters (wallet_address, scope, last_trade_at, daily_trades, day)
+                VALUES (?1, ?2, ?3, ?4, ?5)
+                ON CONFLICT(wallet_address, scope) DO UPDATE SET
+                    last_trade_at = excluded.last_trade_at,
+                    daily_trades = excluded.daily_trades,
+                    day = excluded.day
+                "#,
+            )
+            .bind(&entry.wallet_address)
+            .bind(entry.scope.key())
+            .bind(entry.counter.last_trade_at.map(|t| t.to_rfc3339()))
+            .bind(entry.counter.daily_trades as i64)
+            .bind(entry.counter.day.format("%Y-%m-%d").to_string())
+            .execute(&self.pool)
+            .await?;
+        }
+        Ok(())
+    }
+}
+
+/// Which counters a reset clears.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CounterReset {
+    Cooldowns,
+    DailyCounts,
+    All,
 }
 
 #[derive(Debug, Default)]
 pub struct CooldownManager {
-    cooldowns: HashMap<String, SystemTime>,
-    cooldown_duration: Duration,
+    counters: HashMap<(String, SafetyScope), ScopeCounter>,
+    cooldown_duration: std::time::Duration,
 }
 
 impl CooldownManager {
     pub fn new(cooldown_seconds: u64) -> Self {
         Self {
-            cooldowns: HashMap::new(),
-            cooldown_duration: Duration::from_secs(cooldown_seconds),
+            counters: HashMap::new(),
+            cooldown_duration: std::time::Duration::from_secs(cooldown_seconds),
         }
     }
 
     pub fn set_cooldown_duration(&mut self, seconds: u64) {
-        self.cooldown_duration = Duration::from_secs(seconds);
+        self.cooldown_duration = std::time::Duration::from_secs(seconds);
+    }
+
+    /// Global cooldown length.
+    pub fn cooldown_seconds(&self) -> u64 {
+        self.cooldown_duration.as_secs()
+    }
+
+    pub fn restore(&mut self, entries: Vec<ScopeCounterEntry>) {
+        for entry in entries {
+            self.counters
+                .insert((entry.wallet_address, entry.scope), entry.counter);
+        }
     }
 
     pub fn record_trade(&mut self, wallet_address: &str) {
-        self.cooldown_duration = self.cooldown_duration;
-        self.cooldowns
-            .insert(wallet_address.to_string(), SystemTime::now());
+        self.record_scoped_trade(wallet_address, &[SafetyScope::Global], Utc::now());
+    }
+
+    /// Starts the cooldown and counts the trade in every scope. Returns the
+    /// updated counters.
+    pub fn record_scoped_trade(
+        &mut self,
+        wallet_address: &str,
+        scopes: &[SafetyScope],
+        now: DateTime<Utc>,
+    ) -> Vec<ScopeCounterEntry> {
+        scopes
+            .iter()
+            .map(|scope| {
+                let counter = self
+                    .counters
+                    .entry((wallet_address.to_string(), scope.clone()))
+                    .or_insert_with(|| ScopeCounter::new(now));
+                counter.daily_trades = counter.trades_on(now.date_naive()) + 1;
+                counter.day = now.date_naive();
+                counter.last_trade_at = Some(now);
+                ScopeCounterEntry {
+                    wallet_address: wallet_address.to_string(),
+                    scope: scope.clone(),
+                    counter: *counter,
+                }
+            })
+            .collect()
     }
 
     pub fn is_on_cooldown(&self, wallet_address: &str) -> bool {
-        if let Some(&last_trade) = self.cooldowns.get(wallet_address) {
-            if let Ok(elapsed) = last_trade.elapsed() {
-                return elapsed < self.cooldown_duration;
-            }
-        }
-        false
+        self.get_remaining_cooldown(wallet_address).is_some()
     }
 
+    /// Remaining global cooldown.
     pub fn get_remaining_cooldown(&self, wallet_address: &str) -> Option<CooldownStatus> {
-        self.cooldowns.get(wallet_address).and_then(|last_trade| {
-            let now = SystemTime::now();
-            if let Ok(elapsed) = now.duration_since(*last_trade) {
-                if elapsed < self.cooldown_duration {
-                    let remaining = self.cooldown_duration - elapsed;
-                    let last_trade_timestamp = last_trade
-                        .duration_since(UNIX_EPOCH)
-                        .unwrap_or_else(|_| Duration::from_secs(0));
-                    return Some(CooldownStatus {
-                        wallet_address: wallet_address.to_string(),
-                        cooldown_seconds: self.cooldown_duration.as_secs(),
-                        remaining_seconds: remaining.as_secs(),
-                        last_trade_timestamp: last_trade_timestamp.as_secs(),
-                    });
-                }
-            }
-            None
+        self.remaining(
+            wallet_address,
+            &SafetyScope::Global,
+            self.cooldown_seconds(),
+            Utc::now(),
+        )
+    }
+
+    pub fn remaining(
+        &self,
+        wallet_address: &str,
+        scope: &SafetyScope,
+        cooldown_seconds: u64,
+        now: DateTime<Utc>,
+    ) -> Option<CooldownStatus> {
+        let counter = self
+            .counters
+            .get(&(wallet_address.to_string(), scope.clone()))?;
+        let last_trade = counter.last_trade_at?;
+        let expires_at = last_trade + Duration::seconds(cooldown_seconds as i64);
+        if expires_at <= now {
+            return None;
+        }
+        Some(CooldownStatus {
+            wallet_address: wallet_address.to_string(),
+            scope: scope.clone(),
+            cooldown_seconds,
+            // Rounded up so a blocked trade never reports zero seconds left.
+            remaining_seconds: ((expires_at - now).num_milliseconds() as u64).div_ceil(1000),
+            last_trade_timestamp: last_trade.timestamp().max(0) as u64,
+            expires_at,
         })
     }
 
+    pub fn daily_trades(
+        &self,
+        wallet_address: &str,
+        scope: &SafetyScope,
+        now: DateTime<Utc>,
+    ) -> u32 {
+        self.counters
+            .get(&(wallet_address.to_string(), scope.clone()))
+            .map(|c| c.trades_on(now.date_naive()))
+            .unwrap_or(0)
+    }
+
+    pub fn entries(&self, wallet_address: &str) -> Vec<ScopeCounterEntry> {
+        self.counters
+            .iter()
+            .filter(|((wallet, _), _)| wallet == wallet_address)
+            .map(|((wallet, scope), counter)| ScopeCounterEntry {
+                wallet_address: wallet.clone(),
+                scope: scope.clone(),
+                counter: *counter,
+            })
+            .collect()
+    }
+
+    /// Clears counters matching `wallet_address` and `scope` (all when
+    /// `None`). Returns the cleared counters.
+    pub fn reset(
+        &mut self,
+        wallet_address: Option<&str>,
+        scope: Option<&SafetyScope>,
+        reset: CounterReset,
+    ) -> Vec<ScopeCounterEntry> {
+        self.counters
+            .iter_mut()
+            .filter(|((wallet, counter_scope), _)| {
+                wallet_address.map_or(true, |w| w == wallet.as_str())
+                    && scope.map_or(true, |s| s == counter_scope)
+            })
+            .map(|((wallet, counter_scope), counter)| {
+                if reset != CounterReset::DailyCounts {
+                    counter.last_trade_at = None;
+                }
+                if reset != CounterReset::Cooldowns {
+                    counter.daily_trades = 0;
+                }
+                ScopeCounterEntry {
+                    wallet_address: wallet.clone(),
+                    scope: counter_scope.clone(),
+                    counter: *counter,
+                }
+            })
+            .collect()
+    }
+
     pub fn clear(&mut self) {
-        self.cooldowns.clear();
+        self.counters.clear();
     }
 }
 
@@ -80,7 +355,7 @@ mod tests {
         manager.record_trade("wallet1");
         assert!(manager.is_on_cooldown("wallet1"));
 
-        sleep(Duration::from_millis(1100));
+        sleep(std::time::Duration::from_millis(1100));
         assert!(!manager.is_on_cooldown("wallet1"));
     }
 
@@ -94,4 +369,42 @@ mod tests {
         assert_eq!(status.cooldown_seconds, 2);
         assert!(status.remaining_seconds <= 2);
     }
+
+    #[test]
+    fn test_scopes_are_independent() {
+        let mut manager = CooldownManager::new(0);
+        let now = Utc::now();
+        let bonk = SafetyScope::Token("BONK".to_string());
+        let wif = SafetyScope::Token("WIF".to_string());
+        manager.record_scoped_trade("wallet1", &[SafetyScope::Global, bonk.clone()], now);
+
+        assert!(manager.remaining("wallet1", &bonk, 60, now).is_some());
+        assert!(manager.remaining("wallet1", &wif, 60, now).is_none());
+        assert_eq!(manager.daily_trades("wallet1", &bonk, now), 1);
+
+        // Counts roll over at midnight UTC.
+        assert_eq!(
+            manager.daily_trades("wallet1", &bonk, now + Duration::days(1)),
+            0
+        );
+
+        let cleared = manager.reset(Some("wallet1"), Some(&bonk), CounterReset::All);
+        assert_eq!(cleared.len(), 1);
+        assert!(manager.remaining("wallet1", &bonk, 60, now).is_none());
+        assert_eq!(
+            manager.daily_trades("wallet1", &SafetyScope::Global, now),
+            1
+        );
+    }
+
+    #[test]
+    fn test_scope_keys_round_trip() {
+        for scope in [
+            SafetyScope::Global,
+            SafetyScope::Token("So11111111111111111111111111111111111111112".to_string()),
+            SafetyScope::Strategy("momentum:1".to_string()),
+        ] {
+            assert_eq!(SafetyScope::from_key(&scope.key()), Some(scope));
+        }
+    }
 }
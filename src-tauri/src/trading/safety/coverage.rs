@@ -0,0 +1,592 @@
+//! Insurance policies created from selected quotes, and claim drafts against
+//! them.
+//!
+//! Policies and claims are stored as JSON records in `insurance.db`.
+//! `monitor_insurance_policies` re-checks active policies every few minutes
+//! and emits `insurance-policy-alert` when one is about to expire, has expired,
+//! or a covered position has grown past the amount it is covered for.
+
+use super::insurance::InsuranceSelection;
+use crate::portfolio::SharedPortfolioData;
+use crate::security::activity_log::ActivityLog;
+use crate::security::audit::AuditResult;
+use crate::trading::types::Order;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Row, Sqlite, SqlitePool};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::time::interval;
+use uuid::Uuid;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+pub const INSURANCE_POLICY_ALERT_EVENT: &str = "insurance-policy-alert";
+pub const DEFAULT_POLICY_DURATION_DAYS: i64 = 30;
+const EXPIRY_WARNING_HOURS: i64 = 72;
+const POLICY_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(300);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyStatus {
+    Active,
+    Expired,
+}
+
+impl PolicyStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            PolicyStatus::Active => "active",
+            PolicyStatus::Expired => "expired",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CoveredPosition {
+    pub mint: String,
+    pub symbol: String,
+    /// Position value the policy covers.
+    pub covered_value_usd: f64,
+}
+
+/// What a policy covers.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CoverageScope {
+    #[serde(default)]
+    pub wallets: Vec<String>,
+    #[serde(default)]
+    pub positions: Vec<CoveredPosition>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InsurancePolicy {
+    pub id: String,
+    pub provider_id: String,
+    pub provider_name: String,
+    pub status: PolicyStatus,
+    pub coverage_usd: f64,
+    pub coverage_percentage: f64,
+    pub premium_usd: f64,
+    pub includes_mev_protection: bool,
+    pub scope: CoverageScope,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub expiry_warned_at: Option<DateTime<Utc>>,
+    /// Mints already reported as outside coverage, so each drift alerts once.
+    #[serde(default)]
+    pub drifted_mints: Vec<String>,
+}
+
+impl InsurancePolicy {
+    pub fn from_selection(
+        selection: &InsuranceSelection,
+        provider_name: &str,
+        scope: CoverageScope,
+        duration_days: i64,
+    ) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4().to_string(),
+            provider_id: selection.provider_id.clone(),
+            provider_name: provider_name.to_string(),
+            status: PolicyStatus::Active,
+            coverage_usd: selection.coverage_usd,
+            coverage_percentage: selection.coverage_percentage,
+            premium_usd: selection.premium_usd,
+            includes_mev_protection: selection.includes_mev_protection,
+            scope,
+            starts_at: now,
+            ends_at: now + Duration::days(duration_days),
+            created_at: now,
+            expiry_warned_at: None,
+            drifted_mints: Vec::new(),
+        }
+    }
+
+    /// Updates the policy for `now` and current position values (USD by mint)
+    /// and returns the alerts it raises. Each condition alerts once.
+    pub fn check(
+        &mut self,
+        now: DateTime<Utc>,
+        positions: &HashMap<String, f64>,
+    ) -> Vec<PolicyAlert> {
+        if self.status != PolicyStatus::Active {
+            return Vec::new();
+        }
+
+        let mut alerts = Vec::new();
+        if now >= self.ends_at {
+            self.status = PolicyStatus::Expired;
+            alerts.push(self.alert(
+                now,
+                PolicyAlertKind::Expired,
+                format!("{} policy {} has expired", self.provider_name, self.id),
+            ));
+            return alerts;
+        }
+
+        if self.expiry_warned_at.is_none()
+            && self.ends_at - now <= Duration::hours(EXPIRY_WARNING_HOURS)
+        {
+            self.expiry_warned_at = Some(now);
+            alerts.push(self.alert(
+                now,
+                PolicyAlertKind::Expiring {
+                    ends_at: self.ends_at,
+                },
+                format!(
+                    "{} policy {} expires at {}",
+                    self.provider_name,
+                    self.id,
+                    self.ends_at.to_rfc3339()
+                ),
+            ));
+        }
+
+        for position in self.scope.positions.clone() {
+            let Some(&current_value_usd) = positions.get(&position.mint) else {
+                continue;
+            };
+            let drifted = current_value_usd > position.covered_value_usd;
+            let warned = self.drifted_mints.contains(&position.mint);
+            if drifted && !warned {
+                self.drifted_mints.push(position.mint.clone());
+                alerts.push(self.alert(
+                    now,
+                    PolicyAlertKind::PositionDrift {
+                        mint: position.mint.clone(),
+                        covered_value_usd: position.covered_value_usd,
+                        current_value_usd,
+                    },
+                    format!(
+                        "{} position is worth ${:.2} but policy {} covers ${:.2}",
+                        position.symbol, current_value_usd, self.id, position.covered_value_usd
+                    ),
+                ));
+            } else if !drifted && warned {
+                self.drifted_mints.retain(|mint| mint != &position.mint);
+            }
+        }
+
+        alerts
+    }
+
+    fn alert(&self, now: DateTime<Utc>, kind: PolicyAlertKind, message: String) -> PolicyAlert {
+        PolicyAlert {
+            policy_id: self.id.clone(),
+            kind,
+            message,
+            timestamp: now,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PolicyAlertKind {
+    Expiring {
+        ends_at: DateTime<Utc>,
+    },
+    Expired,
+    PositionDrift {
+        mint: String,
+        covered_value_usd: f64,
+        current_value_usd: f64,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicyAlert {
+    pub policy_id: String,
+    #[serde(flatten)]
+    pub kind: PolicyAlertKind,
+    pub message: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClaimStatus {
+    Draft,
+    Exported,
+}
+
+/// Records bundled with a claim for the provider.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ClaimEvidence {
+    pub activity_logs: Vec<ActivityLog>,
+    pub transactions: Vec<Order>,
+    pub audit_reports: Vec<AuditResult>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClaimDraft {
+    pub id: String,
+    pub policy_id: String,
+    pub status: ClaimStatus,
+    pub description: String,
+    pub incident_at: DateTime<Utc>,
+    pub claimed_amount_usd: f64,
+    pub evidence: ClaimEvidence,
+    pub created_at: DateTime<Utc>,
+    pub exported_at: Option<DateTime<Utc>>,
+    pub package_path: Option<String>,
+}
+
+/// The `insurance_policies` and `insurance_claims` tables.
+pub struct InsuranceLedger {
+    pool: Pool<Sqlite>,
+}
+
+pub type SharedInsuranceLedger = Arc<InsuranceLedger>;
+
+impl InsuranceLedger {
+    pub async fn new(db_path: PathBuf) -> Result<Self, sqlx::Error> {
+        let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+        let pool = SqlitePool::connect(&db_url).await?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS insurance_policies (
+                id TEXT PRIMARY KEY,
+                status TEXT NOT NULL,
+                ends_at TEXT NOT NULL,
+                data TEXT NOT NULL
+            )
+            "#,
+        )
+        .execute(&pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS insurance_claims (
+                id TEXT PRIMARY KEY,
+                policy_id TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                data TEXT NOT NULL
+            )
+            "#,
+        )
+        .execute(&pool)
+        .await?;
+
+        Ok(Self { pool })
+    }
+
+    pub async fn save_policy(&self, policy: &InsurancePolicy) -> Result<(), String> {
+        let data = serde_json::to_string(policy).map_err(|e| e.to_string())?;
+        sqlx::query(
+            r#"
+            INSERT INTO insurance_policies (id, status, ends_at, data)
+            VALUES (?1, ?2, ?3, ?4)
+            ON CONFLICT(id) DO UPDATE SET
+                status = excluded.status,
+                ends_at = excluded.ends_at,
+                data = excluded.data
+            "#,
+        )
+        .bind(&policy.id)
+        .bind(policy.status.as_str())
+        .bind(policy.ends_at.to_rfc3339())
+        .bind(data)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| format!("Failed to save insurance policy: {}", e))?;
+        Ok(())
+    }
+
+    pub async fn get_policy(&self, id: &str) -> Result<Option<InsurancePolicy>, String> {
+        let row = sqlx::query("SELECT data FROM insurance_policies WHERE id = ?1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| format!("Failed to load insurance policy: {}", e))?;
+        row.map(|row| decode(&row.get::<String, _>("data")))
+            .transpose()
+    }
+
+    /// Active policies, soonest to expire first.
+    pub async fn active_policies(&self) -> Result<Vec<InsurancePolicy>, String> {
+        let rows = sqlx::query(
+            "SELECT data FROM insurance_policies WHERE status = ?1 ORDER BY ends_at ASC",
+        )
+        .bind(PolicyStatus::Active.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| format!("Failed to load insurance policies: {}", e))?;
+        rows.iter()
+            .map(|row| decode(&row.get::<String, _>("data")))
+            .collect()
+    }
+
+    pub async fn save_claim(&self, claim: &ClaimDraft) -> Result<(), String> {
+        let data = serde_json::to_string(claim).map_err(|e| e.to_string())?;
+        sqlx::query(
+            r#"
+            INSERT INTO insurance_claims (id, policy_id, created_at, data)
+            VALUES (?1, ?2, ?3, ?4)
+            ON CONFLICT(id) DO UPDATE SET data = excluded.data
+            "#,
+        )
+        .bind(&claim.id)
+        .bind(&claim.policy_id)
+        .bind(claim.created_at.to_rfc3339())
+        .bind(data)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| format!("Failed to save claim draft: {}", e))?;
+        Ok(())
+    }
+
+    pub async fn get_claim(&self, id: &str) -> Result<Option<ClaimDraft>, String> {
+        let row = sqlx::query("SELECT data FROM insurance_claims WHERE id = ?1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| format!("Failed to load claim draft: {}", e))?;
+        row.map(|row| decode(&row.get::<String, _>("data")))
+            .transpose()
+    }
+
+    /// Newest first.
+    pub async fn list_claims(&self, policy_id: &str) -> Result<Vec<ClaimDraft>, String> {
+        let rows = sqlx::query(
+            "SELECT data FROM insurance_claims WHERE policy_id = ?1 ORDER BY created_at DESC",
+        )
+        .bind(policy_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| format!("Failed to load claim drafts: {}", e))?;
+        rows.iter()
+            .map(|row| decode(&row.get::<String, _>("data")))
+            .collect()
+    }
+
+    /// Checks every active policy and saves the ones that changed.
+    pub async fn check_policies(
+        &self,
+        now: DateTime<Utc>,
+        positions: &HashMap<String, f64>,
+    ) -> Result<Vec<PolicyAlert>, String> {
+        let mut alerts = Vec::new();
+        for mut policy in self.active_policies().await? {
+            let drifted_before = policy.drifted_mints.clone();
+            let raised = policy.check(now, positions);
+            if !raised.is_empty() || policy.drifted_mints != drifted_before {
+                self.save_policy(&policy).await?;
+            }
+            alerts.extend(raised);
+        }
+        Ok(alerts)
+    }
+}
+
+fn decode<T: serde::de::DeserializeOwned>(data: &str) -> Result<T, String> {
+    serde_json::from_str(data).map_err(|e| format!("Failed to decode insurance record: {}", e))
+}
+
+/// Writes the claim, its policy and the evidence into a zip at `path`.
+pub fn write_claim_package(
+    claim: &ClaimDraft,
+    policy: &InsurancePolicy,
+    path: &Path,
+) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create claim package directory: {}", e))?;
+    }
+    let file = File::create(path).map_err(|e| format!("Failed to create claim package: {}", e))?;
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    let mut add = |name: &str, contents: Vec<u8>| -> Result<(), String> {
+        zip.start_file(name, options)
+            .map_err(|e| format!("Failed to add {} to claim package: {}", name, e))?;
+        zip.write_all(&contents)
+            .map_err(|e| format!("Failed to write {} to claim package: {}", name, e))
+    };
+
+    add("claim.json", to_json(&ClaimSummary::from(claim))?)?;
+    add("policy.json", to_json(policy)?)?;
+    add("activity_log.json", to_json(&claim.evidence.activity_logs)?)?;
+    add("transactions.json", to_json(&claim.evidence.transactions)?)?;
+    for report in &claim.evidence.audit_reports {
+        add(
+            &format!("audit_reports/{}.json", report.contract_address),
+            to_json(report)?,
+        )?;
+    }
+
+    zip.finish()
+        .map_err(|e| format!("Failed to finish claim package: {}", e))?;
+    Ok(())
+}
+
+/// The claim without its evidence, which is packaged in separate files.
+#[derive(Serialize)]
+struct ClaimSummary<'a> {
+    id: &'a str,
+    policy_id: &'a str,
+    description: &'a str,
+    incident_at: DateTime<Utc>,
+    claimed_amount_usd: f64,
+    created_at: DateTime<Utc>,
+}
+
+impl<'a> From<&'a ClaimDraft> for ClaimSummary<'a> {
+    fn from(claim: &'a ClaimDraft) -> Self {
+        Self {
+            id: &claim.id,
+            policy_id: &claim.policy_id,
+            description: &claim.description,
+            incident_at: claim.incident_at,
+            claimed_amount_usd: claim.claimed_amount_usd,
+            created_at: claim.created_at,
+        }
+    }
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<Vec<u8>, String> {
+    serde_json::to_vec_pretty(value).map_err(|e| e.to_string())
+}
+
+/// Current position values in USD by mint.
+fn position_values(app: &AppHandle) -> HashMap<String, f64> {
+    app.try_state::<SharedPortfolioData>()
+        .and_then(|data| {
+            data.lock().ok().map(|guard| {
+                guard
+                    .positions()
+                    .into_iter()
+                    .map(|p| (p.mint, p.total_value))
+                    .collect()
+            })
+        })
+        .unwrap_or_default()
+}
+
+/// Raises expiry and coverage drift alerts for active policies.
+pub async fn monitor_insurance_policies(app: AppHandle, ledger: SharedInsuranceLedger) {
+    let mut tick = interval(POLICY_CHECK_INTERVAL);
+    loop {
+        tick.tick().await;
+        let positions = position_values(&app);
+        match ledger.check_policies(Utc::now(), &positions).await {
+            Ok(alerts) => {
+                for alert in alerts {
+                    if let Err(e) = app.emit(INSURANCE_POLICY_ALERT_EVENT, &alert) {
+                        eprintln!("Failed to emit insurance policy alert: {}", e);
+                    }
+                }
+            }
+            Err(e) => eprintln!("Failed to check insurance policies: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    fn policy(duration_days: i64) -> InsurancePolicy {
+        let selection = InsuranceSelection {
+            provider_id: "sol_shield".to_string(),
+            premium_usd: 15.0,
+            coverage_usd: 7500.0,
+            coverage_percentage: 0.75,
+            includes_mev_protection: false,
+            policy_id: None,
+        };
+        let scope = CoverageScope {
+            wallets: vec!["wallet1".to_string()],
+            positions: vec![CoveredPosition {
+                mint: "BONK".to_string(),
+                symbol: "BONK".to_string(),
+                covered_value_usd: 7500.0,
+            }],
+        };
+        InsurancePolicy::from_selection(&selection, "SolShield Mutual", scope, duration_days)
+    }
+
+    #[test]
+    fn test_expiry_alerts_once() {
+        let mut policy = policy(2);
+        let now = Utc::now();
+        let alerts = policy.check(now, &HashMap::new());
+        assert_eq!(alerts.len(), 1);
+        assert!(matches!(alerts[0].kind, PolicyAlertKind::Expiring { .. }));
+        assert!(policy.check(now, &HashMap::new()).is_empty());
+
+        let alerts = policy.check(now + Duration::days(3), &HashMap::new());
+        assert_eq!(alerts[0].kind, PolicyAlertKind::Expired);
+        assert_eq!(policy.status, PolicyStatus::Expired);
+    }
+
+    #[test]
+    fn test_position_drift_alerts_until_back_in_scope() {
+        let mut policy = policy(30);
+        let now = Utc::now();
+        let grown = HashMap::from([("BONK".to_string(), 9000.0)]);
+        let alerts = policy.check(now, &grown);
+        assert_eq!(alerts.len(), 1);
+        assert!(matches!(
+            alerts[0].kind,
+            PolicyAlertKind::PositionDrift { current_value_usd, .. } if current_value_usd == 9000.0
+        ));
+        assert!(policy.check(now, &grown).is_empty());
+
+        let shrunk = HashMap::from([("BONK".to_string(), 5000.0)]);
+        assert!(policy.check(now, &shrunk).is_empty());
+        assert_eq!(policy.check(now, &grown).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_claim_package_contains_evidence() {
+        let dir = std::env::temp_dir().join(format!("insurance_test_{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let ledger = InsuranceLedger::new(dir.join("insurance.db"))
+            .await
+            .unwrap();
+
+        let policy = policy(30);
+        ledger.save_policy(&policy).await.unwrap();
+        assert_eq!(ledger.active_policies().await.unwrap().len(), 1);
+
+        let claim = ClaimDraft {
+            id: Uuid::new_v4().to_string(),
+            policy_id: policy.id.clone(),
+            status: ClaimStatus::Draft,
+            description: "Sandwiched swap".to_string(),
+            incident_at: Utc::now(),
+            claimed_amount_usd: 120.0,
+            evidence: ClaimEvidence::default(),
+            created_at: Utc::now(),
+            exported_at: None,
+            package_path: None,
+        };
+        ledger.save_claim(&claim).await.unwrap();
+        assert_eq!(ledger.list_claims(&policy.id).await.unwrap().len(), 1);
+
+        let path = dir.join("claim.zip");
+        write_claim_package(&claim, &policy, &path).unwrap();
+
+        let mut archive = zip::ZipArchive::new(File::open(&path).unwrap()).unwrap();
+        let mut contents = String::new();
+        archive
+            .by_name("claim.json")
+            .unwrap()
+            .read_to_string(&mut contents)
+            .unwrap();
+        assert!(contents.contains("Sandwiched swap"));
+        assert!(archive.by_name("activity_log.json").is_ok());
+        assert!(archive.by_name("transactions.json").is_ok());
+    }
+}
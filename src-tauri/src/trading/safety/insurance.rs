@@ -30,7 +30,11 @@ pub struct InsuranceSelection {
     pub provider_id: String,
     pub premium_usd: f64,
     pub coverage_usd: f64,
+    pub coverage_percentage: f64,
     pub includes_mev_protection: bool,
+    /// The policy created for this selection.
+    #[serde(default)]
+    pub policy_id: Option<String>,
 }
 
 pub struct InsuranceCoordinator {
@@ -116,8 +120,13 @@ impl InsuranceCoordinator {
             return Err(format!("Insurance provider {} is not active", provider_id));
         }
 
-        // Check cache first
-        if let Some(quote) = self.quote_cache.get(provider_id) {
+        // Check cache first. Quotes are cached per trade so a quote for one
+        // amount is never reused for another.
+        let cache_key = format!(
+            "{}:{:.2}:{:.4}:{:.4}",
+            provider_id, trade_amount_usd, price_impact_percent, mev_risk_level
+        );
+        if let Some(quote) = self.quote_cache.get(&cache_key) {
             if quote.expires_at > Utc::now() {
                 return Ok(quote.clone());
             }
@@ -159,11 +168,53 @@ impl InsuranceCoordinator {
             expires_at: Utc::now() + cache_ttl,
         };
 
-        self.quote_cache
-            .insert(provider_id.to_string(), quote.clone());
+        self.quote_cache.insert(cache_key, quote.clone());
         Ok(quote)
     }
 
+    /// Checks that a quote's terms are still valid for the trade: the quote
+    /// has not expired, its provider is active, and it covers no more than
+    /// the trade amount or the provider's limit.
+    pub fn verify_quote(
+        &self,
+        quote: &InsuranceQuote,
+        trade_amount_usd: f64,
+    ) -> Result<(), String> {
+        let provider = self
+            .providers
+            .get(&quote.provider_id)
+            .ok_or_else(|| format!("Insurance provider {} not found", quote.provider_id))?;
+        if !provider.is_active {
+            return Err(format!("Insurance provider {} is not active", provider.id));
+        }
+        if quote.expires_at <= Utc::now() {
+            return Err(format!("Quote from {} has expired", provider.name));
+        }
+        if !(quote.coverage_percentage > 0.0 && quote.coverage_percentage <= 1.0) {
+            return Err(format!(
+                "Quote from {} covers {:.0}% of the trade",
+                provider.name,
+                quote.coverage_percentage * 100.0
+            ));
+        }
+        if quote.coverage_amount_usd > provider.coverage_limit_usd {
+            return Err(format!(
+                "Quoted coverage ${:.2} exceeds {}'s limit of ${:.2}",
+                quote.coverage_amount_usd, provider.name, provider.coverage_limit_usd
+            ));
+        }
+        if quote.coverage_amount_usd > trade_amount_usd * quote.coverage_percentage + 0.01 {
+            return Err(format!(
+                "Quoted coverage ${:.2} does not match a ${:.2} trade",
+                quote.coverage_amount_usd, trade_amount_usd
+            ));
+        }
+        if !quote.total_premium_usd.is_finite() || quote.total_premium_usd <= 0.0 {
+            return Err(format!("Quote from {} has no valid premium", provider.name));
+        }
+        Ok(())
+    }
+
     pub fn recommend_provider(
         &mut self,
         trade_amount_usd: f64,
@@ -207,12 +258,15 @@ impl InsuranceCoordinator {
             price_impact_percent,
             mev_risk_level,
         )?;
+        self.verify_quote(&quote, trade_amount_usd)?;
 
         Ok(InsuranceSelection {
             provider_id: provider_id.to_string(),
             premium_usd: quote.total_premium_usd,
             coverage_usd: quote.coverage_amount_usd,
+            coverage_percentage: quote.coverage_percentage,
             includes_mev_protection: quote.mev_protection_included,
+            policy_id: None,
         })
     }
 
@@ -265,4 +319,24 @@ mod tests {
         assert_eq!(selection.provider_id, "sol_shield");
         assert!(selection.coverage_usd > 0.0);
     }
+
+    #[test]
+    fn test_quotes_are_cached_per_trade() {
+        let mut coordinator = InsuranceCoordinator::default();
+        let small = coordinator
+            .request_quote("sol_shield", 1000.0, 1.0, 0.2)
+            .unwrap();
+        let large = coordinator
+            .request_quote("sol_shield", 100000.0, 1.0, 0.2)
+            .unwrap();
+        assert!(large.coverage_amount_usd > small.coverage_amount_usd);
+
+        // A quote for the small trade does not verify against the large one,
+        // and tampered or expired terms are rejected.
+        assert!(coordinator.verify_quote(&large, 1000.0).is_err());
+        let mut expired = small.clone();
+        expired.expires_at = Utc::now() - ChronoDuration::seconds(1);
+        assert!(coordinator.verify_quote(&expired, 1000.0).is_err());
+        assert!(coordinator.verify_quote(&small, 1000.0).is_ok());
+    }
 }
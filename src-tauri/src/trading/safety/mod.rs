@@ -1,22 +1,39 @@
 pub mod cooldown;
+pub mod coverage;
 pub mod insurance;
 pub mod policy;
+pub mod schedule;
 pub mod simulator;
 
-use cooldown::CooldownManager;
+use cooldown::{CooldownManager, CooldownStore, CounterReset, ScopeCounterEntry};
 use insurance::InsuranceCoordinator;
 use policy::PolicyEngine;
 use simulator::TransactionSimulator;
 
-pub use cooldown::CooldownStatus;
+pub use cooldown::{CooldownStatus, SafetyScope};
+pub use coverage::{
+    ClaimDraft, CoverageScope, CoveredPosition, InsuranceLedger, InsurancePolicy, PolicyAlert,
+    SharedInsuranceLedger,
+};
 pub use insurance::{InsuranceProvider, InsuranceQuote, InsuranceSelection};
-pub use policy::{PolicyCheckResult, PolicyViolation, SafetyPolicy, ViolationSeverity};
+pub use policy::{
+    PolicyCheckResult, PolicyViolation, SafetyPolicy, ScopeLimits, ScopedLimits, ViolationSeverity,
+};
+pub use schedule::{
+    OutsideTradingHours, TradeOrigin, TradingSchedule, TradingScheduleError, TradingWindow,
+};
 pub use simulator::{ImpactPreview, MevRiskLevel, RouteHop, TransactionSimulation};
 
+use crate::errors::AppError;
+use chrono::{DateTime, Days, Duration, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+/// How long an approved trading hours override lasts.
+pub const SCHEDULE_OVERRIDE_MINUTES: i64 = 30;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SafetyCheckRequest {
     pub wallet_address: String,
@@ -29,13 +46,55 @@ pub struct SafetyCheckRequest {
     pub slippage_bps: u64,
     pub price_impact_percent: f64,
     pub security_score: Option<f64>,
+    /// Token whose limits apply. Defaults to `output_mint`.
+    #[serde(default)]
+    pub token_mint: Option<String>,
+    /// Strategy placing the trade, if any.
+    #[serde(default)]
+    pub strategy_id: Option<String>,
+    /// Where the trade was placed; decides whether the trading schedule applies.
+    #[serde(default)]
+    pub origin: TradeOrigin,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SafetyCheckError {
+    #[error(transparent)]
+    OutsideTradingHours(#[from] OutsideTradingHours),
+    #[error("{0}")]
+    Simulation(String),
+}
+
+impl From<SafetyCheckError> for AppError {
+    fn from(err: SafetyCheckError) -> Self {
+        match err {
+            SafetyCheckError::OutsideTradingHours(err) => AppError::validation(err.to_string())
+                .with_code("outside_trading_hours")
+                .with_detail("timezone", &err.timezone)
+                .with_detail("nextAllowedAt", err.next_allowed_at),
+            SafetyCheckError::Simulation(message) => AppError::internal(message),
+        }
+    }
+}
+
+/// A cooldown or daily limit that blocks a trade.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SafetyBlock {
+    pub scope: SafetyScope,
+    pub rule: String,
+    pub message: String,
+    /// When the block lifts.
+    pub expires_at: DateTime<Utc>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SafetyCheckResult {
     pub allowed: bool,
     pub policy_result: PolicyCheckResult,
+    /// The longest active cooldown among the trade's scopes.
     pub cooldown_status: Option<CooldownStatus>,
+    /// The most restrictive cooldown or daily limit, when one blocks the trade.
+    pub blocked_by: Option<SafetyBlock>,
     pub simulation: Option<TransactionSimulation>,
     pub impact_preview: Option<ImpactPreview>,
     pub insurance_required: bool,
@@ -48,6 +107,18 @@ pub struct SafetyEngine {
     cooldown_manager: CooldownManager,
     simulator: TransactionSimulator,
     insurance_coordinator: InsuranceCoordinator,
+    store: Option<CooldownStore>,
+    /// Trades may ignore the trading schedule until then.
+    schedule_override_until: Option<DateTime<Utc>>,
+    /// Depegging stablecoins trades may not buy, with the reason.
+    depeg_blocks: HashMap<String, String>,
+}
+
+fn trade_scopes(token_mint: Option<&str>, strategy_id: Option<&str>) -> Vec<SafetyScope> {
+    let mut scopes = vec![SafetyScope::Global];
+    scopes.extend(token_mint.map(|mint| SafetyScope::Token(mint.to_string())));
+    scopes.extend(strategy_id.map(|id| SafetyScope::Strategy(id.to_string())));
+    scopes
 }
 
 impl SafetyEngine {
@@ -57,6 +128,28 @@ impl SafetyEngine {
             cooldown_manager: CooldownManager::new(cooldown_seconds),
             simulator: TransactionSimulator::default(),
             insurance_coordinator: InsuranceCoordinator::default(),
+            store: None,
+            schedule_override_until: None,
+            depeg_blocks: HashMap::new(),
+        }
+    }
+
+    /// Restores persisted counters and writes later changes through to `store`.
+    pub async fn attach_store(&mut self, store: CooldownStore) -> Result<(), String> {
+        let entries = store
+            .load()
+            .await
+            .map_err(|e| format!("Failed to load safety counters: {}", e))?;
+        self.cooldown_manager.restore(entries);
+        self.store = Some(store);
+        Ok(())
+    }
+
+    async fn persist(&self, entries: &[ScopeCounterEntry]) {
+        if let Some(store) = &self.store {
+            if let Err(e) = store.save(entries).await {
+                eprintln!("Failed to persist safety counters: {}", e);
+            }
         }
     }
 
@@ -72,12 +165,45 @@ impl SafetyEngine {
         self.policy_engine.update_policy(policy);
     }
 
+    /// `Err` when the trading schedule locks trades from `origin` at `now`
+    /// and no override is active.
+    pub fn check_trading_hours(
+        &self,
+        origin: TradeOrigin,
+        now: DateTime<Utc>,
+    ) -> Result<(), OutsideTradingHours> {
+        let policy = self.get_policy();
+        if !policy.enabled
+            || self
+                .schedule_override_until
+                .map_or(false, |until| now < until)
+        {
+            return Ok(());
+        }
+        policy.trading_schedule.check(origin, now)
+    }
+
+    /// Lifts the trading schedule for [`SCHEDULE_OVERRIDE_MINUTES`]. Returns
+    /// when the override ends.
+    pub fn override_trading_hours(&mut self, now: DateTime<Utc>) -> DateTime<Utc> {
+        let until = now + Duration::minutes(SCHEDULE_OVERRIDE_MINUTES);
+        self.schedule_override_until = Some(until);
+        until
+    }
+
+    /// Replaces the stablecoins blocked by the depeg monitor.
+    pub fn set_depeg_blocks(&mut self, blocks: HashMap<String, String>) {
+        self.depeg_blocks = blocks;
+    }
+
     pub async fn check_trade_safety(
         &mut self,
         request: SafetyCheckRequest,
-    ) -> Result<SafetyCheckResult, String> {
+    ) -> Result<SafetyCheckResult, SafetyCheckError> {
+        self.check_trading_hours(request.origin, Utc::now())?;
+
         // Check policy violations
-        let policy_result = self.policy_engine.check_trade_policy(
+        let mut policy_result = self.policy_engine.check_trade_policy(
             &request.wallet_address,
             request.amount_usd,
             request.price_impact_percent,
@@ -85,15 +211,39 @@ impl SafetyEngine {
             request.security_score,
         );
 
-        // Check cooldown
-        let cooldown_status = if self.get_policy().cooldown_enabled {
-            self.cooldown_manager
-                .get_remaining_cooldown(&request.wallet_address)
-        } else {
-            None
-        };
-
-        let on_cooldown = cooldown_status.is_some();
+        // Check cooldowns and daily limits in every scope the trade falls under
+        let scopes = trade_scopes(
+            Some(
+                request
+                    .token_mint
+                    .as_deref()
+                    .unwrap_or(&request.output_mint),
+            ),
+            request.strategy_id.as_deref(),
+        );
+        let (blocks, cooldown_status) =
+            self.limit_blocks(&request.wallet_address, &scopes, Utc::now());
+        for block in &blocks {
+            policy_result.add_violation(PolicyViolation {
+                rule: block.rule.clone(),
+                message: block.message.clone(),
+                severity: ViolationSeverity::Error,
+                can_override: false,
+            });
+        }
+        let blocked_by = blocks.into_iter().max_by_key(|block| block.expires_at);
+
+        // Buying a depegging stablecoin adds exposure; selling it is fine
+        if let Some(reason) = self.depeg_blocks.get(&request.output_mint) {
+            if self.get_policy().enabled {
+                policy_result.add_violation(PolicyViolation {
+                    rule: "stablecoin_depeg".to_string(),
+                    message: reason.clone(),
+                    severity: ViolationSeverity::Error,
+                    can_override: false,
+                });
+            }
+        }
 
         // Run transaction simulation if required
         let simulation = if self.get_policy().require_simulation {
@@ -105,7 +255,8 @@ impl SafetyEngine {
                         &request.output_mint,
                         request.slippage_bps,
                     )
-                    .await?,
+                    .await
+                    .map_err(SafetyCheckError::Simulation)?,
             )
         } else {
             None
@@ -120,7 +271,8 @@ impl SafetyEngine {
                     request.output_symbol.clone(),
                     request.slippage_bps as f64 / 100.0,
                 )
-                .await?,
+                .await
+                .map_err(SafetyCheckError::Simulation)?,
         );
 
         // Check if insurance is required or recommended
@@ -148,12 +300,13 @@ impl SafetyEngine {
         // Generate MEV protection suggestions
         let mev_suggestions = self.simulator.suggest_mev_protection(request.amount_usd);
 
-        let allowed = policy_result.allowed && !on_cooldown;
+        let allowed = policy_result.allowed && blocked_by.is_none();
 
         Ok(SafetyCheckResult {
             allowed,
             policy_result,
             cooldown_status,
+            blocked_by,
             simulation,
             impact_preview,
             insurance_required,
@@ -162,20 +315,113 @@ impl SafetyEngine {
         })
     }
 
-    pub fn approve_trade(&mut self, wallet_address: &str) {
-        if self.get_policy().cooldown_enabled {
-            self.cooldown_manager.record_trade(wallet_address);
+    /// Cooldowns and daily limits blocking a trade in `scopes`, and the
+    /// longest active cooldown.
+    fn limit_blocks(
+        &self,
+        wallet_address: &str,
+        scopes: &[SafetyScope],
+        now: DateTime<Utc>,
+    ) -> (Vec<SafetyBlock>, Option<CooldownStatus>) {
+        let policy = self.get_policy();
+        let mut blocks = Vec::new();
+        let mut longest: Option<CooldownStatus> = None;
+
+        for scope in scopes {
+            let limits = policy.limits_for(scope);
+
+            if let Some(cooldown) = limits.cooldown_seconds.and_then(|seconds| {
+                self.cooldown_manager
+                    .remaining(wallet_address, scope, seconds, now)
+            }) {
+                blocks.push(SafetyBlock {
+                    scope: scope.clone(),
+                    rule: "cooldown".to_string(),
+                    message: format!(
+                        "Cooldown for {} active for {} more seconds (until {})",
+                        scope,
+                        cooldown.remaining_seconds,
+                        cooldown.expires_at.to_rfc3339()
+                    ),
+                    expires_at: cooldown.expires_at,
+                });
+                if longest
+                    .as_ref()
+                    .map_or(true, |current| cooldown.expires_at > current.expires_at)
+                {
+                    longest = Some(cooldown);
+                }
+            }
+
+            if !policy.enabled {
+                continue;
+            }
+            if let Some(max_daily) = limits.max_daily_trades {
+                let count = self
+                    .cooldown_manager
+                    .daily_trades(wallet_address, scope, now);
+                if count >= max_daily {
+                    let resets_at = (now.date_naive() + Days::new(1))
+                        .and_hms_opt(0, 0, 0)
+                        .expect("midnight is a valid time")
+                        .and_utc();
+                    blocks.push(SafetyBlock {
+                        scope: scope.clone(),
+                        rule: "max_daily_trades".to_string(),
+                        message: format!(
+                            "Daily trade limit for {} reached: {} of {} trades (resets {})",
+                            scope,
+                            count,
+                            max_daily,
+                            resets_at.to_rfc3339()
+                        ),
+                        expires_at: resets_at,
+                    });
+                }
+            }
         }
-        self.policy_engine
-            .increment_daily_trade_count(wallet_address);
+
+        (blocks, longest)
+    }
+
+    /// Starts cooldowns and counts the trade in its global, token and strategy
+    /// scopes.
+    pub async fn approve_trade(
+        &mut self,
+        wallet_address: &str,
+        token_mint: Option<&str>,
+        strategy_id: Option<&str>,
+    ) {
+        let entries = self.cooldown_manager.record_scoped_trade(
+            wallet_address,
+            &trade_scopes(token_mint, strategy_id),
+            Utc::now(),
+        );
+        self.persist(&entries).await;
     }
 
+    /// The longest active cooldown for the wallet in any scope.
     pub fn get_cooldown_status(&self, wallet_address: &str) -> Option<CooldownStatus> {
-        self.cooldown_manager.get_remaining_cooldown(wallet_address)
+        let scopes: Vec<SafetyScope> = self
+            .cooldown_manager
+            .entries(wallet_address)
+            .into_iter()
+            .map(|entry| entry.scope)
+            .collect();
+        self.limit_blocks(wallet_address, &scopes, Utc::now()).1
     }
 
-    pub fn reset_daily_limits(&mut self) {
-        self.policy_engine.reset_daily_counts();
+    /// Clears cooldowns, daily counts or both for the wallet and scope (every
+    /// wallet and scope when `None`). Returns the cleared counters.
+    pub async fn reset_limits(
+        &mut self,
+        wallet_address: Option<&str>,
+        scope: Option<&SafetyScope>,
+        reset: CounterReset,
+    ) -> Vec<ScopeCounterEntry> {
+        let entries = self.cooldown_manager.reset(wallet_address, scope, reset);
+        self.persist(&entries).await;
+        entries
     }
 
     pub fn get_insurance_quote(
@@ -208,6 +454,12 @@ impl SafetyEngine {
         )
     }
 
+    pub fn get_insurance_provider(&self, provider_id: &str) -> Option<InsuranceProvider> {
+        self.insurance_coordinator
+            .get_provider(provider_id)
+            .cloned()
+    }
+
     pub fn list_insurance_providers(&self) -> Vec<InsuranceProvider> {
         self.insurance_coordinator
             .list_providers()
@@ -222,6 +474,7 @@ pub type SharedSafetyEngine = Arc<RwLock<SafetyEngine>>;
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::Datelike;
 
     #[tokio::test]
     async fn test_safety_check_allowed() {
@@ -239,6 +492,9 @@ mod tests {
             slippage_bps: 50,
             price_impact_percent: 1.5,
             security_score: Some(85.0),
+            token_mint: None,
+            strategy_id: None,
+            origin: TradeOrigin::Manual,
         };
 
         let result = engine.check_trade_safety(request).await;
@@ -263,10 +519,13 @@ mod tests {
             slippage_bps: 50,
             price_impact_percent: 1.5,
             security_score: Some(85.0),
+            token_mint: None,
+            strategy_id: None,
+            origin: TradeOrigin::Manual,
         };
 
         // First trade allowed
-        engine.approve_trade("wallet1");
+        engine.approve_trade("wallet1", Some("USDC"), None).await;
 
         // Second trade should be blocked by cooldown
         let result = engine.check_trade_safety(request).await;
@@ -274,6 +533,147 @@ mod tests {
         let check = result.unwrap();
         assert!(!check.allowed);
         assert!(check.cooldown_status.is_some());
+        assert_eq!(check.blocked_by.unwrap().scope, SafetyScope::Global);
+    }
+
+    fn request(output_mint: &str, strategy_id: Option<&str>) -> SafetyCheckRequest {
+        SafetyCheckRequest {
+            wallet_address: "wallet1".to_string(),
+            input_amount: 100.0,
+            input_mint: "SOL".to_string(),
+            output_mint: output_mint.to_string(),
+            input_symbol: "SOL".to_string(),
+            output_symbol: output_mint.to_string(),
+            amount_usd: 500.0,
+            slippage_bps: 50,
+            price_impact_percent: 1.5,
+            security_score: Some(85.0),
+            token_mint: None,
+            strategy_id: strategy_id.map(str::to_string),
+            origin: TradeOrigin::Manual,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_scoped_limits_block_only_their_scope() {
+        let mut policy = SafetyPolicy::default();
+        policy.cooldown_seconds = 0;
+        policy.require_simulation = false;
+        policy.token_limits = Some(ScopeLimits {
+            cooldown_seconds: Some(600),
+            max_daily_trades: None,
+        });
+        policy.strategy_limits = Some(ScopeLimits {
+            cooldown_seconds: None,
+            max_daily_trades: Some(1),
+        });
+        let mut engine = SafetyEngine::new(policy, 0);
+
+        engine
+            .approve_trade("wallet1", Some("BONK"), Some("momentum"))
+            .await;
+
+        let other_token = engine
+            .check_trade_safety(request("WIF", None))
+            .await
+            .unwrap();
+        assert!(other_token.allowed);
+
+        let same_token = engine
+            .check_trade_safety(request("BONK", None))
+            .await
+            .unwrap();
+        assert!(!same_token.allowed);
+        let block = same_token.blocked_by.unwrap();
+        assert_eq!(block.scope, SafetyScope::Token("BONK".to_string()));
+        assert_eq!(block.rule, "cooldown");
+
+        let same_strategy = engine
+            .check_trade_safety(request("WIF", Some("momentum")))
+            .await
+            .unwrap();
+        let block = same_strategy.blocked_by.unwrap();
+        assert_eq!(block.rule, "max_daily_trades");
+        assert_eq!(block.scope, SafetyScope::Strategy("momentum".to_string()));
+
+        engine
+            .reset_limits(
+                Some("wallet1"),
+                Some(&SafetyScope::Token("BONK".to_string())),
+                CounterReset::All,
+            )
+            .await;
+        assert!(
+            engine
+                .check_trade_safety(request("BONK", None))
+                .await
+                .unwrap()
+                .allowed
+        );
+    }
+
+    #[tokio::test]
+    async fn test_trading_schedule_locks_until_override() {
+        let mut policy = SafetyPolicy::default();
+        policy.require_simulation = false;
+        // Only open three days from today.
+        let weekday = (Utc::now().weekday().num_days_from_sunday() as u8 + 3) % 7;
+        policy.trading_schedule = TradingSchedule {
+            enabled: true,
+            timezone: "UTC".to_string(),
+            windows: vec![TradingWindow {
+                weekday,
+                start: "09:00".to_string(),
+                end: "17:00".to_string(),
+            }],
+            exempt_auto_trading: true,
+            exempt_dca: false,
+        };
+        let mut engine = SafetyEngine::new(policy, 0);
+
+        let err = engine
+            .check_trade_safety(request("BONK", None))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            SafetyCheckError::OutsideTradingHours(OutsideTradingHours {
+                next_allowed_at: Some(_),
+                ..
+            })
+        ));
+
+        let mut auto = request("BONK", Some("momentum"));
+        auto.origin = TradeOrigin::AutoTrading;
+        assert!(engine.check_trade_safety(auto).await.is_ok());
+
+        engine.override_trading_hours(Utc::now());
+        assert!(engine
+            .check_trade_safety(request("BONK", None))
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_counters_survive_restart() {
+        let mut path = std::env::temp_dir();
+        path.push(format!("safety_counters_{}.db", uuid::Uuid::new_v4()));
+
+        let mut engine = SafetyEngine::new(SafetyPolicy::default(), 30);
+        engine
+            .attach_store(CooldownStore::new(path.clone()).await.unwrap())
+            .await
+            .unwrap();
+        engine.approve_trade("wallet1", Some("BONK"), None).await;
+        drop(engine);
+
+        let mut restarted = SafetyEngine::new(SafetyPolicy::default(), 30);
+        restarted
+            .attach_store(CooldownStore::new(path).await.unwrap())
+            .await
+            .unwrap();
+        let status = restarted.get_cooldown_status("wallet1").unwrap();
+        assert_eq!(status.cooldown_seconds, 30);
     }
 
     #[tokio::test]
@@ -292,6 +692,9 @@ mod tests {
             slippage_bps: 50,
             price_impact_percent: 1.5,
             security_score: Some(30.0),
+            token_mint: None,
+            strategy_id: None,
+            origin: TradeOrigin::Manual,
         };
 
         let result = engine.check_trade_safety(request).await;
@@ -300,4 +703,48 @@ mod tests {
         assert!(!check.allowed);
         assert!(!check.policy_result.violations.is_empty());
     }
+
+    #[tokio::test]
+    async fn test_depeg_block_stops_buying_the_stablecoin() {
+        let policy = SafetyPolicy::default();
+        let mut engine = SafetyEngine::new(policy, 30);
+        engine.set_depeg_blocks(HashMap::from([(
+            "USDC".to_string(),
+            "USDC is 2.50% off its peg".to_string(),
+        )]));
+
+        let buy = SafetyCheckRequest {
+            wallet_address: "wallet1".to_string(),
+            input_amount: 100.0,
+            input_mint: "SOL".to_string(),
+            output_mint: "USDC".to_string(),
+            input_symbol: "SOL".to_string(),
+            output_symbol: "USDC".to_string(),
+            amount_usd: 5000.0,
+            slippage_bps: 50,
+            price_impact_percent: 1.5,
+            security_score: Some(85.0),
+            token_mint: None,
+            strategy_id: None,
+            origin: TradeOrigin::Manual,
+        };
+        let check = engine.check_trade_safety(buy.clone()).await.unwrap();
+        assert!(!check.allowed);
+        assert!(check
+            .policy_result
+            .violations
+            .iter()
+            .any(|violation| violation.rule == "stablecoin_depeg"));
+
+        // Selling the stablecoin reduces exposure and stays allowed.
+        let sell = SafetyCheckRequest {
+            wallet_address: "wallet2".to_string(),
+            input_mint: "USDC".to_string(),
+            output_mint: "SOL".to_string(),
+            input_symbol: "USDC".to_string(),
+            output_symbol: "SOL".to_string(),
+            ..buy
+        };
+        assert!(engine.check_trade_safety(sell).await.unwrap().allowed);
+    }
 }
@@ -1,3 +1,5 @@
+use super::cooldown::SafetyScope;
+use super::schedule::{TradeOrigin, TradingSchedule};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -14,6 +16,61 @@ pub struct SafetyPolicy {
     pub require_insurance_above_usd: Option<f64>,
     pub max_price_impact_percent: f64,
     pub max_slippage_percent: f64,
+    /// Limits applied to each token separately.
+    #[serde(default)]
+    pub token_limits: Option<ScopeLimits>,
+    /// Limits applied to each strategy separately.
+    #[serde(default)]
+    pub strategy_limits: Option<ScopeLimits>,
+    /// Limits for individual tokens or strategies. They add to the per-token
+    /// and per-strategy defaults; the most restrictive limit wins.
+    #[serde(default)]
+    pub scoped_limits: Vec<ScopedLimits>,
+    /// Hours in which manual, voice and mobile trades are allowed.
+    #[serde(default)]
+    pub trading_schedule: TradingSchedule,
+    /// Largest adverse move, in basis points, a pre-sign re-quote may show
+    /// against the quote the user approved before the swap is aborted.
+    #[serde(default = "default_manual_quote_drift_bps")]
+    pub manual_quote_drift_bps: u32,
+    /// The same bound for DCA and auto-trading, which re-quote once more
+    /// before giving up instead of asking for confirmation.
+    #[serde(default = "default_automated_quote_drift_bps")]
+    pub automated_quote_drift_bps: u32,
+}
+
+fn default_manual_quote_drift_bps() -> u32 {
+    100
+}
+
+fn default_automated_quote_drift_bps() -> u32 {
+    50
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScopeLimits {
+    pub cooldown_seconds: Option<u64>,
+    pub max_daily_trades: Option<u32>,
+}
+
+impl ScopeLimits {
+    fn tighten(&mut self, other: &ScopeLimits) {
+        self.cooldown_seconds = match (self.cooldown_seconds, other.cooldown_seconds) {
+            (Some(a), Some(b)) => Some(a.max(b)),
+            (a, b) => a.or(b),
+        };
+        self.max_daily_trades = match (self.max_daily_trades, other.max_daily_trades) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScopedLimits {
+    pub scope: SafetyScope,
+    #[serde(flatten)]
+    pub limits: ScopeLimits,
 }
 
 impl Default for SafetyPolicy {
@@ -30,6 +87,12 @@ impl Default for SafetyPolicy {
             require_insurance_above_usd: Some(50000.0),
             max_price_impact_percent: 10.0,
             max_slippage_percent: 5.0,
+            token_limits: None,
+            strategy_limits: None,
+            scoped_limits: Vec::new(),
+            trading_schedule: TradingSchedule::default(),
+            manual_quote_drift_bps: default_manual_quote_drift_bps(),
+            automated_quote_drift_bps: default_automated_quote_drift_bps(),
         }
     }
 }
@@ -50,6 +113,34 @@ impl std::fmt::Display for SafetyCheck {
 }
 
 impl SafetyPolicy {
+    /// Effective limits for `scope`, combining every declaration that applies
+    /// to it. `cooldown_enabled` turns all cooldowns off.
+    pub fn limits_for(&self, scope: &SafetyScope) -> ScopeLimits {
+        let mut limits = match scope {
+            SafetyScope::Global => ScopeLimits {
+                cooldown_seconds: Some(self.cooldown_seconds),
+                max_daily_trades: self.max_daily_trades,
+            },
+            SafetyScope::Token(_) => self.token_limits.unwrap_or_default(),
+            SafetyScope::Strategy(_) => self.strategy_limits.unwrap_or_default(),
+        };
+        for scoped in self.scoped_limits.iter().filter(|s| &s.scope == scope) {
+            limits.tighten(&scoped.limits);
+        }
+        if !self.cooldown_enabled {
+            limits.cooldown_seconds = None;
+        }
+        limits
+    }
+
+    pub fn max_quote_drift_bps(&self, origin: TradeOrigin) -> u32 {
+        if origin.is_automated() {
+            self.automated_quote_drift_bps
+        } else {
+            self.manual_quote_drift_bps
+        }
+    }
+
     pub fn check_mobile_quick_trade(
         &self,
         check: SafetyCheck,
@@ -347,6 +438,34 @@ mod tests {
         assert_eq!(result.violations[0].rule, "max_daily_trades");
     }
 
+    #[test]
+    fn test_most_restrictive_scoped_limit_wins() {
+        let bonk = SafetyScope::Token("BONK".to_string());
+        let mut policy = SafetyPolicy::default();
+        policy.token_limits = Some(ScopeLimits {
+            cooldown_seconds: Some(60),
+            max_daily_trades: Some(10),
+        });
+        policy.scoped_limits.push(ScopedLimits {
+            scope: bonk.clone(),
+            limits: ScopeLimits {
+                cooldown_seconds: Some(30),
+                max_daily_trades: Some(2),
+            },
+        });
+
+        let limits = policy.limits_for(&bonk);
+        assert_eq!(limits.cooldown_seconds, Some(60));
+        assert_eq!(limits.max_daily_trades, Some(2));
+        assert_eq!(
+            policy.limits_for(&SafetyScope::Strategy("dca".to_string())),
+            ScopeLimits::default()
+        );
+
+        policy.cooldown_enabled = false;
+        assert_eq!(policy.limits_for(&bonk).cooldown_seconds, None);
+    }
+
     #[test]
     fn test_insurance_required() {
         let mut engine = PolicyEngine::new(SafetyPolicy::default());
@@ -0,0 +1,391 @@
+// Trading hours. Windows are wall-clock times in the user's timezone, so
+// every check converts the instant to local time first and a window keeps
+// its local hours across DST changes.
+
+use chrono::{DateTime, Datelike, Days, NaiveDate, NaiveTime, TimeZone, Timelike, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+const MINUTES_PER_DAY: u32 = 24 * 60;
+/// Granularity used to step past local times skipped by a DST jump.
+const GAP_STEP_MINUTES: u32 = 15;
+
+/// Where a trade was placed. Decides whether the trading schedule applies.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TradeOrigin {
+    #[default]
+    Manual,
+    Voice,
+    MobileQuickTrade,
+    AutoTrading,
+    Dca,
+}
+
+impl TradeOrigin {
+    /// Placed by a bot rather than confirmed by the user.
+    pub fn is_automated(&self) -> bool {
+        matches!(self, TradeOrigin::AutoTrading | TradeOrigin::Dca)
+    }
+}
+
+/// An allowed trading window on one weekday.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TradingWindow {
+    /// 0 = Sunday, as in DND schedules.
+    pub weekday: u8,
+    /// Local "HH:MM".
+    pub start: String,
+    /// Local "HH:MM" after `start`; "24:00" for the end of the day. Overnight
+    /// hours are two windows.
+    pub end: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TradingSchedule {
+    pub enabled: bool,
+    /// IANA name, e.g. "America/New_York".
+    pub timezone: String,
+    pub windows: Vec<TradingWindow>,
+    /// Let auto-trading strategies trade outside the windows.
+    #[serde(default)]
+    pub exempt_auto_trading: bool,
+    /// Let DCA purchases run outside the windows.
+    #[serde(default)]
+    pub exempt_dca: bool,
+}
+
+impl Default for TradingSchedule {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            timezone: "UTC".to_string(),
+            windows: Vec::new(),
+            exempt_auto_trading: false,
+            exempt_dca: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum TradingScheduleError {
+    #[error("unknown timezone: {0}")]
+    UnknownTimezone(String),
+    #[error("an enabled trading schedule needs at least one window")]
+    NoWindows,
+    #[error("invalid weekday {0}; expected 0 (Sunday) to 6")]
+    InvalidWeekday(u8),
+    #[error("invalid time {0:?}; expected HH:MM")]
+    InvalidTime(String),
+    #[error("window {start}-{end} on day {weekday} is empty")]
+    EmptyWindow {
+        weekday: u8,
+        start: String,
+        end: String,
+    },
+    #[error("windows {first} and {second} on day {weekday} overlap")]
+    Overlapping {
+        weekday: u8,
+        first: String,
+        second: String,
+    },
+}
+
+/// A trade refused because it falls outside the trading schedule.
+#[derive(Debug, Clone, PartialEq, Serialize, thiserror::Error)]
+#[serde(rename_all = "camelCase")]
+#[error("Trading is locked outside scheduled hours ({timezone}){}", next_allowed_suffix(.next_allowed_at))]
+pub struct OutsideTradingHours {
+    pub timezone: String,
+    /// `None` when no window would ever open.
+    pub next_allowed_at: Option<DateTime<Utc>>,
+}
+
+fn next_allowed_suffix(next_allowed_at: &Option<DateTime<Utc>>) -> String {
+    next_allowed_at
+        .map(|at| format!("; next allowed at {}", at.to_rfc3339()))
+        .unwrap_or_default()
+}
+
+/// Minutes since local midnight; "24:00" is the end of the day.
+fn parse_minutes(time: &str) -> Option<u32> {
+    if time == "24:00" {
+        return Some(MINUTES_PER_DAY);
+    }
+    NaiveTime::parse_from_str(time, "%H:%M")
+        .ok()
+        .map(|time| time.hour() * 60 + time.minute())
+}
+
+impl TradingWindow {
+    fn span(&self) -> Option<(u32, u32)> {
+        Some((parse_minutes(&self.start)?, parse_minutes(&self.end)?))
+    }
+
+    fn label(&self) -> String {
+        format!("{}-{}", self.start, self.end)
+    }
+}
+
+impl TradingSchedule {
+    /// Checks the timezone and windows as the schedule editor saves them.
+    pub fn validate(&self) -> Result<(), TradingScheduleError> {
+        self.timezone
+            .parse::<Tz>()
+            .map_err(|_| TradingScheduleError::UnknownTimezone(self.timezone.clone()))?;
+        if self.enabled && self.windows.is_empty() {
+            return Err(TradingScheduleError::NoWindows);
+        }
+
+        let mut spans = Vec::with_capacity(self.windows.len());
+        for window in &self.windows {
+            if window.weekday > 6 {
+                return Err(TradingScheduleError::InvalidWeekday(window.weekday));
+            }
+            let start = parse_minutes(&window.start)
+                .filter(|start| *start < MINUTES_PER_DAY)
+                .ok_or_else(|| TradingScheduleError::InvalidTime(window.start.clone()))?;
+            let end = parse_minutes(&window.end)
+                .ok_or_else(|| TradingScheduleError::InvalidTime(window.end.clone()))?;
+            if end <= start {
+                return Err(TradingScheduleError::EmptyWindow {
+                    weekday: window.weekday,
+                    start: window.start.clone(),
+                    end: window.end.clone(),
+                });
+            }
+            spans.push((window.weekday, start, end, window));
+        }
+
+        spans.sort_by_key(|(weekday, start, _, _)| (*weekday, *start));
+        for pair in spans.windows(2) {
+            let (weekday, _, first_end, first) = pair[0];
+            let (next_weekday, second_start, _, second) = pair[1];
+            if weekday == next_weekday && second_start < first_end {
+                return Err(TradingScheduleError::Overlapping {
+                    weekday,
+                    first: first.label(),
+                    second: second.label(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether trades placed from `origin` are held to the windows.
+    pub fn applies_to(&self, origin: TradeOrigin) -> bool {
+        self.enabled
+            && match origin {
+                TradeOrigin::AutoTrading => !self.exempt_auto_trading,
+                TradeOrigin::Dca => !self.exempt_dca,
+                TradeOrigin::Manual | TradeOrigin::Voice | TradeOrigin::MobileQuickTrade => true,
+            }
+    }
+
+    /// Falls back to UTC for a timezone that fails to parse; `validate`
+    /// keeps those out of saved policies.
+    fn tz(&self) -> Tz {
+        self.timezone.parse().unwrap_or(Tz::UTC)
+    }
+
+    fn spans_on(&self, weekday: u8) -> Vec<(u32, u32)> {
+        let mut spans: Vec<(u32, u32)> = self
+            .windows
+            .iter()
+            .filter(|window| window.weekday == weekday)
+            .filter_map(TradingWindow::span)
+            .collect();
+        spans.sort_unstable();
+        spans
+    }
+
+    pub fn is_open_at(&self, now: DateTime<Utc>) -> bool {
+        let local = now.with_timezone(&self.tz());
+        let minute = local.hour() * 60 + local.minute();
+        self.spans_on(local.weekday().num_days_from_sunday() as u8)
+            .into_iter()
+            .any(|(start, end)| minute >= start && minute < end)
+    }
+
+    /// The first instant at or after `now` inside a window, looking a week
+    /// ahead.
+    pub fn next_open_at(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        if self.is_open_at(now) {
+            return Some(now);
+        }
+        let tz = self.tz();
+        let today = now.with_timezone(&tz).date_naive();
+        (0..=7)
+            .filter_map(|offset| today.checked_add_days(Days::new(offset)))
+            .flat_map(|date| {
+                let weekday = date.weekday().num_days_from_sunday() as u8;
+                self.spans_on(weekday)
+                    .into_iter()
+                    .map(move |(start, end)| (date, start, end))
+            })
+            .find_map(|(date, start, end)| first_instant_after(&tz, date, start, end, now))
+    }
+
+    /// `Err` when a trade from `origin` at `now` falls outside the windows.
+    pub fn check(
+        &self,
+        origin: TradeOrigin,
+        now: DateTime<Utc>,
+    ) -> Result<(), OutsideTradingHours> {
+        if !self.applies_to(origin) || self.is_open_at(now) {
+            return Ok(());
+        }
+        Err(OutsideTradingHours {
+            timezone: self.timezone.clone(),
+            next_allowed_at: self.next_open_at(now),
+        })
+    }
+}
+
+/// The first instant after `now` whose local time on `date` falls in
+/// `[start, end)`. A start skipped by a spring-forward jump opens at the
+/// first local time that exists; a start repeated by a fall-back change
+/// opens at whichever occurrence is still ahead.
+fn first_instant_after(
+    tz: &Tz,
+    date: NaiveDate,
+    start: u32,
+    end: u32,
+    now: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    let mut minute = start;
+    while minute < end {
+        let local = date.and_hms_opt(minute / 60, minute % 60, 0)?;
+        let candidates = tz.from_local_datetime(&local);
+        if let Some(earliest) = candidates.earliest() {
+            return [Some(earliest), candidates.latest()]
+                .into_iter()
+                .flatten()
+                .map(|instant| instant.with_timezone(&Utc))
+                .find(|instant| *instant > now);
+        }
+        minute += GAP_STEP_MINUTES;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(weekday: u8, start: &str, end: &str) -> TradingWindow {
+        TradingWindow {
+            weekday,
+            start: start.to_string(),
+            end: end.to_string(),
+        }
+    }
+
+    fn schedule(timezone: &str, windows: Vec<TradingWindow>) -> TradingSchedule {
+        TradingSchedule {
+            enabled: true,
+            timezone: timezone.to_string(),
+            windows,
+            ..TradingSchedule::default()
+        }
+    }
+
+    fn utc(text: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(text)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn validation_rejects_empty_and_overlapping_windows() {
+        assert_eq!(
+            schedule("UTC", vec![]).validate(),
+            Err(TradingScheduleError::NoWindows)
+        );
+        assert!(matches!(
+            schedule("UTC", vec![window(1, "10:00", "10:00")]).validate(),
+            Err(TradingScheduleError::EmptyWindow { .. })
+        ));
+        assert!(matches!(
+            schedule(
+                "UTC",
+                vec![window(1, "09:00", "12:00"), window(1, "11:30", "14:00")]
+            )
+            .validate(),
+            Err(TradingScheduleError::Overlapping { weekday: 1, .. })
+        ));
+        assert!(matches!(
+            schedule("Mars/Olympus", vec![window(1, "09:00", "12:00")]).validate(),
+            Err(TradingScheduleError::UnknownTimezone(_))
+        ));
+
+        // Touching windows and the same hours on other days are fine.
+        assert!(schedule(
+            "UTC",
+            vec![
+                window(1, "09:00", "12:00"),
+                window(1, "12:00", "24:00"),
+                window(2, "09:00", "12:00"),
+            ]
+        )
+        .validate()
+        .is_ok());
+    }
+
+    #[test]
+    fn exemptions_apply_per_origin() {
+        let mut schedule = schedule("UTC", vec![window(1, "09:00", "17:00")]);
+        schedule.exempt_dca = true;
+        // Sunday.
+        let now = utc("2024-03-10T12:00:00Z");
+
+        assert!(schedule.check(TradeOrigin::Dca, now).is_ok());
+        assert!(schedule.check(TradeOrigin::AutoTrading, now).is_err());
+        assert!(schedule.check(TradeOrigin::MobileQuickTrade, now).is_err());
+    }
+
+    #[test]
+    fn windows_keep_local_hours_across_spring_forward() {
+        // New York moves from UTC-5 to UTC-4 at 02:00 on Sunday 2024-03-10.
+        let schedule = schedule(
+            "America/New_York",
+            vec![window(5, "09:30", "16:00"), window(1, "09:30", "16:00")],
+        );
+
+        // Friday 09:30 EST is 14:30 UTC; Monday 09:30 EDT is 13:30 UTC.
+        assert!(schedule.is_open_at(utc("2024-03-08T14:30:00Z")));
+        assert!(!schedule.is_open_at(utc("2024-03-08T14:29:00Z")));
+        assert!(schedule.is_open_at(utc("2024-03-11T13:30:00Z")));
+        assert!(!schedule.is_open_at(utc("2024-03-11T20:30:00Z")));
+
+        let err = schedule
+            .check(TradeOrigin::Manual, utc("2024-03-09T15:00:00Z"))
+            .unwrap_err();
+        assert_eq!(err.next_allowed_at, Some(utc("2024-03-11T13:30:00Z")));
+    }
+
+    #[test]
+    fn window_starting_in_a_dst_gap_opens_when_local_time_resumes() {
+        // 02:30 does not exist in New York on 2024-03-10; 03:00 EDT does.
+        let schedule = schedule("America/New_York", vec![window(0, "02:30", "04:00")]);
+
+        assert_eq!(
+            schedule.next_open_at(utc("2024-03-10T06:00:00Z")),
+            Some(utc("2024-03-10T07:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn repeated_hour_after_fall_back_opens_again() {
+        // New York repeats 01:00-02:00 on Sunday 2024-11-03.
+        let schedule = schedule("America/New_York", vec![window(0, "01:30", "01:45")]);
+
+        // 01:30 EDT.
+        assert!(schedule.is_open_at(utc("2024-11-03T05:30:00Z")));
+        // 01:00 EST, after the first pass; the second 01:30 is still ahead.
+        assert_eq!(
+            schedule.next_open_at(utc("2024-11-03T06:00:00Z")),
+            Some(utc("2024-11-03T06:30:00Z"))
+        );
+    }
+}
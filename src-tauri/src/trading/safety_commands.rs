@@ -1,9 +1,29 @@
+use crate::auth::two_factor::{TwoFactorError, TwoFactorManager};
+use crate::config::DataPaths;
+use crate::errors::AppError;
+use crate::journal::{EntryType, JournalEntry, SharedJournalDatabase};
+use crate::security::activity_log::{ActivityAction, ActivityLogFilter, ActivityLogger};
+use crate::security::keystore::Keystore;
+use crate::trading::execution_audit::{record_execution_stage, ExecutionStage};
+use crate::trading::safety::cooldown::{CounterReset, ScopeCounterEntry};
+use crate::trading::safety::coverage::{
+    self, ClaimDraft, ClaimEvidence, ClaimStatus, CoverageScope, InsurancePolicy,
+    SharedInsuranceLedger, DEFAULT_POLICY_DURATION_DAYS,
+};
 use crate::trading::safety::policy::SafetyPolicy;
 use crate::trading::safety::{
-    InsuranceProvider, SafetyCheckRequest, SafetyCheckResult, SharedSafetyEngine,
+    InsuranceProvider, SafetyCheckRequest, SafetyCheckResult, SafetyScope, SharedSafetyEngine,
+    TradeOrigin,
 };
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use tauri::State;
+use serde_json::json;
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager, State};
+use uuid::Uuid;
+
+const CLAIM_EVIDENCE_LOG_LIMIT: i64 = 1000;
+const TRADING_HOURS_OVERRIDE_TAG: &str = "trading_hours_override";
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SafetyConfigUpdate {
@@ -13,19 +33,44 @@ pub struct SafetyConfigUpdate {
 #[tauri::command]
 pub async fn check_trade_safety(
     request: SafetyCheckRequest,
+    correlation_id: Option<String>,
     safety_engine: State<'_, SharedSafetyEngine>,
-) -> Result<SafetyCheckResult, String> {
-    let mut engine = safety_engine.write().await;
-    engine.check_trade_safety(request).await
+) -> Result<SafetyCheckResult, AppError> {
+    let result = safety_engine
+        .write()
+        .await
+        .check_trade_safety(request)
+        .await;
+    let stage = match &result {
+        Ok(result) => ExecutionStage::SafetyCheck {
+            allowed: result.allowed,
+            result: serde_json::to_value(result).unwrap_or_default(),
+        },
+        Err(e) => ExecutionStage::Error {
+            source: "safety".to_string(),
+            message: e.to_string(),
+            retrying: false,
+        },
+    };
+    record_execution_stage(correlation_id.as_deref(), stage).await;
+    Ok(result?)
 }
 
 #[tauri::command]
 pub async fn approve_trade(
     wallet_address: String,
+    token_mint: Option<String>,
+    strategy_id: Option<String>,
     safety_engine: State<'_, SharedSafetyEngine>,
 ) -> Result<(), String> {
     let mut engine = safety_engine.write().await;
-    engine.approve_trade(&wallet_address);
+    engine
+        .approve_trade(
+            &wallet_address,
+            token_mint.as_deref(),
+            strategy_id.as_deref(),
+        )
+        .await;
     Ok(())
 }
 
@@ -42,11 +87,124 @@ pub async fn update_safety_policy(
     policy: SafetyPolicy,
     safety_engine: State<'_, SharedSafetyEngine>,
 ) -> Result<(), String> {
+    policy
+        .trading_schedule
+        .validate()
+        .map_err(|e| format!("Invalid trading schedule: {}", e))?;
     let mut engine = safety_engine.write().await;
     engine.update_policy(policy);
     Ok(())
 }
 
+/// Lifts the trading schedule for a short while after a 2FA check. The note
+/// explaining the exception is saved as a journal entry, and the override or
+/// the failed attempt is recorded in the activity log.
+#[tauri::command]
+pub async fn override_trading_hours(
+    wallet_address: String,
+    code: String,
+    note: String,
+    safety_engine: State<'_, SharedSafetyEngine>,
+    two_factor: State<'_, TwoFactorManager>,
+    keystore: State<'_, Keystore>,
+    journal: State<'_, SharedJournalDatabase>,
+    logger: State<'_, ActivityLogger>,
+) -> Result<DateTime<Utc>, AppError> {
+    let note = note.trim().to_string();
+    if note.is_empty() {
+        return Err(
+            AppError::validation("A journal note explaining the exception is required")
+                .with_code("journal_note_required"),
+        );
+    }
+
+    let now = Utc::now();
+    let outside = safety_engine
+        .read()
+        .await
+        .check_trading_hours(TradeOrigin::Manual, now)
+        .err();
+
+    let verified = two_factor.verify(&code, keystore.inner());
+    if !matches!(verified, Ok(true)) {
+        let error = match verified {
+            Err(TwoFactorError::NotEnrolled) => AppError::unauthorized(
+                "Two-factor authentication must be enabled to override trading hours",
+            )
+            .with_code("two_factor_not_enrolled"),
+            _ => AppError::unauthorized("Two-factor verification failed")
+                .with_code("two_factor_failed"),
+        };
+        let details = json!({ "note": note, "reason": error.code() });
+        if let Err(e) = logger
+            .log_activity(
+                &wallet_address,
+                ActivityAction::TradingHoursOverride,
+                details,
+                false,
+                None,
+            )
+            .await
+        {
+            eprintln!("Failed to log trading hours override: {}", e);
+        }
+        return Err(error);
+    }
+
+    let entry = JournalEntry {
+        id: Uuid::new_v4().to_string(),
+        timestamp: now.timestamp(),
+        trade_id: None,
+        entry_type: EntryType::PreTrade,
+        strategy_tags: vec![TRADING_HOURS_OVERRIDE_TAG.to_string()],
+        emotions: Default::default(),
+        notes: note.clone(),
+        market_conditions: Default::default(),
+        confidence_level: 0.5,
+        position_size: None,
+        entry_price: None,
+        exit_price: None,
+        outcome: None,
+        lessons_learned: None,
+        attachments: Vec::new(),
+        created_at: now.timestamp(),
+        updated_at: now.timestamp(),
+        token_mint: None,
+        token_annotation: None,
+    };
+    journal
+        .read()
+        .await
+        .create_entry(&entry)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to save journal note: {}", e)))?;
+
+    let until = safety_engine.write().await.override_trading_hours(now);
+
+    let details = json!({
+        "note": note,
+        "journalEntryId": entry.id,
+        "until": until.to_rfc3339(),
+        "nextAllowedAt": outside
+            .and_then(|outside| outside.next_allowed_at)
+            .map(|at| at.to_rfc3339()),
+    });
+    if let Err(e) = logger
+        .log_activity(
+            &wallet_address,
+            ActivityAction::TradingHoursOverride,
+            details,
+            true,
+            None,
+        )
+        .await
+    {
+        eprintln!("Failed to log trading hours override: {}", e);
+    }
+
+    Ok(until)
+}
+
 #[tauri::command]
 pub async fn get_cooldown_status(
     wallet_address: String,
@@ -56,13 +214,74 @@ pub async fn get_cooldown_status(
     Ok(engine.get_cooldown_status(&wallet_address))
 }
 
+/// Resets counters for the wallet and scope (all when omitted) and records the
+/// reset in the activity log.
+async fn reset_counters(
+    wallet_address: Option<String>,
+    scope: Option<SafetyScope>,
+    reset: CounterReset,
+    safety_engine: &SharedSafetyEngine,
+    logger: &ActivityLogger,
+) -> Vec<ScopeCounterEntry> {
+    let cleared = safety_engine
+        .write()
+        .await
+        .reset_limits(wallet_address.as_deref(), scope.as_ref(), reset)
+        .await;
+
+    let details = json!({
+        "reset": reset,
+        "scope": scope.as_ref().map(SafetyScope::key).unwrap_or_else(|| "all".to_string()),
+        "cleared": cleared.len(),
+    });
+    if let Err(e) = logger
+        .log_activity(
+            wallet_address.as_deref().unwrap_or("all"),
+            ActivityAction::ResetLimits,
+            details,
+            true,
+            None,
+        )
+        .await
+    {
+        eprintln!("Failed to log safety limit reset: {}", e);
+    }
+
+    cleared
+}
+
 #[tauri::command]
 pub async fn reset_daily_limits(
+    scope: Option<SafetyScope>,
+    wallet_address: Option<String>,
     safety_engine: State<'_, SharedSafetyEngine>,
-) -> Result<(), String> {
-    let mut engine = safety_engine.write().await;
-    engine.reset_daily_limits();
-    Ok(())
+    logger: State<'_, ActivityLogger>,
+) -> Result<Vec<ScopeCounterEntry>, String> {
+    Ok(reset_counters(
+        wallet_address,
+        scope,
+        CounterReset::DailyCounts,
+        &safety_engine,
+        &logger,
+    )
+    .await)
+}
+
+#[tauri::command]
+pub async fn reset_cooldowns(
+    scope: Option<SafetyScope>,
+    wallet_address: Option<String>,
+    safety_engine: State<'_, SharedSafetyEngine>,
+    logger: State<'_, ActivityLogger>,
+) -> Result<Vec<ScopeCounterEntry>, String> {
+    Ok(reset_counters(
+        wallet_address,
+        scope,
+        CounterReset::Cooldowns,
+        &safety_engine,
+        &logger,
+    )
+    .await)
 }
 
 #[tauri::command]
@@ -88,15 +307,209 @@ pub async fn select_insurance(
     trade_amount_usd: f64,
     price_impact_percent: f64,
     mev_risk_level: f64,
+    coverage_scope: Option<CoverageScope>,
+    duration_days: Option<i64>,
     safety_engine: State<'_, SharedSafetyEngine>,
+    ledger: State<'_, SharedInsuranceLedger>,
 ) -> Result<crate::trading::safety::insurance::InsuranceSelection, String> {
-    let mut engine = safety_engine.write().await;
-    engine.select_insurance(
-        &provider_id,
-        trade_amount_usd,
-        price_impact_percent,
-        mev_risk_level,
-    )
+    let duration_days = duration_days.unwrap_or(DEFAULT_POLICY_DURATION_DAYS);
+    if duration_days <= 0 {
+        return Err("Policy duration must be at least one day".to_string());
+    }
+
+    let (mut selection, provider) = {
+        let mut engine = safety_engine.write().await;
+        let selection = engine.select_insurance(
+            &provider_id,
+            trade_amount_usd,
+            price_impact_percent,
+            mev_risk_level,
+        )?;
+        let provider = engine
+            .get_insurance_provider(&provider_id)
+            .ok_or_else(|| format!("Insurance provider {} not found", provider_id))?;
+        (selection, provider)
+    };
+
+    let policy = InsurancePolicy::from_selection(
+        &selection,
+        &provider.name,
+        coverage_scope.unwrap_or_default(),
+        duration_days,
+    );
+    ledger.save_policy(&policy).await?;
+    selection.policy_id = Some(policy.id);
+    Ok(selection)
+}
+
+#[tauri::command]
+pub async fn get_active_policies(
+    ledger: State<'_, SharedInsuranceLedger>,
+) -> Result<Vec<InsurancePolicy>, String> {
+    ledger.active_policies().await
+}
+
+#[tauri::command]
+pub async fn get_policy(
+    id: String,
+    ledger: State<'_, SharedInsuranceLedger>,
+) -> Result<InsurancePolicy, String> {
+    ledger
+        .get_policy(&id)
+        .await?
+        .ok_or_else(|| format!("Insurance policy {} not found", id))
+}
+
+/// Activity of the covered wallets during the policy, their transactions
+/// (only `tx_signatures` when given) and audit reports for covered tokens.
+async fn gather_claim_evidence(
+    app: &AppHandle,
+    policy: &InsurancePolicy,
+    tx_signatures: Option<&[String]>,
+) -> ClaimEvidence {
+    let mut evidence = ClaimEvidence::default();
+    let period_end = policy.ends_at.min(Utc::now());
+
+    let logger = app.state::<ActivityLogger>();
+    for wallet in &policy.scope.wallets {
+        let filter = ActivityLogFilter {
+            wallet_address: Some(wallet.clone()),
+            start_date: Some(policy.starts_at.to_rfc3339()),
+            end_date: Some(period_end.to_rfc3339()),
+            limit: Some(CLAIM_EVIDENCE_LOG_LIMIT),
+            ..Default::default()
+        };
+        match logger.get_logs(filter).await {
+            Ok(logs) => evidence.activity_logs.extend(logs),
+            Err(e) => eprintln!("Failed to collect activity logs for claim: {}", e),
+        }
+    }
+
+    if let Ok(trading) = crate::trading::limit_orders::require_state() {
+        let db = trading.db.read().await;
+        for wallet in &policy.scope.wallets {
+            let orders = match db.get_order_history(wallet, CLAIM_EVIDENCE_LOG_LIMIT).await {
+                Ok(orders) => orders,
+                Err(e) => {
+                    eprintln!("Failed to collect transactions for claim: {}", e);
+                    continue;
+                }
+            };
+            evidence
+                .transactions
+                .extend(orders.into_iter().filter(|order| {
+                    let Some(signature) = &order.tx_signature else {
+                        return false;
+                    };
+                    match tx_signatures {
+                        Some(signatures) => signatures.contains(signature),
+                        None => {
+                            order.created_at >= policy.starts_at && order.created_at <= period_end
+                        }
+                    }
+                }));
+        }
+    }
+
+    for position in &policy.scope.positions {
+        match crate::security::audit::scan_contract(position.mint.clone(), app.clone()).await {
+            Ok(report) => evidence.audit_reports.push(report),
+            Err(e) => eprintln!(
+                "Failed to collect audit report for {}: {}",
+                position.mint, e
+            ),
+        }
+    }
+
+    evidence
+}
+
+#[tauri::command]
+pub async fn create_claim_draft(
+    app: AppHandle,
+    policy_id: String,
+    description: String,
+    incident_at: DateTime<Utc>,
+    claimed_amount_usd: f64,
+    tx_signatures: Option<Vec<String>>,
+    ledger: State<'_, SharedInsuranceLedger>,
+) -> Result<ClaimDraft, String> {
+    let policy = ledger
+        .get_policy(&policy_id)
+        .await?
+        .ok_or_else(|| format!("Insurance policy {} not found", policy_id))?;
+    if incident_at < policy.starts_at || incident_at > policy.ends_at {
+        return Err(format!(
+            "Incident at {} is outside the coverage period of policy {}",
+            incident_at.to_rfc3339(),
+            policy_id
+        ));
+    }
+    if claimed_amount_usd <= 0.0 || claimed_amount_usd > policy.coverage_usd {
+        return Err(format!(
+            "Claimed amount must be between $0 and the ${:.2} covered",
+            policy.coverage_usd
+        ));
+    }
+
+    let evidence = gather_claim_evidence(&app, &policy, tx_signatures.as_deref()).await;
+    let claim = ClaimDraft {
+        id: Uuid::new_v4().to_string(),
+        policy_id,
+        status: ClaimStatus::Draft,
+        description,
+        incident_at,
+        claimed_amount_usd,
+        evidence,
+        created_at: Utc::now(),
+        exported_at: None,
+        package_path: None,
+    };
+    ledger.save_claim(&claim).await?;
+    Ok(claim)
+}
+
+#[tauri::command]
+pub async fn list_claim_drafts(
+    policy_id: String,
+    ledger: State<'_, SharedInsuranceLedger>,
+) -> Result<Vec<ClaimDraft>, String> {
+    ledger.list_claims(&policy_id).await
+}
+
+/// Writes the claim's evidence package as a zip, by default to
+/// `insurance_claims/` in the app data directory, and returns the claim.
+#[tauri::command]
+pub async fn export_claim_package(
+    app: AppHandle,
+    claim_id: String,
+    path: Option<String>,
+    ledger: State<'_, SharedInsuranceLedger>,
+) -> Result<ClaimDraft, String> {
+    let mut claim = ledger
+        .get_claim(&claim_id)
+        .await?
+        .ok_or_else(|| format!("Claim draft {} not found", claim_id))?;
+    let policy = ledger
+        .get_policy(&claim.policy_id)
+        .await?
+        .ok_or_else(|| format!("Insurance policy {} not found", claim.policy_id))?;
+
+    let path = match path {
+        Some(path) => PathBuf::from(path),
+        None => app
+            .app_data_root()
+            .map_err(|e| format!("Failed to resolve app data directory: {}", e))?
+            .join("insurance_claims")
+            .join(format!("claim-{}.zip", claim.id)),
+    };
+    coverage::write_claim_package(&claim, &policy, &path)?;
+
+    claim.status = ClaimStatus::Exported;
+    claim.exported_at = Some(Utc::now());
+    claim.package_path = Some(path.display().to_string());
+    ledger.save_claim(&claim).await?;
+    Ok(claim)
 }
 
 #[tauri::command]
@@ -68,6 +68,16 @@ impl std::fmt::Display for OrderStatus {
     }
 }
 
+/// What placed an order the user didn't place themselves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum OrderOrigin {
+    /// A stop-loss or take-profit bracketing a filled buy; see
+    /// `trading::auto_bracket`.
+    AutoBracket,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Order {
     pub id: String,
@@ -80,6 +90,9 @@ pub struct Order {
     pub output_symbol: String,
     pub amount: f64,
     pub filled_amount: f64,
+    /// Execution price, recorded for fills since the column was added.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fill_price: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub limit_price: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -92,6 +105,10 @@ pub struct Order {
     pub lowest_price: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub linked_order_id: Option<String>,
+    /// USD the order book must hold within 1% of the mid on the side the
+    /// order takes from before the order may trigger.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_book_depth_usd: Option<f64>,
     pub slippage_bps: i32,
     pub priority_fee_micro_lamports: i32,
     pub wallet_address: String,
@@ -103,6 +120,13 @@ pub struct Order {
     pub tx_signature: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error_message: Option<String>,
+    /// Ties the order to the quotes, safety checks and transactions of its
+    /// execution path; see `get_execution_audit`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub correlation_id: Option<String>,
+    /// Set on orders placed automatically; `None` for the user's own.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin: Option<OrderOrigin>,
 }
 
 impl<'r> sqlx::FromRow<'r, sqlx::sqlite::SqliteRow> for Order {
@@ -120,12 +144,15 @@ impl<'r> sqlx::FromRow<'r, sqlx::sqlite::SqliteRow> for Order {
             output_symbol: row.try_get("output_symbol")?,
             amount: row.try_get("amount")?,
             filled_amount: row.try_get("filled_amount")?,
+            fill_price: row.try_get("fill_price").unwrap_or(None),
             limit_price: row.try_get("limit_price")?,
             stop_price: row.try_get("stop_price")?,
             trailing_percent: row.try_get("trailing_percent")?,
             highest_price: row.try_get("highest_price")?,
             lowest_price: row.try_get("lowest_price")?,
             linked_order_id: row.try_get("linked_order_id")?,
+            // Absent from order tables copied before the column existed.
+            min_book_depth_usd: row.try_get("min_book_depth_usd").unwrap_or(None),
             slippage_bps: row.try_get("slippage_bps")?,
             priority_fee_micro_lamports: row.try_get("priority_fee_micro_lamports")?,
             wallet_address: row.try_get("wallet_address")?,
@@ -134,6 +161,8 @@ impl<'r> sqlx::FromRow<'r, sqlx::sqlite::SqliteRow> for Order {
             triggered_at: OptionalRfc3339DateTime::try_from(row.try_get::<Option<String>, _>("triggered_at")?)?.into(),
             tx_signature: row.try_get("tx_signature")?,
             error_message: row.try_get("error_message")?,
+            correlation_id: row.try_get("correlation_id").unwrap_or(None),
+            origin: row.try_get("origin").unwrap_or(None),
         })
     }
 }
@@ -155,9 +184,24 @@ pub struct CreateOrderRequest {
     pub trailing_percent: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub linked_order_id: Option<String>,
+    /// Holds the order until the token's order book shows this much USD
+    /// depth within 1% of the mid. Only for tokens with an order book.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_book_depth_usd: Option<f64>,
     pub slippage_bps: i32,
     pub priority_fee_micro_lamports: i32,
     pub wallet_address: String,
+    /// UUID chosen by the client; retrying with the same id returns the
+    /// order created by the first attempt.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_request_id: Option<String>,
+    /// Id the client passed to the quote, safety check and fee commands while
+    /// preparing the order. One is generated when absent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub correlation_id: Option<String>,
+    /// Set by the backend when it places the order; never by the client.
+    #[serde(skip)]
+    pub origin: Option<OrderOrigin>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
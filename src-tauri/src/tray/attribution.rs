@@ -0,0 +1,359 @@
+//! Today's P&L split by where it came from, shown in the tray menu and the
+//! portfolio widget. Trade P&L is grouped by the strategy source recorded
+//! with each trade in the performance database; DeFi yield is estimated from
+//! the APY of open positions.
+
+use super::{SharedTrayManager, TrayAlertPreview, TrayStats};
+use crate::alerts::SharedAlertManager;
+use crate::defi::position_manager::PositionManager;
+use crate::portfolio::SharedPortfolioData;
+use crate::wallet::multi_wallet::MultiWalletManager;
+use crate::wallet::performance::{SharedPerformanceDatabase, StrategySource};
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tauri::{AppHandle, Manager};
+
+const AGGREGATION_INTERVAL_SECS: u64 = 60;
+/// Positions come from protocol APIs, so the yield estimate is refreshed less
+/// often than the rest of the stats.
+const DEFI_REFRESH_MINUTES: i64 = 15;
+const TRAY_ALERT_PREVIEWS: usize = 3;
+/// Sources below this share of the day's absolute P&L are folded into
+/// "other", which keeps the tray and widget breakdowns short.
+pub const MIN_CONTRIBUTION_SHARE: f64 = 0.05;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PnlSource {
+    Manual,
+    Dca,
+    CopyTrading,
+    AutoTrading,
+    DefiYield,
+    Other,
+}
+
+impl PnlSource {
+    pub fn label(&self) -> &'static str {
+        match self {
+            PnlSource::Manual => "Manual trading",
+            PnlSource::Dca => "DCA bots",
+            PnlSource::CopyTrading => "Copy trading",
+            PnlSource::AutoTrading => "Auto-trading",
+            PnlSource::DefiYield => "DeFi yield",
+            PnlSource::Other => "Other",
+        }
+    }
+
+    /// Tags that no longer parse are still counted, under "other".
+    fn from_strategy_tag(tag: &str) -> Self {
+        match StrategySource::parse(tag) {
+            Ok(StrategySource::Manual) => PnlSource::Manual,
+            Ok(StrategySource::Dca(_)) => PnlSource::Dca,
+            Ok(StrategySource::Copy(_)) => PnlSource::CopyTrading,
+            Ok(StrategySource::Auto(_)) => PnlSource::AutoTrading,
+            Err(_) => PnlSource::Other,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PnlContribution {
+    pub source: PnlSource,
+    pub label: String,
+    pub pnl_value: f64,
+    /// Fraction (0-1) of the day's absolute P&L.
+    pub share: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PnlAttribution {
+    /// UTC day the figures cover.
+    pub date: Option<NaiveDate>,
+    pub pnl_value: f64,
+    /// Largest contributors first; "other", when present, comes last.
+    pub contributions: Vec<PnlContribution>,
+}
+
+impl PnlAttribution {
+    pub fn from_totals(date: NaiveDate, totals: HashMap<PnlSource, f64>) -> Self {
+        let absolute: f64 = totals.values().map(|value| value.abs()).sum();
+        let share = |value: f64| {
+            if absolute > 0.0 {
+                value / absolute
+            } else {
+                0.0
+            }
+        };
+
+        let mut contributions = Vec::new();
+        let mut other = 0.0;
+        let mut other_absolute = 0.0;
+        for (source, value) in totals {
+            if value == 0.0 {
+                continue;
+            }
+            if source == PnlSource::Other || share(value.abs()) < MIN_CONTRIBUTION_SHARE {
+                other += value;
+                other_absolute += value.abs();
+            } else {
+                contributions.push(PnlContribution {
+                    source,
+                    label: source.label().to_string(),
+                    pnl_value: value,
+                    share: share(value.abs()),
+                });
+            }
+        }
+        contributions.sort_by(|a, b| {
+            b.pnl_value
+                .abs()
+                .total_cmp(&a.pnl_value.abs())
+                .then_with(|| a.source.cmp(&b.source))
+        });
+        if other_absolute > 0.0 {
+            contributions.push(PnlContribution {
+                source: PnlSource::Other,
+                label: PnlSource::Other.label().to_string(),
+                pnl_value: other,
+                share: share(other_absolute),
+            });
+        }
+
+        Self {
+            date: Some(date),
+            pnl_value: contributions.iter().map(|c| c.pnl_value).sum(),
+            contributions,
+        }
+    }
+
+    /// The largest named source, falling back to "other" only when nothing
+    /// else contributed.
+    pub fn top_contributor(&self) -> Option<&PnlContribution> {
+        self.contributions
+            .iter()
+            .find(|c| c.source != PnlSource::Other)
+            .or_else(|| self.contributions.first())
+    }
+}
+
+/// Daily DeFi earnings across the user's own wallets, as last fetched.
+#[derive(Debug, Default)]
+struct DefiYieldEstimate {
+    earnings_24h: f64,
+    fetched_at: Option<DateTime<Utc>>,
+}
+
+impl DefiYieldEstimate {
+    fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.fetched_at.map_or(true, |at| {
+            now - at >= Duration::minutes(DEFI_REFRESH_MINUTES)
+        })
+    }
+
+    /// Watch-only wallets are left out: their yield is not the user's.
+    async fn refresh(&mut self, app: &AppHandle, now: DateTime<Utc>) {
+        self.fetched_at = Some(now);
+        let wallets: Vec<String> = match app.try_state::<MultiWalletManager>() {
+            Some(manager) => match manager.list_wallets() {
+                Ok(wallets) => wallets
+                    .into_iter()
+                    .filter(|wallet| !wallet.watch_only)
+                    .map(|wallet| wallet.public_key)
+                    .collect(),
+                Err(e) => {
+                    eprintln!("Tray stats: failed to list wallets: {}", e);
+                    return;
+                }
+            },
+            None => return,
+        };
+
+        let positions = PositionManager::new();
+        let mut earnings = 0.0;
+        for wallet in &wallets {
+            match positions.build_portfolio_summary(wallet).await {
+                Ok(summary) => earnings += summary.total_earnings_24h,
+                Err(e) => eprintln!("Tray stats: failed to read DeFi positions: {}", e),
+            }
+        }
+        self.earnings_24h = earnings;
+    }
+}
+
+async fn todays_attribution(
+    app: &AppHandle,
+    defi_earnings_24h: f64,
+    now: DateTime<Utc>,
+) -> PnlAttribution {
+    let start = now.date_naive().and_time(NaiveTime::MIN).and_utc();
+    let mut totals: HashMap<PnlSource, f64> = HashMap::new();
+
+    if let Some(db) = app.try_state::<SharedPerformanceDatabase>() {
+        match db.read().await.get_realized_pnl_by_source(start).await {
+            Ok(rows) => {
+                for (tag, pnl) in rows {
+                    *totals
+                        .entry(PnlSource::from_strategy_tag(&tag))
+                        .or_default() += pnl;
+                }
+            }
+            Err(e) => eprintln!("Tray stats: failed to read trade P&L: {}", e),
+        }
+    }
+
+    // Yield accrues continuously, so only the part of the day gone so far counts
+    let elapsed = (now - start).num_seconds() as f64 / 86_400.0;
+    *totals.entry(PnlSource::DefiYield).or_default() += defi_earnings_24h * elapsed;
+
+    PnlAttribution::from_totals(now.date_naive(), totals)
+}
+
+async fn recent_alert_previews(app: &AppHandle) -> Option<Vec<TrayAlertPreview>> {
+    let alerts = app.try_state::<SharedAlertManager>()?;
+    let alerts = match alerts.read().await.list_alerts().await {
+        Ok(alerts) => alerts,
+        Err(e) => {
+            eprintln!("Tray stats: failed to read alerts: {}", e);
+            return None;
+        }
+    };
+
+    let mut triggered: Vec<(DateTime<Utc>, TrayAlertPreview)> = alerts
+        .into_iter()
+        .filter_map(|alert| {
+            let at = DateTime::parse_from_rfc3339(alert.last_triggered_at.as_deref()?).ok()?;
+            let at = at.with_timezone(&Utc);
+            Some((
+                at,
+                TrayAlertPreview {
+                    summary: format!("{} at {}", alert.symbol, at.format("%H:%M UTC")),
+                    id: alert.id,
+                    title: alert.name,
+                },
+            ))
+        })
+        .collect();
+    triggered.sort_by(|a, b| b.0.cmp(&a.0));
+    Some(
+        triggered
+            .into_iter()
+            .take(TRAY_ALERT_PREVIEWS)
+            .map(|(_, preview)| preview)
+            .collect(),
+    )
+}
+
+async fn collect_tray_stats(
+    app: &AppHandle,
+    previous: TrayStats,
+    defi_earnings_24h: f64,
+    now: DateTime<Utc>,
+) -> TrayStats {
+    let mut stats = previous;
+    if let Some(data) = app.try_state::<SharedPortfolioData>() {
+        if let Ok(guard) = data.lock() {
+            let metrics = guard.metrics();
+            stats.portfolio_value = metrics.total_value;
+            stats.pnl_value = metrics.daily_pnl;
+            stats.pnl_percentage = metrics.daily_pnl_percent;
+        }
+    }
+    if let Some(previews) = recent_alert_previews(app).await {
+        stats.recent_alerts = previews;
+    }
+    stats.attribution = todays_attribution(app, defi_earnings_24h, now).await;
+    stats
+}
+
+/// Recomputes the tray stats, including today's P&L attribution, on a timer.
+/// The mobile portfolio widget reads its breakdown from the same stats.
+pub async fn run_tray_stats_aggregator(app: AppHandle) {
+    let mut interval =
+        tokio::time::interval(std::time::Duration::from_secs(AGGREGATION_INTERVAL_SECS));
+    let mut defi = DefiYieldEstimate::default();
+    loop {
+        interval.tick().await;
+        let Some(tray) = app.try_state::<SharedTrayManager>() else {
+            continue;
+        };
+
+        let now = Utc::now();
+        if defi.is_due(now) {
+            defi.refresh(&app, now).await;
+        }
+        let stats = collect_tray_stats(&app, tray.get_stats(), defi.earnings_24h, now).await;
+        if let Err(e) = tray.update_stats(&app, stats) {
+            eprintln!("Failed to update tray stats: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 3, 2).unwrap()
+    }
+
+    #[test]
+    fn small_sources_are_folded_into_other() {
+        let totals = HashMap::from([
+            (PnlSource::Manual, -40.0),
+            (PnlSource::Dca, 55.0),
+            (PnlSource::CopyTrading, 2.0),
+            (PnlSource::DefiYield, 1.0),
+            (PnlSource::Other, 2.0),
+            (PnlSource::AutoTrading, 0.0),
+        ]);
+        let attribution = PnlAttribution::from_totals(date(), totals);
+
+        let sources: Vec<PnlSource> = attribution.contributions.iter().map(|c| c.source).collect();
+        assert_eq!(
+            sources,
+            vec![PnlSource::Dca, PnlSource::Manual, PnlSource::Other]
+        );
+        assert!((attribution.pnl_value - 20.0).abs() < 1e-9);
+        let other = attribution.contributions.last().unwrap();
+        assert!((other.pnl_value - 5.0).abs() < 1e-9);
+        assert!((other.share - 0.05).abs() < 1e-9);
+        assert_eq!(
+            attribution.top_contributor().unwrap().source,
+            PnlSource::Dca
+        );
+    }
+
+    #[test]
+    fn empty_day_has_no_contributors() {
+        let attribution = PnlAttribution::from_totals(date(), HashMap::new());
+        assert!(attribution.contributions.is_empty());
+        assert!(attribution.top_contributor().is_none());
+        assert_eq!(attribution.pnl_value, 0.0);
+    }
+
+    #[test]
+    fn strategy_tags_map_to_sources() {
+        assert_eq!(PnlSource::from_strategy_tag("manual"), PnlSource::Manual);
+        assert_eq!(PnlSource::from_strategy_tag("dca:cfg-1"), PnlSource::Dca);
+        assert_eq!(
+            PnlSource::from_strategy_tag("copy:leader"),
+            PnlSource::CopyTrading
+        );
+        assert_eq!(
+            PnlSource::from_strategy_tag("auto:grid"),
+            PnlSource::AutoTrading
+        );
+        assert_eq!(PnlSource::from_strategy_tag("legacy:x"), PnlSource::Other);
+    }
+
+    #[test]
+    fn tray_stats_without_attribution_still_deserialize() {
+        let stats: TrayStats = serde_json::from_str(
+            r#"{"portfolio_value":1.0,"pnl_percentage":0.0,"pnl_value":0.0,"alert_count":0,"recent_alerts":[]}"#,
+        )
+        .unwrap();
+        assert!(stats.attribution.contributions.is_empty());
+    }
+}
@@ -1,3 +1,10 @@
+pub mod attribution;
+
+pub use attribution::*;
+
+use crate::config::privacy_mode::{PrivacyMasked, SharedPrivacyMode};
+use crate::config::DataPaths;
+use crate::security::kiosk::is_kiosk_active;
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 use std::cmp::min;
@@ -70,6 +77,13 @@ pub struct TrayStats {
     pub pnl_value: f64,
     pub alert_count: u32,
     pub recent_alerts: Vec<TrayAlertPreview>,
+    /// Today's P&L by source; filled in by the stats aggregator.
+    #[serde(default)]
+    pub attribution: PnlAttribution,
+}
+
+impl PrivacyMasked for TrayStats {
+    const MASKED_FIELDS: &'static [&'static str] = &["portfolio_value", "pnl_value"];
 }
 
 impl Default for TrayStats {
@@ -80,6 +94,7 @@ impl Default for TrayStats {
             pnl_value: 0.0,
             alert_count: 0,
             recent_alerts: Vec::new(),
+            attribution: PnlAttribution::default(),
         }
     }
 }
@@ -141,6 +156,12 @@ impl TrayManager {
     }
 
     fn handle_menu_event(app_handle: &AppHandle, menu_id: &str) {
+        // The menu is rebuilt without these items on entering kiosk mode,
+        // but a menu opened just before can still deliver them.
+        if matches!(menu_id, "settings" | "quit") && is_kiosk_active(app_handle) {
+            return;
+        }
+
         match menu_id {
             "open" => {
                 if let Some(window) = app_handle.get_webview_window("main") {
@@ -158,7 +179,7 @@ impl TrayManager {
                 }
             }
             "quit" => {
-                std::process::exit(0);
+                crate::errors::request_exit(app_handle, 0);
             }
             "alerts" => {
                 if let Some(window) = app_handle.get_webview_window("main") {
@@ -173,7 +194,7 @@ impl TrayManager {
     }
 
     pub fn initialize(&self, app_handle: &AppHandle) {
-        match app_handle.path().app_data_dir() {
+        match app_handle.app_data_root() {
             Ok(mut data_dir) => {
                 if let Err(err) = fs::create_dir_all(&data_dir) {
                     eprintln!("Failed to ensure tray settings directory: {err}");
@@ -229,7 +250,7 @@ impl TrayManager {
         Ok(())
     }
 
-    fn register_shortcut(&self, app_handle: &AppHandle) -> Result<(), String> {
+    fn unregister_shortcut(&self, app_handle: &AppHandle) -> Result<(), String> {
         use tauri_plugin_global_shortcut::{Code, Modifiers, Shortcut};
 
         let mut registered = self.shortcut.write();
@@ -247,6 +268,18 @@ impl TrayManager {
             registered.take();
         }
 
+        Ok(())
+    }
+
+    fn register_shortcut(&self, app_handle: &AppHandle) -> Result<(), String> {
+        use tauri_plugin_global_shortcut::{Code, Modifiers, Shortcut};
+
+        self.unregister_shortcut(app_handle)?;
+        if is_kiosk_active(app_handle) {
+            return Ok(());
+        }
+
+        let mut registered = self.shortcut.write();
         let shortcut_str = self.settings.read().restore_shortcut.clone();
         if let Some(shortcut_str) = shortcut_str {
             // Parse the shortcut string and create a Shortcut
@@ -305,29 +338,47 @@ impl TrayManager {
         builder = builder.item(&open);
 
         if settings.show_stats {
+            let privacy = app_handle
+                .try_state::<SharedPrivacyMode>()
+                .and_then(|privacy| privacy.read().ok().map(|privacy| privacy.clone()));
+            let mask = |amount: f64| match &privacy {
+                Some(privacy) => privacy.mask_amount(amount, None),
+                None => Some(amount),
+            };
+
             builder = builder.separator();
+            let portfolio_label = match mask(stats.portfolio_value) {
+                Some(value) => format!("Portfolio: ${:.2}", value),
+                None => "Portfolio: ••••".to_string(),
+            };
             let portfolio = MenuItem::with_id(
                 app_handle,
                 "portfolio",
-                format!("Portfolio: ${:.2}", stats.portfolio_value),
+                portfolio_label,
                 false,
                 None::<&str>,
             )
             .map_err(|e| format!("Failed to create menu item: {e}"))?;
             builder = builder.item(&portfolio);
 
-            let pnl = MenuItem::with_id(
-                app_handle,
-                "pnl",
-                format!(
-                    "P&L: ${:.2} ({:.2}%)",
-                    stats.pnl_value, stats.pnl_percentage
-                ),
-                false,
-                None::<&str>,
-            )
-            .map_err(|e| format!("Failed to create menu item: {e}"))?;
+            let pnl_label = match mask(stats.pnl_value) {
+                Some(value) => format!("P&L: ${:.2} ({:.2}%)", value, stats.pnl_percentage),
+                None => format!("P&L: •••• ({:.2}%)", stats.pnl_percentage),
+            };
+            let pnl = MenuItem::with_id(app_handle, "pnl", pnl_label, false, None::<&str>)
+                .map_err(|e| format!("Failed to create menu item: {e}"))?;
             builder = builder.item(&pnl);
+
+            if let Some(top) = stats.attribution.top_contributor() {
+                let top_label = match mask(top.pnl_value) {
+                    Some(value) => format!("Top today: {} {:+.2}", top.label, value),
+                    None => format!("Top today: {} ••••", top.label),
+                };
+                let top_item =
+                    MenuItem::with_id(app_handle, "pnl-top-source", top_label, false, None::<&str>)
+                        .map_err(|e| format!("Failed to create menu item: {e}"))?;
+                builder = builder.item(&top_item);
+            }
         }
 
         if settings.show_alerts && (!stats.recent_alerts.is_empty() || stats.alert_count > 0) {
@@ -358,6 +409,12 @@ impl TrayManager {
             }
         }
 
+        if is_kiosk_active(app_handle) {
+            return builder
+                .build()
+                .map_err(|e| format!("Failed to build menu: {e}"));
+        }
+
         builder = builder.separator();
         let settings_item =
             MenuItem::with_id(app_handle, "settings", "Settings", true, None::<&str>)
@@ -386,6 +443,22 @@ impl TrayManager {
         Ok(())
     }
 
+    /// Drops the restore shortcut and the Settings and Exit menu items while
+    /// kiosk mode is active, and brings them back afterwards.
+    pub fn set_kiosk_mode(&self, app_handle: &AppHandle, active: bool) {
+        let shortcut = if active {
+            self.unregister_shortcut(app_handle)
+        } else {
+            self.register_shortcut(app_handle)
+        };
+        if let Err(err) = shortcut {
+            eprintln!("Failed to update tray shortcut for kiosk mode: {err}");
+        }
+        if let Err(err) = self.refresh_tray_menu(app_handle) {
+            eprintln!("Failed to update tray menu for kiosk mode: {err}");
+        }
+    }
+
     pub fn get_settings(&self) -> TraySettings {
         self.settings.read().clone()
     }
@@ -407,10 +480,19 @@ impl TrayManager {
         Ok(())
     }
 
+    pub fn get_stats(&self) -> TrayStats {
+        self.stats.read().clone()
+    }
+
+    /// `alert_count` is ignored: the badge is owned by the notification
+    /// history, see [`TrayManager::update_badge`].
     pub fn update_stats(&self, app_handle: &AppHandle, new_stats: TrayStats) -> Result<(), String> {
         {
             let mut stats = self.stats.write();
-            *stats = new_stats;
+            *stats = TrayStats {
+                alert_count: stats.alert_count,
+                ..new_stats
+            };
         }
 
         self.apply_icon_style(app_handle)?;
@@ -418,6 +500,8 @@ impl TrayManager {
         Ok(())
     }
 
+    /// Set by the notification router to the number of unread critical
+    /// notifications.
     pub fn update_badge(&self, app_handle: &AppHandle, count: u32) -> Result<(), String> {
         {
             let mut stats = self.stats.write();
@@ -468,7 +552,9 @@ pub fn attach_window_listeners(window: &tauri::WebviewWindow, tray_manager: Shar
 
     window.on_window_event(move |event| match event {
         WindowEvent::CloseRequested { api, .. } => {
-            if tray_manager_clone.should_close_to_tray() {
+            if is_kiosk_active(&handle_clone) {
+                api.prevent_close();
+            } else if tray_manager_clone.should_close_to_tray() {
                 api.prevent_close();
                 if let Some(window) = handle_clone.get_webview_window("main") {
                     let _ = window.hide();
@@ -524,15 +610,6 @@ pub fn update_tray_stats(
     tray_manager.update_stats(&app, stats)
 }
 
-#[tauri::command]
-pub fn update_tray_badge(
-    count: u32,
-    tray_manager: tauri::State<'_, SharedTrayManager>,
-    app: AppHandle,
-) -> Result<(), String> {
-    tray_manager.update_badge(&app, count)
-}
-
 #[tauri::command]
 pub fn minimize_to_tray(app: AppHandle) -> Result<(), String> {
     if let Some(window) = app.get_webview_window("main") {
@@ -0,0 +1,589 @@
+use chrono::{DateTime, Utc};
+use futures_util::future::join_all;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State};
+
+use crate::alerts::{PriceAlert, SharedAlertManager};
+use crate::config::privacy_mode::{PrivacyMode, SharedPrivacyMode};
+use crate::mobile::{DeviceSyncStatus, SharedMobileAuthManager, SharedMobileSyncManager};
+use crate::monitor::command_metrics;
+use crate::notifications::{NotificationFilter, NotificationPage, SharedNotificationRouter};
+use crate::portfolio::{
+    PortfolioMetrics, Position, SharedPortfolioData, SharedTokenAnnotationStore,
+    SharedWatchlistManager, Watchlist,
+};
+use crate::trading::types::Order;
+use crate::tray::{SharedTrayManager, TrayStats};
+
+const RECENT_NOTIFICATIONS: u32 = 20;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum DashboardSection {
+    PortfolioMetrics,
+    Positions,
+    Alerts,
+    OpenOrders,
+    Watchlists,
+    TrayStats,
+    Notifications,
+    SyncStatus,
+}
+
+impl DashboardSection {
+    pub const ALL: [DashboardSection; 8] = [
+        DashboardSection::PortfolioMetrics,
+        DashboardSection::Positions,
+        DashboardSection::Alerts,
+        DashboardSection::OpenOrders,
+        DashboardSection::Watchlists,
+        DashboardSection::TrayStats,
+        DashboardSection::Notifications,
+        DashboardSection::SyncStatus,
+    ];
+
+    /// How long a computed section may be served from cache.
+    pub fn ttl(&self) -> Duration {
+        let millis = match self {
+            DashboardSection::PortfolioMetrics
+            | DashboardSection::Positions
+            | DashboardSection::OpenOrders
+            | DashboardSection::TrayStats => 2_000,
+            DashboardSection::Notifications => 3_000,
+            DashboardSection::Alerts | DashboardSection::SyncStatus => 5_000,
+            DashboardSection::Watchlists => 10_000,
+        };
+        Duration::from_millis(millis)
+    }
+
+    /// Name the section's timings are recorded under in the command metrics.
+    pub fn metric_name(&self) -> &'static str {
+        match self {
+            DashboardSection::PortfolioMetrics => "dashboard_snapshot.portfolio_metrics",
+            DashboardSection::Positions => "dashboard_snapshot.positions",
+            DashboardSection::Alerts => "dashboard_snapshot.alerts",
+            DashboardSection::OpenOrders => "dashboard_snapshot.open_orders",
+            DashboardSection::Watchlists => "dashboard_snapshot.watchlists",
+            DashboardSection::TrayStats => "dashboard_snapshot.tray_stats",
+            DashboardSection::Notifications => "dashboard_snapshot.notifications",
+            DashboardSection::SyncStatus => "dashboard_snapshot.sync_status",
+        }
+    }
+
+    /// Portfolio metrics and positions are always computed together from one
+    /// read of the portfolio data, so the P&L header matches the table.
+    fn fetch_unit(&self) -> DashboardSection {
+        match self {
+            DashboardSection::Positions => DashboardSection::PortfolioMetrics,
+            other => *other,
+        }
+    }
+
+    fn is_per_wallet(&self) -> bool {
+        matches!(self, DashboardSection::OpenOrders)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SectionSnapshot {
+    pub section: DashboardSection,
+    pub data: Option<Value>,
+    /// Set when the section could not be gathered; the others are unaffected.
+    pub error: Option<String>,
+    /// When the data was computed. Earlier than `snapshot_ts` for cache hits.
+    pub as_of: Option<DateTime<Utc>>,
+    pub cached: bool,
+    pub elapsed_ms: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DashboardSnapshot {
+    pub snapshot_ts: DateTime<Utc>,
+    pub total_ms: f64,
+    pub sections: Vec<SectionSnapshot>,
+}
+
+#[derive(Debug, Clone)]
+enum SectionData {
+    PortfolioMetrics(PortfolioMetrics),
+    Positions(Vec<Position>),
+    Alerts(Vec<PriceAlert>),
+    OpenOrders(Vec<Order>),
+    Watchlists(Vec<Watchlist>),
+    TrayStats(TrayStats),
+    Notifications(NotificationPage),
+    SyncStatus(Vec<DeviceSyncStatus>),
+}
+
+impl SectionData {
+    /// Balance-bearing sections are masked the same way their standalone
+    /// commands mask them.
+    fn into_json(self, privacy: Option<&PrivacyMode>) -> serde_json::Result<Value> {
+        match (self, privacy) {
+            (SectionData::PortfolioMetrics(v), Some(p)) => serde_json::to_value(p.apply(v)),
+            (SectionData::Positions(v), Some(p)) => serde_json::to_value(p.apply(v)),
+            (SectionData::TrayStats(v), Some(p)) => serde_json::to_value(p.apply(v)),
+            (SectionData::PortfolioMetrics(v), None) => serde_json::to_value(v),
+            (SectionData::Positions(v), None) => serde_json::to_value(v),
+            (SectionData::TrayStats(v), None) => serde_json::to_value(v),
+            (SectionData::Alerts(v), _) => serde_json::to_value(v),
+            (SectionData::OpenOrders(v), _) => serde_json::to_value(v),
+            (SectionData::Watchlists(v), _) => serde_json::to_value(v),
+            (SectionData::Notifications(v), _) => serde_json::to_value(v),
+            (SectionData::SyncStatus(v), _) => serde_json::to_value(v),
+        }
+    }
+}
+
+type CacheKey = (DashboardSection, Option<String>);
+
+#[derive(Debug, Clone)]
+struct CachedSection {
+    data: SectionData,
+    as_of: DateTime<Utc>,
+    stored_at: Instant,
+}
+
+/// Recently computed dashboard sections, each with its own TTL.
+#[derive(Default)]
+pub struct DashboardSnapshotCache {
+    entries: Mutex<HashMap<CacheKey, CachedSection>>,
+}
+
+pub type SharedDashboardSnapshotCache = Arc<DashboardSnapshotCache>;
+
+impl DashboardSnapshotCache {
+    fn key(section: DashboardSection, wallet: Option<&str>) -> CacheKey {
+        let wallet = section
+            .is_per_wallet()
+            .then(|| wallet.map(str::to_string))
+            .flatten();
+        (section, wallet)
+    }
+
+    fn get(
+        &self,
+        section: DashboardSection,
+        wallet: Option<&str>,
+        now: Instant,
+    ) -> Option<CachedSection> {
+        let entries = self.entries.lock();
+        entries
+            .get(&Self::key(section, wallet))
+            .filter(|entry| now.duration_since(entry.stored_at) < section.ttl())
+            .cloned()
+    }
+
+    fn put(
+        &self,
+        section: DashboardSection,
+        wallet: Option<&str>,
+        data: SectionData,
+        as_of: DateTime<Utc>,
+        now: Instant,
+    ) {
+        self.entries.lock().insert(
+            Self::key(section, wallet),
+            CachedSection {
+                data,
+                as_of,
+                stored_at: now,
+            },
+        );
+    }
+
+    pub fn invalidate(&self) {
+        self.entries.lock().clear();
+    }
+}
+
+/// Requested sections in order, without duplicates; none means all of them.
+fn requested_sections(sections: Option<Vec<DashboardSection>>) -> Vec<DashboardSection> {
+    let sections = match sections {
+        Some(sections) if !sections.is_empty() => sections,
+        _ => return DashboardSection::ALL.to_vec(),
+    };
+
+    let mut unique = Vec::with_capacity(sections.len());
+    for section in sections {
+        if !unique.contains(&section) {
+            unique.push(section);
+        }
+    }
+    unique
+}
+
+/// Sections needing a fresh fetch, grouped by what computes them.
+fn fetch_units(missing: &[DashboardSection]) -> Vec<DashboardSection> {
+    let mut units = Vec::new();
+    for section in missing {
+        let unit = section.fetch_unit();
+        if !units.contains(&unit) {
+            units.push(unit);
+        }
+    }
+    units
+}
+
+async fn fetch_unit(
+    app: &AppHandle,
+    unit: DashboardSection,
+    wallet: Option<&str>,
+) -> Result<Vec<(DashboardSection, SectionData)>, String> {
+    match unit {
+        DashboardSection::PortfolioMetrics | DashboardSection::Positions => {
+            let data = app
+                .try_state::<SharedPortfolioData>()
+                .ok_or_else(|| "Portfolio data is not available".to_string())?;
+            let guard = data
+                .lock()
+                .map_err(|_| "Portfolio data locked".to_string())?;
+            Ok(vec![
+                (
+                    DashboardSection::PortfolioMetrics,
+                    SectionData::PortfolioMetrics(guard.metrics()),
+                ),
+                (
+                    DashboardSection::Positions,
+                    SectionData::Positions(guard.positions()),
+                ),
+            ])
+        }
+        DashboardSection::Alerts => {
+            let manager = app
+                .try_state::<SharedAlertManager>()
+                .ok_or_else(|| "Alerts are not available".to_string())?;
+            let alerts = manager
+                .read()
+                .await
+                .list_alerts()
+                .await
+                .map_err(|e| e.to_string())?;
+            Ok(vec![(unit, SectionData::Alerts(alerts))])
+        }
+        DashboardSection::OpenOrders => {
+            let wallet = wallet.ok_or_else(|| "A wallet address is required".to_string())?;
+            let orders = crate::trading::get_active_orders(wallet.to_string())
+                .await
+                .map_err(|e| e.to_string())?;
+            Ok(vec![(unit, SectionData::OpenOrders(orders))])
+        }
+        DashboardSection::Watchlists => {
+            let manager = app
+                .try_state::<SharedWatchlistManager>()
+                .ok_or_else(|| "Watchlists are not available".to_string())?;
+            let watchlists = manager
+                .read()
+                .await
+                .list_watchlists()
+                .await
+                .map_err(|e| e.to_string())?;
+            let watchlists = match app.try_state::<SharedTokenAnnotationStore>() {
+                Some(annotations) => watchlists
+                    .into_iter()
+                    .map(|watchlist| watchlist.with_annotations(&annotations))
+                    .collect(),
+                None => watchlists,
+            };
+            Ok(vec![(unit, SectionData::Watchlists(watchlists))])
+        }
+        DashboardSection::TrayStats => {
+            let tray = app
+                .try_state::<SharedTrayManager>()
+                .ok_or_else(|| "Tray is not available".to_string())?;
+            Ok(vec![(unit, SectionData::TrayStats(tray.get_stats()))])
+        }
+        DashboardSection::Notifications => {
+            let router = app
+                .try_state::<SharedNotificationRouter>()
+                .ok_or_else(|| "Notifications are not available".to_string())?;
+            let filter = NotificationFilter {
+                limit: Some(RECENT_NOTIFICATIONS),
+                ..NotificationFilter::default()
+            };
+            let page = router
+                .read()
+                .await
+                .list_history(&filter, None)
+                .await
+                .map_err(|e| e.to_string())?;
+            Ok(vec![(unit, SectionData::Notifications(page))])
+        }
+        DashboardSection::SyncStatus => {
+            let (Some(auth), Some(sync)) = (
+                app.try_state::<SharedMobileAuthManager>(),
+                app.try_state::<SharedMobileSyncManager>(),
+            ) else {
+                return Err("Mobile sync is not available".to_string());
+            };
+            let devices = auth.read().await.get_devices();
+            let sync = sync.read().await;
+            let statuses = devices
+                .iter()
+                .filter_map(|device| sync.device_status(&device.device_id))
+                .collect();
+            Ok(vec![(unit, SectionData::SyncStatus(statuses))])
+        }
+    }
+}
+
+/// Gathers the requested sections concurrently. Sections still within their
+/// TTL are served from cache; each fetch is timed and recorded in the command
+/// metrics so slow contributors show up in diagnostics.
+pub async fn gather_dashboard_snapshot(
+    app: &AppHandle,
+    cache: &DashboardSnapshotCache,
+    sections: Option<Vec<DashboardSection>>,
+    wallet: Option<&str>,
+    refresh: bool,
+) -> DashboardSnapshot {
+    let started = Instant::now();
+    let snapshot_ts = Utc::now();
+    let sections = requested_sections(sections);
+
+    let mut results: HashMap<DashboardSection, SectionSnapshot> = HashMap::new();
+    let mut fresh: HashMap<DashboardSection, SectionData> = HashMap::new();
+    let mut missing = Vec::new();
+
+    for section in &sections {
+        let lookup = Instant::now();
+        match cache.get(*section, wallet, lookup).filter(|_| !refresh) {
+            Some(entry) => {
+                fresh.insert(*section, entry.data);
+                results.insert(
+                    *section,
+                    SectionSnapshot {
+                        section: *section,
+                        data: None,
+                        error: None,
+                        as_of: Some(entry.as_of),
+                        cached: true,
+                        elapsed_ms: lookup.elapsed().as_secs_f64() * 1000.0,
+                    },
+                );
+            }
+            None => missing.push(*section),
+        }
+    }
+
+    // A cached half of the portfolio pair is refetched with the other half
+    let missing_units = fetch_units(&missing);
+    let fetches = missing_units.iter().map(|unit| async move {
+        let unit_started = Instant::now();
+        let result = fetch_unit(app, *unit, wallet).await;
+        (*unit, result, unit_started.elapsed())
+    });
+
+    for (unit, result, elapsed) in join_all(fetches).await {
+        let elapsed_ms = elapsed.as_secs_f64() * 1000.0;
+        let covered: Vec<DashboardSection> = sections
+            .iter()
+            .copied()
+            .filter(|section| section.fetch_unit() == unit)
+            .collect();
+
+        match result {
+            Ok(entries) => {
+                let as_of = Utc::now();
+                for (section, data) in entries {
+                    command_metrics().record(
+                        section.metric_name(),
+                        elapsed.as_micros() as u64,
+                        true,
+                    );
+                    cache.put(section, wallet, data.clone(), as_of, Instant::now());
+                    if !covered.contains(&section) {
+                        continue;
+                    }
+                    fresh.insert(section, data);
+                    results.insert(
+                        section,
+                        SectionSnapshot {
+                            section,
+                            data: None,
+                            error: None,
+                            as_of: Some(as_of),
+                            cached: false,
+                            elapsed_ms,
+                        },
+                    );
+                }
+            }
+            Err(error) => {
+                for section in covered {
+                    command_metrics().record(
+                        section.metric_name(),
+                        elapsed.as_micros() as u64,
+                        false,
+                    );
+                    fresh.remove(&section);
+                    results.insert(
+                        section,
+                        SectionSnapshot {
+                            section,
+                            data: None,
+                            error: Some(error.clone()),
+                            as_of: None,
+                            cached: false,
+                            elapsed_ms,
+                        },
+                    );
+                }
+            }
+        }
+    }
+
+    let privacy = app
+        .try_state::<SharedPrivacyMode>()
+        .and_then(|privacy| privacy.read().ok().map(|guard| guard.clone()));
+    let sections = sections
+        .into_iter()
+        .filter_map(|section| {
+            let mut snapshot = results.remove(&section)?;
+            if let Some(data) = fresh.remove(&section) {
+                match data.into_json(privacy.as_ref()) {
+                    Ok(value) => snapshot.data = Some(value),
+                    Err(e) => snapshot.error = Some(format!("Failed to serialize section: {}", e)),
+                }
+            }
+            Some(snapshot)
+        })
+        .collect();
+
+    DashboardSnapshot {
+        snapshot_ts,
+        total_ms: started.elapsed().as_secs_f64() * 1000.0,
+        sections,
+    }
+}
+
+/// One consistent read of several dashboard modules. `wallet_address` is
+/// needed for open orders; `refresh` bypasses the section cache.
+#[tauri::command]
+pub async fn get_dashboard_snapshot(
+    sections: Option<Vec<DashboardSection>>,
+    wallet_address: Option<String>,
+    refresh: Option<bool>,
+    app: AppHandle,
+    cache: State<'_, SharedDashboardSnapshotCache>,
+) -> Result<DashboardSnapshot, String> {
+    Ok(gather_dashboard_snapshot(
+        &app,
+        &cache,
+        sections,
+        wallet_address.as_deref(),
+        refresh.unwrap_or(false),
+    )
+    .await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(portfolio_value: f64) -> SectionData {
+        SectionData::TrayStats(TrayStats {
+            portfolio_value,
+            ..TrayStats::default()
+        })
+    }
+
+    #[test]
+    fn sections_default_to_all_and_drop_duplicates() {
+        assert_eq!(requested_sections(None), DashboardSection::ALL.to_vec());
+        assert_eq!(requested_sections(Some(Vec::new())).len(), 8);
+        assert_eq!(
+            requested_sections(Some(vec![
+                DashboardSection::Alerts,
+                DashboardSection::Positions,
+                DashboardSection::Alerts,
+            ])),
+            vec![DashboardSection::Alerts, DashboardSection::Positions]
+        );
+    }
+
+    #[test]
+    fn portfolio_sections_share_one_fetch() {
+        let units = fetch_units(&[
+            DashboardSection::Positions,
+            DashboardSection::Alerts,
+            DashboardSection::PortfolioMetrics,
+        ]);
+        assert_eq!(
+            units,
+            vec![DashboardSection::PortfolioMetrics, DashboardSection::Alerts]
+        );
+    }
+
+    #[test]
+    fn cached_sections_expire_after_their_ttl() {
+        let cache = DashboardSnapshotCache::default();
+        let stored = Instant::now();
+        cache.put(
+            DashboardSection::TrayStats,
+            None,
+            stats(100.0),
+            Utc::now(),
+            stored,
+        );
+
+        assert!(cache
+            .get(
+                DashboardSection::TrayStats,
+                None,
+                stored + Duration::from_millis(500)
+            )
+            .is_some());
+        assert!(cache
+            .get(
+                DashboardSection::TrayStats,
+                None,
+                stored + DashboardSection::TrayStats.ttl()
+            )
+            .is_none());
+
+        cache.invalidate();
+        assert!(cache
+            .get(DashboardSection::TrayStats, None, stored)
+            .is_none());
+    }
+
+    #[test]
+    fn open_orders_are_cached_per_wallet() {
+        let cache = DashboardSnapshotCache::default();
+        let now = Instant::now();
+        cache.put(
+            DashboardSection::OpenOrders,
+            Some("wallet-a"),
+            SectionData::OpenOrders(Vec::new()),
+            Utc::now(),
+            now,
+        );
+
+        assert!(cache
+            .get(DashboardSection::OpenOrders, Some("wallet-a"), now)
+            .is_some());
+        assert!(cache
+            .get(DashboardSection::OpenOrders, Some("wallet-b"), now)
+            .is_none());
+
+        // Sections not tied to a wallet ignore it
+        cache.put(
+            DashboardSection::TrayStats,
+            Some("wallet-a"),
+            stats(1.0),
+            Utc::now(),
+            now,
+        );
+        assert!(cache
+            .get(DashboardSection::TrayStats, Some("wallet-b"), now)
+            .is_some());
+    }
+}
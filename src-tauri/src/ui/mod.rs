@@ -1 +1,2 @@
+pub mod dashboard;
 pub mod theme_engine;
@@ -1,10 +1,11 @@
+use crate::config::DataPaths;
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
-use tauri::{AppHandle, Manager, State};
+use tauri::{AppHandle, State};
 
 const STORAGE_FILE: &str = "themes.json";
 const DEFAULT_THEME_ID: &str = "lunar-eclipse";
@@ -137,8 +138,7 @@ impl ThemeEngine {
 
     fn resolve_storage_path(app: &AppHandle) -> Result<PathBuf, String> {
         let mut dir = app
-            .path()
-            .app_data_dir()
+            .app_data_root()
             .map_err(|_| "Unable to resolve app data directory".to_string())?;
 
         if !dir.exists() {
@@ -0,0 +1,122 @@
+use super::{AvailableUpdate, DeltaPatch};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io::{self, Read};
+
+/// Delta patches are zstd frames compressed with the installed binary as a
+/// raw-content dictionary (`zstd --patch-from`); long-range matches need a
+/// window as large as the binary itself.
+const PATCH_WINDOW_LOG_MAX: u32 = 31;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PackageSource {
+    Delta,
+    Full,
+}
+
+pub(crate) fn sha256_hex(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
+
+fn verify(bytes: &[u8], expected: &str, what: &str) -> Result<(), String> {
+    if expected.is_empty() {
+        return Err(format!("Release manifest has no hash for the {}", what));
+    }
+    let actual = sha256_hex(bytes);
+    if !actual.eq_ignore_ascii_case(expected) {
+        return Err(format!(
+            "Hash mismatch for the {}: expected {}, got {}",
+            what, expected, actual
+        ));
+    }
+    Ok(())
+}
+
+pub(crate) fn apply_patch(base: &[u8], patch: &[u8]) -> io::Result<Vec<u8>> {
+    let mut decoder = zstd::stream::read::Decoder::with_dictionary(patch, base)?;
+    decoder.window_log_max(PATCH_WINDOW_LOG_MAX)?;
+    let mut output = Vec::new();
+    decoder.read_to_end(&mut output)?;
+    Ok(output)
+}
+
+async fn download(client: &reqwest::Client, url: &str) -> Result<Vec<u8>, String> {
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("Download of {} failed: {}", url, e))?;
+    let bytes = response
+        .bytes()
+        .await
+        .map_err(|e| format!("Download of {} failed: {}", url, e))?;
+    Ok(bytes.to_vec())
+}
+
+async fn fetch_delta(
+    client: &reqwest::Client,
+    delta: &DeltaPatch,
+    installed: &[u8],
+    package_sha256: &str,
+) -> Result<Vec<u8>, String> {
+    let patch = download(client, &delta.url).await?;
+    verify(&patch, &delta.sha256, "delta patch")?;
+    let patched =
+        apply_patch(installed, &patch).map_err(|e| format!("Failed to apply patch: {}", e))?;
+    verify(&patched, package_sha256, "patched package")?;
+    Ok(patched)
+}
+
+/// Downloads the update, preferring the delta from the installed binary and
+/// falling back to the full package if the patch is unusable for any
+/// reason. Whichever path is taken, the result matches the package hash.
+pub(crate) async fn fetch_package(
+    client: &reqwest::Client,
+    update: &AvailableUpdate,
+    installed: Option<&[u8]>,
+) -> Result<(Vec<u8>, PackageSource), String> {
+    if let (Some(delta), Some(installed)) = (&update.delta, installed) {
+        match fetch_delta(client, delta, installed, &update.package.sha256).await {
+            Ok(bytes) => return Ok((bytes, PackageSource::Delta)),
+            Err(e) => tracing::warn!(
+                "Delta update to {} unusable, downloading full package: {}",
+                update.version,
+                e
+            ),
+        }
+    }
+
+    let bytes = download(client, &update.package.url).await?;
+    verify(&bytes, &update.package.sha256, "update package")?;
+    Ok((bytes, PackageSource::Full))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn patch_round_trips_against_installed_binary() {
+        let installed: Vec<u8> = (0..64 * 1024).map(|i| (i % 251) as u8).collect();
+        let mut updated = installed.clone();
+        updated[1000..1010].copy_from_slice(b"new-build!");
+        updated.extend_from_slice(b"appended section");
+
+        let mut encoder =
+            zstd::stream::write::Encoder::with_dictionary(Vec::new(), 3, &installed).unwrap();
+        encoder.write_all(&updated).unwrap();
+        let patch = encoder.finish().unwrap();
+        assert!(patch.len() < updated.len() / 10);
+
+        let rebuilt = apply_patch(&installed, &patch).unwrap();
+        assert!(verify(&rebuilt, &sha256_hex(&updated), "patched package").is_ok());
+        assert!(verify(&rebuilt, &sha256_hex(&installed), "patched package").is_err());
+
+        // A different base either fails to decode or fails the hash check.
+        let wrong_base = apply_patch(b"some other build", &patch);
+        assert!(!wrong_base.is_ok_and(|bytes| sha256_hex(&bytes) == sha256_hex(&updated)));
+    }
+}
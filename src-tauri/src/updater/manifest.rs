@@ -0,0 +1,377 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// Newest manifest layout this client understands. Later schemas only add
+/// fields, so they are read with the newest known layout and the
+/// additions are ignored.
+pub const MANIFEST_SCHEMA_VERSION: u32 = 2;
+
+/// Platform key for a schema 1 manifest served per target, which carries
+/// its package fields at the top level instead of under `platforms`.
+const ANY_PLATFORM: &str = "*";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ReleaseChannel {
+    #[default]
+    Stable,
+    Beta,
+    Nightly,
+}
+
+impl ReleaseChannel {
+    fn from_manifest(name: &str) -> Option<Self> {
+        match name {
+            "stable" => Some(Self::Stable),
+            "beta" => Some(Self::Beta),
+            "nightly" => Some(Self::Nightly),
+            _ => None,
+        }
+    }
+
+    /// Channels whose releases this channel receives. Beta users still get a
+    /// stable release when it is newer than the latest beta.
+    fn receives(&self) -> &'static [ReleaseChannel] {
+        match self {
+            Self::Stable => &[Self::Stable],
+            Self::Beta => &[Self::Stable, Self::Beta],
+            Self::Nightly => &[Self::Stable, Self::Beta, Self::Nightly],
+        }
+    }
+}
+
+/// `major.minor.patch[-prerelease]`; a prerelease sorts before its release.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReleaseVersion {
+    core: [u64; 3],
+    prerelease: Vec<String>,
+}
+
+impl ReleaseVersion {
+    pub fn parse(version: &str) -> Option<Self> {
+        let version = version.trim().trim_start_matches('v');
+        let version = version.split('+').next()?;
+        let (core, prerelease) = match version.split_once('-') {
+            Some((core, pre)) => (core, pre.split('.').map(str::to_string).collect()),
+            None => (version, Vec::new()),
+        };
+
+        let mut parts = core.split('.').map(|part| part.parse::<u64>().ok());
+        let mut numbers = [0u64; 3];
+        for number in numbers.iter_mut() {
+            *number = parts.next().flatten()?;
+        }
+        if parts.next().is_some() {
+            return None;
+        }
+
+        Some(Self {
+            core: numbers,
+            prerelease,
+        })
+    }
+}
+
+impl Ord for ReleaseVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.core.cmp(&other.core).then_with(|| {
+            match (self.prerelease.is_empty(), other.prerelease.is_empty()) {
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) => compare_prerelease(&self.prerelease, &other.prerelease),
+            }
+        })
+    }
+}
+
+impl PartialOrd for ReleaseVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn compare_prerelease(a: &[String], b: &[String]) -> Ordering {
+    for (left, right) in a.iter().zip(b) {
+        let ordering = match (left.parse::<u64>(), right.parse::<u64>()) {
+            (Ok(l), Ok(r)) => l.cmp(&r),
+            (Ok(_), Err(_)) => Ordering::Less,
+            (Err(_), Ok(_)) => Ordering::Greater,
+            (Err(_), Err(_)) => left.cmp(right),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    a.len().cmp(&b.len())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeltaPatch {
+    pub from_version: String,
+    pub url: String,
+    /// Hash of the patch file itself.
+    pub sha256: String,
+    #[serde(default)]
+    pub size: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlatformPackage {
+    pub url: String,
+    /// Hash of the full package, which a patched build must also match.
+    /// Schema 1 manifests may omit it; such packages can't be downloaded.
+    #[serde(default)]
+    pub sha256: String,
+    #[serde(default)]
+    pub size: Option<u64>,
+    #[serde(default)]
+    pub signature: Option<String>,
+    #[serde(default)]
+    pub deltas: Vec<DeltaPatch>,
+}
+
+fn default_rollout_percentage() -> f64 {
+    100.0
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReleaseEntry {
+    pub version: String,
+    #[serde(default)]
+    pub notes: Option<String>,
+    #[serde(default, alias = "pub_date")]
+    pub pub_date: Option<String>,
+    #[serde(default = "default_rollout_percentage")]
+    pub rollout_percentage: f64,
+    #[serde(default)]
+    pub platforms: HashMap<String, PlatformPackage>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReleaseManifest {
+    pub schema_version: u32,
+    pub channels: HashMap<ReleaseChannel, ReleaseEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AvailableUpdate {
+    pub version: String,
+    pub channel: ReleaseChannel,
+    pub notes: Option<String>,
+    pub pub_date: Option<String>,
+    pub package: PlatformPackage,
+    /// Patch from the installed version, when the manifest offers one.
+    pub delta: Option<DeltaPatch>,
+}
+
+/// Schema 1 is a single release object and is treated as the stable channel.
+/// Schema 2 and later list one release per channel under `channels`;
+/// channels this client doesn't know are skipped.
+pub fn parse_manifest(json: &str) -> Result<ReleaseManifest, String> {
+    let value: Value =
+        serde_json::from_str(json).map_err(|e| format!("Invalid update manifest: {}", e))?;
+    let schema_version = value
+        .get("schemaVersion")
+        .and_then(Value::as_u64)
+        .map(|v| v as u32)
+        .unwrap_or(1);
+
+    let mut channels = HashMap::new();
+    if schema_version < 2 {
+        let mut entry: ReleaseEntry = serde_json::from_value(value.clone())
+            .map_err(|e| format!("Invalid update manifest: {}", e))?;
+        if entry.platforms.is_empty() {
+            if let Ok(package) = serde_json::from_value::<PlatformPackage>(value) {
+                entry.platforms.insert(ANY_PLATFORM.to_string(), package);
+            }
+        }
+        channels.insert(ReleaseChannel::Stable, entry);
+    } else {
+        if schema_version > MANIFEST_SCHEMA_VERSION {
+            tracing::debug!(
+                "Update manifest schema {} is newer than {}; ignoring unknown fields",
+                schema_version,
+                MANIFEST_SCHEMA_VERSION
+            );
+        }
+        let listed = value
+            .get("channels")
+            .and_then(Value::as_object)
+            .ok_or_else(|| "Update manifest has no channels".to_string())?;
+        for (name, entry) in listed {
+            let Some(channel) = ReleaseChannel::from_manifest(name) else {
+                continue;
+            };
+            match serde_json::from_value::<ReleaseEntry>(entry.clone()) {
+                Ok(entry) => {
+                    channels.insert(channel, entry);
+                }
+                Err(e) => tracing::warn!("Skipping malformed {} release: {}", name, e),
+            }
+        }
+    }
+
+    Ok(ReleaseManifest {
+        schema_version,
+        channels,
+    })
+}
+
+/// Stable position of an install in `[0, 100)` for a given release. The
+/// same install always lands in the same bucket, so raising the rollout
+/// percentage only ever adds installs.
+pub(crate) fn rollout_bucket(install_id: &str, version: &str) -> f64 {
+    let digest = Sha256::digest(format!("{}:{}", install_id, version).as_bytes());
+    let mut head = [0u8; 8];
+    head.copy_from_slice(&digest[..8]);
+    (u64::from_be_bytes(head) % 10_000) as f64 / 100.0
+}
+
+/// Newest release on the channel (or a more stable one) that is newer than
+/// `current`, built for `target`, not dismissed and rolled out to this install.
+pub(crate) fn select_update(
+    manifest: &ReleaseManifest,
+    channel: ReleaseChannel,
+    current: &ReleaseVersion,
+    target: &str,
+    install_id: &str,
+    dismissed_version: Option<&str>,
+) -> Option<AvailableUpdate> {
+    channel
+        .receives()
+        .iter()
+        .filter_map(|source| {
+            let entry = manifest.channels.get(source)?;
+            let version = ReleaseVersion::parse(&entry.version)?;
+            let package = entry
+                .platforms
+                .get(target)
+                .or_else(|| entry.platforms.get(ANY_PLATFORM))?;
+            let eligible = version > *current
+                && dismissed_version != Some(entry.version.as_str())
+                && rollout_bucket(install_id, &entry.version) < entry.rollout_percentage;
+            eligible.then_some((version, *source, entry, package))
+        })
+        .max_by(|a, b| a.0.cmp(&b.0))
+        .map(|(_, source, entry, package)| AvailableUpdate {
+            version: entry.version.clone(),
+            channel: source,
+            notes: entry.notes.clone(),
+            pub_date: entry.pub_date.clone(),
+            delta: package
+                .deltas
+                .iter()
+                .find(|delta| ReleaseVersion::parse(&delta.from_version).as_ref() == Some(current))
+                .cloned(),
+            package: package.clone(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TARGET: &str = "linux-x86_64";
+
+    fn release(version: &str, rollout: f64) -> Value {
+        serde_json::json!({
+            "version": version,
+            "rolloutPercentage": rollout,
+            "platforms": { TARGET: { "url": "https://example.com/full", "sha256": "abc" } }
+        })
+    }
+
+    #[test]
+    fn reads_both_schemas_and_ignores_unknown_fields() {
+        let legacy = serde_json::json!({
+            "version": "1.2.0",
+            "pub_date": "2024-01-01",
+            "url": "https://example.com/full",
+            "signature": "sig"
+        });
+        let manifest = parse_manifest(&legacy.to_string()).unwrap();
+        assert_eq!(manifest.schema_version, 1);
+        let stable = &manifest.channels[&ReleaseChannel::Stable];
+        assert_eq!(stable.pub_date.as_deref(), Some("2024-01-01"));
+        let current = ReleaseVersion::parse("1.1.0").unwrap();
+        let update = select_update(
+            &manifest,
+            ReleaseChannel::Stable,
+            &current,
+            TARGET,
+            "id",
+            None,
+        );
+        assert_eq!(update.unwrap().package.url, "https://example.com/full");
+
+        let future = serde_json::json!({
+            "schemaVersion": 5,
+            "signingKeys": ["k1"],
+            "channels": {
+                "stable": release("1.2.0", 100.0),
+                "beta": release("1.3.0-beta.1", 100.0),
+                "canary": release("9.9.9", 100.0)
+            }
+        });
+        let manifest = parse_manifest(&future.to_string()).unwrap();
+        assert_eq!(manifest.channels.len(), 2);
+        assert!(manifest.channels.contains_key(&ReleaseChannel::Beta));
+    }
+
+    #[test]
+    fn beta_receives_newer_stable_and_honours_dismissal() {
+        let manifest = parse_manifest(
+            &serde_json::json!({
+                "schemaVersion": 2,
+                "channels": {
+                    "stable": release("1.3.0", 100.0),
+                    "beta": release("1.3.0-beta.2", 100.0)
+                }
+            })
+            .to_string(),
+        )
+        .unwrap();
+        let current = ReleaseVersion::parse("1.2.0").unwrap();
+
+        let beta = select_update(
+            &manifest,
+            ReleaseChannel::Beta,
+            &current,
+            TARGET,
+            "id",
+            None,
+        );
+        assert_eq!(beta.unwrap().channel, ReleaseChannel::Stable);
+
+        let dismissed = select_update(
+            &manifest,
+            ReleaseChannel::Beta,
+            &current,
+            TARGET,
+            "id",
+            Some("1.3.0"),
+        );
+        assert_eq!(dismissed.unwrap().version, "1.3.0-beta.2");
+        assert!(ReleaseVersion::parse("1.3.0-beta.10") > ReleaseVersion::parse("1.3.0-beta.2"));
+    }
+
+    #[test]
+    fn staged_rollout_is_deterministic_per_install() {
+        let included = (0..1000)
+            .filter(|i| rollout_bucket(&format!("install-{i}"), "2.0.0") < 25.0)
+            .count();
+        assert!((150..350).contains(&included));
+
+        let bucket = rollout_bucket("install-7", "2.0.0");
+        assert_eq!(bucket, rollout_bucket("install-7", "2.0.0"));
+        assert!((0.0..100.0).contains(&bucket));
+    }
+}
@@ -1,10 +1,22 @@
+mod delta;
+mod manifest;
+
+pub use delta::*;
+pub use manifest::*;
+
+use crate::config::DataPaths;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tauri::{AppHandle, Emitter, Manager, Runtime, State, Window};
 use tokio::sync::RwLock;
 
+/// Same endpoint as the `updater` section of `tauri.conf.json`.
+const UPDATE_ENDPOINT: &str = "https://updates.eclipsemarketpro.com/{target}/{current_version}";
+const BACKUP_BINARY: &str = "previous-build";
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum UpdateSchedule {
@@ -27,6 +39,8 @@ pub struct UpdateSettings {
     pub auto_install: bool,
     pub last_check: Option<String>,
     pub dismissed_version: Option<String>,
+    #[serde(default)]
+    pub channel: ReleaseChannel,
 }
 
 impl Default for UpdateSettings {
@@ -37,6 +51,7 @@ impl Default for UpdateSettings {
             auto_install: false,
             last_check: None,
             dismissed_version: None,
+            channel: ReleaseChannel::Stable,
         }
     }
 }
@@ -47,18 +62,44 @@ pub struct RollbackInfo {
     pub available: bool,
     pub previous_version: Option<String>,
     pub backup_timestamp: Option<String>,
+    pub previous_channel: Option<ReleaseChannel>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BackupMetadata {
+    version: String,
+    timestamp: String,
+    /// Absent in backups written before release channels existed.
+    #[serde(default)]
+    channel: Option<ReleaseChannel>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StagedUpdate {
+    pub version: String,
+    pub channel: ReleaseChannel,
+    pub source: PackageSource,
+    pub sha256: String,
+    pub staged_at: DateTime<Utc>,
+    #[serde(skip)]
+    path: PathBuf,
 }
 
 pub struct UpdaterState {
     pub settings: RwLock<UpdateSettings>,
     pub backup_path: PathBuf,
+    pub staging_path: PathBuf,
+    /// Random per-install id that fixes this install's staged-rollout bucket.
+    pub install_id: String,
+    pub available: RwLock<Option<AvailableUpdate>>,
+    pub staged: RwLock<Option<StagedUpdate>>,
 }
 
 impl UpdaterState {
     pub fn new(app_handle: &AppHandle) -> Result<Self, String> {
         let app_data_dir = app_handle
-            .path()
-            .app_data_dir()
+            .app_data_root()
             .map_err(|e| format!("Unable to resolve app data directory: {}", e))?;
 
         let backup_path = app_data_dir.join("backups");
@@ -67,6 +108,23 @@ impl UpdaterState {
                 .map_err(|e| format!("Failed to create backup directory: {}", e))?;
         }
 
+        let staging_path = app_data_dir.join("updates");
+        if !staging_path.exists() {
+            fs::create_dir_all(&staging_path)
+                .map_err(|e| format!("Failed to create update staging directory: {}", e))?;
+        }
+
+        let install_id_path = app_data_dir.join("install_id");
+        let install_id = match fs::read_to_string(&install_id_path) {
+            Ok(id) if !id.trim().is_empty() => id.trim().to_string(),
+            _ => {
+                let id = uuid::Uuid::new_v4().to_string();
+                fs::write(&install_id_path, &id)
+                    .map_err(|e| format!("Failed to write install id: {}", e))?;
+                id
+            }
+        };
+
         let settings_path = app_data_dir.join("updater_settings.json");
         let settings = if settings_path.exists() {
             let contents = fs::read_to_string(&settings_path)
@@ -79,13 +137,16 @@ impl UpdaterState {
         Ok(Self {
             settings: RwLock::new(settings),
             backup_path,
+            staging_path,
+            install_id,
+            available: RwLock::new(None),
+            staged: RwLock::new(None),
         })
     }
 
-    pub async fn save_settings(&self, app_handle: &AppHandle) -> Result<(), String> {
+    pub async fn save_settings<R: Runtime>(&self, app_handle: &AppHandle<R>) -> Result<(), String> {
         let app_data_dir = app_handle
-            .path()
-            .app_data_dir()
+            .app_data_root()
             .map_err(|e| format!("Unable to resolve app data directory: {}", e))?;
 
         let settings_path = app_data_dir.join("updater_settings.json");
@@ -102,6 +163,184 @@ impl UpdaterState {
 
 pub type SharedUpdaterState = Arc<UpdaterState>;
 
+/// Platform key used by the manifest, e.g. `darwin-aarch64`.
+fn update_target() -> String {
+    let os = match std::env::consts::OS {
+        "macos" => "darwin",
+        os => os,
+    };
+    format!("{}-{}", os, std::env::consts::ARCH)
+}
+
+fn read_backup_metadata(backup_dir: &Path) -> Option<BackupMetadata> {
+    let contents = fs::read_to_string(backup_dir.join("metadata.json")).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+/// Swaps in a new executable. A running binary can be renamed but not
+/// overwritten on Windows, so the current one is moved aside first.
+fn replace_executable(exe: &Path, bytes: &[u8]) -> Result<(), String> {
+    let incoming = exe.with_extension("new");
+    let retired = exe.with_extension("old");
+    let permissions = fs::metadata(exe)
+        .map_err(|e| format!("Failed to read executable metadata: {}", e))?
+        .permissions();
+
+    fs::write(&incoming, bytes).map_err(|e| format!("Failed to write new executable: {}", e))?;
+    fs::set_permissions(&incoming, permissions)
+        .map_err(|e| format!("Failed to set executable permissions: {}", e))?;
+    let _ = fs::remove_file(&retired);
+    fs::rename(exe, &retired).map_err(|e| format!("Failed to move current executable: {}", e))?;
+    if let Err(e) = fs::rename(&incoming, exe) {
+        let _ = fs::rename(&retired, exe);
+        return Err(format!("Failed to install new executable: {}", e));
+    }
+    Ok(())
+}
+
+fn schedule_restart<R: Runtime>(app_handle: AppHandle<R>) {
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+        crate::errors::request_restart(&app_handle);
+    });
+}
+
+/// Fetches the release manifest and picks the update for the selected
+/// channel, honouring staged rollouts and dismissed versions.
+#[tauri::command]
+pub async fn check_for_updates(
+    app_handle: AppHandle,
+    state: State<'_, SharedUpdaterState>,
+) -> Result<Option<AvailableUpdate>, String> {
+    let current_version = app_handle.package_info().version.to_string();
+    let current = ReleaseVersion::parse(&current_version)
+        .ok_or_else(|| format!("Unrecognised app version {}", current_version))?;
+    let target = update_target();
+    let url = UPDATE_ENDPOINT
+        .replace("{target}", &target)
+        .replace("{current_version}", &current_version);
+
+    let response = reqwest::get(&url)
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("Failed to fetch update manifest: {}", e))?;
+    // Servers answer 204 when there is nothing newer for this version.
+    let body = if response.status() == reqwest::StatusCode::NO_CONTENT {
+        String::new()
+    } else {
+        response
+            .text()
+            .await
+            .map_err(|e| format!("Failed to read update manifest: {}", e))?
+    };
+    let manifest = if body.trim().is_empty() {
+        None
+    } else {
+        Some(parse_manifest(&body)?)
+    };
+
+    let mut settings = state.settings.write().await;
+    settings.last_check = Some(Utc::now().to_rfc3339());
+    let update = manifest.and_then(|manifest| {
+        select_update(
+            &manifest,
+            settings.channel,
+            &current,
+            &target,
+            &state.install_id,
+            settings.dismissed_version.as_deref(),
+        )
+    });
+    drop(settings);
+    state.save_settings(&app_handle).await?;
+
+    *state.available.write().await = update.clone();
+    if let Some(update) = &update {
+        let _ = app_handle.emit("update-available", update);
+    }
+    Ok(update)
+}
+
+#[tauri::command]
+pub async fn download_update(
+    app_handle: AppHandle,
+    state: State<'_, SharedUpdaterState>,
+) -> Result<StagedUpdate, String> {
+    let update = state
+        .available
+        .read()
+        .await
+        .clone()
+        .ok_or_else(|| "No update available; check for updates first".to_string())?;
+
+    let installed = match std::env::current_exe() {
+        Ok(exe) => tokio::fs::read(exe).await.ok(),
+        Err(_) => None,
+    };
+    let client = reqwest::Client::new();
+    let (bytes, source) = fetch_package(&client, &update, installed.as_deref()).await?;
+
+    let path = state.staging_path.join(format!("{}.pkg", update.version));
+    tokio::fs::write(&path, &bytes)
+        .await
+        .map_err(|e| format!("Failed to stage update: {}", e))?;
+
+    let staged = StagedUpdate {
+        version: update.version.clone(),
+        channel: update.channel,
+        source,
+        sha256: sha256_hex(&bytes),
+        staged_at: Utc::now(),
+        path,
+    };
+    *state.staged.write().await = Some(staged.clone());
+    let _ = app_handle.emit("update-downloaded", &staged);
+    Ok(staged)
+}
+
+/// Backs up the running build, recording the channel it came from, then
+/// installs the staged update and restarts.
+#[tauri::command]
+pub async fn install_update(
+    app_handle: AppHandle,
+    state: State<'_, SharedUpdaterState>,
+) -> Result<(), String> {
+    let staged = state
+        .staged
+        .read()
+        .await
+        .clone()
+        .ok_or_else(|| "No downloaded update to install".to_string())?;
+    let package =
+        fs::read(&staged.path).map_err(|e| format!("Failed to read staged update: {}", e))?;
+    if sha256_hex(&package) != staged.sha256 {
+        return Err("Staged update is corrupted; download it again".to_string());
+    }
+
+    let exe = std::env::current_exe()
+        .map_err(|e| format!("Unable to locate current executable: {}", e))?;
+    fs::copy(&exe, state.backup_path.join(BACKUP_BINARY))
+        .map_err(|e| format!("Failed to back up current build: {}", e))?;
+    let metadata = BackupMetadata {
+        version: app_handle.package_info().version.to_string(),
+        timestamp: Utc::now().to_rfc3339(),
+        channel: Some(state.settings.read().await.channel),
+    };
+    let json = serde_json::to_string_pretty(&metadata)
+        .map_err(|e| format!("Failed to serialize backup metadata: {}", e))?;
+    fs::write(state.backup_path.join("metadata.json"), json)
+        .map_err(|e| format!("Failed to write backup metadata: {}", e))?;
+
+    replace_executable(&exe, &package)?;
+
+    let _ = fs::remove_file(&staged.path);
+    *state.staged.write().await = None;
+    *state.available.write().await = None;
+    let _ = app_handle.emit("update-installed", &staged);
+    schedule_restart(app_handle);
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn get_update_settings(
     state: State<'_, SharedUpdaterState>,
@@ -145,6 +384,7 @@ pub async fn get_rollback_info(
             available: false,
             previous_version: None,
             backup_timestamp: None,
+            previous_channel: None,
         });
     }
 
@@ -153,17 +393,12 @@ pub async fn get_rollback_info(
         let contents = fs::read_to_string(&metadata_path)
             .map_err(|e| format!("Failed to read backup metadata: {}", e))?;
 
-        #[derive(Deserialize)]
-        struct BackupMetadata {
-            version: String,
-            timestamp: String,
-        }
-
         if let Ok(metadata) = serde_json::from_str::<BackupMetadata>(&contents) {
             return Ok(RollbackInfo {
-                available: true,
+                available: backup_dir.join(BACKUP_BINARY).exists(),
                 previous_version: Some(metadata.version),
                 backup_timestamp: Some(metadata.timestamp),
+                previous_channel: metadata.channel,
             });
         }
     }
@@ -172,6 +407,7 @@ pub async fn get_rollback_info(
         available: false,
         previous_version: None,
         backup_timestamp: None,
+        previous_channel: None,
     })
 }
 
@@ -186,26 +422,43 @@ pub async fn rollback_update<R: Runtime>(
         return Err("No backup available for rollback".to_string());
     }
 
-    let metadata_path = backup_dir.join("metadata.json");
-    if !metadata_path.exists() {
-        return Err("Backup metadata not found".to_string());
+    let metadata =
+        read_backup_metadata(backup_dir).ok_or_else(|| "Backup metadata not found".to_string())?;
+    let binary_path = backup_dir.join(BACKUP_BINARY);
+    if !binary_path.exists() {
+        return Err("Backup build not found".to_string());
     }
 
-    // Emit events to notify the UI. Real rollback logic would restore files and restart the app.
     let _ = window.emit("rollback-started", ());
 
     tracing::info!(
-        "Rollback requested. Restore backup located at: {:?}",
+        "Rolling back to {} ({:?}) from backup at {:?}",
+        metadata.version,
+        metadata.channel,
         backup_dir
     );
+    let exe = std::env::current_exe()
+        .map_err(|e| format!("Unable to locate current executable: {}", e))?;
+    let previous_build =
+        fs::read(&binary_path).map_err(|e| format!("Failed to read backup build: {}", e))?;
+    replace_executable(&exe, &previous_build)?;
 
-    let _ = window.emit("rollback-completed", ());
+    // The backup may come from another channel than the build being left,
+    // e.g. returning to stable after trying a beta. Go back to that channel
+    // and don't offer the abandoned version again.
+    let mut settings = state.settings.write().await;
+    settings.dismissed_version = Some(app_handle.package_info().version.to_string());
+    if let Some(channel) = metadata.channel {
+        settings.channel = channel;
+    }
+    drop(settings);
+    state.save_settings(&app_handle).await?;
+    *state.available.write().await = None;
+    *state.staged.write().await = None;
+    let _ = fs::remove_file(backup_dir.join("metadata.json"));
 
-    let app_handle = app_handle.clone();
-    tauri::async_runtime::spawn(async move {
-        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
-        let _ = app_handle.restart();
-    });
+    let _ = window.emit("rollback-completed", ());
+    schedule_restart(app_handle.clone());
 
     Ok(())
 }
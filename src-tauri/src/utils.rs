@@ -1,5 +1,5 @@
 use chrono::{DateTime, Utc};
-use sqlx::Error as SqlxError;
+use sqlx::{Error as SqlxError, Row, SqlitePool};
 
 #[derive(Debug, Clone)]
 pub struct Rfc3339DateTime(pub DateTime<Utc>);
@@ -41,3 +41,41 @@ impl From<OptionalRfc3339DateTime> for Option<DateTime<Utc>> {
         value.0
     }
 }
+
+/// Adds `column` to `table` unless it already exists, for tables created by an
+/// earlier version of their schema.
+pub(crate) async fn ensure_column(
+    pool: &SqlitePool,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> Result<(), SqlxError> {
+    let columns = sqlx::query(&format!("PRAGMA table_info({})", table))
+        .fetch_all(pool)
+        .await?;
+    let exists = columns
+        .iter()
+        .any(|row| row.try_get::<String, _>("name").ok().as_deref() == Some(column));
+
+    if !exists {
+        sqlx::query(&format!(
+            "ALTER TABLE {} ADD COLUMN {} {}",
+            table, column, definition
+        ))
+        .execute(pool)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// A single-connection in-memory pool, since every in-memory connection is its
+/// own database.
+#[cfg(test)]
+pub(crate) async fn test_pool() -> SqlitePool {
+    sqlx::sqlite::SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap()
+}
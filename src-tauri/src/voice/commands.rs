@@ -1,11 +1,12 @@
 use super::audio_manager::{AudioContextManager, AudioSessionSnapshot, MicrophoneStatus};
+use super::readout::{self, ReadoutConfig, ReadoutItem, ReadoutQueueSnapshot};
 use super::speech_to_text::{
     LanguageOption, SpeechRecognitionResult, SpeechToTextConfig, SpeechToTextEngine,
 };
 use super::text_to_speech::{SpeechSynthesisStatus, TextToSpeechConfig, TextToSpeechEngine, Voice};
 use super::wake_word::{WakeWordConfig, WakeWordDetection, WakeWordDetector};
 use std::sync::Arc;
-use tauri::State;
+use tauri::{AppHandle, State};
 use tokio::sync::RwLock;
 
 pub type SharedVoiceState = Arc<RwLock<VoiceState>>;
@@ -124,9 +125,14 @@ pub async fn voice_start_recognition(state: State<'_, SharedVoiceState>) -> Resu
 }
 
 #[tauri::command]
-pub async fn voice_stop_recognition(state: State<'_, SharedVoiceState>) -> Result<(), String> {
+pub async fn voice_stop_recognition(
+    app: AppHandle,
+    state: State<'_, SharedVoiceState>,
+) -> Result<(), String> {
     let voice_state = state.read().await;
-    voice_state.stt_engine.stop_recognition()
+    voice_state.stt_engine.stop_recognition()?;
+    // Readouts held while the command was being captured can play now
+    readout::start_pending_readout(&app, &voice_state)
 }
 
 #[tauri::command]
@@ -193,9 +199,54 @@ pub async fn voice_pause_speaking(state: State<'_, SharedVoiceState>) -> Result<
 }
 
 #[tauri::command]
-pub async fn voice_resume_speaking(state: State<'_, SharedVoiceState>) -> Result<(), String> {
+pub async fn voice_resume_speaking(
+    app: AppHandle,
+    state: State<'_, SharedVoiceState>,
+) -> Result<(), String> {
+    let voice_state = state.read().await;
+    voice_state.tts_engine.resume()?;
+    readout::start_pending_readout(&app, &voice_state)
+}
+
+/// Called by the frontend when an utterance ends; emits the next readout.
+#[tauri::command]
+pub async fn voice_speech_finished(
+    app: AppHandle,
+    state: State<'_, SharedVoiceState>,
+) -> Result<Option<ReadoutItem>, String> {
+    let voice_state = state.read().await;
+    let listening = voice_state.stt_engine.is_recognizing();
+    let next = voice_state.tts_engine.finish_utterance(listening)?;
+    if let Some(item) = &next {
+        readout::emit_readout(&app, item.clone(), false);
+    }
+    Ok(next)
+}
+
+#[tauri::command]
+pub async fn voice_configure_readout(
+    state: State<'_, SharedVoiceState>,
+    config: ReadoutConfig,
+) -> Result<(), String> {
+    let voice_state = state.read().await;
+    voice_state.tts_engine.configure_readout(config)
+}
+
+#[tauri::command]
+pub async fn voice_get_readout_config(
+    state: State<'_, SharedVoiceState>,
+) -> Result<ReadoutConfig, String> {
+    let voice_state = state.read().await;
+    voice_state.tts_engine.readout_config()
+}
+
+#[tauri::command]
+pub async fn voice_get_readout_queue(
+    state: State<'_, SharedVoiceState>,
+) -> Result<ReadoutQueueSnapshot, String> {
     let voice_state = state.read().await;
-    voice_state.tts_engine.resume()
+    let listening = voice_state.stt_engine.is_recognizing();
+    voice_state.tts_engine.readout_snapshot(listening)
 }
 
 #[tauri::command]
@@ -1,5 +1,6 @@
 pub mod audio_manager;
 pub mod commands;
+pub mod readout;
 pub mod speech_to_text;
 pub mod text_to_speech;
 pub mod wake_word;
@@ -7,6 +8,7 @@ pub mod trading;
 
 pub use audio_manager::*;
 pub use commands::*;
+pub use readout::*;
 pub use speech_to_text::*;
 pub use text_to_speech::*;
 pub use wake_word::*;
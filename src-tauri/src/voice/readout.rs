@@ -0,0 +1,637 @@
+use super::commands::{SharedVoiceState, VoiceState};
+use crate::notifications::types::{AlertPriority, HistoryCategory, NewNotification};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use tauri::{AppHandle, Emitter, Manager};
+use uuid::Uuid;
+
+pub const READOUT_EVENT: &str = "voice://readout";
+pub const MAX_READOUT_QUEUE: usize = 100;
+
+/// Which notifications are read aloud and how.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadoutConfig {
+    pub enabled: bool,
+    pub categories: Vec<HistoryCategory>,
+    pub priorities: Vec<AlertPriority>,
+    /// Readouts at or above this priority interrupt lower-priority speech.
+    pub interrupt_priority: AlertPriority,
+    /// Spoken template per category. `{title}`, `{message}`, `{priority}` and
+    /// `{category}` are substituted; categories without one use `{title}. {message}`.
+    pub templates: HashMap<HistoryCategory, String>,
+    pub max_queue: usize,
+    /// Hold readouts while speech recognition is capturing a command.
+    pub suppress_while_listening: bool,
+}
+
+impl Default for ReadoutConfig {
+    fn default() -> Self {
+        let templates = [
+            (HistoryCategory::PriceAlert, "Price alert: {message}"),
+            (
+                HistoryCategory::ClusterTrigger,
+                "Wallet cluster alert: {title}",
+            ),
+            (HistoryCategory::Order, "Order update: {title}. {message}"),
+            (HistoryCategory::Governance, "Governance: {title}"),
+            (HistoryCategory::CopyTrading, "Copy trading: {title}"),
+            (HistoryCategory::TokenUnlock, "Token unlock: {title}"),
+            (HistoryCategory::LiquidityLock, "Liquidity lock: {title}"),
+            (HistoryCategory::StablecoinDepeg, "Stablecoin: {title}"),
+        ]
+        .into_iter()
+        .map(|(category, template)| (category, template.to_string()))
+        .collect();
+
+        Self {
+            enabled: false,
+            categories: vec![
+                HistoryCategory::PriceAlert,
+                HistoryCategory::ClusterTrigger,
+                HistoryCategory::Order,
+            ],
+            priorities: vec![AlertPriority::High, AlertPriority::Critical],
+            interrupt_priority: AlertPriority::Critical,
+            templates,
+            max_queue: 10,
+            suppress_while_listening: true,
+        }
+    }
+}
+
+impl ReadoutConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_queue == 0 || self.max_queue > MAX_READOUT_QUEUE {
+            return Err(format!(
+                "Readout queue size must be between 1 and {}",
+                MAX_READOUT_QUEUE
+            ));
+        }
+        if let Some((category, _)) = self
+            .templates
+            .iter()
+            .find(|(_, template)| template.trim().is_empty())
+        {
+            return Err(format!(
+                "Readout template for {} is empty",
+                category.as_str()
+            ));
+        }
+        Ok(())
+    }
+
+    fn accepts(&self, notification: &NewNotification) -> bool {
+        self.enabled
+            && self.categories.contains(&notification.category)
+            && self.priorities.contains(&notification.priority)
+    }
+
+    fn render(&self, notification: &NewNotification) -> String {
+        let template = self
+            .templates
+            .get(&notification.category)
+            .map(String::as_str)
+            .unwrap_or("{title}. {message}");
+        let text = template
+            .replace("{title}", &notification.title)
+            .replace("{message}", &notification.message)
+            .replace("{priority}", notification.priority.as_str())
+            .replace(
+                "{category}",
+                &notification.category.as_str().replace('_', " "),
+            );
+        spoken_text(&text)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadoutItem {
+    pub id: String,
+    pub category: HistoryCategory,
+    pub priority: AlertPriority,
+    pub text: String,
+    pub queued_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadoutEvent {
+    pub item: ReadoutItem,
+    /// The frontend should cut off whatever it is saying first.
+    pub interrupt: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "outcome", content = "item", rename_all = "snake_case")]
+pub enum ReadoutDecision {
+    /// Not a selected category/priority, or readout is off.
+    Filtered,
+    Speak(ReadoutItem),
+    Interrupt(ReadoutItem),
+    /// Waiting for the current speech to finish.
+    Queued,
+    /// Held while paused or while a voice command is being captured.
+    Deferred,
+    /// The queue was full of higher-priority readouts.
+    Dropped,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadoutQueueSnapshot {
+    pub current: Option<ReadoutItem>,
+    pub pending: Vec<ReadoutItem>,
+    pub max_queue: usize,
+    pub paused: bool,
+    pub suppressed: bool,
+}
+
+/// Readouts waiting to be spoken, highest priority first and oldest first
+/// within a priority.
+#[derive(Debug)]
+pub struct ReadoutQueue {
+    config: ReadoutConfig,
+    current: Option<ReadoutItem>,
+    pending: VecDeque<ReadoutItem>,
+}
+
+impl ReadoutQueue {
+    pub fn new(config: ReadoutConfig) -> Self {
+        Self {
+            config,
+            current: None,
+            pending: VecDeque::new(),
+        }
+    }
+
+    pub fn config(&self) -> &ReadoutConfig {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: ReadoutConfig) {
+        self.config = config;
+        self.pending
+            .retain(|item| self.config.priorities.contains(&item.priority));
+        while self.pending.len() > self.config.max_queue {
+            self.evict_lowest();
+        }
+    }
+
+    pub fn current(&self) -> Option<&ReadoutItem> {
+        self.current.as_ref()
+    }
+
+    pub fn has_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// Decides what to do with a routed notification. `busy` is true while
+    /// ad-hoc speech is playing.
+    pub fn offer(
+        &mut self,
+        notification: &NewNotification,
+        busy: bool,
+        paused: bool,
+        listening: bool,
+    ) -> ReadoutDecision {
+        if !self.config.accepts(notification) {
+            return ReadoutDecision::Filtered;
+        }
+
+        let item = ReadoutItem {
+            id: Uuid::new_v4().to_string(),
+            category: notification.category,
+            priority: notification.priority,
+            text: self.config.render(notification),
+            queued_at: chrono::Utc::now().timestamp_millis(),
+        };
+
+        if paused || (listening && self.config.suppress_while_listening) {
+            return self.enqueue(item, ReadoutDecision::Deferred);
+        }
+
+        if busy || self.current.is_some() {
+            let outranks_current = self
+                .current
+                .as_ref()
+                .map_or(true, |current| current.priority < item.priority);
+            if item.priority >= self.config.interrupt_priority && outranks_current {
+                self.current = Some(item.clone());
+                return ReadoutDecision::Interrupt(item);
+            }
+            return self.enqueue(item, ReadoutDecision::Queued);
+        }
+
+        self.current = Some(item.clone());
+        ReadoutDecision::Speak(item)
+    }
+
+    pub fn finish_current(&mut self) -> Option<ReadoutItem> {
+        self.current.take()
+    }
+
+    /// Starts the next pending readout when nothing is being read.
+    pub fn start_next(&mut self, paused: bool, listening: bool) -> Option<ReadoutItem> {
+        if self.current.is_some() || paused || (listening && self.config.suppress_while_listening) {
+            return None;
+        }
+        self.current = self.pending.pop_front();
+        self.current.clone()
+    }
+
+    pub fn clear(&mut self) {
+        self.current = None;
+        self.pending.clear();
+    }
+
+    pub fn snapshot(&self, paused: bool, listening: bool) -> ReadoutQueueSnapshot {
+        ReadoutQueueSnapshot {
+            current: self.current.clone(),
+            pending: self.pending.iter().cloned().collect(),
+            max_queue: self.config.max_queue,
+            paused,
+            suppressed: listening && self.config.suppress_while_listening,
+        }
+    }
+
+    fn enqueue(&mut self, item: ReadoutItem, queued: ReadoutDecision) -> ReadoutDecision {
+        if self.pending.len() >= self.config.max_queue {
+            let lowest = self.pending.iter().map(|queued| queued.priority).min();
+            if lowest.map_or(true, |lowest| lowest > item.priority) {
+                return ReadoutDecision::Dropped;
+            }
+            self.evict_lowest();
+        }
+
+        let position = self
+            .pending
+            .iter()
+            .position(|queued| queued.priority < item.priority)
+            .unwrap_or(self.pending.len());
+        self.pending.insert(position, item);
+        queued
+    }
+
+    /// Drops the oldest of the lowest-priority pending readouts.
+    fn evict_lowest(&mut self) {
+        let Some(lowest) = self.pending.iter().map(|item| item.priority).min() else {
+            return;
+        };
+        if let Some(index) = self
+            .pending
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| item.priority == lowest)
+            .min_by_key(|(_, item)| item.queued_at)
+            .map(|(index, _)| index)
+        {
+            self.pending.remove(index);
+        }
+    }
+}
+
+pub fn emit_readout(app: &AppHandle, item: ReadoutItem, interrupt: bool) {
+    if let Err(e) = app.emit(READOUT_EVENT, ReadoutEvent { item, interrupt }) {
+        eprintln!("Failed to emit voice readout: {}", e);
+    }
+}
+
+/// Offers a routed notification to the readout queue and tells the frontend
+/// to speak it when it should be read now.
+pub async fn announce_notification(app: &AppHandle, notification: &NewNotification) {
+    let Some(state) = app.try_state::<SharedVoiceState>() else {
+        return;
+    };
+    let voice_state = state.read().await;
+    let listening = voice_state.stt_engine.is_recognizing();
+
+    match voice_state
+        .tts_engine
+        .offer_readout(notification, listening)
+    {
+        Ok(ReadoutDecision::Speak(item)) => emit_readout(app, item, false),
+        Ok(ReadoutDecision::Interrupt(item)) => emit_readout(app, item, true),
+        Ok(_) => {}
+        Err(e) => eprintln!("Failed to queue voice readout: {}", e),
+    }
+}
+
+/// Starts a held readout once speech is idle again, e.g. after resuming or
+/// when a voice command has been captured.
+pub fn start_pending_readout(app: &AppHandle, voice_state: &VoiceState) -> Result<(), String> {
+    let listening = voice_state.stt_engine.is_recognizing();
+    if let Some(item) = voice_state.tts_engine.start_pending_readout(listening)? {
+        emit_readout(app, item, false);
+    }
+    Ok(())
+}
+
+const ONES: [&str; 20] = [
+    "zero",
+    "one",
+    "two",
+    "three",
+    "four",
+    "five",
+    "six",
+    "seven",
+    "eight",
+    "nine",
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+];
+
+const TENS: [&str; 10] = [
+    "", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+
+fn below_thousand(n: u64) -> String {
+    let mut words = Vec::new();
+    if n >= 100 {
+        words.push(format!("{} hundred", ONES[(n / 100) as usize]));
+    }
+    let rest = n % 100;
+    if rest >= 20 {
+        match rest % 10 {
+            0 => words.push(TENS[(rest / 10) as usize].to_string()),
+            unit => words.push(format!(
+                "{}-{}",
+                TENS[(rest / 10) as usize],
+                ONES[unit as usize]
+            )),
+        }
+    } else if rest > 0 || words.is_empty() {
+        words.push(ONES[rest as usize].to_string());
+    }
+    words.join(" ")
+}
+
+pub fn number_to_words(n: u64) -> String {
+    if n < 1000 {
+        return below_thousand(n);
+    }
+
+    let scales = [
+        (1_000_000_000_000, "trillion"),
+        (1_000_000_000, "billion"),
+        (1_000_000, "million"),
+        (1_000, "thousand"),
+    ];
+    let mut remaining = n;
+    let mut words = Vec::new();
+    for (scale, name) in scales {
+        if remaining >= scale {
+            words.push(format!("{} {}", number_to_words(remaining / scale), name));
+            remaining %= scale;
+        }
+    }
+    if remaining > 0 {
+        words.push(below_thousand(remaining));
+    }
+    words.join(" ")
+}
+
+/// "$200" reads as "two hundred dollars"; sub-dollar prices keep their digits
+/// since rounding them to cents would lose the price.
+pub fn spoken_dollars(amount: f64) -> String {
+    if amount < 1.0 {
+        let digits = format!("{}", amount);
+        return format!("{} dollars", digits);
+    }
+
+    let cents = (amount * 100.0).round() as u64;
+    let (dollars, cents) = (cents / 100, cents % 100);
+    let mut spoken = format!(
+        "{} {}",
+        number_to_words(dollars),
+        if dollars == 1 { "dollar" } else { "dollars" }
+    );
+    if cents > 0 {
+        spoken.push_str(&format!(
+            " and {} {}",
+            number_to_words(cents),
+            if cents == 1 { "cent" } else { "cents" }
+        ));
+    }
+    spoken
+}
+
+/// Rewrites dollar amounts in `text` so they are spoken naturally.
+pub fn spoken_text(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut spoken = String::with_capacity(text.len());
+    let mut i = 0;
+
+    while i < chars.len() {
+        let starts_amount = chars[i] == '$' && chars.get(i + 1).is_some_and(|c| c.is_ascii_digit());
+        if !starts_amount {
+            spoken.push(chars[i]);
+            i += 1;
+            continue;
+        }
+
+        let mut end = i + 1;
+        while end < chars.len()
+            && (chars[end].is_ascii_digit() || chars[end] == ',' || chars[end] == '.')
+        {
+            end += 1;
+        }
+        // Sentence punctuation after the amount stays in the text
+        while matches!(chars[end - 1], ',' | '.') {
+            end -= 1;
+        }
+
+        let digits: String = chars[i + 1..end].iter().filter(|c| **c != ',').collect();
+        match digits.parse::<f64>() {
+            Ok(amount) => spoken.push_str(&spoken_dollars(amount)),
+            Err(_) => spoken.extend(&chars[i..end]),
+        }
+        i = end;
+    }
+
+    spoken
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notification(category: HistoryCategory, priority: AlertPriority) -> NewNotification {
+        NewNotification {
+            category,
+            priority,
+            title: "SOL above 200".to_string(),
+            message: "SOL crossed $200.0000".to_string(),
+            entity: None,
+            link: None,
+        }
+    }
+
+    fn enabled_queue(max_queue: usize) -> ReadoutQueue {
+        ReadoutQueue::new(ReadoutConfig {
+            enabled: true,
+            priorities: vec![
+                AlertPriority::Low,
+                AlertPriority::Medium,
+                AlertPriority::High,
+                AlertPriority::Critical,
+            ],
+            max_queue,
+            ..ReadoutConfig::default()
+        })
+    }
+
+    #[test]
+    fn test_spoken_amounts() {
+        assert_eq!(number_to_words(0), "zero");
+        assert_eq!(number_to_words(42), "forty-two");
+        assert_eq!(
+            number_to_words(1_205_310),
+            "one million two hundred five thousand three hundred ten"
+        );
+        assert_eq!(spoken_dollars(200.0), "two hundred dollars");
+        assert_eq!(spoken_dollars(1.05), "one dollar and five cents");
+        assert_eq!(spoken_dollars(0.0025), "0.0025 dollars");
+        assert_eq!(
+            spoken_text("BTC crossed $64,250.5."),
+            "BTC crossed sixty-four thousand two hundred fifty dollars and fifty cents."
+        );
+    }
+
+    #[test]
+    fn test_templates_render_spoken_text() {
+        let queue = enabled_queue(5);
+        let text = queue.config().render(&notification(
+            HistoryCategory::PriceAlert,
+            AlertPriority::High,
+        ));
+        assert_eq!(text, "Price alert: SOL crossed two hundred dollars");
+
+        let text = queue
+            .config()
+            .render(&notification(HistoryCategory::System, AlertPriority::High));
+        assert_eq!(text, "SOL above 200. SOL crossed two hundred dollars");
+    }
+
+    #[test]
+    fn test_filters_by_category_and_priority() {
+        let mut queue = ReadoutQueue::new(ReadoutConfig {
+            enabled: true,
+            ..ReadoutConfig::default()
+        });
+
+        let low = notification(HistoryCategory::PriceAlert, AlertPriority::Low);
+        assert!(matches!(
+            queue.offer(&low, false, false, false),
+            ReadoutDecision::Filtered
+        ));
+
+        let governance = notification(HistoryCategory::Governance, AlertPriority::Critical);
+        assert!(matches!(
+            queue.offer(&governance, false, false, false),
+            ReadoutDecision::Filtered
+        ));
+
+        let high = notification(HistoryCategory::PriceAlert, AlertPriority::High);
+        assert!(matches!(
+            queue.offer(&high, false, false, false),
+            ReadoutDecision::Speak(_)
+        ));
+
+        let mut disabled = ReadoutQueue::new(ReadoutConfig::default());
+        assert!(matches!(
+            disabled.offer(&high, false, false, false),
+            ReadoutDecision::Filtered
+        ));
+    }
+
+    #[test]
+    fn test_critical_interrupts_and_lower_priorities_queue() {
+        let mut queue = enabled_queue(5);
+        let high = notification(HistoryCategory::PriceAlert, AlertPriority::High);
+        let critical = notification(HistoryCategory::PriceAlert, AlertPriority::Critical);
+
+        // Ad-hoc speech is playing
+        assert!(matches!(
+            queue.offer(&high, true, false, false),
+            ReadoutDecision::Queued
+        ));
+        assert!(matches!(
+            queue.offer(&critical, true, false, false),
+            ReadoutDecision::Interrupt(_)
+        ));
+        assert_eq!(queue.current().unwrap().priority, AlertPriority::Critical);
+
+        // A critical readout is not interrupted by another critical one
+        assert!(matches!(
+            queue.offer(&critical, true, false, false),
+            ReadoutDecision::Queued
+        ));
+
+        queue.finish_current();
+        let next = queue.start_next(false, false).unwrap();
+        assert_eq!(next.priority, AlertPriority::Critical);
+        queue.finish_current();
+        assert_eq!(
+            queue.start_next(false, false).unwrap().priority,
+            AlertPriority::High
+        );
+    }
+
+    #[test]
+    fn test_suppressed_while_listening_and_paused() {
+        let mut queue = enabled_queue(5);
+        let critical = notification(HistoryCategory::PriceAlert, AlertPriority::Critical);
+
+        assert!(matches!(
+            queue.offer(&critical, false, false, true),
+            ReadoutDecision::Deferred
+        ));
+        assert!(queue.start_next(false, true).is_none());
+        assert!(queue.snapshot(false, true).suppressed);
+
+        assert!(matches!(
+            queue.offer(&critical, false, true, false),
+            ReadoutDecision::Deferred
+        ));
+        assert!(queue.start_next(true, false).is_none());
+        assert!(queue.start_next(false, false).is_some());
+    }
+
+    #[test]
+    fn test_full_queue_evicts_oldest_low_priority() {
+        let mut queue = enabled_queue(3);
+        let low = notification(HistoryCategory::PriceAlert, AlertPriority::Low);
+        let medium = notification(HistoryCategory::PriceAlert, AlertPriority::Medium);
+        let high = notification(HistoryCategory::PriceAlert, AlertPriority::High);
+
+        queue.offer(&low, true, false, false);
+        let oldest_low = queue.snapshot(false, false).pending[0].id.clone();
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        queue.offer(&low, true, false, false);
+        queue.offer(&medium, true, false, false);
+
+        assert!(matches!(
+            queue.offer(&high, true, false, false),
+            ReadoutDecision::Queued
+        ));
+        let pending = queue.snapshot(false, false).pending;
+        assert_eq!(pending.len(), 3);
+        assert_eq!(pending[0].priority, AlertPriority::High);
+        assert_eq!(pending[1].priority, AlertPriority::Medium);
+        assert!(pending.iter().all(|item| item.id != oldest_low));
+
+        // Nothing lower than the new readout is left to evict
+        queue.offer(&high, true, false, false);
+        assert!(matches!(
+            queue.offer(&low, true, false, false),
+            ReadoutDecision::Dropped
+        ));
+    }
+}
@@ -1,3 +1,7 @@
+use super::readout::{
+    ReadoutConfig, ReadoutDecision, ReadoutItem, ReadoutQueue, ReadoutQueueSnapshot,
+};
+use crate::notifications::types::NewNotification;
 use serde::{Deserialize, Serialize};
 use std::sync::{Arc, Mutex};
 
@@ -42,7 +46,9 @@ pub struct SpeechSynthesisStatus {
 pub struct TextToSpeechEngine {
     config: Arc<Mutex<TextToSpeechConfig>>,
     is_speaking: Arc<Mutex<bool>>,
+    paused: Arc<Mutex<bool>>,
     queue: Arc<Mutex<Vec<String>>>,
+    readout: Arc<Mutex<ReadoutQueue>>,
 }
 
 impl TextToSpeechEngine {
@@ -50,7 +56,9 @@ impl TextToSpeechEngine {
         Self {
             config: Arc::new(Mutex::new(config)),
             is_speaking: Arc::new(Mutex::new(false)),
+            paused: Arc::new(Mutex::new(false)),
             queue: Arc::new(Mutex::new(Vec::new())),
+            readout: Arc::new(Mutex::new(ReadoutQueue::new(ReadoutConfig::default()))),
         }
     }
 
@@ -74,23 +82,27 @@ impl TextToSpeechEngine {
         Ok(())
     }
 
+    /// Stops speaking and discards queued speech, including pending readouts.
     pub fn stop(&self) -> Result<(), String> {
+        self.readout.lock().map_err(|e| e.to_string())?.clear();
+        self.queue.lock().map_err(|e| e.to_string())?.clear();
+
         let mut speaking = self.is_speaking.lock().map_err(|e| e.to_string())?;
         *speaking = false;
 
-        let mut queue = self.queue.lock().map_err(|e| e.to_string())?;
-        queue.clear();
-
         Ok(())
     }
 
+    /// Readouts arriving while paused are held until `resume`.
     pub fn pause(&self) -> Result<(), String> {
-        // Placeholder for pause functionality
+        let mut paused = self.paused.lock().map_err(|e| e.to_string())?;
+        *paused = true;
         Ok(())
     }
 
     pub fn resume(&self) -> Result<(), String> {
-        // Placeholder for resume functionality
+        let mut paused = self.paused.lock().map_err(|e| e.to_string())?;
+        *paused = false;
         Ok(())
     }
 
@@ -98,17 +110,118 @@ impl TextToSpeechEngine {
         self.is_speaking.lock().map(|s| *s).unwrap_or(false)
     }
 
+    pub fn is_paused(&self) -> bool {
+        self.paused.lock().map(|p| *p).unwrap_or(false)
+    }
+
     pub fn get_status(&self) -> SpeechSynthesisStatus {
         let speaking = self.is_speaking();
-        let pending = self.queue.lock().map(|q| !q.is_empty()).unwrap_or(false);
+        let pending = self.queue.lock().map(|q| !q.is_empty()).unwrap_or(false)
+            || self
+                .readout
+                .lock()
+                .map(|r| r.has_pending())
+                .unwrap_or(false);
 
         SpeechSynthesisStatus {
             speaking,
-            paused: false,
+            paused: self.is_paused(),
             pending,
         }
     }
 
+    /// Offers a notification for read-out. `listening` is true while speech
+    /// recognition is capturing a command.
+    pub fn offer_readout(
+        &self,
+        notification: &NewNotification,
+        listening: bool,
+    ) -> Result<ReadoutDecision, String> {
+        if !self.get_config()?.enabled {
+            return Ok(ReadoutDecision::Filtered);
+        }
+
+        let paused = self.is_paused();
+        let mut readout = self.readout.lock().map_err(|e| e.to_string())?;
+        let interrupted_adhoc = readout.current().is_none() && self.is_speaking();
+        let decision = readout.offer(notification, self.is_speaking(), paused, listening);
+
+        match &decision {
+            ReadoutDecision::Interrupt(_) => {
+                // The interrupted ad-hoc utterance is cut off, not resumed
+                if interrupted_adhoc {
+                    let mut queue = self.queue.lock().map_err(|e| e.to_string())?;
+                    if !queue.is_empty() {
+                        queue.remove(0);
+                    }
+                }
+                *self.is_speaking.lock().map_err(|e| e.to_string())? = true;
+            }
+            ReadoutDecision::Speak(_) => {
+                *self.is_speaking.lock().map_err(|e| e.to_string())? = true;
+            }
+            _ => {}
+        }
+
+        Ok(decision)
+    }
+
+    /// Marks the current utterance as finished and returns the next readout
+    /// to speak. Pending readouts go ahead of queued ad-hoc speech.
+    pub fn finish_utterance(&self, listening: bool) -> Result<Option<ReadoutItem>, String> {
+        let paused = self.is_paused();
+        let mut readout = self.readout.lock().map_err(|e| e.to_string())?;
+        if readout.finish_current().is_none() {
+            let mut queue = self.queue.lock().map_err(|e| e.to_string())?;
+            if !queue.is_empty() {
+                queue.remove(0);
+            }
+        }
+
+        let next = readout.start_next(paused, listening);
+        let adhoc_pending = !self.queue.lock().map_err(|e| e.to_string())?.is_empty();
+        *self.is_speaking.lock().map_err(|e| e.to_string())? = next.is_some() || adhoc_pending;
+        Ok(next)
+    }
+
+    /// Starts a held readout if nothing is being spoken.
+    pub fn start_pending_readout(&self, listening: bool) -> Result<Option<ReadoutItem>, String> {
+        if self.is_speaking() {
+            return Ok(None);
+        }
+
+        let paused = self.is_paused();
+        let next = self
+            .readout
+            .lock()
+            .map_err(|e| e.to_string())?
+            .start_next(paused, listening);
+        if next.is_some() {
+            *self.is_speaking.lock().map_err(|e| e.to_string())? = true;
+        }
+        Ok(next)
+    }
+
+    pub fn configure_readout(&self, config: ReadoutConfig) -> Result<(), String> {
+        config.validate()?;
+        self.readout
+            .lock()
+            .map_err(|e| e.to_string())?
+            .set_config(config);
+        Ok(())
+    }
+
+    pub fn readout_config(&self) -> Result<ReadoutConfig, String> {
+        let readout = self.readout.lock().map_err(|e| e.to_string())?;
+        Ok(readout.config().clone())
+    }
+
+    pub fn readout_snapshot(&self, listening: bool) -> Result<ReadoutQueueSnapshot, String> {
+        let paused = self.is_paused();
+        let readout = self.readout.lock().map_err(|e| e.to_string())?;
+        Ok(readout.snapshot(paused, listening))
+    }
+
     pub fn update_config(&self, config: TextToSpeechConfig) -> Result<(), String> {
         let mut current = self.config.lock().map_err(|e| e.to_string())?;
         *current = config;
@@ -186,6 +299,7 @@ impl TextToSpeechEngine {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::notifications::types::{AlertPriority, HistoryCategory};
 
     #[test]
     fn test_tts_engine_creation() {
@@ -234,6 +348,83 @@ mod tests {
         assert!(!engine.is_speaking());
     }
 
+    fn readout_engine() -> TextToSpeechEngine {
+        let engine = TextToSpeechEngine::new(TextToSpeechConfig::default());
+        engine
+            .configure_readout(ReadoutConfig {
+                enabled: true,
+                ..ReadoutConfig::default()
+            })
+            .unwrap();
+        engine
+    }
+
+    fn alert(priority: AlertPriority) -> NewNotification {
+        NewNotification {
+            category: HistoryCategory::PriceAlert,
+            priority,
+            title: "SOL above 200".to_string(),
+            message: "SOL at $201.5000: above".to_string(),
+            entity: None,
+            link: None,
+        }
+    }
+
+    #[test]
+    fn test_readout_waits_for_current_speech() {
+        let engine = readout_engine();
+        engine.speak("Portfolio summary".to_string()).unwrap();
+
+        let decision = engine
+            .offer_readout(&alert(AlertPriority::High), false)
+            .unwrap();
+        assert!(matches!(decision, ReadoutDecision::Queued));
+        assert!(engine.get_status().pending);
+
+        let next = engine.finish_utterance(false).unwrap().unwrap();
+        assert_eq!(next.priority, AlertPriority::High);
+        assert!(engine.is_speaking());
+        assert!(engine.finish_utterance(false).unwrap().is_none());
+        assert!(!engine.is_speaking());
+    }
+
+    #[test]
+    fn test_critical_readout_interrupts_speech() {
+        let engine = readout_engine();
+        engine.speak("Portfolio summary".to_string()).unwrap();
+
+        let decision = engine
+            .offer_readout(&alert(AlertPriority::Critical), false)
+            .unwrap();
+        assert!(matches!(decision, ReadoutDecision::Interrupt(_)));
+        assert!(engine.finish_utterance(false).unwrap().is_none());
+        assert!(!engine.get_status().pending);
+    }
+
+    #[test]
+    fn test_pause_and_stop_apply_to_readouts() {
+        let engine = readout_engine();
+        engine.pause().unwrap();
+        assert!(engine.get_status().paused);
+
+        let decision = engine
+            .offer_readout(&alert(AlertPriority::High), false)
+            .unwrap();
+        assert!(matches!(decision, ReadoutDecision::Deferred));
+        assert!(engine.start_pending_readout(false).unwrap().is_none());
+
+        engine.resume().unwrap();
+        assert!(engine.start_pending_readout(false).unwrap().is_some());
+
+        engine
+            .offer_readout(&alert(AlertPriority::High), false)
+            .unwrap();
+        engine.stop().unwrap();
+        assert!(!engine.is_speaking());
+        assert!(engine.readout_snapshot(false).unwrap().pending.is_empty());
+        assert!(engine.readout_snapshot(false).unwrap().current.is_none());
+    }
+
     #[test]
     fn test_available_voices() {
         let voices = TextToSpeechEngine::get_available_voices();
@@ -2,9 +2,11 @@ use crate::alerts::price_alerts::{
     AlertCondition, AlertConditionType, CompoundCondition, CreateAlertRequest, LogicalOperator,
     NotificationChannel, PriceAlert, SharedAlertManager,
 };
+use crate::trading::{SharedSafetyEngine, TradeOrigin};
+use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use tauri::State;
+use tauri::{AppHandle, Manager, State};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -59,13 +61,22 @@ pub struct TrendingToken {
 /// 2. Validate permissions
 /// 3. Execute through the trading engine
 #[tauri::command]
-pub async fn execute_voice_trade(command: String) -> Result<String, String> {
+pub async fn execute_voice_trade(app: AppHandle, command: String) -> Result<String, String> {
     // Parse command (stub)
     let parts: Vec<&str> = command.split_whitespace().collect();
     if parts.is_empty() {
         return Err("Empty command".to_string());
     }
 
+    // Voice trades are held to the trading schedule like manual ones
+    if let Some(safety_engine) = app.try_state::<SharedSafetyEngine>() {
+        safety_engine
+            .read()
+            .await
+            .check_trading_hours(TradeOrigin::Voice, Utc::now())
+            .map_err(|e| e.to_string())?;
+    }
+
     // Stub response indicating command received
     Ok(format!(
         "Voice trade command received: {}. Trading feature is under development.",
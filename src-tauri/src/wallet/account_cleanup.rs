@@ -0,0 +1,759 @@
+//! Finds empty token accounts and closes them to reclaim their rent.
+//!
+//! Closing happens in two steps so every wallet type signs the same way:
+//! `close_token_accounts` prepares unsigned, batched close transactions, the
+//! frontend signs them through the usual wallet path (Phantom or a hardware
+//! wallet) and `submit_token_account_closures` verifies and sends them.
+//! Accounts whose mint is still in use by an order, a DCA config or a price
+//! alert are refused unless the caller explicitly allows it; frozen accounts
+//! and accounts holding tokens are never closed.
+
+use chrono::{DateTime, Duration, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use solana_sdk::{
+    hash::Hash,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    transaction::Transaction,
+};
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use std::sync::Arc;
+use tauri::{AppHandle, Manager, Runtime, State, Wry};
+use uuid::Uuid;
+
+use crate::alerts::{AlertState, SharedAlertManager};
+use crate::api_config::{rpc_endpoint, solana_rpc};
+use crate::config::DataPaths;
+use crate::p2p::onchain::{
+    decode_transaction, encode_transaction, merge_signatures, missing_signers,
+};
+use crate::wallet::multi_wallet::{MultiWalletError, MultiWalletManager};
+
+const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+const TOKEN_PROGRAM_ID: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+const TOKEN_2022_PROGRAM_ID: &str = "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb";
+/// SPL Token `CloseAccount`, the same tag in Token-2022.
+const CLOSE_ACCOUNT: u8 = 9;
+/// Close instructions per transaction. A full batch stays well under the
+/// 1232-byte packet limit, leaving room for a compute budget instruction.
+const MAX_CLOSES_PER_TRANSACTION: usize = 20;
+/// Prepared batches carry a blockhash that expires after about a minute.
+const PREPARED_BATCH_TTL_SECS: i64 = 120;
+
+#[derive(Debug, thiserror::Error)]
+pub enum AccountCleanupError {
+    #[error("rpc error: {0}")]
+    Rpc(String),
+    #[error("state error: {0}")]
+    State(String),
+    #[error("transaction error: {0}")]
+    Transaction(String),
+    #[error(transparent)]
+    Wallet(#[from] MultiWalletError),
+}
+
+/// Why an empty account is left alone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CloseGuard {
+    /// The mint authority froze the account; it can't be closed.
+    Frozen,
+    OpenOrder,
+    ActiveDca,
+    PriceAlert,
+}
+
+impl CloseGuard {
+    /// Whether `allow_in_use` lets the account be closed anyway.
+    pub fn overridable(&self) -> bool {
+        !matches!(self, CloseGuard::Frozen)
+    }
+
+    fn describe(&self) -> &'static str {
+        match self {
+            CloseGuard::Frozen => "account is frozen",
+            CloseGuard::OpenOrder => "mint has an open order",
+            CloseGuard::ActiveDca => "mint is used by an active DCA config",
+            CloseGuard::PriceAlert => "mint has a price alert",
+        }
+    }
+}
+
+/// A token account owned by the wallet, as read from chain.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OwnedTokenAccount {
+    pub address: String,
+    pub mint: String,
+    pub program_id: String,
+    pub lamports: u64,
+    pub amount: u64,
+    pub frozen: bool,
+}
+
+/// Mints the rest of the app still relies on.
+#[derive(Debug, Clone, Default)]
+pub struct MintsInUse {
+    pub orders: HashSet<String>,
+    pub dca: HashSet<String>,
+    pub alerts: HashSet<String>,
+}
+
+impl MintsInUse {
+    pub fn guard_for(&self, account: &OwnedTokenAccount) -> Option<CloseGuard> {
+        if account.frozen {
+            Some(CloseGuard::Frozen)
+        } else if self.orders.contains(&account.mint) {
+            Some(CloseGuard::OpenOrder)
+        } else if self.dca.contains(&account.mint) {
+            Some(CloseGuard::ActiveDca)
+        } else if self.alerts.contains(&account.mint) {
+            Some(CloseGuard::PriceAlert)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ReclaimableAccount {
+    pub address: String,
+    pub mint: String,
+    pub program_id: String,
+    pub rent_lamports: u64,
+    /// Set for accounts that can only be closed with `allow_in_use`.
+    pub guard: Option<CloseGuard>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ExcludedAccount {
+    pub address: String,
+    pub mint: String,
+    pub reason: CloseGuard,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReclaimableAccounts {
+    pub wallet: String,
+    pub accounts: Vec<ReclaimableAccount>,
+    /// Empty accounts held by open orders, active DCA configs or a freeze.
+    pub excluded: Vec<ExcludedAccount>,
+    /// Rent recovered by closing every account in `accounts` without a guard.
+    pub reclaimable_sol: f64,
+    pub scanned_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CloseStatus {
+    /// Prepared and waiting for a signature.
+    Prepared,
+    Closed,
+    Refused,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountCloseResult {
+    pub address: String,
+    pub status: CloseStatus,
+    pub batch_id: Option<String>,
+    pub signature: Option<String>,
+    pub error: Option<String>,
+}
+
+impl AccountCloseResult {
+    fn new(address: &str, status: CloseStatus, batch_id: Option<&str>) -> Self {
+        Self {
+            address: address.to_string(),
+            status,
+            batch_id: batch_id.map(str::to_string),
+            signature: None,
+            error: None,
+        }
+    }
+
+    fn refused(address: &str, error: impl Into<String>) -> Self {
+        Self {
+            error: Some(error.into()),
+            ..Self::new(address, CloseStatus::Refused, None)
+        }
+    }
+}
+
+/// One unsigned close transaction for the wallet to sign.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CloseBatch {
+    pub id: String,
+    /// Base64 bincode transaction, the format the wallet signing commands take.
+    pub transaction: String,
+    pub accounts: Vec<String>,
+    pub rent_lamports: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreparedAccountClosure {
+    pub wallet: String,
+    pub batches: Vec<CloseBatch>,
+    pub results: Vec<AccountCloseResult>,
+    pub reclaimable_sol: f64,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignedCloseBatch {
+    pub id: String,
+    pub transaction: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountCloseReport {
+    pub wallet: String,
+    pub results: Vec<AccountCloseResult>,
+    pub closed: usize,
+    pub failed: usize,
+    pub reclaimed_sol: f64,
+}
+
+/// Parses a jsonParsed `getTokenAccountsByOwner` result.
+pub fn parse_token_accounts(result: &Value) -> Vec<OwnedTokenAccount> {
+    result
+        .get("value")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| {
+            let account = entry.get("account")?;
+            let info = account.pointer("/data/parsed/info")?;
+            Some(OwnedTokenAccount {
+                address: entry.get("pubkey")?.as_str()?.to_string(),
+                mint: info.get("mint")?.as_str()?.to_string(),
+                program_id: account.get("owner")?.as_str()?.to_string(),
+                lamports: account.get("lamports")?.as_u64()?,
+                amount: info
+                    .pointer("/tokenAmount/amount")?
+                    .as_str()?
+                    .parse()
+                    .ok()?,
+                frozen: info.get("state").and_then(Value::as_str) == Some("frozen"),
+            })
+        })
+        .collect()
+}
+
+/// Splits the wallet's empty accounts into closable and excluded ones.
+pub fn reclaimable_accounts(
+    wallet: &str,
+    accounts: &[OwnedTokenAccount],
+    in_use: &MintsInUse,
+) -> ReclaimableAccounts {
+    let mut report = ReclaimableAccounts {
+        wallet: wallet.to_string(),
+        accounts: Vec::new(),
+        excluded: Vec::new(),
+        reclaimable_sol: 0.0,
+        scanned_at: Utc::now(),
+    };
+    for account in accounts.iter().filter(|account| account.amount == 0) {
+        let guard = in_use.guard_for(account);
+        match guard {
+            Some(reason) if reason != CloseGuard::PriceAlert => {
+                report.excluded.push(ExcludedAccount {
+                    address: account.address.clone(),
+                    mint: account.mint.clone(),
+                    reason,
+                })
+            }
+            _ => {
+                if guard.is_none() {
+                    report.reclaimable_sol += account.lamports as f64 / LAMPORTS_PER_SOL;
+                }
+                report.accounts.push(ReclaimableAccount {
+                    address: account.address.clone(),
+                    mint: account.mint.clone(),
+                    program_id: account.program_id.clone(),
+                    rent_lamports: account.lamports,
+                    guard,
+                });
+            }
+        }
+    }
+    report
+        .accounts
+        .sort_by(|a, b| b.rent_lamports.cmp(&a.rent_lamports));
+    report
+}
+
+/// Checks a requested account against its current on-chain state.
+pub fn check_closable(
+    account: Option<&OwnedTokenAccount>,
+    in_use: &MintsInUse,
+    allow_in_use: bool,
+) -> Result<(), String> {
+    let Some(account) = account else {
+        return Err("not a token account owned by this wallet".to_string());
+    };
+    if account.amount > 0 {
+        return Err("account still holds tokens".to_string());
+    }
+    match in_use.guard_for(account) {
+        Some(guard) if !guard.overridable() || !allow_in_use => Err(guard.describe().to_string()),
+        _ => Ok(()),
+    }
+}
+
+fn close_account(account: &Pubkey, program_id: &Pubkey, owner: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*account, false),
+            AccountMeta::new(*owner, false),
+            AccountMeta::new_readonly(*owner, true),
+        ],
+        data: vec![CLOSE_ACCOUNT],
+    }
+}
+
+/// Close transactions for `accounts`, paid by and returning rent to `owner`.
+pub fn close_transactions(
+    owner: &Pubkey,
+    accounts: &[&OwnedTokenAccount],
+    blockhash: Hash,
+) -> Result<Vec<Transaction>, String> {
+    accounts
+        .chunks(MAX_CLOSES_PER_TRANSACTION)
+        .map(|chunk| {
+            let instructions = chunk
+                .iter()
+                .map(|account| {
+                    let address = Pubkey::from_str(&account.address)
+                        .map_err(|_| format!("Invalid account address: {}", account.address))?;
+                    let program = Pubkey::from_str(&account.program_id)
+                        .map_err(|_| format!("Invalid program id: {}", account.program_id))?;
+                    Ok(close_account(&address, &program, owner))
+                })
+                .collect::<Result<Vec<_>, String>>()?;
+            let mut transaction = Transaction::new_with_payer(&instructions, Some(owner));
+            transaction.message.recent_blockhash = blockhash;
+            Ok(transaction)
+        })
+        .collect()
+}
+
+struct PendingClosure {
+    wallet: String,
+    transaction: Transaction,
+    accounts: Vec<(String, u64)>,
+    prepared_at: DateTime<Utc>,
+}
+
+pub struct AccountCleanup<R: Runtime = Wry> {
+    app: AppHandle<R>,
+    pending: Mutex<HashMap<String, PendingClosure>>,
+}
+
+pub type SharedAccountCleanup = Arc<AccountCleanup>;
+
+impl<R: Runtime> AccountCleanup<R> {
+    pub fn new(app: &AppHandle<R>) -> Self {
+        Self {
+            app: app.clone(),
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub async fn list(&self, wallet: &str) -> Result<ReclaimableAccounts, AccountCleanupError> {
+        let accounts = self.token_accounts(wallet).await?;
+        let in_use = self.mints_in_use(wallet).await?;
+        Ok(reclaimable_accounts(wallet, &accounts, &in_use))
+    }
+
+    /// Prepares close transactions for the requested accounts. Refusals are
+    /// reported per account and don't stop the others.
+    pub async fn prepare(
+        &self,
+        wallet: &str,
+        requested: &[String],
+        allow_in_use: bool,
+    ) -> Result<PreparedAccountClosure, AccountCleanupError> {
+        // The batches are signed by `wallet`, so a watch-only one is refused
+        // before anything is fetched.
+        if let Some(wallets) = self.app.try_state::<MultiWalletManager>() {
+            wallets.ensure_can_sign(wallet)?;
+        }
+        let owner = Pubkey::from_str(wallet)
+            .map_err(|_| AccountCleanupError::Transaction(format!("Invalid wallet: {wallet}")))?;
+        let accounts = self.token_accounts(wallet).await?;
+        let in_use = self.mints_in_use(wallet).await?;
+        let by_address: HashMap<&str, &OwnedTokenAccount> = accounts
+            .iter()
+            .map(|account| (account.address.as_str(), account))
+            .collect();
+
+        let mut results = Vec::new();
+        let mut closable = Vec::new();
+        let mut seen = HashSet::new();
+        for address in requested {
+            if !seen.insert(address.as_str()) {
+                continue;
+            }
+            let account = by_address.get(address.as_str()).copied();
+            match check_closable(account, &in_use, allow_in_use) {
+                Ok(()) => closable.extend(account),
+                Err(reason) => results.push(AccountCloseResult::refused(address, reason)),
+            }
+        }
+
+        let prepared_at = Utc::now();
+        let mut prepared = PreparedAccountClosure {
+            wallet: wallet.to_string(),
+            batches: Vec::new(),
+            results,
+            reclaimable_sol: 0.0,
+            expires_at: prepared_at + Duration::seconds(PREPARED_BATCH_TTL_SECS),
+        };
+        if closable.is_empty() {
+            return Ok(prepared);
+        }
+
+        let blockhash = self.latest_blockhash().await?;
+        let transactions = close_transactions(&owner, &closable, blockhash)
+            .map_err(AccountCleanupError::Transaction)?;
+
+        let mut pending = self.pending.lock();
+        pending.retain(|_, batch| {
+            prepared_at - batch.prepared_at < Duration::seconds(PREPARED_BATCH_TTL_SECS)
+        });
+        for (transaction, chunk) in transactions
+            .into_iter()
+            .zip(closable.chunks(MAX_CLOSES_PER_TRANSACTION))
+        {
+            let id = Uuid::new_v4().to_string();
+            let batch = CloseBatch {
+                id: id.clone(),
+                transaction: encode_transaction(&transaction)
+                    .map_err(|e| AccountCleanupError::Transaction(e.to_string()))?,
+                accounts: chunk.iter().map(|a| a.address.clone()).collect(),
+                rent_lamports: chunk.iter().map(|a| a.lamports).sum(),
+            };
+            prepared.reclaimable_sol += batch.rent_lamports as f64 / LAMPORTS_PER_SOL;
+            prepared.results.extend(
+                chunk
+                    .iter()
+                    .map(|a| AccountCloseResult::new(&a.address, CloseStatus::Prepared, Some(&id))),
+            );
+            pending.insert(
+                id,
+                PendingClosure {
+                    wallet: wallet.to_string(),
+                    transaction,
+                    accounts: chunk
+                        .iter()
+                        .map(|a| (a.address.clone(), a.lamports))
+                        .collect(),
+                    prepared_at,
+                },
+            );
+            prepared.batches.push(batch);
+        }
+
+        Ok(prepared)
+    }
+
+    /// Sends signed batches. A batch that fails to verify or send marks all
+    /// of its accounts failed; the other batches still go out.
+    pub async fn submit(&self, wallet: &str, signed: Vec<SignedCloseBatch>) -> AccountCloseReport {
+        let mut report = AccountCloseReport {
+            wallet: wallet.to_string(),
+            results: Vec::new(),
+            closed: 0,
+            failed: 0,
+            reclaimed_sol: 0.0,
+        };
+
+        for batch in signed {
+            let pending = {
+                let mut pending = self.pending.lock();
+                match pending.get(&batch.id) {
+                    Some(closure) if closure.wallet == wallet => pending.remove(&batch.id),
+                    _ => None,
+                }
+            };
+            let Some(pending) = pending else {
+                report.results.push(AccountCloseResult {
+                    error: Some("Unknown or expired batch; prepare it again".to_string()),
+                    ..AccountCloseResult::new("", CloseStatus::Failed, Some(&batch.id))
+                });
+                report.failed += 1;
+                continue;
+            };
+
+            let outcome = self
+                .send_batch(pending.transaction, &batch.transaction)
+                .await;
+            for (address, lamports) in pending.accounts {
+                let mut result =
+                    AccountCloseResult::new(&address, CloseStatus::Closed, Some(&batch.id));
+                match &outcome {
+                    Ok(signature) => {
+                        result.signature = Some(signature.clone());
+                        report.closed += 1;
+                        report.reclaimed_sol += lamports as f64 / LAMPORTS_PER_SOL;
+                    }
+                    Err(e) => {
+                        result.status = CloseStatus::Failed;
+                        result.error = Some(e.to_string());
+                        report.failed += 1;
+                    }
+                }
+                report.results.push(result);
+            }
+        }
+
+        report
+    }
+
+    async fn send_batch(
+        &self,
+        mut transaction: Transaction,
+        signed: &str,
+    ) -> Result<String, AccountCleanupError> {
+        let signed = decode_transaction(signed)
+            .map_err(|e| AccountCleanupError::Transaction(e.to_string()))?;
+        merge_signatures(&mut transaction, &signed)
+            .map_err(|e| AccountCleanupError::Transaction(e.to_string()))?;
+        if !missing_signers(&transaction).is_empty() {
+            return Err(AccountCleanupError::Transaction(
+                "Transaction is not fully signed".to_string(),
+            ));
+        }
+        let encoded = encode_transaction(&transaction)
+            .map_err(|e| AccountCleanupError::Transaction(e.to_string()))?;
+        let signature = self
+            .rpc(
+                "sendTransaction",
+                json!([encoded, { "encoding": "base64", "preflightCommitment": "confirmed" }]),
+            )
+            .await?;
+        signature
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| AccountCleanupError::Rpc("sendTransaction returned no signature".into()))
+    }
+
+    async fn mints_in_use(&self, wallet: &str) -> Result<MintsInUse, AccountCleanupError> {
+        let data_dir = self
+            .app
+            .app_data_root()
+            .map_err(|e| AccountCleanupError::State(e.to_string()))?;
+        let mut in_use = MintsInUse {
+            orders: crate::trading::active_order_mints(&data_dir, wallet)
+                .await
+                .map_err(AccountCleanupError::State)?,
+            dca: crate::bots::active_dca_mints(&data_dir, wallet)
+                .await
+                .map_err(AccountCleanupError::State)?,
+            alerts: HashSet::new(),
+        };
+        if let Some(alerts) = self.app.try_state::<SharedAlertManager>() {
+            let alerts = alerts
+                .read()
+                .await
+                .list_alerts()
+                .await
+                .map_err(|e| AccountCleanupError::State(e.to_string()))?;
+            in_use.alerts = alerts
+                .into_iter()
+                .filter(|alert| alert.state != AlertState::Disabled)
+                .map(|alert| alert.mint)
+                .collect();
+        }
+        Ok(in_use)
+    }
+
+    async fn token_accounts(
+        &self,
+        wallet: &str,
+    ) -> Result<Vec<OwnedTokenAccount>, AccountCleanupError> {
+        let mut accounts = Vec::new();
+        for program_id in [TOKEN_PROGRAM_ID, TOKEN_2022_PROGRAM_ID] {
+            let result = self
+                .rpc(
+                    "getTokenAccountsByOwner",
+                    json!([wallet, { "programId": program_id }, { "encoding": "jsonParsed" }]),
+                )
+                .await?;
+            accounts.extend(parse_token_accounts(&result));
+        }
+        Ok(accounts)
+    }
+
+    async fn latest_blockhash(&self) -> Result<Hash, AccountCleanupError> {
+        let result = self
+            .rpc("getLatestBlockhash", json!([{ "commitment": "confirmed" }]))
+            .await?;
+        result
+            .pointer("/value/blockhash")
+            .and_then(Value::as_str)
+            .and_then(|hash| Hash::from_str(hash).ok())
+            .ok_or_else(|| AccountCleanupError::Rpc("getLatestBlockhash returned no hash".into()))
+    }
+
+    async fn rpc(&self, method: &str, params: Value) -> Result<Value, AccountCleanupError> {
+        solana_rpc(&rpc_endpoint(&self.app), method, params)
+            .await
+            .map_err(AccountCleanupError::Rpc)
+    }
+}
+
+// Tauri commands
+#[tauri::command]
+pub async fn list_reclaimable_accounts(
+    wallet: String,
+    cleanup: State<'_, SharedAccountCleanup>,
+) -> Result<ReclaimableAccounts, String> {
+    cleanup.list(&wallet).await.map_err(|e| e.to_string())
+}
+
+/// Prepares close transactions; sign each batch with the wallet and pass it
+/// to `submit_token_account_closures`.
+#[tauri::command]
+pub async fn close_token_accounts(
+    wallet: String,
+    accounts: Vec<String>,
+    allow_in_use: Option<bool>,
+    cleanup: State<'_, SharedAccountCleanup>,
+) -> Result<PreparedAccountClosure, String> {
+    cleanup
+        .prepare(&wallet, &accounts, allow_in_use.unwrap_or(false))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn submit_token_account_closures(
+    wallet: String,
+    batches: Vec<SignedCloseBatch>,
+    cleanup: State<'_, SharedAccountCleanup>,
+) -> Result<AccountCloseReport, String> {
+    Ok(cleanup.submit(&wallet, batches).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BONK: &str = "DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263";
+    const USDC: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+    const WIF: &str = "EKpQGSJtjMFqKZ9KQanSqYXRcF8fBopzLHYxdM65zcjm";
+
+    fn account(mint: &str, amount: u64, frozen: bool) -> OwnedTokenAccount {
+        OwnedTokenAccount {
+            address: Pubkey::new_unique().to_string(),
+            mint: mint.to_string(),
+            program_id: TOKEN_PROGRAM_ID.to_string(),
+            lamports: 2_039_280,
+            amount,
+            frozen,
+        }
+    }
+
+    #[test]
+    fn parses_owner_accounts_and_skips_malformed_entries() {
+        let result = json!({
+            "value": [
+                {
+                    "pubkey": "acct1",
+                    "account": {
+                        "lamports": 2_039_280,
+                        "owner": TOKEN_PROGRAM_ID,
+                        "data": { "parsed": { "info": {
+                            "mint": BONK, "state": "frozen",
+                            "tokenAmount": { "amount": "0", "decimals": 5 },
+                        }}},
+                    },
+                },
+                { "pubkey": "acct2", "account": { "lamports": 1, "owner": TOKEN_PROGRAM_ID } },
+            ]
+        });
+        let accounts = parse_token_accounts(&result);
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(accounts[0].mint, BONK);
+        assert!(accounts[0].frozen);
+        assert_eq!(accounts[0].amount, 0);
+    }
+
+    #[test]
+    fn lists_empty_accounts_and_excludes_ones_still_in_use() {
+        let accounts = vec![
+            account(BONK, 0, false),
+            account(USDC, 0, false),
+            account(WIF, 0, true),
+            account("alerted", 0, false),
+            account("held", 5, false),
+        ];
+        let in_use = MintsInUse {
+            orders: HashSet::from([USDC.to_string()]),
+            alerts: HashSet::from(["alerted".to_string()]),
+            ..MintsInUse::default()
+        };
+
+        let report = reclaimable_accounts("wallet", &accounts, &in_use);
+        let reasons: Vec<CloseGuard> = report.excluded.iter().map(|a| a.reason).collect();
+        assert_eq!(reasons, [CloseGuard::OpenOrder, CloseGuard::Frozen]);
+        assert_eq!(report.accounts.len(), 2);
+        let alerted = report
+            .accounts
+            .iter()
+            .find(|a| a.mint == "alerted")
+            .unwrap();
+        assert_eq!(alerted.guard, Some(CloseGuard::PriceAlert));
+        assert!((report.reclaimable_sol - 0.00203928).abs() < 1e-12);
+    }
+
+    #[test]
+    fn refuses_in_use_mints_unless_overridden_but_never_frozen_or_funded() {
+        let in_use = MintsInUse {
+            dca: HashSet::from([BONK.to_string()]),
+            ..MintsInUse::default()
+        };
+        let dca = account(BONK, 0, false);
+        assert!(check_closable(Some(&dca), &in_use, false).is_err());
+        assert!(check_closable(Some(&dca), &in_use, true).is_ok());
+
+        let frozen = account(WIF, 0, true);
+        assert!(check_closable(Some(&frozen), &in_use, true).is_err());
+        let funded = account(USDC, 1, false);
+        assert!(check_closable(Some(&funded), &in_use, true).is_err());
+        assert!(check_closable(None, &in_use, true).is_err());
+    }
+
+    #[test]
+    fn batches_closes_within_the_packet_limit() {
+        let owner = Pubkey::new_unique();
+        let accounts: Vec<OwnedTokenAccount> = (0..45).map(|_| account(BONK, 0, false)).collect();
+        let refs: Vec<&OwnedTokenAccount> = accounts.iter().collect();
+
+        let transactions = close_transactions(&owner, &refs, Hash::new_unique()).unwrap();
+        let sizes: Vec<usize> = transactions
+            .iter()
+            .map(|tx| tx.message.instructions.len())
+            .collect();
+        assert_eq!(sizes, [20, 20, 5]);
+        for transaction in &transactions {
+            assert!(bincode::serialize(transaction).unwrap().len() <= 1232);
+            assert_eq!(transaction.message.account_keys[0], owner);
+            assert_eq!(missing_signers(transaction), [owner]);
+        }
+    }
+}
@@ -0,0 +1,108 @@
+//! Devnet faucet. Airdrops are refused on mainnet, where there is no faucet
+//! and a request could only fail.
+
+use crate::config::{active_environment, NetworkEnvironment};
+use crate::errors::AppError;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+
+const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+/// The public devnet faucet rejects larger single requests.
+const MAX_AIRDROP_SOL: f64 = 2.0;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AirdropResult {
+    pub address: String,
+    pub lamports: u64,
+    pub signature: String,
+    pub environment: NetworkEnvironment,
+}
+
+fn airdrop_lamports(amount_sol: f64) -> Result<u64, AppError> {
+    if !amount_sol.is_finite() || amount_sol <= 0.0 || amount_sol > MAX_AIRDROP_SOL {
+        return Err(AppError::validation(format!(
+            "Airdrops must be more than 0 and at most {} SOL",
+            MAX_AIRDROP_SOL
+        ))
+        .with_code("invalid_airdrop_amount"));
+    }
+    Ok((amount_sol * LAMPORTS_PER_SOL).round() as u64)
+}
+
+/// Requests test SOL for `address` from the active environment's faucet.
+#[tauri::command]
+pub async fn wallet_request_airdrop(
+    address: String,
+    amount_sol: f64,
+) -> Result<AirdropResult, AppError> {
+    let environment = active_environment();
+    if environment.is_mainnet() {
+        return Err(
+            AppError::validation("Airdrops are only available off mainnet")
+                .with_code("airdrop_unavailable"),
+        );
+    }
+    Pubkey::from_str(&address).map_err(|e| {
+        AppError::validation(format!("Invalid address: {}", e)).with_code("invalid_address")
+    })?;
+    let lamports = airdrop_lamports(amount_sol)?;
+
+    let payload = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "requestAirdrop",
+        "params": [address, lamports],
+    });
+    let body: Value = reqwest::Client::new()
+        .post(environment.rpc_url())
+        .json(&payload)
+        .send()
+        .await?
+        .json()
+        .await?;
+
+    if let Some(error) = body.get("error") {
+        let message = error["message"].as_str().unwrap_or("airdrop rejected");
+        return Err(AppError::upstream(
+            "solana_rpc",
+            format!("Faucet refused the airdrop: {}", message),
+            true,
+        )
+        .with_code("airdrop_failed"));
+    }
+    let signature = body["result"]
+        .as_str()
+        .ok_or_else(|| AppError::upstream("solana_rpc", "Faucet returned no signature", true))?
+        .to_string();
+
+    Ok(AirdropResult {
+        address,
+        lamports,
+        signature,
+        environment: environment.environment,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn airdrop_amount_is_capped() {
+        assert_eq!(airdrop_lamports(1.5).unwrap(), 1_500_000_000);
+        assert!(airdrop_lamports(0.0).is_err());
+        assert!(airdrop_lamports(2.5).is_err());
+        assert!(airdrop_lamports(f64::NAN).is_err());
+    }
+
+    #[tokio::test]
+    async fn mainnet_refuses_airdrops() {
+        let err = wallet_request_airdrop("11111111111111111111111111111111".to_string(), 1.0)
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), "airdrop_unavailable");
+    }
+}
@@ -0,0 +1,841 @@
+use crate::config::DataPaths;
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Listener, Manager, State};
+use tokio::sync::RwLock;
+
+use super::operations::TokenBalance;
+use crate::api_analytics::{send_metered, RequestPriority};
+use crate::api_config::{rpc_endpoint, solana_rpc};
+use crate::config::privacy_mode::{Masked, PrivacyMasked, SharedPrivacyMode};
+use crate::core::price_engine::get_price_engine;
+use crate::core::websocket_manager::WebSocketManager;
+use crate::websocket::activity::{ActivityKind, DecodedActivity, SOL_MINT};
+use crate::websocket::types::TransactionUpdate;
+
+const MINT_METADATA_FILE: &str = "mint_metadata.json";
+const JUPITER_TOKEN_URL: &str = "https://lite-api.jup.ag/tokens/v1/token";
+const TOKEN_PROGRAM_ID: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+const TOKEN_2022_PROGRAM_ID: &str = "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb";
+const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+/// Upper bound on keys per `getMultipleAccounts` call.
+const MULTIPLE_ACCOUNTS_LIMIT: usize = 100;
+/// Live views drift (fees and rent are not decoded as balance changes), so
+/// they are reconciled against chain state this often.
+const LIVE_RESYNC_SECS: i64 = 600;
+/// Wallets without a live subscription are refetched once their view is this old.
+const POLL_TTL_SECS: i64 = 60;
+pub const TOKEN_BALANCES_EVENT: &str = "token_balances_updated";
+
+#[derive(Debug, thiserror::Error)]
+pub enum BalanceServiceError {
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+    #[error("rpc error: {0}")]
+    Rpc(String),
+}
+
+/// Display metadata for a mint. Entries resolved from the token list are
+/// marked `resolved`; placeholders built from on-chain decimals are not and
+/// get looked up again.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MintMetadata {
+    pub mint: String,
+    pub symbol: String,
+    pub name: String,
+    pub decimals: u8,
+    pub logo_uri: Option<String>,
+    #[serde(default)]
+    pub resolved: bool,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl MintMetadata {
+    fn placeholder(mint: &str, decimals: u8) -> Self {
+        let short: String = mint.chars().take(4).collect();
+        Self {
+            mint: mint.to_string(),
+            symbol: short.clone(),
+            name: format!("Unknown token ({}…)", short),
+            decimals,
+            logo_uri: None,
+            resolved: false,
+            updated_at: Utc::now(),
+        }
+    }
+}
+
+fn builtin_metadata() -> Vec<MintMetadata> {
+    let logo = |mint: &str| {
+        Some(format!(
+            "https://raw.githubusercontent.com/solana-labs/token-list/main/assets/mainnet/{}/logo.png",
+            mint
+        ))
+    };
+    [
+        (SOL_MINT, "SOL", "Solana", 9),
+        (
+            "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+            "USDC",
+            "USD Coin",
+            6,
+        ),
+        (
+            "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB",
+            "USDT",
+            "USDT",
+            6,
+        ),
+    ]
+    .into_iter()
+    .map(|(mint, symbol, name, decimals)| MintMetadata {
+        mint: mint.to_string(),
+        symbol: symbol.to_string(),
+        name: name.to_string(),
+        decimals,
+        logo_uri: logo(mint),
+        resolved: true,
+        updated_at: Utc::now(),
+    })
+    .collect()
+}
+
+/// Local cache of mint symbol, name, decimals and logo, persisted as JSON so
+/// balances render without a token-list round trip.
+pub struct MintMetadataCache {
+    path: Option<PathBuf>,
+    entries: HashMap<String, MintMetadata>,
+}
+
+impl MintMetadataCache {
+    pub fn load(path: PathBuf) -> Result<Self, BalanceServiceError> {
+        let mut cache = Self {
+            path: None,
+            entries: HashMap::new(),
+        };
+        if path.exists() {
+            let records: Vec<MintMetadata> = serde_json::from_str(&fs::read_to_string(&path)?)?;
+            cache.entries = records
+                .into_iter()
+                .map(|record| (record.mint.clone(), record))
+                .collect();
+        }
+        for builtin in builtin_metadata() {
+            cache.entries.entry(builtin.mint.clone()).or_insert(builtin);
+        }
+        cache.path = Some(path);
+        Ok(cache)
+    }
+
+    /// A cache that is never written to disk.
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            entries: builtin_metadata()
+                .into_iter()
+                .map(|record| (record.mint.clone(), record))
+                .collect(),
+        }
+    }
+
+    pub fn get(&self, mint: &str) -> Option<&MintMetadata> {
+        self.entries.get(mint)
+    }
+
+    pub fn insert(&mut self, metadata: MintMetadata) -> Result<(), BalanceServiceError> {
+        self.entries.insert(metadata.mint.clone(), metadata);
+        if let Some(path) = &self.path {
+            let records: BTreeMap<&String, &MintMetadata> = self.entries.iter().collect();
+            let records: Vec<&MintMetadata> = records.into_values().collect();
+            let tmp = path.with_extension("json.tmp");
+            fs::write(&tmp, serde_json::to_vec_pretty(&records)?)?;
+            fs::rename(&tmp, path)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Holding {
+    amount: f64,
+    decimals: Option<u8>,
+}
+
+/// What is known about one wallet: native SOL, token totals per mint and the
+/// token accounts behind them, which is what batched refreshes read back.
+#[derive(Debug, Clone, Default)]
+struct WalletHoldings {
+    sol: f64,
+    tokens: HashMap<String, Holding>,
+    accounts: HashMap<String, String>,
+    fetched_at: Option<DateTime<Utc>>,
+    updated_at: Option<DateTime<Utc>>,
+    live: bool,
+    /// Set when a live update could not be applied exactly; the next read
+    /// reloads the wallet with `getTokenAccountsByOwner`.
+    needs_resync: bool,
+}
+
+impl WalletHoldings {
+    fn from_accounts(sol: f64, accounts: Vec<ParsedTokenAccount>, now: DateTime<Utc>) -> Self {
+        let mut holdings = Self {
+            sol,
+            fetched_at: Some(now),
+            updated_at: Some(now),
+            ..Self::default()
+        };
+        holdings.replace_accounts(accounts);
+        holdings
+    }
+
+    fn replace_accounts(&mut self, accounts: Vec<ParsedTokenAccount>) {
+        self.tokens.clear();
+        self.accounts.clear();
+        for account in accounts {
+            let holding = self.tokens.entry(account.mint.clone()).or_insert(Holding {
+                amount: 0.0,
+                decimals: Some(account.decimals),
+            });
+            holding.amount += account.amount;
+            self.accounts.insert(account.pubkey, account.mint);
+        }
+    }
+
+    /// Applies the balance legs of a decoded transaction. Returns whether
+    /// anything changed.
+    fn apply_activity(&mut self, activity: &DecodedActivity, now: DateTime<Utc>) -> bool {
+        if activity.token_in.is_none() && activity.token_out.is_none() {
+            if activity.kind == ActivityKind::Unknown {
+                self.needs_resync = true;
+            }
+            return false;
+        }
+        for (leg, sign) in [(&activity.token_in, 1.0), (&activity.token_out, -1.0)] {
+            if let Some(leg) = leg {
+                self.apply_delta(&leg.mint, sign * leg.amount);
+            }
+        }
+        self.updated_at = Some(now);
+        true
+    }
+
+    fn apply_delta(&mut self, mint: &str, delta: f64) {
+        if mint == SOL_MINT {
+            self.sol = (self.sol + delta).max(0.0);
+            return;
+        }
+        match self.tokens.get_mut(mint) {
+            Some(holding) => holding.amount = (holding.amount + delta).max(0.0),
+            None => {
+                // A token account we have not seen yet: show the amount now
+                // and pick the account up on the next reload.
+                self.tokens.insert(
+                    mint.to_string(),
+                    Holding {
+                        amount: delta.max(0.0),
+                        decimals: None,
+                    },
+                );
+                self.needs_resync = true;
+            }
+        }
+    }
+
+    fn is_due(&self, now: DateTime<Utc>) -> bool {
+        let ttl = if self.live {
+            LIVE_RESYNC_SECS
+        } else {
+            POLL_TTL_SECS
+        };
+        match self.fetched_at {
+            Some(at) => (now - at).num_seconds() > ttl,
+            None => true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct ParsedTokenAccount {
+    pubkey: String,
+    mint: String,
+    amount: f64,
+    decimals: u8,
+}
+
+/// Reads mint and UI amount from a `jsonParsed` SPL token account.
+fn parse_token_account(pubkey: &str, account: &Value) -> Option<ParsedTokenAccount> {
+    let info = account.pointer("/data/parsed/info")?;
+    let token_amount = info.get("tokenAmount")?;
+    let decimals = token_amount.get("decimals")?.as_u64()? as u8;
+    let amount = match token_amount.get("uiAmountString").and_then(Value::as_str) {
+        Some(ui) => ui.parse().ok()?,
+        None => {
+            let raw: f64 = token_amount.get("amount")?.as_str()?.parse().ok()?;
+            raw / 10f64.powi(decimals as i32)
+        }
+    };
+    Some(ParsedTokenAccount {
+        pubkey: pubkey.to_string(),
+        mint: info.get("mint")?.as_str()?.to_string(),
+        amount,
+        decimals,
+    })
+}
+
+/// Token balances of one wallet as currently held by the balance service.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenBalancesView {
+    pub address: String,
+    pub balances: Vec<TokenBalance>,
+    /// Last full read from chain.
+    pub fetched_at: DateTime<Utc>,
+    /// Last change, from a read or a live account update.
+    pub as_of: DateTime<Utc>,
+    pub stale_seconds: i64,
+    /// Whether account updates are being applied as they arrive.
+    pub live: bool,
+}
+
+impl PrivacyMasked for TokenBalancesView {
+    const MASKED_FIELDS: &'static [&'static str] = &["balance", "usdValue"];
+    const WALLET_KEY: Option<&'static str> = Some("address");
+
+    fn wallets(&self) -> Vec<String> {
+        vec![self.address.clone()]
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenBalancesUpdated {
+    pub address: String,
+    pub as_of: DateTime<Utc>,
+}
+
+/// Keeps token balances for active wallets. A wallet is read once with
+/// `getTokenAccountsByOwner`, subscribed on the account stream and then kept
+/// current from decoded transactions; periodic reconciliation of several
+/// wallets goes through batched `getMultipleAccounts` calls.
+pub struct TokenBalanceService {
+    app: AppHandle,
+    client: reqwest::Client,
+    wallets: RwLock<HashMap<String, WalletHoldings>>,
+    metadata: Mutex<MintMetadataCache>,
+    resolving: Mutex<HashSet<String>>,
+}
+
+pub type SharedTokenBalanceService = Arc<TokenBalanceService>;
+
+impl TokenBalanceService {
+    pub fn new(app: &AppHandle) -> Result<Self, BalanceServiceError> {
+        let mut path = app.app_data_root().map_err(|e| {
+            BalanceServiceError::Io(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("App data directory not found: {}", e),
+            ))
+        })?;
+        fs::create_dir_all(&path)?;
+        path.push(MINT_METADATA_FILE);
+        let metadata = MintMetadataCache::load(path).unwrap_or_else(|e| {
+            eprintln!("Failed to load mint metadata cache, starting empty: {}", e);
+            MintMetadataCache::in_memory()
+        });
+
+        Ok(Self {
+            app: app.clone(),
+            client: reqwest::Client::new(),
+            wallets: RwLock::new(HashMap::new()),
+            metadata: Mutex::new(metadata),
+            resolving: Mutex::new(HashSet::new()),
+        })
+    }
+
+    /// Applies decoded transactions of subscribed wallets as they arrive.
+    pub fn start_listener(self: &Arc<Self>) {
+        let service = self.clone();
+        self.app.listen("transaction_update", move |event| {
+            let Ok(update) = serde_json::from_str::<TransactionUpdate>(event.payload()) else {
+                return;
+            };
+            let Some(activity) = update.activity else {
+                return;
+            };
+            let service = service.clone();
+            tauri::async_runtime::spawn(async move {
+                service.apply_activity(&activity).await;
+            });
+        });
+    }
+
+    async fn apply_activity(&self, activity: &DecodedActivity) {
+        let now = Utc::now();
+        let changed = {
+            let mut wallets = self.wallets.write().await;
+            match wallets.get_mut(&activity.wallet) {
+                Some(holdings) => holdings.apply_activity(activity, now),
+                None => return,
+            }
+        };
+        if changed {
+            let payload = TokenBalancesUpdated {
+                address: activity.wallet.clone(),
+                as_of: now,
+            };
+            if let Err(e) = self.app.emit(TOKEN_BALANCES_EVENT, payload) {
+                eprintln!("Failed to emit token balance update: {}", e);
+            }
+        }
+    }
+
+    /// Balances for one wallet, loading and subscribing it on first use.
+    pub async fn balances(
+        self: &Arc<Self>,
+        address: &str,
+        force_refresh: bool,
+    ) -> Result<TokenBalancesView, BalanceServiceError> {
+        let mut views = self
+            .balances_for(&[address.to_string()], force_refresh)
+            .await?;
+        Ok(views.remove(0))
+    }
+
+    /// Balances for several wallets. Wallets seen for the first time (or
+    /// flagged for resync) are loaded individually; the rest are reconciled
+    /// together with batched `getMultipleAccounts` calls.
+    pub async fn balances_for(
+        self: &Arc<Self>,
+        addresses: &[String],
+        force_refresh: bool,
+    ) -> Result<Vec<TokenBalancesView>, BalanceServiceError> {
+        let now = Utc::now();
+        let (to_load, to_refresh) = {
+            let wallets = self.wallets.read().await;
+            let mut to_load = Vec::new();
+            let mut to_refresh = Vec::new();
+            for address in addresses {
+                match wallets.get(address) {
+                    None => to_load.push(address.clone()),
+                    Some(holdings) if holdings.needs_resync => to_load.push(address.clone()),
+                    Some(holdings) if force_refresh || holdings.is_due(now) => {
+                        to_refresh.push(address.clone())
+                    }
+                    Some(_) => {}
+                }
+            }
+            (to_load, to_refresh)
+        };
+
+        for address in &to_load {
+            self.load_wallet(address).await?;
+        }
+        if !to_refresh.is_empty() {
+            self.refresh_batch(&to_refresh).await?;
+        }
+        if !to_load.is_empty() {
+            self.subscribe(&to_load).await;
+        }
+
+        let wallets = self.wallets.read().await;
+        let mut unknown = Vec::new();
+        let views = addresses
+            .iter()
+            .map(|address| {
+                let holdings = wallets.get(address).cloned().unwrap_or_default();
+                self.build_view(address, &holdings, &mut unknown)
+            })
+            .collect();
+        drop(wallets);
+
+        self.resolve_metadata(unknown);
+        Ok(views)
+    }
+
+    async fn rpc(&self, method: &str, params: Value) -> Result<Value, BalanceServiceError> {
+        solana_rpc(&rpc_endpoint(&self.app), method, params)
+            .await
+            .map_err(BalanceServiceError::Rpc)
+    }
+
+    async fn load_wallet(&self, address: &str) -> Result<(), BalanceServiceError> {
+        let lamports = self
+            .rpc("getBalance", json!([address]))
+            .await?
+            .get("value")
+            .and_then(Value::as_u64)
+            .unwrap_or(0);
+
+        let mut accounts = Vec::new();
+        for program in [TOKEN_PROGRAM_ID, TOKEN_2022_PROGRAM_ID] {
+            let result = self
+                .rpc(
+                    "getTokenAccountsByOwner",
+                    json!([address, { "programId": program }, { "encoding": "jsonParsed" }]),
+                )
+                .await?;
+            let entries = result
+                .get("value")
+                .and_then(Value::as_array)
+                .cloned()
+                .unwrap_or_default();
+            accounts.extend(entries.iter().filter_map(|entry| {
+                parse_token_account(entry.get("pubkey")?.as_str()?, entry.get("account")?)
+            }));
+        }
+
+        let mut holdings =
+            WalletHoldings::from_accounts(lamports as f64 / LAMPORTS_PER_SOL, accounts, Utc::now());
+        let mut wallets = self.wallets.write().await;
+        holdings.live = wallets.get(address).is_some_and(|previous| previous.live);
+        wallets.insert(address.to_string(), holdings);
+        Ok(())
+    }
+
+    /// Re-reads the owner and known token accounts of `addresses` in chunks
+    /// of [`MULTIPLE_ACCOUNTS_LIMIT`] keys.
+    async fn refresh_batch(&self, addresses: &[String]) -> Result<(), BalanceServiceError> {
+        let keys: Vec<String> = {
+            let wallets = self.wallets.read().await;
+            addresses
+                .iter()
+                .flat_map(|address| {
+                    let accounts = wallets
+                        .get(address)
+                        .map(|holdings| holdings.accounts.keys().cloned().collect::<Vec<_>>())
+                        .unwrap_or_default();
+                    std::iter::once(address.clone()).chain(accounts)
+                })
+                .collect()
+        };
+
+        let mut fetched: HashMap<String, Value> = HashMap::new();
+        for chunk in keys.chunks(MULTIPLE_ACCOUNTS_LIMIT) {
+            let result = self
+                .rpc(
+                    "getMultipleAccounts",
+                    json!([chunk, { "encoding": "jsonParsed" }]),
+                )
+                .await?;
+            let values = result
+                .get("value")
+                .and_then(Value::as_array)
+                .cloned()
+                .unwrap_or_default();
+            for (key, value) in chunk.iter().zip(values) {
+                fetched.insert(key.clone(), value);
+            }
+        }
+
+        let now = Utc::now();
+        let mut wallets = self.wallets.write().await;
+        for address in addresses {
+            let Some(holdings) = wallets.get_mut(address) else {
+                continue;
+            };
+            holdings.sol = fetched
+                .get(address)
+                .and_then(|account| account.get("lamports"))
+                .and_then(Value::as_u64)
+                .unwrap_or(0) as f64
+                / LAMPORTS_PER_SOL;
+            // Closed accounts come back as null and drop out here.
+            let accounts = holdings
+                .accounts
+                .keys()
+                .filter_map(|pubkey| parse_token_account(pubkey, fetched.get(pubkey)?))
+                .collect();
+            holdings.replace_accounts(accounts);
+            holdings.fetched_at = Some(now);
+            holdings.updated_at = Some(now);
+        }
+        Ok(())
+    }
+
+    async fn subscribe(&self, addresses: &[String]) {
+        let Some(ws) = self.app.try_state::<WebSocketManager>() else {
+            return;
+        };
+        let subscribed = match ws.subscribe_wallets(addresses.to_vec()).await {
+            Ok(()) => true,
+            Err(e) => {
+                eprintln!("Balance updates fall back to polling: {}", e);
+                false
+            }
+        };
+        let mut wallets = self.wallets.write().await;
+        for address in addresses {
+            if let Some(holdings) = wallets.get_mut(address) {
+                holdings.live = subscribed;
+            }
+        }
+    }
+
+    fn build_view(
+        &self,
+        address: &str,
+        holdings: &WalletHoldings,
+        unknown: &mut Vec<String>,
+    ) -> TokenBalancesView {
+        let now = Utc::now();
+        let fetched_at = holdings.fetched_at.unwrap_or(now);
+        let as_of = holdings.updated_at.unwrap_or(fetched_at);
+        let metadata = self.metadata.lock();
+        let engine = get_price_engine();
+
+        let native = (
+            SOL_MINT.to_string(),
+            Holding {
+                amount: holdings.sol,
+                decimals: Some(9),
+            },
+        );
+        let mut balances: Vec<TokenBalance> = std::iter::once(native)
+            .chain(holdings.tokens.clone())
+            .filter(|(mint, holding)| mint == SOL_MINT || holding.amount > 0.0)
+            .map(|(mint, holding)| {
+                let meta = match metadata.get(&mint) {
+                    Some(meta) => {
+                        if !meta.resolved {
+                            unknown.push(mint.clone());
+                        }
+                        meta.clone()
+                    }
+                    None => {
+                        unknown.push(mint.clone());
+                        MintMetadata::placeholder(&mint, holding.decimals.unwrap_or(0))
+                    }
+                };
+                let cached = engine
+                    .get_cached_price(&mint)
+                    .or_else(|| engine.get_cached_price(&meta.symbol));
+                TokenBalance {
+                    symbol: meta.symbol,
+                    name: meta.name,
+                    balance: holding.amount,
+                    decimals: holding.decimals.unwrap_or(meta.decimals),
+                    usd_value: cached.as_ref().map_or(0.0, |p| p.price * holding.amount),
+                    change_24h: cached.as_ref().map_or(0.0, |p| p.change_24h),
+                    logo_uri: meta.logo_uri,
+                    last_updated: as_of,
+                    mint,
+                }
+            })
+            .collect();
+        balances.sort_by(|a, b| b.usd_value.total_cmp(&a.usd_value));
+
+        TokenBalancesView {
+            address: address.to_string(),
+            balances,
+            fetched_at,
+            as_of,
+            stale_seconds: (now - as_of).num_seconds().max(0),
+            live: holdings.live,
+        }
+    }
+
+    /// Looks up unknown mints in the background and backfills the cache.
+    fn resolve_metadata(self: &Arc<Self>, mints: Vec<String>) {
+        let pending: Vec<String> = {
+            let mut resolving = self.resolving.lock();
+            mints
+                .into_iter()
+                .filter(|mint| resolving.insert(mint.clone()))
+                .collect()
+        };
+        if pending.is_empty() {
+            return;
+        }
+
+        let service = self.clone();
+        tauri::async_runtime::spawn(async move {
+            for mint in pending {
+                match service.fetch_metadata(&mint).await {
+                    Ok(metadata) => {
+                        if let Err(e) = service.metadata.lock().insert(metadata) {
+                            eprintln!("Failed to save mint metadata for {}: {}", mint, e);
+                        }
+                    }
+                    Err(e) => eprintln!("Failed to resolve mint metadata for {}: {}", mint, e),
+                }
+                service.resolving.lock().remove(&mint);
+            }
+        });
+    }
+
+    /// Decimals of `mint`, from the cache or the token list. Placeholder
+    /// entries carry on-chain decimals, so they answer this too.
+    pub async fn mint_decimals(&self, mint: &str) -> Result<u8, BalanceServiceError> {
+        if let Some(metadata) = self.metadata.lock().get(mint) {
+            return Ok(metadata.decimals);
+        }
+        let metadata = self.fetch_metadata(mint).await?;
+        let decimals = metadata.decimals;
+        if let Err(e) = self.metadata.lock().insert(metadata) {
+            eprintln!("Failed to save mint metadata for {}: {}", mint, e);
+        }
+        Ok(decimals)
+    }
+
+    async fn fetch_metadata(&self, mint: &str) -> Result<MintMetadata, BalanceServiceError> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct JupiterToken {
+            symbol: String,
+            name: String,
+            decimals: u8,
+            #[serde(rename = "logoURI")]
+            logo_uri: Option<String>,
+        }
+
+        let request = self.client.get(format!("{}/{}", JUPITER_TOKEN_URL, mint));
+        let token: JupiterToken = send_metered("jupiter", RequestPriority::Standard, request)
+            .await
+            .map_err(|e| BalanceServiceError::Rpc(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| BalanceServiceError::Rpc(e.to_string()))?;
+
+        Ok(MintMetadata {
+            mint: mint.to_string(),
+            symbol: token.symbol,
+            name: token.name,
+            decimals: token.decimals,
+            logo_uri: token.logo_uri,
+            resolved: true,
+            updated_at: Utc::now(),
+        })
+    }
+}
+
+// Tauri commands
+#[tauri::command]
+pub async fn wallet_get_token_balances_batch(
+    addresses: Vec<String>,
+    force_refresh: bool,
+    service: State<'_, SharedTokenBalanceService>,
+    privacy: State<'_, SharedPrivacyMode>,
+) -> Result<Masked<Vec<TokenBalancesView>>, String> {
+    let views = service
+        .balances_for(&addresses, force_refresh)
+        .await
+        .map_err(|e| e.to_string())?;
+    let privacy = privacy.read().map_err(|e| e.to_string())?;
+    Ok(privacy.apply(views))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::websocket::activity::TokenAmount;
+
+    const BONK: &str = "DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263";
+
+    fn token_account(mint: &str, amount: &str, decimals: u64) -> Value {
+        json!({
+            "lamports": 2039280,
+            "data": {
+                "program": "spl-token",
+                "parsed": {
+                    "type": "account",
+                    "info": {
+                        "mint": mint,
+                        "owner": "owner",
+                        "tokenAmount": { "amount": amount, "decimals": decimals }
+                    }
+                }
+            }
+        })
+    }
+
+    fn activity(
+        kind: ActivityKind,
+        token_in: Option<(&str, f64)>,
+        token_out: Option<(&str, f64)>,
+    ) -> DecodedActivity {
+        let leg = |(mint, amount): (&str, f64)| TokenAmount {
+            mint: mint.to_string(),
+            symbol: None,
+            amount,
+        };
+        DecodedActivity {
+            signature: "sig".into(),
+            wallet: "wallet".into(),
+            kind,
+            slot: 1,
+            timestamp: 0,
+            token_in: token_in.map(leg),
+            token_out: token_out.map(leg),
+            counterparty: None,
+            program_id: None,
+            program_name: None,
+            nft_mint: None,
+            description: None,
+        }
+    }
+
+    #[test]
+    fn parses_raw_amount_with_decimals() {
+        let parsed = parse_token_account("acct", &token_account(BONK, "1234500000", 5)).unwrap();
+        assert_eq!(parsed.mint, BONK);
+        assert_eq!(parsed.decimals, 5);
+        assert!((parsed.amount - 12_345.0).abs() < 1e-9);
+        assert!(parse_token_account("acct", &json!({ "data": ["", "base64"] })).is_none());
+    }
+
+    #[test]
+    fn accounts_of_the_same_mint_are_summed() {
+        let accounts = vec![
+            parse_token_account("a", &token_account(BONK, "100000", 5)).unwrap(),
+            parse_token_account("b", &token_account(BONK, "50000", 5)).unwrap(),
+        ];
+        let holdings = WalletHoldings::from_accounts(1.0, accounts, Utc::now());
+        assert_eq!(holdings.accounts.len(), 2);
+        assert!((holdings.tokens[BONK].amount - 1.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn swap_moves_both_legs() {
+        let accounts = vec![parse_token_account("a", &token_account(BONK, "100000", 5)).unwrap()];
+        let mut holdings = WalletHoldings::from_accounts(2.0, accounts, Utc::now());
+
+        let swap = activity(ActivityKind::Swap, Some((BONK, 3.0)), Some((SOL_MINT, 0.5)));
+        assert!(holdings.apply_activity(&swap, Utc::now()));
+        assert!((holdings.sol - 1.5).abs() < 1e-9);
+        assert!((holdings.tokens[BONK].amount - 4.0).abs() < 1e-9);
+        assert!(!holdings.needs_resync);
+    }
+
+    #[test]
+    fn unseen_mint_or_undecoded_activity_requests_resync() {
+        let mut holdings = WalletHoldings::from_accounts(1.0, Vec::new(), Utc::now());
+        let transfer = activity(ActivityKind::TransferIn, Some((BONK, 10.0)), None);
+        assert!(holdings.apply_activity(&transfer, Utc::now()));
+        assert_eq!(holdings.tokens[BONK].decimals, None);
+        assert!(holdings.needs_resync);
+
+        let mut holdings = WalletHoldings::from_accounts(1.0, Vec::new(), Utc::now());
+        assert!(!holdings.apply_activity(&activity(ActivityKind::Unknown, None, None), Utc::now()));
+        assert!(holdings.needs_resync);
+    }
+
+    #[test]
+    fn unsubscribed_wallets_expire_sooner() {
+        let now = Utc::now();
+        let mut holdings = WalletHoldings {
+            fetched_at: Some(now - chrono::Duration::seconds(POLL_TTL_SECS + 1)),
+            ..WalletHoldings::default()
+        };
+        assert!(holdings.is_due(now));
+        holdings.live = true;
+        assert!(!holdings.is_due(now));
+    }
+}
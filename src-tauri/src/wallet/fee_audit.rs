@@ -0,0 +1,736 @@
+//! Fee and rent audit for a wallet's on-chain activity.
+//!
+//! Every transaction the wallet paid for in the window is split into base
+//! fees, priority fees, Jupiter platform fees and rent. Rent counts the
+//! lamports the wallet spent creating accounts, net of what it got back when
+//! closing token accounts. Whatever can't be split that way lands in the
+//! `unattributed` bucket instead of being dropped. SOL amounts are valued at
+//! the current SOL price, so older months are approximate in USD.
+
+use chrono::{DateTime, Duration, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use tauri::{AppHandle, Manager, State};
+
+use super::performance::SharedPerformanceDatabase;
+use crate::api_config::{rpc_endpoint, solana_rpc};
+use crate::config::privacy_mode::{Masked, PrivacyMasked, SharedPrivacyMode};
+use crate::core::price_engine::get_price_engine;
+use crate::websocket::activity::{
+    decode_transaction, known_program, ActivityKind, ProgramCategory, SOL_MINT,
+};
+
+const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+/// Base fee charged per signature.
+const LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+const SYSTEM_PROGRAM: &str = "11111111111111111111111111111111";
+const JUPITER_PROGRAM: &str = "JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4";
+/// Signatures requested per `getSignaturesForAddress` page.
+const SIGNATURE_PAGE_LIMIT: usize = 1_000;
+/// Upper bound on transactions fetched for a single audit.
+const MAX_AUDITED_TRANSACTIONS: usize = 2_000;
+/// Decoded transactions kept between audits; they never change once final.
+const MAX_CACHED_TRANSACTIONS: usize = 10_000;
+const DEFAULT_WINDOW_DAYS: u32 = 30;
+const MAX_WINDOW_DAYS: u32 = 365;
+
+#[derive(Debug, thiserror::Error)]
+pub enum FeeAuditError {
+    #[error("rpc error: {0}")]
+    Rpc(String),
+    #[error("state error: {0}")]
+    State(String),
+}
+
+/// What one transaction cost the wallet.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionFees {
+    pub signature: String,
+    pub timestamp: i64,
+    /// Slot the transaction was confirmed in.
+    pub slot: Option<u64>,
+    /// Program the transaction was for, ignoring compute budget instructions.
+    pub program_id: Option<String>,
+    pub is_trade: bool,
+    pub base_fee_lamports: u64,
+    pub priority_fee_lamports: u64,
+    pub rent_paid_lamports: u64,
+    pub rent_reclaimed_lamports: u64,
+    pub platform_fee_usd: f64,
+    /// Fees known to be paid that couldn't be put in a category.
+    pub unattributed_lamports: u64,
+    /// Whether any part of the transaction couldn't be decoded.
+    pub unattributed: bool,
+}
+
+impl TransactionFees {
+    fn native_lamports(&self) -> i64 {
+        (self.base_fee_lamports
+            + self.priority_fee_lamports
+            + self.rent_paid_lamports
+            + self.unattributed_lamports) as i64
+            - self.rent_reclaimed_lamports as i64
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeTotals {
+    pub transactions: usize,
+    pub base_fees_sol: f64,
+    pub priority_fees_sol: f64,
+    pub platform_fees_usd: f64,
+    pub rent_paid_sol: f64,
+    pub rent_reclaimed_sol: f64,
+    pub net_rent_sol: f64,
+    pub unattributed_sol: f64,
+    /// Transactions with at least one part that couldn't be decoded.
+    pub unattributed_transactions: usize,
+    pub total_usd: f64,
+}
+
+impl FeeTotals {
+    fn add(&mut self, fees: &TransactionFees, sol_usd: f64) {
+        let sol = |lamports: u64| lamports as f64 / LAMPORTS_PER_SOL;
+        self.transactions += 1;
+        self.base_fees_sol += sol(fees.base_fee_lamports);
+        self.priority_fees_sol += sol(fees.priority_fee_lamports);
+        self.platform_fees_usd += fees.platform_fee_usd;
+        self.rent_paid_sol += sol(fees.rent_paid_lamports);
+        self.rent_reclaimed_sol += sol(fees.rent_reclaimed_lamports);
+        self.net_rent_sol = self.rent_paid_sol - self.rent_reclaimed_sol;
+        self.unattributed_sol += sol(fees.unattributed_lamports);
+        if fees.unattributed {
+            self.unattributed_transactions += 1;
+        }
+        self.total_usd +=
+            fees.native_lamports() as f64 / LAMPORTS_PER_SOL * sol_usd + fees.platform_fee_usd;
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ProgramFees {
+    /// `None` for transactions whose program couldn't be determined.
+    pub program_id: Option<String>,
+    pub program_name: String,
+    #[serde(flatten)]
+    pub fees: FeeTotals,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MonthlyFees {
+    /// `YYYY-MM`, UTC.
+    pub month: String,
+    #[serde(flatten)]
+    pub fees: FeeTotals,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeAuditReport {
+    pub wallet: String,
+    pub since: DateTime<Utc>,
+    pub until: DateTime<Utc>,
+    pub sol_price_usd: f64,
+    pub totals: FeeTotals,
+    /// Sorted by total cost, highest first.
+    pub by_program: Vec<ProgramFees>,
+    /// Oldest month first.
+    pub monthly: Vec<MonthlyFees>,
+    /// Swaps found in the window.
+    pub trades: usize,
+    pub fee_per_trade_usd: Option<f64>,
+    /// Realized P&L recorded in the performance database over the window.
+    pub realized_pnl: Option<f64>,
+    /// Fees relative to the absolute realized P&L, in percent.
+    pub fees_pct_of_pnl: Option<f64>,
+    /// Set when the window held more transactions than one audit fetches.
+    pub truncated: bool,
+    pub generated_at: DateTime<Utc>,
+}
+
+impl PrivacyMasked for FeeAuditReport {
+    const MASKED_FIELDS: &'static [&'static str] = &[
+        "totalUsd",
+        "platformFeesUsd",
+        "feePerTradeUsd",
+        "realizedPnl",
+        "feesPctOfPnl",
+    ];
+    const WALLET_KEY: Option<&'static str> = Some("wallet");
+}
+
+fn account_keys(tx: &Value) -> Vec<String> {
+    let mut keys: Vec<String> = tx
+        .pointer("/transaction/message/accountKeys")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|key| {
+            key.get("pubkey")
+                .and_then(Value::as_str)
+                .or_else(|| key.as_str())
+                .map(str::to_string)
+        })
+        .collect();
+    // Lookup-table accounts of v0 transactions follow the static keys.
+    for table in ["writable", "readonly"] {
+        keys.extend(
+            tx.pointer(&format!("/meta/loadedAddresses/{table}"))
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .map(str::to_string),
+        );
+    }
+    keys
+}
+
+/// Outer instructions followed by every inner instruction.
+fn all_instructions(tx: &Value) -> Vec<&Value> {
+    let outer = tx
+        .pointer("/transaction/message/instructions")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten();
+    let inner = tx
+        .pointer("/meta/innerInstructions")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|group| group.get("instructions").and_then(Value::as_array))
+        .flatten();
+    outer.chain(inner).collect()
+}
+
+fn lamports_at(tx: &Value, balances: &str, keys: &[String], account: &str) -> Option<u64> {
+    let index = keys.iter().position(|key| key == account)?;
+    tx.pointer(&format!("/meta/{balances}/{index}"))
+        .and_then(Value::as_u64)
+}
+
+/// Platform fee in basis points from a Jupiter v6 route instruction. Every
+/// route variant ends its arguments with the fee as a single byte.
+fn jupiter_platform_fee_bps(data: &str) -> Option<u8> {
+    let bytes = bs58::decode(data).into_vec().ok()?;
+    // 8-byte discriminator, then at least the route plan and amounts.
+    if bytes.len() <= 8 {
+        return None;
+    }
+    bytes.last().copied()
+}
+
+/// Splits what a jsonParsed `getTransaction` result cost `wallet`. `price`
+/// returns a token's USD price by mint.
+pub fn audit_transaction(
+    tx: &Value,
+    wallet: &str,
+    price: impl Fn(&str) -> Option<f64>,
+) -> TransactionFees {
+    let keys = account_keys(tx);
+    let activity = decode_transaction(tx, wallet);
+    let mut fees = TransactionFees {
+        signature: activity.signature.clone(),
+        timestamp: activity.timestamp,
+        slot: tx.get("slot").and_then(Value::as_u64),
+        is_trade: activity.kind == ActivityKind::Swap,
+        ..TransactionFees::default()
+    };
+
+    let outer: Vec<&Value> = tx
+        .pointer("/transaction/message/instructions")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .collect();
+    let program_of = |ix: &Value| ix.get("programId").and_then(Value::as_str);
+    let programs: Vec<&str> = outer
+        .iter()
+        .filter_map(|ix| program_of(ix))
+        .filter(|id| !matches!(known_program(id), Some(("Compute Budget", _))))
+        .collect();
+    fees.program_id = programs
+        .iter()
+        .find(|id| !matches!(known_program(id), Some((_, ProgramCategory::Core))))
+        .or_else(|| programs.first())
+        .map(|id| id.to_string());
+
+    // Only the fee payer is charged the transaction fee.
+    if keys.first().map(String::as_str) == Some(wallet) {
+        let signatures = tx
+            .pointer("/transaction/signatures")
+            .and_then(Value::as_array)
+            .map(Vec::len)
+            .unwrap_or_default() as u64;
+        match tx.pointer("/meta/fee").and_then(Value::as_u64) {
+            Some(fee) if signatures > 0 => {
+                fees.base_fee_lamports = (signatures * LAMPORTS_PER_SIGNATURE).min(fee);
+                fees.priority_fee_lamports = fee - fees.base_fee_lamports;
+            }
+            Some(fee) => {
+                fees.unattributed_lamports = fee;
+                fees.unattributed = true;
+            }
+            None => fees.unattributed = true,
+        }
+    }
+
+    // A failed transaction still pays its fee, but none of its instructions
+    // took effect.
+    if tx.pointer("/meta/err").is_some_and(|err| !err.is_null()) {
+        return fees;
+    }
+
+    let mut created: HashMap<String, u64> = HashMap::new();
+    for ix in all_instructions(tx) {
+        let Some(parsed) = ix.get("parsed") else {
+            continue;
+        };
+        let kind = parsed
+            .get("type")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let info = parsed.get("info").cloned().unwrap_or(Value::Null);
+        let field = |key: &str| info.get(key).and_then(Value::as_str);
+        match (program_of(ix), kind) {
+            (Some(SYSTEM_PROGRAM), "createAccount" | "createAccountWithSeed")
+                if field("source") == Some(wallet) =>
+            {
+                if let (Some(account), Some(lamports)) = (
+                    field("newAccount"),
+                    info.get("lamports").and_then(Value::as_u64),
+                ) {
+                    created.entry(account.to_string()).or_insert(lamports);
+                }
+            }
+            // Newer associated token program versions fund the account with a
+            // plain transfer, so read the rent off the balances instead.
+            (_, "create" | "createIdempotent") if field("source") == Some(wallet) => {
+                if let Some(account) = field("account") {
+                    let before = lamports_at(tx, "preBalances", &keys, account).unwrap_or(0);
+                    let after = lamports_at(tx, "postBalances", &keys, account).unwrap_or(0);
+                    if before == 0 && after > 0 {
+                        created.entry(account.to_string()).or_insert(after);
+                    }
+                }
+            }
+            (_, "closeAccount") if field("destination") == Some(wallet) => {
+                match field("account").and_then(|a| lamports_at(tx, "preBalances", &keys, a)) {
+                    Some(lamports) => fees.rent_reclaimed_lamports += lamports,
+                    None => fees.unattributed = true,
+                }
+            }
+            _ => {}
+        }
+    }
+    fees.rent_paid_lamports = created.values().sum();
+
+    for ix in outer
+        .iter()
+        .filter(|ix| program_of(ix) == Some(JUPITER_PROGRAM))
+    {
+        let bps = ix
+            .get("data")
+            .and_then(Value::as_str)
+            .and_then(jupiter_platform_fee_bps);
+        let Some(bps) = bps else {
+            fees.unattributed = true;
+            continue;
+        };
+        if bps == 0 {
+            continue;
+        }
+        // The fee is taken from the output before it reaches the wallet.
+        let received = activity
+            .token_in
+            .as_ref()
+            .and_then(|token| price(&token.mint).map(|usd| token.amount * usd));
+        match received {
+            Some(usd) => {
+                fees.platform_fee_usd += usd * f64::from(bps) / (10_000.0 - f64::from(bps))
+            }
+            None => fees.unattributed = true,
+        }
+    }
+
+    fees
+}
+
+fn month_of(timestamp: i64) -> String {
+    DateTime::from_timestamp(timestamp, 0)
+        .map(|time| time.format("%Y-%m").to_string())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Rolls per-transaction fees up into a report.
+pub fn summarize_fees(
+    wallet: &str,
+    since: DateTime<Utc>,
+    until: DateTime<Utc>,
+    transactions: &[TransactionFees],
+    sol_price_usd: f64,
+    realized_pnl: Option<f64>,
+) -> FeeAuditReport {
+    let mut totals = FeeTotals::default();
+    let mut by_program: HashMap<Option<String>, FeeTotals> = HashMap::new();
+    let mut monthly: BTreeMap<String, FeeTotals> = BTreeMap::new();
+    for fees in transactions {
+        totals.add(fees, sol_price_usd);
+        by_program
+            .entry(fees.program_id.clone())
+            .or_default()
+            .add(fees, sol_price_usd);
+        monthly
+            .entry(month_of(fees.timestamp))
+            .or_default()
+            .add(fees, sol_price_usd);
+    }
+
+    let mut by_program: Vec<ProgramFees> = by_program
+        .into_iter()
+        .map(|(program_id, fees)| ProgramFees {
+            program_name: match program_id.as_deref() {
+                Some(id) => known_program(id).map_or_else(|| id.to_string(), |(n, _)| n.into()),
+                None => "unattributed".to_string(),
+            },
+            program_id,
+            fees,
+        })
+        .collect();
+    by_program.sort_by(|a, b| b.fees.total_usd.total_cmp(&a.fees.total_usd));
+
+    let trades = transactions.iter().filter(|fees| fees.is_trade).count();
+    FeeAuditReport {
+        wallet: wallet.to_string(),
+        since,
+        until,
+        sol_price_usd,
+        fee_per_trade_usd: (trades > 0).then(|| totals.total_usd / trades as f64),
+        fees_pct_of_pnl: realized_pnl
+            .filter(|pnl| pnl.abs() > f64::EPSILON)
+            .map(|pnl| totals.total_usd / pnl.abs() * 100.0),
+        realized_pnl,
+        totals,
+        by_program,
+        monthly: monthly
+            .into_iter()
+            .map(|(month, fees)| MonthlyFees { month, fees })
+            .collect(),
+        trades,
+        truncated: false,
+        generated_at: Utc::now(),
+    }
+}
+
+fn price(mint: &str) -> Option<f64> {
+    let engine = get_price_engine();
+    engine.get_price(mint).or_else(|| match mint {
+        SOL_MINT => engine.get_price("SOL"),
+        _ => None,
+    })
+}
+
+pub struct FeeAuditor {
+    app: AppHandle,
+    cache: Mutex<HashMap<(String, String), TransactionFees>>,
+}
+
+pub type SharedFeeAuditor = Arc<FeeAuditor>;
+
+impl FeeAuditor {
+    pub fn new(app: &AppHandle) -> Self {
+        Self {
+            app: app.clone(),
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Audits the transactions `wallet` made between `since` and `until`.
+    pub async fn audit(
+        &self,
+        wallet: &str,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> Result<FeeAuditReport, FeeAuditError> {
+        let (signatures, truncated) = self.signatures(wallet, since, until).await?;
+
+        let mut transactions = Vec::with_capacity(signatures.len());
+        for signature in signatures {
+            if let Some(fees) = self.transaction_fees(wallet, &signature).await? {
+                transactions.push(fees);
+            }
+        }
+
+        let realized_pnl = match self.app.try_state::<SharedPerformanceDatabase>() {
+            Some(db) => {
+                let trades = db
+                    .read()
+                    .await
+                    .get_trades_between(wallet, since, until)
+                    .await
+                    .map_err(|e| FeeAuditError::State(e.to_string()))?;
+                let pnl: Vec<f64> = trades.iter().filter_map(|trade| trade.pnl).collect();
+                (!pnl.is_empty()).then(|| pnl.iter().sum())
+            }
+            None => None,
+        };
+
+        let sol_price = price(SOL_MINT).unwrap_or_default();
+        let mut report =
+            summarize_fees(wallet, since, until, &transactions, sol_price, realized_pnl);
+        report.truncated = truncated;
+        Ok(report)
+    }
+
+    /// What one confirmed transaction cost `wallet`; `None` when the RPC node
+    /// doesn't have it.
+    pub async fn transaction_fees(
+        &self,
+        wallet: &str,
+        signature: &str,
+    ) -> Result<Option<TransactionFees>, FeeAuditError> {
+        let key = (wallet.to_string(), signature.to_string());
+        if let Some(fees) = self.cache.lock().get(&key).cloned() {
+            return Ok(Some(fees));
+        }
+        let raw = self
+            .rpc(
+                "getTransaction",
+                json!([signature, {
+                    "encoding": "jsonParsed",
+                    "maxSupportedTransactionVersion": 0,
+                    "commitment": "confirmed",
+                }]),
+            )
+            .await?;
+        if raw.is_null() {
+            return Ok(None);
+        }
+        let mut fees = audit_transaction(&raw, wallet, price);
+        fees.signature = signature.to_string();
+        let mut cache = self.cache.lock();
+        if cache.len() >= MAX_CACHED_TRANSACTIONS {
+            cache.clear();
+        }
+        cache.insert(key, fees.clone());
+        Ok(Some(fees))
+    }
+
+    /// Confirmed signatures in the window, newest first, and whether the
+    /// window held more than one audit fetches.
+    async fn signatures(
+        &self,
+        wallet: &str,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> Result<(Vec<String>, bool), FeeAuditError> {
+        let mut signatures = Vec::new();
+        let mut before: Option<String> = None;
+        loop {
+            let mut options = json!({ "limit": SIGNATURE_PAGE_LIMIT, "commitment": "confirmed" });
+            if let Some(before) = &before {
+                options["before"] = json!(before);
+            }
+            let page = self
+                .rpc("getSignaturesForAddress", json!([wallet, options]))
+                .await?;
+            let page = page.as_array().cloned().unwrap_or_default();
+            let full_page = page.len() >= SIGNATURE_PAGE_LIMIT;
+
+            for info in &page {
+                let Some(signature) = info.get("signature").and_then(Value::as_str) else {
+                    continue;
+                };
+                before = Some(signature.to_string());
+                let Some(block_time) = info.get("blockTime").and_then(Value::as_i64) else {
+                    continue;
+                };
+                if block_time < since.timestamp() {
+                    return Ok((signatures, false));
+                }
+                if block_time > until.timestamp() {
+                    continue;
+                }
+                if signatures.len() >= MAX_AUDITED_TRANSACTIONS {
+                    return Ok((signatures, true));
+                }
+                signatures.push(signature.to_string());
+            }
+
+            if !full_page {
+                return Ok((signatures, false));
+            }
+        }
+    }
+
+    async fn rpc(&self, method: &str, params: Value) -> Result<Value, FeeAuditError> {
+        solana_rpc(&rpc_endpoint(&self.app), method, params)
+            .await
+            .map_err(FeeAuditError::Rpc)
+    }
+}
+
+// Tauri commands
+#[tauri::command]
+pub async fn get_fee_audit(
+    wallet: String,
+    window_days: Option<u32>,
+    auditor: State<'_, SharedFeeAuditor>,
+    privacy: State<'_, SharedPrivacyMode>,
+) -> Result<Masked<FeeAuditReport>, String> {
+    let days = window_days
+        .unwrap_or(DEFAULT_WINDOW_DAYS)
+        .clamp(1, MAX_WINDOW_DAYS);
+    let until = Utc::now();
+    let report = auditor
+        .audit(&wallet, until - Duration::days(i64::from(days)), until)
+        .await
+        .map_err(|e| e.to_string())?;
+    let privacy = privacy.read().map_err(|e| e.to_string())?;
+    Ok(privacy.apply_for_wallet(report, &wallet))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WALLET: &str = "7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU";
+    const USDC: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+
+    fn no_price(_: &str) -> Option<f64> {
+        None
+    }
+
+    fn route_data(platform_fee_bps: u8) -> String {
+        let mut bytes = vec![0xE5, 0x17, 0xCB, 0x97, 0x7A, 0xE3, 0xAD, 0x2A, 1, 2, 3];
+        bytes.push(platform_fee_bps);
+        bs58::encode(bytes).into_string()
+    }
+
+    #[test]
+    fn splits_fees_and_nets_rent_for_the_fee_payer() {
+        let mut raw: Value =
+            serde_json::from_str(include_str!("../websocket/fixtures/raydium_swap_rpc.json"))
+                .unwrap();
+        raw["meta"]["fee"] = json!(25_000);
+        raw["meta"]["innerInstructions"][0]["instructions"]
+            .as_array_mut()
+            .unwrap()
+            .extend([
+                json!({
+                    "programId": SYSTEM_PROGRAM,
+                    "parsed": { "type": "createAccount", "info": {
+                        "source": WALLET, "newAccount": "NewAcct", "lamports": 2_039_280,
+                    }},
+                }),
+                json!({
+                    "programId": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+                    "parsed": { "type": "closeAccount", "info": {
+                        "account": "F3x8C6e4gAhNJyTZuWFx4ryQ5TGo7ZdR3rrc9eVDXyZp",
+                        "destination": WALLET,
+                        "owner": WALLET,
+                    }},
+                }),
+            ]);
+
+        let fees = audit_transaction(&raw, WALLET, no_price);
+        assert!(fees.is_trade);
+        assert_eq!(fees.base_fee_lamports, 5_000);
+        assert_eq!(fees.priority_fee_lamports, 20_000);
+        assert_eq!(fees.rent_paid_lamports, 2_039_280);
+        assert_eq!(fees.rent_reclaimed_lamports, 2_039_280);
+        assert_eq!(
+            fees.program_id.as_deref(),
+            Some("675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8")
+        );
+        assert!(!fees.unattributed);
+
+        // Someone else paid for it: no transaction fee on this wallet.
+        let other = audit_transaction(
+            &raw,
+            "58oQChx4yWmvKdwLLZzBi4ChoCc2fqCUWBkwMihLYQo2",
+            no_price,
+        );
+        assert_eq!(other.base_fee_lamports + other.priority_fee_lamports, 0);
+    }
+
+    #[test]
+    fn decodes_jupiter_platform_fees_and_buckets_what_it_cannot_price() {
+        let tx = |data: &str| {
+            json!({
+                "blockTime": 1_700_000_000,
+                "transaction": {
+                    "signatures": ["jupSig"],
+                    "message": {
+                        "accountKeys": [{ "pubkey": WALLET, "signer": true }, { "pubkey": "pool" }],
+                        "instructions": [{ "programId": JUPITER_PROGRAM, "accounts": [], "data": data }],
+                    },
+                },
+                "meta": {
+                    "err": null,
+                    "fee": 5_000,
+                    "preBalances": [1_000_000_000, 0],
+                    "postBalances": [899_995_000, 0],
+                    "preTokenBalances": [],
+                    "postTokenBalances": [{
+                        "accountIndex": 1, "mint": USDC, "owner": WALLET,
+                        "uiTokenAmount": { "amount": "99000000", "decimals": 6, "uiAmount": 99.0, "uiAmountString": "99" },
+                    }],
+                },
+            })
+        };
+
+        let usdc = |mint: &str| (mint == USDC).then_some(1.0);
+        let fees = audit_transaction(&tx(&route_data(100)), WALLET, usdc);
+        assert!((fees.platform_fee_usd - 1.0).abs() < 1e-9);
+        assert!(!fees.unattributed);
+
+        let unpriced = audit_transaction(&tx(&route_data(100)), WALLET, no_price);
+        assert_eq!(unpriced.platform_fee_usd, 0.0);
+        assert!(unpriced.unattributed);
+
+        let garbled = audit_transaction(&tx("0OIl"), WALLET, usdc);
+        assert!(garbled.unattributed);
+        assert_eq!(garbled.base_fee_lamports, 5_000);
+    }
+
+    #[test]
+    fn report_groups_by_program_and_month_and_relates_fees_to_trades() {
+        let fees = |program: Option<&str>, timestamp: i64, is_trade: bool| TransactionFees {
+            signature: format!("{timestamp}"),
+            timestamp,
+            program_id: program.map(str::to_string),
+            is_trade,
+            base_fee_lamports: 5_000,
+            priority_fee_lamports: 95_000,
+            platform_fee_usd: if is_trade { 1.0 } else { 0.0 },
+            ..TransactionFees::default()
+        };
+        let transactions = vec![
+            fees(Some(JUPITER_PROGRAM), 1_700_000_000, true),
+            fees(Some(JUPITER_PROGRAM), 1_702_000_000, true),
+            fees(None, 1_702_000_100, false),
+        ];
+        let now = Utc::now();
+        let report = summarize_fees(WALLET, now, now, &transactions, 100.0, Some(-40.0));
+
+        // 0.0001 SOL of network fees per transaction at $100, plus $1 per trade.
+        assert!((report.totals.total_usd - 2.03).abs() < 1e-9);
+        assert_eq!(report.trades, 2);
+        assert!((report.fee_per_trade_usd.unwrap() - 1.015).abs() < 1e-9);
+        assert!((report.fees_pct_of_pnl.unwrap() - 5.075).abs() < 1e-9);
+
+        assert_eq!(report.by_program[0].program_name, "Jupiter");
+        assert_eq!(report.by_program[0].fees.transactions, 2);
+        assert_eq!(report.by_program[1].program_name, "unattributed");
+        let months: Vec<&str> = report.monthly.iter().map(|m| m.month.as_str()).collect();
+        assert_eq!(months, ["2023-11", "2023-12"]);
+    }
+}
@@ -1,7 +1,13 @@
+pub mod account_cleanup;
+pub mod airdrop;
+pub mod balances;
+pub mod fee_audit;
 pub mod hardware_wallet;
 pub mod ledger;
 pub mod multi_wallet;
 pub mod multisig;
 pub mod operations;
 pub mod performance;
+pub mod performance_score;
 pub mod phantom;
+pub mod swap_reconciliation;
@@ -1,10 +1,14 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
 use std::collections::HashMap;
+use std::str::FromStr;
 use std::sync::{Mutex, MutexGuard};
 use tauri::State;
 use uuid::Uuid;
 
+use crate::config::privacy_mode::{Masked, PrivacyMasked, SharedPrivacyMode};
+use crate::errors::AppError;
 use crate::security::keystore::{Keystore, KeystoreError};
 
 const KEYSTORE_STATE_KEY: &str = "wallet.multi_state";
@@ -47,6 +51,10 @@ pub struct WalletInfo {
     pub balance: f64,
     pub preferences: WalletPreferences,
     pub performance: PerformanceMetrics,
+    /// Tracked without a key. Counts everywhere a wallet is read, but every
+    /// path that signs or sends refuses it.
+    #[serde(default)]
+    pub watch_only: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -178,6 +186,8 @@ pub struct UpdateGroupRequest {
 pub struct AggregatedPortfolio {
     pub total_balance: f64,
     pub total_wallets: usize,
+    /// Included in `total_wallets` and every total.
+    pub watch_only_wallets: usize,
     pub total_groups: usize,
     pub total_trades: u64,
     pub total_volume: f64,
@@ -186,6 +196,25 @@ pub struct AggregatedPortfolio {
     pub wallets: Vec<WalletInfo>,
 }
 
+impl PrivacyMasked for WalletInfo {
+    const MASKED_FIELDS: &'static [&'static str] =
+        &["balance", "totalVolume", "realizedPnl", "unrealizedPnl"];
+    const WALLET_KEY: Option<&'static str> = Some("publicKey");
+}
+
+impl PrivacyMasked for AggregatedPortfolio {
+    const MASKED_FIELDS: &'static [&'static str] = &[
+        "totalBalance",
+        "totalVolume",
+        "totalRealizedPnl",
+        "totalUnrealizedPnl",
+        "balance",
+        "realizedPnl",
+        "unrealizedPnl",
+    ];
+    const WALLET_KEY: Option<&'static str> = Some("publicKey");
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum MultiWalletError {
     #[error("serialization error: {0}")]
@@ -198,12 +227,41 @@ pub enum MultiWalletError {
     WalletExists(String),
     #[error("invalid input: {0}")]
     InvalidInput(String),
+    #[error("wallet is watch-only: {0}")]
+    WatchOnlyWallet(String),
     #[error("keystore error: {0}")]
     Keystore(#[from] KeystoreError),
     #[error("internal error")]
     Internal,
 }
 
+impl From<MultiWalletError> for AppError {
+    fn from(err: MultiWalletError) -> Self {
+        match err {
+            MultiWalletError::WalletNotFound(id) => AppError::not_found("Wallet not found")
+                .with_code("wallet_not_found")
+                .with_detail("walletId", id),
+            MultiWalletError::GroupNotFound(id) => AppError::not_found("Wallet group not found")
+                .with_code("wallet_group_not_found")
+                .with_detail("groupId", id),
+            MultiWalletError::WalletExists(address) => {
+                AppError::validation("Wallet already exists")
+                    .with_code("wallet_exists")
+                    .with_detail("address", address)
+            }
+            MultiWalletError::InvalidInput(message) => AppError::validation(message),
+            MultiWalletError::WatchOnlyWallet(address) => {
+                AppError::validation("Watch-only wallets cannot sign or send transactions")
+                    .with_code("watch_only_wallet")
+                    .with_detail("address", address)
+            }
+            MultiWalletError::Keystore(err) => err.into(),
+            MultiWalletError::Serialization(err) => err.into(),
+            MultiWalletError::Internal => AppError::internal("Multi-wallet state is unavailable"),
+        }
+    }
+}
+
 pub struct MultiWalletManager {
     state: Mutex<MultiWalletState>,
 }
@@ -221,71 +279,66 @@ impl MultiWalletManager {
         })
     }
 
+    /// Adds a wallet, or connects the key of a watch-only wallet with the
+    /// same address. Connecting keeps the wallet's id, group, balance and
+    /// performance history.
     pub fn add_wallet(
         &self,
         request: AddWalletRequest,
         keystore: &Keystore,
     ) -> Result<WalletInfo, MultiWalletError> {
         let mut guard = self.lock_state()?;
+        let wallet = insert_wallet(&mut guard, request, false)?;
+        self.persist_locked(&guard, keystore)?;
+        Ok(wallet)
+    }
 
-        if guard
-            .wallets
-            .values()
-            .any(|w| w.public_key == request.public_key)
-        {
-            return Err(MultiWalletError::WalletExists(request.public_key));
-        }
-
-        let wallet_id = format!("wallet_{}", Uuid::new_v4());
-        let now = Utc::now();
-
-        if let Some(group_id) = &request.group_id {
-            if !guard.groups.contains_key(group_id) {
-                return Err(MultiWalletError::GroupNotFound(group_id.clone()));
-            }
-        }
-
-        let chain_id = request
-            .chain_id
-            .clone()
-            .unwrap_or_else(|| infer_chain_id(&request.network));
-
-        let wallet = WalletInfo {
-            id: wallet_id.clone(),
-            public_key: request.public_key,
-            label: request.label,
-            network: request.network,
-            chain_id,
-            wallet_type: request.wallet_type,
-            group_id: request.group_id.clone(),
-            created_at: now,
-            updated_at: now,
-            last_used: None,
-            balance: 0.0,
-            preferences: WalletPreferences::default(),
-            performance: PerformanceMetrics::default(),
+    pub fn add_watch_only_wallet(
+        &self,
+        address: String,
+        label: String,
+        keystore: &Keystore,
+    ) -> Result<WalletInfo, MultiWalletError> {
+        let pubkey = Pubkey::from_str(address.trim())
+            .map_err(|_| MultiWalletError::InvalidInput(format!("Invalid address: {}", address)))?;
+        let request = AddWalletRequest {
+            public_key: pubkey.to_string(),
+            label,
+            network: default_chain_id(),
+            wallet_type: WalletType::Imported,
+            group_id: None,
+            chain_id: None,
         };
 
-        if let Some(group_id) = &request.group_id {
-            if let Some(group) = guard.groups.get_mut(group_id) {
-                if !group.wallet_ids.contains(&wallet_id) {
-                    group.wallet_ids.push(wallet_id.clone());
-                }
-            }
-        }
-
-        if guard.active_wallet_id.is_none() {
-            guard.active_wallet_id = Some(wallet_id.clone());
-        }
-
-        guard.wallets.insert(wallet_id.clone(), wallet.clone());
-        guard.last_updated = now;
-
+        let mut guard = self.lock_state()?;
+        let wallet = insert_wallet(&mut guard, request, true)?;
         self.persist_locked(&guard, keystore)?;
-
         Ok(wallet)
     }
 
+    /// Fails with `WatchOnlyWallet` when `address` belongs to a watch-only
+    /// wallet. Addresses the manager does not track are allowed, since
+    /// connected wallets need not be added here. Call before building a
+    /// transaction.
+    pub fn ensure_can_sign(&self, address: &str) -> Result<(), MultiWalletError> {
+        let guard = self.lock_state()?;
+        ensure_can_sign(&guard, address)
+    }
+
+    /// `ensure_can_sign` for the active wallet, for paths that act on it
+    /// without naming an address.
+    pub fn ensure_active_can_sign(&self) -> Result<(), MultiWalletError> {
+        let guard = self.lock_state()?;
+        let active = guard
+            .active_wallet_id
+            .as_ref()
+            .and_then(|id| guard.wallets.get(id));
+        match active {
+            Some(wallet) => ensure_can_sign(&guard, &wallet.public_key),
+            None => Ok(()),
+        }
+    }
+
     pub fn update_wallet(
         &self,
         request: UpdateWalletRequest,
@@ -435,7 +488,12 @@ impl MultiWalletManager {
     pub fn list_wallets(&self) -> Result<Vec<WalletInfo>, MultiWalletError> {
         let guard = self.lock_state()?;
         let mut wallets: Vec<WalletInfo> = guard.wallets.values().cloned().collect();
-        wallets.sort_by(|a, b| b.last_used.cmp(&a.last_used));
+        // Wallets that can sign come first.
+        wallets.sort_by(|a, b| {
+            a.watch_only
+                .cmp(&b.watch_only)
+                .then_with(|| b.last_used.cmp(&a.last_used))
+        });
         Ok(wallets)
     }
 
@@ -641,6 +699,7 @@ impl MultiWalletManager {
         Ok(AggregatedPortfolio {
             total_balance,
             total_wallets: wallets.len(),
+            watch_only_wallets: wallets.iter().filter(|w| w.watch_only).count(),
             total_groups: guard.groups.len(),
             total_trades,
             total_volume,
@@ -666,15 +725,112 @@ impl MultiWalletManager {
     }
 }
 
+fn ensure_can_sign(state: &MultiWalletState, address: &str) -> Result<(), MultiWalletError> {
+    if state
+        .wallets
+        .values()
+        .any(|w| w.public_key == address && w.watch_only)
+    {
+        return Err(MultiWalletError::WatchOnlyWallet(address.to_string()));
+    }
+    Ok(())
+}
+
+fn insert_wallet(
+    state: &mut MultiWalletState,
+    request: AddWalletRequest,
+    watch_only: bool,
+) -> Result<WalletInfo, MultiWalletError> {
+    let now = Utc::now();
+
+    if let Some(existing) = state
+        .wallets
+        .values_mut()
+        .find(|w| w.public_key == request.public_key)
+    {
+        if watch_only || !existing.watch_only {
+            return Err(MultiWalletError::WalletExists(request.public_key));
+        }
+        existing.watch_only = false;
+        existing.wallet_type = request.wallet_type;
+        existing.preferences.trading_enabled = true;
+        existing.updated_at = now;
+        let wallet = existing.clone();
+        state.last_updated = now;
+        return Ok(wallet);
+    }
+
+    if let Some(group_id) = &request.group_id {
+        if !state.groups.contains_key(group_id) {
+            return Err(MultiWalletError::GroupNotFound(group_id.clone()));
+        }
+    }
+
+    let wallet_id = format!("wallet_{}", Uuid::new_v4());
+    let chain_id = request
+        .chain_id
+        .clone()
+        .unwrap_or_else(|| infer_chain_id(&request.network));
+
+    let wallet = WalletInfo {
+        id: wallet_id.clone(),
+        public_key: request.public_key,
+        label: request.label,
+        network: request.network,
+        chain_id,
+        wallet_type: request.wallet_type,
+        group_id: request.group_id.clone(),
+        created_at: now,
+        updated_at: now,
+        last_used: None,
+        balance: 0.0,
+        preferences: WalletPreferences {
+            trading_enabled: !watch_only,
+            ..WalletPreferences::default()
+        },
+        performance: PerformanceMetrics::default(),
+        watch_only,
+    };
+
+    if let Some(group_id) = &request.group_id {
+        if let Some(group) = state.groups.get_mut(group_id) {
+            if !group.wallet_ids.contains(&wallet_id) {
+                group.wallet_ids.push(wallet_id.clone());
+            }
+        }
+    }
+
+    if state.active_wallet_id.is_none() {
+        state.active_wallet_id = Some(wallet_id.clone());
+    }
+
+    state.wallets.insert(wallet_id, wallet.clone());
+    state.last_updated = now;
+
+    Ok(wallet)
+}
+
 #[tauri::command]
 pub async fn multi_wallet_add(
     request: AddWalletRequest,
     manager: State<'_, MultiWalletManager>,
     keystore: State<'_, Keystore>,
-) -> Result<WalletInfo, String> {
+) -> Result<WalletInfo, AppError> {
     manager
         .add_wallet(request, &keystore)
-        .map_err(|e| e.to_string())
+        .map_err(AppError::from)
+}
+
+#[tauri::command]
+pub async fn multi_wallet_add_watch_only(
+    address: String,
+    label: String,
+    manager: State<'_, MultiWalletManager>,
+    keystore: State<'_, Keystore>,
+) -> Result<WalletInfo, AppError> {
+    manager
+        .add_watch_only_wallet(address, label, &keystore)
+        .map_err(AppError::from)
 }
 
 #[tauri::command]
@@ -682,10 +838,10 @@ pub async fn multi_wallet_update(
     request: UpdateWalletRequest,
     manager: State<'_, MultiWalletManager>,
     keystore: State<'_, Keystore>,
-) -> Result<WalletInfo, String> {
+) -> Result<WalletInfo, AppError> {
     manager
         .update_wallet(request, &keystore)
-        .map_err(|e| e.to_string())
+        .map_err(AppError::from)
 }
 
 #[tauri::command]
@@ -693,10 +849,10 @@ pub async fn multi_wallet_remove(
     wallet_id: String,
     manager: State<'_, MultiWalletManager>,
     keystore: State<'_, Keystore>,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     manager
         .remove_wallet(&wallet_id, &keystore)
-        .map_err(|e| e.to_string())
+        .map_err(AppError::from)
 }
 
 #[tauri::command]
@@ -704,24 +860,29 @@ pub async fn multi_wallet_set_active(
     wallet_id: String,
     manager: State<'_, MultiWalletManager>,
     keystore: State<'_, Keystore>,
-) -> Result<WalletInfo, String> {
+) -> Result<WalletInfo, AppError> {
     manager
         .set_active_wallet(&wallet_id, &keystore)
-        .map_err(|e| e.to_string())
+        .map_err(AppError::from)
 }
 
 #[tauri::command]
 pub async fn multi_wallet_get_active(
     manager: State<'_, MultiWalletManager>,
-) -> Result<Option<WalletInfo>, String> {
-    manager.get_active_wallet().map_err(|e| e.to_string())
+) -> Result<Option<WalletInfo>, AppError> {
+    manager.get_active_wallet().map_err(AppError::from)
 }
 
 #[tauri::command]
 pub async fn multi_wallet_list(
     manager: State<'_, MultiWalletManager>,
-) -> Result<Vec<WalletInfo>, String> {
-    manager.list_wallets().map_err(|e| e.to_string())
+    privacy: State<'_, SharedPrivacyMode>,
+) -> Result<Masked<Vec<WalletInfo>>, AppError> {
+    let wallets = manager.list_wallets()?;
+    let privacy = privacy
+        .read()
+        .map_err(|e| AppError::internal(e.to_string()))?;
+    Ok(privacy.apply(wallets))
 }
 
 #[tauri::command]
@@ -730,10 +891,10 @@ pub async fn multi_wallet_update_balance(
     balance: f64,
     manager: State<'_, MultiWalletManager>,
     keystore: State<'_, Keystore>,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     manager
         .update_wallet_balance(&wallet_id, balance, &keystore)
-        .map_err(|e| e.to_string())
+        .map_err(AppError::from)
 }
 
 #[tauri::command]
@@ -742,10 +903,10 @@ pub async fn multi_wallet_update_performance(
     metrics: PerformanceMetrics,
     manager: State<'_, MultiWalletManager>,
     keystore: State<'_, Keystore>,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     manager
         .update_performance_metrics(&wallet_id, metrics, &keystore)
-        .map_err(|e| e.to_string())
+        .map_err(AppError::from)
 }
 
 #[tauri::command]
@@ -753,10 +914,10 @@ pub async fn multi_wallet_create_group(
     request: CreateGroupRequest,
     manager: State<'_, MultiWalletManager>,
     keystore: State<'_, Keystore>,
-) -> Result<WalletGroup, String> {
+) -> Result<WalletGroup, AppError> {
     manager
         .create_group(request, &keystore)
-        .map_err(|e| e.to_string())
+        .map_err(AppError::from)
 }
 
 #[tauri::command]
@@ -764,10 +925,10 @@ pub async fn multi_wallet_update_group(
     request: UpdateGroupRequest,
     manager: State<'_, MultiWalletManager>,
     keystore: State<'_, Keystore>,
-) -> Result<WalletGroup, String> {
+) -> Result<WalletGroup, AppError> {
     manager
         .update_group(request, &keystore)
-        .map_err(|e| e.to_string())
+        .map_err(AppError::from)
 }
 
 #[tauri::command]
@@ -775,24 +936,331 @@ pub async fn multi_wallet_delete_group(
     group_id: String,
     manager: State<'_, MultiWalletManager>,
     keystore: State<'_, Keystore>,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     manager
         .delete_group(&group_id, &keystore)
-        .map_err(|e| e.to_string())
+        .map_err(AppError::from)
 }
 
 #[tauri::command]
 pub async fn multi_wallet_list_groups(
     manager: State<'_, MultiWalletManager>,
-) -> Result<Vec<WalletGroup>, String> {
-    manager.list_groups().map_err(|e| e.to_string())
+) -> Result<Vec<WalletGroup>, AppError> {
+    manager.list_groups().map_err(AppError::from)
 }
 
 #[tauri::command]
 pub async fn multi_wallet_get_aggregated(
     manager: State<'_, MultiWalletManager>,
-) -> Result<AggregatedPortfolio, String> {
-    manager
-        .get_aggregated_portfolio()
-        .map_err(|e| e.to_string())
+    privacy: State<'_, SharedPrivacyMode>,
+) -> Result<Masked<AggregatedPortfolio>, AppError> {
+    let portfolio = manager.get_aggregated_portfolio()?;
+    let privacy = privacy
+        .read()
+        .map_err(|e| AppError::internal(e.to_string()))?;
+    Ok(privacy.apply(portfolio))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::jupiter::{jupiter_swap, QuoteResponse, SwapCommandInput, SwapMode};
+    use crate::p2p::{
+        prepare_p2p_escrow_transaction, CreateEscrowRequest, CreateOfferRequest, EscrowAction,
+        EscrowMode, OfferPricing, OfferType, P2PDatabase, PriceQuote, SharedP2PDatabase,
+    };
+    use crate::portfolio::rebalancer::{
+        execute_rebalance, SharedPortfolioData, SharedRebalancerState,
+    };
+    use crate::trading::database::OrderDatabase;
+    use crate::trading::order_manager::OrderManager;
+    use crate::trading::types::{CreateOrderRequest, OrderSide, OrderType};
+    use crate::wallet::account_cleanup::AccountCleanup;
+    use crate::wallet::operations::{wallet_send_transaction, SendTransactionInput};
+    use chrono::Utc;
+    use std::sync::Arc;
+    use tauri::test::{mock_app, MockRuntime};
+    use tauri::Manager;
+
+    const COLD: &str = "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin";
+
+    fn request(public_key: &str, wallet_type: WalletType) -> AddWalletRequest {
+        AddWalletRequest {
+            public_key: public_key.to_string(),
+            label: "Cold storage".to_string(),
+            network: "solana".to_string(),
+            wallet_type,
+            group_id: None,
+            chain_id: None,
+        }
+    }
+
+    fn manager(state: MultiWalletState) -> MultiWalletManager {
+        MultiWalletManager {
+            state: Mutex::new(state),
+        }
+    }
+
+    /// An app whose managed wallet manager holds `COLD` as its active,
+    /// watch-only wallet.
+    fn app_watching_cold() -> tauri::App<MockRuntime> {
+        let mut state = MultiWalletState::default();
+        insert_wallet(&mut state, request(COLD, WalletType::Imported), true).unwrap();
+        let app = mock_app();
+        app.manage(manager(state));
+        app
+    }
+
+    fn watch_only_message() -> String {
+        MultiWalletError::WatchOnlyWallet(COLD.to_string()).to_string()
+    }
+
+    #[test]
+    fn signing_guards_refuse_watch_only_wallets() {
+        let mut state = MultiWalletState::default();
+        insert_wallet(&mut state, request(COLD, WalletType::Imported), true).unwrap();
+        let manager = manager(state);
+
+        // Address-based paths: wallet_send_transaction, jupiter_swap and
+        // every order created through the order manager.
+        let err = manager.ensure_can_sign(COLD).unwrap_err();
+        assert!(matches!(err, MultiWalletError::WatchOnlyWallet(ref address) if address == COLD));
+        assert_eq!(AppError::from(err).code(), "watch_only_wallet");
+
+        // Active-wallet paths: rebalancer execution. The first wallet added
+        // becomes active.
+        assert!(matches!(
+            manager.ensure_active_can_sign(),
+            Err(MultiWalletError::WatchOnlyWallet(_))
+        ));
+
+        // Untracked addresses are not the manager's to refuse.
+        assert!(manager.ensure_can_sign("SomeConnectedWallet").is_ok());
+    }
+
+    #[test]
+    fn watch_only_wallets_count_in_aggregation_and_list_last() {
+        let mut state = MultiWalletState::default();
+        insert_wallet(&mut state, request(COLD, WalletType::Imported), true).unwrap();
+        insert_wallet(&mut state, request("HotWallet", WalletType::Phantom), false).unwrap();
+        for wallet in state.wallets.values_mut() {
+            wallet.balance = 10.0;
+        }
+        let manager = manager(state);
+
+        let portfolio = manager.get_aggregated_portfolio().unwrap();
+        assert_eq!(portfolio.total_wallets, 2);
+        assert_eq!(portfolio.watch_only_wallets, 1);
+        assert_eq!(portfolio.total_balance, 20.0);
+
+        let wallets = manager.list_wallets().unwrap();
+        assert!(!wallets[0].watch_only);
+        assert!(wallets[1].watch_only);
+        assert!(!wallets[1].preferences.trading_enabled);
+    }
+
+    #[test]
+    fn connecting_the_key_keeps_history() {
+        let mut state = MultiWalletState::default();
+        let watched = insert_wallet(&mut state, request(COLD, WalletType::Imported), true).unwrap();
+        state
+            .wallets
+            .get_mut(&watched.id)
+            .unwrap()
+            .performance
+            .total_trades = 12;
+
+        // Adding the same address again as watch-only is still a duplicate.
+        assert!(matches!(
+            insert_wallet(&mut state, request(COLD, WalletType::Imported), true),
+            Err(MultiWalletError::WalletExists(_))
+        ));
+
+        let connected =
+            insert_wallet(&mut state, request(COLD, WalletType::HardwareLedger), false).unwrap();
+        assert_eq!(connected.id, watched.id);
+        assert_eq!(connected.created_at, watched.created_at);
+        assert_eq!(connected.performance.total_trades, 12);
+        assert!(!connected.watch_only);
+        assert!(connected.preferences.trading_enabled);
+        assert!(matches!(connected.wallet_type, WalletType::HardwareLedger));
+        assert_eq!(state.wallets.len(), 1);
+
+        assert!(manager(state).ensure_can_sign(COLD).is_ok());
+    }
+
+    #[tokio::test]
+    async fn jupiter_swap_refuses_watch_only_wallets() {
+        let app = app_watching_cold();
+        let input = SwapCommandInput {
+            quote: QuoteResponse {
+                input_mint: "So11111111111111111111111111111111111111112".to_string(),
+                output_mint: "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v".to_string(),
+                input_amount: "1000000".to_string(),
+                output_amount: "150000".to_string(),
+                other_amount_threshold: "149250".to_string(),
+                swap_mode: SwapMode::ExactIn,
+                slippage_bps: Some(50),
+                price_impact_pct: 0.0,
+                context_slot: 0,
+                time_taken: 0.0,
+                route_plan: Vec::new(),
+                prioritization_fee_lamports: None,
+            },
+            user_public_key: COLD.to_string(),
+            fee_account: None,
+            wrap_and_unwrap_sol: None,
+            as_legacy_transaction: None,
+            priority_fee_config: None,
+            simulate: None,
+        };
+
+        // The empty route would fail next; the guard must refuse first.
+        let err = jupiter_swap(input, app.state()).await.unwrap_err();
+        assert_eq!(err, watch_only_message());
+    }
+
+    #[tokio::test]
+    async fn wallet_send_transaction_refuses_watch_only_wallets() {
+        let app = app_watching_cold();
+        let input = SendTransactionInput {
+            recipient: "HotWallet".to_string(),
+            amount: 1.0,
+            token_mint: None,
+            memo: None,
+        };
+
+        let err = wallet_send_transaction(input, COLD.to_string(), app.state())
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), "watch_only_wallet");
+    }
+
+    #[tokio::test]
+    async fn order_manager_refuses_watch_only_wallets() {
+        let app = app_watching_cold();
+        let dir = tempfile::tempdir().unwrap();
+        let db = OrderDatabase::new(dir.path().join("orders.db"))
+            .await
+            .unwrap();
+        let orders =
+            OrderManager::new(Arc::new(tokio::sync::RwLock::new(db)), app.handle().clone());
+        let request = CreateOrderRequest {
+            order_type: OrderType::Limit,
+            side: OrderSide::Buy,
+            input_mint: "usdc".to_string(),
+            output_mint: "bonk".to_string(),
+            input_symbol: "USDC".to_string(),
+            output_symbol: "BONK".to_string(),
+            amount: 100.0,
+            limit_price: Some(2.0),
+            stop_price: None,
+            trailing_percent: None,
+            linked_order_id: None,
+            min_book_depth_usd: None,
+            slippage_bps: 50,
+            priority_fee_micro_lamports: 0,
+            wallet_address: COLD.to_string(),
+            client_request_id: None,
+            correlation_id: None,
+            origin: None,
+        };
+
+        let err = orders.create_order(request).await.unwrap_err();
+        assert_eq!(err.code(), "watch_only_wallet");
+        assert!(orders.get_active_orders(COLD).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn account_closures_refuse_watch_only_wallets() {
+        let app = app_watching_cold();
+        let cleanup = AccountCleanup::new(app.handle());
+        let err = cleanup
+            .prepare(COLD, &["account".to_string()], false)
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), watch_only_message());
+    }
+
+    #[tokio::test]
+    async fn escrow_funding_refuses_a_watch_only_buyer() {
+        let app = app_watching_cold();
+        let dir = tempfile::tempdir().unwrap();
+        let db = P2PDatabase::new(dir.path().join("p2p.db")).await.unwrap();
+        let offer = db
+            .create_offer(CreateOfferRequest {
+                creator: "HotWallet".to_string(),
+                offer_type: OfferType::Sell,
+                token_address: "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v".to_string(),
+                token_symbol: "USDC".to_string(),
+                amount: 100.0,
+                price: 1.0,
+                fiat_currency: "USD".to_string(),
+                payment_methods: vec!["bank".to_string()],
+                min_amount: None,
+                max_amount: None,
+                terms: None,
+                time_limit: 30,
+                reputation_required: None,
+                pricing: OfferPricing::Fixed,
+                expires_at: None,
+            })
+            .await
+            .unwrap();
+        let escrow = db
+            .create_escrow(
+                CreateEscrowRequest {
+                    offer_id: offer.id,
+                    buyer: COLD.to_string(),
+                    seller: "So11111111111111111111111111111111111111112".to_string(),
+                    amount: 10.0,
+                    fiat_amount: 10.0,
+                    mode: EscrowMode::OnChain,
+                    arbiter: None,
+                },
+                PriceQuote {
+                    unit_price: 1.0,
+                    pricing: OfferPricing::Fixed,
+                    oracle: None,
+                    quoted_at: Utc::now(),
+                },
+            )
+            .await
+            .unwrap();
+        let db: SharedP2PDatabase = Arc::new(tokio::sync::RwLock::new(db));
+        app.manage(db);
+
+        let err =
+            prepare_p2p_escrow_transaction(escrow.id, EscrowAction::Fund, app.state(), app.state())
+                .await
+                .unwrap_err();
+        assert_eq!(err, watch_only_message());
+    }
+
+    #[test]
+    fn rebalance_execution_refuses_a_watch_only_active_wallet() {
+        let app = app_watching_cold();
+        app.manage(SharedRebalancerState::default());
+        app.manage(SharedPortfolioData::default());
+
+        let err = execute_rebalance(
+            "core-growth".to_string(),
+            false,
+            app.state(),
+            app.state(),
+            app.state(),
+        )
+        .unwrap_err();
+        assert_eq!(err, watch_only_message());
+
+        // A dry run signs nothing, so the guard lets it through.
+        let dry_run = execute_rebalance(
+            "core-growth".to_string(),
+            true,
+            app.state(),
+            app.state(),
+            app.state(),
+        );
+        assert_ne!(dry_run.err(), Some(watch_only_message()));
+    }
 }
@@ -5,11 +5,16 @@ use std::sync::Mutex;
 use tauri::State;
 use uuid::Uuid;
 
+use super::balances::{SharedTokenBalanceService, TokenBalancesView};
+use super::multi_wallet::MultiWalletManager;
+use crate::config::privacy_mode::{Masked, PrivacyMasked, SharedPrivacyMode};
+use crate::errors::AppError;
 use crate::security::keystore::{Keystore, KeystoreError};
+use crate::trading::execution_audit::{record_execution_stage, ExecutionStage};
 
-const KEYSTORE_TOKEN_CACHE_KEY: &str = "wallet.token_cache";
 const KEYSTORE_ADDRESS_BOOK_KEY: &str = "wallet.address_book";
 const KEYSTORE_SWAP_HISTORY_KEY: &str = "wallet.swap_history";
+const MAX_SWAP_HISTORY: usize = 100;
 
 // Token Balance Types
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -26,21 +31,8 @@ pub struct TokenBalance {
     pub last_updated: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct TokenBalancesCache {
-    pub balances: HashMap<String, Vec<TokenBalance>>,
-    pub last_updated: DateTime<Utc>,
-    pub ttl_seconds: u64,
-}
-
-impl Default for TokenBalancesCache {
-    fn default() -> Self {
-        Self {
-            balances: HashMap::new(),
-            last_updated: Utc::now(),
-            ttl_seconds: 60,
-        }
-    }
+impl PrivacyMasked for TokenBalance {
+    const MASKED_FIELDS: &'static [&'static str] = &["balance", "usdValue"];
 }
 
 // Transaction Types
@@ -128,9 +120,18 @@ pub struct SwapHistoryEntry {
     pub tx_signature: Option<String>,
     pub timestamp: DateTime<Utc>,
     pub status: SwapStatus,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
+    /// Wallet that signed the swap, when known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wallet: Option<String>,
+    #[serde(default)]
+    pub source: SwapSource,
+    #[serde(default)]
+    pub reconciliation: ReconciliationStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reconciled_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum SwapStatus {
     Pending,
@@ -138,6 +139,30 @@ pub enum SwapStatus {
     Failed,
 }
 
+/// Where a swap history entry came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SwapSource {
+    /// Recorded by the app when it executed the swap.
+    #[default]
+    App,
+    /// Found on chain by reconciliation (e.g. a swap made in another wallet UI).
+    Chain,
+}
+
+/// How an entry compares with the chain after the last reconciliation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReconciliationStatus {
+    /// Not checked yet, or still within the confirmation timeout.
+    #[default]
+    Pending,
+    /// The signature landed on chain; `status` reflects whether it succeeded.
+    Confirmed,
+    /// The signature never confirmed within the timeout.
+    Unconfirmed,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SwapHistory {
     pub swaps: Vec<SwapHistoryEntry>,
@@ -153,6 +178,36 @@ impl Default for SwapHistory {
     }
 }
 
+impl SwapHistory {
+    /// Adds a swap recorded by the app. An entry reconciliation already
+    /// imported under the same signature is replaced, keeping its confirmation.
+    pub fn record(&mut self, mut entry: SwapHistoryEntry) {
+        let existing = entry.tx_signature.as_deref().and_then(|signature| {
+            self.swaps
+                .iter()
+                .position(|swap| swap.tx_signature.as_deref() == Some(signature))
+        });
+        if let Some(index) = existing {
+            let previous = self.swaps.remove(index);
+            if previous.reconciliation == ReconciliationStatus::Confirmed {
+                entry.reconciliation = previous.reconciliation;
+                entry.reconciled_at = previous.reconciled_at;
+            }
+            entry.wallet = entry.wallet.or(previous.wallet);
+        }
+
+        self.swaps.push(entry);
+        self.trim();
+    }
+
+    /// Sorts newest first and keeps the most recent `MAX_SWAP_HISTORY` swaps.
+    pub fn trim(&mut self) {
+        self.swaps.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+        self.swaps.truncate(MAX_SWAP_HISTORY);
+        self.last_updated = Utc::now();
+    }
+}
+
 // QR Code Types
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -199,19 +254,12 @@ pub struct ProviderFees {
 
 // Managers
 pub struct WalletOperationsManager {
-    token_cache: Mutex<TokenBalancesCache>,
     address_book: Mutex<AddressBook>,
-    swap_history: Mutex<SwapHistory>,
+    pub(super) swap_history: Mutex<SwapHistory>,
 }
 
 impl WalletOperationsManager {
     pub fn initialize(keystore: &Keystore) -> Result<Self, KeystoreError> {
-        let token_cache = match keystore.retrieve_secret(KEYSTORE_TOKEN_CACHE_KEY) {
-            Ok(raw) => serde_json::from_slice(&raw).unwrap_or_default(),
-            Err(KeystoreError::NotFound) => TokenBalancesCache::default(),
-            Err(err) => return Err(err),
-        };
-
         let address_book = match keystore.retrieve_secret(KEYSTORE_ADDRESS_BOOK_KEY) {
             Ok(raw) => serde_json::from_slice(&raw).unwrap_or_default(),
             Err(KeystoreError::NotFound) => AddressBook::default(),
@@ -225,21 +273,11 @@ impl WalletOperationsManager {
         };
 
         Ok(Self {
-            token_cache: Mutex::new(token_cache),
             address_book: Mutex::new(address_book),
             swap_history: Mutex::new(swap_history),
         })
     }
 
-    pub fn persist_token_cache(&self, keystore: &Keystore) -> Result<(), KeystoreError> {
-        let guard = self
-            .token_cache
-            .lock()
-            .map_err(|_| KeystoreError::LockError)?;
-        let data = serde_json::to_vec(&*guard).map_err(|_| KeystoreError::SerializationError)?;
-        keystore.store_secret(KEYSTORE_TOKEN_CACHE_KEY, &data)
-    }
-
     pub fn persist_address_book(&self, keystore: &Keystore) -> Result<(), KeystoreError> {
         let guard = self
             .address_book
@@ -264,57 +302,15 @@ impl WalletOperationsManager {
 pub async fn wallet_get_token_balances(
     address: String,
     force_refresh: bool,
-    operations: State<'_, WalletOperationsManager>,
-    keystore: State<'_, Keystore>,
-) -> Result<Vec<TokenBalance>, String> {
-    let mut cache = operations.token_cache.lock().map_err(|e| e.to_string())?;
-
-    let now = Utc::now();
-    let should_refresh = force_refresh
-        || !cache.balances.contains_key(&address)
-        || (now.timestamp() - cache.last_updated.timestamp()) > cache.ttl_seconds as i64;
-
-    if should_refresh {
-        // In a real implementation, this would fetch from blockchain
-        // For now, we'll return mock data
-        let mock_balances = vec![
-            TokenBalance {
-                mint: "So11111111111111111111111111111111111111112".to_string(),
-                symbol: "SOL".to_string(),
-                name: "Solana".to_string(),
-                balance: 1.5,
-                decimals: 9,
-                usd_value: 150.0,
-                change_24h: 2.5,
-                logo_uri: Some("https://raw.githubusercontent.com/solana-labs/token-list/main/assets/mainnet/So11111111111111111111111111111111111111112/logo.png".to_string()),
-                last_updated: now,
-            },
-            TokenBalance {
-                mint: "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v".to_string(),
-                symbol: "USDC".to_string(),
-                name: "USD Coin".to_string(),
-                balance: 100.0,
-                decimals: 6,
-                usd_value: 100.0,
-                change_24h: 0.0,
-                logo_uri: Some("https://raw.githubusercontent.com/solana-labs/token-list/main/assets/mainnet/EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v/logo.png".to_string()),
-                last_updated: now,
-            },
-        ];
-
-        cache
-            .balances
-            .insert(address.clone(), mock_balances.clone());
-        cache.last_updated = now;
-
-        operations
-            .persist_token_cache(&keystore)
-            .map_err(|e| e.to_string())?;
-
-        Ok(mock_balances)
-    } else {
-        Ok(cache.balances.get(&address).cloned().unwrap_or_default())
-    }
+    balances: State<'_, SharedTokenBalanceService>,
+    privacy: State<'_, SharedPrivacyMode>,
+) -> Result<Masked<TokenBalancesView>, String> {
+    let view = balances
+        .balances(&address, force_refresh)
+        .await
+        .map_err(|e| e.to_string())?;
+    let privacy = privacy.read().map_err(|e| e.to_string())?;
+    Ok(privacy.apply_for_wallet(view, &address))
 }
 
 #[tauri::command]
@@ -343,10 +339,25 @@ pub async fn wallet_estimate_fee(
 pub async fn wallet_send_transaction(
     input: SendTransactionInput,
     wallet_address: String,
-) -> Result<String, String> {
+    correlation_id: Option<String>,
+    wallets: State<'_, MultiWalletManager>,
+) -> Result<String, AppError> {
+    wallets.ensure_can_sign(&wallet_address)?;
+
     // Mock implementation - in production, this would sign and send transaction
     // Returns transaction signature
-    Ok(format!("mock_tx_signature_{}", Uuid::new_v4()))
+    let submitted = std::time::Instant::now();
+    let signature = format!("mock_tx_signature_{}", Uuid::new_v4());
+    record_execution_stage(
+        correlation_id.as_deref(),
+        ExecutionStage::Transaction {
+            signature: signature.clone(),
+            slot: None,
+            latency_ms: Some(submitted.elapsed().as_millis() as u64),
+        },
+    )
+    .await;
+    Ok(signature)
 }
 
 #[tauri::command]
@@ -599,16 +610,9 @@ pub async fn swap_history_add_entry(
     keystore: State<'_, Keystore>,
 ) -> Result<(), String> {
     let mut history = operations.swap_history.lock().map_err(|e| e.to_string())?;
+    history.record(entry);
+    drop(history);
 
-    history.swaps.push(entry);
-    history.swaps.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
-
-    // Keep only last 100 swaps
-    if history.swaps.len() > 100 {
-        history.swaps.truncate(100);
-    }
-
-    history.last_updated = Utc::now();
     operations
         .persist_swap_history(&keystore)
         .map_err(|e| e.to_string())?;
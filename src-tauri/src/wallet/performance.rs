@@ -1,4 +1,8 @@
-use crate::utils::Rfc3339DateTime;
+use super::performance_score::{self, ScoreComponent};
+use crate::config::privacy_mode::{Masked, PrivacyMasked, SharedPrivacyMode};
+use crate::config::settings_manager::SharedSettingsManager;
+use crate::config::settings_schema::PerformanceScoreWeights;
+use crate::utils::{ensure_column, Rfc3339DateTime};
 use chrono::{DateTime, Utc};
 use chrono::{Datelike, Timelike};
 use serde::{Deserialize, Serialize};
@@ -6,7 +10,7 @@ use sqlx::{FromRow, Pool, Row, Sqlite, SqlitePool};
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
-use tauri::State;
+use tauri::{AppHandle, Manager, State};
 use tokio::sync::RwLock;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,6 +29,8 @@ pub struct Trade {
     pub timestamp: DateTime<Utc>,
     pub pnl: Option<f64>,
     pub hold_duration_seconds: Option<i64>,
+    /// Which part of the app placed the trade, see [`StrategySource`].
+    pub strategy_source: String,
 }
 
 impl<'r> sqlx::FromRow<'r, sqlx::sqlite::SqliteRow> for Trade {
@@ -45,6 +51,7 @@ impl<'r> sqlx::FromRow<'r, sqlx::sqlite::SqliteRow> for Trade {
             timestamp: Rfc3339DateTime::try_from(row.try_get::<String, _>("timestamp")?)?.into(),
             pnl: row.try_get("pnl")?,
             hold_duration_seconds: row.try_get("hold_duration_seconds")?,
+            strategy_source: row.try_get("strategy_source")?,
         })
     }
 }
@@ -70,6 +77,21 @@ pub struct PerformanceScore {
     pub avg_hold_duration_seconds: f64,
     pub best_trade_pnl: f64,
     pub worst_trade_pnl: f64,
+    /// Realized P&L of trades placed by bots and automated strategies.
+    pub automated_pnl: f64,
+    /// Fraction (0-1) of absolute realized P&L that came from automation.
+    pub automation_share: f64,
+    /// Largest fall of cumulative realized P&L from its peak. `None` on
+    /// scores saved before drawdown was tracked.
+    #[serde(default)]
+    pub max_drawdown: Option<f64>,
+    /// Fees across all trades, open or closed. `None` on scores saved before
+    /// fees were tracked.
+    #[serde(default)]
+    pub total_fees: Option<f64>,
+    /// How each component contributed to `score`, see [`performance_score`].
+    #[serde(default)]
+    pub components: Vec<ScoreComponent>,
     pub calculated_at: DateTime<Utc>,
 }
 
@@ -96,6 +118,14 @@ impl<'r> sqlx::FromRow<'r, sqlx::sqlite::SqliteRow> for PerformanceScore {
             avg_hold_duration_seconds: row.try_get("avg_hold_duration_seconds")?,
             best_trade_pnl: row.try_get("best_trade_pnl")?,
             worst_trade_pnl: row.try_get("worst_trade_pnl")?,
+            automated_pnl: row.try_get("automated_pnl")?,
+            automation_share: row.try_get("automation_share")?,
+            max_drawdown: row.try_get("max_drawdown")?,
+            total_fees: row.try_get("total_fees")?,
+            components: row
+                .try_get::<Option<String>, _>("components")?
+                .and_then(|json| serde_json::from_str(&json).ok())
+                .unwrap_or_default(),
             calculated_at: Rfc3339DateTime::try_from(row.try_get::<String, _>("calculated_at")?)?.into(),
         })
     }
@@ -167,6 +197,103 @@ pub struct RecordTradeRequest {
     pub price: f64,
     pub fee: f64,
     pub tx_signature: String,
+    /// Source tag such as `manual` or `dca:<config id>`; defaults to manual.
+    #[serde(default)]
+    pub strategy_source: Option<String>,
+    /// Stable id of the originating execution. Recording the same id twice
+    /// returns the stored trade instead of inserting a duplicate.
+    #[serde(default)]
+    pub external_id: Option<String>,
+    /// Exchange the trade was imported from, e.g. `binance`; `None` for
+    /// trades made through the app.
+    #[serde(default)]
+    pub external_source: Option<String>,
+    /// When the trade was executed; defaults to now.
+    #[serde(default)]
+    pub executed_at: Option<DateTime<Utc>>,
+}
+
+/// Where a recorded trade originated.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum StrategySource {
+    Manual,
+    Dca(String),
+    Copy(String),
+    Auto(String),
+}
+
+impl StrategySource {
+    pub fn parse(tag: &str) -> Result<Self, String> {
+        let tag = tag.trim();
+        if tag.is_empty() || tag == "manual" {
+            return Ok(StrategySource::Manual);
+        }
+
+        let (kind, id) = tag
+            .split_once(':')
+            .ok_or_else(|| format!("Unknown strategy source '{}'", tag))?;
+        if id.is_empty() {
+            return Err(format!("Strategy source '{}' is missing an id", tag));
+        }
+
+        match kind {
+            "dca" => Ok(StrategySource::Dca(id.to_string())),
+            "copy" => Ok(StrategySource::Copy(id.to_string())),
+            "auto" => Ok(StrategySource::Auto(id.to_string())),
+            _ => Err(format!("Unknown strategy source '{}'", tag)),
+        }
+    }
+
+    pub fn tag(&self) -> String {
+        match self {
+            StrategySource::Manual => "manual".to_string(),
+            StrategySource::Dca(id) => format!("dca:{}", id),
+            StrategySource::Copy(id) => format!("copy:{}", id),
+            StrategySource::Auto(id) => format!("auto:{}", id),
+        }
+    }
+
+    pub fn is_automated(&self) -> bool {
+        !matches!(self, StrategySource::Manual)
+    }
+}
+
+fn is_automated_tag(tag: &str) -> bool {
+    StrategySource::parse(tag)
+        .map(|source| source.is_automated())
+        .unwrap_or(false)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SourcePerformance {
+    pub strategy_source: String,
+    pub automated: bool,
+    pub total_trades: i64,
+    pub closed_trades: i64,
+    pub winning_trades: i64,
+    pub losing_trades: i64,
+    pub win_rate: f64,
+    pub net_pnl: f64,
+    pub total_volume: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SourcePerformanceReport {
+    pub wallet_address: String,
+    pub window_days: Option<u32>,
+    pub sources: Vec<SourcePerformance>,
+    pub automated_pnl: f64,
+    pub manual_pnl: f64,
+    pub automation_share: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TradeBackfillReport {
+    pub inserted: usize,
+    pub skipped: usize,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -180,6 +307,47 @@ pub struct WalletPerformanceData {
     pub benchmark: Option<BenchmarkComparison>,
 }
 
+/// Value-bearing fields across the performance report types; ratios, rates
+/// and scores stay visible.
+const PERFORMANCE_MASKED_FIELDS: &[&str] = &[
+    "totalProfit",
+    "totalLoss",
+    "netPnl",
+    "avgProfitPerTrade",
+    "avgLossPerTrade",
+    "bestTradePnl",
+    "worstTradePnl",
+    "totalVolume",
+    "avgPnl",
+    "amount",
+    "totalValue",
+    "fee",
+    "pnl",
+    "automatedPnl",
+    "manualPnl",
+    "maxDrawdown",
+    "totalFees",
+];
+
+impl PrivacyMasked for PerformanceScore {
+    const MASKED_FIELDS: &'static [&'static str] = PERFORMANCE_MASKED_FIELDS;
+    const WALLET_KEY: Option<&'static str> = Some("walletAddress");
+}
+
+impl PrivacyMasked for TokenPerformance {
+    const MASKED_FIELDS: &'static [&'static str] = PERFORMANCE_MASKED_FIELDS;
+}
+
+impl PrivacyMasked for SourcePerformanceReport {
+    const MASKED_FIELDS: &'static [&'static str] = PERFORMANCE_MASKED_FIELDS;
+    const WALLET_KEY: Option<&'static str> = Some("walletAddress");
+}
+
+impl PrivacyMasked for WalletPerformanceData {
+    const MASKED_FIELDS: &'static [&'static str] = PERFORMANCE_MASKED_FIELDS;
+    const WALLET_KEY: Option<&'static str> = Some("walletAddress");
+}
+
 pub struct PerformanceDatabase {
     pool: Pool<Sqlite>,
 }
@@ -299,12 +467,67 @@ impl PerformanceDatabase {
         .execute(&self.pool)
         .await?;
 
+        ensure_column(
+            &self.pool,
+            "trades",
+            "strategy_source",
+            "TEXT NOT NULL DEFAULT 'manual'",
+        )
+        .await?;
+        ensure_column(&self.pool, "trades", "external_id", "TEXT").await?;
+        ensure_column(&self.pool, "trades", "external_source", "TEXT").await?;
+        ensure_column(
+            &self.pool,
+            "performance_scores",
+            "automated_pnl",
+            "REAL NOT NULL DEFAULT 0",
+        )
+        .await?;
+        ensure_column(
+            &self.pool,
+            "performance_scores",
+            "automation_share",
+            "REAL NOT NULL DEFAULT 0",
+        )
+        .await?;
+        ensure_column(&self.pool, "performance_scores", "max_drawdown", "REAL").await?;
+        ensure_column(&self.pool, "performance_scores", "total_fees", "REAL").await?;
+        ensure_column(&self.pool, "performance_scores", "components", "TEXT").await?;
+
+        sqlx::query(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_trades_source ON trades(wallet_address, strategy_source);
+            CREATE UNIQUE INDEX IF NOT EXISTS idx_trades_external_id ON trades(external_id)
+                WHERE external_id IS NOT NULL;
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
         Ok(())
     }
 
+    async fn trade_by_external_id(&self, external_id: &str) -> Result<Option<Trade>, sqlx::Error> {
+        sqlx::query_as::<_, Trade>("SELECT * FROM trades WHERE external_id = ?1")
+            .bind(external_id)
+            .fetch_optional(&self.pool)
+            .await
+    }
+
     pub async fn record_trade(&self, request: RecordTradeRequest) -> Result<Trade, sqlx::Error> {
+        let strategy_source =
+            StrategySource::parse(request.strategy_source.as_deref().unwrap_or("manual"))
+                .map_err(sqlx::Error::Protocol)?
+                .tag();
+
+        if let Some(external_id) = request.external_id.as_deref() {
+            if let Some(existing) = self.trade_by_external_id(external_id).await? {
+                return Ok(existing);
+            }
+        }
+
         let id = format!("trade_{}", uuid::Uuid::new_v4());
-        let timestamp = Utc::now();
+        let timestamp = request.executed_at.unwrap_or_else(Utc::now);
         let total_value = request.amount * request.price;
 
         // Calculate PnL for sell trades by finding matching buy
@@ -335,6 +558,7 @@ impl PerformanceDatabase {
             timestamp,
             pnl,
             hold_duration_seconds: hold_duration,
+            strategy_source,
         };
 
         sqlx::query(
@@ -342,9 +566,9 @@ impl PerformanceDatabase {
             INSERT INTO trades (
                 id, wallet_address, token_mint, token_symbol, side,
                 amount, price, total_value, fee, tx_signature, timestamp,
-                pnl, hold_duration_seconds
+                pnl, hold_duration_seconds, strategy_source, external_id, external_source
             ) VALUES (
-                ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13
+                ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16
             )
             "#,
         )
@@ -361,12 +585,53 @@ impl PerformanceDatabase {
         .bind(trade.timestamp.to_rfc3339())
         .bind(trade.pnl)
         .bind(trade.hold_duration_seconds)
+        .bind(&trade.strategy_source)
+        .bind(&request.external_id)
+        .bind(&request.external_source)
         .execute(&self.pool)
         .await?;
 
         Ok(trade)
     }
 
+    /// Records historical trades oldest first so sell P&L matches the buys
+    /// that preceded it. Trades whose external id is already stored are
+    /// skipped, which makes repeated runs a no-op.
+    pub async fn backfill_trades(
+        &self,
+        mut requests: Vec<RecordTradeRequest>,
+    ) -> Result<TradeBackfillReport, sqlx::Error> {
+        requests.sort_by_key(|request| request.executed_at);
+
+        let mut report = TradeBackfillReport::default();
+        for request in requests {
+            if let Some(external_id) = request.external_id.as_deref() {
+                if self.trade_by_external_id(external_id).await?.is_some() {
+                    report.skipped += 1;
+                    continue;
+                }
+            }
+            self.record_trade(request).await?;
+            report.inserted += 1;
+        }
+
+        Ok(report)
+    }
+
+    /// Deletes trades whose external id starts with `prefix`, such as every
+    /// trade recorded by one exchange import batch.
+    pub async fn delete_trades_by_external_prefix(
+        &self,
+        prefix: &str,
+    ) -> Result<u64, sqlx::Error> {
+        let result =
+            sqlx::query("DELETE FROM trades WHERE substr(external_id, 1, length(?1)) = ?1")
+                .bind(prefix)
+                .execute(&self.pool)
+                .await?;
+        Ok(result.rows_affected())
+    }
+
     async fn calculate_pnl(
         &self,
         wallet_address: &str,
@@ -406,6 +671,7 @@ impl PerformanceDatabase {
     pub async fn calculate_performance_score(
         &self,
         wallet_address: &str,
+        weights: &PerformanceScoreWeights,
     ) -> Result<PerformanceScore, sqlx::Error> {
         let trades = sqlx::query_as::<_, Trade>(
             r#"
@@ -488,8 +754,11 @@ impl PerformanceDatabase {
         };
 
         let all_pnls: Vec<f64> = completed_trades.iter().filter_map(|t| t.pnl).collect();
-        let sharpe_ratio = self.calculate_sharpe_ratio(&all_pnls);
-        let consistency_score = self.calculate_consistency_score(&all_pnls);
+        let sharpe_ratio = performance_score::sharpe_ratio(&all_pnls);
+        let consistency_score = performance_score::consistency_score(&all_pnls);
+        // Trades are fetched oldest first, so this walks the equity curve.
+        let max_drawdown = performance_score::max_drawdown(&all_pnls);
+        let total_fees = trades.iter().map(|t| t.fee).sum::<f64>();
 
         let avg_hold_duration = if !completed_trades.is_empty() {
             completed_trades
@@ -504,14 +773,12 @@ impl PerformanceDatabase {
         let best_trade_pnl = all_pnls.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
         let worst_trade_pnl = all_pnls.iter().cloned().fold(f64::INFINITY, f64::min);
 
-        // Calculate overall score (0-100)
-        let score =
-            self.calculate_overall_score(win_rate, profit_factor, sharpe_ratio, consistency_score);
+        let (automated_pnl, automation_share) = automation_split(&completed_trades);
 
-        let performance = PerformanceScore {
+        let mut performance = PerformanceScore {
             id: 0,
             wallet_address: wallet_address.to_string(),
-            score,
+            score: 0.0,
             win_rate,
             total_trades,
             winning_trades,
@@ -527,11 +794,18 @@ impl PerformanceDatabase {
             avg_hold_duration_seconds: avg_hold_duration,
             best_trade_pnl,
             worst_trade_pnl,
+            automated_pnl,
+            automation_share,
+            max_drawdown: Some(max_drawdown),
+            total_fees: Some(total_fees),
+            components: Vec::new(),
             calculated_at: Utc::now(),
         };
+        performance_score::apply_weights(&mut performance, weights);
 
         self.save_performance_score(&performance).await?;
-        self.check_and_create_alert(wallet_address, score).await?;
+        self.check_and_create_alert(wallet_address, performance.score)
+            .await?;
 
         Ok(performance)
     }
@@ -556,75 +830,15 @@ impl PerformanceDatabase {
             avg_hold_duration_seconds: 0.0,
             best_trade_pnl: 0.0,
             worst_trade_pnl: 0.0,
+            automated_pnl: 0.0,
+            automation_share: 0.0,
+            max_drawdown: None,
+            total_fees: None,
+            components: Vec::new(),
             calculated_at: Utc::now(),
         }
     }
 
-    fn calculate_sharpe_ratio(&self, pnls: &[f64]) -> f64 {
-        if pnls.len() < 2 {
-            return 0.0;
-        }
-
-        let mean = pnls.iter().sum::<f64>() / pnls.len() as f64;
-        let variance =
-            pnls.iter().map(|pnl| (pnl - mean).powi(2)).sum::<f64>() / (pnls.len() - 1) as f64;
-        let std_dev = variance.sqrt();
-
-        if std_dev == 0.0 {
-            return 0.0;
-        }
-
-        // Annualized Sharpe ratio (assuming risk-free rate of 0)
-        let sharpe = mean / std_dev * (252.0_f64).sqrt();
-        sharpe.max(-5.0).min(5.0) // Clamp between -5 and 5
-    }
-
-    fn calculate_consistency_score(&self, pnls: &[f64]) -> f64 {
-        if pnls.len() < 2 {
-            return 50.0;
-        }
-
-        let mean = pnls.iter().sum::<f64>() / pnls.len() as f64;
-        let variance =
-            pnls.iter().map(|pnl| (pnl - mean).powi(2)).sum::<f64>() / (pnls.len() - 1) as f64;
-        let std_dev = variance.sqrt();
-
-        let coefficient_of_variation = if mean.abs() > 0.0 {
-            (std_dev / mean.abs()).abs()
-        } else {
-            10.0
-        };
-
-        let consistency = 100.0 / (1.0 + coefficient_of_variation);
-        consistency.max(0.0).min(100.0)
-    }
-
-    fn calculate_overall_score(
-        &self,
-        win_rate: f64,
-        profit_factor: f64,
-        sharpe_ratio: f64,
-        consistency_score: f64,
-    ) -> f64 {
-        let win_rate_component = (win_rate / 100.0) * 30.0;
-
-        let profit_factor_normalized = if profit_factor > 3.0 {
-            1.0
-        } else {
-            profit_factor / 3.0
-        };
-        let profit_component = profit_factor_normalized * 30.0;
-
-        let sharpe_normalized = ((sharpe_ratio + 5.0) / 10.0).max(0.0).min(1.0);
-        let sharpe_component = sharpe_normalized * 20.0;
-
-        let consistency_component = (consistency_score / 100.0) * 20.0;
-
-        let score =
-            win_rate_component + profit_component + sharpe_component + consistency_component;
-        score.max(0.0).min(100.0)
-    }
-
     async fn save_performance_score(&self, score: &PerformanceScore) -> Result<(), sqlx::Error> {
         sqlx::query(
             r#"
@@ -632,9 +846,11 @@ impl PerformanceDatabase {
                 wallet_address, score, win_rate, total_trades, winning_trades, losing_trades,
                 total_profit, total_loss, net_pnl, avg_profit_per_trade, avg_loss_per_trade,
                 profit_factor, sharpe_ratio, consistency_score, avg_hold_duration_seconds,
-                best_trade_pnl, worst_trade_pnl, calculated_at
+                best_trade_pnl, worst_trade_pnl, automated_pnl, automation_share, max_drawdown,
+                total_fees, components, calculated_at
             ) VALUES (
-                ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18
+                ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18,
+                ?19, ?20, ?21, ?22, ?23
             )
             "#,
         )
@@ -655,6 +871,11 @@ impl PerformanceDatabase {
         .bind(score.avg_hold_duration_seconds)
         .bind(score.best_trade_pnl)
         .bind(score.worst_trade_pnl)
+        .bind(score.automated_pnl)
+        .bind(score.automation_share)
+        .bind(score.max_drawdown)
+        .bind(score.total_fees)
+        .bind(serde_json::to_string(&score.components).unwrap_or_else(|_| "[]".to_string()))
         .bind(score.calculated_at.to_rfc3339())
         .execute(&self.pool)
         .await?;
@@ -823,6 +1044,53 @@ impl PerformanceDatabase {
         })
     }
 
+    pub async fn get_trades_between(
+        &self,
+        wallet_address: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<Trade>, sqlx::Error> {
+        sqlx::query_as::<_, Trade>(
+            r#"
+            SELECT * FROM trades
+            WHERE wallet_address = ?1 AND timestamp >= ?2 AND timestamp <= ?3
+            ORDER BY timestamp ASC
+            "#,
+        )
+        .bind(wallet_address)
+        .bind(from.to_rfc3339())
+        .bind(to.to_rfc3339())
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// One page of [`Self::get_trades_between`], for callers that walk the
+    /// whole window without holding it in memory.
+    pub async fn get_trades_page(
+        &self,
+        wallet_address: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        offset: i64,
+        limit: i64,
+    ) -> Result<Vec<Trade>, sqlx::Error> {
+        sqlx::query_as::<_, Trade>(
+            r#"
+            SELECT * FROM trades
+            WHERE wallet_address = ?1 AND timestamp >= ?2 AND timestamp <= ?3
+            ORDER BY timestamp ASC, id ASC
+            LIMIT ?4 OFFSET ?5
+            "#,
+        )
+        .bind(wallet_address)
+        .bind(from.to_rfc3339())
+        .bind(to.to_rfc3339())
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await
+    }
+
     pub async fn get_timing_analysis(
         &self,
         wallet_address: &str,
@@ -945,11 +1213,122 @@ impl PerformanceDatabase {
         .await
     }
 
+    /// Aggregates trades per strategy source, optionally limited to the last
+    /// `window_days` days.
+    pub async fn get_performance_by_source(
+        &self,
+        wallet_address: &str,
+        window_days: Option<u32>,
+    ) -> Result<SourcePerformanceReport, sqlx::Error> {
+        let since = window_days
+            .map(|days| (Utc::now() - chrono::Duration::days(days as i64)).to_rfc3339())
+            .unwrap_or_default();
+
+        let rows = sqlx::query(
+            r#"
+            SELECT
+                strategy_source,
+                COUNT(*) as total_trades,
+                SUM(CASE WHEN pnl IS NOT NULL THEN 1 ELSE 0 END) as closed_trades,
+                SUM(CASE WHEN pnl > 0 THEN 1 ELSE 0 END) as winning_trades,
+                SUM(CASE WHEN pnl < 0 THEN 1 ELSE 0 END) as losing_trades,
+                COALESCE(SUM(pnl), 0) as net_pnl,
+                COALESCE(SUM(total_value), 0) as total_volume
+            FROM trades
+            WHERE wallet_address = ?1 AND timestamp >= ?2
+            GROUP BY strategy_source
+            ORDER BY net_pnl DESC
+            "#,
+        )
+        .bind(wallet_address)
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut sources = Vec::with_capacity(rows.len());
+        let mut automated_pnl = 0.0;
+        let mut manual_pnl = 0.0;
+        let mut automated_abs = 0.0;
+        let mut total_abs = 0.0;
+
+        for row in rows {
+            let strategy_source: String = row.get("strategy_source");
+            let closed_trades: i64 = row.get("closed_trades");
+            let winning_trades: i64 = row.get("winning_trades");
+            let net_pnl: f64 = row.get("net_pnl");
+            let automated = is_automated_tag(&strategy_source);
+
+            if automated {
+                automated_pnl += net_pnl;
+                automated_abs += net_pnl.abs();
+            } else {
+                manual_pnl += net_pnl;
+            }
+            total_abs += net_pnl.abs();
+
+            sources.push(SourcePerformance {
+                strategy_source,
+                automated,
+                total_trades: row.get("total_trades"),
+                closed_trades,
+                winning_trades,
+                losing_trades: row.get("losing_trades"),
+                win_rate: if closed_trades > 0 {
+                    (winning_trades as f64 / closed_trades as f64) * 100.0
+                } else {
+                    0.0
+                },
+                net_pnl,
+                total_volume: row.get("total_volume"),
+            });
+        }
+
+        Ok(SourcePerformanceReport {
+            wallet_address: wallet_address.to_string(),
+            window_days,
+            sources,
+            automated_pnl,
+            manual_pnl,
+            automation_share: if total_abs > 0.0 {
+                automated_abs / total_abs
+            } else {
+                0.0
+            },
+        })
+    }
+
+    /// Realized P&L per strategy source tag across every wallet, for trades
+    /// closed since `since`.
+    pub async fn get_realized_pnl_by_source(
+        &self,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<(String, f64)>, sqlx::Error> {
+        let rows = sqlx::query(
+            r#"
+            SELECT strategy_source, COALESCE(SUM(pnl), 0) as net_pnl
+            FROM trades
+            WHERE pnl IS NOT NULL AND timestamp >= ?1
+            GROUP BY strategy_source
+            "#,
+        )
+        .bind(since.to_rfc3339())
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| (row.get("strategy_source"), row.get("net_pnl")))
+            .collect())
+    }
+
     pub async fn get_wallet_performance(
         &self,
         wallet_address: &str,
+        weights: &PerformanceScoreWeights,
     ) -> Result<WalletPerformanceData, sqlx::Error> {
-        let score = self.calculate_performance_score(wallet_address).await?;
+        let score = self
+            .calculate_performance_score(wallet_address, weights)
+            .await?;
         let score_history = self.get_score_history(wallet_address, 30).await?;
         let token_performance = self.get_token_performance(wallet_address).await?;
         let timing_analysis = self.get_timing_analysis(wallet_address).await?;
@@ -982,8 +1361,64 @@ impl<'r> sqlx::FromRow<'r, sqlx::sqlite::SqliteRow> for ScoreAlert {
     }
 }
 
+/// Splits realized P&L into the automated total and the automated share of
+/// absolute P&L, so a bot that lost money still counts towards the share.
+fn automation_split(completed_trades: &[&Trade]) -> (f64, f64) {
+    let mut automated_pnl = 0.0;
+    let mut automated_abs = 0.0;
+    let mut total_abs = 0.0;
+
+    for trade in completed_trades {
+        let pnl = trade.pnl.unwrap_or(0.0);
+        if is_automated_tag(&trade.strategy_source) {
+            automated_pnl += pnl;
+            automated_abs += pnl.abs();
+        }
+        total_abs += pnl.abs();
+    }
+
+    let share = if total_abs > 0.0 {
+        automated_abs / total_abs
+    } else {
+        0.0
+    };
+    (automated_pnl, share)
+}
+
 pub type SharedPerformanceDatabase = Arc<RwLock<PerformanceDatabase>>;
 
+/// Records a trade placed by a bot. Failures are logged rather than returned
+/// so performance bookkeeping never fails the execution itself.
+pub async fn record_bot_trade(app: &AppHandle, request: RecordTradeRequest) {
+    let Some(db) = app.try_state::<SharedPerformanceDatabase>() else {
+        return;
+    };
+    let source = request.strategy_source.clone().unwrap_or_default();
+    let db = db.read().await;
+    if let Err(e) = db.record_trade(request).await {
+        eprintln!(
+            "Failed to record {} trade in performance database: {}",
+            source, e
+        );
+    }
+}
+
+/// Copies successful DCA and copy-trading executions into the performance
+/// database. Safe to run on every start.
+pub async fn backfill_bot_history(app: &AppHandle) -> Result<TradeBackfillReport, String> {
+    let db = app
+        .try_state::<SharedPerformanceDatabase>()
+        .ok_or_else(|| "Performance database not initialized".to_string())?;
+
+    let mut requests = crate::bots::dca_performance_history().await?;
+    requests.extend(crate::trading::copy_trade_performance_history().await?);
+
+    let db = db.read().await;
+    db.backfill_trades(requests)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn record_trade(
     request: RecordTradeRequest,
@@ -993,26 +1428,70 @@ pub async fn record_trade(
     db.record_trade(request).await.map_err(|e| e.to_string())
 }
 
+async fn score_weights(settings: &SharedSettingsManager) -> PerformanceScoreWeights {
+    settings
+        .read()
+        .await
+        .get_all_settings()
+        .trading
+        .performance_score_weights
+}
+
 #[tauri::command]
 pub async fn calculate_wallet_performance(
     wallet_address: String,
     db: State<'_, SharedPerformanceDatabase>,
-) -> Result<PerformanceScore, String> {
+    settings: State<'_, SharedSettingsManager>,
+    privacy: State<'_, SharedPrivacyMode>,
+) -> Result<Masked<PerformanceScore>, String> {
+    let weights = score_weights(&settings).await;
     let db = db.read().await;
-    db.calculate_performance_score(&wallet_address)
+    let score = db
+        .calculate_performance_score(&wallet_address, &weights)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(|e| e.to_string())?;
+    let privacy = privacy.read().map_err(|e| e.to_string())?;
+    Ok(privacy.apply(score))
+}
+
+#[tauri::command]
+pub async fn get_performance_by_source(
+    wallet_address: String,
+    window_days: Option<u32>,
+    db: State<'_, SharedPerformanceDatabase>,
+    privacy: State<'_, SharedPrivacyMode>,
+) -> Result<Masked<SourcePerformanceReport>, String> {
+    let db = db.read().await;
+    let report = db
+        .get_performance_by_source(&wallet_address, window_days)
+        .await
+        .map_err(|e| e.to_string())?;
+    let privacy = privacy.read().map_err(|e| e.to_string())?;
+    Ok(privacy.apply_for_wallet(report, &wallet_address))
+}
+
+#[tauri::command]
+pub async fn backfill_bot_performance_history(
+    app: AppHandle,
+) -> Result<TradeBackfillReport, String> {
+    backfill_bot_history(&app).await
 }
 
 #[tauri::command]
 pub async fn get_wallet_performance_data(
     wallet_address: String,
     db: State<'_, SharedPerformanceDatabase>,
-) -> Result<WalletPerformanceData, String> {
+    settings: State<'_, SharedSettingsManager>,
+    privacy: State<'_, SharedPrivacyMode>,
+) -> Result<Masked<WalletPerformanceData>, String> {
+    let weights = score_weights(&settings).await;
     let db = db.read().await;
-    db.get_wallet_performance(&wallet_address)
+    let data = db
+        .get_wallet_performance(&wallet_address, &weights)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(|e| e.to_string())?;
+    let privacy = privacy.read().map_err(|e| e.to_string())?;
+    Ok(privacy.apply_for_wallet(data, &wallet_address))
 }
 
 #[tauri::command]
@@ -1020,22 +1499,81 @@ pub async fn get_performance_score_history(
     wallet_address: String,
     limit: i64,
     db: State<'_, SharedPerformanceDatabase>,
-) -> Result<Vec<PerformanceScore>, String> {
+    privacy: State<'_, SharedPrivacyMode>,
+) -> Result<Masked<Vec<PerformanceScore>>, String> {
     let db = db.read().await;
-    db.get_score_history(&wallet_address, limit)
+    let history = db
+        .get_score_history(&wallet_address, limit)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(|e| e.to_string())?;
+    let privacy = privacy.read().map_err(|e| e.to_string())?;
+    Ok(privacy.apply_for_wallet(history, &wallet_address))
+}
+
+/// Saves new score weights. Scores already stored keep the weights they were
+/// calculated with; use [`recompute_score_history`] to compare.
+#[tauri::command]
+pub async fn update_performance_score_weights(
+    weights: PerformanceScoreWeights,
+    settings: State<'_, SharedSettingsManager>,
+) -> Result<PerformanceScoreWeights, String> {
+    weights.validate()?;
+    let value = serde_json::to_value(weights).map_err(|e| e.to_string())?;
+    settings
+        .write()
+        .await
+        .update_setting(
+            "trading".to_string(),
+            "performanceScoreWeights".to_string(),
+            value,
+        )
+        .map_err(|e| e.to_string())?;
+    Ok(weights)
+}
+
+/// Re-weights the stored score history without saving it, so a methodology
+/// change can be previewed against past scores. Defaults to the saved weights.
+#[tauri::command]
+pub async fn recompute_score_history(
+    wallet_address: String,
+    weights: Option<PerformanceScoreWeights>,
+    limit: i64,
+    db: State<'_, SharedPerformanceDatabase>,
+    settings: State<'_, SharedSettingsManager>,
+    privacy: State<'_, SharedPrivacyMode>,
+) -> Result<Masked<Vec<PerformanceScore>>, String> {
+    let weights = match weights {
+        Some(weights) => {
+            weights.validate()?;
+            weights
+        }
+        None => score_weights(&settings).await,
+    };
+    let db = db.read().await;
+    let mut history = db
+        .get_score_history(&wallet_address, limit)
+        .await
+        .map_err(|e| e.to_string())?;
+    for score in &mut history {
+        performance_score::apply_weights(score, &weights);
+    }
+    let privacy = privacy.read().map_err(|e| e.to_string())?;
+    Ok(privacy.apply_for_wallet(history, &wallet_address))
 }
 
 #[tauri::command]
 pub async fn get_token_performance_breakdown(
     wallet_address: String,
     db: State<'_, SharedPerformanceDatabase>,
-) -> Result<Vec<TokenPerformance>, String> {
+    privacy: State<'_, SharedPrivacyMode>,
+) -> Result<Masked<Vec<TokenPerformance>>, String> {
     let db = db.read().await;
-    db.get_token_performance(&wallet_address)
+    let breakdown = db
+        .get_token_performance(&wallet_address)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(|e| e.to_string())?;
+    let privacy = privacy.read().map_err(|e| e.to_string())?;
+    Ok(privacy.apply_for_wallet(breakdown, &wallet_address))
 }
 
 #[tauri::command]
@@ -1083,3 +1621,158 @@ pub async fn get_performance_alerts(
         .await
         .map_err(|e| e.to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_pool;
+
+    async fn database() -> PerformanceDatabase {
+        let pool = test_pool().await;
+        let db = PerformanceDatabase { pool };
+        db.initialize().await.unwrap();
+        db
+    }
+
+    fn trade(
+        side: &str,
+        price: f64,
+        source: &str,
+        external_id: Option<&str>,
+        minutes_ago: i64,
+    ) -> RecordTradeRequest {
+        RecordTradeRequest {
+            wallet_address: "wallet".to_string(),
+            token_mint: "mint".to_string(),
+            token_symbol: "TKN".to_string(),
+            side: side.to_string(),
+            amount: 10.0,
+            price,
+            fee: 0.0,
+            tx_signature: "sig".to_string(),
+            strategy_source: Some(source.to_string()),
+            external_id: external_id.map(str::to_string),
+            external_source: None,
+            executed_at: Some(Utc::now() - chrono::Duration::minutes(minutes_ago)),
+        }
+    }
+
+    #[test]
+    fn test_strategy_source_tags() {
+        assert_eq!(StrategySource::parse("").unwrap(), StrategySource::Manual);
+        assert_eq!(
+            StrategySource::parse("dca:abc").unwrap(),
+            StrategySource::Dca("abc".to_string())
+        );
+        assert_eq!(StrategySource::parse("copy:cfg").unwrap().tag(), "copy:cfg");
+        assert!(StrategySource::parse("auto:s1").unwrap().is_automated());
+        assert!(StrategySource::parse("dca:").is_err());
+        assert!(StrategySource::parse("grid:1").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_backfill_is_idempotent() {
+        let db = database().await;
+        let history = vec![
+            trade("sell", 2.0, "dca:plan", Some("dca:2"), 5),
+            trade("buy", 1.0, "dca:plan", Some("dca:1"), 10),
+        ];
+
+        let first = db.backfill_trades(history.clone()).await.unwrap();
+        assert_eq!(first.inserted, 2);
+        let second = db.backfill_trades(history).await.unwrap();
+        assert_eq!(second.inserted, 0);
+        assert_eq!(second.skipped, 2);
+
+        // Oldest first, so the sell found its buy.
+        let report = db.get_performance_by_source("wallet", None).await.unwrap();
+        assert_eq!(report.sources.len(), 1);
+        assert_eq!(report.sources[0].total_trades, 2);
+        assert!((report.sources[0].net_pnl - 10.0).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_automation_share() {
+        let db = database().await;
+        db.record_trade(trade("buy", 1.0, "manual", None, 40))
+            .await
+            .unwrap();
+        db.record_trade(trade("sell", 2.0, "manual", None, 30))
+            .await
+            .unwrap();
+        db.record_trade(trade("buy", 2.0, "copy:cfg", Some("copy:1"), 20))
+            .await
+            .unwrap();
+        db.record_trade(trade("sell", 5.0, "copy:cfg", Some("copy:2"), 10))
+            .await
+            .unwrap();
+
+        let score = db
+            .calculate_performance_score("wallet", &PerformanceScoreWeights::default())
+            .await
+            .unwrap();
+        assert!((score.automated_pnl - 30.0).abs() < 1e-9);
+        assert!((score.automation_share - 0.75).abs() < 1e-9);
+
+        let report = db
+            .get_performance_by_source("wallet", Some(1))
+            .await
+            .unwrap();
+        assert!((report.manual_pnl - 10.0).abs() < 1e-9);
+        assert!((report.automation_share - 0.75).abs() < 1e-9);
+
+        let invalid = db.record_trade(trade("buy", 1.0, "grid:1", None, 0)).await;
+        assert!(invalid.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_score_weights_and_components() {
+        let db = database().await;
+        // Round trips with P&L 100, -80, 60, -90 and 30; cumulative P&L peaks
+        // at 100 and bottoms at -10.
+        let prices = [10.0, 20.0, 20.0, 12.0, 12.0, 18.0, 18.0, 9.0, 9.0, 12.0];
+        for (i, price) in prices.into_iter().enumerate() {
+            let side = if i % 2 == 0 { "buy" } else { "sell" };
+            let mut request = trade(side, price, "manual", None, 100 - i as i64);
+            request.fee = 1.9;
+            db.record_trade(request).await.unwrap();
+        }
+
+        let original = db
+            .calculate_performance_score("wallet", &PerformanceScoreWeights::default())
+            .await
+            .unwrap();
+        assert!((original.score - 41.568605).abs() < 1e-6);
+        assert_eq!(original.max_drawdown, Some(110.0));
+        assert!((original.total_fees.unwrap() - 19.0).abs() < 1e-9);
+
+        let equal = PerformanceScoreWeights {
+            win_rate: 1.0,
+            profit_factor: 1.0,
+            sharpe_ratio: 1.0,
+            drawdown: 1.0,
+            consistency: 1.0,
+            fee_efficiency: 1.0,
+        };
+        let score = db
+            .calculate_performance_score("wallet", &equal)
+            .await
+            .unwrap();
+        let normalized: Vec<f64> = score
+            .components
+            .iter()
+            .map(|c| c.normalized_value)
+            .collect();
+        let expected = [0.6, 0.372549, 0.574678, 0.044929, 0.421053, 0.9];
+        for (value, expected) in normalized.iter().zip(expected) {
+            assert!((value - expected).abs() < 1e-6);
+        }
+        assert!((score.score - 48.553473).abs() < 1e-6);
+
+        // The stored breakdown survives a reload and re-weights back to the original.
+        let mut history = db.get_score_history("wallet", 1).await.unwrap();
+        assert_eq!(history[0].components, score.components);
+        performance_score::apply_weights(&mut history[0], &PerformanceScoreWeights::default());
+        assert!((history[0].score - original.score).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,312 @@
+//! The wallet performance score.
+//!
+//! The score is a weighted composite of six components, each normalized to
+//! 0-1 where higher is better. Weights live in the trading settings and only
+//! their ratios matter. Every score carries its component breakdown, and a
+//! stored score can be re-weighted from its raw values without touching the
+//! stored series.
+
+use super::performance::PerformanceScore;
+use crate::config::settings_schema::PerformanceScoreWeights;
+use serde::{Deserialize, Serialize};
+
+/// Profit factor at which the component reaches its maximum.
+const PROFIT_FACTOR_CAP: f64 = 3.0;
+/// Sharpe-like ratios are clamped to plus or minus this before normalizing.
+const SHARPE_CLAMP: f64 = 5.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ScoreComponentKind {
+    WinRate,
+    ProfitFactor,
+    SharpeRatio,
+    Drawdown,
+    Consistency,
+    FeeEfficiency,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScoreComponent {
+    pub component: ScoreComponentKind,
+    /// Percent for win rate, consistency, drawdown and fees (the latter two
+    /// as a share of gross profit); a plain ratio for the others.
+    pub raw_value: f64,
+    /// 0-1, higher is better.
+    pub normalized_value: f64,
+    /// Share of the total weight of the components present, 0-1.
+    pub weight: f64,
+    /// Points this component adds to the 0-100 score.
+    pub contribution: f64,
+}
+
+impl PerformanceScoreWeights {
+    pub fn validate(&self) -> Result<(), String> {
+        let weights = [
+            self.win_rate,
+            self.profit_factor,
+            self.sharpe_ratio,
+            self.drawdown,
+            self.consistency,
+            self.fee_efficiency,
+        ];
+        if weights.iter().any(|w| !w.is_finite() || *w < 0.0) {
+            return Err("Score weights must be zero or positive".to_string());
+        }
+        if weights.iter().sum::<f64>() <= 0.0 {
+            return Err("At least one score weight must be positive".to_string());
+        }
+        Ok(())
+    }
+
+    fn weight(&self, component: ScoreComponentKind) -> f64 {
+        match component {
+            ScoreComponentKind::WinRate => self.win_rate,
+            ScoreComponentKind::ProfitFactor => self.profit_factor,
+            ScoreComponentKind::SharpeRatio => self.sharpe_ratio,
+            ScoreComponentKind::Drawdown => self.drawdown,
+            ScoreComponentKind::Consistency => self.consistency,
+            ScoreComponentKind::FeeEfficiency => self.fee_efficiency,
+        }
+    }
+}
+
+/// The components of `score` under `weights`. Scores saved before drawdown
+/// and fees were tracked lack those two components, and the remaining
+/// weights are rescaled to cover the whole score.
+pub fn score_components(
+    score: &PerformanceScore,
+    weights: &PerformanceScoreWeights,
+) -> Vec<ScoreComponent> {
+    let mut measured = vec![
+        (
+            ScoreComponentKind::WinRate,
+            score.win_rate,
+            score.win_rate / 100.0,
+        ),
+        (
+            ScoreComponentKind::ProfitFactor,
+            score.profit_factor,
+            score.profit_factor / PROFIT_FACTOR_CAP,
+        ),
+        (
+            ScoreComponentKind::SharpeRatio,
+            score.sharpe_ratio,
+            (score.sharpe_ratio + SHARPE_CLAMP) / (2.0 * SHARPE_CLAMP),
+        ),
+        (
+            ScoreComponentKind::Consistency,
+            score.consistency_score,
+            score.consistency_score / 100.0,
+        ),
+    ];
+    if let Some(drawdown) = score.max_drawdown {
+        let pct = share_of_profit(drawdown, score.total_profit);
+        measured.push((ScoreComponentKind::Drawdown, pct, 1.0 - pct / 100.0));
+    }
+    if let Some(fees) = score.total_fees {
+        let pct = share_of_profit(fees, score.total_profit);
+        measured.push((ScoreComponentKind::FeeEfficiency, pct, 1.0 - pct / 100.0));
+    }
+
+    let total_weight: f64 = measured
+        .iter()
+        .map(|(component, _, _)| weights.weight(*component))
+        .sum();
+    measured
+        .into_iter()
+        .map(|(component, raw_value, normalized)| {
+            let normalized_value = normalized.clamp(0.0, 1.0);
+            let weight = if total_weight > 0.0 {
+                weights.weight(component) / total_weight
+            } else {
+                0.0
+            };
+            ScoreComponent {
+                component,
+                raw_value,
+                normalized_value,
+                weight,
+                contribution: normalized_value * weight * 100.0,
+            }
+        })
+        .collect()
+}
+
+/// Sets `score.components` and `score.score` from `weights`.
+pub fn apply_weights(score: &mut PerformanceScore, weights: &PerformanceScoreWeights) {
+    score.components = score_components(score, weights);
+    score.score = score
+        .components
+        .iter()
+        .map(|component| component.contribution)
+        .sum::<f64>()
+        .clamp(0.0, 100.0);
+}
+
+/// `amount` as a percentage of gross profit; 100% when there was no profit
+/// to set it against.
+fn share_of_profit(amount: f64, total_profit: f64) -> f64 {
+    if total_profit > 0.0 {
+        amount / total_profit * 100.0
+    } else if amount > 0.0 {
+        100.0
+    } else {
+        0.0
+    }
+}
+
+fn mean_and_std_dev(pnls: &[f64]) -> (f64, f64) {
+    let mean = pnls.iter().sum::<f64>() / pnls.len() as f64;
+    let variance =
+        pnls.iter().map(|pnl| (pnl - mean).powi(2)).sum::<f64>() / (pnls.len() - 1) as f64;
+    (mean, variance.sqrt())
+}
+
+/// Mean over sample standard deviation of per-trade P&L, annualized as if
+/// trades were daily returns and clamped to plus or minus 5.
+pub fn sharpe_ratio(pnls: &[f64]) -> f64 {
+    if pnls.len() < 2 {
+        return 0.0;
+    }
+
+    let (mean, std_dev) = mean_and_std_dev(pnls);
+    if std_dev == 0.0 {
+        return 0.0;
+    }
+
+    // Risk-free rate of 0
+    let sharpe = mean / std_dev * (252.0_f64).sqrt();
+    sharpe.clamp(-SHARPE_CLAMP, SHARPE_CLAMP)
+}
+
+/// 100 / (1 + coefficient of variation) of per-trade P&L.
+pub fn consistency_score(pnls: &[f64]) -> f64 {
+    if pnls.len() < 2 {
+        return 50.0;
+    }
+
+    let (mean, std_dev) = mean_and_std_dev(pnls);
+    let coefficient_of_variation = if mean.abs() > 0.0 {
+        (std_dev / mean.abs()).abs()
+    } else {
+        10.0
+    };
+
+    (100.0 / (1.0 + coefficient_of_variation)).clamp(0.0, 100.0)
+}
+
+/// Largest fall of cumulative realized P&L from its running peak, with the
+/// curve starting at zero. `pnls` must be in trade order.
+pub fn max_drawdown(pnls: &[f64]) -> f64 {
+    let mut cumulative = 0.0_f64;
+    let mut peak = 0.0_f64;
+    let mut drawdown = 0.0_f64;
+    for pnl in pnls {
+        cumulative += pnl;
+        peak = peak.max(cumulative);
+        drawdown = drawdown.max(peak - cumulative);
+    }
+    drawdown
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn score(max_drawdown: Option<f64>, total_fees: Option<f64>) -> PerformanceScore {
+        PerformanceScore {
+            id: 0,
+            wallet_address: "wallet".to_string(),
+            score: 0.0,
+            win_rate: 60.0,
+            total_trades: 5,
+            winning_trades: 3,
+            losing_trades: 2,
+            total_profit: 190.0,
+            total_loss: -170.0,
+            net_pnl: 20.0,
+            avg_profit_per_trade: 190.0 / 3.0,
+            avg_loss_per_trade: -85.0,
+            profit_factor: 190.0 / 170.0,
+            sharpe_ratio: 1.5,
+            consistency_score: 40.0,
+            avg_hold_duration_seconds: 0.0,
+            best_trade_pnl: 100.0,
+            worst_trade_pnl: -90.0,
+            automated_pnl: 0.0,
+            automation_share: 0.0,
+            max_drawdown,
+            total_fees,
+            components: Vec::new(),
+            calculated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn drawdown_tracks_the_running_peak() {
+        // Cumulative P&L 100, 20, 80, -10, 20: the worst fall is 100 to -10.
+        assert_eq!(max_drawdown(&[100.0, -80.0, 60.0, -90.0, 30.0]), 110.0);
+        // A losing start falls from the zero baseline.
+        assert_eq!(max_drawdown(&[-30.0, 10.0]), 30.0);
+        assert_eq!(max_drawdown(&[10.0, 20.0]), 0.0);
+    }
+
+    #[test]
+    fn default_weights_reproduce_the_original_formula() {
+        let mut scored = score(Some(110.0), Some(19.0));
+        apply_weights(&mut scored, &PerformanceScoreWeights::default());
+
+        // 0.6 * 30 + (1.117647 / 3) * 30 + (6.5 / 10) * 20 + 0.4 * 20
+        let expected = 18.0 + 190.0 / 170.0 / 3.0 * 30.0 + 13.0 + 8.0;
+        assert!((scored.score - expected).abs() < 1e-9);
+        assert_eq!(scored.components.len(), 6);
+        let drawdown = scored
+            .components
+            .iter()
+            .find(|c| c.component == ScoreComponentKind::Drawdown)
+            .unwrap();
+        assert_eq!(drawdown.contribution, 0.0);
+        assert!((drawdown.raw_value - 110.0 / 190.0 * 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn missing_components_rescale_the_remaining_weights() {
+        let weights = PerformanceScoreWeights {
+            win_rate: 1.0,
+            profit_factor: 0.0,
+            sharpe_ratio: 0.0,
+            drawdown: 1.0,
+            consistency: 0.0,
+            fee_efficiency: 0.0,
+        };
+        let mut legacy = score(None, None);
+        apply_weights(&mut legacy, &weights);
+
+        assert_eq!(legacy.components.len(), 4);
+        let win_rate = &legacy.components[0];
+        assert_eq!(win_rate.weight, 1.0);
+        assert!((legacy.score - 60.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn weights_must_be_non_negative_and_not_all_zero() {
+        let mut weights = PerformanceScoreWeights::default();
+        assert!(weights.validate().is_ok());
+        weights.drawdown = -1.0;
+        assert!(weights.validate().is_err());
+        weights = PerformanceScoreWeights {
+            win_rate: 0.0,
+            profit_factor: 0.0,
+            sharpe_ratio: 0.0,
+            drawdown: 0.0,
+            consistency: 0.0,
+            fee_efficiency: f64::NAN,
+        };
+        assert!(weights.validate().is_err());
+        weights.fee_efficiency = 0.0;
+        assert!(weights.validate().is_err());
+    }
+}
@@ -1,4 +1,6 @@
+use crate::config::DataPaths;
 use crate::data::event_store::{Event as AuditEvent, SharedEventStore};
+use crate::errors::AppError;
 use crate::security::activity_log::ActivityLogger;
 use base64::engine::general_purpose::STANDARD as BASE64_ENGINE;
 use base64::Engine;
@@ -143,6 +145,24 @@ impl std::fmt::Display for PhantomError {
 
 impl std::error::Error for PhantomError {}
 
+impl From<PhantomError> for AppError {
+    fn from(err: PhantomError) -> Self {
+        let code = serde_json::to_value(&err.code)
+            .ok()
+            .and_then(|value| value.as_str().map(str::to_string))
+            .unwrap_or_else(|| "internal".to_string());
+        let error = match err.code {
+            PhantomErrorCode::NotConnected => AppError::unauthorized(err.message),
+            PhantomErrorCode::InvalidInput => AppError::validation(err.message),
+            PhantomErrorCode::Storage
+            | PhantomErrorCode::Serialization
+            | PhantomErrorCode::Internal => AppError::internal(err.message),
+        };
+        // Keeps the codes the frontend already matches on.
+        error.with_code(code)
+    }
+}
+
 #[derive(Default)]
 pub struct WalletState {
     session: Mutex<Option<PhantomSession>>,
@@ -172,7 +192,7 @@ pub fn hydrate_wallet_state(app: &AppHandle) -> Result<(), PhantomError> {
 }
 
 fn session_path(app: &AppHandle) -> Result<PathBuf, PhantomError> {
-    let mut path = app.path().app_data_dir().map_err(|err| {
+    let mut path = app.app_data_root().map_err(|err| {
         PhantomError::storage(format!("Unable to resolve app data directory: {err}"))
     })?;
     if !path.exists() {
@@ -220,7 +240,7 @@ pub async fn phantom_connect(
     payload: PhantomConnectPayload,
     state: State<'_, WalletState>,
     app: AppHandle,
-) -> Result<PhantomSession, PhantomError> {
+) -> Result<PhantomSession, AppError> {
     let logger = app.state::<ActivityLogger>();
     let public_key = payload.public_key.trim().to_string();
 
@@ -234,10 +254,9 @@ pub async fn phantom_connect(
             )
             .await;
 
-        return Err(PhantomError::new(
-            PhantomErrorCode::InvalidInput,
-            "Public key is required",
-        ));
+        return Err(
+            PhantomError::new(PhantomErrorCode::InvalidInput, "Public key is required").into(),
+        );
     }
 
     let network = payload
@@ -262,7 +281,7 @@ pub async fn phantom_connect(
                 None,
             )
             .await;
-        return Err(err);
+        return Err(err.into());
     }
 
     {
@@ -302,7 +321,7 @@ pub async fn phantom_connect(
 pub async fn phantom_disconnect(
     state: State<'_, WalletState>,
     app: AppHandle,
-) -> Result<(), PhantomError> {
+) -> Result<(), AppError> {
     let logger = app.state::<ActivityLogger>();
     let wallet_address = {
         let guard = lock_session(&state).await;
@@ -358,7 +377,7 @@ pub async fn phantom_disconnect(
                     None,
                 )
                 .await;
-            Err(err)
+            Err(err.into())
         }
     }
 }
@@ -366,7 +385,7 @@ pub async fn phantom_disconnect(
 #[tauri::command]
 pub async fn phantom_session(
     state: State<'_, WalletState>,
-) -> Result<Option<PhantomSession>, PhantomError> {
+) -> Result<Option<PhantomSession>, AppError> {
     let guard = lock_session(&state).await;
     Ok(guard.clone())
 }
@@ -376,7 +395,7 @@ pub async fn phantom_sign_message(
     request: PhantomSignMessageRequest,
     state: State<'_, WalletState>,
     app: AppHandle,
-) -> Result<PhantomSignMessageResponse, PhantomError> {
+) -> Result<PhantomSignMessageResponse, AppError> {
     let logger = app.state::<ActivityLogger>();
     let guard = lock_session(&state).await;
     let session = guard.as_ref().ok_or_else(|| {
@@ -406,7 +425,8 @@ pub async fn phantom_sign_message(
             return Err(PhantomError::new(
                 PhantomErrorCode::InvalidInput,
                 format!("Invalid session public key: {err}"),
-            ));
+            )
+            .into());
         }
     };
 
@@ -428,7 +448,8 @@ pub async fn phantom_sign_message(
             return Err(PhantomError::new(
                 PhantomErrorCode::InvalidInput,
                 format!("Invalid signature: {err}"),
-            ));
+            )
+            .into());
         }
     };
 
@@ -454,7 +475,7 @@ pub async fn phantom_sign_message(
 pub async fn phantom_sign_transaction(
     request: PhantomSignTransactionRequest,
     state: State<'_, WalletState>,
-) -> Result<PhantomSignTransactionResponse, PhantomError> {
+) -> Result<PhantomSignTransactionResponse, AppError> {
     let session_data = {
         let guard = lock_session(&state).await;
         let session = guard.as_ref().ok_or_else(|| {
@@ -517,7 +538,7 @@ fn resolve_endpoint(network: &str) -> String {
 pub async fn phantom_balance(
     address: String,
     state: State<'_, WalletState>,
-) -> Result<f64, PhantomError> {
+) -> Result<f64, AppError> {
     use solana_client::rpc_client::RpcClient;
     use solana_sdk::pubkey::Pubkey;
     use std::str::FromStr;
@@ -542,8 +563,11 @@ pub async fn phantom_balance(
 
     match client.get_balance(&pubkey) {
         Ok(lamports) => Ok(lamports as f64 / 1_000_000_000.0),
-        Err(err) => Err(PhantomError::internal(format!(
-            "Failed to fetch balance: {err}"
-        ))),
+        Err(err) => Err(AppError::upstream(
+            "solana_rpc",
+            format!("Failed to fetch balance: {err}"),
+            true,
+        )
+        .with_code("balance_unavailable")),
     }
 }
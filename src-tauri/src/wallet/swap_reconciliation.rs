@@ -0,0 +1,603 @@
+//! Reconciles the recorded swap history against on-chain data.
+//!
+//! Each run pulls a wallet's recent signatures, decodes the ones the history
+//! doesn't know with the shared activity decoder and adds the swaps among them
+//! as `source: chain`. Recorded signatures are confirmed from the same list or,
+//! failing that, from `getSignatureStatuses`; ones that never landed are
+//! flagged `unconfirmed` once they are older than the timeout. Entries are
+//! matched by signature only, so partially decoded swaps still match and
+//! repeated runs never add duplicates.
+
+use chrono::{DateTime, Duration, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::time::interval;
+use uuid::Uuid;
+
+use super::multi_wallet::MultiWalletManager;
+use super::operations::{
+    ReconciliationStatus, SwapHistory, SwapHistoryEntry, SwapSource, SwapStatus,
+    WalletOperationsManager,
+};
+use crate::api_config::{rpc_endpoint, solana_rpc};
+use crate::security::keystore::Keystore;
+use crate::websocket::activity::{decode_transaction, ActivityKind, DecodedActivity, TokenAmount};
+
+/// Signatures requested per `getSignaturesForAddress` call.
+const SIGNATURE_PAGE_LIMIT: usize = 100;
+/// Upper bound on signatures per `getSignatureStatuses` call.
+const SIGNATURE_STATUS_LIMIT: usize = 256;
+/// A recorded swap not seen on chain after this long is flagged `unconfirmed`.
+const UNCONFIRMED_AFTER_SECS: i64 = 600;
+const RECONCILE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(300);
+/// How far back scheduled runs look.
+const SCHEDULED_LOOKBACK_HOURS: i64 = 24;
+/// Non-swap signatures remembered so scheduled runs don't decode them again.
+const MAX_INSPECTED_SIGNATURES: usize = 5_000;
+pub const SWAP_HISTORY_RECONCILED_EVENT: &str = "swap_history_reconciled";
+
+#[derive(Debug, thiserror::Error)]
+pub enum SwapReconcileError {
+    #[error("rpc error: {0}")]
+    Rpc(String),
+    #[error("state error: {0}")]
+    State(String),
+}
+
+/// What the chain says about a signature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignatureOutcome {
+    Confirmed,
+    /// Landed, but the transaction returned an error.
+    Failed,
+    NotFound,
+}
+
+/// Chain data gathered for one wallet, ready to be applied to the history.
+#[derive(Debug, Clone, Default)]
+pub struct ChainScan {
+    pub wallet: String,
+    pub outcomes: HashMap<String, SignatureOutcome>,
+    /// Decoded swaps for confirmed signatures the history didn't have.
+    pub swaps: Vec<DecodedActivity>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReconcileReport {
+    pub wallet: String,
+    pub since: DateTime<Utc>,
+    pub inserted: usize,
+    pub confirmed: usize,
+    pub failed: usize,
+    pub unconfirmed: usize,
+    pub checked_at: DateTime<Utc>,
+}
+
+impl ReconcileReport {
+    fn changed(&self) -> bool {
+        self.inserted + self.confirmed + self.failed + self.unconfirmed > 0
+    }
+}
+
+fn leg_label(leg: Option<&TokenAmount>) -> String {
+    leg.map(|token| token.symbol.clone().unwrap_or_else(|| token.mint.clone()))
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// History entry for a swap found on chain. Legs the decoder couldn't
+/// resolve are kept as `unknown` with a zero amount.
+fn entry_from_activity(activity: &DecodedActivity, now: DateTime<Utc>) -> SwapHistoryEntry {
+    let from_amount = activity.token_out.as_ref().map_or(0.0, |t| t.amount);
+    let to_amount = activity.token_in.as_ref().map_or(0.0, |t| t.amount);
+    SwapHistoryEntry {
+        id: Uuid::new_v4().to_string(),
+        from_token: leg_label(activity.token_out.as_ref()),
+        to_token: leg_label(activity.token_in.as_ref()),
+        from_amount,
+        to_amount,
+        rate: if from_amount > 0.0 {
+            to_amount / from_amount
+        } else {
+            0.0
+        },
+        fee: 0.0,
+        price_impact: 0.0,
+        tx_signature: Some(activity.signature.clone()),
+        timestamp: DateTime::from_timestamp(activity.timestamp, 0).unwrap_or(now),
+        status: SwapStatus::Completed,
+        wallet: Some(activity.wallet.clone()),
+        source: SwapSource::Chain,
+        reconciliation: ReconciliationStatus::Confirmed,
+        reconciled_at: Some(now),
+    }
+}
+
+/// Recorded signatures in the window that `outcomes` doesn't cover yet and
+/// need a status lookup.
+pub fn signatures_to_verify(
+    history: &SwapHistory,
+    outcomes: &HashMap<String, SignatureOutcome>,
+    since: DateTime<Utc>,
+) -> Vec<String> {
+    history
+        .swaps
+        .iter()
+        .filter(|swap| {
+            swap.timestamp >= since && swap.reconciliation != ReconciliationStatus::Confirmed
+        })
+        .filter_map(|swap| swap.tx_signature.clone())
+        .filter(|signature| !outcomes.contains_key(signature))
+        .collect()
+}
+
+/// Applies one wallet's chain data to the history.
+pub fn apply_scan(
+    history: &mut SwapHistory,
+    scan: &ChainScan,
+    since: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> ReconcileReport {
+    let mut report = ReconcileReport {
+        wallet: scan.wallet.clone(),
+        since,
+        inserted: 0,
+        confirmed: 0,
+        failed: 0,
+        unconfirmed: 0,
+        checked_at: now,
+    };
+    let timeout = Duration::seconds(UNCONFIRMED_AFTER_SECS);
+
+    for swap in history.swaps.iter_mut() {
+        let outcome = swap
+            .tx_signature
+            .as_ref()
+            .and_then(|signature| scan.outcomes.get(signature));
+        match outcome {
+            Some(SignatureOutcome::Confirmed) => {
+                if swap.reconciliation != ReconciliationStatus::Confirmed {
+                    report.confirmed += 1;
+                }
+                if swap.status == SwapStatus::Pending {
+                    swap.status = SwapStatus::Completed;
+                }
+                swap.reconciliation = ReconciliationStatus::Confirmed;
+                swap.wallet.get_or_insert_with(|| scan.wallet.clone());
+                swap.reconciled_at = Some(now);
+            }
+            Some(SignatureOutcome::Failed) => {
+                if swap.status != SwapStatus::Failed {
+                    report.failed += 1;
+                }
+                swap.status = SwapStatus::Failed;
+                swap.reconciliation = ReconciliationStatus::Confirmed;
+                swap.wallet.get_or_insert_with(|| scan.wallet.clone());
+                swap.reconciled_at = Some(now);
+            }
+            Some(SignatureOutcome::NotFound) | None => {
+                let awaiting = swap.reconciliation == ReconciliationStatus::Pending
+                    && swap.status != SwapStatus::Failed
+                    && swap.timestamp >= since;
+                // Entries without a signature can only be judged by their status.
+                let unresolved = outcome.is_some()
+                    || (swap.tx_signature.is_none() && swap.status == SwapStatus::Pending);
+                if awaiting && unresolved && now - swap.timestamp >= timeout {
+                    swap.reconciliation = ReconciliationStatus::Unconfirmed;
+                    swap.reconciled_at = Some(now);
+                    report.unconfirmed += 1;
+                }
+            }
+        }
+    }
+
+    let mut known: HashSet<String> = history
+        .swaps
+        .iter()
+        .filter_map(|swap| swap.tx_signature.clone())
+        .collect();
+    for activity in &scan.swaps {
+        if activity.kind != ActivityKind::Swap
+            || scan.outcomes.get(&activity.signature) != Some(&SignatureOutcome::Confirmed)
+            || !known.insert(activity.signature.clone())
+        {
+            continue;
+        }
+        history.swaps.push(entry_from_activity(activity, now));
+        report.inserted += 1;
+    }
+
+    history.trim();
+    report
+}
+
+pub struct SwapReconciler {
+    app: AppHandle,
+    inspected: Mutex<HashSet<String>>,
+}
+
+pub type SharedSwapReconciler = Arc<SwapReconciler>;
+
+impl SwapReconciler {
+    pub fn new(app: &AppHandle) -> Self {
+        Self {
+            app: app.clone(),
+            inspected: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Reconciles `wallet`'s swaps since `since` and persists the result.
+    pub async fn reconcile(
+        &self,
+        wallet: &str,
+        since: DateTime<Utc>,
+    ) -> Result<ReconcileReport, SwapReconcileError> {
+        let operations = self
+            .app
+            .try_state::<WalletOperationsManager>()
+            .ok_or_else(|| SwapReconcileError::State("Wallet operations unavailable".into()))?;
+        let history = operations
+            .swap_history
+            .lock()
+            .map_err(|e| SwapReconcileError::State(e.to_string()))?
+            .clone();
+
+        let scan = self.scan(wallet, since, &history).await?;
+
+        let now = Utc::now();
+        let report = {
+            let mut history = operations
+                .swap_history
+                .lock()
+                .map_err(|e| SwapReconcileError::State(e.to_string()))?;
+            apply_scan(&mut history, &scan, since, now)
+        };
+
+        if report.changed() {
+            if let Some(keystore) = self.app.try_state::<Keystore>() {
+                operations
+                    .persist_swap_history(&keystore)
+                    .map_err(|e| SwapReconcileError::State(e.to_string()))?;
+            }
+            if let Err(e) = self.app.emit(SWAP_HISTORY_RECONCILED_EVENT, &report) {
+                eprintln!("Failed to emit swap history reconciliation: {}", e);
+            }
+        }
+
+        Ok(report)
+    }
+
+    async fn scan(
+        &self,
+        wallet: &str,
+        since: DateTime<Utc>,
+        history: &SwapHistory,
+    ) -> Result<ChainScan, SwapReconcileError> {
+        let mut scan = ChainScan {
+            wallet: wallet.to_string(),
+            ..ChainScan::default()
+        };
+
+        let signatures = self
+            .rpc(
+                "getSignaturesForAddress",
+                json!([wallet, { "limit": SIGNATURE_PAGE_LIMIT }]),
+            )
+            .await?;
+        for info in signatures.as_array().into_iter().flatten() {
+            let Some(signature) = info.get("signature").and_then(Value::as_str) else {
+                continue;
+            };
+            let block_time = info.get("blockTime").and_then(Value::as_i64);
+            if block_time.is_some_and(|time| time < since.timestamp()) {
+                continue;
+            }
+            let outcome = if info.get("err").is_some_and(|err| !err.is_null()) {
+                SignatureOutcome::Failed
+            } else {
+                SignatureOutcome::Confirmed
+            };
+            scan.outcomes.insert(signature.to_string(), outcome);
+        }
+
+        let recorded: HashSet<&str> = history
+            .swaps
+            .iter()
+            .filter_map(|swap| swap.tx_signature.as_deref())
+            .collect();
+        let unknown: Vec<String> = {
+            let inspected = self.inspected.lock();
+            scan.outcomes
+                .iter()
+                .filter(|(signature, outcome)| {
+                    **outcome == SignatureOutcome::Confirmed
+                        && !recorded.contains(signature.as_str())
+                        && !inspected.contains(*signature)
+                })
+                .map(|(signature, _)| signature.clone())
+                .collect()
+        };
+        for signature in unknown {
+            let raw = self
+                .rpc(
+                    "getTransaction",
+                    json!([signature, {
+                        "encoding": "jsonParsed",
+                        "maxSupportedTransactionVersion": 0,
+                        "commitment": "confirmed",
+                    }]),
+                )
+                .await?;
+            if raw.is_null() {
+                continue;
+            }
+            let mut activity = decode_transaction(&raw, wallet);
+            activity.signature = signature.clone();
+            if activity.kind == ActivityKind::Swap {
+                scan.swaps.push(activity);
+            } else {
+                let mut inspected = self.inspected.lock();
+                if inspected.len() >= MAX_INSPECTED_SIGNATURES {
+                    inspected.clear();
+                }
+                inspected.insert(signature);
+            }
+        }
+
+        let to_verify = signatures_to_verify(history, &scan.outcomes, since);
+        for chunk in to_verify.chunks(SIGNATURE_STATUS_LIMIT) {
+            let statuses = self
+                .rpc(
+                    "getSignatureStatuses",
+                    json!([chunk, { "searchTransactionHistory": true }]),
+                )
+                .await?;
+            let values = statuses
+                .get("value")
+                .and_then(Value::as_array)
+                .cloned()
+                .unwrap_or_default();
+            for (signature, status) in chunk.iter().zip(values) {
+                let outcome = if status.is_null() {
+                    SignatureOutcome::NotFound
+                } else if status.get("err").is_some_and(|err| !err.is_null()) {
+                    SignatureOutcome::Failed
+                } else if status.get("confirmationStatus").and_then(Value::as_str)
+                    == Some("processed")
+                {
+                    // Not final yet; look again on the next run.
+                    continue;
+                } else {
+                    SignatureOutcome::Confirmed
+                };
+                scan.outcomes.insert(signature.clone(), outcome);
+            }
+        }
+
+        Ok(scan)
+    }
+
+    async fn rpc(&self, method: &str, params: Value) -> Result<Value, SwapReconcileError> {
+        solana_rpc(&rpc_endpoint(&self.app), method, params)
+            .await
+            .map_err(SwapReconcileError::Rpc)
+    }
+}
+
+/// Reconciles every wallet in the multi-wallet manager on a fixed interval.
+pub async fn run_swap_reconciliation(app: AppHandle, reconciler: SharedSwapReconciler) {
+    let mut tick = interval(RECONCILE_INTERVAL);
+    loop {
+        tick.tick().await;
+        let wallets = match app.try_state::<MultiWalletManager>() {
+            Some(manager) => match manager.list_wallets() {
+                Ok(wallets) => wallets,
+                Err(e) => {
+                    eprintln!("Failed to list wallets for swap reconciliation: {}", e);
+                    continue;
+                }
+            },
+            None => continue,
+        };
+
+        let since = Utc::now() - Duration::hours(SCHEDULED_LOOKBACK_HOURS);
+        for wallet in wallets {
+            if let Err(e) = reconciler.reconcile(&wallet.public_key, since).await {
+                eprintln!(
+                    "Failed to reconcile swap history for {}: {}",
+                    wallet.public_key, e
+                );
+            }
+        }
+    }
+}
+
+// Tauri commands
+#[tauri::command]
+pub async fn swap_history_reconcile(
+    wallet: String,
+    since: Option<DateTime<Utc>>,
+    reconciler: State<'_, SharedSwapReconciler>,
+) -> Result<ReconcileReport, String> {
+    let since = since.unwrap_or_else(|| Utc::now() - Duration::hours(SCHEDULED_LOOKBACK_HOURS));
+    reconciler
+        .reconcile(&wallet, since)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WALLET: &str = "wallet";
+    const BONK: &str = "DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263";
+
+    fn recorded(signature: Option<&str>, age_secs: i64, now: DateTime<Utc>) -> SwapHistoryEntry {
+        SwapHistoryEntry {
+            id: Uuid::new_v4().to_string(),
+            from_token: "SOL".into(),
+            to_token: "BONK".into(),
+            from_amount: 1.0,
+            to_amount: 1_000.0,
+            rate: 1_000.0,
+            fee: 0.0,
+            price_impact: 0.0,
+            tx_signature: signature.map(str::to_string),
+            timestamp: now - Duration::seconds(age_secs),
+            status: SwapStatus::Pending,
+            wallet: None,
+            source: SwapSource::App,
+            reconciliation: ReconciliationStatus::Pending,
+            reconciled_at: None,
+        }
+    }
+
+    fn chain_swap(signature: &str, token_in: Option<(&str, f64)>) -> DecodedActivity {
+        let leg = |(mint, amount): (&str, f64)| TokenAmount {
+            mint: mint.to_string(),
+            symbol: None,
+            amount,
+        };
+        DecodedActivity {
+            signature: signature.into(),
+            wallet: WALLET.into(),
+            kind: ActivityKind::Swap,
+            slot: 1,
+            timestamp: Utc::now().timestamp() - 60,
+            token_in: token_in.map(leg),
+            token_out: Some(leg(("So11111111111111111111111111111111111111112", 2.0))),
+            counterparty: None,
+            program_id: None,
+            program_name: None,
+            nft_mint: None,
+            description: None,
+        }
+    }
+
+    fn history(swaps: Vec<SwapHistoryEntry>) -> SwapHistory {
+        SwapHistory {
+            swaps,
+            ..SwapHistory::default()
+        }
+    }
+
+    #[test]
+    fn inserts_missing_swaps_once_and_confirms_recorded_ones() {
+        let now = Utc::now();
+        let since = now - Duration::hours(1);
+        let mut history = history(vec![recorded(Some("mine"), 30, now)]);
+        let scan = ChainScan {
+            wallet: WALLET.into(),
+            outcomes: [
+                ("mine".to_string(), SignatureOutcome::Confirmed),
+                ("elsewhere".to_string(), SignatureOutcome::Confirmed),
+                ("partial".to_string(), SignatureOutcome::Confirmed),
+            ]
+            .into_iter()
+            .collect(),
+            swaps: vec![
+                chain_swap("elsewhere", Some((BONK, 500.0))),
+                chain_swap("partial", None),
+            ],
+        };
+
+        let report = apply_scan(&mut history, &scan, since, now);
+        assert_eq!((report.inserted, report.confirmed), (2, 1));
+        assert_eq!(history.swaps.len(), 3);
+
+        let mine = history
+            .swaps
+            .iter()
+            .find(|swap| swap.tx_signature.as_deref() == Some("mine"))
+            .unwrap();
+        assert_eq!(mine.status, SwapStatus::Completed);
+        assert_eq!(mine.reconciliation, ReconciliationStatus::Confirmed);
+        assert_eq!(mine.wallet.as_deref(), Some(WALLET));
+
+        let partial = history
+            .swaps
+            .iter()
+            .find(|swap| swap.tx_signature.as_deref() == Some("partial"))
+            .unwrap();
+        assert_eq!(partial.source, SwapSource::Chain);
+        assert_eq!(partial.to_token, "unknown");
+
+        let rerun = apply_scan(&mut history, &scan, since, now);
+        assert!(!rerun.changed());
+        assert_eq!(history.swaps.len(), 3);
+    }
+
+    #[test]
+    fn flags_unconfirmed_only_after_timeout_and_marks_failures() {
+        let now = Utc::now();
+        let since = now - Duration::hours(1);
+        let mut history = history(vec![
+            recorded(Some("fresh"), 60, now),
+            recorded(Some("stale"), UNCONFIRMED_AFTER_SECS + 60, now),
+            recorded(Some("reverted"), 120, now),
+        ]);
+        let scan = ChainScan {
+            wallet: WALLET.into(),
+            outcomes: [
+                ("fresh".to_string(), SignatureOutcome::NotFound),
+                ("stale".to_string(), SignatureOutcome::NotFound),
+                ("reverted".to_string(), SignatureOutcome::Failed),
+            ]
+            .into_iter()
+            .collect(),
+            swaps: vec![],
+        };
+
+        let report = apply_scan(&mut history, &scan, since, now);
+        assert_eq!((report.unconfirmed, report.failed), (1, 1));
+        let by_signature = |signature: &str| {
+            history
+                .swaps
+                .iter()
+                .find(|swap| swap.tx_signature.as_deref() == Some(signature))
+                .unwrap()
+                .clone()
+        };
+        assert_eq!(
+            by_signature("fresh").reconciliation,
+            ReconciliationStatus::Pending
+        );
+        assert_eq!(
+            by_signature("stale").reconciliation,
+            ReconciliationStatus::Unconfirmed
+        );
+        assert_eq!(by_signature("reverted").status, SwapStatus::Failed);
+
+        assert!(signatures_to_verify(&history, &HashMap::new(), since)
+            .iter()
+            .all(|signature| signature != "reverted"));
+    }
+
+    #[test]
+    fn recording_an_imported_swap_replaces_it() {
+        let now = Utc::now();
+        let mut history = history(vec![]);
+        let scan = ChainScan {
+            wallet: WALLET.into(),
+            outcomes: [("sig".to_string(), SignatureOutcome::Confirmed)]
+                .into_iter()
+                .collect(),
+            swaps: vec![chain_swap("sig", Some((BONK, 500.0)))],
+        };
+        apply_scan(&mut history, &scan, now - Duration::hours(1), now);
+
+        history.record(recorded(Some("sig"), 0, now));
+        assert_eq!(history.swaps.len(), 1);
+        assert_eq!(history.swaps[0].source, SwapSource::App);
+        assert_eq!(
+            history.swaps[0].reconciliation,
+            ReconciliationStatus::Confirmed
+        );
+        assert_eq!(history.swaps[0].wallet.as_deref(), Some(WALLET));
+    }
+}
@@ -1,3 +1,4 @@
+use super::events::{event_catalog, WebhookEventDefinition};
 use super::manager::WebhookManager;
 use super::types::{WebhookConfig, WebhookDeliveryLog, WebhookError, WebhookTestResult};
 use serde_json::Value;
@@ -90,3 +91,8 @@ pub async fn list_webhook_delivery_logs(
         .await
         .map_err(|e| e.to_string())
 }
+
+#[tauri::command]
+pub async fn list_webhook_event_types() -> Result<Vec<WebhookEventDefinition>, String> {
+    Ok(event_catalog())
+}
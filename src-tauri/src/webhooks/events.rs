@@ -0,0 +1,771 @@
+use super::commands::SharedWebhookManager;
+use super::types::WebhookError;
+use crate::trading::events::{order_events, OrderEventKind, OrderEventSource};
+use crate::trading::types::OrderSide;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tauri::{AppHandle, Manager};
+use tokio::sync::broadcast::error::RecvError;
+
+/// Events webhooks can subscribe to. Each has a documented payload schema
+/// in [`event_catalog`]; a schema's version is bumped whenever a field is
+/// renamed, removed or changes type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum WebhookEventType {
+    #[serde(rename = "order.filled")]
+    OrderFilled,
+    #[serde(rename = "alert.triggered")]
+    AlertTriggered,
+    #[serde(rename = "whale.detected")]
+    WhaleDetected,
+    #[serde(rename = "dca.executed")]
+    DcaExecuted,
+    #[serde(rename = "backup.completed")]
+    BackupCompleted,
+    #[serde(rename = "safety.kill_switch_activated")]
+    KillSwitchActivated,
+    /// Manual and test deliveries. Every webhook receives these, so it
+    /// cannot be subscribed to.
+    #[serde(rename = "webhook.test")]
+    WebhookTest,
+}
+
+impl WebhookEventType {
+    /// Event types a webhook can subscribe to, i.e. everything except
+    /// `webhook.test`.
+    pub const SUBSCRIBABLE: [WebhookEventType; 6] = [
+        WebhookEventType::OrderFilled,
+        WebhookEventType::AlertTriggered,
+        WebhookEventType::WhaleDetected,
+        WebhookEventType::DcaExecuted,
+        WebhookEventType::BackupCompleted,
+        WebhookEventType::KillSwitchActivated,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEventType::OrderFilled => "order.filled",
+            WebhookEventType::AlertTriggered => "alert.triggered",
+            WebhookEventType::WhaleDetected => "whale.detected",
+            WebhookEventType::DcaExecuted => "dca.executed",
+            WebhookEventType::BackupCompleted => "backup.completed",
+            WebhookEventType::KillSwitchActivated => "safety.kill_switch_activated",
+            WebhookEventType::WebhookTest => "webhook.test",
+        }
+    }
+
+    pub fn schema_version(&self) -> u32 {
+        1
+    }
+
+    pub fn is_subscribable(&self) -> bool {
+        *self != WebhookEventType::WebhookTest
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PayloadFieldType {
+    String,
+    Number,
+    Boolean,
+    Object,
+    Array,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PayloadField {
+    pub name: String,
+    pub field_type: PayloadFieldType,
+    pub description: String,
+    /// Optional fields are omitted or `null` when the source has no value.
+    pub required: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookEventDefinition {
+    pub event_type: WebhookEventType,
+    pub schema_version: u32,
+    pub description: String,
+    pub subscribable: bool,
+    /// Top-level fields of the delivery's `data` object. Empty for events
+    /// whose payload is free-form.
+    pub fields: Vec<PayloadField>,
+}
+
+impl WebhookEventDefinition {
+    fn field(&self, name: &str) -> Option<&PayloadField> {
+        self.fields.iter().find(|field| field.name == name)
+    }
+}
+
+fn field(
+    name: &str,
+    field_type: PayloadFieldType,
+    required: bool,
+    description: &str,
+) -> PayloadField {
+    PayloadField {
+        name: name.to_string(),
+        field_type,
+        description: description.to_string(),
+        required,
+    }
+}
+
+pub fn event_definition(event_type: WebhookEventType) -> WebhookEventDefinition {
+    use PayloadFieldType::*;
+
+    let (description, fields) = match event_type {
+        WebhookEventType::OrderFilled => (
+            "A live, paper or DCA order filled.",
+            vec![
+                field("orderId", String, true, "Order identifier"),
+                field("source", String, true, "live, paper or dca"),
+                field("symbol", String, true, "Token bought or sold"),
+                field("side", String, true, "buy or sell"),
+                field("amount", Number, true, "Order amount in the input token"),
+                field("filledAmount", Number, true, "Amount filled by this fill"),
+                field("fillPrice", Number, false, "Execution price, when known"),
+                field(
+                    "notional",
+                    Number,
+                    false,
+                    "filledAmount × fillPrice, when the price is known",
+                ),
+                field("txSignature", String, false, "Transaction signature"),
+            ],
+        ),
+        WebhookEventType::AlertTriggered => (
+            "A price alert's conditions were met.",
+            vec![
+                field("alertId", String, true, "Alert identifier"),
+                field("alertName", String, true, "Alert name"),
+                field("symbol", String, true, "Token symbol"),
+                field(
+                    "currentPrice",
+                    Number,
+                    true,
+                    "Price that triggered the alert",
+                ),
+                field("conditionsMet", String, true, "Human-readable conditions"),
+            ],
+        ),
+        WebhookEventType::WhaleDetected => (
+            "A tracked whale wallet made a transaction above its alert threshold.",
+            vec![
+                field("walletAddress", String, true, "Whale wallet address"),
+                field("walletLabel", String, false, "Label given to the wallet"),
+                field("actionType", String, true, "buy, sell or transfer"),
+                field("tokenSymbol", String, false, "Token involved"),
+                field("amountUsd", Number, true, "Transaction value in USD"),
+                field("threshold", Number, true, "Alert threshold in USD"),
+                field("txSignature", String, true, "Transaction signature"),
+            ],
+        ),
+        WebhookEventType::DcaExecuted => (
+            "A DCA bot ran one of its scheduled purchases.",
+            vec![
+                field("dcaId", String, true, "DCA bot identifier"),
+                field("name", String, true, "DCA bot name"),
+                field("inputSymbol", String, true, "Token spent"),
+                field("outputSymbol", String, true, "Token bought"),
+                field("inputAmount", Number, true, "Amount spent"),
+                field("outputAmount", Number, true, "Amount received"),
+                field("price", Number, true, "Execution price"),
+                field("status", String, true, "Execution status"),
+                field("txSignature", String, false, "Transaction signature"),
+            ],
+        ),
+        WebhookEventType::BackupCompleted => (
+            "An encrypted settings backup was uploaded.",
+            vec![
+                field("filename", String, true, "Backup file name"),
+                field("sizeBytes", Number, true, "Encrypted size in bytes"),
+                field("version", Number, true, "Backup format version"),
+                field("checksum", String, true, "Checksum of the plaintext"),
+            ],
+        ),
+        WebhookEventType::KillSwitchActivated => (
+            "The auto-trading kill switch was activated, stopping all strategies.",
+            vec![field(
+                "stoppedStrategies",
+                Number,
+                true,
+                "Running strategies the kill switch stopped",
+            )],
+        ),
+        WebhookEventType::WebhookTest => (
+            "A manual or test delivery. The payload is the variables supplied by the caller.",
+            Vec::new(),
+        ),
+    };
+
+    WebhookEventDefinition {
+        event_type,
+        schema_version: event_type.schema_version(),
+        description: description.to_string(),
+        subscribable: event_type.is_subscribable(),
+        fields,
+    }
+}
+
+pub fn event_catalog() -> Vec<WebhookEventDefinition> {
+    WebhookEventType::SUBSCRIBABLE
+        .into_iter()
+        .chain(std::iter::once(WebhookEventType::WebhookTest))
+        .map(event_definition)
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterOperator {
+    Eq,
+    Ne,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+    Contains,
+    Exists,
+}
+
+/// A condition on one payload field, e.g. `$.notional gt 1000`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookEventFilter {
+    /// JSON path into the payload: `$.field` or `$.field.nested`.
+    pub path: String,
+    pub op: FilterOperator,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<Value>,
+}
+
+impl WebhookEventFilter {
+    fn segments(&self) -> Result<Vec<&str>, String> {
+        let path = self.path.trim();
+        let path = path
+            .strip_prefix("$.")
+            .ok_or_else(|| format!("filter path '{}' must start with '$.'", self.path))?;
+        let segments: Vec<&str> = path.split('.').collect();
+        if segments.iter().any(|segment| segment.is_empty()) {
+            return Err(format!("filter path '{}' has an empty segment", self.path));
+        }
+        Ok(segments)
+    }
+
+    /// Checks the filter against `definition`'s schema: the path must name
+    /// a declared field, the operator must suit that field's type and the
+    /// value must be present (and of the field's type) unless the operator
+    /// is `exists`.
+    pub fn validate(&self, definition: &WebhookEventDefinition) -> Result<(), String> {
+        let segments = self.segments()?;
+
+        let field_type = if definition.fields.is_empty() {
+            None
+        } else {
+            let field = definition.field(segments[0]).ok_or_else(|| {
+                format!(
+                    "{} has no field '{}'",
+                    definition.event_type.as_str(),
+                    segments[0]
+                )
+            })?;
+            if segments.len() > 1 && field.field_type != PayloadFieldType::Object {
+                return Err(format!("field '{}' has no nested fields", field.name));
+            }
+            (segments.len() == 1).then_some(field.field_type)
+        };
+
+        let value = match (self.op, &self.value) {
+            (FilterOperator::Exists, None) => return Ok(()),
+            (FilterOperator::Exists, Some(_)) => {
+                return Err("'exists' filters take no value".to_string())
+            }
+            (_, None) => return Err(format!("filter on '{}' needs a value", self.path)),
+            (_, Some(value)) => value,
+        };
+
+        match self.op {
+            FilterOperator::Gt | FilterOperator::Gte | FilterOperator::Lt | FilterOperator::Lte => {
+                if !value.is_number() {
+                    return Err(format!("'{}' needs a numeric value", self.path));
+                }
+                if matches!(field_type, Some(t) if t != PayloadFieldType::Number) {
+                    return Err(format!("'{}' is not a number field", self.path));
+                }
+            }
+            FilterOperator::Contains => {
+                if matches!(
+                    field_type,
+                    Some(t) if t != PayloadFieldType::String && t != PayloadFieldType::Array
+                ) {
+                    return Err(format!("'{}' is not a string or array field", self.path));
+                }
+                if field_type == Some(PayloadFieldType::String) && !value.is_string() {
+                    return Err(format!("'{}' needs a string value", self.path));
+                }
+            }
+            FilterOperator::Eq | FilterOperator::Ne => {
+                let value_matches = match field_type {
+                    Some(PayloadFieldType::String) => value.is_string(),
+                    Some(PayloadFieldType::Number) => value.is_number(),
+                    Some(PayloadFieldType::Boolean) => value.is_boolean(),
+                    _ => true,
+                };
+                if !value_matches {
+                    return Err(format!("value for '{}' has the wrong type", self.path));
+                }
+            }
+            FilterOperator::Exists => unreachable!(),
+        }
+
+        Ok(())
+    }
+
+    pub fn matches(&self, payload: &Value) -> bool {
+        let Ok(segments) = self.segments() else {
+            return false;
+        };
+        let actual = segments
+            .iter()
+            .try_fold(payload, |value, segment| value.get(*segment))
+            .filter(|value| !value.is_null());
+
+        let (actual, expected) = match (self.op, actual, &self.value) {
+            (FilterOperator::Exists, actual, _) => return actual.is_some(),
+            (FilterOperator::Ne, None, Some(_)) => return true,
+            (_, Some(actual), Some(expected)) => (actual, expected),
+            _ => return false,
+        };
+
+        let compare = |ordering: fn(f64, f64) -> bool| match (actual.as_f64(), expected.as_f64()) {
+            (Some(a), Some(b)) => ordering(a, b),
+            _ => false,
+        };
+
+        match self.op {
+            FilterOperator::Eq => actual == expected,
+            FilterOperator::Ne => actual != expected,
+            FilterOperator::Gt => compare(|a, b| a > b),
+            FilterOperator::Gte => compare(|a, b| a >= b),
+            FilterOperator::Lt => compare(|a, b| a < b),
+            FilterOperator::Lte => compare(|a, b| a <= b),
+            FilterOperator::Contains => match (actual, expected) {
+                (Value::String(haystack), Value::String(needle)) => {
+                    haystack.contains(needle.as_str())
+                }
+                (Value::Array(items), expected) => items.contains(expected),
+                _ => false,
+            },
+            FilterOperator::Exists => unreachable!(),
+        }
+    }
+}
+
+/// A webhook's interest in one event type. All filters must match for the
+/// event to be delivered.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookSubscription {
+    pub event_type: WebhookEventType,
+    #[serde(default)]
+    pub filters: Vec<WebhookEventFilter>,
+}
+
+impl WebhookSubscription {
+    pub fn matches(&self, event: &WebhookEvent) -> bool {
+        self.event_type == event.event_type
+            && self
+                .filters
+                .iter()
+                .all(|filter| filter.matches(&event.data))
+    }
+}
+
+/// Subscriptions given to webhooks saved before event types existed.
+pub fn default_subscriptions() -> Vec<WebhookSubscription> {
+    WebhookEventType::SUBSCRIBABLE
+        .into_iter()
+        .map(|event_type| WebhookSubscription {
+            event_type,
+            filters: Vec::new(),
+        })
+        .collect()
+}
+
+pub fn validate_subscriptions(subscriptions: &[WebhookSubscription]) -> Result<(), WebhookError> {
+    for (index, subscription) in subscriptions.iter().enumerate() {
+        let event_type = subscription.event_type;
+        if !event_type.is_subscribable() {
+            return Err(WebhookError::InvalidSubscription(format!(
+                "{} cannot be subscribed to",
+                event_type.as_str()
+            )));
+        }
+        if subscriptions[..index]
+            .iter()
+            .any(|earlier| earlier.event_type == event_type)
+        {
+            return Err(WebhookError::InvalidSubscription(format!(
+                "{} is subscribed to more than once",
+                event_type.as_str()
+            )));
+        }
+
+        let definition = event_definition(event_type);
+        for filter in &subscription.filters {
+            filter.validate(&definition).map_err(|err| {
+                WebhookError::InvalidSubscription(format!("{}: {}", event_type.as_str(), err))
+            })?;
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookEvent {
+    #[serde(rename = "event")]
+    pub event_type: WebhookEventType,
+    pub schema_version: u32,
+    pub occurred_at: DateTime<Utc>,
+    pub data: Value,
+}
+
+impl WebhookEvent {
+    pub fn new(event_type: WebhookEventType, data: impl Serialize) -> Self {
+        Self {
+            event_type,
+            schema_version: event_type.schema_version(),
+            occurred_at: Utc::now(),
+            data: serde_json::to_value(data).unwrap_or(Value::Null),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderFilledPayload {
+    pub order_id: String,
+    pub source: OrderEventSource,
+    pub symbol: String,
+    pub side: OrderSide,
+    pub amount: f64,
+    pub filled_amount: f64,
+    pub fill_price: Option<f64>,
+    pub notional: Option<f64>,
+    pub tx_signature: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AlertTriggeredPayload {
+    pub alert_id: String,
+    pub alert_name: String,
+    pub symbol: String,
+    pub current_price: f64,
+    pub conditions_met: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WhaleDetectedPayload {
+    pub wallet_address: String,
+    pub wallet_label: Option<String>,
+    pub action_type: String,
+    pub token_symbol: Option<String>,
+    pub amount_usd: f64,
+    pub threshold: f64,
+    pub tx_signature: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DcaExecutedPayload {
+    pub dca_id: String,
+    pub name: String,
+    pub input_symbol: String,
+    pub output_symbol: String,
+    pub input_amount: f64,
+    pub output_amount: f64,
+    pub price: f64,
+    pub status: String,
+    pub tx_signature: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupCompletedPayload {
+    pub filename: String,
+    pub size_bytes: u64,
+    pub version: u32,
+    pub checksum: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KillSwitchActivatedPayload {
+    pub stopped_strategies: usize,
+}
+
+/// Template variables for `event`: its top-level payload fields plus
+/// `event_type`, `schema_version` and `occurred_at`.
+pub fn template_variables(event: &WebhookEvent) -> Map<String, Value> {
+    let mut variables = match &event.data {
+        Value::Object(fields) => fields.clone(),
+        _ => Map::new(),
+    };
+    variables.insert(
+        "event_type".to_string(),
+        Value::from(event.event_type.as_str()),
+    );
+    variables.insert(
+        "schema_version".to_string(),
+        Value::from(event.schema_version),
+    );
+    variables.insert(
+        "occurred_at".to_string(),
+        Value::from(event.occurred_at.to_rfc3339()),
+    );
+    variables
+}
+
+/// Delivers `data` as `event_type` to subscribed webhooks in the
+/// background. Does nothing before the webhook manager is registered.
+pub fn emit_webhook_event(app: &AppHandle, event_type: WebhookEventType, data: impl Serialize) {
+    let Some(manager) = app.try_state::<SharedWebhookManager>() else {
+        return;
+    };
+    let manager = manager.inner().clone();
+    let event = WebhookEvent::new(event_type, data);
+
+    tauri::async_runtime::spawn(async move {
+        if let Err(err) = manager.read().await.dispatch_event(&event).await {
+            eprintln!(
+                "Failed to deliver {} webhooks: {}",
+                event.event_type.as_str(),
+                err
+            );
+        }
+    });
+}
+
+/// Publishes `order.filled` for every fill on the order lifecycle bus.
+pub async fn forward_order_fills(app: AppHandle) {
+    let mut events = order_events();
+    loop {
+        match events.recv().await {
+            Ok(event) => {
+                let OrderEventKind::Filled {
+                    filled_amount,
+                    fill_price,
+                    tx_signature,
+                } = event.kind
+                else {
+                    continue;
+                };
+                let payload = OrderFilledPayload {
+                    order_id: event.order_id,
+                    source: event.source,
+                    symbol: event.symbol,
+                    side: event.side,
+                    amount: event.amount,
+                    filled_amount,
+                    fill_price,
+                    notional: fill_price.map(|price| price * filled_amount),
+                    tx_signature,
+                };
+                emit_webhook_event(&app, WebhookEventType::OrderFilled, payload);
+            }
+            Err(RecvError::Lagged(_)) => continue,
+            Err(RecvError::Closed) => break,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn filter(path: &str, op: FilterOperator, value: Option<Value>) -> WebhookEventFilter {
+        WebhookEventFilter {
+            path: path.to_string(),
+            op,
+            value,
+        }
+    }
+
+    fn declared_names(event_type: WebhookEventType) -> Vec<String> {
+        event_definition(event_type)
+            .fields
+            .into_iter()
+            .map(|field| field.name)
+            .collect()
+    }
+
+    fn payload_keys(payload: impl Serialize) -> Vec<String> {
+        match serde_json::to_value(payload).unwrap() {
+            Value::Object(fields) => fields.keys().cloned().collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    #[test]
+    fn payloads_match_their_schemas() {
+        let mut cases = vec![
+            (
+                WebhookEventType::OrderFilled,
+                payload_keys(OrderFilledPayload {
+                    order_id: "o".into(),
+                    source: OrderEventSource::Live,
+                    symbol: "SOL".into(),
+                    side: OrderSide::Buy,
+                    amount: 1.0,
+                    filled_amount: 1.0,
+                    fill_price: Some(150.0),
+                    notional: Some(150.0),
+                    tx_signature: None,
+                }),
+            ),
+            (
+                WebhookEventType::DcaExecuted,
+                payload_keys(DcaExecutedPayload {
+                    dca_id: "d".into(),
+                    name: "n".into(),
+                    input_symbol: "USDC".into(),
+                    output_symbol: "SOL".into(),
+                    input_amount: 10.0,
+                    output_amount: 0.1,
+                    price: 100.0,
+                    status: "completed".into(),
+                    tx_signature: None,
+                }),
+            ),
+            (
+                WebhookEventType::KillSwitchActivated,
+                payload_keys(KillSwitchActivatedPayload {
+                    stopped_strategies: 2,
+                }),
+            ),
+        ];
+
+        for (event_type, keys) in cases.iter_mut() {
+            let mut declared = declared_names(*event_type);
+            declared.sort();
+            keys.sort();
+            assert_eq!(&declared, keys, "{}", event_type.as_str());
+        }
+    }
+
+    #[test]
+    fn catalog_serializes_dotted_names() {
+        let catalog = serde_json::to_value(event_catalog()).unwrap();
+        assert_eq!(catalog[0]["eventType"], "order.filled");
+        assert_eq!(catalog[0]["schemaVersion"], 1);
+        assert_eq!(catalog[5]["eventType"], "safety.kill_switch_activated");
+        assert_eq!(catalog[6]["subscribable"], false);
+    }
+
+    #[test]
+    fn validates_filters_against_the_schema() {
+        let order = event_definition(WebhookEventType::OrderFilled);
+
+        assert!(filter("$.notional", FilterOperator::Gt, Some(json!(1000)))
+            .validate(&order)
+            .is_ok());
+        assert!(filter("$.side", FilterOperator::Eq, Some(json!("buy")))
+            .validate(&order)
+            .is_ok());
+        assert!(filter("$.txSignature", FilterOperator::Exists, None)
+            .validate(&order)
+            .is_ok());
+
+        assert!(filter("notional", FilterOperator::Gt, Some(json!(1)))
+            .validate(&order)
+            .is_err());
+        assert!(filter("$.volume", FilterOperator::Gt, Some(json!(1)))
+            .validate(&order)
+            .is_err());
+        assert!(filter("$.side", FilterOperator::Gt, Some(json!(1)))
+            .validate(&order)
+            .is_err());
+        assert!(
+            filter("$.notional", FilterOperator::Gt, Some(json!("1000")))
+                .validate(&order)
+                .is_err()
+        );
+        assert!(filter("$.notional", FilterOperator::Gt, None)
+            .validate(&order)
+            .is_err());
+        assert!(filter("$.symbol.base", FilterOperator::Exists, None)
+            .validate(&order)
+            .is_err());
+    }
+
+    #[test]
+    fn rejects_test_and_duplicate_subscriptions() {
+        let test_only = vec![WebhookSubscription {
+            event_type: WebhookEventType::WebhookTest,
+            filters: Vec::new(),
+        }];
+        assert!(validate_subscriptions(&test_only).is_err());
+
+        let mut duplicated = default_subscriptions();
+        duplicated.push(duplicated[0].clone());
+        assert!(validate_subscriptions(&duplicated).is_err());
+        assert!(validate_subscriptions(&default_subscriptions()).is_ok());
+    }
+
+    #[test]
+    fn subscription_filters_select_events() {
+        let subscription = WebhookSubscription {
+            event_type: WebhookEventType::OrderFilled,
+            filters: vec![
+                filter("$.notional", FilterOperator::Gt, Some(json!(1000))),
+                filter("$.symbol", FilterOperator::Contains, Some(json!("SOL"))),
+            ],
+        };
+
+        let large = WebhookEvent::new(
+            WebhookEventType::OrderFilled,
+            json!({ "symbol": "SOL/USDC", "notional": 1500.0 }),
+        );
+        let small = WebhookEvent::new(
+            WebhookEventType::OrderFilled,
+            json!({ "symbol": "SOL/USDC", "notional": 200.0 }),
+        );
+        let unpriced = WebhookEvent::new(
+            WebhookEventType::OrderFilled,
+            json!({ "symbol": "SOL/USDC", "notional": null }),
+        );
+        let other = WebhookEvent::new(
+            WebhookEventType::DcaExecuted,
+            json!({ "symbol": "SOL/USDC", "notional": 1500.0 }),
+        );
+
+        assert!(subscription.matches(&large));
+        assert!(!subscription.matches(&small));
+        assert!(!subscription.matches(&unpriced));
+        assert!(!subscription.matches(&other));
+    }
+
+    #[test]
+    fn template_variables_include_event_metadata() {
+        let event = WebhookEvent::new(
+            WebhookEventType::BackupCompleted,
+            json!({ "filename": "backup.enc" }),
+        );
+        let variables = template_variables(&event);
+
+        assert_eq!(variables["filename"], "backup.enc");
+        assert_eq!(variables["event_type"], "backup.completed");
+        assert_eq!(variables["schema_version"], 1);
+    }
+}
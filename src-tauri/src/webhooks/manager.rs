@@ -1,21 +1,61 @@
+use super::events::{
+    default_subscriptions, template_variables, validate_subscriptions, WebhookEvent,
+    WebhookEventType,
+};
 use super::retry::RetryExecutor;
 use super::template::TemplateEngine;
 use super::types::{
     DeliveryStatus, RetryPolicy, WebhookConfig, WebhookDeliveryLog, WebhookError, WebhookMethod,
     WebhookTestResult,
 };
+use crate::config::DataPaths;
+use crate::utils::ensure_column;
 use chrono::{DateTime, Utc};
 use reqwest::Client;
 use serde_json::Value;
 use sqlx::{Pool, Row, Sqlite, SqlitePool};
 use std::collections::HashMap;
 use std::time::Instant;
-use tauri::{AppHandle, Manager};
+use tauri::AppHandle;
 use tokio::sync::Mutex;
 use uuid::Uuid;
 
 const WEBHOOKS_DB_FILE: &str = "webhooks.db";
 
+/// What one delivery sends, apart from the webhook's own configuration.
+#[derive(Debug, Clone)]
+struct Delivery {
+    event_type: WebhookEventType,
+    schema_version: u32,
+    /// Values for `${name}` placeholders in the body template.
+    variables: HashMap<String, Value>,
+    /// Body sent by POST webhooks without a template.
+    default_body: Value,
+}
+
+impl Delivery {
+    /// A manual or test delivery, which posts the caller's variables as-is.
+    fn manual(variables: HashMap<String, Value>) -> Self {
+        let event_type = WebhookEventType::WebhookTest;
+        Self {
+            event_type,
+            schema_version: event_type.schema_version(),
+            default_body: serde_json::to_value(&variables).unwrap_or(Value::Null),
+            variables,
+        }
+    }
+
+    /// An event delivery, which posts the event envelope.
+    fn event(event: &WebhookEvent) -> Self {
+        Self {
+            event_type: event.event_type,
+            schema_version: event.schema_version,
+            variables: template_variables(event).into_iter().collect(),
+            default_body: serde_json::to_value(event).unwrap_or(Value::Null),
+        }
+    }
+}
+
 pub struct WebhookManager {
     pool: Pool<Sqlite>,
     client: Client,
@@ -50,7 +90,7 @@ impl WebhookManager {
     }
 
     fn webhooks_db_path(app: &AppHandle) -> Result<std::path::PathBuf, WebhookError> {
-        let mut path = app.path().app_data_dir().map_err(|err| {
+        let mut path = app.app_data_root().map_err(|err| {
             WebhookError::Internal(format!("Unable to resolve app data directory: {err}"))
         })?;
 
@@ -74,7 +114,8 @@ impl WebhookManager {
                 enabled INTEGER NOT NULL,
                 retry_policy_json TEXT NOT NULL,
                 created_at TEXT NOT NULL,
-                updated_at TEXT NOT NULL
+                updated_at TEXT NOT NULL,
+                subscriptions_json TEXT
             )
             "#,
         )
@@ -102,6 +143,13 @@ impl WebhookManager {
         .execute(&self.pool)
         .await?;
 
+        ensure_column(&self.pool, "webhooks", "subscriptions_json", "TEXT").await?;
+        // Webhooks saved before event types existed keep receiving everything.
+        sqlx::query("UPDATE webhooks SET subscriptions_json = ?1 WHERE subscriptions_json IS NULL")
+            .bind(serde_json::to_string(&default_subscriptions())?)
+            .execute(&self.pool)
+            .await?;
+
         Ok(())
     }
 
@@ -142,6 +190,8 @@ impl WebhookManager {
         &self,
         mut config: WebhookConfig,
     ) -> Result<WebhookConfig, WebhookError> {
+        validate_subscriptions(&config.subscriptions)?;
+
         let now = Utc::now();
         config.id = Uuid::new_v4().to_string();
         config.created_at = now;
@@ -156,6 +206,8 @@ impl WebhookManager {
         id: &str,
         config: WebhookConfig,
     ) -> Result<(), WebhookError> {
+        validate_subscriptions(&config.subscriptions)?;
+
         let mut updated = config.clone();
         updated.id = id.to_string();
         updated.updated_at = Utc::now();
@@ -176,8 +228,9 @@ impl WebhookManager {
             r#"
             INSERT INTO webhooks (
                 id, name, description, url, method, headers_json, body_template,
-                variables_json, enabled, retry_policy_json, created_at, updated_at
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
+                variables_json, enabled, retry_policy_json, created_at, updated_at,
+                subscriptions_json
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
             ON CONFLICT(id) DO UPDATE SET
                 name = excluded.name,
                 description = excluded.description,
@@ -188,7 +241,8 @@ impl WebhookManager {
                 variables_json = excluded.variables_json,
                 enabled = excluded.enabled,
                 retry_policy_json = excluded.retry_policy_json,
-                updated_at = excluded.updated_at
+                updated_at = excluded.updated_at,
+                subscriptions_json = excluded.subscriptions_json
             "#,
         )
         .bind(&config.id)
@@ -206,6 +260,7 @@ impl WebhookManager {
         .bind(serde_json::to_string(&config.retry_policy).map_err(WebhookError::Serialization)?)
         .bind(config.created_at.to_rfc3339())
         .bind(config.updated_at.to_rfc3339())
+        .bind(serde_json::to_string(&config.subscriptions).map_err(WebhookError::Serialization)?)
         .execute(&self.pool)
         .await?;
 
@@ -217,6 +272,10 @@ impl WebhookManager {
         let headers_json: String = row.try_get("headers_json")?;
         let variables_json: String = row.try_get("variables_json")?;
         let retry_policy_json: String = row.try_get("retry_policy_json")?;
+        let subscriptions = match row.try_get::<Option<String>, _>("subscriptions_json")? {
+            Some(json) => serde_json::from_str(&json).map_err(WebhookError::Serialization)?,
+            None => default_subscriptions(),
+        };
 
         Ok(WebhookConfig {
             id: row.try_get("id")?,
@@ -241,6 +300,7 @@ impl WebhookManager {
             updated_at: DateTime::parse_from_rfc3339(&row.try_get::<String, _>("updated_at")?)
                 .map_err(|e| WebhookError::Internal(format!("Invalid timestamp: {}", e)))?
                 .with_timezone(&Utc),
+            subscriptions,
         })
     }
 
@@ -322,7 +382,8 @@ impl WebhookManager {
             return Err(WebhookError::Disabled);
         }
 
-        self.send_with_retries(&config, variables).await
+        self.send_with_retries(&config, Delivery::manual(variables))
+            .await
     }
 
     pub async fn test_webhook(
@@ -331,29 +392,64 @@ impl WebhookManager {
         variables: HashMap<String, Value>,
     ) -> Result<WebhookTestResult, WebhookError> {
         let config = self.get_webhook(id).await?;
-        let result = self.send_once(&config, variables, true, 1).await?;
+        let result = self
+            .send_once(&config, &Delivery::manual(variables), true, 1)
+            .await?;
 
         Ok(result)
     }
 
+    /// Delivers `event` to every enabled webhook subscribed to its type
+    /// whose filters match its payload. A failed delivery is recorded in
+    /// the delivery log and does not stop the others.
+    pub async fn dispatch_event(
+        &self,
+        event: &WebhookEvent,
+    ) -> Result<Vec<WebhookDeliveryLog>, WebhookError> {
+        let delivery = Delivery::event(event);
+        let mut logs = Vec::new();
+
+        for config in self.list_webhooks().await? {
+            let subscribed = config
+                .subscriptions
+                .iter()
+                .any(|subscription| subscription.matches(event));
+            if !config.enabled || !subscribed {
+                continue;
+            }
+
+            match self.send_with_retries(&config, delivery.clone()).await {
+                Ok(log) => logs.push(log),
+                Err(err) => eprintln!(
+                    "Webhook {} failed to deliver {}: {}",
+                    config.id,
+                    event.event_type.as_str(),
+                    err
+                ),
+            }
+        }
+
+        Ok(logs)
+    }
+
     async fn send_with_retries(
         &self,
         config: &WebhookConfig,
-        variables: HashMap<String, Value>,
+        delivery: Delivery,
     ) -> Result<WebhookDeliveryLog, WebhookError> {
         let executor = RetryExecutor::new(config.retry_policy.clone());
         let log_id = Uuid::new_v4().to_string();
         let triggered_at = Utc::now();
 
         let mut last_log = None;
-        let payload_preview = self.preview_payload(config, &variables)?;
+        let payload_preview = self.preview_payload(config, &delivery)?;
 
         let _guard = self.sending_lock.lock().await;
 
         let result = executor
             .execute(|| {
                 let config = config.clone();
-                let variables = variables.clone();
+                let delivery = delivery.clone();
                 let payload_preview = payload_preview.clone();
                 let log_id = log_id.clone();
                 async move {
@@ -372,10 +468,7 @@ impl WebhookManager {
                     .await?;
 
                     let attempt = 1; // actual attempt tracked inside send_once
-                    match self
-                        .send_once(&config, variables.clone(), false, attempt)
-                        .await
-                    {
+                    match self.send_once(&config, &delivery, false, attempt).await {
                         Ok(test_result) => {
                             self.log_status(
                                 &log_id,
@@ -435,7 +528,7 @@ impl WebhookManager {
     async fn send_once(
         &self,
         config: &WebhookConfig,
-        variables: HashMap<String, Value>,
+        delivery: &Delivery,
         test_only: bool,
         attempt: u32,
     ) -> Result<WebhookTestResult, WebhookError> {
@@ -447,16 +540,24 @@ impl WebhookManager {
         for (key, value) in &config.headers {
             request_builder = request_builder.header(key, value);
         }
+        request_builder = request_builder
+            .header("X-Webhook-Event", delivery.event_type.as_str())
+            .header(
+                "X-Webhook-Schema-Version",
+                delivery.schema_version.to_string(),
+            );
 
-        let payload_preview = self.preview_payload(config, &variables)?;
+        let payload_preview = self.preview_payload(config, delivery)?;
 
         if let Some(body_template) = &config.body_template {
-            let rendered = self.template_engine.render(body_template, &variables)?;
+            let rendered = self
+                .template_engine
+                .render(body_template, &delivery.variables)?;
             let json: Value = serde_json::from_str(&rendered)
                 .map_err(|e| WebhookError::InvalidTemplate(e.to_string()))?;
             request_builder = request_builder.json(&json);
         } else if config.method == WebhookMethod::Post {
-            request_builder = request_builder.json(&variables);
+            request_builder = request_builder.json(&delivery.default_body);
         }
 
         let start = Instant::now();
@@ -498,13 +599,16 @@ impl WebhookManager {
     fn preview_payload(
         &self,
         config: &WebhookConfig,
-        variables: &HashMap<String, Value>,
+        delivery: &Delivery,
     ) -> Result<String, WebhookError> {
         if let Some(body_template) = &config.body_template {
-            let rendered = self.template_engine.render(body_template, variables)?;
+            let rendered = self
+                .template_engine
+                .render(body_template, &delivery.variables)?;
             Ok(rendered)
         } else {
-            Ok(serde_json::to_string(variables).map_err(WebhookError::Serialization)?)
+            Ok(serde_json::to_string(&delivery.default_body)
+                .map_err(WebhookError::Serialization)?)
         }
     }
 
@@ -1,9 +1,11 @@
 pub mod commands;
+pub mod events;
 pub mod manager;
 pub mod retry;
 pub mod template;
 pub mod types;
 
 pub use commands::*;
+pub use events::{emit_webhook_event, WebhookEventType};
 pub use manager::WebhookManager;
 pub use types::*;
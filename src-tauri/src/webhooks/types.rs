@@ -1,3 +1,4 @@
+use super::events::{default_subscriptions, WebhookSubscription};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -33,6 +34,10 @@ pub struct WebhookConfig {
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub retry_policy: RetryPolicy,
+    /// Events this webhook is delivered. Configs without the field
+    /// subscribe to every event type.
+    #[serde(default = "default_subscriptions")]
+    pub subscriptions: Vec<WebhookSubscription>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -104,6 +109,8 @@ pub enum WebhookError {
     InvalidTemplate(String),
     #[error("webhook not found: {0}")]
     NotFound(String),
+    #[error("invalid subscription: {0}")]
+    InvalidSubscription(String),
     #[error("webhook disabled")]
     Disabled,
     #[error("internal error: {0}")]
@@ -0,0 +1,762 @@
+use crate::websocket::types::TransactionUpdate;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+pub const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+const USDC_MINT: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+const USDT_MINT: &str = "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB";
+const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+/// Balance changes below this are rounding noise.
+const DUST: f64 = 1e-9;
+/// SOL movements up to this size next to a token leg are treated as rent/fees.
+const RENT_THRESHOLD_SOL: f64 = 0.01;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityKind {
+    Swap,
+    TransferIn,
+    TransferOut,
+    Nft,
+    Stake,
+    Unstake,
+    ProgramInteraction,
+    Unknown,
+}
+
+impl ActivityKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ActivityKind::Swap => "swap",
+            ActivityKind::TransferIn => "transfer_in",
+            ActivityKind::TransferOut => "transfer_out",
+            ActivityKind::Nft => "nft",
+            ActivityKind::Stake => "stake",
+            ActivityKind::Unstake => "unstake",
+            ActivityKind::ProgramInteraction => "program_interaction",
+            ActivityKind::Unknown => "unknown",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgramCategory {
+    Dex,
+    Nft,
+    Stake,
+    /// System, token and other plumbing programs that every transaction touches.
+    Core,
+    Other,
+}
+
+/// Name and category for well-known Solana programs.
+pub fn known_program(program_id: &str) -> Option<(&'static str, ProgramCategory)> {
+    let known = match program_id {
+        "JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4" => ("Jupiter", ProgramCategory::Dex),
+        "JUP4Fb2cqiRUcaTHdrPC8h2gNsA2ETXiPDD33WcGuJB" => ("Jupiter v4", ProgramCategory::Dex),
+        "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8" => ("Raydium AMM", ProgramCategory::Dex),
+        "CAMMCzo5YL8w4VFF8KVHrK22GGUsp5VTaW7grrKgrWqK" => ("Raydium CLMM", ProgramCategory::Dex),
+        "CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C" => ("Raydium CPMM", ProgramCategory::Dex),
+        "whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc" => ("Orca Whirlpool", ProgramCategory::Dex),
+        "9W959DqEETiGZocYWCQPaJ6sBmUzgfxXfqGeTEdp3aQP" => ("Orca", ProgramCategory::Dex),
+        "LBUZKhRxPF3XUpBCjp4YzTKgLccjZhTSDM9YuVaPwxo" => ("Meteora DLMM", ProgramCategory::Dex),
+        "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P" => ("Pump.fun", ProgramCategory::Dex),
+        "M2mx93ekt1fmXSVkTrUL9xVFHkmME8HTUi5Cyc5aF7K" => ("Magic Eden", ProgramCategory::Nft),
+        "TSWAPaqyCSx2KABk68Shruf4rp7CxcNi8hAsbdwmHbN" => ("Tensor", ProgramCategory::Nft),
+        "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s" => {
+            ("Metaplex Token Metadata", ProgramCategory::Nft)
+        }
+        "Stake11111111111111111111111111111111111111" => ("Stake Program", ProgramCategory::Stake),
+        "MarBmsSgKXdrN1egZf5sqe1TMai9K1rChYNDJgjq7aD" => ("Marinade", ProgramCategory::Stake),
+        "SMPLecH534NA9acpos4G6x7uf3LWbCAwZQE9e8ZekMu" => {
+            ("Squads Multisig", ProgramCategory::Other)
+        }
+        "11111111111111111111111111111111" => ("System Program", ProgramCategory::Core),
+        "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA" => ("Token Program", ProgramCategory::Core),
+        "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb" => ("Token-2022", ProgramCategory::Core),
+        "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL" => {
+            ("Associated Token Account", ProgramCategory::Core)
+        }
+        "ComputeBudget111111111111111111111111111111" => ("Compute Budget", ProgramCategory::Core),
+        "MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr" => ("Memo", ProgramCategory::Core),
+        _ => return None,
+    };
+    Some(known)
+}
+
+fn known_symbol(mint: &str) -> Option<&'static str> {
+    match mint {
+        SOL_MINT => Some("SOL"),
+        USDC_MINT => Some("USDC"),
+        USDT_MINT => Some("USDT"),
+        _ => None,
+    }
+}
+
+pub(crate) fn is_quote_mint(mint: &str) -> bool {
+    matches!(mint, SOL_MINT | USDC_MINT | USDT_MINT)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TokenAmount {
+    pub mint: String,
+    pub symbol: Option<String>,
+    pub amount: f64,
+}
+
+impl TokenAmount {
+    fn new(mint: &str, amount: f64) -> Self {
+        Self {
+            mint: mint.to_string(),
+            symbol: known_symbol(mint).map(|s| s.to_string()),
+            amount,
+        }
+    }
+}
+
+/// A transaction as seen from one wallet. Fields that could not be decoded stay
+/// `None`; nothing is dropped, undecodable transactions come out as `Unknown`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DecodedActivity {
+    pub signature: String,
+    pub wallet: String,
+    pub kind: ActivityKind,
+    pub slot: u64,
+    pub timestamp: i64,
+    /// What the wallet received.
+    pub token_in: Option<TokenAmount>,
+    /// What the wallet sent.
+    pub token_out: Option<TokenAmount>,
+    pub counterparty: Option<String>,
+    pub program_id: Option<String>,
+    pub program_name: Option<String>,
+    pub nft_mint: Option<String>,
+    pub description: Option<String>,
+}
+
+impl DecodedActivity {
+    /// Action label used by the wallet monitor and copy trading: swaps against
+    /// SOL or a stablecoin become buys and sells, transfers collapse to `transfer`.
+    pub fn action(&self) -> &'static str {
+        match self.kind {
+            ActivityKind::Swap => {
+                let spent_quote = self
+                    .token_out
+                    .as_ref()
+                    .is_some_and(|t| is_quote_mint(&t.mint));
+                let got_quote = self
+                    .token_in
+                    .as_ref()
+                    .is_some_and(|t| is_quote_mint(&t.mint));
+                match (spent_quote, got_quote) {
+                    (true, false) => "buy",
+                    (false, true) => "sell",
+                    _ => "swap",
+                }
+            }
+            ActivityKind::TransferIn | ActivityKind::TransferOut => "transfer",
+            kind => kind.as_str(),
+        }
+    }
+
+    /// The amount the wallet moved: what it sent, or what it received if it sent nothing.
+    pub fn amount(&self) -> Option<f64> {
+        self.token_out
+            .as_ref()
+            .or(self.token_in.as_ref())
+            .map(|t| t.amount)
+    }
+
+    /// USD value when one side is a stablecoin.
+    pub fn usd_value(&self) -> Option<f64> {
+        [self.token_out.as_ref(), self.token_in.as_ref()]
+            .into_iter()
+            .flatten()
+            .find(|t| matches!(t.mint.as_str(), USDC_MINT | USDT_MINT))
+            .map(|t| t.amount)
+    }
+
+    /// Price of the non-quote token in terms of the quote side, for swaps.
+    pub fn implied_price(&self) -> Option<f64> {
+        if self.kind != ActivityKind::Swap {
+            return None;
+        }
+        let (Some(out), Some(inn)) = (&self.token_out, &self.token_in) else {
+            return None;
+        };
+        match (is_quote_mint(&out.mint), is_quote_mint(&inn.mint)) {
+            (true, false) if inn.amount > 0.0 => Some(out.amount / inn.amount),
+            (false, true) if out.amount > 0.0 => Some(inn.amount / out.amount),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Flows {
+    /// Net change per mint for the wallet, in UI units.
+    net: Vec<(String, f64)>,
+    counterparty: Option<String>,
+}
+
+impl Flows {
+    fn add(&mut self, mint: &str, delta: f64) {
+        match self.net.iter_mut().find(|(m, _)| m == mint) {
+            Some((_, total)) => *total += delta,
+            None => self.net.push((mint.to_string(), delta)),
+        }
+    }
+
+    fn sides(&self) -> (Vec<(String, f64)>, Vec<(String, f64)>) {
+        let outs = self
+            .net
+            .iter()
+            .filter(|(_, v)| *v < -DUST)
+            .map(|(m, v)| (m.clone(), -v))
+            .collect();
+        let ins = self
+            .net
+            .iter()
+            .filter(|(_, v)| *v > DUST)
+            .map(|(m, v)| (m.clone(), *v))
+            .collect();
+        (outs, ins)
+    }
+}
+
+/// Picks the leg that matters on one side, ignoring small SOL amounts (rent,
+/// tips) when a token moved too.
+fn pick_leg(side: Vec<(String, f64)>) -> Option<TokenAmount> {
+    let has_token = side.iter().any(|(mint, _)| mint != SOL_MINT);
+    side.into_iter()
+        .filter(|(mint, amount)| !(has_token && mint == SOL_MINT && *amount <= RENT_THRESHOLD_SOL))
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(mint, amount)| TokenAmount::new(&mint, amount))
+}
+
+/// Whether `address` appears anywhere in a raw transaction payload.
+pub fn mentions_address(raw: &Value, address: &str) -> bool {
+    match raw {
+        Value::String(s) => s == address,
+        Value::Array(items) => items.iter().any(|v| mentions_address(v, address)),
+        Value::Object(map) => map.values().any(|v| mentions_address(v, address)),
+        _ => false,
+    }
+}
+
+/// First of `wallets` involved in the transaction.
+pub fn involved_wallet<'a>(raw: &Value, wallets: &'a [String]) -> Option<&'a String> {
+    wallets.iter().find(|wallet| mentions_address(raw, wallet))
+}
+
+/// Decodes a Helius enhanced transaction or a jsonParsed RPC transaction from
+/// the point of view of `wallet`.
+pub fn decode_transaction(raw: &Value, wallet: &str) -> DecodedActivity {
+    let rpc = rpc_view(raw);
+    let str_field =
+        |v: &Value, key: &str| v.get(key).and_then(Value::as_str).map(|s| s.to_string());
+
+    let signature = str_field(raw, "signature")
+        .or_else(|| {
+            rpc.and_then(|tx| tx.pointer("/transaction/signatures/0"))
+                .and_then(Value::as_str)
+                .map(|s| s.to_string())
+        })
+        .unwrap_or_default();
+    let slot = raw
+        .get("slot")
+        .or_else(|| rpc.and_then(|tx| tx.get("slot")))
+        .and_then(Value::as_u64)
+        .unwrap_or_default();
+    let timestamp = raw
+        .get("timestamp")
+        .or_else(|| raw.get("blockTime"))
+        .or_else(|| rpc.and_then(|tx| tx.get("blockTime")))
+        .and_then(Value::as_i64)
+        .unwrap_or_else(|| chrono::Utc::now().timestamp());
+
+    let is_enhanced = raw.get("tokenTransfers").is_some() || raw.get("nativeTransfers").is_some();
+    let flows = if is_enhanced {
+        enhanced_flows(raw, wallet)
+    } else if let Some(tx) = rpc {
+        rpc_flows(tx, wallet)
+    } else {
+        Flows::default()
+    };
+
+    let programs = program_ids(raw, rpc);
+    let hint = str_field(raw, "type").unwrap_or_default().to_uppercase();
+    let nft_event = raw.pointer("/events/nft");
+
+    let mut activity = classify(wallet, flows, &programs, &hint, nft_event.is_some());
+    activity.signature = signature;
+    activity.slot = slot;
+    activity.timestamp = timestamp;
+    activity.description = str_field(raw, "description").filter(|d| !d.is_empty());
+    activity.nft_mint = nft_event
+        .and_then(|nft| nft.pointer("/nfts/0/mint"))
+        .and_then(Value::as_str)
+        .map(|s| s.to_string());
+    activity
+}
+
+/// Best-effort decode of a stream update that came without the raw transaction.
+pub fn decode_update(tx: &TransactionUpdate, wallet: &str) -> DecodedActivity {
+    let mut flows = Flows::default();
+    if let Some(amount) = tx.amount {
+        let mint = tx.symbol.as_deref().unwrap_or("unknown");
+        let mint = if mint == "SOL" { SOL_MINT } else { mint };
+        if tx.from.as_deref() == Some(wallet) {
+            flows.add(mint, -amount);
+            flows.counterparty = tx.to.clone();
+        } else if tx.to.as_deref() == Some(wallet) {
+            flows.add(mint, amount);
+            flows.counterparty = tx.from.clone();
+        }
+    }
+
+    let hint = tx.typ.as_deref().unwrap_or_default().to_uppercase();
+    let mut activity = classify(wallet, flows, &[], &hint, false);
+    activity.signature = tx.signature.clone();
+    activity.slot = tx.slot;
+    activity.timestamp = tx.timestamp;
+    activity
+}
+
+fn classify(
+    wallet: &str,
+    flows: Flows,
+    programs: &[String],
+    hint: &str,
+    has_nft_event: bool,
+) -> DecodedActivity {
+    let has_category = |category: ProgramCategory| {
+        programs
+            .iter()
+            .any(|id| matches!(known_program(id), Some((_, c)) if c == category))
+    };
+    // Top-level order wins; unknown programs only when nothing known stands out.
+    let main_program = programs
+        .iter()
+        .find(|id| matches!(known_program(id), Some((_, c)) if c != ProgramCategory::Core))
+        .or_else(|| programs.iter().find(|id| known_program(id).is_none()));
+
+    let sol_net = flows
+        .net
+        .iter()
+        .find(|(mint, _)| mint == SOL_MINT)
+        .map(|(_, v)| *v)
+        .unwrap_or_default();
+    let counterparty = flows.counterparty.clone();
+    let (outs, ins) = flows.sides();
+    let token_out = pick_leg(outs);
+    let token_in = pick_leg(ins);
+
+    let kind = if hint.starts_with("NFT") || has_nft_event || has_category(ProgramCategory::Nft) {
+        ActivityKind::Nft
+    } else if hint.contains("STAKE") || has_category(ProgramCategory::Stake) {
+        if hint.contains("UNSTAKE") || hint.contains("WITHDRAW") || sol_net > DUST {
+            ActivityKind::Unstake
+        } else {
+            ActivityKind::Stake
+        }
+    } else if hint == "SWAP" || has_category(ProgramCategory::Dex) {
+        if token_in.is_some() && token_out.is_some() {
+            ActivityKind::Swap
+        } else {
+            ActivityKind::Unknown
+        }
+    } else if main_program.is_none() || hint == "TRANSFER" {
+        match (&token_out, &token_in) {
+            (Some(_), None) => ActivityKind::TransferOut,
+            (None, Some(_)) => ActivityKind::TransferIn,
+            _ => ActivityKind::Unknown,
+        }
+    } else if main_program.is_some_and(|id| known_program(id).is_some()) {
+        ActivityKind::ProgramInteraction
+    } else {
+        ActivityKind::Unknown
+    };
+
+    let is_transfer = matches!(kind, ActivityKind::TransferIn | ActivityKind::TransferOut);
+    DecodedActivity {
+        signature: String::new(),
+        wallet: wallet.to_string(),
+        kind,
+        slot: 0,
+        timestamp: 0,
+        token_in,
+        token_out,
+        counterparty: counterparty.filter(|_| is_transfer),
+        program_id: main_program.cloned(),
+        program_name: main_program
+            .and_then(|id| known_program(id))
+            .map(|(name, _)| name.to_string()),
+        nft_mint: None,
+        description: None,
+    }
+}
+
+/// The `{transaction, meta}` object of an RPC-shaped payload, also when it is
+/// wrapped one level deeper as in `transactionSubscribe` notifications.
+fn rpc_view(raw: &Value) -> Option<&Value> {
+    if raw.get("meta").is_some() {
+        return Some(raw);
+    }
+    raw.get("transaction").filter(|tx| tx.get("meta").is_some())
+}
+
+fn enhanced_flows(raw: &Value, wallet: &str) -> Flows {
+    let mut flows = Flows::default();
+    let transfers = |key: &str| {
+        raw.get(key)
+            .and_then(Value::as_array)
+            .cloned()
+            .unwrap_or_default()
+    };
+
+    for (key, native) in [("tokenTransfers", false), ("nativeTransfers", true)] {
+        for transfer in transfers(key) {
+            let party = |k: &str| transfer.get(k).and_then(Value::as_str);
+            let (mint, amount) = if native {
+                let lamports = transfer
+                    .get("amount")
+                    .and_then(Value::as_f64)
+                    .unwrap_or_default();
+                (SOL_MINT, lamports / LAMPORTS_PER_SOL)
+            } else {
+                let Some(mint) = party("mint") else {
+                    continue;
+                };
+                (
+                    mint,
+                    transfer
+                        .get("tokenAmount")
+                        .and_then(Value::as_f64)
+                        .unwrap_or_default(),
+                )
+            };
+
+            let from = party("fromUserAccount");
+            let to = party("toUserAccount");
+            if from == to {
+                continue;
+            }
+            if from == Some(wallet) {
+                flows.add(mint, -amount);
+                flows.counterparty = flows.counterparty.take().or(to.map(|s| s.to_string()));
+            } else if to == Some(wallet) {
+                flows.add(mint, amount);
+                flows.counterparty = flows.counterparty.take().or(from.map(|s| s.to_string()));
+            }
+        }
+    }
+    flows
+}
+
+fn rpc_flows(tx: &Value, wallet: &str) -> Flows {
+    let mut flows = Flows::default();
+    let keys: Vec<&str> = tx
+        .pointer("/transaction/message/accountKeys")
+        .and_then(Value::as_array)
+        .map(|keys| {
+            keys.iter()
+                .filter_map(|k| {
+                    k.as_str()
+                        .or_else(|| k.get("pubkey").and_then(Value::as_str))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let meta = &tx["meta"];
+
+    if let Some(index) = keys.iter().position(|k| *k == wallet) {
+        let balance = |key: &str| {
+            meta.get(key)
+                .and_then(|b| b.get(index))
+                .and_then(Value::as_f64)
+        };
+        if let (Some(pre), Some(post)) = (balance("preBalances"), balance("postBalances")) {
+            let mut lamports = post - pre;
+            // The fee is not activity; add it back for the fee payer.
+            if index == 0 {
+                lamports += meta.get("fee").and_then(Value::as_f64).unwrap_or_default();
+            }
+            flows.add(SOL_MINT, lamports / LAMPORTS_PER_SOL);
+        }
+    }
+
+    let token_balances = |key: &str| -> Vec<(String, f64)> {
+        meta.get(key)
+            .and_then(Value::as_array)
+            .map(|balances| {
+                balances
+                    .iter()
+                    .filter(|b| b.get("owner").and_then(Value::as_str) == Some(wallet))
+                    .filter_map(|b| {
+                        let mint = b.get("mint")?.as_str()?.to_string();
+                        let ui = b.get("uiTokenAmount")?;
+                        let amount = ui.get("uiAmount").and_then(Value::as_f64).or_else(|| {
+                            ui.get("uiAmountString")
+                                .and_then(Value::as_str)
+                                .and_then(|s| s.parse().ok())
+                        })?;
+                        Some((mint, amount))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    for (mint, amount) in token_balances("preTokenBalances") {
+        flows.add(&mint, -amount);
+    }
+    for (mint, amount) in token_balances("postTokenBalances") {
+        flows.add(&mint, amount);
+    }
+
+    // Plain system/token transfers name the other side in their parsed info.
+    let instructions = tx
+        .pointer("/transaction/message/instructions")
+        .and_then(Value::as_array);
+    for info in instructions
+        .into_iter()
+        .flatten()
+        .filter_map(|ix| ix.pointer("/parsed/info"))
+    {
+        let field = |k: &str| info.get(k).and_then(Value::as_str);
+        let (source, destination) = (field("source").or(field("authority")), field("destination"));
+        if source == Some(wallet) {
+            flows.counterparty = destination.map(|s| s.to_string());
+        } else if destination == Some(wallet) {
+            flows.counterparty = source.map(|s| s.to_string());
+        }
+        if flows.counterparty.is_some() {
+            break;
+        }
+    }
+    flows
+}
+
+fn program_ids(raw: &Value, rpc: Option<&Value>) -> Vec<String> {
+    let mut ids: Vec<String> = Vec::new();
+    let mut push = |id: Option<&str>| {
+        if let Some(id) = id {
+            if !ids.iter().any(|existing| existing == id) {
+                ids.push(id.to_string());
+            }
+        }
+    };
+
+    // Helius enhanced: instructions[].programId with nested innerInstructions.
+    for ix in raw
+        .get("instructions")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        push(ix.get("programId").and_then(Value::as_str));
+        for inner in ix
+            .get("innerInstructions")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            push(inner.get("programId").and_then(Value::as_str));
+        }
+    }
+
+    if let Some(tx) = rpc {
+        let keys: Vec<&str> = tx
+            .pointer("/transaction/message/accountKeys")
+            .and_then(Value::as_array)
+            .map(|keys| {
+                keys.iter()
+                    .filter_map(|k| {
+                        k.as_str()
+                            .or_else(|| k.get("pubkey").and_then(Value::as_str))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let program_of = |ix: &Value| {
+            ix.get("programId").and_then(Value::as_str).or_else(|| {
+                ix.get("programIdIndex")
+                    .and_then(Value::as_u64)
+                    .and_then(|i| keys.get(i as usize).copied())
+            })
+        };
+
+        for ix in tx
+            .pointer("/transaction/message/instructions")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            push(program_of(ix));
+        }
+        for group in tx
+            .pointer("/meta/innerInstructions")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            for ix in group
+                .get("instructions")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+            {
+                push(program_of(ix));
+            }
+        }
+    }
+    ids
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WALLET: &str = "7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU";
+
+    fn fixture(name: &str) -> Value {
+        let raw = match name {
+            "jupiter_swap" => include_str!("fixtures/jupiter_swap.json"),
+            "raydium_swap_rpc" => include_str!("fixtures/raydium_swap_rpc.json"),
+            "orca_swap" => include_str!("fixtures/orca_swap.json"),
+            "sol_transfer" => include_str!("fixtures/sol_transfer.json"),
+            "stake" => include_str!("fixtures/stake.json"),
+            "nft_sale" => include_str!("fixtures/nft_sale.json"),
+            "unknown_program" => include_str!("fixtures/unknown_program.json"),
+            _ => panic!("no fixture {name}"),
+        };
+        serde_json::from_str(raw).expect("valid fixture")
+    }
+
+    #[test]
+    fn decodes_jupiter_swap() {
+        let activity = decode_transaction(&fixture("jupiter_swap"), WALLET);
+        assert_eq!(activity.kind, ActivityKind::Swap);
+        assert_eq!(activity.signature, "5jupSwapSig");
+        assert_eq!(activity.program_name.as_deref(), Some("Jupiter"));
+
+        let out = activity.token_out.as_ref().unwrap();
+        assert_eq!(out.mint, SOL_MINT);
+        assert!((out.amount - 1.5).abs() < 1e-9);
+        let inn = activity.token_in.as_ref().unwrap();
+        assert_eq!(inn.symbol.as_deref(), Some("USDC"));
+        assert!((inn.amount - 215.37).abs() < 1e-9);
+
+        // SOL and USDC are both quote assets, so this is neither a buy nor a sell.
+        assert_eq!(activity.action(), "swap");
+        assert_eq!(activity.usd_value(), Some(215.37));
+    }
+
+    #[test]
+    fn decodes_raydium_swap_from_rpc_balances() {
+        let activity = decode_transaction(&fixture("raydium_swap_rpc"), WALLET);
+        assert_eq!(activity.kind, ActivityKind::Swap);
+        assert_eq!(activity.program_name.as_deref(), Some("Raydium AMM"));
+        assert_eq!(activity.slot, 250_000_123);
+
+        let out = activity.token_out.as_ref().unwrap();
+        assert_eq!(out.mint, "DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263");
+        assert!((out.amount - 1_000_000.0).abs() < 1e-6);
+        // The fee is added back, so the SOL leg is the pool's payout only.
+        let inn = activity.token_in.as_ref().unwrap();
+        assert_eq!(inn.mint, SOL_MINT);
+        assert!((inn.amount - 0.25).abs() < 1e-9);
+        assert_eq!(activity.action(), "sell");
+    }
+
+    #[test]
+    fn decodes_orca_swap_without_type_hint() {
+        let activity = decode_transaction(&fixture("orca_swap"), WALLET);
+        assert_eq!(activity.kind, ActivityKind::Swap);
+        assert_eq!(activity.program_name.as_deref(), Some("Orca Whirlpool"));
+        assert_eq!(
+            activity.token_out.as_ref().unwrap().symbol.as_deref(),
+            Some("USDC")
+        );
+        assert_eq!(
+            activity.token_in.as_ref().unwrap().mint,
+            "mSoLzYCxHdYgdzU16g5QSh3i5K3z3KZK7ytfqcJm7So"
+        );
+        // Rent for the new token account is not mistaken for the bought leg.
+        assert_eq!(activity.action(), "buy");
+        assert!((activity.implied_price().unwrap() - 50.0 / 0.3).abs() < 1e-6);
+    }
+
+    #[test]
+    fn decodes_transfers_stake_and_nft() {
+        let transfer = decode_transaction(&fixture("sol_transfer"), WALLET);
+        assert_eq!(transfer.kind, ActivityKind::TransferOut);
+        assert_eq!(
+            transfer.counterparty.as_deref(),
+            Some("9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM")
+        );
+        assert_eq!(transfer.action(), "transfer");
+
+        let received = decode_transaction(
+            &fixture("sol_transfer"),
+            "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM",
+        );
+        assert_eq!(received.kind, ActivityKind::TransferIn);
+        assert_eq!(received.counterparty.as_deref(), Some(WALLET));
+
+        let stake = decode_transaction(&fixture("stake"), WALLET);
+        assert_eq!(stake.kind, ActivityKind::Stake);
+        assert_eq!(stake.program_name.as_deref(), Some("Stake Program"));
+
+        let nft = decode_transaction(&fixture("nft_sale"), WALLET);
+        assert_eq!(nft.kind, ActivityKind::Nft);
+        assert_eq!(
+            nft.nft_mint.as_deref(),
+            Some("NftMint1111111111111111111111111111111111111")
+        );
+        assert_eq!(nft.program_name.as_deref(), Some("Magic Eden"));
+        assert_eq!(nft.token_in.as_ref().unwrap().mint, SOL_MINT);
+    }
+
+    #[test]
+    fn unknown_transactions_keep_partial_data() {
+        let activity = decode_transaction(&fixture("unknown_program"), WALLET);
+        assert_eq!(activity.kind, ActivityKind::Unknown);
+        assert_eq!(
+            activity.program_id.as_deref(),
+            Some("UnknownProg1111111111111111111111111111111")
+        );
+        assert!(activity.program_name.is_none());
+        assert!(activity.token_out.is_some());
+
+        // A DEX program with only one decoded leg is not claimed as a swap.
+        let mut partial = fixture("jupiter_swap");
+        partial["tokenTransfers"] = serde_json::json!([]);
+        let activity = decode_transaction(&partial, WALLET);
+        assert_eq!(activity.kind, ActivityKind::Unknown);
+        assert_eq!(activity.program_name.as_deref(), Some("Jupiter"));
+        assert!(activity.token_out.is_some());
+
+        let json = serde_json::to_value(&activity).unwrap();
+        assert_eq!(json["kind"], "unknown");
+    }
+
+    #[test]
+    fn decodes_thin_stream_updates() {
+        let tx = TransactionUpdate {
+            signature: "sig".to_string(),
+            slot: 1,
+            timestamp: 10,
+            typ: Some("TRANSFER".to_string()),
+            amount: Some(2.0),
+            symbol: Some("SOL".to_string()),
+            from: Some("other".to_string()),
+            to: Some(WALLET.to_string()),
+            activity: None,
+        };
+        let activity = decode_update(&tx, WALLET);
+        assert_eq!(activity.kind, ActivityKind::TransferIn);
+        assert_eq!(activity.token_in.unwrap().symbol.as_deref(), Some("SOL"));
+        assert_eq!(activity.counterparty.as_deref(), Some("other"));
+    }
+}
@@ -0,0 +1,319 @@
+use crate::api_config::ApiConfigManager;
+use crate::config::provider_url;
+use crate::core::websocket_manager::StreamConnection;
+use crate::security::keystore::Keystore;
+use crate::websocket::activity;
+use crate::websocket::types::*;
+use serde_json::Value;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+pub const STREAM_GAP_FILLED_EVENT: &str = "stream-gap-filled";
+
+const BIRDEYE_OHLCV_URL: &str = "https://public-api.birdeye.so/defi/ohlcv";
+const HELIUS_API_URL: &str = "https://api.helius.xyz/v0";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// Longer outages are only replayed for their most recent part; charts reload
+/// full history on their own when the gap is this large.
+const MAX_BACKFILL_WINDOW_SECS: i64 = 6 * 60 * 60;
+
+/// Fetches whatever the stream missed while it was down and replays it through the
+/// regular stream events, then emits `stream-gap-filled`.
+pub fn spawn_gap_fill(connection: StreamConnection, app_handle: AppHandle, gap_started_ms: i64) {
+    tauri::async_runtime::spawn(async move {
+        let event = fill_gap(&connection, &app_handle, gap_started_ms).await;
+        *connection.last_gap.write().await = Some(event.clone());
+        let _ = app_handle.emit(STREAM_GAP_FILLED_EVENT, &event);
+    });
+}
+
+async fn fill_gap(
+    connection: &StreamConnection,
+    app_handle: &AppHandle,
+    gap_started_ms: i64,
+) -> GapFilledEvent {
+    let gap_ended_ms = chrono::Utc::now().timestamp_millis();
+    let mut event = GapFilledEvent {
+        provider: connection.provider.clone(),
+        gap_ms: (gap_ended_ms - gap_started_ms).max(0),
+        gap_started: gap_started_ms,
+        gap_ended: gap_ended_ms,
+        prices_backfilled: 0,
+        transactions_backfilled: 0,
+        errors: Vec::new(),
+    };
+
+    let client = match reqwest::Client::builder().timeout(REQUEST_TIMEOUT).build() {
+        Ok(client) => client,
+        Err(err) => {
+            event
+                .errors
+                .push(format!("HTTP client unavailable: {}", err));
+            return event;
+        }
+    };
+
+    let until = gap_ended_ms / 1000;
+    let floor = until - MAX_BACKFILL_WINDOW_SECS;
+    let gap_started = gap_started_ms / 1000;
+
+    match connection.provider {
+        StreamProvider::Birdeye => {
+            let Some(api_key) = provider_key(app_handle, "birdeye") else {
+                event
+                    .errors
+                    .push("Birdeye API key not configured".to_string());
+                return event;
+            };
+            let cursors = connection.registry.read().await.price_cursors();
+            for (symbol, cursor) in cursors {
+                let since = cursor.unwrap_or(gap_started).max(floor);
+                match fetch_price_gap(&client, &api_key, &symbol, since, until).await {
+                    Ok(deltas) => {
+                        event.prices_backfilled += deltas.len();
+                        for delta in deltas {
+                            connection
+                                .publish_event(app_handle, StreamEvent::PriceUpdate(delta))
+                                .await;
+                        }
+                    }
+                    Err(err) => event.errors.push(format!("{}: {}", symbol, err)),
+                }
+            }
+        }
+        StreamProvider::Helius => {
+            let Some(api_key) = provider_key(app_handle, "helius") else {
+                event
+                    .errors
+                    .push("Helius API key not configured".to_string());
+                return event;
+            };
+            let cursors = connection.registry.read().await.wallet_cursors();
+            for (address, cursor) in cursors {
+                let since = cursor
+                    .as_ref()
+                    .map(|c| c.timestamp)
+                    .unwrap_or(gap_started)
+                    .max(floor);
+                match fetch_wallet_gap(&client, &api_key, &address, cursor.as_ref(), since).await {
+                    Ok(transactions) => {
+                        event.transactions_backfilled += transactions.len();
+                        for tx in transactions {
+                            connection
+                                .publish_event(app_handle, StreamEvent::TransactionUpdate(tx))
+                                .await;
+                        }
+                    }
+                    Err(err) => event.errors.push(format!("{}: {}", address, err)),
+                }
+            }
+        }
+    }
+
+    event
+}
+
+fn provider_key(app_handle: &AppHandle, service: &str) -> Option<String> {
+    let manager = app_handle.try_state::<ApiConfigManager>()?;
+    let keystore = app_handle.try_state::<Keystore>()?;
+    manager.api_key(service, &keystore)
+}
+
+async fn fetch_price_gap(
+    client: &reqwest::Client,
+    api_key: &str,
+    symbol: &str,
+    since: i64,
+    until: i64,
+) -> anyhow::Result<Vec<PriceDelta>> {
+    let response = client
+        .get(BIRDEYE_OHLCV_URL)
+        .query(&[
+            ("address", symbol.to_string()),
+            ("type", "1m".to_string()),
+            ("time_from", since.to_string()),
+            ("time_to", until.to_string()),
+        ])
+        .header("X-API-KEY", api_key)
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        anyhow::bail!("Birdeye returned {}", response.status());
+    }
+
+    let body: Value = response.json().await?;
+    Ok(candles_to_deltas(symbol, &body, since))
+}
+
+async fn fetch_wallet_gap(
+    client: &reqwest::Client,
+    api_key: &str,
+    address: &str,
+    cursor: Option<&WalletCursor>,
+    since: i64,
+) -> anyhow::Result<Vec<TransactionUpdate>> {
+    let mut params = vec![("api-key", api_key.to_string())];
+    if let Some(cursor) = cursor {
+        params.push(("until", cursor.signature.clone()));
+    }
+
+    let response = client
+        .get(format!(
+            "{}/addresses/{}/transactions",
+            provider_url(HELIUS_API_URL),
+            address
+        ))
+        .query(&params)
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        anyhow::bail!("Helius returned {}", response.status());
+    }
+
+    let body: Value = response.json().await?;
+    Ok(parse_wallet_transactions(address, &body, since))
+}
+
+/// Converts Birdeye 1m candles newer than `after` into oldest-first price deltas.
+pub fn candles_to_deltas(symbol: &str, body: &Value, after: i64) -> Vec<PriceDelta> {
+    let Some(items) = body.pointer("/data/items").and_then(Value::as_array) else {
+        return Vec::new();
+    };
+
+    let mut deltas: Vec<PriceDelta> = items
+        .iter()
+        .filter_map(|item| {
+            let ts = item.get("unixTime")?.as_i64()?;
+            let close = item.get("c")?.as_f64()?;
+            (ts > after).then(|| PriceDelta {
+                symbol: symbol.to_string(),
+                price: Some(close),
+                change: None,
+                volume: item.get("v").and_then(Value::as_f64),
+                ts,
+                snapshot: false,
+            })
+        })
+        .collect();
+    deltas.sort_by_key(|delta| delta.ts);
+    deltas
+}
+
+/// Converts a Helius enhanced-transactions page (newest first) into oldest-first
+/// updates, keeping only those at or after `since`.
+pub fn parse_wallet_transactions(
+    address: &str,
+    body: &Value,
+    since: i64,
+) -> Vec<TransactionUpdate> {
+    let Some(items) = body.as_array() else {
+        return Vec::new();
+    };
+
+    let mut updates: Vec<TransactionUpdate> = items
+        .iter()
+        .filter_map(|item| {
+            let signature = item.get("signature")?.as_str()?.to_string();
+            let timestamp = item.get("timestamp").and_then(Value::as_i64)?;
+            if timestamp < since {
+                return None;
+            }
+
+            let transfer = ["tokenTransfers", "nativeTransfers"]
+                .iter()
+                .filter_map(|key| item.get(*key).and_then(Value::as_array))
+                .flatten()
+                .find(|t| {
+                    t.get("fromUserAccount").and_then(Value::as_str) == Some(address)
+                        || t.get("toUserAccount").and_then(Value::as_str) == Some(address)
+                });
+
+            let field = |key: &str| {
+                transfer
+                    .and_then(|t| t.get(key))
+                    .and_then(Value::as_str)
+                    .map(|s| s.to_string())
+            };
+            let (amount, symbol) = match transfer {
+                Some(t) if t.get("tokenAmount").is_some() => {
+                    (t.get("tokenAmount").and_then(Value::as_f64), field("mint"))
+                }
+                Some(t) => (
+                    t.get("amount")
+                        .and_then(Value::as_f64)
+                        .map(|lamports| lamports / 1_000_000_000.0),
+                    Some("SOL".to_string()),
+                ),
+                None => (None, None),
+            };
+
+            Some(TransactionUpdate {
+                signature,
+                slot: item.get("slot").and_then(Value::as_u64).unwrap_or_default(),
+                timestamp,
+                typ: item
+                    .get("type")
+                    .and_then(Value::as_str)
+                    .map(|s| s.to_string()),
+                amount,
+                symbol,
+                from: field("fromUserAccount").or_else(|| {
+                    item.get("feePayer")
+                        .and_then(Value::as_str)
+                        .map(|s| s.to_string())
+                }),
+                to: field("toUserAccount"),
+                activity: Some(activity::decode_transaction(item, address)),
+            })
+        })
+        .collect();
+    updates.sort_by_key(|tx| tx.timestamp);
+    updates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn candles_after_cursor_are_replayed_in_order() {
+        let body = json!({
+            "data": { "items": [
+                { "unixTime": 180, "o": 1.0, "h": 1.2, "l": 0.9, "c": 1.1, "v": 10.0 },
+                { "unixTime": 60, "o": 1.0, "h": 1.0, "l": 1.0, "c": 1.0, "v": 5.0 },
+                { "unixTime": 120, "o": 1.0, "h": 1.1, "l": 1.0, "c": 1.05, "v": 7.0 }
+            ]}
+        });
+
+        let deltas = candles_to_deltas("SOL", &body, 60);
+        assert_eq!(deltas.len(), 2);
+        assert_eq!(deltas[0].ts, 120);
+        assert_eq!(deltas[1].price, Some(1.1));
+        assert!(!deltas[0].snapshot);
+    }
+
+    #[test]
+    fn wallet_transactions_use_matching_transfer() {
+        let body = json!([
+            {
+                "signature": "sig-new",
+                "slot": 20,
+                "timestamp": 200,
+                "type": "TRANSFER",
+                "nativeTransfers": [
+                    { "fromUserAccount": "other", "toUserAccount": "wallet", "amount": 2_000_000_000u64 }
+                ]
+            },
+            { "signature": "sig-old", "slot": 10, "timestamp": 50, "feePayer": "wallet" }
+        ]);
+
+        let txs = parse_wallet_transactions("wallet", &body, 100);
+        assert_eq!(txs.len(), 1);
+        assert_eq!(txs[0].signature, "sig-new");
+        assert_eq!(txs[0].amount, Some(2.0));
+        assert_eq!(txs[0].to.as_deref(), Some("wallet"));
+    }
+}
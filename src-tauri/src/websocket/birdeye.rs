@@ -1,10 +1,11 @@
-use crate::core::websocket_manager::{ConnectionStateInternal, StreamConnection};
+use crate::core::websocket_manager::StreamConnection;
+use crate::websocket::backfill;
 use crate::websocket::types::*;
 use futures_util::{SinkExt, StreamExt};
 use serde_json::json;
 use std::sync::Arc;
 use std::time::Instant;
-use tauri::{AppHandle, Emitter, Manager};
+use tauri::{AppHandle, Manager};
 use tokio::net::TcpStream;
 use tokio::sync::{mpsc, Mutex};
 use tokio_tungstenite::tungstenite::Message;
@@ -38,23 +39,16 @@ impl BirdeyeStream {
         let url = url::Url::parse(BIRDEYE_WS_URL)?;
         let (ws_stream, _) = connect_async(url).await?;
 
-        {
-            let mut state = self.connection.state.write().await;
-            *state = ConnectionStateInternal::Connected;
-        }
-        {
-            let mut fallback = self.connection.fallback.write().await;
-            fallback.active = false;
-            fallback.reason = None;
-        }
+        let gap_started = self.connection.mark_connected().await;
 
         self.emit_status().await;
-        self.handle_stream(ws_stream).await
+        self.handle_stream(ws_stream, gap_started).await
     }
 
     async fn handle_stream(
         &self,
         ws_stream: WebSocketStream<MaybeTlsStream<TcpStream>>,
+        gap_started: Option<i64>,
     ) -> anyhow::Result<()> {
         let (write, mut read) = ws_stream.split();
         let write = Arc::new(Mutex::new(write));
@@ -139,6 +133,15 @@ impl BirdeyeStream {
             writer.send(Message::Text(msg.to_string())).await?;
         }
 
+        // Subscriptions are restored above; now replay what was missed while down.
+        if let Some(gap_started) = gap_started {
+            backfill::spawn_gap_fill(
+                self.connection.clone(),
+                self.app_handle.clone(),
+                gap_started,
+            );
+        }
+
         while let Some(msg) = read.next().await {
             match msg {
                 Ok(Message::Text(text)) => {
@@ -305,18 +308,7 @@ impl BirdeyeStream {
     async fn emit_status(&self) {}
 
     async fn enqueue_event(&self, event: StreamEvent) {
-        let mut queue = self.connection.queue.lock().await;
-        queue.push(event.clone());
-        drop(queue);
-
-        match &event {
-            StreamEvent::PriceUpdate(delta) => {
-                let _ = self.app_handle.emit("price_update", delta);
-            }
-            _ => {}
-        }
-
-        let _ = self.connection.event_tx.send(event);
+        self.connection.publish_event(&self.app_handle, event).await;
     }
 
     pub async fn subscribe(
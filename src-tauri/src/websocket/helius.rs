@@ -1,10 +1,13 @@
-use crate::core::websocket_manager::{ConnectionStateInternal, StreamConnection};
+use crate::config::provider_url;
+use crate::core::websocket_manager::StreamConnection;
+use crate::websocket::activity;
+use crate::websocket::backfill;
 use crate::websocket::types::*;
 use futures_util::{SinkExt, StreamExt};
 use serde_json::json;
 use std::sync::Arc;
 use std::time::Instant;
-use tauri::{AppHandle, Emitter, Manager};
+use tauri::{AppHandle, Manager};
 use tokio::net::TcpStream;
 use tokio::sync::{mpsc, Mutex};
 use tokio_tungstenite::tungstenite::Message;
@@ -27,25 +30,18 @@ impl HeliusStream {
     }
 
     pub async fn start(&self) -> anyhow::Result<()> {
-        let url = url::Url::parse(HELIUS_WS_URL)?;
+        let url = url::Url::parse(&provider_url(HELIUS_WS_URL))?;
         let (ws_stream, _) = connect_async(url).await?;
 
-        {
-            let mut state = self.connection.state.write().await;
-            *state = ConnectionStateInternal::Connected;
-        }
-        {
-            let mut fallback = self.connection.fallback.write().await;
-            fallback.active = false;
-            fallback.reason = None;
-        }
+        let gap_started = self.connection.mark_connected().await;
 
-        self.handle_stream(ws_stream).await
+        self.handle_stream(ws_stream, gap_started).await
     }
 
     async fn handle_stream(
         &self,
         ws_stream: WebSocketStream<MaybeTlsStream<TcpStream>>,
+        gap_started: Option<i64>,
     ) -> anyhow::Result<()> {
         let (ws_stream_tx, mut ws_stream_rx) = ws_stream.split();
         let write = Arc::new(Mutex::new(ws_stream_tx));
@@ -127,6 +123,15 @@ impl HeliusStream {
             writer.send(Message::Text(msg.to_string())).await?;
         }
 
+        // Subscriptions are restored above; now replay what was missed while down.
+        if let Some(gap_started) = gap_started {
+            backfill::spawn_gap_fill(
+                self.connection.clone(),
+                self.app_handle.clone(),
+                gap_started,
+            );
+        }
+
         while let Some(msg) = ws_stream_rx.next().await {
             match msg {
                 Ok(Message::Text(text)) => {
@@ -167,22 +172,21 @@ impl HeliusStream {
     async fn process_message(&self, value: serde_json::Value) {
         if let Some(method) = value.get("method").and_then(|v| v.as_str()) {
             if method == "accountNotification" || method == "notification" {
-                if let Ok(tx) = self.parse_transaction(&value) {
-                    let event = StreamEvent::TransactionUpdate(tx);
-                    let _ = self.connection.event_tx.send(event.clone());
-                    let _ = self.app_handle.emit("transaction_update", &event);
-
-                    let mut queue = self.connection.queue.lock().await;
-                    queue.push(event);
+                if let Ok(mut tx) = self.parse_transaction(&value) {
+                    if let Some(raw) = value.pointer("/params/result") {
+                        let wallets = self.connection.subscriptions.read().await.wallets.clone();
+                        tx.activity = activity::involved_wallet(raw, &wallets)
+                            .map(|wallet| activity::decode_transaction(raw, wallet));
+                    }
+                    self.connection
+                        .publish_event(&self.app_handle, StreamEvent::TransactionUpdate(tx))
+                        .await;
                 }
             }
         }
     }
 
-    fn parse_transaction(
-        &self,
-        value: &serde_json::Value,
-    ) -> anyhow::Result<TransactionUpdate> {
+    fn parse_transaction(&self, value: &serde_json::Value) -> anyhow::Result<TransactionUpdate> {
         let params = value
             .get("params")
             .and_then(|v| v.get("result"))
@@ -219,6 +223,7 @@ impl HeliusStream {
                 .get("to")
                 .and_then(|v| v.as_str())
                 .map(|s| s.to_string()),
+            activity: None,
         })
     }
 
@@ -258,4 +263,4 @@ impl HeliusStream {
         subs.wallets.retain(|a| !addresses.contains(a));
         Ok(())
     }
-}
\ No newline at end of file
+}
@@ -1,6 +1,8 @@
 use std::sync::Arc;
 use tauri::AppHandle;
 
+pub mod activity;
+pub mod backfill;
 pub mod birdeye;
 pub mod helius;
 pub mod reconnect;
@@ -1,5 +1,8 @@
-use crate::websocket::types::BackoffConfig;
-use std::time::Duration;
+use crate::websocket::types::{BackoffConfig, CircuitBreakerStatus, CircuitState};
+use std::time::{Duration, Instant};
+
+const CIRCUIT_FAILURE_THRESHOLD: u32 = 5;
+const CIRCUIT_COOLDOWN: Duration = Duration::from_secs(120);
 
 pub struct ExponentialBackoff {
     config: BackoffConfig,
@@ -49,6 +52,98 @@ impl ExponentialBackoff {
     }
 }
 
+/// Stops reconnect storms: after `failure_threshold` consecutive failures the
+/// circuit opens and reconnects wait out `cooldown` before a single probe attempt.
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    cooldown: Duration,
+    consecutive_failures: u32,
+    trips: u32,
+    opened_at: Option<Instant>,
+    last_error: Option<String>,
+}
+
+impl CircuitBreaker {
+    pub fn new(failure_threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            failure_threshold: failure_threshold.max(1),
+            cooldown,
+            consecutive_failures: 0,
+            trips: 0,
+            opened_at: None,
+            last_error: None,
+        }
+    }
+
+    pub fn state(&self) -> CircuitState {
+        match self.opened_at {
+            None => CircuitState::Closed,
+            Some(opened) if opened.elapsed() >= self.cooldown => CircuitState::HalfOpen,
+            Some(_) => CircuitState::Open,
+        }
+    }
+
+    /// Records a failed attempt. Returns true when this failure trips the circuit
+    /// (including a failed half-open probe, which re-opens it).
+    pub fn record_failure(&mut self, error: &str) -> bool {
+        self.consecutive_failures += 1;
+        self.last_error = Some(error.to_string());
+
+        let probe_failed = self.state() == CircuitState::HalfOpen;
+        let threshold_hit =
+            self.opened_at.is_none() && self.consecutive_failures >= self.failure_threshold;
+        if probe_failed || threshold_hit {
+            self.trips += 1;
+            self.opened_at = Some(Instant::now());
+            return true;
+        }
+        false
+    }
+
+    pub fn record_success(&mut self) {
+        self.reset();
+    }
+
+    /// Closes the circuit without a successful connect, e.g. on a manual reconnect.
+    pub fn reset(&mut self) {
+        self.consecutive_failures = 0;
+        self.opened_at = None;
+        self.last_error = None;
+    }
+
+    /// Time left before a half-open probe is allowed; zero when closed.
+    pub fn remaining_cooldown(&self) -> Duration {
+        self.opened_at
+            .map(|opened| self.cooldown.saturating_sub(opened.elapsed()))
+            .unwrap_or_default()
+    }
+
+    pub fn status(&self) -> CircuitBreakerStatus {
+        let now = chrono::Utc::now();
+        let to_chrono = |d: Duration| {
+            chrono::Duration::from_std(d).unwrap_or_else(|_| chrono::Duration::zero())
+        };
+        CircuitBreakerStatus {
+            state: self.state(),
+            consecutive_failures: self.consecutive_failures,
+            trips: self.trips,
+            opened_at: self
+                .opened_at
+                .map(|opened| (now - to_chrono(opened.elapsed())).timestamp()),
+            retry_at: self
+                .opened_at
+                .map(|_| (now + to_chrono(self.remaining_cooldown())).timestamp()),
+            last_error: self.last_error.clone(),
+        }
+    }
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self::new(CIRCUIT_FAILURE_THRESHOLD, CIRCUIT_COOLDOWN)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -98,4 +193,34 @@ mod tests {
         backoff.reset();
         assert_eq!(backoff.attempts(), 0);
     }
+
+    #[test]
+    fn test_circuit_opens_after_threshold() {
+        let mut circuit = CircuitBreaker::new(3, Duration::from_secs(60));
+
+        assert!(!circuit.record_failure("refused"));
+        assert!(!circuit.record_failure("refused"));
+        assert!(circuit.record_failure("refused"));
+        assert_eq!(circuit.state(), CircuitState::Open);
+        assert!(circuit.remaining_cooldown() > Duration::from_secs(59));
+
+        // Further failures while open do not count as new trips.
+        assert!(!circuit.record_failure("refused"));
+        assert_eq!(circuit.status().trips, 1);
+
+        circuit.record_success();
+        assert_eq!(circuit.state(), CircuitState::Closed);
+        assert_eq!(circuit.status().consecutive_failures, 0);
+    }
+
+    #[test]
+    fn test_circuit_half_open_probe_failure_reopens() {
+        let mut circuit = CircuitBreaker::new(1, Duration::ZERO);
+
+        assert!(circuit.record_failure("timeout"));
+        assert_eq!(circuit.state(), CircuitState::HalfOpen);
+
+        assert!(circuit.record_failure("timeout"));
+        assert_eq!(circuit.status().trips, 2);
+    }
 }
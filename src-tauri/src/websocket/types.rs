@@ -1,3 +1,6 @@
+use crate::core::stream_multiplexer::MultiplexerStats;
+use crate::trading::order_protection::TokenProtectionStatus;
+use crate::websocket::activity::DecodedActivity;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, VecDeque};
 use std::time::{Duration, Instant};
@@ -36,6 +39,47 @@ pub struct StreamStatus {
     pub statistics: StreamStatistics,
     pub subscriptions: StreamSubscriptions,
     pub fallback: Option<FallbackStatus>,
+    #[serde(default)]
+    pub circuit_breaker: Option<CircuitBreakerStatus>,
+    #[serde(default)]
+    pub last_gap: Option<GapFilledEvent>,
+    #[serde(default)]
+    pub multiplexer: Option<MultiplexerStats>,
+    /// Feed health of the tokens with armed orders, on the price stream.
+    #[serde(default)]
+    pub order_protection: Vec<TokenProtectionStatus>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum CircuitState {
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CircuitBreakerStatus {
+    pub state: CircuitState,
+    pub consecutive_failures: u32,
+    pub trips: u32,
+    pub opened_at: Option<i64>,
+    pub retry_at: Option<i64>,
+    pub last_error: Option<String>,
+}
+
+/// Payload of the `stream-gap-filled` event, emitted once the REST backfill for a
+/// reconnect has been replayed through the regular stream events.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GapFilledEvent {
+    pub provider: StreamProvider,
+    pub gap_ms: i64,
+    pub gap_started: i64,
+    pub gap_ended: i64,
+    pub prices_backfilled: usize,
+    pub transactions_backfilled: usize,
+    #[serde(default)]
+    pub errors: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -65,6 +109,90 @@ pub struct StreamSubscriptions {
     pub wallets: Vec<String>,
 }
 
+/// Resume points for every active subscription: the last price timestamp seen per
+/// symbol and the last transaction signature seen per wallet.
+#[derive(Debug, Clone, Default)]
+pub struct SubscriptionRegistry {
+    prices: HashMap<String, Option<i64>>,
+    wallets: HashMap<String, Option<WalletCursor>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WalletCursor {
+    pub signature: String,
+    pub timestamp: i64,
+}
+
+impl SubscriptionRegistry {
+    pub fn register_prices(&mut self, symbols: &[String]) {
+        for symbol in symbols {
+            self.prices.entry(symbol.clone()).or_insert(None);
+        }
+    }
+
+    pub fn unregister_prices(&mut self, symbols: &[String]) {
+        for symbol in symbols {
+            self.prices.remove(symbol);
+        }
+    }
+
+    pub fn register_wallets(&mut self, addresses: &[String]) {
+        for address in addresses {
+            self.wallets.entry(address.clone()).or_insert(None);
+        }
+    }
+
+    pub fn unregister_wallets(&mut self, addresses: &[String]) {
+        for address in addresses {
+            self.wallets.remove(address);
+        }
+    }
+
+    /// Advances cursors for whatever the event refers to. Out-of-order (older)
+    /// events never move a cursor backwards.
+    pub fn observe(&mut self, event: &StreamEvent) {
+        match event {
+            StreamEvent::PriceUpdate(delta) => {
+                if let Some(cursor) = self.prices.get_mut(&delta.symbol) {
+                    if !matches!(cursor, Some(ts) if *ts >= delta.ts) {
+                        *cursor = Some(delta.ts);
+                    }
+                }
+            }
+            StreamEvent::TransactionUpdate(tx) => {
+                if tx.signature.is_empty() {
+                    return;
+                }
+                for party in [tx.from.as_ref(), tx.to.as_ref()].into_iter().flatten() {
+                    if let Some(cursor) = self.wallets.get_mut(party) {
+                        if !matches!(cursor, Some(c) if c.timestamp > tx.timestamp) {
+                            *cursor = Some(WalletCursor {
+                                signature: tx.signature.clone(),
+                                timestamp: tx.timestamp,
+                            });
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub fn price_cursors(&self) -> Vec<(String, Option<i64>)> {
+        self.prices
+            .iter()
+            .map(|(symbol, ts)| (symbol.clone(), *ts))
+            .collect()
+    }
+
+    pub fn wallet_cursors(&self) -> Vec<(String, Option<WalletCursor>)> {
+        self.wallets
+            .iter()
+            .map(|(address, cursor)| (address.clone(), cursor.clone()))
+            .collect()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct PriceSnapshot {
     pub price: f64,
@@ -96,6 +224,9 @@ pub struct TransactionUpdate {
     pub symbol: Option<String>,
     pub from: Option<String>,
     pub to: Option<String>,
+    /// Decoded from the point of view of the subscribed wallet involved.
+    #[serde(default)]
+    pub activity: Option<DecodedActivity>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -1,16 +1,27 @@
 use app_lib::collab::crypto::{hash_password, verify_password, RoomEncryption};
+use app_lib::collab::history::MessageHistoryStore;
 use app_lib::collab::moderation::{ensure_moderation_permission, ModerationManager};
 use app_lib::collab::permissions::default_permissions_for_role;
 use app_lib::collab::rtc::RtcSessionManager;
 use app_lib::collab::state::CollabState;
 use app_lib::collab::types::*;
 use app_lib::collab::websocket::CollabWebSocketManager;
+use sqlx::sqlite::SqlitePoolOptions;
 use uuid::Uuid;
 
+async fn history_store() -> MessageHistoryStore {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    MessageHistoryStore::with_pool(pool).await.unwrap()
+}
+
 #[tokio::test]
 async fn test_room_creation_and_join() {
     let ws_manager = CollabWebSocketManager::without_handle();
-    let state = CollabState::new(ws_manager);
+    let state = CollabState::new(ws_manager, history_store().await);
 
     // Create a room
     let create_req = CreateRoomRequest {
@@ -36,6 +47,7 @@ async fn test_room_creation_and_join() {
         room_id: room.id,
         password: None,
         username: "testuser".to_string(),
+        history_limit: None,
     };
 
     let participant = state
@@ -54,7 +66,7 @@ async fn test_room_creation_and_join() {
 #[tokio::test]
 async fn test_password_protected_room() {
     let ws_manager = CollabWebSocketManager::without_handle();
-    let state = CollabState::new(ws_manager);
+    let state = CollabState::new(ws_manager, history_store().await);
 
     let create_req = CreateRoomRequest {
         name: "Private Room".to_string(),
@@ -77,6 +89,7 @@ async fn test_password_protected_room() {
         room_id: room.id,
         password: Some("wrongpassword".to_string()),
         username: "testuser".to_string(),
+        history_limit: None,
     };
 
     let result = state.rooms.join_room(join_req_wrong, "user456".to_string());
@@ -87,6 +100,7 @@ async fn test_password_protected_room() {
         room_id: room.id,
         password: Some("secret123".to_string()),
         username: "testuser".to_string(),
+        history_limit: None,
     };
 
     let participant = state
@@ -100,7 +114,7 @@ async fn test_password_protected_room() {
 #[tokio::test]
 async fn test_chat_messages() {
     let ws_manager = CollabWebSocketManager::without_handle();
-    let state = CollabState::new(ws_manager);
+    let state = CollabState::new(ws_manager, history_store().await);
 
     let create_req = CreateRoomRequest {
         name: "Chat Room".to_string(),
@@ -120,6 +134,7 @@ async fn test_chat_messages() {
         room_id: room.id,
         password: None,
         username: "testuser".to_string(),
+        history_limit: None,
     };
 
     state
@@ -141,14 +156,19 @@ async fn test_chat_messages() {
     assert_eq!(message.content, "Hello, world!");
     assert_eq!(message.username, "testuser");
 
-    let messages = state.rooms.get_messages(&room.id, None);
-    assert_eq!(messages.len(), 1);
+    state.history.append(&message).await.unwrap();
+    let page = state
+        .history
+        .page(&room.id, None, None, None)
+        .await
+        .unwrap();
+    assert_eq!(page.messages.len(), 1);
 }
 
 #[tokio::test]
 async fn test_watchlist_sharing() {
     let ws_manager = CollabWebSocketManager::without_handle();
-    let state = CollabState::new(ws_manager);
+    let state = CollabState::new(ws_manager, history_store().await);
 
     let create_req = CreateRoomRequest {
         name: "Trading Room".to_string(),
@@ -184,7 +204,7 @@ async fn test_watchlist_sharing() {
 #[tokio::test]
 async fn test_order_sharing() {
     let ws_manager = CollabWebSocketManager::without_handle();
-    let state = CollabState::new(ws_manager);
+    let state = CollabState::new(ws_manager, history_store().await);
 
     let create_req = CreateRoomRequest {
         name: "Trading Room".to_string(),
@@ -208,11 +228,16 @@ async fn test_order_sharing() {
         symbol: "SOL".to_string(),
         side: OrderSide::Buy,
         order_type: OrderType::Limit,
-        quantity: 10.0,
+        quantity: Some(10.0),
         price: Some(100.0),
         status: OrderStatus::Pending,
         timestamp: chrono::Utc::now(),
         notes: Some("Test order".to_string()),
+        linked_order_id: None,
+        visibility: SharedOrderVisibility::Full,
+        filled_quantity: None,
+        updated_at: None,
+        finalized: None,
     };
 
     state.rooms.add_order(order.clone()).unwrap();
@@ -309,7 +334,7 @@ async fn test_webrtc_signals() {
 #[tokio::test]
 async fn test_competition_leaderboard() {
     let ws_manager = CollabWebSocketManager::without_handle();
-    let state = CollabState::new(ws_manager);
+    let state = CollabState::new(ws_manager, history_store().await);
 
     let create_req = CreateRoomRequest {
         name: "Competition Room".to_string(),
@@ -340,6 +365,7 @@ async fn test_competition_leaderboard() {
             allowed_assets: Some(vec!["SOL".to_string(), "BTC".to_string()]),
             max_position_size: Some(1000.0),
             scoring_method: ScoringMethod::TotalReturn,
+            data_scope: None,
         },
         leaderboard: vec![
             LeaderboardEntry {
@@ -362,6 +388,8 @@ async fn test_competition_leaderboard() {
             },
         ],
         status: CompetitionStatus::Active,
+        entrants: Vec::new(),
+        final_snapshot: None,
     };
 
     state.rooms.set_competition(competition.clone()).unwrap();
@@ -398,7 +426,7 @@ async fn test_moderation_permission_check() {
 #[tokio::test]
 async fn test_room_state_sync() {
     let ws_manager = CollabWebSocketManager::without_handle();
-    let state = CollabState::new(ws_manager);
+    let state = CollabState::new(ws_manager, history_store().await);
 
     let create_req = CreateRoomRequest {
         name: "Sync Room".to_string(),
@@ -418,6 +446,7 @@ async fn test_room_state_sync() {
         room_id: room.id,
         password: None,
         username: "testuser".to_string(),
+        history_limit: None,
     };
 
     state
@@ -1,6 +1,7 @@
 use app_lib::data::database::CompressionManager;
 use app_lib::data::event_store::{Event, EventFilter, EventStore};
-use chrono::Utc;
+use chrono::{Duration, Utc};
+use sqlx::SqlitePool;
 use tempfile::tempdir;
 
 #[tokio::test]
@@ -220,6 +221,119 @@ async fn test_compression_threshold() {
     assert_eq!(count, 5);
 }
 
+#[tokio::test]
+async fn test_tiered_retention_roundtrip() {
+    let temp_dir = tempdir().unwrap();
+    let db_path = temp_dir.path().join("events.db");
+
+    let manager = CompressionManager::new(db_path.clone())
+        .await
+        .expect("Failed to create compression manager");
+    let store = EventStore::new(db_path.clone())
+        .await
+        .expect("Failed to create event store");
+
+    let aggregate_id = "tiered_wallet";
+    let mut event_ids = Vec::new();
+    for i in 0..12 {
+        let event = Event::SettingChanged {
+            key: format!("setting_{i}"),
+            old_value: i.to_string(),
+            new_value: (i + 1).to_string(),
+            timestamp: Utc::now(),
+        };
+        event_ids.push(store.publish_event(event, aggregate_id).await.unwrap());
+    }
+
+    // Age the first ten events past the hot window
+    let pool = SqlitePool::connect(&format!("sqlite:{}", db_path.display()))
+        .await
+        .unwrap();
+    for (i, id) in event_ids.iter().take(10).enumerate() {
+        let aged = Utc::now() - Duration::days(30) + Duration::seconds(i as i64);
+        sqlx::query("UPDATE events SET timestamp = ?1 WHERE id = ?2")
+            .bind(aged.to_rfc3339())
+            .bind(id)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    let moved = manager.compress_old_events().await.unwrap();
+    assert_eq!(moved, 10);
+
+    let filter = EventFilter {
+        aggregate_id: Some(aggregate_id.to_string()),
+        event_type: None,
+        from_time: None,
+        to_time: None,
+        limit: None,
+        offset: None,
+    };
+    let result = store.query_events(filter.clone()).await.unwrap();
+    assert_eq!(result.events.len(), 12);
+    assert_eq!(result.warm_rows, 10);
+    assert_eq!(result.events[0].id, event_ids[0]);
+
+    let paged = store
+        .get_events(EventFilter {
+            limit: Some(3),
+            offset: Some(9),
+            ..filter.clone()
+        })
+        .await
+        .unwrap();
+    let paged_ids: Vec<_> = paged.iter().map(|e| e.id.clone()).collect();
+    assert_eq!(paged_ids, event_ids[9..12].to_vec());
+
+    let stats = manager.get_compression_stats().await.unwrap();
+    assert_eq!(stats.tiers.hot.rows, 2);
+    assert_eq!(stats.tiers.warm.rows, 10);
+
+    // Move the warm block to the cold tier
+    let mut config = manager.get_config().await;
+    config.cold_after_days = Some(20);
+    config.validate().unwrap();
+    manager.update_config(config).await.unwrap();
+    let manifest = manager
+        .archive_cold_events()
+        .await
+        .unwrap()
+        .expect("Warm block should be archived");
+    assert_eq!(manifest.row_count, 10);
+    assert_eq!(store.get_events(filter.clone()).await.unwrap().len(), 2);
+
+    let stats = manager.get_compression_stats().await.unwrap();
+    assert_eq!(stats.tiers.warm.rows, 0);
+    assert_eq!(stats.tiers.cold.rows, 10);
+    assert_eq!(stats.tiers.cold_archives, 1);
+
+    manager.restore_archive(&manifest.id).await.unwrap();
+    assert_eq!(store.get_events(filter).await.unwrap().len(), 12);
+
+    // Sequences keep increasing after an aggregate's rows leave the hot tier
+    drop(store);
+    let store = EventStore::new(db_path).await.unwrap();
+    let id = store
+        .publish_event(
+            Event::SettingChanged {
+                key: "after_restart".to_string(),
+                old_value: String::new(),
+                new_value: String::new(),
+                timestamp: Utc::now(),
+            },
+            aggregate_id,
+        )
+        .await
+        .unwrap();
+    let sequence: i64 = sqlx::query_scalar("SELECT sequence FROM events WHERE id = ?1")
+        .bind(&id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(sequence, 13);
+}
+
 #[tokio::test]
 async fn test_event_export_formats() {
     let temp_dir = tempdir().unwrap();
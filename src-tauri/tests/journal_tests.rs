@@ -251,6 +251,8 @@ mod journal_tests {
             attachments: vec![],
             created_at: timestamp,
             updated_at: timestamp,
+            token_mint: None,
+            token_annotation: None,
         }
     }
 
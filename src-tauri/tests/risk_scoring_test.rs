@@ -22,6 +22,8 @@ async fn test_risk_model_high_risk_token() {
         token_age_days: 2.0,
         volume_24h: 8000.0,
         price_volatility: 52.0,
+        upcoming_unlock_percent: 0.0,
+        liquidity_unlock_exposure: 0.0,
     };
 
     let (score, factors) = model.score_token(&high_risk);
@@ -64,6 +66,8 @@ async fn test_risk_model_low_risk_token() {
         token_age_days: 390.0,
         volume_24h: 580000.0,
         price_volatility: 8.0,
+        upcoming_unlock_percent: 0.0,
+        liquidity_unlock_exposure: 0.0,
     };
 
     let (score, _factors) = model.score_token(&low_risk);
@@ -96,6 +100,8 @@ async fn test_risk_model_boundary_conditions() {
         token_age_days: 0.1,
         volume_24h: 1.0,
         price_volatility: 100.0,
+        upcoming_unlock_percent: 0.0,
+        liquidity_unlock_exposure: 0.0,
     };
 
     let (score, _) = model.score_token(&extreme);
@@ -139,6 +145,8 @@ async fn test_model_serialization() {
         token_age_days: 30.0,
         volume_24h: 50000.0,
         price_volatility: 10.0,
+        upcoming_unlock_percent: 0.0,
+        liquidity_unlock_exposure: 0.0,
     };
 
     let (score1, _) = model.score_token(&test_features);
@@ -179,6 +187,8 @@ async fn test_risk_analyzer_scoring_and_storage() {
         token_age_days: 15.0,
         volume_24h: 35000.0,
         price_volatility: 20.0,
+        upcoming_unlock_percent: 0.0,
+        liquidity_unlock_exposure: 0.0,
     };
 
     let token_address = "TestToken123";
@@ -260,6 +270,8 @@ async fn test_risk_analyzer_model_persistence() {
         token_age_days: 30.0,
         volume_24h: 50000.0,
         price_volatility: 10.0,
+        upcoming_unlock_percent: 0.0,
+        liquidity_unlock_exposure: 0.0,
     };
 
     let score = analyzer
@@ -289,6 +301,8 @@ fn test_feature_extraction_correctness() {
         token_age_days: 10.0,
         volume_24h: 20000.0,
         price_volatility: 30.0,
+        upcoming_unlock_percent: 0.0,
+        liquidity_unlock_exposure: 0.0,
     };
 
     let (score, factors) = model.score_token(&features);
@@ -349,6 +363,8 @@ fn test_risk_level_classification() {
             token_age_days: if target_score > 70.0 { 2.0 } else { 200.0 },
             volume_24h: 100000.0,
             price_volatility: if target_score > 70.0 { 50.0 } else { 5.0 },
+            upcoming_unlock_percent: 0.0,
+            liquidity_unlock_exposure: 0.0,
         };
 
         let (score, _) = model.score_token(&features);
@@ -371,3 +387,41 @@ fn test_risk_level_classification() {
         }
     }
 }
+
+#[tokio::test]
+async fn test_risk_model_upcoming_unlock_raises_score() {
+    let model = RiskModel::new();
+
+    let no_unlock = RiskFeatures {
+        gini_coefficient: 0.5,
+        top_10_percentage: 50.0,
+        total_holders: 1000,
+        liquidity_usd: 100000.0,
+        liquidity_to_mcap_ratio: 0.1,
+        has_mint_authority: false,
+        has_freeze_authority: false,
+        verified: true,
+        audited: false,
+        community_trust_score: 0.7,
+        sentiment_score: 0.3,
+        token_age_days: 30.0,
+        volume_24h: 50000.0,
+        price_volatility: 10.0,
+        upcoming_unlock_percent: 0.0,
+        liquidity_unlock_exposure: 0.0,
+    };
+    let large_unlock = RiskFeatures {
+        upcoming_unlock_percent: 12.0,
+        ..no_unlock.clone()
+    };
+
+    let (baseline, _) = model.score_token(&no_unlock);
+    let (score, _) = model.score_token(&large_unlock);
+
+    assert!(
+        score > baseline,
+        "A 12% unlock should raise the score above {}, got {}",
+        baseline,
+        score
+    );
+}
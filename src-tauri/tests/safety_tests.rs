@@ -4,7 +4,7 @@ mod tests {
     use app_lib::trading::safety::insurance::InsuranceCoordinator;
     use app_lib::trading::safety::policy::{PolicyEngine, SafetyPolicy};
     use app_lib::trading::safety::simulator::TransactionSimulator;
-    use app_lib::trading::safety::{SafetyCheckRequest, SafetyEngine};
+    use app_lib::trading::safety::{SafetyCheckRequest, SafetyEngine, TradeOrigin};
     use std::thread::sleep;
     use std::time::Duration;
 
@@ -172,6 +172,9 @@ mod tests {
             slippage_bps: 50,
             price_impact_percent: 1.5,
             security_score: Some(85.0),
+            token_mint: None,
+            strategy_id: None,
+            origin: TradeOrigin::Manual,
         };
 
         // Test 1: First trade should be allowed
@@ -183,7 +186,7 @@ mod tests {
         assert!(check.impact_preview.is_some());
 
         // Approve the trade
-        engine.approve_trade("wallet1");
+        engine.approve_trade("wallet1", Some("USDC"), None).await;
 
         // Test 2: Second immediate trade should be blocked by cooldown
         let result = engine.check_trade_safety(request.clone()).await;
@@ -209,6 +212,9 @@ mod tests {
             slippage_bps: 50,
             price_impact_percent: 1.5,
             security_score: Some(25.0), // High risk token
+            token_mint: None,
+            strategy_id: None,
+            origin: TradeOrigin::Manual,
         };
 
         let result = engine.check_trade_safety(request).await;
@@ -240,6 +246,9 @@ mod tests {
             slippage_bps: 50,
             price_impact_percent: 1.5,
             security_score: Some(85.0),
+            token_mint: None,
+            strategy_id: None,
+            origin: TradeOrigin::Manual,
         };
 
         let result = engine.check_trade_safety(request).await;
@@ -266,6 +275,9 @@ mod tests {
             slippage_bps: 50,
             price_impact_percent: 1.5,
             security_score: Some(85.0),
+            token_mint: None,
+            strategy_id: None,
+            origin: TradeOrigin::Manual,
         };
 
         let result = engine.check_trade_safety(request).await;
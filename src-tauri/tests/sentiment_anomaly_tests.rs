@@ -52,6 +52,8 @@ fn test_sentiment_manager_tracking() {
             timestamp: now,
             sentiment: analyze_sentiment("Great project!"),
             engagement: 100,
+            author_followers: None,
+            author_created_at: None,
         },
         SocialPost {
             id: "2".to_string(),
@@ -61,6 +63,8 @@ fn test_sentiment_manager_tracking() {
             timestamp: now,
             sentiment: analyze_sentiment("Bullish on this!"),
             engagement: 200,
+            author_followers: None,
+            author_created_at: None,
         },
     ];
 
@@ -87,6 +91,8 @@ fn test_sentiment_trend_tracking() {
             timestamp: now + i * 3600,
             sentiment: analyze_sentiment("Great project!"),
             engagement: 100,
+            author_followers: None,
+            author_created_at: None,
         }];
         manager.add_sentiment_data(token.clone(), posts);
     }
@@ -112,6 +118,8 @@ fn test_sentiment_alert_thresholds() {
         timestamp: now,
         sentiment: analyze_sentiment("Amazing! Excellent! Great! Bullish! Moon!"),
         engagement: 100,
+        author_followers: None,
+        author_created_at: None,
     }];
 
     manager.add_sentiment_data(token.clone(), positive_posts);
@@ -341,6 +349,8 @@ fn test_alert_routing() {
         timestamp: now,
         sentiment: analyze_sentiment("Amazing! Excellent! Great! Bullish! Moon! Rocket!"),
         engagement: 100,
+        author_followers: None,
+        author_created_at: None,
     }];
 
     manager.add_sentiment_data(token.clone(), posts);
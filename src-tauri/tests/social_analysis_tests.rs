@@ -284,6 +284,8 @@ async fn test_influencer_scoring() {
             timestamp: now,
             sentiment: analyze_sentiment("Great project!"),
             engagement: 10000,
+            author_followers: None,
+            author_created_at: None,
         },
         SocialPost {
             id: "2".to_string(),
@@ -293,6 +295,8 @@ async fn test_influencer_scoring() {
             timestamp: now,
             sentiment: analyze_sentiment("Amazing!"),
             engagement: 8000,
+            author_followers: None,
+            author_created_at: None,
         },
         SocialPost {
             id: "3".to_string(),
@@ -302,6 +306,8 @@ async fn test_influencer_scoring() {
             timestamp: now,
             sentiment: analyze_sentiment("Nice!"),
             engagement: 10,
+            author_followers: None,
+            author_created_at: None,
         },
     ];
 
@@ -489,5 +495,7 @@ fn create_test_post(id: &str, text: &str, timestamp: i64) -> SocialPost {
         timestamp,
         sentiment: analyze_sentiment(text),
         engagement: 100,
+        author_followers: None,
+        author_created_at: None,
     }
 }
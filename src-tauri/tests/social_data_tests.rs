@@ -134,6 +134,8 @@ async fn test_cache_stores_and_retrieves_posts() {
                 confidence: 0.9,
             },
             engagement: 100,
+            author_followers: None,
+            author_created_at: None,
         },
         SocialPost {
             id: "post_2".to_string(),
@@ -147,6 +149,8 @@ async fn test_cache_stores_and_retrieves_posts() {
                 confidence: 0.85,
             },
             engagement: 50,
+            author_followers: None,
+            author_created_at: None,
         },
     ];
 
@@ -189,6 +193,8 @@ async fn test_mention_aggregates_calculation() {
                 confidence: 0.9,
             },
             engagement: 100,
+            author_followers: None,
+            author_created_at: None,
         },
         SocialPost {
             id: "post_2".to_string(),
@@ -202,6 +208,8 @@ async fn test_mention_aggregates_calculation() {
                 confidence: 0.8,
             },
             engagement: 50,
+            author_followers: None,
+            author_created_at: None,
         },
         SocialPost {
             id: "post_3".to_string(),
@@ -215,6 +223,8 @@ async fn test_mention_aggregates_calculation() {
                 confidence: 0.6,
             },
             engagement: 25,
+            author_followers: None,
+            author_created_at: None,
         },
     ];
 
@@ -261,6 +271,8 @@ async fn test_trend_snapshot_creation() {
                 confidence: 0.9,
             },
             engagement: 200,
+            author_followers: None,
+            author_created_at: None,
         },
         SocialPost {
             id: "post_2".to_string(),
@@ -274,6 +286,8 @@ async fn test_trend_snapshot_creation() {
                 confidence: 0.85,
             },
             engagement: 150,
+            author_followers: None,
+            author_created_at: None,
         },
     ];
 
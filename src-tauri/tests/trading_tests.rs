@@ -16,9 +16,13 @@ mod tests {
             stop_price: None,
             trailing_percent: None,
             linked_order_id: None,
+            min_book_depth_usd: None,
             slippage_bps: 50,
             priority_fee_micro_lamports: 5000,
             wallet_address: "test_wallet".to_string(),
+            client_request_id: None,
+            correlation_id: None,
+            origin: None,
         };
 
         assert_eq!(request.order_type, OrderType::Limit);
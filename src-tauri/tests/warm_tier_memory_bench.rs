@@ -0,0 +1,181 @@
+//! Peak heap usage of event queries over a 1M-row warm tier.
+//!
+//! Run with `cargo test --release --test warm_tier_memory_bench -- --ignored --nocapture`.
+
+use app_lib::data::event_store::{EventFilter, EventRecord, EventStore};
+use app_lib::data::tiers::encode_block;
+use chrono::{Duration, Utc};
+use sqlx::SqlitePool;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tempfile::tempdir;
+
+struct CountingAllocator;
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let current = CURRENT.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+            PEAK.fetch_max(current, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+const WARM_ROWS: usize = 1_000_000;
+const BLOCK_ROWS: usize = 1_000;
+const AGGREGATES: usize = 1_000;
+const MAX_QUERY_HEAP_BYTES: usize = 32 * 1024 * 1024;
+
+/// Heap growth above the level at the start of `f`.
+async fn peak_heap_during<F, T>(f: F) -> (T, usize)
+where
+    F: std::future::Future<Output = T>,
+{
+    let baseline = CURRENT.load(Ordering::Relaxed);
+    PEAK.store(baseline, Ordering::Relaxed);
+    let result = f.await;
+    let peak = PEAK.load(Ordering::Relaxed);
+    (result, peak.saturating_sub(baseline))
+}
+
+async fn fill_warm_tier(db_path: &std::path::Path) -> i64 {
+    let pool = SqlitePool::connect(&format!("sqlite:{}?mode=rwc", db_path.display()))
+        .await
+        .unwrap();
+    let start = Utc::now() - Duration::days(60);
+    let mut original_bytes = 0;
+
+    for block_index in 0..WARM_ROWS / BLOCK_ROWS {
+        let records: Vec<EventRecord> = (0..BLOCK_ROWS)
+            .map(|offset| {
+                let row = block_index * BLOCK_ROWS + offset;
+                EventRecord {
+                    id: format!("evt-{:07}", row),
+                    event_type: "balance_changed".to_string(),
+                    event_data: format!(
+                        r#"{{"type":"balance_changed","wallet":"wallet_{}","token":"SOL","old_balance":{},"new_balance":{},"reason":"bench","timestamp":"{}"}}"#,
+                        row % AGGREGATES,
+                        row,
+                        row + 1,
+                        start.to_rfc3339()
+                    ),
+                    aggregate_id: format!("wallet_{}", row % AGGREGATES),
+                    sequence: (row / AGGREGATES) as i64 + 1,
+                    timestamp: (start + Duration::milliseconds(row as i64)).to_rfc3339(),
+                }
+            })
+            .collect();
+
+        let (block, original_size) = encode_block(&records, 3).unwrap();
+        original_bytes += original_size;
+        sqlx::query(
+            r#"
+            INSERT INTO event_blocks
+            (id, first_timestamp, last_timestamp, row_count, original_size, compressed_size, data, compressed_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+            "#,
+        )
+        .bind(format!("block-{:04}", block_index))
+        .bind(&records[0].timestamp)
+        .bind(&records[BLOCK_ROWS - 1].timestamp)
+        .bind(BLOCK_ROWS as i64)
+        .bind(original_size)
+        .bind(block.len() as i64)
+        .bind(&block)
+        .bind(Utc::now().to_rfc3339())
+        .execute(&pool)
+        .await
+        .unwrap();
+    }
+
+    pool.close().await;
+    original_bytes
+}
+
+#[tokio::test]
+#[ignore = "benchmark; run in release with --ignored"]
+async fn bench_warm_query_memory_is_bounded() {
+    let temp_dir = tempdir().unwrap();
+    let db_path = temp_dir.path().join("events.db");
+
+    let store = EventStore::new(db_path.clone())
+        .await
+        .expect("Failed to create event store");
+    let inflated_bytes = fill_warm_tier(&db_path).await;
+    assert_eq!(store.get_event_count(None).await.unwrap(), WARM_ROWS as i64);
+
+    // A selective filter has to decode every block of the tier
+    let filter = EventFilter {
+        aggregate_id: Some("wallet_7".to_string()),
+        event_type: None,
+        from_time: None,
+        to_time: None,
+        limit: None,
+        offset: None,
+    };
+    let started = std::time::Instant::now();
+    let (result, selective_peak) = peak_heap_during(store.query_events(filter)).await;
+    let selective_elapsed = started.elapsed();
+    let result = result.expect("Warm query failed");
+    assert_eq!(result.events.len(), WARM_ROWS / AGGREGATES);
+    assert!(result
+        .events
+        .iter()
+        .all(|event| event.aggregate_id == "wallet_7"));
+
+    // An unfiltered query stops at the warm row limit
+    let filter = EventFilter {
+        aggregate_id: None,
+        event_type: None,
+        from_time: None,
+        to_time: None,
+        limit: None,
+        offset: Some(500_000),
+    };
+    let (result, unfiltered_peak) = peak_heap_during(store.query_events(filter)).await;
+    let result = result.expect("Warm query failed");
+    assert!(result.warm_truncated);
+    assert_eq!(result.events[0].id, "evt-0500000");
+
+    println!("=== Warm Tier Query Memory ===");
+    println!("Warm rows: {}", WARM_ROWS);
+    println!(
+        "Inflated size: {:.1} MB",
+        inflated_bytes as f64 / 1_048_576.0
+    );
+    println!(
+        "Selective query: {:.1} MB peak, {:?}",
+        selective_peak as f64 / 1_048_576.0,
+        selective_elapsed
+    );
+    println!(
+        "Unfiltered query ({} rows): {:.1} MB peak",
+        result.events.len(),
+        unfiltered_peak as f64 / 1_048_576.0
+    );
+
+    assert!(inflated_bytes as usize > 4 * MAX_QUERY_HEAP_BYTES);
+    assert!(
+        selective_peak < MAX_QUERY_HEAP_BYTES,
+        "Selective warm query peaked at {} bytes",
+        selective_peak
+    );
+    assert!(
+        unfiltered_peak < MAX_QUERY_HEAP_BYTES,
+        "Unfiltered warm query peaked at {} bytes",
+        unfiltered_peak
+    );
+}
@@ -185,3 +185,51 @@ async fn test_message_queue_drain() {
     let empty = queue.drain();
     assert!(empty.is_empty());
 }
+
+#[tokio::test]
+async fn test_subscription_registry_cursors() {
+    use app_lib::websocket::types::{
+        PriceDelta, StreamEvent, SubscriptionRegistry, TransactionUpdate,
+    };
+
+    let mut registry = SubscriptionRegistry::default();
+    registry.register_prices(&["SOL".to_string()]);
+    registry.register_wallets(&["wallet1".to_string()]);
+
+    let price = |ts| {
+        StreamEvent::PriceUpdate(PriceDelta {
+            symbol: "SOL".to_string(),
+            price: Some(100.0),
+            change: None,
+            volume: None,
+            ts,
+            snapshot: false,
+        })
+    };
+    registry.observe(&price(200));
+    registry.observe(&price(150));
+    assert_eq!(
+        registry.price_cursors(),
+        vec![("SOL".to_string(), Some(200))]
+    );
+
+    registry.observe(&StreamEvent::TransactionUpdate(TransactionUpdate {
+        signature: "sig1".to_string(),
+        slot: 1,
+        timestamp: 300,
+        typ: None,
+        amount: None,
+        symbol: None,
+        from: Some("wallet1".to_string()),
+        to: None,
+        activity: None,
+    }));
+    let wallets = registry.wallet_cursors();
+    assert_eq!(
+        wallets[0].1.as_ref().map(|c| c.signature.as_str()),
+        Some("sig1")
+    );
+
+    registry.unregister_prices(&["SOL".to_string()]);
+    assert!(registry.price_cursors().is_empty());
+}
@@ -0,0 +1,55 @@
+import { useEffect } from 'react';
+import { listen } from '@tauri-apps/api/event';
+import { dataRootCommands } from '@/lib/tauri/commands';
+import { useUIStore } from '@/store/uiStore';
+
+const formatBytes = (bytes: number) => `${(bytes / (1024 * 1024)).toFixed(1)} MB`;
+
+/**
+ * Reports the outcome of a data directory move. The move runs at startup
+ * before the window opens, so its progress is read from
+ * `get_data_root_status` once mounted; a move that fails to schedule is
+ * reported through the `data_migration_failed` event.
+ */
+export function useDataMigrationNotice() {
+  const addToast = useUIStore(state => state.addToast);
+
+  useEffect(() => {
+    let cancelled = false;
+
+    dataRootCommands.getStatus().then(response => {
+      if (cancelled || !response.success) return;
+      const { migrationError, migrationProgress } = response.data;
+      if (migrationError) {
+        addToast({
+          type: 'error',
+          title: 'Data directory move failed',
+          message: `${migrationError}. The app is still using the previous directory.`,
+        });
+        return;
+      }
+      const last = migrationProgress[migrationProgress.length - 1];
+      if (last?.phase === 'verifying' && last.filesDone === last.filesTotal) {
+        addToast({
+          type: 'success',
+          title: 'Data directory moved',
+          message: `${last.filesTotal} files (${formatBytes(last.bytesTotal)}) copied and verified.`,
+          duration: 8000,
+        });
+      }
+    });
+
+    const unlisten = listen<string>('data_migration_failed', event => {
+      addToast({
+        type: 'error',
+        title: 'Data directory move not scheduled',
+        message: event.payload,
+      });
+    });
+
+    return () => {
+      cancelled = true;
+      unlisten.then(fn => fn());
+    };
+  }, [addToast]);
+}
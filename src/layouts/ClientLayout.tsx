@@ -2,6 +2,7 @@ import type { ReactNode } from 'react';
 import { useCallback, useEffect, useState } from 'react';
 import Sidebar from '@/components/sidebar';
 import TopNav from '@/components/top-nav';
+import { useDataMigrationNotice } from '@/hooks/useDataMigrationNotice';
 import { useSettingsStore } from '@/store/settingsStore';
 import { useThemeStore, type ThemeStoreState } from '@/store/themeStore';
 import { useShallow } from '@/store/createBoundStore';
@@ -24,6 +25,8 @@ export default function ClientLayout({ children }: ClientLayoutProps) {
   const { activeThemeId, setActiveTheme } = useThemeStore(themeSelector, useShallow);
   const settingsTheme = useSettingsStore(state => state.theme);
 
+  useDataMigrationNotice();
+
   useEffect(() => {
     setActiveTheme(settingsTheme);
   }, [settingsTheme, setActiveTheme]);
@@ -1,7 +1,7 @@
 import { invoke } from '@tauri-apps/api/core';
 import { listen, UnlistenFn } from '@tauri-apps/api/event';
 import type {
-  TokenBalance,
+  TokenBalancesView,
   SendTransactionInput,
   TransactionFeeEstimate,
   QRCodeData,
@@ -17,17 +17,22 @@ import type {
   TauriError,
   ApiResponse,
   StreamingChunk,
+  DataRootStatus,
 } from './types';
 
 // Error handling utility
-const handleTauriError = (error: any): TauriError => {
+export const handleTauriError = (error: any): TauriError => {
   if (typeof error === 'string') {
     return { message: error };
   }
   if (error?.message) {
     return {
+      kind: error.kind,
       message: error.message,
       code: error.code,
+      retryable: error.retryable,
+      retryAfterMs: error.retryAfterMs,
+      provider: error.provider,
       details: error.details,
     };
   }
@@ -57,7 +62,7 @@ const wrapCommand = async <T>(
 // Wallet Commands
 export const walletCommands = {
   getTokenBalances: (address: string, forceRefresh = false) =>
-    wrapCommand<TokenBalance[]>('wallet_get_token_balances', {
+    wrapCommand<TokenBalancesView>('wallet_get_token_balances', {
       address,
       force_refresh: forceRefresh,
     }),
@@ -152,6 +157,11 @@ export const portfolioCommands = {
   clearCache: () => wrapCommand<void>('clear_portfolio_cache'),
 };
 
+// Data Directory Commands
+export const dataRootCommands = {
+  getStatus: () => wrapCommand<DataRootStatus>('get_data_root_status'),
+};
+
 // Streaming Commands (for AI chat)
 export class StreamingCommandManager {
   private static activeListeners = new Map<string, UnlistenFn>();
@@ -11,6 +11,15 @@ export interface TokenBalance {
   lastUpdated: string;
 }
 
+export interface TokenBalancesView {
+  address: string;
+  balances: TokenBalance[];
+  fetchedAt: string;
+  asOf: string;
+  staleSeconds: number;
+  live: boolean;
+}
+
 export interface SendTransactionInput {
   recipient: string;
   amount: number;
@@ -186,10 +195,47 @@ export interface SectorAllocation {
   symbols: string[];
 }
 
+// Data Directory Types
+export type MigrationPhase = 'copying' | 'verifying' | 'rolling_back';
+
+export interface DataMigrationProgress {
+  phase: MigrationPhase;
+  filesDone: number;
+  filesTotal: number;
+  bytesDone: number;
+  bytesTotal: number;
+  currentFile: string | null;
+}
+
+export interface DataRootStatus {
+  activeDir: string;
+  defaultDir: string;
+  configuredDir: string | null;
+  unavailableReason: string | null;
+  migrating: boolean;
+  migrationError: string | null;
+  // Last update of each phase of the move run at this start.
+  migrationProgress: DataMigrationProgress[];
+}
+
 // Common Error Types
+export type TauriErrorKind =
+  | 'validation'
+  | 'not_found'
+  | 'upstream'
+  | 'unauthorized'
+  | 'rate_limited'
+  | 'internal';
+
+// Mirrors the serialized `AppError` returned by migrated commands. Commands
+// that still return plain strings only populate `message`.
 export interface TauriError {
+  kind?: TauriErrorKind;
   code?: string;
   message: string;
+  retryable?: boolean;
+  retryAfterMs?: number;
+  provider?: string;
   details?: Record<string, any>;
 }
 
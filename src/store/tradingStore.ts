@@ -1,6 +1,7 @@
 import { invoke } from '@tauri-apps/api/core';
 import type { Order, CreateOrderRequest, OrderUpdate, QuickTradeRequest } from '../types';
 import { createBoundStore } from './createBoundStore';
+import { handleTauriError } from '../lib/tauri/commands';
 
 export interface OrderDraft {
   id: string;
@@ -59,7 +60,7 @@ const storeResult = createBoundStore<TradingStoreState>((set, get) => ({
       await invoke('trading_init');
       set({ isInitialized: true, isLoading: false });
     } catch (error) {
-      set({ error: String(error), isLoading: false });
+      set({ error: handleTauriError(error).message, isLoading: false });
     }
   },
 
@@ -103,7 +104,7 @@ const storeResult = createBoundStore<TradingStoreState>((set, get) => ({
       return order;
     } catch (error) {
       get().removeOptimisticOrder(optimisticOrder.id);
-      set({ error: String(error), isLoading: false });
+      set({ error: handleTauriError(error).message, isLoading: false });
       throw error;
     }
   },
@@ -121,7 +122,7 @@ const storeResult = createBoundStore<TradingStoreState>((set, get) => ({
       }));
     } catch (error) {
       await get().getActiveOrders(get().activeOrders[0]?.walletAddress);
-      set({ error: String(error), isLoading: false });
+      set({ error: handleTauriError(error).message, isLoading: false });
       throw error;
     }
   },
@@ -132,7 +133,7 @@ const storeResult = createBoundStore<TradingStoreState>((set, get) => ({
       const orders = await invoke<Order[]>('get_active_orders', { walletAddress });
       set({ activeOrders: orders, isLoading: false });
     } catch (error) {
-      set({ error: String(error), isLoading: false });
+      set({ error: handleTauriError(error).message, isLoading: false });
     }
   },
 
@@ -142,7 +143,7 @@ const storeResult = createBoundStore<TradingStoreState>((set, get) => ({
       const orders = await invoke<Order[]>('get_order_history', { walletAddress, limit });
       set({ orderHistory: orders, isLoading: false });
     } catch (error) {
-      set({ error: String(error), isLoading: false });
+      set({ error: handleTauriError(error).message, isLoading: false });
     }
   },
 
@@ -153,7 +154,7 @@ const storeResult = createBoundStore<TradingStoreState>((set, get) => ({
       set({ isLoading: false });
       return order;
     } catch (error) {
-      set({ error: String(error), isLoading: false });
+      set({ error: handleTauriError(error).message, isLoading: false });
       throw error;
     }
   },
@@ -163,7 +164,7 @@ const storeResult = createBoundStore<TradingStoreState>((set, get) => ({
       await invoke('acknowledge_order', { orderId });
       get().updateOrderOptimistic(orderId, { status: 'pending' });
     } catch (error) {
-      set({ error: String(error) });
+      set({ error: handleTauriError(error).message });
       throw error;
     }
   },
@@ -1,6 +1,7 @@
 import { invoke } from '@tauri-apps/api/core';
 import type {
   TokenBalance,
+  TokenBalancesView,
   TransactionFeeEstimate,
   SendTransactionInput,
   AddressBookContact,
@@ -30,6 +31,7 @@ interface WalletStoreState {
   accounts: WalletAccount[];
   activeAccount: WalletAccount | null;
   balances: Record<string, TokenBalance[]>;
+  balancesAsOf: Record<string, string>;
   feeEstimates: Record<string, TransactionFeeEstimate>;
   addressBook: AddressBookContact[];
   swapHistory: SwapHistoryEntry[];
@@ -69,6 +71,7 @@ const initialState = {
   accounts: [],
   activeAccount: null,
   balances: {},
+  balancesAsOf: {},
   feeEstimates: {},
   addressBook: [],
   swapHistory: [],
@@ -93,12 +96,13 @@ const storeResult = createBoundStore<WalletStoreState>((set, get) => ({
   fetchBalances: async (address: string, forceRefresh = false) => {
     set({ isLoading: true, error: null });
     try {
-      const balances = await invoke<TokenBalance[]>('wallet_get_token_balances', {
+      const view = await invoke<TokenBalancesView>('wallet_get_token_balances', {
         address,
         forceRefresh,
       });
       set(state => ({
-        balances: { ...state.balances, [address]: balances },
+        balances: { ...state.balances, [address]: view.balances },
+        balancesAsOf: { ...state.balancesAsOf, [address]: view.asOf },
         isLoading: false,
       }));
     } catch (error) {
@@ -25,6 +25,7 @@ export interface Order {
   highestPrice?: number;
   lowestPrice?: number;
   linkedOrderId?: string;
+  minBookDepthUsd?: number;
   slippageBps: number;
   priorityFeeMicroLamports: number;
   walletAddress: string;
@@ -47,6 +48,7 @@ export interface CreateOrderRequest {
   stopPrice?: number;
   trailingPercent?: number;
   linkedOrderId?: string;
+  minBookDepthUsd?: number;
   slippageBps: number;
   priorityFeeMicroLamports: number;
   walletAddress: string;
@@ -12,6 +12,15 @@ export interface TokenBalance {
   lastUpdated: string;
 }
 
+export interface TokenBalancesView {
+  address: string;
+  balances: TokenBalance[];
+  fetchedAt: string;
+  asOf: string;
+  staleSeconds: number;
+  live: boolean;
+}
+
 export interface TransactionFeeEstimate {
   baseFee: number;
   priorityFee: number;
@@ -77,6 +86,10 @@ export interface SolanaPayQR {
 
 export type SwapStatus = 'pending' | 'completed' | 'failed';
 
+export type SwapSource = 'app' | 'chain';
+
+export type SwapReconciliationStatus = 'pending' | 'confirmed' | 'unconfirmed';
+
 export interface SwapHistoryEntry {
   id: string;
   fromToken: string;
@@ -89,6 +102,20 @@ export interface SwapHistoryEntry {
   txSignature?: string;
   timestamp: string;
   status: SwapStatus;
+  wallet?: string;
+  source?: SwapSource;
+  reconciliation?: SwapReconciliationStatus;
+  reconciledAt?: string;
+}
+
+export interface SwapReconcileReport {
+  wallet: string;
+  since: string;
+  inserted: number;
+  confirmed: number;
+  failed: number;
+  unconfirmed: number;
+  checkedAt: string;
 }
 
 export interface BridgeProvider {
@@ -107,7 +107,15 @@ describe('Wallet Store', () => {
         },
       ];
 
-      vi.mocked(invoke).mockResolvedValueOnce(mockBalances);
+      const asOf = new Date().toISOString();
+      vi.mocked(invoke).mockResolvedValueOnce({
+        address,
+        balances: mockBalances,
+        fetchedAt: asOf,
+        asOf,
+        staleSeconds: 0,
+        live: true,
+      });
 
       await act(async () => {
         await result.current.fetchBalances(address);
@@ -118,6 +126,7 @@ describe('Wallet Store', () => {
         forceRefresh: false,
       });
       expect(result.current.balances[address]).toEqual(mockBalances);
+      expect(result.current.balancesAsOf[address]).toBe(asOf);
       expect(result.current.isLoading).toBe(false);
       expect(result.current.error).toBeNull();
     });
@@ -142,7 +151,14 @@ describe('Wallet Store', () => {
       const address = 'test-address';
       const mockBalances: TokenBalance[] = [];
 
-      vi.mocked(invoke).mockResolvedValueOnce(mockBalances);
+      vi.mocked(invoke).mockResolvedValueOnce({
+        address,
+        balances: mockBalances,
+        fetchedAt: '2023-01-01T00:00:00Z',
+        asOf: '2023-01-01T00:00:00Z',
+        staleSeconds: 0,
+        live: false,
+      });
 
       await act(async () => {
         await result.current.fetchBalances(address, true);
@@ -14,7 +14,12 @@ import {
   isSuccess,
   unwrapResponse,
 } from '../src/lib/tauri/commands';
-import type { TokenBalance, CreateOrderRequest, ChatResponse } from '../src/lib/tauri/types';
+import type {
+  TokenBalance,
+  TokenBalancesView,
+  CreateOrderRequest,
+  ChatResponse,
+} from '../src/lib/tauri/types';
 
 // Mock Tauri APIs
 vi.mock('@tauri-apps/api/core', () => ({
@@ -46,7 +51,16 @@ describe('Tauri Commands Client', () => {
         },
       ];
 
-      vi.mocked(invoke).mockResolvedValue(mockBalances);
+      const mockView: TokenBalancesView = {
+        address: 'test-address',
+        balances: mockBalances,
+        fetchedAt: '2023-01-01T00:00:00Z',
+        asOf: '2023-01-01T00:00:00Z',
+        staleSeconds: 0,
+        live: true,
+      };
+
+      vi.mocked(invoke).mockResolvedValue(mockView);
 
       const result = await walletCommands.getTokenBalances('test-address', true);
 
@@ -55,7 +69,7 @@ describe('Tauri Commands Client', () => {
         force_refresh: true,
       });
       expect(result).toEqual({
-        data: mockBalances,
+        data: mockView,
         success: true,
       });
     });